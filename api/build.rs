@@ -0,0 +1,24 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Build hooks, generate the gRPC Owner API code from the proto definitions
+
+fn main() {
+	#[cfg(feature = "grpc")]
+	{
+		println!("cargo:rerun-if-changed=proto/owner.proto");
+		tonic_build::compile_protos("proto/owner.proto")
+			.expect("Unable to compile proto/owner.proto");
+	}
+}
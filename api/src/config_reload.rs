@@ -0,0 +1,506 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wallet settings that can be changed without restart. They are applied at the start and
+//! on config reload (SIGHUP or owner API reload_config). Everything else is reported as
+//! 'restart required'.
+
+use crate::config::{
+	parse_node_address_string, GlobalWalletConfig, GlobalWalletConfigMembers,
+	WALLET_CONFIG_FILE_NAME,
+};
+use crate::core::libtx::DEFAULT_BASE_FEE;
+use crate::impls::adapters::email::{self, EmailConfig};
+use crate::impls::adapters::matrix::{self, MatrixConfig};
+use crate::impls::adapters::{ipfs, nostr, p2p, telegram};
+use crate::impls::payment_id_resolver::DEFAULT_DOH_URL;
+use crate::impls::{
+	create_backup_store, AlertCommandNotifier, HttpPaymentIdResolver, HttpPriceSource,
+	WebhookNotifier,
+};
+use crate::keychain::Keychain;
+use crate::libwallet::audit_log;
+use crate::libwallet::backup::{self, BackupSchedule};
+use crate::libwallet::internal::selection::{self, ChangePolicy};
+use crate::libwallet::listener_limits::{self, ListenerLimits};
+use crate::libwallet::node_cache::{self, NodeCacheTtl};
+use crate::libwallet::notifications::{self, EventNotifier, NotifierList};
+use crate::libwallet::payment_id::{self, PaymentIdResolver};
+use crate::libwallet::price::{self, PriceProvider};
+use crate::libwallet::proof_verifier::{self, BatchProofVerifier, SingleProofVerifier};
+use crate::libwallet::receive_policy::{self, ReceivePolicy};
+use crate::libwallet::slate_limits::{self, SlateLimits};
+use crate::libwallet::tx_hooks::{self, TxHooks};
+use crate::libwallet::tx_mirror::{self, TxMirrorConfig};
+use crate::libwallet::{
+	owner, set_scan_threads, AutoCancelPolicy, Error, ErrorKind, NodeClient, WalletInst,
+	WalletLCProvider,
+};
+use crate::util::{LogLevel, Mutex};
+use log::LevelFilter;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+lazy_static! {
+	/// Config that is applied now. Reload compares the new config file with it.
+	static ref ACTIVE_CONFIG: RwLock<Option<GlobalWalletConfig>> = RwLock::new(None);
+	/// Logger appenders are created at the start, their levels can't be raised later
+	static ref STARTUP_LOG_LEVEL: RwLock<LevelFilter> = RwLock::new(LevelFilter::Trace);
+}
+
+/// Wallet config settings that are applied on reload
+const RUNTIME_WALLET_SETTINGS: [&str; 39] = [
+	"check_node_api_http_addr",
+	"base_fee",
+	"auto_cancel_expired_tx",
+	"auto_cancel_tx_age_hours",
+	"auto_expire_receive_days",
+	"tx_auto_repost_interval",
+	"scan_threads",
+	"batch_proof_verification",
+	"node_cache_tip_ttl_ms",
+	"node_cache_ttl_ms",
+	"min_change_output",
+	"change_split_target",
+	"change_split_max_outputs",
+	"max_slate_size",
+	"max_slate_inputs",
+	"max_slate_outputs",
+	"max_slate_participants",
+	"max_slate_json_depth",
+	"listener_rate_limit",
+	"listener_max_concurrent_requests",
+	"listener_trust_proxy_headers",
+	"receive_min_amount",
+	"receive_max_amount",
+	"receive_daily_cap",
+	"receive_allowed_senders",
+	"tx_hook_pre_lock",
+	"tx_hook_pre_finalize",
+	"tx_hook_post_post",
+	"tx_mirror_file",
+	"tx_mirror_syslog",
+	"audit_log_file",
+	"webhook_urls",
+	"webhook_confirmations",
+	"alert_command",
+	"backup_dest",
+	"backup_interval_hours",
+	"backup_keep",
+	"price_source_url",
+	"price_cache_minutes",
+	"payment_id_doh_url",
+];
+
+/// Result of the config reload
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConfigReloadStatus {
+	/// Changed settings that are applied
+	pub applied: Vec<String>,
+	/// Changed settings that will be applied after the wallet restart
+	pub restart_required: Vec<String>,
+}
+
+/// Apply runtime settings from the config. Called once at the start, the config is
+/// kept for the reload.
+pub fn apply_runtime_config(config: &GlobalWalletConfig) -> Result<(), Error> {
+	let members = config_members(config)?;
+	check_wallet_settings(members)?;
+	check_owner_api_wallets(members)?;
+	apply_wallet_settings(members);
+	// Messenger listeners keep the account, relays and address they are started with, so
+	// these are not runtime settings
+	let wallet_config = &members.wallet;
+	matrix::set_matrix_config(
+		match (
+			&wallet_config.matrix_homeserver,
+			&wallet_config.matrix_access_token,
+		) {
+			(Some(homeserver), Some(access_token)) => Some(MatrixConfig {
+				homeserver: homeserver.clone(),
+				access_token: access_token.clone(),
+			}),
+			_ => None,
+		},
+	);
+	nostr::set_nostr_relays(wallet_config.nostr_relays.clone());
+	email::set_email_config(
+		match (
+			&wallet_config.email_address,
+			&wallet_config.email_smtp_server,
+			&wallet_config.email_imap_server,
+			&wallet_config.email_password,
+		) {
+			(Some(address), Some(smtp_server), Some(imap_server), Some(password)) => {
+				Some(EmailConfig {
+					address: address.clone(),
+					smtp_server: smtp_server.clone(),
+					imap_server: imap_server.clone(),
+					username: wallet_config.email_username.clone(),
+					password: password.clone(),
+				})
+			}
+			_ => None,
+		},
+	);
+	telegram::set_telegram_config(wallet_config.telegram_bot_token.clone());
+	p2p::set_p2p_config(
+		wallet_config.p2p_listen_address.clone(),
+		wallet_config.p2p_public_address.clone(),
+	);
+	ipfs::set_ipfs_config(
+		wallet_config.ipfs_api_url.clone(),
+		Some(PathBuf::from(&wallet_config.data_file_dir)),
+	);
+	if let Some(logging) = &members.logging {
+		let level = max_log_level(&logging.stdout_log_level, &logging.file_log_level);
+		*STARTUP_LOG_LEVEL.write().unwrap() = level;
+	}
+	*ACTIVE_CONFIG.write().unwrap() = Some(config.clone());
+	Ok(())
+}
+
+/// Re-read the config file and apply the settings that can be changed at runtime.
+/// Node URL can be updated only if the wallet is open.
+pub fn reload_config<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+) -> Result<ConfigReloadStatus, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let active = ACTIVE_CONFIG
+		.read()
+		.unwrap()
+		.clone()
+		.ok_or(ErrorKind::GenericError(
+			"Wallet config is not loaded, nothing to reload".to_string(),
+		))?;
+	let config_file = active
+		.config_file_path
+		.clone()
+		.and_then(|p| p.to_str().map(|s| s.to_string()))
+		.ok_or(ErrorKind::GenericError(
+			"Wallet config file path is unknown".to_string(),
+		))?;
+	let mut config = GlobalWalletConfig::new(&config_file).map_err(|e| {
+		ErrorKind::GenericError(format!("Unable to read config {}, {}", config_file, e))
+	})?;
+	let (active_members, updated) = (config_members(&active)?, config_members(&config)?.clone());
+	check_wallet_settings(&updated)?;
+	check_owner_api_wallets(&updated)?;
+
+	let mut status = ConfigReloadStatus {
+		applied: vec![],
+		restart_required: vec![],
+	};
+	let startup_log_level = *STARTUP_LOG_LEVEL.read().unwrap();
+	for setting in changed_settings(active_members, &updated)? {
+		let mut split = setting.splitn(2, '.');
+		let runtime = match (split.next().unwrap_or(""), split.next().unwrap_or("")) {
+			("wallet", key) => RUNTIME_WALLET_SETTINGS.contains(&key),
+			("logging", "stdout_log_level") | ("logging", "file_log_level") => {
+				let logging = updated.logging.as_ref().unwrap();
+				max_log_level(&logging.stdout_log_level, &logging.file_log_level)
+					<= startup_log_level
+			}
+			_ => false,
+		};
+		if runtime {
+			status.applied.push(setting);
+		} else {
+			status.restart_required.push(setting);
+		}
+	}
+
+	apply_wallet_settings(&updated);
+	if let Some(logging) = &updated.logging {
+		let level = max_log_level(&logging.stdout_log_level, &logging.file_log_level);
+		log::set_max_level(std::cmp::min(level, startup_log_level));
+	}
+	let node_setting = "wallet.check_node_api_http_addr".to_string();
+	if status.applied.contains(&node_setting) {
+		let mut w_lock = wallet_inst.lock();
+		match w_lock.lc_provider().and_then(|p| p.wallet_inst()) {
+			Ok(w) => {
+				w.w2n_client().set_node_url(parse_node_address_string(
+					updated.wallet.check_node_api_http_addr.clone(),
+				));
+				w.w2n_client().reset_cache();
+			}
+			Err(_) => {
+				// Node client belongs to the opened wallet
+				status.applied.retain(|s| *s != node_setting);
+				status.restart_required.push(node_setting);
+			}
+		}
+	}
+
+	config.members = Some(updated);
+	*ACTIVE_CONFIG.write().unwrap() = Some(config);
+
+	info!(
+		"Wallet config is reloaded. Applied: {:?}, restart required: {:?}",
+		status.applied, status.restart_required
+	);
+	Ok(status)
+}
+
+fn config_members(config: &GlobalWalletConfig) -> Result<&GlobalWalletConfigMembers, Error> {
+	config
+		.members
+		.as_ref()
+		.ok_or(ErrorKind::GenericError("Wallet config is empty".to_string()).into())
+}
+
+// Values that are not valid for the runtime settings
+fn check_wallet_settings(members: &GlobalWalletConfigMembers) -> Result<(), Error> {
+	if members.wallet.backup_interval_hours == Some(0) {
+		return Err(ErrorKind::GenericError(
+			"backup_interval_hours must be at least 1".to_string(),
+		)
+		.into());
+	}
+	Ok(())
+}
+
+// The runtime settings are process wide, so the wallets that are served by the same owner API
+// can't have own values. The config files of these wallets must not define other values.
+fn check_owner_api_wallets(members: &GlobalWalletConfigMembers) -> Result<(), Error> {
+	let wallets = members.wallet.owner_api_wallets.clone().unwrap_or_default();
+	for (wallet_id, dir) in wallets {
+		let config_file = PathBuf::from(&dir).join(WALLET_CONFIG_FILE_NAME);
+		if !config_file.exists() {
+			continue;
+		}
+		let config_file = config_file.to_string_lossy().to_string();
+		let config = GlobalWalletConfig::new(&config_file).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to read config {}, {}", config_file, e))
+		})?;
+		let conflicts: Vec<String> = changed_settings(members, config_members(&config)?)?
+			.into_iter()
+			.filter(|setting| {
+				let mut split = setting.splitn(2, '.');
+				match (split.next().unwrap_or(""), split.next().unwrap_or("")) {
+					("wallet", key) => RUNTIME_WALLET_SETTINGS.contains(&key),
+					_ => false,
+				}
+			})
+			.collect();
+		if !conflicts.is_empty() {
+			return Err(ErrorKind::GenericError(format!(
+				"Config {} of the owner API wallet '{}' has different {}. These settings are shared by all wallets of the process, use the same values or run the wallet in a separate process",
+				config_file,
+				wallet_id,
+				conflicts.join(", ")
+			))
+			.into());
+		}
+	}
+	Ok(())
+}
+
+fn apply_wallet_settings(members: &GlobalWalletConfigMembers) {
+	let wallet_config = &members.wallet;
+
+	selection::set_base_fee(wallet_config.base_fee.unwrap_or(DEFAULT_BASE_FEE));
+	selection::set_change_policy(ChangePolicy {
+		min_change: wallet_config.min_change_output,
+		split_target: wallet_config.change_split_target,
+		max_split_outputs: wallet_config
+			.change_split_max_outputs
+			.unwrap_or(selection::DEFAULT_MAX_SPLIT_CHANGE_OUTPUTS),
+	});
+
+	owner::set_auto_cancel_policy(AutoCancelPolicy {
+		cancel_on_ttl: wallet_config.auto_cancel_expired_tx.unwrap_or(true),
+		max_age_hours: wallet_config.auto_cancel_tx_age_hours,
+		receive_expire_days: wallet_config.auto_expire_receive_days,
+	});
+	owner::set_auto_repost_interval(wallet_config.tx_auto_repost_interval);
+	set_scan_threads(wallet_config.scan_threads);
+	proof_verifier::set_proof_verifier(
+		match wallet_config.batch_proof_verification.unwrap_or(true) {
+			true => Arc::new(BatchProofVerifier),
+			false => Arc::new(SingleProofVerifier),
+		},
+	);
+
+	let default_cache_ttl = NodeCacheTtl::default();
+	node_cache::set_node_cache_ttl(NodeCacheTtl {
+		chain_tip_ms: wallet_config
+			.node_cache_tip_ttl_ms
+			.unwrap_or(default_cache_ttl.chain_tip_ms),
+		data_ms: wallet_config
+			.node_cache_ttl_ms
+			.unwrap_or(default_cache_ttl.data_ms),
+	});
+
+	let default_limits = SlateLimits::default();
+	slate_limits::set_slate_limits(SlateLimits {
+		max_size: wallet_config
+			.max_slate_size
+			.unwrap_or(default_limits.max_size),
+		max_inputs: wallet_config
+			.max_slate_inputs
+			.unwrap_or(default_limits.max_inputs),
+		max_outputs: wallet_config
+			.max_slate_outputs
+			.unwrap_or(default_limits.max_outputs),
+		max_participants: wallet_config
+			.max_slate_participants
+			.unwrap_or(default_limits.max_participants),
+		max_json_depth: wallet_config
+			.max_slate_json_depth
+			.unwrap_or(default_limits.max_json_depth),
+	});
+
+	let default_listener_limits = ListenerLimits::default();
+	listener_limits::set_listener_limits(ListenerLimits {
+		rate_limit: wallet_config
+			.listener_rate_limit
+			.unwrap_or(default_listener_limits.rate_limit),
+		max_concurrent: wallet_config
+			.listener_max_concurrent_requests
+			.unwrap_or(default_listener_limits.max_concurrent),
+		trust_proxy_headers: wallet_config.listener_trust_proxy_headers.unwrap_or(false),
+	});
+
+	receive_policy::set_receive_policy(ReceivePolicy {
+		min_amount: wallet_config.receive_min_amount,
+		max_amount: wallet_config.receive_max_amount,
+		daily_cap: wallet_config.receive_daily_cap,
+		allowed_senders: wallet_config.receive_allowed_senders.clone(),
+	});
+
+	tx_hooks::set_tx_hooks(TxHooks {
+		pre_lock: wallet_config.tx_hook_pre_lock.clone(),
+		pre_finalize: wallet_config.tx_hook_pre_finalize.clone(),
+		post_post: wallet_config.tx_hook_post_post.clone(),
+	});
+	tx_mirror::set_tx_mirror(TxMirrorConfig {
+		file: wallet_config.tx_mirror_file.clone(),
+		syslog: wallet_config.tx_mirror_syslog.unwrap_or(false),
+	});
+	audit_log::set_audit_log_file(wallet_config.audit_log_file.clone());
+
+	let mut notifiers: Vec<Arc<dyn EventNotifier>> = vec![];
+	if let Some(urls) = &wallet_config.webhook_urls {
+		notifiers.push(Arc::new(WebhookNotifier::new(urls.clone())));
+	}
+	if let Some(command) = &wallet_config.alert_command {
+		notifiers.push(Arc::new(AlertCommandNotifier::new(command.clone())));
+	}
+	notifications::set_event_notifier(match notifiers.len() {
+		0 => None,
+		1 => notifiers.pop(),
+		_ => Some(Arc::new(NotifierList(notifiers))),
+	});
+	notifications::set_notify_confirmations(
+		wallet_config
+			.webhook_confirmations
+			.unwrap_or(notifications::DEFAULT_NOTIFY_CONFIRMATIONS),
+	);
+
+	backup::set_backup_schedule(match &wallet_config.backup_dest {
+		Some(dest) => match create_backup_store(dest) {
+			Ok(store) => Some(BackupSchedule {
+				store,
+				interval_hours: wallet_config
+					.backup_interval_hours
+					.unwrap_or(backup::DEFAULT_BACKUP_INTERVAL_HOURS),
+				keep: wallet_config
+					.backup_keep
+					.unwrap_or(backup::DEFAULT_BACKUP_KEEP),
+			}),
+			Err(e) => {
+				error!("Scheduled backups are disabled, {}", e);
+				None
+			}
+		},
+		None => None,
+	});
+
+	price::set_price_provider(match &wallet_config.price_source_url {
+		Some(url) => Some(PriceProvider {
+			source: Arc::new(HttpPriceSource::new(url)),
+			cache_minutes: wallet_config
+				.price_cache_minutes
+				.unwrap_or(price::DEFAULT_PRICE_CACHE_MINUTES),
+		}),
+		None => None,
+	});
+
+	payment_id::set_payment_id_resolver(
+		match wallet_config
+			.payment_id_doh_url
+			.as_deref()
+			.unwrap_or(DEFAULT_DOH_URL)
+		{
+			"" => None,
+			url => Some(Arc::new(HttpPaymentIdResolver::new(url)) as Arc<dyn PaymentIdResolver>),
+		},
+	);
+}
+
+// Names of the changed settings in 'section.key' format
+fn changed_settings(
+	old: &GlobalWalletConfigMembers,
+	new: &GlobalWalletConfigMembers,
+) -> Result<Vec<String>, Error> {
+	let to_json = |members: &GlobalWalletConfigMembers| {
+		serde_json::to_value(members).map_err(|e| {
+			Error::from(ErrorKind::GenericError(format!(
+				"Unable to serialize config, {}",
+				e
+			)))
+		})
+	};
+	let (old, new) = (to_json(old)?, to_json(new)?);
+
+	let mut res = vec![];
+	let empty = serde_json::Map::new();
+	for section in ["wallet", "logging", "tor", "mqs"].iter() {
+		let old_section = old
+			.get(section)
+			.and_then(Value::as_object)
+			.unwrap_or(&empty);
+		let new_section = new
+			.get(section)
+			.and_then(Value::as_object)
+			.unwrap_or(&empty);
+		let mut keys: Vec<&String> = old_section.keys().chain(new_section.keys()).collect();
+		keys.sort();
+		keys.dedup();
+		for key in keys {
+			if old_section.get(key) != new_section.get(key) {
+				res.push(format!("{}.{}", section, key));
+			}
+		}
+	}
+	Ok(res)
+}
+
+fn max_log_level(stdout_level: &LogLevel, file_level: &LogLevel) -> LevelFilter {
+	let to_filter = |level: &LogLevel| match level {
+		LogLevel::Error => LevelFilter::Error,
+		LogLevel::Warning => LevelFilter::Warn,
+		LogLevel::Info => LevelFilter::Info,
+		LogLevel::Debug => LevelFilter::Debug,
+		LogLevel::Trace => LevelFilter::Trace,
+	};
+	std::cmp::max(to_filter(stdout_level), to_filter(file_level))
+}
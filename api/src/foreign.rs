@@ -15,12 +15,13 @@
 //! Foreign API External Definition
 
 use crate::keychain::Keychain;
-use crate::libwallet::api_impl::foreign;
+use crate::libwallet::api_impl::{foreign, owner};
+use crate::libwallet::secure_api::ListenerKey;
 use crate::libwallet::{
-	BlockFees, CbData, Error, NodeClient, NodeVersionInfo, Slate, SlatePurpose, SlateVersion,
-	VersionInfo, VersionedSlate, WalletInst, WalletLCProvider,
+	receive_policy, BlockFees, CbData, Error, NodeClient, NodeVersionInfo, Slate, SlatePurpose,
+	SlateVersion, VersionInfo, VersionedSlate, WalletInst, WalletLCProvider,
 };
-use crate::util::secp::key::SecretKey;
+use crate::util::secp::key::{PublicKey, SecretKey};
 use crate::util::Mutex;
 use ed25519_dalek::PublicKey as DalekPublicKey;
 use std::sync::Arc;
@@ -220,6 +221,20 @@ where
 		foreign::get_proof_address(&mut **w, (&self.keychain_mask).as_ref())
 	}
 
+	/// Signs the ECDH public key of the encrypted requests with the key of the wallet Tor
+	/// address. The sender checks the signature against the address it sends to.
+	/// # Arguments
+	/// * `ecdh_pubkey` - ECDH public key of the listener
+	/// # Returns
+	/// * [`ListenerKey`](../grin_wallet_libwallet/secure_api/struct.ListenerKey.html)
+
+	pub fn sign_listener_key(&self, ecdh_pubkey: &PublicKey) -> Result<ListenerKey, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+
+		foreign::sign_listener_key(&mut **w, (&self.keychain_mask).as_ref(), ecdh_pubkey)
+	}
+
 	/// Builds a new unconfirmed coinbase output in the wallet, generally for inclusion in a
 	/// potential new block's coinbase output during mining.
 	///
@@ -376,6 +391,8 @@ where
 	/// # Remarks
 	///
 	/// * This method will store a partially completed transaction in the wallet's transaction log.
+	/// * The slate is checked against the receive policy from the wallet config (amount limits,
+	/// daily cap, senders allowlist). The wallet's own `self` and `sweep` transactions are not checked.
 	///
 	/// # Example
 	/// Set up as in [new](struct.Foreign.html#method.new) method above.
@@ -402,6 +419,7 @@ where
 		dest_acct_name: Option<&str>,
 		message: Option<String>,
 	) -> Result<Slate, Error> {
+		let _operation = owner::begin_wallet_operation()?;
 		let mut w_lock = self.wallet_inst.lock();
 		let w = w_lock.lc_provider()?.wallet_inst()?;
 		if let Some(m) = self.middleware.as_ref() {
@@ -411,6 +429,14 @@ where
 				Some(slate),
 			)?;
 		}
+		// Own transactions are received through the foreign API too, the policy is for the others
+		let own_tx = match address.as_ref().map(String::as_str) {
+			Some("self") | Some("sweep") => true,
+			_ => false,
+		};
+		if !own_tx {
+			receive_policy::check_receive_policy(&**w, slate)?;
+		}
 
 		let (slate, _context) = foreign::receive_tx(
 			&mut **w,
@@ -475,6 +501,7 @@ where
 	/// ```
 
 	pub fn finalize_invoice_tx(&self, slate: &Slate) -> Result<Slate, Error> {
+		let _operation = owner::begin_wallet_operation()?;
 		let mut w_lock = self.wallet_inst.lock();
 		let w = w_lock.lc_provider()?.wallet_inst()?;
 		if let Some(m) = self.middleware.as_ref() {
@@ -494,6 +521,7 @@ where
 	}
 
 	pub fn receive_swap_message(&self, swap_message: &String) -> Result<(), Error> {
+		let _operation = owner::begin_wallet_operation()?;
 		foreign::receive_swap_message(
 			self.wallet_inst.clone(),
 			(&self.keychain_mask).as_ref(),
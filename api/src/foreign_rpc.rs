@@ -54,12 +54,13 @@ pub trait ForeignRpc {
 		"jsonrpc": "2.0",
 		"result": {
 			"Ok": {
-				"foreign_api_version": 2,
+				"foreign_api_version": 3,
 				"supported_slate_versions": [
 					"SP",
 					"V3B",
 					"V3",
-					"V2"
+					"V2",
+					"GV4"
 				]
 			}
 		}
@@ -0,0 +1,685 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! gRPC variant of the Owner API. The service is generated from `proto/owner.proto`, every call
+//! is passed to the [`Owner`](struct.Owner.html) the same way as the JSON-RPC API does.
+
+use crate::core::core::Transaction;
+use crate::keychain::Keychain;
+use crate::libwallet::api_impl::owner_updater::StatusMessage;
+use crate::libwallet::notifications;
+use crate::libwallet::{
+	AcctPathMapping, Error, ErrorKind, InitTxArgs, IssueInvoiceTxArgs, NodeClient,
+	OutputCommitMapping, Slate, TxLogEntry, VersionedSlate, WalletInfo, WalletLCProvider,
+};
+use crate::util::secp::key::SecretKey;
+use crate::util::{self, Mutex};
+use crate::Owner;
+use std::fs;
+use std::net::SocketAddr;
+use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::thread;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tonic::transport::{Identity, Server, ServerTlsConfig};
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+/// Code generated from `proto/owner.proto`
+pub mod proto {
+	tonic::include_proto!("mwc.wallet.owner.v1");
+}
+
+use self::proto::owner_api_server::{OwnerApi, OwnerApiServer};
+
+/// gRPC Owner API service
+pub struct GrpcOwner<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	owner: Arc<Owner<L, C, K>>,
+	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+}
+
+impl<L, C, K> GrpcOwner<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	/// Create the service. The keychain mask is shared with the other listeners of the wallet.
+	pub fn new(owner: Owner<L, C, K>, keychain_mask: Arc<Mutex<Option<SecretKey>>>) -> Self {
+		GrpcOwner {
+			owner: Arc::new(owner),
+			keychain_mask,
+		}
+	}
+
+	// Owner API calls are blocking, running them outside of the async runtime
+	async fn call<R, F>(&self, f: F) -> Result<Response<R>, Status>
+	where
+		R: Send + 'static,
+		F: FnOnce(&Owner<L, C, K>, Option<&SecretKey>) -> Result<R, Error> + Send + 'static,
+	{
+		let owner = self.owner.clone();
+		let keychain_mask = self.keychain_mask.clone();
+		tokio::task::spawn_blocking(move || {
+			let mask = keychain_mask.lock().clone();
+			f(&owner, mask.as_ref())
+		})
+		.await
+		.map_err(|e| Status::internal(format!("Owner API call failed, {}", e)))?
+		.map(Response::new)
+		.map_err(to_status)
+	}
+}
+
+#[tonic::async_trait]
+impl<L, C, K> OwnerApi for GrpcOwner<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	async fn accounts(
+		&self,
+		_request: Request<proto::AccountsRequest>,
+	) -> Result<Response<proto::AccountsResponse>, Status> {
+		self.call(|owner, m| {
+			let accounts = owner.accounts(m)?;
+			Ok(proto::AccountsResponse {
+				accounts: accounts.into_iter().map(acct_path_mapping).collect(),
+			})
+		})
+		.await
+	}
+
+	async fn create_account_path(
+		&self,
+		request: Request<proto::CreateAccountPathRequest>,
+	) -> Result<Response<proto::CreateAccountPathResponse>, Status> {
+		let label = request.into_inner().label;
+		self.call(move |owner, m| {
+			let path = owner.create_account_path(m, &label)?;
+			Ok(proto::CreateAccountPathResponse {
+				path: path.to_hex(),
+			})
+		})
+		.await
+	}
+
+	async fn set_active_account(
+		&self,
+		request: Request<proto::SetActiveAccountRequest>,
+	) -> Result<Response<proto::Empty>, Status> {
+		let label = request.into_inner().label;
+		self.call(move |owner, m| {
+			owner.set_active_account(m, &label)?;
+			Ok(proto::Empty {})
+		})
+		.await
+	}
+
+	async fn retrieve_summary_info(
+		&self,
+		request: Request<proto::RetrieveSummaryInfoRequest>,
+	) -> Result<Response<proto::RetrieveSummaryInfoResponse>, Status> {
+		let req = request.into_inner();
+		self.call(move |owner, m| {
+			let (refreshed_from_node, info) =
+				owner.retrieve_summary_info(m, req.refresh_from_node, req.minimum_confirmations)?;
+			Ok(proto::RetrieveSummaryInfoResponse {
+				refreshed_from_node,
+				info: Some(wallet_info(info)),
+			})
+		})
+		.await
+	}
+
+	async fn retrieve_outputs(
+		&self,
+		request: Request<proto::RetrieveOutputsRequest>,
+	) -> Result<Response<proto::RetrieveOutputsResponse>, Status> {
+		let req = request.into_inner();
+		self.call(move |owner, m| {
+			let (refreshed_from_node, outputs) = owner.retrieve_outputs(
+				m,
+				req.include_spent,
+				req.refresh_from_node,
+				req.tx_id.map(|v| v.value),
+			)?;
+			Ok(proto::RetrieveOutputsResponse {
+				refreshed_from_node,
+				outputs: outputs.into_iter().map(output).collect(),
+			})
+		})
+		.await
+	}
+
+	async fn retrieve_txs(
+		&self,
+		request: Request<proto::RetrieveTxsRequest>,
+	) -> Result<Response<proto::RetrieveTxsResponse>, Status> {
+		let req = request.into_inner();
+		let tx_slate_id = parse_uuid(&req.tx_slate_id)?;
+		self.call(move |owner, m| {
+			let (refreshed_from_node, txs) = owner.retrieve_txs(
+				m,
+				req.refresh_from_node,
+				req.tx_id.map(|v| v.value),
+				tx_slate_id,
+			)?;
+			Ok(proto::RetrieveTxsResponse {
+				refreshed_from_node,
+				txs: txs.into_iter().map(tx_log_entry).collect(),
+			})
+		})
+		.await
+	}
+
+	async fn node_height(
+		&self,
+		_request: Request<proto::Empty>,
+	) -> Result<Response<proto::NodeHeightResponse>, Status> {
+		self.call(|owner, m| {
+			let res = owner.node_height(m)?;
+			Ok(proto::NodeHeightResponse {
+				height: res.height,
+				header_hash: res.header_hash,
+				updated_from_node: res.updated_from_node,
+			})
+		})
+		.await
+	}
+
+	async fn init_send_tx(
+		&self,
+		request: Request<proto::InitSendTxRequest>,
+	) -> Result<Response<proto::SlateResponse>, Status> {
+		let req = request.into_inner();
+		let routputs = std::cmp::max(req.routputs, 1) as usize;
+		let args = init_tx_args(req);
+		self.call(move |owner, m| {
+			let slate = owner.init_send_tx(m, &args, routputs)?;
+			slate_response(slate)
+		})
+		.await
+	}
+
+	async fn issue_invoice_tx(
+		&self,
+		request: Request<proto::IssueInvoiceTxRequest>,
+	) -> Result<Response<proto::SlateResponse>, Status> {
+		let req = request.into_inner();
+		let args = IssueInvoiceTxArgs {
+			dest_acct_name: non_empty(req.dest_acct_name),
+			amount: req.amount,
+			message: non_empty(req.message),
+			address: non_empty(req.address),
+			..Default::default()
+		};
+		self.call(move |owner, m| {
+			let slate = owner.issue_invoice_tx(m, &args)?;
+			slate_response(slate)
+		})
+		.await
+	}
+
+	async fn process_invoice_tx(
+		&self,
+		request: Request<proto::ProcessInvoiceTxRequest>,
+	) -> Result<Response<proto::SlateResponse>, Status> {
+		let req = request.into_inner();
+		let slate = parse_slate(&req.slate_json)?;
+		let args = init_tx_args(req.args.unwrap_or_default());
+		self.call(move |owner, m| {
+			let slate = owner.process_invoice_tx(m, &slate, &args)?;
+			slate_response(slate)
+		})
+		.await
+	}
+
+	async fn tx_lock_outputs(
+		&self,
+		request: Request<proto::TxLockOutputsRequest>,
+	) -> Result<Response<proto::Empty>, Status> {
+		let req = request.into_inner();
+		let slate = parse_slate(&req.slate_json)?;
+		self.call(move |owner, m| {
+			owner.tx_lock_outputs(
+				m,
+				&slate,
+				non_empty(req.address),
+				req.participant_id as usize,
+			)?;
+			Ok(proto::Empty {})
+		})
+		.await
+	}
+
+	async fn finalize_tx(
+		&self,
+		request: Request<proto::SlateRequest>,
+	) -> Result<Response<proto::SlateResponse>, Status> {
+		let slate = parse_slate(&request.into_inner().slate_json)?;
+		self.call(move |owner, m| {
+			let slate = owner.finalize_tx(m, &slate)?;
+			slate_response(slate)
+		})
+		.await
+	}
+
+	async fn post_tx(
+		&self,
+		request: Request<proto::PostTxRequest>,
+	) -> Result<Response<proto::Empty>, Status> {
+		let req = request.into_inner();
+		let tx: Transaction = serde_json::from_str(&req.tx_json)
+			.map_err(|e| Status::invalid_argument(format!("Unable to parse transaction, {}", e)))?;
+		self.call(move |owner, m| {
+			owner.post_tx(m, &tx, req.fluff)?;
+			Ok(proto::Empty {})
+		})
+		.await
+	}
+
+	async fn cancel_tx(
+		&self,
+		request: Request<proto::CancelTxRequest>,
+	) -> Result<Response<proto::Empty>, Status> {
+		let req = request.into_inner();
+		let tx_slate_id = parse_uuid(&req.tx_slate_id)?;
+		self.call(move |owner, m| {
+			owner.cancel_tx(m, req.tx_id.map(|v| v.value), tx_slate_id)?;
+			Ok(proto::Empty {})
+		})
+		.await
+	}
+
+	async fn get_stored_tx(
+		&self,
+		request: Request<proto::GetStoredTxRequest>,
+	) -> Result<Response<proto::GetStoredTxResponse>, Status> {
+		let tx_id = request.into_inner().tx_id;
+		self.call(move |owner, m| {
+			let (_, txs) = owner.retrieve_txs(m, false, Some(tx_id), None)?;
+			let entry = txs
+				.into_iter()
+				.next()
+				.ok_or_else(|| ErrorKind::TransactionDoesntExist(format!("tx_id {}", tx_id)))?;
+			let tx_json = match owner.get_stored_tx(m, &entry)? {
+				Some(tx) => serde_json::to_string(&tx).map_err(|e| {
+					ErrorKind::GenericError(format!("Unable to serialize transaction, {}", e))
+				})?,
+				None => String::new(),
+			};
+			Ok(proto::GetStoredTxResponse { tx_json })
+		})
+		.await
+	}
+
+	type ScanStream = UnboundedReceiver<Result<proto::StatusUpdate, Status>>;
+
+	async fn scan(
+		&self,
+		request: Request<proto::ScanRequest>,
+	) -> Result<Response<Self::ScanStream>, Status> {
+		let req = request.into_inner();
+		let (tx, rx) = unbounded_channel();
+		let owner = self.owner.clone();
+		let keychain_mask = self.keychain_mask.clone();
+		thread::Builder::new()
+			.name("grpc-scan".to_string())
+			.spawn(move || {
+				let (status_tx, status_rx) = channel();
+				let progress_tx = tx.clone();
+				let progress = thread::spawn(move || {
+					while let Ok(msg) = status_rx.recv() {
+						if progress_tx.send(Ok(status_update(msg))).is_err() {
+							break;
+						}
+					}
+				});
+				let mask = keychain_mask.lock().clone();
+				let res = owner.scan_with_status(
+					mask.as_ref(),
+					req.start_height.map(|v| v.value),
+					req.delete_unconfirmed,
+					&Some(status_tx),
+				);
+				let _ = progress.join();
+				if let Err(e) = res {
+					let _ = tx.send(Err(to_status(e)));
+				}
+			})
+			.map_err(|e| Status::internal(format!("Unable to start the scan thread, {}", e)))?;
+		Ok(Response::new(rx))
+	}
+
+	type SubscribeEventsStream = UnboundedReceiver<Result<proto::WalletEvent, Status>>;
+
+	async fn subscribe_events(
+		&self,
+		_request: Request<proto::Empty>,
+	) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+		let (tx, rx) = unbounded_channel();
+		let events = notifications::subscribe();
+		// The subscription ends at the first event after the client is gone
+		thread::Builder::new()
+			.name("grpc-events".to_string())
+			.spawn(move || {
+				while let Ok(event) = events.recv() {
+					if tx.send(Ok(wallet_event(&event))).is_err() {
+						break;
+					}
+				}
+			})
+			.map_err(|e| Status::internal(format!("Unable to start the events thread, {}", e)))?;
+		Ok(Response::new(rx))
+	}
+}
+
+/// Start the gRPC Owner API server at the address. `api_basic_auth` is the expected value of the
+/// `authorization` header, TLS is set by the certificate and the private key files.
+pub fn start_grpc_owner_server<L, C, K>(
+	owner: Owner<L, C, K>,
+	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	addr: &str,
+	api_basic_auth: Option<String>,
+	tls: Option<(String, String)>,
+) -> Result<thread::JoinHandle<()>, Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	let addr: SocketAddr = addr.parse().map_err(|e| {
+		ErrorKind::GenericError(format!("Invalid gRPC listen address {}, {}", addr, e))
+	})?;
+
+	let tls = match tls {
+		Some((certificate, private_key)) => {
+			let certificate = fs::read(&certificate)
+				.map_err(|e| ErrorKind::IO(format!("Unable to read {}, {}", certificate, e)))?;
+			let private_key = fs::read(&private_key)
+				.map_err(|e| ErrorKind::IO(format!("Unable to read {}, {}", private_key, e)))?;
+			Some(ServerTlsConfig::new().identity(Identity::from_pem(certificate, private_key)))
+		}
+		None => None,
+	};
+
+	// tonic 0.3 validates the certificate and the key when the TLS is set
+	let mut server = Server::builder();
+	if let Some(tls) = tls {
+		server = server.tls_config(tls).map_err(|e| {
+			ErrorKind::GenericError(format!("Invalid gRPC owner listener TLS config, {}", e))
+		})?;
+	}
+
+	let service = OwnerApiServer::with_interceptor(
+		GrpcOwner::new(owner, keychain_mask),
+		move |request: Request<()>| check_basic_auth(&api_basic_auth, request),
+	);
+
+	warn!("gRPC owner listener started {}", addr);
+	let handle = thread::Builder::new()
+		.name("grpc-owner-listener".to_string())
+		.spawn(move || {
+			let mut runtime = match tokio::runtime::Runtime::new() {
+				Ok(runtime) => runtime,
+				Err(e) => {
+					error!("Unable to start gRPC owner listener runtime, {}", e);
+					return;
+				}
+			};
+			if let Err(e) = runtime.block_on(server.add_service(service).serve(addr)) {
+				error!("gRPC owner listener failed, {}", e);
+			}
+		})
+		.map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to start gRPC owner listener, {}", e))
+		})?;
+	Ok(handle)
+}
+
+fn check_basic_auth(
+	api_basic_auth: &Option<String>,
+	request: Request<()>,
+) -> Result<Request<()>, Status> {
+	if let Some(expected) = api_basic_auth {
+		let authorized = request
+			.metadata()
+			.get("authorization")
+			.and_then(|v| v.to_str().ok())
+			.map(|v| v == expected)
+			.unwrap_or(false);
+		if !authorized {
+			return Err(Status::unauthenticated("Invalid or missing credentials"));
+		}
+	}
+	Ok(request)
+}
+
+fn to_status(e: Error) -> Status {
+	let msg = format!("{}", e);
+	match e.kind() {
+		ErrorKind::NotEnoughFunds { .. } | ErrorKind::TransactionNotCancellable(_) => {
+			Status::failed_precondition(msg)
+		}
+		ErrorKind::TransactionDoesntExist(_)
+		| ErrorKind::AccountLabelNotExists(_)
+		| ErrorKind::UnknownAccountLabel(_) => Status::not_found(msg),
+		ErrorKind::AccountLabelAlreadyExists(_) => Status::already_exists(msg),
+		ErrorKind::InvalidKeychainMask => Status::unauthenticated(msg),
+		ErrorKind::Node(_) | ErrorKind::NodeNotReady | ErrorKind::ClientCallback(_) => {
+			Status::unavailable(msg)
+		}
+		ErrorKind::SlateDeser(_) | ErrorKind::SlateValidation(_) | ErrorKind::Fee(_) => {
+			Status::invalid_argument(msg)
+		}
+		_ => Status::internal(msg),
+	}
+}
+
+fn non_empty(s: String) -> Option<String> {
+	if s.is_empty() {
+		None
+	} else {
+		Some(s)
+	}
+}
+
+fn parse_uuid(s: &str) -> Result<Option<Uuid>, Status> {
+	if s.is_empty() {
+		return Ok(None);
+	}
+	Uuid::parse_str(s)
+		.map(Some)
+		.map_err(|e| Status::invalid_argument(format!("Invalid slate id {}, {}", s, e)))
+}
+
+fn parse_slate(slate_json: &str) -> Result<Slate, Status> {
+	Slate::deserialize_upgrade_plain(slate_json)
+		.map_err(|e| Status::invalid_argument(format!("Unable to parse slate, {}", e)))
+}
+
+fn slate_response(slate: Slate) -> Result<proto::SlateResponse, Error> {
+	let version = slate.lowest_version();
+	let slate = VersionedSlate::into_version_plain(slate, version)?;
+	let slate_json = serde_json::to_string(&slate)
+		.map_err(|e| ErrorKind::SlateSer(format!("Unable to serialize slate, {}", e)))?;
+	Ok(proto::SlateResponse { slate_json })
+}
+
+fn init_tx_args(req: proto::InitSendTxRequest) -> InitTxArgs {
+	let default = InitTxArgs::default();
+	InitTxArgs {
+		src_acct_name: non_empty(req.src_acct_name),
+		amount: req.amount,
+		minimum_confirmations: if req.minimum_confirmations == 0 {
+			default.minimum_confirmations
+		} else {
+			req.minimum_confirmations
+		},
+		max_outputs: if req.max_outputs == 0 {
+			default.max_outputs
+		} else {
+			req.max_outputs
+		},
+		num_change_outputs: std::cmp::max(req.num_change_outputs, 1),
+		selection_strategy_is_use_all: req.selection_strategy_is_use_all,
+		message: non_empty(req.message),
+		ttl_blocks: req.ttl_blocks.map(|v| v.value),
+		address: non_empty(req.address),
+		estimate_only: if req.estimate_only { Some(true) } else { None },
+		..default
+	}
+}
+
+fn acct_path_mapping(m: AcctPathMapping) -> proto::AcctPathMapping {
+	proto::AcctPathMapping {
+		label: m.label,
+		path: m.path.to_hex(),
+	}
+}
+
+fn wallet_info(info: WalletInfo) -> proto::WalletInfo {
+	proto::WalletInfo {
+		last_confirmed_height: info.last_confirmed_height,
+		minimum_confirmations: info.minimum_confirmations,
+		total: info.total,
+		amount_awaiting_finalization: info.amount_awaiting_finalization,
+		amount_awaiting_confirmation: info.amount_awaiting_confirmation,
+		amount_immature: info.amount_immature,
+		amount_currently_spendable: info.amount_currently_spendable,
+		amount_locked: info.amount_locked,
+		immature_spendable_at_height: info
+			.immature_spendable_at_height
+			.map(|value| proto::OptionalU64 { value }),
+		amount_committed_to_swaps: info
+			.amount_committed_to_swaps
+			.map(|value| proto::OptionalU64 { value }),
+	}
+}
+
+fn output(m: OutputCommitMapping) -> proto::Output {
+	let o = m.output;
+	proto::Output {
+		root_key_id: o.root_key_id.to_hex(),
+		key_id: o.key_id.to_hex(),
+		n_child: o.n_child,
+		commit: util::to_hex(m.commit.0.to_vec()),
+		mmr_index: o.mmr_index.map(|value| proto::OptionalU64 { value }),
+		value: o.value,
+		status: o.status.to_string(),
+		height: o.height,
+		lock_height: o.lock_height,
+		is_coinbase: o.is_coinbase,
+		tx_log_entry: o.tx_log_entry.map(|value| proto::OptionalU32 { value }),
+		spendable_at_height: m
+			.spendable_at_height
+			.map(|value| proto::OptionalU64 { value }),
+	}
+}
+
+fn tx_log_entry(t: TxLogEntry) -> proto::TxLogEntry {
+	proto::TxLogEntry {
+		parent_key_id: t.parent_key_id.to_hex(),
+		id: t.id,
+		tx_slate_id: t.tx_slate_id.map(|id| id.to_string()).unwrap_or_default(),
+		tx_type: t.tx_type.to_string(),
+		address: t.address.unwrap_or_default(),
+		creation_ts: t.creation_ts.to_rfc3339(),
+		confirmation_ts: t
+			.confirmation_ts
+			.map(|ts| ts.to_rfc3339())
+			.unwrap_or_default(),
+		confirmed: t.confirmed,
+		output_height: t.output_height,
+		num_inputs: t.num_inputs as u64,
+		num_outputs: t.num_outputs as u64,
+		amount_credited: t.amount_credited,
+		amount_debited: t.amount_debited,
+		fee: t.fee.map(|value| proto::OptionalU64 { value }),
+		ttl_cutoff_height: t
+			.ttl_cutoff_height
+			.map(|value| proto::OptionalU64 { value }),
+		kernel_excess: t
+			.kernel_excess
+			.map(|c| util::to_hex(c.0.to_vec()))
+			.unwrap_or_default(),
+		status: t.status.to_string(),
+	}
+}
+
+fn status_update(msg: StatusMessage) -> proto::StatusUpdate {
+	let (kind, message, percentage) = match msg {
+		StatusMessage::UpdatingOutputs(_, m) => ("updating_outputs", m, 0),
+		StatusMessage::FullScanWarn(m) => ("full_scan_warn", m, 0),
+		StatusMessage::Scanning(_, m, p) => ("scanning", m, p),
+		StatusMessage::ScanningComplete(_, m) => ("scanning_complete", m, 100),
+		StatusMessage::Warning(m) => ("warning", m, 0),
+		StatusMessage::Info(m) => ("info", m, 0),
+	};
+	proto::StatusUpdate {
+		kind: kind.to_string(),
+		message,
+		percentage: percentage as u32,
+	}
+}
+
+fn wallet_event(event: &notifications::WalletEvent) -> proto::WalletEvent {
+	let json = serde_json::to_value(event).unwrap_or(serde_json::Value::Null);
+	proto::WalletEvent {
+		event: json["event"].as_str().unwrap_or_default().to_string(),
+		json: json.to_string(),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn grpc_basic_auth() {
+		let expected = Some("Basic bXdjOnNlY3JldA==".to_string());
+		assert!(check_basic_auth(&None, Request::new(())).is_ok());
+		assert_eq!(
+			check_basic_auth(&expected, Request::new(()))
+				.unwrap_err()
+				.code(),
+			tonic::Code::Unauthenticated
+		);
+		let mut request = Request::new(());
+		request
+			.metadata_mut()
+			.insert("authorization", "Basic bXdjOnNlY3JldA==".parse().unwrap());
+		assert!(check_basic_auth(&expected, request).is_ok());
+	}
+
+	#[test]
+	fn grpc_status_update() {
+		let update = status_update(StatusMessage::Scanning(false, "Scanning".to_string(), 42));
+		assert_eq!(update.kind, "scanning");
+		assert_eq!(update.percentage, 42);
+		let event = wallet_event(&notifications::WalletEvent::SwapFailed {
+			swap_id: "swap".to_string(),
+			state: "state".to_string(),
+			error: "error".to_string(),
+		});
+		assert_eq!(event.event, "swap_failed");
+	}
+}
@@ -35,16 +35,24 @@ extern crate serde_json;
 
 #[macro_use]
 extern crate log;
+#[macro_use]
+extern crate lazy_static;
 
+mod config_reload;
 mod foreign;
 mod foreign_rpc;
 
+/// gRPC variant of the Owner API
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
 mod owner;
 mod owner_rpc_v2;
 mod owner_rpc_v3;
 
 mod types;
 
+pub use crate::config_reload::{apply_runtime_config, reload_config, ConfigReloadStatus};
 pub use crate::foreign::{Foreign, ForeignCheckMiddleware, ForeignCheckMiddlewareFn};
 pub use crate::foreign_rpc::ForeignRpc;
 pub use crate::owner::Owner;
@@ -19,27 +19,50 @@ use ed25519_dalek::PublicKey as DalekPublicKey;
 use uuid::Uuid;
 
 use crate::config::{MQSConfig, TorConfig, WalletConfig};
+use crate::config_reload::{reload_config, ConfigReloadStatus};
+use crate::core::core::hash::Hashed;
 use crate::core::core::Transaction;
 use crate::core::global;
-use crate::impls::create_sender;
+use crate::impls::{
+	create_backup_store, create_sender, flush_mwcmqs_outbox, get_mwcmqs_outbox, init_mwcmqs_outbox,
+	MqsQueuedMessage, OfflineBundleStage, OfflineTxBundle, SlateSender,
+};
 use crate::keychain::{Identifier, Keychain};
 use crate::libwallet::api_impl::foreign;
 use crate::libwallet::api_impl::owner_updater::{start_updater_log_thread, StatusMessage};
-use crate::libwallet::api_impl::{owner, owner_eth, owner_swap, owner_updater};
+use crate::libwallet::api_impl::{owner, owner_eth, owner_multisig, owner_swap, owner_updater};
+use crate::libwallet::audit_log::{self, AuditBalance};
+use crate::libwallet::backup;
+use crate::libwallet::listener_limits::{self, ListenerMetrics};
+use crate::libwallet::listener_status::{self, ListenerStatus};
+use crate::libwallet::migration::{WalletMigrationData, WalletMigrationReport};
+use crate::libwallet::multisig::{MultisigRecord, MultisigSession};
+use crate::libwallet::node_cache::{self, NodeCacheMetrics};
+use crate::libwallet::payment_id::{self, PaymentId, PaymentIdRecord, ResolvedPaymentId};
+use crate::libwallet::price::{self, ExchangeRate};
 use crate::libwallet::proof::proofaddress;
 use crate::libwallet::proof::tx_proof::TxProof;
+use crate::libwallet::swap::bitcoin::{SecondaryAddress, SweptAddress};
 use crate::libwallet::swap::fsm::state::{StateEtaInfo, StateId, StateProcessRespond};
+use crate::libwallet::swap::history::SwapHistoryRecord;
 use crate::libwallet::swap::types::{Action, Currency, SwapTransactionsConfirmations};
 use crate::libwallet::swap::{message::Message, swap::Swap, swap::SwapJournalRecord};
+use crate::libwallet::tx_import::{TxImportRecord, TxImportReport};
+use crate::libwallet::tx_mirror;
 use crate::libwallet::{
-	AcctPathMapping, Error, ErrorKind, InitTxArgs, IssueInvoiceTxArgs, NodeClient,
-	NodeHeightResult, OutputCommitMapping, PaymentProof, Slate, SlatePurpose, SlateVersion,
-	SwapStartArgs, TxLogEntry, VersionedSlate, WalletInfo, WalletInst, WalletLCProvider,
+	AccountSummaryInfo, AcctPathMapping, AddressMapping, AutoCancelPolicy, CheckCategory,
+	CheckReport, ConsensusParams, Contact, Error, ErrorKind, GcArgs, GcResult, HistoryBucket,
+	InitTxArgs, IssueInvoiceTxArgs, KernelLookupResult, MultisigCreateArgs, NodeClient,
+	NodeHeightResult, OutputCommitMapping, OutputQueryArgs, OutputTagArgs, PaymentProof,
+	PublicProofAddress, ReissueTxArgs, ScanProgress, Slate, SlatePurpose, SlateVersion,
+	SwapStartArgs, TxLabelArgs, TxLogEntry, TxQueryArgs, UtxoSnapshot, VersionedSlate, WalletInfo,
+	WalletInst, WalletLCProvider,
 };
 use crate::util::logger::LoggingConfig;
 use crate::util::secp::key::SecretKey;
 use crate::util::{from_hex, Mutex, ZeroingString};
 use grin_wallet_util::grin_util::secp::key::PublicKey;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, Sender};
 use std::sync::Arc;
@@ -77,6 +100,8 @@ where
 	updater: Arc<Mutex<owner_updater::Updater<'static, L, C, K>>>,
 	/// Stop state for update thread
 	pub updater_running: Arc<AtomicBool>,
+	/// The update thread does the scheduled backups
+	scheduled_backups: Arc<AtomicBool>,
 	/// Sender for update messages
 	status_tx: Mutex<Option<Sender<StatusMessage>>>,
 	/// Holds all update and status messages returned by the
@@ -205,9 +230,11 @@ where
 		tor_config: Option<TorConfig>,
 	) -> Self {
 		let updater_running = Arc::new(AtomicBool::new(false));
+		let scheduled_backups = Arc::new(AtomicBool::new(true));
 		let updater = Arc::new(Mutex::new(owner_updater::Updater::new(
 			wallet_inst.clone(),
 			updater_running.clone(),
+			scheduled_backups.clone(),
 		)));
 		let updater_messages = Arc::new(Mutex::new(vec![]));
 
@@ -229,6 +256,7 @@ where
 			shared_key: Arc::new(Mutex::new(None)),
 			updater,
 			updater_running,
+			scheduled_backups,
 			status_tx: Mutex::new(Some(tx)),
 			updater_messages,
 			tor_config: Mutex::new(tor_config),
@@ -250,6 +278,19 @@ where
 		*lock = tor_config;
 	}
 
+	/// Enable or disable the scheduled backups from the update thread of this instance.
+	/// The backup schedule and store are shared by the process, so only one wallet
+	/// of the process can use them. Enabled by default.
+	///
+	/// # Arguments
+	/// * `enabled` - false disables the scheduled backups of this wallet
+	/// # Returns
+	/// * Nothing
+
+	pub fn set_scheduled_backups(&self, enabled: bool) {
+		self.scheduled_backups.store(enabled, Ordering::Relaxed);
+	}
+
 	/// Returns a list of accounts stored in the wallet (i.e. mappings between
 	/// user-specified labels and BIP32 derivation paths.
 	/// # Arguments
@@ -337,9 +378,104 @@ where
 		keychain_mask: Option<&SecretKey>,
 		label: &str,
 	) -> Result<Identifier, Error> {
-		let mut w_lock = self.wallet_inst.lock();
-		let w = w_lock.lc_provider()?.wallet_inst()?;
-		owner::create_account_path(&mut **w, keychain_mask, label)
+		self.audit("create_account_path", format!("label: {}", label), || {
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			owner::create_account_path(&mut **w, keychain_mask, label)
+		})
+	}
+
+	/// Renames an existing account. The BIP32 path of the account is not changed.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `old_label` - Current label of the account
+	/// * `new_label` - New label, it must not be used by another account
+	///
+	/// # Returns
+	/// * `Ok(())` if the account was renamed
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Remarks
+	///
+	/// * The 'default' account can't be renamed.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.create_account_path(None, "account1");
+	///
+	/// if let Ok(_) = result {
+	///     let result2 = api_owner.rename_account(None, "account1", "savings");
+	/// }
+	/// ```
+
+	pub fn rename_account(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		old_label: &str,
+		new_label: &str,
+	) -> Result<(), Error> {
+		self.audit(
+			"rename_account",
+			format!("old_label: {}, new_label: {}", old_label, new_label),
+			|| {
+				let mut w_lock = self.wallet_inst.lock();
+				let w = w_lock.lc_provider()?.wallet_inst()?;
+				owner::rename_account(&mut **w, keychain_mask, old_label, new_label)
+			},
+		)
+	}
+
+	/// Deletes an account. Only accounts without funds can be deleted, the account must
+	/// not have any unspent, unconfirmed or locked outputs.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `label` - Label of the account to delete
+	///
+	/// # Returns
+	/// * `Ok(())` if the account was deleted
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Remarks
+	///
+	/// * The 'default' and the active accounts can't be deleted.
+	/// * Funds can be moved to another account with a self send before the deletion.
+	/// * Transactions of the deleted account stay in the wallet history.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.create_account_path(None, "account1");
+	///
+	/// if let Ok(_) = result {
+	///     let result2 = api_owner.delete_account(None, "account1");
+	/// }
+	/// ```
+
+	pub fn delete_account(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		label: &str,
+	) -> Result<(), Error> {
+		self.audit("delete_account", format!("label: {}", label), || {
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			owner::delete_account(&mut **w, keychain_mask, label)
+		})
 	}
 
 	/// Sets the wallet's currently active account. This sets the
@@ -392,6 +528,197 @@ where
 		owner::set_active_account(&mut **w, label)
 	}
 
+	/// Returns a list of address book contacts stored in the wallet, sorted by name.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	///
+	/// # Returns
+	/// * Result Containing:
+	/// * A Vector of [`Contact`](../grin_wallet_libwallet/types/struct.Contact.html) data
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.contacts(None);
+	///
+	/// if let Ok(contacts) = result {
+	///     //...
+	/// }
+	/// ```
+
+	pub fn contacts(&self, keychain_mask: Option<&SecretKey>) -> Result<Vec<Contact>, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		// Test keychain mask, to keep API consistent
+		let _ = w.keychain(keychain_mask)?;
+		owner::contacts(&mut **w)
+	}
+
+	/// Adds a new address book contact. Contact can be referenced as `@name` instead of
+	/// the MQS/Tor/HTTP address in send and swap commands.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `name` - Contact name. Can contain letters, digits, '_', '-' and '.'
+	/// * `address` - MQS, Tor or HTTP(S) address of the contact wallet.
+	///
+	/// # Returns
+	/// * Result Containing:
+	/// * `Ok(())` if the contact was created
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.add_contact(None, "alice", "http://127.0.0.1:3415");
+	/// ```
+
+	pub fn add_contact(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		name: &str,
+		address: &str,
+	) -> Result<(), Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::add_contact(&mut **w, keychain_mask, Contact::new(name, address))
+	}
+
+	/// Removes address book contact.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `name` - Contact name to remove.
+	///
+	/// # Returns
+	/// * Result Containing:
+	/// * `Ok(())` if the contact was removed
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.add_contact(None, "alice", "http://127.0.0.1:3415");
+	///
+	/// if let Ok(_) = result {
+	///     let result2 = api_owner.remove_contact(None, "alice");
+	/// }
+	/// ```
+
+	pub fn remove_contact(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		name: &str,
+	) -> Result<(), Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::remove_contact(&mut **w, keychain_mask, name)
+	}
+
+	/// Resolves the destination. If the destination is a contact reference `@name`, the address
+	/// of the contact is returned. Any other destination is returned unchanged.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `dest` - Destination address or `@name` contact reference.
+	///
+	/// # Returns
+	/// * Result Containing:
+	/// * Resolved address
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the contact is not found.
+
+	pub fn resolve_contact_address(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		dest: &str,
+	) -> Result<String, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		// Test keychain mask, to keep API consistent
+		let _ = w.keychain(keychain_mask)?;
+		owner::resolve_contact_address(&mut **w, dest)
+	}
+
+	/// Resolves the payment id `name@domain` into the wallet address. The record is looked up in
+	/// the DNS TXT record `<name>.user._mwc-payment.<domain>` with the DNS-over-HTTPS resolver
+	/// from `payment_id_doh_url`, then at `https://<domain>/.well-known/mwc-payment/<name>.json`.
+	/// The record is accepted only if it is signed by its proof address.
+	///
+	/// # Arguments
+	/// * `payment_id` - Payment id `name@domain`.
+	///
+	/// # Returns
+	/// * Ok with the [`ResolvedPaymentId`](../grin_wallet_libwallet/payment_id/struct.ResolvedPaymentId.html),
+	/// the address for send and the proof address.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the record is not
+	/// found or the signature is not valid.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// if let Ok(resolved) = api_owner.resolve_payment_id("alice@example.com") {
+	///     println!("Sending to {}, proof address {}", resolved.address, resolved.proof_address);
+	/// }
+	/// ```
+
+	pub fn resolve_payment_id(&self, payment_id: &str) -> Result<ResolvedPaymentId, Error> {
+		payment_id::resolve_payment_id(payment_id)
+	}
+
+	/// Builds the signed record that binds the payment id `name@domain` to the wallet address.
+	/// The record is signed by the proof address of this wallet. Publish it as a DNS TXT record
+	/// or as the `.well-known` json file of the domain.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `payment_id` - Payment id `name@domain`.
+	/// * `address` - Address to receive the payments: Tor address, MQS address or http(s) URL.
+	/// * `tor` - Sign with the Tor proof address, MQS proof address otherwise.
+	///
+	/// # Returns
+	/// * Ok with the [`PaymentIdRecord`](../grin_wallet_libwallet/payment_id/struct.PaymentIdRecord.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+
+	pub fn payment_id_record(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		payment_id: &str,
+		address: &str,
+		tor: bool,
+	) -> Result<PaymentIdRecord, Error> {
+		let id = PaymentId::parse(payment_id).ok_or(ErrorKind::PaymentId(format!(
+			"'{}' is not a payment id 'name@domain'",
+			payment_id
+		)))?;
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		let keychain = w.keychain(keychain_mask)?;
+		PaymentIdRecord::sign(&keychain, &id, address, tor)
+	}
+
 	/// Returns a list of outputs from the active account in the wallet.
 	///
 	/// # Arguments
@@ -544,51 +871,51 @@ where
 		Ok(res)
 	}
 
-	/// Returns summary information from the active account in the wallet.
+	/// Returns a page of the outputs from the active account that match the query. Use it
+	/// instead of [`retrieve_outputs`](struct.Owner.html#method.retrieve_outputs) for the
+	/// wallets with many outputs.
 	///
 	/// # Arguments
 	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
 	/// being used.
 	/// * `refresh_from_node` - If true, the wallet will attempt to contact
-	/// a node (via the [`NodeClient`](../grin_wallet_libwallet/types/trait.NodeClient.html)
-	/// provided during wallet instantiation). If `false`, the results will
-	/// contain transaction information that may be out-of-date (from the last time
-	/// the wallet's output set was refreshed against the node).
-	/// Note this setting is ignored if the updater process is running via a call to
-	/// [`start_updater`](struct.Owner.html#method.start_updater)
-	/// * `minimum_confirmations` - The minimum number of confirmations an output
-	/// should have before it's included in the 'amount_currently_spendable' total
+	/// a node first, same as for [`retrieve_outputs`](struct.Owner.html#method.retrieve_outputs).
+	/// * `query` - [`OutputQueryArgs`](../grin_wallet_libwallet/api_impl/types/struct.OutputQueryArgs.html)
+	/// with the filters, offset and limit.
 	///
 	/// # Returns
-	/// * (`bool`, [`WalletInfo`](../grin_wallet_libwallet/types/struct.WalletInfo.html)) - A tuple:
+	/// * `(bool, Vec<OutputCommitMapping>)` - A tuple:
 	/// * The first `bool` element indicates whether the data was successfully
-	/// refreshed from the node (note this may be false even if the `refresh_from_node`
-	/// argument was set to `true`.
-	/// * The second element contains the Summary [`WalletInfo`](../grin_wallet_libwallet/types/struct.WalletInfo.html)
+	/// refreshed from the node.
+	/// * The second element contains the outputs from the requested page.
 	///
 	/// # Example
 	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	/// use grin_wallet_libwallet::{OutputQueryArgs, OutputStatus};
 	///
-	/// let mut api_owner = Owner::new(wallet.clone(), None, None);
-	/// let update_from_node = true;
-	/// let minimum_confirmations=10;
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// // First 20 unspent outputs
+	/// let query = OutputQueryArgs {
+	///     limit: Some(20),
+	///     status: Some(vec![OutputStatus::Unspent]),
+	///     ..Default::default()
+	/// };
 	///
-	/// // Return summary info for active account
-	/// let result = api_owner.retrieve_summary_info(None, update_from_node, minimum_confirmations);
+	/// let result = api_owner.query_outputs(None, true, &query);
 	///
-	/// if let Ok((was_updated, summary_info)) = result {
+	/// if let Ok((was_updated, output_mappings)) = result {
 	///     //...
 	/// }
 	/// ```
 
-	pub fn retrieve_summary_info(
+	pub fn query_outputs(
 		&self,
 		keychain_mask: Option<&SecretKey>,
 		refresh_from_node: bool,
-		minimum_confirmations: u64,
-	) -> Result<(bool, WalletInfo), Error> {
+		query: &OutputQueryArgs,
+	) -> Result<(bool, Vec<OutputCommitMapping>), Error> {
 		let tx = {
 			let t = self.status_tx.lock();
 			t.clone()
@@ -597,37 +924,228 @@ where
 			true => false,
 			false => refresh_from_node,
 		};
-		owner::retrieve_summary_info(
+		owner::query_outputs(
 			self.wallet_inst.clone(),
 			keychain_mask,
 			&tx,
 			refresh_from_node,
-			minimum_confirmations,
+			query,
 		)
 	}
 
-	/// Initiates a new transaction as the sender, creating a new
-	/// [`Slate`](../grin_wallet_libwallet/slate/struct.Slate.html) object containing
-	/// the sender's inputs, change outputs, and public signature data. This slate can
-	/// then be sent to the recipient to continue the transaction via the
-	/// [Foreign API's `receive_tx`](struct.Foreign.html#method.receive_tx) method.
-	///
-	/// When a transaction is created, the wallet must also lock inputs (and create unconfirmed
-	/// outputs) corresponding to the transaction created in the slate, so that the wallet doesn't
-	/// attempt to re-spend outputs that are already included in a transaction before the transaction
-	/// is confirmed. This method also returns a function that will perform that locking, and it is
-	/// up to the caller to decide the best time to call the lock function
-	/// (via the [`tx_lock_outputs`](struct.Owner.html#method.tx_lock_outputs) method).
-	/// If the exchange method is intended to be synchronous (such as via a direct http call,)
-	/// then the lock call can wait until the response is confirmed. If it is asynchronous, (such
-	/// as via file transfer,) the lock call should happen immediately (before the file is sent
-	/// to the recipient).
-	///
-	/// If the `send_args` [`InitTxSendArgs`](../grin_wallet_libwallet/types/struct.InitTxSendArgs.html),
-	/// of the [`args`](../grin_wallet_libwallet/types/struct.InitTxArgs.html), field is Some, this
-	/// function will attempt to perform a synchronous send to the recipient specified in the `dest`
-	/// field according to the `method` field, and will also finalize and post the transaction if
-	/// the `finalize` field is set.
+	/// Returns a page of the [Transaction Log Entries](../grin_wallet_libwallet/types/struct.TxLogEntry.html)
+	/// from the active account that match the query. Use it instead of
+	/// [`retrieve_txs`](struct.Owner.html#method.retrieve_txs) for the wallets with long history.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `refresh_from_node` - If true, the wallet will attempt to contact
+	/// a node first, same as for [`retrieve_txs`](struct.Owner.html#method.retrieve_txs).
+	/// * `query` - [`TxQueryArgs`](../grin_wallet_libwallet/api_impl/types/struct.TxQueryArgs.html)
+	/// with the filters, offset and limit.
+	///
+	/// # Returns
+	/// * `(bool, Vec<TxLogEntry)` - A tuple:
+	/// * The first `bool` element indicates whether the data was successfully
+	/// refreshed from the node.
+	/// * The second element contains the transactions from the requested page.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	/// use grin_wallet_libwallet::TxQueryArgs;
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// // Second page of the confirmed transactions
+	/// let query = TxQueryArgs {
+	///     offset: Some(50),
+	///     limit: Some(50),
+	///     confirmed: Some(true),
+	///     ..Default::default()
+	/// };
+	///
+	/// let result = api_owner.query_txs(None, true, &query);
+	///
+	/// if let Ok((was_updated, tx_log_entries)) = result {
+	///     //...
+	/// }
+	/// ```
+
+	pub fn query_txs(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		refresh_from_node: bool,
+		query: &TxQueryArgs,
+	) -> Result<(bool, Vec<TxLogEntry>), Error> {
+		let tx = {
+			let t = self.status_tx.lock();
+			t.clone()
+		};
+		let refresh_from_node = match self.updater_running.load(Ordering::Relaxed) {
+			true => false,
+			false => refresh_from_node,
+		};
+		let mut res = owner::query_txs(
+			self.wallet_inst.clone(),
+			keychain_mask,
+			&tx,
+			refresh_from_node,
+			query,
+		)?;
+		if self.doctest_mode {
+			res.1 = res
+				.1
+				.into_iter()
+				.map(|mut t| {
+					t.confirmation_ts = Some(Utc.ymd(2019, 1, 15).and_hms(16, 1, 26));
+					t.creation_ts = Utc.ymd(2019, 1, 15).and_hms(16, 1, 26);
+					t
+				})
+				.collect();
+		}
+		Ok(res)
+	}
+
+	/// Returns summary information from the active account in the wallet.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `refresh_from_node` - If true, the wallet will attempt to contact
+	/// a node (via the [`NodeClient`](../grin_wallet_libwallet/types/trait.NodeClient.html)
+	/// provided during wallet instantiation). If `false`, the results will
+	/// contain transaction information that may be out-of-date (from the last time
+	/// the wallet's output set was refreshed against the node).
+	/// Note this setting is ignored if the updater process is running via a call to
+	/// [`start_updater`](struct.Owner.html#method.start_updater)
+	/// * `minimum_confirmations` - The minimum number of confirmations an output
+	/// should have before it's included in the 'amount_currently_spendable' total
+	///
+	/// # Returns
+	/// * (`bool`, [`WalletInfo`](../grin_wallet_libwallet/types/struct.WalletInfo.html)) - A tuple:
+	/// * The first `bool` element indicates whether the data was successfully
+	/// refreshed from the node (note this may be false even if the `refresh_from_node`
+	/// argument was set to `true`.
+	/// * The second element contains the Summary [`WalletInfo`](../grin_wallet_libwallet/types/struct.WalletInfo.html)
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let mut api_owner = Owner::new(wallet.clone(), None, None);
+	/// let update_from_node = true;
+	/// let minimum_confirmations=10;
+	///
+	/// // Return summary info for active account
+	/// let result = api_owner.retrieve_summary_info(None, update_from_node, minimum_confirmations);
+	///
+	/// if let Ok((was_updated, summary_info)) = result {
+	///     //...
+	/// }
+	/// ```
+
+	pub fn retrieve_summary_info(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		refresh_from_node: bool,
+		minimum_confirmations: u64,
+	) -> Result<(bool, WalletInfo), Error> {
+		let tx = {
+			let t = self.status_tx.lock();
+			t.clone()
+		};
+		let refresh_from_node = match self.updater_running.load(Ordering::Relaxed) {
+			true => false,
+			false => refresh_from_node,
+		};
+		owner::retrieve_summary_info(
+			self.wallet_inst.clone(),
+			keychain_mask,
+			&tx,
+			refresh_from_node,
+			minimum_confirmations,
+		)
+	}
+
+	/// Returns summary information for every account in the wallet. The wallet is refreshed
+	/// from the node once for all accounts.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `refresh_from_node` - If true, the wallet will attempt to contact a node first. See
+	/// [`retrieve_summary_info`](struct.Owner.html#method.retrieve_summary_info) for details.
+	/// * `minimum_confirmations` - The minimum number of confirmations an output
+	/// should have before it's included in the 'amount_currently_spendable' total
+	///
+	/// # Returns
+	/// * (`bool`, `Vec<AccountSummaryInfo>`) - A tuple:
+	/// * The first `bool` element indicates whether the data was successfully
+	/// refreshed from the node.
+	/// * The second element contains the account labels, paths and summary info
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let mut api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.retrieve_accounts_summary_info(None, true, 10);
+	///
+	/// if let Ok((was_updated, accounts)) = result {
+	///     let total: u64 = accounts.iter().map(|a| a.info.total).sum();
+	/// }
+	/// ```
+
+	pub fn retrieve_accounts_summary_info(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		refresh_from_node: bool,
+		minimum_confirmations: u64,
+	) -> Result<(bool, Vec<AccountSummaryInfo>), Error> {
+		let tx = {
+			let t = self.status_tx.lock();
+			t.clone()
+		};
+		let refresh_from_node = match self.updater_running.load(Ordering::Relaxed) {
+			true => false,
+			false => refresh_from_node,
+		};
+		owner::retrieve_accounts_summary_info(
+			self.wallet_inst.clone(),
+			keychain_mask,
+			&tx,
+			refresh_from_node,
+			minimum_confirmations,
+		)
+	}
+
+	/// Initiates a new transaction as the sender, creating a new
+	/// [`Slate`](../grin_wallet_libwallet/slate/struct.Slate.html) object containing
+	/// the sender's inputs, change outputs, and public signature data. This slate can
+	/// then be sent to the recipient to continue the transaction via the
+	/// [Foreign API's `receive_tx`](struct.Foreign.html#method.receive_tx) method.
+	///
+	/// When a transaction is created, the wallet must also lock inputs (and create unconfirmed
+	/// outputs) corresponding to the transaction created in the slate, so that the wallet doesn't
+	/// attempt to re-spend outputs that are already included in a transaction before the transaction
+	/// is confirmed. This method also returns a function that will perform that locking, and it is
+	/// up to the caller to decide the best time to call the lock function
+	/// (via the [`tx_lock_outputs`](struct.Owner.html#method.tx_lock_outputs) method).
+	/// If the exchange method is intended to be synchronous (such as via a direct http call,)
+	/// then the lock call can wait until the response is confirmed. If it is asynchronous, (such
+	/// as via file transfer,) the lock call should happen immediately (before the file is sent
+	/// to the recipient).
+	///
+	/// If the `send_args` [`InitTxSendArgs`](../grin_wallet_libwallet/types/struct.InitTxSendArgs.html),
+	/// of the [`args`](../grin_wallet_libwallet/types/struct.InitTxArgs.html), field is Some, this
+	/// function will attempt to perform a synchronous send to the recipient specified in the `dest`
+	/// field according to the `method` field, and will also finalize and post the transaction if
+	/// the `finalize` field is set.
 	///
 	/// # Arguments
 	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
@@ -756,11 +1274,18 @@ where
 			None
 		};
 
-		let mut slate = {
-			let mut w_lock = self.wallet_inst.lock();
-			let w = w_lock.lc_provider()?.wallet_inst()?;
-			owner::init_send_tx(&mut **w, keychain_mask, &args, self.doctest_mode, routputs)?
-		};
+		let mut slate = self.audit(
+			"init_send_tx",
+			format!(
+				"amount: {}, src_acct_name: {:?}, address: {:?}",
+				args.amount, args.src_acct_name, args.address
+			),
+			|| {
+				let mut w_lock = self.wallet_inst.lock();
+				let w = w_lock.lc_provider()?.wallet_inst()?;
+				owner::init_send_tx(&mut **w, keychain_mask, &args, self.doctest_mode, routputs)
+			},
+		)?;
 
 		match send_args {
 			Some(sa) => {
@@ -878,9 +1403,18 @@ where
 		keychain_mask: Option<&SecretKey>,
 		args: &IssueInvoiceTxArgs,
 	) -> Result<Slate, Error> {
-		let mut w_lock = self.wallet_inst.lock();
-		let w = w_lock.lc_provider()?.wallet_inst()?;
-		owner::issue_invoice_tx(&mut **w, keychain_mask, args, self.doctest_mode, 1)
+		self.audit(
+			"issue_invoice_tx",
+			format!(
+				"amount: {}, dest_acct_name: {:?}",
+				args.amount, args.dest_acct_name
+			),
+			|| {
+				let mut w_lock = self.wallet_inst.lock();
+				let w = w_lock.lc_provider()?.wallet_inst()?;
+				owner::issue_invoice_tx(&mut **w, keychain_mask, args, self.doctest_mode, 1)
+			},
+		)
 	}
 
 	/// Processes an invoice tranaction created by another party, essentially
@@ -944,25 +1478,31 @@ where
 		slate: &Slate,
 		args: &InitTxArgs,
 	) -> Result<Slate, Error> {
-		owner::update_wallet_state(self.wallet_inst.clone(), keychain_mask, &None)?;
+		self.audit(
+			"process_invoice_tx",
+			format!("slate_id: {}, amount: {}", slate.id, slate.amount),
+			|| {
+				owner::update_wallet_state(self.wallet_inst.clone(), keychain_mask, &None)?;
 
-		//minimum_confirmations cannot be zero.
-		let minimum_confirmations = args.minimum_confirmations.clone();
-		if minimum_confirmations < 1 {
-			return Err(ErrorKind::ClientCallback(
-				"minimum_confirmations can not smaller than 1".to_owned(),
-			)
-			.into());
-		}
-		let mut w_lock = self.wallet_inst.lock();
-		let w = w_lock.lc_provider()?.wallet_inst()?;
-		owner::process_invoice_tx(
-			&mut **w,
-			keychain_mask,
-			slate,
-			args,
-			self.doctest_mode,
-			true,
+				//minimum_confirmations cannot be zero.
+				let minimum_confirmations = args.minimum_confirmations.clone();
+				if minimum_confirmations < 1 {
+					return Err(ErrorKind::ClientCallback(
+						"minimum_confirmations can not smaller than 1".to_owned(),
+					)
+					.into());
+				}
+				let mut w_lock = self.wallet_inst.lock();
+				let w = w_lock.lc_provider()?.wallet_inst()?;
+				owner::process_invoice_tx(
+					&mut **w,
+					keychain_mask,
+					slate,
+					args,
+					self.doctest_mode,
+					true,
+				)
+			},
 		)
 	}
 
@@ -1029,15 +1569,21 @@ where
 		address: Option<String>,
 		participant_id: usize,
 	) -> Result<(), Error> {
-		let mut w_lock = self.wallet_inst.lock();
-		let w = w_lock.lc_provider()?.wallet_inst()?;
-		owner::tx_lock_outputs(
-			&mut **w,
-			keychain_mask,
-			slate,
-			address,
-			participant_id,
-			self.doctest_mode,
+		self.audit(
+			"tx_lock_outputs",
+			format!("slate_id: {}, address: {:?}", slate.id, address),
+			|| {
+				let mut w_lock = self.wallet_inst.lock();
+				let w = w_lock.lc_provider()?.wallet_inst()?;
+				owner::tx_lock_outputs(
+					&mut **w,
+					keychain_mask,
+					slate,
+					address,
+					participant_id,
+					self.doctest_mode,
+				)
+			},
 		)
 	}
 
@@ -1106,12 +1652,21 @@ where
 		keychain_mask: Option<&SecretKey>,
 		slate: &Slate,
 	) -> Result<Slate, Error> {
-		let mut w_lock = self.wallet_inst.lock();
-		let w = w_lock.lc_provider()?.wallet_inst()?;
-		let (slate_res, _context) =
-			owner::finalize_tx(&mut **w, keychain_mask, &slate, true, self.doctest_mode)?;
+		self.audit(
+			"finalize_tx",
+			format!(
+				"slate_id: {}, amount: {}, fee: {}",
+				slate.id, slate.amount, slate.fee
+			),
+			|| {
+				let mut w_lock = self.wallet_inst.lock();
+				let w = w_lock.lc_provider()?.wallet_inst()?;
+				let (slate_res, _context) =
+					owner::finalize_tx(&mut **w, keychain_mask, &slate, true, self.doctest_mode)?;
 
-		Ok(slate_res)
+				Ok(slate_res)
+			},
+		)
 	}
 
 	/// Posts a completed transaction to the listening node for validation and inclusion in a block
@@ -1172,36 +1727,46 @@ where
 		tx: &Transaction,
 		fluff: bool,
 	) -> Result<(), Error> {
-		let client = {
-			let mut w_lock = self.wallet_inst.lock();
-			let w = w_lock.lc_provider()?.wallet_inst()?;
-			// Test keychain mask, to keep API consistent
-			let _ = w.keychain(keychain_mask)?;
-			w.w2n_client().clone()
-		};
-		owner::post_tx(&client, tx, fluff)
+		self.audit(
+			"post_tx",
+			format!("tx_hash: {}, fluff: {}", tx.hash(), fluff),
+			|| {
+				let (client, slate_id) = {
+					let mut w_lock = self.wallet_inst.lock();
+					let w = w_lock.lc_provider()?.wallet_inst()?;
+					// Test keychain mask, to keep API consistent
+					let _ = w.keychain(keychain_mask)?;
+					// Slate Id is needed only for the posted transactions mirror
+					let slate_id = if tx_mirror::is_enabled() {
+						let excess = tx.kernels().first().map(|k| k.excess);
+						w.tx_log_iter()
+							.find(|t| excess.is_some() && t.kernel_excess == excess)
+							.and_then(|t| t.tx_slate_id)
+					} else {
+						None
+					};
+					(w.w2n_client().clone(), slate_id)
+				};
+				owner::post_tx(&client, tx, slate_id, fluff)
+			},
+		)
 	}
 
-	/// Cancels a transaction. This entails:
-	/// * Setting the transaction status to either `TxSentCancelled` or `TxReceivedCancelled`
-	/// * Deleting all change outputs or recipient outputs associated with the transaction
-	/// * Setting the status of all assocatied inputs from `Locked` to `Spent` so they can be
-	/// used in new transactions.
-	///
-	/// Transactions can be cancelled by transaction log id or slate id (call with either set to
-	/// Some, not both)
+	/// Creates the offline signing (cold wallet) bundle for a send transaction. This is the first
+	/// step of the offline signing workflow, it is done by the online wallet. The online wallet
+	/// only reads the chain tip from the node and checks the recipient wallet, the transaction
+	/// is built and signed by the offline wallet with
+	/// [`sign_offline_bundle`](struct.Owner.html#method.sign_offline_bundle).
 	///
 	/// # Arguments
-	///
 	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
 	/// being used.
-	/// * `tx_id` - If present, cancel by the [`TxLogEntry`](../grin_wallet_libwallet/types/struct.TxLogEntry.html) id
-	/// for the transaction.
-	///
-	/// * `tx_slate_id` - If present, cancel by the Slate id.
+	/// * `args` - [`InitTxArgs`](../grin_wallet_libwallet/types/struct.InitTxArgs.html),
+	/// transaction initialization arguments. `send_args` are required, they define the recipient
+	/// that the online wallet will send the slate to. Late locking is not supported.
 	///
 	/// # Returns
-	/// * `Ok(())` if successful
+	/// * Ok with the unsigned [`OfflineTxBundle`](../grin_wallet_impls/offline_bundle/struct.OfflineTxBundle.html)
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -1209,66 +1774,88 @@ where
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
-	/// let mut api_owner = Owner::new(wallet.clone(), None, None);
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
 	/// let args = InitTxArgs {
-	///     src_acct_name: None,
 	///     amount: 2_000_000_000,
 	///     minimum_confirmations: 10,
-	///     max_outputs: 500,
-	///     num_change_outputs: 1,
-	///     selection_strategy_is_use_all: false,
-	///     message: Some("Cancel this tx".to_owned()),
+	///     send_args: Some(libwallet::InitTxSendArgs {
+	///         method: "http".to_string(),
+	///         dest: "http://127.0.0.1:3415".to_string(),
+	///         apisecret: None,
+	///         finalize: true,
+	///         post_tx: true,
+	///         fluff: false,
+	///     }),
 	///     ..Default::default()
 	/// };
-	/// let result = api_owner.init_send_tx(
-	/// 	None,
-	/// 	&args,
-	/// 	1,
-	/// );
+	/// let result = api_owner.create_offline_bundle(None, &args);
 	///
-	/// if let Ok(slate) = result {
-	///		// Send slate somehow
-	///		// ...
-	///		// Lock our outputs if we're happy the slate was (or is being) sent
-	///		let res = api_owner.tx_lock_outputs(None, &slate, None, 0);
-	///		//
-	///		// We didn't get the slate back, or something else went wrong
-	///		//
-	///		let res = api_owner.cancel_tx(None, None, Some(slate.id.clone()));
+	/// if let Ok(bundle) = result {
+	///     // Move the bundle to the offline wallet
+	///     // ...
 	/// }
 	/// ```
 
-	pub fn cancel_tx(
+	pub fn create_offline_bundle(
 		&self,
 		keychain_mask: Option<&SecretKey>,
-		tx_id: Option<u32>,
-		tx_slate_id: Option<Uuid>,
-	) -> Result<(), Error> {
-		let tx = {
-			let t = self.status_tx.lock();
-			t.clone()
+		args: &InitTxArgs,
+	) -> Result<OfflineTxBundle, Error> {
+		let sa = args.send_args.clone().ok_or(ErrorKind::GenericError(
+			"Offline bundle needs the recipient, send_args are not defined".to_string(),
+		))?;
+		if args.late_lock.unwrap_or(false) || args.estimate_only.unwrap_or(false) {
+			return Err(ErrorKind::GenericError(
+				"Late locking and estimation are not supported for the offline bundle".to_string(),
+			)
+			.into());
+		}
+		if args.minimum_confirmations < 1 {
+			return Err(ErrorKind::ClientCallback(
+				"Minimum_confirmations can not be smaller than 1".to_owned(),
+			)
+			.into());
+		}
+
+		// The slate is built offline, so the slate version is checked now
+		let mut args = args.clone();
+		let sender = self.create_bundle_sender(&sa.method, &sa.dest, &sa.apisecret)?;
+		let other_wallet_version = sender.check_other_wallet_version(&sa.dest).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to get other wallet info, {}", e))
+		})?;
+		if let Some(other_wallet_version) = &other_wallet_version {
+			if args.target_slate_version.is_none() {
+				args.target_slate_version =
+					Some(other_wallet_version.0.to_numeric_version() as u16);
+			}
+		}
+
+		let (height, header_hash) = {
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			// Test keychain mask, to keep API consistent
+			let _ = w.keychain(keychain_mask)?;
+			let (height, header_hash, _) = w.w2n_client().get_chain_tip()?;
+			(height, header_hash)
 		};
-		owner::cancel_tx(
-			self.wallet_inst.clone(),
-			keychain_mask,
-			&tx,
-			tx_id,
-			tx_slate_id,
-		)
+		Ok(OfflineTxBundle::new(args, height, header_hash))
 	}
 
-	/// Retrieves the stored transaction associated with a TxLogEntry. Can be used even after the
-	/// transaction has completed.
+	/// Signs the offline signing bundle. This method is called by the offline wallet, it doesn't
+	/// need the node, the chain height is taken from the bundle. It is called twice:
+	/// * for the unsigned bundle it builds the send slate and locks the outputs;
+	/// * for the bundle with the recipient response it finalizes the transaction.
 	///
-	/// # Arguments
+	/// The secret data of the transaction stays in the offline wallet.
 	///
+	/// # Arguments
 	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
 	/// being used.
-	/// * `tx_log_entry` - A [`TxLogEntry`](../grin_wallet_libwallet/types/struct.TxLogEntry.html)
+	/// * `bundle` - [`OfflineTxBundle`](../grin_wallet_impls/offline_bundle/struct.OfflineTxBundle.html)
+	/// at the `Unsigned` or `Responded` stage.
 	///
 	/// # Returns
-	/// * Ok with the stored  [`Transaction`](../grin_core/core/transaction/struct.Transaction.html)
-	/// if successful
+	/// * Ok with the bundle at the `Signed` or `Finalized` stage
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -1277,55 +1864,237 @@ where
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
 	/// let api_owner = Owner::new(wallet.clone(), None, None);
-	/// let update_from_node = true;
-	/// let tx_id = None;
-	/// let tx_slate_id = None;
-	///
-	/// // Return all TxLogEntries
-	/// let result = api_owner.retrieve_txs(None, update_from_node, tx_id, tx_slate_id);
+	/// let args = InitTxArgs {
+	///     amount: 2_000_000_000,
+	///     minimum_confirmations: 10,
+	///     ..Default::default()
+	/// };
+	/// let bundle = impls::OfflineTxBundle::new(args, 100, String::new());
+	/// let result = api_owner.sign_offline_bundle(None, &bundle);
 	///
-	/// if let Ok((was_updated, tx_log_entries)) = result {
-	///     let stored_tx = api_owner.get_stored_tx(None, &tx_log_entries[0]).unwrap();
-	///     //...
+	/// if let Ok(bundle) = result {
+	///     // Move the signed bundle back to the online wallet
+	///     // ...
 	/// }
 	/// ```
 
-	// TODO: Should be accepting an id, not an entire entry struct
-	pub fn get_stored_tx(
+	pub fn sign_offline_bundle(
 		&self,
 		keychain_mask: Option<&SecretKey>,
-		tx_log_entry: &TxLogEntry,
-	) -> Result<Option<Transaction>, Error> {
-		let mut w_lock = self.wallet_inst.lock();
-		let w = w_lock.lc_provider()?.wallet_inst()?;
-		// Test keychain mask, to keep API consistent
-		let _ = w.keychain(keychain_mask)?;
-		owner::get_stored_tx(&**w, tx_log_entry)
+		bundle: &OfflineTxBundle,
+	) -> Result<OfflineTxBundle, Error> {
+		let bundle_err = |e: crate::impls::Error| ErrorKind::GenericError(format!("{}", e));
+		bundle
+			.check_stage(&[OfflineBundleStage::Unsigned, OfflineBundleStage::Responded])
+			.map_err(bundle_err)?;
+		if bundle.args.late_lock.unwrap_or(false) {
+			return Err(ErrorKind::GenericError(
+				"Late locking is not supported for the offline bundle".to_string(),
+			)
+			.into());
+		}
+
+		self.audit(
+			"sign_offline_bundle",
+			format!(
+				"stage: {}, amount: {}, height: {}",
+				bundle.stage, bundle.args.amount, bundle.height
+			),
+			|| {
+				let mut bundle = bundle.clone();
+				let mut w_lock = self.wallet_inst.lock();
+				let w = w_lock.lc_provider()?.wallet_inst()?;
+				if bundle.stage == OfflineBundleStage::Unsigned {
+					let address = bundle.args.send_args.as_ref().map(|sa| sa.dest.clone());
+					let slate = owner::init_send_tx_at_height(
+						&mut **w,
+						keychain_mask,
+						&bundle.args,
+						self.doctest_mode,
+						1,
+						Some(bundle.height),
+					)?;
+					owner::tx_lock_outputs_at_height(
+						&mut **w,
+						keychain_mask,
+						&slate,
+						address,
+						0,
+						self.doctest_mode,
+						Some(bundle.height),
+					)?;
+					bundle
+						.set_slate(&slate, OfflineBundleStage::Signed)
+						.map_err(bundle_err)?;
+				} else {
+					let slate = bundle.get_slate().map_err(bundle_err)?;
+					let (slate, _context) = owner::finalize_tx_at_height(
+						&mut **w,
+						keychain_mask,
+						&slate,
+						false,
+						self.doctest_mode,
+						Some(bundle.height),
+					)?;
+					bundle
+						.set_slate(&slate, OfflineBundleStage::Finalized)
+						.map_err(bundle_err)?;
+				}
+				Ok(bundle)
+			},
+		)
 	}
 
-	/// Loads a stored transaction from a file
-	pub fn load_stored_tx(&self, file: &String) -> Result<Transaction, Error> {
-		let mut w_lock = self.wallet_inst.lock();
-		let w = w_lock.lc_provider()?.wallet_inst()?;
-		owner::load_stored_tx(&**w, file)
+	/// Processes the signed offline bundle at the online wallet:
+	/// * for the `Signed` bundle the slate is sent to the recipient from the bundle `send_args`.
+	/// The response is stored in the bundle together with the current chain height, the bundle
+	/// needs to be signed by the offline wallet again;
+	/// * for the `Finalized` bundle the transaction is posted to the node.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `bundle` - [`OfflineTxBundle`](../grin_wallet_impls/offline_bundle/struct.OfflineTxBundle.html)
+	/// at the `Signed` or `Finalized` stage.
+	///
+	/// # Returns
+	/// * Ok with the bundle. `Responded` bundle if the slate was sent, the same bundle if the
+	/// transaction was posted.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let args = InitTxArgs {
+	///     amount: 2_000_000_000,
+	///     minimum_confirmations: 10,
+	///     ..Default::default()
+	/// };
+	/// let bundle = impls::OfflineTxBundle::new(args, 100, String::new());
+	/// // Unsigned bundle can't be processed by the online wallet
+	/// let result = api_owner.finalize_offline_bundle(None, &bundle);
+	/// assert!(result.is_err());
+	/// ```
+
+	pub fn finalize_offline_bundle(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		bundle: &OfflineTxBundle,
+	) -> Result<OfflineTxBundle, Error> {
+		let bundle_err = |e: crate::impls::Error| ErrorKind::GenericError(format!("{}", e));
+		bundle
+			.check_stage(&[OfflineBundleStage::Signed, OfflineBundleStage::Finalized])
+			.map_err(bundle_err)?;
+		let sa = bundle
+			.args
+			.send_args
+			.clone()
+			.ok_or(ErrorKind::GenericError(
+				"Offline bundle doesn't have the recipient, send_args are not defined".to_string(),
+			))?;
+		let slate = bundle.get_slate().map_err(bundle_err)?;
+		let mut bundle = bundle.clone();
+
+		if bundle.stage == OfflineBundleStage::Finalized {
+			self.post_tx(keychain_mask, &slate.tx, sa.fluff)?;
+			return Ok(bundle);
+		}
+
+		let recipient = match &bundle.args.slatepack_recipient {
+			Some(r) => Some(r.tor_public_key()?),
+			None => None,
+		};
+		let slatepack_secret = {
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			let keychain = w.keychain(keychain_mask)?;
+			proofaddress::payment_proof_address_dalek_secret(&keychain, None)?
+		};
+		let sender = self.create_bundle_sender(&sa.method, &sa.dest, &sa.apisecret)?;
+		let other_wallet_version = sender.check_other_wallet_version(&sa.dest).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to get other wallet info, {}", e))
+		})?;
+		let mut response = sender
+			.send_tx(
+				&slate,
+				SlatePurpose::SendInitial,
+				&slatepack_secret,
+				recipient,
+				other_wallet_version,
+			)
+			.map_err(|e| {
+				ErrorKind::ClientCallback(format!(
+					"Unable to send slate {} with {}, {}",
+					slate.id, sa.method, e
+				))
+			})?;
+		// Restore back ttl, because it can be gone
+		response.ttl_cutoff_height = slate.ttl_cutoff_height.clone();
+		// Checking is sender didn't do any harm to slate
+		Slate::compare_slates_send(&slate, &response)?;
+		self.verify_slate_messages(keychain_mask, &response)?;
+
+		let (height, header_hash) = {
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			let (height, header_hash, _) = w.w2n_client().get_chain_tip()?;
+			(height, header_hash)
+		};
+		bundle.height = height;
+		bundle.header_hash = header_hash;
+		bundle
+			.set_slate(&response, OfflineBundleStage::Responded)
+			.map_err(bundle_err)?;
+		Ok(bundle)
 	}
 
-	/// Verifies all messages in the slate match their public keys.
+	// Sender for the bundle recipient. The response must come back synchronously, so only the
+	// direct methods are accepted.
+	fn create_bundle_sender(
+		&self,
+		method: &str,
+		dest: &str,
+		apisecret: &Option<String>,
+	) -> Result<Box<dyn SlateSender>, Error> {
+		match method {
+			"file" | "slatepack" | "self" => {
+				return Err(ErrorKind::GenericError(format!(
+					"Method '{}' is not supported for the offline bundle",
+					method
+				))
+				.into())
+			}
+			_ => (),
+		}
+		let tor_config = self.tor_config.lock().clone();
+		create_sender(method, dest, apisecret, tor_config).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to create a sender, {}", e)).into()
+		})
+	}
+
+	/// Cancels a transaction. This entails:
+	/// * Setting the transaction status to either `TxSentCancelled` or `TxReceivedCancelled`
+	/// * Deleting all change outputs or recipient outputs associated with the transaction
+	/// * Setting the status of all assocatied inputs from `Locked` to `Spent` so they can be
+	/// used in new transactions.
 	///
-	/// The optional messages themselves are part of the `participant_data` field within the slate.
-	/// Messages are signed with the same key used to sign for the paricipant's inputs, and can thus be
-	/// verified with the public key found in the `public_blind_excess` field. This function is a
-	/// simple helper to returns whether all signatures in the participant data match their public
-	/// keys.
+	/// Transactions can be cancelled by transaction log id or slate id (call with either set to
+	/// Some, not both)
 	///
 	/// # Arguments
 	///
 	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
 	/// being used.
-	/// * `slate` - The transaction [`Slate`](../grin_wallet_libwallet/slate/struct.Slate.html).
+	/// * `tx_id` - If present, cancel by the [`TxLogEntry`](../grin_wallet_libwallet/types/struct.TxLogEntry.html) id
+	/// for the transaction.
+	///
+	/// * `tx_slate_id` - If present, cancel by the Slate id.
 	///
 	/// # Returns
-	/// * `Ok(())` if successful and the signatures validate
+	/// * `Ok(())` if successful
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -1341,7 +2110,7 @@ where
 	///     max_outputs: 500,
 	///     num_change_outputs: 1,
 	///     selection_strategy_is_use_all: false,
-	///     message: Some("Just verify messages".to_owned()),
+	///     message: Some("Cancel this tx".to_owned()),
 	///     ..Default::default()
 	/// };
 	/// let result = api_owner.init_send_tx(
@@ -1356,126 +2125,1799 @@ where
 	///		// Lock our outputs if we're happy the slate was (or is being) sent
 	///		let res = api_owner.tx_lock_outputs(None, &slate, None, 0);
 	///		//
-	///		// Retrieve slate back from recipient
+	///		// We didn't get the slate back, or something else went wrong
 	///		//
-	///		let res = api_owner.verify_slate_messages(None, &slate);
+	///		let res = api_owner.cancel_tx(None, None, Some(slate.id.clone()));
 	/// }
 	/// ```
-	pub fn verify_slate_messages(
+
+	pub fn cancel_tx(
 		&self,
 		keychain_mask: Option<&SecretKey>,
-		slate: &Slate,
+		tx_id: Option<u32>,
+		tx_slate_id: Option<Uuid>,
 	) -> Result<(), Error> {
-		{
-			let mut w_lock = self.wallet_inst.lock();
-			let w = w_lock.lc_provider()?.wallet_inst()?;
-			// Test keychain mask, to keep API consistent
-			let _ = w.keychain(keychain_mask)?;
+		self.audit(
+			"cancel_tx",
+			format!("tx_id: {:?}, tx_slate_id: {:?}", tx_id, tx_slate_id),
+			|| {
+				let tx = {
+					let t = self.status_tx.lock();
+					t.clone()
+				};
+				owner::cancel_tx(
+					self.wallet_inst.clone(),
+					keychain_mask,
+					&tx,
+					tx_id,
+					tx_slate_id,
+				)
+			},
+		)
+	}
+
+	/// Sets the local label and memo of a transaction. The label and memo are private notes, they
+	/// are stored in the wallet DB only and are never sent to the other party, unlike the slate
+	/// messages. They are returned with the
+	/// [Transaction Log Entries](../grin_wallet_libwallet/types/struct.TxLogEntry.html) and
+	/// can be used as a filter in [`query_txs`](struct.Owner.html#method.query_txs).
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `args` - [`TxLabelArgs`](../grin_wallet_libwallet/api_impl/types/struct.TxLabelArgs.html),
+	/// the transaction Id, the label and the memo. `None` keeps the current value, an empty string
+	/// removes it.
+	///
+	/// # Returns
+	/// * Ok(()) if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the transaction
+	/// doesn't exist in the active account or the label or memo is too long.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_wallet_libwallet::TxLabelArgs;
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let args = TxLabelArgs {
+	///     tx_id: 1,
+	///     label: Some("exchange withdrawal".to_string()),
+	///     memo: None,
+	/// };
+	///
+	/// let result = api_owner.set_tx_label(None, &args);
+	/// ```
+
+	pub fn set_tx_label(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		args: &TxLabelArgs,
+	) -> Result<(), Error> {
+		self.audit("set_tx_label", format!("tx_id: {}", args.tx_id), || {
+			owner::set_tx_label(self.wallet_inst.clone(), keychain_mask, args)
+		})
+	}
+
+	/// Sets the number of confirmations when the transaction is settled. Some services need more
+	/// confirmations than the wallet uses to mark the transaction as confirmed, for example an
+	/// exchange might require 60 confirmations for the deposit. When the confirmed transaction
+	/// reaches its target, the updater moves it into `Settled` state and reports `tx_settled`
+	/// event to the webhooks and the event subscribers.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `tx_id` - Id of the transaction in the active account.
+	/// * `target` - Number of confirmations, `None` or 0 removes the target. The target that is
+	/// already reached settles the transaction immediately.
+	///
+	/// # Returns
+	/// * Ok(()) if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the transaction
+	/// doesn't exist in the active account or it is cancelled.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.set_tx_confirmation_target(None, 1, Some(60));
+	/// ```
+
+	pub fn set_tx_confirmation_target(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		tx_id: u32,
+		target: Option<u64>,
+	) -> Result<(), Error> {
+		self.audit(
+			"set_tx_confirmation_target",
+			format!("tx_id: {}, target: {:?}", tx_id, target),
+			|| {
+				owner::set_tx_confirmation_target(
+					self.wallet_inst.clone(),
+					keychain_mask,
+					tx_id,
+					target,
+				)
+			},
+		)
+	}
+
+	/// Imports the transaction history that was recorded outside of this wallet, for example by
+	/// the recovered mwc713 wallet or the GUI wallet export. The records are added to the active
+	/// account. A record with the same slate id or kernel as an existing transaction or another
+	/// record is skipped, so the same file can be imported again. Only the confirmed and the
+	/// cancelled transactions are imported, they have no outputs and don't change the balance.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `records` - Transaction records, see
+	/// [`parse_tx_import`](../grin_wallet_libwallet/tx_import/fn.parse_tx_import.html) to read
+	/// them from the CSV or Json file.
+	/// * `dry_run` - Only check the records, nothing is imported.
+	///
+	/// # Returns
+	/// * Ok with the [`TxImportReport`](../grin_wallet_libwallet/tx_import/struct.TxImportReport.html),
+	/// the ids of the imported transactions and the skipped records with the reason
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	/// use grin_wallet_libwallet::tx_import::{self, TxImportFormat};
+	/// use std::collections::HashMap;
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let csv = "tx_type,creation_ts,amount_credited\nTxReceived,2021-03-12,2000000000\n";
+	/// let records = tx_import::parse_tx_import(csv, &TxImportFormat::Csv, &HashMap::new());
+	/// if let Ok(records) = records {
+	///     let result = api_owner.import_txs(None, records, true);
+	/// }
+	/// ```
+
+	pub fn import_txs(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		records: Vec<TxImportRecord>,
+		dry_run: bool,
+	) -> Result<TxImportReport, Error> {
+		if dry_run {
+			return owner::import_txs(self.wallet_inst.clone(), keychain_mask, records, true);
 		}
-		owner::verify_slate_messages(slate)
+		self.audit("import_txs", format!("records: {}", records.len()), || {
+			owner::import_txs(self.wallet_inst.clone(), keychain_mask, records, false)
+		})
 	}
 
-	/// Scans the entire UTXO set from the node, identify which outputs belong to the given wallet
-	/// update the wallet state to be consistent with what's currently in the UTXO set.
+	/// Imports the data of another wallet with the same seed, like mwc713: the accounts, the
+	/// transactions with their outputs and the address book. The transactions get the new ids.
+	/// A transaction with the same slate id or kernel as an existing one, an existing output or
+	/// contact are skipped, so the migration can be repeated. The imported outputs are as the old
+	/// wallet recorded them, run [`scan`](struct.Owner.html#method.scan) to update them from the
+	/// chain.
 	///
-	/// This function can be used to repair wallet state, particularly by restoring outputs that may
-	/// be missing if the wallet owner has cancelled transactions locally that were then successfully
-	/// posted to the chain.
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `data` - The wallet data, see
+	/// [`read_mwc713_wallet`](../grin_wallet_impls/fn.read_mwc713_wallet.html) to read it from the
+	/// mwc713 data directory.
 	///
-	/// This operation scans the entire chain, and is expected to be time intensive. It is imperative
-	/// that no other processes should be trying to use the wallet at the same time this function is
-	/// running.
+	/// # Returns
+	/// * Ok with the [`WalletMigrationReport`](../grin_wallet_libwallet/migration/struct.WalletMigrationReport.html),
+	/// the numbers of the imported records and the skipped records with the reason
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	/// use grin_wallet_libwallet::migration::WalletMigrationData;
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.import_wallet_data(None, WalletMigrationData::default());
+	/// ```
+
+	pub fn import_wallet_data(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		data: WalletMigrationData,
+	) -> Result<WalletMigrationReport, Error> {
+		let details = format!(
+			"accounts: {}, outputs: {}, txs: {}, contacts: {}",
+			data.accounts.len(),
+			data.outputs.len(),
+			data.txs.len(),
+			data.contacts.len()
+		);
+		self.audit("import_wallet_data", details, || {
+			owner::import_wallet_data(self.wallet_inst.clone(), keychain_mask, data)
+		})
+	}
+
+	/// Freezes, unfreezes or tags an output of the active account. A frozen output is never picked
+	/// by the coin selection of [`init_send_tx`](struct.Owner.html#method.init_send_tx) unless it
+	/// is listed explicitly in the `outputs` of the send arguments. It is useful to reserve the
+	/// outputs for a planned swap or to keep a 'do not spend' buffer. The frozen outputs are still
+	/// counted in the wallet balance. The tags are local and can be used as a filter in
+	/// [`query_outputs`](struct.Owner.html#method.query_outputs).
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `args` - [`OutputTagArgs`](../grin_wallet_libwallet/api_impl/types/struct.OutputTagArgs.html),
+	/// the output commitment, the frozen flag and the tags. `None` keeps the current value.
+	///
+	/// # Returns
+	/// * Ok(()) if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the output
+	/// doesn't exist in the active account or the tag is too long.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_wallet_libwallet::OutputTagArgs;
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let args = OutputTagArgs {
+	///     commit: "0910c1752100733bae49e877286835aab76d5856ef8139b6c6e3f51798aa461b03".to_string(),
+	///     frozen: Some(true),
+	///     tags: Some(vec!["swap".to_string()]),
+	/// };
+	///
+	/// let result = api_owner.tag_output(None, &args);
+	/// ```
+
+	pub fn tag_output(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		args: &OutputTagArgs,
+	) -> Result<(), Error> {
+		self.audit("tag_output", format!("commit: {}", args.commit), || {
+			owner::tag_output(self.wallet_inst.clone(), keychain_mask, args)
+		})
+	}
+
+	/// Re-issues a stuck send with a higher fee. The unconfirmed transaction is cancelled and
+	/// rebuilt with the same inputs, amount and recipient, and the fee multiplied by
+	/// `fee_multiplier`. The original transaction is cancelled the same way as with
+	/// [`cancel_tx`](struct.Owner.html#method.cancel_tx), if the new transaction can't be built
+	/// the original one is restored.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `args` - [`ReissueTxArgs`](../grin_wallet_libwallet/api_impl/types/struct.ReissueTxArgs.html),
+	/// the transaction to reissue and the fee multiplier.
+	///
+	/// # Returns
+	/// * Ok with the new [`Slate`](../grin_wallet_libwallet/slate/struct.Slate.html) with locked
+	/// outputs. The slate needs to be sent to the recipient the same way as a regular send.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the transaction
+	/// is confirmed, its kernel is on the chain, or the inputs don't cover the higher fee.
+	/// Finalized transaction is refused unless `args.force` is set.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_wallet_libwallet::ReissueTxArgs;
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let args = ReissueTxArgs {
+	///     tx_id: 1,
+	///     fee_multiplier: 2.0,
+	///     target_slate_version: None,
+	///     slatepack_recipient: None,
+	///     force: false,
+	/// };
+	///
+	/// let result = api_owner.reissue_tx(None, &args);
+	///
+	/// if let Ok(slate) = result {
+	///     // Send the new slate to the recipient
+	///     // ...
+	/// }
+	/// ```
+
+	pub fn reissue_tx(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		args: &ReissueTxArgs,
+	) -> Result<Slate, Error> {
+		self.audit(
+			"reissue_tx",
+			format!(
+				"tx_id: {}, fee_multiplier: {}",
+				args.tx_id, args.fee_multiplier
+			),
+			|| {
+				let tx = {
+					let t = self.status_tx.lock();
+					t.clone()
+				};
+				owner::reissue_tx(
+					self.wallet_inst.clone(),
+					keychain_mask,
+					&tx,
+					args,
+					self.doctest_mode,
+				)
+			},
+		)
+	}
+
+	/// Retrieves the stored transaction associated with a TxLogEntry. Can be used even after the
+	/// transaction has completed.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `tx_log_entry` - A [`TxLogEntry`](../grin_wallet_libwallet/types/struct.TxLogEntry.html)
+	///
+	/// # Returns
+	/// * Ok with the stored  [`Transaction`](../grin_core/core/transaction/struct.Transaction.html)
+	/// if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let update_from_node = true;
+	/// let tx_id = None;
+	/// let tx_slate_id = None;
+	///
+	/// // Return all TxLogEntries
+	/// let result = api_owner.retrieve_txs(None, update_from_node, tx_id, tx_slate_id);
+	///
+	/// if let Ok((was_updated, tx_log_entries)) = result {
+	///     let stored_tx = api_owner.get_stored_tx(None, &tx_log_entries[0]).unwrap();
+	///     //...
+	/// }
+	/// ```
+
+	// TODO: Should be accepting an id, not an entire entry struct
+	pub fn get_stored_tx(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		tx_log_entry: &TxLogEntry,
+	) -> Result<Option<Transaction>, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		// Test keychain mask, to keep API consistent
+		let _ = w.keychain(keychain_mask)?;
+		owner::get_stored_tx(&**w, tx_log_entry)
+	}
+
+	/// Loads a stored transaction from a file
+	pub fn load_stored_tx(&self, file: &String) -> Result<Transaction, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::load_stored_tx(&**w, file)
+	}
+
+	/// Rebuilds the response slate of a received transaction. If the receiver lost the
+	/// response before sending it back, the same response can be sent to the sender again
+	/// instead of cancelling the transaction. Responses are saved by the wallet on receive.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `tx_slate_id` - The UUID of the received transaction
+	///
+	/// # Returns
+	/// * Ok with the response [`Slate`](../grin_wallet_libwallet/slate/struct.Slate.html)
+	/// if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the transaction
+	/// is not found, cancelled or confirmed, or its response was not saved.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let tx_slate_id = Uuid::parse_str("0436430c-2b02-624c-2032-570501212b00").unwrap();
+	///
+	/// let result = api_owner.retrieve_response_slate(None, &tx_slate_id);
+	///
+	/// if let Ok(slate) = result {
+	///     // Send the response back to the sender
+	///     // ...
+	/// }
+	/// ```
+
+	pub fn retrieve_response_slate(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		tx_slate_id: &Uuid,
+	) -> Result<Slate, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::retrieve_response_slate(&**w, keychain_mask, tx_slate_id)
+	}
+
+	/// Removes saved transaction files and swap trade files that are not needed any more.
+	/// Saved transactions that are not referenced by the transaction log are removed, and with
+	/// `finished_txs` also the ones of the confirmed and cancelled transactions. Swap files of
+	/// the deleted trades are removed as well. Files that are younger than `retention_days`
+	/// are never touched.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `args` - [`GcArgs`](../grin_wallet_libwallet/api_impl/types/struct.GcArgs.html), retention
+	/// policy. With `dry_run` the files are reported but not removed.
+	///
+	/// # Returns
+	/// * Ok with [`GcResult`](../grin_wallet_libwallet/api_impl/types/struct.GcResult.html) that
+	/// lists the removed files.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_wallet_libwallet::GcArgs;
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let args = GcArgs {
+	///     dry_run: true,
+	///     ..Default::default()
+	/// };
+	/// let result = api_owner.gc_files(None, &args);
+	///
+	/// if let Ok(result) = result {
+	///     println!("{} bytes can be freed", result.bytes);
+	/// }
+	/// ```
+
+	pub fn gc_files(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		args: &GcArgs,
+	) -> Result<GcResult, Error> {
+		owner::gc_files(self.wallet_inst.clone(), keychain_mask, args)
+	}
+
+	/// Exports the wallet unspent outputs anchored to the block at the given height, so the
+	/// balance can be reconciled against the chain data independently. Every output comes
+	/// with the commitment and value from the wallet and the block height and MMR index
+	/// reported by the node.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `height` - Snapshot height, the chain tip if None.
+	///
+	/// # Returns
+	/// * Ok with [`UtxoSnapshot`](../grin_wallet_libwallet/api_impl/types/struct.UtxoSnapshot.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Remarks
+	///
+	/// * Outputs of all accounts are exported.
+	/// * The node reports the current UTXO set, outputs that are spent after the snapshot height
+	/// are not included. Take the snapshot at a recent height.
+	/// * Wallet outputs that the node doesn't have are listed as `missing`. Run `scan` if the list
+	/// is not empty.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.utxo_snapshot(None, None);
+	///
+	/// if let Ok(snapshot) = result {
+	///     println!("{} at height {}", snapshot.total, snapshot.height);
+	/// }
+	/// ```
+
+	pub fn utxo_snapshot(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		height: Option<u64>,
+	) -> Result<UtxoSnapshot, Error> {
+		owner::utxo_snapshot(self.wallet_inst.clone(), keychain_mask, height)
+	}
+
+	/// Verifies all messages in the slate match their public keys.
+	///
+	/// The optional messages themselves are part of the `participant_data` field within the slate.
+	/// Messages are signed with the same key used to sign for the paricipant's inputs, and can thus be
+	/// verified with the public key found in the `public_blind_excess` field. This function is a
+	/// simple helper to returns whether all signatures in the participant data match their public
+	/// keys.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `slate` - The transaction [`Slate`](../grin_wallet_libwallet/slate/struct.Slate.html).
+	///
+	/// # Returns
+	/// * `Ok(())` if successful and the signatures validate
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let mut api_owner = Owner::new(wallet.clone(), None, None);
+	/// let args = InitTxArgs {
+	///     src_acct_name: None,
+	///     amount: 2_000_000_000,
+	///     minimum_confirmations: 10,
+	///     max_outputs: 500,
+	///     num_change_outputs: 1,
+	///     selection_strategy_is_use_all: false,
+	///     message: Some("Just verify messages".to_owned()),
+	///     ..Default::default()
+	/// };
+	/// let result = api_owner.init_send_tx(
+	/// 	None,
+	/// 	&args,
+	/// 	1,
+	/// );
+	///
+	/// if let Ok(slate) = result {
+	///		// Send slate somehow
+	///		// ...
+	///		// Lock our outputs if we're happy the slate was (or is being) sent
+	///		let res = api_owner.tx_lock_outputs(None, &slate, None, 0);
+	///		//
+	///		// Retrieve slate back from recipient
+	///		//
+	///		let res = api_owner.verify_slate_messages(None, &slate);
+	/// }
+	/// ```
+	pub fn verify_slate_messages(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		slate: &Slate,
+	) -> Result<(), Error> {
+		{
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			// Test keychain mask, to keep API consistent
+			let _ = w.keychain(keychain_mask)?;
+		}
+		owner::verify_slate_messages(slate)
+	}
+
+	/// Scans the entire UTXO set from the node, identify which outputs belong to the given wallet
+	/// update the wallet state to be consistent with what's currently in the UTXO set.
+	///
+	/// This function can be used to repair wallet state, particularly by restoring outputs that may
+	/// be missing if the wallet owner has cancelled transactions locally that were then successfully
+	/// posted to the chain.
+	///
+	/// This operation scans the entire chain, and is expected to be time intensive. It is imperative
+	/// that no other processes should be trying to use the wallet at the same time this function is
+	/// running.
+	///
+	/// When an output is found that doesn't exist in the wallet, a corresponding
+	/// [TxLogEntry](../grin_wallet_libwallet/types/struct.TxLogEntry.html) is created.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `start_height` - If provided, the height of the first block from which to start scanning.
+	/// The scan will start from block 1 if this is not provided.
+	/// * `delete_unconfirmed` - if `false`, the scan process will be non-destructive, and
+	/// mostly limited to restoring missing outputs. It will leave unconfirmed transaction logs entries
+	/// and unconfirmed outputs intact. If `true`, the process will unlock all locked outputs,
+	/// restore all missing outputs, and mark any outputs that have been marked 'Spent' but are still
+	/// in the UTXO set as 'Unspent' (as can happen during a fork). It will also attempt to cancel any
+	/// transaction log entries associated with any locked outputs or outputs incorrectly marked 'Spent'.
+	/// Note this completely removes all outstanding transactions, so users should be very aware what
+	/// will happen if this flag is set. Note that if transactions/outputs are removed that later
+	/// confirm on the chain, another call to this function will restore them.
+	///
+	/// # Returns
+	/// * `Ok(())` if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let mut api_owner = Owner::new(wallet.clone(), None, None);
+	/// let result = api_owner.scan(
+	///     None,
+	///     Some(20000),
+	///     false,
+	/// );
+	///
+	/// if let Ok(_) = result {
+	///     // Wallet outputs should be consistent with what's on chain
+	///     // ...
+	/// }
+	/// ```
+
+	pub fn scan(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		start_height: Option<u64>,
+		delete_unconfirmed: bool,
+	) -> Result<(), Error> {
+		let tx = {
+			let t = self.status_tx.lock();
+			t.clone()
+		};
+		self.scan_with_status(keychain_mask, start_height, delete_unconfirmed, &tx)
+	}
+
+	// Scan reporting the progress into the given channel instead of the updater one
+	pub(crate) fn scan_with_status(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		start_height: Option<u64>,
+		delete_unconfirmed: bool,
+		status_tx: &Option<Sender<StatusMessage>>,
+	) -> Result<(), Error> {
+		self.audit(
+			"scan",
+			format!(
+				"start_height: {:?}, delete_unconfirmed: {}",
+				start_height, delete_unconfirmed
+			),
+			|| {
+				owner::scan(
+					self.wallet_inst.clone(),
+					keychain_mask,
+					start_height,
+					delete_unconfirmed,
+					status_tx,
+					true,
+					false,
+				)
+			},
+		)
+	}
+
+	/// Continue the [`scan`](struct.Owner.html#method.scan) that was interrupted. The full scan
+	/// periodically saves a checkpoint with the processed outputs into the wallet DB, so the
+	/// scan is resumed from the last checkpoint instead of starting over. If there is no
+	/// valid checkpoint (for example because of a reorg), it is the same as a regular scan.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `start_height` - If there is no checkpoint, the height of the first block from which to
+	/// start scanning. Otherwise the start height of the interrupted scan is used.
+	/// * `delete_unconfirmed` - same as for [`scan`](struct.Owner.html#method.scan)
+	///
+	/// # Returns
+	/// * `Ok(())` if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let mut api_owner = Owner::new(wallet.clone(), None, None);
+	/// let result = api_owner.scan_resume(
+	///     None,
+	///     Some(20000),
+	///     false,
+	/// );
+	///
+	/// if let Ok(_) = result {
+	///     // Wallet outputs should be consistent with what's on chain
+	///     // ...
+	/// }
+	/// ```
+
+	pub fn scan_resume(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		start_height: Option<u64>,
+		delete_unconfirmed: bool,
+	) -> Result<(), Error> {
+		let tx = {
+			let t = self.status_tx.lock();
+			t.clone()
+		};
+		owner::scan(
+			self.wallet_inst.clone(),
+			keychain_mask,
+			start_height,
+			delete_unconfirmed,
+			&tx,
+			true,
+			true,
+		)
+	}
+
+	/// Checks the wallet against the chain and the swap storage and returns a report of the
+	/// problems by category. Unlike [`scan`](struct.Owner.html#method.scan) with `delete_unconfirmed`,
+	/// only the problems of the selected categories are repaired.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `start_height` - If provided, the height of the first block from which to check the chain
+	/// outputs. The check starts from block 1 if this is not provided.
+	/// * `fix` - Categories of the problems to repair. Empty list - report only.
+	///
+	/// # Returns
+	/// * Ok with the [`CheckReport`](../grin_wallet_libwallet/internal/check/struct.CheckReport.html).
+	/// `fixed` lists the categories that were repaired.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let result = api_owner.check(None, None, vec![]);
+	///
+	/// if let Ok(report) = result {
+	///     println!("{} dangling transactions", report.dangling_txs.len());
+	/// }
+	/// ```
+
+	pub fn check(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		start_height: Option<u64>,
+		fix: Vec<CheckCategory>,
+	) -> Result<CheckReport, Error> {
+		let tx = {
+			let t = self.status_tx.lock();
+			t.clone()
+		};
+		let run = || {
+			owner::check(
+				self.wallet_inst.clone(),
+				keychain_mask,
+				start_height,
+				&fix,
+				&tx,
+			)
+		};
+		if fix.is_empty() {
+			return run();
+		}
+		self.audit(
+			"check",
+			format!(
+				"start_height: {:?}, fix: {}",
+				start_height,
+				fix.iter()
+					.map(|c| c.to_string())
+					.collect::<Vec<String>>()
+					.join(",")
+			),
+			run,
+		)
+	}
+
+	/// Dump wallet data (outputs,transactions) into the logs
+	pub fn dump_wallet_data(&self, file_name: Option<String>) -> Result<(), Error> {
+		let tx = {
+			let t = self.status_tx.lock();
+			t.clone()
+		};
+
+		owner::dump_wallet_data(self.wallet_inst.clone(), &tx.unwrap(), file_name)
+	}
+
+	/// Retrieves the last known height known by the wallet. This is determined as follows:
+	/// * If the wallet can successfully contact its configured node, the reported node
+	/// height is returned, and the `updated_from_node` field in the response is `true`
+	/// * If the wallet cannot contact the node, this function returns the maximum height
+	/// of all outputs contained within the wallet, and the `updated_from_node` fields
+	/// in the response is set to false.
+	///
+	/// Clients should generally ensure the `updated_from_node` field is returned as
+	/// `true` before assuming the height for any operation.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	///
+	/// # Returns
+	/// * Ok with a  [`NodeHeightResult`](../grin_wallet_libwallet/types/struct.NodeHeightResult.html)
+	/// if successful. If the height result was obtained from the configured node,
+	/// `updated_from_node` will be set to `true`
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let result = api_owner.node_height(None);
+	///
+	/// if let Ok(node_height_result) = result {
+	///     if node_height_result.updated_from_node {
+	///          //we can assume node_height_result.height is relatively safe to use
+	///
+	///     }
+	///     //...
+	/// }
+	/// ```
+
+	pub fn node_height(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+	) -> Result<NodeHeightResult, Error> {
+		{
+			let mut w_lock = self.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			// Test keychain mask, to keep API consistent
+			let _ = w.keychain(keychain_mask)?;
+		}
+		let mut res = owner::node_height(self.wallet_inst.clone(), keychain_mask)?;
+		if self.doctest_mode {
+			// return a consistent hash for doctest
+			res.header_hash =
+				"d4b3d3c40695afd8c7760f8fc423565f7d41310b7a4e1c4a4a7950a66f16240d".to_owned();
+		}
+		Ok(res)
+	}
+
+	// LIFECYCLE FUNCTIONS
+
+	/// Retrieve the top-level directory for the wallet. This directory should contain the
+	/// `mwc-wallet.toml` file and the `wallet_data` directory that contains the wallet
+	/// seed + data files. Future versions of the wallet API will support multiple wallets
+	/// within the top level directory.
+	///
+	/// The top level directory defaults to (in order of precedence):
+	///
+	/// 1) The current directory, from which `mwc-wallet` or the main process was run, if it
+	/// contains a `mwc-wallet.toml` file.
+	/// 2) ~/.grin/<chaintype>/ otherwise
+	///
+	/// # Arguments
+	///
+	/// * None
+	///
+	/// # Returns
+	/// * Ok with a String value representing the full path to the top level wallet dierctory
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let result = api_owner.get_top_level_directory();
+	///
+	/// if let Ok(dir) = result {
+	///     println!("Top level directory is: {}", dir);
+	///     //...
+	/// }
+	/// ```
+
+	pub fn get_top_level_directory(&self) -> Result<String, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let lc = w_lock.lc_provider()?;
+		if self.doctest_mode {
+			Ok("/doctest/dir".to_owned())
+		} else {
+			lc.get_top_level_directory()
+		}
+	}
+
+	/// Set the top-level directory for the wallet. This directory can be empty, and will be created
+	/// during a subsequent calls to [`create_config`](struct.Owner.html#method.create_config)
+	///
+	/// Set [`get_top_level_directory`](struct.Owner.html#method.get_top_level_directory) for a
+	/// description of the top level directory and default paths.
+	///
+	/// # Arguments
+	///
+	/// * `dir`: The new top-level directory path (either relative to current directory or
+	/// absolute.
+	///
+	/// # Returns
+	/// * Ok if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let dir = "path/to/wallet/dir";
+	///
+	/// # let dir = tempdir().map_err(|e| format!("{:#?}", e)).unwrap();
+	/// # let dir = dir
+	/// #   .path()
+	/// #   .to_str()
+	/// #   .ok_or("Failed to convert tmpdir path to string.".to_owned())
+	/// #   .unwrap();
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let result = api_owner.set_top_level_directory(dir);
+	///
+	/// if let Ok(dir) = result {
+	///    //...
+	/// }
+	/// ```
+
+	pub fn set_top_level_directory(&self, dir: &str) -> Result<(), Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let lc = w_lock.lc_provider()?;
+		lc.set_top_level_directory(dir)
+	}
+
+	/// Create a `mwc-wallet.toml` configuration file in the top-level directory for the
+	/// specified chain type.
+	/// A custom [`WalletConfig`](../grin_wallet_config/types/struct.WalletConfig.html)
+	/// and/or grin `LoggingConfig` may optionally be provided, otherwise defaults will be used.
+	///
+	/// Paths in the configuration file will be updated to reflect the top level directory, so
+	/// path-related values in the optional configuration structs will be ignored.
+	///
+	/// # Arguments
+	///
+	/// * `chain_type`: The chain type to use in creation of the configuration file. This can be
+	///     * `AutomatedTesting`
+	///     * `UserTesting`
+	///     * `Floonet`
+	///     * `Mainnet`
+	///
+	/// # Returns
+	/// * Ok if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_core::global::ChainTypes;
+	///
+	/// let dir = "path/to/wallet/dir";
+	///
+	/// # let dir = tempdir().map_err(|e| format!("{:#?}", e)).unwrap();
+	/// # let dir = dir
+	/// #   .path()
+	/// #   .to_str()
+	/// #   .ok_or("Failed to convert tmpdir path to string.".to_owned())
+	/// #   .unwrap();
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let _ = api_owner.set_top_level_directory(dir);
+	///
+	/// let result = api_owner.create_config(&ChainTypes::Mainnet, None, None, None, None );
+	///
+	/// if let Ok(_) = result {
+	///    //...
+	/// }
+	/// ```
+
+	pub fn create_config(
+		&self,
+		chain_type: &global::ChainTypes,
+		wallet_config: Option<WalletConfig>,
+		logging_config: Option<LoggingConfig>,
+		tor_config: Option<TorConfig>,
+		mqs_config: Option<MQSConfig>,
+	) -> Result<(), Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let lc = w_lock.lc_provider()?;
+		lc.create_config(
+			chain_type,
+			"mwc-wallet.toml",
+			wallet_config,
+			logging_config,
+			tor_config,
+			mqs_config,
+		)
+	}
+
+	/// Creates a new wallet seed and empty wallet database in the `wallet_data` directory of
+	/// the top level directory.
+	///
+	/// Paths in the configuration file will be updated to reflect the top level directory, so
+	/// path-related values in the optional configuration structs will be ignored.
+	///
+	/// The wallet files must not already exist, and ~The `mwc-wallet.toml` file must exist
+	/// in the top level directory (can be created via a call to
+	/// [`create_config`](struct.Owner.html#method.create_config))
+	///
+	/// # Arguments
+	///
+	/// * `name`: Reserved for future use, use `None` for the time being.
+	/// * `mnemonic`: If present, restore the wallet seed from the given mnemonic instead of creating
+	/// a new random seed.
+	/// * `mnemonic_length`: Desired length of mnemonic in bytes (16 or 32, either 12 or 24 words).
+	/// Use 0 if mnemonic isn't being used.
+	/// * `password`: The password used to encrypt/decrypt the `wallet.seed` file
+	///
+	/// # Returns
+	/// * Ok if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_core::global::ChainTypes;
+	///
+	/// // note that the WalletInst struct does not necessarily need to contain an
+	/// // instantiated wallet
+	///
+	/// let dir = "path/to/wallet/dir";
+	///
+	/// # let dir = tempdir().map_err(|e| format!("{:#?}", e)).unwrap();
+	/// # let dir = dir
+	/// #   .path()
+	/// #   .to_str()
+	/// #   .ok_or("Failed to convert tmpdir path to string.".to_owned())
+	/// #   .unwrap();
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let _ = api_owner.set_top_level_directory(dir);
+	///
+	/// // Create configuration
+	/// let result = api_owner.create_config(&ChainTypes::Mainnet,None,  None, None, None);
+	///
+	///	// create new wallet wirh random seed
+	///	let pw = ZeroingString::from("my_password");
+	/// let result = api_owner.create_wallet(None, None, 0, pw, None);
+	///
+	/// if let Ok(r) = result {
+	///     //...
+	/// }
+	/// ```
+
+	pub fn create_wallet(
+		&self,
+		name: Option<&str>,
+		mnemonic: Option<ZeroingString>,
+		mnemonic_length: u32,
+		password: ZeroingString,
+		wallet_data_dir: Option<&str>,
+	) -> Result<(), Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let lc = w_lock.lc_provider()?;
+		lc.create_wallet(
+			name,
+			mnemonic,
+			mnemonic_length as usize,
+			password,
+			self.doctest_mode,
+			wallet_data_dir,
+		)
+	}
+
+	/// `Opens` a wallet, populating the internal keychain with the encrypted seed, and optionally
+	/// returning a `keychain_mask` token to the caller to provide in all future calls.
+	/// If using a mask, the seed will be stored in-memory XORed against the `keychain_mask`, and
+	/// will not be useable if the mask is not provided.
+	///
+	/// # Arguments
+	///
+	/// * `name`: Reserved for future use, use `None` for the time being.
+	/// * `password`: The password to use to open the wallet
+	/// a new random seed.
+	/// * `use_mask`: Whether to create and return a mask which much be provided in all future
+	/// API calls.
+	///
+	/// # Returns
+	/// * Ok if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_core::global::ChainTypes;
+	///
+	/// // note that the WalletInst struct does not necessarily need to contain an
+	/// // instantiated wallet
+	/// let dir = "path/to/wallet/dir";
+	///
+	/// # let dir = tempdir().map_err(|e| format!("{:#?}", e)).unwrap();
+	/// # let dir = dir
+	/// #   .path()
+	/// #   .to_str()
+	/// #   .ok_or("Failed to convert tmpdir path to string.".to_owned())
+	/// #   .unwrap();
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let _ = api_owner.set_top_level_directory(dir);
+	///
+	/// // Create configuration
+	/// let result = api_owner.create_config(&ChainTypes::Mainnet, None,  None, None, None);
+	///
+	///	// create new wallet wirh random seed
+	///	let pw = ZeroingString::from("my_password");
+	/// let _ = api_owner.create_wallet(None, None, 0, pw.clone(), None);
+	///
+	/// let result = api_owner.open_wallet(None, pw, true, None);
+	///
+	/// if let Ok(m) = result {
+	///     // use this mask in all subsequent calls
+	///     let mask = m;
+	/// }
+	/// ```
+
+	pub fn open_wallet(
+		&self,
+		name: Option<&str>,
+		password: ZeroingString,
+		use_mask: bool,
+		wallet_data_dir: Option<&str>,
+	) -> Result<Option<SecretKey>, Error> {
+		// just return a representative string for doctest mode
+		if self.doctest_mode {
+			return Ok(Some(SecretKey::from_slice(
+				&from_hex("d096b3cb75986b3b13f80b8f5243a9edf0af4c74ac37578c5a12cfb5b59b1868")
+					.unwrap(),
+			)?));
+		}
+		let mut w_lock = self.wallet_inst.lock();
+		let lc = w_lock.lc_provider()?;
+		lc.open_wallet(name, password, use_mask, self.doctest_mode, wallet_data_dir)
+	}
+
+	/// `Close` a wallet, removing the master seed from memory.
+	///
+	/// # Arguments
+	///
+	/// * `name`: Reserved for future use, use `None` for the time being.
+	///
+	/// # Returns
+	/// * Ok if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_core::global::ChainTypes;
+	///
+	/// // Set up as above
+	/// # let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let res = api_owner.close_wallet(None);
+	///
+	/// if let Ok(_) = res {
+	///     // ...
+	/// }
+	/// ```
+
+	pub fn close_wallet(&self, name: Option<&str>) -> Result<(), Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let lc = w_lock.lc_provider()?;
+		lc.close_wallet(name)
+	}
+
+	/// Return the BIP39 mnemonic for the given wallet. This function will decrypt
+	/// the wallet's seed file with the given password, and thus does not need the
+	/// wallet to be open.
+	///
+	/// # Arguments
+	///
+	/// * `name`: Reserved for future use, use `None` for the time being.
+	/// * `password`: The password used to encrypt the seed file.
+	///
+	/// # Returns
+	/// * Ok(BIP-39 mneminc) if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_core::global::ChainTypes;
+	///
+	/// // Set up as above
+	/// # let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	///	let pw = ZeroingString::from("my_password");
+	/// let res = api_owner.get_mnemonic(None, pw, None);
+	///
+	/// if let Ok(mne) = res {
+	///     // ...
+	/// }
+	/// ```
+	pub fn get_mnemonic(
+		&self,
+		name: Option<&str>,
+		password: ZeroingString,
+		wallet_data_dir: Option<&str>,
+	) -> Result<ZeroingString, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let lc = w_lock.lc_provider()?;
+		lc.get_mnemonic(name, password, wallet_data_dir)
+	}
+
+	/// Returns the recovery phrase of the receive-only wallet that belongs to this wallet.
+	/// The receive-only wallet seed is a one way hash of this wallet root key, so the listener
+	/// can run on an always online box without the seed of this wallet. Funds received there
+	/// can be moved into this wallet with `sweep-seed`.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	///
+	/// # Returns
+	/// * Ok(BIP-39 mneminc) of the receive-only wallet if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let res = api_owner.get_receive_wallet_phrase(None);
+	///
+	/// if let Ok(mne) = res {
+	///     // ...
+	/// }
+	/// ```
+	pub fn get_receive_wallet_phrase(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+	) -> Result<ZeroingString, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::get_receive_wallet_phrase(&mut **w, keychain_mask)
+	}
+
+	/// Changes a wallet's password, meaning the old seed file is decrypted with the old password,
+	/// and a new seed file is created with the same mnemonic and encrypted with the new password.
+	///
+	/// This function temporarily backs up the old seed file until a test-decryption of the new
+	/// file is confirmed to contain the same seed as the original seed file, at which point the
+	/// backup is deleted. If this operation fails for an unknown reason, the backup file will still
+	/// exist in the wallet's data directory encrypted with the old password.
+	///
+	/// # Arguments
+	///
+	/// * `name`: Reserved for future use, use `None` for the time being.
+	/// * `old`: The password used to encrypt the existing seed file (i.e. old password)
+	/// * `new`: The password to be used to encrypt the new seed file
+	///
+	/// # Returns
+	/// * Ok(()) if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_core::global::ChainTypes;
+	///
+	/// // Set up as above
+	/// # let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	///	let old = ZeroingString::from("my_password");
+	///	let new = ZeroingString::from("new_password");
+	/// let res = api_owner.change_password(None, old, new, None);
+	///
+	/// if let Ok(mne) = res {
+	///     // ...
+	/// }
+	/// ```
+	pub fn change_password(
+		&self,
+		name: Option<&str>,
+		old: ZeroingString,
+		new: ZeroingString,
+		wallet_data_dir: Option<&str>,
+	) -> Result<(), Error> {
+		self.audit("change_password", format!("name: {:?}", name), || {
+			let mut w_lock = self.wallet_inst.lock();
+			let lc = w_lock.lc_provider()?;
+			lc.change_password(name, old, new, wallet_data_dir)
+		})
+	}
+
+	/// Sets the decoy password of the wallet. The decoy password opens a separate wallet that is
+	/// derived from the same seed with a different root, it can be revealed instead of the real
+	/// wallet. The decoy wallet keeps its data apart from the wallet data.
+	///
+	/// # Arguments
+	///
+	/// * `name`: Reserved for future use, use `None` for the time being.
+	/// * `password`: The wallet password
+	/// * `decoy_password`: The decoy password, `None` removes the decoy
+	///
+	/// # Returns
+	/// * Ok(()) if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_core::global::ChainTypes;
+	///
+	/// // Set up as above
+	/// # let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	///	let password = ZeroingString::from("my_password");
+	///	let decoy = ZeroingString::from("decoy_password");
+	/// let res = api_owner.set_decoy_password(None, password, Some(decoy), None);
+	///
+	/// if let Ok(_) = res {
+	///     // ...
+	/// }
+	/// ```
+	pub fn set_decoy_password(
+		&self,
+		name: Option<&str>,
+		password: ZeroingString,
+		decoy_password: Option<ZeroingString>,
+		wallet_data_dir: Option<&str>,
+	) -> Result<(), Error> {
+		// Not in the audit log, the log would show that the decoy exists
+		let mut w_lock = self.wallet_inst.lock();
+		let lc = w_lock.lc_provider()?;
+		lc.set_decoy_password(name, password, decoy_password, wallet_data_dir)
+	}
+
+	/// Deletes a wallet, removing the config file, seed file and all data files.
+	/// Obviously, use with extreme caution and plenty of user warning
+	///
+	/// Highly recommended that the wallet be explicitly closed first via the `close_wallet`
+	/// function.
+	///
+	/// # Arguments
+	///
+	/// * `name`: Reserved for future use, use `None` for the time being.
+	///
+	/// # Returns
+	/// * Ok if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_core::global::ChainTypes;
+	///
+	/// // Set up as above
+	/// # let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let res = api_owner.delete_wallet(None);
+	///
+	/// if let Ok(_) = res {
+	///     // ...
+	/// }
+	/// ```
+
+	pub fn delete_wallet(&self, name: Option<&str>) -> Result<(), Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let lc = w_lock.lc_provider()?;
+		lc.delete_wallet(name)
+	}
+
+	/// Starts a background wallet update thread, which performs the wallet update process
+	/// automatically at the frequency specified.
+	///
+	/// The updater process is as follows:
+	///
+	/// * Reconcile the wallet outputs against the node's current UTXO set, confirming
+	/// transactions if needs be.
+	/// * Look up transactions by kernel in cases where it's necessary (for instance, when
+	/// there are no change outputs for a transaction and transaction status can't be
+	/// inferred from the output state.
+	/// * Incrementally perform a scan of the UTXO set, correcting outputs and transactions
+	/// where their local state differs from what's on-chain. The wallet stores the last
+	/// position scanned, and will scan back 100 blocks worth of UTXOs on each update, to
+	/// correct any differences due to forks or otherwise.
+	///
+	/// Note that an update process can take a long time, particularly when the entire
+	/// UTXO set is being scanned for correctness. The wallet status can be determined by
+	/// calling the [`get_updater_messages`](struct.Owner.html#method.get_updater_messages).
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `frequency`: The frequency at which to call the update process. Note this is
+	/// time elapsed since the last successful update process. If calling via the JSON-RPC
+	/// api, this represents milliseconds.
+	///
+	/// # Returns
+	/// * Ok if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_core::global::ChainTypes;
+	///
+	/// use std::time::Duration;
+	///
+	/// // Set up as above
+	/// # let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let res = api_owner.start_updater(None, Duration::from_secs(60));
+	///
+	/// if let Ok(_) = res {
+	///   // ...
+	/// }
+	/// ```
+
+	pub fn start_updater(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		frequency: Duration,
+	) -> Result<(), Error> {
+		let updater_inner = self.updater.clone();
+		let tx_inner = {
+			let t = self.status_tx.lock();
+			t.clone()
+		};
+		let keychain_mask = match keychain_mask {
+			Some(m) => Some(m.clone()),
+			None => None,
+		};
+		let _ = thread::Builder::new()
+			.name("wallet-updater".to_string())
+			.spawn(move || {
+				let u = updater_inner.lock();
+				if let Err(e) = u.run(frequency, keychain_mask, &tx_inner) {
+					error!("Wallet state updater failed with error: {}", e);
+				}
+			})?;
+		Ok(())
+	}
+
+	/// Stops the background update thread. If the updater is currently updating, the
+	/// thread will stop after the next update
+	///
+	/// # Arguments
+	///
+	/// * None
+	///
+	/// # Returns
+	/// * Ok if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// use grin_core::global::ChainTypes;
+	///
+	/// use std::time::Duration;
+	///
+	/// // Set up as above
+	/// # let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let res = api_owner.start_updater(None, Duration::from_secs(60));
+	///
+	/// if let Ok(_) = res {
+	///   // ...
+	/// }
+	///
+	/// let res = api_owner.stop_updater();
+	/// ```
+
+	pub fn stop_updater(&self) -> Result<(), Error> {
+		self.updater_running.store(false, Ordering::Relaxed);
+		Ok(())
+	}
+
+	/// Puts the wallet into maintenance mode without shutting down the process. While paused,
+	/// listeners are responding with 'retry later' error, the state changing calls of this API
+	/// (send, cancel, scan, etc.) are returning `WalletPaused` error, the refresh from the node
+	/// is skipped and background jobs (updater thread, auto swap) are skipping their work. The
+	/// call returns after the wallet operations that are currently in progress are finished,
+	/// so the data directory can be backed up right after.
+	///
+	/// # Arguments
+	///
+	/// * None
+	///
+	/// # Returns
+	/// * Ok if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let res = api_owner.pause();
+	///
+	/// if let Ok(_) = res {
+	///   // backup the wallet data directory
+	///   let res = api_owner.resume();
+	/// }
+	/// ```
+
+	pub fn pause(&self) -> Result<(), Error> {
+		// Returns after the operations in progress are finished
+		owner::set_wallet_paused(true);
+		Ok(())
+	}
+
+	/// Exits from the maintenance mode that was started with [`pause`](struct.Owner.html#method.pause).
+	/// Listeners and background jobs continue to work normally.
+	///
+	/// # Arguments
+	///
+	/// * None
+	///
+	/// # Returns
+	/// * Ok if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`pause`](struct.Owner.html#method.pause) method above.
+
+	pub fn resume(&self) -> Result<(), Error> {
+		owner::set_wallet_paused(false);
+		Ok(())
+	}
+
+	/// Returns true if the wallet is in maintenance mode.
+	///
+	/// # Arguments
+	///
+	/// * None
+	///
+	/// # Returns
+	/// * true if wallet is paused by [`pause`](struct.Owner.html#method.pause).
+
+	pub fn is_paused(&self) -> bool {
+		owner::is_wallet_paused()
+	}
+
+	/// Re-reads the wallet configuration file and applies the changes that are safe to apply
+	/// without restart: log levels (can't be raised above the level at the start), webhook
+	/// notifications, base fee, slate limits, transaction hooks, auto cancel policy and the
+	/// node URL. Listeners keep running. The same reload is done on SIGHUP.
+	///
+	/// # Arguments
+	///
+	/// * None
+	///
+	/// # Returns
+	/// * Ok with [`ConfigReloadStatus`](struct.ConfigReloadStatus.html) that lists the changed
+	/// settings that were applied and the ones that need the wallet restart.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered,
+	/// for example the config file can't be parsed. In this case nothing is changed.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let res = api_owner.reload_config();
+	///
+	/// if let Ok(status) = res {
+	///   for s in status.restart_required {
+	///     println!("{} will be applied after restart", s);
+	///   }
+	/// }
+	/// ```
+
+	pub fn reload_config(&self) -> Result<ConfigReloadStatus, Error> {
+		reload_config(self.wallet_inst.clone())
+	}
+
+	/// Sets the policy for the automatic cancellation of the transactions that were never
+	/// confirmed. The policy is applied on every wallet update, including the updater thread (see
+	/// [`start_updater`](struct.Owner.html#method.start_updater)). Cancelled transactions unlock
+	/// their outputs the same way as [`cancel_tx`](struct.Owner.html#method.cancel_tx) does.
+	///
+	/// # Arguments
+	///
+	/// * `policy` - [`AutoCancelPolicy`](../grin_wallet_libwallet/api_impl/types/struct.AutoCancelPolicy.html)
+	/// with the TTL expiry flag, optional max age of the transaction in hours and optional
+	/// expiration of the unfinalized receives in days.
+	///
+	/// # Returns
+	/// * Ok if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	/// use grin_wallet_libwallet::AutoCancelPolicy;
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let res = api_owner.set_auto_cancel_policy(AutoCancelPolicy {
+	/// 	cancel_on_ttl: true,
+	/// 	max_age_hours: Some(48),
+	/// 	receive_expire_days: Some(7),
+	/// });
+	///
+	/// if let Ok(_) = res {
+	///   let policy = api_owner.get_auto_cancel_policy().unwrap();
+	///   assert_eq!(policy.max_age_hours, Some(48));
+	/// }
+	/// ```
+
+	pub fn set_auto_cancel_policy(&self, policy: AutoCancelPolicy) -> Result<(), Error> {
+		owner::set_auto_cancel_policy(policy);
+		Ok(())
+	}
+
+	/// Returns the current policy for the automatic cancellation of the expired transactions.
+	///
+	/// # Arguments
+	///
+	/// * None
+	///
+	/// # Returns
+	/// * Ok with [`AutoCancelPolicy`](../grin_wallet_libwallet/api_impl/types/struct.AutoCancelPolicy.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`set_auto_cancel_policy`](struct.Owner.html#method.set_auto_cancel_policy) method above.
+
+	pub fn get_auto_cancel_policy(&self) -> Result<AutoCancelPolicy, Error> {
+		Ok(owner::get_auto_cancel_policy())
+	}
+
+	/// Cancels the received transactions that the sender didn't finalize within
+	/// `receive_expire_days` of the [`AutoCancelPolicy`](../grin_wallet_libwallet/api_impl/types/struct.AutoCancelPolicy.html).
+	/// Expired receives are already excluded from the amount awaiting finalization, this call
+	/// removes them from the transaction log and deletes their unconfirmed outputs, the same
+	/// way as [`cancel_tx`](struct.Owner.html#method.cancel_tx) does.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	///
+	/// # Returns
+	/// * Ok with Ids of the cancelled transactions. Empty if the expiration is not configured.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`set_auto_cancel_policy`](struct.Owner.html#method.set_auto_cancel_policy) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.cancel_expired_receives(None);
+	///
+	/// if let Ok(tx_ids) = result {
+	///   // ...
+	/// }
+	/// ```
+
+	pub fn cancel_expired_receives(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+	) -> Result<Vec<u32>, Error> {
+		self.audit("cancel_expired_receives", String::new(), || {
+			let tx = {
+				let t = self.status_tx.lock();
+				t.clone()
+			};
+			owner::cancel_expired_receives(self.wallet_inst.clone(), keychain_mask, &tx)
+		})
+	}
+
+	/// Returns the progress of the running chain scan. The scan is started by
+	/// [`scan`](struct.Owner.html#method.scan) or by the wallet update, including the updater
+	/// thread, so the clients can poll this method to display the progress.
+	///
+	/// # Arguments
+	///
+	/// * None
+	///
+	/// # Returns
+	/// * Ok with [`ScanProgress`](../grin_wallet_libwallet/api_impl/types/struct.ScanProgress.html).
+	/// `running` is false if there is no scan in progress, then the rest of the fields describe
+	/// the last scan.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
 	///
-	/// When an output is found that doesn't exist in the wallet, a corresponding
-	/// [TxLogEntry](../grin_wallet_libwallet/types/struct.TxLogEntry.html) is created.
+	/// let progress = api_owner.get_scan_progress().unwrap();
+	/// if progress.running {
+	///   println!("Scanning {}%, height {}", progress.percent, progress.height);
+	/// }
+	/// ```
+
+	pub fn get_scan_progress(&self) -> Result<ScanProgress, Error> {
+		Ok(owner::get_scan_progress())
+	}
+
+	/// Returns the request counters of the owner and foreign HTTP listeners, including the
+	/// requests that were rejected by the rate limit or by the bound of the concurrent requests.
 	///
 	/// # Arguments
 	///
-	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
-	/// being used.
-	/// * `start_height` - If provided, the height of the first block from which to start scanning.
-	/// The scan will start from block 1 if this is not provided.
-	/// * `delete_unconfirmed` - if `false`, the scan process will be non-destructive, and
-	/// mostly limited to restoring missing outputs. It will leave unconfirmed transaction logs entries
-	/// and unconfirmed outputs intact. If `true`, the process will unlock all locked outputs,
-	/// restore all missing outputs, and mark any outputs that have been marked 'Spent' but are still
-	/// in the UTXO set as 'Unspent' (as can happen during a fork). It will also attempt to cancel any
-	/// transaction log entries associated with any locked outputs or outputs incorrectly marked 'Spent'.
-	/// Note this completely removes all outstanding transactions, so users should be very aware what
-	/// will happen if this flag is set. Note that if transactions/outputs are removed that later
-	/// confirm on the chain, another call to this function will restore them.
+	/// * None
 	///
 	/// # Returns
-	/// * `Ok(())` if successful
+	/// * Ok with the list of [`ListenerMetrics`](../grin_wallet_libwallet/listener_limits/struct.ListenerMetrics.html),
+	/// one for every listener. The counters are kept since the wallet start.
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
-
+	///
 	/// # Example
 	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
-	/// let mut api_owner = Owner::new(wallet.clone(), None, None);
-	/// let result = api_owner.scan(
-	///     None,
-	///     Some(20000),
-	///     false,
-	/// );
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
 	///
-	/// if let Ok(_) = result {
-	///     // Wallet outputs should be consistent with what's on chain
-	///     // ...
+	/// for m in api_owner.get_listener_metrics().unwrap() {
+	///   println!("{}: {} requests, {} rate limited", m.listener, m.requests, m.rate_limited);
 	/// }
 	/// ```
 
-	pub fn scan(
-		&self,
-		keychain_mask: Option<&SecretKey>,
-		start_height: Option<u64>,
-		delete_unconfirmed: bool,
-	) -> Result<(), Error> {
-		let tx = {
-			let t = self.status_tx.lock();
-			t.clone()
-		};
-		owner::scan(
-			self.wallet_inst.clone(),
-			keychain_mask,
-			start_height,
-			delete_unconfirmed,
-			&tx,
-			true,
-		)
-	}
-
-	/// Dump wallet data (outputs,transactions) into the logs
-	pub fn dump_wallet_data(&self, file_name: Option<String>) -> Result<(), Error> {
-		let tx = {
-			let t = self.status_tx.lock();
-			t.clone()
-		};
-
-		owner::dump_wallet_data(self.wallet_inst.clone(), &tx.unwrap(), file_name)
+	pub fn get_listener_metrics(&self) -> Result<Vec<ListenerMetrics>, Error> {
+		Ok(listener_limits::get_listener_metrics())
 	}
 
-	/// Retrieves the last known height known by the wallet. This is determined as follows:
-	/// * If the wallet can successfully contact its configured node, the reported node
-	/// height is returned, and the `updated_from_node` field in the response is `true`
-	/// * If the wallet cannot contact the node, this function returns the maximum height
-	/// of all outputs contained within the wallet, and the `updated_from_node` fields
-	/// in the response is set to false.
-	///
-	/// Clients should generally ensure the `updated_from_node` field is returned as
-	/// `true` before assuming the height for any operation.
+	/// Returns the hit and miss counters of the node responses cache. The TTLs of the cache are
+	/// set by the `node_cache_tip_ttl_ms` and `node_cache_ttl_ms` config settings.
 	///
 	/// # Arguments
 	///
-	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
-	/// being used.
+	/// * None
 	///
 	/// # Returns
-	/// * Ok with a  [`NodeHeightResult`](../grin_wallet_libwallet/types/struct.NodeHeightResult.html)
-	/// if successful. If the height result was obtained from the configured node,
-	/// `updated_from_node` will be set to `true`
+	/// * Ok with the list of [`NodeCacheMetrics`](../grin_wallet_libwallet/node_cache/struct.NodeCacheMetrics.html),
+	/// one for the chain tip, headers, blocks, kernels and outputs. The counters are kept since the wallet start.
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -1484,55 +3926,27 @@ where
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
 	/// let api_owner = Owner::new(wallet.clone(), None, None);
-	/// let result = api_owner.node_height(None);
-	///
-	/// if let Ok(node_height_result) = result {
-	///     if node_height_result.updated_from_node {
-	///          //we can assume node_height_result.height is relatively safe to use
 	///
-	///     }
-	///     //...
+	/// for m in api_owner.get_node_cache_metrics().unwrap() {
+	///   println!("{}: {} hits, {} misses", m.cache, m.hits, m.misses);
 	/// }
 	/// ```
 
-	pub fn node_height(
-		&self,
-		keychain_mask: Option<&SecretKey>,
-	) -> Result<NodeHeightResult, Error> {
-		{
-			let mut w_lock = self.wallet_inst.lock();
-			let w = w_lock.lc_provider()?.wallet_inst()?;
-			// Test keychain mask, to keep API consistent
-			let _ = w.keychain(keychain_mask)?;
-		}
-		let mut res = owner::node_height(self.wallet_inst.clone(), keychain_mask)?;
-		if self.doctest_mode {
-			// return a consistent hash for doctest
-			res.header_hash =
-				"d4b3d3c40695afd8c7760f8fc423565f7d41310b7a4e1c4a4a7950a66f16240d".to_owned();
-		}
-		Ok(res)
+	pub fn get_node_cache_metrics(&self) -> Result<Vec<NodeCacheMetrics>, Error> {
+		Ok(node_cache::get_node_cache_metrics())
 	}
 
-	// LIFECYCLE FUNCTIONS
-
-	/// Retrieve the top-level directory for the wallet. This directory should contain the
-	/// `mwc-wallet.toml` file and the `wallet_data` directory that contains the wallet
-	/// seed + data files. Future versions of the wallet API will support multiple wallets
-	/// within the top level directory.
-	///
-	/// The top level directory defaults to (in order of precedence):
-	///
-	/// 1) The current directory, from which `mwc-wallet` or the main process was run, if it
-	/// contains a `mwc-wallet.toml` file.
-	/// 2) ~/.grin/<chaintype>/ otherwise
+	/// Returns the state of the listeners that receive the slates and the swap messages:
+	/// the http and tor foreign listeners, mwcmqs and the messenger transports.
 	///
 	/// # Arguments
 	///
 	/// * None
 	///
 	/// # Returns
-	/// * Ok with a String value representing the full path to the top level wallet dierctory
+	/// * Ok with the list of [`ListenerStatus`](../grin_wallet_libwallet/listener_status/struct.ListenerStatus.html),
+	/// one for every transport. http, tor and mwcmqs are always listed, the messenger transports
+	/// are listed after they were started. The counters are kept since the wallet start.
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -1541,85 +3955,78 @@ where
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
 	/// let api_owner = Owner::new(wallet.clone(), None, None);
-	/// let result = api_owner.get_top_level_directory();
 	///
-	/// if let Ok(dir) = result {
-	///     println!("Top level directory is: {}", dir);
-	///     //...
+	/// for l in api_owner.query_listeners().unwrap() {
+	///   println!("{}: running {}, {} messages, {} errors", l.transport, l.running, l.messages, l.errors);
 	/// }
 	/// ```
 
-	pub fn get_top_level_directory(&self) -> Result<String, Error> {
-		let mut w_lock = self.wallet_inst.lock();
-		let lc = w_lock.lc_provider()?;
-		if self.doctest_mode {
-			Ok("/doctest/dir".to_owned())
-		} else {
-			lc.get_top_level_directory()
-		}
+	pub fn query_listeners(&self) -> Result<Vec<ListenerStatus>, Error> {
+		Ok(listener_status::get_listeners_status())
 	}
 
-	/// Set the top-level directory for the wallet. This directory can be empty, and will be created
-	/// during a subsequent calls to [`create_config`](struct.Owner.html#method.create_config)
-	///
-	/// Set [`get_top_level_directory`](struct.Owner.html#method.get_top_level_directory) for a
-	/// description of the top level directory and default paths.
+	/// Starts the listener of the transport with the settings of the running wallet process.
+	/// Together with [`stop_listener`](struct.Owner.html#method.stop_listener) it restarts the
+	/// listener without the restart of the Owner API, for example to publish the onion address
+	/// of the new address index or to recover the mwcmqs connection.
 	///
 	/// # Arguments
 	///
-	/// * `dir`: The new top-level directory path (either relative to current directory or
-	/// absolute.
+	/// * `transport` - 'http', 'tor' or 'mwcmqs'. 'http' starts the foreign listener with the
+	/// Tor onion service if it is enabled in the config, 'tor' starts it with the onion service.
 	///
 	/// # Returns
-	/// * Ok if successful
-	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	/// * Ok when the listener is accepting the messages. mwcmqs listener keeps connecting in the
+	/// background if the server is not reachable.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered,
+	/// for example the listener is already running or the listeners are not managed by this
+	/// process. Only the `owner_api` command manages the listeners.
 	///
 	/// # Example
 	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
-	/// let dir = "path/to/wallet/dir";
-	///
-	/// # let dir = tempdir().map_err(|e| format!("{:#?}", e)).unwrap();
-	/// # let dir = dir
-	/// #   .path()
-	/// #   .to_str()
-	/// #   .ok_or("Failed to convert tmpdir path to string.".to_owned())
-	/// #   .unwrap();
-	///
 	/// let api_owner = Owner::new(wallet.clone(), None, None);
-	/// let result = api_owner.set_top_level_directory(dir);
 	///
-	/// if let Ok(dir) = result {
-	///    //...
+	/// if api_owner.stop_listener("mwcmqs").is_ok() {
+	///   let res = api_owner.start_listener("mwcmqs");
 	/// }
 	/// ```
 
-	pub fn set_top_level_directory(&self, dir: &str) -> Result<(), Error> {
-		let mut w_lock = self.wallet_inst.lock();
-		let lc = w_lock.lc_provider()?;
-		lc.set_top_level_directory(dir)
+	pub fn start_listener(&self, transport: &str) -> Result<(), Error> {
+		listener_status::get_listener_control()?.start_listener(transport)
 	}
 
-	/// Create a `mwc-wallet.toml` configuration file in the top-level directory for the
-	/// specified chain type.
-	/// A custom [`WalletConfig`](../grin_wallet_config/types/struct.WalletConfig.html)
-	/// and/or grin `LoggingConfig` may optionally be provided, otherwise defaults will be used.
+	/// Stops the listener of the transport. The listener stops accepting the new messages, the
+	/// requests and the messages in progress are finished first.
 	///
-	/// Paths in the configuration file will be updated to reflect the top level directory, so
-	/// path-related values in the optional configuration structs will be ignored.
+	/// # Arguments
+	///
+	/// * `transport` - 'http', 'tor' or 'mwcmqs'. 'http' and 'tor' stop the foreign listener
+	/// together with its onion service.
+	///
+	/// # Returns
+	/// * Ok when the listener is stopped
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered,
+	/// for example the listener is not running.
+	///
+	/// # Example
+	/// Set up as in [`start_listener`](struct.Owner.html#method.start_listener) method above.
+
+	pub fn stop_listener(&self, transport: &str) -> Result<(), Error> {
+		listener_status::get_listener_control()?.stop_listener(transport)
+	}
+
+	/// Returns the consensus parameters that the wallet uses for the fee and dust calculations,
+	/// so clients can estimate the fees the same way the wallet does.
 	///
 	/// # Arguments
 	///
-	/// * `chain_type`: The chain type to use in creation of the configuration file. This can be
-	///     * `AutomatedTesting`
-	///     * `UserTesting`
-	///     * `Floonet`
-	///     * `Mainnet`
+	/// * None
 	///
 	/// # Returns
-	/// * Ok if successful
+	/// * Ok with [`ConsensusParams`](../grin_wallet_libwallet/api_impl/types/struct.ConsensusParams.html)
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -1627,68 +4034,37 @@ where
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
-	/// use grin_core::global::ChainTypes;
-	///
-	/// let dir = "path/to/wallet/dir";
-	///
-	/// # let dir = tempdir().map_err(|e| format!("{:#?}", e)).unwrap();
-	/// # let dir = dir
-	/// #   .path()
-	/// #   .to_str()
-	/// #   .ok_or("Failed to convert tmpdir path to string.".to_owned())
-	/// #   .unwrap();
-	///
 	/// let api_owner = Owner::new(wallet.clone(), None, None);
-	/// let _ = api_owner.set_top_level_directory(dir);
 	///
-	/// let result = api_owner.create_config(&ChainTypes::Mainnet, None, None, None, None );
+	/// let result = api_owner.get_consensus_params();
 	///
-	/// if let Ok(_) = result {
-	///    //...
+	/// if let Ok(params) = result {
+	///   // fee of 1 input, 2 outputs and 1 kernel transaction
+	///   let weight = params.input_weight + 2 * params.output_weight + params.kernel_weight;
+	///   let fee = weight as u64 * params.base_fee;
+	///   assert!(fee >= params.min_tx_fee);
 	/// }
 	/// ```
 
-	pub fn create_config(
-		&self,
-		chain_type: &global::ChainTypes,
-		wallet_config: Option<WalletConfig>,
-		logging_config: Option<LoggingConfig>,
-		tor_config: Option<TorConfig>,
-		mqs_config: Option<MQSConfig>,
-	) -> Result<(), Error> {
+	pub fn get_consensus_params(&self) -> Result<ConsensusParams, Error> {
 		let mut w_lock = self.wallet_inst.lock();
-		let lc = w_lock.lc_provider()?;
-		lc.create_config(
-			chain_type,
-			"mwc-wallet.toml",
-			wallet_config,
-			logging_config,
-			tor_config,
-			mqs_config,
-		)
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::get_consensus_params(&mut **w)
 	}
 
-	/// Creates a new wallet seed and empty wallet database in the `wallet_data` directory of
-	/// the top level directory.
-	///
-	/// Paths in the configuration file will be updated to reflect the top level directory, so
-	/// path-related values in the optional configuration structs will be ignored.
-	///
-	/// The wallet files must not already exist, and ~The `mwc-wallet.toml` file must exist
-	/// in the top level directory (can be created via a call to
-	/// [`create_config`](struct.Owner.html#method.create_config))
+	/// Returns the balance, fees and volume series for the current account, aggregated from
+	/// the confirmed transactions into the time buckets. GUI wallets can render the charts
+	/// without retrieving the whole transaction history. The series starts at the bucket with
+	/// the first transaction and ends at the current time.
 	///
 	/// # Arguments
 	///
-	/// * `name`: Reserved for future use, use `None` for the time being.
-	/// * `mnemonic`: If present, restore the wallet seed from the given mnemonic instead of creating
-	/// a new random seed.
-	/// * `mnemonic_length`: Desired length of mnemonic in bytes (16 or 32, either 12 or 24 words).
-	/// Use 0 if mnemonic isn't being used.
-	/// * `password`: The password used to encrypt/decrypt the `wallet.seed` file
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `bucket_sec` - Bucket length in seconds, at least 3600. Default is one day.
 	///
 	/// # Returns
-	/// * Ok if successful
+	/// * Ok with a Vec of [`HistoryBucket`](../grin_wallet_libwallet/api_impl/types/struct.HistoryBucket.html)
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -1696,133 +4072,81 @@ where
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
-	/// use grin_core::global::ChainTypes;
-	///
-	/// // note that the WalletInst struct does not necessarily need to contain an
-	/// // instantiated wallet
-	///
-	/// let dir = "path/to/wallet/dir";
-	///
-	/// # let dir = tempdir().map_err(|e| format!("{:#?}", e)).unwrap();
-	/// # let dir = dir
-	/// #   .path()
-	/// #   .to_str()
-	/// #   .ok_or("Failed to convert tmpdir path to string.".to_owned())
-	/// #   .unwrap();
 	/// let api_owner = Owner::new(wallet.clone(), None, None);
-	/// let _ = api_owner.set_top_level_directory(dir);
-	///
-	/// // Create configuration
-	/// let result = api_owner.create_config(&ChainTypes::Mainnet,None,  None, None, None);
 	///
-	///	// create new wallet wirh random seed
-	///	let pw = ZeroingString::from("my_password");
-	/// let result = api_owner.create_wallet(None, None, 0, pw, None);
+	/// // Daily buckets
+	/// let result = api_owner.retrieve_history_series(None, None);
 	///
-	/// if let Ok(r) = result {
-	///     //...
+	/// if let Ok(series) = result {
+	///   for bucket in series {
+	///     // ...
+	///   }
 	/// }
 	/// ```
 
-	pub fn create_wallet(
-		&self,
-		name: Option<&str>,
-		mnemonic: Option<ZeroingString>,
-		mnemonic_length: u32,
-		password: ZeroingString,
-		wallet_data_dir: Option<&str>,
-	) -> Result<(), Error> {
+	pub fn retrieve_history_series(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		bucket_sec: Option<u64>,
+	) -> Result<Vec<HistoryBucket>, Error> {
 		let mut w_lock = self.wallet_inst.lock();
-		let lc = w_lock.lc_provider()?;
-		lc.create_wallet(
-			name,
-			mnemonic,
-			mnemonic_length as usize,
-			password,
-			self.doctest_mode,
-			wallet_data_dir,
-		)
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::retrieve_history_series(&mut **w, keychain_mask, bucket_sec)
 	}
 
-	/// `Opens` a wallet, populating the internal keychain with the encrypted seed, and optionally
-	/// returning a `keychain_mask` token to the caller to provide in all future calls.
-	/// If using a mask, the seed will be stored in-memory XORed against the `keychain_mask`, and
-	/// will not be useable if the mask is not provided.
+	/// Returns the MWC exchange rate from the price source that is configured with
+	/// `price_source_url`, so clients can show the fiat values of the amounts. The current rate
+	/// is cached for `price_cache_minutes`, the historical daily rates are cached while the
+	/// wallet is running.
 	///
 	/// # Arguments
 	///
-	/// * `name`: Reserved for future use, use `None` for the time being.
-	/// * `password`: The password to use to open the wallet
-	/// a new random seed.
-	/// * `use_mask`: Whether to create and return a mask which much be provided in all future
-	/// API calls.
+	/// * `currency` - Currency code, for example `usd` or `eur`.
+	/// * `date` - Date (UTC) of the historical daily rate. `None`, today or a future date
+	/// returns the current rate.
 	///
 	/// # Returns
-	/// * Ok if successful
-	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	/// * Ok with [`ExchangeRate`](../grin_wallet_libwallet/price/struct.ExchangeRate.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the price source
+	/// is not configured or the rate is not available.
 	///
 	/// # Example
 	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
-	/// use grin_core::global::ChainTypes;
-	///
-	/// // note that the WalletInst struct does not necessarily need to contain an
-	/// // instantiated wallet
-	/// let dir = "path/to/wallet/dir";
-	///
-	/// # let dir = tempdir().map_err(|e| format!("{:#?}", e)).unwrap();
-	/// # let dir = dir
-	/// #   .path()
-	/// #   .to_str()
-	/// #   .ok_or("Failed to convert tmpdir path to string.".to_owned())
-	/// #   .unwrap();
 	/// let api_owner = Owner::new(wallet.clone(), None, None);
-	/// let _ = api_owner.set_top_level_directory(dir);
-	///
-	/// // Create configuration
-	/// let result = api_owner.create_config(&ChainTypes::Mainnet, None,  None, None, None);
-	///
-	///	// create new wallet wirh random seed
-	///	let pw = ZeroingString::from("my_password");
-	/// let _ = api_owner.create_wallet(None, None, 0, pw.clone(), None);
 	///
-	/// let result = api_owner.open_wallet(None, pw, true, None);
+	/// let result = api_owner.get_exchange_rate("usd", None);
 	///
-	/// if let Ok(m) = result {
-	///     // use this mask in all subsequent calls
-	///     let mask = m;
+	/// if let Ok(rate) = result {
+	///   // Value of 2.5 MWC
+	///   let value = rate.value(2_500_000_000);
 	/// }
 	/// ```
 
-	pub fn open_wallet(
+	pub fn get_exchange_rate(
 		&self,
-		name: Option<&str>,
-		password: ZeroingString,
-		use_mask: bool,
-		wallet_data_dir: Option<&str>,
-	) -> Result<Option<SecretKey>, Error> {
-		// just return a representative string for doctest mode
-		if self.doctest_mode {
-			return Ok(Some(SecretKey::from_slice(
-				&from_hex("d096b3cb75986b3b13f80b8f5243a9edf0af4c74ac37578c5a12cfb5b59b1868")
-					.unwrap(),
-			)?));
-		}
-		let mut w_lock = self.wallet_inst.lock();
-		let lc = w_lock.lc_provider()?;
-		lc.open_wallet(name, password, use_mask, self.doctest_mode, wallet_data_dir)
+		currency: &str,
+		date: Option<NaiveDate>,
+	) -> Result<ExchangeRate, Error> {
+		price::get_exchange_rate(currency, date)
 	}
 
-	/// `Close` a wallet, removing the master seed from memory.
+	/// Retrieve messages from the updater thread, up to `count` number of messages.
+	/// The resulting array will be ordered newest messages first. The updater will
+	/// store a maximum of 10,000 messages, after which it will start removing the oldest
+	/// messages as newer ones are created.
+	///
+	/// Messages retrieved via this method are removed from the internal queue, so calling
+	/// this function at a specified interval should result in a complete message history.
 	///
 	/// # Arguments
 	///
-	/// * `name`: Reserved for future use, use `None` for the time being.
+	/// * `count` - The number of messages to retrieve.
 	///
 	/// # Returns
-	/// * Ok if successful
+	/// * Ok with a Vec of [`StatusMessage`](../grin_wallet_libwallet/api_impl/owner_updater/enum.StatusMessage.html)
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -1832,33 +4156,36 @@ where
 	///
 	/// use grin_core::global::ChainTypes;
 	///
+	/// use std::time::Duration;
+	///
 	/// // Set up as above
 	/// # let api_owner = Owner::new(wallet.clone(), None, None);
 	///
-	/// let res = api_owner.close_wallet(None);
+	/// let res = api_owner.start_updater(None, Duration::from_secs(60));
+	///
+	/// let messages = api_owner.get_updater_messages(10000);
 	///
 	/// if let Ok(_) = res {
-	///     // ...
+	///   // ...
 	/// }
+	///
 	/// ```
 
-	pub fn close_wallet(&self, name: Option<&str>) -> Result<(), Error> {
-		let mut w_lock = self.wallet_inst.lock();
-		let lc = w_lock.lc_provider()?;
-		lc.close_wallet(name)
+	pub fn get_updater_messages(&self, count: usize) -> Result<Vec<StatusMessage>, Error> {
+		let mut q = self.updater_messages.lock();
+		let index = q.len().saturating_sub(count);
+		Ok(q.split_off(index))
 	}
 
-	/// Return the BIP39 mnemonic for the given wallet. This function will decrypt
-	/// the wallet's seed file with the given password, and thus does not need the
-	/// wallet to be open.
+	/// Retrieve the MQS address associated with the wallet. This address can be changed with
+	/// address index. In this case it will affect all wallet public addresses
 	///
 	/// # Arguments
 	///
-	/// * `name`: Reserved for future use, use `None` for the time being.
-	/// * `password`: The password used to encrypt the seed file.
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
 	///
 	/// # Returns
-	/// * Ok(BIP-39 mneminc) if successful
+	/// * Ok with a PublicKey that representing the address for MQS
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -1868,43 +4195,34 @@ where
 	///
 	/// use grin_core::global::ChainTypes;
 	///
+	/// use std::time::Duration;
+	///
 	/// // Set up as above
 	/// # let api_owner = Owner::new(wallet.clone(), None, None);
 	///
-	///	let pw = ZeroingString::from("my_password");
-	/// let res = api_owner.get_mnemonic(None, pw, None);
+	/// let res = api_owner.get_mqs_address(None);
 	///
-	/// if let Ok(mne) = res {
-	///     // ...
+	/// if let Ok(_) = res {
+	///   // ...
 	/// }
+	///
 	/// ```
-	pub fn get_mnemonic(
-		&self,
-		name: Option<&str>,
-		password: ZeroingString,
-		wallet_data_dir: Option<&str>,
-	) -> Result<ZeroingString, Error> {
-		let mut w_lock = self.wallet_inst.lock();
-		let lc = w_lock.lc_provider()?;
-		lc.get_mnemonic(name, password, wallet_data_dir)
+
+	pub fn get_mqs_address(&self, keychain_mask: Option<&SecretKey>) -> Result<PublicKey, Error> {
+		owner::get_mqs_address(self.wallet_inst.clone(), keychain_mask)
 	}
 
-	/// Changes a wallet's password, meaning the old seed file is decrypted with the old password,
-	/// and a new seed file is created with the same mnemonic and encrypted with the new password.
-	///
-	/// This function temporarily backs up the old seed file until a test-decryption of the new
-	/// file is confirmed to contain the same seed as the original seed file, at which point the
-	/// backup is deleted. If this operation fails for an unknown reason, the backup file will still
-	/// exist in the wallet's data directory encrypted with the old password.
+	/// Returns the MQS messages that the broker didn't accept yet. Such messages are kept in the
+	/// wallet data directory and the mwcmqs listener retries them with a growing delay till they
+	/// are delivered or expired. Slates expire when the sender stops waiting for the response,
+	/// swap messages in an hour.
 	///
 	/// # Arguments
 	///
-	/// * `name`: Reserved for future use, use `None` for the time being.
-	/// * `old`: The password used to encrypt the existing seed file (i.e. old password)
-	/// * `new`: The password to be used to encrypt the new seed file
+	/// * None
 	///
 	/// # Returns
-	/// * Ok(()) if successful
+	/// * Ok with the list of [`MqsQueuedMessage`](../grin_wallet_impls/struct.MqsQueuedMessage.html)
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -1912,98 +4230,75 @@ where
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
-	/// use grin_core::global::ChainTypes;
-	///
-	/// // Set up as above
-	/// # let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
 	///
-	///	let old = ZeroingString::from("my_password");
-	///	let new = ZeroingString::from("new_password");
-	/// let res = api_owner.change_password(None, old, new, None);
+	/// let res = api_owner.get_mqs_outbox();
 	///
-	/// if let Ok(mne) = res {
-	///     // ...
+	/// if let Ok(messages) = res {
+	///   for m in messages {
+	///     println!("{} to {}, attempts: {}", m.id, m.to, m.attempts);
+	///   }
 	/// }
 	/// ```
-	pub fn change_password(
-		&self,
-		name: Option<&str>,
-		old: ZeroingString,
-		new: ZeroingString,
-		wallet_data_dir: Option<&str>,
-	) -> Result<(), Error> {
-		let mut w_lock = self.wallet_inst.lock();
-		let lc = w_lock.lc_provider()?;
-		lc.change_password(name, old, new, wallet_data_dir)
+
+	pub fn get_mqs_outbox(&self) -> Result<Vec<MqsQueuedMessage>, Error> {
+		self.init_mqs_outbox()?;
+		Ok(get_mwcmqs_outbox())
 	}
 
-	/// Deletes a wallet, removing the config file, seed file and all data files.
-	/// Obviously, use with extreme caution and plenty of user warning
-	///
-	/// Highly recommended that the wallet be explicitly closed first via the `close_wallet`
-	/// function.
+	/// Tries to deliver all queued MQS messages now, without waiting for their retry time.
+	/// The mwcmqs listener must be running.
 	///
 	/// # Arguments
 	///
-	/// * `name`: Reserved for future use, use `None` for the time being.
+	/// * None
 	///
 	/// # Returns
-	/// * Ok if successful
-	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	/// * Ok with the list of [`MqsQueuedMessage`](../grin_wallet_impls/struct.MqsQueuedMessage.html)
+	/// that are still not delivered
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered,
+	/// for example the mwcmqs listener is not running.
 	///
 	/// # Example
 	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
-	/// use grin_core::global::ChainTypes;
-	///
-	/// // Set up as above
-	/// # let api_owner = Owner::new(wallet.clone(), None, None);
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
 	///
-	/// let res = api_owner.delete_wallet(None);
+	/// let res = api_owner.flush_mqs_outbox();
 	///
-	/// if let Ok(_) = res {
-	///     // ...
+	/// if let Ok(messages) = res {
+	///   println!("{} messages are not delivered", messages.len());
 	/// }
 	/// ```
 
-	pub fn delete_wallet(&self, name: Option<&str>) -> Result<(), Error> {
+	pub fn flush_mqs_outbox(&self) -> Result<Vec<MqsQueuedMessage>, Error> {
+		self.init_mqs_outbox()?;
+		flush_mwcmqs_outbox().map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to deliver the queued messages, {}", e)).into()
+		})
+	}
+
+	fn init_mqs_outbox(&self) -> Result<(), Error> {
 		let mut w_lock = self.wallet_inst.lock();
-		let lc = w_lock.lc_provider()?;
-		lc.delete_wallet(name)
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		init_mwcmqs_outbox(Path::new(w.get_data_file_dir())).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to load the mwcmqs outbox, {}", e)).into()
+		})
 	}
 
-	/// Starts a background wallet update thread, which performs the wallet update process
-	/// automatically at the frequency specified.
-	///
-	/// The updater process is as follows:
-	///
-	/// * Reconcile the wallet outputs against the node's current UTXO set, confirming
-	/// transactions if needs be.
-	/// * Look up transactions by kernel in cases where it's necessary (for instance, when
-	/// there are no change outputs for a transaction and transaction status can't be
-	/// inferred from the output state.
-	/// * Incrementally perform a scan of the UTXO set, correcting outputs and transactions
-	/// where their local state differs from what's on-chain. The wallet stores the last
-	/// position scanned, and will scan back 100 blocks worth of UTXOs on each update, to
-	/// correct any differences due to forks or otherwise.
-	///
-	/// Note that an update process can take a long time, particularly when the entire
-	/// UTXO set is being scanned for correctness. The wallet status can be determined by
-	/// calling the [`get_updater_messages`](struct.Owner.html#method.get_updater_messages).
+	/// Retrieve the Tor or wallet public address associated with the wallet. This address can be changed with
+	/// address index. In this case it will affect all wallet public addresses
 	///
 	/// # Arguments
 	///
 	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
-	/// being used.
-	/// * `frequency`: The frequency at which to call the update process. Note this is
-	/// time elapsed since the last successful update process. If calling via the JSON-RPC
-	/// api, this represents milliseconds.
 	///
 	/// # Returns
-	/// * Ok if successful
-	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	/// * Ok(DalekPublicKey) representing the public key associated with the address, if successful
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered
+	/// or the address provided is invalid
 	///
 	/// # Example
 	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
@@ -2017,47 +4312,69 @@ where
 	/// // Set up as above
 	/// # let api_owner = Owner::new(wallet.clone(), None, None);
 	///
-	/// let res = api_owner.start_updater(None, Duration::from_secs(60));
+	/// let res = api_owner.get_wallet_public_address(None);
 	///
 	/// if let Ok(_) = res {
 	///   // ...
 	/// }
+	///
 	/// ```
 
-	pub fn start_updater(
+	pub fn get_wallet_public_address(
 		&self,
 		keychain_mask: Option<&SecretKey>,
-		frequency: Duration,
-	) -> Result<(), Error> {
-		let updater_inner = self.updater.clone();
-		let tx_inner = {
-			let t = self.status_tx.lock();
-			t.clone()
-		};
-		let keychain_mask = match keychain_mask {
-			Some(m) => Some(m.clone()),
-			None => None,
-		};
-		let _ = thread::Builder::new()
-			.name("wallet-updater".to_string())
-			.spawn(move || {
-				let u = updater_inner.lock();
-				if let Err(e) = u.run(frequency, keychain_mask, &tx_inner) {
-					error!("Wallet state updater failed with error: {}", e);
-				}
-			})?;
-		Ok(())
+	) -> Result<DalekPublicKey, Error> {
+		owner::get_wallet_public_address(self.wallet_inst.clone(), keychain_mask)
+	}
+
+	/// Retrieve the MQS and Tor addresses at the derivation index, without changing the current
+	/// wallet address. A service can hand out a distinct address per customer; the payments to
+	/// such address are received with the valid proof and the received transaction has the index
+	/// in `address_index`. Note that the listeners are still working with the current address,
+	/// the addresses by index are for the http(s), file and slatepack payments.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `index` - Address derivation index.
+	///
+	/// # Returns
+	/// * Ok with the [`PublicProofAddress`](../grin_wallet_libwallet/api_impl/types/struct.PublicProofAddress.html)
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// // Address for the customer 42
+	/// if let Ok(address) = api_owner.get_public_proof_address(None, 42) {
+	///     println!("Tor address: {}", address.tor_address.public_key);
+	/// }
+	/// ```
+
+	pub fn get_public_proof_address(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		index: u32,
+	) -> Result<PublicProofAddress, Error> {
+		owner::get_public_proof_address(self.wallet_inst.clone(), keychain_mask, index)
 	}
 
-	/// Stops the background update thread. If the updater is currently updating, the
-	/// thread will stop after the next update
+	/// Returns the address indexes that received the payments with the proof, sorted.
+	/// Use [`query_txs`](struct.Owner.html#method.query_txs) to find the transactions, they have
+	/// the index in `address_index`.
 	///
 	/// # Arguments
 	///
-	/// * None
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
 	///
 	/// # Returns
-	/// * Ok if successful
+	/// * Ok with the list of the address indexes
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -2065,41 +4382,29 @@ where
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
-	/// use grin_core::global::ChainTypes;
-	///
-	/// use std::time::Duration;
-	///
-	/// // Set up as above
-	/// # let api_owner = Owner::new(wallet.clone(), None, None);
-	///
-	/// let res = api_owner.start_updater(None, Duration::from_secs(60));
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
 	///
-	/// if let Ok(_) = res {
-	///   // ...
+	/// if let Ok(indices) = api_owner.list_used_address_indices(None) {
+	///     println!("Addresses with payments: {:?}", indices);
 	/// }
-	///
-	/// let res = api_owner.stop_updater();
 	/// ```
 
-	pub fn stop_updater(&self) -> Result<(), Error> {
-		self.updater_running.store(false, Ordering::Relaxed);
-		Ok(())
+	pub fn list_used_address_indices(
+		&self,
+		_keychain_mask: Option<&SecretKey>,
+	) -> Result<Vec<u32>, Error> {
+		owner::list_used_address_indices(self.wallet_inst.clone())
 	}
 
-	/// Retrieve messages from the updater thread, up to `count` number of messages.
-	/// The resulting array will be ordered newest messages first. The updater will
-	/// store a maximum of 10,000 messages, after which it will start removing the oldest
-	/// messages as newer ones are created.
-	///
-	/// Messages retrieved via this method are removed from the internal queue, so calling
-	/// this function at a specified interval should result in a complete message history.
+	/// Returns the mappings of the address indexes to the accounts and labels, sorted by index.
 	///
 	/// # Arguments
-	///
-	/// * `count` - The number of messages to retrieve.
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
 	///
 	/// # Returns
-	/// * Ok with a Vec of [`StatusMessage`](../grin_wallet_libwallet/api_impl/owner_updater/enum.StatusMessage.html)
+	/// * Result Containing:
+	/// * A Vector of [`AddressMapping`](../grin_wallet_libwallet/types/struct.AddressMapping.html) data
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
@@ -2107,101 +4412,95 @@ where
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
-	/// use grin_core::global::ChainTypes;
-	///
-	/// use std::time::Duration;
-	///
-	/// // Set up as above
-	/// # let api_owner = Owner::new(wallet.clone(), None, None);
-	///
-	/// let res = api_owner.start_updater(None, Duration::from_secs(60));
-	///
-	/// let messages = api_owner.get_updater_messages(10000);
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
 	///
-	/// if let Ok(_) = res {
-	///   // ...
+	/// if let Ok(mappings) = api_owner.address_mappings(None) {
+	///     //...
 	/// }
-	///
 	/// ```
 
-	pub fn get_updater_messages(&self, count: usize) -> Result<Vec<StatusMessage>, Error> {
-		let mut q = self.updater_messages.lock();
-		let index = q.len().saturating_sub(count);
-		Ok(q.split_off(index))
+	pub fn address_mappings(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+	) -> Result<Vec<AddressMapping>, Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		// Test keychain mask, to keep API consistent
+		let _ = w.keychain(keychain_mask)?;
+		owner::address_mappings(&mut **w)
 	}
 
-	/// Retrieve the MQS address associated with the wallet. This address can be changed with
-	/// address index. In this case it will affect all wallet public addresses
+	/// Books the payments received on the address index to the account and the label. The
+	/// explicit account of the receive call has priority over the mapping. The label is set
+	/// for the received transaction, the mapping of the index is replaced.
 	///
 	/// # Arguments
-	///
 	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `mapping` - Address index with the account and/or the label. The account must exist.
 	///
 	/// # Returns
-	/// * Ok with a PublicKey that representing the address for MQS
+	/// * Result Containing:
+	/// * `Ok(())` if the mapping was saved
 	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
 	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	/// use grin_wallet_libwallet::AddressMapping;
 	///
-	/// use grin_core::global::ChainTypes;
-	///
-	/// use std::time::Duration;
-	///
-	/// // Set up as above
-	/// # let api_owner = Owner::new(wallet.clone(), None, None);
-	///
-	/// let res = api_owner.get_mqs_address(None);
-	///
-	/// if let Ok(_) = res {
-	///   // ...
-	/// }
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
 	///
+	/// let mapping = AddressMapping {
+	///     index: 42,
+	///     account: None,
+	///     label: Some("user 42".to_string()),
+	/// };
+	/// let result = api_owner.set_address_mapping(None, &mapping);
 	/// ```
 
-	pub fn get_mqs_address(&self, keychain_mask: Option<&SecretKey>) -> Result<PublicKey, Error> {
-		owner::get_mqs_address(self.wallet_inst.clone(), keychain_mask)
+	pub fn set_address_mapping(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		mapping: &AddressMapping,
+	) -> Result<(), Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::set_address_mapping(&mut **w, keychain_mask, mapping.clone())
 	}
 
-	/// Retrieve the Tor or wallet public address associated with the wallet. This address can be changed with
-	/// address index. In this case it will affect all wallet public addresses
+	/// Removes the mapping of the address index. The transactions that were already received
+	/// keep their account and label.
 	///
 	/// # Arguments
-	///
 	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `index` - Address derivation index.
 	///
 	/// # Returns
-	/// * Ok(DalekPublicKey) representing the public key associated with the address, if successful
-	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered
-	/// or the address provided is invalid
+	/// * Result Containing:
+	/// * `Ok(())` if the mapping was removed
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
 	///
 	/// # Example
 	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
 	/// ```
 	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
 	///
-	/// use grin_core::global::ChainTypes;
-	///
-	/// use std::time::Duration;
-	///
-	/// // Set up as above
-	/// # let api_owner = Owner::new(wallet.clone(), None, None);
-	///
-	/// let res = api_owner.get_wallet_public_address(None);
-	///
-	/// if let Ok(_) = res {
-	///   // ...
-	/// }
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
 	///
+	/// let result = api_owner.remove_address_mapping(None, 42);
 	/// ```
 
-	pub fn get_wallet_public_address(
+	pub fn remove_address_mapping(
 		&self,
 		keychain_mask: Option<&SecretKey>,
-	) -> Result<DalekPublicKey, Error> {
-		owner::get_wallet_public_address(self.wallet_inst.clone(), keychain_mask)
+		index: u32,
+	) -> Result<(), Error> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		owner::remove_address_mapping(&mut **w, keychain_mask, index)
 	}
 
 	/// Returns a single, exportable [PaymentProof](../grin_wallet_libwallet/api_impl/types/struct.PaymentProof.html)
@@ -2338,15 +4637,68 @@ where
 		owner::verify_payment_proof(self.wallet_inst.clone(), keychain_mask, proof)
 	}
 
+	/// Looks up a transaction kernel on the node by the kernel excess. This is the way to confirm
+	/// a payment by its kernel: the excess is in the payment proof and in the transaction log of
+	/// both parties, so the receiver can check that the transaction is on the chain and how deep
+	/// it is. The transaction of the active account with this kernel is returned as well, if the
+	/// wallet has it.
+	///
+	/// # Arguments
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `excess` - Kernel excess, hex.
+	///
+	/// # Returns
+	/// * Ok with [`KernelLookupResult`](../grin_wallet_libwallet/api_impl/types/struct.KernelLookupResult.html),
+	/// the inclusion height and the number of the confirmations if the kernel is on the chain.
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if the excess is
+	/// invalid or the node is not available.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let result = api_owner.lookup_kernel(
+	///     None,
+	///     "09eac5f5872fa5e08e0c29fd900f1b8f77ff3ad1d0d1c46aeb202cbf92363fe0af",
+	/// );
+	///
+	/// if let Ok(res) = result {
+	///     if res.on_chain && res.confirmations >= 10 {
+	///         // ...
+	///     }
+	/// }
+	/// ```
+
+	pub fn lookup_kernel(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		excess: &str,
+	) -> Result<KernelLookupResult, Error> {
+		owner::lookup_kernel(self.wallet_inst.clone(), keychain_mask, excess)
+	}
+
 	/// Start swap trade process. Return SwapID that can be used to check the status or perform further action.
 	pub fn swap_start(
 		&self,
 		keychain_mask: Option<&SecretKey>,
 		params: &SwapStartArgs,
 	) -> Result<String, Error> {
-		// Updating wallet state first because we need to select outputs.
-		owner::update_wallet_state(self.wallet_inst.clone(), keychain_mask, &None)?;
-		owner_swap::swap_start(self.wallet_inst.clone(), keychain_mask, params)
+		self.audit(
+			"swap_start",
+			format!(
+				"mwc_amount: {}, secondary_currency: {}, secondary_amount: {}",
+				params.mwc_amount, params.secondary_currency, params.secondary_amount
+			),
+			|| {
+				// Updating wallet state first because we need to select outputs.
+				owner::update_wallet_state(self.wallet_inst.clone(), keychain_mask, &None)?;
+				owner_swap::swap_start(self.wallet_inst.clone(), keychain_mask, params)
+			},
+		)
 	}
 
 	pub fn swap_create_from_offer(
@@ -2354,10 +4706,16 @@ where
 		keychain_mask: Option<&SecretKey>,
 		message_filename: String,
 	) -> Result<String, Error> {
-		owner_swap::swap_create_from_offer(
-			self.wallet_inst.clone(),
-			keychain_mask,
-			message_filename,
+		self.audit(
+			"swap_create_from_offer",
+			format!("message_filename: {}", message_filename),
+			|| {
+				owner_swap::swap_create_from_offer(
+					self.wallet_inst.clone(),
+					keychain_mask,
+					message_filename,
+				)
+			},
 		)
 	}
 
@@ -2370,13 +4728,83 @@ where
 		owner_swap::swap_list(self.wallet_inst.clone(), keychain_mask, do_check)
 	}
 
+	/// History of the finished swap trades
+	pub fn swap_history(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+	) -> Result<Vec<SwapHistoryRecord>, Error> {
+		owner_swap::swap_history(self.wallet_inst.clone(), keychain_mask)
+	}
+
+	/// Generate a new secondary currency address of the wallet. The key is derived from the
+	/// wallet seed.
+	pub fn swap_new_secondary_address(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		currency: String,
+	) -> Result<SecondaryAddress, Error> {
+		self.audit(
+			"swap_new_secondary_address",
+			format!("currency: {}", currency),
+			|| {
+				owner_swap::swap_new_secondary_address(
+					self.wallet_inst.clone(),
+					keychain_mask,
+					&currency,
+				)
+			},
+		)
+	}
+
+	/// Secondary currency addresses of the wallet, all or for the currency
+	pub fn swap_list_secondary_addresses(
+		&self,
+		currency: Option<String>,
+	) -> Result<Vec<SecondaryAddress>, Error> {
+		owner_swap::swap_list_secondary_addresses(currency)
+	}
+
+	/// Sweep the funds of the secondary currency addresses of the wallet to the destination
+	pub fn swap_sweep_secondary_addresses(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		currency: String,
+		destination: String,
+		secondary_fee: Option<f32>,
+		electrum_node_uri1: Option<String>,
+		electrum_node_uri2: Option<String>,
+		post_tx: bool,
+	) -> Result<Vec<SweptAddress>, Error> {
+		self.audit(
+			"swap_sweep_secondary_addresses",
+			format!(
+				"currency: {}, destination: {}, post_tx: {}",
+				currency, destination, post_tx
+			),
+			|| {
+				owner_swap::swap_sweep_secondary_addresses(
+					self.wallet_inst.clone(),
+					keychain_mask,
+					&currency,
+					&destination,
+					secondary_fee,
+					electrum_node_uri1,
+					electrum_node_uri2,
+					post_tx,
+				)
+			},
+		)
+	}
+
 	/// Delete swap trade
 	pub fn swap_delete(
 		&self,
 		keychain_mask: Option<&SecretKey>,
 		swap_id: String,
 	) -> Result<(), Error> {
-		owner_swap::swap_delete(self.wallet_inst.clone(), keychain_mask, &swap_id)
+		self.audit("swap_delete", format!("swap_id: {}", swap_id), || {
+			owner_swap::swap_delete(self.wallet_inst.clone(), keychain_mask, &swap_id)
+		})
 	}
 	/// Retrieve swap trade
 	pub fn swap_get(
@@ -2402,20 +4830,28 @@ where
 		electrum_node_uri2: Option<String>,
 		eth_infura_project_id: Option<String>,
 		tag: Option<String>,
+		lock_window_sec: Option<u64>,
 	) -> Result<(StateId, Action), Error> {
-		owner_swap::swap_adjust(
-			self.wallet_inst.clone(),
-			keychain_mask,
-			&swap_id,
-			&adjust_cmd,
-			method,
-			destination,
-			secondary_address,
-			secondary_fee,
-			electrum_node_uri1,
-			electrum_node_uri2,
-			eth_infura_project_id,
-			tag,
+		self.audit(
+			"swap_adjust",
+			format!("swap_id: {}, adjust_cmd: {}", swap_id, adjust_cmd),
+			|| {
+				owner_swap::swap_adjust(
+					self.wallet_inst.clone(),
+					keychain_mask,
+					&swap_id,
+					&adjust_cmd,
+					method,
+					destination,
+					secondary_address,
+					secondary_fee,
+					electrum_node_uri1,
+					electrum_node_uri2,
+					eth_infura_project_id,
+					tag,
+					lock_window_sec,
+				)
+			},
 		)
 	}
 
@@ -2520,20 +4956,22 @@ where
 		F: FnOnce(Message, String, String) -> Result<(bool, String), crate::libwallet::Error>
 			+ 'static,
 	{
-		owner_swap::swap_process(
-			self.wallet_inst.clone(),
-			keychain_mask,
-			swap_id,
-			message_sender,
-			message_file_name,
-			buyer_refund_address,
-			secondary_fee,
-			secondary_address,
-			electrum_node_uri1,
-			electrum_node_uri2,
-			eth_infura_project_id,
-			false,
-		)
+		self.audit("swap_process", format!("swap_id: {}", swap_id), || {
+			owner_swap::swap_process(
+				self.wallet_inst.clone(),
+				keychain_mask,
+				swap_id,
+				message_sender,
+				message_file_name,
+				buyer_refund_address,
+				secondary_fee,
+				secondary_address,
+				electrum_node_uri1,
+				electrum_node_uri2,
+				eth_infura_project_id,
+				false,
+			)
+		})
 	}
 
 	/// Process swap income message
@@ -2545,6 +4983,166 @@ where
 		owner_swap::swap_income_message(self.wallet_inst.clone(), keychain_mask, &message, None)
 	}
 
+	/// Start a new multisig output session, this wallet funds the output.
+	/// Return the session document for the next participant.
+	pub fn multisig_create(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		args: &MultisigCreateArgs,
+	) -> Result<MultisigSession, Error> {
+		self.audit(
+			"multisig_create",
+			format!("scheme: {}, amount: {}", args.scheme, args.amount),
+			|| {
+				// Updating wallet state first because we need to select outputs.
+				owner::update_wallet_state(self.wallet_inst.clone(), keychain_mask, &None)?;
+				owner_multisig::multisig_create(self.wallet_inst.clone(), keychain_mask, args)
+			},
+		)
+	}
+
+	/// Process the multisig session document from another participant
+	pub fn multisig_process(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		session: &MultisigSession,
+	) -> Result<MultisigSession, Error> {
+		self.audit(
+			"multisig_process",
+			format!("session: {}, status: {}", session.id, session.status),
+			|| owner_multisig::multisig_process(self.wallet_inst.clone(), keychain_mask, session),
+		)
+	}
+
+	/// Start spending the multisig output into this wallet
+	pub fn multisig_spend(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		id: &Uuid,
+		cosigner: Option<usize>,
+	) -> Result<MultisigSession, Error> {
+		self.audit(
+			"multisig_spend",
+			format!("session: {}, cosigner: {:?}", id, cosigner),
+			|| {
+				owner_multisig::multisig_spend(
+					self.wallet_inst.clone(),
+					keychain_mask,
+					id,
+					cosigner,
+				)
+			},
+		)
+	}
+
+	/// Cancel the multisig session setup or the spending in progress
+	pub fn multisig_cancel(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		id: &Uuid,
+	) -> Result<(), Error> {
+		self.audit("multisig_cancel", format!("session: {}", id), || {
+			owner_multisig::multisig_cancel(self.wallet_inst.clone(), keychain_mask, id)
+		})
+	}
+
+	/// Restore the multisig session from the document after the wallet restore
+	pub fn multisig_restore(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		session: &MultisigSession,
+		participant_id: usize,
+	) -> Result<(), Error> {
+		self.audit(
+			"multisig_restore",
+			format!("session: {}, participant: {}", session.id, participant_id),
+			|| {
+				owner_multisig::multisig_restore(
+					self.wallet_inst.clone(),
+					keychain_mask,
+					session,
+					participant_id,
+				)
+			},
+		)
+	}
+
+	/// List the multisig sessions
+	pub fn multisig_list(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+	) -> Result<Vec<MultisigRecord>, Error> {
+		owner_multisig::multisig_list(self.wallet_inst.clone(), keychain_mask)
+	}
+
+	/// Retrieve the multisig session
+	pub fn multisig_get(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		id: &Uuid,
+	) -> Result<MultisigRecord, Error> {
+		owner_multisig::multisig_get(self.wallet_inst.clone(), keychain_mask, id)
+	}
+
+	/// Creates an encrypted backup of the wallet data: the seed file, the wallet DB, the swap
+	/// trades, the saved transactions and proofs. The backup is encrypted with the key derived
+	/// from the wallet seed, the restore needs the recovery phrase.
+	///
+	/// # Arguments
+	///
+	/// * `keychain_mask` - Wallet secret mask to XOR against the stored wallet seed before using, if
+	/// being used.
+	/// * `dest` - Directory or `s3://<bucket>/<prefix>`. If `None`, the backup is created at the
+	/// `backup_dest` of the wallet config and the old backups are deleted according to `backup_keep`.
+	///
+	/// # Returns
+	/// * Ok with the location of the created backup
+	/// * or [`libwallet::Error`](../grin_wallet_libwallet/struct.Error.html) if an error is encountered.
+	///
+	/// # Example
+	/// Set up as in [`new`](struct.Owner.html#method.new) method above.
+	/// ```
+	/// # grin_wallet_api::doctest_helper_setup_doc_env!(wallet, wallet_config);
+	///
+	/// let api_owner = Owner::new(wallet.clone(), None, None);
+	///
+	/// let dest = std::env::temp_dir().join("mwc_backups");
+	/// let res = api_owner.backup_wallet(None, Some(dest.to_str().unwrap().to_string()));
+	///
+	/// if let Ok(location) = res {
+	///   println!("Backup is created at {}", location);
+	/// }
+	/// ```
+
+	pub fn backup_wallet(
+		&self,
+		keychain_mask: Option<&SecretKey>,
+		dest: Option<String>,
+	) -> Result<String, Error> {
+		self.audit("backup_wallet", format!("dest: {:?}", dest), || {
+			let (store, keep) = match &dest {
+				Some(d) => (create_backup_store(d)?, 0),
+				None => match backup::get_backup_schedule() {
+					Some(s) => (s.store, s.keep),
+					None => {
+						return Err(ErrorKind::Backup(
+							"Backup destination is not set, specify it or set backup_dest in the wallet config"
+								.to_string(),
+						)
+						.into())
+					}
+				},
+			};
+			let name =
+				backup::backup_wallet(self.wallet_inst.clone(), keychain_mask, &*store, keep)?;
+			Ok(format!(
+				"{}/{}",
+				store.location().trim_end_matches('/'),
+				name
+			))
+		})
+	}
+
 	// decryipt income slate. It is the common routine for most API calls that accept the slates
 	// Note, the merge case if not covered by this API.
 	pub fn decrypt_versioned_slate(
@@ -2610,6 +5208,35 @@ where
 			use_test_rng,
 		)
 	}
+
+	// Balance of the active account for the audit record, None if the wallet is not open
+	fn audit_balance(&self) -> Option<AuditBalance> {
+		let mut w_lock = self.wallet_inst.lock();
+		let w = w_lock.lc_provider().ok()?.wallet_inst().ok()?;
+		Some(AuditBalance::from_wallet(&**w))
+	}
+
+	// Run the state changing operation and write it into the audit log if it is enabled.
+	// The operation is rejected in maintenance mode.
+	fn audit<R, F>(&self, operation: &str, details: String, f: F) -> Result<R, Error>
+	where
+		F: FnOnce() -> Result<R, Error>,
+	{
+		let _operation = owner::begin_wallet_operation()?;
+		if !audit_log::is_enabled() {
+			return f();
+		}
+		let balance_before = self.audit_balance();
+		let res = f();
+		audit_log::record_operation(
+			operation,
+			details,
+			res.as_ref().err().map(|e| e.to_string()),
+			balance_before,
+			self.audit_balance(),
+		);
+		res
+	}
 }
 
 #[doc(hidden)]
@@ -2639,7 +5266,7 @@ macro_rules! doctest_helper_setup_doc_env {
 
 		grin_wallet_util::grin_core::global::set_local_chain_type(
 			grin_wallet_util::grin_core::global::ChainTypes::AutomatedTesting,
-			);
+		);
 
 		let dir = tempdir().map_err(|e| format!("{:#?}", e)).unwrap();
 		let dir = dir
@@ -2655,7 +5282,7 @@ macro_rules! doctest_helper_setup_doc_env {
 		let node_client = HTTPNodeClient::new(node_list, None).unwrap();
 		let mut wallet = Box::new(
 			DefaultWalletImpl::<'static, HTTPNodeClient>::new(node_client.clone()).unwrap(),
-			)
+		)
 			as Box<
 				WalletInst<
 					'static,
@@ -2663,7 +5290,7 @@ macro_rules! doctest_helper_setup_doc_env {
 					HTTPNodeClient,
 					ExtKeychain,
 				>,
-				>;
+			>;
 		let lc = wallet.lc_provider().unwrap();
 		let _ = lc.set_top_level_directory(&wallet_config.data_file_dir);
 		lc.open_wallet(None, pw, false, false, None);
@@ -18,18 +18,21 @@
 use uuid::Uuid;
 
 use crate::core::core::Transaction;
+use crate::impls::MqsQueuedMessage;
 use crate::keychain::{Identifier, Keychain};
+use crate::libwallet::slate_versions::grin_v4::SlateGrinV4;
 use crate::libwallet::slate_versions::v3::TransactionV3;
 use crate::libwallet::{
-	AcctPathMapping, ErrorKind, InitTxArgs, IssueInvoiceTxArgs, NodeClient, NodeHeightResult,
-	OutputCommitMapping, PaymentProof, Slate, SlatePurpose, SlateVersion, StatusMessage,
-	TxLogEntry, VersionedSlate, WalletInfo, WalletLCProvider,
+	AcctPathMapping, AutoCancelPolicy, ConsensusParams, ErrorKind, HistoryBucket, InitTxArgs,
+	IssueInvoiceTxArgs, NodeClient, NodeHeightResult, OutputCommitMapping, OutputQueryArgs,
+	PaymentProof, ScanProgress, Slate, SlatePurpose, SlateVersion, StatusMessage, TxLogEntry,
+	TxQueryArgs, VersionedSlate, WalletInfo, WalletLCProvider, GRIN_V4_TARGET_SLATE_VERSION,
 };
 use crate::types::{SlatepackInfo, TxLogEntryAPI};
 use crate::util;
 use crate::util::secp::pedersen;
 use crate::util::Mutex;
-use crate::{Owner, OwnerRpcV3};
+use crate::{ConfigReloadStatus, Owner, OwnerRpcV3};
 use easy_jsonrpc_mw;
 use ed25519_dalek::PublicKey as DalekPublicKey;
 use grin_wallet_libwallet::proof::proofaddress::{self, ProvableAddress};
@@ -184,7 +187,8 @@ pub trait OwnerRpcV2: Sync + Send {
 				"status": "Unspent",
 				"tx_log_entry": 0,
 				"value": "2380952380"
-			  }
+			  },
+			  "spendable_at_height": "4"
 			},
 			{
 			  "commit": "098778ce2243fa34e5876c8cb7f6dbbbd6a5649c1561973a807a6811941c12363c",
@@ -200,7 +204,8 @@ pub trait OwnerRpcV2: Sync + Send {
 				"status": "Unspent",
 				"tx_log_entry": 1,
 				"value": "2380952380"
-			  }
+			  },
+			  "spendable_at_height": "5"
 			}
 		  ]
 		]
@@ -267,6 +272,7 @@ pub trait OwnerRpcV2: Sync + Send {
 				  "output_height": 1,
 				  "parent_key_id": "0200000000000000000000000000000000",
 				  "payment_proof": null,
+				  "status": "Confirmed",
 				  "stored_tx": null,
 				  "ttl_cutoff_height": null,
 				  "tx_slate_id": null,
@@ -294,6 +300,7 @@ pub trait OwnerRpcV2: Sync + Send {
 				  "output_height": 2,
 				  "parent_key_id": "0200000000000000000000000000000000",
 				  "payment_proof": null,
+				  "status": "Confirmed",
 				  "stored_tx": null,
 				  "ttl_cutoff_height": null,
 				  "tx_slate_id": null,
@@ -315,6 +322,97 @@ pub trait OwnerRpcV2: Sync + Send {
 		tx_slate_id: Option<Uuid>,
 	) -> Result<(bool, Vec<TxLogEntryAPI>), ErrorKind>;
 
+	/**
+	Networked version of [Owner::query_outputs](struct.Owner.html#method.query_outputs).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "query_outputs",
+		"params": {
+			"refresh_from_node": false,
+			"query": {
+				"offset": 0,
+				"limit": 20,
+				"min_height": null,
+				"max_height": null,
+				"min_value": "1000000000",
+				"max_value": null,
+				"status": ["Unspent", "Locked"]
+			}
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": [
+				false,
+				[]
+			]
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn query_outputs(
+		&self,
+		refresh_from_node: bool,
+		query: OutputQueryArgs,
+	) -> Result<(bool, Vec<OutputCommitMapping>), ErrorKind>;
+
+	/**
+	Networked version of [Owner::query_txs](struct.Owner.html#method.query_txs).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "query_txs",
+		"params": {
+			"refresh_from_node": false,
+			"query": {
+				"offset": 0,
+				"limit": 20,
+				"min_creation_ts": 1547510400,
+				"max_creation_ts": null,
+				"min_amount": null,
+				"max_amount": null,
+				"tx_types": ["TxReceived", "TxSent"],
+				"confirmed": true
+			}
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": [
+				false,
+				[]
+			]
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn query_txs(
+		&self,
+		refresh_from_node: bool,
+		query: TxQueryArgs,
+	) -> Result<(bool, Vec<TxLogEntryAPI>), ErrorKind>;
+
 	/**
 	Networked version of [Owner::retrieve_summary_info](struct.Owner.html#method.retrieve_summary_info).
 
@@ -345,6 +443,7 @@ pub trait OwnerRpcV2: Sync + Send {
 			"amount_currently_spendable": "2380952380",
 			"amount_immature": "7142857140",
 			"amount_locked": "0",
+			"immature_spendable_at_height": "5",
 			"last_confirmed_height": "4",
 			"minimum_confirmations": "1",
 			"total": "9523809520"
@@ -2177,6 +2276,308 @@ pub trait OwnerRpcV2: Sync + Send {
 	*/
 	fn stop_updater(&self) -> Result<(), ErrorKind>;
 
+	/**
+	Networked version of [Owner::pause](struct.Owner.html#method.pause).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "pause",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn pause(&self) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::resume](struct.Owner.html#method.resume).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "resume",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn resume(&self) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::is_paused](struct.Owner.html#method.is_paused).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "is_paused",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": false
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn is_paused(&self) -> Result<bool, ErrorKind>;
+
+	/**
+	Networked version of [Owner::reload_config](struct.Owner.html#method.reload_config).
+	The test wallet has no config file, so the call is failing here.
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "reload_config",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"GenericError": "Wallet config is not loaded, nothing to reload"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn reload_config(&self) -> Result<ConfigReloadStatus, ErrorKind>;
+
+	/**
+	Networked version of [Owner::set_auto_cancel_policy](struct.Owner.html#method.set_auto_cancel_policy).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "set_auto_cancel_policy",
+		"params": {
+			"policy": {
+				"cancel_on_ttl": true,
+				"max_age_hours": "48",
+				"receive_expire_days": "7"
+			}
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn set_auto_cancel_policy(&self, policy: AutoCancelPolicy) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_auto_cancel_policy](struct.Owner.html#method.get_auto_cancel_policy).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_auto_cancel_policy",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": {
+				"cancel_on_ttl": true,
+				"max_age_hours": null,
+				"receive_expire_days": null
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn get_auto_cancel_policy(&self) -> Result<AutoCancelPolicy, ErrorKind>;
+
+	/**
+	Networked version of [Owner::cancel_expired_receives](struct.Owner.html#method.cancel_expired_receives).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "cancel_expired_receives",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": []
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn cancel_expired_receives(&self) -> Result<Vec<u32>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_scan_progress](struct.Owner.html#method.get_scan_progress).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_scan_progress",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": {
+				"running": false,
+				"percent": 0,
+				"height": 0,
+				"tip_height": 0,
+				"outputs_found": 0
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn get_scan_progress(&self) -> Result<ScanProgress, ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_consensus_params](struct.Owner.html#method.get_consensus_params).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_consensus_params",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": {
+				"base_fee": "1000000",
+				"coinbase_maturity": "3",
+				"current_height": "0",
+				"input_weight": -1,
+				"kernel_weight": 1,
+				"min_tx_fee": "4000000",
+				"output_weight": 4
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn get_consensus_params(&self) -> Result<ConsensusParams, ErrorKind>;
+
+	/**
+	Networked version of [Owner::retrieve_history_series](struct.Owner.html#method.retrieve_history_series).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "retrieve_history_series",
+		"params": {
+			"bucket_sec": 86400
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": []
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn retrieve_history_series(
+		&self,
+		bucket_sec: Option<u64>,
+	) -> Result<Vec<HistoryBucket>, ErrorKind>;
+
 	/**
 	Networked version of [Owner::get_updater_messages](struct.Owner.html#method.get_updater_messages).
 	```
@@ -2239,6 +2640,64 @@ pub trait OwnerRpcV2: Sync + Send {
 
 	fn get_mqs_address(&self) -> Result<ProvableAddress, ErrorKind>;
 
+	/**
+	Networked version of [Owner::get_mqs_outbox](struct.Owner.html#method.get_mqs_outbox).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_mqs_outbox",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+	  "id": 1,
+	  "jsonrpc": "2.0",
+	  "result": {
+		"Ok": []
+	  }
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+
+	fn get_mqs_outbox(&self) -> Result<Vec<MqsQueuedMessage>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::flush_mqs_outbox](struct.Owner.html#method.flush_mqs_outbox).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "flush_mqs_outbox",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+	  "id": 1,
+	  "jsonrpc": "2.0",
+	  "result": {
+		"Err": {
+		  "GenericError": "Unable to deliver the queued messages, MQS error: MQS is not started, not able to deliver the queued messages"
+		}
+	  }
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+
+	fn flush_mqs_outbox(&self) -> Result<Vec<MqsQueuedMessage>, ErrorKind>;
+
 	/**
 	Networked version of [Owner::get_wallet_public_address](struct.Owner.html#method.get_wallet_public_address).
 	```
@@ -2847,6 +3306,31 @@ where
 			})
 	}
 
+	fn query_outputs(
+		&self,
+		refresh_from_node: bool,
+		query: OutputQueryArgs,
+	) -> Result<(bool, Vec<OutputCommitMapping>), ErrorKind> {
+		Owner::query_outputs(self, None, refresh_from_node, &query).map_err(|e| e.kind())
+	}
+
+	fn query_txs(
+		&self,
+		refresh_from_node: bool,
+		query: TxQueryArgs,
+	) -> Result<(bool, Vec<TxLogEntryAPI>), ErrorKind> {
+		Owner::query_txs(self, None, refresh_from_node, &query)
+			.map_err(|e| e.kind())
+			.map(|(b, tx)| {
+				(
+					b,
+					tx.iter()
+						.map(|t| TxLogEntryAPI::from_txlogemtry(t))
+						.collect(),
+				)
+			})
+	}
+
 	fn retrieve_summary_info(
 		&self,
 		refresh_from_node: bool,
@@ -2863,6 +3347,11 @@ where
 		// handle the workflow in lower level.
 		// If caller did send with thius API - then the slate is just for logging. For logging it is
 		// better to have plain slate so it can be readable.
+		if args.target_slate_version == Some(GRIN_V4_TARGET_SLATE_VERSION) {
+			let slate = SlateGrinV4::from_slate(&slate, &SlatePurpose::SendInitial)
+				.map_err(|e| ErrorKind::SlatepackEncodeError(format!("{}", e)))?;
+			return Ok(VersionedSlate::GV4(slate));
+		}
 		let version = slate.lowest_version();
 		Ok(VersionedSlate::into_version_plain(slate, version)
 			.map_err(|e| ErrorKind::SlatepackEncodeError(format!("{}", e)))?)
@@ -3043,6 +3532,49 @@ where
 		Owner::stop_updater(self).map_err(|e| e.kind())
 	}
 
+	fn pause(&self) -> Result<(), ErrorKind> {
+		Owner::pause(self).map_err(|e| e.kind())
+	}
+
+	fn resume(&self) -> Result<(), ErrorKind> {
+		Owner::resume(self).map_err(|e| e.kind())
+	}
+
+	fn is_paused(&self) -> Result<bool, ErrorKind> {
+		Ok(Owner::is_paused(self))
+	}
+
+	fn reload_config(&self) -> Result<ConfigReloadStatus, ErrorKind> {
+		Owner::reload_config(self).map_err(|e| e.kind())
+	}
+
+	fn set_auto_cancel_policy(&self, policy: AutoCancelPolicy) -> Result<(), ErrorKind> {
+		Owner::set_auto_cancel_policy(self, policy).map_err(|e| e.kind())
+	}
+
+	fn get_auto_cancel_policy(&self) -> Result<AutoCancelPolicy, ErrorKind> {
+		Owner::get_auto_cancel_policy(self).map_err(|e| e.kind())
+	}
+
+	fn cancel_expired_receives(&self) -> Result<Vec<u32>, ErrorKind> {
+		Owner::cancel_expired_receives(self, None).map_err(|e| e.kind())
+	}
+
+	fn get_scan_progress(&self) -> Result<ScanProgress, ErrorKind> {
+		Owner::get_scan_progress(self).map_err(|e| e.kind())
+	}
+
+	fn get_consensus_params(&self) -> Result<ConsensusParams, ErrorKind> {
+		Owner::get_consensus_params(self).map_err(|e| e.kind())
+	}
+
+	fn retrieve_history_series(
+		&self,
+		bucket_sec: Option<u64>,
+	) -> Result<Vec<HistoryBucket>, ErrorKind> {
+		Owner::retrieve_history_series(self, None, bucket_sec).map_err(|e| e.kind())
+	}
+
 	fn get_updater_messages(&self, count: u32) -> Result<Vec<StatusMessage>, ErrorKind> {
 		Owner::get_updater_messages(self, count as usize).map_err(|e| e.kind())
 	}
@@ -3054,6 +3586,14 @@ where
 		Ok(public_proof_address)
 	}
 
+	fn get_mqs_outbox(&self) -> Result<Vec<MqsQueuedMessage>, ErrorKind> {
+		Owner::get_mqs_outbox(self).map_err(|e| e.kind())
+	}
+
+	fn flush_mqs_outbox(&self) -> Result<Vec<MqsQueuedMessage>, ErrorKind> {
+		Owner::flush_mqs_outbox(self).map_err(|e| e.kind())
+	}
+
 	fn get_wallet_public_address(&self) -> Result<ProvableAddress, ErrorKind> {
 		let address = Owner::get_wallet_public_address(self, None).map_err(|e| e.kind())?;
 		let address = ProvableAddress::from_tor_pub_key(&address);
@@ -3447,7 +3987,7 @@ pub fn run_doctest_owner(
 	}
 
 	if payment_proof {
-		api_impl::owner::post_tx(&client1, &slate_outer.tx, true).unwrap();
+		api_impl::owner::post_tx(&client1, &slate_outer.tx, Some(slate_outer.id), true).unwrap();
 	}
 
 	if perform_tx && lock_tx && finalize_tx {
@@ -18,12 +18,23 @@ use uuid::Uuid;
 use crate::config::{MQSConfig, TorConfig, WalletConfig};
 use crate::core::core::Transaction;
 use crate::core::global;
+use crate::impls::MqsQueuedMessage;
 use crate::keychain::{Identifier, Keychain};
+use crate::libwallet::listener_limits::ListenerMetrics;
+use crate::libwallet::listener_status::ListenerStatus;
+use crate::libwallet::node_cache::NodeCacheMetrics;
+use crate::libwallet::payment_id::ResolvedPaymentId;
+use crate::libwallet::price::ExchangeRate;
+use crate::libwallet::slate_versions::grin_v4::SlateGrinV4;
 use crate::libwallet::slate_versions::v3::TransactionV3;
+use crate::libwallet::tx_import::{TxImportRecord, TxImportReport};
 use crate::libwallet::{
-	AcctPathMapping, ErrorKind, InitTxArgs, IssueInvoiceTxArgs, NodeClient, NodeHeightResult,
-	OutputCommitMapping, PaymentProof, Slate, SlatePurpose, SlateVersion, StatusMessage,
-	TxLogEntry, VersionedSlate, WalletInfo, WalletLCProvider,
+	AccountSummaryInfo, AcctPathMapping, AddressMapping, AutoCancelPolicy, CheckCategory,
+	CheckReport, ConsensusParams, ErrorKind, HistoryBucket, InitTxArgs, IssueInvoiceTxArgs,
+	KernelLookupResult, NodeClient, NodeHeightResult, OutputCommitMapping, OutputQueryArgs,
+	OutputTagArgs, PaymentProof, PublicProofAddress, ScanProgress, Slate, SlatePurpose,
+	SlateVersion, StatusMessage, TxLabelArgs, TxLogEntry, TxQueryArgs, UtxoSnapshot,
+	VersionedSlate, WalletInfo, WalletLCProvider, GRIN_V4_TARGET_SLATE_VERSION,
 };
 use crate::types::{SlatepackInfo, TxLogEntryAPI};
 use crate::util;
@@ -31,7 +42,8 @@ use crate::util::logger::LoggingConfig;
 use crate::util::secp::key::{PublicKey, SecretKey};
 use crate::util::secp::pedersen;
 use crate::util::{static_secp_instance, ZeroingString};
-use crate::{ECDHPubkey, Owner, Token};
+use crate::{ConfigReloadStatus, ECDHPubkey, Owner, Token};
+use chrono::NaiveDate;
 use easy_jsonrpc_mw;
 use ed25519_dalek::PublicKey as DalekPublicKey;
 use grin_wallet_libwallet::proof::proofaddress::ProvableAddress;
@@ -149,6 +161,78 @@ pub trait OwnerRpcV3 {
 	 */
 	fn set_active_account(&self, token: Token, label: &String) -> Result<(), ErrorKind>;
 
+	/**
+	Networked version of [Owner::rename_account](struct.Owner.html#method.rename_account).
+
+	# Json rpc example
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "rename_account",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"old_label": "default",
+			"new_label": "savings"
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": "AccountDefaultCannotBeRenamed"
+		},
+		"id": 1
+	}
+	# "#
+	# , true, 4, false, false, false, false, true);
+	```
+	 */
+	fn rename_account(
+		&self,
+		token: Token,
+		old_label: &String,
+		new_label: &String,
+	) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::delete_account](struct.Owner.html#method.delete_account).
+
+	# Json rpc example
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "delete_account",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"label": "default"
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": "AccountDefaultCannotBeDeleted"
+		},
+		"id": 1
+	}
+	# "#
+	# , true, 4, false, false, false, false, true);
+	```
+	 */
+	fn delete_account(&self, token: Token, label: &String) -> Result<(), ErrorKind>;
+
 	/**
 	Networked version of [Owner::retrieve_outputs](struct.Owner.html#method.retrieve_outputs).
 
@@ -192,7 +276,8 @@ pub trait OwnerRpcV3 {
 				"status": "Unspent",
 				"tx_log_entry": 0,
 				"value": "2380952380"
-			  }
+			  },
+			  "spendable_at_height": "4"
 			},
 			{
 			  "commit": "098778ce2243fa34e5876c8cb7f6dbbbd6a5649c1561973a807a6811941c12363c",
@@ -208,7 +293,8 @@ pub trait OwnerRpcV3 {
 				"status": "Unspent",
 				"tx_log_entry": 1,
 				"value": "2380952380"
-			  }
+			  },
+			  "spendable_at_height": "5"
 			}
 		  ]
 		]
@@ -277,6 +363,7 @@ pub trait OwnerRpcV3 {
 				  "output_height": 1,
 				  "parent_key_id": "0200000000000000000000000000000000",
 				  "payment_proof": null,
+				  "status": "Confirmed",
 				  "stored_tx": null,
 				  "ttl_cutoff_height": null,
 				  "tx_slate_id": null,
@@ -304,6 +391,7 @@ pub trait OwnerRpcV3 {
 				  "output_height": 2,
 				  "parent_key_id": "0200000000000000000000000000000000",
 				  "payment_proof": null,
+				  "status": "Confirmed",
 				  "stored_tx": null,
 				  "ttl_cutoff_height": null,
 				  "tx_slate_id": null,
@@ -326,6 +414,101 @@ pub trait OwnerRpcV3 {
 		tx_slate_id: Option<Uuid>,
 	) -> Result<(bool, Vec<TxLogEntryAPI>), ErrorKind>;
 
+	/**
+	Networked version of [Owner::query_outputs](struct.Owner.html#method.query_outputs).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "query_outputs",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"refresh_from_node": false,
+			"query": {
+				"offset": 0,
+				"limit": 20,
+				"min_height": null,
+				"max_height": null,
+				"min_value": "1000000000",
+				"max_value": null,
+				"status": ["Unspent", "Locked"]
+			}
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": [
+				false,
+				[]
+			]
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn query_outputs(
+		&self,
+		token: Token,
+		refresh_from_node: bool,
+		query: OutputQueryArgs,
+	) -> Result<(bool, Vec<OutputCommitMapping>), ErrorKind>;
+
+	/**
+	Networked version of [Owner::query_txs](struct.Owner.html#method.query_txs).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "query_txs",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"refresh_from_node": false,
+			"query": {
+				"offset": 0,
+				"limit": 20,
+				"min_creation_ts": 1547510400,
+				"max_creation_ts": null,
+				"min_amount": null,
+				"max_amount": null,
+				"tx_types": ["TxReceived", "TxSent"],
+				"confirmed": true
+			}
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": [
+				false,
+				[]
+			]
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn query_txs(
+		&self,
+		token: Token,
+		refresh_from_node: bool,
+		query: TxQueryArgs,
+	) -> Result<(bool, Vec<TxLogEntryAPI>), ErrorKind>;
+
 	/**
 	Networked version of [Owner::retrieve_summary_info](struct.Owner.html#method.retrieve_summary_info).
 
@@ -357,6 +540,7 @@ pub trait OwnerRpcV3 {
 			"amount_currently_spendable": "2380952380",
 			"amount_immature": "7142857140",
 			"amount_locked": "0",
+			"immature_spendable_at_height": "5",
 			"last_confirmed_height": "4",
 			"minimum_confirmations": "1",
 			"total": "9523809520"
@@ -376,6 +560,63 @@ pub trait OwnerRpcV3 {
 		minimum_confirmations: u64,
 	) -> Result<(bool, WalletInfo), ErrorKind>;
 
+	/**
+	Networked version of [Owner::retrieve_accounts_summary_info](struct.Owner.html#method.retrieve_accounts_summary_info).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "retrieve_accounts_summary_info",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"refresh_from_node": true,
+			"minimum_confirmations": 1
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+	  "id": 1,
+	  "jsonrpc": "2.0",
+	  "result": {
+		"Ok": [
+		  true,
+		  [
+			{
+			  "info": {
+				"amount_awaiting_confirmation": "0",
+				"amount_awaiting_finalization": "0",
+				"amount_currently_spendable": "2380952380",
+				"amount_immature": "7142857140",
+				"amount_locked": "0",
+				"immature_spendable_at_height": "5",
+				"last_confirmed_height": "4",
+				"minimum_confirmations": "1",
+				"total": "9523809520"
+			  },
+			  "label": "default",
+			  "path": "0200000000000000000000000000000000"
+			}
+		  ]
+		]
+	  }
+	}
+	# "#
+	# ,true, 4, false, false, false, false, true);
+	```
+	 */
+
+	fn retrieve_accounts_summary_info(
+		&self,
+		token: Token,
+		refresh_from_node: bool,
+		minimum_confirmations: u64,
+	) -> Result<(bool, Vec<AccountSummaryInfo>), ErrorKind>;
+
 	/**
 	Networked version of [Owner::init_send_tx](struct.Owner.html#method.init_send_tx).
 	```
@@ -1946,88 +2187,243 @@ pub trait OwnerRpcV3 {
 	) -> Result<(), ErrorKind>;
 
 	/**
-	Networked version of [Owner::get_stored_tx](struct.Owner.html#method.get_stored_tx).
+	Networked version of [Owner::set_tx_label](struct.Owner.html#method.set_tx_label).
 
 	```
 	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
 	# r#"
 	{
 		"jsonrpc": "2.0",
-		"method": "get_stored_tx",
-		"id": 1,
+		"method": "set_tx_label",
 		"params": {
 			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
-			"tx": {
-				"stored_tx": "0436430c-2b02-624c-2032-570501212b00.mwctx",
-				"tx_slate_id": "0436430c-2b02-624c-2032-570501212b00",
-				"tx_type": "TxSent"
+			"args": {
+				"tx_id": 1,
+				"label": "exchange withdrawal",
+				"memo": "Moved to the cold storage"
 			}
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
 		}
 	}
 	# "#
+	# , true, 5, false, false, false, false, true);
+	```
+	 */
+	fn set_tx_label(&self, token: Token, args: TxLabelArgs) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::set_tx_confirmation_target](struct.Owner.html#method.set_tx_confirmation_target).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "set_tx_confirmation_target",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"tx_id": 1,
+			"target": 60
+		},
+		"id": 1
+	}
+	# "#
 	# ,
 	# r#"
 	{
-	  "id": 1,
-	  "jsonrpc": "2.0",
-	  "result": {
-		"Ok": {
-		  "body": {
-			"inputs": [
-			  {
-				"commit": "098778ce2243fa34e5876c8cb7f6dbbbd6a5649c1561973a807a6811941c12363c",
-				"features": "Coinbase"
-			  },
-			  {
-				"commit": "0910c1752100733bae49e877286835aab76d5856ef8139b6c6e3f51798aa461b03",
-				"features": "Coinbase"
-			  }
-			],
-			"kernels": [
-			  {
-				"excess": "000000000000000000000000000000000000000000000000000000000000000000",
-				"excess_sig": "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
-				"features": "Plain",
-				"fee": "7000000",
-				"lock_height": "0"
-			  }
-			],
-			"outputs": [
-			  {
-				"commit": "082967b3fe580cd110355010ef45450314fb067720db01b0e6873bb083d76708c9",
-				"features": "Plain",
-				"proof": "828bb24121aa0332c872062a42a8333c3ef81f8ae37d24053d953217368b3cada90410a50509a0b9fcbb5aded41397fc00ca1ff5acdac20d48afb0a3281d21e7026d32fdc6c5157461a35f98a809ffa09187c1e170ea24652ad213b7e4c9878654ac3dd9a8915eaf742db53182fcb42d2d341fbdfe8bd31bd001f4ff2c1ca9f9b1531da29137214f211edb7a5eb8f494cb8945f8527dd25bf7e698515043db4249540720008a708db5342230d05b069c094688ccb7c07d4a4a2293ea76cf999c555dc0ddc757891c360db1901bbb4dc20cae997f875f8de482d8160e05d60f9b0135e0fc313d8f953db78f1ea252449dd81cfa22dd895512ed39d566f0924542b543d25fc9fc7a819d228f3b0ee5e381f088f54893e86437dafc49dd923b3e6dff956ca843f951910379531fac9bb5fd01a182dd32a4c597f92da3c01af37cb9b0ec984500884438e74e54d7e76fa1ae7241d5050b13376310b24761634a6f6eb7cf000082f50ed7c1899d7918023d4f877586f964932a7af72e7a4984ddecfdd1921a2e1b80b00d6bd2e64a3f4cb6915a27a8d17a69d163cf45220a13fcddd15dc2bb91ae4f1b6a67224ab3b23e8d7d785df178ec78a84cf42cea086426f563822c8a4271a0b89bb21f84b643dbf1de21b6395039d673a376492767199fa36ccd9a13628ce61695424091acc16059450d59bc59fa7879e7306f5727217211b0264a6a560f886d520e41406ef45b1668805b88d246c5b2ca5a1762042c85be34fcd420ac3843f32236d079b4bd57d6b8d8013d9d18f8efb55e8e443cd9e1af9b144e7a56c8c6be0138af3b4a6c99bee9109bed2bce2e5145e736b125a2ec19aaf3fff713f6897fdd4158ce2ab04706b062ca2847bf70259c0fc4b0d390dc7fdaf0362047f775a912bd22da9d40f04d9790bcd5ece4b36b74c6c340b48c2926b916e8a9"
-			  },
-			  {
-				"commit": "096e1669267c22ecb38c466d73b8578261d8e91c14dd66702dd5bf34f4232e10db",
-				"features": "Plain",
-				"proof": "bdb629cdbaa68943426d977eb6fa804de99b3962ab4e1055aa49607ee2b2a08b69713b915b803812587de635002354fa96884f8bb0732b4fd7e700b4b132f0080d1890f042091247c09d7becd1ac8f0e0bae3b5efebdfd4c3e4ce1249da42d246f8e68d83ff897e61cfc145fe376fa428b55e25cf20d15f10b9054778229798b301d4436c39a07d65fb2adfb8095d804a062bdcd10b769abb7ce9f4cbb9d9fbcbe08b56264fc295c3facb6dfa4dfac41a6ee056b29b1af9bf3ce6223f125a274b563c0b2599214208d6b4f109158eb11b06875a8fcbb3ed95fd8fb23e23186cd0353498436a940bd0acec8c5b93c45a299771e0b3f5cc7e8af034d3717700cc05e73932287f1bf2c1dc71abe5f0a6c6e84839c3cd18e77b8dc93c40f6b86b42808d5eaf98cc4597cfc8f4b56aae06cf2478bce60119556d0e0900b43e02426c8b9b0b5a6fd739a30deb2f67563512ad95e82ec88e18d303cbbc39dd1455b4dbb9b010174bfd7db92c8f56f3286f97e18601eb922e268822de30332d6fcf614a1c9012c317bcbf808ef836377b21437f9023a646cd4696a0ab7a544ee8e9692df50288e4765fff311543070d6623b4c5508d10f25ddbd130f56bbb5458ab8a6af519ec2c9d24b19c9b55e8024abb8193e4e3bf455986c38cca230ac0a6d05543a870a7fccd6a4fa507516f2fe344f1343e2a5dbdd5962121dd69106d80283f704c9a2211b6cbbe547075ab23d2c7962443a0749635505fcedd9740b7eaee7ae9c653fb0b55167eec7570ad27d7a0e5df21ccea82f986aa6650adc0659d5a1b9fc6131248ebfc08edcbd4a7fd88c8a882d6f8bf4fb59be82aec2740afdae5c424c83238a146cbf9210fafcf7b4eaecfb5cef3087a1710cc47a1ca703aaa811a90bbab9281ada7b27e9d5b67452bb2f9e9351ae63a4ccff826545382e7398dcf7743e0525"
-				}
-			]
-		  },
-		  "offset": "d202964900000000d302964900000000d402964900000000d502964900000000"
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
 		}
-	  }
 	}
 	# "#
-	# , true, 5, true, true, false, false, false);
+	# , true, 5, false, false, false, false, true);
 	```
 	 */
-	fn get_stored_tx(
+	fn set_tx_confirmation_target(
 		&self,
 		token: Token,
-		tx: &TxLogEntryAPI,
-	) -> Result<Option<TransactionV3>, ErrorKind>;
+		tx_id: u32,
+		target: Option<u64>,
+	) -> Result<(), ErrorKind>;
 
 	/**
-	Networked version of [Owner::verify_slate_messages](struct.Owner.html#method.verify_slate_messages).
+	Networked version of [Owner::import_txs](struct.Owner.html#method.import_txs).
 
 	```
 	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
 	# r#"
 	{
 		"jsonrpc": "2.0",
-		"method": "verify_slate_messages",
+		"method": "import_txs",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"records": [
+				{
+					"tx_slate_id": "0fd2e1b7-5b24-4e6c-a5d4-0e7b5d0f4c3a",
+					"tx_type": "TxReceived",
+					"creation_ts": "2021-03-12T10:20:30Z",
+					"amount_credited": "2000000000"
+				}
+			],
+			"dry_run": true
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": {
+				"imported": [],
+				"imported_count": 1,
+				"skipped": []
+			}
+		}
+	}
+	# "#
+	# , true, 5, false, false, false, false, true);
+	```
+	 */
+	fn import_txs(
+		&self,
+		token: Token,
+		records: Vec<TxImportRecord>,
+		dry_run: bool,
+	) -> Result<TxImportReport, ErrorKind>;
+
+	/**
+	Networked version of [Owner::tag_output](struct.Owner.html#method.tag_output).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "tag_output",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"args": {
+				"commit": "0910c1752100733bae49e877286835aab76d5856ef8139b6c6e3f51798aa461b03",
+				"frozen": true,
+				"tags": ["swap"]
+			}
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , true, 2, false, false, false, false, true);
+	```
+	 */
+	fn tag_output(&self, token: Token, args: OutputTagArgs) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_stored_tx](struct.Owner.html#method.get_stored_tx).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_stored_tx",
+		"id": 1,
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"tx": {
+				"stored_tx": "0436430c-2b02-624c-2032-570501212b00.mwctx",
+				"tx_slate_id": "0436430c-2b02-624c-2032-570501212b00",
+				"tx_type": "TxSent"
+			}
+		}
+	}
+	# "#
+	# ,
+	# r#"
+	{
+	  "id": 1,
+	  "jsonrpc": "2.0",
+	  "result": {
+		"Ok": {
+		  "body": {
+			"inputs": [
+			  {
+				"commit": "098778ce2243fa34e5876c8cb7f6dbbbd6a5649c1561973a807a6811941c12363c",
+				"features": "Coinbase"
+			  },
+			  {
+				"commit": "0910c1752100733bae49e877286835aab76d5856ef8139b6c6e3f51798aa461b03",
+				"features": "Coinbase"
+			  }
+			],
+			"kernels": [
+			  {
+				"excess": "000000000000000000000000000000000000000000000000000000000000000000",
+				"excess_sig": "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+				"features": "Plain",
+				"fee": "7000000",
+				"lock_height": "0"
+			  }
+			],
+			"outputs": [
+			  {
+				"commit": "082967b3fe580cd110355010ef45450314fb067720db01b0e6873bb083d76708c9",
+				"features": "Plain",
+				"proof": "828bb24121aa0332c872062a42a8333c3ef81f8ae37d24053d953217368b3cada90410a50509a0b9fcbb5aded41397fc00ca1ff5acdac20d48afb0a3281d21e7026d32fdc6c5157461a35f98a809ffa09187c1e170ea24652ad213b7e4c9878654ac3dd9a8915eaf742db53182fcb42d2d341fbdfe8bd31bd001f4ff2c1ca9f9b1531da29137214f211edb7a5eb8f494cb8945f8527dd25bf7e698515043db4249540720008a708db5342230d05b069c094688ccb7c07d4a4a2293ea76cf999c555dc0ddc757891c360db1901bbb4dc20cae997f875f8de482d8160e05d60f9b0135e0fc313d8f953db78f1ea252449dd81cfa22dd895512ed39d566f0924542b543d25fc9fc7a819d228f3b0ee5e381f088f54893e86437dafc49dd923b3e6dff956ca843f951910379531fac9bb5fd01a182dd32a4c597f92da3c01af37cb9b0ec984500884438e74e54d7e76fa1ae7241d5050b13376310b24761634a6f6eb7cf000082f50ed7c1899d7918023d4f877586f964932a7af72e7a4984ddecfdd1921a2e1b80b00d6bd2e64a3f4cb6915a27a8d17a69d163cf45220a13fcddd15dc2bb91ae4f1b6a67224ab3b23e8d7d785df178ec78a84cf42cea086426f563822c8a4271a0b89bb21f84b643dbf1de21b6395039d673a376492767199fa36ccd9a13628ce61695424091acc16059450d59bc59fa7879e7306f5727217211b0264a6a560f886d520e41406ef45b1668805b88d246c5b2ca5a1762042c85be34fcd420ac3843f32236d079b4bd57d6b8d8013d9d18f8efb55e8e443cd9e1af9b144e7a56c8c6be0138af3b4a6c99bee9109bed2bce2e5145e736b125a2ec19aaf3fff713f6897fdd4158ce2ab04706b062ca2847bf70259c0fc4b0d390dc7fdaf0362047f775a912bd22da9d40f04d9790bcd5ece4b36b74c6c340b48c2926b916e8a9"
+			  },
+			  {
+				"commit": "096e1669267c22ecb38c466d73b8578261d8e91c14dd66702dd5bf34f4232e10db",
+				"features": "Plain",
+				"proof": "bdb629cdbaa68943426d977eb6fa804de99b3962ab4e1055aa49607ee2b2a08b69713b915b803812587de635002354fa96884f8bb0732b4fd7e700b4b132f0080d1890f042091247c09d7becd1ac8f0e0bae3b5efebdfd4c3e4ce1249da42d246f8e68d83ff897e61cfc145fe376fa428b55e25cf20d15f10b9054778229798b301d4436c39a07d65fb2adfb8095d804a062bdcd10b769abb7ce9f4cbb9d9fbcbe08b56264fc295c3facb6dfa4dfac41a6ee056b29b1af9bf3ce6223f125a274b563c0b2599214208d6b4f109158eb11b06875a8fcbb3ed95fd8fb23e23186cd0353498436a940bd0acec8c5b93c45a299771e0b3f5cc7e8af034d3717700cc05e73932287f1bf2c1dc71abe5f0a6c6e84839c3cd18e77b8dc93c40f6b86b42808d5eaf98cc4597cfc8f4b56aae06cf2478bce60119556d0e0900b43e02426c8b9b0b5a6fd739a30deb2f67563512ad95e82ec88e18d303cbbc39dd1455b4dbb9b010174bfd7db92c8f56f3286f97e18601eb922e268822de30332d6fcf614a1c9012c317bcbf808ef836377b21437f9023a646cd4696a0ab7a544ee8e9692df50288e4765fff311543070d6623b4c5508d10f25ddbd130f56bbb5458ab8a6af519ec2c9d24b19c9b55e8024abb8193e4e3bf455986c38cca230ac0a6d05543a870a7fccd6a4fa507516f2fe344f1343e2a5dbdd5962121dd69106d80283f704c9a2211b6cbbe547075ab23d2c7962443a0749635505fcedd9740b7eaee7ae9c653fb0b55167eec7570ad27d7a0e5df21ccea82f986aa6650adc0659d5a1b9fc6131248ebfc08edcbd4a7fd88c8a882d6f8bf4fb59be82aec2740afdae5c424c83238a146cbf9210fafcf7b4eaecfb5cef3087a1710cc47a1ca703aaa811a90bbab9281ada7b27e9d5b67452bb2f9e9351ae63a4ccff826545382e7398dcf7743e0525"
+				}
+			]
+		  },
+		  "offset": "d202964900000000d302964900000000d402964900000000d502964900000000"
+		}
+	  }
+	}
+	# "#
+	# , true, 5, true, true, false, false, false);
+	```
+	 */
+	fn get_stored_tx(
+		&self,
+		token: Token,
+		tx: &TxLogEntryAPI,
+	) -> Result<Option<TransactionV3>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::verify_slate_messages](struct.Owner.html#method.verify_slate_messages).
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "verify_slate_messages",
 		"id": 1,
 		"params": {
 			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
@@ -2139,6 +2535,52 @@ pub trait OwnerRpcV3 {
 		delete_unconfirmed: bool,
 	) -> Result<(), ErrorKind>;
 
+	/**
+	Networked version of [Owner::check](struct.Owner.html#method.check).
+
+
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "check",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"start_height": 1,
+			"fix": []
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": {
+				"start_height": 1,
+				"tip_height": 1,
+				"missing_outputs": [],
+				"spent_outputs": [],
+				"dangling_txs": [],
+				"orphaned_swaps": [],
+				"fixed": []
+			}
+		}
+	}
+	# "#
+	# , true, 1, false, false, false, false, true);
+	```
+	 */
+	fn check(
+		&self,
+		token: Token,
+		start_height: Option<u64>,
+		fix: Vec<CheckCategory>,
+	) -> Result<CheckReport, ErrorKind>;
+
 	/**
 	Networked version of [Owner::node_height](struct.Owner.html#method.node_height).
 
@@ -2532,15 +2974,855 @@ pub trait OwnerRpcV3 {
 	) -> Result<(), ErrorKind>;
 
 	/**
-	Networked version of [Owner::delete_wallet](struct.Owner.html#method.delete_wallet).
+	Networked version of [Owner::delete_wallet](struct.Owner.html#method.delete_wallet).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "delete_wallet",
+		"params": {
+			"name": null
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn delete_wallet(&self, name: Option<String>) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::start_updated](struct.Owner.html#method.start_updater).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "start_updater",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"frequency": 30000
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+
+	fn start_updater(&self, token: Token, frequency: u32) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::stop_updater](struct.Owner.html#method.stop_updater).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "stop_updater",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn stop_updater(&self) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::pause](struct.Owner.html#method.pause).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "pause",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn pause(&self) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::resume](struct.Owner.html#method.resume).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "resume",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn resume(&self) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::is_paused](struct.Owner.html#method.is_paused).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "is_paused",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": false
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn is_paused(&self) -> Result<bool, ErrorKind>;
+
+	/**
+	Networked version of [Owner::reload_config](struct.Owner.html#method.reload_config).
+	The test wallet has no config file, so the call is failing here.
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "reload_config",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"GenericError": "Wallet config is not loaded, nothing to reload"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn reload_config(&self) -> Result<ConfigReloadStatus, ErrorKind>;
+
+	/**
+	Networked version of [Owner::utxo_snapshot](struct.Owner.html#method.utxo_snapshot).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "utxo_snapshot",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"height": "1000"
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"GenericError": "Snapshot height 1000 is above the chain tip 5"
+			}
+		}
+	}
+	# "#
+	# , true, 5, false, false, false, false, true);
+	```
+	*/
+	fn utxo_snapshot(
+		&self,
+		token: Token,
+		height: Option<String>,
+	) -> Result<UtxoSnapshot, ErrorKind>;
+
+	/**
+	Networked version of [Owner::set_auto_cancel_policy](struct.Owner.html#method.set_auto_cancel_policy).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "set_auto_cancel_policy",
+		"params": {
+			"policy": {
+				"cancel_on_ttl": true,
+				"max_age_hours": "48",
+				"receive_expire_days": "7"
+			}
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn set_auto_cancel_policy(&self, policy: AutoCancelPolicy) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_auto_cancel_policy](struct.Owner.html#method.get_auto_cancel_policy).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_auto_cancel_policy",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": {
+				"cancel_on_ttl": true,
+				"max_age_hours": null,
+				"receive_expire_days": null
+			}
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn get_auto_cancel_policy(&self) -> Result<AutoCancelPolicy, ErrorKind>;
+
+	/**
+	Networked version of [Owner::cancel_expired_receives](struct.Owner.html#method.cancel_expired_receives).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "cancel_expired_receives",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000"
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": []
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn cancel_expired_receives(&self, token: Token) -> Result<Vec<u32>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_scan_progress](struct.Owner.html#method.get_scan_progress).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_scan_progress",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": {
+				"running": false,
+				"percent": 0,
+				"height": 0,
+				"tip_height": 0,
+				"outputs_found": 0
+			}
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn get_scan_progress(&self) -> Result<ScanProgress, ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_listener_metrics](struct.Owner.html#method.get_listener_metrics).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_listener_metrics",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": [
+				{
+					"listener": "owner",
+					"requests": 0,
+					"rate_limited": 0,
+					"concurrency_rejected": 0,
+					"in_flight": 0,
+					"max_in_flight": 0
+				},
+				{
+					"listener": "foreign",
+					"requests": 0,
+					"rate_limited": 0,
+					"concurrency_rejected": 0,
+					"in_flight": 0,
+					"max_in_flight": 0
+				}
+			]
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn get_listener_metrics(&self) -> Result<Vec<ListenerMetrics>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_node_cache_metrics](struct.Owner.html#method.get_node_cache_metrics).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_node_cache_metrics",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": [
+				{
+					"cache": "chain_tip",
+					"hits": 0,
+					"misses": 0
+				},
+				{
+					"cache": "header",
+					"hits": 0,
+					"misses": 0
+				},
+				{
+					"cache": "block",
+					"hits": 0,
+					"misses": 0
+				},
+				{
+					"cache": "kernel",
+					"hits": 0,
+					"misses": 0
+				},
+				{
+					"cache": "outputs",
+					"hits": 0,
+					"misses": 0
+				}
+			]
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn get_node_cache_metrics(&self) -> Result<Vec<NodeCacheMetrics>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::query_listeners](struct.Owner.html#method.query_listeners).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "query_listeners",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": [
+				{
+					"transport": "http",
+					"running": false,
+					"connected": null,
+					"address": null,
+					"started": null,
+					"last_message": null,
+					"messages": 0,
+					"errors": 0,
+					"last_error": null
+				},
+				{
+					"transport": "tor",
+					"running": false,
+					"connected": null,
+					"address": null,
+					"started": null,
+					"last_message": null,
+					"messages": 0,
+					"errors": 0,
+					"last_error": null
+				},
+				{
+					"transport": "mwcmqs",
+					"running": false,
+					"connected": null,
+					"address": null,
+					"started": null,
+					"last_message": null,
+					"messages": 0,
+					"errors": 0,
+					"last_error": null
+				}
+			]
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn query_listeners(&self) -> Result<Vec<ListenerStatus>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::start_listener](struct.Owner.html#method.start_listener).
+	The listeners of the test wallet are not managed, so the call is failing here.
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "start_listener",
+		"params": {
+			"transport": "mwcmqs"
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"GenericError": "Listeners are not managed by this wallet process, please start the wallet with owner_api command"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn start_listener(&self, transport: String) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::stop_listener](struct.Owner.html#method.stop_listener).
+	The listeners of the test wallet are not managed, so the call is failing here.
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "stop_listener",
+		"params": {
+			"transport": "mwcmqs"
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"GenericError": "Listeners are not managed by this wallet process, please start the wallet with owner_api command"
+			}
+		}
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+	fn stop_listener(&self, transport: String) -> Result<(), ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_consensus_params](struct.Owner.html#method.get_consensus_params).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_consensus_params",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": {
+				"base_fee": "1000000",
+				"coinbase_maturity": "3",
+				"current_height": "0",
+				"input_weight": -1,
+				"kernel_weight": 1,
+				"min_tx_fee": "4000000",
+				"output_weight": 4
+			}
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn get_consensus_params(&self) -> Result<ConsensusParams, ErrorKind>;
+
+	/**
+	Networked version of [Owner::retrieve_history_series](struct.Owner.html#method.retrieve_history_series).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "retrieve_history_series",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"bucket_sec": 86400
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": []
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn retrieve_history_series(
+		&self,
+		token: Token,
+		bucket_sec: Option<u64>,
+	) -> Result<Vec<HistoryBucket>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_exchange_rate](struct.Owner.html#method.get_exchange_rate).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_exchange_rate",
+		"params": {
+			"currency": "usd",
+			"date": "2021-03-12"
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"PriceFeed": "exchange rate source is not configured, please set price_source_url"
+			}
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn get_exchange_rate(
+		&self,
+		currency: String,
+		date: Option<NaiveDate>,
+	) -> Result<ExchangeRate, ErrorKind>;
+
+	/**
+	Networked version of [Owner::resolve_payment_id](struct.Owner.html#method.resolve_payment_id).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "resolve_payment_id",
+		"params": {
+			"payment_id": "alice@example.com"
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"PaymentId": "resolver is not configured, please set payment_id_doh_url"
+			}
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+	fn resolve_payment_id(&self, payment_id: String) -> Result<ResolvedPaymentId, ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_updater_messages](struct.Owner.html#method.get_updater_messages).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_updater_messages",
+		"params": {
+			"count": 1
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": []
+		}
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+
+	fn get_updater_messages(&self, count: u32) -> Result<Vec<StatusMessage>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_mqs_address](struct.Owner.html#method.get_mqs_address).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_mqs_address",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000"
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+	  "id": 1,
+	  "jsonrpc": "2.0",
+	  "result": {
+		"Ok": {
+		  "public_key": "xmgwbyjMEMBojnVadEkwVi1GyL1WPiVE5dziQf3TLedHdrVBPGw5",
+			"domain": "",
+		  "port": null
+		}
+	  }
+	}
+	# "#
+	# , true, 0, false, false, false, false, true);
+	```
+	*/
+
+	fn get_mqs_address(&self, token: Token) -> Result<ProvableAddress, ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_mqs_outbox](struct.Owner.html#method.get_mqs_outbox).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "get_mqs_outbox",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+	  "id": 1,
+	  "jsonrpc": "2.0",
+	  "result": {
+		"Ok": []
+	  }
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+
+	fn get_mqs_outbox(&self) -> Result<Vec<MqsQueuedMessage>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::flush_mqs_outbox](struct.Owner.html#method.flush_mqs_outbox).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "flush_mqs_outbox",
+		"params": null,
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+	  "id": 1,
+	  "jsonrpc": "2.0",
+	  "result": {
+		"Err": {
+		  "GenericError": "Unable to deliver the queued messages, MQS error: MQS is not started, not able to deliver the queued messages"
+		}
+	  }
+	}
+	# "#
+	# , false, 0, false, false, false, false, true);
+	```
+	*/
+
+	fn flush_mqs_outbox(&self) -> Result<Vec<MqsQueuedMessage>, ErrorKind>;
+
+	/**
+	Networked version of [Owner::get_wallet_public_address](struct.Owner.html#method.get_wallet_public_address).
 	```
 	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
 	# r#"
 	{
 		"jsonrpc": "2.0",
-		"method": "delete_wallet",
+		"method": "get_wallet_public_address",
 		"params": {
-			"name": null
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000"
 		},
 		"id": 1
 	}
@@ -2548,29 +3830,34 @@ pub trait OwnerRpcV3 {
 	# ,
 	# r#"
 	{
-		"id": 1,
-		"jsonrpc": "2.0",
-		"result": {
-			"Ok": null
+	  "id": 1,
+	  "jsonrpc": "2.0",
+	  "result": {
+		"Ok": {
+		  "public_key": "fffqrotuelaodwjblwmifg36xjedjw4azbwvfexmxmmzsb6xvzbkhuqd",
+		  "domain": "",
+		  "port": null
 		}
+	  }
 	}
 	# "#
 	# , true, 0, false, false, false, false, true);
 	```
 	*/
-	fn delete_wallet(&self, name: Option<String>) -> Result<(), ErrorKind>;
+
+	fn get_wallet_public_address(&self, token: Token) -> Result<ProvableAddress, ErrorKind>;
 
 	/**
-	Networked version of [Owner::start_updated](struct.Owner.html#method.start_updater).
+	Networked version of [Owner::get_public_proof_address](struct.Owner.html#method.get_public_proof_address).
 	```
 	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
 	# r#"
 	{
 		"jsonrpc": "2.0",
-		"method": "start_updater",
+		"method": "get_public_proof_address",
 		"params": {
 			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
-			"frequency": 30000
+			"index": 0
 		},
 		"id": 1
 	}
@@ -2581,25 +3868,42 @@ pub trait OwnerRpcV3 {
 		"id": 1,
 		"jsonrpc": "2.0",
 		"result": {
-			"Ok": null
+			"Ok": {
+				"index": 0,
+				"mqs_address": {
+					"public_key": "xmgwbyjMEMBojnVadEkwVi1GyL1WPiVE5dziQf3TLedHdrVBPGw5",
+					"domain": "",
+					"port": null
+				},
+				"tor_address": {
+					"public_key": "fffqrotuelaodwjblwmifg36xjedjw4azbwvfexmxmmzsb6xvzbkhuqd",
+					"domain": "",
+					"port": null
+				}
+			}
 		}
 	}
 	# "#
 	# , true, 0, false, false, false, false, true);
 	```
 	*/
-
-	fn start_updater(&self, token: Token, frequency: u32) -> Result<(), ErrorKind>;
+	fn get_public_proof_address(
+		&self,
+		token: Token,
+		index: u32,
+	) -> Result<PublicProofAddress, ErrorKind>;
 
 	/**
-	Networked version of [Owner::stop_updater](struct.Owner.html#method.stop_updater).
+	Networked version of [Owner::list_used_address_indices](struct.Owner.html#method.list_used_address_indices).
 	```
 	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
 	# r#"
 	{
 		"jsonrpc": "2.0",
-		"method": "stop_updater",
-		"params": null,
+		"method": "list_used_address_indices",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000"
+		},
 		"id": 1
 	}
 	# "#
@@ -2609,25 +3913,25 @@ pub trait OwnerRpcV3 {
 		"id": 1,
 		"jsonrpc": "2.0",
 		"result": {
-			"Ok": null
+			"Ok": []
 		}
 	}
 	# "#
 	# , true, 0, false, false, false, false, true);
 	```
 	*/
-	fn stop_updater(&self) -> Result<(), ErrorKind>;
+	fn list_used_address_indices(&self, token: Token) -> Result<Vec<u32>, ErrorKind>;
 
 	/**
-	Networked version of [Owner::get_updater_messages](struct.Owner.html#method.get_updater_messages).
+	Networked version of [Owner::address_mappings](struct.Owner.html#method.address_mappings).
 	```
 	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
 	# r#"
 	{
 		"jsonrpc": "2.0",
-		"method": "get_updater_messages",
+		"method": "address_mappings",
 		"params": {
-			"count": 1
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000"
 		},
 		"id": 1
 	}
@@ -2645,19 +3949,22 @@ pub trait OwnerRpcV3 {
 	# , true, 0, false, false, false, false, true);
 	```
 	*/
-
-	fn get_updater_messages(&self, count: u32) -> Result<Vec<StatusMessage>, ErrorKind>;
+	fn address_mappings(&self, token: Token) -> Result<Vec<AddressMapping>, ErrorKind>;
 
 	/**
-	Networked version of [Owner::get_mqs_address](struct.Owner.html#method.get_mqs_address).
+	Networked version of [Owner::set_address_mapping](struct.Owner.html#method.set_address_mapping).
 	```
 	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
 	# r#"
 	{
 		"jsonrpc": "2.0",
-		"method": "get_mqs_address",
+		"method": "set_address_mapping",
 		"params": {
-			"token": "d202964900000000d302964900000000d402964900000000d502964900000000"
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"mapping": {
+				"index": 42,
+				"label": "user 42"
+			}
 		},
 		"id": 1
 	}
@@ -2665,33 +3972,29 @@ pub trait OwnerRpcV3 {
 	# ,
 	# r#"
 	{
-	  "id": 1,
-	  "jsonrpc": "2.0",
-	  "result": {
-		"Ok": {
-		  "public_key": "xmgwbyjMEMBojnVadEkwVi1GyL1WPiVE5dziQf3TLedHdrVBPGw5",
-			"domain": "",
-		  "port": null
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": null
 		}
-	  }
 	}
 	# "#
 	# , true, 0, false, false, false, false, true);
 	```
 	*/
-
-	fn get_mqs_address(&self, token: Token) -> Result<ProvableAddress, ErrorKind>;
+	fn set_address_mapping(&self, token: Token, mapping: AddressMapping) -> Result<(), ErrorKind>;
 
 	/**
-	Networked version of [Owner::get_wallet_public_address](struct.Owner.html#method.get_wallet_public_address).
+	Networked version of [Owner::remove_address_mapping](struct.Owner.html#method.remove_address_mapping).
 	```
 	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
 	# r#"
 	{
 		"jsonrpc": "2.0",
-		"method": "get_wallet_public_address",
+		"method": "remove_address_mapping",
 		"params": {
-			"token": "d202964900000000d302964900000000d402964900000000d502964900000000"
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"index": 42
 		},
 		"id": 1
 	}
@@ -2699,22 +4002,19 @@ pub trait OwnerRpcV3 {
 	# ,
 	# r#"
 	{
-	  "id": 1,
-	  "jsonrpc": "2.0",
-	  "result": {
-		"Ok": {
-		  "public_key": "fffqrotuelaodwjblwmifg36xjedjw4azbwvfexmxmmzsb6xvzbkhuqd",
-		  "domain": "",
-		  "port": null
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Err": {
+				"AddressMapping": "address index 42 is not mapped"
+			}
 		}
-	  }
 	}
 	# "#
 	# , true, 0, false, false, false, false, true);
 	```
 	*/
-
-	fn get_wallet_public_address(&self, token: Token) -> Result<ProvableAddress, ErrorKind>;
+	fn remove_address_mapping(&self, token: Token, index: u32) -> Result<(), ErrorKind>;
 
 	/**
 	Networked version of [Owner::retrieve_payment_proof](struct.Owner.html#method.retrieve_payment_proof).
@@ -2910,6 +4210,44 @@ pub trait OwnerRpcV3 {
 		proof: PaymentProof,
 	) -> Result<(bool, bool), ErrorKind>;
 
+	/**
+	Networked version of [Owner::lookup_kernel](struct.Owner.html#method.lookup_kernel).
+	```
+	# grin_wallet_api::doctest_helper_json_rpc_owner_assert_response!(
+	# r#"
+	{
+		"jsonrpc": "2.0",
+		"method": "lookup_kernel",
+		"params": {
+			"token": "d202964900000000d302964900000000d402964900000000d502964900000000",
+			"excess": "08b1b12c28d7c4e7a2b0d0a0f0c7f6e0a1b2c3d4e5f60718293a4b5c6d7e8f9012"
+		},
+		"id": 1
+	}
+	# "#
+	# ,
+	# r#"
+	{
+		"id": 1,
+		"jsonrpc": "2.0",
+		"result": {
+			"Ok": {
+				"confirmations": "0",
+				"excess": "08b1b12c28d7c4e7a2b0d0a0f0c7f6e0a1b2c3d4e5f60718293a4b5c6d7e8f9012",
+				"height": null,
+				"mmr_index": null,
+				"on_chain": false,
+				"tip_height": "5",
+				"tx_id": null
+			}
+		}
+	}
+	# "#
+	# , true, 5, false, false, false, false, true);
+	```
+	*/
+	fn lookup_kernel(&self, token: Token, excess: String) -> Result<KernelLookupResult, ErrorKind>;
+
 	/**
 	Networked version of [Owner::set_tor_config](struct.Owner.html#method.set_tor_config).
 	```
@@ -3315,6 +4653,20 @@ where
 			.map_err(|e| e.kind())
 	}
 
+	fn rename_account(
+		&self,
+		token: Token,
+		old_label: &String,
+		new_label: &String,
+	) -> Result<(), ErrorKind> {
+		Owner::rename_account(self, (&token.keychain_mask).as_ref(), old_label, new_label)
+			.map_err(|e| e.kind())
+	}
+
+	fn delete_account(&self, token: Token, label: &String) -> Result<(), ErrorKind> {
+		Owner::delete_account(self, (&token.keychain_mask).as_ref(), label).map_err(|e| e.kind())
+	}
+
 	fn retrieve_outputs(
 		&self,
 		token: Token,
@@ -3357,6 +4709,44 @@ where
 		})
 	}
 
+	fn query_outputs(
+		&self,
+		token: Token,
+		refresh_from_node: bool,
+		query: OutputQueryArgs,
+	) -> Result<(bool, Vec<OutputCommitMapping>), ErrorKind> {
+		Owner::query_outputs(
+			self,
+			(&token.keychain_mask).as_ref(),
+			refresh_from_node,
+			&query,
+		)
+		.map_err(|e| e.kind())
+	}
+
+	fn query_txs(
+		&self,
+		token: Token,
+		refresh_from_node: bool,
+		query: TxQueryArgs,
+	) -> Result<(bool, Vec<TxLogEntryAPI>), ErrorKind> {
+		Owner::query_txs(
+			self,
+			(&token.keychain_mask).as_ref(),
+			refresh_from_node,
+			&query,
+		)
+		.map_err(|e| e.kind())
+		.map(|(b, tx)| {
+			(
+				b,
+				tx.iter()
+					.map(|t| TxLogEntryAPI::from_txlogemtry(t))
+					.collect(),
+			)
+		})
+	}
+
 	fn retrieve_summary_info(
 		&self,
 		token: Token,
@@ -3372,6 +4762,21 @@ where
 		.map_err(|e| e.kind())
 	}
 
+	fn retrieve_accounts_summary_info(
+		&self,
+		token: Token,
+		refresh_from_node: bool,
+		minimum_confirmations: u64,
+	) -> Result<(bool, Vec<AccountSummaryInfo>), ErrorKind> {
+		Owner::retrieve_accounts_summary_info(
+			self,
+			(&token.keychain_mask).as_ref(),
+			refresh_from_node,
+			minimum_confirmations,
+		)
+		.map_err(|e| e.kind())
+	}
+
 	fn init_send_tx(&self, token: Token, args: InitTxArgs) -> Result<VersionedSlate, ErrorKind> {
 		let slate = Owner::init_send_tx(self, (&token.keychain_mask).as_ref(), &args, 1)
 			.map_err(|e| e.kind())?;
@@ -3379,6 +4784,11 @@ where
 		// handle the workflow in lower level.
 		// If caller did send with thius API - then the slate is just for logging. For logging it is
 		// better to have plain slate so it can be readable.
+		if args.target_slate_version == Some(GRIN_V4_TARGET_SLATE_VERSION) {
+			let slate = SlateGrinV4::from_slate(&slate, &SlatePurpose::SendInitial)
+				.map_err(|e| ErrorKind::SlatepackEncodeError(format!("{}", e)))?;
+			return Ok(VersionedSlate::GV4(slate));
+		}
 		let version = slate.lowest_version();
 		Ok(VersionedSlate::into_version_plain(slate, version)
 			.map_err(|e| ErrorKind::SlatepackEncodeError(format!("{}", e)))?)
@@ -3512,6 +4922,34 @@ where
 			.map_err(|e| e.kind())
 	}
 
+	fn set_tx_label(&self, token: Token, args: TxLabelArgs) -> Result<(), ErrorKind> {
+		Owner::set_tx_label(self, (&token.keychain_mask).as_ref(), &args).map_err(|e| e.kind())
+	}
+
+	fn set_tx_confirmation_target(
+		&self,
+		token: Token,
+		tx_id: u32,
+		target: Option<u64>,
+	) -> Result<(), ErrorKind> {
+		Owner::set_tx_confirmation_target(self, (&token.keychain_mask).as_ref(), tx_id, target)
+			.map_err(|e| e.kind())
+	}
+
+	fn import_txs(
+		&self,
+		token: Token,
+		records: Vec<TxImportRecord>,
+		dry_run: bool,
+	) -> Result<TxImportReport, ErrorKind> {
+		Owner::import_txs(self, (&token.keychain_mask).as_ref(), records, dry_run)
+			.map_err(|e| e.kind())
+	}
+
+	fn tag_output(&self, token: Token, args: OutputTagArgs) -> Result<(), ErrorKind> {
+		Owner::tag_output(self, (&token.keychain_mask).as_ref(), &args).map_err(|e| e.kind())
+	}
+
 	fn get_stored_tx(
 		&self,
 		token: Token,
@@ -3599,6 +5037,15 @@ where
 		.map_err(|e| e.kind())
 	}
 
+	fn check(
+		&self,
+		token: Token,
+		start_height: Option<u64>,
+		fix: Vec<CheckCategory>,
+	) -> Result<CheckReport, ErrorKind> {
+		Owner::check(self, (&token.keychain_mask).as_ref(), start_height, fix).map_err(|e| e.kind())
+	}
+
 	fn node_height(&self, token: Token) -> Result<NodeHeightResult, ErrorKind> {
 		Owner::node_height(self, (&token.keychain_mask).as_ref()).map_err(|e| e.kind())
 	}
@@ -3740,6 +5187,97 @@ where
 		Owner::stop_updater(self).map_err(|e| e.kind())
 	}
 
+	fn pause(&self) -> Result<(), ErrorKind> {
+		Owner::pause(self).map_err(|e| e.kind())
+	}
+
+	fn resume(&self) -> Result<(), ErrorKind> {
+		Owner::resume(self).map_err(|e| e.kind())
+	}
+
+	fn is_paused(&self) -> Result<bool, ErrorKind> {
+		Ok(Owner::is_paused(self))
+	}
+
+	fn reload_config(&self) -> Result<ConfigReloadStatus, ErrorKind> {
+		Owner::reload_config(self).map_err(|e| e.kind())
+	}
+
+	fn utxo_snapshot(
+		&self,
+		token: Token,
+		height: Option<String>,
+	) -> Result<UtxoSnapshot, ErrorKind> {
+		let height = match height {
+			Some(h) => Some(h.parse::<u64>().map_err(|e| {
+				ErrorKind::GenericError(format!("Unable to parse height '{}', {}", h, e))
+			})?),
+			None => None,
+		};
+		Owner::utxo_snapshot(self, (&token.keychain_mask).as_ref(), height).map_err(|e| e.kind())
+	}
+
+	fn set_auto_cancel_policy(&self, policy: AutoCancelPolicy) -> Result<(), ErrorKind> {
+		Owner::set_auto_cancel_policy(self, policy).map_err(|e| e.kind())
+	}
+
+	fn get_auto_cancel_policy(&self) -> Result<AutoCancelPolicy, ErrorKind> {
+		Owner::get_auto_cancel_policy(self).map_err(|e| e.kind())
+	}
+
+	fn cancel_expired_receives(&self, token: Token) -> Result<Vec<u32>, ErrorKind> {
+		Owner::cancel_expired_receives(self, (&token.keychain_mask).as_ref()).map_err(|e| e.kind())
+	}
+
+	fn get_scan_progress(&self) -> Result<ScanProgress, ErrorKind> {
+		Owner::get_scan_progress(self).map_err(|e| e.kind())
+	}
+
+	fn get_listener_metrics(&self) -> Result<Vec<ListenerMetrics>, ErrorKind> {
+		Owner::get_listener_metrics(self).map_err(|e| e.kind())
+	}
+
+	fn get_node_cache_metrics(&self) -> Result<Vec<NodeCacheMetrics>, ErrorKind> {
+		Owner::get_node_cache_metrics(self).map_err(|e| e.kind())
+	}
+
+	fn query_listeners(&self) -> Result<Vec<ListenerStatus>, ErrorKind> {
+		Owner::query_listeners(self).map_err(|e| e.kind())
+	}
+
+	fn start_listener(&self, transport: String) -> Result<(), ErrorKind> {
+		Owner::start_listener(self, &transport).map_err(|e| e.kind())
+	}
+
+	fn stop_listener(&self, transport: String) -> Result<(), ErrorKind> {
+		Owner::stop_listener(self, &transport).map_err(|e| e.kind())
+	}
+
+	fn get_consensus_params(&self) -> Result<ConsensusParams, ErrorKind> {
+		Owner::get_consensus_params(self).map_err(|e| e.kind())
+	}
+
+	fn retrieve_history_series(
+		&self,
+		token: Token,
+		bucket_sec: Option<u64>,
+	) -> Result<Vec<HistoryBucket>, ErrorKind> {
+		Owner::retrieve_history_series(self, (&token.keychain_mask).as_ref(), bucket_sec)
+			.map_err(|e| e.kind())
+	}
+
+	fn get_exchange_rate(
+		&self,
+		currency: String,
+		date: Option<NaiveDate>,
+	) -> Result<ExchangeRate, ErrorKind> {
+		Owner::get_exchange_rate(self, &currency, date).map_err(|e| e.kind())
+	}
+
+	fn resolve_payment_id(&self, payment_id: String) -> Result<ResolvedPaymentId, ErrorKind> {
+		Owner::resolve_payment_id(self, &payment_id).map_err(|e| e.kind())
+	}
+
 	fn get_updater_messages(&self, count: u32) -> Result<Vec<StatusMessage>, ErrorKind> {
 		Owner::get_updater_messages(self, count as usize).map_err(|e| e.kind())
 	}
@@ -3752,6 +5290,14 @@ where
 		Ok(public_proof_address)
 	}
 
+	fn get_mqs_outbox(&self) -> Result<Vec<MqsQueuedMessage>, ErrorKind> {
+		Owner::get_mqs_outbox(self).map_err(|e| e.kind())
+	}
+
+	fn flush_mqs_outbox(&self) -> Result<Vec<MqsQueuedMessage>, ErrorKind> {
+		Owner::flush_mqs_outbox(self).map_err(|e| e.kind())
+	}
+
 	fn get_wallet_public_address(&self, token: Token) -> Result<ProvableAddress, ErrorKind> {
 		let address = Owner::get_wallet_public_address(self, (&token.keychain_mask).as_ref())
 			.map_err(|e| e.kind())?;
@@ -3760,6 +5306,34 @@ where
 		Ok(address)
 	}
 
+	fn get_public_proof_address(
+		&self,
+		token: Token,
+		index: u32,
+	) -> Result<PublicProofAddress, ErrorKind> {
+		Owner::get_public_proof_address(self, (&token.keychain_mask).as_ref(), index)
+			.map_err(|e| e.kind())
+	}
+
+	fn list_used_address_indices(&self, token: Token) -> Result<Vec<u32>, ErrorKind> {
+		Owner::list_used_address_indices(self, (&token.keychain_mask).as_ref())
+			.map_err(|e| e.kind())
+	}
+
+	fn address_mappings(&self, token: Token) -> Result<Vec<AddressMapping>, ErrorKind> {
+		Owner::address_mappings(self, (&token.keychain_mask).as_ref()).map_err(|e| e.kind())
+	}
+
+	fn set_address_mapping(&self, token: Token, mapping: AddressMapping) -> Result<(), ErrorKind> {
+		Owner::set_address_mapping(self, (&token.keychain_mask).as_ref(), &mapping)
+			.map_err(|e| e.kind())
+	}
+
+	fn remove_address_mapping(&self, token: Token, index: u32) -> Result<(), ErrorKind> {
+		Owner::remove_address_mapping(self, (&token.keychain_mask).as_ref(), index)
+			.map_err(|e| e.kind())
+	}
+
 	fn retrieve_payment_proof(
 		&self,
 		token: Token,
@@ -3786,6 +5360,10 @@ where
 			.map_err(|e| e.kind())
 	}
 
+	fn lookup_kernel(&self, token: Token, excess: String) -> Result<KernelLookupResult, ErrorKind> {
+		Owner::lookup_kernel(self, (&token.keychain_mask).as_ref(), &excess).map_err(|e| e.kind())
+	}
+
 	fn set_tor_config(&self, tor_config: Option<TorConfig>) -> Result<(), ErrorKind> {
 		Owner::set_tor_config(self, tor_config);
 		Ok(())
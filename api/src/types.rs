@@ -14,21 +14,19 @@
 use crate::core::libtx::secp_ser;
 use crate::keychain::Identifier;
 use crate::libwallet::dalek_ser;
+pub use crate::libwallet::secure_api::EncryptedBody;
 use crate::libwallet::{Error, ErrorKind};
 use crate::libwallet::{
-	ParticipantMessages, StoredProofInfo, TxLogEntry, TxLogEntryType, VersionedSlate,
+	ParticipantMessages, StoredProofInfo, TxLogEntry, TxLogEntryType, TxStatus, VersionedSlate,
 };
 use crate::util::secp::key::{PublicKey, SecretKey};
 use crate::util::secp::pedersen;
-use crate::util::{from_hex, to_hex};
+use crate::util::to_hex;
 use grin_wallet_libwallet::slatepack::SlatePurpose;
 
-use base64;
 use chrono::{DateTime, Utc};
 use ed25519_dalek::PublicKey as DalekPublicKey;
 use grin_wallet_libwallet::proof::proofaddress::ProvableAddress;
-use rand::{thread_rng, Rng};
-use ring::aead;
 use serde_json::{self, Value};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -71,115 +69,6 @@ pub struct ECDHPubkey {
 	pub ecdh_pubkey: PublicKey,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct EncryptedBody {
-	/// nonce used for encryption
-	pub nonce: String,
-	/// Encrypted base64 body request
-	pub body_enc: String,
-}
-
-impl EncryptedBody {
-	/// Encrypts and encodes json as base 64
-	pub fn from_json(json_in: &Value, enc_key: &SecretKey) -> Result<Self, Error> {
-		let mut to_encrypt = serde_json::to_string(&json_in)
-			.map_err(|e| {
-				ErrorKind::APIEncryption(format!("EncryptedBody Enc: Unable to encode JSON, {}", e))
-			})?
-			.as_bytes()
-			.to_vec();
-
-		let nonce: [u8; 12] = thread_rng().gen();
-
-		let unbound_key = aead::UnboundKey::new(&aead::AES_256_GCM, &enc_key.0).unwrap();
-		let sealing_key: aead::LessSafeKey = aead::LessSafeKey::new(unbound_key);
-		let aad = aead::Aad::from(&[]);
-		let res = sealing_key.seal_in_place_append_tag(
-			aead::Nonce::assume_unique_for_key(nonce),
-			aad,
-			&mut to_encrypt,
-		);
-		if let Err(e) = res {
-			return Err(ErrorKind::APIEncryption(format!(
-				"EncryptedBody: encryption failed, {}",
-				e
-			))
-			.into());
-		}
-
-		Ok(EncryptedBody {
-			nonce: to_hex(&nonce),
-			body_enc: base64::encode(&to_encrypt),
-		})
-	}
-
-	/// return serialize JSON self
-	pub fn as_json_value(&self) -> Result<Value, Error> {
-		let res = serde_json::to_value(self).map_err(|e| {
-			ErrorKind::APIEncryption(format!("EncryptedBody: JSON serialization failed, {}", e))
-		})?;
-		Ok(res)
-	}
-
-	/// return serialized JSON self as string
-	pub fn as_json_str(&self) -> Result<String, Error> {
-		let res = self.as_json_value()?;
-		let res = serde_json::to_string(&res).map_err(|e| {
-			ErrorKind::APIEncryption(format!(
-				"EncryptedBody: JSON String serialization failed, {}",
-				e
-			))
-		})?;
-		Ok(res)
-	}
-
-	/// Return original request
-	pub fn decrypt(&self, dec_key: &SecretKey) -> Result<Value, Error> {
-		let mut to_decrypt = base64::decode(&self.body_enc).map_err(|e| {
-			ErrorKind::APIEncryption(format!(
-				"EncryptedBody Dec: Encrypted request contains invalid Base64, {}",
-				e
-			))
-		})?;
-
-		let nonce = from_hex(&self.nonce).map_err(|e| {
-			ErrorKind::APIEncryption(format!(
-				"EncryptedBody Dec: Encrypted request contains invalid nonce, {}",
-				e
-			))
-		})?;
-
-		if nonce.len() < 12 {
-			return Err(ErrorKind::APIEncryption(
-				"EncryptedBody Dec: Invalid Nonce length".to_string(),
-			)
-			.into());
-		}
-		let mut n = [0u8; 12];
-		n.copy_from_slice(&nonce[0..12]);
-		let unbound_key = aead::UnboundKey::new(&aead::AES_256_GCM, &dec_key.0).unwrap();
-		let opening_key: aead::LessSafeKey = aead::LessSafeKey::new(unbound_key);
-		let aad = aead::Aad::from(&[]);
-		opening_key
-			.open_in_place(aead::Nonce::assume_unique_for_key(n), aad, &mut to_decrypt)
-			.map_err(|e| {
-				ErrorKind::APIEncryption(format!("EncryptedBody: decryption failed, {}", e))
-			})?;
-
-		for _ in 0..aead::AES_256_GCM.tag_len() {
-			to_decrypt.pop();
-		}
-
-		let decrypted = String::from_utf8(to_decrypt).map_err(|_| {
-			ErrorKind::APIEncryption("EncryptedBody Dec: Invalid UTF-8".to_string())
-		})?;
-
-		Ok(serde_json::from_str(&decrypted).map_err(|e| {
-			ErrorKind::APIEncryption(format!("EncryptedBody Dec: Invalid JSON, {}", e))
-		})?)
-	}
-}
-
 /// Wrapper for secure JSON requests
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EncryptedRequest {
@@ -410,6 +299,25 @@ pub struct TxLogEntryAPI {
 	/// Output commits as Strings, defined for send & recieve
 	#[serde(default)]
 	pub output_commits: Vec<String>,
+	/// Transaction lifecycle state
+	#[serde(default)]
+	pub status: TxStatus,
+	/// Local label
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub label: Option<String>,
+	/// Local memo
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub memo: Option<String>,
+	/// Number of confirmations when the transaction is settled
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub confirmation_target: Option<u64>,
+	/// Derivation index of the receive address the payment was sent to
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub address_index: Option<u32>,
+	/// Transaction is inferred from the outputs found during the restore
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub recovered: bool,
 }
 
 impl TxLogEntryAPI {
@@ -439,6 +347,12 @@ impl TxLogEntryAPI {
 			payment_proof: tle.payment_proof.clone(),
 			input_commits: tle.input_commits.iter().map(|c| to_hex(&c.0)).collect(),
 			output_commits: tle.output_commits.iter().map(|c| to_hex(&c.0)).collect(),
+			status: tle.current_status(),
+			label: tle.label.clone(),
+			memo: tle.memo.clone(),
+			confirmation_target: tle.confirmation_target.clone(),
+			address_index: tle.address_index.clone(),
+			recovered: tle.recovered,
 		}
 	}
 
@@ -66,6 +66,14 @@ fn comments() -> HashMap<String, String> {
 		.to_string(),
 	);
 
+	retval.insert(
+		"owner_grpc_listen_port".to_string(),
+		"
+#port for the gRPC variant of the wallet owner api, needs the wallet built with the grpc feature
+"
+		.to_string(),
+	);
+
 	retval.insert(
 		"api_secret_path".to_string(),
 		"
@@ -78,6 +86,18 @@ fn comments() -> HashMap<String, String> {
 		"check_node_api_http_addr".to_string(),
 		"
 #where the wallet should find a running node
+"
+		.to_string(),
+	);
+	retval.insert(
+		"node_mode".to_string(),
+		"
+#Optional, 'external' (default) or 'embedded'. The embedded mode runs a pruned mwc node in the
+#'listen' and 'owner_api' wallet process instead of the node at check_node_api_http_addr, the
+#other commands connect to that node. The wallet must be built with
+#the embedded_node feature. 'embedded_node_dir' is the chain data directory, default is 'node'
+#in the wallet top level directory. The node API listens on 127.0.0.1:'embedded_node_api_port',
+#the node connects to the peers on 'embedded_node_p2p_port'.
 "
 		.to_string(),
 	);
@@ -94,6 +114,15 @@ fn comments() -> HashMap<String, String> {
 #include the foreign API endpoints on the same port as the owner
 #API. Useful for networking environments like AWS ECS that make
 #it difficult to access multiple ports on a single service.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"owner_api_invoice_endpoint".to_string(),
+		"
+#include the merchant invoice endpoint POST /v3/invoice {\"amount\", \"memo\"} on the
+#owner API port. It returns the invoice slatepack, 'invoice_paid' event is sent to the
+#webhooks when the invoice transaction is confirmed. Requires the api secret or tokens.
 "
 		.to_string(),
 	);
@@ -117,6 +146,24 @@ fn comments() -> HashMap<String, String> {
 		"dark_background_color_scheme".to_string(),
 		"
 #Whether to use the black background color scheme for command line
+"
+		.to_string(),
+	);
+	retval.insert(
+		"locale".to_string(),
+		"
+#Locale of the command line output, the status messages, amounts and dates.
+#Supported: en-US (default), zh-CN, de-DE
+#locale = \"zh-CN\"
+"
+		.to_string(),
+	);
+	retval.insert(
+		"log_privacy".to_string(),
+		"
+#How the addresses, amounts and slate ids are written into the log file.
+#off - as they are (default), redacted - replaced with a short hash, full - hidden
+#log_privacy = \"redacted\"
 "
 		.to_string(),
 	);
@@ -138,6 +185,309 @@ fn comments() -> HashMap<String, String> {
 		"eth_infura_project_id".to_string(),
 		"
 #ethereum infura chain project id
+"
+		.to_string(),
+	);
+	retval.insert(
+		"proxy_addr".to_string(),
+		"
+#SOCKS5 proxy for outbound HTTP(S), mwc-node API and Electrum connections,
+#for example \"socks5://127.0.0.1:1080\". Tor connections keep using the Tor socks port.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"auto_cancel_expired_tx".to_string(),
+		"
+#If true, the wallet update cancels unconfirmed transactions and unlocks their outputs
+#once the transaction TTL cutoff height is passed. Default is true
+"
+		.to_string(),
+	);
+	retval.insert(
+		"auto_cancel_tx_age_hours".to_string(),
+		"
+#Optional, number of hours after which unconfirmed transactions are cancelled automatically
+#on the wallet update, even if they don't have TTL
+"
+		.to_string(),
+	);
+	retval.insert(
+		"auto_expire_receive_days".to_string(),
+		"
+#Optional, number of days after which received transactions that the sender didn't finalize
+#are expired. Expired receives are not counted as awaiting finalization,
+#run 'cancel-expired' command to remove them from the transaction log
+"
+		.to_string(),
+	);
+	retval.insert(
+		"tx_auto_repost_interval".to_string(),
+		"
+#Optional, interval in seconds for the automatic repost of finalized but unconfirmed
+#transactions. Works while the updater thread is running: in cli mode or after the owner
+#API 'start_updater' call.
+#Every next repost of the same transaction doubles the delay, up to one day
+"
+		.to_string(),
+	);
+	retval.insert(
+		"scan_threads".to_string(),
+		"
+#Optional, number of the parallel workers that rewind the range proofs of the outputs during
+#the scan. The outputs are fetched from the node by the separate workers at the same time.
+#Rewind is CPU bound, the full restore is faster with up to the number of CPU cores workers.
+#Default is 4, max is 64
+"
+		.to_string(),
+	);
+	retval.insert(
+		"batch_proof_verification".to_string(),
+		"
+#Optional, the range proofs of the outputs found by the scan and of the received slates are
+#verified in one batch. Set to false to verify them one by one. Default is true
+"
+		.to_string(),
+	);
+	retval.insert(
+		"node_cache_tip_ttl_ms".to_string(),
+		"
+#Optional, the node responses are cached so the updater thread and the commands don't request
+#the same data again and again. 'node_cache_tip_ttl_ms' is for the chain tip, 'node_cache_ttl_ms'
+#is for the headers, blocks, kernel lookups and output queries. Time is in ms, 0 disables the
+#cache. Default is 5000 for both. The hit and miss counters are returned by the owner API
+#'get_node_cache_metrics'
+"
+		.to_string(),
+	);
+	retval.insert(
+		"min_change_output".to_string(),
+		"
+#Optional, change policy for the send transactions, amounts are in nanoMWC.
+#Change that is smaller than 'min_change_output' is added to the fee, such dust outputs
+#cost more to spend than they are worth. Only the change up to the base fee is added to
+#the fee, the larger change is kept as an output.
+#If 'change_split_target' is set, the change is split into outputs of at least this amount,
+#up to 'change_split_max_outputs' outputs (default is 10). It keeps enough outputs for the
+#wallets that send many transactions. Send 'min_change_output' and 'change_split_target'
+#parameters override these values.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"change_split_target".to_string(),
+		"
+#Optional, split the change into outputs of at least this amount, in nanoMWC
+"
+		.to_string(),
+	);
+	retval.insert(
+		"change_split_max_outputs".to_string(),
+		"
+#Optional, max number of the change outputs for the split change. Default is 10
+"
+		.to_string(),
+	);
+	retval.insert(
+		"max_slate_size".to_string(),
+		"
+#Limits for the slates and messages received by the listeners. Larger or more complex
+#payloads are rejected before processing. Max size is in bytes
+"
+		.to_string(),
+	);
+	retval.insert(
+		"listener_rate_limit".to_string(),
+		"
+#Limits for the owner and foreign HTTP listeners. listener_rate_limit is the number of the
+#requests per minute from the same client, listener_max_concurrent_requests is the number of
+#the requests that are processed at the same time. Requests over the limits get HTTP 429.
+#0 disables the limit. The clients are identified by the peer IP address. Behind a reverse
+#proxy all requests come from the proxy address, set listener_trust_proxy_headers to true
+#only if the proxy sets X-Forwarded-For or X-Real-IP.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"receive_min_amount".to_string(),
+		"
+#Optional, policy for the transactions received by the foreign API, amounts are in nanoMWC.
+#'receive_min_amount' and 'receive_max_amount' are the limits for a single transaction,
+#'receive_daily_cap' is the limit for the amount received in the last 24 hours.
+#If 'receive_allowed_senders' is set, only the transactions with the payment proof from
+#these addresses are accepted, for example receive_allowed_senders = [\"<address>\"]
+"
+		.to_string(),
+	);
+	retval.insert(
+		"tx_hook_pre_lock".to_string(),
+		"
+#Transaction hooks, the commands that can inspect and reject the transactions. The transaction
+#data (hook, slate_id, amount, fee, destination, num_inputs, num_outputs, tx_hash) is written
+#into the command stdin as JSON. Non zero exit code rejects the transaction, the command output
+#is reported as a reason. Hooks: tx_hook_pre_lock, tx_hook_pre_finalize, tx_hook_post_post.
+#tx_hook_post_post is called after the transaction is posted, it can't reject it.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"tx_mirror_file".to_string(),
+		"
+#Optional, mirror of the posted transactions for disaster recovery. Every posted transaction
+#(timestamp, slate_id, tx_hash, kernel_excess, fee and the transaction JSON) is appended as
+#a line to this file. Put it on a different host or a file share, so the record of the
+#in-flight transactions survives the wallet host. For other storages use tx_hook_post_post.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"tx_mirror_syslog".to_string(),
+		"
+#Optional, if true the posted transactions are written into the local syslog as well
+"
+		.to_string(),
+	);
+	retval.insert(
+		"audit_log_file".to_string(),
+		"
+#Optional, audit log of the state changing owner API operations: sends, finalizes, cancels,
+#account and swap actions. Every record has the caller info, the balance before and after the
+#operation and the hash of the previous record. Run 'audit --verify' to check the log.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"webhook_urls".to_string(),
+		"
+#Webhooks, URLs that receive the wallet and swap events as JSON POST requests.
+#Events: payment_received, tx_confirmed, tx_settled, invoice_paid, swap_state_changed, swap_failed,
+#swap_action_required
+#For example: webhook_urls = [\"https://127.0.0.1:8080/mwc_events\"]
+#webhook_confirmations defines when tx_confirmed event is sent, default is 10
+"
+		.to_string(),
+	);
+	retval.insert(
+		"alert_command".to_string(),
+		"
+#Optional, command for the events that need attention: swap_failed and swap_action_required
+#(the swap waits for a deposit or a backup, or it is refunding). The event is written into the
+#command stdin as JSON, the command can send an email or a chat message.
+#For example: alert_command = \"/usr/local/bin/mwc_alert_mail.sh\"
+"
+		.to_string(),
+	);
+	retval.insert(
+		"backup_dest".to_string(),
+		"
+#Optional, scheduled encrypted backups of the wallet data: the seed file, the wallet DB and
+#the swap trades. The destination is a directory or s3://<bucket>/<prefix>, S3 credentials are
+#taken from AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY environment variables. The backups are
+#encrypted with the key derived from the wallet seed, 'restore' needs the recovery phrase.
+#backup_interval_hours is the time between the backups, at least 1, default is 24.
+#backup_keep is the number of the backups to keep, default is 7. 0 keeps all backups.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"price_source_url".to_string(),
+		"
+#Optional, source of the MWC exchange rates for 'info --fiat' and 'txs --fiat'. It is a
+#CoinGecko compatible coin URL, the rates are requested only when the fiat values are asked.
+#Example: price_source_url = \"https://api.coingecko.com/api/v3/coins/mimblewimblecoin\"
+#price_cache_minutes is the time to cache the current rate, default is 10.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"payment_id_doh_url".to_string(),
+		"
+#Optional, DNS-over-HTTPS resolver of the payment ids 'name@domain' for send --dest and
+#--proof_address. It must support the JSON API. The records are also requested from
+#https://<domain>/.well-known/mwc-payment/<name>.json. Default is \"https://dns.google/resolve\",
+#empty value disables the payment ids.
+"
+		.to_string(),
+	);
+	retval.insert(
+		"matrix_homeserver".to_string(),
+		"
+#Matrix account for the 'matrix' method of send and listen. The slates are exchanged as
+#messages in a room, the send destination is a room id, room alias or user id.
+#Example: matrix_homeserver = \"https://matrix.org\"
+"
+		.to_string(),
+	);
+	retval.insert(
+		"nostr_relays".to_string(),
+		"
+#Relays for the 'nostr' method of send and listen. The slates are exchanged as encrypted
+#direct messages, the send destination is the npub of the other wallet. If not set,
+#wss://relay.damus.io, wss://nos.lol and wss://relay.nostr.band are used.
+#Example: nostr_relays = [\"wss://relay.damus.io\"]
+"
+		.to_string(),
+	);
+	retval.insert(
+		"p2p_listen_address".to_string(),
+		"
+#Listening multiaddr for the 'p2p' method of listen. The wallets connect directly, the send
+#destination is the p2p address from the 'address' command. If not set, /ip4/0.0.0.0/tcp/3419
+#is used.
+#Example: p2p_listen_address = \"/ip4/0.0.0.0/tcp/3419\"
+"
+		.to_string(),
+	);
+	retval.insert(
+		"p2p_public_address".to_string(),
+		"
+#Multiaddr that other wallets use to reach the 'p2p' listener, for example the external address
+#of the router that forwards the port. If not set, the listening address is published.
+#Example: p2p_public_address = \"/dns4/wallet.example.org/tcp/3419\"
+"
+		.to_string(),
+	);
+	retval.insert(
+		"ipfs_api_url".to_string(),
+		"
+#HTTP API of the IPFS node for the 'ipfs' method of send and listen. The slates are published
+#to IPFS and listed in the mailbox of the recipient, the send destination is the slatepack
+#address of the other wallet. If not set, http://127.0.0.1:5001 is used.
+#Example: ipfs_api_url = \"http://127.0.0.1:5001\"
+"
+		.to_string(),
+	);
+	retval.insert(
+		"email_address".to_string(),
+		"
+#Email account for the 'email' method of send and listen. The slatepack is mailed through the
+#SMTP server, the listener checks the IMAP inbox every minute. The send destination is the email
+#address of the other party. SMTP port 465 uses TLS, other ports use STARTTLS.
+#Example: email_address = \"wallet@example.com\"
+#email_smtp_server = \"smtp.example.com:465\"
+#email_imap_server = \"imap.example.com:993\"
+#email_username = \"wallet@example.com\"
+#email_password = \"<app password>\"
+"
+		.to_string(),
+	);
+	retval.insert(
+		"telegram_bot_token".to_string(),
+		"
+#Token of the Telegram bot for the 'telegram' method of send and listen, from @BotFather.
+#The listener processes the slates that are sent or forwarded to the bot and replies in the
+#same chat. The send destination is a chat id or a @channel name.
+#Example: telegram_bot_token = \"123456789:AAE...\"
+"
+		.to_string(),
+	);
+	retval.insert(
+		"autoswap_resume".to_string(),
+		"
+#If true, 'listen' and 'owner_api' resume the auto swap of the trades that were running in
+#the auto swap mode when the wallet was stopped or crashed. The auto swap is resumed when the
+#listener of the trade method is running.
+#autoswap_resume = true
 "
 		.to_string(),
 	);
@@ -165,10 +515,29 @@ fn comments() -> HashMap<String, String> {
 # Electrum X servers that are used for Atomic Swap operations. Each Secondary Currency need
 # its own dedicated Electrum X instance. We highly advise to use your own instance, instead of
 # using those community servers.
-# For every secondary currency expected 4 instances:
-# mainnet primary, mainnet secondary, testnet primary, testnet secondary,
-# Key: <coin>_[main|test]_[1|2]
+# For every secondary currency expected at least 2 instances per network, primary and secondary.
+# More instances can be added with the next numbers, they are used if the first ones are not
+# reachable. Failed servers are moved to the end of the list for 10 minutes.
+# Key: <coin>_[main|test]_<N>, N is 1,2,3...
 # value: URI
+# Use 'swap --check' to see which servers are reachable.
+"
+		.to_string(),
+	);
+
+	retval.insert(
+		"[wallet.owner_api_wallets]".to_string(),
+		"
+# Additional wallets that are served by the owner API V3 listener. Every wallet has its own
+# data directory and password, select it with the 'wallet_id' field of the JSON-RPC request.
+# Requests without 'wallet_id' go to the wallet from this config.
+# Swaps, MQS and Tor listeners are available for the wallet from this config only.
+# The runtime settings from this config (slate limits, receive policy, tx hooks, tx mirror,
+# audit log, webhooks, backups, etc.) are shared by all wallets. The wallet is not started if
+# the mwc-wallet.toml of an additional wallet has other values for them. Scheduled backups are
+# done for the wallet from this config only.
+# Key: wallet id
+# value: wallet top level directory
 "
 		.to_string(),
 	);
@@ -274,6 +643,15 @@ fn comments() -> HashMap<String, String> {
 		.to_string(),
 	);
 
+	retval.insert(
+		"listener_address_indexes".to_string(),
+		"
+#Additional address derivation indexes to publish onion services for, for example [0, 1].
+#Addresses from the previous indexes stay reachable after 'tor --rotate'
+"
+		.to_string(),
+	);
+
 	retval.insert(
 		"[mqs]".to_string(),
 		"
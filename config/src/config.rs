@@ -185,6 +185,22 @@ pub fn initial_setup_wallet(
 	Ok(config)
 }
 
+// Compare paths, resolving them if they exist
+fn same_path(path: Option<&PathBuf>, other: &PathBuf) -> bool {
+	match path {
+		Some(p) => match (fs::canonicalize(p), fs::canonicalize(other)) {
+			(Ok(a), Ok(b)) => a == b,
+			_ => p == other,
+		},
+		None => false,
+	}
+}
+
+// Port of the 'host:port' address
+fn port_of(addr: &str) -> Option<u16> {
+	addr.rsplit(':').next().and_then(|p| p.parse().ok())
+}
+
 impl Default for GlobalWalletConfigMembers {
 	fn default() -> GlobalWalletConfigMembers {
 		GlobalWalletConfigMembers {
@@ -218,17 +234,134 @@ impl GlobalWalletConfig {
 			global::ChainTypes::Floonet => {
 				defaults.api_listen_port = 13415;
 				defaults.libp2p_listen_port = Some(13418);
+				defaults.owner_api_listen_port = Some(13420);
 				defaults.check_node_api_http_addr = "http://127.0.0.1:13413".to_owned();
 			}
 			global::ChainTypes::UserTesting => {
 				defaults.api_listen_port = 23415;
 				defaults.libp2p_listen_port = Some(23418);
+				defaults.owner_api_listen_port = Some(23420);
 				defaults.check_node_api_http_addr = "http://127.0.0.1:23413".to_owned();
 			}
 			_ => {}
 		}
+
+		// Every chain runs its own tor process, so socks ports must differ as well
+		let tor = defaults_conf
+			.members
+			.as_mut()
+			.unwrap()
+			.tor
+			.as_mut()
+			.unwrap();
+		match *chain_type {
+			global::ChainTypes::Floonet => tor.socks_proxy_addr = "127.0.0.1:59051".to_owned(),
+			global::ChainTypes::UserTesting => tor.socks_proxy_addr = "127.0.0.1:59052".to_owned(),
+			_ => {}
+		}
 		defaults_conf
 	}
+
+	/// Check that this configuration doesn't share the ports or directories with the wallets
+	/// of other chain types configured at their default locations. Shared directories are
+	/// reported as an error, shared ports are returned as warnings because they conflict
+	/// only if both wallets are running at the same time.
+	pub fn check_chain_collisions(&self) -> Result<Vec<String>, ConfigError> {
+		let members = match self.members.as_ref() {
+			Some(m) => m,
+			None => return Ok(vec![]),
+		};
+		let chain_type = members
+			.wallet
+			.chain_type
+			.clone()
+			.unwrap_or(global::ChainTypes::Mainnet);
+
+		let mut warnings: Vec<String> = vec![];
+		for other_chain in &[
+			global::ChainTypes::Mainnet,
+			global::ChainTypes::Floonet,
+			global::ChainTypes::UserTesting,
+		] {
+			if *other_chain == chain_type {
+				continue;
+			}
+			let mut other_path = match get_grin_path(other_chain, false) {
+				Ok(p) => p,
+				Err(_) => continue,
+			};
+			other_path.push(WALLET_CONFIG_FILE_NAME);
+			if !other_path.exists() || same_path(self.config_file_path.as_ref(), &other_path) {
+				continue;
+			}
+			let other = GlobalWalletConfig::new(other_path.to_str().unwrap())?;
+			let other_members = match other.members.as_ref() {
+				Some(m) => m,
+				None => continue,
+			};
+			if other_members.wallet.chain_type.as_ref() == Some(&chain_type) {
+				continue;
+			}
+			let other_name = other_chain.shortname();
+
+			let mut shared_dirs = vec![(
+				"data_file_dir",
+				&members.wallet.data_file_dir,
+				&other_members.wallet.data_file_dir,
+			)];
+			if let (Some(t), Some(o)) = (members.tor.as_ref(), other_members.tor.as_ref()) {
+				shared_dirs.push(("send_config_dir", &t.send_config_dir, &o.send_config_dir));
+			}
+			for (name, dir, other_dir) in shared_dirs {
+				if same_path(Some(&PathBuf::from(dir)), &PathBuf::from(other_dir)) {
+					return Err(ConfigError::ChainCollisionError(format!(
+						"{} {} is used by the {} wallet configured at {}",
+						name,
+						dir,
+						other_name,
+						other_path.to_str().unwrap()
+					)));
+				}
+			}
+
+			let mut ports = vec![
+				(
+					"api_listen_port",
+					Some(members.wallet.api_listen_port),
+					Some(other_members.wallet.api_listen_port),
+				),
+				(
+					"owner_api_listen_port",
+					Some(members.wallet.owner_api_listen_port()),
+					Some(other_members.wallet.owner_api_listen_port()),
+				),
+				(
+					"libp2p_listen_port",
+					members.wallet.libp2p_listen_port,
+					other_members.wallet.libp2p_listen_port,
+				),
+			];
+			if let (Some(t), Some(o)) = (members.tor.as_ref(), other_members.tor.as_ref()) {
+				ports.push((
+					"socks_proxy_addr",
+					port_of(&t.socks_proxy_addr),
+					port_of(&o.socks_proxy_addr),
+				));
+			}
+			for (name, port, other_port) in ports {
+				if port.is_some() && port == other_port {
+					warnings.push(format!(
+						"{} {} is also used by the {} wallet configured at {}",
+						name,
+						port.unwrap(),
+						other_name,
+						other_path.to_str().unwrap()
+					));
+				}
+			}
+		}
+		Ok(warnings)
+	}
 	/// Requires the path to a config file
 	pub fn new(file_path: &str) -> Result<GlobalWalletConfig, ConfigError> {
 		let mut return_value = GlobalWalletConfig::default();
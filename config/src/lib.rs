@@ -36,5 +36,5 @@ pub use crate::config::{
 };
 pub use crate::types::{
 	parse_node_address_string, ConfigError, GlobalWalletConfig, GlobalWalletConfigMembers,
-	MQSConfig, TorConfig, WalletConfig,
+	MQSConfig, NodeMode, TorConfig, WalletConfig,
 };
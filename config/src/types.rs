@@ -38,6 +38,9 @@ pub struct WalletConfig {
 	pub libp2p_listen_port: Option<u16>,
 	/// The port this wallet's owner API will run on
 	pub owner_api_listen_port: Option<u16>,
+	/// The port of the gRPC Owner API, None - gRPC API is disabled.
+	/// The wallet must be built with the `grpc` feature
+	pub owner_grpc_listen_port: Option<u16>,
 	/// Location of the secret for basic auth on the Owner API
 	pub api_secret_path: Option<String>,
 	/// Location of the node api secret for basic auth on the Grin API
@@ -45,8 +48,19 @@ pub struct WalletConfig {
 	/// The api address of a running server node against which transaction inputs
 	/// will be checked during send; could be multiple nodes separated by semicolon
 	pub check_node_api_http_addr: String,
+	/// Node that the wallet uses, default is the external node at check_node_api_http_addr.
+	/// The embedded node needs the wallet built with the `embedded_node` feature
+	pub node_mode: Option<NodeMode>,
+	/// Chain data directory of the embedded node. Default is 'node' in the wallet top level directory
+	pub embedded_node_dir: Option<String>,
+	/// API port of the embedded node, it listens on 127.0.0.1. Default is 3413, 13413 for floonet
+	pub embedded_node_api_port: Option<u16>,
+	/// P2P port of the embedded node. Default is 3414, 13414 for floonet
+	pub embedded_node_p2p_port: Option<u16>,
 	/// Whether to include foreign API endpoints on the Owner API
 	pub owner_api_include_foreign: Option<bool>,
+	/// Whether to include the merchant invoice endpoint /v3/invoice on the Owner API
+	pub owner_api_invoice_endpoint: Option<bool>,
 	/// Whether to include the mwcmqs listener
 	pub owner_api_include_mqs_listener: Option<bool>,
 	///Index used to derive address
@@ -63,6 +77,11 @@ pub struct WalletConfig {
 	/// Whether to use the black background color scheme for command line
 	/// if enabled, wallet command output color will be suitable for black background terminal
 	pub dark_background_color_scheme: Option<bool>,
+	/// Locale of the command line output: messages, amounts and dates. Default is en-US
+	pub locale: Option<String>,
+	/// How addresses, amounts and slate ids are written into the log: off, redacted or full.
+	/// Default is off
+	pub log_privacy: Option<String>,
 	/// Wallet data directory. Default none is 'wallet_data'
 	pub wallet_data_dir: Option<String>,
 	/// Base fee for all transactions. Please note, that fee can't be lower then Base fee
@@ -74,10 +93,133 @@ pub struct WalletConfig {
 	pub erc20_swap_contract_address: Option<String>,
 	/// Ethereum Infura Project Id
 	pub eth_infura_project_id: Option<String>,
+	/// SOCKS5 proxy for outbound HTTP(S), node API and Electrum connections,
+	/// for example 'socks5://127.0.0.1:1080'. Tor connections use the Tor socks port.
+	pub proxy_addr: Option<String>,
+	/// If Some(false), unconfirmed transactions are not cancelled when their TTL cutoff
+	/// height is passed. Default is true
+	pub auto_cancel_expired_tx: Option<bool>,
+	/// If set, unconfirmed transactions that are older than this number of hours are
+	/// cancelled on wallet update
+	pub auto_cancel_tx_age_hours: Option<u64>,
+	/// If set, received transactions that the sender didn't finalize within this number of days
+	/// are expired: excluded from the awaiting finalization amount, `cancel-expired` removes them
+	pub auto_expire_receive_days: Option<u64>,
+	/// If set, the updater thread reposts finalized but unconfirmed transactions. Value is the
+	/// first repost delay in seconds, every next repost doubles it up to one day
+	pub tx_auto_repost_interval: Option<u64>,
+	/// Number of the workers that rewind the range proofs of the outputs during the scan.
+	/// Default is 4
+	pub scan_threads: Option<usize>,
+	/// If false, the range proofs of the scanned and received outputs are verified one by one
+	/// instead of the batch verification. Default is true
+	pub batch_proof_verification: Option<bool>,
+	/// How long the chain tip from the node is cached, ms. 0 - no caching. Default is 5000
+	pub node_cache_tip_ttl_ms: Option<u64>,
+	/// How long the headers, blocks, kernels and outputs from the node are cached, ms.
+	/// 0 - no caching. Default is 5000
+	pub node_cache_ttl_ms: Option<u64>,
+	/// Change below this amount is added to the fee instead of creating a dust output
+	pub min_change_output: Option<u64>,
+	/// If set, the change of the send transactions is split into outputs of this size
+	pub change_split_target: Option<u64>,
+	/// Max number of the change outputs when the change is split. Default is 10
+	pub change_split_max_outputs: Option<usize>,
+	/// Max size in bytes of the slate or message accepted by the listeners
+	pub max_slate_size: Option<usize>,
+	/// Max number of the transaction inputs in the slate accepted by the listeners
+	pub max_slate_inputs: Option<usize>,
+	/// Max number of the transaction outputs in the slate accepted by the listeners
+	pub max_slate_outputs: Option<usize>,
+	/// Max number of the participants in the slate accepted by the listeners
+	pub max_slate_participants: Option<usize>,
+	/// Max JSON nesting depth of the slate or message accepted by the listeners
+	pub max_slate_json_depth: Option<usize>,
+	/// Max number of the listener requests per minute from the same client, 0 - no limit
+	pub listener_rate_limit: Option<u32>,
+	/// Max number of the listener requests that are processed at the same time, 0 - no limit
+	pub listener_max_concurrent_requests: Option<usize>,
+	/// If true, the listener clients are identified by the X-Forwarded-For and X-Real-IP headers
+	pub listener_trust_proxy_headers: Option<bool>,
+	/// Min amount of the transaction received by the foreign API, nanoMWC
+	pub receive_min_amount: Option<u64>,
+	/// Max amount of the transaction received by the foreign API, nanoMWC
+	pub receive_max_amount: Option<u64>,
+	/// Max amount received by the foreign API in the last 24 hours, nanoMWC
+	pub receive_daily_cap: Option<u64>,
+	/// Payment proof addresses of the senders that the foreign API accepts the transactions from
+	pub receive_allowed_senders: Option<Vec<String>>,
+	/// Command that is called before the outputs are locked for the send transaction.
+	/// Non zero exit code rejects the transaction
+	pub tx_hook_pre_lock: Option<String>,
+	/// Command that is called before the transaction is finalized.
+	/// Non zero exit code rejects the transaction
+	pub tx_hook_pre_finalize: Option<String>,
+	/// Command that is called after the transaction is posted
+	pub tx_hook_post_post: Option<String>,
+	/// File that every posted transaction is appended to as a JSON line
+	pub tx_mirror_file: Option<String>,
+	/// If true, every posted transaction is written into the syslog
+	pub tx_mirror_syslog: Option<bool>,
+	/// Hash-chained audit log of the state changing owner API operations
+	pub audit_log_file: Option<String>,
+	/// URLs that receive the wallet and swap events as JSON POST requests
+	pub webhook_urls: Option<Vec<String>>,
+	/// Number of confirmations for the transaction confirmed event. Default is 10
+	pub webhook_confirmations: Option<u64>,
+	/// Command that is called for the events that need the attention of the user,
+	/// the event is written into the command stdin as JSON
+	pub alert_command: Option<String>,
+	/// Scheduled encrypted backups of the wallet data: a directory or s3://<bucket>/<prefix>
+	pub backup_dest: Option<String>,
+	/// Hours between the scheduled backups. Default is 24
+	pub backup_interval_hours: Option<u64>,
+	/// Number of the scheduled backups to keep, older ones are deleted. Default is 7
+	pub backup_keep: Option<usize>,
+	/// CoinGecko compatible coin URL of the exchange rates for the fiat values. None - disabled
+	pub price_source_url: Option<String>,
+	/// Minutes to cache the current exchange rate. Default is 10
+	pub price_cache_minutes: Option<u64>,
+	/// DNS-over-HTTPS JSON API for the payment ids 'name@domain'. Default is
+	/// https://dns.google/resolve, empty value disables the payment ids
+	pub payment_id_doh_url: Option<String>,
+	/// Matrix homeserver of the wallet account for the 'matrix' send and listen method
+	pub matrix_homeserver: Option<String>,
+	/// Access token of the wallet Matrix account
+	pub matrix_access_token: Option<String>,
+	/// Relays for the 'nostr' send and listen method. Default relays are used if not set
+	pub nostr_relays: Option<Vec<String>>,
+	/// Listening multiaddr for the 'p2p' listen method. Default is /ip4/0.0.0.0/tcp/3419
+	pub p2p_listen_address: Option<String>,
+	/// Multiaddr that other wallets use to reach the 'p2p' listener, if it differs from the
+	/// listening one
+	pub p2p_public_address: Option<String>,
+	/// HTTP API of the IPFS node for the 'ipfs' send and listen method. Default is
+	/// http://127.0.0.1:5001
+	pub ipfs_api_url: Option<String>,
+	/// Email address of the wallet account for the 'email' send and listen method
+	pub email_address: Option<String>,
+	/// SMTP server of the email account, host:port
+	pub email_smtp_server: Option<String>,
+	/// IMAP server of the email account, host:port
+	pub email_imap_server: Option<String>,
+	/// Login of the email account, email_address is used if not set
+	pub email_username: Option<String>,
+	/// Password of the email account
+	pub email_password: Option<String>,
+	/// Bot API token for the 'telegram' send and listen method
+	pub telegram_bot_token: Option<String>,
+	/// If true, 'listen' and 'owner_api' resume the auto swap of the trades that were
+	/// running in the auto swap mode when the wallet was stopped
+	pub autoswap_resume: Option<bool>,
 	/// Electrum nodes for secondary coins
-	/// Key: <coin>_[main|test]_[1|2]
+	/// Key: <coin>_[main|test]_<N>, N is 1,2,3...
 	/// Value: url
 	pub swap_electrumx_addr: Option<BTreeMap<String, String>>,
+	/// Additional wallets that are served by the owner API V3 in the same process.
+	/// Key: wallet id that is used in the 'wallet_id' field of the JSON-RPC request
+	/// Value: wallet top level directory
+	pub owner_api_wallets: Option<BTreeMap<String, String>>,
 }
 
 impl Default for WalletConfig {
@@ -88,10 +230,16 @@ impl Default for WalletConfig {
 			api_listen_port: 3415,
 			libp2p_listen_port: Some(3418),
 			owner_api_listen_port: Some(WalletConfig::default_owner_api_listen_port()),
+			owner_grpc_listen_port: None,
 			api_secret_path: Some(".owner_api_secret".to_string()),
 			node_api_secret_path: Some(".api_secret".to_string()),
 			check_node_api_http_addr: "http://127.0.0.1:3413".to_string(),
+			node_mode: None,
+			embedded_node_dir: None,
+			embedded_node_api_port: None,
+			embedded_node_p2p_port: None,
 			owner_api_include_foreign: Some(false),
+			owner_api_invoice_endpoint: Some(false),
 			owner_api_include_mqs_listener: Some(false),
 			data_file_dir: ".".to_string(),
 			grinbox_address_index: None,
@@ -99,6 +247,8 @@ impl Default for WalletConfig {
 			tls_certificate_file: None,
 			tls_certificate_key: None,
 			dark_background_color_scheme: Some(true),
+			locale: None,
+			log_privacy: None,
 			wallet_data_dir: None,
 			base_fee: None,
 			eth_swap_contract_address: Some("2FA243fC8f9EAF014f8d6E909157B6A48cEE0bdC".to_string()),
@@ -106,6 +256,58 @@ impl Default for WalletConfig {
 				"Dd62a95626453F54E686cF0531bCbf6766150794".to_string(),
 			),
 			eth_infura_project_id: Some("7f1274674be54d2881bf3c0168bf9855".to_string()),
+			proxy_addr: None,
+			auto_cancel_expired_tx: Some(true),
+			auto_cancel_tx_age_hours: None,
+			auto_expire_receive_days: None,
+			tx_auto_repost_interval: None,
+			scan_threads: None,
+			batch_proof_verification: None,
+			node_cache_tip_ttl_ms: None,
+			node_cache_ttl_ms: None,
+			min_change_output: None,
+			change_split_target: None,
+			change_split_max_outputs: None,
+			max_slate_size: Some(4 * 1024 * 1024),
+			max_slate_inputs: Some(5000),
+			max_slate_outputs: Some(500),
+			max_slate_participants: Some(10),
+			max_slate_json_depth: Some(32),
+			listener_rate_limit: Some(300),
+			listener_max_concurrent_requests: Some(32),
+			listener_trust_proxy_headers: None,
+			receive_min_amount: None,
+			receive_max_amount: None,
+			receive_daily_cap: None,
+			receive_allowed_senders: None,
+			tx_hook_pre_lock: None,
+			tx_hook_pre_finalize: None,
+			tx_hook_post_post: None,
+			tx_mirror_file: None,
+			tx_mirror_syslog: None,
+			audit_log_file: None,
+			webhook_urls: None,
+			webhook_confirmations: None,
+			alert_command: None,
+			backup_dest: None,
+			backup_interval_hours: None,
+			backup_keep: None,
+			price_source_url: None,
+			price_cache_minutes: None,
+			payment_id_doh_url: None,
+			matrix_homeserver: None,
+			matrix_access_token: None,
+			nostr_relays: None,
+			p2p_listen_address: None,
+			p2p_public_address: None,
+			ipfs_api_url: None,
+			email_address: None,
+			email_smtp_server: None,
+			email_imap_server: None,
+			email_username: None,
+			email_password: None,
+			telegram_bot_token: None,
+			autoswap_resume: None,
 			swap_electrumx_addr: Some(
 				[
 					("btc_main_1", "btc.main1.swap.mwc.mw:18337"),
@@ -138,10 +340,21 @@ impl Default for WalletConfig {
 				.map(|i| (i.0.to_string(), i.1.to_string()))
 				.collect::<BTreeMap<String, String>>(),
 			),
+			owner_api_wallets: None,
 		}
 	}
 }
 
+/// Node that the wallet uses for the chain data
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeMode {
+	/// Running mwc node at check_node_api_http_addr
+	External,
+	/// Pruned node that runs in the wallet process
+	Embedded,
+}
+
 impl WalletConfig {
 	/// API Listen address
 	pub fn api_listen_addr(&self) -> String {
@@ -164,6 +377,17 @@ impl WalletConfig {
 		format!("127.0.0.1:{}", self.owner_api_listen_port())
 	}
 
+	/// gRPC Owner API listen address, None if it is disabled
+	pub fn owner_grpc_listen_addr(&self) -> Option<String> {
+		self.owner_grpc_listen_port
+			.map(|port| format!("127.0.0.1:{}", port))
+	}
+
+	/// True if the wallet runs its own node
+	pub fn is_embedded_node(&self) -> bool {
+		self.node_mode == Some(NodeMode::Embedded)
+	}
+
 	pub fn get_data_path(&self) -> String {
 		//mqs feature
 		self.wallet_data_dir
@@ -195,6 +419,10 @@ pub enum ConfigError {
 	/// Path doesn't exist
 	#[fail(display = "Not found expected path {}", _0)]
 	PathNotFoundError(String),
+
+	/// Configuration shares resources with the wallet of another chain type
+	#[fail(display = "Configuration conflicts with another chain, {}", _0)]
+	ChainCollisionError(String),
 }
 
 impl From<io::Error> for ConfigError {
@@ -216,6 +444,9 @@ pub struct TorConfig {
 	pub socks_running: bool,
 	/// Optional log file for tor. Default is
 	pub tor_log_file: Option<String>,
+	/// Additional address derivation indexes to publish onion services for. The main
+	/// service is always published for the current address index (grinbox_address_index)
+	pub listener_address_indexes: Option<Vec<u32>>,
 }
 
 impl Default for TorConfig {
@@ -226,6 +457,7 @@ impl Default for TorConfig {
 			send_config_dir: ".".into(),
 			socks_running: false,
 			tor_log_file: None,
+			listener_address_indexes: None,
 		}
 	}
 }
@@ -0,0 +1,377 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bearer tokens for the Owner API. Every token has the scopes that limit the API methods it can
+//! call and the expiration time. Only the token hashes are stored, the token itself is shown
+//! once when it is issued.
+
+use crate::util;
+use crate::{Error, ErrorKind};
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+/// File with the issued tokens, stored in the top level wallet directory
+pub const API_TOKENS_FILE: &str = "owner_api_tokens.json";
+
+const TOKEN_PREFIX: &str = "mwc_";
+
+/// Group of the Owner API methods that a token can call
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenScope {
+	/// Balances, outputs, transactions, proofs and the wallet status
+	Read,
+	/// Build, finalize, post and cancel transactions
+	Send,
+	/// Atomic swap trades
+	Swap,
+	/// Everything, including the wallet lifecycle and the configuration
+	Admin,
+}
+
+impl fmt::Display for TokenScope {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			TokenScope::Read => "read",
+			TokenScope::Send => "send",
+			TokenScope::Swap => "swap",
+			TokenScope::Admin => "admin",
+		};
+		write!(f, "{}", s)
+	}
+}
+
+impl FromStr for TokenScope {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.trim().to_lowercase().as_str() {
+			"read" => Ok(TokenScope::Read),
+			"send" => Ok(TokenScope::Send),
+			"swap" => Ok(TokenScope::Swap),
+			"admin" => Ok(TokenScope::Admin),
+			_ => Err(ErrorKind::ArgumentError(format!(
+				"Unknown token scope '{}', expected read, send, swap or admin",
+				s
+			))
+			.into()),
+		}
+	}
+}
+
+/// Issued token. The token is identified by the hash, `id` is a short public name of the token.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiToken {
+	/// Token Id, to list and revoke the token
+	pub id: String,
+	/// SHA256 of the token, hex
+	pub hash: String,
+	/// Methods that the token can call
+	pub scopes: Vec<TokenScope>,
+	/// Time when the token was issued
+	pub created: DateTime<Utc>,
+	/// Token is rejected after this time
+	pub expires: DateTime<Utc>,
+}
+
+impl ApiToken {
+	/// Check if the token is expired
+	pub fn is_expired(&self, now: &DateTime<Utc>) -> bool {
+		self.expires <= *now
+	}
+}
+
+/// Access rights of the Owner API request. The listener middleware puts it into the request
+/// extensions, the handlers check it against the called method.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApiAccess {
+	/// Authorized by the api secret
+	Full,
+	/// Authorized by the token with these scopes
+	Scopes(Vec<TokenScope>),
+}
+
+impl ApiAccess {
+	/// Check if the Owner API method can be called
+	pub fn allows(&self, method: &str) -> bool {
+		match self {
+			ApiAccess::Full => true,
+			ApiAccess::Scopes(scopes) => {
+				scopes.contains(&TokenScope::Admin)
+					|| match method_scope(method) {
+						None => true,
+						Some(scope) => scopes.contains(&scope),
+					}
+			}
+		}
+	}
+
+	/// JSON-RPC error response if the request isn't allowed. Requests without a method
+	/// (batches) are allowed for the full access only.
+	pub fn check_request(&self, val: &serde_json::Value) -> Option<serde_json::Value> {
+		let method = val["method"].as_str().unwrap_or("");
+		if (method.is_empty() && *self != ApiAccess::Full) || !self.allows(method) {
+			return Some(serde_json::json!({
+				"jsonrpc": "2.0",
+				"id": val["id"],
+				"error": {
+					"message": format!("Token scopes don't allow method '{}'", method),
+					"code": -32005
+				}
+			}));
+		}
+		None
+	}
+}
+
+/// Scope needed to call the Owner API method. None - any valid token can call it.
+/// The wallet lifecycle, including the secure API session setup, needs the admin scope.
+pub fn method_scope(method: &str) -> Option<TokenScope> {
+	if method.starts_with("swap_") {
+		return Some(TokenScope::Swap);
+	}
+	match method {
+		"accounts"
+		| "retrieve_outputs"
+		| "retrieve_txs"
+		| "query_outputs"
+		| "query_txs"
+		| "retrieve_summary_info"
+		| "retrieve_accounts_summary_info"
+		| "get_stored_tx"
+		| "verify_slate_messages"
+		| "node_height"
+		| "is_paused"
+		| "get_top_level_directory"
+		| "get_auto_cancel_policy"
+		| "get_scan_progress"
+		| "get_listener_metrics"
+		| "get_node_cache_metrics"
+		| "query_listeners"
+		| "get_consensus_params"
+		| "retrieve_history_series"
+		| "get_exchange_rate"
+		| "resolve_payment_id"
+		| "get_updater_messages"
+		| "get_mqs_address"
+		| "get_mqs_outbox"
+		| "get_wallet_public_address"
+		| "list_used_address_indices"
+		| "address_mappings"
+		| "retrieve_payment_proof"
+		| "verify_payment_proof"
+		| "lookup_kernel"
+		| "decode_slatepack_message" => Some(TokenScope::Read),
+		"init_send_tx"
+		| "issue_invoice_tx"
+		| "process_invoice_tx"
+		| "tx_lock_outputs"
+		| "finalize_tx"
+		| "post_tx"
+		| "cancel_tx"
+		| "cancel_expired_receives"
+		| "flush_mqs_outbox"
+		| "encode_slatepack_message" => Some(TokenScope::Send),
+		_ => Some(TokenScope::Admin),
+	}
+}
+
+/// Parse comma separated scopes, like 'read,send'
+pub fn parse_scopes(s: &str) -> Result<Vec<TokenScope>, Error> {
+	let mut scopes = vec![];
+	for scope in s.split(',').filter(|s| !s.trim().is_empty()) {
+		let scope = TokenScope::from_str(scope)?;
+		if !scopes.contains(&scope) {
+			scopes.push(scope);
+		}
+	}
+	if scopes.is_empty() {
+		return Err(ErrorKind::ArgumentError("Token scopes are not specified".to_string()).into());
+	}
+	Ok(scopes)
+}
+
+/// Parse the token time to live: number with the unit s, m, h or d, like '24h'.
+/// Number without the unit is seconds.
+pub fn parse_ttl(s: &str) -> Result<Duration, Error> {
+	let s = s.trim();
+	let (num, unit) = match s.chars().last() {
+		Some(c) if c.is_ascii_alphabetic() => (&s[..s.len() - 1], c.to_ascii_lowercase()),
+		_ => (s, 's'),
+	};
+	let num: i64 = num
+		.parse()
+		.map_err(|_| ErrorKind::ArgumentError(format!("Invalid token ttl '{}'", s)))?;
+	if num <= 0 {
+		return Err(ErrorKind::ArgumentError(format!("Invalid token ttl '{}'", s)).into());
+	}
+	let secs = match unit {
+		's' => Some(num),
+		'm' => num.checked_mul(60),
+		'h' => num.checked_mul(60 * 60),
+		'd' => num.checked_mul(24 * 60 * 60),
+		_ => {
+			return Err(ErrorKind::ArgumentError(format!(
+				"Invalid token ttl '{}', expected units s, m, h or d",
+				s
+			))
+			.into())
+		}
+	};
+	// Duration is stored in milliseconds, larger values panic
+	match secs.filter(|secs| *secs <= i64::MAX / 1000) {
+		Some(secs) => Ok(Duration::seconds(secs)),
+		None => Err(ErrorKind::ArgumentError(format!("Token ttl '{}' is too large", s)).into()),
+	}
+}
+
+fn token_hash(token: &str) -> String {
+	util::to_hex(
+		ring::digest::digest(&ring::digest::SHA256, token.as_bytes())
+			.as_ref()
+			.to_vec(),
+	)
+}
+
+// Loaded tokens and the modification time of the file they are loaded from
+type TokenCache = Option<(Option<SystemTime>, Vec<ApiToken>)>;
+
+/// Issued tokens of the wallet, stored as JSON in the wallet directory. The tokens are loaded
+/// once and reloaded when the token commands change the file. Clones share the loaded tokens.
+#[derive(Clone, Debug)]
+pub struct ApiTokenStore {
+	path: PathBuf,
+	cache: Arc<RwLock<TokenCache>>,
+}
+
+impl ApiTokenStore {
+	/// Token store in the top level wallet directory
+	pub fn new<P: AsRef<Path>>(wallet_dir: P) -> Self {
+		ApiTokenStore {
+			path: wallet_dir.as_ref().join(API_TOKENS_FILE),
+			cache: Arc::new(RwLock::new(None)),
+		}
+	}
+
+	fn modified(&self) -> Option<SystemTime> {
+		fs::metadata(&self.path).and_then(|m| m.modified()).ok()
+	}
+
+	/// Issued tokens, including the expired ones
+	pub fn load(&self) -> Result<Vec<ApiToken>, Error> {
+		// The token commands can run in another process, the file time shows the change
+		let modified = self.modified();
+		if let Some((time, tokens)) = &*self.cache.read().unwrap() {
+			if *time == modified {
+				return Ok(tokens.clone());
+			}
+		}
+		let tokens: Vec<ApiToken> = if !self.path.exists() {
+			vec![]
+		} else {
+			let data = fs::read_to_string(&self.path).map_err(|e| {
+				ErrorKind::IO(format!("Unable to read {}, {}", self.path.display(), e))
+			})?;
+			serde_json::from_str(&data).map_err(|e| {
+				ErrorKind::Format(format!("Unable to parse {}, {}", self.path.display(), e))
+			})?
+		};
+		*self.cache.write().unwrap() = Some((modified, tokens.clone()));
+		Ok(tokens)
+	}
+
+	/// Check if there are issued tokens, including the expired ones
+	pub fn is_empty(&self) -> Result<bool, Error> {
+		Ok(self.load()?.is_empty())
+	}
+
+	fn save(&self, tokens: &[ApiToken]) -> Result<(), Error> {
+		let data = serde_json::to_string_pretty(tokens)
+			.map_err(|e| ErrorKind::Format(format!("Unable to serialize tokens, {}", e)))?;
+		let tmp_path = self.path.with_extension("tmp");
+		fs::write(&tmp_path, data)
+			.map_err(|e| ErrorKind::IO(format!("Unable to write {}, {}", tmp_path.display(), e)))?;
+		fs::rename(&tmp_path, &self.path).map_err(|e| {
+			ErrorKind::IO(format!("Unable to write {}, {}", self.path.display(), e))
+		})?;
+		*self.cache.write().unwrap() = Some((self.modified(), tokens.to_vec()));
+		Ok(())
+	}
+
+	/// Issue a new token. Returns the token record and the token itself, the token can't be
+	/// restored later. Expired tokens are removed from the store.
+	pub fn issue(
+		&self,
+		scopes: Vec<TokenScope>,
+		ttl: Duration,
+	) -> Result<(ApiToken, String), Error> {
+		let mut secret = [0u8; 32];
+		rand::thread_rng().fill(&mut secret);
+		let token = format!("{}{}", TOKEN_PREFIX, util::to_hex(secret.to_vec()));
+		let hash = token_hash(&token);
+		let created = Utc::now();
+		let expires = created
+			.checked_add_signed(ttl)
+			.ok_or(ErrorKind::ArgumentError(
+				"Token ttl is too large".to_string(),
+			))?;
+		let record = ApiToken {
+			id: hash[..8].to_string(),
+			hash,
+			scopes,
+			created,
+			expires,
+		};
+		let mut tokens: Vec<ApiToken> = self
+			.load()?
+			.into_iter()
+			.filter(|t| !t.is_expired(&created))
+			.collect();
+		tokens.push(record.clone());
+		self.save(&tokens)?;
+		Ok((record, token))
+	}
+
+	/// Revoke the token by id. Returns false if there is no such token.
+	pub fn revoke(&self, id: &str) -> Result<bool, Error> {
+		let tokens = self.load()?;
+		let count = tokens.len();
+		let tokens: Vec<ApiToken> = tokens.into_iter().filter(|t| t.id != id).collect();
+		if tokens.len() == count {
+			return Ok(false);
+		}
+		self.save(&tokens)?;
+		Ok(true)
+	}
+
+	/// Scopes of the token, None if the token is unknown or expired
+	pub fn validate(&self, token: &str) -> Result<Option<Vec<TokenScope>>, Error> {
+		if !token.starts_with(TOKEN_PREFIX) {
+			return Ok(None);
+		}
+		let hash = token_hash(token);
+		let now = Utc::now();
+		Ok(self
+			.load()?
+			.into_iter()
+			.find(|t| t.hash == hash && !t.is_expired(&now))
+			.map(|t| t.scopes))
+	}
+}
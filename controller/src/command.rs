@@ -15,15 +15,25 @@
 //! Grin wallet command-line function implementations
 
 use crate::api::TLSConfig;
+use crate::api_tokens::{ApiTokenStore, TokenScope};
 use crate::apiwallet::Owner;
-use crate::config::{MQSConfig, TorConfig, WalletConfig, WALLET_CONFIG_FILE_NAME};
+use crate::config::{
+	parse_node_address_string, GlobalWalletConfig, MQSConfig, TorConfig, WalletConfig,
+	GRIN_WALLET_DIR, WALLET_CONFIG_FILE_NAME,
+};
+use crate::core::libtx::tx_fee;
 use crate::core::{core, global};
 use crate::error::{Error, ErrorKind};
-use crate::impls::{create_sender, SlateGetter as _};
+use crate::impls::{create_backup_store, create_sender, create_transport, SlateGetter as _};
 use crate::impls::{PathToSlateGetter, PathToSlatePutter, SlatePutter};
 use crate::keychain;
+use crate::libwallet::tx_import::{self, TxImportFormat};
 use crate::libwallet::{
-	swap::types::Currency, InitTxArgs, IssueInvoiceTxArgs, NodeClient, WalletLCProvider,
+	multisig::{MultisigScheme, MultisigSession, MultisigStatus},
+	swap::types::Currency,
+	AddressMapping, CheckCategory, GcArgs, InitTxArgs, InitTxSendArgs, IssueInvoiceTxArgs,
+	MultisigCreateArgs, NodeClient, OutputQueryArgs, OutputStatus, OutputTagArgs, PaymentProof,
+	ReissueTxArgs, TxLabelArgs, TxLogEntryType, TxQueryArgs, TxStatus, WalletLCProvider,
 };
 use crate::util::secp::key::SecretKey;
 use crate::util::{Mutex, ZeroingString};
@@ -31,21 +41,37 @@ use crate::{controller, display};
 use chrono::Utc;
 use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
 use grin_wallet_impls::adapters::{
-	create_swap_message_sender, validate_tor_address, MarketplaceMessageSender,
+	create_swap_message_sender, create_swap_transport, get_mwcmqs_brocker, p2p,
+	validate_tor_address, MarketplaceMessageSender,
 };
+use grin_wallet_impls::lifecycle::{slip39, WalletSeed};
 use grin_wallet_impls::tor;
-use grin_wallet_impls::{libp2p_messaging, HttpDataSender};
-use grin_wallet_impls::{Address, MWCMQSAddress, Publisher};
+use grin_wallet_impls::{copy_mwc713_files, read_mwc713_wallet};
+use grin_wallet_impls::{libp2p_messaging, DefaultLCProvider, DefaultWalletImpl, HttpDataSender};
+use grin_wallet_impls::{Address, HTTPNodeClient, MWCMQSAddress, Publisher};
+use grin_wallet_impls::{OfflineBundleStage, OfflineTxBundle};
 use grin_wallet_libwallet::api_impl::{owner, owner_eth, owner_libp2p, owner_swap};
+use grin_wallet_libwallet::audit_log;
+use grin_wallet_libwallet::backup;
 use grin_wallet_libwallet::internal::selection;
+use grin_wallet_libwallet::listener_status;
+use grin_wallet_libwallet::log_privacy;
+use grin_wallet_libwallet::migration;
+use grin_wallet_libwallet::notifications::{self, WalletEvent};
+use grin_wallet_libwallet::payment_id::PaymentId;
+use grin_wallet_libwallet::payment_uri::PaymentUri;
+use grin_wallet_libwallet::proof::payment_proof;
 use grin_wallet_libwallet::proof::proofaddress::{self, ProvableAddress};
 use grin_wallet_libwallet::proof::tx_proof::TxProof;
+use grin_wallet_libwallet::proof::vectors;
 use grin_wallet_libwallet::slatepack::SlatePurpose;
+use grin_wallet_libwallet::swap::bitcoin::WALLET_SECONDARY_ADDRESS;
 use grin_wallet_libwallet::swap::fsm::state::StateId;
-use grin_wallet_libwallet::swap::trades;
+use grin_wallet_libwallet::swap::history;
+use grin_wallet_libwallet::swap::trades::{self, AutoSwapSession};
 use grin_wallet_libwallet::swap::types::Action;
 use grin_wallet_libwallet::swap::{message, Swap};
-use grin_wallet_libwallet::{Slate, TxLogEntry, WalletInst};
+use grin_wallet_libwallet::{Slate, TxLogEntry, WalletInst, GRIN_V4_TARGET_SLATE_VERSION};
 use grin_wallet_util::grin_core::consensus::GRIN_BASE;
 use grin_wallet_util::grin_core::core::amount_to_hr_string;
 use grin_wallet_util::grin_core::global::{FLOONET_DNS_SEEDS, MAINNET_DNS_SEEDS};
@@ -54,15 +80,16 @@ use grin_wallet_util::grin_p2p::{libp2p_connection, PeerAddr};
 use serde_json as json;
 use serde_json::json;
 use serde_json::{Map as JsonMap, Value as JsonValue};
-use std::collections::HashSet;
-use std::fs::File;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fs::{self, File};
 use std::io;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 lazy_static! {
@@ -149,9 +176,250 @@ where
 	Ok(())
 }
 
+/// Arguments for the backup command
+pub struct BackupArgs {
+	/// Directory or s3://<bucket>/<prefix>. None - backup_dest of the config
+	pub dest: Option<String>,
+}
+
+pub fn backup<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: BackupArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let location = api.backup_wallet(m, args.dest.clone())?;
+		println!("Wallet backup is created at {}", location);
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Arguments for the restore command
+pub struct RestoreArgs {
+	/// Directory or s3://<bucket>/<prefix>
+	pub src: String,
+	/// Name of the backup. None - the latest one
+	pub name: Option<String>,
+	/// Recovery phrase of the wallet, the backups are encrypted with the key derived from it
+	pub phrase: ZeroingString,
+}
+
+pub fn restore<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	args: RestoreArgs,
+	wallet_data_dir: Option<&str>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let mut w_lock = owner_api.wallet_inst.lock();
+	let p = w_lock.lc_provider()?;
+	let data_dir = Path::new(&p.get_top_level_directory()?)
+		.join(wallet_data_dir.unwrap_or(GRIN_WALLET_DIR))
+		.to_str()
+		.unwrap()
+		.to_string();
+	if p.wallet_exists(None, wallet_data_dir)? {
+		return Err(ErrorKind::ArgumentError(format!(
+			"Wallet already exists at {}, restore needs an empty wallet directory",
+			data_dir
+		))
+		.into());
+	}
+
+	let keychain: keychain::ExtKeychain =
+		WalletSeed::from_mnemonic(args.phrase)?.derive_keychain(global::is_floonet())?;
+	let key = backup::backup_key(&keychain)?;
+	let store = create_backup_store(&args.src)?;
+	let name = match args.name {
+		Some(name) => name,
+		None => backup::list_backups(&*store)?
+			.pop()
+			.ok_or(ErrorKind::ArgumentError(format!(
+				"There are no wallet backups at {}",
+				store.location()
+			)))?,
+	};
+	let snapshot = store.get(&name)?;
+	let files = backup::restore_snapshot(&snapshot, &key, &data_dir)?;
+	println!(
+		"{} files of the backup {} are restored into {}",
+		files, name, data_dir
+	);
+	if p.wallet_exists(None, wallet_data_dir)? {
+		println!("Open the wallet with the password it had at the backup time.");
+	} else {
+		println!("The backup doesn't have the seed file, create it with 'init -r' and the recovery phrase.");
+	}
+	Ok(())
+}
+
+/// Arguments for the migrate command
+pub struct MigrateArgs {
+	/// mwc713 data directory
+	pub from_mwc713: String,
+	/// Password of the mwc713 wallet, the migrated wallet gets the same password
+	pub password: ZeroingString,
+}
+
+pub fn migrate<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	g_args: &GlobalArgs,
+	args: MigrateArgs,
+	wallet_data_dir: Option<&str>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let (seed, data) = read_mwc713_wallet(&args.from_mwc713, args.password.clone())?;
+	let mwc713_balance = data.balance();
+	println!(
+		"mwc713 wallet has {} account(s), {} transaction(s), {} output(s) and {} contact(s)",
+		data.accounts.len(),
+		data.txs.len(),
+		data.outputs.len(),
+		data.contacts.len()
+	);
+
+	let (mask, data_dir) = {
+		let mut w_lock = owner_api.wallet_inst.lock();
+		let p = w_lock.lc_provider()?;
+		if p.wallet_exists(None, wallet_data_dir)? {
+			return Err(ErrorKind::ArgumentError(format!(
+				"Wallet already exists at {}, migration needs an empty wallet directory",
+				Path::new(&p.get_top_level_directory()?)
+					.join(wallet_data_dir.unwrap_or(GRIN_WALLET_DIR))
+					.display()
+			))
+			.into());
+		}
+		p.create_config(
+			&g_args.chain_type,
+			WALLET_CONFIG_FILE_NAME,
+			None,
+			None,
+			None,
+			None,
+		)?;
+		p.create_wallet(
+			None,
+			Some(ZeroingString::from(seed.to_mnemonic()?)),
+			32,
+			args.password.clone(),
+			false,
+			wallet_data_dir,
+		)?;
+		let mask = p.open_wallet(None, args.password, false, false, wallet_data_dir)?;
+		let data_dir = p.wallet_inst()?.get_data_file_dir().to_string();
+		(mask, data_dir)
+	};
+
+	let files = copy_mwc713_files(&args.from_mwc713, &data_dir)?;
+	controller::owner_single_use(None, mask.as_ref(), Some(owner_api), |api, m| {
+		let report = api.import_wallet_data(m, data)?;
+		for s in &report.skipped {
+			println!("Skipped {}", s);
+		}
+		println!(
+			"Imported {} account(s), {} transaction(s), {} output(s), {} contact(s) and {} stored transaction and proof file(s)",
+			report.accounts, report.txs, report.outputs, report.contacts, files
+		);
+
+		println!("Scanning the chain to verify the balance, it can take a while...");
+		api.scan(m, Some(1), false)?;
+		let mut balance = 0;
+		for account in api.accounts(m)? {
+			api.set_active_account(m, &account.label)?;
+			let (_, outputs) = api.retrieve_outputs(m, false, false, None)?;
+			balance += migration::outputs_balance(outputs.iter().map(|o| &o.output));
+		}
+		api.set_active_account(m, "default")?;
+		if balance == mwc713_balance {
+			println!(
+				"Balance {} MWC is verified, the migration is completed",
+				amount_to_hr_string(balance, false)
+			);
+		} else {
+			println!(
+				"WARNING: The balance after the scan is {} MWC, mwc713 had {} MWC. The wallet keeps the scan result, please check the transactions.",
+				amount_to_hr_string(balance, false),
+				amount_to_hr_string(mwc713_balance, false)
+			);
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Arguments for the seed export-shamir command
+pub struct SeedExportShamirArgs {
+	pub passphrase: ZeroingString,
+	pub shares: u8,
+	pub threshold: u8,
+}
+
+pub fn seed_export_shamir<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	args: SeedExportShamirArgs,
+	wallet_data_dir: Option<&str>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let mut w_lock = owner_api.wallet_inst.lock();
+	let p = w_lock.lc_provider()?;
+	let m = p.get_mnemonic(None, args.passphrase, wallet_data_dir)?;
+	let shares = slip39::split_mnemonic(&m, args.threshold, args.shares)?;
+	slip39::show_shamir_shares(args.threshold, &shares);
+	Ok(())
+}
+
+/// Arguments for the decoy command
+pub struct DecoyArgs {
+	pub password: ZeroingString,
+	/// `None` removes the decoy password
+	pub decoy_password: Option<ZeroingString>,
+}
+
+pub fn decoy<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	args: DecoyArgs,
+	wallet_data_dir: Option<&str>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let remove = args.decoy_password.is_none();
+	owner_api.set_decoy_password(None, args.password, args.decoy_password, wallet_data_dir)?;
+	if remove {
+		println!("Decoy password is removed");
+	} else {
+		println!("Decoy password is set. It opens the decoy wallet, fund it to make it look used");
+	}
+	Ok(())
+}
+
 /// Arguments for listen command
 pub struct ListenArgs {
 	pub method: String,
+	pub show_queue: bool,
+	pub flush_queue: bool,
+	pub status: bool,
+	pub stop: bool,
 }
 
 pub fn listen<L, C, K>(
@@ -169,6 +437,50 @@ where
 	C: NodeClient + 'static,
 	K: keychain::Keychain + 'static,
 {
+	if args.status {
+		display::listeners_status(owner_api.query_listeners()?);
+		return Ok(());
+	}
+
+	if args.stop {
+		controller::stop_listener(&args.method, controller::LISTENER_STOP_TIMEOUT)?;
+		println!("{} listener is stopped", args.method);
+		return Ok(());
+	}
+
+	if args.show_queue || args.flush_queue {
+		let messages = if args.flush_queue {
+			// Delivery needs a connection to the mwcmqs broker
+			if get_mwcmqs_brocker().is_none() {
+				controller::init_start_mwcmqs_listener(
+					owner_api.wallet_inst.clone(),
+					mqs_config.clone(),
+					keychain_mask,
+					false,
+				)?;
+			}
+			owner_api.flush_mqs_outbox()?
+		} else {
+			owner_api.get_mqs_outbox()?
+		};
+		display::mwcmqs_outbox(messages);
+		return Ok(());
+	}
+
+	if !cli_mode {
+		controller::start_config_reload_listener(owner_api.wallet_inst.clone())?;
+		if config.autoswap_resume.unwrap_or(false) {
+			resume_autoswap(
+				owner_api.wallet_inst.clone(),
+				keychain_mask.clone(),
+				config.api_listen_addr(),
+				tor_config.clone(),
+				mqs_config.clone(),
+				g_args.tls_conf.clone(),
+			);
+		}
+	}
+
 	match args.method.as_str() {
 		"http" => {
 			let wallet_inst = owner_api.wallet_inst.clone();
@@ -187,6 +499,7 @@ where
 						&tor_config.socks_proxy_addr,
 						&config.libp2p_listen_port,
 						&tor_config.tor_log_file,
+						&tor_config.listener_address_indexes,
 					);
 					if let Err(e) = res {
 						error!("Error starting http listener: {}", e);
@@ -216,11 +529,27 @@ where
 				Error::from(ErrorKind::ListenerError)
 			})?;
 		}
-		method => {
-			return Err(
-				ErrorKind::ArgumentError(format!("No listener for method '{}'", method)).into(),
-			);
-		}
+		method => match create_transport(method, "")? {
+			Some(transport) => {
+				controller::start_transport_listener(
+					owner_api.wallet_inst.clone(),
+					keychain_mask,
+					transport,
+					!cli_mode,
+				)
+				.map_err(|e| {
+					error!("Unable to start {} listener, {}", method, e);
+					Error::from(ErrorKind::ListenerError)
+				})?;
+			}
+			None => {
+				return Err(ErrorKind::ArgumentError(format!(
+					"No listener for method '{}'",
+					method
+				))
+				.into());
+			}
+		},
 	};
 	Ok(())
 }
@@ -232,6 +561,7 @@ pub fn owner_api<L, C, K>(
 	tor_config: &TorConfig,
 	mqs_config: &MQSConfig,
 	g_args: &GlobalArgs,
+	wallets: HashMap<String, Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>>,
 ) -> Result<(), Error>
 where
 	L: WalletLCProvider<'static, C, K> + Send + Sync + 'static,
@@ -242,6 +572,8 @@ where
 	// also being run at the same time
 	let km = Arc::new(Mutex::new(keychain_mask));
 
+	controller::start_config_reload_listener(owner_api.wallet_inst.clone())?;
+
 	// Starting MQS first
 	if config.owner_api_include_mqs_listener.unwrap_or(false) {
 		let _ = controller::init_start_mwcmqs_listener(
@@ -253,6 +585,37 @@ where
 		)?;
 	}
 
+	if let Some(addr) = config.owner_grpc_listen_addr() {
+		controller::grpc_owner_listener(
+			owner_api.wallet_inst.clone(),
+			km.clone(),
+			&addr,
+			g_args.api_secret.clone(),
+			g_args.tls_conf.clone(),
+		)?;
+	}
+
+	// Owner API can stop and start the listeners of this process
+	listener_status::set_listener_control(Some(Arc::new(controller::ListenerController::new(
+		owner_api.wallet_inst.clone(),
+		km.clone(),
+		config.api_listen_addr(),
+		g_args.tls_conf.clone(),
+		tor_config.clone(),
+		mqs_config.clone(),
+	))));
+
+	if config.autoswap_resume.unwrap_or(false) {
+		resume_autoswap(
+			owner_api.wallet_inst.clone(),
+			km.clone(),
+			config.api_listen_addr(),
+			tor_config.clone(),
+			mqs_config.clone(),
+			g_args.tls_conf.clone(),
+		);
+	}
+
 	// Now Owner API
 	controller::owner_listener(
 		owner_api.wallet_inst.clone(),
@@ -261,15 +624,210 @@ where
 		g_args.api_secret.clone(),
 		g_args.tls_conf.clone(),
 		config.owner_api_include_foreign.clone(),
+		config.owner_api_invoice_endpoint.clone(),
 		Some(tor_config.clone()),
+		wallets,
 	)
 	.map_err(|e| ErrorKind::LibWallet(format!("Unable to start Listener, {}", e)))?;
 	Ok(())
 }
 
+/// Resume the auto swap of the trades that were running in the auto swap mode when the wallet
+/// was stopped. Every trade is resumed when the listener of its method is running, the trades
+/// which listener is not started stay in the list until the next start.
+fn resume_autoswap<L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>,
+	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	api_listen_addr: String,
+	tor_config: TorConfig,
+	mqs_config: MQSConfig,
+	tls_conf: Option<TLSConfig>,
+) where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let sessions = match trades::list_autoswap_sessions() {
+		Ok(sessions) => sessions,
+		Err(e) => {
+			error!("Unable to read auto swap sessions, {}", e);
+			return;
+		}
+	};
+	if sessions.is_empty() {
+		return;
+	}
+
+	let res = thread::Builder::new()
+		.name("wallet-autoswap-resume".to_string())
+		.spawn(move || {
+			for session in sessions {
+				let km = keychain_mask.lock().clone();
+				let swap = match owner_swap::swap_get(
+					wallet_inst.clone(),
+					km.as_ref(),
+					&session.swap_id,
+				) {
+					Ok(swap) => swap,
+					Err(e) => {
+						warn!(
+							"Unable to resume auto swap for trade {}, {}",
+							session.swap_id, e
+						);
+						let _ = trades::remove_autoswap_session(&session.swap_id);
+						continue;
+					}
+				};
+
+				// The listeners are starting together with this thread
+				let start = Instant::now();
+				let is_ready = || match swap.communication_method.as_str() {
+					"mwcmqs" => get_mwcmqs_brocker().is_some(),
+					"tor" => {
+						controller::is_foreign_api_running()
+							&& tor::status::get_tor_address().is_some()
+					}
+					method => controller::get_transport_listener_address(method).is_some(),
+				};
+				while !is_ready() && start.elapsed() < controller::FOREIGN_LISTENER_START_TIMEOUT {
+					thread::sleep(Duration::from_millis(500));
+				}
+				if !is_ready() {
+					warn!(
+						"Auto swap for trade {} is not resumed, {} listener is not running",
+						session.swap_id, swap.communication_method
+					);
+					continue;
+				}
+				if swap.communication_method == "mwcmqs" {
+					controller::wait_for_mwcmqs_listener(controller::MWCMQS_LISTENER_START_TIMEOUT);
+				}
+
+				info!("Resuming auto swap for trade {}", session.swap_id);
+				let args = SwapArgs {
+					subcommand: SwapSubcommand::Autoswap,
+					swap_id: Some(session.swap_id.clone()),
+					adjust: vec![],
+					method: None,
+					destination: None,
+					apisecret: None,
+					secondary_fee: session.secondary_fee,
+					message_file_name: session.message_file_name,
+					buyer_refund_address: session.buyer_refund_address,
+					start_listener: false,
+					secondary_address: session.secondary_address,
+					json_format: false,
+					electrum_node_uri1: None,
+					electrum_node_uri2: None,
+					eth_swap_contract_address: None,
+					erc20_swap_contract_address: None,
+					eth_infura_project_id: None,
+					eth_redirect_to_private_wallet: None,
+					wait_for_backup1: session.wait_for_backup1,
+					tag: None,
+					lock_window_sec: None,
+					currency: None,
+				};
+				if let Err(e) = swap(
+					wallet_inst.clone(),
+					km.as_ref(),
+					api_listen_addr.clone(),
+					mqs_config.clone(),
+					tor_config.clone(),
+					tls_conf.clone(),
+					args,
+					true,
+				) {
+					error!(
+						"Unable to resume auto swap for trade {}, {}",
+						session.swap_id, e
+					);
+				}
+			}
+		});
+	if let Err(e) = res {
+		error!("Unable to start auto swap resume thread, {}", e);
+	}
+}
+
+/// Owner API token management, 'owner_api' runs it instead of the listener
+pub enum ApiTokenArgs {
+	Issue {
+		scopes: Vec<TokenScope>,
+		ttl: chrono::Duration,
+	},
+	List,
+	Revoke(String),
+}
+
+pub fn owner_api_token<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	args: ApiTokenArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let store = {
+		let mut w_lock = owner_api.wallet_inst.lock();
+		ApiTokenStore::new(w_lock.lc_provider()?.get_top_level_directory()?)
+	};
+	match args {
+		ApiTokenArgs::Issue { scopes, ttl } => {
+			let (record, token) = store.issue(scopes, ttl)?;
+			println!(
+				"Token {} with scopes {} is valid until {}",
+				record.id,
+				record
+					.scopes
+					.iter()
+					.map(|s| s.to_string())
+					.collect::<Vec<String>>()
+					.join(","),
+				record.expires.to_rfc3339()
+			);
+			println!("{}", token);
+			println!(
+				"The token is shown only once. Send it as 'Authorization: Bearer <token>' header."
+			);
+		}
+		ApiTokenArgs::List => {
+			let now = Utc::now();
+			let tokens = store.load()?;
+			if tokens.is_empty() {
+				println!("No Owner API tokens are issued");
+			}
+			for t in tokens {
+				println!(
+					"{}  {:<20}  expires {}{}",
+					t.id,
+					t.scopes
+						.iter()
+						.map(|s| s.to_string())
+						.collect::<Vec<String>>()
+						.join(","),
+					t.expires.to_rfc3339(),
+					if t.is_expired(&now) { " (expired)" } else { "" }
+				);
+			}
+		}
+		ApiTokenArgs::Revoke(id) => {
+			if !store.revoke(&id)? {
+				return Err(ErrorKind::ArgumentError(format!("Token {} is not found", id)).into());
+			}
+			println!("Token {} is revoked", id);
+		}
+	}
+	Ok(())
+}
+
 /// Arguments for account command
 pub struct AccountArgs {
 	pub create: Option<String>,
+	/// (old label, new label)
+	pub rename: Option<(String, String)>,
+	pub delete: Option<String>,
 }
 
 pub fn account<L, C, K>(
@@ -282,7 +840,33 @@ where
 	C: NodeClient + 'static,
 	K: keychain::Keychain + 'static,
 {
-	if args.create.is_none() {
+	if let Some((old_label, new_label)) = args.rename {
+		let res = controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+			api.rename_account(m, &old_label, &new_label)?;
+			thread::sleep(Duration::from_millis(200));
+			info!("Account: '{}' Renamed to '{}'!", old_label, new_label);
+			Ok(())
+		});
+		if let Err(e) = res {
+			thread::sleep(Duration::from_millis(200));
+			let err_str = format!("Error renaming account '{}': {}", old_label, e);
+			error!("{}", err_str);
+			return Err(ErrorKind::LibWallet(err_str).into());
+		}
+	} else if let Some(label) = args.delete {
+		let res = controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+			api.delete_account(m, &label)?;
+			thread::sleep(Duration::from_millis(200));
+			info!("Account: '{}' Deleted!", label);
+			Ok(())
+		});
+		if let Err(e) = res {
+			thread::sleep(Duration::from_millis(200));
+			let err_str = format!("Error deleting account '{}': {}", label, e);
+			error!("{}", err_str);
+			return Err(ErrorKind::LibWallet(err_str).into());
+		}
+	} else if args.create.is_none() {
 		let res = controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
 			let acct_mappings = api.accounts(m)?;
 			// give logging thread a moment to catch up
@@ -313,13 +897,264 @@ where
 	Ok(())
 }
 
-/// Arguments for the send command
-pub struct SendArgs {
-	pub amount: u64,
-	pub message: Option<String>,
-	pub minimum_confirmations: u64,
-	pub selection_strategy: String,
-	pub estimate_selection_strategies: bool,
+// Address book operation
+#[derive(PartialEq)]
+pub enum ContactSubcommand {
+	List,
+	Add,
+	Remove,
+}
+
+/// Arguments for contact command
+pub struct ContactArgs {
+	/// What we want to do with the address book
+	pub subcommand: ContactSubcommand,
+	/// Contact name for Add and Remove
+	pub name: Option<String>,
+	/// Contact address for Add
+	pub address: Option<String>,
+}
+
+pub fn contact<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: ContactArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	match args.subcommand {
+		ContactSubcommand::List => {
+			let res =
+				controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+					let contacts = api.contacts(m)?;
+					thread::sleep(Duration::from_millis(200));
+					display::contacts(contacts);
+					Ok(())
+				});
+			if let Err(e) = res {
+				let err_str = format!("Error listing contacts: {}", e);
+				error!("{}", err_str);
+				return Err(ErrorKind::LibWallet(err_str).into());
+			}
+		}
+		ContactSubcommand::Add => {
+			let name = args.name.unwrap_or_default();
+			let address = args.address.unwrap_or_default();
+			let res =
+				controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+					api.add_contact(m, &name, &address)?;
+					thread::sleep(Duration::from_millis(200));
+					info!(
+						"Contact '{}' with address {} is added",
+						name,
+						log_privacy::address(&address)
+					);
+					Ok(())
+				});
+			if let Err(e) = res {
+				thread::sleep(Duration::from_millis(200));
+				let err_str = format!("Error adding contact '{}': {}", name, e);
+				error!("{}", err_str);
+				return Err(ErrorKind::LibWallet(err_str).into());
+			}
+		}
+		ContactSubcommand::Remove => {
+			let name = args.name.unwrap_or_default();
+			let res =
+				controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+					api.remove_contact(m, &name)?;
+					thread::sleep(Duration::from_millis(200));
+					info!("Contact '{}' is removed", name);
+					Ok(())
+				});
+			if let Err(e) = res {
+				thread::sleep(Duration::from_millis(200));
+				let err_str = format!("Error removing contact '{}': {}", name, e);
+				error!("{}", err_str);
+				return Err(ErrorKind::LibWallet(err_str).into());
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Address mapping command subcommands
+pub enum AddressMappingSubcommand {
+	List,
+	Map(AddressMapping),
+	Unmap(u32),
+}
+
+/// Arguments for address-mapping command
+pub struct AddressMappingArgs {
+	/// What we want to do with the address mappings
+	pub subcommand: AddressMappingSubcommand,
+}
+
+pub fn address_mapping<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: AddressMappingArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		match &args.subcommand {
+			AddressMappingSubcommand::List => {
+				let mappings = api.address_mappings(m)?;
+				display::address_mappings(mappings);
+			}
+			AddressMappingSubcommand::Map(mapping) => {
+				api.set_address_mapping(m, mapping)?;
+				info!("Address index {} is mapped", mapping.index);
+			}
+			AddressMappingSubcommand::Unmap(index) => {
+				api.remove_address_mapping(m, *index)?;
+				info!("Address index {} is unmapped", index);
+			}
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Tor command subcommands
+pub enum TorSubcommand {
+	ListAddresses,
+	Rotate,
+}
+
+/// Arguments for tor command
+pub struct TorArgs {
+	/// What we want to do with the onion addresses
+	pub subcommand: TorSubcommand,
+}
+
+pub fn tor<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	config: &WalletConfig,
+	tor_config: &TorConfig,
+	args: TorArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let current_index = proofaddress::get_address_index();
+	match args.subcommand {
+		TorSubcommand::ListAddresses => {
+			let res =
+				controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+					let mut w_lock = api.wallet_inst.lock();
+					let lc = w_lock.lc_provider()?;
+					let tor_dir = format!("{}/tor/listener", lc.get_top_level_directory()?);
+					let w = lc.wallet_inst()?;
+					let keychain = w.keychain(m)?;
+
+					let mut indexes = vec![current_index];
+					indexes.extend(
+						tor_config
+							.listener_address_indexes
+							.clone()
+							.unwrap_or(vec![]),
+					);
+					let mut addresses = vec![];
+					for index in indexes {
+						if addresses.iter().any(|(i, _)| *i == index) {
+							continue;
+						}
+						let address = proofaddress::payment_proof_address_from_index(
+							&keychain,
+							index,
+							proofaddress::ProofAddressType::Onion,
+						)?;
+						addresses.push((index, address.public_key));
+					}
+					let stored = tor::config::list_onion_service_addresses(&tor_dir)
+						.map_err(|e| ErrorKind::TorConfig(format!("{}", e)))?;
+					display::tor_addresses(current_index, addresses, stored);
+					Ok(())
+				});
+			if let Err(e) = res {
+				let err_str = format!("Error listing onion addresses: {}", e);
+				error!("{}", err_str);
+				return Err(ErrorKind::LibWallet(err_str).into());
+			}
+		}
+		TorSubcommand::Rotate => {
+			let new_index = current_index.checked_add(1).ok_or(ErrorKind::LibWallet(
+				"Address index can't be rotated any further".to_string(),
+			))?;
+
+			// Config file is located in the parent of the wallet data directory
+			let config_file = Path::new(&config.data_file_dir)
+				.parent()
+				.map(|p| p.join(WALLET_CONFIG_FILE_NAME))
+				.ok_or(ErrorKind::LibWallet(format!(
+					"Unable to locate {} for wallet data at {}",
+					WALLET_CONFIG_FILE_NAME, config.data_file_dir
+				)))?;
+			let config_file = config_file.to_str().unwrap().to_string();
+			let mut global_config = GlobalWalletConfig::new(&config_file).map_err(|e| {
+				ErrorKind::LibWallet(format!("Unable to read config {}, {}", config_file, e))
+			})?;
+			global_config
+				.members
+				.as_mut()
+				.unwrap()
+				.wallet
+				.grinbox_address_index = Some(new_index);
+			global_config.write_to_file(&config_file).map_err(|e| {
+				ErrorKind::LibWallet(format!("Unable to update config {}, {}", config_file, e))
+			})?;
+			proofaddress::set_address_index(new_index);
+
+			let res = controller::owner_single_use(
+				None,
+				keychain_mask,
+				Some(owner_api),
+				|api, m| {
+					let mut w_lock = api.wallet_inst.lock();
+					let w = w_lock.lc_provider()?.wallet_inst()?;
+					let keychain = w.keychain(m)?;
+					let address = proofaddress::payment_proof_address_from_index(
+						&keychain,
+						new_index,
+						proofaddress::ProofAddressType::Onion,
+					)?;
+					println!(
+						"Address index is rotated from {} to {}. New onion address: {}.onion",
+						current_index, new_index, address.public_key
+					);
+					println!("Please restart the listener to publish the new address. To keep the previous address reachable, add {} to 'listener_address_indexes' at the [tor] section of {}", current_index, config_file);
+					Ok(())
+				},
+			);
+			if let Err(e) = res {
+				let err_str = format!("Error rotating onion address: {}", e);
+				error!("{}", err_str);
+				return Err(ErrorKind::LibWallet(err_str).into());
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Arguments for the send command
+pub struct SendArgs {
+	pub amount: u64,
+	pub message: Option<String>,
+	pub minimum_confirmations: u64,
+	pub selection_strategy: String,
+	pub estimate_selection_strategies: bool,
 	pub method: String,
 	pub dest: String,
 	pub apisecret: Option<String>,
@@ -334,8 +1169,39 @@ pub struct SendArgs {
 	pub address: Option<String>,      //this is only for file proof.
 	pub outputs: Option<Vec<String>>, // Outputs to use. If None, all outputs can be used
 	pub slatepack_recipient: Option<ProvableAddress>, // Destination for slatepack. The address will be the same as for payment_proof_address. The role is different.
+	pub envelope_recipient: Option<ProvableAddress>, // The plain slate is encrypted into the envelope for this address
 	pub late_lock: bool,
 	pub min_fee: Option<u64>,
+	pub unsigned_bundle: Option<String>, // Save the bundle for the offline wallet instead of sending
+}
+
+// Slate respond is coming back through the listener, so it must be running
+fn start_mwcmqs_listener_if_needed<L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>,
+	mqs_config: Option<MQSConfig>,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	if grin_wallet_impls::adapters::get_mwcmqs_brocker().is_none() {
+		//check to see if mqs_config is there, if not, return error
+		let mqs_config = mqs_config.ok_or(ErrorKind::MQSConfig(format!("NO MQS config!")))?;
+
+		let km = keychain_mask.map(|k| k.clone());
+
+		//start the listener finalize tx
+		let _ = controller::init_start_mwcmqs_listener(
+			wallet_inst,
+			mqs_config,
+			Arc::new(Mutex::new(km)),
+			false,
+		)?;
+		controller::wait_for_mwcmqs_listener(controller::MWCMQS_LISTENER_START_TIMEOUT);
+	}
+	Ok(())
 }
 
 pub fn send<L, C, K>(
@@ -401,39 +1267,45 @@ where
 			};
 
 			//if it is mwcmqs, start listner first.
-			match args.method.as_str() {
-				"mwcmqs" => {
-					if grin_wallet_impls::adapters::get_mwcmqs_brocker().is_none() {
-						//check to see if mqs_config is there, if not, return error
-						let mqs_config_unwrapped;
-						match mqs_config {
-							Some(s) => {
-								mqs_config_unwrapped = s;
-							}
-							None => {
-								return Err(ErrorKind::MQSConfig(format!("NO MQS config!")).into());
-							}
-						}
+			if args.method == "mwcmqs" {
+				start_mwcmqs_listener_if_needed(wallet_inst.clone(), mqs_config, keychain_mask)?;
+			}
 
-						let km = keychain_mask.map(|k| k.clone());
+			// Cold wallet, the transaction is built and signed by the offline wallet
+			if let Some(bundle_file) = &args.unsigned_bundle {
+				init_args.send_args = Some(InitTxSendArgs {
+					method: args.method.clone(),
+					dest: args.dest.clone(),
+					apisecret: args.apisecret.clone(),
+					finalize: true,
+					post_tx: true,
+					fluff: args.fluff,
+				});
+				let bundle = api.create_offline_bundle(m, &init_args)?;
+				bundle.write_to(Path::new(bundle_file))?;
+				println!(
+					"Unsigned bundle to send {} MWC to {} is saved at {}. Sign it with the offline wallet: sign --bundle {}",
+					core::amount_to_hr_string(args.amount, false),
+					args.dest,
+					bundle_file,
+					bundle_file
+				);
+				return Ok(());
+			}
 
-						//start the listener finalize tx
-						let _ = controller::init_start_mwcmqs_listener(
-							wallet_inst.clone(),
-							mqs_config_unwrapped,
-							Arc::new(Mutex::new(km)),
-							false,
-							//None,
-						)?;
-						thread::sleep(Duration::from_millis(2000));
-					}
+			// Store-and-forward transports deliver the slate and don't wait for the response
+			let store_and_forward = match args.method.as_str() {
+				"file" | "slatepack" | "self" => None,
+				method => {
+					create_transport(method, &args.dest)?.filter(|t| t.is_store_and_forward())
 				}
-				_ => {}
-			}
+			};
 
 			// Creating sender because we need to request other wallet version first
 			let sender_info = match args.method.as_str() {
-				"http" | "mwcmqs" => {
+				"file" | "slatepack" | "self" => None,
+				_ if store_and_forward.is_some() => None,
+				_ => {
 					let sender =
 						create_sender(&args.method, &args.dest, &args.apisecret, tor_config)?;
 					let other_wallet_version = sender.check_other_wallet_version(&args.dest)?;
@@ -445,7 +1317,6 @@ where
 					}
 					Some((sender, other_wallet_version))
 				}
-				_ => None,
 			};
 
 			let result = api.init_send_tx(m, &init_args, 1);
@@ -453,8 +1324,8 @@ where
 				Ok(s) => {
 					info!(
 						"Tx created: {} mwc to {} (strategy '{}')",
-						core::amount_to_hr_string(args.amount, false),
-						args.dest,
+						log_privacy::amount(args.amount),
+						log_privacy::address(&args.dest),
 						args.selection_strategy,
 					);
 					s
@@ -473,6 +1344,11 @@ where
 			if let Some(sp_address) = &args.slatepack_recipient {
 				recipient = Some(sp_address.tor_public_key()?);
 			}
+			// Slatepacks are encrypted already, the plain slates go into the envelope
+			let envelope_recipient = match &args.envelope_recipient {
+				Some(address) => Some(address.tor_public_key()?),
+				None => None,
+			};
 
 			let (slatepack_secret, slatepack_sender) = {
 				let mut w_lock = api.wallet_inst.lock();
@@ -498,23 +1374,50 @@ where
 						Some((&args.dest).into())
 					};
 
-					let slate_str = PathToSlatePutter::build_encrypted(
-						dest,
-						SlatePurpose::SendInitial,
-						slatepack_sender,
-						recipient,
-						args.method == "slatepack",
-					)
-					.put_tx(&slate, &slatepack_secret, false)
-					.map_err(|e| {
-						ErrorKind::IO(format!("Unable to store the file at {}, {}", args.dest, e))
-					})?;
+					let putter = if args.target_slate_version == Some(GRIN_V4_TARGET_SLATE_VERSION)
+					{
+						PathToSlatePutter::build_grin_v4(dest, SlatePurpose::SendInitial)
+					} else if let Some(envelope_recipient) = envelope_recipient {
+						PathToSlatePutter::build_envelope(dest, envelope_recipient)
+					} else {
+						PathToSlatePutter::build_encrypted(
+							dest,
+							SlatePurpose::SendInitial,
+							slatepack_sender,
+							recipient,
+							args.method == "slatepack",
+						)
+					};
+					let slate_str =
+						putter
+							.put_tx(&slate, &slatepack_secret, false)
+							.map_err(|e| {
+								ErrorKind::IO(format!(
+									"Unable to store the file at {}, {}",
+									args.dest, e
+								))
+							})?;
 					api.tx_lock_outputs(m, &slate, Some(String::from("file")), 0)?;
 					if args.dest.is_empty() {
 						println!("Slatepack: {}", slate_str);
 					}
 					return Ok(());
 				}
+				method if store_and_forward.is_some() => {
+					// The response comes later, the listener of the method finalizes it
+					store_and_forward.as_ref().unwrap().post_slate(
+						&slate,
+						SlatePurpose::SendInitial,
+						&slatepack_secret,
+						recipient.or(envelope_recipient),
+					)?;
+					api.tx_lock_outputs(m, &slate, Some(args.dest.clone()), 0)?;
+					println!(
+						"slate [{}] will be finalized when the response comes to 'listen --method {}'",
+						slate.id, method
+					);
+					return Ok(());
+				}
 				"self" => {
 					api.tx_lock_outputs(m, &slate, Some(String::from("self")), 0)?;
 					let km = match keychain_mask.as_ref() {
@@ -545,7 +1448,7 @@ where
 						&slate,
 						SlatePurpose::SendInitial,
 						&slatepack_secret,
-						recipient,
+						recipient.or(envelope_recipient),
 						wallet_info,
 					)?;
 					// Restore back ttl, because it can be gone
@@ -565,7 +1468,10 @@ where
 			let result = api.post_tx(m, &slate.tx, args.fluff);
 			match result {
 				Ok(_) => {
-					info!("slate [{}] finalized successfully", slate.id.to_string());
+					info!(
+						"slate [{}] finalized successfully",
+						log_privacy::slate_id(slate.id)
+					);
 					println!("slate [{}] finalized successfully", slate.id.to_string());
 					return Ok(());
 				}
@@ -580,6 +1486,166 @@ where
 	Ok(())
 }
 
+/// Arguments for the split-pay command
+pub struct SplitPayArgs {
+	pub amount: u64,
+	pub destinations: Vec<(String, u64)>, // destination address, weight
+	pub message: Option<String>,
+	pub minimum_confirmations: u64,
+	pub selection_strategy: String,
+	pub method: String,
+	pub apisecret: Option<String>,
+	pub fluff: bool,
+	pub change_outputs: usize,
+	pub max_outputs: usize,
+	pub exclude_change_outputs: bool,
+	pub minimum_confirmations_change_outputs: u64,
+	pub ttl_blocks: Option<u64>,
+	pub stop_on_error: bool,
+}
+
+/// Split the amount between the weights. The rounding remainder goes to the first destinations,
+/// one nano coin per destination, so the sum of the shares is always equal to the amount.
+/// Every weight must be positive and every destination must receive something.
+pub(crate) fn split_amount_by_weights(amount: u64, weights: &Vec<u64>) -> Result<Vec<u64>, Error> {
+	if weights.is_empty() {
+		return Err(
+			ErrorKind::ArgumentError("No destinations to split the amount".to_string()).into(),
+		);
+	}
+	if let Some(i) = weights.iter().position(|w| *w == 0) {
+		return Err(
+			ErrorKind::ArgumentError(format!("Destination {} has zero weight", i + 1)).into(),
+		);
+	}
+	let total_weight: u128 = weights.iter().map(|w| *w as u128).sum();
+
+	let mut shares: Vec<u64> = weights
+		.iter()
+		.map(|w| (amount as u128 * *w as u128 / total_weight) as u64)
+		.collect();
+
+	let mut remainder = amount - shares.iter().sum::<u64>();
+	for share in shares.iter_mut() {
+		if remainder == 0 {
+			break;
+		}
+		*share += 1;
+		remainder -= 1;
+	}
+
+	if shares.iter().any(|share| *share == 0) {
+		return Err(ErrorKind::ArgumentError(format!(
+			"Amount {} is too small to be split between {} destinations",
+			core::amount_to_hr_string(amount, false),
+			weights.len()
+		))
+		.into());
+	}
+
+	Ok(shares)
+}
+
+/// Send the amount to several destinations, every destination receives the share that is
+/// proportional to its weight. Every share is a separate transaction, the failed sends are
+/// reported and don't stop the rest unless 'stop_on_error' is set.
+pub fn split_pay<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	config: &WalletConfig,
+	keychain_mask: Option<&SecretKey>,
+	tor_config: Option<TorConfig>,
+	mqs_config: Option<MQSConfig>,
+	args: SplitPayArgs,
+	dark_scheme: bool,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let weights: Vec<u64> = args.destinations.iter().map(|(_, w)| *w).collect();
+	let shares = split_amount_by_weights(args.amount, &weights)?;
+
+	// destination, weight, amount, result
+	let mut report: Vec<(String, u64, u64, Result<(), String>)> = vec![];
+	let mut stopped = false;
+
+	for ((dest, weight), share) in args.destinations.iter().zip(shares.iter()) {
+		if stopped {
+			report.push((
+				dest.clone(),
+				*weight,
+				*share,
+				Err("Skipped because of the previous failure".to_string()),
+			));
+			continue;
+		}
+
+		let send_args = SendArgs {
+			amount: *share,
+			message: args.message.clone(),
+			minimum_confirmations: args.minimum_confirmations,
+			selection_strategy: args.selection_strategy.clone(),
+			estimate_selection_strategies: false,
+			method: args.method.clone(),
+			dest: dest.clone(),
+			apisecret: args.apisecret.clone(),
+			change_outputs: args.change_outputs,
+			fluff: args.fluff,
+			max_outputs: args.max_outputs,
+			target_slate_version: None,
+			payment_proof_address: None,
+			ttl_blocks: args.ttl_blocks,
+			exclude_change_outputs: args.exclude_change_outputs,
+			minimum_confirmations_change_outputs: args.minimum_confirmations_change_outputs,
+			address: None,
+			outputs: None,
+			slatepack_recipient: None,
+			envelope_recipient: None,
+			late_lock: false,
+			min_fee: None,
+			unsigned_bundle: None,
+		};
+
+		let res = send(
+			owner_api,
+			config,
+			keychain_mask,
+			config.api_listen_addr(),
+			None,
+			tor_config.clone(),
+			mqs_config.clone(),
+			send_args,
+			dark_scheme,
+		)
+		.map_err(|e| format!("{}", e));
+
+		if let Err(e) = &res {
+			error!(
+				"Unable to send {} to {}, {}",
+				log_privacy::amount(*share),
+				log_privacy::address(dest),
+				e
+			);
+			stopped = args.stop_on_error;
+		}
+		report.push((dest.clone(), *weight, *share, res));
+	}
+
+	display::split_pay_report(args.amount, &report, dark_scheme);
+
+	let failed = report.iter().filter(|r| r.3.is_err()).count();
+	if failed > 0 {
+		return Err(ErrorKind::GenericError(format!(
+			"{} of {} split payments were not sent",
+			failed,
+			report.len()
+		))
+		.into());
+	}
+	Ok(())
+}
+
 /// Receive command argument
 pub struct ReceiveArgs {
 	pub input_file: Option<String>,
@@ -629,6 +1695,8 @@ where
 				},
 			};
 
+		let grin_v4_format = slate_pkg.is_grin_v4();
+		let envelope_sender = slate_pkg.envelope_sender();
 		let (mut slate, sender, _recipient, content, slatepack_format) = slate_pkg.to_slate()?;
 
 		if !(content == SlatePurpose::FullSlate || content == SlatePurpose::SendInitial) {
@@ -657,14 +1725,26 @@ where
 			response_file = args.input_file.map(|n| format!("{}.response", n));
 		}
 
-		let slatepack_str = PathToSlatePutter::build_encrypted(
-			response_file.clone().map(|s| s.into()),
-			SlatePurpose::SendResponse,
-			DalekPublicKey::from(&slatepack_secret),
-			sender,
-			slatepack_format,
-		)
-		.put_tx(&slate, &slatepack_secret, false)?;
+		let putter = if grin_v4_format {
+			PathToSlatePutter::build_grin_v4(
+				response_file.clone().map(|s| s.into()),
+				SlatePurpose::SendResponse,
+			)
+		} else if let Some(envelope_sender) = envelope_sender {
+			PathToSlatePutter::build_envelope(
+				response_file.clone().map(|s| s.into()),
+				envelope_sender,
+			)
+		} else {
+			PathToSlatePutter::build_encrypted(
+				response_file.clone().map(|s| s.into()),
+				SlatePurpose::SendResponse,
+				DalekPublicKey::from(&slatepack_secret),
+				sender,
+				slatepack_format,
+			)
+		};
+		let slatepack_str = putter.put_tx(&slate, &slatepack_secret, false)?;
 
 		if let Some(response_file) = &response_file {
 			info!("Response file {}.response generated, and can be sent back to the transaction originator.", response_file);
@@ -677,31 +1757,86 @@ where
 	Ok(())
 }
 
-pub fn unpack<L, C, K>(
+/// Retrieve response command argument
+pub struct RetrieveResponseArgs {
+	pub tx_slate_id: Uuid,
+	pub outfile: Option<String>,
+}
+
+pub fn retrieve_response<L, C, K>(
 	owner_api: &mut Owner<L, C, K>,
 	keychain_mask: Option<&SecretKey>,
-	args: ReceiveArgs,
+	args: RetrieveResponseArgs,
 ) -> Result<(), Error>
 where
 	L: WalletLCProvider<'static, C, K>,
 	C: NodeClient + 'static,
 	K: keychain::Keychain + 'static,
 {
-	let km = match keychain_mask.as_ref() {
-		None => None,
-		Some(&m) => Some(m.to_owned()),
-	};
-	controller::foreign_single_use(owner_api.wallet_inst.clone(), km, |api| {
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let slate = api.retrieve_response_slate(m, &args.tx_slate_id)?;
+
 		let slatepack_secret = {
 			let mut w_lock = api.wallet_inst.lock();
 			let w = w_lock.lc_provider()?.wallet_inst()?;
-			let keychain = w.keychain(keychain_mask)?;
-			let slatepack_secret =
-				proofaddress::payment_proof_address_dalek_secret(&keychain, None)?;
-			slatepack_secret
+			let keychain = w.keychain(m)?;
+			proofaddress::payment_proof_address_dalek_secret(&keychain, None)?
 		};
 
-		let slate_pkg =
+		// Compact slates are exchanged as slatepacks. The sender address is not stored, so the
+		// slatepack is not encrypted.
+		let response_file = args.outfile.clone().map(|s| s.into());
+		let putter = if slate.compact_slate {
+			PathToSlatePutter::build_encrypted(
+				response_file,
+				SlatePurpose::SendResponse,
+				DalekPublicKey::from(&slatepack_secret),
+				None,
+				true,
+			)
+		} else {
+			PathToSlatePutter::build_plain(response_file)
+		};
+		let slatepack_str = putter.put_tx(&slate, &slatepack_secret, false)?;
+
+		if let Some(response_file) = &args.outfile {
+			info!(
+				"Response file {} generated, and can be sent back to the transaction originator.",
+				response_file
+			);
+		} else {
+			println!("Response Slate: {}", slatepack_str);
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
+pub fn unpack<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: ReceiveArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K>,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let km = match keychain_mask.as_ref() {
+		None => None,
+		Some(&m) => Some(m.to_owned()),
+	};
+	controller::foreign_single_use(owner_api.wallet_inst.clone(), km, |api| {
+		let slatepack_secret = {
+			let mut w_lock = api.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			let keychain = w.keychain(keychain_mask)?;
+			let slatepack_secret =
+				proofaddress::payment_proof_address_dalek_secret(&keychain, None)?;
+			slatepack_secret
+		};
+
+		let slate_pkg =
 			match &args.input_file {
 				Some(file_name) => PathToSlateGetter::build_form_path(file_name.into())
 					.get_tx(&slatepack_secret)?,
@@ -774,6 +1909,8 @@ where
 	let mut sender = None;
 	let mut recipient = None;
 	let mut slatepack_format = false;
+	let mut grin_v4_format = false;
+	let mut envelope_sender = None;
 
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
 		let slatepack_secret = {
@@ -802,6 +1939,8 @@ where
 				},
 			};
 
+		grin_v4_format = slate_pkg.is_grin_v4();
+		envelope_sender = slate_pkg.envelope_sender();
 		let (slate2, sender2, recipient2, content2, slatepack_format2) = slate_pkg.to_slate()?;
 		slate = slate2;
 		sender = sender2;
@@ -897,14 +2036,21 @@ where
 			};
 
 			// save to a destination not as a slatepack
-			PathToSlatePutter::build_encrypted(
-				Some((&args.dest.unwrap()).into()),
-				SlatePurpose::FullSlate,
-				DalekPublicKey::from(&slatepack_secret),
-				sender,
-				slatepack_format,
-			)
-			.put_tx(&slate, &slatepack_secret, false)?;
+			let dest = Some((&args.dest.unwrap()).into());
+			let putter = if grin_v4_format {
+				PathToSlatePutter::build_grin_v4(dest, SlatePurpose::FullSlate)
+			} else if let Some(envelope_sender) = envelope_sender {
+				PathToSlatePutter::build_envelope(dest, envelope_sender)
+			} else {
+				PathToSlatePutter::build_encrypted(
+					dest,
+					SlatePurpose::FullSlate,
+					DalekPublicKey::from(&slatepack_secret),
+					sender,
+					slatepack_format,
+				)
+			};
+			putter.put_tx(&slate, &slatepack_secret, false)?;
 
 			Ok(())
 		})?;
@@ -913,10 +2059,321 @@ where
 	Ok(())
 }
 
+/// Roundtrip command args
+pub struct RoundtripArgs {
+	pub input_file: String,
+	pub message: Option<String>,
+	pub outfile: Option<String>,
+	pub post: bool,
+	pub fluff: bool,
+}
+
+/// Process the slate file in one step. Depending on the wallet role it is 'receive' with a
+/// response file, or 'finalize'/'finalize_invoice' with optional post.
+pub fn roundtrip<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	g_args: &GlobalArgs,
+	args: RoundtripArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	// Role is defined by slatepack content. Plain slates don't have it, for them we are checking
+	// if this wallet already has the transaction.
+	let mut content = SlatePurpose::FullSlate;
+	let mut tx_type = None;
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let slatepack_secret = {
+			let mut w_lock = api.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			let keychain = w.keychain(m)?;
+			proofaddress::payment_proof_address_dalek_secret(&keychain, None)?
+		};
+
+		let (slate, _sender, _recipient, content2, _slatepack_format) =
+			PathToSlateGetter::build_form_path((&args.input_file).into())
+				.get_tx(&slatepack_secret)?
+				.to_slate()?;
+		content = content2;
+
+		let (_, txs) = api.retrieve_txs(m, false, None, Some(slate.id))?;
+		tx_type = txs.first().map(|tx| tx.tx_type.clone());
+		Ok(())
+	})?;
+
+	let is_invoice = match (&content, &tx_type) {
+		(SlatePurpose::SendInitial, _) | (SlatePurpose::FullSlate, None) => {
+			info!("Receiving the slate from {}", args.input_file);
+			return receive(
+				owner_api,
+				keychain_mask,
+				g_args,
+				ReceiveArgs {
+					input_file: Some(args.input_file),
+					input_slatepack_message: None,
+					message: args.message,
+					outfile: args.outfile,
+				},
+			);
+		}
+		(SlatePurpose::SendResponse, _)
+		| (SlatePurpose::FullSlate, Some(TxLogEntryType::TxSent)) => false,
+		(SlatePurpose::InvoiceResponse, _)
+		| (SlatePurpose::FullSlate, Some(TxLogEntryType::TxReceived)) => true,
+		_ => {
+			return Err(ErrorKind::ArgumentError(format!(
+				"Unable to process slate with content {:?} in one step, wallet transaction type is {:?}. Please use 'receive', 'finalize' or 'pay' command",
+				content, tx_type
+			))
+			.into());
+		}
+	};
+
+	info!(
+		"Finalizing the {} slate from {}",
+		if is_invoice { "invoice" } else { "send" },
+		args.input_file
+	);
+	finalize(
+		owner_api,
+		keychain_mask,
+		FinalizeArgs {
+			input_file: Some(args.input_file),
+			input_slatepack_message: None,
+			fluff: args.fluff,
+			nopost: !args.post,
+			dest: None,
+		},
+		is_invoice,
+	)
+}
+
+/// Offline signing bundle file
+pub struct BundleArgs {
+	pub bundle: String,
+}
+
+/// Offline (cold) wallet step. Builds and signs the slate of the unsigned bundle, or finalizes
+/// the slate of the bundle with the recipient response. The bundle file is updated in place.
+pub fn sign<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: BundleArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let path = Path::new(&args.bundle);
+	let bundle = OfflineTxBundle::read_from(path)?;
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let bundle = api.sign_offline_bundle(m, &bundle)?;
+		bundle.write_to(path)?;
+		let slate_id = bundle.get_slate()?.id;
+		match bundle.stage {
+			OfflineBundleStage::Finalized => println!(
+				"slate [{}] is finalized. Post it with the online wallet: finalize --bundle {}",
+				slate_id, args.bundle
+			),
+			_ => println!(
+				"slate [{}] is signed. Send it with the online wallet: finalize --bundle {}",
+				slate_id, args.bundle
+			),
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Online wallet step for the signed bundle. Sends the slate to the recipient and saves the
+/// response into the bundle, or posts the transaction that the offline wallet finalized.
+pub fn finalize_bundle<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	mqs_config: Option<MQSConfig>,
+	args: BundleArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let path = Path::new(&args.bundle);
+	let bundle = OfflineTxBundle::read_from(path)?;
+	let wallet_inst = owner_api.wallet_inst.clone();
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let mwcmqs = bundle.args.send_args.as_ref().map(|sa| sa.method.as_str()) == Some("mwcmqs");
+		if bundle.stage == OfflineBundleStage::Signed && mwcmqs {
+			start_mwcmqs_listener_if_needed(wallet_inst, mqs_config, m)?;
+		}
+		let bundle = api.finalize_offline_bundle(m, &bundle)?;
+		let slate_id = bundle.get_slate()?.id;
+		match bundle.stage {
+			OfflineBundleStage::Responded => {
+				bundle.write_to(path)?;
+				println!(
+					"Response for slate [{}] is received. Finalize it with the offline wallet: sign --bundle {}",
+					slate_id, args.bundle
+				);
+			}
+			_ => {
+				info!(
+					"slate [{}] from the offline bundle is posted",
+					log_privacy::slate_id(&slate_id)
+				);
+				println!("slate [{}] is posted", slate_id);
+			}
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Multisig operation
+#[derive(PartialEq)]
+pub enum MultisigSubcommand {
+	Create,
+	Process,
+	Spend,
+	List,
+	Cancel,
+	Restore,
+	Export,
+}
+
+/// Arguments for the multisig command
+pub struct MultisigArgs {
+	/// What we want to do with the multisig session
+	pub subcommand: MultisigSubcommand,
+	/// Session id
+	pub id: Option<Uuid>,
+	/// 2-of-2 or 2-of-3, for create
+	pub scheme: Option<MultisigScheme>,
+	/// Output amount, for create
+	pub amount: u64,
+	/// Minimum confirmations of the funding outputs
+	pub minimum_confirmations: u64,
+	/// Participant that signs the spending transaction together with this wallet
+	pub cosigner: Option<usize>,
+	/// This wallet participant id, for restore
+	pub participant_id: Option<usize>,
+	/// Session document file
+	pub file: Option<String>,
+}
+
+fn read_multisig_session(file: &str) -> Result<MultisigSession, Error> {
+	let mut data = String::new();
+	File::open(file)
+		.and_then(|mut f| f.read_to_string(&mut data))
+		.map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to read the session file {}, {}", file, e))
+		})?;
+	Ok(MultisigSession::from_json(&data)?)
+}
+
+fn write_multisig_session(file: &str, session: &MultisigSession) -> Result<(), Error> {
+	let data = session.to_json()?;
+	File::create(file)
+		.and_then(|mut f| {
+			f.write_all(data.as_bytes())?;
+			f.sync_all()
+		})
+		.map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to save the session file {}, {}", file, e))
+		})?;
+	Ok(())
+}
+
+fn print_multisig_next_step(session: &MultisigSession, file: &str) {
+	match session.status {
+		MultisigStatus::Setup | MultisigStatus::Spending => {
+			let next = session
+				.waiting_for()
+				.iter()
+				.map(|id| id.to_string())
+				.collect::<Vec<String>>()
+				.join(", ");
+			println!(
+				"Multisig session {} is '{}'. Send {} to the participant {}, it should run: multisig process --file <file>",
+				session.id, session.status, file, next
+			);
+		}
+		_ => println!("Multisig session {} is '{}'", session.id, session.status),
+	}
+}
+
+pub fn multisig<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: MultisigArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let file = args.file.clone().unwrap_or_default();
+	let id = args.id.unwrap_or(Uuid::nil());
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		match args.subcommand {
+			MultisigSubcommand::Create => {
+				let create_args = MultisigCreateArgs {
+					scheme: args.scheme.unwrap_or(MultisigScheme::TwoOfTwo),
+					amount: args.amount,
+					src_acct_name: None,
+					minimum_confirmations: args.minimum_confirmations,
+				};
+				let session = api.multisig_create(m, &create_args)?;
+				write_multisig_session(&file, &session)?;
+				print_multisig_next_step(&session, &file);
+			}
+			MultisigSubcommand::Process => {
+				let session = api.multisig_process(m, &read_multisig_session(&file)?)?;
+				write_multisig_session(&file, &session)?;
+				print_multisig_next_step(&session, &file);
+			}
+			MultisigSubcommand::Spend => {
+				let session = api.multisig_spend(m, &id, args.cosigner)?;
+				write_multisig_session(&file, &session)?;
+				print_multisig_next_step(&session, &file);
+			}
+			MultisigSubcommand::List => {
+				display::multisig_sessions(api.multisig_list(m)?);
+			}
+			MultisigSubcommand::Cancel => {
+				api.multisig_cancel(m, &id)?;
+				println!("Multisig session {} is cancelled", id);
+			}
+			MultisigSubcommand::Restore => {
+				let session = read_multisig_session(&file)?;
+				api.multisig_restore(m, &session, args.participant_id.unwrap_or_default())?;
+				println!("Multisig session {} is restored", session.id);
+			}
+			MultisigSubcommand::Export => {
+				let record = api.multisig_get(m, &id)?;
+				write_multisig_session(&file, &record.session)?;
+				println!("Multisig session {} is exported into {}", id, file);
+			}
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
 /// Issue Invoice Args
 pub struct IssueInvoiceArgs {
-	/// output file
+	/// output file or payer address
 	pub dest: String,
+	/// file, mwcmqs, http or tor
+	pub method: String,
+	/// payer wallet apisecret, http/tor only
+	pub apisecret: Option<String>,
+	/// fluff the paid transaction
+	pub fluff: bool,
 	/// issue invoice tx args
 	pub issue_args: IssueInvoiceTxArgs,
 }
@@ -924,6 +2381,8 @@ pub struct IssueInvoiceArgs {
 pub fn issue_invoice_tx<L, C, K>(
 	owner_api: &mut Owner<L, C, K>,
 	keychain_mask: Option<&SecretKey>,
+	tor_config: Option<TorConfig>,
+	mqs_config: Option<MQSConfig>,
 	args: IssueInvoiceArgs,
 ) -> Result<(), Error>
 where
@@ -931,6 +2390,10 @@ where
 	C: NodeClient + 'static,
 	K: keychain::Keychain + 'static,
 {
+	if args.method != "file" {
+		return send_invoice_tx(owner_api, keychain_mask, tor_config, mqs_config, args);
+	}
+
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
 		let mut recipient: Option<DalekPublicKey> = None;
 		if let Some(sp_address) = &args.issue_args.slatepack_recipient {
@@ -962,6 +2425,95 @@ where
 	Ok(())
 }
 
+// Push the invoice to the payer wallet and wait for the paid slate, then finalize and post it.
+// Payer wallet must be set up to process the invoices.
+fn send_invoice_tx<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	tor_config: Option<TorConfig>,
+	mqs_config: Option<MQSConfig>,
+	args: IssueInvoiceArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let wallet_inst = owner_api.wallet_inst.clone();
+	if args.method == "mwcmqs" {
+		start_mwcmqs_listener_if_needed(wallet_inst.clone(), mqs_config, keychain_mask)?;
+	}
+
+	let mut slate = Slate::blank(2, false); // result placeholder
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let mut issue_args = args.issue_args.clone();
+		let sender = create_sender(&args.method, &args.dest, &args.apisecret, tor_config)?;
+		let other_wallet_version = sender.check_other_wallet_version(&args.dest)?;
+		if let Some(other_wallet_version) = &other_wallet_version {
+			if issue_args.target_slate_version.is_none() {
+				issue_args.target_slate_version =
+					Some(other_wallet_version.0.to_numeric_version() as u16);
+			}
+		}
+
+		let mut recipient: Option<DalekPublicKey> = None;
+		if let Some(sp_address) = &issue_args.slatepack_recipient {
+			recipient = Some(sp_address.tor_public_key()?);
+		}
+
+		let invoice_slate = api.issue_invoice_tx(m, &issue_args)?;
+		info!(
+			"Invoice created: {} mwc to {}",
+			log_privacy::amount(invoice_slate.amount),
+			log_privacy::address(&args.dest),
+		);
+
+		let slatepack_secret = {
+			let mut w_lock = api.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			let keychain = w.keychain(m)?;
+			proofaddress::payment_proof_address_dalek_secret(&keychain, None)?
+		};
+
+		slate = sender.send_tx(
+			&invoice_slate,
+			SlatePurpose::InvoiceInitial,
+			&slatepack_secret,
+			recipient,
+			other_wallet_version,
+		)?;
+		// Restore back ttl, because it can be gone
+		slate.ttl_cutoff_height = invoice_slate.ttl_cutoff_height.clone();
+		// Checking that payer didn't change anything that we requested
+		Slate::compare_slates_invoice(&invoice_slate, &slate)?;
+		Ok(())
+	})?;
+
+	let km = keychain_mask.map(|k| k.clone());
+	controller::foreign_single_use(wallet_inst, km, |api| {
+		api.verify_slate_messages(&slate).map_err(|e| {
+			error!("Error validating participant messages: {}", e);
+			e
+		})?;
+		slate = api.finalize_invoice_tx(&slate)?;
+		Ok(())
+	})?;
+
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		api.post_tx(m, &slate.tx, args.fluff).map_err(|e| {
+			error!("Tx sent fail: {}", e);
+			ErrorKind::LibWallet(format!("Unable to post slate, {}", e))
+		})?;
+		info!(
+			"invoice slate [{}] finalized successfully",
+			log_privacy::slate_id(slate.id)
+		);
+		println!("invoice slate [{}] finalized successfully", slate.id);
+		Ok(())
+	})?;
+	Ok(())
+}
+
 /// Arguments for the process_invoice command
 pub struct ProcessInvoiceArgs {
 	pub message: Option<String>,
@@ -1054,8 +2606,8 @@ where
 				Ok(s) => {
 					info!(
 						"Invoice processed: {} mwc to {} (strategy '{}')",
-						core::amount_to_hr_string(slate.amount, false),
-						args.dest,
+						log_privacy::amount(slate.amount),
+						log_privacy::address(&args.dest),
 						args.selection_strategy,
 					);
 					s
@@ -1110,6 +2662,10 @@ where
 /// Info command args
 pub struct InfoArgs {
 	pub minimum_confirmations: u64,
+	/// Show every account and the wallet total
+	pub all_accounts: bool,
+	/// Show the values in this fiat currency at the current exchange rate
+	pub fiat: Option<String>,
 }
 
 pub fn info<L, C, K>(
@@ -1126,23 +2682,43 @@ where
 {
 	let updater_running = owner_api.updater_running.load(Ordering::Relaxed);
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		if args.all_accounts {
+			let (validated, accounts) =
+				api.retrieve_accounts_summary_info(m, true, args.minimum_confirmations)?;
+			display::accounts_info(&accounts, validated || updater_running, dark_scheme);
+			return Ok(());
+		}
 		let (validated, wallet_info) =
 			api.retrieve_summary_info(m, true, args.minimum_confirmations)?;
+		let rate = match &args.fiat {
+			Some(currency) => Some(api.get_exchange_rate(currency, None)?),
+			None => None,
+		};
 		display::info(
 			&g_args.account,
 			&wallet_info,
 			validated || updater_running,
 			dark_scheme,
+			rate.as_ref(),
 		);
 		Ok(())
 	})?;
 	Ok(())
 }
 
+/// Outputs command args
+pub struct OutputsArgs {
+	/// Filters and pagination, None - all outputs
+	pub query: Option<OutputQueryArgs>,
+	/// Freeze, unfreeze or tag the output instead of listing the outputs
+	pub tag_output: Option<OutputTagArgs>,
+}
+
 pub fn outputs<L, C, K>(
 	owner_api: &mut Owner<L, C, K>,
 	keychain_mask: Option<&SecretKey>,
 	g_args: &GlobalArgs,
+	args: OutputsArgs,
 	dark_scheme: bool,
 ) -> Result<(), Error>
 where
@@ -1152,8 +2728,26 @@ where
 {
 	let updater_running = owner_api.updater_running.load(Ordering::Relaxed);
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		if let Some(tag_args) = &args.tag_output {
+			api.tag_output(m, tag_args)?;
+			println!("Output {} is updated", tag_args.commit);
+			return Ok(());
+		}
 		let res = api.node_height(m)?;
-		let (validated, outputs) = api.retrieve_outputs(m, g_args.show_spent, true, None)?;
+		let (validated, outputs) = match &args.query {
+			Some(query) => {
+				let mut query = query.clone();
+				if query.status.is_none() && !g_args.show_spent {
+					query.status = Some(vec![
+						OutputStatus::Unconfirmed,
+						OutputStatus::Unspent,
+						OutputStatus::Locked,
+					]);
+				}
+				api.query_outputs(m, true, &query)?
+			}
+			None => api.retrieve_outputs(m, g_args.show_spent, true, None)?,
+		};
 		display::outputs(
 			&g_args.account,
 			res.height,
@@ -1170,6 +2764,27 @@ where
 pub struct TxsArgs {
 	pub id: Option<u32>,
 	pub tx_slate_id: Option<Uuid>,
+	/// Filters and pagination, None - all transactions
+	pub query: Option<TxQueryArgs>,
+	/// Set the local label or memo of the transaction instead of listing the transactions
+	pub set_label: Option<TxLabelArgs>,
+	/// Show the values in this fiat currency at the rate of the transaction day
+	pub fiat: Option<String>,
+	/// Look up the transaction kernel with this excess on the node
+	pub kernel: Option<String>,
+	/// Set the confirmation target (transaction Id, confirmations) instead of listing the transactions
+	pub set_target: Option<(u32, u64)>,
+	/// Import the transactions history from the file instead of listing the transactions
+	pub import: Option<TxImportArgs>,
+}
+
+/// Arguments for the transactions import
+pub struct TxImportArgs {
+	pub file: String,
+	pub format: TxImportFormat,
+	/// Record field -> column or key name of the file
+	pub columns: HashMap<String, String>,
+	pub dry_run: bool,
 }
 
 pub fn txs<L, C, K>(
@@ -1186,24 +2801,104 @@ where
 {
 	let updater_running = owner_api.updater_running.load(Ordering::Relaxed);
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
-		let res = api.node_height(m)?;
-		let (validated, txs) = api.retrieve_txs(m, true, args.id, args.tx_slate_id)?;
-		let include_status = !args.id.is_some() && !args.tx_slate_id.is_some();
-		display::txs(
-			&g_args.account,
-			res.height,
+		if let Some(label_args) = &args.set_label {
+			api.set_tx_label(m, label_args)?;
+			println!("Transaction {} is updated", label_args.tx_id);
+			return Ok(());
+		}
+		if let Some((id, confirmations)) = args.set_target {
+			api.set_tx_confirmation_target(m, id, Some(confirmations))?;
+			println!("Transaction {} is updated", id);
+			return Ok(());
+		}
+		if let Some(import) = &args.import {
+			let data = fs::read_to_string(&import.file).map_err(|e| {
+				ErrorKind::GenericError(format!("Unable to read {}, {}", import.file, e))
+			})?;
+			let records = tx_import::parse_tx_import(&data, &import.format, &import.columns)?;
+			let report = api.import_txs(m, records, import.dry_run)?;
+			for s in &report.skipped {
+				println!("Record {} is skipped: {}", s.record, s.reason);
+			}
+			if import.dry_run {
+				println!(
+					"Dry run: {} transaction(s) can be imported, {} skipped",
+					report.imported_count,
+					report.skipped.len()
+				);
+			} else {
+				println!(
+					"{} transaction(s) are imported into the account '{}', {} skipped",
+					report.imported_count,
+					g_args.account,
+					report.skipped.len()
+				);
+			}
+			return Ok(());
+		}
+		let mut tx_id = args.id;
+		if let Some(excess) = &args.kernel {
+			let lookup = api.lookup_kernel(m, excess)?;
+			display::kernel_lookup(&lookup);
+			// the wallet transaction with this kernel is displayed as for '--id'
+			match lookup.tx_id {
+				Some(id) => tx_id = Some(id),
+				None => return Ok(()),
+			}
+		}
+		let res = api.node_height(m)?;
+		let (validated, txs) = match &args.query {
+			Some(query) => api.query_txs(m, true, query)?,
+			None => api.retrieve_txs(m, true, tx_id, args.tx_slate_id)?,
+		};
+		let include_status = !tx_id.is_some() && !args.tx_slate_id.is_some();
+		// Net difference at the daily rate of the confirmation or creation day
+		let mut fiat_values: HashMap<u32, String> = HashMap::new();
+		let mut fiat_error = None;
+		if let Some(currency) = &args.fiat {
+			for t in &txs {
+				let date = t
+					.confirmation_ts
+					.unwrap_or(t.creation_ts)
+					.date()
+					.naive_utc();
+				match api.get_exchange_rate(currency, Some(date)) {
+					Ok(rate) => {
+						let value = if t.amount_credited >= t.amount_debited {
+							rate.value_hr(t.amount_credited - t.amount_debited)
+						} else {
+							format!("-{}", rate.value_hr(t.amount_debited - t.amount_credited))
+						};
+						fiat_values.insert(t.id, value);
+					}
+					Err(e) => {
+						fiat_error.get_or_insert(e);
+					}
+				}
+			}
+		}
+		display::txs(
+			&g_args.account,
+			res.height,
 			validated || updater_running,
 			&txs,
 			include_status,
 			dark_scheme,
 			true, // mwc-wallet alwways show the full info because it is advanced tool
 			|tx: &TxLogEntry| tx.payment_proof.is_some(), // it is how mwc-wallet address proofs feature
+			&fiat_values,
 		)?;
+		if let Some(e) = fiat_error {
+			println!(
+				"WARNING: Fiat values are not available for some transactions, {}\n",
+				e
+			);
+		}
 
 		// if given a particular transaction id or uuid, also get and display associated
 		// inputs/outputs and messages
-		let id = if args.id.is_some() {
-			args.id
+		let id = if tx_id.is_some() {
+			tx_id
 		} else if args.tx_slate_id.is_some() {
 			if let Some(tx) = txs.iter().find(|t| t.tx_slate_id == args.tx_slate_id) {
 				Some(tx.id)
@@ -1227,6 +2922,8 @@ where
 			// should only be one here, but just in case
 			for tx in txs {
 				display::tx_messages(&tx, dark_scheme)?;
+				display::tx_memo(&tx);
+				display::tx_kernel(&tx);
 				display::payment_proof(&tx)?;
 			}
 		}
@@ -1301,7 +2998,8 @@ where
 
 /// Repost
 pub struct RepostArgs {
-	pub id: u32,
+	pub id: Option<u32>,
+	pub all_unconfirmed: bool,
 	pub dump_file: Option<String>,
 	pub fluff: bool,
 }
@@ -1317,26 +3015,49 @@ where
 	K: keychain::Keychain + 'static,
 {
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
-		let (_, txs) = api.retrieve_txs(m, true, Some(args.id), None)?;
+		let id = match args.id {
+			Some(id) => id,
+			None => {
+				// Finalized transactions that are waiting for the confirmation
+				let (_, txs) = api.retrieve_txs(m, true, None, None)?;
+				let mut reposted = 0;
+				for t in txs
+					.iter()
+					.filter(|t| t.current_status() == TxStatus::AwaitingConfirmation)
+				{
+					match api.get_stored_tx(m, t)? {
+						Some(stored_tx) => {
+							api.post_tx(m, &stored_tx, args.fluff)?;
+							info!("Reposted transaction at {}", t.id);
+							reposted += 1;
+						}
+						None => warn!(
+							"Transaction with id {} does not have transaction data. Not reposting.",
+							t.id
+						),
+					}
+				}
+				println!("Reposted {} unconfirmed transaction(s)", reposted);
+				return Ok(());
+			}
+		};
+		let (_, txs) = api.retrieve_txs(m, true, Some(id), None)?;
 		let stored_tx = api.get_stored_tx(m, &txs[0])?;
 		if stored_tx.is_none() {
 			error!(
 				"Transaction with id {} does not have transaction data. Not reposting.",
-				args.id
+				id
 			);
 			return Ok(());
 		}
 		match args.dump_file {
 			None => {
 				if txs[0].confirmed {
-					error!(
-						"Transaction with id {} is confirmed. Not reposting.",
-						args.id
-					);
+					error!("Transaction with id {} is confirmed. Not reposting.", id);
 					return Ok(());
 				}
 				api.post_tx(m, &stored_tx.unwrap(), args.fluff)?;
-				info!("Reposted transaction at {}", args.id);
+				info!("Reposted transaction at {}", id);
 				return Ok(());
 			}
 			Some(f) => {
@@ -1352,7 +3073,7 @@ where
 				tx_file.sync_all().map_err(|e| {
 					ErrorKind::IO(format!("Unable to save tx to the file {}, {}", f, e))
 				})?;
-				info!("Dumped transaction data for tx {} to {}", args.id, f);
+				info!("Dumped transaction data for tx {} to {}", id, f);
 				return Ok(());
 			}
 		}
@@ -1397,11 +3118,120 @@ where
 	Ok(())
 }
 
+/// Reissue of a stuck send with a higher fee
+pub struct ReissueArgs {
+	pub tx_id: u32,
+	pub fee_multiplier: f32,
+	pub outfile: Option<String>,
+	pub target_slate_version: Option<u16>,
+	pub slatepack_recipient: Option<ProvableAddress>,
+	pub force: bool,
+}
+
+pub fn reissue<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: ReissueArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let reissue_args = ReissueTxArgs {
+			tx_id: args.tx_id,
+			fee_multiplier: args.fee_multiplier,
+			target_slate_version: args.target_slate_version,
+			slatepack_recipient: args.slatepack_recipient.clone(),
+			force: args.force,
+		};
+		let slate = api.reissue_tx(m, &reissue_args).map_err(|e| {
+			error!("TX Reissue failed: {}", e);
+			ErrorKind::LibWallet(format!(
+				"Unable to reissue Transaction {}, {}",
+				args.tx_id, e
+			))
+		})?;
+
+		let mut recipient: Option<DalekPublicKey> = None;
+		if let Some(sp_address) = &args.slatepack_recipient {
+			recipient = Some(sp_address.tor_public_key()?);
+		}
+		let slatepack_secret = {
+			let mut w_lock = api.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			let keychain = w.keychain(m)?;
+			proofaddress::payment_proof_address_dalek_secret(&keychain, None)?
+		};
+
+		// Outputs are already locked, the new slate only needs to get to the recipient
+		let putter = PathToSlatePutter::build_encrypted(
+			args.outfile.clone().map(|s| s.into()),
+			SlatePurpose::SendInitial,
+			DalekPublicKey::from(&slatepack_secret),
+			recipient,
+			slate.compact_slate,
+		);
+		let slate_str = putter.put_tx(&slate, &slatepack_secret, false)?;
+
+		info!(
+			"Transaction {} is reissued as {} with fee {}",
+			args.tx_id,
+			log_privacy::slate_id(slate.id),
+			amount_to_hr_string(slate.fee, false)
+		);
+		match &args.outfile {
+			Some(outfile) => info!(
+				"Slate file {} generated, send it to the recipient to complete the transaction.",
+				outfile
+			),
+			None => println!("Slate: {}", slate_str),
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
+pub fn cancel_expired<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		if api.get_auto_cancel_policy()?.receive_expire_days.is_none() {
+			println!("Receive expiration is not configured, please set auto_expire_receive_days at mwc-wallet.toml");
+			return Ok(());
+		}
+		let tx_ids = api.cancel_expired_receives(m).map_err(|e| {
+			error!("Expired receives cancellation failed: {}", e);
+			ErrorKind::LibWallet(format!("Unable to cancel expired receives, {}", e))
+		})?;
+		if tx_ids.is_empty() {
+			println!("There are no expired receive transactions");
+		} else {
+			let ids: Vec<String> = tx_ids.iter().map(|id| id.to_string()).collect();
+			println!(
+				"Cancelled {} expired receive transactions: {}",
+				tx_ids.len(),
+				ids.join(", ")
+			);
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
 /// wallet check
 pub struct CheckArgs {
 	pub delete_unconfirmed: bool,
 	pub start_height: Option<u64>,
 	pub backwards_from_tip: Option<u64>,
+	pub resume: bool,
 }
 
 pub fn scan<L, C, K>(
@@ -1423,11 +3253,20 @@ where
 				None => 1,
 			},
 		};
-		warn!("Starting output scan from height {} ...", start_height);
-		let result = api.scan(m, Some(start_height), args.delete_unconfirmed);
+		let result = if args.resume {
+			warn!("Resuming output scan ...");
+			api.scan_resume(m, Some(start_height), args.delete_unconfirmed)
+		} else {
+			warn!("Starting output scan from height {} ...", start_height);
+			api.scan(m, Some(start_height), args.delete_unconfirmed)
+		};
 		match result {
 			Ok(_) => {
-				warn!("Wallet check complete",);
+				let progress = api.get_scan_progress()?;
+				warn!(
+					"Wallet check complete, found {} outputs",
+					progress.outputs_found
+				);
 				Ok(())
 			}
 			Err(e) => {
@@ -1440,11 +3279,327 @@ where
 	Ok(())
 }
 
+/// Arguments for the check command
+pub struct CheckRepairArgs {
+	pub start_height: Option<u64>,
+	pub backwards_from_tip: Option<u64>,
+	/// Categories to repair, empty - report only
+	pub fix: Vec<CheckCategory>,
+	pub json: bool,
+}
+
+/// Check the wallet against the chain and the swap storage, print the report and repair
+/// the selected categories
+pub fn check<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: CheckRepairArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let start_height = match args.backwards_from_tip {
+			Some(b) => Some(api.node_height(m)?.height.saturating_sub(b)),
+			None => args.start_height,
+		};
+		let report = api
+			.check(m, start_height, args.fix.clone())
+			.map_err(|e| ErrorKind::LibWallet(format!("Wallet check failed, {}", e)))?;
+
+		if args.json {
+			println!("{}", json::to_string_pretty(&report).unwrap());
+			return Ok(());
+		}
+
+		println!(
+			"Wallet check from height {} to {}",
+			report.start_height, report.tip_height
+		);
+		for category in CheckCategory::ALL.iter() {
+			let fixed = if report.fixed.contains(category) {
+				", fixed"
+			} else {
+				""
+			};
+			println!("{}: {}{}", category, report.count(*category), fixed);
+			match category {
+				CheckCategory::MissingOutputs | CheckCategory::SpentOutputs => {
+					let outputs = match category {
+						CheckCategory::MissingOutputs => &report.missing_outputs,
+						_ => &report.spent_outputs,
+					};
+					for o in outputs {
+						println!(
+							"    {}  {} MWC  height {}  wallet status {}",
+							o.commit,
+							amount_to_hr_string(o.value, true),
+							o.height,
+							o.local_status
+								.as_ref()
+								.map(|s| s.to_string())
+								.unwrap_or("missing".to_string())
+						);
+					}
+				}
+				CheckCategory::DanglingTxs => {
+					for t in &report.dangling_txs {
+						println!(
+							"    tx {}  {}  slate {}",
+							t.id,
+							t.tx_type.to_string().replace("\n", " "),
+							t.tx_slate_id.clone().unwrap_or("None".to_string())
+						);
+					}
+				}
+				CheckCategory::OrphanedSwaps => {
+					for s in &report.orphaned_swaps {
+						println!("    {}  {}", s.swap_id, s.issue);
+					}
+				}
+			}
+		}
+
+		let not_fixed: Vec<String> = CheckCategory::ALL
+			.iter()
+			.filter(|c| report.count(**c) > 0 && !report.fixed.contains(c))
+			.map(|c| c.to_string())
+			.collect();
+		if report.is_clean() {
+			println!("No problems found");
+		} else if !not_fixed.is_empty() {
+			println!(
+				"To repair, run 'check --fix {}' or 'check --repair' for all categories",
+				not_fixed.join(",")
+			);
+		}
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Temporary wallet that holds the outputs of the swept recovery phrase
+type SweepWallet<C> = Arc<
+	Mutex<
+		Box<
+			dyn WalletInst<
+				'static,
+				DefaultLCProvider<'static, C, keychain::ExtKeychain>,
+				C,
+				keychain::ExtKeychain,
+			>,
+		>,
+	>,
+>;
+
+/// Arguments for the sweep-seed command
+pub struct SweepSeedArgs {
+	/// None to sweep the receive-only wallet of this wallet
+	pub recovery_phrase: Option<ZeroingString>,
+	pub start_height: Option<u64>,
+	pub minimum_confirmations: u64,
+	pub fluff: bool,
+}
+
+/// Restore the wallet from another recovery phrase into a temporary directory, scan it and
+/// move all spendable outputs into the current wallet with a single transaction.
+pub fn sweep_seed<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	config: &WalletConfig,
+	args: SweepSeedArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let node_client = {
+		let mut w_lock = owner_api.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		w.w2n_client().clone()
+	};
+
+	let mut sweep_dir = PathBuf::from(&config.data_file_dir);
+	sweep_dir.push(format!("sweep_{}", Uuid::new_v4()));
+	let sweep_dir_str = sweep_dir.to_str().unwrap_or("").to_string();
+
+	let sweep_wallet = DefaultWalletImpl::<'static, C>::new(node_client)
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to create sweep wallet, {}", e)))?;
+	let sweep_wallet: SweepWallet<C> = Arc::new(Mutex::new(Box::new(sweep_wallet)));
+
+	let res = sweep_seed_impl(
+		owner_api,
+		keychain_mask,
+		sweep_wallet.clone(),
+		&sweep_dir_str,
+		&args,
+	);
+
+	{
+		let mut w_lock = sweep_wallet.lock();
+		if let Ok(lc) = w_lock.lc_provider() {
+			let _ = lc.close_wallet(None);
+		}
+	}
+	if sweep_dir.exists() {
+		if let Err(e) = std::fs::remove_dir_all(&sweep_dir) {
+			warn!(
+				"Unable to clean up sweep wallet data at {}, {}",
+				sweep_dir_str, e
+			);
+		}
+	}
+
+	match res {
+		Ok(None) => println!("No spendable funds found for the provided recovery phrase"),
+		Ok(Some(amount)) => println!(
+			"{} MWC was swept into this wallet",
+			amount_to_hr_string(amount, true)
+		),
+		Err(e) => {
+			error!("Sweep failed: {}", e);
+			return Err(e);
+		}
+	}
+	Ok(())
+}
+
+fn sweep_seed_impl<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	sweep_wallet: SweepWallet<C>,
+	sweep_dir: &str,
+	args: &SweepSeedArgs,
+) -> Result<Option<u64>, Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let recovery_phrase = match &args.recovery_phrase {
+		Some(phrase) => phrase.clone(),
+		None => owner_api.get_receive_wallet_phrase(keychain_mask)?,
+	};
+
+	let sweep_mask = {
+		let mut w_lock = sweep_wallet.lock();
+		let lc = w_lock.lc_provider()?;
+		lc.set_top_level_directory(sweep_dir)?;
+		lc.create_wallet(
+			None,
+			Some(recovery_phrase),
+			32,
+			ZeroingString::from(""),
+			false,
+			None,
+		)?;
+		lc.open_wallet(None, ZeroingString::from(""), false, false, None)?
+	};
+	let sweep_km = sweep_mask.as_ref();
+
+	let sweep_api = Owner::new(sweep_wallet.clone(), None, None);
+	let start_height = args.start_height.unwrap_or(1);
+	warn!(
+		"Scanning for the swept wallet outputs from height {} ...",
+		start_height
+	);
+	sweep_api.scan(sweep_km, Some(start_height), false)?;
+
+	let tip_height = sweep_api.node_height(sweep_km)?.height;
+	let (_, outputs) = sweep_api.retrieve_outputs(sweep_km, false, false, None)?;
+	let outputs: Vec<_> = outputs
+		.into_iter()
+		.filter(|o| {
+			o.output
+				.eligible_to_spend(tip_height, args.minimum_confirmations)
+		})
+		.collect();
+	if outputs.is_empty() {
+		return Ok(None);
+	}
+
+	let total_amount: u64 = outputs.iter().map(|o| o.output.value).sum();
+	let fee = tx_fee(outputs.len(), 1, 1, Some(selection::get_base_fee()));
+	if total_amount <= fee {
+		return Err(ErrorKind::LibWallet(format!(
+			"Swept funds {} are smaller than the transaction fee {}",
+			amount_to_hr_string(total_amount, true),
+			amount_to_hr_string(fee, true)
+		))
+		.into());
+	}
+
+	let init_args = InitTxArgs {
+		src_acct_name: None,
+		amount: total_amount - fee,
+		minimum_confirmations: args.minimum_confirmations,
+		max_outputs: outputs.len() as u32,
+		num_change_outputs: 1,
+		selection_strategy_is_use_all: true,
+		address: Some(String::from("sweep")),
+		..Default::default()
+	};
+	let mut slate = sweep_api.init_send_tx(sweep_km, &init_args, 1)?;
+	sweep_api.tx_lock_outputs(sweep_km, &slate, Some(String::from("sweep")), 0)?;
+
+	let km = keychain_mask.map(|m| m.to_owned());
+	controller::foreign_single_use(owner_api.wallet_inst.clone(), km, |api| {
+		slate = api.receive_tx(&slate, Some(String::from("sweep")), None, None)?;
+		Ok(())
+	})?;
+
+	let slate = sweep_api.finalize_tx(sweep_km, &slate)?;
+	sweep_api
+		.post_tx(sweep_km, &slate.tx, args.fluff)
+		.map_err(|e| ErrorKind::LibWallet(format!("Unable to post slate, {}", e)))?;
+	info!(
+		"sweep slate [{}] posted successfully",
+		log_privacy::slate_id(slate.id)
+	);
+
+	Ok(Some(init_args.amount))
+}
+
+/// Display the recovery phrase of the receive-only wallet. The listener can run with that wallet
+/// on an online box while this wallet seed stays offline.
+pub fn receive_wallet<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let phrase = api.get_receive_wallet_phrase(m)?;
+		println!("Receive-only wallet belongs to this wallet and can be recreated from its seed.");
+		grin_wallet_impls::lifecycle::show_recovery_phrase(phrase);
+		println!();
+		println!(
+			"Run `init -r` with this phrase on the listening box. The funds received there can"
+		);
+		println!("be moved into this wallet with `sweep-seed --receive_wallet`.");
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Arguments for address command
+pub struct AddressArgs {
+	pub index: Option<u32>,
+	pub list_used: bool,
+}
+
 /// Payment Proof Address
 pub fn address<L, C, K>(
 	owner_api: &mut Owner<L, C, K>,
 	_g_args: &GlobalArgs,
 	keychain_mask: Option<&SecretKey>,
+	args: AddressArgs,
 ) -> Result<(), Error>
 where
 	L: WalletLCProvider<'static, C, K> + 'static,
@@ -1452,16 +3607,122 @@ where
 	K: keychain::Keychain + 'static,
 {
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
-		// Just address at derivation index 0 for now
+		if args.list_used {
+			let indices = api.list_used_address_indices(m)?;
+			if indices.is_empty() {
+				println!("No payments with the proof were received yet");
+			} else {
+				let indices: Vec<String> = indices.iter().map(|i| i.to_string()).collect();
+				println!("Address indexes with payments: {}", indices.join(", "));
+			}
+			return Ok(());
+		}
+
+		if let Some(index) = args.index {
+			let address = api.get_public_proof_address(m, index)?;
+			println!();
+			println!("Address index:            {}", address.index);
+			println!("MQS public address:       {}", address.mqs_address);
+			println!("Tor/SlatepackTor address: {}", address.tor_address);
+			println!();
+			return Ok(());
+		}
+
 		let mqs_pub_key = api.get_mqs_address(m)?;
 		let tor_pub_key = api.get_wallet_public_address(m)?;
 
 		let mqs_addr = ProvableAddress::from_pub_key(&mqs_pub_key);
 		let tor_addr = ProvableAddress::from_tor_pub_key(&tor_pub_key);
 
+		// p2p peer id is derived from the slatepack key
+		let p2p_addr = {
+			let mut w_lock = api.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			let keychain = w.keychain(m)?;
+			let slatepack_secret =
+				proofaddress::payment_proof_address_dalek_secret(&keychain, None)?;
+			p2p::p2p_address(&slatepack_secret)?
+		};
+
 		println!();
 		println!("MQS public address:       {}", mqs_addr);
 		println!("Tor/SlatepackTor address: {}", tor_addr);
+		println!("P2P address:              {}", p2p_addr);
+		println!();
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Arguments for payment-id command
+pub struct PaymentIdArgs {
+	pub payment_id: String,
+	pub address: String,
+	pub tor: bool,
+}
+
+/// Print the signed record to publish for the payment id
+pub fn payment_id<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: PaymentIdArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let record = api.payment_id_record(m, &args.payment_id, &args.address, args.tor)?;
+		// the record is built, so the payment id is valid
+		let id = PaymentId::parse(&args.payment_id).unwrap();
+
+		println!();
+		println!("DNS TXT record name: {}", id.txt_record_name());
+		println!("DNS TXT record:      {}", record.to_txt());
+		println!();
+		println!("Or publish at {}", id.well_known_url());
+		println!("{}", json::to_string_pretty(&record).unwrap());
+		println!();
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Payment request Args
+pub struct RequestArgs {
+	pub amount: Option<u64>,
+	pub message: Option<String>,
+	pub method: String,
+}
+
+/// Print mwc payment URI for this wallet, payer can use it with 'send --uri'
+pub fn request<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: RequestArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		// mwcmqs method needs MQS address, tor address works for http and slatepack
+		let address = match args.method.as_str() {
+			"mwcmqs" => ProvableAddress::from_pub_key(&api.get_mqs_address(m)?),
+			_ => ProvableAddress::from_tor_pub_key(&api.get_wallet_public_address(m)?),
+		};
+
+		let uri = PaymentUri {
+			address: address.to_string(),
+			amount: args.amount,
+			message: args.message.clone(),
+			method: Some(args.method.clone()),
+		};
+
+		println!();
+		println!("Payment URI: {}", uri);
 		println!();
 		Ok(())
 	})?;
@@ -1473,6 +3734,159 @@ pub struct ProofExportArgs {
 	pub output_file: String,
 	pub id: Option<u32>,
 	pub tx_slate_id: Option<Uuid>,
+	/// Export proofs of all confirmed sent transactions into the directory or zip file
+	pub all: bool,
+	/// With 'all', only the transactions created at or after this UTC timestamp
+	pub since: Option<i64>,
+}
+
+/// Name of the manifest file in the proof bundle
+pub const PROOF_MANIFEST_FILE: &str = "manifest.json";
+
+/// Manifest of the exported proof bundle
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofManifest {
+	/// Export time
+	pub created: chrono::DateTime<Utc>,
+	/// Transactions created at or after this UTC timestamp are included
+	pub since: Option<i64>,
+	/// Exported proofs
+	pub proofs: Vec<ProofManifestEntry>,
+}
+
+/// Exported proof in the manifest
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofManifestEntry {
+	/// Proof file name in the bundle
+	pub file: String,
+	pub tx_id: u32,
+	pub tx_slate_id: Option<Uuid>,
+	pub creation_ts: chrono::DateTime<Utc>,
+	pub confirmation_ts: Option<chrono::DateTime<Utc>>,
+	/// Proven amount, nanoMWC
+	pub amount: u64,
+	pub sender: Option<String>,
+	pub receiver: String,
+	pub kernel: String,
+}
+
+fn write_json_file<T: serde::Serialize>(path: &Path, data: &T) -> Result<(), Error> {
+	let mut file = File::create(path).map_err(|e| {
+		ErrorKind::GenericError(format!("Unable to create file {}, {}", path.display(), e))
+	})?;
+	file.write_all(json::to_string_pretty(data).unwrap().as_bytes())
+		.map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to save file {}, {}", path.display(), e))
+		})?;
+	file.sync_all().map_err(|e| {
+		ErrorKind::GenericError(format!("Unable to save file {}, {}", path.display(), e))
+	})?;
+	Ok(())
+}
+
+fn is_zip_path(path: &str) -> bool {
+	path.to_lowercase().ends_with(".zip")
+}
+
+// Export the proofs of all confirmed sent transactions with the manifest. The output is
+// a directory, or a zip file if its name ends with '.zip'.
+fn proof_export_all<L, C, K>(
+	api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: &ProofExportArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let query = TxQueryArgs {
+		min_creation_ts: args.since,
+		tx_types: Some(vec![TxLogEntryType::TxSent]),
+		confirmed: Some(true),
+		..Default::default()
+	};
+	let (_, txs) = api.query_txs(keychain_mask, false, &query)?;
+
+	let zip = is_zip_path(&args.output_file);
+	let dir = if zip {
+		std::env::temp_dir().join(format!("mwc-proofs-{}", Uuid::new_v4()))
+	} else {
+		PathBuf::from(&args.output_file)
+	};
+	fs::create_dir_all(&dir).map_err(|e| {
+		ErrorKind::GenericError(format!(
+			"Unable to create directory {}, {}",
+			dir.display(),
+			e
+		))
+	})?;
+
+	let mut manifest = ProofManifest {
+		created: Utc::now(),
+		since: args.since,
+		proofs: vec![],
+	};
+	for tx in &txs {
+		// Transactions without the proof are not eligible
+		let proof = match api.get_stored_tx_proof(keychain_mask, Some(tx.id)) {
+			Ok(p) => p,
+			Err(e) => {
+				debug!("Transaction {} has no payment proof, {}", tx.id, e);
+				continue;
+			}
+		};
+		let (sender, receiver, amount, _outputs, kernel) =
+			match grin_wallet_libwallet::proof::tx_proof::verify_tx_proof_wrapper(&proof) {
+				Ok(r) => r,
+				Err(e) => {
+					warn!("Payment proof of transaction {} is not valid, {}", tx.id, e);
+					continue;
+				}
+			};
+		let file = match tx.tx_slate_id {
+			Some(uuid) => format!("{}-{}.proof", tx.id, uuid),
+			None => format!("{}.proof", tx.id),
+		};
+		write_json_file(&dir.join(&file), &proof)?;
+		manifest.proofs.push(ProofManifestEntry {
+			file,
+			tx_id: tx.id,
+			tx_slate_id: tx.tx_slate_id,
+			creation_ts: tx.creation_ts,
+			confirmation_ts: tx.confirmation_ts,
+			amount,
+			sender,
+			receiver,
+			kernel,
+		});
+	}
+	write_json_file(&dir.join(PROOF_MANIFEST_FILE), &manifest)?;
+
+	if zip {
+		let mut files: Vec<PathBuf> = manifest
+			.proofs
+			.iter()
+			.map(|p| PathBuf::from(&p.file))
+			.collect();
+		files.push(PathBuf::from(PROOF_MANIFEST_FILE));
+		let res = File::create(&args.output_file)
+			.and_then(|zip_file| crate::util::zip::create_zip(&zip_file, &dir, files));
+		let _ = fs::remove_dir_all(&dir);
+		res.map_err(|e| {
+			ErrorKind::GenericError(format!(
+				"Unable to create zip file {}, {}",
+				args.output_file, e
+			))
+		})?;
+	}
+
+	warn!(
+		"{} payment proofs exported to {}",
+		manifest.proofs.len(),
+		args.output_file
+	);
+	Ok(())
 }
 
 pub fn proof_export<L, C, K>(
@@ -1486,6 +3900,9 @@ where
 	K: keychain::Keychain + 'static,
 {
 	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		if args.all {
+			return proof_export_all(api, m, &args);
+		}
 		let result = api.get_stored_tx_proof(m, args.id);
 		match result {
 			Ok(p) => {
@@ -1529,11 +3946,185 @@ where
 /// Proof Verify Args
 pub struct ProofVerifyArgs {
 	pub input_file: String,
+	/// Verify all proofs from the directory or zip file, see 'export_proof --all'
+	pub batch: bool,
+	/// Don't look up the kernel of the payment proof at the node
+	pub offline: bool,
+}
+
+fn read_proof_file(path: &Path) -> Result<String, Error> {
+	let mut file = File::open(path)
+		.map_err(|e| ErrorKind::LibWallet(format!("Unable to open proof data, {}", e)))?;
+	let mut proof = String::new();
+	file.read_to_string(&mut proof)
+		.map_err(|e| ErrorKind::LibWallet(format!("Unable to read proof data, {}", e)))?;
+	Ok(proof)
+}
+
+fn read_tx_proof(path: &Path) -> Result<TxProof, Error> {
+	let proof = read_proof_file(path)?;
+	let tx_pf: TxProof = serde_json::from_str(&proof)
+		.map_err(|e| ErrorKind::LibWallet(format!("Unable to deserialize proof data, {}", e)))?;
+	Ok(tx_pf)
+}
+
+// Verify the payment proof from 'retrieve_payment_proof' without the wallet. The kernel is
+// checked at the configured node unless 'offline' is set.
+fn payment_proof_verify(
+	proof: &PaymentProof,
+	wallet_config: &WalletConfig,
+	node_api_secret: Option<String>,
+	offline: bool,
+) -> Result<(), Error> {
+	payment_proof::verify_payment_proof_signatures(proof)
+		.map_err(|e| ErrorKind::LibWallet(format!("Proof not valid: {}", e)))?;
+	println!(
+		"Payment proof signatures are valid: {} MWC from {} to {}",
+		amount_to_hr_string(proof.amount, false),
+		proof.sender_address.public_key,
+		proof.recipient_address.public_key
+	);
+
+	if offline {
+		println!("Kernel lookup is skipped, the proof is not checked against the chain");
+		return Ok(());
+	}
+	let node_list = parse_node_address_string(wallet_config.check_node_api_http_addr.clone());
+	let client = HTTPNodeClient::new(node_list, node_api_secret)?;
+	let height = payment_proof::verify_payment_proof_kernel(&client, proof)
+		.map_err(|e| ErrorKind::LibWallet(format!("Proof not valid: {}", e)))?;
+	println!(
+		"Kernel {} is found on the chain at height {}",
+		crate::util::to_hex(&proof.excess.0),
+		height
+	);
+	Ok(())
+}
+
+// Verify the proof file of the bundle, the manifest entry must match the proof
+fn verify_bundle_proof(
+	dir: &Path,
+	file: &str,
+	entry: Option<&ProofManifestEntry>,
+) -> Result<(u64, String), Error> {
+	let proof = read_tx_proof(&dir.join(file))?;
+	let (sender, receiver, amount, _outputs, kernel) =
+		grin_wallet_libwallet::proof::tx_proof::verify_tx_proof_wrapper(&proof)
+			.map_err(|e| ErrorKind::LibWallet(format!("Proof not valid: {}", e)))?;
+	if let Some(entry) = entry {
+		if entry.amount != amount
+			|| entry.kernel != kernel
+			|| entry.receiver != receiver
+			|| entry.sender != sender
+		{
+			return Err(
+				ErrorKind::LibWallet("Proof doesn't match the manifest entry".to_string()).into(),
+			);
+		}
+	}
+	Ok((amount, receiver))
+}
+
+fn read_proof_manifest(path: &Path) -> Result<ProofManifest, Error> {
+	let data = fs::read_to_string(path)
+		.map_err(|e| ErrorKind::LibWallet(format!("Unable to read {}, {}", path.display(), e)))?;
+	let manifest = serde_json::from_str(&data)
+		.map_err(|e| ErrorKind::LibWallet(format!("Unable to parse {}, {}", path.display(), e)))?;
+	Ok(manifest)
+}
+
+// Verify all proofs from the exported bundle. With the manifest the listed proofs are
+// verified, without it all '*.proof' files of the directory.
+fn proof_verify_batch(input: &str) -> Result<(), Error> {
+	let zip = is_zip_path(input);
+	let dir = if zip {
+		let dir = std::env::temp_dir().join(format!("mwc-proofs-{}", Uuid::new_v4()));
+		let open_zip = || {
+			File::open(input)
+				.map_err(|e| ErrorKind::LibWallet(format!("Unable to open {}, {}", input, e)))
+		};
+		crate::util::zip::extract_files(
+			open_zip()?,
+			&dir,
+			vec![PathBuf::from(PROOF_MANIFEST_FILE)],
+		)
+		.map_err(|e| ErrorKind::LibWallet(format!("Unable to extract {}, {}", input, e)))?;
+		let manifest = read_proof_manifest(&dir.join(PROOF_MANIFEST_FILE))?;
+		let files = manifest
+			.proofs
+			.iter()
+			.map(|p| PathBuf::from(&p.file))
+			.collect();
+		crate::util::zip::extract_files(open_zip()?, &dir, files)
+			.map_err(|e| ErrorKind::LibWallet(format!("Unable to extract {}, {}", input, e)))?;
+		dir
+	} else {
+		PathBuf::from(input)
+	};
+
+	let res = verify_proof_dir(&dir);
+	if zip {
+		let _ = fs::remove_dir_all(&dir);
+	}
+	res
+}
+
+fn verify_proof_dir(dir: &Path) -> Result<(), Error> {
+	let manifest_path = dir.join(PROOF_MANIFEST_FILE);
+	let entries: Vec<(String, Option<ProofManifestEntry>)> = if manifest_path.exists() {
+		read_proof_manifest(&manifest_path)?
+			.proofs
+			.into_iter()
+			.map(|p| (p.file.clone(), Some(p)))
+			.collect()
+	} else {
+		let mut files: Vec<(String, Option<ProofManifestEntry>)> = fs::read_dir(dir)
+			.map_err(|e| {
+				ErrorKind::LibWallet(format!("Unable to read directory {}, {}", dir.display(), e))
+			})?
+			.filter_map(|e| e.ok())
+			.map(|e| e.file_name().to_string_lossy().to_string())
+			.filter(|name| name.ends_with(".proof"))
+			.map(|name| (name, None))
+			.collect();
+		files.sort();
+		files
+	};
+
+	let mut failed = 0;
+	for (file, entry) in &entries {
+		match verify_bundle_proof(dir, file, entry.as_ref()) {
+			Ok((amount, receiver)) => println!(
+				"OK      {}  {} MWC to {}",
+				file,
+				amount_to_hr_string(amount, false),
+				receiver
+			),
+			Err(e) => {
+				failed += 1;
+				println!("FAILED  {}  {}", file, e);
+			}
+		}
+	}
+	println!();
+	println!(
+		"Verified {} payment proofs, {} failed",
+		entries.len(),
+		failed
+	);
+	if failed > 0 {
+		return Err(
+			ErrorKind::LibWallet(format!("{} payment proofs are not valid", failed)).into(),
+		);
+	}
+	Ok(())
 }
 
 pub fn proof_verify<L, C, K>(
 	_owner_api: &mut Owner<L, C, K>,
 	_keychain_mask: Option<&SecretKey>,
+	wallet_config: &WalletConfig,
+	node_api_secret: Option<String>,
 	args: ProofVerifyArgs,
 ) -> Result<(), Error>
 where
@@ -1541,6 +4132,9 @@ where
 	C: NodeClient + 'static,
 	K: keychain::Keychain + 'static,
 {
+	if args.batch {
+		return proof_verify_batch(&args.input_file);
+	}
 	//read the file.
 	let input = &args.input_file;
 	let home_dir = std::env::current_exe() //  dirs::home_dir()
@@ -1556,11 +4150,11 @@ where
 		error!("{}", msg);
 		return Err(ErrorKind::LibWallet(msg).into());
 	}
-	let mut file = File::open(path)
-		.map_err(|e| ErrorKind::LibWallet(format!("Unable to open proof data, {}", e)))?;
-	let mut proof = String::new();
-	file.read_to_string(&mut proof)
-		.map_err(|e| ErrorKind::LibWallet(format!("Unable to read proof data, {}", e)))?;
+	// Payment proof from 'retrieve_payment_proof' is verified by the signatures and the kernel
+	let proof = read_proof_file(&path)?;
+	if let Ok(payment_proof) = serde_json::from_str::<PaymentProof>(&proof) {
+		return payment_proof_verify(&payment_proof, wallet_config, node_api_secret, args.offline);
+	}
 	let tx_pf: TxProof = serde_json::from_str(&proof)
 		.map_err(|e| ErrorKind::LibWallet(format!("Unable to deserialize proof data, {}", e)))?;
 
@@ -1604,6 +4198,83 @@ where
 	Ok(())
 }
 
+pub fn gc<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	args: GcArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	controller::owner_single_use(None, keychain_mask, Some(owner_api), |api, m| {
+		let result = api.gc_files(m, &args)?;
+		let action = if args.dry_run {
+			"To be removed"
+		} else {
+			"Removed"
+		};
+		for f in &result.tx_files {
+			println!("{} saved transaction {}", action, f);
+		}
+		for f in &result.swap_files {
+			println!("{} swap file {}", action, f);
+		}
+		println!(
+			"{} {} saved transactions and {} swap files, {} bytes",
+			action,
+			result.tx_files.len(),
+			result.swap_files.len(),
+			result.bytes
+		);
+		Ok(())
+	})?;
+	Ok(())
+}
+
+/// Audit log print and verification
+pub struct AuditArgs {
+	pub verify: bool,
+	pub file: Option<String>,
+}
+
+pub fn audit(args: AuditArgs) -> Result<(), Error> {
+	let file = args
+		.file
+		.or_else(audit_log::get_audit_log_file)
+		.ok_or(ErrorKind::ArgumentError(
+			"Audit log is not configured. Please set audit_log_file in the wallet config or specify --file".to_string(),
+		))?;
+
+	if args.verify {
+		let res = audit_log::verify_audit_log(&file)?;
+		return match res.broken_at {
+			None => {
+				println!("Audit log {} is valid, {} records", file, res.valid_records);
+				Ok(())
+			}
+			Some((line, reason)) => Err(ErrorKind::GenericError(format!(
+				"Audit log {} is broken at line {}, {}. Only {} records before it are valid",
+				file, line, reason, res.valid_records
+			))
+			.into()),
+		};
+	}
+
+	for r in audit_log::read_audit_log(&file)? {
+		let result = match &r.error {
+			Some(e) => format!("failed: {}", e),
+			None => "ok".to_string(),
+		};
+		println!(
+			"{} {} {} [{}] {} - {}",
+			r.seq, r.timestamp, r.operation, r.caller, r.details, result
+		);
+	}
+	Ok(())
+}
+
 pub fn swap_start<L, C, K>(
 	owner_api: &mut Owner<L, C, K>,
 	keychain_mask: Option<&SecretKey>,
@@ -1627,12 +4298,15 @@ where
 			})?;
 		}
 		"file" => (), // not validating the fine name. Files are secondary and testing method.
-		_ => {
-			return Err(ErrorKind::ArgumentError(format!(
-				"Invalid communication method '{}'. Valid methods: mwcmqs, tor, file",
-				args.buyer_communication_method
-			))
-			.into())
+		method => {
+			// Messenger transport validates its destination address
+			if create_swap_transport(method, &args.buyer_communication_address)?.is_none() {
+				return Err(ErrorKind::ArgumentError(format!(
+					"Invalid communication method '{}'. Valid methods: mwcmqs, tor, file and the messenger transports that deliver the swap messages",
+					method
+				))
+				.into());
+			}
 		}
 	}
 
@@ -1696,6 +4370,10 @@ pub enum SwapSubcommand {
 	TradeExport,
 	TradeImport,
 	StopAllAutoSwap,
+	History,
+	NewSecondaryAddress,
+	ListSecondaryAddresses,
+	SweepSecondaryAddresses,
 }
 
 /// Arguments for the swap command
@@ -1740,6 +4418,10 @@ pub struct SwapArgs {
 	pub wait_for_backup1: bool,
 	/// Assign tag to this trade
 	pub tag: Option<String>,
+	/// Lock window to adjust, seconds
+	pub lock_window_sec: Option<u64>,
+	/// Currency of the wallet secondary addresses
+	pub currency: Option<String>,
 }
 
 /// Eth operation
@@ -1841,6 +4523,20 @@ pub struct SwapJournalRecordString {
 	pub message: String,
 }
 
+// ElectrumX servers that can be used by the swap, with their status. Empty for non BTC family.
+fn electrumx_servers_status(
+	swap: &Swap,
+) -> Vec<(String, Result<u64, grin_wallet_libwallet::swap::ErrorKind>)> {
+	if !swap.secondary_currency.is_btc_family() {
+		return vec![];
+	}
+	owner_swap::check_electrumx_servers(
+		&swap.secondary_currency,
+		&swap.electrum_node_uri1,
+		&swap.electrum_node_uri2,
+	)
+}
+
 fn notify_about_cancelled_swaps<L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>,
 	keychain_mask: Option<&SecretKey>,
@@ -1870,7 +4566,8 @@ fn notify_about_cancelled_swaps<L, C, K>(
 					) {
 						error!(
 							"Unable to send fail_bidding message to the wallet {}, {}",
-							swap.communication_address, e
+							log_privacy::address(&swap.communication_address),
+							e
 						);
 					}
 				}
@@ -1885,7 +4582,7 @@ pub fn swap<L, C, K>(
 	mqs_config: MQSConfig,
 	tor_config: TorConfig,
 	tls_conf: Option<TLSConfig>,
-	args: SwapArgs,
+	mut args: SwapArgs,
 	cli_mode: bool,
 ) -> Result<(), Error>
 where
@@ -1998,11 +4695,13 @@ where
 					}
 					"file" => (),
 					_ => {
-						return Err(ErrorKind::ArgumentError(format!(
-							"Unknown communication method value '{}'",
-							method
-						))
-						.into());
+						if create_swap_transport(method, &destination)?.is_none() {
+							return Err(ErrorKind::ArgumentError(format!(
+								"Unknown communication method value '{}'",
+								method
+							))
+							.into());
+						}
 					}
 				}
 			}
@@ -2030,6 +4729,7 @@ where
 					args.electrum_node_uri2.clone(),
 					args.eth_infura_project_id.clone(),
 					args.tag.clone(),
+					args.lock_window_sec,
 				);
 				match result {
 					Ok((state, _action)) => {
@@ -2112,10 +4812,30 @@ where
 									"eth_swap_contract_address": swap.eth_swap_contract_address.clone().unwrap_or("".to_string()),
 									"erc20_swap_contract_address": swap.erc20_swap_contract_address.clone().unwrap_or("".to_string()),
 									"eth_infura_project_id": swap.eth_infura_project_id.clone().unwrap_or("".to_string()),
+									"electrumServers": electrumx_servers_status(&swap)
+										.into_iter()
+										.map(|(uri, res)| json::json!({
+											"uri": uri,
+											"height": res.as_ref().map(|h| *h).unwrap_or(0),
+											"error": res.err().map(|e| format!("{}", e)).unwrap_or("".to_string()),
+										}))
+										.collect::<Vec<json::Value>>(),
 								});
 								println!("JSON: {}", item.to_string());
 								return Ok(());
 							} else {
+								if swap.secondary_currency.is_btc_family() {
+									println!("Unable to get the swap status, checking ElectrumX servers:");
+									for (uri, res) in electrumx_servers_status(&swap) {
+										match res {
+											Ok(height) => println!(
+												"    {} is reachable, height {}",
+												uri, height
+											),
+											Err(e) => println!("    {} is unreachable, {}", uri, e),
+										}
+									}
+								}
 								return Err(Error::from(e));
 							}
 						}
@@ -2270,7 +4990,9 @@ where
 									e
 								))
 							})?;
-							thread::sleep(Duration::from_millis(2000));
+							controller::wait_for_mwcmqs_listener(
+								controller::MWCMQS_LISTENER_START_TIMEOUT,
+							);
 						}
 						from_address = grin_wallet_impls::adapters::get_mwcmqs_brocker()
 							.ok_or(crate::libwallet::ErrorKind::SwapError(
@@ -2288,25 +5010,20 @@ where
 					}
 					"tor" => {
 						if !controller::is_foreign_api_running() {
-							let tor_config = tor_config2.clone();
-							let _api_thread = thread::Builder::new()
-								.name("wallet-http-listener".to_string())
-								.spawn(move || {
-									let res = controller::foreign_listener(
-										wallet_inst2,
-										Arc::new(Mutex::new(km)),
-										&api_listen_addr,
-										tls_conf,
-										tor_config.use_tor_listener,
-										&tor_config.socks_proxy_addr,
-										&None,
-										&tor_config.tor_log_file,
-									);
-									if let Err(e) = res {
-										error!("Error starting http listener: {}", e);
-									}
-								});
-							thread::sleep(Duration::from_millis(2000));
+							controller::start_foreign_listener_thread(
+								wallet_inst2,
+								Arc::new(Mutex::new(km)),
+								api_listen_addr,
+								tls_conf,
+								tor_config2.clone(),
+								controller::FOREIGN_LISTENER_START_TIMEOUT,
+							)
+							.map_err(|e| {
+								crate::libwallet::ErrorKind::SwapError(format!(
+									"Unable to start tor listener, {}",
+									e
+								))
+							})?;
 						}
 						from_address = tor::status::get_tor_address().ok_or(
 							crate::libwallet::ErrorKind::GenericError(
@@ -2327,13 +5044,24 @@ where
 						println!("Message is written into the file {}", dest);
 						return Ok((true, destination_str)); // ack if true, because file is concidered as delivered
 					}
-					_ => {
-						error!("Please specify a method (mwcmqs, tor, or file) for transporting swap messages to the other party with whom you're doing the swap!");
-						return Err(crate::libwallet::Error::from(
-							crate::libwallet::ErrorKind::SwapError(
-								"Expected 'method' argument is not found".to_string(),
-							),
-						));
+					method => {
+						let transport = create_swap_transport(method, "").map_err(|e| {
+							crate::libwallet::ErrorKind::SwapError(format!("{}", e))
+						})?;
+						if transport.is_none() {
+							error!("Please specify a method (mwcmqs, tor, file or a messenger transport) for transporting swap messages to the other party with whom you're doing the swap!");
+							return Err(crate::libwallet::Error::from(
+								crate::libwallet::ErrorKind::SwapError(
+									"Expected 'method' argument is not found".to_string(),
+								),
+							));
+						}
+						from_address = controller::ensure_transport_listener(
+							wallet_inst2,
+							Arc::new(Mutex::new(km)),
+							method,
+						)
+						.map_err(|e| crate::libwallet::ErrorKind::SwapError(format!("{}", e)))?;
 					}
 				}
 
@@ -2423,6 +5151,18 @@ where
 
 			let swap = owner_swap::swap_get(wallet_inst.clone(), keychain_mask, &swap_id)?;
 
+			// The wallet address is generated once, every step of the trade uses the same one
+			for address in vec![&mut args.buyer_refund_address, &mut args.secondary_address] {
+				if address.as_deref() == Some(WALLET_SECONDARY_ADDRESS) {
+					let wallet_address = owner_swap::swap_new_secondary_address(
+						wallet_inst.clone(),
+						keychain_mask,
+						&swap.secondary_currency.to_string(),
+					)?;
+					address.replace(wallet_address.address);
+				}
+			}
+
 			let wallet_inst2 = wallet_inst.clone();
 			let km2 = km.clone();
 
@@ -2448,7 +5188,9 @@ where
 						.map_err(|e| {
 							ErrorKind::LibWallet(format!("Unable to start mwcmqs listener, {}", e))
 						})?;
-						thread::sleep(Duration::from_millis(2000));
+						controller::wait_for_mwcmqs_listener(
+							controller::MWCMQS_LISTENER_START_TIMEOUT,
+						);
 					}
 					"tor" => {
 						// Checking is foreign API is running. It dont't important if it is tor or http.
@@ -2457,32 +5199,32 @@ where
 						}
 
 						// Starting tor
-						let tor_config = tor_config.clone();
-						let _api_thread = thread::Builder::new()
-							.name("wallet-http-listener".to_string())
-							.spawn(move || {
-								let res = controller::foreign_listener(
-									wallet_inst,
-									Arc::new(Mutex::new(km)),
-									&api_listen_addr,
-									tls_conf,
-									tor_config.use_tor_listener,
-									&tor_config.socks_proxy_addr,
-									&None,
-									&tor_config.tor_log_file,
-								);
-								if let Err(e) = res {
-									error!("Error starting http listener: {}", e);
-								}
-							});
-						thread::sleep(Duration::from_millis(2000));
+						controller::start_foreign_listener_thread(
+							wallet_inst,
+							Arc::new(Mutex::new(km)),
+							api_listen_addr,
+							tls_conf,
+							tor_config.clone(),
+							controller::FOREIGN_LISTENER_START_TIMEOUT,
+						)?;
 					}
-					_ => {
-						return Err(ErrorKind::ArgumentError(format!(
-							"Auto Swap doesn't support communication method {}",
-							swap.communication_method
-						))
-						.into());
+					method => {
+						let transport = create_swap_transport(method, "")?.ok_or(
+							ErrorKind::ArgumentError(format!(
+								"Auto Swap doesn't support communication method {}",
+								method
+							)),
+						)?;
+						if controller::get_transport_listener_address(method).is_some() {
+							return Err(ErrorKind::GenericError(format!("{} listener is already running, there is no need to specify '--start_listener' parameter", method)).into());
+						}
+
+						controller::start_transport_listener(
+							wallet_inst,
+							Arc::new(Mutex::new(km)),
+							transport,
+							false,
+						)?;
 					}
 				}
 			}
@@ -2529,12 +5271,18 @@ where
 					from_address = tor::status::get_tor_address()
 						.ok_or(ErrorKind::GenericError("Tor is not running".to_string()))?;
 				}
-				_ => {
-					return Err(ErrorKind::ArgumentError(format!(
-						"Auto Swap doesn't support communication method {}",
-						swap.communication_method
-					))
-					.into());
+				method => {
+					// Messenger transport validates its destination address
+					if create_swap_transport(method, &swap.communication_address)?.is_none() {
+						return Err(ErrorKind::ArgumentError(format!(
+							"Auto Swap doesn't support communication method {}",
+							method
+						))
+						.into());
+					}
+
+					from_address = controller::get_transport_listener_address(method)
+						.ok_or(ErrorKind::GenericError(format!("{} listener is not running. Please start it with 'listen' command or '--start_listener' argument", method)))?;
 				}
 			}
 
@@ -2656,6 +5404,17 @@ where
 			};
 
 			if !one_shot {
+				// The trade is resumed if the wallet is restarted before it is finished
+				if let Err(e) = trades::save_autoswap_session(AutoSwapSession {
+					swap_id: swap_id.clone(),
+					secondary_fee: args.secondary_fee.clone(),
+					message_file_name: args.message_file_name.clone(),
+					buyer_refund_address: args.buyer_refund_address.clone(),
+					secondary_address: args.secondary_address.clone(),
+					wait_for_backup1: args.wait_for_backup1,
+				}) {
+					warn!("Unable to save auto swap session for {}, {}", swap_id, e);
+				}
 				println!(
 					"Swap started in auto mode.... Status will be displayed as swap progresses."
 				);
@@ -2677,12 +5436,24 @@ where
 			let wait_for_backup1 = args.wait_for_backup1;
 			let kc_mask = keychain_mask.map(|m| m.clone());
 			let tor_config2 = tor_config.clone();
+			// Action that the alert was sent for, the same action is reported once
+			let mut alerted_action: Option<String> = None;
 
 			debug!("Starting autoswap thread for swap id {}", swap_id);
 			let api_thread = thread::Builder::new()
 				.name("wallet-auto-swap".to_string())
 				.spawn(move || {
 					loop {
+						// Maintenance mode, waiting until the wallet is resumed
+						if owner::is_wallet_paused() {
+							if stop_thread_clone.load(Ordering::Relaxed) {
+								println!("Auto swap for trade {} is stopped. You can continue with the swap manually by entering individual commands.", swap_id2);
+								let _ = trades::remove_autoswap_session(&swap_id2);
+								break;
+							}
+							thread::sleep(Duration::from_millis(1000));
+							continue;
+						}
 						// we can't exit by error from the loop.
 						let (
 							mut curr_state,
@@ -2757,6 +5528,22 @@ where
 							);
 						}
 
+						// The thread might be detached, the user gets the alert if the trade needs attention
+						if curr_action.requires_attention() {
+							let action_id = curr_action.get_id_str();
+							if alerted_action.as_ref() != Some(&action_id) {
+								notifications::notify(WalletEvent::SwapActionRequired {
+									swap_id: swap_id2.clone(),
+									state: format!("{:?}", curr_state),
+									action: action_id.clone(),
+									description: curr_action.to_string(),
+								});
+								alerted_action = Some(action_id);
+							}
+						} else {
+							alerted_action = None;
+						}
+
 						if !json_format_clone {
 							if prev_journal_len < journal_records.len() {
 								for i in prev_journal_len..journal_records.len() {
@@ -2828,6 +5615,7 @@ where
 						// In case of final state - we are exiting.
 						if curr_state.is_final_state() {
 							println!("{}Swap trade is finished", swap_report_prefix);
+							let _ = trades::remove_autoswap_session(&swap_id2);
 							break;
 						}
 
@@ -2842,6 +5630,7 @@ where
 							// check if the thread is asked to stop
 							if stop_thread_clone.load(Ordering::Relaxed) {
 								println!("Auto swap for trade {} is stopped. You can continue with the swap manually by entering individual commands.", swap_id2);
+								let _ = trades::remove_autoswap_session(&swap_id2);
 								exited = true;
 								break;
 							};
@@ -2886,6 +5675,125 @@ where
 			}
 			Ok(())
 		}
+		SwapSubcommand::History => {
+			let records = owner_swap::swap_history(wallet_inst, keychain_mask).map_err(|e| {
+				ErrorKind::LibWallet(format!("Unable to read swap trades history, {}", e))
+			})?;
+			match args.destination {
+				Some(file_name) => {
+					let content = if file_name.to_lowercase().ends_with(".json") {
+						json::to_string_pretty(&records).map_err(|e| {
+							ErrorKind::GenericError(format!(
+								"Unable to convert swap trades history to Json, {}",
+								e
+							))
+						})?
+					} else {
+						history::history_to_csv(&records)
+					};
+					let mut file = File::create(&file_name).map_err(|e| {
+						ErrorKind::IO(format!("Unable to create file {}, {}", file_name, e))
+					})?;
+					file.write_all(content.as_bytes()).map_err(|e| {
+						ErrorKind::IO(format!("Unable to write into file {}, {}", file_name, e))
+					})?;
+					println!(
+						"History of {} swap trades is exported into {}",
+						records.len(),
+						file_name
+					);
+				}
+				None => {
+					if args.json_format {
+						println!("JSON: {}", json::to_string(&records).unwrap_or_default());
+					} else if records.is_empty() {
+						println!("You don't have any finished Swap trades");
+					} else {
+						display::swap_history(&records);
+					}
+				}
+			}
+			Ok(())
+		}
+		SwapSubcommand::NewSecondaryAddress => {
+			let currency = args.currency.ok_or(ErrorKind::ArgumentError(
+				"Not found expected 'currency' argument".to_string(),
+			))?;
+			let address =
+				owner_swap::swap_new_secondary_address(wallet_inst, keychain_mask, &currency)
+					.map_err(|e| {
+						ErrorKind::LibWallet(format!(
+							"Unable to generate {} address, {}",
+							currency, e
+						))
+					})?;
+			if args.json_format {
+				println!("JSON: {}", json::to_string(&address).unwrap_or_default());
+			} else {
+				println!(
+					"New {} address of the wallet: {}",
+					address.currency, address.address
+				);
+			}
+			Ok(())
+		}
+		SwapSubcommand::ListSecondaryAddresses => {
+			let addresses =
+				owner_swap::swap_list_secondary_addresses(args.currency).map_err(|e| {
+					ErrorKind::LibWallet(format!("Unable to read the wallet addresses, {}", e))
+				})?;
+			if args.json_format {
+				println!("JSON: {}", json::to_string(&addresses).unwrap_or_default());
+			} else if addresses.is_empty() {
+				println!("You don't have any generated secondary currency addresses");
+			} else {
+				display::secondary_addresses(&addresses);
+			}
+			Ok(())
+		}
+		SwapSubcommand::SweepSecondaryAddresses => {
+			let currency = args.currency.ok_or(ErrorKind::ArgumentError(
+				"Not found expected 'currency' argument".to_string(),
+			))?;
+			let destination = args.secondary_address.ok_or(ErrorKind::ArgumentError(
+				"Please define the destination address with '--secondary_address'".to_string(),
+			))?;
+			let swept = owner_swap::swap_sweep_secondary_addresses(
+				wallet_inst,
+				keychain_mask,
+				&currency,
+				&destination,
+				args.secondary_fee,
+				args.electrum_node_uri1,
+				args.electrum_node_uri2,
+				true,
+			)
+			.map_err(|e| {
+				ErrorKind::LibWallet(format!("Unable to sweep {} addresses, {}", currency, e))
+			})?;
+			if args.json_format {
+				println!("JSON: {}", json::to_string(&swept).unwrap_or_default());
+			} else if swept.is_empty() {
+				println!(
+					"{} addresses of the wallet don't have confirmed funds",
+					currency
+				);
+			} else {
+				let currency = Currency::try_from(currency.as_str())
+					.map_err(|e| ErrorKind::ArgumentError(format!("{}", e)))?;
+				for s in &swept {
+					println!(
+						"Address {} is swept to {}, amount {} {}, transaction {}",
+						s.address,
+						destination,
+						currency.amount_to_hr_string(s.amount, true),
+						currency,
+						s.txid
+					);
+				}
+			}
+			Ok(())
+		}
 		SwapSubcommand::Dump => {
 			let swap_id = args.swap_id.ok_or(ErrorKind::ArgumentError(
 				"Not found expected 'swap_id' argument".to_string(),
@@ -2918,8 +5826,13 @@ where
 				"Not found expected file name for the exported data".to_string(),
 			))?;
 
-			trades::export_trade(swap_id.as_str(), file_name.as_str())
-				.map_err(|e| ErrorKind::LibWallet(format!("Unable to export trade data, {}", e)))?;
+			owner_swap::swap_export_trade(
+				wallet_inst,
+				keychain_mask,
+				swap_id.as_str(),
+				file_name.as_str(),
+			)
+			.map_err(|e| ErrorKind::LibWallet(format!("Unable to export trade data, {}", e)))?;
 
 			println!("Swap trade is exported to {}", file_name);
 			Ok(())
@@ -3740,3 +6653,75 @@ where
 	}
 	Ok(())
 }
+
+/// Arguments for the devtools vectors command
+pub struct DevtoolsVectorsArgs {
+	/// Number of address indexes to generate the vectors for
+	pub count: u32,
+}
+
+/// Print deterministic test vectors for addresses and payment proofs. Vectors are generated
+/// from the known test seed, so the wallet is not used.
+pub fn devtools_vectors(args: DevtoolsVectorsArgs) -> Result<(), Error> {
+	let keychain = vectors::test_vectors_keychain()?;
+	let test_vectors = vectors::generate_test_vectors(&keychain, args.count)?;
+	let json = serde_json::to_string_pretty(&test_vectors)
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to serialize test vectors, {}", e)))?;
+	println!("{}", json);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn split_pay_even_weights() {
+		assert_eq!(
+			split_amount_by_weights(9_000_000_000, &vec![1, 1, 1]).unwrap(),
+			vec![3_000_000_000, 3_000_000_000, 3_000_000_000]
+		);
+		assert_eq!(
+			split_amount_by_weights(1_000_000_000, &vec![3, 1]).unwrap(),
+			vec![750_000_000, 250_000_000]
+		);
+		assert_eq!(split_amount_by_weights(7, &vec![5]).unwrap(), vec![7]);
+	}
+
+	#[test]
+	fn split_pay_rounding_remainder() {
+		// 10 / 3 leaves 1 nano coin, it goes to the first destination
+		let shares = split_amount_by_weights(10, &vec![1, 1, 1]).unwrap();
+		assert_eq!(shares, vec![4, 3, 3]);
+
+		// 100 by 1:1:1:1:1:1:1 leaves 2, one per destination from the first
+		let shares = split_amount_by_weights(100, &vec![1; 7]).unwrap();
+		assert_eq!(shares, vec![15, 15, 14, 14, 14, 14, 14]);
+		assert_eq!(shares.iter().sum::<u64>(), 100);
+
+		// Large amounts and weights don't overflow, the sum is kept
+		let amount = u64::MAX / 2;
+		let shares =
+			split_amount_by_weights(amount, &vec![u64::MAX, u64::MAX / 3, u64::MAX - 7]).unwrap();
+		assert_eq!(shares.iter().sum::<u64>(), amount);
+	}
+
+	#[test]
+	fn split_pay_zero_weights() {
+		assert!(split_amount_by_weights(1_000, &vec![]).is_err());
+		assert!(split_amount_by_weights(1_000, &vec![0, 0]).is_err());
+		assert!(split_amount_by_weights(1_000, &vec![2, 0, 1]).is_err());
+	}
+
+	#[test]
+	fn split_pay_amount_smaller_than_destinations() {
+		assert!(split_amount_by_weights(2, &vec![1, 1, 1]).is_err());
+		assert!(split_amount_by_weights(0, &vec![1]).is_err());
+		// A small weight gets nothing even if the amount is large enough for the count
+		assert!(split_amount_by_weights(5, &vec![100, 1]).is_err());
+		assert_eq!(
+			split_amount_by_weights(3, &vec![1, 1, 1]).unwrap(),
+			vec![1, 1, 1]
+		);
+	}
+}
@@ -14,24 +14,30 @@
 
 //! Controller for wallet.. instantiates and handles listeners (or single-run
 //! invocations) as needed.
-use crate::api::{self, ApiServer, BasicAuthMiddleware, ResponseFuture, Router, TLSConfig};
+use crate::api::{self, HandlerObj, ResponseFuture, Router, TLSConfig};
+use crate::api_tokens::{ApiAccess, ApiTokenStore};
+use crate::http_server::{HttpServer, RemoteAddr};
+use crate::invoices::{self, InvoiceStore, MerchantInvoice};
 use crate::libwallet::{
-	NodeClient, NodeVersionInfo, Slate, WalletInst, WalletLCProvider, GRIN_BLOCK_HEADER_VERSION,
+	self, IssueInvoiceTxArgs, NodeClient, NodeVersionInfo, Slate, VersionedSlate, WalletInst,
+	WalletLCProvider, GRIN_BLOCK_HEADER_VERSION,
 };
-use crate::util::secp::key::SecretKey;
-use crate::util::{from_hex, to_base64, Mutex};
+use crate::util::secp::key::{PublicKey, SecretKey};
+use crate::util::{from_hex, static_secp_instance, to_base64, Mutex};
 use crate::{Error, ErrorKind};
 use grin_wallet_api::JsonId;
 use grin_wallet_util::OnionV3Address;
 use hyper::body;
+use hyper::body::HttpBody as _;
 use hyper::header::HeaderValue;
 use hyper::{Body, Request, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json;
 
 use grin_wallet_impls::{
-	Address, CloseReason, MWCMQPublisher, MWCMQSAddress, MWCMQSubscriber, Publisher, Subscriber,
-	SubscriptionHandler,
+	create_transport, Address, CloseReason, MWCMQPublisher, MWCMQSAddress, MWCMQSubscriber,
+	PathToSlateGetter, PathToSlatePutter, Publisher, SlateGetter, SlatePutter, SlateTransport,
+	SlateTransportHandler, Subscriber, SubscriptionHandler,
 };
 use grin_wallet_libwallet::swap::message::Message;
 use grin_wallet_libwallet::wallet_lock;
@@ -48,28 +54,60 @@ use crate::impls::tor::process as tor_process;
 use crate::keychain::Keychain;
 use chrono::Utc;
 use easy_jsonrpc_mw::{Handler, MaybeReply};
+use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
 use grin_wallet_impls::tor;
+use grin_wallet_libwallet::api_impl::owner;
+use grin_wallet_libwallet::audit_log;
 use grin_wallet_libwallet::internal::selection;
+use grin_wallet_libwallet::listener_limits::{
+	self, LimitRejection, RequestLimiter, FOREIGN_LIMITER, OWNER_LIMITER,
+};
+use grin_wallet_libwallet::listener_status::{self, HTTP_LISTENER, MWCMQS_LISTENER, TOR_LISTENER};
+use grin_wallet_libwallet::log_privacy;
 use grin_wallet_libwallet::proof::crypto;
 use grin_wallet_libwallet::proof::proofaddress;
+use grin_wallet_libwallet::secure_api;
+use grin_wallet_libwallet::slate_limits;
+use grin_wallet_libwallet::slatepack::SlatePurpose;
 use grin_wallet_util::grin_core::core::TxKernel;
 use grin_wallet_util::grin_p2p;
 use grin_wallet_util::grin_p2p::libp2p_connection;
 use grin_wallet_util::grin_util::secp::pedersen::Commitment;
+use rand::Rng;
 use std::collections::HashMap;
 use std::net::{SocketAddr, SocketAddrV4};
+use std::path::Path;
 use std::pin::Pin;
-use std::sync::mpsc::Sender;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
 use std::sync::{Arc, RwLock};
 use std::thread;
+use std::time::{Duration, Instant};
 
 lazy_static! {
 	pub static ref MWC_OWNER_BASIC_REALM: HeaderValue =
 		HeaderValue::from_str("Basic realm=MWC-OwnerAPI").unwrap();
 	static ref FOREIGN_API_RUNNING: RwLock<bool> = RwLock::new(false);
+	// Server of the running foreign listener, it is needed to stop the listener
+	static ref FOREIGN_API_SERVER: Mutex<Option<HttpServer>> = Mutex::new(None);
 	static ref OWNER_API_RUNNING: RwLock<bool> = RwLock::new(false);
+	// Stop requests of the running messenger transport listeners, by the method
+	static ref TRANSPORT_LISTENER_STOPS: Mutex<HashMap<String, Arc<AtomicBool>>> =
+		Mutex::new(HashMap::new());
 }
 
+// Retry-After value (seconds) for the listener requests while the wallet is paused
+const PAUSED_RETRY_AFTER_SEC: u64 = 60;
+/// How long the commands wait for the started mwcmqs listener to connect
+pub const MWCMQS_LISTENER_START_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long the commands wait for the started foreign listener. Tor might need some time
+/// to publish the onion service.
+pub const FOREIGN_LISTENER_START_TIMEOUT: Duration = Duration::from_secs(120);
+/// How long the listener stop waits for the requests in progress
+pub const LISTENER_STOP_TIMEOUT: Duration = Duration::from_secs(30);
+// How often the listener state is checked while waiting for it
+const LISTENER_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
 pub fn is_foreign_api_running() -> bool {
 	*FOREIGN_API_RUNNING.read().unwrap()
 }
@@ -139,6 +177,7 @@ pub fn init_tor_listener<L, C, K>(
 	libp2p_listener_port: &Option<u16>,
 	tor_base: Option<&str>,
 	tor_log_file: &Option<String>,
+	listener_address_indexes: &Option<Vec<u32>>,
 ) -> Result<(tor_process::TorProcess, SecretKey), Error>
 where
 	L: WalletLCProvider<'static, C, K> + 'static,
@@ -168,12 +207,34 @@ where
 		onion_address, addr
 	);
 
+	// Extra hidden services for the pinned address indexes, so the older addresses stay reachable
+	let mut service_keys = vec![sec_key.clone()];
+	let current_index = proofaddress::get_address_index();
+	let mut extra_indexes = listener_address_indexes.clone().unwrap_or(vec![]);
+	extra_indexes.sort();
+	extra_indexes.dedup();
+	for index in extra_indexes {
+		if index == current_index {
+			continue;
+		}
+		let key = proofaddress::payment_proof_address_secret(&k, Some(index)).map_err(|e| {
+			ErrorKind::TorConfig(format!("Unable to build key for onion address, {}", e))
+		})?;
+		let address = OnionV3Address::from_private(&key.0)
+			.map_err(|e| ErrorKind::TorConfig(format!("Unable to build onion address, {}", e)))?;
+		warn!(
+			"Starting TOR Hidden Service for address index {} at address {}, binding to {}",
+			index, address, addr
+		);
+		service_keys.push(key);
+	}
+
 	tor_config::output_tor_listener_config(
 		&tor_dir,
 		socks_listener_addr,
 		addr,
 		libp2p_listener_port,
-		&vec![sec_key.clone()],
+		&service_keys,
 		tor_log_file,
 	)
 	.map_err(|e| ErrorKind::TorConfig(format!("Failed to configure tor, {}", e).into()))?;
@@ -342,6 +403,7 @@ where
 		slate: &mut Slate,
 		dest_acct_name: Option<&str>,
 	) -> Result<(), Error> {
+		slate_limits::check_slate_limits(slate)?;
 		let owner_api = Owner::new(self.wallet.clone(), None, None);
 		let foreign_api = Foreign::new(self.wallet.clone(), None, None);
 		let mask = self.keychain_mask.lock().clone();
@@ -407,6 +469,8 @@ where
 					// other waller recipient for encrypted slatepack.
 					slatepack_recipient: None,
 					min_fee: None,
+					min_change_output: None,
+					change_split_target: None,
 				};
 
 				*slate = owner_api.process_invoice_tx((&mask).as_ref(), slate, &params)?;
@@ -430,21 +494,28 @@ where
 			}
 
 			// Send slate back
-			self.publisher
+			let res = self
+				.publisher
 				.lock()
 				.as_ref()
 				.expect("call set_publisher() method!!!")
-				.post_slate(slate, from)
-				.map_err(|e| {
+				.post_slate(slate, from);
+			match res {
+				Ok(_) => self.do_log_info(format!(
+					"slate [{}] sent back to [{}] successfully",
+					self.log_value(slate.id),
+					self.log_value(from.get_stripped())
+				)),
+				Err(e) if grin_wallet_impls::adapters::is_queued(&e) => self.do_log_warn(format!(
+					"slate [{}] for [{}] is queued, mwcmqs will retry to send it back",
+					self.log_value(slate.id),
+					self.log_value(from.get_stripped())
+				)),
+				Err(e) => {
 					self.do_log_error(format!("ERROR: Unable to send slate back, {}", e));
-					e
-				})?;
-
-			self.do_log_info(format!(
-				"slate [{}] sent back to [{}] successfully",
-				slate.id.to_string(),
-				from.get_stripped()
-			));
+					return Err(e.into());
+				}
+			}
 
 			Ok(())
 		} else {
@@ -458,7 +529,7 @@ where
 				// Report error. We are not processing any finalization transactions if nobody waiting for that
 				self.do_log_warn(format!(
 					"Get back slate {}. Because slate arrive too late, wallet not processing it",
-					slate.id
+					self.log_value(slate.id)
 				));
 			}
 
@@ -470,6 +541,7 @@ where
 		&self,
 		swapmessage: Message,
 	) -> Result<Option<Message>, Error> {
+		owner::check_wallet_not_paused()?;
 		let owner_api = Owner::new(self.wallet.clone(), None, None);
 		let mask = self.keychain_mask.lock().clone();
 
@@ -484,6 +556,15 @@ where
 		Ok(ack_msg)
 	}
 
+	// The console shows the values as they are, the log follows the log privacy policy
+	fn log_value<T: std::fmt::Display>(&self, value: T) -> String {
+		if self.print_to_log {
+			log_privacy::value(value)
+		} else {
+			value.to_string()
+		}
+	}
+
 	fn do_log_info(&self, message: String) {
 		if self.print_to_log {
 			info!("{}", message);
@@ -527,9 +608,9 @@ where
 		} else {
 			self.do_log_info(format!(
 				"slate [{}] received back from [{}] for [{}] MWCs",
-				slate.id.to_string(),
-				display_from,
-				core::amount_to_hr_string(slate.amount, false)
+				self.log_value(slate.id),
+				self.log_value(display_from),
+				self.log_value(core::amount_to_hr_string(slate.amount, false))
 			));
 		};
 
@@ -537,8 +618,11 @@ where
 
 		//send the message back
 		match result {
-			Ok(()) => {}
-			Err(e) => self.do_log_error(format!("Unable to process incoming slate, {}", e)),
+			Ok(()) => listener_status::message_processed(MWCMQS_LISTENER),
+			Err(e) => {
+				listener_status::listener_error(MWCMQS_LISTENER, format!("{}", e));
+				self.do_log_error(format!("Unable to process incoming slate, {}", e))
+			}
 		}
 	}
 
@@ -546,8 +630,12 @@ where
 		let result = self.process_incoming_swap_message(swap);
 
 		match result {
-			Ok(message) => return message,
+			Ok(message) => {
+				listener_status::message_processed(MWCMQS_LISTENER);
+				return message;
+			}
 			Err(e) => {
+				listener_status::listener_error(MWCMQS_LISTENER, format!("{}", e));
 				self.do_log_error(format!("{}", e));
 				None
 			}
@@ -586,6 +674,69 @@ where
 	}
 }
 
+/// Start the thread that reloads the wallet config on SIGHUP. Settings that can't be changed
+/// at runtime are reported into the log. Not supported on Windows.
+pub fn start_config_reload_listener<L, C, K>(
+	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	#[cfg(unix)]
+	{
+		use tokio::signal::unix::{signal, SignalKind};
+
+		thread::Builder::new()
+			.name("config-reload".to_string())
+			.spawn(move || {
+				let mut rt = match tokio::runtime::Builder::new()
+					.basic_scheduler()
+					.enable_all()
+					.build()
+				{
+					Ok(rt) => rt,
+					Err(e) => {
+						error!("Unable to start config reload listener, {}", e);
+						return;
+					}
+				};
+				rt.block_on(async {
+					let mut hangup = match signal(SignalKind::hangup()) {
+						Ok(s) => s,
+						Err(e) => {
+							error!("Unable to listen for SIGHUP, {}", e);
+							return;
+						}
+					};
+					while hangup.recv().await.is_some() {
+						info!("SIGHUP is received, reloading wallet config");
+						match grin_wallet_api::reload_config(wallet.clone()) {
+							Ok(status) => {
+								if !status.restart_required.is_empty() {
+									warn!(
+										"Wallet restart is required to apply {:?}",
+										status.restart_required
+									);
+								}
+							}
+							Err(e) => error!("Unable to reload wallet config, {}", e),
+						}
+					}
+				});
+			})
+			.map_err(|e| {
+				ErrorKind::GenericError(format!("Unable to start config reload listener, {}", e))
+			})?;
+	}
+	#[cfg(not(unix))]
+	{
+		let _ = wallet;
+	}
+	Ok(())
+}
+
 pub fn init_start_mwcmqs_listener<L, C, K>(
 	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>,
 	mqs_config: MQSConfig,
@@ -637,6 +788,12 @@ where
 	let mwcmqs_domain = mqs_config.mwcmqs_domain;
 	let mwcmqs_port = mqs_config.mwcmqs_port;
 
+	// Messages that were not delivered before are sent by the new listener
+	{
+		wallet_lock!(wallet, w);
+		grin_wallet_impls::adapters::init_mwcmqs_outbox(Path::new(&w.get_data_file_dir()))?;
+	}
+
 	let mwcmqs_secret_key =
 		controller_derive_address_key(wallet.clone(), keychain_mask.lock().as_ref())?;
 	let mwc_pub_key = crypto::public_key_from_secret_key(&mwcmqs_secret_key)?;
@@ -683,6 +840,7 @@ where
 
 	// Publishing this running MQS service
 	crate::impls::init_mwcmqs_access_data(mwcmqs_publisher.clone(), mwcmqs_subscriber.clone());
+	listener_status::listener_started(MWCMQS_LISTENER, Some(mwcmqs_address.get_full_name()));
 
 	if wait_for_thread {
 		let _ = thread.join();
@@ -691,10 +849,510 @@ where
 	Ok((mwcmqs_publisher, mwcmqs_subscriber))
 }
 
+/// Wait until the running mwcmqs listener is connected to the server. Returns false if there
+/// is no listener or it is not connected within the timeout. The messages still can be sent
+/// in this case, they are queued and delivered when the connection is established.
+pub fn wait_for_mwcmqs_listener(timeout: Duration) -> bool {
+	match grin_wallet_impls::adapters::get_mwcmqs_brocker() {
+		Some((_, subscriber)) => {
+			let connected = subscriber.wait_for_connection(timeout);
+			if !connected {
+				warn!(
+					"mwcmqs listener is not connected after {} seconds",
+					timeout.as_secs()
+				);
+			}
+			connected
+		}
+		None => false,
+	}
+}
+
+/// Start the foreign listener (http and tor if it is enabled) in the background thread and
+/// wait until it accepts the requests. Returns an error if the listener failed to start or
+/// didn't start within the timeout.
+pub fn start_foreign_listener_thread<L, C, K>(
+	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	addr: String,
+	tls_config: Option<TLSConfig>,
+	tor_config: TorConfig,
+	timeout: Duration,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	let (error_tx, error_rx) = mpsc::channel();
+	thread::Builder::new()
+		.name("wallet-http-listener".to_string())
+		.spawn(move || {
+			let res = foreign_listener(
+				wallet,
+				keychain_mask,
+				&addr,
+				tls_config,
+				tor_config.use_tor_listener,
+				&tor_config.socks_proxy_addr,
+				&None,
+				&tor_config.tor_log_file,
+				&tor_config.listener_address_indexes,
+			);
+			if let Err(e) = res {
+				error!("Error starting http listener: {}", e);
+				let _ = error_tx.send(format!("{}", e));
+			}
+		})
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to start http listener, {}", e)))?;
+
+	let start = Instant::now();
+	while !is_foreign_api_running() {
+		match error_rx.recv_timeout(LISTENER_CHECK_INTERVAL) {
+			Ok(e) => {
+				return Err(ErrorKind::GenericError(format!(
+					"Unable to start http listener, {}",
+					e
+				))
+				.into())
+			}
+			Err(mpsc::RecvTimeoutError::Timeout) => {
+				if start.elapsed() >= timeout {
+					return Err(ErrorKind::GenericError(format!(
+						"http listener is not started after {} seconds",
+						timeout.as_secs()
+					))
+					.into());
+				}
+			}
+			// listener is running or finished without error
+			Err(mpsc::RecvTimeoutError::Disconnected) => break,
+		}
+	}
+	Ok(())
+}
+
+/// Stop the listener of the transport: 'http' or 'tor' stop the foreign listener with its
+/// onion service, 'mwcmqs' stops the mwcmqs listener, the messenger transport methods stop
+/// their listeners. Waits until the requests in progress are finished.
+pub fn stop_listener(transport: &str, timeout: Duration) -> Result<(), Error> {
+	match transport {
+		HTTP_LISTENER | TOR_LISTENER => {
+			let mut server = FOREIGN_API_SERVER
+				.lock()
+				.take()
+				.ok_or(ErrorKind::GenericError(
+					"Foreign listener is not running".to_string(),
+				))?;
+			warn!("Stopping HTTP Foreign listener");
+			// The server finishes the requests in progress before the API thread exits
+			server.stop();
+			wait_for_listener(timeout, || !is_foreign_api_running())
+		}
+		MWCMQS_LISTENER => {
+			let (_, mut subscriber) = grin_wallet_impls::adapters::get_mwcmqs_brocker().ok_or(
+				ErrorKind::GenericError("mwcmqs listener is not running".to_string()),
+			)?;
+			warn!("Stopping mwcmqs listener");
+			subscriber.stop();
+			// The subscriber processes the received messages before it exits
+			wait_for_listener(timeout, || {
+				!listener_status::get_listeners_status()
+					.iter()
+					.any(|l| l.transport == MWCMQS_LISTENER && l.running)
+			})
+		}
+		method => {
+			let stop = TRANSPORT_LISTENER_STOPS.lock().get(method).cloned().ok_or(
+				ErrorKind::ArgumentError(format!(
+					"Listener '{}' is not running, supported listeners: http, tor, mwcmqs and the messenger transports",
+					method
+				)),
+			)?;
+			warn!("Stopping {} listener", method);
+			// The transport finishes the message in progress before the listener exits
+			stop.store(true, Ordering::Relaxed);
+			wait_for_listener(timeout, || {
+				!TRANSPORT_LISTENER_STOPS.lock().contains_key(method)
+			})
+		}
+	}
+}
+
+// Wait until the listener is stopped
+fn wait_for_listener<F>(timeout: Duration, stopped: F) -> Result<(), Error>
+where
+	F: Fn() -> bool,
+{
+	let start = Instant::now();
+	while !stopped() {
+		if start.elapsed() >= timeout {
+			return Err(ErrorKind::GenericError(format!(
+				"Listener is not stopped after {} seconds",
+				timeout.as_secs()
+			))
+			.into());
+		}
+		thread::sleep(LISTENER_CHECK_INTERVAL);
+	}
+	Ok(())
+}
+
+/// Control of the listeners for the Owner API. It starts the listeners with the settings of
+/// the running wallet process.
+pub struct ListenerController<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	foreign_addr: String,
+	tls_config: Option<TLSConfig>,
+	tor_config: TorConfig,
+	mqs_config: MQSConfig,
+}
+
+impl<L, C, K> ListenerController<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	/// Control of the listeners with the wallet and the listener settings
+	pub fn new(
+		wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+		keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+		foreign_addr: String,
+		tls_config: Option<TLSConfig>,
+		tor_config: TorConfig,
+		mqs_config: MQSConfig,
+	) -> Self {
+		ListenerController {
+			wallet,
+			keychain_mask,
+			foreign_addr,
+			tls_config,
+			tor_config,
+			mqs_config,
+		}
+	}
+}
+
+impl<L, C, K> listener_status::ListenerControl for ListenerController<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	fn start_listener(&self, transport: &str) -> Result<(), libwallet::Error> {
+		let res = match transport {
+			HTTP_LISTENER | TOR_LISTENER => {
+				if is_foreign_api_running() {
+					return Err(libwallet::ErrorKind::GenericError(
+						"Foreign listener is already running".to_string(),
+					)
+					.into());
+				}
+				let mut tor_config = self.tor_config.clone();
+				if transport == TOR_LISTENER {
+					tor_config.use_tor_listener = true;
+				}
+				start_foreign_listener_thread(
+					self.wallet.clone(),
+					self.keychain_mask.clone(),
+					self.foreign_addr.clone(),
+					self.tls_config.clone(),
+					tor_config,
+					FOREIGN_LISTENER_START_TIMEOUT,
+				)
+			}
+			MWCMQS_LISTENER => start_mwcmqs_listener(
+				self.wallet.clone(),
+				self.mqs_config.clone(),
+				false,
+				self.keychain_mask.clone(),
+				true,
+			)
+			.map(|_| {
+				wait_for_mwcmqs_listener(MWCMQS_LISTENER_START_TIMEOUT);
+			}),
+			method => match create_transport(method, "") {
+				Ok(Some(slate_transport)) => start_transport_listener(
+					self.wallet.clone(),
+					self.keychain_mask.clone(),
+					slate_transport,
+					false,
+				),
+				Ok(None) => Err(ErrorKind::ArgumentError(format!(
+					"Listener '{}' can't be started, supported listeners: http, tor, mwcmqs and the messenger transports",
+					method
+				))
+				.into()),
+				Err(e) => Err(e.into()),
+			},
+		};
+		res.map_err(|e| {
+			libwallet::ErrorKind::GenericError(format!(
+				"Unable to start {} listener, {}",
+				transport, e
+			))
+			.into()
+		})
+	}
+
+	fn stop_listener(&self, transport: &str) -> Result<(), libwallet::Error> {
+		stop_listener(transport, LISTENER_STOP_TIMEOUT).map_err(|e| {
+			libwallet::ErrorKind::GenericError(format!(
+				"Unable to stop {} listener, {}",
+				transport, e
+			))
+			.into()
+		})
+	}
+}
+
+/// Handler of the messenger transport listener. Incoming send slates are received by the
+/// wallet, the response slate is the reply.
+struct TransportController<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>,
+	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	// transport method for the listener status
+	method: String,
+	// store-and-forward transport, nobody waits for the response slates
+	finalize_responses: bool,
+	// set by stop_listener
+	stop: Arc<AtomicBool>,
+}
+
+impl<L, C, K> TransportController<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	fn slatepack_secret(&self) -> Result<DalekSecretKey, Error> {
+		let mask = self.keychain_mask.lock().clone();
+		wallet_lock!(self.wallet, w);
+		let keychain = w.keychain(mask.as_ref())?;
+		Ok(proofaddress::payment_proof_address_dalek_secret(
+			&keychain, None,
+		)?)
+	}
+
+	// Finalize and post the response slate of the transaction that this wallet sent
+	fn finalize_response(&self, mask: Option<&SecretKey>, slate: &Slate) -> Result<(), Error> {
+		let owner_api = Owner::new(self.wallet.clone(), None, None);
+		owner_api.verify_slate_messages(mask, slate)?;
+		let slate = owner_api.finalize_tx(mask, slate)?;
+		owner_api.post_tx(mask, &slate.tx, false)?;
+		info!(
+			"slate [{}] for [{}] MWCs is finalized and posted",
+			log_privacy::slate_id(slate.id),
+			log_privacy::amount(slate.amount)
+		);
+		Ok(())
+	}
+
+	fn process_message(&self, from: &str, message: &str) -> Result<Option<String>, Error> {
+		owner::check_wallet_not_paused()?;
+		let mask = self.keychain_mask.lock().clone();
+
+		if let Ok(swap_message) = serde_json::from_str::<Message>(message) {
+			let owner_api = Owner::new(self.wallet.clone(), None, None);
+			let ack = owner_api.swap_income_message(mask.as_ref(), message.to_string())?;
+			info!(
+				"swap message {} from [{}] is processed",
+				swap_message.id,
+				log_privacy::address(from)
+			);
+			return match ack {
+				Some(ack) => Ok(Some(ack.to_json().map_err(libwallet::Error::from)?)),
+				None => Ok(None),
+			};
+		}
+
+		let slatepack_secret = self.slatepack_secret()?;
+		let slate_pkg = match PathToSlateGetter::build_form_str(message.to_string())
+			.get_tx(&slatepack_secret)
+		{
+			Ok(slate_pkg) => slate_pkg,
+			// Not every message is a slate
+			Err(_) => return Ok(None),
+		};
+		let envelope_sender = slate_pkg.envelope_sender();
+		let (slate, sender, _recipient, content, slatepack_format) = slate_pkg.to_slate()?;
+		if slate.num_participants <= slate.participant_data.len() {
+			if self.finalize_responses {
+				self.finalize_response(mask.as_ref(), &slate)?;
+			}
+			// Otherwise it is a response slate that the sending wallet is waiting for
+			return Ok(None);
+		}
+		let is_invoice = match content {
+			SlatePurpose::InvoiceInitial => true,
+			SlatePurpose::FullSlate => slate.tx.inputs().len() == 0,
+			_ => false,
+		};
+		if is_invoice {
+			// mwc-wallet doesn't support invoices
+			return Err(ErrorKind::DoesNotAcceptInvoices.into());
+		}
+		slate_limits::check_slate_limits(&slate)?;
+
+		let foreign_api = Foreign::new(self.wallet.clone(), mask, Some(check_middleware));
+		foreign_api.verify_slate_messages(&slate)?;
+		let slate = foreign_api.receive_tx(&slate, Some(from.to_string()), None, None)?;
+		info!(
+			"slate [{}] from [{}] for [{}] MWCs is received",
+			log_privacy::slate_id(slate.id),
+			log_privacy::address(from),
+			log_privacy::amount(slate.amount)
+		);
+
+		let putter = match envelope_sender {
+			Some(envelope_sender) => PathToSlatePutter::build_envelope(None, envelope_sender),
+			None => PathToSlatePutter::build_encrypted(
+				None,
+				SlatePurpose::SendResponse,
+				DalekPublicKey::from(&slatepack_secret),
+				sender,
+				slatepack_format,
+			),
+		};
+		Ok(Some(putter.put_tx(&slate, &slatepack_secret, false)?))
+	}
+}
+
+impl<L, C, K> SlateTransportHandler for TransportController<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	fn on_message(&self, from: &str, message: &str) -> Option<String> {
+		match self.process_message(from, message) {
+			Ok(reply) => {
+				listener_status::message_processed(&self.method);
+				reply
+			}
+			Err(e) => {
+				error!(
+					"Unable to process incoming slate from {}, {}",
+					log_privacy::address(from),
+					e
+				);
+				listener_status::listener_error(&self.method, format!("{}", e));
+				None
+			}
+		}
+	}
+
+	fn is_stopped(&self) -> bool {
+		self.stop.load(Ordering::Relaxed)
+	}
+}
+
+/// Start the listener of a messenger transport
+pub fn start_transport_listener<L, C, K>(
+	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>,
+	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	transport: Box<dyn SlateTransport>,
+	wait_for_thread: bool,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	let method = transport.method().to_string();
+	if TRANSPORT_LISTENER_STOPS.lock().contains_key(&method) {
+		return Err(
+			ErrorKind::GenericError(format!("{} listener is already running", method)).into(),
+		);
+	}
+	let stop = Arc::new(AtomicBool::new(false));
+	let handler = TransportController {
+		wallet,
+		keychain_mask,
+		method: method.clone(),
+		finalize_responses: transport.is_store_and_forward(),
+		stop: stop.clone(),
+	};
+	let wallet_secret = handler.slatepack_secret()?;
+	let address = transport.address(&wallet_secret)?;
+	warn!("Starting {} listener for {}", method, address);
+	listener_status::listener_started(&method, Some(address));
+	TRANSPORT_LISTENER_STOPS.lock().insert(method.clone(), stop);
+
+	let thread_method = method.clone();
+	let thread = thread::Builder::new()
+		.name(format!("{}-listener", method))
+		.spawn(move || {
+			if let Err(e) = transport.listen(&wallet_secret, &handler) {
+				error!("Unable to start {} listener, {}", thread_method, e);
+				listener_status::listener_error(&thread_method, format!("{}", e));
+			}
+			listener_status::listener_stopped(&thread_method);
+			TRANSPORT_LISTENER_STOPS.lock().remove(&thread_method);
+		})
+		.map_err(|e| {
+			TRANSPORT_LISTENER_STOPS.lock().remove(&method);
+			listener_status::listener_stopped(&method);
+			ErrorKind::GenericError(format!("Unable to start {} listener, {}", method, e))
+		})?;
+
+	if wait_for_thread {
+		let _ = thread.join();
+	}
+	Ok(())
+}
+
+/// Start the listener of the messenger transport if it is not running.
+/// Return the address of the listener.
+pub fn ensure_transport_listener<L, C, K>(
+	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>,
+	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	method: &str,
+) -> Result<String, Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	if get_transport_listener_address(method).is_none() {
+		let transport = create_transport(method, "")?.ok_or(ErrorKind::ArgumentError(format!(
+			"'{}' is not a messenger transport",
+			method
+		)))?;
+		start_transport_listener(wallet, keychain_mask, transport, false).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to start {} listener, {}", method, e))
+		})?;
+	}
+	get_transport_listener_address(method)
+		.ok_or(ErrorKind::GenericError(format!("{} listener is not running", method)).into())
+}
+
+/// Address of the running messenger transport listener, None if it is not running
+pub fn get_transport_listener_address(method: &str) -> Option<String> {
+	listener_status::get_listeners_status()
+		.into_iter()
+		.find(|l| l.transport == method && l.running)
+		.and_then(|l| l.address)
+}
+
 /// Listener version, providing same API but listening for requests on a
 /// port and wrapping the calls
 /// Note keychain mask is only provided here in case the foreign listener is also being used
 /// in the same wallet instance
+/// Additional wallets are served by the V3 API only, the request selects them with the
+/// top level 'wallet_id' field
 pub fn owner_listener<L, C, K>(
 	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
 	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
@@ -702,7 +1360,9 @@ pub fn owner_listener<L, C, K>(
 	api_secret: Option<String>,
 	tls_config: Option<TLSConfig>,
 	owner_api_include_foreign: Option<bool>,
+	owner_api_invoice_endpoint: Option<bool>,
 	tor_config: Option<TorConfig>,
+	wallets: HashMap<String, Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>>,
 ) -> Result<(), Error>
 where
 	L: WalletLCProvider<'static, C, K> + 'static,
@@ -728,24 +1388,37 @@ where
 	//I don't know why but it seems the warn message in controller.rs will get printed to console.
 	warn!("owner listener started {}", addr);
 	let mut router = Router::new();
-	if api_secret.is_some() {
-		let api_basic_auth =
-			"Basic ".to_string() + &to_base64(&("mwc:".to_string() + &api_secret.unwrap()));
-		let basic_auth_middleware = Arc::new(BasicAuthMiddleware::new(
-			api_basic_auth,
-			&MWC_OWNER_BASIC_REALM,
-			Some("/v2/foreign".into()),
-		));
-		router.add_middleware(basic_auth_middleware);
+	router.add_middleware(Arc::new(RateLimitMiddleware::new(&OWNER_LIMITER)));
+	// Tokens are issued by 'owner_api --issue-token' and stored next to the api secret
+	let top_level_dir = {
+		let mut w_lock = wallet.lock();
+		w_lock.lc_provider()?.get_top_level_directory()?
+	};
+	let tokens = ApiTokenStore::new(&top_level_dir);
+	let invoice_endpoint = owner_api_invoice_endpoint.unwrap_or(false);
+	if invoice_endpoint && api_secret.is_none() && tokens.is_empty()? {
+		return Err(ErrorKind::ArgumentError(
+			"Invoice endpoint requires the api secret or the Owner API tokens".to_string(),
+		)
+		.into());
 	}
+	// Installed even without the secret and the tokens, the tokens issued later are checked
+	let api_basic_auth =
+		api_secret.map(|secret| "Basic ".to_string() + &to_base64(&("mwc:".to_string() + &secret)));
+	router.add_middleware(Arc::new(OwnerAuthMiddleware {
+		api_basic_auth,
+		tokens,
+		ignore_uri: Some("/v2/foreign".into()),
+	}));
 
 	let api_handler_v2 = OwnerAPIHandlerV2::new(wallet.clone(), tor_config.clone());
 	let api_handler_v3 = OwnerAPIHandlerV3::new(
 		wallet.clone(),
 		keychain_mask.clone(),
-		tor_config,
+		tor_config.clone(),
 		running_foreign,
-	);
+	)
+	.with_wallets(wallets, tor_config);
 
 	router
 		.add_route("/v2/owner", Arc::new(api_handler_v2))
@@ -759,6 +1432,17 @@ where
 			ErrorKind::GenericError(format!("Router failed to add route /v3/owner, {}", e))
 		})?;
 
+	if invoice_endpoint {
+		let store = Arc::new(InvoiceStore::new(&top_level_dir));
+		invoices::start_invoice_watcher(store.clone())?;
+		let invoice_handler = InvoiceAPIHandler::new(wallet.clone(), keychain_mask.clone(), store);
+		router
+			.add_route("/v3/invoice", Arc::new(invoice_handler))
+			.map_err(|e| {
+				ErrorKind::GenericError(format!("Router failed to add route /v3/invoice, {}", e))
+			})?;
+	}
+
 	// If so configured, add the foreign API to the same port
 	if running_foreign {
 		warn!("Starting HTTP Foreign API on Owner server at {}.", addr);
@@ -770,7 +1454,7 @@ where
 			})?;
 	}
 
-	let mut apis = ApiServer::new();
+	let mut apis = HttpServer::new();
 	warn!("Starting HTTP Owner API server at {}.", addr);
 	let socket_addr: SocketAddr = addr.parse().expect("unable to parse socket address");
 	let api_thread = apis
@@ -795,6 +1479,56 @@ where
 	res
 }
 
+/// gRPC Owner API listener, runs in its own thread next to the HTTP Owner API.
+/// It uses the same api secret and TLS certificate as the HTTP listener.
+#[cfg(feature = "grpc")]
+pub fn grpc_owner_listener<L, C, K>(
+	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+	keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	addr: &str,
+	api_secret: Option<String>,
+	tls_config: Option<TLSConfig>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	let api_basic_auth =
+		api_secret.map(|secret| "Basic ".to_string() + &to_base64(&("mwc:".to_string() + &secret)));
+	let tls = tls_config.map(|tls| (tls.certificate, tls.private_key));
+	grin_wallet_api::grpc::start_grpc_owner_server(
+		Owner::new(wallet, None, None),
+		keychain_mask,
+		addr,
+		api_basic_auth,
+		tls,
+	)
+	.map_err(|e| ErrorKind::GenericError(format!("Unable to start gRPC owner listener, {}", e)))?;
+	Ok(())
+}
+
+/// gRPC Owner API listener, the wallet is built without the gRPC support
+#[cfg(not(feature = "grpc"))]
+pub fn grpc_owner_listener<L, C, K>(
+	_wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+	_keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	_addr: &str,
+	_api_secret: Option<String>,
+	_tls_config: Option<TLSConfig>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	Err(ErrorKind::ArgumentError(
+		"gRPC Owner API is configured but the wallet is built without the 'grpc' feature"
+			.to_string(),
+	)
+	.into())
+}
+
 /// Start libp2p listener thread.
 /// stop_mutex allows to stop the thread when value will be 0
 pub fn start_libp2p_listener<L, C, K>(
@@ -935,6 +1669,7 @@ pub fn foreign_listener<L, C, K>(
 	socks_proxy_addr: &str,
 	libp2p_listen_port: &Option<u16>,
 	tor_log_file: &Option<String>,
+	listener_address_indexes: &Option<Vec<u32>>,
 ) -> Result<(), Error>
 where
 	L: WalletLCProvider<'static, C, K> + 'static,
@@ -963,20 +1698,27 @@ where
 			libp2p_listen_port,
 			None,
 			tor_log_file,
+			listener_address_indexes,
 		) {
-			Ok((tp, tor_secret)) => Some((tp, tor_secret)),
+			Ok((tp, tor_secret)) => {
+				listener_status::listener_started(TOR_LISTENER, tor::status::get_tor_address());
+				Some((tp, tor_secret))
+			}
 			Err(e) => {
 				warn!("Unable to start TOR listener; Check that TOR executable is installed and on your path");
 				warn!("Tor Error: {}", e);
 				warn!("Listener will be available via HTTP only");
+				listener_status::listener_error(TOR_LISTENER, format!("{}", e));
 				None
 			}
 		},
 		false => None,
 	};
+	let tor_started = tor_info.is_some();
 
 	let api_handler_v2 = ForeignAPIHandlerV2::new(wallet.clone(), keychain_mask);
 	let mut router = Router::new();
+	router.add_middleware(Arc::new(RateLimitMiddleware::new(&FOREIGN_LIMITER)));
 
 	router
 		.add_route("/v2/foreign", Arc::new(api_handler_v2))
@@ -984,7 +1726,7 @@ where
 			ErrorKind::GenericError(format!("Router failed to add route /v2/foreign, {}", e))
 		})?;
 
-	let mut apis = ApiServer::new();
+	let mut apis = HttpServer::new();
 	warn!("Starting HTTP Foreign listener API server at {}.", addr);
 	let socket_addr: SocketAddr = addr.parse().expect("unable to parse socket address");
 	let api_thread = apis
@@ -993,6 +1735,8 @@ where
 
 	warn!("HTTP Foreign listener started.");
 	*FOREIGN_API_RUNNING.write().unwrap() = true;
+	*FOREIGN_API_SERVER.lock() = Some(apis);
+	listener_status::listener_started(HTTP_LISTENER, Some(addr.to_string()));
 
 	// Starting libp2p listener
 	let tor_process = if tor_info.is_some() && libp2p_listen_port.is_some() {
@@ -1014,7 +1758,13 @@ where
 		.join()
 		.map_err(|e| ErrorKind::GenericError(format!("API thread panicked :{:?}", e)).into());
 
+	*FOREIGN_API_SERVER.lock() = None;
 	*FOREIGN_API_RUNNING.write().unwrap() = false;
+	listener_status::listener_stopped(HTTP_LISTENER);
+	if tor_started {
+		tor::status::set_tor_address(None);
+		listener_status::listener_stopped(TOR_LISTENER);
+	}
 
 	// Stopping tor, we failed to start in any case
 	if let Some(mut tor_process) = tor_process {
@@ -1024,6 +1774,184 @@ where
 	res
 }
 
+/// Rate limit and the bound of the concurrent requests for the listener. It goes before the
+/// other middlewares, so the requests are limited before the authorization.
+pub struct RateLimitMiddleware {
+	limiter: &'static RequestLimiter,
+}
+
+impl RateLimitMiddleware {
+	/// Middleware with the limiter of the listener
+	pub fn new(limiter: &'static RequestLimiter) -> Self {
+		RateLimitMiddleware { limiter }
+	}
+
+	// Peer address of the request, the proxy headers are used if they are trusted
+	fn client_id(req: &Request<Body>) -> String {
+		let remote_ip = req
+			.extensions()
+			.get::<RemoteAddr>()
+			.map(|RemoteAddr(addr)| addr.ip().to_string())
+			.unwrap_or_else(|| "all".to_string());
+		if !listener_limits::get_listener_limits().trust_proxy_headers {
+			return remote_ip;
+		}
+		let forwarded_for = req
+			.headers()
+			.get("x-forwarded-for")
+			.and_then(|v| v.to_str().ok())
+			.and_then(|v| v.split(',').next())
+			.map(|v| v.trim().to_string());
+		let real_ip = req
+			.headers()
+			.get("x-real-ip")
+			.and_then(|v| v.to_str().ok())
+			.map(|v| v.trim().to_string());
+		forwarded_for
+			.or(real_ip)
+			.filter(|v| !v.is_empty())
+			.unwrap_or(remote_ip)
+	}
+}
+
+impl api::Handler for RateLimitMiddleware {
+	fn call(
+		&self,
+		req: Request<Body>,
+		mut handlers: Box<dyn Iterator<Item = HandlerObj>>,
+	) -> ResponseFuture {
+		let next_handler = match handlers.next() {
+			Some(h) => h,
+			None => {
+				return Box::pin(async {
+					Ok(response(
+						StatusCode::INTERNAL_SERVER_ERROR,
+						"no handler found",
+					))
+				})
+			}
+		};
+		let retry_after = match self.limiter.start_request(&Self::client_id(&req)) {
+			Ok(guard) => {
+				let res = next_handler.call(req, handlers);
+				return Box::pin(async move {
+					let res = res.await;
+					drop(guard);
+					res
+				});
+			}
+			Err(LimitRejection::RateLimited(retry_after)) => retry_after,
+			Err(LimitRejection::TooManyRequests) => 1,
+		};
+		Box::pin(async move {
+			Ok(Response::builder()
+				.status(StatusCode::TOO_MANY_REQUESTS)
+				.header("access-control-allow-origin", "*")
+				.header(hyper::header::RETRY_AFTER, retry_after.to_string())
+				.body("Too many requests".into())
+				.unwrap())
+		})
+	}
+}
+
+/// Owner API authorization. The api secret (basic auth) gives the full access, the bearer
+/// tokens can call the methods of their scopes only. The access is passed to the handlers
+/// in the request extensions. Without the api secret and the issued tokens every request
+/// passes with the full access.
+pub struct OwnerAuthMiddleware {
+	api_basic_auth: Option<String>,
+	tokens: ApiTokenStore,
+	ignore_uri: Option<String>,
+}
+
+impl OwnerAuthMiddleware {
+	fn access(&self, req: &Request<Body>) -> Option<ApiAccess> {
+		if self.api_basic_auth.is_none() {
+			match self.tokens.is_empty() {
+				Ok(true) => return Some(ApiAccess::Full),
+				Ok(false) => (),
+				Err(e) => {
+					error!("Unable to load Owner API tokens, {}", e);
+					return None;
+				}
+			}
+		}
+		let auth = req
+			.headers()
+			.get(hyper::header::AUTHORIZATION)
+			.and_then(|v| v.to_str().ok())?;
+		if let Some(api_basic_auth) = &self.api_basic_auth {
+			if ring::constant_time::verify_slices_are_equal(
+				auth.as_bytes(),
+				api_basic_auth.as_bytes(),
+			)
+			.is_ok()
+			{
+				return Some(ApiAccess::Full);
+			}
+		}
+		if auth.starts_with("Bearer ") {
+			match self.tokens.validate(auth["Bearer ".len()..].trim()) {
+				Ok(scopes) => return scopes.map(ApiAccess::Scopes),
+				Err(e) => error!("Unable to validate Owner API token, {}", e),
+			}
+		}
+		None
+	}
+}
+
+impl api::Handler for OwnerAuthMiddleware {
+	fn call(
+		&self,
+		mut req: Request<Body>,
+		mut handlers: Box<dyn Iterator<Item = HandlerObj>>,
+	) -> ResponseFuture {
+		let next_handler = match handlers.next() {
+			Some(h) => h,
+			None => {
+				return Box::pin(async {
+					Ok(response(
+						StatusCode::INTERNAL_SERVER_ERROR,
+						"no handler found",
+					))
+				})
+			}
+		};
+		if req.method() == hyper::Method::OPTIONS {
+			return next_handler.call(req, handlers);
+		}
+		if let Some(ignore_uri) = &self.ignore_uri {
+			if req.uri().path() == ignore_uri {
+				return next_handler.call(req, handlers);
+			}
+		}
+		match self.access(&req) {
+			Some(access) => {
+				req.extensions_mut().insert(access);
+				next_handler.call(req, handlers)
+			}
+			None => Box::pin(async {
+				Ok(Response::builder()
+					.status(StatusCode::UNAUTHORIZED)
+					.header(
+						hyper::header::WWW_AUTHENTICATE,
+						MWC_OWNER_BASIC_REALM.clone(),
+					)
+					.body(Body::empty())
+					.unwrap())
+			}),
+		}
+	}
+}
+
+// Access of the request, full if the authorization is disabled
+fn request_access(req: &Request<Body>) -> ApiAccess {
+	req.extensions()
+		.get::<ApiAccess>()
+		.cloned()
+		.unwrap_or(ApiAccess::Full)
+}
+
 /// V2 API Handler/Wrapper for owner functions
 pub struct OwnerAPIHandlerV2<L, C, K>
 where
@@ -1051,7 +1979,12 @@ where
 	}
 
 	async fn call_api(req: Request<Body>, api: Owner<L, C, K>) -> Result<serde_json::Value, Error> {
+		audit_log::set_caller(Some(api_caller_info("owner_api_v2", &req)));
+		let access = request_access(&req);
 		let val: serde_json::Value = parse_body(req).await?;
+		if let Some(denied) = access.check_request(&val) {
+			return Ok(denied);
+		}
 		match <dyn OwnerRpcV2>::handle_request(&api, val) {
 			MaybeReply::Reply(r) => Ok(r),
 			MaybeReply::DontReply => {
@@ -1129,6 +2062,20 @@ where
 	/// Whether we're running the foreign API on the same port, and therefore
 	/// have to store the mask in-process
 	pub running_foreign: bool,
+
+	/// Additional wallets, selected by the 'wallet_id' field of the request
+	wallets: Arc<HashMap<String, OwnerAPIWallet<L, C, K>>>,
+}
+
+/// Owner API and ECDH shared key of the additional wallet served by OwnerAPIHandlerV3
+struct OwnerAPIWallet<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	owner_api: Arc<Owner<L, C, K>>,
+	shared_key: Arc<Mutex<Option<SecretKey>>>,
 }
 
 pub struct OwnerV3Helpers;
@@ -1146,6 +2093,14 @@ impl OwnerV3Helpers {
 		}
 	}
 
+	/// Wallet id of the request, None for the default wallet. Wallet id is removed
+	/// from the request because it is not a part of the JSON-RPC call.
+	pub fn take_wallet_id(val: &mut serde_json::Value) -> Option<String> {
+		val.as_object_mut()
+			.and_then(|o| o.remove("wallet_id"))
+			.and_then(|id| id.as_str().map(|s| s.to_string()))
+	}
+
 	/// Checks whether a request is to open the wallet
 	pub fn is_open_wallet(val: &serde_json::Value) -> bool {
 		if let Some(m) = val["method"].as_str() {
@@ -1374,17 +2329,70 @@ where
 			shared_key: Arc::new(Mutex::new(None)),
 			keychain_mask: keychain_mask,
 			running_foreign,
+			wallets: Arc::new(HashMap::new()),
 		}
 	}
 
+	/// Serve additional wallets. Every wallet has own Owner API instance, so the secure API
+	/// session and keychain mask (token) are per wallet. The runtime settings from the config
+	/// (slate limits, audit log, tx mirror, etc.) are shared by all wallets of the process.
+	/// The scheduled backups are done for the default wallet only.
+	pub fn with_wallets(
+		mut self,
+		wallets: HashMap<String, Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>>,
+		tor_config: Option<TorConfig>,
+	) -> Self {
+		let wallets = wallets
+			.into_iter()
+			.map(|(wallet_id, wallet)| {
+				let owner_api = Owner::new(wallet, None, tor_config.clone());
+				owner_api.set_tor_config(tor_config.clone());
+				owner_api.set_scheduled_backups(false);
+				let wallet = OwnerAPIWallet {
+					owner_api: Arc::new(owner_api),
+					shared_key: Arc::new(Mutex::new(None)),
+				};
+				(wallet_id, wallet)
+			})
+			.collect();
+		self.wallets = Arc::new(wallets);
+		self
+	}
+
 	async fn call_api(
 		req: Request<Body>,
 		key: Arc<Mutex<Option<SecretKey>>>,
 		mask: Arc<Mutex<Option<SecretKey>>>,
 		running_foreign: bool,
 		api: Arc<Owner<L, C, K>>,
+		wallets: Arc<HashMap<String, OwnerAPIWallet<L, C, K>>>,
 	) -> Result<serde_json::Value, Error> {
+		let mut caller = api_caller_info("owner_api_v3", &req);
+		let access = request_access(&req);
 		let mut val: serde_json::Value = parse_body(req).await?;
+		// Wallet id is not encrypted, every wallet has own shared key
+		let (key, api, running_foreign) = match OwnerV3Helpers::take_wallet_id(&mut val) {
+			None => (key, api, running_foreign),
+			Some(wallet_id) => match wallets.get(&wallet_id) {
+				// Foreign API is running for the default wallet only
+				Some(w) => {
+					// all wallets write into the same audit log
+					caller.push_str(&format!(", wallet_id: {}", wallet_id));
+					(w.shared_key.clone(), w.owner_api.clone(), false)
+				}
+				None => {
+					return Ok(serde_json::json!({
+						"jsonrpc": "2.0",
+						"id": val["id"],
+						"error": {
+							"message": format!("Unknown wallet_id '{}'", wallet_id),
+							"code": -32004
+						}
+					}))
+				}
+			},
+		};
+		audit_log::set_caller(Some(caller));
 		let mut is_init_secure_api = OwnerV3Helpers::is_init_secure_api(&val);
 		let mut was_encrypted = false;
 		let mut encrypted_req_id = JsonId::StrId(String::from(""));
@@ -1402,6 +2410,18 @@ where
 			}
 			was_encrypted = true;
 		}
+		// token scopes are checked for the decrypted request
+		if let Some(denied) = access.check_request(&val) {
+			if was_encrypted {
+				return Ok(
+					match OwnerV3Helpers::encrypt_response(key, &encrypted_req_id, &denied) {
+						Ok(v) => v,
+						Err(v) => v,
+					},
+				);
+			}
+			return Ok(denied);
+		}
 		// check again, in case it was an encrypted call to init_secure_api
 		is_init_secure_api = OwnerV3Helpers::is_init_secure_api(&val);
 		// also need to intercept open/close wallet requests
@@ -1449,11 +2469,12 @@ where
 		mask: Arc<Mutex<Option<SecretKey>>>,
 		running_foreign: bool,
 		api: Arc<Owner<L, C, K>>,
+		wallets: Arc<HashMap<String, OwnerAPIWallet<L, C, K>>>,
 	) -> Result<Response<Body>, Error> {
 		//Here is a wrapper to call future from that.
 		// Issue that we can't call future form future
 		let handler = move || -> Pin<Box<dyn std::future::Future<Output=Result<serde_json::Value, Error>>>> {
-		let future = Self::call_api(req, key, mask, running_foreign, api);
+		let future = Self::call_api(req, key, mask, running_foreign, api, wallets);
 		Box::pin(future)
 	};
 		let res = crate::executor::RunHandlerInThread::new(handler).await?;
@@ -1474,9 +2495,10 @@ where
 		let mask = self.keychain_mask.clone();
 		let running_foreign = self.running_foreign;
 		let api = self.owner_api.clone();
+		let wallets = self.wallets.clone();
 
 		Box::pin(async move {
-			match Self::handle_post_request(req, key, mask, running_foreign, api).await {
+			match Self::handle_post_request(req, key, mask, running_foreign, api, wallets).await {
 				Ok(r) => Ok(r),
 				Err(e) => {
 					error!("Request Error: {:?}", e);
@@ -1501,6 +2523,8 @@ where
 	pub wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
 	/// Keychain mask
 	pub keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	/// Listener key of the encrypted requests, it is new for every listener run
+	secure_key: Arc<SecretKey>,
 }
 
 impl<L, C, K> ForeignAPIHandlerV2<L, C, K>
@@ -1514,23 +2538,122 @@ where
 		wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
 		keychain_mask: Arc<Mutex<Option<SecretKey>>>,
 	) -> ForeignAPIHandlerV2<L, C, K> {
+		let secure_key = loop {
+			let mut secret = [0u8; 32];
+			rand::thread_rng().fill(&mut secret);
+			if let Ok(key) = SecretKey::from_slice(&secret) {
+				break key;
+			}
+		};
 		ForeignAPIHandlerV2 {
 			wallet,
 			keychain_mask,
+			secure_key: Arc::new(secure_key),
 		}
 	}
 
 	async fn call_api(
 		req: Request<Body>,
 		api: Foreign<'static, L, C, K>,
+		secure_key: Arc<SecretKey>,
 	) -> Result<serde_json::Value, Error> {
-		let val: serde_json::Value = parse_body(req).await?;
-		match <dyn ForeignRpc>::handle_request(&api, val) {
-			MaybeReply::Reply(r) => Ok(r),
+		let mut val: serde_json::Value = parse_limited_body(req).await?;
+		// The sender gets the listener key with init_secure_api and encrypts every request
+		// with the ECDH key of the listener key and its own one. Plain requests from the
+		// older wallets are served as before.
+		if OwnerV3Helpers::is_init_secure_api(&val) {
+			return Ok(Self::init_secure_api(&val, &api, &secure_key));
+		}
+		let mut encrypted = None;
+		if OwnerV3Helpers::is_encrypted_request(&val) {
+			match Self::decrypt_request(&val, &secure_key) {
+				Ok((id, shared_key, req)) => {
+					encrypted = Some((id, shared_key));
+					val = req;
+				}
+				Err(e) => return Ok(e),
+			}
+		}
+		let res = match <dyn ForeignRpc>::handle_request(&api, val) {
+			MaybeReply::Reply(r) => r,
 			MaybeReply::DontReply => {
 				// Since it's http, we need to return something. We return [] because jsonrpc
 				// clients will parse it as an empty batch response.
-				Ok(serde_json::json!([]))
+				serde_json::json!([])
+			}
+		};
+		match encrypted {
+			Some((id, shared_key)) => Ok(Self::encrypt_response(&id, &res, &shared_key)),
+			None => Ok(res),
+		}
+	}
+
+	// Reply with the public key of the listener, signed with the key of the wallet address
+	fn init_secure_api(
+		val: &serde_json::Value,
+		api: &Foreign<'static, L, C, K>,
+		secure_key: &SecretKey,
+	) -> serde_json::Value {
+		let pub_key = {
+			let secp_inst = static_secp_instance();
+			let secp = secp_inst.lock();
+			PublicKey::from_secret_key(&secp, secure_key)
+				.map_err(|e| format!("Unable to build the listener key, {}", e))
+		};
+		let listener_key = pub_key.and_then(|k| {
+			api.sign_listener_key(&k)
+				.map_err(|e| format!("Unable to sign the listener key, {}", e))
+		});
+		match listener_key.and_then(|k| serde_json::to_value(k).map_err(|e| e.to_string())) {
+			Ok(listener_key) => serde_json::json!({
+				"jsonrpc": "2.0",
+				"id": val["id"],
+				"result": { "Ok": listener_key }
+			}),
+			Err(e) => EncryptionErrorResponse::new(1, -32002, &e).as_json_value(),
+		}
+	}
+
+	// The request has the public key of the sender in 'ecdh_pubkey'
+	fn decrypt_request(
+		val: &serde_json::Value,
+		secure_key: &SecretKey,
+	) -> Result<(JsonId, SecretKey, serde_json::Value), serde_json::Value> {
+		let error =
+			|message: String| EncryptionErrorResponse::new(1, -32002, &message).as_json_value();
+		let sender_key = {
+			let secp_inst = static_secp_instance();
+			let secp = secp_inst.lock();
+			val["ecdh_pubkey"]
+				.as_str()
+				.and_then(|k| from_hex(k).ok())
+				.and_then(|k| PublicKey::from_slice(&secp, &k).ok())
+				.ok_or(error(
+					"Encrypted request doesn't have a valid ecdh_pubkey".to_string(),
+				))?
+		};
+		let shared_key = secure_api::ecdh_shared_key(&sender_key, secure_key)
+			.map_err(|e| error(format!("Unable to build the shared key, {}", e)))?;
+		let enc_req: EncryptedRequest = serde_json::from_value(val.clone())
+			.map_err(|e| error(format!("Encrypted request format error: {}", e)))?;
+		let req = enc_req
+			.decrypt(&shared_key)
+			.map_err(|e| error(format!("Decryption error: {}", e.kind())))?;
+		Ok((enc_req.id, shared_key, req))
+	}
+
+	fn encrypt_response(
+		id: &JsonId,
+		res: &serde_json::Value,
+		shared_key: &SecretKey,
+	) -> serde_json::Value {
+		match EncryptedResponse::from_json(id, res, shared_key)
+			.and_then(|enc_res| enc_res.as_json_value())
+		{
+			Ok(v) => v,
+			Err(e) => {
+				EncryptionErrorResponse::new(1, -32003, &format!("Encryption Error: {}", e.kind()))
+					.as_json_value()
 			}
 		}
 	}
@@ -1539,13 +2662,14 @@ where
 		req: Request<Body>,
 		mask: Option<SecretKey>,
 		wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+		secure_key: Arc<SecretKey>,
 	) -> Result<Response<Body>, Error> {
 		let api = Foreign::new(wallet, mask, Some(check_middleware));
 
 		//Here is a wrapper to call future from that.
 		// Issue that we can't call future form future
 		let handler = move || -> Pin<Box<dyn std::future::Future<Output=Result<serde_json::Value, Error>>>> {
-		let future = Self::call_api(req, api);
+		let future = Self::call_api(req, api, secure_key);
 		Box::pin(future)
 	};
 		let res = crate::executor::RunHandlerInThread::new(handler).await?;
@@ -1560,14 +2684,194 @@ where
 	K: Keychain + 'static,
 {
 	fn post(&self, req: Request<Body>) -> ResponseFuture {
+		if owner::is_wallet_paused() {
+			return Box::pin(async { Ok(create_paused_response()) });
+		}
 		let mask = self.keychain_mask.lock().clone();
 		let wallet = self.wallet.clone();
+		let secure_key = self.secure_key.clone();
 
 		Box::pin(async move {
-			match Self::handle_post_request(req, mask, wallet).await {
-				Ok(v) => Ok(v),
+			match Self::handle_post_request(req, mask, wallet, secure_key).await {
+				Ok(v) => {
+					listener_status::message_processed(HTTP_LISTENER);
+					Ok(v)
+				}
 				Err(e) => {
 					error!("Request Error: {:?}", e);
+					listener_status::listener_error(HTTP_LISTENER, format!("{}", e));
+					Ok(create_error_response(e))
+				}
+			}
+		})
+	}
+
+	fn options(&self, _req: Request<Body>) -> ResponseFuture {
+		Box::pin(async { Ok(create_ok_response("{}")) })
+	}
+}
+
+/// Request of the merchant invoice endpoint
+#[derive(Deserialize)]
+struct InvoiceRequest {
+	/// Invoice amount, nanoMWC
+	amount: u64,
+	/// Merchant memo, it is stored with the invoice and signed as the slate message
+	#[serde(default)]
+	memo: Option<String>,
+}
+
+/// Response of the merchant invoice endpoint
+#[derive(Serialize)]
+struct InvoiceResponse {
+	/// Invoice Id, it is the slate Id
+	id: String,
+	/// Invoice amount, nanoMWC
+	amount: u64,
+	/// Merchant memo
+	memo: Option<String>,
+	/// Invoice slate as a slatepack for the payer
+	slatepack: String,
+}
+
+/// Merchant invoice endpoint of the Owner API listener. POST issues the invoice and returns it
+/// as a slatepack, `invoice_paid` event is reported when the invoice transaction is confirmed.
+pub struct InvoiceAPIHandler<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	/// Wallet instance
+	pub wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+	/// Keychain mask
+	pub keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+	/// Issued invoices
+	pub store: Arc<InvoiceStore>,
+}
+
+impl<L, C, K> InvoiceAPIHandler<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	/// Create a new merchant invoice handler
+	pub fn new(
+		wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+		keychain_mask: Arc<Mutex<Option<SecretKey>>>,
+		store: Arc<InvoiceStore>,
+	) -> InvoiceAPIHandler<L, C, K> {
+		InvoiceAPIHandler {
+			wallet,
+			keychain_mask,
+			store,
+		}
+	}
+
+	async fn issue_invoice(
+		req: Request<Body>,
+		mask: Option<SecretKey>,
+		wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+		store: Arc<InvoiceStore>,
+	) -> Result<serde_json::Value, Error> {
+		let request: InvoiceRequest = parse_limited_body(req).await?;
+		if request.amount == 0 {
+			return Err(
+				ErrorKind::ArgumentError("Invoice amount must be positive".to_string()).into(),
+			);
+		}
+		let api = Owner::new(wallet, None, None);
+		let args = IssueInvoiceTxArgs {
+			amount: request.amount,
+			message: request.memo.clone(),
+			..Default::default()
+		};
+		let slate = api.issue_invoice_tx((&mask).as_ref(), &args)?;
+		let slatepack = match api.encrypt_slate(
+			(&mask).as_ref(),
+			&slate,
+			None,
+			SlatePurpose::InvoiceInitial,
+			None,
+			None,
+			false,
+		)? {
+			VersionedSlate::SP(slatepack) => slatepack,
+			_ => {
+				return Err(ErrorKind::GenericError(
+					"Unable to build the invoice slatepack".to_string(),
+				)
+				.into())
+			}
+		};
+		let invoice = MerchantInvoice {
+			id: slate.id.to_string(),
+			amount: slate.amount,
+			memo: request.memo,
+			created: Utc::now(),
+			paid: None,
+		};
+		store.add(invoice.clone())?;
+		info!(
+			"Merchant invoice {} is issued for {} MWC",
+			log_privacy::slate_id(&invoice.id),
+			log_privacy::amount(invoice.amount)
+		);
+		let response = InvoiceResponse {
+			id: invoice.id,
+			amount: invoice.amount,
+			memo: invoice.memo,
+			slatepack,
+		};
+		serde_json::to_value(response).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to serialize the invoice, {}", e)).into()
+		})
+	}
+
+	async fn handle_post_request(
+		req: Request<Body>,
+		mask: Option<SecretKey>,
+		wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K> + 'static>>>,
+		store: Arc<InvoiceStore>,
+	) -> Result<Response<Body>, Error> {
+		let handler = move || -> Pin<Box<dyn std::future::Future<Output=Result<serde_json::Value, Error>>>> {
+			let future = Self::issue_invoice(req, mask, wallet, store);
+			Box::pin(future)
+		};
+		let res = crate::executor::RunHandlerInThread::new(handler).await?;
+		Ok(json_response_pretty(&res))
+	}
+}
+
+impl<L, C, K> api::Handler for InvoiceAPIHandler<L, C, K>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: Keychain + 'static,
+{
+	fn post(&self, req: Request<Body>) -> ResponseFuture {
+		if owner::is_wallet_paused() {
+			return Box::pin(async { Ok(create_paused_response()) });
+		}
+		// Issuing the invoice needs the same scope as issue_invoice_tx
+		if !request_access(&req).allows("issue_invoice_tx") {
+			return Box::pin(async {
+				Ok(response(
+					StatusCode::FORBIDDEN,
+					"The token scopes don't allow to issue the invoices",
+				))
+			});
+		}
+		let mask = self.keychain_mask.lock().clone();
+		let wallet = self.wallet.clone();
+		let store = self.store.clone();
+
+		Box::pin(async move {
+			match Self::handle_post_request(req, mask, wallet, store).await {
+				Ok(v) => Ok(v),
+				Err(e) => {
+					error!("Invoice Request Error: {:?}", e);
 					Ok(create_error_response(e))
 				}
 			}
@@ -1608,6 +2912,24 @@ where
 	}
 }
 
+// Caller info for the audit log. Listener doesn't provide the peer address, so the proxy
+// headers and the user agent are reported.
+// The peer address is always recorded, the headers are set by the client or the proxy and
+// are only a hint
+fn api_caller_info(api: &str, req: &Request<Body>) -> String {
+	let remote_addr = match req.extensions().get::<RemoteAddr>() {
+		Some(RemoteAddr(addr)) => addr.to_string(),
+		None => "unknown".to_string(),
+	};
+	let mut caller = format!("{}, remote_addr: {}", api, remote_addr);
+	for name in &["x-forwarded-for", "x-real-ip", "user-agent"] {
+		if let Some(value) = req.headers().get(*name).and_then(|v| v.to_str().ok()) {
+			caller.push_str(&format!(", {}: {}", name, value));
+		}
+	}
+	caller
+}
+
 fn create_error_response(e: Error) -> Response<Body> {
 	Response::builder()
 		.status(StatusCode::INTERNAL_SERVER_ERROR)
@@ -1620,6 +2942,23 @@ fn create_error_response(e: Error) -> Response<Body> {
 		.unwrap()
 }
 
+// Response for the listener requests while the wallet is in maintenance mode
+fn create_paused_response() -> Response<Body> {
+	Response::builder()
+		.status(StatusCode::SERVICE_UNAVAILABLE)
+		.header("access-control-allow-origin", "*")
+		.header(
+			"access-control-allow-headers",
+			"Content-Type, Authorization",
+		)
+		.header(
+			hyper::header::RETRY_AFTER,
+			PAUSED_RETRY_AFTER_SEC.to_string(),
+		)
+		.body(format!("{}", libwallet::ErrorKind::WalletPaused).into())
+		.unwrap()
+}
+
 fn create_ok_response(json: &str) -> Response<Body> {
 	Response::builder()
 		.status(StatusCode::OK)
@@ -1653,6 +2992,35 @@ fn response<T: Into<Body>>(status: StatusCode, text: T) -> Response<Body> {
 	builder.body(text.into()).unwrap()
 }
 
+// Parse the body of the request from the untrusted party. Size and JSON depth are checked
+// before parsing, the body is never read beyond the size limit.
+async fn parse_limited_body<T>(req: Request<Body>) -> Result<T, Error>
+where
+	for<'de> T: Deserialize<'de> + Send + 'static,
+{
+	let content_length = req
+		.headers()
+		.get(hyper::header::CONTENT_LENGTH)
+		.and_then(|v| v.to_str().ok())
+		.and_then(|v| v.parse::<usize>().ok());
+	if let Some(len) = content_length {
+		slate_limits::check_message_size(len)?;
+	}
+
+	let mut body = req.into_body();
+	let mut data: Vec<u8> = vec![];
+	while let Some(chunk) = body.data().await {
+		let chunk =
+			chunk.map_err(|e| ErrorKind::GenericError(format!("Failed to read request, {}", e)))?;
+		slate_limits::check_message_size(data.len() + chunk.len())?;
+		data.extend_from_slice(&chunk);
+	}
+	slate_limits::check_message_limits(&data)?;
+
+	serde_json::from_slice(&data)
+		.map_err(|e| ErrorKind::GenericError(format!("Invalid request body, {}", e)).into())
+}
+
 async fn parse_body<T>(req: Request<Body>) -> Result<T, Error>
 where
 	for<'de> T: Deserialize<'de> + Send + 'static,
@@ -12,14 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::core::core::{self, amount_to_hr_string};
+use crate::core::consensus;
+use crate::core::core::amount_to_hr_string;
 use crate::core::global;
+use crate::impls::{MqsMessageKind, MqsQueuedMessage};
+use crate::libwallet::listener_status::ListenerStatus;
+use crate::libwallet::multisig::MultisigRecord;
+use crate::libwallet::price::{amount_with_value, ExchangeRate};
+use crate::libwallet::swap::bitcoin::SecondaryAddress;
 use crate::libwallet::swap::fsm::state::StateEtaInfo;
+use crate::libwallet::swap::history::SwapHistoryRecord;
 use crate::libwallet::swap::swap;
 use crate::libwallet::swap::types::{Action, Currency, Role};
 use crate::libwallet::{
-	AcctPathMapping, Error, OutputCommitMapping, OutputStatus, TxLogEntry, WalletInfo,
+	AccountSummaryInfo, AcctPathMapping, AddressMapping, Contact, Error, KernelLookupResult,
+	OutputCommitMapping, OutputStatus, TxLogEntry, WalletInfo,
 };
+use crate::locale::{self, tr, tr_fmt};
 
 use crate::util;
 use chrono::prelude::*;
@@ -28,6 +37,18 @@ use colored::*;
 use grin_wallet_libwallet::swap::swap::SwapJournalRecord;
 use grin_wallet_libwallet::swap::types::SwapTransactionsConfirmations;
 use prettytable;
+use std::collections::HashMap;
+
+// Height with the estimated time to reach it, like "1500 (~2.5 hours)"
+fn spendable_at_hr(height: u64, cur_height: u64) -> String {
+	let secs = height.saturating_sub(cur_height) * consensus::BLOCK_TIME_SEC;
+	if secs < 3600 {
+		tr_fmt("{} (~{} min)", &[&height, &((secs + 59) / 60)])
+	} else {
+		let hours = locale::number(format!("{:.1}", secs as f64 / 3600.0));
+		tr_fmt("{} (~{} hours)", &[&height, &hours])
+	}
+}
 
 /// Display outputs in a pretty way
 pub fn outputs(
@@ -40,9 +61,9 @@ pub fn outputs(
 	println!();
 	println!(
 		"{}",
-		format!(
+		tr_fmt(
 			"Wallet Outputs - Account '{}' - Block Height: {}",
-			account, cur_height
+			&[&account, &cur_height]
 		)
 		.magenta()
 	);
@@ -50,39 +71,52 @@ pub fn outputs(
 	let mut table = table!();
 
 	table.set_titles(row![
-		bMG->"Output Commitment",
-		bMG->"MMR Index",
-		bMG->"Block Height",
-		bMG->"Locked Until",
-		bMG->"Status",
-		bMG->"Coinbase?",
-		bMG->"# Confirms",
-		bMG->"Value",
-		bMG->"Tx"
+		bMG->tr("Output Commitment"),
+		bMG->tr("MMR Index"),
+		bMG->tr("Block Height"),
+		bMG->tr("Locked Until"),
+		bMG->tr("Spendable At"),
+		bMG->tr("Status"),
+		bMG->tr("Coinbase?"),
+		bMG->tr("# Confirms"),
+		bMG->tr("Value"),
+		bMG->tr("Tx"),
+		bMG->tr("Tags")
 	]);
 
 	for m in outputs {
 		let commit = format!("{}", util::to_hex(&m.commit.0));
 		let index = match m.output.mmr_index {
-			None => "None".to_owned(),
+			None => tr("None").to_owned(),
 			Some(t) => t.to_string(),
 		};
 		let height = format!("{}", m.output.height);
 		let lock_height = format!("{}", m.output.lock_height);
+		let spendable_at = match m.spendable_at_height {
+			None => "".to_owned(),
+			Some(h) => spendable_at_hr(h, cur_height),
+		};
 		let is_coinbase = format!("{}", m.output.is_coinbase);
 
 		// Mark unconfirmed coinbase outputs as "Mining" instead of "Unconfirmed"
 		let status = match m.output.status {
-			OutputStatus::Unconfirmed if m.output.is_coinbase => "Mining".to_string(),
-			_ => format!("{}", m.output.status),
+			OutputStatus::Unconfirmed if m.output.is_coinbase => tr("Mining").to_string(),
+			_ => tr(&m.output.status.to_string()).to_string(),
+		};
+		// Frozen outputs are not selected for sending
+		let status = if m.output.frozen {
+			format!("{} ({})", status, tr("Frozen"))
+		} else {
+			status
 		};
 
 		let num_confirmations = format!("{}", m.output.num_confirmations(cur_height));
-		let value = format!("{}", core::amount_to_hr_string(m.output.value, false));
+		let value = locale::amount(m.output.value, false);
 		let tx = match m.output.tx_log_entry {
 			None => "".to_owned(),
 			Some(t) => t.to_string(),
 		};
+		let tags = m.output.tags.join(",");
 
 		if dark_background_color_scheme {
 			table.add_row(row![
@@ -90,11 +124,13 @@ pub fn outputs(
 				bFB->index,
 				bFB->height,
 				bFB->lock_height,
+				bFY->spendable_at,
 				bFR->status,
 				bFY->is_coinbase,
 				bFB->num_confirmations,
 				bFG->value,
 				bFC->tx,
+				bFY->tags,
 			]);
 		} else {
 			table.add_row(row![
@@ -102,11 +138,13 @@ pub fn outputs(
 				bFB->index,
 				bFB->height,
 				bFB->lock_height,
+				bFB->spendable_at,
 				bFR->status,
 				bFD->is_coinbase,
 				bFB->num_confirmations,
 				bFG->value,
 				bFD->tx,
+				bFB->tags,
 			]);
 		}
 	}
@@ -117,9 +155,10 @@ pub fn outputs(
 
 	if !validated {
 		println!(
-			"\nWARNING: Wallet failed to verify data. \
-			 The above is from local cache and possibly invalid! \
-			 (is your `mwc server` offline or broken?)"
+			"\n{}",
+			tr("WARNING: Wallet failed to verify data. \
+				The above is from local cache and possibly invalid! \
+				(is your `mwc server` offline or broken?)")
 		);
 	}
 	Ok(())
@@ -135,13 +174,14 @@ pub fn txs(
 	dark_background_color_scheme: bool,
 	show_full_info: bool,
 	has_proof: impl Fn(&TxLogEntry) -> bool,
+	fiat_values: &HashMap<u32, String>,
 ) -> Result<(), Error> {
 	println!();
 	println!(
 		"{}",
-		format!(
+		tr_fmt(
 			"Transaction Log - Account '{}' - Block Height: {}",
-			account, cur_height
+			&[&account, &cur_height]
 		)
 		.magenta()
 	);
@@ -150,38 +190,40 @@ pub fn txs(
 
 	if show_full_info {
 		table.set_titles(row![
-			bMG->"Id",
-			bMG->"Type",
-			bMG->"Shared Transaction Id",
-			bMG->"Address",
-			bMG->"Creation Time",
-			bMG->"TTL Cutoff Height",
-			bMG->"Confirmed?",
-			bMG->"Height",
-			bMG->"Confirmation Time",
-			bMG->"Num. \nInputs",
-			bMG->"Num. \nOutputs",
-			bMG->"Amount \nCredited",
-			bMG->"Amount \nDebited",
-			bMG->"Fee",
-			bMG->"Net \nDifference",
-			bMG->"Payment \nProof",
-			bMG->"Kernel",
-			bMG->"Tx \nData",
+			bMG->tr("Id"),
+			bMG->tr("Type"),
+			bMG->tr("Shared Transaction Id"),
+			bMG->tr("Address"),
+			bMG->tr("Label"),
+			bMG->tr("Creation Time"),
+			bMG->tr("TTL Cutoff Height"),
+			bMG->tr("Confirmed?"),
+			bMG->tr("Status"),
+			bMG->tr("Height"),
+			bMG->tr("Confirmation Time"),
+			bMG->tr("Num. \nInputs"),
+			bMG->tr("Num. \nOutputs"),
+			bMG->tr("Amount \nCredited"),
+			bMG->tr("Amount \nDebited"),
+			bMG->tr("Fee"),
+			bMG->tr("Net \nDifference"),
+			bMG->tr("Payment \nProof"),
+			bMG->tr("Kernel"),
+			bMG->tr("Tx \nData"),
 		]);
 	} else {
 		// 'short' format is used by mwc 713 wallet
 		table.set_titles(row![
-			bMG->"Id",
-			bMG->"Type",
-			bMG->"TXID", // short 'Shared Transaction Id' value
-			bMG->"Address",
-			bMG->"Creation Time",
-			bMG->"Confirmed?",
-			bMG->"Height",
-			bMG->"Confirmation Time",
-			bMG->"Net \nDifference",
-			bMG->"Proof?",
+			bMG->tr("Id"),
+			bMG->tr("Type"),
+			bMG->tr("TXID"), // short 'Shared Transaction Id' value
+			bMG->tr("Address"),
+			bMG->tr("Creation Time"),
+			bMG->tr("Confirmed?"),
+			bMG->tr("Height"),
+			bMG->tr("Confirmation Time"),
+			bMG->tr("Net \nDifference"),
+			bMG->tr("Proof?"),
 		]);
 	}
 
@@ -189,7 +231,7 @@ pub fn txs(
 		let id = format!("{}", t.id);
 		let slate_id = match t.tx_slate_id {
 			Some(m) => format!("{}", m),
-			None => "None".to_owned(),
+			None => tr("None").to_owned(),
 		};
 		// mwc713 (short) representation of ID
 		let short_slate_id = match t.tx_slate_id {
@@ -201,17 +243,27 @@ pub fn txs(
 			Some(addr) => addr,
 			None => "",
 		};
-		let entry_type = format!("{}", t.tx_type);
-		let creation_ts = format!("{}", t.creation_ts.format("%Y-%m-%d %H:%M:%S"));
+		let label = match &t.label {
+			Some(label) => label,
+			None => "",
+		};
+		let entry_type = tr(&t.tx_type.to_string()).to_string();
+		let entry_type = if t.recovered {
+			format!("{}\n({})", entry_type, tr("recovered"))
+		} else {
+			entry_type
+		};
+		let creation_ts = locale::datetime(&t.creation_ts);
 		let ttl_cutoff_height = match t.ttl_cutoff_height {
 			Some(b) => format!("{}", b),
-			None => "None".to_owned(),
+			None => tr("None").to_owned(),
 		};
 		let confirmation_ts = match t.confirmation_ts {
-			Some(m) => format!("{}", m.format("%Y-%m-%d %H:%M:%S")),
-			None => "None".to_owned(),
+			Some(m) => locale::datetime(&m),
+			None => tr("None").to_owned(),
 		};
 		let confirmed = format!("{}", t.confirmed);
+		let status = tr(&t.current_status().to_string()).to_string();
 		let height = if t.confirmed && t.output_height > 0 {
 			format!("{}", t.output_height)
 		} else {
@@ -219,32 +271,35 @@ pub fn txs(
 		};
 		let num_inputs = format!("{}", t.num_inputs);
 		let num_outputs = format!("{}", t.num_outputs);
-		let amount_debited_str = core::amount_to_hr_string(t.amount_debited, true);
-		let amount_credited_str = core::amount_to_hr_string(t.amount_credited, true);
+		let amount_debited_str = locale::amount(t.amount_debited, true);
+		let amount_credited_str = locale::amount(t.amount_credited, true);
 		let fee = match t.fee {
-			Some(f) => format!("{}", core::amount_to_hr_string(f, true)),
-			None => "None".to_owned(),
+			Some(f) => locale::amount(f, true),
+			None => tr("None").to_owned(),
 		};
-		let net_diff = if t.amount_credited >= t.amount_debited {
-			core::amount_to_hr_string(t.amount_credited - t.amount_debited, true)
+		let mut net_diff = if t.amount_credited >= t.amount_debited {
+			locale::amount(t.amount_credited - t.amount_debited, true)
 		} else {
 			format!(
 				"-{}",
-				core::amount_to_hr_string(t.amount_debited - t.amount_credited, true)
+				locale::amount(t.amount_debited - t.amount_credited, true)
 			)
 		};
+		if let Some(value) = fiat_values.get(&t.id) {
+			net_diff = format!("{}\n{}", net_diff, value);
+		}
 		let tx_data = match t.stored_tx {
-			Some(_) => "Yes".to_owned(),
-			None => "None".to_owned(),
+			Some(_) => tr("Yes").to_owned(),
+			None => tr("None").to_owned(),
 		};
 		let kernel_excess = match t.kernel_excess {
 			Some(e) => util::to_hex(&e.0),
-			None => "None".to_owned(),
+			None => tr("None").to_owned(),
 		};
 		let payment_proof = if has_proof(t) {
-			"Yes".to_owned()
+			tr("Yes").to_owned()
 		} else {
-			"None".to_owned()
+			tr("None").to_owned()
 		};
 
 		if show_full_info {
@@ -254,9 +309,11 @@ pub fn txs(
 					bFC->entry_type,
 					bFC->slate_id,
 					bFC->address,
+					bFY->label,
 					bFB->creation_ts,
 					bFB->ttl_cutoff_height,
 					bFC->confirmed,
+					bFC->status,
 					bFC->height,
 					bFB->confirmation_ts,
 					bFC->num_inputs,
@@ -276,8 +333,10 @@ pub fn txs(
 						bFb->entry_type,
 						bFD->slate_id,
 						bFD->address,
+						bFD->label,
 						bFB->creation_ts,
 						bFg->confirmed,
+						bFg->status,
 						bFg->height,
 						bFB->confirmation_ts,
 						bFD->num_inputs,
@@ -296,8 +355,10 @@ pub fn txs(
 						bFb->entry_type,
 						bFD->slate_id,
 						bFD->address,
+						bFD->label,
 						bFB->creation_ts,
 						bFR->confirmed,
+						bFR->status,
 						bFR->height,
 						bFB->confirmation_ts,
 						bFD->num_inputs,
@@ -335,9 +396,10 @@ pub fn txs(
 
 	if !validated && include_status {
 		println!(
-			"\nWARNING: Wallet failed to verify data. \
-			 The above is from local cache and possibly invalid! \
-			 (is your `mwc server` offline or broken?)"
+			"\n{}",
+			tr("WARNING: Wallet failed to verify data. \
+				The above is from local cache and possibly invalid! \
+				(is your `mwc server` offline or broken?)")
 		);
 	}
 	Ok(())
@@ -348,84 +410,216 @@ pub fn info(
 	wallet_info: &WalletInfo,
 	validated: bool,
 	dark_background_color_scheme: bool,
+	rate: Option<&ExchangeRate>,
 ) {
 	println!(
-		"\n____ Wallet Summary Info - Account '{}' as of height {} ____\n",
-		account, wallet_info.last_confirmed_height,
+		"\n____ {} ____\n",
+		tr_fmt(
+			"Wallet Summary Info - Account '{}' as of height {}",
+			&[&account, &wallet_info.last_confirmed_height]
+		)
 	);
 
 	let mut table = table!();
 
 	if dark_background_color_scheme {
 		table.add_row(row![
-			bFG->"Confirmed Total",
-			FG->amount_to_hr_string(wallet_info.total, false)
+			bFG->tr("Confirmed Total"),
+			FG->locale::number(amount_with_value(wallet_info.total, rate))
 		]);
 		// Only dispay "Immature Coinbase" if we have related outputs in the wallet.
 		// This row just introduces confusion if the wallet does not receive coinbase rewards.
 		if wallet_info.amount_immature > 0 {
 			table.add_row(row![
-				bFY->format!("Immature Coinbase (< {})", global::coinbase_maturity()),
-				FY->amount_to_hr_string(wallet_info.amount_immature, false)
+				bFY->tr_fmt("Immature Coinbase (< {})", &[&global::coinbase_maturity()]),
+				FY->locale::number(amount_with_value(wallet_info.amount_immature, rate))
 			]);
 		}
 		table.add_row(row![
-			bFY->format!("Awaiting Confirmation (< {})", wallet_info.minimum_confirmations),
-			FY->amount_to_hr_string(wallet_info.amount_awaiting_confirmation, false)
+			bFY->tr_fmt("Awaiting Confirmation (< {})", &[&wallet_info.minimum_confirmations]),
+			FY->locale::number(amount_with_value(wallet_info.amount_awaiting_confirmation, rate))
 		]);
 		table.add_row(row![
-			bFB->format!("Awaiting Finalization"),
-			FB->amount_to_hr_string(wallet_info.amount_awaiting_finalization, false)
+			bFB->tr("Awaiting Finalization"),
+			FB->locale::number(amount_with_value(wallet_info.amount_awaiting_finalization, rate))
 		]);
 		table.add_row(row![
-			Fr->"Locked by previous transaction",
-			Fr->amount_to_hr_string(wallet_info.amount_locked, false)
+			Fr->tr("Locked by previous transaction"),
+			Fr->locale::number(amount_with_value(wallet_info.amount_locked, rate))
 		]);
+		// Only display if the wallet is selling in the active swap trades
+		if let Some(committed) = wallet_info.amount_committed_to_swaps {
+			table.add_row(row![
+				Fm->tr("Committed to swaps"),
+				Fm->locale::number(amount_with_value(committed, rate))
+			]);
+		}
 		table.add_row(row![
 			Fw->"--------------------------------",
 			Fw->"-------------"
 		]);
 		table.add_row(row![
-			bFG->"Currently Spendable",
-			FG->amount_to_hr_string(wallet_info.amount_currently_spendable, false)
+			bFG->tr("Currently Spendable"),
+			FG->locale::number(amount_with_value(wallet_info.amount_currently_spendable, rate))
 		]);
 	} else {
 		table.add_row(row![
-			bFG->"Total",
-			FG->amount_to_hr_string(wallet_info.total, false)
+			bFG->tr("Total"),
+			FG->locale::number(amount_with_value(wallet_info.total, rate))
 		]);
 		// Only dispay "Immature Coinbase" if we have related outputs in the wallet.
 		// This row just introduces confusion if the wallet does not receive coinbase rewards.
 		if wallet_info.amount_immature > 0 {
 			table.add_row(row![
-				bFB->format!("Immature Coinbase (< {})", global::coinbase_maturity()),
-				FB->amount_to_hr_string(wallet_info.amount_immature, false)
+				bFB->tr_fmt("Immature Coinbase (< {})", &[&global::coinbase_maturity()]),
+				FB->locale::number(amount_with_value(wallet_info.amount_immature, rate))
 			]);
 		}
 		table.add_row(row![
-			bFB->format!("Awaiting Confirmation (< {})", wallet_info.minimum_confirmations),
-			FB->amount_to_hr_string(wallet_info.amount_awaiting_confirmation, false)
+			bFB->tr_fmt("Awaiting Confirmation (< {})", &[&wallet_info.minimum_confirmations]),
+			FB->locale::number(amount_with_value(wallet_info.amount_awaiting_confirmation, rate))
 		]);
 		table.add_row(row![
-			Fr->"Locked by previous transaction",
-			Fr->amount_to_hr_string(wallet_info.amount_locked, false)
+			Fr->tr("Locked by previous transaction"),
+			Fr->locale::number(amount_with_value(wallet_info.amount_locked, rate))
 		]);
+		if let Some(committed) = wallet_info.amount_committed_to_swaps {
+			table.add_row(row![
+				Fm->tr("Committed to swaps"),
+				Fm->locale::number(amount_with_value(committed, rate))
+			]);
+		}
 		table.add_row(row![
 			Fw->"--------------------------------",
 			Fw->"-------------"
 		]);
 		table.add_row(row![
-			bFG->"Currently Spendable",
-			FG->amount_to_hr_string(wallet_info.amount_currently_spendable, false)
+			bFG->tr("Currently Spendable"),
+			FG->locale::number(amount_with_value(wallet_info.amount_currently_spendable, rate))
 		]);
 	};
 	table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
 	table.printstd();
 	println!();
+	if let Some(rate) = rate {
+		println!(
+			"{}",
+			tr_fmt(
+				"Exchange rate: 1 MWC = {}",
+				&[&rate.value_hr(1_000_000_000)]
+			)
+		);
+		println!();
+	}
+	if let Some(height) = wallet_info.immature_spendable_at_height {
+		println!(
+			"{}",
+			tr_fmt(
+				"Next immature coinbase is spendable at height {}",
+				&[&spendable_at_hr(height, wallet_info.last_confirmed_height)]
+			)
+		);
+		println!();
+	}
+	if !validated {
+		println!(
+			"\n{}",
+			tr(
+				"WARNING: Wallet failed to verify data against a live chain. \
+				The above is from local cache and only valid up to the given height! \
+				(is your `mwc server` offline or broken?)"
+			)
+		);
+	}
+}
+
+/// Display summary info of every account and the wallet total
+pub fn accounts_info(
+	accounts: &Vec<AccountSummaryInfo>,
+	validated: bool,
+	dark_background_color_scheme: bool,
+) {
+	let height = accounts
+		.iter()
+		.map(|a| a.info.last_confirmed_height)
+		.max()
+		.unwrap_or(0);
+	println!(
+		"\n____ Wallet Summary Info - All Accounts as of height {} ____\n",
+		height,
+	);
+
+	let mut table = table!();
+	table.set_titles(row![
+		bMG->"Account",
+		bMG->"Total",
+		bMG->"Immature Coinbase",
+		bMG->"Awaiting Confirmation",
+		bMG->"Awaiting Finalization",
+		bMG->"Locked",
+		bMG->"Currently Spendable",
+	]);
+
+	// total, immature, awaiting confirmation, awaiting finalization, locked, spendable
+	let mut totals = [0u64; 6];
+	for a in accounts {
+		let amounts = [
+			a.info.total,
+			a.info.amount_immature,
+			a.info.amount_awaiting_confirmation,
+			a.info.amount_awaiting_finalization,
+			a.info.amount_locked,
+			a.info.amount_currently_spendable,
+		];
+		for (t, v) in totals.iter_mut().zip(amounts.iter()) {
+			*t += v;
+		}
+		let amounts: Vec<String> = amounts
+			.iter()
+			.map(|v| amount_to_hr_string(*v, false))
+			.collect();
+		if dark_background_color_scheme {
+			table.add_row(row![
+				bFC->a.label,
+				FG->amounts[0],
+				FY->amounts[1],
+				FY->amounts[2],
+				FB->amounts[3],
+				Fr->amounts[4],
+				bFG->amounts[5],
+			]);
+		} else {
+			table.add_row(row![
+				bFD->a.label,
+				FD->amounts[0],
+				FB->amounts[1],
+				FB->amounts[2],
+				FB->amounts[3],
+				Fr->amounts[4],
+				bFG->amounts[5],
+			]);
+		}
+	}
+	let totals: Vec<String> = totals
+		.iter()
+		.map(|v| amount_to_hr_string(*v, false))
+		.collect();
+	table.add_row(row![
+		b->"Wallet Total",
+		b->totals[0],
+		b->totals[1],
+		b->totals[2],
+		b->totals[3],
+		b->totals[4],
+		bFG->totals[5],
+	]);
+	table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+	table.printstd();
+	println!();
 	if !validated {
 		println!(
 			"\nWARNING: Wallet failed to verify data against a live chain. \
-			 The above is from local cache and only valid up to the given height! \
+			 The above is from local cache and only valid up to the given height! \
 			 (is your `mwc server` offline or broken?)"
 		);
 	}
@@ -473,6 +667,68 @@ pub fn estimate(
 	println!();
 }
 
+/// Display consolidated report for split payment
+pub fn split_pay_report(
+	amount: u64,
+	report: &Vec<(
+		String,             // destination
+		u64,                // weight
+		u64,                // amount
+		Result<(), String>, // send result
+	)>,
+	dark_background_color_scheme: bool,
+) {
+	println!(
+		"\n____ Split payment of {} between {} destinations ____\n",
+		amount_to_hr_string(amount, false),
+		report.len(),
+	);
+
+	let mut table = table!();
+
+	table.set_titles(row![
+		bMG->"Destination",
+		bMG->"Weight",
+		bMG->"Amount",
+		bMG->"Status",
+	]);
+
+	let mut sent_amount = 0;
+	for (dest, weight, share, result) in report {
+		let status = match result {
+			Ok(_) => {
+				sent_amount += share;
+				"Sent".to_string()
+			}
+			Err(e) => format!("Failed: {}", e),
+		};
+		if dark_background_color_scheme {
+			table.add_row(row![
+				bFC->dest,
+				FB->weight,
+				FG->amount_to_hr_string(*share, false),
+				bFY->status,
+			]);
+		} else {
+			table.add_row(row![
+				bFD->dest,
+				FB->weight,
+				FG->amount_to_hr_string(*share, false),
+				bFD->status,
+			]);
+		}
+	}
+	table.set_format(*prettytable::format::consts::FORMAT_NO_COLSEP);
+	table.printstd();
+	println!();
+	println!(
+		"Sent {} of {}",
+		amount_to_hr_string(sent_amount, false),
+		amount_to_hr_string(amount, false)
+	);
+	println!();
+}
+
 /// Display list of wallet accounts in a pretty way
 pub fn accounts(acct_mappings: Vec<AcctPathMapping>) {
 	println!("\n____ Wallet Accounts ____\n",);
@@ -493,6 +749,220 @@ pub fn accounts(acct_mappings: Vec<AcctPathMapping>) {
 	println!();
 }
 
+/// Display list of address book contacts
+pub fn contacts(contacts: Vec<Contact>) {
+	println!("\n____ Address Book ____\n",);
+	if contacts.is_empty() {
+		println!("No contacts found");
+		println!();
+		return;
+	}
+	let mut table = table!();
+
+	table.set_titles(row![
+		mMG->"Name",
+		bMG->"Address",
+	]);
+	for c in contacts {
+		table.add_row(row![
+			bFC->format!("@{}", c.name),
+			bGC->c.address,
+		]);
+	}
+	table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+	table.printstd();
+	println!();
+}
+
+/// Display the mappings of the address indexes
+pub fn address_mappings(mappings: Vec<AddressMapping>) {
+	println!("\n____ Address Mappings ____\n",);
+	if mappings.is_empty() {
+		println!("No address mappings found");
+		println!();
+		return;
+	}
+	let mut table = table!();
+
+	table.set_titles(row![
+		mMG->"Index",
+		bMG->"Account",
+		bMG->"Label",
+	]);
+	for m in mappings {
+		table.add_row(row![
+			bFC->m.index,
+			bGC->m.account.unwrap_or_default(),
+			bFY->m.label.unwrap_or_default(),
+		]);
+	}
+	table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+	table.printstd();
+	println!();
+}
+
+/// Display the multisig sessions of the wallet
+pub fn multisig_sessions(records: Vec<MultisigRecord>) {
+	println!("\n____ Multisig Sessions ____\n",);
+	if records.is_empty() {
+		println!("No multisig sessions found");
+		println!();
+		return;
+	}
+	let mut table = table!();
+
+	table.set_titles(row![
+		mMG->"Id",
+		bMG->"Scheme",
+		bMG->"Amount",
+		bMG->"Participant",
+		bMG->"Status",
+		bMG->"Waiting For",
+		bMG->"Updated",
+	]);
+	for r in records {
+		let waiting_for = r
+			.session
+			.waiting_for()
+			.iter()
+			.map(|id| id.to_string())
+			.collect::<Vec<String>>()
+			.join(", ");
+		table.add_row(row![
+			bFC->r.session.id,
+			bFC->r.session.scheme,
+			bFB->amount_to_hr_string(r.session.amount, true),
+			bFB->r.participant_id,
+			bGC->r.session.status,
+			bFB->waiting_for,
+			bFB->r.updated.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+		]);
+	}
+	table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+	table.printstd();
+	println!();
+}
+
+/// Display the MWCMQS messages that are waiting for delivery
+pub fn mwcmqs_outbox(messages: Vec<MqsQueuedMessage>) {
+	println!("\n____ MWCMQS Outbound Queue ____\n",);
+	if messages.is_empty() {
+		println!("No queued messages");
+		println!();
+		return;
+	}
+	let mut table = table!();
+
+	table.set_titles(row![
+		mMG->"Id",
+		bMG->"Kind",
+		bMG->"To",
+		bMG->"Attempts",
+		bMG->"Next Attempt",
+		bMG->"Expires",
+		bMG->"Last Error",
+	]);
+	for m in messages {
+		let kind = match m.kind {
+			MqsMessageKind::Slate => "Slate",
+			MqsMessageKind::SwapMessage => "Swap Message",
+		};
+		table.add_row(row![
+			bFC->m.id,
+			bFC->kind,
+			bGC->m.to,
+			bFB->m.attempts,
+			bFB->m.next_attempt.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+			bFB->m.expires.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+			bFR->m.last_error,
+		]);
+	}
+	table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+	table.printstd();
+	println!();
+}
+
+/// Display state of the wallet listeners
+pub fn listeners_status(listeners: Vec<ListenerStatus>) {
+	println!("\n____ Listeners ____\n",);
+	let mut table = table!();
+
+	table.set_titles(row![
+		mMG->"Transport",
+		bMG->"Status",
+		bMG->"Address",
+		bMG->"Started",
+		bMG->"Last Message",
+		bMG->"Messages",
+		bMG->"Errors",
+		bMG->"Last Error",
+	]);
+	for l in listeners {
+		let status = match (l.running, l.connected) {
+			(false, _) => "Stopped",
+			(true, Some(false)) => "Not connected",
+			(true, _) => "Running",
+		};
+		let time = |t: Option<DateTime<Utc>>| match t {
+			Some(t) => t
+				.with_timezone(&Local)
+				.format("%Y-%m-%d %H:%M:%S")
+				.to_string(),
+			None => "None".to_string(),
+		};
+		table.add_row(row![
+			bFC->l.transport,
+			bFY->status,
+			bGC->l.address.unwrap_or("None".to_string()),
+			bFB->time(l.started),
+			bFB->time(l.last_message),
+			bFB->l.messages,
+			bFB->l.errors,
+			bFR->l.last_error.unwrap_or("".to_string()),
+		]);
+	}
+	table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+	table.printstd();
+	println!();
+}
+
+/// Display onion addresses published by the wallet listener
+pub fn tor_addresses(current_index: u32, addresses: Vec<(u32, String)>, stored: Vec<String>) {
+	println!("\n____ Onion Addresses ____\n",);
+	let mut table = table!();
+
+	table.set_titles(row![
+		mMG->"Index",
+		bMG->"Onion Address",
+		bMG->"Status",
+	]);
+	for (index, address) in &addresses {
+		let status = if *index == current_index {
+			"Current"
+		} else {
+			"Pinned"
+		};
+		table.add_row(row![
+			bFC->index,
+			bGC->format!("{}.onion", address),
+			bFY->status,
+		]);
+	}
+	for address in stored
+		.iter()
+		.filter(|a| !addresses.iter().any(|(_, addr)| addr == *a))
+	{
+		table.add_row(row![
+			bFD->"-",
+			bGC->format!("{}.onion", address),
+			bFD->"Not published",
+		]);
+	}
+	table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+	table.printstd();
+	println!();
+}
+
 /// Display transaction log messages
 pub fn tx_messages(tx: &TxLogEntry, dark_background_color_scheme: bool) -> Result<(), Error> {
 	println!();
@@ -558,6 +1028,68 @@ pub fn tx_messages(tx: &TxLogEntry, dark_background_color_scheme: bool) -> Resul
 	Ok(())
 }
 
+/// Display the local memo of the transaction
+pub fn tx_memo(tx: &TxLogEntry) {
+	println!();
+	println!(
+		"{}",
+		format!("Transaction Memo - Transaction '{}'", tx.id,).magenta()
+	);
+	println!();
+	match &tx.memo {
+		Some(memo) => println!("{}", memo),
+		None => println!("None"),
+	}
+	println!();
+}
+
+/// Display the kernel excess of the transaction, it is used to look up the transaction on the chain
+pub fn tx_kernel(tx: &TxLogEntry) {
+	println!();
+	println!(
+		"{}",
+		format!("Transaction Kernel - Transaction '{}'", tx.id).magenta()
+	);
+	println!();
+	match &tx.kernel_excess {
+		Some(excess) => println!("Excess: {}", util::to_hex(&excess.0)),
+		None => println!("None"),
+	}
+	println!();
+}
+
+/// Display the result of the kernel lookup on the node
+pub fn kernel_lookup(res: &KernelLookupResult) {
+	println!();
+	println!(
+		"{}",
+		format!("Kernel Lookup - Block Height: {}", res.tip_height).magenta()
+	);
+	println!();
+	println!("Excess: {}", res.excess);
+	match res.height {
+		Some(height) if res.on_chain => {
+			println!(
+				"{}",
+				format!(
+					"Kernel is on the chain at height {}, {} confirmations",
+					height, res.confirmations
+				)
+				.green()
+			);
+			if let Some(mmr_index) = res.mmr_index {
+				println!("MMR index: {}", mmr_index);
+			}
+		}
+		_ => println!("{}", "Kernel is not found on the chain".red()),
+	}
+	match res.tx_id {
+		Some(id) => println!("Wallet transaction: {}", id),
+		None => println!("Wallet transaction: None"),
+	}
+	println!();
+}
+
 /// Display individual Payment Proof
 pub fn payment_proof(tx: &TxLogEntry) -> Result<(), Error> {
 	println!();
@@ -633,6 +1165,94 @@ pub fn swap_trades(trades: Vec<(String, String)>) {
 	println!();
 }
 
+/// Display history of the finished swap trades and the totals by the secondary currency
+pub fn swap_history(records: &Vec<SwapHistoryRecord>) {
+	println!("\n____ Swap trades history ____\n",);
+	let mut table = table!();
+
+	table.set_titles(row![
+		mMG->"Finished",
+		bMG->"Swap ID",
+		bMG->"Role",
+		bMG->"Result",
+		bMG->"MWC",
+		bMG->"Secondary",
+		bMG->"Rate",
+		bMG->"MWC Fee",
+		bMG->"MWC Change",
+		bMG->"Secondary Change",
+	]);
+	let signed = |amount: i64, to_hr: &dyn Fn(u64) -> String| {
+		if amount < 0 {
+			format!("-{}", to_hr(amount.abs() as u64))
+		} else {
+			to_hr(amount as u64)
+		}
+	};
+	// Currency: (MWC change, secondary change)
+	let mut totals: Vec<(Currency, i64, i64)> = vec![];
+	for r in records {
+		let currency = r.secondary_currency;
+		match totals.iter_mut().find(|t| t.0 == currency) {
+			Some(t) => {
+				t.1 += r.mwc_change;
+				t.2 += r.secondary_change;
+			}
+			None => totals.push((currency, r.mwc_change, r.secondary_change)),
+		}
+		table.add_row(row![
+			bFB->r.finished.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+			bFC->r.swap_id,
+			bFD->if r.is_seller { "Seller" } else { "Buyer" },
+			bGC->r.state,
+			bFD->amount_to_hr_string(r.mwc_amount, true),
+			bFD->format!("{} {}", currency.amount_to_hr_string(r.secondary_amount, true), currency),
+			bFD->format!("{:.8}", r.exchange_rate),
+			bFD->amount_to_hr_string(r.mwc_fee, true),
+			bFY->signed(r.mwc_change, &|a| amount_to_hr_string(a, true)),
+			bFY->signed(r.secondary_change, &|a| currency.amount_to_hr_string(a, true)),
+		]);
+	}
+	table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+	table.printstd();
+	println!();
+	for (currency, mwc_change, secondary_change) in totals {
+		println!(
+			"Total MWC/{}: {} MWC, {} {}",
+			currency,
+			signed(mwc_change, &|a| amount_to_hr_string(a, true)),
+			signed(secondary_change, &|a| currency.amount_to_hr_string(a, true)),
+			currency
+		);
+	}
+	println!("Secondary currency network fees are not included");
+	println!();
+}
+
+/// Display the secondary currency addresses of the wallet
+pub fn secondary_addresses(addresses: &Vec<SecondaryAddress>) {
+	println!("\n____ Secondary currency addresses of the wallet ____\n",);
+	let mut table = table!();
+
+	table.set_titles(row![
+		mMG->"Currency",
+		bMG->"Index",
+		bMG->"Address",
+		bMG->"Created",
+	]);
+	for a in addresses {
+		table.add_row(row![
+			bFC->a.currency,
+			bFD->a.index,
+			bFY->a.address,
+			bFB->a.created.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+		]);
+	}
+	table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+	table.printstd();
+	println!();
+}
+
 /// Display list of wallet accounts in a pretty way
 pub fn swap_trade(
 	swap: &swap::Swap,
@@ -694,6 +1314,15 @@ pub fn swap_trade(
 	};
 	println!("    Locking order: {}", lock_str.bold().yellow());
 
+	if let Some(expires_at) = &swap.offer_expires_at {
+		println!(
+			"    Offer expires at: {}",
+			timestamp_to_local_time(expires_at.timestamp())
+				.bold()
+				.yellow()
+		);
+	}
+
 	if tx_conf.mwc_tip < swap.refund_slate.lock_height {
 		let mwc_lock_sec = (swap.refund_slate.lock_height - tx_conf.mwc_tip) * 60;
 		let sel_lock_h = mwc_lock_sec / 3600;
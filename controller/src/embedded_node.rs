@@ -0,0 +1,256 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Embedded mwc node. The wallet runs a pruned node in its own process, so the wallets that
+//! wrap this crate don't need to bundle and supervise a separate node process.
+//! The node is available with the `embedded_node` feature.
+
+use crate::config::{WalletConfig, GRIN_WALLET_DIR};
+use crate::core::global::{self, ChainTypes};
+use crate::{Error, ErrorKind};
+use std::path::PathBuf;
+
+/// Directory of the node chain data in the wallet top level directory
+const EMBEDDED_NODE_DIR: &str = "node";
+
+/// API secret file of the embedded node in the node directory
+#[cfg(feature = "embedded_node")]
+const EMBEDDED_NODE_API_SECRET: &str = ".api_secret";
+
+/// Interval of the sync status check
+#[cfg(feature = "embedded_node")]
+const SYNC_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// The node API thread keeps its channel after the node is stopped, so the channel is
+/// allocated once and the node can be started once per process
+#[cfg(feature = "embedded_node")]
+static EMBEDDED_NODE_STARTED: std::sync::atomic::AtomicBool =
+	std::sync::atomic::AtomicBool::new(false);
+
+/// Running embedded node, returned by `start_embedded_node`. The node stops with `stop` or
+/// when the handle is dropped.
+pub struct EmbeddedNode {
+	#[cfg(feature = "embedded_node")]
+	server: Option<grin_servers::Server>,
+	api_url: String,
+	api_secret_path: String,
+}
+
+impl EmbeddedNode {
+	/// API url of the node, the node client of the wallet connects to it
+	pub fn api_url(&self) -> &str {
+		&self.api_url
+	}
+
+	/// Path of the node API secret file, the node client of the wallet needs that secret
+	pub fn api_secret_path(&self) -> &str {
+		&self.api_secret_path
+	}
+
+	/// True if the node is done with the chain sync
+	pub fn is_synced(&self) -> bool {
+		#[cfg(feature = "embedded_node")]
+		{
+			if let Some(server) = &self.server {
+				return !server.sync_state.is_syncing();
+			}
+		}
+		false
+	}
+
+	/// Wait until the node is done with the chain sync. The wallet doesn't update from the
+	/// node before that, the unsynced node reports an old height and misses the outputs.
+	pub fn wait_for_sync(&self) {
+		#[cfg(feature = "embedded_node")]
+		{
+			let mut reported = None;
+			while let Some(server) = &self.server {
+				if !server.sync_state.is_syncing() {
+					break;
+				}
+				let status = format!("{:?}", server.sync_state.status());
+				if reported.as_ref() != Some(&status) {
+					info!("Embedded node is syncing, {}", status);
+					reported = Some(status);
+				}
+				std::thread::sleep(SYNC_CHECK_INTERVAL);
+			}
+		}
+	}
+
+	/// Stop the node
+	pub fn stop(mut self) {
+		self.stop_server();
+	}
+
+	fn stop_server(&mut self) {
+		#[cfg(feature = "embedded_node")]
+		{
+			if let Some(server) = self.server.take() {
+				info!("Stopping the embedded node");
+				server.stop();
+			}
+		}
+	}
+}
+
+impl Drop for EmbeddedNode {
+	fn drop(&mut self) {
+		self.stop_server();
+	}
+}
+
+/// API and p2p ports of the embedded node
+fn node_ports(config: &WalletConfig) -> (u16, u16) {
+	let (api_port, p2p_port) = match global::get_chain_type() {
+		ChainTypes::Mainnet => (3413, 3414),
+		_ => (13413, 13414),
+	};
+	(
+		config.embedded_node_api_port.unwrap_or(api_port),
+		config.embedded_node_p2p_port.unwrap_or(p2p_port),
+	)
+}
+
+/// Chain data directory of the embedded node, 'node' next to the wallet data directory
+/// if it is not configured
+pub fn node_dir(config: &WalletConfig) -> String {
+	if let Some(dir) = &config.embedded_node_dir {
+		return dir.clone();
+	}
+	let mut dir = PathBuf::from(&config.data_file_dir);
+	if dir.ends_with(GRIN_WALLET_DIR) {
+		dir.pop();
+	}
+	dir.push(EMBEDDED_NODE_DIR);
+	dir.to_str().unwrap_or(EMBEDDED_NODE_DIR).to_string()
+}
+
+/// API url of the embedded node, the node client of the wallet connects to it
+pub fn embedded_node_api_url(config: &WalletConfig) -> String {
+	format!("http://127.0.0.1:{}", node_ports(config).0)
+}
+
+/// API secret of the embedded node, the file is created with a new secret if it doesn't exist.
+/// Return the path of the secret file. The commands that don't start the node use it to reach
+/// the node of the running wallet process.
+#[cfg(feature = "embedded_node")]
+pub fn embedded_node_api_secret(config: &WalletConfig) -> Result<String, Error> {
+	use crate::config::config::{check_api_secret, init_api_secret};
+
+	let db_root = node_dir(config);
+	let error = |e: String| -> Error {
+		ErrorKind::GenericError(format!(
+			"Unable to create the embedded node API secret at {}, {}",
+			db_root, e
+		))
+		.into()
+	};
+	std::fs::create_dir_all(&db_root).map_err(|e| error(e.to_string()))?;
+	let mut secret_path = PathBuf::from(&db_root);
+	secret_path.push(EMBEDDED_NODE_API_SECRET);
+	if secret_path.exists() {
+		check_api_secret(&secret_path).map_err(|e| error(e.to_string()))?;
+	} else {
+		init_api_secret(&secret_path).map_err(|e| error(e.to_string()))?;
+	}
+	secret_path
+		.to_str()
+		.map(|p| p.to_string())
+		.ok_or(error("invalid path".to_string()))
+}
+
+/// API secret of the embedded node, the wallet is built without the embedded node support
+#[cfg(not(feature = "embedded_node"))]
+pub fn embedded_node_api_secret(_config: &WalletConfig) -> Result<String, Error> {
+	Err(not_built())
+}
+
+/// Start the embedded node. The node starts with the chain sync, `EmbeddedNode::wait_for_sync`
+/// waits until it is done. The node can be started once per process.
+#[cfg(feature = "embedded_node")]
+pub fn start_embedded_node(config: &WalletConfig) -> Result<EmbeddedNode, Error> {
+	use futures::channel::oneshot;
+	use grin_servers::{Server, ServerConfig};
+	use std::sync::atomic::Ordering;
+
+	let api_url = embedded_node_api_url(config);
+	let db_root = node_dir(config);
+	let (api_port, p2p_port) = node_ports(config);
+	let api_secret_path = embedded_node_api_secret(config)?;
+
+	let mut server_config = ServerConfig::default();
+	server_config.db_root = db_root.clone();
+	server_config.chain_type = global::get_chain_type();
+	server_config.api_http_addr = format!("127.0.0.1:{}", api_port);
+	// Other local processes can reach the API, both APIs require the secret
+	server_config.api_secret_path = Some(api_secret_path.clone());
+	server_config.foreign_api_secret_path = Some(api_secret_path.clone());
+	// Pruned node, the wallet doesn't need the full history
+	server_config.archive_mode = Some(false);
+	server_config.skip_sync_wait = Some(true);
+	server_config.run_tui = Some(false);
+	server_config.p2p_config.port = p2p_port;
+
+	if EMBEDDED_NODE_STARTED.swap(true, Ordering::SeqCst) {
+		return Err(ErrorKind::GenericError(
+			"The embedded node can be started once per process".to_string(),
+		)
+		.into());
+	}
+	// Node API keeps the channel for the whole process life
+	let api_chan: &'static mut (oneshot::Sender<()>, oneshot::Receiver<()>) =
+		Box::leak(Box::new(oneshot::channel::<()>()));
+	// The node reports its own bind error, an external node can run on the same port
+	let server = Server::new(server_config, None, api_chan).map_err(|e| {
+		let e = format!("{:?}", e);
+		if e.contains("AddrInUse") || e.contains("in use") {
+			ErrorKind::ArgumentError(format!(
+				"Unable to start the embedded node, the API port {} or the p2p port {} is in use ({}). \
+				 Stop the node that runs on this port or set embedded_node_api_port and \
+				 embedded_node_p2p_port in the wallet config",
+				api_port, p2p_port, e
+			))
+		} else {
+			ErrorKind::GenericError(format!(
+				"Unable to start the embedded node at {}, {}",
+				db_root, e
+			))
+		}
+	})?;
+	info!(
+		"Embedded node is started at {}, API {}, p2p port {}",
+		db_root, api_url, p2p_port
+	);
+	Ok(EmbeddedNode {
+		server: Some(server),
+		api_url,
+		api_secret_path,
+	})
+}
+
+/// Start the embedded node, the wallet is built without the embedded node support
+#[cfg(not(feature = "embedded_node"))]
+pub fn start_embedded_node(_config: &WalletConfig) -> Result<EmbeddedNode, Error> {
+	Err(not_built())
+}
+
+#[cfg(not(feature = "embedded_node"))]
+fn not_built() -> Error {
+	ErrorKind::ArgumentError(
+		"node_mode is 'embedded' but the wallet is built without the 'embedded_node' feature"
+			.to_string(),
+	)
+	.into()
+}
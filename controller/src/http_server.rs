@@ -0,0 +1,171 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HTTP server for the owner and foreign API listeners. It works as the node ApiServer, but
+//! the peer address of the connection is added to the extensions of every request, so the
+//! handlers know who is calling.
+
+use crate::api::{ResponseFuture, Router, TLSConfig};
+use crate::error::{Error, ErrorKind};
+use futures::channel::oneshot;
+use futures::{future, StreamExt, TryStreamExt};
+use hyper::server::accept;
+use hyper::server::conn::{AddrIncoming, AddrStream};
+use hyper::service::{make_service_fn, Service};
+use hyper::{Body, Request, Response, Server};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::task::{Context, Poll};
+use std::thread;
+use tokio::net::TcpListener;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+
+/// Peer address of the API request, it is in the request extensions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RemoteAddr(pub SocketAddr);
+
+/// HTTP server that routes the requests with the Router
+pub struct HttpServer {
+	shutdown_sender: Option<oneshot::Sender<()>>,
+}
+
+impl HttpServer {
+	/// New server, it is not started yet
+	pub fn new() -> HttpServer {
+		HttpServer {
+			shutdown_sender: None,
+		}
+	}
+
+	/// Start the server in its own thread. The thread exits when the server is stopped.
+	pub fn start(
+		&mut self,
+		addr: SocketAddr,
+		router: Router,
+		tls_config: Option<TLSConfig>,
+	) -> Result<thread::JoinHandle<()>, Error> {
+		if self.shutdown_sender.is_some() {
+			return Err(ErrorKind::GenericError("Server is already started".to_string()).into());
+		}
+		let acceptor = match tls_config {
+			Some(conf) => Some(TlsAcceptor::from(conf.build_server_config().map_err(
+				|e| ErrorKind::GenericError(format!("Invalid TLS config, {}", e)),
+			)?)),
+			None => None,
+		};
+		let (tx, rx) = oneshot::channel::<()>();
+		self.shutdown_sender = Some(tx);
+
+		thread::Builder::new()
+			.name("apis".to_string())
+			.spawn(move || {
+				let server = async move {
+					let shutdown = async {
+						rx.await.ok();
+					};
+					let res = match acceptor {
+						None => {
+							let listener = AddrIncoming::bind(&addr).expect("failed to bind");
+							Server::builder(listener)
+								.serve(make_service_fn(move |conn: &AddrStream| {
+									RouterService::new(router.clone(), Some(conn.remote_addr()))
+								}))
+								.with_graceful_shutdown(shutdown)
+								.await
+						}
+						Some(acceptor) => {
+							let mut listener =
+								TcpListener::bind(&addr).await.expect("failed to bind");
+							let listener = listener
+								.incoming()
+								.and_then(move |s| acceptor.accept(s))
+								.filter(|r| {
+									if let Err(ref e) = r {
+										error!("TLS accept failed: {}", e);
+									}
+									future::ready(r.is_ok())
+								});
+							Server::builder(accept::from_stream(listener))
+								.serve(make_service_fn(move |conn: &TlsStream<_>| {
+									let (stream, _) = conn.get_ref();
+									RouterService::new(router.clone(), stream.peer_addr().ok())
+								}))
+								.with_graceful_shutdown(shutdown)
+								.await
+						}
+					};
+					if let Err(e) = res {
+						error!("HTTP API server error: {}", e);
+					}
+				};
+
+				let mut rt = tokio::runtime::Builder::new()
+					.threaded_scheduler()
+					.enable_all()
+					.build()
+					.unwrap();
+				rt.block_on(server);
+			})
+			.map_err(|e| {
+				ErrorKind::GenericError(format!("Unable to start the API thread, {}", e)).into()
+			})
+	}
+
+	/// Stop the server. The requests in progress are finished before the server thread exits.
+	pub fn stop(&mut self) -> bool {
+		match self.shutdown_sender.take() {
+			Some(tx) => tx.send(()).is_ok(),
+			None => {
+				error!("Can't stop the HTTP server, it is not running");
+				false
+			}
+		}
+	}
+}
+
+// Service for the connection, it passes the requests with the peer address to the router
+struct RouterService {
+	router: Router,
+	remote_addr: Option<SocketAddr>,
+}
+
+impl RouterService {
+	fn new(
+		router: Router,
+		remote_addr: Option<SocketAddr>,
+	) -> future::Ready<Result<Self, Infallible>> {
+		future::ok(RouterService {
+			router,
+			remote_addr,
+		})
+	}
+}
+
+impl Service<Request<Body>> for RouterService {
+	type Response = Response<Body>;
+	type Error = hyper::Error;
+	type Future = ResponseFuture;
+
+	fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+		if let Some(addr) = self.remote_addr {
+			req.extensions_mut().insert(RemoteAddr(addr));
+		}
+		self.router.call(req)
+	}
+}
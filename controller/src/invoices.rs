@@ -0,0 +1,155 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merchant invoices issued by the `/v3/invoice` endpoint of the Owner API listener. The store
+//! keeps the issued invoices, the watcher reports `invoice_paid` event when the invoice
+//! transaction is confirmed.
+
+use crate::libwallet::notifications::{self, WalletEvent};
+use crate::{Error, ErrorKind};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// File with the merchant invoices, stored in the top level wallet directory
+pub const INVOICES_FILE: &str = "merchant_invoices.json";
+
+/// Invoice issued by the endpoint
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MerchantInvoice {
+	/// Invoice Id, it is the slate Id
+	pub id: String,
+	/// Invoice amount, nanoMWC
+	pub amount: u64,
+	/// Merchant memo, for example the order number
+	pub memo: Option<String>,
+	/// Time when the invoice was issued
+	pub created: DateTime<Utc>,
+	/// Time when the invoice transaction was confirmed, None - not paid yet
+	pub paid: Option<DateTime<Utc>>,
+}
+
+/// Merchant invoices store
+pub struct InvoiceStore {
+	path: PathBuf,
+	// The endpoint and the watcher update the file from different threads
+	lock: Mutex<()>,
+}
+
+impl InvoiceStore {
+	/// Invoice store in the top level wallet directory
+	pub fn new<P: AsRef<Path>>(wallet_dir: P) -> Self {
+		InvoiceStore {
+			path: wallet_dir.as_ref().join(INVOICES_FILE),
+			lock: Mutex::new(()),
+		}
+	}
+
+	/// Issued invoices
+	pub fn load(&self) -> Result<Vec<MerchantInvoice>, Error> {
+		if !self.path.exists() {
+			return Ok(vec![]);
+		}
+		let data = fs::read_to_string(&self.path)
+			.map_err(|e| ErrorKind::IO(format!("Unable to read {}, {}", self.path.display(), e)))?;
+		let invoices = serde_json::from_str(&data).map_err(|e| {
+			ErrorKind::Format(format!("Unable to parse {}, {}", self.path.display(), e))
+		})?;
+		Ok(invoices)
+	}
+
+	fn save(&self, invoices: &[MerchantInvoice]) -> Result<(), Error> {
+		let data = serde_json::to_string_pretty(invoices)
+			.map_err(|e| ErrorKind::Format(format!("Unable to serialize invoices, {}", e)))?;
+		let tmp_path = self.path.with_extension("tmp");
+		fs::write(&tmp_path, data)
+			.map_err(|e| ErrorKind::IO(format!("Unable to write {}, {}", tmp_path.display(), e)))?;
+		fs::rename(&tmp_path, &self.path).map_err(|e| {
+			ErrorKind::IO(format!("Unable to write {}, {}", self.path.display(), e))
+		})?;
+		Ok(())
+	}
+
+	/// Add the issued invoice
+	pub fn add(&self, invoice: MerchantInvoice) -> Result<(), Error> {
+		let _lock = self.lock.lock().unwrap();
+		let mut invoices = self.load()?;
+		invoices.push(invoice);
+		self.save(&invoices)
+	}
+
+	/// Mark the invoice as paid. Returns the invoice if it is known and wasn't paid before.
+	pub fn mark_paid(&self, id: &str) -> Result<Option<MerchantInvoice>, Error> {
+		let _lock = self.lock.lock().unwrap();
+		let mut invoices = self.load()?;
+		let invoice = match invoices.iter_mut().find(|i| i.id == id && i.paid.is_none()) {
+			Some(invoice) => {
+				invoice.paid = Some(Utc::now());
+				invoice.clone()
+			}
+			None => return Ok(None),
+		};
+		self.save(&invoices)?;
+		Ok(Some(invoice))
+	}
+}
+
+/// Start the thread that reports `invoice_paid` event when the invoice transaction reaches
+/// the notification confirmations number. The event goes to the webhooks and the event
+/// subscribers like any other wallet event.
+pub fn start_invoice_watcher(store: Arc<InvoiceStore>) -> Result<(), Error> {
+	let events = notifications::subscribe();
+	thread::Builder::new()
+		.name("invoice-watcher".to_string())
+		.spawn(move || {
+			for event in events {
+				if let Some(event) = invoice_paid_event(&store, &event) {
+					notifications::notify(event);
+				}
+			}
+		})
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to start invoice watcher, {}", e)))?;
+	Ok(())
+}
+
+/// `invoice_paid` event for the confirmed transaction of the unpaid invoice
+pub fn invoice_paid_event(store: &InvoiceStore, event: &WalletEvent) -> Option<WalletEvent> {
+	match event {
+		WalletEvent::TxConfirmed {
+			tx_id,
+			slate_id: Some(slate_id),
+			amount_credited,
+			height,
+			confirmations,
+			..
+		} => match store.mark_paid(slate_id) {
+			Ok(Some(invoice)) => Some(WalletEvent::InvoicePaid {
+				invoice_id: invoice.id,
+				amount: *amount_credited,
+				memo: invoice.memo,
+				tx_id: *tx_id,
+				height: *height,
+				confirmations: *confirmations,
+			}),
+			Ok(None) => None,
+			Err(e) => {
+				error!("Unable to update invoice {}, {}", slate_id, e);
+				None
+			}
+		},
+		_ => None,
+	}
+}
@@ -34,10 +34,15 @@ use grin_wallet_util::grin_core as core;
 use grin_wallet_util::grin_keychain as keychain;
 use grin_wallet_util::grin_util as util;
 
+pub mod api_tokens;
 pub mod command;
 pub mod controller;
 pub mod display;
+pub mod embedded_node;
 mod error;
 pub mod executor;
+pub mod http_server;
+pub mod invoices;
+pub mod locale;
 
 pub use crate::error::{Error, ErrorKind};
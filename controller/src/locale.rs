@@ -0,0 +1,319 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Message catalog for the command line output. The English text is the message id, the locale
+//! maps it to the translation. Messages without translation are shown in English.
+
+use crate::core::core::amount_to_hr_string;
+use crate::util::RwLock;
+use crate::{Error, ErrorKind};
+use chrono::{DateTime, TimeZone};
+use std::fmt::Display;
+
+/// Language and number/date conventions of the output
+pub struct Locale {
+	/// Locale name, like "zh-CN"
+	pub name: &'static str,
+	/// Separator of the fractional part of the amounts
+	pub decimal_separator: char,
+	/// Date and time format, strftime syntax
+	pub datetime_format: &'static str,
+	/// (English message, translation)
+	messages: &'static [(&'static str, &'static str)],
+}
+
+const EN_US: Locale = Locale {
+	name: "en-US",
+	decimal_separator: '.',
+	datetime_format: "%Y-%m-%d %H:%M:%S",
+	messages: &[],
+};
+
+const ZH_CN: Locale = Locale {
+	name: "zh-CN",
+	decimal_separator: '.',
+	datetime_format: "%Y-%m-%d %H:%M:%S",
+	messages: &[
+		// outputs
+		(
+			"Wallet Outputs - Account '{}' - Block Height: {}",
+			"钱包输出 - 账户 '{}' - 区块高度: {}",
+		),
+		("Output Commitment", "输出承诺"),
+		("MMR Index", "MMR 索引"),
+		("Block Height", "区块高度"),
+		("Locked Until", "锁定至"),
+		("Spendable At", "可花费高度"),
+		("Status", "状态"),
+		("Coinbase?", "Coinbase?"),
+		("# Confirms", "确认数"),
+		("Value", "金额"),
+		("Tx", "交易"),
+		("Tags", "标签"),
+		("Unconfirmed", "未确认"),
+		("Unspent", "未花费"),
+		("Locked", "已锁定"),
+		("Spent", "已花费"),
+		("Mining", "挖矿中"),
+		("Frozen", "已冻结"),
+		("{} (~{} min)", "{} (约 {} 分钟)"),
+		("{} (~{} hours)", "{} (约 {} 小时)"),
+		("None", "无"),
+		("Yes", "是"),
+		// transactions
+		(
+			"Transaction Log - Account '{}' - Block Height: {}",
+			"交易记录 - 账户 '{}' - 区块高度: {}",
+		),
+		("Id", "编号"),
+		("Type", "类型"),
+		("Shared Transaction Id", "共享交易编号"),
+		("Address", "地址"),
+		("Label", "名称"),
+		("Creation Time", "创建时间"),
+		("TTL Cutoff Height", "TTL 截止高度"),
+		("Confirmed?", "已确认?"),
+		("Height", "高度"),
+		("Confirmation Time", "确认时间"),
+		("Num. \nInputs", "输入\n数量"),
+		("Num. \nOutputs", "输出\n数量"),
+		("Amount \nCredited", "收入\n金额"),
+		("Amount \nDebited", "支出\n金额"),
+		("Fee", "手续费"),
+		("Net \nDifference", "净\n变化"),
+		("Payment \nProof", "支付\n证明"),
+		("Kernel", "内核"),
+		("Tx \nData", "交易\n数据"),
+		("TXID", "交易ID"),
+		("Proof?", "证明?"),
+		("Confirmed \nCoinbase", "已确认\nCoinbase"),
+		("Received Tx", "收款"),
+		("Sent Tx", "付款"),
+		("Received Tx\n- Cancelled", "收款\n- 已取消"),
+		("Sent Tx\n- Cancelled", "付款\n- 已取消"),
+		("recovered", "已恢复"),
+		("Unknown", "未知"),
+		("Awaiting\nCounterparty", "等待\n对方"),
+		("Awaiting\nFinalization", "等待\n完成"),
+		("Awaiting\nConfirmation", "等待\n确认"),
+		("Confirmed", "已确认"),
+		("Settled", "已结算"),
+		("Cancelled", "已取消"),
+		("Expired", "已过期"),
+		(
+			"WARNING: Wallet failed to verify data. The above is from local cache and possibly invalid! (is your `mwc server` offline or broken?)",
+			"警告: 钱包未能验证数据。以上内容来自本地缓存，可能无效！（您的 `mwc server` 是否离线或损坏？）",
+		),
+		// summary info
+		(
+			"Wallet Summary Info - Account '{}' as of height {}",
+			"钱包摘要 - 账户 '{}' 截至高度 {}",
+		),
+		("Confirmed Total", "已确认总额"),
+		("Total", "总额"),
+		("Immature Coinbase (< {})", "未成熟 Coinbase (< {})"),
+		("Awaiting Confirmation (< {})", "等待确认 (< {})"),
+		("Awaiting Finalization", "等待完成"),
+		("Locked by previous transaction", "被之前的交易锁定"),
+		("Committed to swaps", "用于兑换"),
+		("Currently Spendable", "当前可用"),
+		("Exchange rate: 1 MWC = {}", "汇率: 1 MWC = {}"),
+		(
+			"Next immature coinbase is spendable at height {}",
+			"下一个未成熟 Coinbase 可花费高度 {}",
+		),
+		(
+			"WARNING: Wallet failed to verify data against a live chain. The above is from local cache and only valid up to the given height! (is your `mwc server` offline or broken?)",
+			"警告: 钱包未能根据链验证数据。以上内容来自本地缓存，仅在给定高度之前有效！（您的 `mwc server` 是否离线或损坏？）",
+		),
+	],
+};
+
+const DE_DE: Locale = Locale {
+	name: "de-DE",
+	decimal_separator: ',',
+	datetime_format: "%d.%m.%Y %H:%M:%S",
+	messages: &[
+		// outputs
+		(
+			"Wallet Outputs - Account '{}' - Block Height: {}",
+			"Wallet-Outputs - Konto '{}' - Blockhöhe: {}",
+		),
+		("Output Commitment", "Output-Commitment"),
+		("MMR Index", "MMR-Index"),
+		("Block Height", "Blockhöhe"),
+		("Locked Until", "Gesperrt bis"),
+		("Spendable At", "Ausgebbar ab"),
+		("# Confirms", "# Bestät."),
+		("Value", "Betrag"),
+		("Unconfirmed", "Unbestätigt"),
+		("Unspent", "Unausgegeben"),
+		("Locked", "Gesperrt"),
+		("Spent", "Ausgegeben"),
+		("Frozen", "Eingefroren"),
+		("{} (~{} min)", "{} (~{} Min.)"),
+		("{} (~{} hours)", "{} (~{} Std.)"),
+		("None", "Keine"),
+		("Yes", "Ja"),
+		// transactions
+		(
+			"Transaction Log - Account '{}' - Block Height: {}",
+			"Transaktionsprotokoll - Konto '{}' - Blockhöhe: {}",
+		),
+		("Type", "Typ"),
+		("Shared Transaction Id", "Gemeinsame Transaktions-Id"),
+		("Address", "Adresse"),
+		("Label", "Bezeichnung"),
+		("Creation Time", "Erstellt"),
+		("TTL Cutoff Height", "TTL-Grenzhöhe"),
+		("Confirmed?", "Bestätigt?"),
+		("Height", "Höhe"),
+		("Confirmation Time", "Bestätigt am"),
+		("Num. \nInputs", "Anz. \nInputs"),
+		("Num. \nOutputs", "Anz. \nOutputs"),
+		("Amount \nCredited", "Betrag \nGutgeschrieben"),
+		("Amount \nDebited", "Betrag \nBelastet"),
+		("Fee", "Gebühr"),
+		("Net \nDifference", "Netto-\nDifferenz"),
+		("Payment \nProof", "Zahlungs-\nnachweis"),
+		("Tx \nData", "Tx-\nDaten"),
+		("Proof?", "Nachweis?"),
+		("Confirmed \nCoinbase", "Bestätigte \nCoinbase"),
+		("Received Tx", "Empfangen"),
+		("Sent Tx", "Gesendet"),
+		("Received Tx\n- Cancelled", "Empfangen\n- Storniert"),
+		("Sent Tx\n- Cancelled", "Gesendet\n- Storniert"),
+		("recovered", "wiederhergestellt"),
+		("Unknown", "Unbekannt"),
+		("Awaiting\nCounterparty", "Wartet auf\nGegenpartei"),
+		("Awaiting\nFinalization", "Wartet auf\nAbschluss"),
+		("Awaiting\nConfirmation", "Wartet auf\nBestätigung"),
+		("Confirmed", "Bestätigt"),
+		("Settled", "Abgewickelt"),
+		("Cancelled", "Storniert"),
+		("Expired", "Abgelaufen"),
+		(
+			"WARNING: Wallet failed to verify data. The above is from local cache and possibly invalid! (is your `mwc server` offline or broken?)",
+			"WARNUNG: Die Wallet konnte die Daten nicht überprüfen. Die Angaben stammen aus dem lokalen Cache und sind möglicherweise ungültig! (Ist Ihr `mwc server` offline oder defekt?)",
+		),
+		// summary info
+		(
+			"Wallet Summary Info - Account '{}' as of height {}",
+			"Wallet-Übersicht - Konto '{}' bei Höhe {}",
+		),
+		("Confirmed Total", "Bestätigt gesamt"),
+		("Total", "Gesamt"),
+		("Immature Coinbase (< {})", "Unreife Coinbase (< {})"),
+		("Awaiting Confirmation (< {})", "Wartet auf Bestätigung (< {})"),
+		("Awaiting Finalization", "Wartet auf Abschluss"),
+		(
+			"Locked by previous transaction",
+			"Durch vorherige Transaktion gesperrt",
+		),
+		("Committed to swaps", "Für Swaps reserviert"),
+		("Currently Spendable", "Derzeit verfügbar"),
+		("Exchange rate: 1 MWC = {}", "Wechselkurs: 1 MWC = {}"),
+		(
+			"Next immature coinbase is spendable at height {}",
+			"Nächste unreife Coinbase ist ausgebbar ab Höhe {}",
+		),
+		(
+			"WARNING: Wallet failed to verify data against a live chain. The above is from local cache and only valid up to the given height! (is your `mwc server` offline or broken?)",
+			"WARNUNG: Die Wallet konnte die Daten nicht mit der Blockchain abgleichen. Die Angaben stammen aus dem lokalen Cache und gelten nur bis zur angegebenen Höhe! (Ist Ihr `mwc server` offline oder defekt?)",
+		),
+	],
+};
+
+/// Supported locales, the first one is the default
+pub const LOCALES: &[Locale] = &[EN_US, ZH_CN, DE_DE];
+
+lazy_static! {
+	static ref CURRENT_LOCALE: RwLock<&'static Locale> = RwLock::new(&LOCALES[0]);
+}
+
+/// Select the locale of the output by name. The name is case insensitive, 'zh_CN' and the
+/// language only 'zh' are accepted too.
+pub fn set_locale(name: &str) -> Result<(), Error> {
+	let key = name.replace('_', "-").to_lowercase();
+	let locale = LOCALES
+		.iter()
+		.find(|l| l.name.to_lowercase() == key)
+		.or_else(|| {
+			LOCALES
+				.iter()
+				.find(|l| l.name.split('-').next() == Some(key.as_str()))
+		})
+		.ok_or_else(|| {
+			ErrorKind::ArgumentError(format!(
+				"Unknown locale '{}', supported locales: {}",
+				name,
+				LOCALES
+					.iter()
+					.map(|l| l.name)
+					.collect::<Vec<&str>>()
+					.join(", ")
+			))
+		})?;
+	*CURRENT_LOCALE.write() = locale;
+	Ok(())
+}
+
+/// Currently selected locale
+pub fn locale() -> &'static Locale {
+	*CURRENT_LOCALE.read()
+}
+
+/// Translate the message
+pub fn tr(msg: &str) -> &str {
+	locale()
+		.messages
+		.iter()
+		.find(|(m, _)| *m == msg)
+		.map(|(_, t)| *t)
+		.unwrap_or(msg)
+}
+
+/// Translate the message and substitute its '{}' placeholders with the arguments in order
+pub fn tr_fmt(msg: &str, args: &[&dyn Display]) -> String {
+	let mut parts = tr(msg).split("{}");
+	let mut res = parts.next().unwrap_or("").to_string();
+	for (i, part) in parts.enumerate() {
+		if let Some(arg) = args.get(i) {
+			res.push_str(&arg.to_string());
+		}
+		res.push_str(part);
+	}
+	res
+}
+
+/// Apply the decimal separator of the locale to the formatted number
+pub fn number(value: String) -> String {
+	match locale().decimal_separator {
+		'.' => value,
+		sep => value.replace('.', &sep.to_string()),
+	}
+}
+
+/// Amount in MWC, formatted for the locale
+pub fn amount(amount: u64, truncate: bool) -> String {
+	number(amount_to_hr_string(amount, truncate))
+}
+
+/// Date and time, formatted for the locale
+pub fn datetime<Tz: TimeZone>(ts: &DateTime<Tz>) -> String
+where
+	Tz::Offset: Display,
+{
+	ts.format(locale().datetime_format).to_string()
+}
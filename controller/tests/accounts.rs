@@ -263,6 +263,46 @@ fn accounts_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
 		Ok(())
 	})?;
 
+	// rename and delete accounts, wallet 1 is active on account2
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		assert!(api.rename_account(m, "default", "main").is_err());
+		assert!(api.rename_account(m, "account1", "account2").is_err());
+		assert!(api.rename_account(m, "unknown", "account4").is_err());
+		api.rename_account(m, "account1", "savings")?;
+		let accounts = api.accounts(m)?;
+		let savings = accounts.iter().find(|a| a.label == "savings").unwrap();
+		assert_eq!(savings.path, ExtKeychain::derive_key_id(2, 1, 0, 0, 0));
+		assert!(accounts.iter().find(|a| a.label == "account1").is_none());
+
+		assert!(api.delete_account(m, "default").is_err());
+		// active account
+		assert!(api.delete_account(m, "account2").is_err());
+		// account holds the funds
+		assert!(api.delete_account(m, "savings").is_err());
+		api.delete_account(m, "account3")?;
+		let accounts = api.accounts(m)?;
+		assert_eq!(accounts.len(), 3);
+		assert!(accounts.iter().find(|a| a.label == "account3").is_none());
+		Ok(())
+	})?;
+
+	// summary of all accounts matches the per account summary
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (refreshed, accounts) = api.retrieve_accounts_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		assert_eq!(accounts.len(), 3);
+		let (_, info) = api.retrieve_summary_info(m, false, 1)?;
+		let account2 = accounts.iter().find(|a| a.label == "account2").unwrap();
+		assert_eq!(account2.info.total, info.total);
+		assert_eq!(
+			account2.info.amount_currently_spendable,
+			info.amount_currently_spendable
+		);
+		let default = accounts.iter().find(|a| a.label == "default").unwrap();
+		assert_eq!(default.info.total, 0);
+		Ok(())
+	})?;
+
 	// let logging finish
 	thread::sleep(Duration::from_millis(200));
 	Ok(())
@@ -0,0 +1,163 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests payments to the address indexes and their booking to accounts and labels
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{AddressMapping, InitTxArgs, TxLogEntryType};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+use grin_wallet_util::grin_core::global;
+
+fn address_mapping_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask2 = (&mask2_i).as_ref();
+
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 10, false);
+
+	// Address for the customer 5, its payments go to the 'deposits' account
+	let mut address = None;
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		api.create_account_path(m, "deposits")?;
+		let mut mapping = AddressMapping {
+			index: 5,
+			account: Some("unknown".to_string()),
+			label: Some("customer 5".to_string()),
+		};
+		assert!(api.set_address_mapping(m, &mapping).is_err());
+		mapping.account = Some("deposits".to_string());
+		api.set_address_mapping(m, &mapping)?;
+		assert_eq!(api.address_mappings(m)?, vec![mapping]);
+
+		let proof_address = api.get_public_proof_address(m, 5)?;
+		assert_eq!(proof_address.index, 5);
+		address = Some(proof_address.mqs_address);
+		assert!(api.list_used_address_indices(m)?.is_empty());
+		Ok(())
+	})?;
+	let address = address.unwrap();
+
+	let amount = 2_000_000_000;
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |sender_api, m| {
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: true,
+			payment_proof_recipient_address: Some(address.clone()),
+			..Default::default()
+		};
+		let slate_i = sender_api.init_send_tx(m, &args, 1)?;
+		let slate = client1.send_tx_slate_direct("wallet2", &slate_i)?;
+		sender_api.tx_lock_outputs(m, &slate, None, 0)?;
+		let slate = sender_api.finalize_tx(m, &slate)?;
+		sender_api.post_tx(m, &slate.tx, true)?;
+		Ok(())
+	})?;
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 2, false);
+
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		assert_eq!(api.list_used_address_indices(m)?, vec![5]);
+
+		// Nothing is booked to the default account
+		let (_, txs) = api.retrieve_txs(m, true, None, None)?;
+		assert!(txs.is_empty());
+
+		api.set_active_account(m, "deposits")?;
+		let (_, txs) = api.retrieve_txs(m, true, None, None)?;
+		assert_eq!(txs.len(), 1);
+		assert_eq!(txs[0].tx_type, TxLogEntryType::TxReceived);
+		assert_eq!(txs[0].amount_credited, amount);
+		assert_eq!(txs[0].address_index, Some(5));
+		assert_eq!(txs[0].label, Some("customer 5".to_string()));
+
+		api.remove_address_mapping(m, 5)?;
+		assert!(api.address_mappings(m)?.is_empty());
+		assert!(api.remove_address_mapping(m, 5).is_err());
+		Ok(())
+	})?;
+
+	// the payment proof is signed by the key of the address index
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |sender_api, m| {
+		let (_, txs) = sender_api.retrieve_txs(m, true, None, None)?;
+		let tx = txs
+			.iter()
+			.find(|t| t.tx_type == TxLogEntryType::TxSent)
+			.unwrap();
+		let pp = sender_api.retrieve_payment_proof(m, true, Some(tx.id), None)?;
+		assert_eq!(pp.recipient_address.public_key, address.public_key);
+		let res = sender_api.verify_payment_proof(m, &pp)?;
+		assert_eq!(res, (true, false));
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn address_mapping() {
+	let test_dir = "test_output/address_mapping";
+	setup(test_dir);
+	if let Err(e) = address_mapping_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
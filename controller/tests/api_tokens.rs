@@ -0,0 +1,103 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests Owner API tokens and their scopes
+extern crate grin_wallet_controller as wallet;
+
+use chrono::Duration;
+use std::fs;
+use wallet::api_tokens::{
+	parse_scopes, parse_ttl, ApiAccess, ApiTokenStore, TokenScope, API_TOKENS_FILE,
+};
+
+#[test]
+fn api_token_store() {
+	let test_dir = "test_output/api_token_store";
+	let _ = fs::remove_dir_all(test_dir);
+	fs::create_dir_all(test_dir).unwrap();
+
+	let store = ApiTokenStore::new(test_dir);
+	let (record, token) = store
+		.issue(vec![TokenScope::Read], Duration::hours(1))
+		.unwrap();
+	assert_eq!(
+		store.validate(&token).unwrap(),
+		Some(vec![TokenScope::Read])
+	);
+	assert_eq!(store.validate("mwc_00").unwrap(), None);
+	// Only the hash is stored
+	let data = fs::read_to_string(format!("{}/{}", test_dir, API_TOKENS_FILE)).unwrap();
+	assert!(!data.contains(&token));
+
+	let (_, expired) = store
+		.issue(vec![TokenScope::Admin], Duration::seconds(-1))
+		.unwrap();
+	assert_eq!(store.validate(&expired).unwrap(), None);
+
+	assert!(store
+		.issue(vec![TokenScope::Read], Duration::days(1_000_000_000))
+		.is_err());
+
+	// Token issued by another store, like the token command of another process
+	let (_, other) = ApiTokenStore::new(test_dir)
+		.issue(vec![TokenScope::Send], Duration::hours(1))
+		.unwrap();
+	assert_eq!(
+		store.validate(&other).unwrap(),
+		Some(vec![TokenScope::Send])
+	);
+
+	assert!(store.revoke(&record.id).unwrap());
+	assert!(!store.revoke(&record.id).unwrap());
+	assert_eq!(store.validate(&token).unwrap(), None);
+
+	let _ = fs::remove_dir_all(test_dir);
+}
+
+#[test]
+fn api_token_scopes() {
+	let read = ApiAccess::Scopes(vec![TokenScope::Read]);
+	assert!(read.allows("retrieve_summary_info"));
+	assert!(!read.allows("open_wallet"));
+	assert!(!read.allows("init_secure_api"));
+	assert!(!read.allows("init_send_tx"));
+	assert!(!read.allows("swap_start"));
+	assert!(!read.allows("get_mnemonic"));
+	assert!(read
+		.check_request(&serde_json::json!({"method": "post_tx", "id": 1}))
+		.is_some());
+	// Batches are allowed for the api secret only
+	assert!(read
+		.check_request(&serde_json::json!([{"method": "accounts"}]))
+		.is_some());
+
+	let admin = ApiAccess::Scopes(vec![TokenScope::Admin]);
+	assert!(admin.allows("get_mnemonic"));
+	assert!(admin.allows("close_wallet"));
+	assert!(ApiAccess::Full
+		.check_request(&serde_json::json!({"method": "delete_wallet"}))
+		.is_none());
+
+	assert_eq!(
+		parse_scopes("read, send,read").unwrap(),
+		vec![TokenScope::Read, TokenScope::Send]
+	);
+	assert!(parse_scopes("write").is_err());
+	assert_eq!(parse_ttl("24h").unwrap(), Duration::hours(24));
+	assert_eq!(parse_ttl("90").unwrap(), Duration::seconds(90));
+	assert_eq!(parse_ttl("7d").unwrap(), Duration::days(7));
+	assert!(parse_ttl("-1h").is_err());
+	assert!(parse_ttl("1y").is_err());
+	assert!(parse_ttl(&format!("{}d", i64::MAX)).is_err());
+	assert!(parse_ttl(&format!("{}s", i64::MAX)).is_err());
+}
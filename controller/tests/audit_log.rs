@@ -0,0 +1,155 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests audit log of the owner API operations
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::audit_log;
+use libwallet::{InitTxArgs, Slate};
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Send and cancel are recorded with the balances
+fn audit_log_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	let audit_file = format!("{}/audit.log", test_dir);
+	audit_log::set_audit_log_file(Some(audit_file.clone()));
+
+	let args = InitTxArgs {
+		src_acct_name: None,
+		amount: 2_000_000_000,
+		minimum_confirmations: 2,
+		max_outputs: 500,
+		num_change_outputs: 1,
+		selection_strategy_is_use_all: true,
+		..Default::default()
+	};
+
+	let mut slate = Slate::blank(1, false);
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		slate = api.init_send_tx(m, &args, 1)?;
+		api.tx_lock_outputs(m, &slate, None, 0)?;
+		slate = client1.send_tx_slate_direct("wallet2", &slate)?;
+		slate = api.finalize_tx(m, &slate)?;
+		api.post_tx(m, &slate.tx, false)?;
+		Ok(())
+	})?;
+
+	// Failed operations are recorded as well
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		assert!(api.cancel_tx(m, Some(999), None).is_err());
+		Ok(())
+	})?;
+	audit_log::set_audit_log_file(None);
+
+	let records = audit_log::read_audit_log(&audit_file)?;
+	let operations: Vec<&str> = records.iter().map(|r| r.operation.as_str()).collect();
+	assert_eq!(
+		operations,
+		vec![
+			"init_send_tx",
+			"tx_lock_outputs",
+			"finalize_tx",
+			"post_tx",
+			"cancel_tx"
+		]
+	);
+	assert!(records.iter().all(|r| r.caller == audit_log::LOCAL_CALLER));
+	assert!(records[0].error.is_none());
+	assert!(records[4].error.is_some());
+
+	// Inputs are locked by the lock step
+	let before = records[1].balance_before.clone().unwrap();
+	let after = records[1].balance_after.clone().unwrap();
+	assert_eq!(before.locked, 0);
+	assert!(after.locked > 0);
+	assert_eq!(before.unspent, after.unspent + after.locked);
+
+	let res = audit_log::verify_audit_log(&audit_file)?;
+	assert_eq!(res.valid_records, 5);
+	assert!(res.broken_at.is_none());
+
+	// Tampered record is detected
+	let content = fs::read_to_string(&audit_file).unwrap();
+	let tampered = content.replacen("\"post_tx\"", "\"cancel_tx\"", 1);
+	fs::write(&audit_file, tampered).unwrap();
+	let res = audit_log::verify_audit_log(&audit_file)?;
+	assert_eq!(res.valid_records, 3);
+	assert_eq!(res.broken_at.unwrap().0, 4);
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn audit_log() {
+	let test_dir = "test_output/audit_log";
+	setup(test_dir);
+	if let Err(e) = audit_log_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,125 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests automatic cancellation of the expired transactions
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{AutoCancelPolicy, InitTxArgs, Slate, TxLogEntryType};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Test cancellation by the transaction age
+fn auto_cancel_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	// Send without TTL, default policy must keep it
+	let mut slate = Slate::blank(1, false);
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |sender_api, m| {
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount: 2_000_000_000,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: true,
+			..Default::default()
+		};
+		let slate_i = sender_api.init_send_tx(m, &args, 1)?;
+		sender_api.tx_lock_outputs(m, &slate_i, None, 0)?;
+		slate = slate_i;
+
+		let policy = sender_api.get_auto_cancel_policy()?;
+		assert_eq!(policy, AutoCancelPolicy::default());
+		Ok(())
+	})?;
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 2, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |sender_api, m| {
+		let (_, txs) = sender_api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert!(txs[0].tx_type == TxLogEntryType::TxSent);
+
+		// Any transaction is older than 0 hours
+		sender_api.set_auto_cancel_policy(AutoCancelPolicy {
+			cancel_on_ttl: true,
+			max_age_hours: Some(0),
+			receive_expire_days: None,
+		})?;
+		Ok(())
+	})?;
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 2, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |sender_api, m| {
+		let (_, txs) = sender_api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert!(txs[0].tx_type == TxLogEntryType::TxSentCancelled);
+		let (_, outputs) = sender_api.retrieve_outputs(m, false, false, Some(txs[0].id))?;
+		assert!(outputs
+			.iter()
+			.all(|o| o.output.status != libwallet::OutputStatus::Locked));
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn auto_cancel() {
+	let test_dir = "test_output/auto_cancel";
+	setup(test_dir);
+	if let Err(e) = auto_cancel_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
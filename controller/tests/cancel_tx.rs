@@ -0,0 +1,193 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the cancel of the transaction with the kernel on the chain and without the node
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::internal::{tx, updater};
+use libwallet::{ErrorKind, InitTxArgs, NodeClient, OutputStatus, Slate, TxLogEntryType};
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn cancel_tx_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+	let proxy_running = wallet_proxy.running.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let _mask2 = (&mask2_i).as_ref();
+
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let cm = global::coinbase_maturity() as usize;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, cm + 3, false);
+
+	let send = |post: bool| -> Result<Slate, wallet::Error> {
+		let mut slate = Slate::blank(1, false);
+		wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+			let args = InitTxArgs {
+				src_acct_name: None,
+				amount: 1_000_000_000,
+				minimum_confirmations: 2,
+				max_outputs: 500,
+				num_change_outputs: 1,
+				selection_strategy_is_use_all: false,
+				..Default::default()
+			};
+			let slate_i = api.init_send_tx(m, &args, 1)?;
+			slate = client1.send_tx_slate_direct("wallet2", &slate_i)?;
+			api.tx_lock_outputs(m, &slate, None, 0)?;
+			slate = api.finalize_tx(m, &slate)?;
+			if post {
+				api.post_tx(m, &slate.tx, false)?;
+			}
+			Ok(())
+		})?;
+		Ok(slate)
+	};
+
+	// The kernel is on the chain, but the wallet wasn't updated yet. Cancel is refused and
+	// the transaction with its outputs gets the confirmed status.
+	let slate = send(true)?;
+	{
+		wallet_inst!(wallet1, w);
+		let parent_key_id = w.parent_key_id();
+		let res = tx::cancel_tx(&mut **w, mask1, &parent_key_id, None, Some(slate.id), false);
+		match res.map_err(|e| e.kind()) {
+			Err(ErrorKind::TransactionKernelOnChain(_, height)) => assert!(height > 0),
+			r => panic!("Unexpected cancel result {:?}", r),
+		}
+
+		let txs = updater::retrieve_txs(
+			&mut **w,
+			mask1,
+			None,
+			Some(slate.id),
+			None,
+			false,
+			None,
+			None,
+		)?;
+		assert_eq!(txs.len(), 1);
+		assert_eq!(txs[0].tx_type, TxLogEntryType::TxSent);
+		assert!(txs[0].confirmed);
+
+		let outputs = updater::retrieve_outputs(
+			&mut **w,
+			mask1,
+			true,
+			Some(&txs[0]),
+			&parent_key_id,
+			None,
+			None,
+		)?;
+		assert!(!outputs.is_empty());
+		for o in outputs {
+			match o.output.is_coinbase {
+				true => assert_eq!(o.output.status, OutputStatus::Spent),
+				false => assert_eq!(o.output.status, OutputStatus::Unspent),
+			}
+		}
+	}
+
+	// The node is gone, the transaction that was never posted still can be cancelled
+	let slate = send(false)?;
+	proxy_running.store(false, Ordering::Relaxed);
+	// the proxy stops after this response
+	let _ = client2.get_chain_tip();
+	thread::sleep(Duration::from_millis(200));
+	assert!(client1.get_chain_tip().is_err());
+	{
+		wallet_inst!(wallet1, w);
+		let parent_key_id = w.parent_key_id();
+		tx::cancel_tx(&mut **w, mask1, &parent_key_id, None, Some(slate.id), false)?;
+
+		let txs = updater::retrieve_txs(
+			&mut **w,
+			mask1,
+			None,
+			Some(slate.id),
+			None,
+			false,
+			None,
+			None,
+		)?;
+		assert_eq!(txs.len(), 1);
+		assert_eq!(txs[0].tx_type, TxLogEntryType::TxSentCancelled);
+		let outputs = updater::retrieve_outputs(
+			&mut **w,
+			mask1,
+			true,
+			Some(&txs[0]),
+			&parent_key_id,
+			None,
+			None,
+		)?;
+		assert!(outputs
+			.iter()
+			.all(|o| o.output.status != OutputStatus::Locked));
+	}
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn cancel_tx_kernel_check() {
+	let test_dir = "test_output/cancel_tx_kernel_check";
+	setup(test_dir);
+	if let Err(e) = cancel_tx_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,143 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests that the update rolls back the confirmations above the fork height and reports the reorg
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::notifications::{self, WalletEvent};
+use libwallet::{NodeClient, ScannedBlockInfo};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn chain_reorg_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 10, false);
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (refreshed, _) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		Ok(())
+	})?;
+
+	// The blocks above the fork height are replaced: the wallet has another hash for the tip
+	let (tip_height, _, _) = client1.get_chain_tip()?;
+	let fork_height = tip_height - 5;
+	let fork_hash = client1.get_header_info(fork_height)?.hash;
+	let mut rolled_back: Vec<u32> = {
+		wallet_inst!(wallet1, w);
+		let rolled_back = w
+			.tx_log_iter()
+			.filter(|t| t.confirmed && t.output_height > fork_height)
+			.map(|t| t.id)
+			.collect();
+		let mut batch = w.batch(mask1)?;
+		batch.save_last_scanned_blocks(
+			0,
+			&vec![
+				ScannedBlockInfo::new(tip_height, "00".repeat(32)),
+				ScannedBlockInfo::new(fork_height, fork_hash),
+			],
+		)?;
+		batch.commit()?;
+		rolled_back
+	};
+	assert!(!rolled_back.is_empty());
+
+	// The scan confirms the transactions again, they are on the new chain too
+	let events = notifications::subscribe();
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (refreshed, txs) = api.retrieve_txs(m, true, None, None)?;
+		assert!(refreshed);
+		for id in &rolled_back {
+			assert!(txs.iter().find(|t| t.id == *id).unwrap().confirmed);
+		}
+		Ok(())
+	})?;
+
+	let reorgs: Vec<WalletEvent> = events
+		.try_iter()
+		.filter(|e| match e {
+			WalletEvent::ChainReorg { .. } => true,
+			_ => false,
+		})
+		.collect();
+	assert_eq!(reorgs.len(), 1);
+	match &reorgs[0] {
+		WalletEvent::ChainReorg {
+			fork_height: height,
+			prev_height,
+			rolled_back_txs,
+			unconfirmed_txs,
+			..
+		} => {
+			assert_eq!(*height, fork_height);
+			assert_eq!(*prev_height, tip_height);
+			let mut rolled_back_txs = rolled_back_txs.clone();
+			rolled_back_txs.sort();
+			rolled_back.sort();
+			assert_eq!(rolled_back_txs, rolled_back);
+			assert!(unconfirmed_txs.is_empty());
+		}
+		_ => unreachable!(),
+	}
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn chain_reorg() {
+	let test_dir = "test_output/chain_reorg";
+	setup(test_dir);
+	if let Err(e) = chain_reorg_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
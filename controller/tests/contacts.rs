@@ -0,0 +1,115 @@
+// Copyright 2019 The Grin Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests address book contacts
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_util::grin_core as core;
+
+use self::core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Add, resolve and remove address book contacts
+fn contacts_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		// address book is empty for the new wallet
+		assert!(api.contacts(m)?.is_empty());
+
+		api.add_contact(m, "bob", "http://127.0.0.1:3415")?;
+		api.add_contact(
+			m,
+			"alice",
+			"xmgEvZ4MCCGMJnRnNXKHBbHmSGWQchNr9uZpY5J1XXnsCFS45fsU",
+		)?;
+
+		// duplicated name and invalid values should fail
+		assert!(api.add_contact(m, "bob", "http://127.0.0.1:13415").is_err());
+		assert!(api
+			.add_contact(m, "bad name", "http://127.0.0.1:3415")
+			.is_err());
+		assert!(api.add_contact(m, "carol", "").is_err());
+		assert!(api.add_contact(m, "carol", "@bob").is_err());
+
+		let contacts = api.contacts(m)?;
+		assert_eq!(contacts.len(), 2);
+		assert_eq!(contacts[0].name, "alice");
+		assert_eq!(contacts[1].name, "bob");
+
+		// '@name' resolves into the address, anything else stays untouched
+		assert_eq!(
+			api.resolve_contact_address(m, "@bob")?,
+			"http://127.0.0.1:3415"
+		);
+		assert_eq!(
+			api.resolve_contact_address(m, "http://127.0.0.1:13415")?,
+			"http://127.0.0.1:13415"
+		);
+		assert!(api.resolve_contact_address(m, "@carol").is_err());
+
+		api.remove_contact(m, "bob")?;
+		assert!(api.remove_contact(m, "bob").is_err());
+		assert!(api.resolve_contact_address(m, "@bob").is_err());
+		let contacts = api.contacts(m)?;
+		assert_eq!(contacts.len(), 1);
+		assert_eq!(contacts[0].name, "alice");
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn contacts() {
+	let test_dir = "test_output/contacts";
+	setup(test_dir);
+	if let Err(e) = contacts_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
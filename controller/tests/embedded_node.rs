@@ -0,0 +1,86 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests of the embedded node settings
+extern crate grin_wallet_controller as wallet;
+
+use grin_wallet_config::{WalletConfig, GRIN_WALLET_DIR};
+use std::path::PathBuf;
+use wallet::embedded_node;
+
+fn path(parts: &[&str]) -> String {
+	let path: PathBuf = parts.iter().collect();
+	path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn embedded_node_dir() {
+	let mut config = WalletConfig::default();
+
+	// Next to the wallet data directory
+	config.data_file_dir = path(&["top", GRIN_WALLET_DIR]);
+	assert_eq!(embedded_node::node_dir(&config), path(&["top", "node"]));
+
+	// Inside the top level directory if the data directory has another name
+	config.data_file_dir = path(&["top", "other"]);
+	assert_eq!(
+		embedded_node::node_dir(&config),
+		path(&["top", "other", "node"])
+	);
+
+	// Configured directory
+	config.embedded_node_dir = Some(path(&["chain", "data"]));
+	assert_eq!(embedded_node::node_dir(&config), path(&["chain", "data"]));
+}
+
+#[test]
+fn embedded_node_api_url() {
+	let mut config = WalletConfig::default();
+	config.embedded_node_api_port = Some(23413);
+	assert_eq!(
+		embedded_node::embedded_node_api_url(&config),
+		"http://127.0.0.1:23413"
+	);
+}
+
+#[cfg(not(feature = "embedded_node"))]
+#[test]
+fn embedded_node_not_built() {
+	let config = WalletConfig::default();
+	match embedded_node::start_embedded_node(&config) {
+		Err(e) => match e.kind() {
+			wallet::ErrorKind::ArgumentError(_) => (),
+			kind => panic!("Unexpected error {}", kind),
+		},
+		Ok(_) => panic!("The embedded node is started without the feature"),
+	}
+	// The other commands can't use the node either
+	assert!(embedded_node::embedded_node_api_secret(&config).is_err());
+}
+
+#[cfg(feature = "embedded_node")]
+#[test]
+fn embedded_node_port_in_use() {
+	let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+	let mut config = WalletConfig::default();
+	config.data_file_dir = path(&["target", "test_output", "embedded_node", GRIN_WALLET_DIR]);
+	config.embedded_node_api_port = Some(listener.local_addr().unwrap().port());
+	match embedded_node::start_embedded_node(&config) {
+		Err(e) => match e.kind() {
+			wallet::ErrorKind::ArgumentError(msg) => assert!(msg.contains("in use")),
+			kind => panic!("Unexpected error {}", kind),
+		},
+		Ok(_) => panic!("The embedded node is started on the busy port"),
+	}
+}
@@ -0,0 +1,137 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests expiration of the receives that the sender never finalized
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{AutoCancelPolicy, InitTxArgs, Slate, TxLogEntryType};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Test expiration and bulk cancellation of the unfinalized receives
+fn expire_receive_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+
+	create_wallet_and_add!(
+		_client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	// wallet2 receives, but the sender never finalizes
+	let amount = 2_000_000_000;
+	let mut slate = Slate::blank(1, false);
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |sender_api, m| {
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: true,
+			..Default::default()
+		};
+		let slate_i = sender_api.init_send_tx(m, &args, 1)?;
+		slate = client1.send_tx_slate_direct("wallet2", &slate_i)?;
+		sender_api.tx_lock_outputs(m, &slate, None, 0)?;
+		Ok(())
+	})?;
+
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (_, info) = api.retrieve_summary_info(m, true, 1)?;
+		assert_eq!(info.amount_awaiting_finalization, amount);
+
+		// Expiration is not configured, nothing to cancel
+		assert!(api.cancel_expired_receives(m)?.is_empty());
+
+		// Any receive is older than 0 days
+		api.set_auto_cancel_policy(AutoCancelPolicy {
+			receive_expire_days: Some(0),
+			..Default::default()
+		})?;
+
+		let (_, info) = api.retrieve_summary_info(m, true, 1)?;
+		assert_eq!(info.amount_awaiting_finalization, 0);
+
+		// Expired receive is still in the log until it is cancelled
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert!(txs[0].tx_type == TxLogEntryType::TxReceived);
+
+		let cancelled = api.cancel_expired_receives(m)?;
+		assert_eq!(cancelled, vec![txs[0].id]);
+
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert!(txs[0].tx_type == TxLogEntryType::TxReceivedCancelled);
+		let (_, outputs) = api.retrieve_outputs(m, false, true, None)?;
+		assert_eq!(outputs.len(), 0);
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn expire_receive() {
+	let test_dir = "test_output/expire_receive";
+	setup(test_dir);
+	if let Err(e) = expire_receive_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
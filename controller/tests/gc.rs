@@ -0,0 +1,136 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests garbage collection of the saved transaction files
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{GcArgs, InitTxArgs, Slate};
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Test removal of the orphaned and finished saved transactions
+fn gc_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 10, false);
+
+	let mut slate = Slate::blank(1, false);
+	let mut saved_txs = PathBuf::new();
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount: 2_000_000_000,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: true,
+			..Default::default()
+		};
+		slate = api.init_send_tx(m, &args, 1)?;
+		api.tx_lock_outputs(m, &slate, None, 0)?;
+
+		let mut w_lock = api.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		saved_txs = PathBuf::from(w.get_data_file_dir()).join("saved_txs");
+		Ok(())
+	})?;
+
+	let tx_file = saved_txs.join(format!("{}.mwctx", slate.id));
+	let orphan_file = saved_txs.join("orphan.mwctx");
+	assert!(tx_file.exists());
+	fs::write(&orphan_file, "00").unwrap();
+	thread::sleep(Duration::from_millis(100));
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		// Files are too young for the default policy
+		let res = api.gc_files(m, &GcArgs::default())?;
+		assert!(res.tx_files.is_empty());
+
+		let mut args = GcArgs {
+			retention_days: 0,
+			finished_txs: true,
+			dry_run: true,
+		};
+		let res = api.gc_files(m, &args)?;
+		assert_eq!(res.tx_files, vec!["orphan.mwctx".to_string()]);
+		assert_eq!(res.bytes, 2);
+		assert!(orphan_file.exists());
+
+		// Active transaction keeps its file
+		args.dry_run = false;
+		let res = api.gc_files(m, &args)?;
+		assert_eq!(res.tx_files, vec!["orphan.mwctx".to_string()]);
+		assert!(!orphan_file.exists());
+		assert!(tx_file.exists());
+
+		// Cancelled transaction doesn't need it any more
+		api.cancel_tx(m, None, Some(slate.id))?;
+		let res = api.gc_files(m, &args)?;
+		assert_eq!(res.tx_files, vec![format!("{}.mwctx", slate.id)]);
+		assert!(!tx_file.exists());
+		let (_, txs) = api.retrieve_txs(m, false, None, Some(slate.id))?;
+		assert!(txs[0].stored_tx.is_none());
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn gc_saved_txs() {
+	let test_dir = "test_output/gc_saved_txs";
+	setup(test_dir);
+	if let Err(e) = gc_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
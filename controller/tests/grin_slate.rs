@@ -0,0 +1,208 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Test a wallet file send/recieve with grin V4 slates
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core as core;
+
+use impls::test_framework::{self, LocalWalletClient};
+use impls::{PathToSlateGetter, PathToSlatePutter, SlateGetter, SlatePutter};
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+use grin_wallet_libwallet::{InitTxArgs, GRIN_V4_TARGET_SLATE_VERSION};
+
+use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
+use libwallet::proof::proofaddress;
+
+#[macro_use]
+mod common;
+use self::core::global;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+use grin_wallet_libwallet::slatepack::SlatePurpose;
+
+/// grin V4 slate exchange impl
+fn grin_slate_exchange_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	// few values to keep things shorter
+	let reward = core::consensus::MWC_FIRST_GROUP_REWARD;
+
+	let mut bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	let mut secret_1 = DalekSecretKey::from_bytes(&[0; 32]).unwrap();
+	let mut pub_key_2 = DalekPublicKey::from_bytes(&[0; 32]).unwrap();
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let mut w_lock = api.wallet_inst.lock();
+		let w = w_lock.lc_provider()?.wallet_inst()?;
+		let k = w.keychain(m)?;
+		pub_key_2 =
+			DalekPublicKey::from(&proofaddress::payment_proof_address_dalek_secret(&k, None)?);
+		Ok(())
+	})?;
+
+	// Grin slatepack address is the same key as the tor address
+	let grin_address = proofaddress::grin_slatepack_address(&pub_key_2)?;
+	assert!(grin_address.starts_with("tgrin1"));
+	let address_2 = proofaddress::ProvableAddress::from_str(&grin_address)?;
+	assert_eq!(
+		address_2.public_key,
+		proofaddress::ProvableAddress::from_tor_pub_key(&pub_key_2).public_key
+	);
+	assert_eq!(address_2.tor_public_key()?, pub_key_2);
+
+	let (send_file, receive_file) = (
+		format!("{}/standard_S1.json", test_dir),
+		format!("{}/standard_S2.json", test_dir),
+	);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount: reward * 2,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: true,
+			target_slate_version: Some(GRIN_V4_TARGET_SLATE_VERSION),
+			..Default::default()
+		};
+		let slate = api.init_send_tx(m, &args, 1)?;
+		assert!(slate.compact_slate);
+
+		{
+			let mut w_lock = api.wallet_inst.lock();
+			let w = w_lock.lc_provider()?.wallet_inst()?;
+			let k = w.keychain(m)?;
+			secret_1 = proofaddress::payment_proof_address_dalek_secret(&k, None)?;
+		}
+
+		PathToSlatePutter::build_grin_v4(Some((&send_file).into()), SlatePurpose::SendInitial)
+			.put_tx(&slate, &secret_1, true)?;
+		api.tx_lock_outputs(m, &slate, None, 0)?;
+		Ok(())
+	})?;
+
+	let s1: serde_json::Value =
+		serde_json::from_str(&fs::read_to_string(&send_file).unwrap()).unwrap();
+	assert_eq!(s1["ver"], "4:3");
+	assert_eq!(s1["sta"], "S1");
+	assert_eq!(s1["sigs"].as_array().unwrap().len(), 1);
+	assert!(s1.get("coms").is_none());
+
+	// wallet 2 receives file, completes, sends file back
+	wallet::controller::foreign_single_use(wallet2.clone(), mask2_i.clone(), |api| {
+		let slate_pkg =
+			PathToSlateGetter::build_form_path((&send_file).into()).get_tx(&secret_1)?;
+		assert!(slate_pkg.is_grin_v4());
+		let (slate, _, _, content, _) = slate_pkg.to_slate()?;
+		assert_eq!(content, SlatePurpose::SendInitial);
+		let slate = api.receive_tx(&slate, None, None, None)?;
+		PathToSlatePutter::build_grin_v4(Some((&receive_file).into()), SlatePurpose::SendResponse)
+			.put_tx(&slate, &secret_1, true)?;
+		Ok(())
+	})?;
+
+	let s2: serde_json::Value =
+		serde_json::from_str(&fs::read_to_string(&receive_file).unwrap()).unwrap();
+	assert_eq!(s2["sta"], "S2");
+	assert!(s2.get("amt").is_none());
+
+	// wallet 1 finalises and posts
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (slate, _, _, content, _) = PathToSlateGetter::build_form_path((&receive_file).into())
+			.get_tx(&secret_1)?
+			.to_slate()?;
+		assert_eq!(content, SlatePurpose::SendResponse);
+		let slate = api.finalize_tx(m, &slate)?;
+		api.post_tx(m, &slate.tx, false)?;
+		bh += 1;
+		Ok(())
+	})?;
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 3, false);
+	bh += 3;
+
+	// Check totals
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (wallet1_refreshed, wallet1_info) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(wallet1_refreshed);
+		assert_eq!(wallet1_info.last_confirmed_height, bh);
+		assert_eq!(wallet1_info.total, bh * reward - reward * 2);
+		Ok(())
+	})?;
+
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (wallet2_refreshed, wallet2_info) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(wallet2_refreshed);
+		assert_eq!(wallet2_info.last_confirmed_height, bh);
+		assert_eq!(wallet2_info.total, 2 * reward);
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn wallet_grin_slate_exchange() {
+	let test_dir = "test_output/grin_slate_exchange";
+	setup(test_dir);
+	if let Err(e) = grin_slate_exchange_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,70 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests that the API server passes the peer address to the handlers
+extern crate grin_wallet_controller as wallet;
+
+use grin_wallet_util::grin_api::{Handler, ResponseFuture, Router};
+use hyper::{Body, Request, Response};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use wallet::http_server::{HttpServer, RemoteAddr};
+
+struct RemoteAddrHandler;
+
+impl Handler for RemoteAddrHandler {
+	fn get(&self, req: Request<Body>) -> ResponseFuture {
+		let addr = match req.extensions().get::<RemoteAddr>() {
+			Some(RemoteAddr(addr)) => addr.to_string(),
+			None => "unknown".to_string(),
+		};
+		Box::pin(async move { Ok(Response::new(Body::from(addr))) })
+	}
+}
+
+fn get_remote_addr(server_addr: &SocketAddr) -> (SocketAddr, String) {
+	let mut stream = TcpStream::connect(server_addr).unwrap();
+	stream
+		.write_all(b"GET /v1/remote_addr HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+		.unwrap();
+	let mut response = String::new();
+	stream.read_to_string(&mut response).unwrap();
+	let body = response.split("\r\n\r\n").nth(1).unwrap_or("").to_string();
+	(stream.local_addr().unwrap(), body)
+}
+
+#[test]
+fn http_server_remote_addr() {
+	let mut router = Router::new();
+	router
+		.add_route("/v1/remote_addr", Arc::new(RemoteAddrHandler))
+		.unwrap();
+	let server_addr: SocketAddr = "127.0.0.1:33431".parse().unwrap();
+	let mut server = HttpServer::new();
+	let server_thread = server.start(server_addr, router, None).unwrap();
+	thread::sleep(Duration::from_millis(500));
+
+	for _ in 0..2 {
+		let (client_addr, body) = get_remote_addr(&server_addr);
+		assert_eq!(body, client_addr.to_string());
+	}
+
+	assert!(server.stop());
+	server_thread.join().unwrap();
+	assert!(TcpStream::connect(server_addr).is_err());
+	assert!(!server.stop());
+}
@@ -0,0 +1,78 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests merchant invoices store and invoice_paid event
+extern crate grin_wallet_controller as wallet;
+
+use chrono::Utc;
+use grin_wallet_libwallet::notifications::WalletEvent;
+use std::fs;
+use wallet::invoices::{invoice_paid_event, InvoiceStore, MerchantInvoice};
+
+fn tx_confirmed(slate_id: Option<&str>) -> WalletEvent {
+	WalletEvent::TxConfirmed {
+		tx_id: 3,
+		slate_id: slate_id.map(|id| id.to_string()),
+		tx_type: "TxReceived".to_string(),
+		amount_credited: 2_000_000_000,
+		amount_debited: 0,
+		height: 1000,
+		confirmations: 10,
+	}
+}
+
+#[test]
+fn merchant_invoice_paid() {
+	let test_dir = "test_output/merchant_invoice_paid";
+	let _ = fs::remove_dir_all(test_dir);
+	fs::create_dir_all(test_dir).unwrap();
+
+	let store = InvoiceStore::new(test_dir);
+	let id = "0436430c-2b02-624c-2032-570501212b00";
+	store
+		.add(MerchantInvoice {
+			id: id.to_string(),
+			amount: 2_000_000_000,
+			memo: Some("order 1".to_string()),
+			created: Utc::now(),
+			paid: None,
+		})
+		.unwrap();
+
+	// Other transactions are not reported
+	assert_eq!(invoice_paid_event(&store, &tx_confirmed(None)), None);
+	assert_eq!(
+		invoice_paid_event(
+			&store,
+			&tx_confirmed(Some("9ab0a1e8-3c5a-4c5e-8b1f-0c4a36f2d1a7"))
+		),
+		None
+	);
+
+	assert_eq!(
+		invoice_paid_event(&store, &tx_confirmed(Some(id))),
+		Some(WalletEvent::InvoicePaid {
+			invoice_id: id.to_string(),
+			amount: 2_000_000_000,
+			memo: Some("order 1".to_string()),
+			tx_id: 3,
+			height: 1000,
+			confirmations: 10,
+		})
+	);
+	assert!(store.load().unwrap()[0].paid.is_some());
+	// The event is reported once
+	assert_eq!(invoice_paid_event(&store, &tx_confirmed(Some(id))), None);
+
+	let _ = fs::remove_dir_all(test_dir);
+}
@@ -0,0 +1,58 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the message catalog of the display output
+extern crate grin_wallet_controller as wallet;
+
+use chrono::{TimeZone, Utc};
+use grin_wallet_libwallet::TxStatus;
+use wallet::locale::{self, tr, tr_fmt};
+
+#[test]
+fn locale_catalog() {
+	let ts = Utc.ymd(2021, 3, 12).and_hms(10, 20, 30);
+
+	// English is the default, the message id is the text
+	assert_eq!(locale::locale().name, "en-US");
+	assert_eq!(tr("Confirmed"), "Confirmed");
+	assert_eq!(locale::amount(1_500_000_000, false), "1.500000000");
+	assert_eq!(locale::datetime(&ts), "2021-03-12 10:20:30");
+
+	locale::set_locale("zh_cn").unwrap();
+	assert_eq!(locale::locale().name, "zh-CN");
+	assert_eq!(tr(&TxStatus::Confirmed.to_string()), "已确认");
+	assert_eq!(
+		tr_fmt("Immature Coinbase (< {})", &[&1440]),
+		"未成熟 Coinbase (< 1440)"
+	);
+	// Not translated messages are shown in English
+	assert_eq!(tr("Not in the catalog"), "Not in the catalog");
+
+	locale::set_locale("de").unwrap();
+	assert_eq!(locale::locale().name, "de-DE");
+	assert_eq!(locale::amount(1_500_000_000, false), "1,500000000");
+	assert_eq!(locale::datetime(&ts), "12.03.2021 10:20:30");
+	assert_eq!(
+		tr_fmt(
+			"Wallet Summary Info - Account '{}' as of height {}",
+			&[&"default", &100]
+		),
+		"Wallet-Übersicht - Konto 'default' bei Höhe 100"
+	);
+
+	assert!(locale::set_locale("xx-YY").is_err());
+	assert_eq!(locale::locale().name, "de-DE");
+
+	locale::set_locale("en-US").unwrap();
+	assert_eq!(tr("Confirmed"), "Confirmed");
+}
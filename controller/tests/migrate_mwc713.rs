@@ -0,0 +1,205 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the migration of the mwc713 wallet data
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use grin_wallet_util::grin_util::ZeroingString;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::migration;
+use libwallet::InitTxArgs;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn migrate_mwc713_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let seed_phrase = "affair pistol cancel crush garment candy ancient flag work \
+	                   market crush dry stand focus mutual weapon offer ceiling rival turn team spring \
+	                   where swift";
+	let seed_phrase = Some(ZeroingString::from(seed_phrase));
+
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		m_client,
+		miner,
+		miner_mask_i,
+		test_dir,
+		"miner",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let miner_mask = (&miner_mask_i).as_ref();
+
+	// plays the mwc713 wallet, it has the same data formats
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		seed_phrase.clone(),
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+
+	// migrate into here
+	create_wallet_and_add!(
+		_client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		seed_phrase.clone(),
+		&mut wallet_proxy,
+		false
+	);
+	let mask2 = (&mask2_i).as_ref();
+
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let cm = global::coinbase_maturity() as usize;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, miner.clone(), miner_mask, cm + 5, false);
+
+	let base_amount = 1_000_000_000;
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		api.create_account_path(m, "savings")?;
+		api.add_contact(
+			m,
+			"shop",
+			"xmgwbyjMEMBojnVadEkwVi1GyL1WPiVE5dziQf3TLedHdrVBPGw5",
+		)?;
+		Ok(())
+	})?;
+	wallet::controller::owner_single_use(Some(miner.clone()), miner_mask, None, |api, m| {
+		for amount in &[base_amount, base_amount * 2] {
+			let args = InitTxArgs {
+				src_acct_name: None,
+				amount: *amount,
+				minimum_confirmations: 2,
+				max_outputs: 500,
+				num_change_outputs: 1,
+				selection_strategy_is_use_all: false,
+				..Default::default()
+			};
+			let slate_i = api.init_send_tx(m, &args, 1)?;
+			let slate = m_client.send_tx_slate_direct("wallet1", &slate_i)?;
+			api.tx_lock_outputs(m, &slate, None, 0)?;
+			let slate = api.finalize_tx(m, &slate)?;
+			api.post_tx(m, &slate.tx, false)?;
+			let _ =
+				test_framework::award_blocks_to_wallet(&chain, miner.clone(), miner_mask, 2, false);
+		}
+		Ok(())
+	})?;
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, info) = api.retrieve_summary_info(m, true, 1)?;
+		assert_eq!(info.total, base_amount * 3);
+		Ok(())
+	})?;
+
+	// The wallet DB can't be opened twice
+	{
+		let mut w_lock = wallet1.lock();
+		w_lock.lc_provider()?.close_wallet(None)?;
+	}
+	let mwc713_dir = format!("{}/wallet1/wallet_data", test_dir);
+	let proofs_dir = Path::new(&mwc713_dir).join("saved_proofs");
+	fs::create_dir_all(&proofs_dir).unwrap();
+	fs::write(proofs_dir.join("mwc713.proof"), "{}").unwrap();
+
+	// Wrong password doesn't open the seed
+	assert!(impls::read_mwc713_wallet(&mwc713_dir, ZeroingString::from("wrong")).is_err());
+	let (seed, data) = impls::read_mwc713_wallet(&mwc713_dir, ZeroingString::from(""))?;
+	assert_eq!(
+		seed.to_mnemonic()?,
+		seed_phrase.as_ref().unwrap().to_string()
+	);
+	assert_eq!(data.txs.len(), 2);
+	assert_eq!(data.contacts.len(), 1);
+	assert_eq!(data.balance(), base_amount * 3);
+
+	let wallet2_dir = format!("{}/wallet2/wallet_data", test_dir);
+	assert_eq!(impls::copy_mwc713_files(&mwc713_dir, &wallet2_dir)?, 1);
+	assert!(Path::new(&wallet2_dir)
+		.join("saved_proofs")
+		.join("mwc713.proof")
+		.exists());
+	assert_eq!(impls::copy_mwc713_files(&mwc713_dir, &wallet2_dir)?, 0);
+
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let report = api.import_wallet_data(m, data.clone())?;
+		assert_eq!(report.accounts, 1);
+		assert_eq!(report.txs, 2);
+		assert_eq!(report.outputs, 2);
+		assert_eq!(report.contacts, 1);
+		assert!(report.skipped.is_empty());
+
+		api.scan(m, Some(1), false)?;
+		let (_, outputs) = api.retrieve_outputs(m, false, false, None)?;
+		assert_eq!(
+			migration::outputs_balance(outputs.iter().map(|o| &o.output)),
+			data.balance()
+		);
+		// The scan finds the imported outputs, no recovered transactions are added
+		let (_, txs) = api.retrieve_txs(m, false, None, None)?;
+		assert_eq!(txs.len(), 2);
+		assert!(txs.iter().all(|t| !t.recovered && t.confirmed));
+		assert!(outputs.iter().all(|o| o.output.tx_log_entry.is_some()));
+		assert!(api.accounts(m)?.iter().any(|a| a.label == "savings"));
+		assert_eq!(api.contacts(m)?.len(), 1);
+
+		// Repeated migration skips everything
+		let report = api.import_wallet_data(m, data.clone())?;
+		assert_eq!(report.txs + report.outputs + report.contacts, 0);
+		assert_eq!(report.skipped.len(), 5);
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn migrate_mwc713() {
+	let test_dir = "test_output/migrate_mwc713";
+	setup(test_dir);
+	if let Err(e) = migrate_mwc713_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
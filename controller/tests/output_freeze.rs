@@ -0,0 +1,183 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests frozen and tagged outputs
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{InitTxArgs, OutputQueryArgs, OutputStatus, OutputTagArgs};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Frozen outputs are skipped by the coin selection unless listed explicitly
+fn output_freeze_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 10, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let height = api.node_height(m)?.height;
+		let (_, outputs) = api.retrieve_outputs(m, false, true, None)?;
+		let eligible: Vec<_> = outputs
+			.into_iter()
+			.filter(|o| o.output.eligible_to_spend(height, 2))
+			.collect();
+		assert!(eligible.len() > 2);
+		let reward = eligible[0].output.value;
+		let commits: Vec<String> = eligible
+			.iter()
+			.map(|o| o.output.commit.clone().unwrap())
+			.collect();
+
+		// Everything except the first output is frozen, one output is tagged
+		for commit in &commits[1..] {
+			api.tag_output(
+				m,
+				&OutputTagArgs {
+					commit: commit.clone(),
+					frozen: Some(true),
+					..Default::default()
+				},
+			)?;
+		}
+		api.tag_output(
+			m,
+			&OutputTagArgs {
+				commit: commits[1].clone(),
+				tags: Some(vec!["swap".to_string(), " swap ".to_string()]),
+				..Default::default()
+			},
+		)?;
+
+		let query = OutputQueryArgs {
+			tag: Some("swap".to_string()),
+			..Default::default()
+		};
+		let (_, tagged) = api.query_outputs(m, false, &query)?;
+		assert_eq!(tagged.len(), 1);
+		assert_eq!(tagged[0].output.tags, vec!["swap".to_string()]);
+		assert!(tagged[0].output.frozen);
+		let query = OutputQueryArgs {
+			frozen: Some(true),
+			..Default::default()
+		};
+		assert_eq!(
+			api.query_outputs(m, false, &query)?.1.len(),
+			commits.len() - 1
+		);
+
+		// Only the unfrozen output can be selected
+		let mut args = InitTxArgs {
+			src_acct_name: None,
+			amount: reward * 3 / 2,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: false,
+			..Default::default()
+		};
+		assert!(api.init_send_tx(m, &args, 1).is_err());
+
+		args.amount = reward / 2;
+		let slate = api.init_send_tx(m, &args, 1)?;
+		api.tx_lock_outputs(m, &slate, None, 0)?;
+		let (_, outputs) = api.retrieve_outputs(m, false, false, None)?;
+		for o in outputs
+			.iter()
+			.filter(|o| o.output.status == OutputStatus::Locked)
+		{
+			assert_eq!(o.output.commit, Some(commits[0].clone()));
+		}
+
+		// Frozen output is spent if it is listed explicitly
+		args.outputs = Some(vec![commits[1].clone()]);
+		api.init_send_tx(m, &args, 1)?;
+
+		// Unfreeze, remove the tags, unknown output
+		api.tag_output(
+			m,
+			&OutputTagArgs {
+				commit: commits[1].clone(),
+				frozen: Some(false),
+				tags: Some(vec![]),
+			},
+		)?;
+		let query = OutputQueryArgs {
+			frozen: Some(true),
+			..Default::default()
+		};
+		assert_eq!(
+			api.query_outputs(m, false, &query)?.1.len(),
+			commits.len() - 2
+		);
+		assert!(api
+			.tag_output(
+				m,
+				&OutputTagArgs {
+					commit: format!("08{}", "00".repeat(32)),
+					frozen: Some(true),
+					tags: None,
+				},
+			)
+			.is_err());
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn output_freeze() {
+	let test_dir = "test_output/output_freeze";
+	setup(test_dir);
+	if let Err(e) = output_freeze_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
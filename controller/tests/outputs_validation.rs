@@ -0,0 +1,193 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests that the wallet finds the outputs spent by another instance with the same seed, with
+//! the differential update by blocks and with the full outputs validation
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+
+use grin_wallet_util::grin_core as core;
+use grin_wallet_util::grin_util as util;
+
+use self::core::consensus;
+use self::core::global;
+use grin_wallet_libwallet as libwallet;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{NodeClient, OutputStatus};
+use std::thread;
+use std::time::Duration;
+use util::ZeroingString;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+macro_rules! send_to_dest {
+	($a:expr, $m: expr, $b:expr, $c:expr, $d:expr) => {
+		test_framework::send_to_dest($a, $m, $b, $c, $d, false, None, 1)
+	};
+}
+
+// Commits of the wallet outputs with the status
+macro_rules! output_commits {
+	($wallet:expr, $mask:expr, $status:expr) => {{
+		let mut commits: Vec<String> = vec![];
+		wallet::controller::owner_single_use(Some($wallet.clone()), $mask, None, |api, m| {
+			let (_, outputs) = api.retrieve_outputs(m, true, false, None)?;
+			commits = outputs
+				.into_iter()
+				.filter(|o| o.output.status == $status)
+				.filter_map(|o| o.output.commit)
+				.collect();
+			Ok(())
+		})?;
+		commits
+	}};
+}
+
+fn outputs_validation_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let seed_phrase = "affair pistol cancel crush garment candy ancient flag work \
+	                   market crush dry stand focus mutual weapon offer ceiling rival turn team spring \
+	                   where swift";
+	let seed_phrase = Some(ZeroingString::from(seed_phrase));
+
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		m_client,
+		miner,
+		miner_mask_i,
+		test_dir,
+		"miner",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let miner_mask = (&miner_mask_i).as_ref();
+
+	// Two instances of the same wallet, only the first one sends
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		seed_phrase,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		seed_phrase,
+		&mut wallet_proxy,
+		false
+	);
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let amount = consensus::MWC_FIRST_GROUP_REWARD / 10;
+	let _ = test_framework::award_blocks_to_wallet(&chain, miner.clone(), miner_mask, 10, false);
+	for i in 1..4 {
+		send_to_dest!(
+			miner.clone(),
+			miner_mask,
+			m_client.clone(),
+			"wallet1",
+			amount * i
+		)?;
+	}
+	let _ = test_framework::award_blocks_to_wallet(&chain, miner.clone(), miner_mask, 3, false);
+
+	// The first update of the second instance validates all outputs
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		api.scan(m, None, false)?;
+		Ok(())
+	})?;
+	let (tip_height, _, _) = client2.get_chain_tip()?;
+	{
+		wallet_inst!(wallet2, w);
+		assert_eq!(w.last_outputs_validation_height()?, tip_height);
+	}
+	assert_eq!(
+		output_commits!(wallet2, mask2, OutputStatus::Unspent).len(),
+		3
+	);
+
+	// Few new blocks, the update checks the inputs of the blocks
+	send_to_dest!(wallet1.clone(), mask1, client1.clone(), "miner", amount)?;
+	let _ = test_framework::award_blocks_to_wallet(&chain, miner.clone(), miner_mask, 1, false);
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (refreshed, _) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		Ok(())
+	})?;
+	let (tip_height, _, _) = client2.get_chain_tip()?;
+	{
+		wallet_inst!(wallet2, w);
+		assert_eq!(w.last_outputs_validation_height()?, tip_height);
+	}
+	let spent = output_commits!(wallet1, mask1, OutputStatus::Spent);
+	assert!(!spent.is_empty());
+	let spent_before = spent.len();
+	let unspent = output_commits!(wallet2, mask2, OutputStatus::Unspent);
+	assert!(spent.iter().all(|c| !unspent.contains(c)));
+
+	// The node moved too far for the blocks, all outputs are validated
+	send_to_dest!(wallet1.clone(), mask1, client1.clone(), "miner", amount)?;
+	let _ = test_framework::award_blocks_to_wallet(&chain, miner.clone(), miner_mask, 20, false);
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (refreshed, _) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		Ok(())
+	})?;
+	let (tip_height, _, _) = client2.get_chain_tip()?;
+	{
+		wallet_inst!(wallet2, w);
+		assert_eq!(w.last_outputs_validation_height()?, tip_height);
+	}
+	let spent = output_commits!(wallet1, mask1, OutputStatus::Spent);
+	assert!(spent.len() > spent_before);
+	let unspent = output_commits!(wallet2, mask2, OutputStatus::Unspent);
+	assert!(spent.iter().all(|c| !unspent.contains(c)));
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn outputs_validation() {
+	let test_dir = "test_output/outputs_validation";
+	setup(test_dir);
+	if let Err(e) = outputs_validation_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
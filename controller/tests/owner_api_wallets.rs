@@ -0,0 +1,179 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the routing of the owner API V3 requests to the additional wallets
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_api::{ECDHPubkey, EncryptedRequest, EncryptedResponse, JsonId};
+use grin_wallet_util::grin_api::Handler;
+use grin_wallet_util::grin_core::global;
+use grin_wallet_util::grin_util::secp::key::{PublicKey, SecretKey};
+use grin_wallet_util::grin_util::{from_hex, static_secp_instance, Mutex};
+use hyper::{Body, Request};
+use impls::test_framework::{self, LocalWalletClient};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use wallet::controller::OwnerAPIHandlerV3;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn post(handler: &dyn Handler, body: &Value) -> Value {
+	let req = Request::post("/v3/owner")
+		.body(Body::from(body.to_string()))
+		.unwrap();
+	let resp = futures::executor::block_on(handler.post(req)).unwrap();
+	let body = futures::executor::block_on(hyper::body::to_bytes(resp.into_body())).unwrap();
+	serde_json::from_slice(&body).unwrap()
+}
+
+// Negotiate the shared key of the secure API with the wallet
+fn init_secure_api(handler: &dyn Handler, wallet_id: Option<&str>) -> SecretKey {
+	let sec_key_str = "e00dcc4a009e3427c6b1e1a550c538179d46f3827a13ed74c759c860761caf1e";
+	let sec_key = SecretKey::from_slice(&from_hex(sec_key_str).unwrap()).unwrap();
+	let secp_inst = static_secp_instance();
+	let pub_key = {
+		let secp = secp_inst.lock();
+		PublicKey::from_secret_key(&secp, &sec_key).unwrap()
+	};
+	let mut req = json!({
+		"jsonrpc": "2.0",
+		"method": "init_secure_api",
+		"params": {
+			"ecdh_pubkey": ECDHPubkey { ecdh_pubkey: pub_key },
+		},
+		"id": 1
+	});
+	if let Some(wallet_id) = wallet_id {
+		req["wallet_id"] = json!(wallet_id);
+	}
+	let res = post(handler, &req);
+	let wallet_key: ECDHPubkey = serde_json::from_value(res["result"]["Ok"].clone()).unwrap();
+
+	let secp = secp_inst.lock();
+	let mut shared_pubkey = wallet_key.ecdh_pubkey;
+	shared_pubkey.mul_assign(&secp, &sec_key).unwrap();
+	let x_coord = shared_pubkey.serialize_vec(true);
+	SecretKey::from_slice(&x_coord[1..]).unwrap()
+}
+
+// Encrypted 'retrieve_summary_info', returns the total amount of the wallet
+fn wallet_total(handler: &dyn Handler, wallet_id: Option<&str>, key: &SecretKey) -> Value {
+	let req = json!({
+		"jsonrpc": "2.0",
+		"method": "retrieve_summary_info",
+		"params": {
+			"token": null,
+			"refresh_from_node": true,
+			"minimum_confirmations": 1
+		},
+		"id": 1
+	});
+	let enc_req = EncryptedRequest::from_json(&JsonId::IntId(1), &req, key).unwrap();
+	let mut req = enc_req.as_json_value().unwrap();
+	if let Some(wallet_id) = wallet_id {
+		req["wallet_id"] = json!(wallet_id);
+	}
+	let res = post(handler, &req);
+	if res["error"] != json!(null) {
+		return res;
+	}
+	let enc_resp: EncryptedResponse = serde_json::from_value(res).unwrap();
+	let res = enc_resp.decrypt(key).unwrap();
+	res["result"]["Ok"][1]["total"].clone()
+}
+
+fn owner_api_wallets_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		_client2,
+		wallet2,
+		_mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 5, false);
+
+	let mut wallets = HashMap::new();
+	wallets.insert("second".to_string(), wallet2.clone());
+	let handler = OwnerAPIHandlerV3::new(wallet1.clone(), Arc::new(Mutex::new(None)), None, false)
+		.with_wallets(wallets, None);
+
+	// The second wallet has own secure API session, the default wallet is not initialized yet
+	let key2 = init_secure_api(&handler, Some("second"));
+	assert_eq!(wallet_total(&handler, Some("second"), &key2), json!("0"));
+	let res = wallet_total(&handler, None, &key2);
+	assert_eq!(res["error"]["code"], json!(-32001));
+
+	let key1 = init_secure_api(&handler, None);
+	let total = wallet_total(&handler, None, &key1);
+	assert_ne!(total, json!("0"));
+	assert!(total.is_string());
+	// The shared keys are per wallet
+	let res = wallet_total(&handler, Some("second"), &key1);
+	assert_eq!(res["error"]["code"], json!(-32002));
+	assert_eq!(wallet_total(&handler, Some("second"), &key2), json!("0"));
+
+	// Unknown wallet id is rejected, even before the encryption is checked
+	let res = wallet_total(&handler, Some("third"), &key2);
+	assert_eq!(res["error"]["code"], json!(-32004));
+	assert_eq!(res["error"]["message"], json!("Unknown wallet_id 'third'"));
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn owner_api_wallets() {
+	let test_dir = "test_output/owner_api_wallets";
+	setup(test_dir);
+	if let Err(e) = owner_api_wallets_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
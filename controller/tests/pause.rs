@@ -0,0 +1,132 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the owner API maintenance mode
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::api_impl::owner;
+use libwallet::{ErrorKind, InitTxArgs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn pause_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let cm = global::coinbase_maturity() as usize;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, cm + 3, false);
+
+	let is_paused_error = |e: libwallet::Error| match e.kind() {
+		ErrorKind::WalletPaused => true,
+		_ => false,
+	};
+
+	// The state changing calls are rejected while paused and work again after resume
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		api.pause()?;
+		assert!(api.is_paused());
+		let res = api.create_account_path(m, "paused");
+		assert!(is_paused_error(res.unwrap_err()));
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount: 1_000_000_000,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: false,
+			..Default::default()
+		};
+		let res = api.init_send_tx(m, &args, 1);
+		assert!(is_paused_error(res.unwrap_err()));
+		let res = api.scan(m, Some(1), false);
+		assert!(is_paused_error(res.unwrap_err()));
+		// Reading is allowed, but the data is not refreshed from the node
+		let (refreshed, _) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(!refreshed);
+
+		api.resume()?;
+		assert!(!api.is_paused());
+		api.create_account_path(m, "resumed")?;
+		let (refreshed, _) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		let slate = api.init_send_tx(m, &args, 1)?;
+		api.tx_lock_outputs(m, &slate, None, 0)?;
+		api.cancel_tx(m, None, Some(slate.id))?;
+		Ok(())
+	})?;
+
+	// Pause waits until the operation in progress is finished
+	let operation = owner::begin_wallet_operation()?;
+	let paused = Arc::new(AtomicBool::new(false));
+	let paused_clone = paused.clone();
+	let pause_thread = thread::spawn(move || {
+		owner::set_wallet_paused(true);
+		paused_clone.store(true, Ordering::Relaxed);
+	});
+	thread::sleep(Duration::from_millis(500));
+	assert!(!paused.load(Ordering::Relaxed));
+	drop(operation);
+	pause_thread.join().unwrap();
+	assert!(paused.load(Ordering::Relaxed));
+	assert!(owner::begin_wallet_operation().is_err());
+	owner::set_wallet_paused(false);
+	assert!(owner::begin_wallet_operation().is_ok());
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn owner_pause_resume() {
+	let test_dir = "test_output/owner_pause_resume";
+	setup(test_dir);
+	if let Err(e) = pause_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
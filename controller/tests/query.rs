@@ -0,0 +1,170 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the order and the pagination of query_txs and query_outputs
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{InitTxArgs, OutputQueryArgs, OutputStatus, TxLogEntryType, TxQueryArgs};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn query_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let blocks = 10;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, blocks, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, all) = api.query_outputs(m, true, &OutputQueryArgs::default())?;
+		assert_eq!(all.len(), blocks);
+		// Sorted by the derivation index
+		assert!(all
+			.windows(2)
+			.all(|w| w[0].output.n_child < w[1].output.n_child));
+
+		let page = |offset: Option<u32>, limit: Option<u32>| OutputQueryArgs {
+			offset,
+			limit,
+			..Default::default()
+		};
+		let (_, res) = api.query_outputs(m, false, &page(Some(3), Some(4)))?;
+		assert_eq!(
+			res.iter().map(|o| o.output.n_child).collect::<Vec<_>>(),
+			all[3..7]
+				.iter()
+				.map(|o| o.output.n_child)
+				.collect::<Vec<_>>()
+		);
+		assert_eq!(res[0].commit, all[3].commit);
+		let (_, res) = api.query_outputs(m, false, &page(Some(8), Some(4)))?;
+		assert_eq!(res.len(), 2);
+		assert!(api
+			.query_outputs(m, false, &page(Some(blocks as u32), None))?
+			.1
+			.is_empty());
+		assert!(api
+			.query_outputs(m, false, &page(None, Some(0)))?
+			.1
+			.is_empty());
+
+		// Pagination is applied after the filters
+		let query = OutputQueryArgs {
+			min_height: Some(3),
+			offset: Some(1),
+			limit: Some(2),
+			..Default::default()
+		};
+		let (_, res) = api.query_outputs(m, false, &query)?;
+		assert_eq!(res.len(), 2);
+		assert!(res.iter().all(|o| o.output.height >= 3));
+		assert_eq!(res[0].output.n_child, all[3].output.n_child);
+
+		// Unconfirmed outputs of the cancelled transactions are not listed
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount: 1_000_000_000,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: false,
+			..Default::default()
+		};
+		let slate = api.init_send_tx(m, &args, 1)?;
+		api.tx_lock_outputs(m, &slate, None, 0)?;
+		let unconfirmed = OutputQueryArgs {
+			status: Some(vec![OutputStatus::Unconfirmed]),
+			..Default::default()
+		};
+		assert_eq!(api.query_outputs(m, false, &unconfirmed)?.1.len(), 1);
+		api.cancel_tx(m, None, Some(slate.id))?;
+		assert!(api.query_outputs(m, false, &unconfirmed)?.1.is_empty());
+
+		let (_, txs) = api.query_txs(m, false, &TxQueryArgs::default())?;
+		assert!(txs.len() > blocks);
+		// Sorted by the creation time
+		assert!(txs.windows(2).all(|w| w[0].creation_ts <= w[1].creation_ts));
+		let query = TxQueryArgs {
+			tx_types: Some(vec![TxLogEntryType::ConfirmedCoinbase]),
+			offset: Some(2),
+			limit: Some(3),
+			..Default::default()
+		};
+		let (_, res) = api.query_txs(m, false, &query)?;
+		let coinbase: Vec<_> = txs
+			.iter()
+			.filter(|t| t.tx_type == TxLogEntryType::ConfirmedCoinbase)
+			.collect();
+		assert_eq!(
+			res.iter().map(|t| t.id).collect::<Vec<_>>(),
+			coinbase[2..5].iter().map(|t| t.id).collect::<Vec<_>>()
+		);
+		let query = TxQueryArgs {
+			offset: Some(txs.len() as u32),
+			..Default::default()
+		};
+		assert!(api.query_txs(m, false, &query)?.1.is_empty());
+		let query = TxQueryArgs {
+			limit: Some(0),
+			..Default::default()
+		};
+		assert!(api.query_txs(m, false, &query)?.1.is_empty());
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn query_txs_outputs() {
+	let test_dir = "test_output/query_txs_outputs";
+	setup(test_dir);
+	if let Err(e) = query_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
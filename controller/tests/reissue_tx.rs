@@ -0,0 +1,190 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests reissue of a stuck send with a higher fee
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{InitTxArgs, ReissueTxArgs, Slate, TxLogEntryType};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Sender reissues the stuck transaction and the new one gets confirmed
+fn reissue_tx_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	let args = InitTxArgs {
+		src_acct_name: None,
+		amount: 2_000_000_000,
+		minimum_confirmations: 2,
+		max_outputs: 500,
+		num_change_outputs: 1,
+		selection_strategy_is_use_all: true,
+		..Default::default()
+	};
+
+	// Slate is sent, but the transaction never makes it to the chain
+	let mut slate = Slate::blank(1, false);
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		slate = api.init_send_tx(m, &args, 1)?;
+		api.tx_lock_outputs(m, &slate, None, 0)?;
+		Ok(())
+	})?;
+	let stuck_slate = slate.clone();
+
+	let mut stuck_tx = None;
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(stuck_slate.id))?;
+		stuck_tx = txs.into_iter().next();
+		Ok(())
+	})?;
+	let stuck_tx = stuck_tx.unwrap();
+
+	let mut reissue_args = ReissueTxArgs {
+		tx_id: stuck_tx.id,
+		fee_multiplier: 1.0,
+		target_slate_version: None,
+		slatepack_recipient: None,
+		force: false,
+	};
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		// Fee must grow
+		assert!(api.reissue_tx(m, &reissue_args).is_err());
+
+		// Inputs can't pay such fee, the original transaction stays as it was
+		reissue_args.fee_multiplier = 1_000_000.0;
+		assert!(api.reissue_tx(m, &reissue_args).is_err());
+		let (_, txs) = api.retrieve_txs(m, true, Some(stuck_tx.id), None)?;
+		assert_eq!(txs[0].tx_type, TxLogEntryType::TxSent);
+
+		reissue_args.fee_multiplier = 2.0;
+		slate = api.reissue_tx(m, &reissue_args)?;
+		Ok(())
+	})?;
+	assert_ne!(slate.id, stuck_slate.id);
+	assert_eq!(slate.amount, stuck_slate.amount);
+	assert!(slate.fee >= stuck_slate.fee * 2);
+
+	// The original transaction is cancelled, its inputs are locked by the new one
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, Some(stuck_tx.id), None)?;
+		assert_eq!(txs[0].tx_type, TxLogEntryType::TxSentCancelled);
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert_eq!(txs.len(), 1);
+		assert_eq!(txs[0].tx_type, TxLogEntryType::TxSent);
+		assert_eq!(txs[0].input_commits, stuck_tx.input_commits);
+		Ok(())
+	})?;
+
+	// New slate goes through the regular flow
+	slate = client1.send_tx_slate_direct("wallet2", &slate)?;
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		slate = api.finalize_tx(m, &slate)?;
+		api.post_tx(m, &slate.tx, false)?;
+		Ok(())
+	})?;
+
+	// Posted transaction is not reissued without force, it stays as it was
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		reissue_args.tx_id = txs[0].id;
+		assert!(api.reissue_tx(m, &reissue_args).is_err());
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert_eq!(txs[0].tx_type, TxLogEntryType::TxSent);
+		assert!(!txs[0].confirmed);
+		Ok(())
+	})?;
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 3, false);
+
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (_, wallet2_info) = api.retrieve_summary_info(m, true, 1)?;
+		assert_eq!(wallet2_info.amount_currently_spendable, stuck_slate.amount);
+		Ok(())
+	})?;
+
+	// Confirmed transaction can't be reissued
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert!(txs[0].confirmed);
+		reissue_args.tx_id = txs[0].id;
+		assert!(api.reissue_tx(m, &reissue_args).is_err());
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn reissue_tx() {
+	let test_dir = "test_output/reissue_tx";
+	setup(test_dir);
+	if let Err(e) = reissue_tx_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -297,3 +297,100 @@ fn wallet_file_repost() {
 	}
 	clean_output_dir(test_dir);
 }
+
+/// Automatic repost of the finalized but not posted transaction
+fn auto_repost_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let reward = core::consensus::MWC_FIRST_GROUP_REWARD;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 10, false);
+
+	// Finalized, but never posted
+	let mut slate = Slate::blank(2, false);
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount: reward,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: true,
+			..Default::default()
+		};
+		let slate_i = api.init_send_tx(m, &args, 1)?;
+		slate = client1.send_tx_slate_direct("wallet2", &slate_i)?;
+		api.tx_lock_outputs(m, &slate, None, 0)?;
+		slate = api.finalize_tx(m, &slate)?;
+		Ok(())
+	})?;
+
+	libwallet::owner::set_auto_repost_interval(Some(1));
+	// First repost waits for the interval
+	let reposted = libwallet::owner::auto_repost_txs(wallet1.clone(), mask1, &None)?;
+	assert!(reposted.is_empty());
+	thread::sleep(Duration::from_millis(1100));
+	let reposted = libwallet::owner::auto_repost_txs(wallet1.clone(), mask1, &None)?;
+	assert_eq!(reposted.len(), 1);
+	// Next one is delayed by the backoff
+	let reposted = libwallet::owner::auto_repost_txs(wallet1.clone(), mask1, &None)?;
+	assert!(reposted.is_empty());
+	libwallet::owner::set_auto_repost_interval(None);
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 3, false);
+
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (refreshed, info) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+		assert_eq!(info.total, reward);
+		let (_, txs) = api.retrieve_txs(m, false, None, Some(slate.id))?;
+		assert!(txs[0].confirmed);
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn wallet_auto_repost() {
+	let test_dir = "test_output/auto_repost";
+	setup(test_dir);
+	if let Err(e) = auto_repost_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,169 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests reconstruction of the transactions history during the restore
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use grin_wallet_util::grin_util::ZeroingString;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{InitTxArgs, Slate, TxLogEntryType};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+fn restore_history_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let seed_phrase = "affair pistol cancel crush garment candy ancient flag work \
+	                   market crush dry stand focus mutual weapon offer ceiling rival turn team spring \
+	                   where swift";
+	let seed_phrase = Some(ZeroingString::from(seed_phrase));
+
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		m_client,
+		miner,
+		miner_mask_i,
+		test_dir,
+		"miner",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let miner_mask = (&miner_mask_i).as_ref();
+
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		seed_phrase.clone(),
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+
+	// restore into here
+	create_wallet_and_add!(
+		_client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		seed_phrase,
+		&mut wallet_proxy,
+		false
+	);
+	let mask2 = (&mask2_i).as_ref();
+
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let cm = global::coinbase_maturity() as usize;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, miner.clone(), miner_mask, cm + 5, false);
+
+	let base_amount = 1_000_000_000;
+	let mut slates: Vec<Slate> = vec![];
+	wallet::controller::owner_single_use(Some(miner.clone()), miner_mask, None, |api, m| {
+		for amount in &[base_amount, base_amount * 2, base_amount * 5] {
+			let args = InitTxArgs {
+				src_acct_name: None,
+				amount: *amount,
+				minimum_confirmations: 2,
+				max_outputs: 500,
+				num_change_outputs: 1,
+				selection_strategy_is_use_all: false,
+				..Default::default()
+			};
+			let slate_i = api.init_send_tx(m, &args, 1)?;
+			let slate = m_client.send_tx_slate_direct("wallet1", &slate_i)?;
+			api.tx_lock_outputs(m, &slate, None, 0)?;
+			slates.push(api.finalize_tx(m, &slate)?);
+		}
+		Ok(())
+	})?;
+
+	// Two payments in the same block, the third one in its own block
+	test_framework::award_block_to_wallet(
+		&chain,
+		vec![&slates[0].tx, &slates[1].tx],
+		miner.clone(),
+		miner_mask,
+	)?;
+	test_framework::award_block_to_wallet(&chain, vec![&slates[2].tx], miner.clone(), miner_mask)?;
+	let _ = test_framework::award_blocks_to_wallet(&chain, miner.clone(), miner_mask, cm, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, None)?;
+		assert_eq!(txs.len(), 3);
+		assert!(txs.iter().all(|t| !t.recovered));
+		Ok(())
+	})?;
+
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		api.scan(m, None, false)?;
+		let (_, info) = api.retrieve_summary_info(m, false, 1)?;
+		assert_eq!(info.total, base_amount * 8);
+
+		let (_, mut txs) = api.retrieve_txs(m, false, None, None)?;
+		txs.sort_by_key(|t| t.output_height);
+		assert_eq!(txs.len(), 2);
+		assert!(txs.iter().all(|t| t.recovered && t.confirmed));
+		assert!(txs.iter().all(|t| t.tx_type == TxLogEntryType::TxReceived));
+
+		// The block has two kernels, the payments can't be told apart
+		assert_eq!(txs[0].num_outputs, 2);
+		assert_eq!(txs[0].amount_credited, base_amount * 3);
+		assert_eq!(txs[0].kernel_excess, None);
+
+		assert_eq!(txs[1].num_outputs, 1);
+		assert_eq!(txs[1].amount_credited, base_amount * 5);
+		assert_eq!(
+			txs[1].kernel_excess,
+			Some(slates[2].tx.body.kernels[0].excess)
+		);
+		assert_eq!(txs[1].creation_ts, txs[1].confirmation_ts.unwrap());
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn restore_history() {
+	let test_dir = "test_output/restore_history";
+	setup(test_dir);
+	if let Err(e) = restore_history_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
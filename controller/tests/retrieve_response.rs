@@ -0,0 +1,145 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests rebuilding of the lost response slate
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{InitTxArgs, Slate};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Receiver lost the response and sends the rebuilt one
+fn retrieve_response_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	// Regular and compact slates
+	for target_slate_version in &[None, Some(4)] {
+		let args = InitTxArgs {
+			src_acct_name: None,
+			amount: 2_000_000_000,
+			minimum_confirmations: 2,
+			max_outputs: 500,
+			num_change_outputs: 1,
+			selection_strategy_is_use_all: true,
+			target_slate_version: *target_slate_version,
+			..Default::default()
+		};
+
+		let mut slate = Slate::blank(1, false);
+		wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+			slate = api.init_send_tx(m, &args, 1)?;
+			api.tx_lock_outputs(m, &slate, None, 0)?;
+			Ok(())
+		})?;
+		let send_slate = slate.clone();
+
+		// Response is lost
+		let _ = client1.send_tx_slate_direct("wallet2", &send_slate)?;
+
+		wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+			slate = api.retrieve_response_slate(m, &send_slate.id)?;
+			Ok(())
+		})?;
+		assert_eq!(slate.id, send_slate.id);
+		assert_eq!(slate.compact_slate, send_slate.compact_slate);
+
+		// Rebuilt response finalizes the transaction
+		wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+			slate = api.finalize_tx(m, &slate)?;
+			api.post_tx(m, &slate.tx, false)?;
+			Ok(())
+		})?;
+
+		let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 3, false);
+
+		// Confirmed transaction doesn't need the response any more
+		wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+			api.retrieve_txs(m, true, None, None)?;
+			assert!(api.retrieve_response_slate(m, &send_slate.id).is_err());
+			Ok(())
+		})?;
+	}
+
+	// Unknown transaction
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let id = Slate::blank(2, false).id;
+		assert!(api.retrieve_response_slate(m, &id).is_err());
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn retrieve_response() {
+	let test_dir = "test_output/retrieve_response";
+	setup(test_dir);
+	if let Err(e) = retrieve_response_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
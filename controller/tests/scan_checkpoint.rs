@@ -0,0 +1,147 @@
+// Copyright 2020 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests resuming of the interrupted scan from the checkpoint
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{NodeClient, ScanCheckpoint};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Test that scan continues from the saved checkpoint
+fn scan_checkpoint_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	let mut outputs = vec![];
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		outputs = api
+			.retrieve_outputs(m, false, true, None)?
+			.1
+			.into_iter()
+			.map(|o| o.output)
+			.collect();
+		Ok(())
+	})?;
+	assert_eq!(outputs.len(), bh as usize);
+	outputs.sort_by_key(|o| o.mmr_index);
+
+	// Scan was interrupted after the first half of the outputs. Checkpoint has no outputs, so
+	// if the scan is resumed, the outputs from the first half are not restored
+	let (tip_height, tip_hash, _) = client1.get_chain_tip()?;
+	{
+		wallet_inst!(wallet1, w);
+		let mut batch = w.batch(mask1)?;
+		for o in &outputs {
+			batch.delete(&o.key_id, &o.mmr_index)?;
+		}
+		batch.save_scan_checkpoint(&ScanCheckpoint {
+			start_height: 1,
+			end_height: tip_height,
+			end_hash: tip_hash,
+			last_pmmr_index: outputs[4].mmr_index.unwrap(),
+			last_height: outputs[4].height,
+			outputs: vec![],
+			self_spend_candidates: vec![],
+		})?;
+		batch.commit()?;
+	}
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		api.scan_resume(m, None, false)?;
+		let (_, restored) = api.retrieve_outputs(m, false, false, None)?;
+		assert_eq!(restored.len(), 5);
+		assert!(restored
+			.iter()
+			.all(|o| o.output.mmr_index > outputs[4].mmr_index));
+		Ok(())
+	})?;
+
+	// Completed scan removes the checkpoint
+	{
+		wallet_inst!(wallet1, w);
+		assert!(w.scan_checkpoint()?.is_none());
+	}
+
+	// Regular scan starts over and restores everything
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		api.scan(m, None, false)?;
+		let (_, restored) = api.retrieve_outputs(m, false, false, None)?;
+		assert_eq!(restored.len(), bh as usize);
+		Ok(())
+	})?;
+
+	// Number of the scan workers doesn't change the result
+	for threads in &[1, 16] {
+		libwallet::set_scan_threads(Some(*threads));
+		wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+			api.scan(m, None, false)?;
+			let (_, restored) = api.retrieve_outputs(m, false, false, None)?;
+			assert_eq!(restored.len(), bh as usize);
+			Ok(())
+		})?;
+	}
+	libwallet::set_scan_threads(None);
+	assert_eq!(libwallet::get_scan_threads(), 4);
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn scan_checkpoint() {
+	let test_dir = "test_output/scan_checkpoint";
+	setup(test_dir);
+	if let Err(e) = scan_checkpoint_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -63,13 +63,19 @@ fn slate_from_packed(
 		.map_err(|e| {
 			libwallet::ErrorKind::GenericError(format!("Unable to read the slate, {}", e))
 		})? {
-		// Plain slate, V2 or V3
-		SlateGetData::PlainSlate(_) => {
+		// Plain slate, V2, V3 or grin V4
+		SlateGetData::PlainSlate(_) | SlateGetData::GrinV4(_, _) => {
 			return Err(libwallet::ErrorKind::GenericError(
 				"Not found expected encrypted slatepack, found in plain format only".to_string(),
 			)
 			.into())
 		}
+		SlateGetData::Envelope(_, _, _) => {
+			return Err(libwallet::ErrorKind::GenericError(
+				"Not found expected slatepack, found the slate envelope".to_string(),
+			)
+			.into())
+		}
 		SlateGetData::Slatepack(sp) => Ok(sp),
 	}
 }
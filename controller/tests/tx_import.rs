@@ -0,0 +1,128 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests import of the transactions history
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::tx_import::{self, TxImportFormat};
+use libwallet::{TxLogEntryType, TxStatus};
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+const HISTORY_CSV: &str = "Id,Type,Date,amount_credited,amount_debited,fee,confirmed,label
+0fd2e1b7-5b24-4e6c-a5d4-0e7b5d0f4c3a,Received Tx,2020-03-12 10:20:30,2000000000,0,,true,old deposit
+5c0f6ddb-11e3-4d1e-8b6e-4a4d0a3b9d21,sent cancelled,2020-04-01,0,1008000000,8000000,false,
+0fd2e1b7-5b24-4e6c-a5d4-0e7b5d0f4c3a,received,2020-03-12,2000000000,0,,true,
+,received,2020-05-01,1000000000,0,,false,
+";
+
+/// Imported records are merged into the tx log, the conflicts are skipped
+fn tx_import_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 5, false);
+
+	let mut columns = HashMap::new();
+	columns.insert("tx_slate_id".to_string(), "Id".to_string());
+	let records = tx_import::parse_tx_import(HISTORY_CSV, &TxImportFormat::Csv, &columns)?;
+	assert_eq!(records.len(), 4);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, info_before) = api.retrieve_summary_info(m, true, 1)?;
+		let (_, txs_before) = api.retrieve_txs(m, false, None, None)?;
+
+		// Dry run doesn't change anything
+		let report = api.import_txs(m, records.clone(), true)?;
+		assert_eq!(report.imported_count, 2);
+		assert!(report.imported.is_empty());
+		let (_, txs) = api.retrieve_txs(m, false, None, None)?;
+		assert_eq!(txs.len(), txs_before.len());
+
+		let report = api.import_txs(m, records.clone(), false)?;
+		assert_eq!(report.imported.len(), 2);
+		let skipped: Vec<usize> = report.skipped.iter().map(|s| s.record).collect();
+		assert_eq!(skipped, vec![3, 4]);
+		assert_eq!(report.skipped[0].existing_tx_id, None);
+
+		let (_, txs) = api.retrieve_txs(m, false, None, None)?;
+		assert_eq!(txs.len(), txs_before.len() + 2);
+		let received = txs.iter().find(|t| t.id == report.imported[0]).unwrap();
+		assert_eq!(received.tx_type, TxLogEntryType::TxReceived);
+		assert!(received.confirmed);
+		assert_eq!(received.amount_credited, 2_000_000_000);
+		assert_eq!(received.label, Some("old deposit".to_string()));
+		let cancelled = txs.iter().find(|t| t.id == report.imported[1]).unwrap();
+		assert_eq!(cancelled.tx_type, TxLogEntryType::TxSentCancelled);
+		assert_eq!(cancelled.current_status(), TxStatus::Cancelled);
+
+		// The history doesn't change the balance
+		let (_, info) = api.retrieve_summary_info(m, true, 1)?;
+		assert_eq!(info.total, info_before.total);
+
+		// Import of the same file again finds the imported transactions
+		let report = api.import_txs(m, records.clone(), false)?;
+		assert!(report.imported.is_empty());
+		assert_eq!(report.skipped.len(), 4);
+		assert_eq!(report.skipped[0].existing_tx_id, Some(received.id));
+		assert_eq!(report.skipped[1].existing_tx_id, Some(cancelled.id));
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn tx_import() {
+	let test_dir = "test_output/tx_import";
+	setup(test_dir);
+	if let Err(e) = tx_import_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
@@ -0,0 +1,117 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests local labels and memos of the transactions
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{TxLabelArgs, TxQueryArgs};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Label and memo are stored, filtered and removed
+fn tx_labels_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 5, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let mut args = TxLabelArgs {
+			tx_id: 1,
+			label: Some("Mining Reward".to_string()),
+			memo: Some("first blocks".to_string()),
+		};
+		api.set_tx_label(m, &args)?;
+		let (_, txs) = api.retrieve_txs(m, false, Some(1), None)?;
+		assert_eq!(txs[0].label, Some("Mining Reward".to_string()));
+		assert_eq!(txs[0].memo, Some("first blocks".to_string()));
+
+		// Filter by the label or memo text
+		let query = TxQueryArgs {
+			label: Some("reward".to_string()),
+			..Default::default()
+		};
+		let (_, txs) = api.query_txs(m, false, &query)?;
+		assert_eq!(txs.len(), 1);
+		assert_eq!(txs[0].id, 1);
+		let query = TxQueryArgs {
+			label: Some("FIRST".to_string()),
+			..Default::default()
+		};
+		assert_eq!(api.query_txs(m, false, &query)?.1.len(), 1);
+
+		// None keeps the value, empty string removes it
+		args.label = None;
+		args.memo = Some("".to_string());
+		api.set_tx_label(m, &args)?;
+		let (_, txs) = api.retrieve_txs(m, false, Some(1), None)?;
+		assert_eq!(txs[0].label, Some("Mining Reward".to_string()));
+		assert_eq!(txs[0].memo, None);
+
+		// Too long label and unknown transaction
+		args.label = Some("x".repeat(65));
+		assert!(api.set_tx_label(m, &args).is_err());
+		args.label = Some("test".to_string());
+		args.tx_id = 1000;
+		assert!(api.set_tx_label(m, &args).is_err());
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn tx_labels() {
+	let test_dir = "test_output/tx_labels";
+	setup(test_dir);
+	if let Err(e) = tx_labels_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
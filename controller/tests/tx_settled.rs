@@ -0,0 +1,141 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests transaction confirmation targets
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::notifications::{self, WalletEvent};
+use libwallet::TxStatus;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Confirmed transaction is settled when it reaches its confirmation target
+fn tx_settled_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 5, false);
+
+	let mut tx_id = 0;
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, None)?;
+		let tx = txs.iter().find(|t| t.confirmed).unwrap().clone();
+		tx_id = tx.id;
+		assert_eq!(tx.current_status(), TxStatus::Confirmed);
+
+		// Target is not reached yet
+		api.set_tx_confirmation_target(m, tx.id, Some(8))?;
+		let (_, txs) = api.retrieve_txs(m, false, Some(tx.id), None)?;
+		assert_eq!(txs[0].confirmation_target, Some(8));
+		assert_eq!(txs[0].current_status(), TxStatus::Confirmed);
+
+		// Unknown transaction
+		assert!(api.set_tx_confirmation_target(m, 10_000, Some(8)).is_err());
+		Ok(())
+	})?;
+
+	let events = notifications::subscribe();
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 5, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		// Updater settles the transaction and reports it once
+		let (_, txs) = api.retrieve_txs(m, true, Some(tx_id), None)?;
+		assert_eq!(txs[0].current_status(), TxStatus::Settled);
+		let settled: Vec<WalletEvent> = events
+			.try_iter()
+			.filter(|e| match e {
+				WalletEvent::TxSettled { .. } => true,
+				_ => false,
+			})
+			.collect();
+		assert_eq!(settled.len(), 1);
+		match &settled[0] {
+			WalletEvent::TxSettled {
+				tx_id: id,
+				confirmations,
+				target,
+				..
+			} => {
+				assert_eq!(*id, tx_id);
+				assert_eq!(*target, 8);
+				assert!(*confirmations >= 8);
+			}
+			_ => unreachable!(),
+		}
+		api.retrieve_txs(m, true, Some(tx_id), None)?;
+		assert_eq!(events.try_iter().count(), 0);
+
+		// Higher target moves the transaction back to Confirmed, the reached one settles it again
+		api.set_tx_confirmation_target(m, tx_id, Some(1000))?;
+		let (_, txs) = api.retrieve_txs(m, false, Some(tx_id), None)?;
+		assert_eq!(txs[0].current_status(), TxStatus::Confirmed);
+		api.set_tx_confirmation_target(m, tx_id, Some(2))?;
+		let (_, txs) = api.retrieve_txs(m, false, Some(tx_id), None)?;
+		assert_eq!(txs[0].current_status(), TxStatus::Settled);
+
+		// Target is removed
+		api.set_tx_confirmation_target(m, tx_id, None)?;
+		let (_, txs) = api.retrieve_txs(m, false, Some(tx_id), None)?;
+		assert_eq!(txs[0].confirmation_target, None);
+		assert_eq!(txs[0].current_status(), TxStatus::Confirmed);
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn tx_settled() {
+	let test_dir = "test_output/tx_settled";
+	setup(test_dir);
+	if let Err(e) = tx_settled_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
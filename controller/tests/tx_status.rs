@@ -0,0 +1,152 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests transaction lifecycle statuses
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_libwallet as libwallet;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use libwallet::{InitTxArgs, Slate, TxStatus};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Test statuses for send, receive, finalize, confirm and cancel
+fn tx_status_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	create_wallet_and_add!(
+		client2,
+		wallet2,
+		mask2_i,
+		test_dir,
+		"wallet2",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask2 = (&mask2_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let bh = 10u64;
+	let _ =
+		test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, bh as usize, false);
+
+	let amount = 2_000_000_000;
+	let args = InitTxArgs {
+		src_acct_name: None,
+		amount: amount,
+		minimum_confirmations: 2,
+		max_outputs: 500,
+		num_change_outputs: 1,
+		selection_strategy_is_use_all: true,
+		..Default::default()
+	};
+
+	// Sender locked the outputs and waits for the receiver
+	let mut slate = Slate::blank(1, false);
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let slate_i = api.init_send_tx(m, &args, 1)?;
+		api.tx_lock_outputs(m, &slate_i, None, 0)?;
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate_i.id))?;
+		assert_eq!(txs[0].status, TxStatus::AwaitingCounterparty);
+		slate = slate_i;
+		Ok(())
+	})?;
+
+	// Receiver signed and waits for the sender to finalize
+	slate = client1.send_tx_slate_direct("wallet2", &slate)?;
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert_eq!(txs[0].status, TxStatus::AwaitingFinalization);
+		Ok(())
+	})?;
+
+	// Finalized and posted, waiting for the confirmation
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		slate = api.finalize_tx(m, &slate)?;
+		let (_, txs) = api.retrieve_txs(m, false, None, Some(slate.id))?;
+		assert_eq!(txs[0].status, TxStatus::AwaitingConfirmation);
+		api.post_tx(m, &slate.tx, false)?;
+		Ok(())
+	})?;
+
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 3, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert_eq!(txs[0].status, TxStatus::Confirmed);
+		Ok(())
+	})?;
+	wallet::controller::owner_single_use(Some(wallet2.clone()), mask2, None, |api, m| {
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate.id))?;
+		assert_eq!(txs[0].status, TxStatus::Confirmed);
+		Ok(())
+	})?;
+
+	// Cancelled by user
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let slate_i = api.init_send_tx(m, &args, 1)?;
+		api.tx_lock_outputs(m, &slate_i, None, 0)?;
+		api.cancel_tx(m, None, Some(slate_i.id))?;
+		let (_, txs) = api.retrieve_txs(m, true, None, Some(slate_i.id))?;
+		assert_eq!(txs[0].status, TxStatus::Cancelled);
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn tx_status() {
+	let test_dir = "test_output/tx_status";
+	setup(test_dir);
+	if let Err(e) = tx_status_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
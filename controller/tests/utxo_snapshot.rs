@@ -0,0 +1,103 @@
+// Copyright 2021 The MWC Developers
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! tests the height anchored UTXO snapshot export
+#[macro_use]
+extern crate log;
+extern crate grin_wallet_controller as wallet;
+extern crate grin_wallet_impls as impls;
+extern crate grin_wallet_util;
+
+use grin_wallet_util::grin_core as core;
+use grin_wallet_util::grin_core::global;
+use impls::test_framework::{self, LocalWalletClient};
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{clean_output_dir, create_wallet_proxy, setup};
+
+/// Test the snapshot at the tip and at the past height
+fn utxo_snapshot_test_impl(test_dir: &'static str) -> Result<(), wallet::Error> {
+	global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+	// Create a new proxy to simulate server and wallet responses
+	let mut wallet_proxy = create_wallet_proxy(test_dir);
+	let chain = wallet_proxy.chain.clone();
+
+	create_wallet_and_add!(
+		_client1,
+		wallet1,
+		mask1_i,
+		test_dir,
+		"wallet1",
+		None,
+		&mut wallet_proxy,
+		false
+	);
+
+	let mask1 = (&mask1_i).as_ref();
+
+	// Set the wallet proxy listener running
+	thread::spawn(move || {
+		global::set_local_chain_type(global::ChainTypes::AutomatedTesting);
+		if let Err(e) = wallet_proxy.run() {
+			error!("Wallet Proxy error: {}", e);
+		}
+	});
+
+	let reward = core::consensus::MWC_FIRST_GROUP_REWARD;
+	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 10, false);
+
+	wallet::controller::owner_single_use(Some(wallet1.clone()), mask1, None, |api, m| {
+		let (refreshed, info) = api.retrieve_summary_info(m, true, 1)?;
+		assert!(refreshed);
+
+		let snapshot = api.utxo_snapshot(m, None)?;
+		assert_eq!(snapshot.height, 10);
+		assert_eq!(snapshot.tip_height, 10);
+		assert_eq!(snapshot.total, info.total);
+		assert_eq!(snapshot.outputs.len(), 10);
+		assert!(snapshot.missing.is_empty());
+		assert!(snapshot
+			.outputs
+			.iter()
+			.all(|o| o.is_coinbase && o.account == "default" && o.value == reward));
+		let header_hash = snapshot.header_hash.clone();
+
+		let snapshot = api.utxo_snapshot(m, Some(5))?;
+		assert_eq!(snapshot.height, 5);
+		assert_eq!(snapshot.tip_height, 10);
+		assert_ne!(snapshot.header_hash, header_hash);
+		assert_eq!(snapshot.outputs.len(), 5);
+		assert_eq!(snapshot.total, 5 * reward);
+		assert!(snapshot.outputs.iter().all(|o| o.height <= 5));
+
+		assert!(api.utxo_snapshot(m, Some(11)).is_err());
+		Ok(())
+	})?;
+
+	// let logging finish
+	thread::sleep(Duration::from_millis(200));
+	Ok(())
+}
+
+#[test]
+fn utxo_snapshot() {
+	let test_dir = "test_output/utxo_snapshot";
+	setup(test_dir);
+	if let Err(e) = utxo_snapshot_test_impl(test_dir) {
+		panic!("Libwallet Error: {} - {}", e, e.backtrace().unwrap());
+	}
+	clean_output_dir(test_dir);
+}
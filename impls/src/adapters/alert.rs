@@ -0,0 +1,108 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Alert command notifier. Runs the configured command for the events that need the attention
+//! of the user, the event JSON is written into the command stdin. The command can send an
+//! email, a message to a chat, etc.
+
+use crate::libwallet::notifications::{EventNotifier, WalletEvent};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Alert command has this time to finish, otherwise it is killed
+const ALERT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Notifier that runs the alert command for the alert events
+#[derive(Clone)]
+pub struct AlertCommandNotifier {
+	command: String,
+}
+
+impl AlertCommandNotifier {
+	/// Create notifier for the command
+	pub fn new(command: String) -> Self {
+		AlertCommandNotifier { command }
+	}
+}
+
+impl EventNotifier for AlertCommandNotifier {
+	fn notify(&self, event: WalletEvent) {
+		if !event.is_alert() {
+			return;
+		}
+		// The command might be slow, the wallet doesn't wait for it
+		let command = self.command.clone();
+		let _ = thread::Builder::new()
+			.name("alert-notifier".to_string())
+			.spawn(move || {
+				if let Err(e) = run_alert_command(&command, &event) {
+					warn!("Unable to send alert {:?} with {}, {}", event, command, e);
+				}
+			});
+	}
+}
+
+fn run_alert_command(command: &str, event: &WalletEvent) -> Result<(), String> {
+	let input = serde_json::to_string(event)
+		.map_err(|e| format!("Unable to serialize the event, {}", e))?;
+
+	let mut child = Command::new(command)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::null())
+		.stderr(Stdio::null())
+		.spawn()
+		.map_err(|e| format!("Unable to start, {}", e))?;
+
+	if let Some(mut stdin) = child.stdin.take() {
+		stdin
+			.write_all(input.as_bytes())
+			.map_err(|e| format!("Unable to write into stdin, {}", e))?;
+	}
+
+	let start = Instant::now();
+	loop {
+		match child.try_wait() {
+			Ok(Some(status)) if status.success() => return Ok(()),
+			Ok(Some(status)) => return Err(format!("exit with {}", status)),
+			Ok(None) => {
+				if start.elapsed() > ALERT_TIMEOUT {
+					let _ = child.kill();
+					return Err("the command is not responding".to_string());
+				}
+				thread::sleep(Duration::from_millis(100));
+			}
+			Err(e) => return Err(format!("{}", e)),
+		}
+	}
+}
+
+#[cfg(all(test, unix))]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_alert_command() {
+		let event = WalletEvent::SwapActionRequired {
+			swap_id: "swap".to_string(),
+			state: "BuyerSendingAcceptOfferMessage".to_string(),
+			action: "DepositSecondary".to_string(),
+			description: "Please deposit BTC".to_string(),
+		};
+		assert!(run_alert_command("true", &event).is_ok());
+		assert!(run_alert_command("false", &event).is_err());
+		assert!(run_alert_command("/non/existing/alert", &event).is_err());
+	}
+}
@@ -0,0 +1,417 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Email transport. The slatepack is mailed through the SMTP server of the wallet account,
+//! the listener polls the IMAP inbox for the unread slate mails, receives them and mails the
+//! response back. Email is store-and-forward: the sender doesn't wait, its listener finalizes
+//! the response slate. The destination is the email address of the other party, who can also
+//! handle the slatepack manually with any mail client.
+
+use crate::adapters::{
+	listener_wait, transport_message, SlateSender, SlateTransport, SlateTransportHandler,
+};
+use crate::core::core::amount_to_hr_string;
+use crate::error::{Error, ErrorKind};
+use crate::util::RwLock;
+use data_encoding::BASE64;
+use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
+use grin_wallet_libwallet::slatepack::SlatePurpose;
+use grin_wallet_libwallet::{Slate, SlateVersion};
+use lettre::smtp::authentication::Credentials;
+use lettre::{ClientSecurity, ClientTlsParameters, SmtpClient, Transport};
+use lettre_email::EmailBuilder;
+use native_tls::TlsConnector;
+use std::time::Duration;
+
+/// Transport method name
+pub const EMAIL_METHOD: &str = "email";
+
+// Subject of the slate mails, the listener searches for it
+const SLATE_SUBJECT: &str = "MWC slate";
+// Implicit TLS SMTP port, the default one. The other ports use STARTTLS
+const SMTPS_PORT: u16 = 465;
+const DEFAULT_IMAP_PORT: u16 = 993;
+// How often the listener checks the inbox
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Email account of this wallet
+#[derive(Clone, Debug)]
+pub struct EmailConfig {
+	/// Address of the account, the sender of the mails
+	pub address: String,
+	/// SMTP server, host or host:port. Port 465 is implicit TLS, others use STARTTLS
+	pub smtp_server: String,
+	/// IMAP server, host or host:port, TLS
+	pub imap_server: String,
+	/// Login of the account, the address if not set
+	pub username: Option<String>,
+	/// Password of the account
+	pub password: String,
+}
+
+lazy_static! {
+	static ref EMAIL_CONFIG: RwLock<Option<EmailConfig>> = RwLock::new(None);
+}
+
+/// Set the email account. None disables the transport.
+pub fn set_email_config(config: Option<EmailConfig>) {
+	*EMAIL_CONFIG.write() = config;
+}
+
+/// Current email account
+pub fn get_email_config() -> Option<EmailConfig> {
+	EMAIL_CONFIG.read().clone()
+}
+
+fn parse_server(server: &str, default_port: u16) -> Result<(String, u16), Error> {
+	match server.rfind(':') {
+		Some(pos) => {
+			let port = server[pos + 1..].parse::<u16>().map_err(|e| {
+				ErrorKind::ArgumentError(format!("Invalid mail server {}, {}", server, e))
+			})?;
+			Ok((server[..pos].to_string(), port))
+		}
+		None => Ok((server.to_string(), default_port)),
+	}
+}
+
+fn validate_email_address(address: &str) -> Result<(), Error> {
+	let valid = match address.find('@') {
+		Some(pos) => pos > 0 && pos < address.len() - 1 && !address.contains(char::is_whitespace),
+		None => false,
+	};
+	if valid {
+		Ok(())
+	} else {
+		Err(ErrorKind::ArgumentError(format!("Invalid email address {}", address)).into())
+	}
+}
+
+// Email address of the From header: 'Name <user@example.com>' or 'user@example.com'
+fn parse_from_header(value: &str) -> Option<String> {
+	let address = match (value.rfind('<'), value.rfind('>')) {
+		(Some(start), Some(end)) if start < end => &value[start + 1..end],
+		_ => value,
+	};
+	let address = address.trim();
+	validate_email_address(address).ok()?;
+	Some(address.to_string())
+}
+
+fn decode_quoted_printable(body: &str) -> String {
+	let body = body.replace("=\r\n", "").replace("=\n", "");
+	let bytes = body.as_bytes();
+	let mut result = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		if bytes[i] == b'=' && i + 2 < bytes.len() {
+			let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+			if let Ok(b) = u8::from_str_radix(hex, 16) {
+				result.push(b);
+				i += 3;
+				continue;
+			}
+		}
+		result.push(bytes[i]);
+		i += 1;
+	}
+	String::from_utf8_lossy(&result).to_string()
+}
+
+// Sender and the slate text of the raw mail. The wallet mails are text/plain, the
+// slatepack armor or the JSON slate is taken from the body.
+fn parse_mail(raw: &[u8]) -> Option<(String, String)> {
+	let raw = String::from_utf8_lossy(raw).to_string();
+	let (header, body) = match raw.find("\r\n\r\n") {
+		Some(pos) => (&raw[..pos], &raw[pos + 4..]),
+		None => {
+			let pos = raw.find("\n\n")?;
+			(&raw[..pos], &raw[pos + 2..])
+		}
+	};
+
+	let mut from = None;
+	let mut encoding = String::new();
+	for line in header.lines() {
+		let lower = line.to_lowercase();
+		if lower.starts_with("from:") {
+			from = parse_from_header(&line[5..]);
+		} else if lower.starts_with("content-transfer-encoding:") {
+			encoding = lower[26..].trim().to_string();
+		}
+	}
+
+	let body = match encoding.as_str() {
+		"quoted-printable" => decode_quoted_printable(body),
+		"base64" => {
+			let data: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+			String::from_utf8_lossy(&BASE64.decode(data.as_bytes()).ok()?).to_string()
+		}
+		_ => body.to_string(),
+	};
+
+	let message = match (body.find("BEGINSLATEPACK"), body.find("ENDSLATEPACK.")) {
+		(Some(start), Some(end)) if start < end => {
+			body[start..end + "ENDSLATEPACK.".len()].to_string()
+		}
+		_ => body.trim().to_string(),
+	};
+	Some((from?, message))
+}
+
+/// Slate transport over email
+pub struct EmailChannel {
+	config: EmailConfig,
+	dest: String,
+}
+
+impl EmailChannel {
+	/// Channel to the destination address. The listener doesn't need one, dest is empty.
+	pub fn new(dest: &str) -> Result<Self, Error> {
+		let config = get_email_config().ok_or(ErrorKind::WalletComms(
+			"Email transport is not configured, please set email_address, email_smtp_server, email_imap_server and email_password in the wallet config".to_string(),
+		))?;
+		let dest = dest.trim().trim_start_matches("mailto:").to_string();
+		Ok(EmailChannel { config, dest })
+	}
+
+	fn username(&self) -> String {
+		self.config
+			.username
+			.clone()
+			.unwrap_or(self.config.address.clone())
+	}
+
+	fn send_mail(&self, to: &str, subject: &str, text: &str) -> Result<(), Error> {
+		let email = EmailBuilder::new()
+			.to(to)
+			.from(self.config.address.as_str())
+			.subject(subject)
+			.text(text)
+			.build()
+			.map_err(|e| ErrorKind::WalletComms(format!("Unable to build the mail, {}", e)))?;
+
+		let (host, port) = parse_server(&self.config.smtp_server, SMTPS_PORT)?;
+		let tls = TlsConnector::new()
+			.map_err(|e| ErrorKind::WalletComms(format!("Unable to init TLS, {}", e)))?;
+		let tls_parameters = ClientTlsParameters::new(host.clone(), tls);
+		let security = if port == SMTPS_PORT {
+			ClientSecurity::Wrapper(tls_parameters)
+		} else {
+			ClientSecurity::Required(tls_parameters)
+		};
+		let mut mailer = SmtpClient::new((host.as_str(), port), security)
+			.map_err(|e| {
+				ErrorKind::WalletComms(format!(
+					"Unable to connect to SMTP server {}, {}",
+					self.config.smtp_server, e
+				))
+			})?
+			.credentials(Credentials::new(
+				self.username(),
+				self.config.password.clone(),
+			))
+			.transport();
+		mailer.send(email.into()).map_err(|e| {
+			ErrorKind::WalletComms(format!("Unable to send the mail to {}, {}", to, e))
+		})?;
+		Ok(())
+	}
+
+	// Read the unread slate mails of the inbox. Fetching the mail marks it as read.
+	fn fetch_mails(&self) -> Result<Vec<Vec<u8>>, Error> {
+		let err = |e: String| -> Error {
+			ErrorKind::WalletComms(format!(
+				"IMAP server {} error, {}",
+				self.config.imap_server, e
+			))
+			.into()
+		};
+		let (host, port) = parse_server(&self.config.imap_server, DEFAULT_IMAP_PORT)?;
+		let tls = TlsConnector::builder()
+			.build()
+			.map_err(|e| err(format!("{}", e)))?;
+		let client =
+			imap::connect((host.as_str(), port), &host, &tls).map_err(|e| err(format!("{}", e)))?;
+		let mut session = client
+			.login(self.username(), self.config.password.clone())
+			.map_err(|(e, _)| err(format!("{}", e)))?;
+
+		session.select("INBOX").map_err(|e| err(format!("{}", e)))?;
+		let ids = session
+			.search(format!("UNSEEN SUBJECT \"{}\"", SLATE_SUBJECT))
+			.map_err(|e| err(format!("{}", e)))?;
+		let mut mails = vec![];
+		for id in ids {
+			let fetches = session
+				.fetch(id.to_string(), "RFC822")
+				.map_err(|e| err(format!("{}", e)))?;
+			for fetch in fetches.iter() {
+				if let Some(body) = fetch.body() {
+					mails.push(body.to_vec());
+				}
+			}
+		}
+		let _ = session.logout();
+		Ok(mails)
+	}
+}
+
+impl SlateSender for EmailChannel {
+	fn check_other_wallet_version(
+		&self,
+		_destination_address: &String,
+	) -> Result<Option<(SlateVersion, Option<String>)>, Error> {
+		Ok(None)
+	}
+
+	fn send_tx(
+		&self,
+		_slate: &Slate,
+		_slate_content: SlatePurpose,
+		_slatepack_secret: &DalekSecretKey,
+		_recipient: Option<DalekPublicKey>,
+		_other_wallet_version: Option<(SlateVersion, Option<String>)>,
+	) -> Result<Slate, Error> {
+		Err(ErrorKind::WalletComms(
+			"email delivery is asynchronous, the response slate can't be waited for. Please use 'send --method email', the response is finalized by 'listen --method email'".to_string(),
+		)
+		.into())
+	}
+}
+
+impl SlateTransport for EmailChannel {
+	fn method(&self) -> &str {
+		EMAIL_METHOD
+	}
+
+	fn validate_address(&self, dest: &str) -> Result<(), Error> {
+		validate_email_address(dest.trim().trim_start_matches("mailto:"))
+	}
+
+	fn address(&self, _wallet_secret: &DalekSecretKey) -> Result<String, Error> {
+		Ok(self.config.address.clone())
+	}
+
+	fn is_store_and_forward(&self) -> bool {
+		true
+	}
+
+	/// The response comes to the inbox of this wallet
+	fn post_slate(
+		&self,
+		slate: &Slate,
+		slate_content: SlatePurpose,
+		slatepack_secret: &DalekSecretKey,
+		recipient: Option<DalekPublicKey>,
+	) -> Result<(), Error> {
+		let message = transport_message(slate, slate_content, slatepack_secret, recipient)?;
+		self.send_mail(
+			&self.dest,
+			&format!("{} {}", SLATE_SUBJECT, slate.id),
+			&message,
+		)?;
+
+		println!(
+			"slate [{}] for [{}] MWCs mailed to [{}]",
+			slate.id.to_string(),
+			amount_to_hr_string(slate.amount, false),
+			self.dest,
+		);
+		Ok(())
+	}
+
+	fn listen(
+		&self,
+		_wallet_secret: &DalekSecretKey,
+		handler: &dyn SlateTransportHandler,
+	) -> Result<(), Error> {
+		// Check the account before the listener is reported as started
+		self.fetch_mails()?;
+		loop {
+			match self.fetch_mails() {
+				Ok(mails) => {
+					for mail in mails {
+						let (from, message) = match parse_mail(&mail) {
+							Some(mail) => mail,
+							None => {
+								warn!("Unable to parse the slate mail");
+								continue;
+							}
+						};
+						if let Some(reply) = handler.on_message(&from, &message) {
+							let subject = format!("Re: {}", SLATE_SUBJECT);
+							if let Err(e) = self.send_mail(&from, &subject, &reply) {
+								warn!("Unable to mail the response to {}, {}", from, e);
+							}
+						}
+					}
+				}
+				Err(e) => warn!("Unable to check the inbox, {}", e),
+			}
+			if !listener_wait(handler, POLL_INTERVAL) {
+				return Ok(());
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_mail() {
+		assert_eq!(
+			parse_server("smtp.example.com:587", SMTPS_PORT).unwrap(),
+			("smtp.example.com".to_string(), 587)
+		);
+		assert_eq!(
+			parse_server("imap.example.com", DEFAULT_IMAP_PORT).unwrap(),
+			("imap.example.com".to_string(), 993)
+		);
+		assert!(validate_email_address("user@example.com").is_ok());
+		assert!(validate_email_address("user@").is_err());
+		assert!(validate_email_address("user example.com").is_err());
+
+		let mail = "From: Alice <alice@example.com>\r\n\
+			Subject: MWC slate 7b4c2b4f-5a7a-4a3e-9b1e-3c6a1f0a2d11\r\n\
+			Content-Transfer-Encoding: quoted-printable\r\n\
+			\r\n\
+			Hello,\r\n\
+			BEGINSLATEPACK. 4H1qx1wHe668tFW yC2gfL8PPd8kSgv pcXQhyRkHbyKHZ=\r\n\
+			g8ZF6n3pTXWSc3o 2sbPZPhdBmpyX3s. ENDSLATEPACK.\r\n\
+			--\r\n\
+			Alice\r\n";
+		let (from, message) = parse_mail(mail.as_bytes()).unwrap();
+		assert_eq!(from, "alice@example.com");
+		assert_eq!(
+			message,
+			"BEGINSLATEPACK. 4H1qx1wHe668tFW yC2gfL8PPd8kSgv pcXQhyRkHbyKHZg8ZF6n3pTXWSc3o 2sbPZPhdBmpyX3s. ENDSLATEPACK."
+		);
+
+		let json = "{\"version_info\":{\"version\":3}}";
+		let mail = format!(
+			"From: bob@example.com\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n",
+			BASE64.encode(json.as_bytes())
+		);
+		assert_eq!(
+			parse_mail(mail.as_bytes()).unwrap(),
+			("bob@example.com".to_string(), json.to_string())
+		);
+
+		// The reply needs the sender
+		assert!(parse_mail(b"Subject: MWC slate\r\n\r\nBEGINSLATEPACK. ENDSLATEPACK.").is_none());
+	}
+}
@@ -18,9 +18,10 @@ use std::io::{Read, Write};
 
 use crate::adapters::SlateGetData;
 use crate::error::{Error, ErrorKind};
-use crate::libwallet::{Slate, SlateVersion, VersionedSlate};
+use crate::libwallet::{Slate, SlateEnvelope, SlateVersion, VersionedSlate};
 use crate::{SlateGetter, SlatePutter};
 use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
+use grin_wallet_libwallet::slate_versions::grin_v4::SlateGrinV4;
 use grin_wallet_libwallet::slatepack::SlatePurpose;
 use std::path::PathBuf;
 
@@ -31,6 +32,9 @@ pub struct PathToSlatePutter {
 	sender: Option<DalekPublicKey>,
 	recipient: Option<DalekPublicKey>,
 	slatepack_format: bool,
+	grin_v4_format: bool,
+	// Plain slate is encrypted into the envelope for this recipient
+	envelope: Option<DalekPublicKey>,
 }
 
 pub struct PathToSlateGetter {
@@ -55,6 +59,21 @@ impl PathToSlatePutter {
 			sender: Some(sender),
 			recipient: recipient,
 			slatepack_format,
+			grin_v4_format: false,
+			envelope: None,
+		}
+	}
+
+	// Build sender that saves Grin V4 compact slates, the format that grin wallet understands
+	pub fn build_grin_v4(path_buf: Option<PathBuf>, content: SlatePurpose) -> Self {
+		Self {
+			path_buf,
+			content: Some(content),
+			sender: None,
+			recipient: None,
+			slatepack_format: false,
+			grin_v4_format: true,
+			envelope: None,
 		}
 	}
 
@@ -65,6 +84,22 @@ impl PathToSlatePutter {
 			sender: None,
 			recipient: None,
 			slatepack_format: false,
+			grin_v4_format: false,
+			envelope: None,
+		}
+	}
+
+	// Build sender that saves the plain slate encrypted for the recipient. For the slates
+	// that can't be a slatepack.
+	pub fn build_envelope(path_buf: Option<PathBuf>, recipient: DalekPublicKey) -> Self {
+		Self {
+			path_buf,
+			content: None,
+			sender: None,
+			recipient: None,
+			slatepack_format: false,
+			grin_v4_format: false,
+			envelope: Some(recipient),
 		}
 	}
 }
@@ -93,7 +128,14 @@ impl SlatePutter for PathToSlatePutter {
 		use_test_rng: bool,
 	) -> Result<String, Error> {
 		let out_slate = {
-			if self.recipient.is_some() || self.slatepack_format {
+			if self.grin_v4_format {
+				let content = self.content.clone().ok_or(ErrorKind::GenericError(
+					"Content is not defined for Grin V4 slate".to_string(),
+				))?;
+				VersionedSlate::GV4(SlateGrinV4::from_slate(slate, &content).map_err(|e| {
+					ErrorKind::GenericError(format!("Unable to build Grin V4 slate, {}", e))
+				})?)
+			} else if self.recipient.is_some() || self.slatepack_format {
 				// recipient is defining enrypted/nonencrypted format. Sender and content are still required.
 				if self.sender.is_none() || self.content.is_none() {
 					return Err(ErrorKind::GenericError(
@@ -136,7 +178,15 @@ impl SlatePutter for PathToSlatePutter {
 			}
 		};
 
-		let slate_str = out_slate.as_string()?;
+		let slate_str = match &self.envelope {
+			Some(recipient) => SlateEnvelope::seal(
+				&out_slate.as_string()?,
+				slatepack_secret,
+				recipient,
+				use_test_rng,
+			)?,
+			None => out_slate.as_string()?,
+		};
 
 		if let Some(path_buf) = &self.path_buf {
 			let file_name = path_buf.to_str().unwrap_or("INVALID PATH");
@@ -198,7 +248,24 @@ impl SlateGetter for PathToSlateGetter {
 			}
 		};
 
-		if Slate::deserialize_is_plain(&content) {
+		if SlateEnvelope::is_envelope(&content) {
+			let (json, sender, recipient) = SlateEnvelope::open(&content, slatepack_secret)?;
+			let slate = Slate::deserialize_upgrade_plain(&json).map_err(|e| {
+				ErrorKind::IO(format!("Unable to build slate from the envelope, {}", e))
+			})?;
+			Ok(SlateGetData::Envelope(slate, sender, recipient))
+		} else if SlateGrinV4::is_grin_v4_json(&content) {
+			let grin_slate: SlateGrinV4 = serde_json::from_str(&content).map_err(|e| {
+				ErrorKind::IO(format!(
+					"Unable to read Grin V4 slate from the content, {}",
+					e
+				))
+			})?;
+			let (slate, purpose) = grin_slate.to_slate().map_err(|e| {
+				ErrorKind::IO(format!("Unable to build slate from Grin V4 slate, {}", e))
+			})?;
+			Ok(SlateGetData::GrinV4(slate, purpose))
+		} else if Slate::deserialize_is_plain(&content) {
 			let slate = Slate::deserialize_upgrade_plain(&content).map_err(|e| {
 				ErrorKind::IO(format!("Unable to build slate from the content, {}", e))
 			})?;
@@ -13,16 +13,19 @@
 // limitations under the License.
 
 /// HTTP Wallet 'plugin' implementation
-use crate::client_utils::{Client, ClientError};
+use crate::client_utils::{Client, ClientError, ClientErrorKind};
 use crate::error::{Error, ErrorKind};
 use crate::libwallet::slate_versions::{SlateVersion, VersionedSlate};
 use crate::libwallet::swap::message::Message;
 use crate::libwallet::Slate;
+use crate::util::secp::key::{PublicKey, SecretKey};
+use crate::util::{static_secp_instance, to_hex, Mutex};
 use crate::{SlateSender, SwapMessageSender};
-use serde::Serialize;
+use rand::Rng;
 use serde_json::{json, Value};
 use std::net::SocketAddr;
 use std::path::MAIN_SEPARATOR;
+use std::sync::Arc;
 
 use crate::adapters::MarketplaceMessageSender;
 use crate::tor;
@@ -31,10 +34,28 @@ use crate::tor::process as tor_process;
 use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
 use grin_wallet_libwallet::address;
 use grin_wallet_libwallet::proof::proofaddress::ProvableAddress;
+use grin_wallet_libwallet::secure_api::{self, EncryptedBody, ListenerKey};
 use grin_wallet_libwallet::slatepack::SlatePurpose;
 
 const TOR_CONFIG_PATH: &str = "tor/sender";
 
+/// First foreign API version that accepts the encrypted requests
+const SECURE_FOREIGN_API_VERSION: u16 = 3;
+
+/// Error code of the listener when it can't decrypt the request, e.g. it was restarted with a new key
+const DECRYPTION_ERROR_CODE: i64 = -32002;
+
+/// Encryption state of the foreign API calls to the listener
+#[derive(Clone)]
+enum SecureApi {
+	/// Not negotiated yet
+	Unknown,
+	/// The listener is an older wallet, requests go plain
+	Unsupported,
+	/// Public key of the sender (hex) that goes with every request and the ECDH shared key
+	Session(String, SecretKey),
+}
+
 #[derive(Clone)]
 pub struct HttpDataSender {
 	base_url: String,
@@ -44,6 +65,9 @@ pub struct HttpDataSender {
 	tor_config_dir: String,
 	socks_running: bool,
 	tor_log_file: Option<String>,
+	secure_api: Arc<Mutex<SecureApi>>,
+	/// Tor address of the listener, the listener key must be signed by it
+	listener_address: Arc<Mutex<Option<DalekPublicKey>>>,
 }
 
 impl HttpDataSender {
@@ -58,6 +82,17 @@ impl HttpDataSender {
 		if !base_url.starts_with("http") && !base_url.starts_with("https") {
 			Err(ErrorKind::GenericError(format!("Invalid http url: {}", base_url)).into())
 		} else {
+			// The onion address of the url is the best known destination
+			let host = base_url
+				.split("://")
+				.nth(1)
+				.and_then(|s| s.split(|c| c == '/' || c == ':').next())
+				.unwrap_or("");
+			let listener_address = if host.ends_with(".onion") {
+				address::pubkey_from_onion_v3(host).ok()
+			} else {
+				None
+			};
 			Ok(HttpDataSender {
 				base_url: base_url.to_owned(),
 				apisecret,
@@ -66,6 +101,8 @@ impl HttpDataSender {
 				tor_config_dir: tor_config_dir.unwrap_or(String::from("")),
 				socks_running: socks_running,
 				tor_log_file,
+				secure_api: Arc::new(Mutex::new(SecureApi::Unknown)),
+				listener_address: Arc::new(Mutex::new(listener_address)),
 			})
 		}
 	}
@@ -189,6 +226,14 @@ impl HttpDataSender {
 			error!("{}", report);
 			return Err(ErrorKind::ClientCallback(report).into());
 		}
+		{
+			let mut secure_api = self.secure_api.lock();
+			if foreign_api_version < SECURE_FOREIGN_API_VERSION {
+				*secure_api = SecureApi::Unsupported;
+			} else if let SecureApi::Unsupported = *secure_api {
+				*secure_api = SecureApi::Unknown;
+			}
+		}
 
 		let slatepack_address: Option<String> =
 			if supported_slate_versions.contains(&"SP".to_owned()) {
@@ -203,6 +248,13 @@ impl HttpDataSender {
 				None
 			};
 
+		if let Some(slatepack_address) = &slatepack_address {
+			let mut listener_address = self.listener_address.lock();
+			if listener_address.is_none() {
+				*listener_address = address::pubkey_from_onion_v3(slatepack_address).ok();
+			}
+		}
+
 		if supported_slate_versions.contains(&"SP".to_owned()) {
 			return Ok((SlateVersion::SP, slatepack_address));
 		} else if supported_slate_versions.contains(&"V3B".to_owned()) {
@@ -303,22 +355,231 @@ impl HttpDataSender {
 		Err(ErrorKind::ClientCallback(report).into())
 	}
 
-	fn post<IN>(
+	/// Post the request. The calls are encrypted if the listener supports it. The version check
+	/// and the proof address stay plain because they are the way to find out what the listener
+	/// supports and what address must sign its key.
+	fn post(
 		&self,
 		url: &str,
 		api_secret: Option<String>,
-		input: IN,
-	) -> Result<String, ClientError>
-	where
-		IN: Serialize,
-	{
+		input: Value,
+	) -> Result<String, ClientError> {
+		if input["method"] == json!("check_version")
+			|| input["method"] == json!("get_proof_address")
+		{
+			return self.post_plain(url, api_secret, &input);
+		}
+
+		// The listener can be restarted with a new key, the session is negotiated again once
+		let mut retry = true;
+		loop {
+			let (pubkey, shared_key) = match self.secure_session(url, api_secret.clone())? {
+				Some(session) => session,
+				None => return self.post_plain(url, api_secret, &input),
+			};
+
+			let params = EncryptedBody::from_json(&input, &shared_key).map_err(|e| {
+				ClientErrorKind::RequestError(format!("Unable to encrypt the request, {}", e))
+			})?;
+			let req = json!({
+				"jsonrpc": "2.0",
+				"method": "encrypted_request_v3",
+				"id": input["id"],
+				"ecdh_pubkey": pubkey,
+				"params": params,
+			});
+			let res_str = self.post_plain(url, api_secret.clone(), &req)?;
+
+			let res: Value = serde_json::from_str(&res_str).map_err(|e| {
+				ClientErrorKind::ResponseError(format!(
+					"Unable to parse respond {}, {}",
+					res_str, e
+				))
+			})?;
+			// The encryption errors are not encrypted, they are returned as they are
+			if res["error"] != json!(null) {
+				if retry && res["error"]["code"] == json!(DECRYPTION_ERROR_CODE) {
+					retry = false;
+					*self.secure_api.lock() = SecureApi::Unknown;
+					continue;
+				}
+				return Ok(res_str);
+			}
+			let body: EncryptedBody =
+				serde_json::from_value(res["result"]["Ok"].clone()).map_err(|e| {
+					ClientErrorKind::ResponseError(format!(
+						"Listener respond is not encrypted, {}",
+						e
+					))
+				})?;
+			match body.decrypt(&shared_key) {
+				Ok(res) => return Ok(res.to_string()),
+				Err(_) if retry => {
+					retry = false;
+					*self.secure_api.lock() = SecureApi::Unknown;
+				}
+				Err(e) => {
+					return Err(ClientErrorKind::ResponseError(format!(
+						"Unable to decrypt the response, {}",
+						e
+					))
+					.into())
+				}
+			}
+		}
+	}
+
+	fn post_plain(
+		&self,
+		url: &str,
+		api_secret: Option<String>,
+		input: &Value,
+	) -> Result<String, ClientError> {
 		// For state sender we want send and disconnect
 		let client = Client::new(self.use_socks, self.socks_proxy_addr)?;
-		let req = client.create_post_request(url, Some("mwc".to_string()), api_secret, &input)?;
+		let req = client.create_post_request(url, Some("mwc".to_string()), api_secret, input)?;
 		let res = client.send_request(req)?;
 		Ok(res)
 	}
 
+	/// Sender public key and the shared key of the session, None if the listener is an older
+	/// wallet. The session is negotiated with the first call.
+	fn secure_session(
+		&self,
+		url: &str,
+		api_secret: Option<String>,
+	) -> Result<Option<(String, SecretKey)>, ClientError> {
+		let mut secure_api = self.secure_api.lock();
+		if let SecureApi::Unknown = *secure_api {
+			let version = self.foreign_api_version(url, api_secret.clone())?;
+			*secure_api = if version < SECURE_FOREIGN_API_VERSION {
+				SecureApi::Unsupported
+			} else {
+				self.init_secure_api(url, api_secret)?
+			};
+		}
+		match &*secure_api {
+			SecureApi::Session(pubkey, shared_key) => {
+				Ok(Some((pubkey.clone(), shared_key.clone())))
+			}
+			_ => Ok(None),
+		}
+	}
+
+	/// Foreign API version of the listener
+	fn foreign_api_version(
+		&self,
+		url: &str,
+		api_secret: Option<String>,
+	) -> Result<u16, ClientError> {
+		let req = json!({
+			"jsonrpc": "2.0",
+			"method": "check_version",
+			"id": 1,
+			"params": []
+		});
+		let res_str = self.post_plain(url, api_secret, &req)?;
+		let res: Value = serde_json::from_str(&res_str).map_err(|e| {
+			ClientErrorKind::ResponseError(format!("Unable to parse respond {}, {}", res_str, e))
+		})?;
+		let version = serde_json::from_value(res["result"]["Ok"]["foreign_api_version"].clone())
+			.map_err(|e| {
+				ClientErrorKind::ResponseError(format!(
+					"Unable to read respond foreign_api_version value {}, {}",
+					res_str, e
+				))
+			})?;
+		Ok(version)
+	}
+
+	/// Exchange the ECDH keys with the listener. The listener key must be signed by the address
+	/// of the destination. There is no fallback to the plain requests, the errors are returned
+	/// and the negotiation is repeated with the next call.
+	fn init_secure_api(
+		&self,
+		url: &str,
+		api_secret: Option<String>,
+	) -> Result<SecureApi, ClientError> {
+		let secret = loop {
+			let mut secret = [0u8; 32];
+			rand::thread_rng().fill(&mut secret);
+			if let Ok(key) = SecretKey::from_slice(&secret) {
+				break key;
+			}
+		};
+		let pubkey = {
+			let secp_inst = static_secp_instance();
+			let secp = secp_inst.lock();
+			let pubkey = PublicKey::from_secret_key(&secp, &secret).map_err(|e| {
+				ClientErrorKind::Internal(format!("Unable to build the ECDH key, {}", e))
+			})?;
+			to_hex(pubkey.serialize_vec(true).to_vec())
+		};
+
+		let req = json!({
+			"jsonrpc": "2.0",
+			"method": "init_secure_api",
+			"id": 1,
+			"params": {
+				"ecdh_pubkey": pubkey,
+			}
+		});
+		let res_str = self.post_plain(url, api_secret, &req)?;
+
+		let listener_address = self.listener_address.lock().clone();
+		let shared_key = Self::read_listener_key(&res_str, listener_address.as_ref())
+			.and_then(|listener_key| Ok(secure_api::ecdh_shared_key(&listener_key, &secret)?))
+			.map_err(|e| {
+				ClientErrorKind::ResponseError(format!(
+					"Unable to start the encrypted session with other wallet, {}",
+					e
+				))
+			})?;
+		Ok(SecureApi::Session(pubkey, shared_key))
+	}
+
+	/// Public key of the listener from the init_secure_api respond, checked against the address
+	fn read_listener_key(
+		res_str: &str,
+		listener_address: Option<&DalekPublicKey>,
+	) -> Result<PublicKey, Error> {
+		let res: Value = serde_json::from_str(res_str).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to parse respond {}, {}", res_str, e))
+		})?;
+		if res["error"] != json!(null) {
+			return Err(ErrorKind::ClientCallback(format!(
+				"init_secure_api: Error: {}, Message: {}",
+				res["error"]["code"], res["error"]["message"]
+			))
+			.into());
+		}
+
+		let listener_key: ListenerKey = serde_json::from_value(res["result"]["Ok"].clone())
+			.map_err(|e| {
+				ErrorKind::ClientCallback(format!(
+					"init_secure_api: invalid listener key in respond {}, {}",
+					res_str, e
+				))
+			})?;
+		Ok(listener_key.verify(listener_address)?)
+	}
+
+	/// The destination of the slate is known, the listener key must be signed by it
+	fn set_listener_address(&self, address: DalekPublicKey) {
+		let changed = {
+			let mut listener_address = self.listener_address.lock();
+			let changed = listener_address.as_ref() != Some(&address);
+			*listener_address = Some(address);
+			changed
+		};
+		if changed {
+			let mut secure_api = self.secure_api.lock();
+			if let SecureApi::Session(_, _) = *secure_api {
+				*secure_api = SecureApi::Unknown;
+			}
+		}
+	}
+
 	fn set_up_tor_send_process(&self) -> Result<(String, tor_process::TorProcess), Error> {
 		let trailing = match self.base_url.ends_with('/') {
 			true => "",
@@ -408,6 +669,10 @@ impl SlateSender for HttpDataSender {
 
 		let (mut slate_version, slatepack_address) = other_wallet_version.unwrap();
 
+		if let Some(recipient) = recipient {
+			self.set_listener_address(recipient);
+		}
+
 		// Slate can't be slatepack if it is not a compact. Let's handle that here.
 		if slate_version == SlateVersion::SP && !slate.compact_slate {
 			slate_version = SlateVersion::V3B;
@@ -451,6 +716,12 @@ impl SlateSender for HttpDataSender {
 				}
 				VersionedSlate::into_version_plain(slate.clone(), SlateVersion::V3B)?
 			}
+			SlateVersion::GV4 => {
+				return Err(ErrorKind::ClientCallback(
+					"Grin V4 slate can't be sent to mwc wallet".into(),
+				)
+				.into());
+			}
 			SlateVersion::V2 | SlateVersion::V3 => {
 				let mut slate = slate.clone();
 				if slate.compact_slate {
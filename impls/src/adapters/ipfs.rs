@@ -0,0 +1,560 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! IPFS dead-drop transport. The sender adds the encrypted slatepack to IPFS and appends its
+//! CID to the mailbox of the recipient. The mailbox is an IPNS name with the key derived from
+//! the recipient slatepack address, so any wallet can find and update it, and the recipient
+//! listener polls it. The parties don't need to be online at the same time: the response
+//! slate is dropped into the mailbox of the sender, and the sender listener finalizes it.
+//!
+//! The wallet talks to the HTTP API of a local IPFS node (Kubo). The mailbox is updated with
+//! read-modify-write, two senders that update it at the same moment can overwrite each
+//! other. The sender checks that its drop is listed after the update and retries.
+
+use crate::adapters::{
+	listener_wait, transport_message, SlateSender, SlateTransport, SlateTransportHandler,
+};
+use crate::blake2::blake2b::blake2b;
+use crate::core::core::amount_to_hr_string;
+use crate::error::{Error, ErrorKind};
+use crate::util::{Mutex, RwLock};
+use chrono::Utc;
+use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
+use grin_wallet_libwallet::slatepack::SlatePurpose;
+use grin_wallet_libwallet::{Slate, SlateVersion};
+use grin_wallet_util::OnionV3Address;
+use libp2p::identity::{self, PublicKey};
+use libp2p::PeerId;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Transport method name
+pub const IPFS_METHOD: &str = "ipfs";
+
+/// IPFS node HTTP API if ipfs_api_url is not set in the wallet config
+pub const DEFAULT_IPFS_API_URL: &str = "http://127.0.0.1:5001";
+
+// Drops older than that are removed from the mailbox
+const DROP_RETENTION_SECS: i64 = 7 * 24 * 3600;
+// Number of the drops that the mailbox keeps, the oldest are removed first
+const MAX_MAILBOX_DROPS: usize = 100;
+// IPNS record lifetime, the IPFS node republishes the records of its keys
+const MAILBOX_LIFETIME: &str = "168h";
+// How often the listener checks the mailbox
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+// IPNS publish and resolve can be slow
+const API_TIMEOUT: Duration = Duration::from_secs(120);
+// Mailbox update attempts if another sender overwrote it
+const PUBLISH_ATTEMPTS: usize = 3;
+// Processed drops of the listener, in the wallet data directory
+const SEEN_DROPS_FILE: &str = "ipfs_drops.json";
+
+lazy_static! {
+	static ref IPFS_API_URL: RwLock<String> = RwLock::new(DEFAULT_IPFS_API_URL.to_string());
+	static ref IPFS_DATA_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+	// Mailbox updates of this wallet process go in turn
+	static ref MAILBOX_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Set the IPFS node API and the directory for the listener state. None restores the default
+/// API url.
+pub fn set_ipfs_config(api_url: Option<String>, data_dir: Option<PathBuf>) {
+	*IPFS_API_URL.write() = api_url.unwrap_or(DEFAULT_IPFS_API_URL.to_string());
+	*IPFS_DATA_DIR.write() = data_dir;
+}
+
+/// Drop of the mailbox
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct MailboxDrop {
+	/// CID of the drop message
+	cid: String,
+	/// Time when the drop was added, unix seconds
+	time: i64,
+}
+
+/// Content of the mailbox IPNS name
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+struct Mailbox {
+	drops: Vec<MailboxDrop>,
+}
+
+impl Mailbox {
+	fn contains(&self, cid: &str) -> bool {
+		self.drops.iter().any(|d| d.cid == cid)
+	}
+
+	// Add the drop, the expired and the oldest drops are removed
+	fn add(&mut self, cid: &str, time: i64) {
+		self.drops.retain(|d| d.time > time - DROP_RETENTION_SECS);
+		self.drops.push(MailboxDrop {
+			cid: cid.to_string(),
+			time,
+		});
+		if self.drops.len() > MAX_MAILBOX_DROPS {
+			let extra = self.drops.len() - MAX_MAILBOX_DROPS;
+			self.drops.drain(..extra);
+		}
+	}
+}
+
+/// Message that is added to IPFS. 'from' is the slatepack address for the reply.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct DropMessage {
+	from: String,
+	message: String,
+}
+
+/// Slatepack address of the wallet, it is the address of its mailbox
+pub fn ipfs_address(wallet_secret: &DalekSecretKey) -> String {
+	OnionV3Address::from_bytes(DalekPublicKey::from(wallet_secret).to_bytes()).to_string()
+}
+
+fn parse_address(address: &str) -> Result<DalekPublicKey, Error> {
+	let address = OnionV3Address::try_from(address.trim()).map_err(|e| {
+		ErrorKind::ArgumentError(format!("Invalid slatepack address {}, {}", address, e))
+	})?;
+	Ok(address.to_ed25519()?)
+}
+
+// IPNS key of the mailbox: h(recipient public key|"ipfs-drop")
+fn mailbox_keypair(recipient: &DalekPublicKey) -> Result<identity::ed25519::Keypair, Error> {
+	let mut data = recipient.as_bytes().to_vec();
+	data.extend_from_slice(b"ipfs-drop");
+	let hash = blake2b(32, &[], &data);
+	let secret = identity::ed25519::SecretKey::from_bytes(hash.as_bytes().to_vec())
+		.map_err(|e| ErrorKind::GenericError(format!("Invalid mailbox key, {}", e)))?;
+	Ok(secret.into())
+}
+
+fn mailbox_name(keys: &identity::ed25519::Keypair) -> String {
+	PeerId::from(PublicKey::Ed25519(keys.public())).to_string()
+}
+
+// libp2p protobuf of the private key for the key import: type Ed25519, secret and public keys
+fn key_protobuf(keys: &identity::ed25519::Keypair) -> Vec<u8> {
+	let mut data = vec![0x08, 0x01, 0x12, 0x40];
+	data.extend_from_slice(&keys.encode());
+	data
+}
+
+/// Client of the IPFS node HTTP API
+struct IpfsApi {
+	url: String,
+}
+
+impl IpfsApi {
+	fn new() -> Self {
+		IpfsApi {
+			url: IPFS_API_URL.read().trim_end_matches('/').to_string(),
+		}
+	}
+
+	fn call(
+		&self,
+		command: &str,
+		query: &[(&str, &str)],
+		file: Option<Vec<u8>>,
+	) -> Result<Vec<u8>, Error> {
+		let comms_error = |e: String| -> Error {
+			ErrorKind::WalletComms(format!("IPFS {} request failed, {}", command, e)).into()
+		};
+		let client = reqwest::Client::builder()
+			.timeout(API_TIMEOUT)
+			.build()
+			.map_err(|e| comms_error(format!("{}", e)))?;
+		let mut request = client
+			.post(&format!("{}/api/v0/{}", self.url, command))
+			.query(query);
+		if let Some(file) = file {
+			let part = reqwest::multipart::Part::bytes(file).file_name("data");
+			request = request.multipart(reqwest::multipart::Form::new().part("file", part));
+		}
+		let mut response = request.send().map_err(|e| comms_error(format!("{}", e)))?;
+
+		let mut data = vec![];
+		response
+			.read_to_end(&mut data)
+			.map_err(|e| comms_error(format!("{}", e)))?;
+		if !response.status().is_success() {
+			// Errors come as {"Message": "...", "Code": 0, "Type": "error"}
+			let message = serde_json::from_slice::<serde_json::Value>(&data)
+				.ok()
+				.and_then(|v| v["Message"].as_str().map(|s| s.to_string()))
+				.unwrap_or(String::from_utf8_lossy(&data).to_string());
+			return Err(comms_error(message));
+		}
+		Ok(data)
+	}
+
+	fn call_json(&self, command: &str, query: &[(&str, &str)]) -> Result<serde_json::Value, Error> {
+		let data = self.call(command, query, None)?;
+		serde_json::from_slice(&data).map_err(|e| {
+			ErrorKind::WalletComms(format!("Unable to parse IPFS {} response, {}", command, e))
+				.into()
+		})
+	}
+
+	fn id(&self) -> Result<String, Error> {
+		let res = self.call_json("id", &[])?;
+		Ok(res["ID"].as_str().unwrap_or("").to_string())
+	}
+
+	fn add(&self, data: Vec<u8>) -> Result<String, Error> {
+		let res = self.call("add", &[("pin", "true"), ("cid-version", "1")], Some(data))?;
+		let res: serde_json::Value = serde_json::from_slice(&res).map_err(|e| {
+			ErrorKind::WalletComms(format!("Unable to parse IPFS add response, {}", e))
+		})?;
+		res["Hash"].as_str().map(|s| s.to_string()).ok_or(
+			ErrorKind::WalletComms("IPFS add response doesn't have the CID".to_string()).into(),
+		)
+	}
+
+	fn cat(&self, cid: &str) -> Result<Vec<u8>, Error> {
+		self.call("cat", &[("arg", cid)], None)
+	}
+
+	// CID of the name, None if the name is not published
+	fn resolve(&self, name: &str) -> Result<Option<String>, Error> {
+		let path = format!("/ipns/{}", name);
+		match self.call_json("name/resolve", &[("arg", &path), ("nocache", "true")]) {
+			Ok(res) => Ok(res["Path"]
+				.as_str()
+				.map(|p| p.trim_start_matches("/ipfs/").to_string())),
+			Err(e) => {
+				let message = format!("{}", e);
+				if message.contains("not found") || message.contains("could not resolve") {
+					Ok(None)
+				} else {
+					Err(e)
+				}
+			}
+		}
+	}
+
+	fn import_key(&self, key_name: &str, keys: &identity::ed25519::Keypair) -> Result<(), Error> {
+		match self.call("key/import", &[("arg", key_name)], Some(key_protobuf(keys))) {
+			Ok(_) => Ok(()),
+			Err(e) if format!("{}", e).contains("already exists") => Ok(()),
+			Err(e) => Err(e),
+		}
+	}
+
+	fn publish(&self, key_name: &str, cid: &str) -> Result<(), Error> {
+		let path = format!("/ipfs/{}", cid);
+		self.call_json(
+			"name/publish",
+			&[
+				("arg", &path),
+				("key", key_name),
+				("lifetime", MAILBOX_LIFETIME),
+				("allow-offline", "true"),
+			],
+		)?;
+		Ok(())
+	}
+
+	fn read_mailbox(&self, name: &str) -> Result<Mailbox, Error> {
+		let cid = match self.resolve(name)? {
+			Some(cid) => cid,
+			None => return Ok(Mailbox::default()),
+		};
+		let data = self.cat(&cid)?;
+		Ok(serde_json::from_slice(&data).unwrap_or_else(|e| {
+			warn!("IPFS mailbox {} has invalid content, {}", name, e);
+			Mailbox::default()
+		}))
+	}
+
+	/// Add the message to IPFS and list it in the mailbox of the recipient
+	fn drop_message(&self, recipient: &DalekPublicKey, message: &DropMessage) -> Result<(), Error> {
+		let content = serde_json::to_vec(message).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to convert drop message to json, {}", e))
+		})?;
+
+		let _l = MAILBOX_LOCK.lock();
+		let cid = self.add(content)?;
+		let keys = mailbox_keypair(recipient)?;
+		let name = mailbox_name(&keys);
+		let key_name = format!("mwc-drop-{}", name);
+		self.import_key(&key_name, &keys)?;
+
+		for _ in 0..PUBLISH_ATTEMPTS {
+			let mut mailbox = self.read_mailbox(&name)?;
+			if mailbox.contains(&cid) {
+				return Ok(());
+			}
+			mailbox.add(&cid, Utc::now().timestamp());
+			let mailbox_content = serde_json::to_vec(&mailbox).map_err(|e| {
+				ErrorKind::GenericError(format!("Unable to convert mailbox to json, {}", e))
+			})?;
+			let mailbox_cid = self.add(mailbox_content)?;
+			self.publish(&key_name, &mailbox_cid)?;
+		}
+		if self.read_mailbox(&name)?.contains(&cid) {
+			Ok(())
+		} else {
+			Err(ErrorKind::WalletComms(format!(
+				"Unable to update IPFS mailbox {}, it is changed by other senders",
+				name
+			))
+			.into())
+		}
+	}
+}
+
+fn seen_drops_file() -> Option<PathBuf> {
+	IPFS_DATA_DIR
+		.read()
+		.as_ref()
+		.map(|dir| dir.join(SEEN_DROPS_FILE))
+}
+
+fn load_seen_drops() -> HashSet<String> {
+	seen_drops_file()
+		.and_then(|path| fs::read_to_string(path).ok())
+		.and_then(|content| serde_json::from_str(&content).ok())
+		.unwrap_or_default()
+}
+
+fn save_seen_drops(seen: &HashSet<String>) {
+	if let Some(path) = seen_drops_file() {
+		let res = serde_json::to_string(seen)
+			.map_err(|e| format!("{}", e))
+			.and_then(|content| fs::write(&path, content).map_err(|e| format!("{}", e)));
+		if let Err(e) = res {
+			warn!(
+				"Unable to save processed IPFS drops to {}, {}",
+				path.display(),
+				e
+			);
+		}
+	}
+}
+
+/// Store-and-forward transport over IPFS
+pub struct IpfsChannel {
+	dest: String,
+	api: IpfsApi,
+}
+
+impl IpfsChannel {
+	/// Channel to the destination slatepack address. The listener doesn't need one, dest is
+	/// empty.
+	pub fn new(dest: &str) -> Result<Self, Error> {
+		if !dest.is_empty() {
+			parse_address(dest)?;
+		}
+		Ok(IpfsChannel {
+			dest: dest.trim().to_string(),
+			api: IpfsApi::new(),
+		})
+	}
+
+	// Process the new drops of the mailbox
+	fn poll(
+		&self,
+		wallet_secret: &DalekSecretKey,
+		mailbox: &str,
+		seen: &mut HashSet<String>,
+		handler: &dyn SlateTransportHandler,
+	) -> Result<(), Error> {
+		let drops = self.api.read_mailbox(mailbox)?.drops;
+		let mut changed = false;
+		for drop in &drops {
+			if seen.contains(&drop.cid) {
+				continue;
+			}
+			let message = self.api.cat(&drop.cid).and_then(|data| {
+				serde_json::from_slice::<DropMessage>(&data).map_err(|e| {
+					ErrorKind::WalletComms(format!("Invalid drop {}, {}", drop.cid, e)).into()
+				})
+			});
+			match message {
+				Ok(message) => {
+					if let Some(reply) = handler.on_message(&message.from, &message.message) {
+						let reply = DropMessage {
+							from: ipfs_address(wallet_secret),
+							message: reply,
+						};
+						if let Err(e) = parse_address(&message.from)
+							.and_then(|sender| self.api.drop_message(&sender, &reply))
+						{
+							warn!("Unable to drop the reply for {}, {}", message.from, e);
+						}
+					}
+				}
+				Err(e) => warn!("{}", e),
+			}
+			seen.insert(drop.cid.clone());
+			changed = true;
+		}
+		// The removed drops will not come back
+		let before = seen.len();
+		seen.retain(|cid| drops.iter().any(|d| &d.cid == cid));
+		if changed || seen.len() != before {
+			save_seen_drops(seen);
+		}
+		Ok(())
+	}
+}
+
+impl SlateSender for IpfsChannel {
+	fn check_other_wallet_version(
+		&self,
+		_destination_address: &String,
+	) -> Result<Option<(SlateVersion, Option<String>)>, Error> {
+		Ok(None)
+	}
+
+	fn send_tx(
+		&self,
+		_slate: &Slate,
+		_slate_content: SlatePurpose,
+		_slatepack_secret: &DalekSecretKey,
+		_recipient: Option<DalekPublicKey>,
+		_other_wallet_version: Option<(SlateVersion, Option<String>)>,
+	) -> Result<Slate, Error> {
+		Err(ErrorKind::WalletComms(
+			"ipfs delivery is asynchronous, the response slate can't be waited for. Please use 'send --method ipfs', the response is finalized by 'listen --method ipfs'".to_string(),
+		)
+		.into())
+	}
+}
+
+impl SlateTransport for IpfsChannel {
+	fn method(&self) -> &str {
+		IPFS_METHOD
+	}
+
+	fn validate_address(&self, dest: &str) -> Result<(), Error> {
+		parse_address(dest).map(|_| ())
+	}
+
+	fn address(&self, wallet_secret: &DalekSecretKey) -> Result<String, Error> {
+		Ok(ipfs_address(wallet_secret))
+	}
+
+	fn is_store_and_forward(&self) -> bool {
+		true
+	}
+
+	/// The response comes to the mailbox of this wallet
+	fn post_slate(
+		&self,
+		slate: &Slate,
+		slate_content: SlatePurpose,
+		slatepack_secret: &DalekSecretKey,
+		recipient: Option<DalekPublicKey>,
+	) -> Result<(), Error> {
+		let dest = parse_address(&self.dest)?;
+		let message = transport_message(
+			slate,
+			slate_content,
+			slatepack_secret,
+			Some(recipient.unwrap_or(dest)),
+		)?;
+		self.api.drop_message(
+			&dest,
+			&DropMessage {
+				from: ipfs_address(slatepack_secret),
+				message,
+			},
+		)?;
+
+		println!(
+			"slate [{}] for [{}] MWCs dropped for [{}]",
+			slate.id.to_string(),
+			amount_to_hr_string(slate.amount, false),
+			self.dest,
+		);
+		Ok(())
+	}
+
+	fn listen(
+		&self,
+		wallet_secret: &DalekSecretKey,
+		handler: &dyn SlateTransportHandler,
+	) -> Result<(), Error> {
+		let node_id = self.api.id().map_err(|e| {
+			ErrorKind::WalletComms(format!(
+				"IPFS node is not available at {}, {}",
+				self.api.url, e
+			))
+		})?;
+		let mailbox = mailbox_name(&mailbox_keypair(&DalekPublicKey::from(wallet_secret))?);
+		info!(
+			"Checking IPFS mailbox {} with the node {}",
+			mailbox, node_id
+		);
+
+		let mut seen = load_seen_drops();
+		loop {
+			if let Err(e) = self.poll(wallet_secret, &mailbox, &mut seen, handler) {
+				warn!("Unable to check IPFS mailbox {}, {}", mailbox, e);
+			}
+			if !listener_wait(handler, POLL_INTERVAL) {
+				return Ok(());
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_ipfs_mailbox() {
+		let secret = DalekSecretKey::from_bytes(&[1; 32]).unwrap();
+		let address = ipfs_address(&secret);
+		let public = parse_address(&address).unwrap();
+		assert_eq!(public, DalekPublicKey::from(&secret));
+		assert!(parse_address("not an address").is_err());
+		assert!(IpfsChannel::new("").is_ok());
+
+		// Every sender finds the same mailbox of the recipient
+		let keys = mailbox_keypair(&public).unwrap();
+		assert_eq!(
+			mailbox_name(&keys),
+			mailbox_name(&mailbox_keypair(&public).unwrap())
+		);
+		let other = DalekPublicKey::from(&DalekSecretKey::from_bytes(&[2; 32]).unwrap());
+		assert_ne!(
+			mailbox_name(&keys),
+			mailbox_name(&mailbox_keypair(&other).unwrap())
+		);
+		let protobuf = key_protobuf(&keys);
+		assert_eq!(protobuf.len(), 68);
+		assert_eq!(&protobuf[36..], keys.public().encode().as_ref());
+
+		// Expired and the oldest drops are removed
+		let now = Utc::now().timestamp();
+		let mut mailbox = Mailbox::default();
+		mailbox.add("expired", now - DROP_RETENTION_SECS - 1);
+		for i in 0..MAX_MAILBOX_DROPS {
+			mailbox.add(&format!("drop-{}", i), now);
+		}
+		assert!(!mailbox.contains("expired"));
+		assert_eq!(mailbox.drops.len(), MAX_MAILBOX_DROPS);
+		mailbox.add("new", now);
+		assert_eq!(mailbox.drops.len(), MAX_MAILBOX_DROPS);
+		assert!(!mailbox.contains("drop-0"));
+		assert!(mailbox.contains("drop-1"));
+		assert!(mailbox.contains("new"));
+	}
+}
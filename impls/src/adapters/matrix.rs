@@ -0,0 +1,476 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Matrix messenger transport. Slates are posted as text messages into a Matrix room
+//! through the client-server API of the wallet's homeserver. The destination is a room id
+//! (`!id:server`), a room alias (`#alias:server`) or a user (`@user:server`). For a user a
+//! direct room is created, the listener joins the rooms it is invited to.
+
+use crate::adapters::{
+	listener_wait, transport_message, transport_message_slate, SlateSender, SlateTransport,
+	SlateTransportHandler,
+};
+use crate::client_utils::Client;
+use crate::core::core::amount_to_hr_string;
+use crate::error::{Error, ErrorKind};
+use crate::util::RwLock;
+use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
+use grin_wallet_libwallet::slatepack::SlatePurpose;
+use grin_wallet_libwallet::{Slate, SlateVersion};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use url::form_urlencoded;
+use url::percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
+
+/// Transport method name
+pub const MATRIX_METHOD: &str = "matrix";
+
+// Long polling timeout of the sync request, must be below the http client read timeout
+const SYNC_TIMEOUT_MS: u64 = 15_000;
+// How long the sender waits for the response slate
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(120);
+// Pause before the listener retries a failed sync
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Matrix account of this wallet
+#[derive(Clone, Debug)]
+pub struct MatrixConfig {
+	/// Homeserver url, e.g. https://matrix.org
+	pub homeserver: String,
+	/// Access token of the account
+	pub access_token: String,
+}
+
+lazy_static! {
+	static ref MATRIX_CONFIG: RwLock<Option<MatrixConfig>> = RwLock::new(None);
+}
+
+/// Set the Matrix account. None disables the transport.
+pub fn set_matrix_config(config: Option<MatrixConfig>) {
+	*MATRIX_CONFIG.write() = config;
+}
+
+/// Current Matrix account
+pub fn get_matrix_config() -> Option<MatrixConfig> {
+	MATRIX_CONFIG.read().clone()
+}
+
+// Text message from a room
+#[derive(Debug, PartialEq)]
+struct RoomMessage {
+	room_id: String,
+	sender: String,
+	body: String,
+}
+
+// Result of a sync request
+#[derive(Debug, PartialEq)]
+struct SyncResult {
+	next_batch: String,
+	messages: Vec<RoomMessage>,
+	invites: Vec<String>,
+}
+
+/// Slate transport over Matrix
+pub struct MatrixChannel {
+	config: MatrixConfig,
+	dest: String,
+	client: Client,
+}
+
+impl MatrixChannel {
+	/// Channel to the destination. The listener doesn't need one, dest is empty.
+	pub fn new(dest: &str) -> Result<Self, Error> {
+		let config = get_matrix_config().ok_or(ErrorKind::WalletComms(
+			"Matrix transport is not configured, please set matrix_homeserver and matrix_access_token in the wallet config".to_string(),
+		))?;
+		Ok(MatrixChannel {
+			config,
+			dest: dest.trim_start_matches("matrix://").to_string(),
+			client: Client::new(false, None)?,
+		})
+	}
+
+	fn call(&self, method: &str, path: &str, body: Option<Value>) -> Result<Value, Error> {
+		let url = format!(
+			"{}/_matrix/client/v3/{}",
+			self.config.homeserver.trim_end_matches('/'),
+			path
+		);
+		let res = self
+			.client
+			.send_request_with_token(
+				method,
+				&url,
+				&self.config.access_token,
+				body.map(|b| b.to_string()),
+			)
+			.map_err(|e| ErrorKind::WalletComms(format!("Matrix request failed, {}", e)))?;
+		let res: Value = serde_json::from_str(&res).map_err(|e| {
+			ErrorKind::WalletComms(format!("Unable to parse Matrix response {}, {}", res, e))
+		})?;
+		if let Some(errcode) = res["errcode"].as_str() {
+			return Err(ErrorKind::WalletComms(format!(
+				"Matrix request failed, {}: {}",
+				errcode,
+				res["error"].as_str().unwrap_or("")
+			))
+			.into());
+		}
+		Ok(res)
+	}
+
+	fn user_id(&self) -> Result<String, Error> {
+		let res = self.call("GET", "account/whoami", None)?;
+		res["user_id"]
+			.as_str()
+			.map(|s| s.to_string())
+			.ok_or(ErrorKind::WalletComms("Matrix account user id is not found".to_string()).into())
+	}
+
+	fn join(&self, room: &str) -> Result<String, Error> {
+		let res = self.call(
+			"POST",
+			&format!("join/{}", encode_path(room)),
+			Some(json!({})),
+		)?;
+		room_id(&res)
+	}
+
+	// Room to exchange the slates with the destination
+	fn destination_room(&self) -> Result<String, Error> {
+		if self.dest.starts_with('@') {
+			let res = self.call(
+				"POST",
+				"createRoom",
+				Some(json!({
+					"invite": [self.dest],
+					"is_direct": true,
+					"preset": "trusted_private_chat",
+				})),
+			)?;
+			room_id(&res)
+		} else if self.dest.starts_with('!') || self.dest.starts_with('#') {
+			self.join(&self.dest)
+		} else {
+			Err(ErrorKind::WalletComms(format!(
+				"Invalid Matrix destination {}, expected a room id, room alias or user id",
+				self.dest
+			))
+			.into())
+		}
+	}
+
+	fn send_message(&self, room_id: &str, body: &str) -> Result<(), Error> {
+		let path = format!(
+			"rooms/{}/send/m.room.message/{}",
+			encode_path(room_id),
+			uuid::Uuid::new_v4()
+		);
+		self.call(
+			"PUT",
+			&path,
+			Some(json!({ "msgtype": "m.text", "body": body })),
+		)?;
+		Ok(())
+	}
+
+	// The first sync returns the history, it is requested with a minimal timeline
+	// and only its token is used.
+	fn sync(&self, since: Option<&str>) -> Result<SyncResult, Error> {
+		let path = match since {
+			Some(since) => format!(
+				"sync?timeout={}&since={}",
+				SYNC_TIMEOUT_MS,
+				encode_query(since)
+			),
+			None => format!(
+				"sync?timeout=0&filter={}",
+				encode_query(r#"{"room":{"timeline":{"limit":1}}}"#)
+			),
+		};
+		parse_sync(&self.call("GET", &path, None)?)
+	}
+}
+
+impl SlateSender for MatrixChannel {
+	fn check_other_wallet_version(
+		&self,
+		_destination_address: &String,
+	) -> Result<Option<(SlateVersion, Option<String>)>, Error> {
+		// Messenger can't query the other wallet
+		Ok(None)
+	}
+
+	fn send_tx(
+		&self,
+		slate: &Slate,
+		slate_content: SlatePurpose,
+		slatepack_secret: &DalekSecretKey,
+		recipient: Option<DalekPublicKey>,
+		_other_wallet_version: Option<(SlateVersion, Option<String>)>,
+	) -> Result<Slate, Error> {
+		let own_id = self.user_id()?;
+		let room_id = self.destination_room()?;
+		let mut since = self.sync(None)?.next_batch;
+
+		let message = transport_message(slate, slate_content, slatepack_secret, recipient)?;
+		self.send_message(&room_id, &message)?;
+
+		println!(
+			"slate [{}] for [{}] MWCs sent to [{}]",
+			slate.id.to_string(),
+			amount_to_hr_string(slate.amount, false),
+			self.dest,
+		);
+
+		let deadline = Instant::now() + RESPONSE_TIMEOUT;
+		while Instant::now() < deadline {
+			let res = self.sync(Some(&since))?;
+			since = res.next_batch;
+			for msg in res.messages {
+				if msg.room_id != room_id || msg.sender == own_id {
+					continue;
+				}
+				if let Some(response) = transport_message_slate(&msg.body, slatepack_secret) {
+					if response.id == slate.id {
+						return Ok(response);
+					}
+				}
+			}
+		}
+		Err(ErrorKind::WalletComms(format!(
+			"Matrix response for slate {} is not received from {}",
+			slate.id, self.dest
+		))
+		.into())
+	}
+}
+
+impl SlateTransport for MatrixChannel {
+	fn method(&self) -> &str {
+		MATRIX_METHOD
+	}
+
+	fn validate_address(&self, dest: &str) -> Result<(), Error> {
+		let dest = dest.trim_start_matches("matrix://");
+		if dest.len() > 1
+			&& (dest.starts_with('@') || dest.starts_with('!') || dest.starts_with('#'))
+		{
+			Ok(())
+		} else {
+			Err(ErrorKind::ArgumentError(format!(
+				"Invalid Matrix destination {}, expected a room id, room alias or user id",
+				dest
+			))
+			.into())
+		}
+	}
+
+	fn address(&self, _wallet_secret: &DalekSecretKey) -> Result<String, Error> {
+		self.user_id()
+	}
+
+	fn listen(
+		&self,
+		_wallet_secret: &DalekSecretKey,
+		handler: &dyn SlateTransportHandler,
+	) -> Result<(), Error> {
+		let own_id = self.user_id()?;
+		let mut res = self.sync(None)?;
+		// History is skipped, only the invites are processed
+		res.messages.clear();
+		loop {
+			if handler.is_stopped() {
+				return Ok(());
+			}
+			for room in &res.invites {
+				if let Err(e) = self.join(room) {
+					warn!("Unable to join Matrix room {}, {}", room, e);
+				}
+			}
+			for msg in &res.messages {
+				if msg.sender == own_id {
+					continue;
+				}
+				if let Some(reply) = handler.on_message(&msg.sender, &msg.body) {
+					if let Err(e) = self.send_message(&msg.room_id, &reply) {
+						error!("Unable to send reply to {}, {}", msg.sender, e);
+					}
+				}
+			}
+
+			let since = res.next_batch.clone();
+			res = loop {
+				match self.sync(Some(&since)) {
+					Ok(res) => break res,
+					Err(e) => {
+						warn!("Matrix sync failed, {}", e);
+						if !listener_wait(handler, RETRY_DELAY) {
+							return Ok(());
+						}
+					}
+				}
+			};
+		}
+	}
+}
+
+fn encode_path(segment: &str) -> String {
+	utf8_percent_encode(segment, PATH_SEGMENT_ENCODE_SET).to_string()
+}
+
+fn encode_query(value: &str) -> String {
+	form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+fn room_id(res: &Value) -> Result<String, Error> {
+	res["room_id"].as_str().map(|s| s.to_string()).ok_or(
+		ErrorKind::WalletComms(format!("Matrix response doesn't have room_id: {}", res)).into(),
+	)
+}
+
+fn parse_sync(res: &Value) -> Result<SyncResult, Error> {
+	let next_batch = res["next_batch"]
+		.as_str()
+		.ok_or(ErrorKind::WalletComms(
+			"Matrix sync response doesn't have next_batch".to_string(),
+		))?
+		.to_string();
+
+	let mut messages = Vec::new();
+	if let Some(rooms) = res["rooms"]["join"].as_object() {
+		for (room_id, room) in rooms {
+			let events = match room["timeline"]["events"].as_array() {
+				Some(events) => events,
+				None => continue,
+			};
+			for event in events {
+				if event["type"] != "m.room.message" || event["content"]["msgtype"] != "m.text" {
+					continue;
+				}
+				if let (Some(sender), Some(body)) =
+					(event["sender"].as_str(), event["content"]["body"].as_str())
+				{
+					messages.push(RoomMessage {
+						room_id: room_id.clone(),
+						sender: sender.to_string(),
+						body: body.to_string(),
+					});
+				}
+			}
+		}
+	}
+
+	let invites = match res["rooms"]["invite"].as_object() {
+		Some(invites) => invites.keys().cloned().collect(),
+		None => vec![],
+	};
+
+	Ok(SyncResult {
+		next_batch,
+		messages,
+		invites,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_sync() {
+		let res = json!({
+			"next_batch": "s72595_4483_1934",
+			"rooms": {
+				"join": {
+					"!room:example.org": {
+						"timeline": {
+							"events": [
+								{
+									"type": "m.room.message",
+									"sender": "@alice:example.org",
+									"content": { "msgtype": "m.text", "body": "BEGINSLATEPACK. ENDSLATEPACK." }
+								},
+								{
+									"type": "m.room.message",
+									"sender": "@alice:example.org",
+									"content": { "msgtype": "m.image", "body": "image.png" }
+								},
+								{
+									"type": "m.room.member",
+									"sender": "@bob:example.org",
+									"content": { "membership": "join" }
+								}
+							]
+						}
+					}
+				},
+				"invite": {
+					"!direct:example.org": { "invite_state": { "events": [] } }
+				}
+			}
+		});
+
+		assert_eq!(
+			parse_sync(&res).unwrap(),
+			SyncResult {
+				next_batch: "s72595_4483_1934".to_string(),
+				messages: vec![RoomMessage {
+					room_id: "!room:example.org".to_string(),
+					sender: "@alice:example.org".to_string(),
+					body: "BEGINSLATEPACK. ENDSLATEPACK.".to_string(),
+				}],
+				invites: vec!["!direct:example.org".to_string()],
+			}
+		);
+
+		assert!(parse_sync(&json!({ "rooms": {} })).is_err());
+		assert_eq!(
+			parse_sync(&json!({ "next_batch": "s1" })).unwrap(),
+			SyncResult {
+				next_batch: "s1".to_string(),
+				messages: vec![],
+				invites: vec![],
+			}
+		);
+	}
+
+	#[test]
+	fn test_create_transport() {
+		set_matrix_config(None);
+		assert!(crate::adapters::create_transport(MATRIX_METHOD, "@bob:example.org").is_err());
+		assert!(crate::adapters::create_transport("http", "")
+			.unwrap()
+			.is_none());
+
+		set_matrix_config(Some(MatrixConfig {
+			homeserver: "https://example.org".to_string(),
+			access_token: "token".to_string(),
+		}));
+		let transport =
+			crate::adapters::create_transport(MATRIX_METHOD, "matrix://@bob:example.org")
+				.unwrap()
+				.unwrap();
+		assert_eq!(transport.method(), MATRIX_METHOD);
+		set_matrix_config(None);
+	}
+
+	#[test]
+	fn test_encode() {
+		assert_eq!(encode_path("!room:example.org"), "!room:example.org");
+		assert_eq!(encode_path("#mwc:example.org"), "%23mwc:example.org");
+		assert_eq!(encode_query("s1_2 3"), "s1_2+3");
+	}
+}
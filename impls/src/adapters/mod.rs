@@ -12,14 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod alert;
+pub mod email;
 mod file;
 pub mod http;
+pub mod ipfs;
 pub mod libp2p_messaging;
+pub mod matrix;
 mod mwcmq;
+pub mod nostr;
+pub mod p2p;
+mod swap_dispatcher;
+pub mod telegram;
 mod types;
+mod webhook;
 
+pub use self::alert::AlertCommandNotifier;
+pub use self::email::EmailChannel;
 pub use self::file::{PathToSlateGetter, PathToSlatePutter};
 pub use self::http::HttpDataSender;
+pub use self::ipfs::IpfsChannel;
+pub use self::matrix::MatrixChannel;
+pub use self::nostr::NostrChannel;
+pub use self::p2p::P2pChannel;
+pub use self::swap_dispatcher::SwapMessageDispatcher;
+pub use self::telegram::TelegramChannel;
+pub use self::webhook::WebhookNotifier;
 
 use crate::config::{TorConfig, WalletConfig};
 use crate::error::{Error, ErrorKind};
@@ -31,8 +49,13 @@ use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
 use grin_wallet_libwallet::slatepack::SlatePurpose;
 use grin_wallet_libwallet::{SlateVersion, Slatepacker};
 pub use mwcmq::{
-	get_mwcmqs_brocker, init_mwcmqs_access_data, MWCMQPublisher, MWCMQSubscriber, MwcMqsChannel,
+	flush_mwcmqs_outbox, get_mwcmqs_brocker, get_mwcmqs_outbox, init_mwcmqs_access_data,
+	init_mwcmqs_outbox, is_queued, MWCMQPublisher, MWCMQSubscriber, MqsMessageKind,
+	MqsQueuedMessage, MwcMqsChannel,
 };
+use std::cmp;
+use std::thread;
+use std::time::{Duration, Instant};
 pub use types::{
 	Address, AddressType, CloseReason, HttpsAddress, MWCMQSAddress, Publisher, Subscriber,
 	SubscriptionHandler,
@@ -72,6 +95,164 @@ pub trait SlateReceiver {
 	) -> Result<(), Error>;
 }
 
+/// Receives the messages that arrive over a `SlateTransport`
+pub trait SlateTransportHandler: Send {
+	/// Process the message from `from`. Return the reply for the author, None if the
+	/// message doesn't need an answer (not a slate, or a response slate).
+	fn on_message(&self, from: &str, message: &str) -> Option<String>;
+
+	/// True if the listener is asked to stop. The transport checks it between the messages
+	/// and while it waits, and returns from `listen`.
+	fn is_stopped(&self) -> bool {
+		false
+	}
+}
+
+// How often the waiting listener checks the stop request
+const LISTENER_STOP_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Wait for the delay, or less if the listener is asked to stop. Returns false if the
+/// listener is stopped.
+pub fn listener_wait(handler: &dyn SlateTransportHandler, delay: Duration) -> bool {
+	let until = Instant::now() + delay;
+	while !handler.is_stopped() {
+		let now = Instant::now();
+		if now >= until {
+			return true;
+		}
+		thread::sleep(cmp::min(until - now, LISTENER_STOP_CHECK_INTERVAL));
+	}
+	false
+}
+
+/// Messenger based transport. The slates are exchanged as text messages, Slatepack for
+/// the compact slates and JSON for the rest. The sending side is a usual `SlateSender` that
+/// waits for the response message; the listening side passes every incoming message to the
+/// handler and posts the reply back. `send`, `listen` and the swap commands work with any
+/// transport that `create_transport` returns, so a new messenger is a module here plus its
+/// registration.
+pub trait SlateTransport: SlateSender + Send {
+	/// Transport name, the `--method` value
+	fn method(&self) -> &str;
+
+	/// Address of this wallet in the messenger. `wallet_secret` is the wallet slatepack key,
+	/// the transports with a key based identity derive their key from it.
+	fn address(&self, wallet_secret: &DalekSecretKey) -> Result<String, Error>;
+
+	/// Check the destination address of the transport
+	fn validate_address(&self, dest: &str) -> Result<(), Error>;
+
+	/// True if the transport delivers the swap messages
+	fn supports_swap_messages(&self) -> bool {
+		false
+	}
+
+	/// Send the swap message to the destination. Return true if the message delivery
+	/// acknowledge can be set.
+	fn send_swap_message(&self, _swap_message: &Message) -> Result<bool, Error> {
+		Err(ErrorKind::WalletComms(format!(
+			"{} transport doesn't deliver the swap messages",
+			self.method()
+		))
+		.into())
+	}
+
+	/// True if the messages are stored until the other wallet picks them up. The sender
+	/// doesn't wait for the reply, the listener finalizes the response slates.
+	fn is_store_and_forward(&self) -> bool {
+		false
+	}
+
+	/// Deliver the slate without waiting for the response. Only the store-and-forward
+	/// transports support it.
+	fn post_slate(
+		&self,
+		_slate: &Slate,
+		_slate_content: SlatePurpose,
+		_slatepack_secret: &DalekSecretKey,
+		_recipient: Option<DalekPublicKey>,
+	) -> Result<(), Error> {
+		Err(ErrorKind::WalletComms(format!(
+			"{} transport can't deliver the slate without waiting for the response",
+			self.method()
+		))
+		.into())
+	}
+
+	/// Receive the messages and reply to them. Returns if the listener can't start or the
+	/// handler is stopped.
+	fn listen(
+		&self,
+		wallet_secret: &DalekSecretKey,
+		handler: &dyn SlateTransportHandler,
+	) -> Result<(), Error>;
+}
+
+impl SwapMessageSender for Box<dyn SlateTransport> {
+	fn send_swap_message(&self, swap_message: &Message) -> Result<bool, Error> {
+		(**self).send_swap_message(swap_message)
+	}
+}
+
+impl SlateSender for Box<dyn SlateTransport> {
+	fn check_other_wallet_version(
+		&self,
+		destination_address: &String,
+	) -> Result<Option<(SlateVersion, Option<String>)>, Error> {
+		(**self).check_other_wallet_version(destination_address)
+	}
+
+	fn send_tx(
+		&self,
+		slate: &Slate,
+		slate_content: SlatePurpose,
+		slatepack_secret: &DalekSecretKey,
+		recipient: Option<DalekPublicKey>,
+		other_wallet_version: Option<(SlateVersion, Option<String>)>,
+	) -> Result<Slate, Error> {
+		(**self).send_tx(
+			slate,
+			slate_content,
+			slatepack_secret,
+			recipient,
+			other_wallet_version,
+		)
+	}
+}
+
+/// Text of the slate for a messenger transport: Slatepack for the compact slate, JSON for
+/// the rest. JSON is encrypted into the envelope if the recipient is known.
+pub fn transport_message(
+	slate: &Slate,
+	slate_content: SlatePurpose,
+	slatepack_secret: &DalekSecretKey,
+	recipient: Option<DalekPublicKey>,
+) -> Result<String, Error> {
+	let putter = if slate.compact_slate {
+		PathToSlatePutter::build_encrypted(
+			None,
+			slate_content,
+			DalekPublicKey::from(slatepack_secret),
+			recipient,
+			true,
+		)
+	} else if let Some(recipient) = recipient {
+		PathToSlatePutter::build_envelope(None, recipient)
+	} else {
+		PathToSlatePutter::build_plain(None)
+	};
+	putter.put_tx(slate, slatepack_secret, false)
+}
+
+/// Slate from the messenger transport message, None if the message is not a slate
+pub fn transport_message_slate(message: &str, slatepack_secret: &DalekSecretKey) -> Option<Slate> {
+	PathToSlateGetter::build_form_str(message.to_string())
+		.get_tx(slatepack_secret)
+		.and_then(|data| data.to_slate())
+		.ok()
+		.map(|(slate, _, _, _, _)| slate)
+}
+
 /// Posts slates to be read later by a corresponding getter
 pub trait SlatePutter {
 	/// Send a transaction synchronously. Return content that was stored/sent
@@ -89,6 +270,10 @@ pub enum SlateGetData {
 	PlainSlate(Slate),
 	/// Encoded Slatepack
 	Slatepack(Slatepacker),
+	/// Grin V4 compact slate with its content
+	GrinV4(Slate, SlatePurpose),
+	/// Plain slate from the encrypted envelope with its sender and recipient
+	Envelope(Slate, DalekPublicKey, DalekPublicKey),
 }
 
 /// Checks for a transaction from a corresponding SlatePutter, returns the transaction if it exists
@@ -115,6 +300,24 @@ impl SlateGetData {
 		match &self {
 			SlateGetData::PlainSlate(_) => false,
 			SlateGetData::Slatepack(_) => true,
+			SlateGetData::GrinV4(_, _) => false,
+			SlateGetData::Envelope(_, _, _) => true,
+		}
+	}
+
+	/// Check if the slate came from grin wallet, so the response should be a Grin V4 slate too
+	pub fn is_grin_v4(&self) -> bool {
+		match &self {
+			SlateGetData::GrinV4(_, _) => true,
+			_ => false,
+		}
+	}
+
+	/// Sender of the envelope, the response goes back in the envelope for it
+	pub fn envelope_sender(&self) -> Option<DalekPublicKey> {
+		match &self {
+			SlateGetData::Envelope(_, sender, _) => Some(*sender),
+			_ => None,
 		}
 	}
 
@@ -134,6 +337,14 @@ impl SlateGetData {
 	> {
 		let res = match self {
 			SlateGetData::PlainSlate(slate) => (slate, None, None, SlatePurpose::FullSlate, false),
+			SlateGetData::GrinV4(slate, content) => (slate, None, None, content, false),
+			SlateGetData::Envelope(slate, sender, recipient) => (
+				slate,
+				Some(sender),
+				Some(recipient),
+				SlatePurpose::FullSlate,
+				false,
+			),
 			SlateGetData::Slatepack(slatepacker) => {
 				let sender = slatepacker.get_sender();
 				let recipient = slatepacker.get_recipient();
@@ -203,12 +414,50 @@ pub fn create_sender(
 			}
 		},
 		"mwcmqs" => Box::new(MwcMqsChannel::new(dest.to_string())),
-		_ => {
-			return Err(handle_unsupported_types(method));
-		}
+		_ => match create_transport(method, dest)? {
+			Some(transport) => Box::new(transport),
+			None => return Err(handle_unsupported_types(method)),
+		},
 	})
 }
 
+/// Create the messenger transport for the method, None if the method is not a messenger.
+/// This is the registry of the messenger transports. `dest` is empty for the listener,
+/// otherwise it is validated by the transport.
+pub fn create_transport(
+	method: &str,
+	dest: &str,
+) -> Result<Option<Box<dyn SlateTransport>>, Error> {
+	let transport: Box<dyn SlateTransport> = match method {
+		matrix::MATRIX_METHOD => Box::new(MatrixChannel::new(dest)?),
+		nostr::NOSTR_METHOD => Box::new(NostrChannel::new(dest)?),
+		p2p::P2P_METHOD => Box::new(P2pChannel::new(dest)?),
+		ipfs::IPFS_METHOD => Box::new(IpfsChannel::new(dest)?),
+		email::EMAIL_METHOD => Box::new(EmailChannel::new(dest)?),
+		telegram::TELEGRAM_METHOD => Box::new(TelegramChannel::new(dest)?),
+		_ => return Ok(None),
+	};
+	if !dest.is_empty() {
+		transport.validate_address(dest)?;
+	}
+	Ok(Some(transport))
+}
+
+/// Create the messenger transport for the swap messages, None if the method is not
+/// a messenger. Error if the transport doesn't deliver the swap messages.
+pub fn create_swap_transport(
+	method: &str,
+	dest: &str,
+) -> Result<Option<Box<dyn SlateTransport>>, Error> {
+	match create_transport(method, dest)? {
+		Some(transport) if !transport.supports_swap_messages() => Err(ErrorKind::WalletComms(
+			format!("{} transport doesn't deliver the swap messages", method),
+		)
+		.into()),
+		transport => Ok(transport),
+	}
+}
+
 /// create a Swap Message Sender
 pub fn create_swap_message_sender(
 	method: &str,
@@ -239,9 +488,10 @@ pub fn create_swap_message_sender(
 			)
 		}
 		"mwcmqs" => Box::new(MwcMqsChannel::new(dest.to_string())),
-		_ => {
-			return Err(handle_unsupported_types(method));
-		}
+		_ => match create_swap_transport(method, dest)? {
+			Some(transport) => Box::new(transport),
+			None => return Err(handle_unsupported_types(method)),
+		},
 	})
 }
 
@@ -12,6 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::swap_dispatcher::{
+	SwapMessageDispatcher, SWAP_MESSAGE_QUEUE_SIZE, SWAP_MESSAGE_WORKERS,
+};
 use super::types::{Address, Publisher, Subscriber, SubscriptionHandler};
 use crate::adapters::types::MWCMQSAddress;
 use crate::error::{Error, ErrorKind};
@@ -23,10 +26,13 @@ use crate::core::core::amount_to_hr_string;
 use crate::util::RwLock;
 use crate::SlateSender;
 use crate::SwapMessageSender;
+use chrono::{DateTime, Utc};
 use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
+use grin_wallet_libwallet::listener_status::{self, MWCMQS_LISTENER};
 use grin_wallet_libwallet::proof::message::EncryptedMessage;
 use grin_wallet_libwallet::proof::proofaddress::ProvableAddress;
 use grin_wallet_libwallet::proof::tx_proof::{push_proof_for_slate, TxProof};
+use grin_wallet_libwallet::slate_limits;
 use grin_wallet_libwallet::slatepack::SlatePurpose;
 use grin_wallet_libwallet::swap::message::Message;
 use grin_wallet_libwallet::swap::message::SwapMessage;
@@ -34,17 +40,33 @@ use grin_wallet_libwallet::{Slate, SlateVersion, VersionedSlate};
 use grin_wallet_util::grin_util::secp::key::SecretKey;
 use regex::Regex;
 use std::collections::HashMap;
+use std::fs;
 use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{thread, time};
 
 extern crate nanoid;
 
 const TIMEOUT_ERROR_REGEX: &str = r"timed out";
 
+// How long the sender waits for the response slate. Queued slates expire after that.
+const SLATE_RESPONSE_TIMEOUT_SEC: u64 = 120;
+// Queued swap messages expire after that, the swap will resend them anyway
+const SWAP_MESSAGE_QUEUE_TTL_SEC: i64 = 3600;
+// Outbox retry delay doubles with every attempt from the first till the max
+const OUTBOX_FIRST_RETRY_SEC: i64 = 5;
+const OUTBOX_MAX_RETRY_SEC: i64 = 600;
+// How often the listener checks the outbox
+const OUTBOX_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+// How often the connection state is checked while waiting for the listener
+const CONNECTION_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+/// File in the wallet data dir with the messages that are waiting for delivery
+pub const MWCMQS_OUTBOX_FILE: &str = "mwcmqs_outbox.json";
+
 // MQS enforced to have a single instance. And different compoments migth manage
 // instances separatlly.
 // Also all dependent components want to use MQS and they need interface.
@@ -68,6 +90,209 @@ pub fn reset_mwcmqs_brocker() {
 	MWCMQS_BROKER.write().take();
 }
 
+/// Kind of the queued MQS message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum MqsMessageKind {
+	/// Slate, the payload is the plain versioned slate JSON
+	Slate,
+	/// Swap message, the payload is the message JSON
+	SwapMessage,
+}
+
+/// Outbound MQS message that the broker didn't accept yet
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MqsQueuedMessage {
+	/// Slate or swap message id
+	pub id: String,
+	/// Kind of the message
+	pub kind: MqsMessageKind,
+	/// Destination mwcmqs address
+	pub to: String,
+	/// Message data
+	pub payload: String,
+	/// Time when the message was queued
+	pub created: DateTime<Utc>,
+	/// The message is dropped if it is not delivered till that time
+	pub expires: DateTime<Utc>,
+	/// Number of failed delivery attempts
+	pub attempts: u32,
+	/// Time of the next delivery attempt
+	pub next_attempt: DateTime<Utc>,
+	/// Error of the last delivery attempt
+	pub last_error: String,
+}
+
+impl MqsQueuedMessage {
+	fn is_same(&self, other: &MqsQueuedMessage) -> bool {
+		self.id == other.id && self.kind == other.kind && self.to == other.to
+	}
+
+	fn retry_delay(attempts: u32) -> chrono::Duration {
+		let delay = OUTBOX_FIRST_RETRY_SEC
+			.checked_shl(attempts.saturating_sub(1).min(16))
+			.unwrap_or(OUTBOX_MAX_RETRY_SEC);
+		chrono::Duration::seconds(delay.min(OUTBOX_MAX_RETRY_SEC))
+	}
+}
+
+// Outbound queue. The messages are kept in the wallet data dir, so the ones that are not
+// delivered before the wallet stops are retried at the next start of the mwcmqs listener.
+#[derive(Default)]
+struct MqsOutbox {
+	file: Option<PathBuf>,
+	messages: Vec<MqsQueuedMessage>,
+}
+
+impl MqsOutbox {
+	fn load(file: PathBuf) -> Result<Self, Error> {
+		let messages = if file.exists() {
+			let data = fs::read_to_string(&file).map_err(|e| {
+				ErrorKind::MqsGenericError(format!(
+					"Unable to read mwcmqs outbox {}, {}",
+					file.display(),
+					e
+				))
+			})?;
+			serde_json::from_str(&data).map_err(|e| {
+				ErrorKind::MqsGenericError(format!(
+					"Unable to parse mwcmqs outbox {}, {}",
+					file.display(),
+					e
+				))
+			})?
+		} else {
+			vec![]
+		};
+		Ok(MqsOutbox {
+			file: Some(file),
+			messages,
+		})
+	}
+
+	fn save(&self) {
+		if let Some(file) = &self.file {
+			let res = serde_json::to_string_pretty(&self.messages)
+				.map_err(|e| e.to_string())
+				.and_then(|data| fs::write(file, data).map_err(|e| e.to_string()));
+			if let Err(e) = res {
+				error!("Unable to save mwcmqs outbox {}, {}", file.display(), e);
+			}
+		}
+	}
+
+	fn push(&mut self, message: MqsQueuedMessage) {
+		self.messages.retain(|m| !m.is_same(&message));
+		self.messages.push(message);
+		self.save();
+	}
+
+	// Expired messages are dropped, the due ones are returned
+	fn due(&mut self, now: DateTime<Utc>, force: bool) -> Vec<MqsQueuedMessage> {
+		let len = self.messages.len();
+		self.messages.retain(|m| {
+			if m.expires <= now {
+				warn!(
+					"Dropping mwcmqs message {} for {}, it is not delivered in time, {}",
+					m.id, m.to, m.last_error
+				);
+			}
+			m.expires > now
+		});
+		if self.messages.len() != len {
+			self.save();
+		}
+		self.messages
+			.iter()
+			.filter(|m| force || m.next_attempt <= now)
+			.cloned()
+			.collect()
+	}
+
+	fn delivered(&mut self, message: &MqsQueuedMessage) {
+		self.messages.retain(|m| !m.is_same(message));
+		self.save();
+	}
+
+	fn failed(&mut self, message: &MqsQueuedMessage, error: String, now: DateTime<Utc>) {
+		if let Some(m) = self.messages.iter_mut().find(|m| m.is_same(message)) {
+			m.attempts += 1;
+			m.next_attempt = now + MqsQueuedMessage::retry_delay(m.attempts);
+			m.last_error = error;
+		}
+		self.save();
+	}
+}
+
+lazy_static! {
+	static ref MWCMQS_OUTBOX: Mutex<MqsOutbox> = Mutex::new(MqsOutbox::default());
+}
+
+/// Load the outbox of the wallet. The messages that are queued without the wallet data dir
+/// are kept in memory only.
+pub fn init_mwcmqs_outbox(data_dir: &Path) -> Result<(), Error> {
+	let file = data_dir.join(MWCMQS_OUTBOX_FILE);
+	let mut outbox = MWCMQS_OUTBOX.lock();
+	if outbox.file.as_ref() != Some(&file) {
+		*outbox = MqsOutbox::load(file)?;
+	}
+	Ok(())
+}
+
+/// Messages that are waiting for delivery
+pub fn get_mwcmqs_outbox() -> Vec<MqsQueuedMessage> {
+	MWCMQS_OUTBOX.lock().messages.clone()
+}
+
+/// Try to deliver all queued messages now. Needs the running mwcmqs listener. Returns the
+/// messages that are still not delivered.
+pub fn flush_mwcmqs_outbox() -> Result<Vec<MqsQueuedMessage>, Error> {
+	let (publisher, _subscriber) = get_mwcmqs_brocker().ok_or(ErrorKind::MqsGenericError(
+		"MQS is not started, not able to deliver the queued messages".to_string(),
+	))?;
+	publisher.deliver_outbox(true);
+	Ok(get_mwcmqs_outbox())
+}
+
+fn queue_mwcmqs_message(
+	kind: MqsMessageKind,
+	id: String,
+	to: &MWCMQSAddress,
+	payload: String,
+	ttl: chrono::Duration,
+	error: &Error,
+) -> Error {
+	let now = Utc::now();
+	MWCMQS_OUTBOX.lock().push(MqsQueuedMessage {
+		id: id.clone(),
+		kind,
+		to: to.get_stripped(),
+		payload,
+		created: now,
+		expires: now + ttl,
+		attempts: 1,
+		next_attempt: now + MqsQueuedMessage::retry_delay(1),
+		last_error: error.to_string(),
+	});
+	warn!("mwcmqs message {} for {} is queued, {}", id, to, error);
+	ErrorKind::MqsMessageQueued(id).into()
+}
+
+// Broker is not reachable or didn't confirm the message, worth to retry
+fn is_delivery_error(e: &Error) -> bool {
+	match e.kind() {
+		ErrorKind::MqsInvalidRespose(_) => true,
+		_ => false,
+	}
+}
+
+/// Check if the post failed but the message is in the outbox
+pub fn is_queued(e: &Error) -> bool {
+	match e.kind() {
+		ErrorKind::MqsMessageQueued(_) => true,
+		_ => false,
+	}
+}
+
 pub struct MwcMqsChannel {
 	des_address: String,
 }
@@ -88,25 +313,32 @@ impl MwcMqsChannel {
 		let des_address = MWCMQSAddress::from_str(self.des_address.as_ref()).map_err(|e| {
 			ErrorKind::MqsGenericError(format!("Invalid destination address, {}", e))
 		})?;
-		mwcmqs_publisher
-			.post_slate(&slate, &des_address)
-			.map_err(|e| {
-				ErrorKind::MqsGenericError(format!(
+		match mwcmqs_publisher.post_slate(&slate, &des_address) {
+			Ok(_) => println!(
+				"slate [{}] for [{}] MWCs sent to [{}]",
+				slate.id.to_string(),
+				amount_to_hr_string(slate.amount, false),
+				des_address,
+			),
+			// The listener will retry while we are waiting for the response
+			Err(e) if is_queued(&e) => println!(
+				"slate [{}] for [{}] MWCs is not accepted by mwcmqs, retrying to send it to [{}]",
+				slate.id.to_string(),
+				amount_to_hr_string(slate.amount, false),
+				des_address,
+			),
+			Err(e) => {
+				return Err(ErrorKind::MqsGenericError(format!(
 					"MQS unable to transfer slate {} to the worker, {}",
 					slate.id, e
 				))
-			})?;
-
-		println!(
-			"slate [{}] for [{}] MWCs sent to [{}]",
-			slate.id.to_string(),
-			amount_to_hr_string(slate.amount, false),
-			des_address,
-		);
+				.into())
+			}
+		}
 
 		//expect to get slate back.
 		let slate_returned = rx_slate
-			.recv_timeout(Duration::from_secs(120))
+			.recv_timeout(Duration::from_secs(SLATE_RESPONSE_TIMEOUT_SEC))
 			.map_err(|e| {
 				ErrorKind::MqsGenericError(format!(
 					"MQS unable to process slate {}, {}",
@@ -125,15 +357,15 @@ impl MwcMqsChannel {
 		let des_address = MWCMQSAddress::from_str(self.des_address.as_ref()).map_err(|e| {
 			ErrorKind::MqsGenericError(format!("Invalid destination address, {}", e))
 		})?;
-		mwcmqs_publisher
-			.post_take(swap_message, &des_address)
-			.map_err(|e| {
-				ErrorKind::MqsGenericError(format!(
-					"MQS unable to transfer swap message {} to the worker, {}",
-					swap_message.id, e
-				))
-			})?;
-		Ok(())
+		match mwcmqs_publisher.post_take(swap_message, &des_address) {
+			// Queued message will be delivered by the listener
+			Err(e) if !is_queued(&e) => Err(ErrorKind::MqsGenericError(format!(
+				"MQS unable to transfer swap message {} to the worker, {}",
+				swap_message.id, e
+			))
+			.into()),
+			_ => Ok(()),
+		}
 	}
 }
 
@@ -214,14 +446,41 @@ impl MWCMQPublisher {
 			secret_key: secret_key.clone(),
 		}
 	}
+
+	/// Send the queued messages that are due, all of them if `force` is true
+	pub fn deliver_outbox(&self, force: bool) {
+		self.broker
+			.deliver_outbox(&self.address, &self.secret_key, force);
+	}
 }
 impl Publisher for MWCMQPublisher {
 	fn post_slate(&self, slate: &Slate, to: &dyn Address) -> Result<(), Error> {
 		let to_address_raw = format!("mwcmqs://{}", to.get_stripped());
 		let to_address = MWCMQSAddress::from_str(&to_address_raw)?;
-		self.broker
-			.post_slate(slate, &to_address, &self.address, &self.secret_key)?;
-		Ok(())
+		match self
+			.broker
+			.post_slate(slate, &to_address, &self.address, &self.secret_key)
+		{
+			Err(e) if is_delivery_error(&e) => {
+				let version = slate.lowest_version();
+				let payload = serde_json::to_string(&VersionedSlate::into_version_plain(
+					slate.clone(),
+					version,
+				)?)
+				.map_err(|e| {
+					ErrorKind::MqsGenericError(format!("Unable convert Slate to Json, {}", e))
+				})?;
+				Err(queue_mwcmqs_message(
+					MqsMessageKind::Slate,
+					slate.id.to_string(),
+					&to_address,
+					payload,
+					chrono::Duration::seconds(SLATE_RESPONSE_TIMEOUT_SEC as i64),
+					&e,
+				))
+			}
+			res => res,
+		}
 	}
 
 	fn encrypt_slate(&self, slate: &Slate, to: &dyn Address) -> Result<String, Error> {
@@ -268,9 +527,28 @@ impl Publisher for MWCMQPublisher {
 	fn post_take(&self, message: &Message, to: &dyn Address) -> Result<(), Error> {
 		let to_address_raw = format!("mwcmqs://{}", to.get_stripped());
 		let to_address = MWCMQSAddress::from_str(&to_address_raw)?;
-		self.broker
-			.post_take(message, &to_address, &self.address, &self.secret_key)?;
-		Ok(())
+		match self
+			.broker
+			.post_take(message, &to_address, &self.address, &self.secret_key)
+		{
+			Err(e) if is_delivery_error(&e) => {
+				let payload = serde_json::to_string(message).map_err(|e| {
+					ErrorKind::MqsGenericError(format!(
+						"Unable to convert Swap Message to Json, {}",
+						e
+					))
+				})?;
+				Err(queue_mwcmqs_message(
+					MqsMessageKind::SwapMessage,
+					message.id.to_string(),
+					&to_address,
+					payload,
+					chrono::Duration::seconds(SWAP_MESSAGE_QUEUE_TTL_SEC),
+					&e,
+				))
+			}
+			res => res,
+		}
 	}
 
 	// Address of this publisher (from address)
@@ -294,9 +572,50 @@ impl MWCMQSubscriber {
 			secret_key: publisher.secret_key.clone(),
 		}
 	}
+
+	/// True when the listener is connected to the mwcmqs server and can send and receive
+	/// the messages.
+	pub fn is_connected(&self) -> bool {
+		self.broker.is_running() && self.broker.is_connected()
+	}
+
+	/// Wait until the listener is connected to the mwcmqs server. Returns false if the
+	/// listener is stopped or it is not connected within the timeout.
+	pub fn wait_for_connection(&self, timeout: Duration) -> bool {
+		let start = Instant::now();
+		loop {
+			if self.is_connected() {
+				return true;
+			}
+			// subscribe thread might not set the running flag yet, give it the first pause
+			if (!self.broker.is_running() && start.elapsed() > CONNECTION_CHECK_INTERVAL * 10)
+				|| start.elapsed() >= timeout
+			{
+				return false;
+			}
+			thread::sleep(CONNECTION_CHECK_INTERVAL);
+		}
+	}
 }
 impl Subscriber for MWCMQSubscriber {
 	fn start(&mut self) -> Result<(), Error> {
+		let broker = self.broker.clone();
+		let address = self.address.clone();
+		let secret_key = self.secret_key.clone();
+		thread::Builder::new()
+			.name("mwcmqs-outbox".to_string())
+			.spawn(move || {
+				// subscribe sets the running flag, it is checked after the first pause
+				thread::sleep(OUTBOX_CHECK_INTERVAL);
+				while broker.is_running() {
+					broker.deliver_outbox(&address, &secret_key, false);
+					thread::sleep(OUTBOX_CHECK_INTERVAL);
+				}
+			})
+			.map_err(|e| {
+				ErrorKind::MqsGenericError(format!("Unable to start mwcmqs outbox thread, {}", e))
+			})?;
+
 		self.broker
 			.subscribe(&self.address.address, &self.secret_key);
 		Ok(())
@@ -336,25 +655,23 @@ impl Subscriber for MWCMQSubscriber {
 	fn set_notification_channels(&self, slate_id: &uuid::Uuid, slate_send_channel: Sender<Slate>) {
 		self.broker
 			.handler
-			.lock()
 			.set_notification_channels(slate_id, slate_send_channel);
 	}
 
 	fn reset_notification_channels(&self, slate_id: &uuid::Uuid) {
-		self.broker
-			.handler
-			.lock()
-			.reset_notification_channels(slate_id);
+		self.broker.handler.reset_notification_channels(slate_id);
 	}
 }
 
 #[derive(Clone)]
 struct MWCMQSBroker {
 	running: Arc<AtomicBool>,
+	// true while the listener has a working connection to the mwcmqs server
+	connected: Arc<AtomicBool>,
 	pub mwcmqs_domain: String,
 	pub mwcmqs_port: u16,
 	pub print_to_log: bool,
-	pub handler: Arc<Mutex<Box<dyn SubscriptionHandler + Send>>>,
+	pub handler: Arc<Box<dyn SubscriptionHandler + Send>>,
 }
 
 impl MWCMQSBroker {
@@ -366,10 +683,11 @@ impl MWCMQSBroker {
 	) -> Self {
 		Self {
 			running: Arc::new(AtomicBool::new(false)),
+			connected: Arc::new(AtomicBool::new(false)),
 			mwcmqs_domain,
 			mwcmqs_port,
 			print_to_log,
-			handler: Arc::new(Mutex::new(handler)),
+			handler: Arc::new(handler),
 		}
 	}
 
@@ -600,6 +918,52 @@ impl MWCMQSBroker {
 		Ok(())
 	}
 
+	// The messages are posted directly to the broker, so a failed one is not queued again
+	fn deliver_outbox(&self, from: &MWCMQSAddress, secret_key: &SecretKey, force: bool) {
+		let now = Utc::now();
+		let due = MWCMQS_OUTBOX.lock().due(now, force);
+		for message in due {
+			let res = MWCMQSAddress::from_str(&format!("mwcmqs://{}", message.to)).and_then(|to| {
+				match message.kind {
+					MqsMessageKind::Slate => {
+						let slate = Slate::deserialize_upgrade_plain(&message.payload)?;
+						self.post_slate(&slate, &to, from, secret_key)
+					}
+					MqsMessageKind::SwapMessage => {
+						let swap_message: Message = serde_json::from_str(&message.payload)
+							.map_err(|e| {
+								ErrorKind::MqsGenericError(format!(
+									"Unable to parse queued swap message, {}",
+									e
+								))
+							})?;
+						self.post_take(&swap_message, &to, from, secret_key)
+					}
+				}
+			});
+			match res {
+				Ok(_) => {
+					self.do_log_info(format!(
+						"Queued mwcmqs message {} is delivered to [{}]",
+						message.id, message.to
+					));
+					MWCMQS_OUTBOX.lock().delivered(&message);
+				}
+				// Listener is stopping, the message will be sent after the restart
+				Err(e) if is_delivery_error(&e) || !self.is_running() => {
+					MWCMQS_OUTBOX.lock().failed(&message, e.to_string(), now)
+				}
+				Err(e) => {
+					self.do_log_error(format!(
+						"Dropping queued mwcmqs message {} for [{}], {}",
+						message.id, message.to, e
+					));
+					MWCMQS_OUTBOX.lock().delivered(&message);
+				}
+			}
+		}
+	}
+
 	fn print_error(&mut self, messages: Vec<&str>, error: &str, code: i16) {
 		self.do_log_error(format!(
 			"ERROR: messages=[{:?}] produced error: {} (code={})",
@@ -644,12 +1008,30 @@ impl MWCMQSBroker {
 		let secret_key = secret_key.clone();
 		let cloned_address = address.clone();
 		let cloned_running = self.running.clone();
+		let cloned_connected = self.connected.clone();
+		listener_status::listener_connected(MWCMQS_LISTENER, false);
 		let mut count = 0;
 		let mut connected = false;
 		let mut isnginxerror = false;
 		let mut delcount = 0;
 		let mut is_in_warning = false;
 
+		// Workers for the swap messages, they are stopped with the listener
+		let swap_dispatcher = match SwapMessageDispatcher::new(
+			"mwcmqs",
+			SWAP_MESSAGE_WORKERS,
+			SWAP_MESSAGE_QUEUE_SIZE,
+		) {
+			Ok(d) => d,
+			Err(e) => {
+				self.do_log_error(format!("Unable to start mwcmqs listener, {}", e));
+				self.running.store(false, Ordering::SeqCst);
+				listener_status::listener_error(MWCMQS_LISTENER, format!("{}", e));
+				listener_status::listener_stopped(MWCMQS_LISTENER);
+				return;
+			}
+		};
+
 		// get time from server
 		let mut time_now = "";
 		let mut is_error = false;
@@ -730,6 +1112,9 @@ impl MWCMQSBroker {
 				let mut resp_str = "".to_string();
 				count = count + 1;
 				let cloned_cloned_address = cloned_address.clone();
+				if cloned_connected.swap(connected, Ordering::SeqCst) != connected {
+					listener_status::listener_connected(MWCMQS_LISTENER, connected);
+				}
 
 				if !cloned_running.load(Ordering::SeqCst) {
 					break;
@@ -765,6 +1150,7 @@ impl MWCMQSBroker {
 							self.do_log_warn(format!("\nWARNING: mwcmqs listener [{}] lost connection. Will try to restore in the background. tid=[{}]",
 													 cloned_cloned_address.get_stripped(), nanoid ));
 						}
+						listener_status::listener_error(MWCMQS_LISTENER, err_message.clone());
 
 						let second = time::Duration::from_millis(5000);
 						thread::sleep(second);
@@ -800,6 +1186,7 @@ impl MWCMQSBroker {
 							cloned_cloned_address.get_stripped(),
 							nanoid
 						));
+						connected = true;
 					} else if !connected && !isnginxerror {
 						if is_in_warning {
 							self.do_log_info(format!(
@@ -1059,6 +1446,14 @@ impl MWCMQSBroker {
 									from.unwrap()
 								};
 
+								if let Err(e) = slate_limits::check_message_limits(r5.as_bytes()) {
+									self.do_log_error(format!(
+										"Rejected message from {}, {}",
+										from, e
+									));
+									continue;
+								}
+
 								if slate_or_swap == "slate" {
 									let (mut slate, tx_proof) = match TxProof::from_response(
 										&from.address,
@@ -1075,7 +1470,7 @@ impl MWCMQSBroker {
 										}
 									};
 									push_proof_for_slate(&slate.id, tx_proof);
-									self.handler.lock().on_slate(&from, &mut slate);
+									self.handler.on_slate(&from, &mut slate);
 								} else if slate_or_swap == "swap" {
 									let swap_message = match SwapMessage::from_received(
 										&from.address,
@@ -1090,16 +1485,31 @@ impl MWCMQSBroker {
 											continue;
 										}
 									};
-									let ack_message =
-										self.handler.lock().on_swap_message(swap_message);
-									if let Some(ack_message) = ack_message {
-										let mqs_cannel = MwcMqsChannel::new(from.to_string());
-										if let Err(e) = mqs_cannel.send_swap_message(&ack_message) {
-											self.do_log_error(format!(
-												"Unable to send back ack message, {}",
-												e
-											));
+									// Processed by the worker of the trade, the listener is not blocked
+									let swap_id = swap_message.id.to_string();
+									let broker = self.clone();
+									let from = from.to_string();
+									let res = swap_dispatcher.dispatch(&swap_id, move || {
+										let ack_message =
+											broker.handler.on_swap_message(swap_message);
+										if let Some(ack_message) = ack_message {
+											let mqs_cannel = MwcMqsChannel::new(from);
+											if let Err(e) =
+												mqs_cannel.send_swap_message(&ack_message)
+											{
+												broker.do_log_error(format!(
+													"Unable to send back ack message, {}",
+													e
+												));
+											}
 										}
+									});
+									if let Err(e) = res {
+										listener_status::listener_error(
+											MWCMQS_LISTENER,
+											format!("{}", e),
+										);
+										self.do_log_error(format!("{}", e));
 									}
 								}
 
@@ -1123,7 +1533,9 @@ impl MWCMQSBroker {
 			));
 		}
 
+		cloned_connected.store(false, Ordering::SeqCst);
 		cloned_running.store(false, Ordering::SeqCst);
+		listener_status::listener_stopped(MWCMQS_LISTENER);
 		reset_mwcmqs_brocker();
 	}
 
@@ -1134,4 +1546,67 @@ impl MWCMQSBroker {
 	fn is_running(&self) -> bool {
 		self.running.load(Ordering::SeqCst)
 	}
+
+	fn is_connected(&self) -> bool {
+		self.connected.load(Ordering::SeqCst)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn queued(id: &str, now: DateTime<Utc>) -> MqsQueuedMessage {
+		MqsQueuedMessage {
+			id: id.to_string(),
+			kind: MqsMessageKind::Slate,
+			to: "xmgwbyjMEMBojnVadEkwVi1GyL1WPiVE5dziQf3TLedHdrVBPGw5".to_string(),
+			payload: "{}".to_string(),
+			created: now,
+			expires: now + chrono::Duration::seconds(SLATE_RESPONSE_TIMEOUT_SEC as i64),
+			attempts: 1,
+			next_attempt: now + MqsQueuedMessage::retry_delay(1),
+			last_error: "broker is not available".to_string(),
+		}
+	}
+
+	#[test]
+	fn test_retry_delay() {
+		assert_eq!(MqsQueuedMessage::retry_delay(1).num_seconds(), 5);
+		assert_eq!(MqsQueuedMessage::retry_delay(2).num_seconds(), 10);
+		assert_eq!(MqsQueuedMessage::retry_delay(4).num_seconds(), 40);
+		assert_eq!(
+			MqsQueuedMessage::retry_delay(100).num_seconds(),
+			OUTBOX_MAX_RETRY_SEC
+		);
+	}
+
+	#[test]
+	fn test_outbox_retry() {
+		let now = Utc::now();
+		let mut outbox = MqsOutbox::default();
+		outbox.push(queued("a", now));
+		outbox.push(queued("b", now));
+		// the same message is queued once
+		outbox.push(queued("a", now));
+		assert_eq!(outbox.messages.len(), 2);
+
+		assert!(outbox.due(now, false).is_empty());
+		assert_eq!(outbox.due(now, true).len(), 2);
+
+		let later = now + chrono::Duration::seconds(OUTBOX_FIRST_RETRY_SEC);
+		let due = outbox.due(later, false);
+		assert_eq!(due.len(), 2);
+		outbox.delivered(&due[0]);
+		outbox.failed(&due[1], "timeout".to_string(), later);
+		assert_eq!(outbox.messages.len(), 1);
+		assert_eq!(outbox.messages[0].attempts, 2);
+		assert_eq!(outbox.messages[0].last_error, "timeout");
+		assert!(outbox.due(later, false).is_empty());
+
+		// expired messages are dropped
+		let expired = now + chrono::Duration::seconds(SLATE_RESPONSE_TIMEOUT_SEC as i64);
+		assert!(outbox.due(expired, true).is_empty());
+		assert!(outbox.messages.is_empty());
+	}
 }
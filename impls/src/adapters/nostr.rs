@@ -0,0 +1,817 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Nostr transport. Slates and swap messages are exchanged as NIP-04 encrypted direct
+//! messages (kind 4 events) through the configured relays. The nostr key of the wallet is
+//! derived from the wallet slatepack key, so the address (npub) doesn't change while the
+//! wallet seed is the same. The destination is the npub or the hex public key of the
+//! other wallet.
+
+use crate::adapters::{
+	listener_wait, transport_message, transport_message_slate, SlateSender, SlateTransport,
+	SlateTransportHandler,
+};
+use crate::blake2::blake2b::blake2b;
+use crate::core::core::amount_to_hr_string;
+use crate::error::{Error, ErrorKind};
+use crate::util::{from_hex, to_hex, RwLock};
+use aes::Aes256;
+use bech32::{FromBase32, ToBase32};
+use block_modes::block_padding::Pkcs7;
+use block_modes::{BlockMode, Cbc};
+use chrono::Utc;
+use data_encoding::BASE64;
+use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
+use grin_wallet_libwallet::slatepack::SlatePurpose;
+use grin_wallet_libwallet::{Slate, SlateVersion};
+use rand::{thread_rng, Rng};
+use secp256k1::ecdh::SharedSecret;
+use secp256k1::{schnorrsig, Message, PublicKey, Secp256k1, SecretKey};
+use serde_json::Value;
+use std::collections::{HashSet, VecDeque};
+use std::io;
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+use tungstenite::client::AutoStream;
+use tungstenite::stream::Stream as StreamSwitcher;
+use tungstenite::{Message as WsMessage, WebSocket};
+
+/// Transport method name
+pub const NOSTR_METHOD: &str = "nostr";
+
+/// Relays that are used if nostr_relays is not set in the wallet config
+pub const DEFAULT_NOSTR_RELAYS: [&str; 3] = [
+	"wss://relay.damus.io",
+	"wss://nos.lol",
+	"wss://relay.nostr.band",
+];
+
+// NIP-04 encrypted direct message
+const DM_KIND: u64 = 4;
+// Bech32 prefix of the public key
+const NPUB_HRP: &str = "npub";
+// Relay read timeout, the relays are polled in turn
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+// How long the sender waits for the response slate
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(120);
+// Pause before the listener reconnects to the relays
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+// Number of processed event ids that the listener remembers. The same event comes from
+// every relay.
+const SEEN_EVENTS_LIMIT: usize = 1000;
+
+lazy_static! {
+	static ref NOSTR_RELAYS: RwLock<Vec<String>> =
+		RwLock::new(DEFAULT_NOSTR_RELAYS.iter().map(|r| r.to_string()).collect());
+}
+
+/// Set the relays. None restores the default relays.
+pub fn set_nostr_relays(relays: Option<Vec<String>>) {
+	*NOSTR_RELAYS.write() = match relays {
+		Some(relays) => relays,
+		None => DEFAULT_NOSTR_RELAYS.iter().map(|r| r.to_string()).collect(),
+	};
+}
+
+/// Current relays
+pub fn get_nostr_relays() -> Vec<String> {
+	NOSTR_RELAYS.read().clone()
+}
+
+/// Nostr identity of the wallet
+pub struct NostrKeys {
+	secret: SecretKey,
+	keypair: schnorrsig::KeyPair,
+	public: [u8; 32],
+}
+
+impl NostrKeys {
+	/// Keys derived from the wallet slatepack key: h(wallet_secret|"nostr")
+	pub fn from_wallet_secret(wallet_secret: &DalekSecretKey) -> Result<Self, Error> {
+		let mut data = wallet_secret.as_bytes().to_vec();
+		data.extend_from_slice(b"nostr");
+		let hash = blake2b(32, &[], &data);
+		NostrKeys::from_secret_bytes(hash.as_bytes())
+	}
+
+	fn from_secret_bytes(bytes: &[u8]) -> Result<Self, Error> {
+		let secp = Secp256k1::new();
+		let secret = SecretKey::from_slice(bytes)
+			.map_err(|e| ErrorKind::GenericError(format!("Invalid nostr secret key, {}", e)))?;
+		let keypair = schnorrsig::KeyPair::from_seckey_slice(&secp, bytes)
+			.map_err(|e| ErrorKind::GenericError(format!("Invalid nostr secret key, {}", e)))?;
+		let public = schnorrsig::PublicKey::from_keypair(&secp, &keypair).serialize();
+		Ok(NostrKeys {
+			secret,
+			keypair,
+			public,
+		})
+	}
+
+	/// Public key as hex, the form that is used in the events
+	pub fn public_key_hex(&self) -> String {
+		to_hex(&self.public)
+	}
+
+	/// Public key as npub, the form that is shown to the user
+	pub fn npub(&self) -> Result<String, Error> {
+		public_key_to_npub(&self.public)
+	}
+}
+
+/// Encode the x-only public key as npub (NIP-19)
+pub fn public_key_to_npub(public_key: &[u8; 32]) -> Result<String, Error> {
+	bech32::encode(NPUB_HRP, public_key.to_base32()).map_err(|e| {
+		ErrorKind::GenericError(format!("Unable to build nostr npub address, {}", e)).into()
+	})
+}
+
+/// Public key from the npub or from the 64 chars hex string
+pub fn parse_public_key(addr: &str) -> Result<[u8; 32], Error> {
+	let addr = addr.trim().trim_start_matches("nostr:");
+	let bytes = if addr.to_lowercase().starts_with(NPUB_HRP) {
+		let (hrp, data) = bech32::decode(addr).map_err(|e| {
+			ErrorKind::WalletComms(format!("Unable to decode nostr address {}, {}", addr, e))
+		})?;
+		if hrp != NPUB_HRP {
+			return Err(ErrorKind::WalletComms(format!(
+				"Nostr address {} is not a public key",
+				addr
+			))
+			.into());
+		}
+		Vec::<u8>::from_base32(&data).map_err(|e| {
+			ErrorKind::WalletComms(format!("Unable to decode nostr address {}, {}", addr, e))
+		})?
+	} else {
+		from_hex(addr)
+			.map_err(|e| ErrorKind::WalletComms(format!("Invalid nostr address {}, {}", addr, e)))?
+	};
+	if bytes.len() != 32 || schnorrsig::PublicKey::from_slice(&bytes).is_err() {
+		return Err(ErrorKind::WalletComms(format!("Invalid nostr public key {}", addr)).into());
+	}
+	let mut public = [0; 32];
+	public.copy_from_slice(&bytes);
+	Ok(public)
+}
+
+/// Nostr event (NIP-01)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Event {
+	/// sha256 of the serialized event, hex
+	pub id: String,
+	/// Author public key, hex
+	pub pubkey: String,
+	/// Unix timestamp
+	pub created_at: i64,
+	/// Event kind
+	pub kind: u64,
+	/// Tags, 'p' is the recipient
+	pub tags: Vec<Vec<String>>,
+	/// Content, for the direct messages it is encrypted
+	pub content: String,
+	/// Schnorr signature of the id, hex
+	pub sig: String,
+}
+
+impl Event {
+	/// Direct message to the recipient, signed and encrypted by the keys
+	pub fn direct_message(
+		keys: &NostrKeys,
+		recipient: &[u8; 32],
+		text: &str,
+	) -> Result<Self, Error> {
+		let pubkey = keys.public_key_hex();
+		let created_at = Utc::now().timestamp();
+		let tags = vec![vec!["p".to_string(), to_hex(recipient)]];
+		let content = nip04_encrypt(keys, recipient, text)?;
+		let id = event_id(&pubkey, created_at, DM_KIND, &tags, &content);
+
+		let secp = Secp256k1::new();
+		let msg = Message::from_slice(&id)
+			.map_err(|e| ErrorKind::GenericError(format!("Invalid nostr event id, {}", e)))?;
+		let aux_rand: [u8; 32] = thread_rng().gen();
+		let sig = secp.schnorrsig_sign_with_aux_rand(&msg, &keys.keypair, &aux_rand);
+
+		Ok(Event {
+			id: to_hex(&id),
+			pubkey,
+			created_at,
+			kind: DM_KIND,
+			tags,
+			content,
+			sig: sig.to_string(),
+		})
+	}
+
+	/// Check the id and the signature
+	pub fn verify(&self) -> bool {
+		let id = event_id(
+			&self.pubkey,
+			self.created_at,
+			self.kind,
+			&self.tags,
+			&self.content,
+		);
+		if to_hex(&id) != self.id {
+			return false;
+		}
+		let pubkey = match from_hex(&self.pubkey)
+			.ok()
+			.and_then(|p| schnorrsig::PublicKey::from_slice(&p).ok())
+		{
+			Some(pubkey) => pubkey,
+			None => return false,
+		};
+		let sig = match self.sig.parse::<schnorrsig::Signature>() {
+			Ok(sig) => sig,
+			Err(_) => return false,
+		};
+		match Message::from_slice(&id) {
+			Ok(msg) => Secp256k1::verification_only()
+				.schnorrsig_verify(&sig, &msg, &pubkey)
+				.is_ok(),
+			Err(_) => false,
+		}
+	}
+
+	/// Text of the direct message from `sender` to the keys owner
+	pub fn decrypt(&self, keys: &NostrKeys) -> Result<String, Error> {
+		let sender = parse_public_key(&self.pubkey)?;
+		nip04_decrypt(keys, &sender, &self.content)
+	}
+}
+
+fn event_id(
+	pubkey: &str,
+	created_at: i64,
+	kind: u64,
+	tags: &[Vec<String>],
+	content: &str,
+) -> [u8; 32] {
+	let serialized = json!([0, pubkey, created_at, kind, tags, content]).to_string();
+	let digest = ring::digest::digest(&ring::digest::SHA256, serialized.as_bytes());
+	let mut id = [0; 32];
+	id.copy_from_slice(digest.as_ref());
+	id
+}
+
+// NIP-04 key is the x coordinate of the ECDH point, not hashed
+fn nip04_key(keys: &NostrKeys, other: &[u8; 32]) -> Result<[u8; 32], Error> {
+	let mut compressed = [2u8; 33];
+	compressed[1..].copy_from_slice(other);
+	let other = PublicKey::from_slice(&compressed)
+		.map_err(|e| ErrorKind::WalletComms(format!("Invalid nostr public key, {}", e)))?;
+	let shared = SharedSecret::new_with_hash(&other, &keys.secret, |x, _y| x.into());
+	let mut key = [0; 32];
+	key.copy_from_slice(&shared[..32]);
+	Ok(key)
+}
+
+fn nip04_encrypt(keys: &NostrKeys, recipient: &[u8; 32], text: &str) -> Result<String, Error> {
+	let key = nip04_key(keys, recipient)?;
+	let iv: [u8; 16] = thread_rng().gen();
+	let cipher = Cbc::<Aes256, Pkcs7>::new_var(&key, &iv)
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to init nostr cipher, {}", e)))?;
+	let encrypted = cipher.encrypt_vec(text.as_bytes());
+	Ok(format!(
+		"{}?iv={}",
+		BASE64.encode(&encrypted),
+		BASE64.encode(&iv)
+	))
+}
+
+fn nip04_decrypt(keys: &NostrKeys, sender: &[u8; 32], content: &str) -> Result<String, Error> {
+	let invalid = |e: String| ErrorKind::WalletComms(format!("Invalid nostr message, {}", e));
+	let mut parts = content.splitn(2, "?iv=");
+	let data = parts.next().unwrap_or("");
+	let iv = parts.next().ok_or(invalid("iv is not found".to_string()))?;
+	let data = BASE64
+		.decode(data.as_bytes())
+		.map_err(|e| invalid(e.to_string()))?;
+	let iv = BASE64
+		.decode(iv.as_bytes())
+		.map_err(|e| invalid(e.to_string()))?;
+
+	let key = nip04_key(keys, sender)?;
+	let cipher = Cbc::<Aes256, Pkcs7>::new_var(&key, &iv).map_err(|e| invalid(e.to_string()))?;
+	let text = cipher
+		.decrypt_vec(&data)
+		.map_err(|e| invalid(e.to_string()))?;
+	String::from_utf8(text).map_err(|e| invalid(e.to_string()).into())
+}
+
+// Message from the relay
+#[derive(Debug, PartialEq)]
+enum RelayMessage {
+	Event(String, Event),
+	EndOfStoredEvents(String),
+	Ok(String, bool, String),
+	Notice(String),
+}
+
+fn parse_relay_message(msg: &str) -> Option<RelayMessage> {
+	let msg: Vec<Value> = serde_json::from_str(msg).ok()?;
+	let str_at = |i: usize| msg.get(i).and_then(|v| v.as_str()).map(|s| s.to_string());
+	match msg.get(0)?.as_str()? {
+		"EVENT" => Some(RelayMessage::Event(
+			str_at(1)?,
+			serde_json::from_value(msg.get(2)?.clone()).ok()?,
+		)),
+		"EOSE" => Some(RelayMessage::EndOfStoredEvents(str_at(1)?)),
+		"OK" => Some(RelayMessage::Ok(
+			str_at(1)?,
+			msg.get(2)?.as_bool()?,
+			str_at(3).unwrap_or_default(),
+		)),
+		"NOTICE" => Some(RelayMessage::Notice(str_at(1)?)),
+		_ => None,
+	}
+}
+
+// Websocket connection to the relay
+struct Relay {
+	url: String,
+	socket: WebSocket<AutoStream>,
+}
+
+impl Relay {
+	fn connect(url: &str) -> Result<Self, Error> {
+		let err =
+			|e: String| ErrorKind::WalletComms(format!("Unable to connect to {}, {}", url, e));
+		let (socket, _) = tungstenite::connect(url).map_err(|e| err(e.to_string()))?;
+		let tcp: &TcpStream = match socket.get_ref() {
+			StreamSwitcher::Plain(s) => s,
+			StreamSwitcher::Tls(s) => s.get_ref(),
+		};
+		tcp.set_read_timeout(Some(READ_TIMEOUT))
+			.map_err(|e| err(e.to_string()))?;
+		Ok(Relay {
+			url: url.to_string(),
+			socket,
+		})
+	}
+
+	fn send(&mut self, msg: &Value) -> Result<(), Error> {
+		self.socket
+			.write_message(WsMessage::Text(msg.to_string()))
+			.map_err(|e| {
+				ErrorKind::WalletComms(format!("Unable to send to relay {}, {}", self.url, e))
+					.into()
+			})
+	}
+
+	// None if nothing arrived during the read timeout
+	fn read(&mut self) -> Result<Option<RelayMessage>, Error> {
+		match self.socket.read_message() {
+			Ok(WsMessage::Text(text)) => Ok(parse_relay_message(&text)),
+			Ok(_) => Ok(None),
+			Err(tungstenite::Error::Io(ref e))
+				if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut =>
+			{
+				Ok(None)
+			}
+			Err(e) => Err(ErrorKind::WalletComms(format!(
+				"Relay {} connection is lost, {}",
+				self.url, e
+			))
+			.into()),
+		}
+	}
+}
+
+// Ids of the processed events. The same event comes from every relay.
+#[derive(Default)]
+struct SeenEvents {
+	order: VecDeque<String>,
+	ids: HashSet<String>,
+}
+
+impl SeenEvents {
+	// Returns false if the event was seen before
+	fn insert(&mut self, id: &str) -> bool {
+		if !self.ids.insert(id.to_string()) {
+			return false;
+		}
+		self.order.push_back(id.to_string());
+		if self.order.len() > SEEN_EVENTS_LIMIT {
+			if let Some(id) = self.order.pop_front() {
+				self.ids.remove(&id);
+			}
+		}
+		true
+	}
+}
+
+// Set of the relays that are working now. A relay with a broken connection is dropped.
+struct Relays {
+	relays: Vec<Relay>,
+}
+
+impl Relays {
+	fn connect(urls: &[String]) -> Result<Self, Error> {
+		let mut relays = Vec::new();
+		for url in urls {
+			match Relay::connect(url) {
+				Ok(relay) => relays.push(relay),
+				Err(e) => warn!("{}", e),
+			}
+		}
+		if relays.is_empty() {
+			return Err(ErrorKind::WalletComms(
+				"Unable to connect to any of the nostr relays".to_string(),
+			)
+			.into());
+		}
+		Ok(Relays { relays })
+	}
+
+	fn is_empty(&self) -> bool {
+		self.relays.is_empty()
+	}
+
+	fn send(&mut self, msg: &Value) -> usize {
+		let mut sent = 0;
+		let mut failed = vec![];
+		for (i, relay) in self.relays.iter_mut().enumerate() {
+			match relay.send(msg) {
+				Ok(_) => sent += 1,
+				Err(e) => {
+					warn!("{}", e);
+					failed.push(i);
+				}
+			}
+		}
+		for i in failed.into_iter().rev() {
+			self.relays.remove(i);
+		}
+		sent
+	}
+
+	fn subscribe(&mut self, sub_id: &str, filter: Value) -> Result<(), Error> {
+		if self.send(&json!(["REQ", sub_id, filter])) == 0 {
+			return Err(ErrorKind::WalletComms(
+				"Unable to subscribe at the nostr relays".to_string(),
+			)
+			.into());
+		}
+		Ok(())
+	}
+
+	fn publish(&mut self, event: &Event) -> Result<(), Error> {
+		if self.send(&json!(["EVENT", event])) == 0 {
+			return Err(
+				ErrorKind::WalletComms("Unable to publish the nostr event".to_string()).into(),
+			);
+		}
+		Ok(())
+	}
+
+	// Poll every relay once, return the new valid events
+	fn poll(&mut self, seen: &mut SeenEvents) -> Vec<Event> {
+		let mut events = vec![];
+		let mut failed = vec![];
+		for (i, relay) in self.relays.iter_mut().enumerate() {
+			match relay.read() {
+				Ok(Some(RelayMessage::Event(_, event))) => {
+					if event.verify() && seen.insert(&event.id) {
+						events.push(event);
+					}
+				}
+				Ok(Some(RelayMessage::Ok(id, false, reason))) => {
+					warn!("Relay {} rejected event {}, {}", relay.url, id, reason)
+				}
+				Ok(Some(RelayMessage::Notice(notice))) => {
+					debug!("Relay {} notice: {}", relay.url, notice)
+				}
+				Ok(_) => (),
+				Err(e) => {
+					warn!("{}", e);
+					failed.push(i);
+				}
+			}
+		}
+		for i in failed.into_iter().rev() {
+			self.relays.remove(i);
+		}
+		events
+	}
+}
+
+/// Slate transport over Nostr
+pub struct NostrChannel {
+	dest: String,
+	relays: Vec<String>,
+}
+
+impl NostrChannel {
+	/// Channel to the destination. The listener doesn't need one, dest is empty.
+	pub fn new(dest: &str) -> Result<Self, Error> {
+		let relays = get_nostr_relays();
+		if relays.is_empty() {
+			return Err(ErrorKind::WalletComms(
+				"Nostr transport has no relays, please set nostr_relays in the wallet config"
+					.to_string(),
+			)
+			.into());
+		}
+		Ok(NostrChannel {
+			dest: dest.to_string(),
+			relays,
+		})
+	}
+
+	fn subscription_id() -> String {
+		format!("mwc-{}", uuid::Uuid::new_v4().to_simple())
+	}
+}
+
+impl SlateSender for NostrChannel {
+	fn check_other_wallet_version(
+		&self,
+		_destination_address: &String,
+	) -> Result<Option<(SlateVersion, Option<String>)>, Error> {
+		// Messenger can't query the other wallet
+		Ok(None)
+	}
+
+	fn send_tx(
+		&self,
+		slate: &Slate,
+		slate_content: SlatePurpose,
+		slatepack_secret: &DalekSecretKey,
+		recipient: Option<DalekPublicKey>,
+		_other_wallet_version: Option<(SlateVersion, Option<String>)>,
+	) -> Result<Slate, Error> {
+		let keys = NostrKeys::from_wallet_secret(slatepack_secret)?;
+		let dest = parse_public_key(&self.dest)?;
+		let mut relays = Relays::connect(&self.relays)?;
+		relays.subscribe(
+			&NostrChannel::subscription_id(),
+			json!({
+				"kinds": [DM_KIND],
+				"authors": [to_hex(&dest)],
+				"#p": [keys.public_key_hex()],
+				"since": Utc::now().timestamp(),
+			}),
+		)?;
+
+		let message = transport_message(slate, slate_content, slatepack_secret, recipient)?;
+		relays.publish(&Event::direct_message(&keys, &dest, &message)?)?;
+
+		println!(
+			"slate [{}] for [{}] MWCs sent to [{}]",
+			slate.id.to_string(),
+			amount_to_hr_string(slate.amount, false),
+			self.dest,
+		);
+
+		let mut seen = SeenEvents::default();
+		let deadline = Instant::now() + RESPONSE_TIMEOUT;
+		while Instant::now() < deadline && !relays.is_empty() {
+			for event in relays.poll(&mut seen) {
+				if event.pubkey != to_hex(&dest) {
+					continue;
+				}
+				let text = match event.decrypt(&keys) {
+					Ok(text) => text,
+					Err(e) => {
+						warn!("Unable to decrypt nostr message {}, {}", event.id, e);
+						continue;
+					}
+				};
+				if let Some(response) = transport_message_slate(&text, slatepack_secret) {
+					if response.id == slate.id {
+						return Ok(response);
+					}
+				}
+			}
+		}
+		Err(ErrorKind::WalletComms(format!(
+			"Nostr response for slate {} is not received from {}",
+			slate.id, self.dest
+		))
+		.into())
+	}
+}
+
+impl SlateTransport for NostrChannel {
+	fn method(&self) -> &str {
+		NOSTR_METHOD
+	}
+
+	fn validate_address(&self, dest: &str) -> Result<(), Error> {
+		parse_public_key(dest).map(|_| ())
+	}
+
+	fn address(&self, wallet_secret: &DalekSecretKey) -> Result<String, Error> {
+		NostrKeys::from_wallet_secret(wallet_secret)?.npub()
+	}
+
+	fn listen(
+		&self,
+		wallet_secret: &DalekSecretKey,
+		handler: &dyn SlateTransportHandler,
+	) -> Result<(), Error> {
+		let keys = NostrKeys::from_wallet_secret(wallet_secret)?;
+		// History is skipped, the messages since the start are processed. After reconnect
+		// the relays resend the last second, the seen events filter the duplicates out.
+		let mut since = Utc::now().timestamp();
+		let mut seen = SeenEvents::default();
+		let mut relays = Relays::connect(&self.relays)?;
+		loop {
+			if relays.is_empty() {
+				if !listener_wait(handler, RETRY_DELAY) {
+					return Ok(());
+				}
+				relays = match Relays::connect(&self.relays) {
+					Ok(relays) => relays,
+					Err(e) => {
+						warn!("{}", e);
+						continue;
+					}
+				};
+			}
+			let subscribed = relays.subscribe(
+				&NostrChannel::subscription_id(),
+				json!({
+					"kinds": [DM_KIND],
+					"#p": [keys.public_key_hex()],
+					"since": since,
+				}),
+			);
+			if let Err(e) = subscribed {
+				warn!("{}", e);
+				continue;
+			}
+
+			while !relays.is_empty() {
+				// Poll returns after the read timeout even if nothing is received
+				if handler.is_stopped() {
+					return Ok(());
+				}
+				for event in relays.poll(&mut seen) {
+					since = since.max(event.created_at);
+					let text = match event.decrypt(&keys) {
+						Ok(text) => text,
+						Err(e) => {
+							warn!("Unable to decrypt nostr message {}, {}", event.id, e);
+							continue;
+						}
+					};
+					let sender = match parse_public_key(&event.pubkey) {
+						Ok(sender) => sender,
+						Err(_) => continue,
+					};
+					let from = public_key_to_npub(&sender).unwrap_or(event.pubkey.clone());
+					if let Some(reply) = handler.on_message(&from, &text) {
+						let published = Event::direct_message(&keys, &sender, &reply)
+							.and_then(|reply| relays.publish(&reply));
+						if let Err(e) = published {
+							error!("Unable to send reply to {}, {}", from, e);
+						}
+					}
+				}
+			}
+			warn!("Lost connection to all nostr relays, reconnecting");
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn test_keys(seed: u8) -> NostrKeys {
+		let secret = DalekSecretKey::from_bytes(&[seed; 32]).unwrap();
+		NostrKeys::from_wallet_secret(&secret).unwrap()
+	}
+
+	#[test]
+	fn test_npub() {
+		// NIP-19 example
+		let npub = "npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg";
+		let hex = "7e7e9c42a91bfef19fa929e5fda1b72e0ebc1a4c1141673e2794234d86addf4e";
+		let public = parse_public_key(npub).unwrap();
+		assert_eq!(to_hex(&public), hex);
+		assert_eq!(public_key_to_npub(&public).unwrap(), npub);
+		assert_eq!(parse_public_key(hex).unwrap(), public);
+		assert_eq!(
+			parse_public_key(&format!("nostr:{}", npub)).unwrap(),
+			public
+		);
+
+		assert!(parse_public_key("npub1").is_err());
+		assert!(parse_public_key("7e7e9c42").is_err());
+
+		let keys = test_keys(1);
+		assert_eq!(keys.npub().unwrap(), test_keys(1).npub().unwrap());
+		assert_ne!(keys.npub().unwrap(), test_keys(2).npub().unwrap());
+		assert_eq!(
+			parse_public_key(&keys.npub().unwrap()).unwrap(),
+			keys.public
+		);
+	}
+
+	#[test]
+	fn test_direct_message() {
+		let alice = test_keys(1);
+		let bob = test_keys(2);
+		let carol = test_keys(3);
+
+		let event =
+			Event::direct_message(&alice, &bob.public, "BEGINSLATEPACK. ENDSLATEPACK.").unwrap();
+		assert_eq!(event.kind, DM_KIND);
+		assert_eq!(event.pubkey, alice.public_key_hex());
+		assert_eq!(
+			event.tags,
+			vec![vec!["p".to_string(), bob.public_key_hex()]]
+		);
+		assert!(event.verify());
+		assert_eq!(
+			event.decrypt(&bob).unwrap(),
+			"BEGINSLATEPACK. ENDSLATEPACK."
+		);
+		assert!(event.decrypt(&carol).is_err());
+
+		let mut forged = event.clone();
+		forged.content = nip04_encrypt(&alice, &bob.public, "forged").unwrap();
+		assert!(!forged.verify());
+		let mut forged = event.clone();
+		forged.pubkey = carol.public_key_hex();
+		assert!(!forged.verify());
+	}
+
+	#[test]
+	fn test_seen_events() {
+		let mut seen = SeenEvents::default();
+		assert!(seen.insert("id0"));
+		assert!(!seen.insert("id0"));
+		for i in 1..=SEEN_EVENTS_LIMIT {
+			assert!(seen.insert(&format!("id{}", i)));
+		}
+		// The oldest id is forgotten
+		assert!(seen.insert("id0"));
+		assert!(!seen.insert(&format!("id{}", SEEN_EVENTS_LIMIT)));
+	}
+
+	#[test]
+	fn test_parse_relay_message() {
+		let alice = test_keys(1);
+		let bob = test_keys(2);
+		let event = Event::direct_message(&alice, &bob.public, "hello").unwrap();
+
+		assert_eq!(
+			parse_relay_message(&json!(["EVENT", "sub1", event]).to_string()),
+			Some(RelayMessage::Event("sub1".to_string(), event.clone()))
+		);
+		assert_eq!(
+			parse_relay_message(r#"["EOSE","sub1"]"#),
+			Some(RelayMessage::EndOfStoredEvents("sub1".to_string()))
+		);
+		assert_eq!(
+			parse_relay_message(r#"["OK","b1a649ebe8",false,"blocked: rate limited"]"#),
+			Some(RelayMessage::Ok(
+				"b1a649ebe8".to_string(),
+				false,
+				"blocked: rate limited".to_string()
+			))
+		);
+		assert_eq!(
+			parse_relay_message(r#"["NOTICE","restricted"]"#),
+			Some(RelayMessage::Notice("restricted".to_string()))
+		);
+		assert_eq!(parse_relay_message(r#"["AUTH","challenge"]"#), None);
+		assert_eq!(parse_relay_message(r#"["EVENT","sub1",{"id":1}]"#), None);
+		assert_eq!(parse_relay_message("not json"), None);
+	}
+
+	#[test]
+	fn test_create_transport() {
+		set_nostr_relays(Some(vec![]));
+		assert!(crate::adapters::create_transport(NOSTR_METHOD, "").is_err());
+
+		set_nostr_relays(None);
+		assert_eq!(get_nostr_relays().len(), DEFAULT_NOSTR_RELAYS.len());
+		let transport = crate::adapters::create_transport(NOSTR_METHOD, "")
+			.unwrap()
+			.unwrap();
+		assert_eq!(transport.method(), NOSTR_METHOD);
+		let secret = DalekSecretKey::from_bytes(&[1; 32]).unwrap();
+		assert_eq!(
+			transport.address(&secret).unwrap(),
+			test_keys(1).npub().unwrap()
+		);
+	}
+}
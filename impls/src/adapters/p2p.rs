@@ -0,0 +1,487 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Direct peer transport. The wallets connect to each other over TCP and secure the connection
+//! with the libp2p noise handshake, so the sender knows that it talks to the peer id from the
+//! destination. A connection carries one message and its reply: a slate and the response slate,
+//! or a swap message and its ack. The peer key is derived from the wallet slatepack key, the
+//! peer id doesn't change while the wallet seed is the same. The destination is the multiaddr
+//! of the listening wallet with its peer id, for example /ip4/1.2.3.4/tcp/3419/p2p/12D3KooW...
+//!
+//! QUIC and the circuit relays are not available in the libp2p version of the wallet, the
+//! listening wallet needs an address that the other wallet can reach.
+
+use crate::adapters::{
+	listener_wait, transport_message, transport_message_slate, SlateSender, SlateTransport,
+	SlateTransportHandler,
+};
+use crate::blake2::blake2b::blake2b;
+use crate::core::core::amount_to_hr_string;
+use crate::error::{Error, ErrorKind};
+use crate::util::RwLock;
+use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
+use futures::executor::block_on;
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use grin_wallet_libwallet::slatepack::SlatePurpose;
+use grin_wallet_libwallet::swap::message::Message;
+use grin_wallet_libwallet::{Slate, SlateVersion};
+use libp2p::core::multiaddr::{Multiaddr, Protocol};
+use libp2p::core::upgrade::{self, Version};
+use libp2p::identity::{self, Keypair};
+use libp2p::noise::{Keypair as NoiseKeypair, NoiseAuthenticated, NoiseConfig, X25519Spec, XX};
+use libp2p::PeerId;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Transport method name
+pub const P2P_METHOD: &str = "p2p";
+
+/// Listening address if p2p_listen_address is not set in the wallet config
+pub const DEFAULT_P2P_LISTEN_ADDRESS: &str = "/ip4/0.0.0.0/tcp/3419";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+// The listener sends the reply after the message is processed by the wallet
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(120);
+// The sender writes the message right after the handshake
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+// The listener accepts the connections without blocking, so it can be stopped
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(200);
+const MAX_MESSAGE_SIZE: usize = 10 * 1024 * 1024;
+
+lazy_static! {
+	static ref P2P_LISTEN_ADDRESS: RwLock<String> =
+		RwLock::new(DEFAULT_P2P_LISTEN_ADDRESS.to_string());
+	static ref P2P_PUBLIC_ADDRESS: RwLock<Option<String>> = RwLock::new(None);
+}
+
+/// Set the listening address and the address that other wallets use to reach this one.
+/// None restores the default listening address.
+pub fn set_p2p_config(listen_address: Option<String>, public_address: Option<String>) {
+	*P2P_LISTEN_ADDRESS.write() = listen_address.unwrap_or(DEFAULT_P2P_LISTEN_ADDRESS.to_string());
+	*P2P_PUBLIC_ADDRESS.write() = public_address;
+}
+
+/// Peer key of the wallet, derived from the wallet slatepack key: h(wallet_secret|"p2p")
+pub fn p2p_keypair(wallet_secret: &DalekSecretKey) -> Result<Keypair, Error> {
+	let mut data = wallet_secret.as_bytes().to_vec();
+	data.extend_from_slice(b"p2p");
+	let hash = blake2b(32, &[], &data);
+	let secret = identity::ed25519::SecretKey::from_bytes(hash.as_bytes().to_vec())
+		.map_err(|e| ErrorKind::GenericError(format!("Invalid p2p secret key, {}", e)))?;
+	Ok(Keypair::Ed25519(secret.into()))
+}
+
+/// Peer id of the wallet
+pub fn p2p_peer_id(wallet_secret: &DalekSecretKey) -> Result<PeerId, Error> {
+	Ok(PeerId::from(p2p_keypair(wallet_secret)?.public()))
+}
+
+/// Address of the wallet for the other wallets: the public address, or the listening address
+/// if the public one is not set, with the peer id
+pub fn p2p_address(wallet_secret: &DalekSecretKey) -> Result<String, Error> {
+	let address = P2P_PUBLIC_ADDRESS
+		.read()
+		.clone()
+		.unwrap_or(P2P_LISTEN_ADDRESS.read().clone());
+	Ok(format!(
+		"{}/p2p/{}",
+		address.trim_end_matches('/'),
+		p2p_peer_id(wallet_secret)?
+	))
+}
+
+fn parse_multiaddr(address: &str) -> Result<Multiaddr, Error> {
+	Multiaddr::from_str(address).map_err(|e| {
+		ErrorKind::ArgumentError(format!("Invalid p2p address {}, {}", address, e)).into()
+	})
+}
+
+// Socket address of /ip4, /ip6, /dns, /dns4 or /dns6 with /tcp
+fn socket_address(address: &Multiaddr) -> Result<SocketAddr, Error> {
+	let mut host = None;
+	let mut port = None;
+	for p in address.iter() {
+		match p {
+			Protocol::Ip4(ip) => host = Some(ip.to_string()),
+			Protocol::Ip6(ip) => host = Some(ip.to_string()),
+			Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name) => {
+				host = Some(name.to_string())
+			}
+			Protocol::Tcp(p) => port = Some(p),
+			_ => (),
+		}
+	}
+	let (host, port) = match (host, port) {
+		(Some(host), Some(port)) => (host, port),
+		_ => {
+			return Err(ErrorKind::ArgumentError(format!(
+				"p2p address {} must have the host and the tcp port",
+				address
+			))
+			.into())
+		}
+	};
+	(host.as_str(), port)
+		.to_socket_addrs()
+		.ok()
+		.and_then(|mut addrs| addrs.next())
+		.ok_or(ErrorKind::WalletComms(format!("Unable to resolve p2p address {}", address)).into())
+}
+
+/// Address and peer id of the destination /<address>/tcp/<port>/p2p/<peer id>
+pub fn parse_destination(dest: &str) -> Result<(Multiaddr, PeerId), Error> {
+	let mut address = parse_multiaddr(dest)?;
+	let peer_id = match address.pop() {
+		Some(Protocol::P2p(hash)) => PeerId::from_multihash(hash).map_err(|_| {
+			ErrorKind::ArgumentError(format!("Invalid peer id in the p2p address {}", dest))
+		})?,
+		_ => {
+			return Err(ErrorKind::ArgumentError(format!(
+				"p2p address {} must end with /p2p/<peer id>",
+				dest
+			))
+			.into())
+		}
+	};
+	Ok((address, peer_id))
+}
+
+// Blocking TCP stream for the libp2p upgrades. The upgrades run with block_on on the thread of
+// the connection, so the blocking reads and writes are fine.
+struct BlockingStream(TcpStream);
+
+impl AsyncRead for BlockingStream {
+	fn poll_read(
+		self: Pin<&mut Self>,
+		_cx: &mut Context<'_>,
+		buf: &mut [u8],
+	) -> Poll<io::Result<usize>> {
+		Poll::Ready(self.get_mut().0.read(buf))
+	}
+}
+
+impl AsyncWrite for BlockingStream {
+	fn poll_write(
+		self: Pin<&mut Self>,
+		_cx: &mut Context<'_>,
+		buf: &[u8],
+	) -> Poll<io::Result<usize>> {
+		Poll::Ready(self.get_mut().0.write(buf))
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(self.get_mut().0.flush())
+	}
+
+	fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(self.get_mut().0.shutdown(Shutdown::Write))
+	}
+}
+
+fn noise_config(keys: &Keypair) -> Result<NoiseAuthenticated<XX, X25519Spec, ()>, Error> {
+	let noise_keys = NoiseKeypair::<X25519Spec>::new()
+		.into_authentic(keys)
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to build p2p noise keys, {}", e)))?;
+	Ok(NoiseConfig::xx(noise_keys).into_authenticated())
+}
+
+// Message is sent with its length, the empty message means no reply
+async fn write_message<S: AsyncWrite + Unpin>(stream: &mut S, message: &str) -> io::Result<()> {
+	stream
+		.write_all(&(message.len() as u32).to_be_bytes())
+		.await?;
+	stream.write_all(message.as_bytes()).await?;
+	stream.flush().await
+}
+
+async fn read_message<S: AsyncRead + Unpin>(stream: &mut S) -> io::Result<String> {
+	let mut len = [0u8; 4];
+	stream.read_exact(&mut len).await?;
+	let len = u32::from_be_bytes(len) as usize;
+	if len > MAX_MESSAGE_SIZE {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			format!("p2p message size {} is over the limit", len),
+		));
+	}
+	let mut data = vec![0u8; len];
+	stream.read_exact(&mut data).await?;
+	String::from_utf8(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+// Send the message to the destination and wait for the reply
+fn request(keys: &Keypair, dest: &str, message: &str) -> Result<Option<String>, Error> {
+	let (address, peer_id) = parse_destination(dest)?;
+	let comms_error =
+		|e: String| -> Error { ErrorKind::WalletComms(format!("p2p {}, {}", dest, e)).into() };
+
+	let stream = TcpStream::connect_timeout(&socket_address(&address)?, CONNECT_TIMEOUT)
+		.map_err(|e| comms_error(format!("unable to connect, {}", e)))?;
+	stream
+		.set_read_timeout(Some(RESPONSE_TIMEOUT))
+		.and_then(|_| stream.set_write_timeout(Some(REQUEST_TIMEOUT)))
+		.map_err(|e| comms_error(format!("{}", e)))?;
+	let noise = noise_config(keys)?;
+
+	block_on(async move {
+		let (remote, mut conn) =
+			upgrade::apply_outbound(BlockingStream(stream), noise, Version::V1)
+				.await
+				.map_err(|e| comms_error(format!("handshake failed, {}", e)))?;
+		if remote != peer_id {
+			return Err(comms_error(format!("peer {} answered instead", remote)));
+		}
+		write_message(&mut conn, message)
+			.await
+			.map_err(|e| comms_error(format!("unable to send the message, {}", e)))?;
+		let reply = read_message(&mut conn)
+			.await
+			.map_err(|e| comms_error(format!("reply is not received, {}", e)))?;
+		Ok(if reply.is_empty() { None } else { Some(reply) })
+	})
+}
+
+// Read the message of the connection and write the reply of the handler
+fn serve_connection(
+	keys: &Keypair,
+	stream: TcpStream,
+	handler: &dyn SlateTransportHandler,
+) -> Result<(), Error> {
+	stream
+		.set_read_timeout(Some(REQUEST_TIMEOUT))
+		.and_then(|_| stream.set_write_timeout(Some(REQUEST_TIMEOUT)))
+		.map_err(|e| ErrorKind::WalletComms(format!("{}", e)))?;
+	let noise = noise_config(keys)?;
+
+	block_on(async move {
+		let (peer_id, mut conn) = upgrade::apply_inbound(BlockingStream(stream), noise)
+			.await
+			.map_err(|e| ErrorKind::WalletComms(format!("p2p handshake failed, {}", e)))?;
+		let message = read_message(&mut conn).await.map_err(|e| {
+			ErrorKind::WalletComms(format!(
+				"Unable to read p2p message from {}, {}",
+				peer_id, e
+			))
+		})?;
+		let reply = handler
+			.on_message(&peer_id.to_string(), &message)
+			.unwrap_or_default();
+		write_message(&mut conn, &reply).await.map_err(|e| {
+			ErrorKind::WalletComms(format!("Unable to send p2p reply to {}, {}", peer_id, e))
+		})?;
+		Ok(())
+	})
+}
+
+/// Direct transport to the peer
+pub struct P2pChannel {
+	dest: String,
+}
+
+impl P2pChannel {
+	/// Channel to the destination. The listener doesn't need one, dest is empty.
+	pub fn new(dest: &str) -> Result<Self, Error> {
+		if !dest.is_empty() {
+			parse_destination(dest)?;
+		}
+		Ok(P2pChannel {
+			dest: dest.to_string(),
+		})
+	}
+}
+
+impl SlateSender for P2pChannel {
+	fn check_other_wallet_version(
+		&self,
+		_destination_address: &String,
+	) -> Result<Option<(SlateVersion, Option<String>)>, Error> {
+		// The listener answers to the slates only
+		Ok(None)
+	}
+
+	fn send_tx(
+		&self,
+		slate: &Slate,
+		slate_content: SlatePurpose,
+		slatepack_secret: &DalekSecretKey,
+		recipient: Option<DalekPublicKey>,
+		_other_wallet_version: Option<(SlateVersion, Option<String>)>,
+	) -> Result<Slate, Error> {
+		let keys = p2p_keypair(slatepack_secret)?;
+		let message = transport_message(slate, slate_content, slatepack_secret, recipient)?;
+		let reply = request(&keys, &self.dest, &message)?;
+
+		println!(
+			"slate [{}] for [{}] MWCs sent to [{}]",
+			slate.id.to_string(),
+			amount_to_hr_string(slate.amount, false),
+			self.dest,
+		);
+
+		match reply.and_then(|r| transport_message_slate(&r, slatepack_secret)) {
+			Some(response) if response.id == slate.id => Ok(response),
+			_ => Err(ErrorKind::WalletComms(format!(
+				"p2p response for slate {} is not received from {}",
+				slate.id, self.dest
+			))
+			.into()),
+		}
+	}
+}
+
+impl SlateTransport for P2pChannel {
+	fn method(&self) -> &str {
+		P2P_METHOD
+	}
+
+	fn validate_address(&self, dest: &str) -> Result<(), Error> {
+		parse_destination(dest).map(|_| ())
+	}
+
+	fn supports_swap_messages(&self) -> bool {
+		true
+	}
+
+	/// The listener processes the message before it replies, so the ack can be granted
+	fn send_swap_message(&self, swap_message: &Message) -> Result<bool, Error> {
+		// Swap messages don't need the wallet identity, the trade is authenticated by its data
+		let keys = Keypair::generate_ed25519();
+		let message = swap_message.to_json().map_err(|e| {
+			ErrorKind::SwapMessageGenericError(format!(
+				"Unable to convert swap message to json, {}",
+				e
+			))
+		})?;
+		request(&keys, &self.dest, &message)?;
+		Ok(true)
+	}
+
+	fn address(&self, wallet_secret: &DalekSecretKey) -> Result<String, Error> {
+		p2p_address(wallet_secret)
+	}
+
+	fn listen(
+		&self,
+		wallet_secret: &DalekSecretKey,
+		handler: &dyn SlateTransportHandler,
+	) -> Result<(), Error> {
+		let keys = p2p_keypair(wallet_secret)?;
+		let listen_address = P2P_LISTEN_ADDRESS.read().clone();
+		let listener = TcpListener::bind(socket_address(&parse_multiaddr(&listen_address)?)?)
+			.map_err(|e| {
+				ErrorKind::WalletComms(format!(
+					"Unable to listen on p2p address {}, {}",
+					listen_address, e
+				))
+			})?;
+		listener
+			.set_nonblocking(true)
+			.map_err(|e| ErrorKind::WalletComms(format!("{}", e)))?;
+
+		// Connections are served in turn, a connection is limited by the request timeout
+		while !handler.is_stopped() {
+			let stream = match listener.accept() {
+				Ok((stream, _)) => stream,
+				Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+					listener_wait(handler, ACCEPT_INTERVAL);
+					continue;
+				}
+				Err(e) => {
+					warn!("Unable to accept p2p connection, {}", e);
+					continue;
+				}
+			};
+			let served = stream
+				.set_nonblocking(false)
+				.map_err(|e| ErrorKind::WalletComms(format!("{}", e)).into())
+				.and_then(|_| serve_connection(&keys, stream, handler));
+			if let Err(e) = served {
+				warn!("{}", e);
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_p2p_address() {
+		let secret = DalekSecretKey::from_bytes(&[1; 32]).unwrap();
+		let peer_id = p2p_peer_id(&secret).unwrap();
+		// The peer id is derived from the wallet key
+		assert_eq!(peer_id, p2p_peer_id(&secret).unwrap());
+		let other = DalekSecretKey::from_bytes(&[2; 32]).unwrap();
+		assert_ne!(peer_id, p2p_peer_id(&other).unwrap());
+
+		set_p2p_config(None, Some("/dns4/wallet.example.org/tcp/3419".to_string()));
+		let address = p2p_address(&secret).unwrap();
+		assert_eq!(
+			address,
+			format!("/dns4/wallet.example.org/tcp/3419/p2p/{}", peer_id)
+		);
+		let (multiaddr, dest_peer_id) = parse_destination(&address).unwrap();
+		assert_eq!(dest_peer_id, peer_id);
+		assert_eq!(multiaddr.to_string(), "/dns4/wallet.example.org/tcp/3419");
+		set_p2p_config(None, None);
+
+		assert!(parse_destination("/ip4/127.0.0.1/tcp/3419").is_err());
+		assert!(parse_destination("127.0.0.1:3419").is_err());
+		assert!(P2pChannel::new("").is_ok());
+		assert!(P2pChannel::new("/ip4/127.0.0.1/tcp/3419").is_err());
+		assert_eq!(
+			socket_address(&parse_multiaddr("/ip4/127.0.0.1/tcp/3419").unwrap()).unwrap(),
+			"127.0.0.1:3419".parse::<SocketAddr>().unwrap()
+		);
+	}
+
+	struct EchoHandler;
+
+	impl SlateTransportHandler for EchoHandler {
+		fn on_message(&self, from: &str, message: &str) -> Option<String> {
+			Some(format!("{} {}", from, message))
+		}
+	}
+
+	#[test]
+	fn test_p2p_request() {
+		let listener_secret = DalekSecretKey::from_bytes(&[3; 32]).unwrap();
+		let listener_keys = p2p_keypair(&listener_secret).unwrap();
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let port = listener.local_addr().unwrap().port();
+		let server = std::thread::spawn(move || {
+			let (stream, _) = listener.accept().unwrap();
+			serve_connection(&listener_keys, stream, &EchoHandler).unwrap();
+		});
+
+		let sender_keys = Keypair::generate_ed25519();
+		let dest = format!(
+			"/ip4/127.0.0.1/tcp/{}/p2p/{}",
+			port,
+			p2p_peer_id(&listener_secret).unwrap()
+		);
+		let reply = request(&sender_keys, &dest, "hello").unwrap();
+		assert_eq!(
+			reply,
+			Some(format!("{} hello", PeerId::from(sender_keys.public())))
+		);
+		server.join().unwrap();
+	}
+}
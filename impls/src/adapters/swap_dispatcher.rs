@@ -0,0 +1,146 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Worker pool for the swap messages of the listener. The messages of the same trade go to the
+//! same worker, so they are processed in the order they arrived. A trade that is stuck on its lock
+//! only delays the trades that share its worker, the listener keeps reading the messages.
+
+use crate::error::{Error, ErrorKind};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::thread;
+
+/// Number of the workers of the listener
+pub const SWAP_MESSAGE_WORKERS: usize = 4;
+/// Number of the messages that can wait for the worker
+pub const SWAP_MESSAGE_QUEUE_SIZE: usize = 32;
+
+type SwapJob = Box<dyn FnOnce() + Send>;
+
+/// Dispatches the swap message processing into the workers, keyed by the swap id.
+/// When the dispatcher is dropped, the workers finish the queued messages and exit.
+pub struct SwapMessageDispatcher {
+	workers: Vec<SyncSender<SwapJob>>,
+}
+
+impl SwapMessageDispatcher {
+	/// Start the workers, 'name' is used for the thread names
+	pub fn new(name: &str, workers: usize, queue_size: usize) -> Result<Self, Error> {
+		let mut senders = vec![];
+		for i in 0..workers.max(1) {
+			let (tx, rx) = sync_channel::<SwapJob>(queue_size);
+			thread::Builder::new()
+				.name(format!("{}-swap-{}", name, i))
+				.spawn(move || {
+					for job in rx {
+						job();
+					}
+				})
+				.map_err(|e| {
+					ErrorKind::GenericError(format!(
+						"Unable to start swap message worker for {}, {}",
+						name, e
+					))
+				})?;
+			senders.push(tx);
+		}
+		Ok(SwapMessageDispatcher { workers: senders })
+	}
+
+	fn worker_index(&self, swap_id: &str) -> usize {
+		let mut hasher = DefaultHasher::new();
+		swap_id.hash(&mut hasher);
+		(hasher.finish() % self.workers.len() as u64) as usize
+	}
+
+	/// Queue the processing of the trade message. The caller is not blocked. If the queue of the
+	/// worker is full, the message is rejected, the other party will send it again.
+	pub fn dispatch<F>(&self, swap_id: &str, job: F) -> Result<(), Error>
+	where
+		F: FnOnce() + Send + 'static,
+	{
+		match self.workers[self.worker_index(swap_id)].try_send(Box::new(job)) {
+			Ok(()) => Ok(()),
+			Err(TrySendError::Full(_)) => Err(ErrorKind::SwapMessageGenericError(format!(
+				"Too many swap messages are waiting, message for the trade {} is dropped",
+				swap_id
+			))
+			.into()),
+			Err(TrySendError::Disconnected(_)) => Err(ErrorKind::SwapMessageGenericError(format!(
+				"Swap message worker is stopped, message for the trade {} is dropped",
+				swap_id
+			))
+			.into()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::mpsc::channel;
+	use std::sync::{Arc, Barrier};
+	use std::time::Duration;
+
+	#[test]
+	fn test_swap_message_dispatch() {
+		let dispatcher = SwapMessageDispatcher::new("test", 4, 8).unwrap();
+
+		// Find two trades that are processed by the different workers
+		let stuck_id = "stuck-trade".to_string();
+		let other_id = (0..100)
+			.map(|i| format!("trade-{}", i))
+			.find(|id| dispatcher.worker_index(id) != dispatcher.worker_index(&stuck_id))
+			.unwrap();
+
+		// The stuck trade doesn't block the other one
+		let barrier = Arc::new(Barrier::new(2));
+		let stuck_barrier = barrier.clone();
+		dispatcher
+			.dispatch(&stuck_id, move || {
+				stuck_barrier.wait();
+			})
+			.unwrap();
+
+		let (tx, rx) = channel();
+		for i in 0..3 {
+			let tx = tx.clone();
+			dispatcher
+				.dispatch(&other_id, move || tx.send(i).unwrap())
+				.unwrap();
+		}
+		// Messages of the same trade keep the order
+		let processed: Vec<i32> = (0..3)
+			.map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+			.collect();
+		assert_eq!(processed, vec![0, 1, 2]);
+		barrier.wait();
+
+		// Full queue rejects the message
+		let dispatcher = SwapMessageDispatcher::new("test", 1, 1).unwrap();
+		let barrier = Arc::new(Barrier::new(2));
+		let stuck_barrier = barrier.clone();
+		dispatcher
+			.dispatch(&stuck_id, move || {
+				stuck_barrier.wait();
+			})
+			.unwrap();
+		// wait until the worker takes the job, then the queue has a single free slot
+		thread::sleep(Duration::from_millis(200));
+		dispatcher.dispatch(&stuck_id, || {}).unwrap();
+		assert!(dispatcher.dispatch(&stuck_id, || {}).is_err());
+		barrier.wait();
+	}
+}
@@ -0,0 +1,420 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Telegram bot transport. The wallet runs a bot through the Telegram bot API: the slates
+//! and the swap messages that are sent to the bot are processed by the listener, the reply
+//! is posted back into the same chat. Bots don't see the messages of other bots, so the
+//! slatepacks are forwarded by the people in the chat and the method is store-and-forward:
+//! `send` posts the slate to the destination chat, the response is finalized when it comes
+//! to the bot of the sender.
+
+use crate::adapters::{
+	listener_wait, transport_message, SlateSender, SlateTransport, SlateTransportHandler,
+};
+use crate::core::core::amount_to_hr_string;
+use crate::error::{Error, ErrorKind};
+use crate::util::RwLock;
+use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
+use grin_wallet_libwallet::slatepack::SlatePurpose;
+use grin_wallet_libwallet::{Slate, SlateVersion};
+use serde_json::{json, Value};
+use std::io::Read;
+use std::time::Duration;
+
+/// Transport method name
+pub const TELEGRAM_METHOD: &str = "telegram";
+
+const API_URL: &str = "https://api.telegram.org";
+// Long polling timeout of getUpdates, must be below the request timeout
+const POLL_TIMEOUT_SEC: u64 = 25;
+const API_TIMEOUT: Duration = Duration::from_secs(40);
+// Pause before the listener retries a failed poll
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+// Longer messages are sent as a text file
+const MAX_MESSAGE_LEN: usize = 4096;
+// Slatepack files that are larger are ignored
+const MAX_FILE_SIZE: u64 = 1_000_000;
+
+lazy_static! {
+	static ref TELEGRAM_BOT_TOKEN: RwLock<Option<String>> = RwLock::new(None);
+}
+
+/// Set the token of the wallet bot. None disables the transport.
+pub fn set_telegram_config(bot_token: Option<String>) {
+	*TELEGRAM_BOT_TOKEN.write() = bot_token;
+}
+
+/// Current bot token
+pub fn get_telegram_config() -> Option<String> {
+	TELEGRAM_BOT_TOKEN.read().clone()
+}
+
+// Message that came to the bot
+#[derive(Debug, PartialEq)]
+struct ChatMessage {
+	chat_id: String,
+	text: Option<String>,
+	// Id of the attached document
+	file_id: Option<String>,
+}
+
+// Result of getUpdates
+#[derive(Debug, PartialEq)]
+struct Updates {
+	// Offset that confirms these updates
+	next_offset: Option<i64>,
+	messages: Vec<ChatMessage>,
+}
+
+/// Slate transport over a Telegram bot
+pub struct TelegramChannel {
+	token: String,
+	dest: String,
+}
+
+impl TelegramChannel {
+	/// Channel to the destination chat. The listener doesn't need one, dest is empty.
+	pub fn new(dest: &str) -> Result<Self, Error> {
+		let token = get_telegram_config().ok_or(ErrorKind::WalletComms(
+			"Telegram transport is not configured, please set telegram_bot_token in the wallet config".to_string(),
+		))?;
+		Ok(TelegramChannel {
+			token,
+			dest: dest.trim_start_matches("telegram://").to_string(),
+		})
+	}
+
+	// The token is a part of the url, it must not get into the logs
+	fn comms_error(&self, method: &str, e: String) -> Error {
+		ErrorKind::WalletComms(format!(
+			"Telegram {} request failed, {}",
+			method,
+			e.replace(&self.token, "<token>")
+		))
+		.into()
+	}
+
+	fn client(&self) -> Result<reqwest::Client, Error> {
+		reqwest::Client::builder()
+			.timeout(API_TIMEOUT)
+			.build()
+			.map_err(|e| self.comms_error("client", format!("{}", e)))
+	}
+
+	fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+		let request = self
+			.client()?
+			.post(&format!("{}/bot{}/{}", API_URL, self.token, method))
+			.json(&params);
+		self.response(method, request)
+	}
+
+	fn response(&self, method: &str, request: reqwest::RequestBuilder) -> Result<Value, Error> {
+		let mut response = request
+			.send()
+			.map_err(|e| self.comms_error(method, format!("{}", e)))?;
+		let mut body = String::new();
+		response
+			.read_to_string(&mut body)
+			.map_err(|e| self.comms_error(method, format!("{}", e)))?;
+		let res: Value = serde_json::from_str(&body).map_err(|e| {
+			self.comms_error(method, format!("unable to parse response {}, {}", body, e))
+		})?;
+		// Errors come as {"ok": false, "error_code": 401, "description": "Unauthorized"}
+		if res["ok"] != true {
+			return Err(self.comms_error(
+				method,
+				res["description"]
+					.as_str()
+					.unwrap_or("unknown error")
+					.to_string(),
+			));
+		}
+		Ok(res["result"].clone())
+	}
+
+	fn bot_name(&self) -> Result<String, Error> {
+		let res = self.call("getMe", json!({}))?;
+		res["username"]
+			.as_str()
+			.map(|s| format!("@{}", s))
+			.ok_or(ErrorKind::WalletComms("Telegram bot username is not found".to_string()).into())
+	}
+
+	fn send_message(&self, chat_id: &str, text: &str) -> Result<(), Error> {
+		if text.len() <= MAX_MESSAGE_LEN {
+			self.call("sendMessage", json!({ "chat_id": chat_id, "text": text }))?;
+			return Ok(());
+		}
+		let part =
+			reqwest::multipart::Part::bytes(text.as_bytes().to_vec()).file_name("slatepack.txt");
+		let form = reqwest::multipart::Form::new()
+			.text("chat_id", chat_id.to_string())
+			.part("document", part);
+		let request = self
+			.client()?
+			.post(&format!("{}/bot{}/sendDocument", API_URL, self.token))
+			.multipart(form);
+		self.response("sendDocument", request)?;
+		Ok(())
+	}
+
+	fn download(&self, file_id: &str) -> Result<String, Error> {
+		let file = self.call("getFile", json!({ "file_id": file_id }))?;
+		if file["file_size"].as_u64().unwrap_or(0) > MAX_FILE_SIZE {
+			return Err(self.comms_error("getFile", "the file is too large".to_string()));
+		}
+		let path = file["file_path"]
+			.as_str()
+			.ok_or(self.comms_error("getFile", "the file is not available".to_string()))?;
+		let mut response = self
+			.client()?
+			.get(&format!("{}/file/bot{}/{}", API_URL, self.token, path))
+			.send()
+			.map_err(|e| self.comms_error("file", format!("{}", e)))?;
+		let mut text = String::new();
+		response
+			.read_to_string(&mut text)
+			.map_err(|e| self.comms_error("file", format!("{}", e)))?;
+		Ok(text)
+	}
+
+	fn get_updates(&self, offset: Option<i64>) -> Result<Updates, Error> {
+		let mut params = json!({
+			"timeout": POLL_TIMEOUT_SEC,
+			"allowed_updates": ["message", "channel_post"],
+		});
+		if let Some(offset) = offset {
+			params["offset"] = json!(offset);
+		}
+		Ok(parse_updates(&self.call("getUpdates", params)?))
+	}
+}
+
+impl SlateSender for TelegramChannel {
+	fn check_other_wallet_version(
+		&self,
+		_destination_address: &String,
+	) -> Result<Option<(SlateVersion, Option<String>)>, Error> {
+		// Messenger can't query the other wallet
+		Ok(None)
+	}
+
+	fn send_tx(
+		&self,
+		_slate: &Slate,
+		_slate_content: SlatePurpose,
+		_slatepack_secret: &DalekSecretKey,
+		_recipient: Option<DalekPublicKey>,
+		_other_wallet_version: Option<(SlateVersion, Option<String>)>,
+	) -> Result<Slate, Error> {
+		Err(ErrorKind::WalletComms(
+			"telegram delivery is asynchronous, the response slate can't be waited for. Please use 'send --method telegram', the response is finalized by 'listen --method telegram'".to_string(),
+		)
+		.into())
+	}
+}
+
+impl SlateTransport for TelegramChannel {
+	fn method(&self) -> &str {
+		TELEGRAM_METHOD
+	}
+
+	fn validate_address(&self, dest: &str) -> Result<(), Error> {
+		// Chat id or the public @username of the chat
+		let dest = dest.trim_start_matches("telegram://");
+		if dest.parse::<i64>().is_ok() || (dest.len() > 1 && dest.starts_with('@')) {
+			Ok(())
+		} else {
+			Err(ErrorKind::ArgumentError(format!(
+				"Invalid Telegram destination {}, expected a chat id or @username",
+				dest
+			))
+			.into())
+		}
+	}
+
+	fn address(&self, _wallet_secret: &DalekSecretKey) -> Result<String, Error> {
+		self.bot_name()
+	}
+
+	fn is_store_and_forward(&self) -> bool {
+		true
+	}
+
+	/// The response comes to the bot of this wallet
+	fn post_slate(
+		&self,
+		slate: &Slate,
+		slate_content: SlatePurpose,
+		slatepack_secret: &DalekSecretKey,
+		recipient: Option<DalekPublicKey>,
+	) -> Result<(), Error> {
+		let message = transport_message(slate, slate_content, slatepack_secret, recipient)?;
+		self.send_message(&self.dest, &message)?;
+
+		println!(
+			"slate [{}] for [{}] MWCs posted to Telegram chat [{}]",
+			slate.id.to_string(),
+			amount_to_hr_string(slate.amount, false),
+			self.dest,
+		);
+		Ok(())
+	}
+
+	fn listen(
+		&self,
+		_wallet_secret: &DalekSecretKey,
+		handler: &dyn SlateTransportHandler,
+	) -> Result<(), Error> {
+		// Check the token before the listener is reported as started. Telegram keeps the
+		// updates for 24 hours, the ones that came while the wallet was offline are processed.
+		self.bot_name()?;
+		let mut offset = None;
+		loop {
+			if handler.is_stopped() {
+				return Ok(());
+			}
+			let updates = match self.get_updates(offset) {
+				Ok(updates) => updates,
+				Err(e) => {
+					warn!("Telegram getUpdates failed, {}", e);
+					if !listener_wait(handler, RETRY_DELAY) {
+						return Ok(());
+					}
+					continue;
+				}
+			};
+			if updates.next_offset.is_some() {
+				offset = updates.next_offset;
+			}
+			for msg in updates.messages {
+				let text = match (msg.text, msg.file_id) {
+					(Some(text), _) => text,
+					(None, Some(file_id)) => match self.download(&file_id) {
+						Ok(text) => text,
+						Err(e) => {
+							warn!("Unable to get the file from chat {}, {}", msg.chat_id, e);
+							continue;
+						}
+					},
+					(None, None) => continue,
+				};
+				if let Some(reply) = handler.on_message(&msg.chat_id, &text) {
+					if let Err(e) = self.send_message(&msg.chat_id, &reply) {
+						error!("Unable to send reply to chat {}, {}", msg.chat_id, e);
+					}
+				}
+			}
+		}
+	}
+}
+
+fn parse_updates(res: &Value) -> Updates {
+	let mut next_offset = None;
+	let mut messages = Vec::new();
+	for update in res.as_array().map(|a| a.as_slice()).unwrap_or(&[]) {
+		if let Some(update_id) = update["update_id"].as_i64() {
+			next_offset = Some(update_id + 1);
+		}
+		let message = if update["message"].is_object() {
+			&update["message"]
+		} else {
+			&update["channel_post"]
+		};
+		let chat_id = match &message["chat"]["id"] {
+			Value::Number(id) => id.to_string(),
+			_ => continue,
+		};
+		let text = message["text"].as_str().map(|s| s.to_string());
+		let file_id = message["document"]["file_id"]
+			.as_str()
+			.map(|s| s.to_string());
+		if text.is_none() && file_id.is_none() {
+			continue;
+		}
+		messages.push(ChatMessage {
+			chat_id,
+			text,
+			file_id,
+		});
+	}
+	Updates {
+		next_offset,
+		messages,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_updates() {
+		let res = json!([
+			{
+				"update_id": 100,
+				"message": {
+					"message_id": 1,
+					"from": { "id": 111, "is_bot": false, "first_name": "Alice" },
+					"chat": { "id": 111, "type": "private" },
+					"text": "BEGINSLATEPACK. ENDSLATEPACK."
+				}
+			},
+			{
+				"update_id": 101,
+				"message": {
+					"message_id": 2,
+					"chat": { "id": -1001234, "type": "supergroup" },
+					"document": { "file_id": "BQACAgIAAx", "file_name": "slatepack.txt" }
+				}
+			},
+			{
+				"update_id": 102,
+				"message": {
+					"message_id": 3,
+					"chat": { "id": 111, "type": "private" },
+					"sticker": { "file_id": "CAACAgIAAx" }
+				}
+			}
+		]);
+
+		assert_eq!(
+			parse_updates(&res),
+			Updates {
+				next_offset: Some(103),
+				messages: vec![
+					ChatMessage {
+						chat_id: "111".to_string(),
+						text: Some("BEGINSLATEPACK. ENDSLATEPACK.".to_string()),
+						file_id: None,
+					},
+					ChatMessage {
+						chat_id: "-1001234".to_string(),
+						text: None,
+						file_id: Some("BQACAgIAAx".to_string()),
+					},
+				],
+			}
+		);
+
+		assert_eq!(
+			parse_updates(&json!([])),
+			Updates {
+				next_offset: None,
+				messages: vec![],
+			}
+		);
+	}
+}
@@ -43,7 +43,7 @@ pub trait Subscriber {
 	fn reset_notification_channels(&self, slate_id: &uuid::Uuid);
 }
 
-pub trait SubscriptionHandler: Send {
+pub trait SubscriptionHandler: Send + Sync {
 	fn on_open(&self);
 	fn on_slate(&self, from: &dyn Address, slate: &mut Slate);
 	fn on_close(&self, result: CloseReason);
@@ -0,0 +1,58 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Webhook notifier. POSTs the wallet events as JSON to the configured URLs.
+
+use crate::client_utils::Client;
+use crate::libwallet::notifications::{EventNotifier, WalletEvent};
+use std::thread;
+
+/// Notifier that POSTs the wallet events to the webhook URLs
+#[derive(Clone)]
+pub struct WebhookNotifier {
+	urls: Vec<String>,
+}
+
+impl WebhookNotifier {
+	/// Create notifier for the URLs
+	pub fn new(urls: Vec<String>) -> Self {
+		WebhookNotifier { urls }
+	}
+}
+
+impl EventNotifier for WebhookNotifier {
+	fn notify(&self, event: WalletEvent) {
+		if self.urls.is_empty() {
+			return;
+		}
+		// Webhooks are slow, the wallet doesn't wait for them
+		let urls = self.urls.clone();
+		let _ = thread::Builder::new()
+			.name("webhook-notifier".to_string())
+			.spawn(move || {
+				let client = match Client::new(false, None) {
+					Ok(client) => client,
+					Err(e) => {
+						warn!("Unable to create webhook client, {}", e);
+						return;
+					}
+				};
+				for url in &urls {
+					if let Err(e) = client._post_no_ret(url, None, &event) {
+						warn!("Unable to send event {:?} to webhook {}, {}", event, url, e);
+					}
+				}
+			});
+	}
+}
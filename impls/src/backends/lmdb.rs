@@ -20,6 +20,7 @@ use std::fs::File;
 use std::io::{Read, Write};
 use std::marker::PhantomData;
 use std::path::Path;
+use std::time::SystemTime;
 
 use crate::blake2::blake2b::{Blake2b, Blake2bResult};
 
@@ -29,19 +30,24 @@ use crate::store::{self, option_to_not_found, to_key, to_key_u64, u64_to_key};
 use crate::core::core::Transaction;
 use crate::core::ser;
 use crate::libwallet::{
-	swap::ethereum::EthereumWallet, AcctPathMapping, Context, Error, ErrorKind, NodeClient,
-	OutputData, ScannedBlockInfo, TxLogEntry, TxProof, WalletBackend, WalletOutputBatch,
+	internal::scan::OutputResult, swap::ethereum::EthereumWallet, AcctPathMapping, AddressMapping,
+	Contact, Context, Error, ErrorKind, NodeClient, OutputData, ScanCheckpoint, ScannedBlockInfo,
+	Slate, SlateVersion, TxLogEntry, TxProof, TxStatus, VersionedSlate, WalletBackend,
+	WalletOutputBatch,
 };
 use crate::util::secp::constants::SECRET_KEY_SIZE;
 use crate::util::secp::key::SecretKey;
 use crate::util::{self, secp};
 
+use crate::libwallet::migration::WalletMigrationData;
+use crate::libwallet::notifications::{self, WalletEvent};
 use grin_wallet_libwallet::IntegrityContext;
 use rand::rngs::mock::StepRng;
 use rand::thread_rng;
 
 pub const DB_DIR: &str = "db";
 pub const TX_SAVE_DIR: &str = "saved_txs";
+pub const RESPONSE_SAVE_DIR: &str = "saved_responses";
 
 const OUTPUT_PREFIX: u8 = b'o';
 const DERIV_PREFIX: u8 = b'd';
@@ -53,6 +59,13 @@ const ACCOUNT_PATH_MAPPING_PREFIX: u8 = b'a';
 const LAST_SCANNED_BLOCK: u8 = b'm'; // pre v3.0 was l
 const LAST_WORKING_NODE_INDEX: u8 = b'n';
 const INTEGRITY_CONTEXT_PREFIX: u8 = b'g';
+const CONTACT_PREFIX: u8 = b'k';
+const SCAN_CHECKPOINT: u8 = b's';
+const SCAN_CHECKPOINT_OUTPUT_PREFIX: u8 = b'r';
+const SCAN_CHECKPOINT_SELF_SPEND_PREFIX: u8 = b'q';
+const OUTPUTS_VALIDATION_HEIGHT: u8 = b'v';
+const MAX_ISSUED_ADDRESS_INDEX: u8 = b'x';
+const ADDRESS_MAPPING_PREFIX: u8 = b'b';
 
 /// test to see if database files exist in the current directory. If so,
 /// use a DB backend for all operations
@@ -61,6 +74,29 @@ pub fn wallet_db_exists(data_file_dir: &str) -> bool {
 	db_path.exists()
 }
 
+/// Read the accounts, the outputs, the transactions and the contacts from the wallet DB of
+/// another wallet with the same DB format, like mwc713. The DB is not changed.
+pub fn read_wallet_db(data_file_dir: &str) -> Result<WalletMigrationData, Error> {
+	let db_path = path::Path::new(data_file_dir).join(DB_DIR);
+	if !db_path.exists() {
+		return Err(ErrorKind::GenericError(format!(
+			"Wallet DB is not found at {}",
+			db_path.display()
+		))
+		.into());
+	}
+	let store = store::Store::new(db_path.to_str().unwrap(), None, Some(DB_DIR), None)?;
+	Ok(WalletMigrationData {
+		accounts: store
+			.iter(&[ACCOUNT_PATH_MAPPING_PREFIX])?
+			.map(|o| o.1)
+			.collect(),
+		outputs: store.iter(&[OUTPUT_PREFIX])?.map(|o| o.1).collect(),
+		txs: store.iter(&[TX_LOG_ENTRY_PREFIX])?.map(|o| o.1).collect(),
+		contacts: store.iter(&[CONTACT_PREFIX])?.map(|o| o.1).collect(),
+	})
+}
+
 /// Helper to derive XOR keys for storing private transaction keys in the DB
 /// (blind_xor_key, nonce_xor_key)
 fn private_ctx_xor_keys<K>(
@@ -128,6 +164,10 @@ where
 		fs::create_dir_all(&stored_tx_path)
 			.expect("Couldn't create wallet backend tx storage directory!");
 
+		let stored_response_path = path::Path::new(data_file_dir).join(RESPONSE_SAVE_DIR);
+		fs::create_dir_all(&stored_response_path)
+			.expect("Couldn't create wallet backend response storage directory!");
+
 		let store = store::Store::new(db_path.to_str().unwrap(), None, Some(DB_DIR), None)?;
 
 		// Make sure default wallet derivation path always exists
@@ -358,6 +398,29 @@ where
 		self.db.get_ser(&acct_key).map_err(|e| e.into())
 	}
 
+	fn contact_iter<'a>(&'a self) -> Box<dyn Iterator<Item = Contact> + 'a> {
+		Box::new(self.db.iter(&[CONTACT_PREFIX]).unwrap().map(|o| o.1))
+	}
+
+	fn get_contact(&self, name: &str) -> Result<Option<Contact>, Error> {
+		let contact_key = to_key(CONTACT_PREFIX, &mut name.as_bytes().to_vec());
+		self.db.get_ser(&contact_key).map_err(|e| e.into())
+	}
+
+	fn address_mapping_iter<'a>(&'a self) -> Box<dyn Iterator<Item = AddressMapping> + 'a> {
+		Box::new(
+			self.db
+				.iter(&[ADDRESS_MAPPING_PREFIX])
+				.unwrap()
+				.map(|o| o.1),
+		)
+	}
+
+	fn get_address_mapping(&self, index: u32) -> Result<Option<AddressMapping>, Error> {
+		let mapping_key = u64_to_key(ADDRESS_MAPPING_PREFIX, index as u64);
+		self.db.get_ser(&mapping_key).map_err(|e| e.into())
+	}
+
 	fn store_tx(&self, uuid: &str, tx: &Transaction) -> Result<(), Error> {
 		let filename = format!("{}.mwctx", uuid);
 		let path = path::Path::new(&self.data_file_dir)
@@ -423,6 +486,64 @@ where
 		)
 	}
 
+	fn stored_tx_files(&self) -> Result<Vec<(String, SystemTime, u64)>, Error> {
+		let dir = path::Path::new(&self.data_file_dir).join(TX_SAVE_DIR);
+		let mut res = vec![];
+		for entry in fs::read_dir(dir)? {
+			let entry = entry?;
+			let metadata = entry.metadata()?;
+			if !metadata.is_file() {
+				continue;
+			}
+			if let Some(name) = entry.file_name().to_str() {
+				res.push((name.to_string(), metadata.modified()?, metadata.len()));
+			}
+		}
+		Ok(res)
+	}
+
+	fn delete_stored_tx_file(&self, filename: &str) -> Result<(), Error> {
+		// Only plain file names, we don't want to touch anything outside of the storage
+		if filename.contains('/') || filename.contains('\\') || filename.starts_with('.') {
+			return Err(ErrorKind::StoredTransactionError(format!(
+				"Invalid stored transaction file name {}",
+				filename
+			))
+			.into());
+		}
+		let path = path::Path::new(&self.data_file_dir)
+			.join(TX_SAVE_DIR)
+			.join(filename);
+		fs::remove_file(path)?;
+		Ok(())
+	}
+
+	// V3 json keeps all the slate data, including the compact slate flag
+	fn store_response_slate(&self, slate: &Slate) -> Result<(), Error> {
+		let filename = format!("{}.response", slate.id);
+		let path = path::Path::new(&self.data_file_dir)
+			.join(RESPONSE_SAVE_DIR)
+			.join(filename);
+		let slate_json =
+			VersionedSlate::into_version_plain(slate.clone(), SlateVersion::V3)?.as_string()?;
+		let mut stored_slate = File::create(path)?;
+		stored_slate.write_all(slate_json.as_bytes())?;
+		stored_slate.sync_all()?;
+		Ok(())
+	}
+
+	fn get_stored_response_slate(&self, uuid: &str) -> Result<Option<Slate>, Error> {
+		let path = path::Path::new(&self.data_file_dir)
+			.join(RESPONSE_SAVE_DIR)
+			.join(format!("{}.response", uuid));
+		if !path.exists() {
+			return Ok(None);
+		}
+		let mut content = String::new();
+		File::open(path)?.read_to_string(&mut content)?;
+		Ok(Some(Slate::deserialize_upgrade_plain(&content)?))
+	}
+
 	fn batch<'a>(
 		&'a mut self,
 		keychain_mask: Option<&SecretKey>,
@@ -431,6 +552,7 @@ where
 			_store: self,
 			db: RefCell::new(Some(self.db.batch()?)),
 			keychain: Some(self.keychain(keychain_mask)?),
+			status_events: RefCell::new(vec![]),
 		}))
 	}
 
@@ -439,6 +561,7 @@ where
 			_store: self,
 			db: RefCell::new(Some(self.db.batch()?)),
 			keychain: None,
+			status_events: RefCell::new(vec![]),
 		}))
 	}
 
@@ -512,6 +635,36 @@ where
 		Ok(blocks)
 	}
 
+	fn last_outputs_validation_height(&mut self) -> Result<u64, Error> {
+		let height: Option<u64> = self.db.get_ser(&u64_to_key(OUTPUTS_VALIDATION_HEIGHT, 0))?;
+		Ok(height.unwrap_or(0))
+	}
+
+	fn max_issued_address_index(&mut self) -> Result<Option<u32>, Error> {
+		let index: Option<u32> = self.db.get_ser(&u64_to_key(MAX_ISSUED_ADDRESS_INDEX, 0))?;
+		Ok(index)
+	}
+
+	fn scan_checkpoint(&mut self) -> Result<Option<ScanCheckpoint>, Error> {
+		let checkpoint: Option<ScanCheckpoint> =
+			self.db.get_ser(&u64_to_key(SCAN_CHECKPOINT, 0))?;
+		Ok(checkpoint.map(|mut cp| {
+			cp.outputs = self
+				.db
+				.iter(&[SCAN_CHECKPOINT_OUTPUT_PREFIX])
+				.unwrap()
+				.map(|o| o.1)
+				.collect();
+			cp.self_spend_candidates = self
+				.db
+				.iter(&[SCAN_CHECKPOINT_SELF_SPEND_PREFIX])
+				.unwrap()
+				.map(|o| o.1)
+				.collect();
+			cp
+		}))
+	}
+
 	/// set ethereum wallet instance
 	fn set_ethereum_wallet(
 		&mut self,
@@ -545,6 +698,8 @@ where
 	db: RefCell<Option<store::Batch<'a>>>,
 	/// Keychain
 	keychain: Option<K>,
+	/// Transaction status changes, reported after the commit
+	status_events: RefCell<Vec<WalletEvent>>,
 }
 
 #[allow(missing_docs)]
@@ -699,6 +854,69 @@ where
 		Ok(())
 	}
 
+	fn save_scan_checkpoint(&mut self, checkpoint: &ScanCheckpoint) -> Result<(), Error> {
+		let br = self.db.borrow();
+		let db = br.as_ref().unwrap();
+		db.put_ser(&u64_to_key(SCAN_CHECKPOINT, 0), checkpoint)?;
+		for out in &checkpoint.outputs {
+			db.put_ser(
+				&u64_to_key(SCAN_CHECKPOINT_OUTPUT_PREFIX, out.mmr_index),
+				out,
+			)?;
+		}
+		for out in &checkpoint.self_spend_candidates {
+			db.put_ser(
+				&u64_to_key(SCAN_CHECKPOINT_SELF_SPEND_PREFIX, out.mmr_index),
+				out,
+			)?;
+		}
+		Ok(())
+	}
+
+	fn delete_scan_checkpoint(&mut self) -> Result<(), Error> {
+		let br = self.db.borrow();
+		let db = br.as_ref().unwrap();
+
+		let checkpoint_key = u64_to_key(SCAN_CHECKPOINT, 0);
+		let checkpoint: Option<ScanCheckpoint> = db.get_ser(&checkpoint_key)?;
+		if checkpoint.is_some() {
+			db.delete(&checkpoint_key)?;
+		}
+		for prefix in &[
+			SCAN_CHECKPOINT_OUTPUT_PREFIX,
+			SCAN_CHECKPOINT_SELF_SPEND_PREFIX,
+		] {
+			let keys: Vec<Vec<u8>> = db
+				.iter(&[*prefix])
+				.unwrap()
+				.map(|o: (Vec<u8>, OutputResult)| o.0)
+				.collect();
+			for key in &keys {
+				db.delete(key)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Save the height at what all unspent outputs were validated
+	fn save_last_outputs_validation_height(&mut self, height: u64) -> Result<(), Error> {
+		self.db
+			.borrow()
+			.as_ref()
+			.unwrap()
+			.put_ser(&u64_to_key(OUTPUTS_VALIDATION_HEIGHT, 0), &height)?;
+		Ok(())
+	}
+
+	fn save_max_issued_address_index(&mut self, index: u32) -> Result<(), Error> {
+		self.db
+			.borrow()
+			.as_ref()
+			.unwrap()
+			.put_ser(&u64_to_key(MAX_ISSUED_ADDRESS_INDEX, 0), &index)?;
+		Ok(())
+	}
+
 	/// Save the last used good node index
 	fn save_last_working_node_index(&mut self, node_index: u8) -> Result<(), Error> {
 		let node_index_key = u64_to_key(LAST_WORKING_NODE_INDEX, 0 as u64);
@@ -747,6 +965,24 @@ where
 			&mut parent_id.to_bytes().to_vec(),
 			tx_in.id as u64,
 		);
+		let mut tx_in = tx_in;
+		tx_in.sync_status();
+		if notifications::is_enabled() {
+			let prev: Option<TxLogEntry> =
+				self.db.borrow().as_ref().unwrap().get_ser(&tx_log_key)?;
+			let from_status: Option<TxStatus> = prev.map(|t| t.current_status());
+			if from_status.as_ref() != Some(&tx_in.status) {
+				self.status_events
+					.borrow_mut()
+					.push(WalletEvent::TxStatusChanged {
+						tx_id: tx_in.id,
+						slate_id: tx_in.tx_slate_id.map(|id| id.to_string()),
+						tx_type: format!("{:?}", tx_in.tx_type),
+						from_status: from_status.map(|s| format!("{:?}", s)),
+						to_status: format!("{:?}", tx_in.status),
+					});
+			}
+		}
 		self.db
 			.borrow()
 			.as_ref()
@@ -783,7 +1019,13 @@ where
 				break;
 			}
 		}
-		println!("rename acct from '{}' to '{}'", old_name, new_name);
+		debug!("rename acct from '{}' to '{}'", old_name, new_name);
+		Ok(())
+	}
+
+	fn delete_acct_path(&mut self, label: &str) -> Result<(), Error> {
+		let acct_key = to_key(ACCOUNT_PATH_MAPPING_PREFIX, &mut label.as_bytes().to_vec());
+		self.db.borrow().as_ref().unwrap().delete(&acct_key)?;
 		Ok(())
 	}
 
@@ -812,6 +1054,46 @@ where
 		)
 	}
 
+	fn save_contact(&mut self, contact: Contact) -> Result<(), Error> {
+		let contact_key = to_key(CONTACT_PREFIX, &mut contact.name.as_bytes().to_vec());
+		self.db
+			.borrow()
+			.as_ref()
+			.unwrap()
+			.put_ser(&contact_key, &contact)?;
+		Ok(())
+	}
+
+	fn delete_contact(&mut self, name: &str) -> Result<(), Error> {
+		let contact_key = to_key(CONTACT_PREFIX, &mut name.as_bytes().to_vec());
+		self.db
+			.borrow()
+			.as_ref()
+			.unwrap()
+			.delete(&contact_key)
+			.map_err(|e| e.into())
+	}
+
+	fn save_address_mapping(&mut self, mapping: AddressMapping) -> Result<(), Error> {
+		let mapping_key = u64_to_key(ADDRESS_MAPPING_PREFIX, mapping.index as u64);
+		self.db
+			.borrow()
+			.as_ref()
+			.unwrap()
+			.put_ser(&mapping_key, &mapping)?;
+		Ok(())
+	}
+
+	fn delete_address_mapping(&mut self, index: u32) -> Result<(), Error> {
+		let mapping_key = u64_to_key(ADDRESS_MAPPING_PREFIX, index as u64);
+		self.db
+			.borrow()
+			.as_ref()
+			.unwrap()
+			.delete(&mapping_key)
+			.map_err(|e| e.into())
+	}
+
 	fn lock_output(&mut self, out: &mut OutputData) -> Result<(), Error> {
 		out.lock();
 		self.save(out.clone())
@@ -865,6 +1147,9 @@ where
 	fn commit(&self) -> Result<(), Error> {
 		let db = self.db.replace(None);
 		db.unwrap().commit()?;
+		for event in self.status_events.replace(vec![]) {
+			notifications::notify(event);
+		}
 		Ok(())
 	}
 
@@ -13,5 +13,7 @@
 // limitations under the License.
 
 mod lmdb;
+mod mwc713;
 
 pub use self::lmdb::{wallet_db_exists, LMDBBackend};
+pub use self::mwc713::{copy_mwc713_files, read_mwc713_wallet};
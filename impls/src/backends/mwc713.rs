@@ -0,0 +1,107 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading of the mwc713 wallet data for the migration. mwc713 is built on the same libwallet,
+//! the seed file, the wallet DB, the stored transactions and the proofs have the same formats.
+//! The address book is in its own DB.
+
+use super::lmdb::{read_wallet_db, TX_SAVE_DIR};
+use crate::error::{Error, ErrorKind};
+use crate::libwallet::migration::WalletMigrationData;
+use crate::libwallet::proof::tx_proof::TX_PROOF_SAVE_DIR;
+use crate::libwallet::Contact;
+use crate::lifecycle::WalletSeed;
+use crate::store;
+use crate::util::ZeroingString;
+use std::fs;
+use std::path::Path;
+
+/// mwc713 address book DB directory
+const MWC713_CONTACTS_DIR: &str = "contacts";
+/// mwc713 address book records prefix
+const MWC713_CONTACT_PREFIX: u8 = b'X';
+
+/// Read the seed and the wallet data from the mwc713 data directory, 'wallet713_data' by default.
+/// The password is the mwc713 wallet password. The mwc713 files are not changed.
+pub fn read_mwc713_wallet(
+	data_dir: &str,
+	password: ZeroingString,
+) -> Result<(WalletSeed, WalletMigrationData), Error> {
+	if !WalletSeed::seed_file_exists(data_dir)? {
+		return Err(ErrorKind::ArgumentError(format!(
+			"mwc713 wallet seed file is not found at {}",
+			data_dir
+		))
+		.into());
+	}
+	let seed = WalletSeed::from_file(data_dir, password)?;
+	let mut data = read_wallet_db(data_dir)?;
+
+	let contacts_path = Path::new(data_dir).join(MWC713_CONTACTS_DIR);
+	if contacts_path.exists() {
+		let contacts_db = store::Store::new(
+			contacts_path.to_str().unwrap(),
+			None,
+			Some(MWC713_CONTACTS_DIR),
+			None,
+		)
+		.map_err(|e| ErrorKind::LibWallet(format!("Unable to open mwc713 address book, {}", e)))?;
+		let contacts: Vec<Contact> = contacts_db
+			.iter(&[MWC713_CONTACT_PREFIX])
+			.map_err(|e| {
+				ErrorKind::LibWallet(format!("Unable to read mwc713 address book, {}", e))
+			})?
+			.map(|o| o.1)
+			.collect();
+		data.contacts.extend(contacts);
+	}
+	Ok((seed, data))
+}
+
+/// Copy the stored transactions and the transaction proofs from the mwc713 data directory into
+/// the wallet data directory. The existing files are kept. Returns the number of copied files.
+pub fn copy_mwc713_files(data_dir: &str, wallet_data_dir: &str) -> Result<usize, Error> {
+	let mut copied = 0;
+	for dir in &[TX_SAVE_DIR, TX_PROOF_SAVE_DIR] {
+		let src = Path::new(data_dir).join(dir);
+		if !src.exists() {
+			continue;
+		}
+		let dest = Path::new(wallet_data_dir).join(dir);
+		fs::create_dir_all(&dest).map_err(|e| {
+			ErrorKind::IO(format!("Unable to create dir {}, {}", dest.display(), e))
+		})?;
+		let entries = fs::read_dir(&src)
+			.map_err(|e| ErrorKind::IO(format!("Unable to read dir {}, {}", src.display(), e)))?;
+		for entry in entries {
+			let entry = entry.map_err(|e| {
+				ErrorKind::IO(format!("Unable to read dir {}, {}", src.display(), e))
+			})?;
+			let dest_file = dest.join(entry.file_name());
+			if !entry.path().is_file() || dest_file.exists() {
+				continue;
+			}
+			fs::copy(entry.path(), &dest_file).map_err(|e| {
+				ErrorKind::IO(format!(
+					"Unable to copy {} to {}, {}",
+					entry.path().display(),
+					dest_file.display(),
+					e
+				))
+			})?;
+			copied += 1;
+		}
+	}
+	Ok(copied)
+}
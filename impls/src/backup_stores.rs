@@ -0,0 +1,435 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storages of the wallet backups: a local directory (it can be a mounted network share)
+//! or an S3 bucket. S3 credentials are taken from the standard AWS environment variables.
+
+use crate::libwallet::backup::BackupStore;
+use crate::libwallet::{Error, ErrorKind};
+use crate::util::to_hex;
+use chrono::Utc;
+use reqwest::Method;
+use ring::{digest, hmac};
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+const S3_TIMEOUT: Duration = Duration::from_secs(600);
+const S3_DEFAULT_REGION: &str = "us-east-1";
+
+/// Create the storage for the backup destination: 's3://<bucket>/<prefix>' or a directory
+pub fn create_backup_store(dest: &str) -> Result<Arc<dyn BackupStore>, Error> {
+	if dest.starts_with("s3://") {
+		Ok(Arc::new(S3BackupStore::new(dest)?))
+	} else {
+		Ok(Arc::new(DirBackupStore::new(dest)))
+	}
+}
+
+/// Backups in a local directory
+pub struct DirBackupStore {
+	dir: PathBuf,
+}
+
+impl DirBackupStore {
+	/// Backups are in this directory, it is created with the first backup
+	pub fn new(dir: &str) -> Self {
+		DirBackupStore {
+			dir: PathBuf::from(dir),
+		}
+	}
+}
+
+impl BackupStore for DirBackupStore {
+	fn location(&self) -> String {
+		self.dir.display().to_string()
+	}
+
+	fn put(&self, name: &str, data: &[u8]) -> Result<(), Error> {
+		fs::create_dir_all(&self.dir).map_err(|e| {
+			ErrorKind::Backup(format!(
+				"Unable to create backup directory {}, {}",
+				self.dir.display(),
+				e
+			))
+		})?;
+		// the backup appears only when it is complete
+		let tmp_path = self.dir.join(format!("{}.tmp", name));
+		fs::write(&tmp_path, data).map_err(|e| {
+			ErrorKind::Backup(format!("Unable to write {}, {}", tmp_path.display(), e))
+		})?;
+		fs::rename(&tmp_path, self.dir.join(name)).map_err(|e| {
+			ErrorKind::Backup(format!(
+				"Unable to save backup {} in {}, {}",
+				name,
+				self.dir.display(),
+				e
+			))
+		})?;
+		Ok(())
+	}
+
+	fn get(&self, name: &str) -> Result<Vec<u8>, Error> {
+		let path = self.dir.join(name);
+		Ok(fs::read(&path)
+			.map_err(|e| ErrorKind::Backup(format!("Unable to read {}, {}", path.display(), e)))?)
+	}
+
+	fn list(&self) -> Result<Vec<String>, Error> {
+		if !self.dir.exists() {
+			return Ok(vec![]);
+		}
+		let mut res = vec![];
+		for entry in fs::read_dir(&self.dir)? {
+			if let Ok(name) = entry?.file_name().into_string() {
+				res.push(name);
+			}
+		}
+		Ok(res)
+	}
+
+	fn delete(&self, name: &str) -> Result<(), Error> {
+		let path = self.dir.join(name);
+		fs::remove_file(&path).map_err(|e| {
+			ErrorKind::Backup(format!("Unable to delete {}, {}", path.display(), e))
+		})?;
+		Ok(())
+	}
+}
+
+/// Backups in an S3 bucket. The requests are signed with AWS Signature Version 4.
+/// Environment: AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY, optional AWS_SESSION_TOKEN,
+/// AWS_REGION (default us-east-1) and AWS_ENDPOINT_URL for S3 compatible storages.
+pub struct S3BackupStore {
+	bucket: String,
+	/// Key prefix, empty or ends with '/'
+	prefix: String,
+	region: String,
+	/// Scheme and host, e.g. https://s3.example.com
+	endpoint: String,
+	/// Path-style requests for the custom endpoint, virtual-hosted style for AWS
+	path_style: bool,
+	access_key: String,
+	secret_key: String,
+	session_token: Option<String>,
+}
+
+impl S3BackupStore {
+	/// Storage for 's3://<bucket>/<prefix>'
+	pub fn new(dest: &str) -> Result<Self, Error> {
+		let path = dest.trim_start_matches("s3://");
+		let mut split = path.splitn(2, '/');
+		let bucket = split.next().unwrap_or("").to_string();
+		if bucket.is_empty() {
+			return Err(ErrorKind::Backup(format!(
+				"Invalid backup destination {}, expected s3://<bucket>/<prefix>",
+				dest
+			))
+			.into());
+		}
+		let mut prefix = split.next().unwrap_or("").trim_matches('/').to_string();
+		if !prefix.is_empty() {
+			prefix.push('/');
+		}
+
+		let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+		let (access_key, secret_key) =
+			match (var("AWS_ACCESS_KEY_ID"), var("AWS_SECRET_ACCESS_KEY")) {
+				(Some(a), Some(s)) => (a, s),
+				_ => {
+					return Err(ErrorKind::Backup(
+						"S3 backup requires AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY environment variables"
+							.to_string(),
+					)
+					.into())
+				}
+			};
+		let region = var("AWS_REGION").unwrap_or(S3_DEFAULT_REGION.to_string());
+		let (endpoint, path_style) = match var("AWS_ENDPOINT_URL") {
+			Some(e) => (e.trim_end_matches('/').to_string(), true),
+			None => (
+				format!("https://{}.s3.{}.amazonaws.com", bucket, region),
+				false,
+			),
+		};
+
+		Ok(S3BackupStore {
+			bucket,
+			prefix,
+			region,
+			endpoint,
+			path_style,
+			access_key,
+			secret_key,
+			session_token: var("AWS_SESSION_TOKEN"),
+		})
+	}
+
+	fn object_path(&self, key: &str) -> String {
+		let key = uri_encode(key, false);
+		if self.path_style {
+			format!("/{}/{}", uri_encode(&self.bucket, true), key)
+		} else {
+			format!("/{}", key)
+		}
+	}
+
+	fn bucket_path(&self) -> String {
+		if self.path_style {
+			format!("/{}", uri_encode(&self.bucket, true))
+		} else {
+			"/".to_string()
+		}
+	}
+
+	/// Send the signed request, returns the response body
+	fn request(
+		&self,
+		method: Method,
+		path: &str,
+		query: &[(&str, String)],
+		body: Vec<u8>,
+	) -> Result<Vec<u8>, Error> {
+		let host = self
+			.endpoint
+			.splitn(2, "://")
+			.nth(1)
+			.unwrap_or(&self.endpoint)
+			.to_string();
+		let now = Utc::now();
+		let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+		let date = now.format("%Y%m%d").to_string();
+		let payload_hash = to_hex(digest::digest(&digest::SHA256, &body).as_ref());
+
+		let mut query: Vec<(String, String)> = query
+			.iter()
+			.map(|(k, v)| (uri_encode(k, true), uri_encode(v, true)))
+			.collect();
+		query.sort();
+		let query = query
+			.iter()
+			.map(|(k, v)| format!("{}={}", k, v))
+			.collect::<Vec<String>>()
+			.join("&");
+
+		let mut headers = vec![
+			("host".to_string(), host),
+			("x-amz-content-sha256".to_string(), payload_hash.clone()),
+			("x-amz-date".to_string(), amz_date.clone()),
+		];
+		if let Some(token) = &self.session_token {
+			headers.push(("x-amz-security-token".to_string(), token.clone()));
+		}
+		let signed_headers = headers
+			.iter()
+			.map(|(k, _)| k.as_str())
+			.collect::<Vec<&str>>()
+			.join(";");
+		let canonical_headers: String = headers
+			.iter()
+			.map(|(k, v)| format!("{}:{}\n", k, v.trim()))
+			.collect();
+		let canonical_request = format!(
+			"{}\n{}\n{}\n{}\n{}\n{}",
+			method.as_str(),
+			path,
+			query,
+			canonical_headers,
+			signed_headers,
+			payload_hash
+		);
+
+		let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+		let string_to_sign = format!(
+			"AWS4-HMAC-SHA256\n{}\n{}\n{}",
+			amz_date,
+			scope,
+			to_hex(digest::digest(&digest::SHA256, canonical_request.as_bytes()).as_ref())
+		);
+		let sign = |key: &[u8], data: &str| {
+			hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data.as_bytes())
+				.as_ref()
+				.to_vec()
+		};
+		let signing_key = sign(
+			&sign(
+				&sign(
+					&sign(format!("AWS4{}", self.secret_key).as_bytes(), &date),
+					&self.region,
+				),
+				"s3",
+			),
+			"aws4_request",
+		);
+		let authorization = format!(
+			"AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+			self.access_key,
+			scope,
+			signed_headers,
+			to_hex(&sign(&signing_key, &string_to_sign))
+		);
+
+		let url = if query.is_empty() {
+			format!("{}{}", self.endpoint, path)
+		} else {
+			format!("{}{}?{}", self.endpoint, path, query)
+		};
+		let client = reqwest::Client::builder()
+			.timeout(S3_TIMEOUT)
+			.build()
+			.map_err(|e| ErrorKind::Backup(format!("Unable to create S3 client, {}", e)))?;
+		let mut request = client
+			.request(method.clone(), &url)
+			.header("Authorization", authorization);
+		for (k, v) in headers.into_iter().filter(|(k, _)| k != "host") {
+			request = request.header(k.as_str(), v);
+		}
+		let mut response = request.body(body).send().map_err(|e| {
+			ErrorKind::Backup(format!("S3 request {} {} failed, {}", method, url, e))
+		})?;
+
+		let mut data = vec![];
+		response
+			.read_to_end(&mut data)
+			.map_err(|e| ErrorKind::Backup(format!("Unable to read S3 response, {}", e)))?;
+		if !response.status().is_success() {
+			return Err(ErrorKind::Backup(format!(
+				"S3 request {} {} failed with status {}, {}",
+				method,
+				url,
+				response.status(),
+				String::from_utf8_lossy(&data)
+			))
+			.into());
+		}
+		Ok(data)
+	}
+}
+
+impl BackupStore for S3BackupStore {
+	fn location(&self) -> String {
+		format!("s3://{}/{}", self.bucket, self.prefix)
+	}
+
+	fn put(&self, name: &str, data: &[u8]) -> Result<(), Error> {
+		let path = self.object_path(&format!("{}{}", self.prefix, name));
+		self.request(Method::PUT, &path, &[], data.to_vec())?;
+		Ok(())
+	}
+
+	fn get(&self, name: &str) -> Result<Vec<u8>, Error> {
+		let path = self.object_path(&format!("{}{}", self.prefix, name));
+		self.request(Method::GET, &path, &[], vec![])
+	}
+
+	fn list(&self) -> Result<Vec<String>, Error> {
+		let mut res = vec![];
+		let mut continuation: Option<String> = None;
+		loop {
+			let mut query = vec![
+				("list-type", "2".to_string()),
+				("prefix", self.prefix.clone()),
+			];
+			if let Some(token) = &continuation {
+				query.push(("continuation-token", token.clone()));
+			}
+			let response = self.request(Method::GET, &self.bucket_path(), &query, vec![])?;
+			let response = String::from_utf8_lossy(&response);
+			for key in xml_values(&response, "Key") {
+				if key.starts_with(&self.prefix) {
+					// only the objects directly under the prefix
+					let name = &key[self.prefix.len()..];
+					if !name.is_empty() && !name.contains('/') {
+						res.push(name.to_string());
+					}
+				}
+			}
+			continuation = match xml_values(&response, "IsTruncated").first() {
+				Some(t) if t == "true" => xml_values(&response, "NextContinuationToken")
+					.into_iter()
+					.next(),
+				_ => None,
+			};
+			if continuation.is_none() {
+				break;
+			}
+		}
+		Ok(res)
+	}
+
+	fn delete(&self, name: &str) -> Result<(), Error> {
+		let path = self.object_path(&format!("{}{}", self.prefix, name));
+		self.request(Method::DELETE, &path, &[], vec![])?;
+		Ok(())
+	}
+}
+
+/// URI encoding of AWS Signature Version 4
+fn uri_encode(s: &str, encode_slash: bool) -> String {
+	let mut res = String::new();
+	for b in s.bytes() {
+		match b {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+				res.push(b as char)
+			}
+			b'/' if !encode_slash => res.push('/'),
+			_ => res.push_str(&format!("%{:02X}", b)),
+		}
+	}
+	res
+}
+
+/// Values of the XML elements with this tag. The S3 responses are simple enough for that.
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+	let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+	let mut res = vec![];
+	let mut rest = xml;
+	while let Some(start) = rest.find(&open) {
+		rest = &rest[start + open.len()..];
+		match rest.find(&close) {
+			Some(end) => {
+				res.push(
+					rest[..end]
+						.replace("&lt;", "<")
+						.replace("&gt;", ">")
+						.replace("&quot;", "\"")
+						.replace("&apos;", "'")
+						.replace("&amp;", "&"),
+				);
+				rest = &rest[end + close.len()..];
+			}
+			None => break,
+		}
+	}
+	res
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn s3_helpers() {
+		assert_eq!(uri_encode("a b/c~d", false), "a%20b/c~d");
+		assert_eq!(uri_encode("a b/c~d", true), "a%20b%2Fc~d");
+		let xml = "<ListBucketResult><IsTruncated>false</IsTruncated>\
+			<Contents><Key>w/a&amp;b.bak</Key></Contents><Contents><Key>w/c.bak</Key></Contents>\
+			</ListBucketResult>";
+		assert_eq!(xml_values(xml, "Key"), vec!["w/a&b.bak", "w/c.bak"]);
+		assert_eq!(xml_values(xml, "IsTruncated"), vec!["false"]);
+	}
+}
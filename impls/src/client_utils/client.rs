@@ -15,11 +15,12 @@
 //! High level JSON/HTTP client API
 
 use crate::core::global;
+use crate::libwallet::proxy;
 use crate::util::to_base64;
 use crossbeam_utils::thread::scope;
 use failure::{Backtrace, Context, Fail};
 use hyper::body;
-use hyper::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, USER_AGENT, CONNECTION};
+use hyper::header::{HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE, USER_AGENT, CONNECTION};
 use hyper::{self, Body, Client as HyperClient, Request, Uri};
 use hyper_rustls;
 use hyper_timeout::TimeoutConnector;
@@ -98,8 +99,12 @@ pub struct Client {
 }
 
 impl Client {
-	/// New client
+	/// New client. If socks is not requested, the proxy from the config is used if it is set.
 	pub fn new(use_socks: bool, socks_proxy_addr: Option<SocketAddr>) -> Result<Self,Error> {
+		let (use_socks, socks_proxy_addr) = match (use_socks, proxy::get_proxy_addr()) {
+			(false, Some(proxy_addr)) => (true, Some(proxy_addr)),
+			_ => (use_socks, socks_proxy_addr),
+		};
 		let (https_client, socks_client) = Self::construct_client(use_socks, socks_proxy_addr)?;
 		Ok(Client {
 			https_client: Arc::new(https_client),
@@ -358,6 +363,22 @@ impl Client {
 		self.build_request_ex(url, "POST", api_secret, basic_auth_key, Some(json))
 	}
 
+	/// Send the request with a bearer token authorization, used by the messenger transports.
+	/// Return the response body as is.
+	pub fn send_request_with_token(
+		&self,
+		method: &str,
+		url: &str,
+		access_token: &str,
+		body: Option<String>,
+	) -> Result<String, Error> {
+		let mut req = self.build_request_ex(url, method, None, None, body)?;
+		let bearer = HeaderValue::from_str(&format!("Bearer {}", access_token))
+			.map_err(|e| ErrorKind::Argument(format!("Invalid access token, {}", e)))?;
+		req.headers_mut().insert(AUTHORIZATION, bearer);
+		self.send_request(req)
+	}
+
 	fn handle_request<T>(&self, req: Request<Body>) -> Result<T, Error>
 	where
 		for<'de> T: Deserialize<'de>,
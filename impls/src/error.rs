@@ -124,6 +124,18 @@ pub enum ErrorKind {
 	#[fail(display = "{} Sender returned invalid response.", _0)]
 	MqsInvalidRespose(String),
 
+	/// MQS broker didn't accept the message, it is in the outbox
+	#[fail(display = "MQS message {} is not delivered, it is queued for retry", _0)]
+	MqsMessageQueued(String),
+
+	/// Offline signing bundle is invalid or is at the wrong stage
+	#[fail(display = "Offline bundle error, {}", _0)]
+	OfflineBundle(String),
+
+	/// SLIP-39 Shamir share is invalid or the shares don't match
+	#[fail(display = "SLIP-39 share error, {}", _0)]
+	Shamir(String),
+
 	/// Other
 	#[fail(display = "Generic error: {}", _0)]
 	GenericError(String),
@@ -39,26 +39,36 @@ use grin_wallet_config as config;
 
 pub mod adapters;
 mod backends;
+pub mod backup_stores;
 mod client_utils;
 mod error;
 pub mod lifecycle;
 pub mod node_clients;
+pub mod offline_bundle;
+pub mod payment_id_resolver;
+pub mod price_source;
 pub mod test_framework;
 pub mod tor;
 
 pub use crate::adapters::{
-	create_sender,
-	get_mwcmqs_brocker, init_mwcmqs_access_data,
+	create_sender, create_swap_transport, create_transport,
+	flush_mwcmqs_outbox, get_mwcmqs_brocker, get_mwcmqs_outbox, init_mwcmqs_access_data,
+	init_mwcmqs_outbox,
 	Address, AddressType, CloseReason,
-	HttpDataSender, HttpsAddress,
-	MWCMQPublisher, MWCMQSAddress, MWCMQSubscriber, MwcMqsChannel, PathToSlatePutter, PathToSlateGetter, Publisher,
-	SlateGetter, SlatePutter, SlateReceiver, SlateSender, Subscriber, SubscriptionHandler,
-	SwapMessageSender,
+	HttpDataSender, HttpsAddress, MatrixChannel, NostrChannel,
+	MWCMQPublisher, MWCMQSAddress, MWCMQSubscriber, MqsMessageKind, MqsQueuedMessage, MwcMqsChannel, PathToSlatePutter, PathToSlateGetter, Publisher,
+	SlateGetter, SlatePutter, SlateReceiver, SlateSender, SlateTransport, SlateTransportHandler,
+	Subscriber, SubscriptionHandler,
+	SwapMessageSender, WebhookNotifier, AlertCommandNotifier,
 };
-pub use crate::backends::{wallet_db_exists, LMDBBackend};
+pub use crate::backends::{copy_mwc713_files, read_mwc713_wallet, wallet_db_exists, LMDBBackend};
+pub use crate::backup_stores::create_backup_store;
 pub use crate::error::{Error, ErrorKind};
 pub use crate::lifecycle::DefaultLCProvider;
 pub use crate::node_clients::HTTPNodeClient;
+pub use crate::offline_bundle::{OfflineBundleStage, OfflineTxBundle};
+pub use crate::payment_id_resolver::HttpPaymentIdResolver;
+pub use crate::price_source::HttpPriceSource;
 pub use crate::adapters::libp2p_messaging;
 
 use crate::keychain::{ExtKeychain, Keychain};
@@ -22,7 +22,7 @@ use crate::core::global;
 use crate::keychain::Keychain;
 use crate::libwallet::swap::ethereum::generate_ethereum_wallet;
 use crate::libwallet::{Error, ErrorKind, NodeClient, WalletBackend, WalletLCProvider};
-use crate::lifecycle::seed::WalletSeed;
+use crate::lifecycle::seed::{SeedKind, WalletSeed};
 use crate::util::secp::key::SecretKey;
 use crate::util::ZeroingString;
 use crate::LMDBBackend;
@@ -244,6 +244,18 @@ where
 		let mut data_dir_name = PathBuf::from(self.data_dir.clone());
 		data_dir_name.push(wallet_data_dir.unwrap_or(GRIN_WALLET_DIR));
 		let data_dir_name = data_dir_name.to_str().unwrap();
+		let (wallet_seed, seed_kind) =
+			WalletSeed::from_file_with_kind(&data_dir_name, password.clone()).map_err(|e| {
+				ErrorKind::Lifecycle(format!(
+					"Error opening wallet (is password correct?), {}",
+					e
+				))
+			})?;
+		// The decoy wallet has its own data, it never opens the data of the wallet
+		let data_dir_name = match seed_kind {
+			SeedKind::Wallet => data_dir_name.to_string(),
+			SeedKind::Decoy => wallet_seed.decoy_data_dir(data_dir_name),
+		};
 		let mut wallet: LMDBBackend<'a, C, K> =
 			match LMDBBackend::new(&data_dir_name, self.node_client.clone()) {
 				Err(e) => {
@@ -252,12 +264,6 @@ where
 				}
 				Ok(d) => d,
 			};
-		let wallet_seed = WalletSeed::from_file(&data_dir_name, password.clone()).map_err(|e| {
-			ErrorKind::Lifecycle(format!(
-				"Error opening wallet (is password correct?), {}",
-				e
-			))
-		})?;
 
 		if let Ok(mnmenoic) = wallet_seed.to_mnemonic() {
 			let ethereum_wallet = match global::is_mainnet() {
@@ -367,12 +373,19 @@ where
 		let data_dir_name = data_dir_name.to_str().unwrap();
 		// get seed for later check
 
-		let orig_wallet_seed = WalletSeed::from_file(&data_dir_name, old).map_err(|e| {
-			ErrorKind::Lifecycle(format!(
-				"Error opening wallet seed file {}, {}",
-				data_dir_name, e
-			))
-		})?;
+		let (orig_wallet_seed, seed_kind) =
+			WalletSeed::from_file_with_kind(&data_dir_name, old.clone()).map_err(|e| {
+				ErrorKind::Lifecycle(format!(
+					"Error opening wallet seed file {}, {}",
+					data_dir_name, e
+				))
+			})?;
+		if seed_kind == SeedKind::Decoy {
+			// The decoy password is changed in place, the wallet seed is not touched
+			return WalletSeed::change_decoy_password(data_dir_name, old, new).map_err(|e| {
+				ErrorKind::Lifecycle(format!("Error changing password, {}", e)).into()
+			});
+		}
 		let orig_mnemonic = orig_wallet_seed
 			.to_mnemonic()
 			.map_err(|e| ErrorKind::Lifecycle(format!("Error recovering mnemonic, {}", e)))?;
@@ -414,6 +427,13 @@ where
 					.to_string();
 			return Err(ErrorKind::Lifecycle(msg).into());
 		}
+		// The decoy keeps its own password
+		WalletSeed::keep_decoy(&backup_name, data_dir_name).map_err(|e| {
+			ErrorKind::Lifecycle(format!(
+				"Unable to keep the decoy seed, not removing backups, {}",
+				e
+			))
+		})?;
 		// Removing
 		info!("Password change confirmed, removing old seed file.");
 		fs::remove_file(backup_name)
@@ -422,6 +442,21 @@ where
 		Ok(())
 	}
 
+	fn set_decoy_password(
+		&self,
+		_name: Option<&str>,
+		password: ZeroingString,
+		decoy_password: Option<ZeroingString>,
+		wallet_data_dir: Option<&str>,
+	) -> Result<(), Error> {
+		let mut data_dir_name = PathBuf::from(self.data_dir.clone());
+		data_dir_name.push(wallet_data_dir.unwrap_or(GRIN_WALLET_DIR));
+		let data_dir_name = data_dir_name.to_str().unwrap();
+		WalletSeed::set_decoy_password(data_dir_name, password, decoy_password)
+			.map_err(|e| ErrorKind::Lifecycle(format!("Error setting decoy password, {}", e)))?;
+		Ok(())
+	}
+
 	fn delete_wallet(&self, _name: Option<&str>) -> Result<(), Error> {
 		let data_dir_name = PathBuf::from(self.data_dir.clone());
 		let data_dir_path = data_dir_name.to_str().unwrap();
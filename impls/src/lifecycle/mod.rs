@@ -14,8 +14,9 @@
 
 mod default;
 mod seed;
+pub mod slip39;
 
 pub use self::default::DefaultLCProvider;
 pub use seed::show_recovery_phrase;
 pub use seed::EncryptedWalletSeed;
-pub use seed::WalletSeed;
+pub use seed::{SeedKind, WalletSeed};
@@ -31,9 +31,71 @@ use std::num::NonZeroU32;
 
 pub const SEED_FILE: &str = "wallet.seed";
 
+/// Key of the decoy seed derivation from the wallet seed
+const DECOY_SEED_KEY: &[u8] = b"mwc-wallet decoy seed";
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct WalletSeed(Vec<u8>);
 
+/// Seed of the seed file that the password opens
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SeedKind {
+	/// The wallet seed
+	Wallet,
+	/// The decoy seed, it opens a separate wallet that can be revealed instead of the real one
+	Decoy,
+}
+
+fn seed_file_path(data_file_dir: &str) -> String {
+	format!("{}{}{}", data_file_dir, MAIN_SEPARATOR, SEED_FILE)
+}
+
+fn read_seed_file(seed_file_path: &str) -> Result<EncryptedWalletSeed, Error> {
+	let mut file = File::open(seed_file_path)
+		.map_err(|e| ErrorKind::IO(format!("Unable to open file {}, {}", seed_file_path, e)))?;
+	let mut buffer = String::new();
+	file.read_to_string(&mut buffer).map_err(|e| {
+		ErrorKind::IO(format!(
+			"Unable to read from file {}, {}",
+			seed_file_path, e
+		))
+	})?;
+	let enc_seed: EncryptedWalletSeed = serde_json::from_str(&buffer).map_err(|e| {
+		ErrorKind::Format(format!(
+			"Json to EncryptedWalletSeed conversion error, {}",
+			e
+		))
+	})?;
+	Ok(enc_seed)
+}
+
+// The seed file is replaced only after the new content is written. A seed file without the
+// decoy part gets the filler, every saved file looks the same.
+fn write_seed_file(seed_file_path: &str, enc_seed: &EncryptedWalletSeed) -> Result<(), Error> {
+	let mut enc_seed = enc_seed.clone();
+	if enc_seed.alt_seed.is_none() {
+		enc_seed.alt_seed = Some(Box::new(EncryptedWalletSeed::filler(
+			enc_seed.seed_length()?,
+		)?));
+	}
+	let enc_seed_json = serde_json::to_string_pretty(&enc_seed).map_err(|e| {
+		ErrorKind::Format(format!(
+			"EncryptedWalletSeed to json conversion error, {}",
+			e
+		))
+	})?;
+	let tmp_path = format!("{}.tmp", seed_file_path);
+	File::create(&tmp_path)
+		.and_then(|mut f| {
+			f.write_all(enc_seed_json.as_bytes())?;
+			f.sync_all()
+		})
+		.map_err(|e| ErrorKind::IO(format!("Unable to save data to {}, {}", tmp_path, e)))?;
+	fs::rename(&tmp_path, seed_file_path)
+		.map_err(|e| ErrorKind::IO(format!("Unable to replace file {}, {}", seed_file_path, e)))?;
+	Ok(())
+}
+
 pub fn show_recovery_phrase(phrase: ZeroingString) {
 	println!("Your recovery phrase is:");
 	println!();
@@ -151,18 +213,7 @@ impl WalletSeed {
 		}
 		let seed = WalletSeed::from_mnemonic(word_list)?;
 		let enc_seed = EncryptedWalletSeed::from_seed(&seed, password)?;
-		let enc_seed_json = serde_json::to_string_pretty(&enc_seed).map_err(|e| {
-			ErrorKind::Format(format!("EncryptedWalletSeed to json convert error, {}", e))
-		})?;
-		let mut file = File::create(seed_file_path).map_err(|e| {
-			ErrorKind::IO(format!("Unable to crate file {}, {}", seed_file_path, e))
-		})?;
-		file.write_all(&enc_seed_json.as_bytes()).map_err(|e| {
-			ErrorKind::IO(format!(
-				"Unable to store data to file {}, {}",
-				seed_file_path, e
-			))
-		})?;
+		write_seed_file(seed_file_path, &enc_seed)?;
 		warn!("Seed created from word list");
 		Ok(())
 	}
@@ -225,18 +276,7 @@ impl WalletSeed {
 
 		if write_seed {
 			let enc_seed = EncryptedWalletSeed::from_seed(&seed, password)?;
-			let enc_seed_json = serde_json::to_string_pretty(&enc_seed).map_err(|e| {
-				ErrorKind::Format(format!(
-					"EncryptedWalletSeed to json conversion error, {}",
-					e
-				))
-			})?;
-			let mut file = File::create(seed_file_path).map_err(|e| {
-				ErrorKind::IO(format!("Unable to create file {}, {}", seed_file_path, e))
-			})?;
-			file.write_all(&enc_seed_json.as_bytes()).map_err(|e| {
-				ErrorKind::IO(format!("Unable to save data to {}, {}", seed_file_path, e))
-			})?;
+			write_seed_file(seed_file_path, &enc_seed)?;
 		}
 
 		if show_seed {
@@ -250,33 +290,25 @@ impl WalletSeed {
 		data_file_dir: &str,
 		password: util::ZeroingString,
 	) -> Result<WalletSeed, Error> {
+		Ok(WalletSeed::from_file_with_kind(data_file_dir, password)?.0)
+	}
+
+	/// Seed that the password opens, the wallet seed or the decoy seed
+	pub fn from_file_with_kind(
+		data_file_dir: &str,
+		password: util::ZeroingString,
+	) -> Result<(WalletSeed, SeedKind), Error> {
 		// create directory if it doesn't exist
 		fs::create_dir_all(data_file_dir)
 			.map_err(|e| ErrorKind::IO(format!("Unable to create dir {}, {}", data_file_dir, e)))?;
 
-		let seed_file_path = &format!("{}{}{}", data_file_dir, MAIN_SEPARATOR, SEED_FILE,);
+		let seed_file_path = &seed_file_path(data_file_dir);
 
 		debug!("Using wallet seed file at: {}", seed_file_path);
 
 		if Path::new(seed_file_path).exists() {
-			let mut file = File::open(seed_file_path).map_err(|e| {
-				ErrorKind::IO(format!("Unable to open file {}, {}", seed_file_path, e))
-			})?;
-			let mut buffer = String::new();
-			file.read_to_string(&mut buffer).map_err(|e| {
-				ErrorKind::IO(format!(
-					"Unable to read from file {}, {}",
-					seed_file_path, e
-				))
-			})?;
-			let enc_seed: EncryptedWalletSeed = serde_json::from_str(&buffer).map_err(|e| {
-				ErrorKind::Format(format!(
-					"Json to EncryptedWalletSeed conversion error, {}",
-					e
-				))
-			})?;
-			let wallet_seed = enc_seed.decrypt(&password)?;
-			Ok(wallet_seed)
+			let enc_seed = read_seed_file(seed_file_path)?;
+			enc_seed.decrypt_with_kind(&password)
 		} else {
 			error!(
 				"wallet seed file {} could not be opened (mwc wallet init). \
@@ -287,6 +319,86 @@ impl WalletSeed {
 		}
 	}
 
+	/// Seed of the decoy wallet. It is derived from the wallet seed with a different root, the
+	/// decoy wallet doesn't share any keys with the wallet.
+	pub fn to_decoy(&self) -> WalletSeed {
+		let seed = blake2::blake2b::blake2b(self.0.len(), DECOY_SEED_KEY, &self.0);
+		WalletSeed::from_bytes(seed.as_bytes())
+	}
+
+	/// Data directory of the decoy wallet, for the decoy seed. It is a hidden directory next to
+	/// the wallet data directory, so the wallet data and its backups don't show the decoy. The
+	/// decoy wallet never opens the data of the wallet.
+	pub fn decoy_data_dir(&self, data_file_dir: &str) -> String {
+		let hash = blake2::blake2b::blake2b(8, &[], &self.0);
+		let dir_name = format!(".{}", util::to_hex(hash.as_bytes()));
+		let data_path = Path::new(data_file_dir);
+		data_path
+			.parent()
+			.unwrap_or(data_path)
+			.join(dir_name)
+			.to_string_lossy()
+			.to_string()
+	}
+
+	/// Set the decoy password, `None` removes the decoy. The wallet password is required.
+	pub fn set_decoy_password(
+		data_file_dir: &str,
+		password: util::ZeroingString,
+		decoy_password: Option<util::ZeroingString>,
+	) -> Result<(), Error> {
+		let seed_file_path = &seed_file_path(data_file_dir);
+		let mut enc_seed = read_seed_file(seed_file_path)?;
+		let seed = enc_seed.decrypt(&password)?;
+		let alt_seed = match decoy_password {
+			Some(decoy_password) => {
+				if decoy_password == password {
+					return Err(ErrorKind::GenericError(
+						"Decoy password must be different from the wallet password".to_string(),
+					)
+					.into());
+				}
+				EncryptedWalletSeed::encrypt(&seed.to_decoy(), decoy_password)?
+			}
+			None => EncryptedWalletSeed::filler(seed.0.len())?,
+		};
+		enc_seed.alt_seed = Some(Box::new(alt_seed));
+		write_seed_file(seed_file_path, &enc_seed)
+	}
+
+	/// Change the password of the decoy seed, the wallet seed is not touched
+	pub fn change_decoy_password(
+		data_file_dir: &str,
+		old: util::ZeroingString,
+		new: util::ZeroingString,
+	) -> Result<(), Error> {
+		let seed_file_path = &seed_file_path(data_file_dir);
+		let mut enc_seed = read_seed_file(seed_file_path)?;
+		let decoy = match enc_seed.decrypt_with_kind(&old)? {
+			(seed, SeedKind::Decoy) => seed,
+			(_, SeedKind::Wallet) => {
+				return Err(ErrorKind::GenericError(
+					"Password doesn't open the decoy seed".to_string(),
+				)
+				.into())
+			}
+		};
+		enc_seed.alt_seed = Some(Box::new(EncryptedWalletSeed::encrypt(&decoy, new)?));
+		write_seed_file(seed_file_path, &enc_seed)
+	}
+
+	/// Copy the decoy seed from the backup seed file, the wallet seed file was created again
+	pub fn keep_decoy(backup_file: &str, data_file_dir: &str) -> Result<(), Error> {
+		let backup = read_seed_file(backup_file)?;
+		let seed_file_path = &seed_file_path(data_file_dir);
+		let mut enc_seed = read_seed_file(seed_file_path)?;
+		if backup.alt_seed.is_some() {
+			enc_seed.alt_seed = backup.alt_seed;
+			write_seed_file(seed_file_path, &enc_seed)?;
+		}
+		Ok(())
+	}
+
 	pub fn delete_seed_file(data_file_dir: &str) -> Result<(), Error> {
 		let seed_file_path = &format!("{}{}{}", data_file_dir, MAIN_SEPARATOR, SEED_FILE,);
 		if Path::new(seed_file_path).exists() {
@@ -310,6 +422,10 @@ pub struct EncryptedWalletSeed {
 	pub salt: String,
 	/// Nonce
 	pub nonce: String,
+	/// Decoy seed, encrypted with the decoy password. Without the decoy it is random data of the
+	/// same size, so the file doesn't show whether the decoy is set.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub alt_seed: Option<Box<EncryptedWalletSeed>>,
 }
 
 impl EncryptedWalletSeed {
@@ -317,18 +433,37 @@ impl EncryptedWalletSeed {
 	pub fn from_seed(
 		seed: &WalletSeed,
 		password: util::ZeroingString,
+	) -> Result<EncryptedWalletSeed, Error> {
+		let mut enc_seed = EncryptedWalletSeed::encrypt(seed, password)?;
+		enc_seed.alt_seed = Some(Box::new(EncryptedWalletSeed::filler(seed.0.len())?));
+		Ok(enc_seed)
+	}
+
+	// Length of the encrypted seed, the encrypted data has the tag at the end
+	fn seed_length(&self) -> Result<usize, Error> {
+		let encrypted_seed = util::from_hex(&self.encrypted_seed)
+			.map_err(|e| ErrorKind::Encryption(format!("Failed to convert seed HEX, {}", e)))?;
+		Ok(encrypted_seed
+			.len()
+			.saturating_sub(aead::CHACHA20_POLY1305.tag_len()))
+	}
+
+	// Random seed with random password, it takes the place of the decoy seed
+	fn filler(seed_length: usize) -> Result<EncryptedWalletSeed, Error> {
+		let password: [u8; 32] = thread_rng().gen();
+		EncryptedWalletSeed::encrypt(
+			&WalletSeed::init_new(seed_length),
+			ZeroingString::from(util::to_hex(&password)),
+		)
+	}
+
+	fn encrypt(
+		seed: &WalletSeed,
+		password: util::ZeroingString,
 	) -> Result<EncryptedWalletSeed, Error> {
 		let salt: [u8; 8] = thread_rng().gen();
 		let nonce: [u8; 12] = thread_rng().gen();
-		let password = password.as_bytes();
-		let mut key = [0; 32];
-		pbkdf2::derive(
-			ring::pbkdf2::PBKDF2_HMAC_SHA512,
-			NonZeroU32::new(100).unwrap(),
-			&salt,
-			password,
-			&mut key,
-		);
+		let key = EncryptedWalletSeed::derive_key(&salt, password.as_bytes());
 		let content = seed.0.to_vec();
 		let mut enc_bytes = content;
 		/*let suffix_len = aead::CHACHA20_POLY1305.tag_len();
@@ -350,9 +485,44 @@ impl EncryptedWalletSeed {
 			encrypted_seed: util::to_hex(&enc_bytes),
 			salt: util::to_hex(&salt),
 			nonce: util::to_hex(&nonce),
+			alt_seed: None,
 		})
 	}
 
+	fn derive_key(salt: &[u8], password: &[u8]) -> [u8; 32] {
+		let mut key = [0; 32];
+		pbkdf2::derive(
+			ring::pbkdf2::PBKDF2_HMAC_SHA512,
+			NonZeroU32::new(100).unwrap(),
+			salt,
+			password,
+			&mut key,
+		);
+		key
+	}
+
+	/// Decrypt the seed that the password opens, the wallet seed or the decoy seed. Both seeds
+	/// are always decrypted, so the time doesn't show which one the password opens or whether
+	/// the decoy is set. Because of that every open runs the key derivation twice and takes
+	/// double the time of the single seed decryption.
+	pub fn decrypt_with_kind(&self, password: &str) -> Result<(WalletSeed, SeedKind), Error> {
+		let wallet_seed = self.decrypt(password);
+		let alt_seed = match self.alt_seed.as_ref() {
+			Some(alt) => alt.decrypt(password).ok(),
+			None => {
+				// Old seed file without the decoy part, the key derivation still takes the time
+				let salt: [u8; 8] = thread_rng().gen();
+				EncryptedWalletSeed::derive_key(&salt, password.as_bytes());
+				None
+			}
+		};
+		match (wallet_seed, alt_seed) {
+			(Ok(seed), _) => Ok((seed, SeedKind::Wallet)),
+			(Err(_), Some(seed)) => Ok((seed, SeedKind::Decoy)),
+			(Err(e), None) => Err(e),
+		}
+	}
+
 	/// Decrypt seed
 	pub fn decrypt(&self, password: &str) -> Result<WalletSeed, Error> {
 		let mut encrypted_seed = util::from_hex(&self.encrypted_seed)
@@ -362,15 +532,7 @@ impl EncryptedWalletSeed {
 		let nonce = util::from_hex(&self.nonce)
 			.map_err(|e| ErrorKind::Encryption(format!("Failed to convert nonce HEX, {}", e)))?;
 
-		let password = password.as_bytes();
-		let mut key = [0; 32];
-		pbkdf2::derive(
-			ring::pbkdf2::PBKDF2_HMAC_SHA512,
-			NonZeroU32::new(100).unwrap(),
-			&salt,
-			password,
-			&mut key,
-		);
+		let key = EncryptedWalletSeed::derive_key(&salt, password.as_bytes());
 
 		let mut n = [0u8; 12];
 		n.copy_from_slice(&nonce[0..12]);
@@ -396,7 +558,17 @@ impl EncryptedWalletSeed {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::util::ZeroingString;
+	use crate::util::{self, ZeroingString};
+
+	fn clean_output_dir(test_dir: &str) {
+		let _ = fs::remove_dir_all(test_dir);
+	}
+
+	fn setup(test_dir: &str) {
+		util::init_test_logger();
+		clean_output_dir(test_dir);
+	}
+
 	#[test]
 	fn wallet_seed_encrypt() {
 		let password = ZeroingString::from("passwoid");
@@ -416,4 +588,77 @@ mod tests {
 		let decrypted_wallet_seed = enc_wallet_seed.decrypt(&password);
 		assert!(decrypted_wallet_seed.is_err());
 	}
+
+	#[test]
+	fn wallet_seed_decoy() {
+		let password = ZeroingString::from("passwoid");
+		let decoy_password = ZeroingString::from("decoy");
+		let wallet_seed = WalletSeed::init_new(32);
+		let decoy_seed = wallet_seed.to_decoy();
+		assert_ne!(wallet_seed, decoy_seed);
+		assert_eq!(decoy_seed, wallet_seed.to_decoy());
+		assert!(decoy_seed.to_mnemonic().is_ok());
+
+		// Without the decoy the file still has the decoy part
+		let mut enc_wallet_seed =
+			EncryptedWalletSeed::from_seed(&wallet_seed, password.clone()).unwrap();
+		assert!(enc_wallet_seed.alt_seed.is_some());
+		assert!(enc_wallet_seed.decrypt_with_kind(&decoy_password).is_err());
+
+		enc_wallet_seed.alt_seed = Some(Box::new(
+			EncryptedWalletSeed::encrypt(&decoy_seed, decoy_password.clone()).unwrap(),
+		));
+		assert_eq!(
+			enc_wallet_seed.decrypt_with_kind(&password).unwrap(),
+			(wallet_seed, SeedKind::Wallet)
+		);
+		assert_eq!(
+			enc_wallet_seed.decrypt_with_kind(&decoy_password).unwrap(),
+			(decoy_seed, SeedKind::Decoy)
+		);
+		assert!(enc_wallet_seed.decrypt_with_kind("wrong").is_err());
+
+		// The decoy data is hidden next to the wallet data
+		let data_dir = format!("top{}wallet_data", MAIN_SEPARATOR);
+		let decoy_dir = wallet_seed.decoy_data_dir(&data_dir);
+		let decoy_path = Path::new(&decoy_dir);
+		assert_eq!(decoy_path.parent(), Some(Path::new("top")));
+		assert!(decoy_path
+			.file_name()
+			.unwrap()
+			.to_string_lossy()
+			.starts_with('.'));
+		assert_ne!(decoy_dir, decoy_seed.decoy_data_dir(&data_dir));
+	}
+
+	#[test]
+	fn wallet_seed_file_filler() {
+		let data_dir = "test_output/wallet_seed_file_filler";
+		setup(data_dir);
+		fs::create_dir_all(data_dir).unwrap();
+		let password = ZeroingString::from("passwoid");
+		let wallet_seed = WalletSeed::init_new(32);
+
+		// Seed file of the older wallet, without the decoy part
+		let mut enc_wallet_seed =
+			EncryptedWalletSeed::from_seed(&wallet_seed, password.clone()).unwrap();
+		enc_wallet_seed.alt_seed = None;
+		assert_eq!(enc_wallet_seed.seed_length().unwrap(), 32);
+		assert_eq!(
+			enc_wallet_seed.decrypt_with_kind(&password).unwrap(),
+			(wallet_seed.clone(), SeedKind::Wallet)
+		);
+
+		// Saving adds the filler
+		let path = seed_file_path(data_dir);
+		write_seed_file(&path, &enc_wallet_seed).unwrap();
+		let saved = read_seed_file(&path).unwrap();
+		assert!(saved.alt_seed.is_some());
+		assert_eq!(saved.alt_seed.unwrap().seed_length().unwrap(), 32);
+		assert_eq!(
+			WalletSeed::from_file_with_kind(data_dir, password).unwrap(),
+			(wallet_seed, SeedKind::Wallet)
+		);
+		clean_output_dir(data_dir);
+	}
 }
@@ -0,0 +1,675 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SLIP-39 Shamir backup of the wallet seed.
+//! https://github.com/satoshilabs/slips/blob/master/slip-0039.md
+//! The wallet seed (the entropy of the recovery phrase) is the master secret. The shares are created
+//! in a single group without passphrase, recovery accepts any shares that follow the standard.
+
+use std::collections::BTreeMap;
+use std::num::NonZeroU32;
+
+use rand::{thread_rng, Rng};
+use ring::{hmac, pbkdf2};
+use util::ZeroingString;
+
+use crate::keychain::mnemonic;
+use crate::util;
+use crate::{Error, ErrorKind};
+
+/// Number of bits per word
+const RADIX_BITS: usize = 10;
+/// Number of words of the share header: id, iteration exponent, group and member data
+const HEADER_WORDS: usize = 4;
+/// Number of checksum words
+const CHECKSUM_WORDS: usize = 3;
+/// Minimal length of the master secret in bytes
+const MIN_SECRET_LEN: usize = 16;
+/// Maximal number of shares in the group
+const MAX_SHARE_COUNT: u8 = 16;
+/// x coordinate of the digest share
+const DIGEST_INDEX: u8 = 254;
+/// x coordinate of the secret
+const SECRET_INDEX: u8 = 255;
+/// Length of the digest of the shared secret
+const DIGEST_LEN: usize = 4;
+/// Number of Feistel rounds of the master secret encryption
+const ROUND_COUNT: u8 = 4;
+/// Base iteration count of PBKDF2 for all the rounds
+const BASE_ITERATION_COUNT: u32 = 10000;
+/// Iteration exponent of the created shares
+const ITERATION_EXPONENT: u8 = 1;
+
+const CUSTOMIZATION_STRING: &[u8] = b"shamir";
+const CUSTOMIZATION_STRING_EXTENDABLE: &[u8] = b"shamir_extendable";
+
+const RS1024_GEN: [u32; 10] = [
+	0xE0E040, 0x1C1C080, 0x3838100, 0x7070200, 0xE0E0009, 0x1C0C2412, 0x38086C24, 0x3090FC48,
+	0x21B1F890, 0x3F3F120,
+];
+
+struct GfTables {
+	exp: [u8; 255],
+	log: [u8; 256],
+}
+
+lazy_static! {
+	static ref WORDS: Vec<&'static str> = include_str!("slip39_wordlist.txt").lines().collect();
+	static ref GF: GfTables = {
+		let mut exp = [0u8; 255];
+		let mut log = [0u8; 256];
+		let mut poly: u16 = 1;
+		for i in 0..255 {
+			exp[i] = poly as u8;
+			log[poly as usize] = i as u8;
+			// multiply by (x + 1), the generator of GF(256) with the Rijndael polynomial
+			poly = (poly << 1) ^ poly;
+			if poly & 0x100 != 0 {
+				poly ^= 0x11B;
+			}
+		}
+		GfTables { exp, log }
+	};
+}
+
+/// Decoded share
+#[derive(Clone, Debug)]
+struct Share {
+	id: u16,
+	extendable: bool,
+	iteration_exponent: u8,
+	group_index: u8,
+	group_threshold: u8,
+	group_count: u8,
+	member_index: u8,
+	member_threshold: u8,
+	value: Vec<u8>,
+}
+
+impl Share {
+	/// Shares can be combined only if they have the same common parameters
+	fn same_set(&self, other: &Share) -> bool {
+		self.id == other.id
+			&& self.extendable == other.extendable
+			&& self.iteration_exponent == other.iteration_exponent
+			&& self.group_threshold == other.group_threshold
+			&& self.group_count == other.group_count
+	}
+}
+
+/// Print the shares to the console
+pub fn show_shamir_shares(threshold: u8, shares: &Vec<ZeroingString>) {
+	println!(
+		"Your seed is split into {} SLIP-39 shares, any {} of them recover the wallet:",
+		shares.len(),
+		threshold
+	);
+	for (i, share) in shares.iter().enumerate() {
+		println!();
+		println!("Share {}:", i + 1);
+		println!("{}", &**share);
+	}
+	println!();
+	println!(
+		"Please back-up every share in a non-digital format and keep them in different places."
+	);
+}
+
+/// Split the recovery phrase of the wallet into SLIP-39 shares, any threshold of them recover it.
+pub fn split_mnemonic(
+	phrase: &ZeroingString,
+	threshold: u8,
+	share_count: u8,
+) -> Result<Vec<ZeroingString>, Error> {
+	let seed = mnemonic::to_entropy(phrase).map_err(|e| {
+		ErrorKind::Mnemonic(format!(
+			"Unable to convert mnemonic passphrase into seed, {}",
+			e
+		))
+	})?;
+	Ok(split_seed(&seed, threshold, share_count)?
+		.into_iter()
+		.map(|s| ZeroingString::from(s))
+		.collect())
+}
+
+/// Recover the recovery phrase of the wallet from the SLIP-39 shares
+pub fn recover_mnemonic(shares: &[ZeroingString]) -> Result<ZeroingString, Error> {
+	let shares: Vec<&str> = shares.iter().map(|s| s.as_str()).collect();
+	let seed = combine_shares(&shares, b"")?;
+	let phrase = mnemonic::from_entropy(&seed).map_err(|e| {
+		ErrorKind::Mnemonic(format!(
+			"Recovered seed can't be used as a wallet seed, {}",
+			e
+		))
+	})?;
+	Ok(ZeroingString::from(phrase))
+}
+
+/// Check if the words are a valid SLIP-39 share
+pub fn is_share(words: &str) -> bool {
+	decode_share(words).is_ok()
+}
+
+/// Check if there are enough shares to recover the master secret. Error if the shares don't match.
+pub fn is_complete(shares: &[ZeroingString]) -> Result<bool, Error> {
+	let shares: Vec<&str> = shares.iter().map(|s| s.as_str()).collect();
+	let (first, groups) = collect_groups(&shares)?;
+	Ok(complete_groups(&groups).len() >= first.group_threshold as usize)
+}
+
+/// Split the master secret into single group shares without passphrase
+pub fn split_seed(seed: &[u8], threshold: u8, share_count: u8) -> Result<Vec<String>, Error> {
+	if seed.len() < MIN_SECRET_LEN || seed.len() % 2 != 0 {
+		return Err(ErrorKind::Shamir(format!(
+			"master secret must be at least {} bytes long and have even length",
+			MIN_SECRET_LEN
+		))
+		.into());
+	}
+	if share_count == 0 || share_count > MAX_SHARE_COUNT {
+		return Err(ErrorKind::Shamir(format!(
+			"number of shares must be between 1 and {}",
+			MAX_SHARE_COUNT
+		))
+		.into());
+	}
+	if threshold == 0 || threshold > share_count {
+		return Err(ErrorKind::Shamir(
+			"threshold must be between 1 and the number of shares".to_string(),
+		)
+		.into());
+	}
+	if threshold == 1 && share_count > 1 {
+		return Err(ErrorKind::Shamir(
+			"threshold 1 requires a single share, use the recovery phrase instead".to_string(),
+		)
+		.into());
+	}
+
+	let id = thread_rng().gen::<u16>() & 0x7FFF;
+	let encrypted = feistel(seed, b"", ITERATION_EXPONENT, id, false, true)?;
+
+	let shares = split_secret(threshold, share_count, &encrypted)?;
+	Ok(shares
+		.into_iter()
+		.map(|(member_index, value)| {
+			encode_share(&Share {
+				id,
+				extendable: false,
+				iteration_exponent: ITERATION_EXPONENT,
+				group_index: 0,
+				group_threshold: 1,
+				group_count: 1,
+				member_index,
+				member_threshold: threshold,
+				value,
+			})
+		})
+		.collect())
+}
+
+/// Recover the master secret from the shares
+pub fn combine_shares<T: AsRef<str>>(shares: &[T], passphrase: &[u8]) -> Result<Vec<u8>, Error> {
+	let (first, groups) = collect_groups(shares)?;
+	let complete = complete_groups(&groups);
+	if complete.len() < first.group_threshold as usize {
+		return Err(ErrorKind::Shamir(format!(
+			"not enough shares, {} of {} groups are complete",
+			complete.len(),
+			first.group_threshold
+		))
+		.into());
+	}
+
+	let mut group_shares = vec![];
+	for (group_index, members) in complete.into_iter().take(first.group_threshold as usize) {
+		let threshold = members[0].member_threshold;
+		let members: Vec<(u8, Vec<u8>)> = members
+			.iter()
+			.take(threshold as usize)
+			.map(|s| (s.member_index, s.value.clone()))
+			.collect();
+		group_shares.push((group_index, recover_secret(threshold, &members)?));
+	}
+	let encrypted = recover_secret(first.group_threshold, &group_shares)?;
+	feistel(
+		&encrypted,
+		passphrase,
+		first.iteration_exponent,
+		first.id,
+		first.extendable,
+		false,
+	)
+}
+
+/// Decode the shares and sort them by groups. Members with the same index are counted once.
+fn collect_groups<T: AsRef<str>>(shares: &[T]) -> Result<(Share, BTreeMap<u8, Vec<Share>>), Error> {
+	let mut decoded = shares.iter().map(|s| decode_share(s.as_ref()));
+	let first = decoded
+		.next()
+		.ok_or(ErrorKind::Shamir("no shares".to_string()))??;
+
+	let mut groups: BTreeMap<u8, Vec<Share>> = BTreeMap::new();
+	for share in std::iter::once(Ok(first.clone())).chain(decoded) {
+		let share = share?;
+		if !share.same_set(&first) {
+			return Err(ErrorKind::Shamir("shares belong to different backups".to_string()).into());
+		}
+		if share.value.len() != first.value.len() {
+			return Err(ErrorKind::Shamir("shares have different length".to_string()).into());
+		}
+		let members = groups.entry(share.group_index).or_insert(vec![]);
+		if let Some(m) = members.first() {
+			if m.member_threshold != share.member_threshold {
+				return Err(ErrorKind::Shamir(format!(
+					"shares of group {} have different thresholds",
+					share.group_index + 1
+				))
+				.into());
+			}
+		}
+		match members
+			.iter()
+			.find(|m| m.member_index == share.member_index)
+		{
+			Some(m) => {
+				if m.value != share.value {
+					return Err(ErrorKind::Shamir(format!(
+						"different shares with the same index {}",
+						share.member_index + 1
+					))
+					.into());
+				}
+			}
+			None => members.push(share),
+		}
+	}
+	Ok((first, groups))
+}
+
+fn complete_groups(groups: &BTreeMap<u8, Vec<Share>>) -> Vec<(u8, &Vec<Share>)> {
+	groups
+		.iter()
+		.filter(|(_, members)| members.len() >= members[0].member_threshold as usize)
+		.map(|(i, members)| (*i, members))
+		.collect()
+}
+
+fn encode_share(share: &Share) -> String {
+	let header: u64 = (share.id as u64) << 25
+		| (share.extendable as u64) << 24
+		| (share.iteration_exponent as u64) << 20
+		| (share.group_index as u64) << 16
+		| ((share.group_threshold - 1) as u64) << 12
+		| ((share.group_count - 1) as u64) << 8
+		| (share.member_index as u64) << 4
+		| (share.member_threshold - 1) as u64;
+
+	let mut data: Vec<u16> = (0..HEADER_WORDS)
+		.rev()
+		.map(|i| ((header >> (i * RADIX_BITS)) & 0x3FF) as u16)
+		.collect();
+	data.extend(bytes_to_words(&share.value));
+	let checksum = rs1024_create(customization(share.extendable), &data);
+	data.extend(checksum);
+
+	data.iter()
+		.map(|w| WORDS[*w as usize])
+		.collect::<Vec<&str>>()
+		.join(" ")
+}
+
+fn decode_share(mnemonic: &str) -> Result<Share, Error> {
+	let data = mnemonic
+		.split_whitespace()
+		.map(|w| {
+			let w = w.to_lowercase();
+			WORDS
+				.binary_search_by(|probe| (*probe).cmp(w.as_str()))
+				.map(|i| i as u16)
+				.map_err(|_| Error::from(ErrorKind::Shamir(format!("unknown word '{}'", w))))
+		})
+		.collect::<Result<Vec<u16>, Error>>()?;
+
+	let min_words =
+		HEADER_WORDS + CHECKSUM_WORDS + (MIN_SECRET_LEN * 8 + RADIX_BITS - 1) / RADIX_BITS;
+	if data.len() < min_words {
+		return Err(
+			ErrorKind::Shamir(format!("share must have at least {} words", min_words)).into(),
+		);
+	}
+
+	let header = data[..HEADER_WORDS]
+		.iter()
+		.fold(0u64, |acc, w| (acc << RADIX_BITS) | *w as u64);
+	let extendable = (header >> 24) & 1 == 1;
+	if !rs1024_verify(customization(extendable), &data) {
+		return Err(ErrorKind::Shamir("invalid share checksum".to_string()).into());
+	}
+
+	let share = Share {
+		id: (header >> 25) as u16,
+		extendable,
+		iteration_exponent: ((header >> 20) & 0xF) as u8,
+		group_index: ((header >> 16) & 0xF) as u8,
+		group_threshold: ((header >> 12) & 0xF) as u8 + 1,
+		group_count: ((header >> 8) & 0xF) as u8 + 1,
+		member_index: ((header >> 4) & 0xF) as u8,
+		member_threshold: (header & 0xF) as u8 + 1,
+		value: words_to_bytes(&data[HEADER_WORDS..data.len() - CHECKSUM_WORDS])?,
+	};
+	if share.group_threshold > share.group_count {
+		return Err(ErrorKind::Shamir(
+			"group threshold is larger than the number of groups".to_string(),
+		)
+		.into());
+	}
+	Ok(share)
+}
+
+fn customization(extendable: bool) -> &'static [u8] {
+	if extendable {
+		CUSTOMIZATION_STRING_EXTENDABLE
+	} else {
+		CUSTOMIZATION_STRING
+	}
+}
+
+/// Pack the bytes into 10 bit words, the padding zero bits go first
+fn bytes_to_words(bytes: &[u8]) -> Vec<u16> {
+	let bits = bytes.len() * 8;
+	let word_count = (bits + RADIX_BITS - 1) / RADIX_BITS;
+	let mut acc: u32 = 0;
+	let mut acc_bits = word_count * RADIX_BITS - bits;
+	let mut words = Vec::with_capacity(word_count);
+	for b in bytes {
+		acc = (acc << 8) | *b as u32;
+		acc_bits += 8;
+		while acc_bits >= RADIX_BITS {
+			acc_bits -= RADIX_BITS;
+			words.push((acc >> acc_bits) as u16 & 0x3FF);
+			acc &= (1 << acc_bits) - 1;
+		}
+	}
+	words
+}
+
+fn words_to_bytes(words: &[u16]) -> Result<Vec<u8>, Error> {
+	let bits = words.len() * RADIX_BITS;
+	let padding = bits % 16;
+	if padding > 8 {
+		return Err(ErrorKind::Shamir("invalid share length".to_string()).into());
+	}
+	let mut acc: u32 = 0;
+	let mut acc_bits = 0;
+	let mut skip = padding;
+	let mut bytes = Vec::with_capacity((bits - padding) / 8);
+	for w in words {
+		acc = (acc << RADIX_BITS) | *w as u32;
+		acc_bits += RADIX_BITS;
+		if skip > 0 {
+			acc_bits -= skip;
+			if acc >> acc_bits != 0 {
+				return Err(ErrorKind::Shamir("invalid share padding".to_string()).into());
+			}
+			acc &= (1 << acc_bits) - 1;
+			skip = 0;
+		}
+		while acc_bits >= 8 {
+			acc_bits -= 8;
+			bytes.push((acc >> acc_bits) as u8);
+			acc &= (1 << acc_bits) - 1;
+		}
+	}
+	Ok(bytes)
+}
+
+fn rs1024_polymod(customization: &[u8], data: &[u16]) -> u32 {
+	let values = customization
+		.iter()
+		.map(|c| *c as u32)
+		.chain(data.iter().map(|w| *w as u32));
+	let mut chk: u32 = 1;
+	for v in values {
+		let b = chk >> 20;
+		chk = ((chk & 0xFFFFF) << 10) ^ v;
+		for (i, gen) in RS1024_GEN.iter().enumerate() {
+			if (b >> i) & 1 == 1 {
+				chk ^= gen;
+			}
+		}
+	}
+	chk
+}
+
+fn rs1024_create(customization: &[u8], data: &[u16]) -> Vec<u16> {
+	let mut values = data.to_vec();
+	values.extend_from_slice(&[0; CHECKSUM_WORDS]);
+	let polymod = rs1024_polymod(customization, &values) ^ 1;
+	(0..CHECKSUM_WORDS)
+		.rev()
+		.map(|i| ((polymod >> (i * RADIX_BITS)) & 0x3FF) as u16)
+		.collect()
+}
+
+fn rs1024_verify(customization: &[u8], data: &[u16]) -> bool {
+	rs1024_polymod(customization, data) == 1
+}
+
+/// Lagrange interpolation of the shares at the point x in GF(256)
+fn interpolate(shares: &[(u8, Vec<u8>)], x: u8) -> Result<Vec<u8>, Error> {
+	if let Some((_, v)) = shares.iter().find(|(i, _)| *i == x) {
+		return Ok(v.clone());
+	}
+	let len = shares
+		.first()
+		.ok_or(ErrorKind::Shamir("no shares to interpolate".to_string()))?
+		.1
+		.len();
+	for (n, (i, v)) in shares.iter().enumerate() {
+		if v.len() != len || shares[..n].iter().any(|(j, _)| j == i) {
+			return Err(ErrorKind::Shamir("invalid set of shares".to_string()).into());
+		}
+	}
+
+	let log_prod: i32 = shares
+		.iter()
+		.map(|(i, _)| GF.log[(i ^ x) as usize] as i32)
+		.sum();
+	let mut result = vec![0u8; len];
+	for (i, v) in shares {
+		let log_basis = (log_prod
+			- GF.log[(i ^ x) as usize] as i32
+			- shares
+				.iter()
+				.filter(|(j, _)| j != i)
+				.map(|(j, _)| GF.log[(i ^ j) as usize] as i32)
+				.sum::<i32>())
+		.rem_euclid(255);
+		for (r, b) in result.iter_mut().zip(v.iter()) {
+			if *b != 0 {
+				*r ^= GF.exp[((GF.log[*b as usize] as i32 + log_basis) % 255) as usize];
+			}
+		}
+	}
+	Ok(result)
+}
+
+fn digest(random: &[u8], secret: &[u8]) -> Vec<u8> {
+	let key = hmac::Key::new(hmac::HMAC_SHA256, random);
+	hmac::sign(&key, secret).as_ref()[..DIGEST_LEN].to_vec()
+}
+
+fn split_secret(
+	threshold: u8,
+	share_count: u8,
+	secret: &[u8],
+) -> Result<Vec<(u8, Vec<u8>)>, Error> {
+	if threshold == 1 {
+		return Ok((0..share_count).map(|i| (i, secret.to_vec())).collect());
+	}
+
+	let mut rng = thread_rng();
+	let random_len = secret.len() - DIGEST_LEN;
+	let mut shares: Vec<(u8, Vec<u8>)> = (0..threshold - 2)
+		.map(|i| {
+			let mut value = vec![0u8; secret.len()];
+			rng.fill(value.as_mut_slice());
+			(i, value)
+		})
+		.collect();
+
+	let mut random_part = vec![0u8; random_len];
+	rng.fill(random_part.as_mut_slice());
+	let mut digest_share = digest(&random_part, secret);
+	digest_share.extend_from_slice(&random_part);
+
+	let mut base = shares.clone();
+	base.push((DIGEST_INDEX, digest_share));
+	base.push((SECRET_INDEX, secret.to_vec()));
+	for i in threshold - 2..share_count {
+		shares.push((i, interpolate(&base, i)?));
+	}
+	Ok(shares)
+}
+
+fn recover_secret(threshold: u8, shares: &[(u8, Vec<u8>)]) -> Result<Vec<u8>, Error> {
+	if threshold == 1 {
+		return Ok(shares
+			.first()
+			.ok_or(ErrorKind::Shamir("no shares".to_string()))?
+			.1
+			.clone());
+	}
+	let secret = interpolate(shares, SECRET_INDEX)?;
+	let digest_share = interpolate(shares, DIGEST_INDEX)?;
+	if digest_share[..DIGEST_LEN] != digest(&digest_share[DIGEST_LEN..], &secret)[..] {
+		return Err(ErrorKind::Shamir("invalid digest of the shared secret".to_string()).into());
+	}
+	Ok(secret)
+}
+
+/// Encryption of the master secret with the passphrase, 4 rounds of Feistel network
+fn feistel(
+	data: &[u8],
+	passphrase: &[u8],
+	iteration_exponent: u8,
+	id: u16,
+	extendable: bool,
+	encrypt: bool,
+) -> Result<Vec<u8>, Error> {
+	let iterations =
+		NonZeroU32::new((BASE_ITERATION_COUNT << iteration_exponent) / ROUND_COUNT as u32)
+			.ok_or(ErrorKind::Shamir("invalid iteration exponent".to_string()))?;
+	let mut salt_prefix = vec![];
+	if !extendable {
+		salt_prefix.extend_from_slice(CUSTOMIZATION_STRING);
+		salt_prefix.extend_from_slice(&id.to_be_bytes());
+	}
+
+	let half = data.len() / 2;
+	let mut left = data[..half].to_vec();
+	let mut right = data[half..].to_vec();
+	let rounds: Vec<u8> = if encrypt {
+		(0..ROUND_COUNT).collect()
+	} else {
+		(0..ROUND_COUNT).rev().collect()
+	};
+	for i in rounds {
+		let mut password = vec![i];
+		password.extend_from_slice(passphrase);
+		let mut salt = salt_prefix.clone();
+		salt.extend_from_slice(&right);
+		let mut f = vec![0u8; right.len()];
+		pbkdf2::derive(
+			pbkdf2::PBKDF2_HMAC_SHA256,
+			iterations,
+			&salt,
+			&password,
+			&mut f,
+		);
+		for (l, r) in left.iter_mut().zip(f.iter()) {
+			*l ^= r;
+		}
+		std::mem::swap(&mut left, &mut right);
+	}
+	right.extend_from_slice(&left);
+	Ok(right)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn slip39_vectors() {
+		let secret = combine_shares(
+			&["duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard"],
+			b"TREZOR",
+		)
+		.unwrap();
+		assert_eq!(util::to_hex(&secret), "bb54aac4b89dc868ba37d9cc21b2cece");
+
+		let shares = [
+			"shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+			"shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+		];
+		assert!(!is_complete(&[ZeroingString::from(shares[0])]).unwrap());
+		let secret = combine_shares(&shares, b"TREZOR").unwrap();
+		assert_eq!(util::to_hex(&secret), "b43ceb7e57a0ea8766221624d01b0864");
+
+		// invalid checksum
+		assert!(combine_shares(
+			&["duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney"],
+			b"TREZOR",
+		)
+		.is_err());
+	}
+
+	#[test]
+	fn slip39_split_combine() {
+		let seed: Vec<u8> = (0..32u8).collect();
+		let shares = split_seed(&seed, 3, 5).unwrap();
+		assert_eq!(shares.len(), 5);
+		for s in &shares {
+			assert_eq!(s.split_whitespace().count(), 33);
+			assert!(is_share(s));
+		}
+
+		assert_eq!(combine_shares(&shares[..3], b"").unwrap(), seed);
+		assert_eq!(
+			combine_shares(&[&shares[4], &shares[1], &shares[3]], b"").unwrap(),
+			seed
+		);
+		assert!(combine_shares(&shares[..2], b"").is_err());
+		assert!(combine_shares(&[&shares[0], &shares[0], &shares[1]], b"").is_err());
+
+		let other = split_seed(&seed, 3, 5).unwrap();
+		assert!(combine_shares(&[&shares[0], &shares[1], &other[2]], b"").is_err());
+
+		assert!(split_seed(&seed, 1, 2).is_err());
+		assert!(split_seed(&seed, 4, 3).is_err());
+		assert!(split_seed(&seed[..15], 2, 3).is_err());
+	}
+
+	#[test]
+	fn slip39_mnemonic() {
+		let phrase = ZeroingString::from(mnemonic::from_entropy(&[7u8; 32]).unwrap());
+		let shares = split_mnemonic(&phrase, 2, 3).unwrap();
+		let recovered = recover_mnemonic(&shares[1..]).unwrap();
+		assert_eq!(&*recovered, &*phrase);
+		assert!(recover_mnemonic(&shares[..1]).is_err());
+	}
+}
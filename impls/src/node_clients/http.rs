@@ -27,6 +27,9 @@ use tokio::runtime::Builder;
 
 use crate::client_utils::Client;
 use crate::libwallet;
+use crate::libwallet::node_cache::{
+	self, CACHE_BLOCK, CACHE_CHAIN_TIP, CACHE_HEADER, CACHE_KERNEL, CACHE_OUTPUTS,
+};
 use crate::util::secp::pedersen;
 use crate::util::{self, to_hex};
 
@@ -38,14 +41,14 @@ use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
 const ENDPOINT: &str = "/v2/foreign";
-const CACHE_VALID_TIME_MS: u128 = 5000; // 2 seconds for cache should be enough for our purpose
 
 const NODE_CALL_RETRY: i32 = 2; // it is total 3 attempts  to get the data
 const NODE_VERSION_CALL_RETRY: i32 = 7; // it is total 3 attempts  to get the data
 
-// cashed values are stored by the key K
+// cashed values are stored by the key K. TTL is set by config, the lookups are counted by the cache name
 #[derive(Clone)]
 struct CachedValue<K, T> {
+	name: &'static str,
 	data: Arc<RwLock<HashMap<K, (T, Instant)>>>,
 }
 
@@ -54,30 +57,33 @@ where
 	K: std::cmp::Eq + std::hash::Hash,
 	T: Clone,
 {
-	fn new() -> Self {
+	fn new(name: &'static str) -> Self {
 		CachedValue {
+			name,
 			data: Arc::new(RwLock::new(HashMap::new())),
 		}
 	}
 
 	// Return none is cached value not set of epired
 	fn get_value(&self, key: &K) -> Option<T> {
-		match self.data.write().unwrap().get(key) {
-			Some((data, time)) => {
-				if time.elapsed().as_millis() > CACHE_VALID_TIME_MS {
-					return None;
-				}
-				Some((*data).clone())
-			}
-			None => None,
-		}
+		let ttl_ms = node_cache::get_node_cache_ttl().ttl_ms(self.name) as u128;
+		let value = match self.data.read().unwrap().get(key) {
+			Some((data, time)) if time.elapsed().as_millis() <= ttl_ms => Some((*data).clone()),
+			_ => None,
+		};
+		node_cache::record_lookup(self.name, value.is_some());
+		value
 	}
 
 	fn set_value(&self, key: K, value: T) {
-		self.data
-			.write()
-			.unwrap()
-			.insert(key, (value, Instant::now()));
+		let ttl_ms = node_cache::get_node_cache_ttl().ttl_ms(self.name) as u128;
+		if ttl_ms == 0 {
+			return;
+		}
+		let mut data = self.data.write().unwrap();
+		// Expired values are not needed any more
+		data.retain(|_, (_, time)| time.elapsed().as_millis() <= ttl_ms);
+		data.insert(key, (value, Instant::now()));
 	}
 
 	fn clean(&self) {
@@ -97,6 +103,10 @@ pub struct HTTPNodeClient {
 	chain_tip: CachedValue<u8, (u64, String, u64)>,
 	header_info: CachedValue<u64, HeaderInfo>,
 	block_info: CachedValue<u64, api::BlockPrintable>,
+	kernel_info:
+		CachedValue<(pedersen::Commitment, Option<u64>, Option<u64>), Option<(TxKernel, u64, u64)>>,
+	outputs_info:
+		CachedValue<Vec<pedersen::Commitment>, HashMap<pedersen::Commitment, (String, u64, u64)>>,
 }
 
 impl HTTPNodeClient {
@@ -114,9 +124,11 @@ impl HTTPNodeClient {
 			current_node_index: Arc::new(AtomicU8::new(0)),
 			node_version_info: None,
 			client,
-			chain_tip: CachedValue::new(),
-			header_info: CachedValue::new(),
-			block_info: CachedValue::new(),
+			chain_tip: CachedValue::new(CACHE_CHAIN_TIP),
+			header_info: CachedValue::new(CACHE_HEADER),
+			block_info: CachedValue::new(CACHE_BLOCK),
+			kernel_info: CachedValue::new(CACHE_KERNEL),
+			outputs_info: CachedValue::new(CACHE_OUTPUTS),
 		})
 	}
 
@@ -428,6 +440,8 @@ impl NodeClient for HTTPNodeClient {
 		self.chain_tip.clean();
 		self.header_info.clean();
 		self.block_info.clean();
+		self.kernel_info.clean();
+		self.outputs_info.clean();
 	}
 
 	fn get_version_info(&mut self) -> Option<NodeVersionInfo> {
@@ -529,7 +543,13 @@ impl NodeClient for HTTPNodeClient {
 		min_height: Option<u64>,
 		max_height: Option<u64>,
 	) -> Result<Option<(TxKernel, u64, u64)>, libwallet::Error> {
-		self.get_kernel_impl(excess, min_height, max_height, NODE_CALL_RETRY)
+		let key = (*excess, min_height, max_height);
+		if let Some(k) = self.kernel_info.get_value(&key) {
+			return Ok(k);
+		}
+		let res = self.get_kernel_impl(excess, min_height, max_height, NODE_CALL_RETRY)?;
+		self.kernel_info.set_value(key, res.clone());
+		Ok(res)
 	}
 
 	/// Retrieve outputs from node
@@ -538,7 +558,13 @@ impl NodeClient for HTTPNodeClient {
 		&self,
 		wallet_outputs: &Vec<pedersen::Commitment>,
 	) -> Result<HashMap<pedersen::Commitment, (String, u64, u64)>, libwallet::Error> {
-		self.get_outputs_from_node_impl(wallet_outputs, NODE_CALL_RETRY)
+		if let Some(outputs) = self.outputs_info.get_value(wallet_outputs) {
+			return Ok(outputs);
+		}
+		let res = self.get_outputs_from_node_impl(wallet_outputs, NODE_CALL_RETRY)?;
+		self.outputs_info
+			.set_value(wallet_outputs.clone(), res.clone());
+		Ok(res)
 	}
 
 	// Expected respond from non full node, that can return reliable only non spent outputs.
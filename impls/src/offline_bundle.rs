@@ -0,0 +1,236 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Offline signing (cold wallet) bundle. The bundle travels between the online wallet, that has
+//! the node and network access, and the offline wallet, that holds the keys. The sender secrets
+//! of the transaction never leave the offline wallet, that is why the offline wallet builds the
+//! slate and later finalizes it. The online wallet provides the chain height, delivers the slate
+//! to the recipient and posts the finalized transaction.
+
+use crate::error::{Error, ErrorKind};
+use crate::libwallet::{InitTxArgs, Slate, SlateVersion, VersionedSlate};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Current version of the bundle format
+pub const OFFLINE_BUNDLE_VERSION: u16 = 1;
+
+/// Stage of the offline signing bundle
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum OfflineBundleStage {
+	/// Created by the online wallet, the offline wallet needs to build and sign the slate
+	Unsigned,
+	/// Signed by the offline wallet, the online wallet needs to send the slate to the recipient
+	Signed,
+	/// The recipient response is received, the offline wallet needs to finalize the slate
+	Responded,
+	/// Finalized by the offline wallet, the online wallet needs to post the transaction
+	Finalized,
+}
+
+impl fmt::Display for OfflineBundleStage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			OfflineBundleStage::Unsigned => "unsigned",
+			OfflineBundleStage::Signed => "signed",
+			OfflineBundleStage::Responded => "responded",
+			OfflineBundleStage::Finalized => "finalized",
+		};
+		write!(f, "{}", s)
+	}
+}
+
+/// Send transaction that is built by the offline wallet
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OfflineTxBundle {
+	/// Version of the bundle format
+	pub version: u16,
+	/// Current stage
+	pub stage: OfflineBundleStage,
+	/// Time when the online wallet created the bundle
+	pub created: DateTime<Utc>,
+	/// Chain height reported by the node. The online wallet updates it when the recipient
+	/// response is received, the offline wallet uses it instead of the node.
+	pub height: u64,
+	/// Hash of the block header at the height
+	pub header_hash: String,
+	/// Send arguments. `send_args` define how the slate is delivered to the recipient.
+	pub args: InitTxArgs,
+	/// Plain json of the slate for the current stage
+	pub slate: Option<String>,
+}
+
+impl OfflineTxBundle {
+	/// New bundle from the online wallet
+	pub fn new(args: InitTxArgs, height: u64, header_hash: String) -> Self {
+		OfflineTxBundle {
+			version: OFFLINE_BUNDLE_VERSION,
+			stage: OfflineBundleStage::Unsigned,
+			created: Utc::now(),
+			height,
+			header_hash,
+			args,
+			slate: None,
+		}
+	}
+
+	/// Parse the bundle from json
+	pub fn from_json(data: &str) -> Result<Self, Error> {
+		let bundle: OfflineTxBundle = serde_json::from_str(data)
+			.map_err(|e| ErrorKind::OfflineBundle(format!("Unable to parse the bundle, {}", e)))?;
+		if bundle.version > OFFLINE_BUNDLE_VERSION {
+			return Err(ErrorKind::OfflineBundle(format!(
+				"Bundle version {} is not supported, please upgrade the wallet",
+				bundle.version
+			))
+			.into());
+		}
+		if bundle.stage != OfflineBundleStage::Unsigned && bundle.slate.is_none() {
+			return Err(ErrorKind::OfflineBundle(format!(
+				"Bundle at stage '{}' doesn't have a slate",
+				bundle.stage
+			))
+			.into());
+		}
+		Ok(bundle)
+	}
+
+	/// Bundle json
+	pub fn to_json(&self) -> Result<String, Error> {
+		serde_json::to_string_pretty(self).map_err(|e| {
+			ErrorKind::OfflineBundle(format!("Unable to serialize the bundle, {}", e)).into()
+		})
+	}
+
+	/// Read the bundle from the file
+	pub fn read_from(path: &Path) -> Result<Self, Error> {
+		let mut data = String::new();
+		File::open(path)
+			.and_then(|mut f| f.read_to_string(&mut data))
+			.map_err(|e| {
+				ErrorKind::IO(format!(
+					"Unable to read the bundle from {}, {}",
+					path.display(),
+					e
+				))
+			})?;
+		Self::from_json(&data)
+	}
+
+	/// Write the bundle into the file
+	pub fn write_to(&self, path: &Path) -> Result<(), Error> {
+		let data = self.to_json()?;
+		File::create(path)
+			.and_then(|mut f| {
+				f.write_all(data.as_bytes())?;
+				f.sync_all()
+			})
+			.map_err(|e| {
+				ErrorKind::IO(format!(
+					"Unable to write the bundle to {}, {}",
+					path.display(),
+					e
+				))
+				.into()
+			})
+	}
+
+	/// Check that the bundle is at the expected stage
+	pub fn check_stage(&self, expected: &[OfflineBundleStage]) -> Result<(), Error> {
+		if expected.contains(&self.stage) {
+			Ok(())
+		} else {
+			Err(ErrorKind::OfflineBundle(format!(
+				"bundle for {} nanoMWC is at stage '{}', expected {}",
+				self.args.amount,
+				self.stage,
+				expected
+					.iter()
+					.map(|s| format!("'{}'", s))
+					.collect::<Vec<String>>()
+					.join(" or ")
+			))
+			.into())
+		}
+	}
+
+	/// Slate of the current stage
+	pub fn get_slate(&self) -> Result<Slate, Error> {
+		let slate = self.slate.as_ref().ok_or(ErrorKind::OfflineBundle(format!(
+			"Bundle at stage '{}' doesn't have a slate",
+			self.stage
+		)))?;
+		Ok(Slate::deserialize_upgrade_plain(slate)?)
+	}
+
+	/// Store the slate and move the bundle to the next stage
+	pub fn set_slate(&mut self, slate: &Slate, stage: OfflineBundleStage) -> Result<(), Error> {
+		let slate = VersionedSlate::into_version_plain(slate.clone(), SlateVersion::V3)
+			.map_err(|e| ErrorKind::OfflineBundle(format!("Unable to convert the slate, {}", e)))?;
+		self.slate = Some(slate.as_string()?);
+		self.stage = stage;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn bundle() -> OfflineTxBundle {
+		let args = InitTxArgs {
+			amount: 2_000_000_000,
+			minimum_confirmations: 10,
+			..Default::default()
+		};
+		OfflineTxBundle::new(
+			args,
+			100,
+			"0a6e1cb59f68a7b2f2eb2e8e1a11aa36d8dbff4c4b57f9cbd7a6a0e3a9a2c2fc".to_string(),
+		)
+	}
+
+	#[test]
+	fn test_bundle_json() {
+		let b = bundle();
+		let restored = OfflineTxBundle::from_json(&b.to_json().unwrap()).unwrap();
+		assert_eq!(restored.stage, OfflineBundleStage::Unsigned);
+		assert_eq!(restored.height, 100);
+		assert_eq!(restored.args.amount, 2_000_000_000);
+		assert!(restored.slate.is_none());
+
+		// newer format is rejected
+		let mut b = bundle();
+		b.version = OFFLINE_BUNDLE_VERSION + 1;
+		assert!(OfflineTxBundle::from_json(&b.to_json().unwrap()).is_err());
+
+		// signed bundle without slate is broken
+		let mut b = bundle();
+		b.stage = OfflineBundleStage::Signed;
+		assert!(OfflineTxBundle::from_json(&b.to_json().unwrap()).is_err());
+	}
+
+	#[test]
+	fn test_bundle_stage() {
+		let b = bundle();
+		assert!(b.check_stage(&[OfflineBundleStage::Unsigned]).is_ok());
+		assert!(b
+			.check_stage(&[OfflineBundleStage::Signed, OfflineBundleStage::Finalized])
+			.is_err());
+		assert!(b.get_slate().is_err());
+	}
+}
@@ -0,0 +1,118 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lookup of the payment id records. DNS TXT records are requested from a DNS-over-HTTPS
+//! resolver with the JSON API, for example 'https://dns.google/resolve'. The requests go
+//! through the configured proxy, so the DNS queries don't leak to the local network.
+
+use crate::client_utils::Client;
+use crate::libwallet::payment_id::{PaymentId, PaymentIdRecord, PaymentIdResolver};
+use crate::libwallet::{Error, ErrorKind};
+use serde_json::Value;
+
+/// Default DNS-over-HTTPS resolver
+pub const DEFAULT_DOH_URL: &str = "https://dns.google/resolve";
+
+/// DNS TXT record type
+const DNS_TYPE_TXT: u64 = 16;
+
+/// Payment id records from DNS-over-HTTPS and the domain web servers
+pub struct HttpPaymentIdResolver {
+	doh_url: String,
+}
+
+impl HttpPaymentIdResolver {
+	/// Resolver with the DNS-over-HTTPS JSON API url
+	pub fn new(doh_url: &str) -> Self {
+		HttpPaymentIdResolver {
+			doh_url: doh_url.trim_end_matches('/').to_string(),
+		}
+	}
+
+	fn client() -> Result<Client, Error> {
+		Client::new(false, None).map_err(|e| {
+			ErrorKind::PaymentId(format!("Unable to create HTTP client, {}", e)).into()
+		})
+	}
+}
+
+impl PaymentIdResolver for HttpPaymentIdResolver {
+	fn txt_records(&self, name: &str) -> Result<Vec<String>, Error> {
+		let url = format!("{}?name={}&type=TXT", self.doh_url, name);
+		let res: Value = Self::client()?
+			.get(&url, None)
+			.map_err(|e| ErrorKind::PaymentId(format!("DNS request {} failed, {}", url, e)))?;
+		Ok(parse_txt_answer(&res))
+	}
+
+	fn well_known_record(&self, id: &PaymentId) -> Result<Option<PaymentIdRecord>, Error> {
+		let url = id.well_known_url();
+		let record: PaymentIdRecord = Self::client()?
+			.get(&url, None)
+			.map_err(|e| ErrorKind::PaymentId(format!("Request {} failed, {}", url, e)))?;
+		Ok(Some(record))
+	}
+}
+
+/// Texts of the TXT records from the DNS JSON response. The long records are split by the DNS
+/// into the quoted strings of 255 bytes, they are joined back.
+pub fn parse_txt_answer(res: &Value) -> Vec<String> {
+	if res["Status"].as_u64() != Some(0) {
+		return vec![];
+	}
+	res["Answer"]
+		.as_array()
+		.map(|answers| {
+			answers
+				.iter()
+				.filter(|a| a["type"].as_u64() == Some(DNS_TYPE_TXT))
+				.filter_map(|a| a["data"].as_str())
+				.map(|data| {
+					if data.starts_with('"') {
+						// Quoted strings are at the odd positions
+						data.split('"').skip(1).step_by(2).collect()
+					} else {
+						data.to_string()
+					}
+				})
+				.collect()
+		})
+		.unwrap_or(vec![])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn doh_txt_answer() {
+		let res = json!({
+			"Status": 0,
+			"Answer": [
+				{ "name": "alice.user._mwc-payment.example.com.", "type": 5, "data": "other.example.com." },
+				{ "name": "alice.user._mwc-payment.example.com.", "type": 16, "data": "\"mwc1 address=http://127.0.0.1:3415 \" \"proof=abc sig=01\"" },
+				{ "name": "alice.user._mwc-payment.example.com.", "type": 16, "data": "v=spf1 -all" }
+			]
+		});
+		assert_eq!(
+			parse_txt_answer(&res),
+			vec![
+				"mwc1 address=http://127.0.0.1:3415 proof=abc sig=01".to_string(),
+				"v=spf1 -all".to_string()
+			]
+		);
+		assert!(parse_txt_answer(&json!({ "Status": 3 })).is_empty());
+	}
+}
@@ -0,0 +1,105 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Exchange rates from a CoinGecko compatible API. The coin URL, for example
+//! 'https://api.coingecko.com/api/v3/coins/mimblewimblecoin', returns the current price and
+//! '<url>/history?date=dd-mm-yyyy' returns the daily price. Both have the prices in
+//! 'market_data.current_price.<currency>'.
+
+use crate::client_utils::Client;
+use crate::libwallet::price::PriceSource;
+use crate::libwallet::{Error, ErrorKind};
+use chrono::NaiveDate;
+use serde_json::Value;
+
+/// Exchange rates from the coin URL of a CoinGecko compatible API
+pub struct HttpPriceSource {
+	url: String,
+}
+
+impl HttpPriceSource {
+	/// Source for the coin URL
+	pub fn new(url: &str) -> Self {
+		HttpPriceSource {
+			url: url.trim_end_matches('/').to_string(),
+		}
+	}
+
+	fn request(&self, url: &str, currency: &str) -> Result<f64, Error> {
+		let client = Client::new(false, None)
+			.map_err(|e| ErrorKind::PriceFeed(format!("Unable to create HTTP client, {}", e)))?;
+		let res: Value = client
+			.get(url, None)
+			.map_err(|e| ErrorKind::PriceFeed(format!("Request {} failed, {}", url, e)))?;
+		parse_price(&res, currency)
+	}
+}
+
+impl PriceSource for HttpPriceSource {
+	fn location(&self) -> String {
+		self.url.clone()
+	}
+
+	fn current_rate(&self, currency: &str) -> Result<f64, Error> {
+		let url = format!(
+			"{}?localization=false&tickers=false&community_data=false&developer_data=false",
+			self.url
+		);
+		self.request(&url, currency)
+	}
+
+	fn historical_rate(&self, currency: &str, date: &NaiveDate) -> Result<f64, Error> {
+		let url = format!(
+			"{}/history?date={}&localization=false",
+			self.url,
+			date.format("%d-%m-%Y")
+		);
+		self.request(&url, currency)
+	}
+}
+
+/// Price in the currency from the coin data response
+pub fn parse_price(res: &Value, currency: &str) -> Result<f64, Error> {
+	res["market_data"]["current_price"][currency]
+		.as_f64()
+		.ok_or(
+			ErrorKind::PriceFeed(format!(
+				"Response doesn't have the price in '{}' currency",
+				currency
+			))
+			.into(),
+		)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn coingecko_price() {
+		let res = json!({
+			"id": "mimblewimblecoin",
+			"market_data": { "current_price": { "usd": 0.412, "eur": 0.38 } }
+		});
+		assert_eq!(parse_price(&res, "usd").unwrap(), 0.412);
+		assert!(parse_price(&res, "gbp").is_err());
+		assert!(parse_price(&json!({ "error": "coin not found" }), "usd").is_err());
+		assert_eq!(
+			HttpPriceSource::new("https://api.coingecko.com/api/v3/coins/mimblewimblecoin/")
+				.location(),
+			"https://api.coingecko.com/api/v3/coins/mimblewimblecoin"
+		);
+	}
+}
@@ -688,6 +688,8 @@ impl NodeClient for LocalWalletClient {
 			method: "get_outputs_by_pmmr_index".to_owned(),
 			body: query_str,
 		};
+		// Scan calls it from several threads, the response must go to the thread that sent the request
+		let r = self.rx.lock();
 		{
 			let p = self.proxy_tx.lock();
 			p.send(m).map_err(|e| {
@@ -698,7 +700,6 @@ impl NodeClient for LocalWalletClient {
 			})?;
 		}
 
-		let r = self.rx.lock();
 		let m = r.recv().unwrap();
 		let o: api::OutputListing = serde_json::from_str(&m.body).unwrap();
 
@@ -230,6 +230,8 @@ pub fn output_torrc(
 			}
 			_ => (),
 		}
+	}
+	if !service_dirs.is_empty() {
 		if let Some(log_file) = tor_log_file {
 			props.add_item("Log", &format!("info file {}", log_file));
 			props.add_item("Log", &"notice stdout");
@@ -282,6 +284,31 @@ pub fn output_tor_listener_config(
 	Ok(())
 }
 
+/// List onion addresses of the hidden services that have keys stored in the tor config directory
+pub fn list_onion_service_addresses(tor_config_directory: &str) -> Result<Vec<String>, Error> {
+	let hs_dir = format!(
+		"{}{}{}",
+		tor_config_directory, MAIN_SEPARATOR, HIDDEN_SERVICES_DIR
+	);
+	if !Path::new(&hs_dir).exists() {
+		return Ok(vec![]);
+	}
+
+	let mut addresses = vec![];
+	for entry in fs::read_dir(&hs_dir)
+		.map_err(|e| ErrorKind::IO(format!("Unable to read dir {}, {}", hs_dir, e)))?
+	{
+		let entry =
+			entry.map_err(|e| ErrorKind::IO(format!("Unable to read dir {}, {}", hs_dir, e)))?;
+		let name = entry.file_name().to_string_lossy().to_string();
+		if entry.path().is_dir() && is_tor_address(&name).is_ok() {
+			addresses.push(name);
+		}
+	}
+	addresses.sort();
+	Ok(addresses)
+}
+
 /// output tor config for a send
 pub fn output_tor_sender_config(
 	tor_config_dir: &str,
@@ -367,6 +394,32 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_list_onion_service_addresses() -> Result<(), Error> {
+		let test_dir = "./target/test_output/tor_list";
+		setup(test_dir);
+		assert!(list_onion_service_addresses(test_dir)?.is_empty());
+		let mut test_rng = StepRng::new(1_234_567_890_u64, 1);
+		let sec_key1 = secp::key::SecretKey::new(&mut test_rng);
+		let sec_key2 = secp::key::SecretKey::new(&mut test_rng);
+		output_tor_listener_config(
+			test_dir,
+			"0",
+			"127.0.0.1:3415",
+			&None,
+			&[sec_key1.clone(), sec_key2.clone()],
+			&None,
+		)?;
+		let mut expected = vec![
+			OnionV3Address::from_private(&sec_key1.0)?.to_string(),
+			OnionV3Address::from_private(&sec_key2.0)?.to_string(),
+		];
+		expected.sort();
+		assert_eq!(list_onion_service_addresses(test_dir)?, expected);
+		clean_output_dir(test_dir);
+		Ok(())
+	}
+
 	#[test]
 	fn test_is_tor_address() -> Result<(), Error> {
 		assert!(is_tor_address("2a6at2obto3uvkpkitqp4wxcg6u36qf534eucbskqciturczzc5suyid").is_ok());
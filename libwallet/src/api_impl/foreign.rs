@@ -17,14 +17,20 @@ use crate::api_impl::owner::check_ttl;
 use crate::api_impl::owner_swap;
 use crate::grin_core::core::amount_to_hr_string;
 use crate::grin_keychain::Keychain;
-use crate::grin_util::secp::key::SecretKey;
+use crate::grin_util::secp::key::{PublicKey, SecretKey};
 use crate::grin_util::Mutex;
 use crate::internal::selection;
 use crate::internal::{tx, updater};
+use crate::log_privacy;
+use crate::notifications::{self, WalletEvent};
 use crate::proof::crypto::Hex;
 use crate::proof::proofaddress;
 use crate::proof::proofaddress::ProofAddressType;
 use crate::proof::proofaddress::ProvableAddress;
+use crate::proof_verifier;
+use crate::secure_api::ListenerKey;
+use crate::slate_limits;
+use crate::slate_versions::grin_v4::SlateGrinV4;
 use crate::slate_versions::SlateVersion;
 use crate::Context;
 use crate::{
@@ -37,7 +43,8 @@ use std::sync::Arc;
 use std::sync::RwLock;
 use strum::IntoEnumIterator;
 
-const FOREIGN_API_VERSION: u16 = 2;
+// Version 3 supports the encrypted requests (init_secure_api, encrypted_request_v3)
+const FOREIGN_API_VERSION: u16 = 3;
 const USER_MESSAGE_MAX_LEN: usize = 256;
 
 lazy_static! {
@@ -71,6 +78,22 @@ where
 	Ok(provable_address.public_key)
 }
 
+/// Sign the ECDH key of the encrypted requests with the key of the wallet Tor address
+pub fn sign_listener_key<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	ecdh_pubkey: &PublicKey,
+) -> Result<ListenerKey, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let keychain = w.keychain(keychain_mask)?;
+	let secret = proofaddress::payment_proof_address_dalek_secret(&keychain, None)?;
+	ListenerKey::sign(ecdh_pubkey, &secret)
+}
+
 ///
 pub fn set_receive_account(account: String) {
 	RECV_ACCOUNT.write().unwrap().replace(account.to_string());
@@ -124,6 +147,7 @@ where
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
+	slate_limits::check_slate_limits(slate)?;
 	let display_from = address.clone().unwrap_or("http listener".to_string());
 	let slate_message = &slate.participant_data[0].message;
 	let address_for_logging = address.clone().unwrap_or("http".to_string());
@@ -154,11 +178,49 @@ where
 		);
 	}
 
-	debug!("foreign just received_tx just got slate = {:?}", slate);
+	debug!(
+		"foreign just received_tx just got slate = {}",
+		log_privacy::slate(slate)
+	);
 	let mut ret_slate = slate.clone();
 	check_ttl(w, &ret_slate, refresh_from_node)?;
 
+	// Outputs of the sender are verified together, the batch is cheap even for the large slates
+	let sender_outputs: Vec<_> = slate
+		.tx
+		.outputs()
+		.iter()
+		.map(|o| (o.commitment(), o.proof))
+		.collect();
+	proof_verifier::verify_proofs(w.keychain(keychain_mask)?.secp(), &sender_outputs)?;
+
+	// Address index that receives the payment with the proof
+	let proof_address_index = match &ret_slate.payment_proof {
+		Some(p) => {
+			if p.sender_address
+				.public_key
+				.eq(&p.receiver_address.public_key)
+			{
+				Some(proofaddress::get_address_index())
+			} else {
+				// The sender might pay to the address that was handed out for another index
+				let keychain = w.keychain(keychain_mask)?;
+				let max_index = w.max_issued_address_index()?.unwrap_or(0);
+				proofaddress::find_address_index(&keychain, &p.receiver_address, max_index)?
+			}
+		}
+		None => None,
+	};
+	let address_mapping = match proof_address_index {
+		Some(index) => w.get_address_mapping(index)?,
+		None => None,
+	};
+
+	// Explicit account has priority over the address mapping and the receive account
 	let mut dest_acct_name = dest_acct_name.map(|s| s.to_string());
+	if dest_acct_name.is_none() {
+		dest_acct_name = address_mapping.as_ref().and_then(|m| m.account.clone());
+	}
 	if dest_acct_name.is_none() {
 		dest_acct_name = get_receive_account();
 	}
@@ -261,12 +323,54 @@ where
 			&excess,
 			p.sender_address.clone(),
 			p.receiver_address.clone(),
-			proofaddress::payment_proof_address_secret(&keychain, None)?,
+			proofaddress::payment_proof_address_secret(&keychain, proof_address_index)?,
 		)?;
 
 		p.receiver_signature = Some(sig);
 	}
 
+	// Remember which address received the payment, so it can be attributed later.
+	// The label of the address mapping is applied too.
+	if let Some(index) = proof_address_index {
+		let txs = updater::retrieve_txs(
+			&mut *w,
+			keychain_mask,
+			None,
+			Some(ret_slate.id),
+			Some(&parent_key_id),
+			use_test_rng,
+			None,
+			None,
+		)?;
+		let mut batch = w.batch(keychain_mask)?;
+		for mut t in txs
+			.into_iter()
+			.filter(|t| t.tx_type == TxLogEntryType::TxReceived)
+		{
+			t.address_index = Some(index);
+			if t.label.is_none() {
+				t.label = address_mapping.as_ref().and_then(|m| m.label.clone());
+			}
+			batch.save_tx_log_entry(t, &parent_key_id)?;
+		}
+		batch.commit()?;
+	}
+
+	// The transaction is already received, losing the response copy must not fail it
+	if let Err(e) = w.store_response_slate(&ret_slate) {
+		warn!(
+			"Unable to store response slate for transaction {}, {}",
+			log_privacy::slate_id(ret_slate.id),
+			e
+		);
+	}
+
+	notifications::notify(WalletEvent::PaymentReceived {
+		slate_id: ret_slate.id.to_string(),
+		amount: ret_slate.amount,
+		from: address,
+	});
+
 	Ok((ret_slate, context))
 }
 
@@ -283,6 +387,7 @@ where
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
+	slate_limits::check_slate_limits(slate)?;
 	let mut sl = slate.clone();
 	check_ttl(w, &sl, refresh_from_node)?;
 	// Participant id 0 for mwc713 compatibility
@@ -316,6 +421,12 @@ where
 		batch.delete_private_context(sl.id.as_bytes(), 0)?;
 		batch.commit()?;
 	}
+
+	notifications::notify(WalletEvent::PaymentReceived {
+		slate_id: sl.id.to_string(),
+		amount: sl.amount,
+		from: None,
+	});
 	Ok(sl)
 }
 
@@ -412,6 +523,13 @@ where
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
+	if version == Some(SlateVersion::GV4) {
+		// Grin V4 slate is plain json, there is no encryption
+		return Ok(VersionedSlate::GV4(SlateGrinV4::from_slate(
+			slate, &content,
+		)?));
+	}
+
 	let slatepack_format = slatepack_recipient.is_some() || version == Some(SlateVersion::SP);
 
 	if slatepack_format {
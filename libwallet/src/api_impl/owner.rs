@@ -14,41 +14,267 @@
 
 //! Generic implementation of owner API functions
 
+use chrono::Utc;
 use uuid::Uuid;
 
+use crate::grin_core::core::amount_to_hr_string;
 use crate::grin_core::core::hash::Hashed;
 use crate::grin_core::core::Transaction;
+use crate::grin_core::global;
+use crate::grin_core::libtx::tx_fee;
 use crate::grin_util::secp::key::SecretKey;
 use crate::grin_util::Mutex;
+use crate::grin_util::ZeroingString;
 
+use crate::api_impl::owner_swap;
 use crate::api_impl::owner_updater::StatusMessage;
-use crate::grin_keychain::{Identifier, Keychain};
+use crate::grin_keychain::{mnemonic, Identifier, Keychain, SwitchCommitmentType};
+use crate::grin_util as util;
+use crate::grin_util::secp::constants::PEDERSEN_COMMITMENT_SIZE;
 use crate::grin_util::secp::key::PublicKey;
+use crate::grin_util::secp::pedersen;
 
-use crate::internal::{keys, scan, selection, tx, updater};
+use crate::internal::{check, keys, scan, selection, tx, updater};
 use crate::slate::{PaymentInfo, Slate};
 use crate::types::{
-	AcctPathMapping, Context, NodeClient, OutputData, TxLogEntry, WalletBackend, WalletInfo,
+	AcctPathMapping, AddressMapping, Contact, Context, NodeClient, OutputData, OutputStatus,
+	TxLogEntry, TxStatus, WalletBackend, WalletInfo,
 };
 use crate::{
-	wallet_lock, InitTxArgs, IssueInvoiceTxArgs, NodeHeightResult, OutputCommitMapping,
-	PaymentProof, ScannedBlockInfo, TxLogEntryType, WalletInst, WalletLCProvider,
+	wallet_lock, AccountSummaryInfo, AutoCancelPolicy, CheckCategory, CheckReport, ConsensusParams,
+	GcArgs, GcResult, HistoryBucket, InitTxArgs, IssueInvoiceTxArgs, KernelLookupResult,
+	NodeHeightResult, OutputCommitMapping, OutputQueryArgs, OutputTagArgs, PaymentProof,
+	PublicProofAddress, ReissueTxArgs, ScanProgress, ScannedBlockInfo, TxLabelArgs, TxLogEntryType,
+	TxQueryArgs, UtxoSnapshot, UtxoSnapshotOutput, WalletInst, WalletLCProvider,
 };
 use crate::{Error, ErrorKind};
 
 use crate::proof::tx_proof::{pop_proof_for_slate, TxProof};
+use crate::swap::trades;
 use ed25519_dalek::PublicKey as DalekPublicKey;
 use std::cmp;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::Write;
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
+use std::sync::{Condvar, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 
 const USER_MESSAGE_MAX_LEN: usize = 1000; // We can keep messages as long as we need unless the slate will be too large to operate. 1000 symbols should be enough to keep everybody happy
-use crate::proof::crypto;
-use crate::proof::proofaddress;
+use crate::log_privacy;
+use crate::migration::{WalletMigrationData, WalletMigrationReport};
+use crate::notifications::{self, WalletEvent};
+use crate::proof::payment_proof;
+use crate::proof::proofaddress::{self, ProofAddressType, ProvableAddress};
+use crate::tx_hooks;
+use crate::tx_import::{TxImportRecord, TxImportReport, TxImportSkipped};
+use crate::tx_mirror;
 use grin_wallet_util::grin_core::core::Committed;
 
+lazy_static! {
+	/// Maintenance mode. While the wallet is paused, listeners and the state changing
+	/// operations reject the requests and background jobs skip their work, so the data
+	/// directory can be copied consistently.
+	static ref WALLET_OPERATIONS: (std::sync::Mutex<WalletOperations>, Condvar) =
+		(std::sync::Mutex::new(WalletOperations::default()), Condvar::new());
+	/// Policy for the updater thread to cancel the expired transactions
+	static ref AUTO_CANCEL_POLICY: RwLock<AutoCancelPolicy> =
+		RwLock::new(AutoCancelPolicy::default());
+	/// Base interval in seconds for the automatic repost of the unconfirmed transactions
+	static ref AUTO_REPOST_INTERVAL: RwLock<Option<u64>> = RwLock::new(None);
+	/// Automatic repost schedule, keyed by '<account path>/<tx id>'
+	static ref AUTO_REPOST_BACKOFF: RwLock<HashMap<String, RepostBackoff>> =
+		RwLock::new(HashMap::new());
+}
+
+/// Longest delay between the automatic reposts of the same transaction
+pub const AUTO_REPOST_MAX_DELAY_SECS: u64 = 24 * 3600;
+
+/// Max length of the local transaction label, characters
+pub const MAX_TX_LABEL_LENGTH: usize = 64;
+/// Max length of the local transaction memo, characters
+pub const MAX_TX_MEMO_LENGTH: usize = 1000;
+/// Max confirmation target of the transaction, about 30 days of blocks
+pub const MAX_CONFIRMATION_TARGET: u64 = 30 * 1440;
+/// Max length of the local output tag, characters
+pub const MAX_OUTPUT_TAG_LENGTH: usize = 32;
+
+/// Automatic repost schedule of a single transaction
+struct RepostBackoff {
+	attempts: u32,
+	next_attempt: Instant,
+}
+
+/// Maintenance mode flag and the number of the state changing operations in progress
+#[derive(Default)]
+struct WalletOperations {
+	paused: bool,
+	active: usize,
+}
+
+/// State changing operation in progress. The pause waits until all of them are dropped.
+pub struct WalletOperationGuard {
+	_private: (),
+}
+
+impl Drop for WalletOperationGuard {
+	fn drop(&mut self) {
+		let (lock, finished) = &*WALLET_OPERATIONS;
+		let mut ops = lock.lock().unwrap();
+		ops.active -= 1;
+		finished.notify_all();
+	}
+}
+
+/// Start the state changing operation. Returns WalletPaused error in maintenance mode, otherwise
+/// the pause waits until the returned guard is dropped.
+pub fn begin_wallet_operation() -> Result<WalletOperationGuard, Error> {
+	let mut ops = WALLET_OPERATIONS.0.lock().unwrap();
+	if ops.paused {
+		return Err(ErrorKind::WalletPaused.into());
+	}
+	ops.active += 1;
+	Ok(WalletOperationGuard { _private: () })
+}
+
+/// Turn the maintenance mode on or off. Turning it on returns after the operations that are
+/// in progress are finished, new ones are rejected from that moment.
+pub fn set_wallet_paused(paused: bool) {
+	let (lock, finished) = &*WALLET_OPERATIONS;
+	let mut ops = lock.lock().unwrap();
+	ops.paused = paused;
+	if paused {
+		while ops.active > 0 {
+			ops = finished.wait(ops).unwrap();
+		}
+	}
+}
+
+/// Check if the wallet is in maintenance mode
+pub fn is_wallet_paused() -> bool {
+	WALLET_OPERATIONS.0.lock().unwrap().paused
+}
+
+/// Return WalletPaused error if the wallet is in maintenance mode
+pub fn check_wallet_not_paused() -> Result<(), Error> {
+	if is_wallet_paused() {
+		return Err(ErrorKind::WalletPaused.into());
+	}
+	Ok(())
+}
+
+/// Set the policy for the automatic cancellation of the expired transactions
+pub fn set_auto_cancel_policy(policy: AutoCancelPolicy) {
+	*AUTO_CANCEL_POLICY.write().unwrap() = policy;
+}
+
+/// Read the policy for the automatic cancellation of the expired transactions
+pub fn get_auto_cancel_policy() -> AutoCancelPolicy {
+	AUTO_CANCEL_POLICY.read().unwrap().clone()
+}
+
+/// Set the base interval for the automatic repost of the unconfirmed transactions.
+/// None or 0 disables the repost.
+pub fn set_auto_repost_interval(interval_secs: Option<u64>) {
+	*AUTO_REPOST_INTERVAL.write().unwrap() = interval_secs.filter(|i| *i > 0);
+}
+
+/// Base interval for the automatic repost, None if it is disabled
+pub fn get_auto_repost_interval() -> Option<u64> {
+	AUTO_REPOST_INTERVAL.read().unwrap().clone()
+}
+
+/// Progress of the running or last chain scan
+pub fn get_scan_progress() -> ScanProgress {
+	scan::get_scan_progress()
+}
+
+/// Consensus parameters for the fee and dust calculations. Weights are derived from the
+/// same fee function that the wallet is using for the transactions.
+pub fn get_consensus_params<'a, T: ?Sized, C, K>(w: &mut T) -> Result<ConsensusParams, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let base_fee = selection::get_base_fee();
+	let fee = |inputs: usize, outputs: usize, kernels: usize| -> i64 {
+		tx_fee(inputs, outputs, kernels, Some(base_fee)) as i64
+	};
+	let unit = cmp::max(base_fee, 1) as i64;
+
+	Ok(ConsensusParams {
+		base_fee,
+		input_weight: (fee(1, 1, 1) - fee(0, 1, 1)) / unit,
+		output_weight: (fee(0, 2, 1) - fee(0, 1, 1)) / unit,
+		kernel_weight: (fee(0, 1, 2) - fee(0, 1, 1)) / unit,
+		min_tx_fee: fee(1, 1, 1) as u64,
+		coinbase_maturity: global::coinbase_maturity(),
+		current_height: w.last_confirmed_height()?,
+	})
+}
+
+/// Recovery phrase of the receive-only wallet. That wallet can run the listener on an online
+/// box without the seed of this wallet, its funds can be swept with `sweep-seed`.
+pub fn get_receive_wallet_phrase<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<ZeroingString, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let keychain = w.keychain(keychain_mask)?;
+	let seed = keys::receive_wallet_seed(&keychain)?;
+	let phrase = mnemonic::from_entropy(&seed).map_err(|e| {
+		ErrorKind::GenericError(format!(
+			"Unable to build receive wallet recovery phrase, {}",
+			e
+		))
+	})?;
+	Ok(ZeroingString::from(phrase))
+}
+
+/// Default history bucket is a day
+const HISTORY_BUCKET_SEC: u64 = 24 * 3600;
+/// Shortest history bucket is an hour
+const HISTORY_MIN_BUCKET_SEC: u64 = 3600;
+
+/// Time-bucketed balance, fees and volume series for the current account
+pub fn retrieve_history_series<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	bucket_sec: Option<u64>,
+) -> Result<Vec<HistoryBucket>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	// Validating the mask, history is available for the opened wallet only
+	w.keychain(keychain_mask)?;
+	let bucket_sec = bucket_sec.unwrap_or(HISTORY_BUCKET_SEC);
+	if bucket_sec < HISTORY_MIN_BUCKET_SEC {
+		return Err(ErrorKind::GenericError(format!(
+			"History bucket can't be shorter than {} seconds",
+			HISTORY_MIN_BUCKET_SEC
+		))
+		.into());
+	}
+	let parent_key_id = w.parent_key_id();
+	let txs: Vec<TxLogEntry> = w
+		.tx_log_iter()
+		.filter(|t| t.parent_key_id == parent_key_id)
+		.collect();
+	Ok(updater::history_series(
+		&txs,
+		bucket_sec,
+		Utc::now().timestamp(),
+	))
+}
+
 /// List of accounts
 pub fn accounts<'a, T: ?Sized, C, K>(w: &mut T) -> Result<Vec<AcctPathMapping>, Error>
 where
@@ -73,6 +299,36 @@ where
 	keys::new_acct_path(&mut *w, keychain_mask, label)
 }
 
+/// rename account
+pub fn rename_account<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	old_label: &str,
+	new_label: &str,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let accounts = keys::accounts(&mut *w)?;
+	keys::rename_acct_path(&mut *w, keychain_mask, accounts, old_label, new_label)
+}
+
+/// delete account that doesn't hold any funds
+pub fn delete_account<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	label: &str,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	keys::delete_acct_path(&mut *w, keychain_mask, label)
+}
+
 /// set active account
 pub fn set_active_account<'a, T: ?Sized, C, K>(w: &mut T, label: &str) -> Result<(), Error>
 where
@@ -83,6 +339,88 @@ where
 	w.set_parent_key_id_by_name(label)
 }
 
+/// List of address book contacts, sorted by name
+pub fn contacts<'a, T: ?Sized, C, K>(w: &mut T) -> Result<Vec<Contact>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut contacts: Vec<Contact> = w.contact_iter().collect();
+	contacts.sort_by(|a, b| a.name.cmp(&b.name));
+	Ok(contacts)
+}
+
+/// Add a new address book contact
+pub fn add_contact<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	contact: Contact,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	Contact::validate_name(&contact.name)?;
+	let address = contact.address.trim();
+	if address.is_empty()
+		|| address.contains(char::is_whitespace)
+		|| Contact::name_from_reference(address).is_some()
+	{
+		return Err(ErrorKind::InvalidContact(format!(
+			"Invalid address '{}' for contact '{}'",
+			contact.address, contact.name
+		))
+		.into());
+	}
+	if w.get_contact(&contact.name)?.is_some() {
+		return Err(ErrorKind::ContactAlreadyExists(contact.name).into());
+	}
+
+	let mut batch = w.batch(keychain_mask)?;
+	batch.save_contact(Contact::new(&contact.name, address))?;
+	batch.commit()?;
+	Ok(())
+}
+
+/// Remove address book contact
+pub fn remove_contact<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	name: &str,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	if w.get_contact(name)?.is_none() {
+		return Err(ErrorKind::ContactNotFound(name.to_string()).into());
+	}
+	let mut batch = w.batch(keychain_mask)?;
+	batch.delete_contact(name)?;
+	batch.commit()?;
+	Ok(())
+}
+
+/// Resolve destination. If destination is a contact reference '@name', the contact address
+/// will be returned. Otherwise destination is returned as it is.
+pub fn resolve_contact_address<'a, T: ?Sized, C, K>(w: &mut T, dest: &str) -> Result<String, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	match Contact::name_from_reference(dest) {
+		Some(name) => match w.get_contact(name)? {
+			Some(contact) => Ok(contact.address),
+			None => Err(ErrorKind::ContactNotFound(name.to_string()).into()),
+		},
+		None => Ok(dest.to_string()),
+	}
+}
+
 /// Retrieve the MQS address for the wallet
 pub fn get_mqs_address<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
@@ -99,6 +437,135 @@ where
 	Ok(pub_key)
 }
 
+/// Retrieve the MQS and Tor addresses at the derivation index. The highest requested index
+/// is saved, so the payments to these addresses can be received with the valid proof.
+pub fn get_public_proof_address<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	index: u32,
+) -> Result<PublicProofAddress, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let k = w.keychain(keychain_mask)?;
+	let mqs_address =
+		proofaddress::payment_proof_address_from_index(&k, index, ProofAddressType::MQS)?;
+	let tor_address =
+		proofaddress::payment_proof_address_from_index(&k, index, ProofAddressType::Onion)?;
+
+	let max_issued = w.max_issued_address_index()?;
+	if max_issued.map(|i| index > i).unwrap_or(true) {
+		let mut batch = w.batch(keychain_mask)?;
+		batch.save_max_issued_address_index(index)?;
+		batch.commit()?;
+	}
+
+	Ok(PublicProofAddress {
+		index,
+		mqs_address,
+		tor_address,
+	})
+}
+
+/// Address indexes that received the payments with the proof, sorted. The received
+/// transactions have the index in `address_index`.
+pub fn list_used_address_indices<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+) -> Result<Vec<u32>, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let indices: HashSet<u32> = w.tx_log_iter().filter_map(|t| t.address_index).collect();
+	let mut indices: Vec<u32> = indices.into_iter().collect();
+	indices.sort();
+	Ok(indices)
+}
+
+/// Mappings of the address indexes to the accounts and labels, sorted by index
+pub fn address_mappings<'a, T: ?Sized, C, K>(w: &mut T) -> Result<Vec<AddressMapping>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	Ok(w.address_mapping_iter().collect())
+}
+
+/// Book the payments received on the address index to the account and label. The existing
+/// mapping of the index is replaced.
+pub fn set_address_mapping<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	mapping: AddressMapping,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let trimmed = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+	let account = trimmed(mapping.account);
+	let label = trimmed(mapping.label);
+	if account.is_none() && label.is_none() {
+		return Err(ErrorKind::AddressMapping(format!(
+			"account or label is required for the address index {}",
+			mapping.index
+		))
+		.into());
+	}
+	if let Some(a) = &account {
+		if w.get_acct_path(a.clone())?.is_none() {
+			return Err(ErrorKind::UnknownAccountLabel(a.clone()).into());
+		}
+	}
+	if let Some(l) = &label {
+		if l.chars().count() > MAX_TX_LABEL_LENGTH {
+			return Err(ErrorKind::AddressMapping(format!(
+				"label is too long, max length is {} characters",
+				MAX_TX_LABEL_LENGTH
+			))
+			.into());
+		}
+	}
+
+	let mut batch = w.batch(keychain_mask)?;
+	batch.save_address_mapping(AddressMapping {
+		index: mapping.index,
+		account,
+		label,
+	})?;
+	batch.commit()?;
+	Ok(())
+}
+
+/// Remove the mapping of the address index
+pub fn remove_address_mapping<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	index: u32,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	if w.get_address_mapping(index)?.is_none() {
+		return Err(
+			ErrorKind::AddressMapping(format!("address index {} is not mapped", index)).into(),
+		);
+	}
+	let mut batch = w.batch(keychain_mask)?;
+	batch.delete_address_mapping(index)?;
+	batch.commit()?;
+	Ok(())
+}
+
 /// Retrieve TOR or public wallet address
 pub fn get_wallet_public_address<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
@@ -223,14 +690,14 @@ where
 	Ok((validated, txs))
 }
 
-/// Retrieve summary info
-pub fn retrieve_summary_info<'a, L, C, K>(
+/// Retrieve outputs that match the query
+pub fn query_outputs<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
 	keychain_mask: Option<&SecretKey>,
 	status_send_channel: &Option<Sender<StatusMessage>>,
 	refresh_from_node: bool,
-	minimum_confirmations: u64,
-) -> Result<(bool, WalletInfo), Error>
+	query: &OutputQueryArgs,
+) -> Result<(bool, Vec<OutputCommitMapping>), Error>
 where
 	L: WalletLCProvider<'a, C, K>,
 	C: NodeClient + 'a,
@@ -244,47 +711,149 @@ where
 
 	wallet_lock!(wallet_inst, w);
 	let parent_key_id = w.parent_key_id();
-	let wallet_info = updater::retrieve_info(&mut **w, &parent_key_id, minimum_confirmations)?;
-	Ok((validated, wallet_info))
+	let outputs = updater::query_outputs(&mut **w, keychain_mask, &parent_key_id, query)?;
+	Ok((validated, outputs))
 }
 
-/// Retrieve payment proof
-pub fn retrieve_payment_proof<'a, L, C, K>(
+/// Retrieve txs that match the query
+pub fn query_txs<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
 	keychain_mask: Option<&SecretKey>,
 	status_send_channel: &Option<Sender<StatusMessage>>,
 	refresh_from_node: bool,
-	tx_id: Option<u32>,
-	tx_slate_id: Option<Uuid>,
-) -> Result<PaymentProof, Error>
+	query: &TxQueryArgs,
+) -> Result<(bool, Vec<TxLogEntry>), Error>
 where
 	L: WalletLCProvider<'a, C, K>,
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
-	if tx_id.is_none() && tx_slate_id.is_none() {
-		return Err(ErrorKind::PaymentProofRetrieval(
-			"Transaction ID or Slate UUID must be specified".into(),
-		)
-		.into());
-	}
+	let mut validated = false;
 	if refresh_from_node {
-		update_wallet_state(wallet_inst.clone(), keychain_mask, status_send_channel)?
-	} else {
-		false
-	};
-	let txs = retrieve_txs(
-		wallet_inst.clone(),
+		validated =
+			perform_refresh_from_node(wallet_inst.clone(), keychain_mask, status_send_channel)?;
+	}
+
+	wallet_lock!(wallet_inst, w);
+	let parent_key_id = w.parent_key_id();
+	let txs = updater::retrieve_txs(
+		&mut **w,
 		keychain_mask,
-		status_send_channel,
-		refresh_from_node,
-		tx_id,
-		tx_slate_id,
+		None,
+		None,
+		Some(&parent_key_id),
+		false,
+		None,
+		None,
 	)?;
-	if txs.1.len() != 1 {
-		return Err(ErrorKind::PaymentProofRetrieval("Transaction doesn't exist".into()).into());
-	}
-	// Pull out all needed fields, returning an error if they're not present
+
+	let txs = txs.into_iter().filter(|t| query.matches(t));
+	Ok((validated, updater::paginate(txs, query.offset, query.limit)))
+}
+
+/// Retrieve summary info
+pub fn retrieve_summary_info<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	refresh_from_node: bool,
+	minimum_confirmations: u64,
+) -> Result<(bool, WalletInfo), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut validated = false;
+	if refresh_from_node {
+		validated =
+			perform_refresh_from_node(wallet_inst.clone(), keychain_mask, status_send_channel)?;
+	}
+
+	wallet_lock!(wallet_inst, w);
+	let parent_key_id = w.parent_key_id();
+	let mut wallet_info = updater::retrieve_info(&mut **w, &parent_key_id, minimum_confirmations)?;
+	if trades::is_trade_backend_initialized() {
+		let keychain = w.keychain(keychain_mask)?;
+		match owner_swap::swap_committed_amount(&keychain) {
+			Ok(0) => (),
+			Ok(amount) => wallet_info.amount_committed_to_swaps = Some(amount),
+			Err(e) => warn!("Unable to calculate funds committed to swaps, {}", e),
+		}
+	}
+	Ok((validated, wallet_info))
+}
+
+/// Retrieve summary info for every account, the wallet is refreshed from the node once
+pub fn retrieve_accounts_summary_info<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	refresh_from_node: bool,
+	minimum_confirmations: u64,
+) -> Result<(bool, Vec<AccountSummaryInfo>), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut validated = false;
+	if refresh_from_node {
+		validated =
+			perform_refresh_from_node(wallet_inst.clone(), keychain_mask, status_send_channel)?;
+	}
+
+	wallet_lock!(wallet_inst, w);
+	let accounts: Vec<AcctPathMapping> = w.acct_path_iter().collect();
+	let mut res = Vec::with_capacity(accounts.len());
+	for acct in accounts {
+		let info = updater::retrieve_info(&mut **w, &acct.path, minimum_confirmations)?;
+		res.push(AccountSummaryInfo {
+			label: acct.label,
+			path: acct.path,
+			info,
+		});
+	}
+	Ok((validated, res))
+}
+
+/// Retrieve payment proof
+pub fn retrieve_payment_proof<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	refresh_from_node: bool,
+	tx_id: Option<u32>,
+	tx_slate_id: Option<Uuid>,
+) -> Result<PaymentProof, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	if tx_id.is_none() && tx_slate_id.is_none() {
+		return Err(ErrorKind::PaymentProofRetrieval(
+			"Transaction ID or Slate UUID must be specified".into(),
+		)
+		.into());
+	}
+	if refresh_from_node {
+		update_wallet_state(wallet_inst.clone(), keychain_mask, status_send_channel)?
+	} else {
+		false
+	};
+	let txs = retrieve_txs(
+		wallet_inst.clone(),
+		keychain_mask,
+		status_send_channel,
+		refresh_from_node,
+		tx_id,
+		tx_slate_id,
+	)?;
+	if txs.1.len() != 1 {
+		return Err(ErrorKind::PaymentProofRetrieval("Transaction doesn't exist".into()).into());
+	}
+	// Pull out all needed fields, returning an error if they're not present
 	let tx = txs.1[0].clone();
 	let proof = match tx.payment_proof {
 		Some(p) => p,
@@ -392,6 +961,38 @@ pub fn init_send_tx<'a, T: ?Sized, C, K>(
 	use_test_rng: bool,
 	routputs: usize, // Number of resulting outputs. Normally it is 1
 ) -> Result<Slate, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	init_send_tx_at_height(w, keychain_mask, args, use_test_rng, routputs, None)
+}
+
+// Chain height from the node, or the one that the offline wallet got with the bundle
+fn chain_height<'a, T: ?Sized, C, K>(w: &mut T, height: Option<u64>) -> Result<u64, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	match height {
+		Some(h) => Ok(h),
+		None => Ok(w.w2n_client().get_chain_tip()?.0),
+	}
+}
+
+/// Initiate tx as sender at the given chain height. The offline (cold) wallet doesn't have
+/// access to the node, it builds the transaction at the height from the offline signing bundle.
+/// With `None` height the chain tip is requested from the node.
+pub fn init_send_tx_at_height<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	args: &InitTxArgs,
+	use_test_rng: bool,
+	routputs: usize, // Number of resulting outputs. Normally it is 1
+	height: Option<u64>,
+) -> Result<Slate, Error>
 where
 	T: WalletBackend<'a, C, K>,
 	C: NodeClient + 'a,
@@ -420,13 +1021,13 @@ where
 	let compact_slate =
 		args.slatepack_recipient.is_some() || args.target_slate_version.clone().unwrap_or(0) >= 4;
 
-	let mut slate = tx::new_tx_slate(
-		&mut *w,
+	let mut slate = tx::new_tx_slate_at_height(
 		args.amount,
 		2,
 		use_test_rng,
 		args.ttl_blocks,
 		compact_slate,
+		chain_height(w, height)?,
 	)?;
 
 	// if we just want to estimate, don't save a context, just send the results
@@ -439,6 +1040,7 @@ where
 			args.minimum_confirmations,
 			args.max_outputs as usize,
 			args.num_change_outputs as usize,
+			&selection::ChangePolicy::for_send(&args),
 			args.selection_strategy_is_use_all,
 			&parent_key_id,
 			&args.outputs,
@@ -452,7 +1054,7 @@ where
 	}
 
 	// Updating height because it is lookup height for the kernel
-	slate.height = w.w2n_client().get_chain_tip()?.0;
+	slate.height = chain_height(w, height)?;
 	let h = slate.height;
 	let mut context = if args.late_lock.unwrap_or(false) {
 		if !slate.compact_slate {
@@ -482,6 +1084,7 @@ where
 			args.minimum_confirmations,
 			args.max_outputs as usize,
 			args.num_change_outputs as usize,
+			&selection::ChangePolicy::for_send(&args),
 			args.selection_strategy_is_use_all,
 			&parent_key_id,
 			0,
@@ -683,6 +1286,7 @@ where
 		args.minimum_confirmations,
 		args.max_outputs as usize,
 		args.num_change_outputs as usize,
+		&selection::ChangePolicy::for_send(&args),
 		args.selection_strategy_is_use_all,
 		&parent_key_id,
 		1, // Participant id 1 for mwc713 compatibility
@@ -764,6 +1368,33 @@ where
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
+	tx_lock_outputs_at_height(
+		w,
+		keychain_mask,
+		slate,
+		address,
+		participant_id,
+		use_test_rng,
+		None,
+	)
+}
+
+/// Lock sender outputs at the given chain height, `None` to request the height from the node
+pub fn tx_lock_outputs_at_height<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	slate: &Slate,
+	address: Option<String>,
+	participant_id: usize,
+	use_test_rng: bool,
+	height: Option<u64>,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	tx_hooks::run_pre_lock(slate, address.clone())?;
 	let context = w.get_private_context(keychain_mask, slate.id.as_bytes(), participant_id)?;
 	let mut excess_override = None;
 
@@ -785,7 +1416,7 @@ where
 		}
 	}
 
-	let height = w.w2n_client().get_chain_tip()?.0;
+	let height = chain_height(w, height)?;
 	selection::lock_tx_context(
 		&mut *w,
 		keychain_mask,
@@ -794,6 +1425,12 @@ where
 		&context,
 		address,
 		excess_override,
+		// Sender waits for the receiver, invoice payer waits for the issuer to finalize
+		if participant_id == 0 {
+			TxStatus::AwaitingCounterparty
+		} else {
+			TxStatus::AwaitingFinalization
+		},
 	)
 }
 
@@ -806,14 +1443,48 @@ pub fn finalize_tx<'a, T: ?Sized, C, K>(
 	refresh_from_node: bool,
 	use_test_rng: bool,
 ) -> Result<(Slate, Context), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	finalize_tx_at_height(
+		w,
+		keychain_mask,
+		slate,
+		refresh_from_node,
+		use_test_rng,
+		None,
+	)
+}
+
+/// Finalize slate at the given chain height. The offline wallet finalizes at the height from
+/// the offline signing bundle, `None` to request the height from the node.
+pub fn finalize_tx_at_height<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	slate: &Slate,
+	refresh_from_node: bool,
+	use_test_rng: bool,
+	height: Option<u64>,
+) -> Result<(Slate, Context), Error>
 where
 	T: WalletBackend<'a, C, K>,
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
 	let mut sl = slate.clone();
-	sl.height = w.w2n_client().get_chain_tip()?.0;
-	check_ttl(w, &sl, refresh_from_node)?;
+	sl.height = chain_height(w, height)?;
+	match (height, sl.ttl_cutoff_height) {
+		(Some(h), Some(e)) if h >= e => return Err(ErrorKind::TransactionExpired.into()),
+		(Some(_), _) => (),
+		(None, _) => check_ttl(w, &sl, refresh_from_node)?,
+	}
+	let destination = w
+		.tx_log_iter()
+		.find(|t| t.tx_slate_id == Some(sl.id))
+		.and_then(|t| t.address);
+	tx_hooks::run_pre_finalize(&sl, destination)?;
 	let mut context = w.get_private_context(keychain_mask, sl.id.as_bytes(), 0)?;
 	let keychain = w.keychain(keychain_mask)?;
 	let parent_key_id = context.parent_key_id.clone();
@@ -840,6 +1511,7 @@ where
 			args.minimum_confirmations,
 			args.max_outputs as usize,
 			args.num_change_outputs as usize,
+			&selection::ChangePolicy::for_send(&args),
 			args.selection_strategy_is_use_all,
 			parent_key_id.clone(),
 			0,
@@ -891,44 +1563,966 @@ where
 		batch.commit()?;
 	}
 
-	// If Proof available, we can store it at that point
-	if let Some(mut proof) = pop_proof_for_slate(&slate.id) {
-		proof.amount = context.amount;
-		proof.fee = context.fee;
-		for input in &context.input_commits {
-			proof.inputs.push(input.clone());
-		}
-		for output in &context.output_commits {
-			proof.outputs.push(output.clone());
+	// If Proof available, we can store it at that point
+	if let Some(mut proof) = pop_proof_for_slate(&slate.id) {
+		proof.amount = context.amount;
+		proof.fee = context.fee;
+		for input in &context.input_commits {
+			proof.inputs.push(input.clone());
+		}
+		for output in &context.output_commits {
+			proof.outputs.push(output.clone());
+		}
+
+		proof.store_tx_proof(w.get_data_file_dir(), &slate.id.to_string())?;
+	};
+
+	Ok((sl, context))
+}
+
+/// cancel tx
+pub fn cancel_tx<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	tx_id: Option<u32>,
+	tx_slate_id: Option<Uuid>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	if !perform_refresh_from_node(wallet_inst.clone(), keychain_mask, status_send_channel)? {
+		return Err(ErrorKind::TransactionCancellationError(
+			"Can't contact running MWC node. Not Cancelling.",
+		))?;
+	}
+	wallet_lock!(wallet_inst, w);
+	let parent_key_id = w.parent_key_id();
+	tx::cancel_tx(
+		&mut **w,
+		keychain_mask,
+		&parent_key_id,
+		tx_id,
+		tx_slate_id,
+		false,
+	)
+}
+
+/// Set the local label and memo of the transaction. They are stored in the wallet DB only and
+/// are never sent to the other party.
+pub fn set_tx_label<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	args: &TxLabelArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let check_length = |name: &str, value: &Option<String>, max_len: usize| match value {
+		Some(v) if v.chars().count() > max_len => Err(ErrorKind::GenericError(format!(
+			"Transaction {} is too long, max length is {} characters",
+			name, max_len
+		))),
+		_ => Ok(()),
+	};
+	check_length("label", &args.label, MAX_TX_LABEL_LENGTH)?;
+	check_length("memo", &args.memo, MAX_TX_MEMO_LENGTH)?;
+
+	wallet_lock!(wallet_inst, w);
+	let parent_key_id = w.parent_key_id();
+	let mut tx = updater::retrieve_txs(
+		&mut **w,
+		keychain_mask,
+		Some(args.tx_id),
+		None,
+		Some(&parent_key_id),
+		false,
+		None,
+		None,
+	)?
+	.into_iter()
+	.next()
+	.ok_or(ErrorKind::TransactionDoesntExist(args.tx_id.to_string()))?;
+
+	// Empty value removes the label or memo
+	let update = |current: &mut Option<String>, value: &Option<String>| {
+		if let Some(v) = value {
+			let v = v.trim();
+			*current = if v.is_empty() {
+				None
+			} else {
+				Some(v.to_string())
+			};
+		}
+	};
+	update(&mut tx.label, &args.label);
+	update(&mut tx.memo, &args.memo);
+
+	let mut batch = w.batch(keychain_mask)?;
+	batch.save_tx_log_entry(tx, &parent_key_id)?;
+	batch.commit()?;
+	Ok(())
+}
+
+/// Set the number of confirmations when the transaction is settled. When the confirmed
+/// transaction reaches the target, the updater moves it into `Settled` state and reports
+/// TxSettled event. None or 0 removes the target.
+pub fn set_tx_confirmation_target<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	tx_id: u32,
+	target: Option<u64>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let target = target.filter(|t| *t > 0);
+	if let Some(t) = target {
+		if t > MAX_CONFIRMATION_TARGET {
+			return Err(ErrorKind::GenericError(format!(
+				"Confirmation target {} is too large, max value is {}",
+				t, MAX_CONFIRMATION_TARGET
+			))
+			.into());
+		}
+	}
+
+	let event = {
+		wallet_lock!(wallet_inst, w);
+		let parent_key_id = w.parent_key_id();
+		let mut tx = updater::retrieve_txs(
+			&mut **w,
+			keychain_mask,
+			Some(tx_id),
+			None,
+			Some(&parent_key_id),
+			false,
+			None,
+			None,
+		)?
+		.into_iter()
+		.next()
+		.ok_or(ErrorKind::TransactionDoesntExist(tx_id.to_string()))?;
+		if tx.is_cancelled() {
+			return Err(
+				ErrorKind::GenericError(format!("Transaction {} is cancelled", tx_id)).into(),
+			);
+		}
+
+		tx.confirmation_target = target;
+		// The new target is checked from scratch, the transaction might be not settled any more
+		if tx.current_status() == TxStatus::Settled {
+			tx.status = TxStatus::Confirmed;
+		}
+		let event = settle_tx(&mut tx, w.last_confirmed_height()?);
+
+		let mut batch = w.batch(keychain_mask)?;
+		batch.save_tx_log_entry(tx, &parent_key_id)?;
+		batch.commit()?;
+		event
+	};
+	if let Some(event) = event {
+		notifications::notify(event);
+	}
+	Ok(())
+}
+
+/// Merge the imported transaction records into the tx log of the current account. A record
+/// conflicts with the wallet transaction or another record with the same slate id or kernel,
+/// such records are skipped. Only the finished transactions are imported: confirmed or
+/// cancelled. The imported transactions have no outputs, the balance is not changed.
+pub fn import_txs<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	records: Vec<TxImportRecord>,
+	dry_run: bool,
+) -> Result<TxImportReport, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let parent_key_id = w.parent_key_id();
+
+	// The conflicts are checked in all accounts
+	let mut slate_ids: HashMap<Uuid, Option<u32>> = HashMap::new();
+	let mut kernels: HashMap<pedersen::Commitment, Option<u32>> = HashMap::new();
+	for t in w.tx_log_iter() {
+		if let Some(id) = t.tx_slate_id {
+			slate_ids.insert(id, Some(t.id));
+		}
+		if let Some(excess) = t.kernel_excess {
+			kernels.insert(excess, Some(t.id));
+		}
+	}
+
+	let mut report = TxImportReport {
+		imported: vec![],
+		imported_count: 0,
+		skipped: vec![],
+	};
+	let mut entries = vec![];
+	for (i, r) in records.into_iter().enumerate() {
+		let conflict = r
+			.tx_slate_id
+			.and_then(|id| slate_ids.get(&id).map(|t| (t.clone(), "slate id")))
+			.or(r
+				.kernel_excess
+				.and_then(|k| kernels.get(&k).map(|t| (t.clone(), "kernel"))));
+		let cancelled = r.tx_type == TxLogEntryType::TxReceivedCancelled
+			|| r.tx_type == TxLogEntryType::TxSentCancelled;
+		let reason = match &conflict {
+			Some((Some(id), field)) => Some(format!("transaction {} has the same {}", id, field)),
+			Some((None, field)) => Some(format!("duplicate {} in the import", field)),
+			None if !r.confirmed && !cancelled => {
+				Some("only confirmed or cancelled transactions can be imported".to_string())
+			}
+			None => None,
+		};
+		if let Some(reason) = reason {
+			report.skipped.push(TxImportSkipped {
+				record: i + 1,
+				tx_slate_id: r.tx_slate_id,
+				existing_tx_id: conflict.and_then(|(id, _)| id),
+				reason,
+			});
+			continue;
+		}
+		if let Some(id) = r.tx_slate_id {
+			slate_ids.insert(id, None);
+		}
+		if let Some(excess) = r.kernel_excess {
+			kernels.insert(excess, None);
+		}
+		report.imported_count += 1;
+		entries.push(r);
+	}
+
+	if dry_run || entries.is_empty() {
+		return Ok(report);
+	}
+
+	let mut batch = w.batch(keychain_mask)?;
+	for r in entries {
+		let id = batch.next_tx_log_id(&parent_key_id)?;
+		let mut t = TxLogEntry::new(parent_key_id.clone(), r.tx_type, id);
+		let cancelled = t.is_cancelled();
+		t.tx_slate_id = r.tx_slate_id;
+		t.address = r.address;
+		t.creation_ts = r.creation_ts;
+		t.confirmed = r.confirmed && !cancelled;
+		t.confirmation_ts = if t.confirmed { r.confirmation_ts } else { None };
+		t.output_height = if t.confirmed { r.output_height } else { 0 };
+		t.amount_credited = r.amount_credited;
+		t.amount_debited = r.amount_debited;
+		t.fee = r.fee;
+		t.kernel_excess = r.kernel_excess;
+		t.label = r.label;
+		t.memo = r.memo;
+		t.status = if cancelled {
+			TxStatus::Cancelled
+		} else {
+			TxStatus::Confirmed
+		};
+		batch.save_tx_log_entry(t, &parent_key_id)?;
+		report.imported.push(id);
+	}
+	batch.commit()?;
+	Ok(report)
+}
+
+/// Merge the data of another wallet with the same seed into this wallet. The transactions get
+/// the new ids, the outputs keep the links to them. A transaction with the same slate id or
+/// kernel as the wallet transaction, an existing output or contact are skipped, so the
+/// migration can be repeated. The outputs are imported as they were recorded, the scan
+/// updates them from the chain.
+pub fn import_wallet_data<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	data: WalletMigrationData,
+) -> Result<WalletMigrationReport, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let mut report = WalletMigrationReport::default();
+
+	let accounts: Vec<AcctPathMapping> = w.acct_path_iter().collect();
+	let mut new_accounts = vec![];
+	for a in data.accounts {
+		if accounts.iter().any(|m| m.path == a.path) {
+			continue;
+		}
+		if accounts.iter().any(|m| m.label == a.label) {
+			report.skipped.push(format!(
+				"account '{}', the wallet has it with another path",
+				a.label
+			));
+			continue;
+		}
+		new_accounts.push(a);
+	}
+
+	let mut slate_ids: HashSet<Uuid> = HashSet::new();
+	let mut kernels: HashSet<pedersen::Commitment> = HashSet::new();
+	for t in w.tx_log_iter() {
+		if let Some(id) = t.tx_slate_id {
+			slate_ids.insert(id);
+		}
+		if let Some(excess) = t.kernel_excess {
+			kernels.insert(excess);
+		}
+	}
+	let mut txs = vec![];
+	for t in data.txs {
+		let conflict = if t.tx_slate_id.map(|id| slate_ids.contains(&id)) == Some(true) {
+			Some("slate id")
+		} else if t.kernel_excess.map(|k| kernels.contains(&k)) == Some(true) {
+			Some("kernel")
+		} else {
+			None
+		};
+		if let Some(field) = conflict {
+			report.skipped.push(format!(
+				"transaction {}, the wallet has a transaction with the same {}",
+				t.id, field
+			));
+			continue;
+		}
+		txs.push(t);
+	}
+	txs.sort_by_key(|t| t.id);
+
+	let mut outputs = vec![];
+	for o in data.outputs {
+		if w.get(&o.key_id, &o.mmr_index).is_ok() {
+			report
+				.skipped
+				.push(format!("output {}, the wallet has it", o.key_id));
+			continue;
+		}
+		outputs.push(o);
+	}
+	// New outputs must not reuse the derivation indexes of the imported ones
+	let mut child_indexes: HashMap<Identifier, u32> = HashMap::new();
+	for o in &outputs {
+		let max_child = child_indexes.entry(o.root_key_id.clone()).or_insert(0);
+		*max_child = cmp::max(*max_child, o.n_child + 1);
+	}
+	for (parent_key_id, child_index) in child_indexes.iter_mut() {
+		if *child_index <= w.current_child_index(parent_key_id)? {
+			*child_index = 0;
+		}
+	}
+
+	let mut contacts = vec![];
+	for c in data.contacts {
+		if Contact::validate_name(&c.name).is_err() || c.address.trim().is_empty() {
+			report
+				.skipped
+				.push(format!("contact '{}', invalid", c.name));
+		} else if w.get_contact(&c.name)?.is_some() {
+			report
+				.skipped
+				.push(format!("contact '{}', the wallet has it", c.name));
+		} else {
+			contacts.push(c);
+		}
+	}
+
+	let mut batch = w.batch(keychain_mask)?;
+	for a in new_accounts {
+		batch.save_acct_path(a)?;
+		report.accounts += 1;
+	}
+	// (account, old id) -> new id
+	let mut tx_ids: HashMap<(Identifier, u32), u32> = HashMap::new();
+	for mut t in txs {
+		let parent_key_id = t.parent_key_id.clone();
+		let id = batch.next_tx_log_id(&parent_key_id)?;
+		tx_ids.insert((parent_key_id.clone(), t.id), id);
+		t.id = id;
+		batch.save_tx_log_entry(t, &parent_key_id)?;
+		report.txs += 1;
+	}
+	for mut o in outputs {
+		o.tx_log_entry = o
+			.tx_log_entry
+			.and_then(|id| tx_ids.get(&(o.root_key_id.clone(), id)).cloned());
+		batch.save(o)?;
+		report.outputs += 1;
+	}
+	for (parent_key_id, child_index) in child_indexes {
+		if child_index > 0 {
+			batch.save_child_index(&parent_key_id, child_index)?;
+		}
+	}
+	for c in contacts {
+		batch.save_contact(Contact::new(&c.name, c.address.trim()))?;
+		report.contacts += 1;
+	}
+	batch.commit()?;
+	Ok(report)
+}
+
+/// Freeze, unfreeze or tag the output of the current account. Frozen output is not selected for
+/// sending unless it is listed explicitly in the send arguments. Tags are stored in the wallet DB only.
+pub fn tag_output<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	args: &OutputTagArgs,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let tags = match &args.tags {
+		Some(tags) => {
+			let mut res: Vec<String> = vec![];
+			for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+				if tag.chars().count() > MAX_OUTPUT_TAG_LENGTH {
+					return Err(ErrorKind::GenericError(format!(
+						"Output tag '{}' is too long, max length is {} characters",
+						tag, MAX_OUTPUT_TAG_LENGTH
+					))
+					.into());
+				}
+				if !res.iter().any(|t| t == tag) {
+					res.push(tag.to_string());
+				}
+			}
+			Some(res)
+		}
+		None => None,
+	};
+
+	wallet_lock!(wallet_inst, w);
+	let parent_key_id = w.parent_key_id();
+	let mut output = w
+		.iter()
+		.find(|out| out.root_key_id == parent_key_id && out.commit.as_ref() == Some(&args.commit))
+		.ok_or(ErrorKind::GenericError(format!(
+			"Output {} is not found in the current account",
+			args.commit
+		)))?;
+
+	if let Some(frozen) = args.frozen {
+		output.frozen = frozen;
+	}
+	if let Some(tags) = tags {
+		output.tags = tags;
+	}
+
+	let mut batch = w.batch(keychain_mask)?;
+	batch.save(output)?;
+	batch.commit()?;
+	Ok(())
+}
+
+/// Re-issue a stuck send with a higher fee. The unconfirmed transaction is cancelled with
+/// `cancel_tx`, so it is refused if its kernel is already on the chain, and the new one is built
+/// with the same inputs, amount and recipient, and with the fee multiplied by `fee_multiplier`.
+/// Finalized transaction can still be confirmed, it is reissued only with `args.force`.
+/// If the new transaction can't be built, the original one is restored.
+/// Returned slate needs to be sent to the recipient, the same way as a regular send.
+pub fn reissue_tx<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	args: &ReissueTxArgs,
+	use_test_rng: bool,
+) -> Result<Slate, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	// NaN is rejected as well
+	if !(args.fee_multiplier > 1.0) {
+		return Err(ErrorKind::GenericError(format!(
+			"Fee multiplier must be greater than 1.0, got {}",
+			args.fee_multiplier
+		))
+		.into());
+	}
+	let tx_id_string = args.tx_id.to_string();
+
+	let (tx, outputs, init_args) = {
+		wallet_lock!(wallet_inst, w);
+		let parent_key_id = w.parent_key_id();
+
+		let tx = updater::retrieve_txs(
+			&mut **w,
+			keychain_mask,
+			Some(args.tx_id),
+			None,
+			Some(&parent_key_id),
+			false,
+			None,
+			None,
+		)?
+		.into_iter()
+		.next()
+		.ok_or(ErrorKind::TransactionDoesntExist(tx_id_string.clone()))?;
+		if tx.tx_type != TxLogEntryType::TxSent || tx.confirmed {
+			return Err(ErrorKind::TransactionNotCancellable(tx_id_string).into());
+		}
+		// Finalized transaction can be already in the mempool and still get confirmed
+		if tx.current_status() == TxStatus::AwaitingConfirmation && !args.force {
+			return Err(ErrorKind::GenericError(format!(
+				"Transaction {} is finalized and can still be confirmed, use force to reissue it",
+				tx_id_string
+			))
+			.into());
+		}
+		let fee = tx.fee.ok_or(ErrorKind::GenericError(format!(
+			"Transaction {} doesn't have a fee, it can't be reissued",
+			tx_id_string
+		)))?;
+
+		// Snapshot of the original state, it is restored if the new transaction can't be built
+		let outputs: Vec<OutputData> = updater::retrieve_outputs(
+			&mut **w,
+			keychain_mask,
+			false,
+			Some(&tx),
+			&parent_key_id,
+			None,
+			None,
+		)?
+		.into_iter()
+		.map(|m| m.output)
+		.collect();
+		let inputs: Vec<&OutputData> = outputs
+			.iter()
+			.filter(|o| o.status == OutputStatus::Locked && o.commit.is_some())
+			.collect();
+		if inputs.is_empty() {
+			return Err(ErrorKind::GenericError(format!(
+				"Transaction {} doesn't have locked inputs, it can't be reissued",
+				tx_id_string
+			))
+			.into());
+		}
+
+		let amount = tx
+			.amount_debited
+			.saturating_sub(tx.amount_credited)
+			.saturating_sub(fee);
+		let new_fee = (fee as f64 * args.fee_multiplier as f64).ceil() as u64;
+		let inputs_total: u64 = inputs.iter().map(|o| o.value).sum();
+		if inputs_total < amount + new_fee {
+			return Err(ErrorKind::NotEnoughFunds {
+				available: inputs_total,
+				available_disp: amount_to_hr_string(inputs_total, true),
+				needed: amount + new_fee,
+				needed_disp: amount_to_hr_string(amount + new_fee, true),
+			})?;
+		}
+
+		let height = w.w2n_client().get_chain_tip()?.0;
+		let init_args = InitTxArgs {
+			amount,
+			minimum_confirmations: 1,
+			selection_strategy_is_use_all: true,
+			target_slate_version: args.target_slate_version,
+			ttl_blocks: tx
+				.ttl_cutoff_height
+				.filter(|h| *h > height)
+				.map(|h| h - height),
+			payment_proof_recipient_address: tx
+				.payment_proof
+				.as_ref()
+				.map(|p| p.receiver_address.clone()),
+			address: tx.address.clone(),
+			outputs: Some(inputs.iter().filter_map(|o| o.commit.clone()).collect()),
+			slatepack_recipient: args.slatepack_recipient.clone(),
+			min_fee: Some(new_fee),
+			..InitTxArgs::default()
+		};
+		(tx, outputs, init_args)
+	};
+
+	// Refreshes from the node and refuses to cancel if the kernel is already on the chain
+	cancel_tx(
+		wallet_inst.clone(),
+		keychain_mask,
+		status_send_channel,
+		Some(args.tx_id),
+		None,
+	)?;
+
+	wallet_lock!(wallet_inst, w);
+	let parent_key_id = w.parent_key_id();
+
+	let res = match init_send_tx(&mut **w, keychain_mask, &init_args, use_test_rng, 1) {
+		Ok(slate) => tx_lock_outputs(
+			&mut **w,
+			keychain_mask,
+			&slate,
+			tx.address.clone(),
+			0,
+			use_test_rng,
+		)
+		.map(|_| slate),
+		Err(e) => Err(e),
+	};
+
+	match res {
+		Ok(slate) => {
+			info!(
+				"Transaction {} is reissued as slate {} with fee {}",
+				tx_id_string,
+				log_privacy::slate_id(slate.id),
+				slate.fee
+			);
+			Ok(slate)
+		}
+		Err(e) => {
+			// Inputs could be taken by another transaction while the wallet was unlocked
+			let inputs_free = outputs
+				.iter()
+				.filter(|o| o.status == OutputStatus::Locked)
+				.all(|o| match w.get(&o.key_id, &o.mmr_index) {
+					Ok(out) => out.status == OutputStatus::Unspent,
+					Err(_) => false,
+				});
+			if !inputs_free {
+				return Err(ErrorKind::GenericError(format!(
+					"Unable to reissue transaction {}, it stays cancelled because its inputs are used by another transaction, {}",
+					tx_id_string, e
+				))
+				.into());
+			}
+			let mut batch = w.batch(keychain_mask)?;
+			for o in outputs {
+				batch.save(o)?;
+			}
+			batch.save_tx_log_entry(tx, &parent_key_id)?;
+			batch.commit()?;
+			Err(ErrorKind::GenericError(format!(
+				"Unable to reissue transaction {}, the original transaction is restored, {}",
+				tx_id_string, e
+			))
+			.into())
+		}
+	}
+}
+
+/// Repost the finalized transactions that are still waiting for the confirmation, using
+/// the stored tx data. Called by the updater thread after the wallet refresh. First repost
+/// happens one interval after the transaction is found unconfirmed, every next one doubles
+/// the delay up to AUTO_REPOST_MAX_DELAY_SECS. Returns Ids of the reposted transactions.
+pub fn auto_repost_txs<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+) -> Result<Vec<u32>, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let interval = match get_auto_repost_interval() {
+		Some(i) => i,
+		None => return Ok(vec![]),
+	};
+
+	let (client, pending) = {
+		wallet_lock!(wallet_inst, w);
+		let txs =
+			updater::retrieve_txs(&mut **w, keychain_mask, None, None, None, false, None, None)?;
+		let mut pending = vec![];
+		for t in txs {
+			if t.current_status() != TxStatus::AwaitingConfirmation {
+				continue;
+			}
+			if let Some(tx) = w.get_stored_tx(&t)? {
+				let key = format!("{}/{}", t.parent_key_id.to_hex(), t.id);
+				pending.push((key, t.id, t.tx_slate_id, tx));
+			}
+		}
+		(w.w2n_client().clone(), pending)
+	};
+
+	let now = Instant::now();
+	let mut backoff = AUTO_REPOST_BACKOFF.write().unwrap();
+	// Confirmed and cancelled transactions don't need the schedule any more
+	backoff.retain(|k, _| pending.iter().any(|(key, _, _, _)| key == k));
+
+	let mut reposted = vec![];
+	for (key, tx_id, slate_id, tx) in pending {
+		let state = backoff.entry(key).or_insert(RepostBackoff {
+			attempts: 0,
+			next_attempt: now + Duration::from_secs(interval),
+		});
+		if state.next_attempt > now {
+			continue;
+		}
+		state.attempts += 1;
+		let delay = interval
+			.saturating_mul(1u64 << cmp::min(state.attempts, 32))
+			.min(AUTO_REPOST_MAX_DELAY_SECS);
+		state.next_attempt = now + Duration::from_secs(delay);
+
+		match post_tx(&client, &tx, slate_id, false) {
+			Ok(_) => {
+				reposted.push(tx_id);
+				if let Some(ref s) = status_send_channel {
+					let _ = s.send(StatusMessage::Info(format!(
+						"Unconfirmed transaction {} is reposted, attempt {}, next one in {} seconds",
+						tx_id, state.attempts, delay
+					)));
+				}
+			}
+			Err(e) => {
+				if let Some(ref s) = status_send_channel {
+					let _ = s.send(StatusMessage::Warning(format!(
+						"Unable to repost unconfirmed transaction {}, {}",
+						tx_id, e
+					)));
+				}
+			}
+		}
+	}
+	Ok(reposted)
+}
+
+/// Cancel the received transactions that are expired by the auto cancel policy
+/// `receive_expire_days`. Returns the Ids of the cancelled transactions.
+pub fn cancel_expired_receives<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+) -> Result<Vec<u32>, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	if !perform_refresh_from_node(wallet_inst.clone(), keychain_mask, status_send_channel)? {
+		return Err(ErrorKind::TransactionCancellationError(
+			"Can't contact running MWC node. Not Cancelling.",
+		))?;
+	}
+	let policy = get_auto_cancel_policy();
+	let now = Utc::now();
+
+	wallet_lock!(wallet_inst, w);
+	let parent_key_id = w.parent_key_id();
+	let expired: Vec<u32> = updater::retrieve_txs(
+		&mut **w,
+		keychain_mask,
+		None,
+		None,
+		Some(&parent_key_id),
+		false,
+		None,
+		None,
+	)?
+	.iter()
+	.filter(|tx| policy.is_receive_expired(tx, now))
+	.map(|tx| tx.id)
+	.collect();
+
+	for tx_id in &expired {
+		tx::cancel_tx(
+			&mut **w,
+			keychain_mask,
+			&parent_key_id,
+			Some(*tx_id),
+			None,
+			true,
+		)?;
+	}
+	Ok(expired)
+}
+
+/// Export unspent outputs of the wallet that the node confirms at or below the height.
+/// The node reports the current UTXO set, so outputs spent after the snapshot height are
+/// not included. Use a recent height or take the snapshot at the tip.
+pub fn utxo_snapshot<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	height: Option<u64>,
+) -> Result<UtxoSnapshot, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	// (commit, value, account, is_coinbase, wallet height)
+	let mut wallet_outputs: Vec<(pedersen::Commitment, u64, String, bool, u64)> = vec![];
+	let client = {
+		wallet_lock!(wallet_inst, w);
+		let keychain = w.keychain(keychain_mask)?;
+		let accounts: HashMap<Identifier, String> = w
+			.acct_path_iter()
+			.map(|a| (a.path.clone(), a.label.clone()))
+			.collect();
+		for out in w.iter() {
+			if out.status != OutputStatus::Unspent && out.status != OutputStatus::Locked {
+				continue;
+			}
+			let commit = match &out.commit {
+				Some(c) => pedersen::Commitment::from_vec(util::from_hex(c).map_err(|e| {
+					ErrorKind::GenericError(format!("Unable to parse HEX commit {}, {}", c, e))
+				})?),
+				None => keychain.commit(out.value, &out.key_id, SwitchCommitmentType::Regular)?,
+			};
+			let account = accounts
+				.get(&out.root_key_id)
+				.cloned()
+				.unwrap_or(out.root_key_id.to_hex());
+			wallet_outputs.push((commit, out.value, account, out.is_coinbase, out.height));
 		}
-
-		proof.store_tx_proof(w.get_data_file_dir(), &slate.id.to_string())?;
+		w.w2n_client().clone()
 	};
 
-	Ok((sl, context))
+	let (tip_height, _, _) = client.get_chain_tip()?;
+	let height = height.unwrap_or(tip_height);
+	if height > tip_height {
+		return Err(ErrorKind::GenericError(format!(
+			"Snapshot height {} is above the chain tip {}",
+			height, tip_height
+		))
+		.into());
+	}
+	let header = client.get_header_info(height)?;
+
+	let commits: Vec<pedersen::Commitment> = wallet_outputs.iter().map(|o| o.0.clone()).collect();
+	let node_outputs = client.get_outputs_from_node(&commits)?;
+
+	let mut snapshot = UtxoSnapshot {
+		height,
+		header_hash: header.hash,
+		tip_height,
+		outputs: vec![],
+		total: 0,
+		missing: vec![],
+	};
+	for (commit, value, account, is_coinbase, wallet_height) in wallet_outputs {
+		match node_outputs.get(&commit) {
+			Some((commit_str, node_height, mmr_index)) => {
+				if *node_height > height {
+					continue;
+				}
+				snapshot.total += value;
+				snapshot.outputs.push(UtxoSnapshotOutput {
+					commit: commit_str.clone(),
+					value,
+					account,
+					is_coinbase,
+					height: *node_height,
+					mmr_index: *mmr_index,
+				});
+			}
+			None => {
+				if wallet_height <= height {
+					snapshot.missing.push(util::to_hex(&commit.0));
+				}
+			}
+		}
+	}
+	snapshot.outputs.sort_by_key(|o| o.mmr_index);
+	Ok(snapshot)
 }
 
-/// cancel tx
-pub fn cancel_tx<'a, L, C, K>(
+/// Remove saved transaction and swap files that are not needed any more. Saved transactions
+/// of the active transactions and swap trades in progress are never touched.
+pub fn gc_files<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
 	keychain_mask: Option<&SecretKey>,
-	status_send_channel: &Option<Sender<StatusMessage>>,
-	tx_id: Option<u32>,
-	tx_slate_id: Option<Uuid>,
-) -> Result<(), Error>
+	args: &GcArgs,
+) -> Result<GcResult, Error>
 where
 	L: WalletLCProvider<'a, C, K>,
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
-	if !perform_refresh_from_node(wallet_inst.clone(), keychain_mask, status_send_channel)? {
-		return Err(ErrorKind::TransactionCancellationError(
-			"Can't contact running MWC node. Not Cancelling.",
-		))?;
+	let older_than = SystemTime::now()
+		.checked_sub(Duration::from_secs(args.retention_days * 24 * 3600))
+		.unwrap_or(SystemTime::UNIX_EPOCH);
+	let mut result = GcResult::default();
+
+	{
+		wallet_lock!(wallet_inst, w);
+
+		// Saved transactions are referenced from the transaction log
+		let mut referenced: HashSet<String> = HashSet::new();
+		let mut finished: Vec<TxLogEntry> = vec![];
+		for tx in w.tx_log_iter() {
+			if let Some(file) = &tx.stored_tx {
+				let is_finished = tx.confirmed
+					|| tx.tx_type == TxLogEntryType::TxSentCancelled
+					|| tx.tx_type == TxLogEntryType::TxReceivedCancelled;
+				if args.finished_txs && is_finished {
+					finished.push(tx.clone());
+				} else {
+					referenced.insert(file.clone());
+				}
+			}
+		}
+
+		for (name, modified, size) in w.stored_tx_files()? {
+			if referenced.contains(&name) || modified > older_than {
+				continue;
+			}
+			if !args.dry_run {
+				w.delete_stored_tx_file(&name)?;
+			}
+			result.tx_files.push(name);
+			result.bytes += size;
+		}
+
+		if !args.dry_run {
+			let mut batch = w.batch(keychain_mask)?;
+			for mut tx in finished {
+				if result.tx_files.contains(tx.stored_tx.as_ref().unwrap()) {
+					tx.stored_tx = None;
+					let parent_key_id = tx.parent_key_id.clone();
+					batch.save_tx_log_entry(tx, &parent_key_id)?;
+				}
+			}
+			batch.commit()?;
+		}
 	}
-	wallet_lock!(wallet_inst, w);
-	let parent_key_id = w.parent_key_id();
-	tx::cancel_tx(&mut **w, keychain_mask, &parent_key_id, tx_id, tx_slate_id)
+
+	// Swaps in progress are stored in the '.swap' files, they are never collected
+	for (path, size) in trades::list_swap_garbage(older_than)? {
+		if !args.dry_run {
+			std::fs::remove_file(&path).map_err(|e| {
+				ErrorKind::GenericError(format!("Unable to remove {}, {}", path.display(), e))
+			})?;
+		}
+		let name = path
+			.file_name()
+			.and_then(|n| n.to_str())
+			.unwrap_or("?")
+			.to_string();
+		result.swap_files.push(name);
+		result.bytes += size;
+	}
+
+	if !args.dry_run {
+		info!(
+			"Garbage collection removed {} saved transactions and {} swap files, {} bytes",
+			result.tx_files.len(),
+			result.swap_files.len(),
+			result.bytes
+		);
+	}
+	Ok(result)
 }
 
 /// get stored tx
@@ -954,9 +2548,69 @@ where
 	w.load_stored_tx(file)
 }
 
+/// Rebuild the response slate of the received transaction, so it can be sent to the
+/// sender again. The response is restored from the copy that is saved on receive and checked
+/// against the transaction log.
+pub fn retrieve_response_slate<'a, T: ?Sized, C, K>(
+	w: &T,
+	keychain_mask: Option<&SecretKey>,
+	tx_slate_id: &Uuid,
+) -> Result<Slate, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let tx = w
+		.tx_log_iter()
+		.find(|t| {
+			t.tx_slate_id == Some(*tx_slate_id)
+				&& (t.tx_type == TxLogEntryType::TxReceived
+					|| t.tx_type == TxLogEntryType::TxReceivedCancelled)
+		})
+		.ok_or(ErrorKind::TransactionDoesntExist(tx_slate_id.to_string()))?;
+
+	if tx.tx_type == TxLogEntryType::TxReceivedCancelled {
+		return Err(ErrorKind::GenericError(format!(
+			"Transaction {} is cancelled, its response can't be finalized",
+			tx_slate_id
+		))
+		.into());
+	}
+	if tx.confirmed {
+		return Err(ErrorKind::TransactionAlreadyConfirmed.into());
+	}
+
+	let slate = w
+		.get_stored_response_slate(&tx_slate_id.to_string())?
+		.ok_or(ErrorKind::GenericError(format!(
+			"Response slate for transaction {} is not stored. It was received by an older wallet version",
+			tx_slate_id
+		)))?;
+
+	if let Some(excess) = tx.kernel_excess {
+		let keychain = w.keychain(keychain_mask)?;
+		if slate.calc_excess(Some(&keychain))? != excess {
+			return Err(ErrorKind::GenericError(format!(
+				"Stored response slate doesn't match transaction {}",
+				tx_slate_id
+			))
+			.into());
+		}
+	}
+
+	Ok(slate)
+}
+
 /// Posts a transaction to the chain
 /// take a client impl instead of wallet so as not to have to lock the wallet
-pub fn post_tx<'a, C>(client: &C, tx: &Transaction, fluff: bool) -> Result<(), Error>
+/// slate_id - if known, it is written into the posted transactions mirror
+pub fn post_tx<'a, C>(
+	client: &C,
+	tx: &Transaction,
+	slate_id: Option<Uuid>,
+	fluff: bool,
+) -> Result<(), Error>
 where
 	C: NodeClient + 'a,
 {
@@ -970,6 +2624,8 @@ where
 			tx.hash(),
 			fluff
 		);
+		tx_hooks::run_post_post(tx);
+		tx_mirror::mirror_posted_tx(tx, slate_id);
 		Ok(())
 	}
 }
@@ -979,9 +2635,34 @@ pub fn verify_slate_messages(slate: &Slate) -> Result<(), Error> {
 	slate.verify_messages()
 }
 
+/// Check the wallet against the chain and the swap storage, repair the problems of the
+/// 'fix' categories. start_height None - check the chain from the first block.
+pub fn check<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	start_height: Option<u64>,
+	fix: &[CheckCategory],
+	status_send_channel: &Option<Sender<StatusMessage>>,
+) -> Result<CheckReport, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	check::check(
+		wallet_inst,
+		keychain_mask,
+		start_height.unwrap_or(1),
+		fix,
+		status_send_channel,
+	)
+}
+
 /// check repair
 /// Accepts a wallet inst instead of a raw wallet so it can
 /// lock as little as possible
+/// resume - continue the interrupted scan from its checkpoint. start_height of the interrupted
+/// scan is used in this case.
 pub fn scan<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
 	keychain_mask: Option<&SecretKey>,
@@ -989,6 +2670,7 @@ pub fn scan<'a, L, C, K>(
 	delete_unconfirmed: bool,
 	status_send_channel: &Option<Sender<StatusMessage>>,
 	do_full_outputs_refresh: bool,
+	resume: bool,
 ) -> Result<(), Error>
 where
 	L: WalletLCProvider<'a, C, K>,
@@ -1001,16 +2683,18 @@ where
 	}
 
 	// Checking from what point we should start scanning
-	let (tip_height, tip_hash, last_scanned_block, has_reorg) = get_last_detect_last_scanned_block(
-		wallet_inst.clone(),
-		keychain_mask,
-		status_send_channel,
-	)?;
+	let (tip_height, tip_hash, last_scanned_block, reorg_head) =
+		get_last_detect_last_scanned_block(
+			wallet_inst.clone(),
+			keychain_mask,
+			status_send_channel,
+		)?;
 
 	if tip_height == 0 {
 		return Err(ErrorKind::NodeNotReady)?;
 	}
 
+	let has_reorg = reorg_head.is_some();
 	if has_reorg {
 		info!(
 			"Wallet update will do full outputs checking because since last update reorg happend"
@@ -1022,9 +2706,17 @@ where
 		last_scanned_block.height, tip_height
 	);
 
-	let start_height = match start_height {
-		Some(h) => cmp::min(last_scanned_block.height, h),
-		None => 1,
+	let checkpoint = if resume {
+		wallet_lock!(wallet_inst, w);
+		w.scan_checkpoint()?
+	} else {
+		None
+	};
+
+	let start_height = match (checkpoint, start_height) {
+		(Some(cp), _) => cp.start_height,
+		(None, Some(h)) => cmp::min(last_scanned_block.height, h),
+		(None, None) => 1,
 	};
 
 	// First we need to get the hashes for heights... Reason, if block chain will be changed during scan, we will detect that naturally with next wallet_update.
@@ -1052,6 +2744,7 @@ where
 		status_send_channel,
 		true,
 		do_full_outputs_refresh,
+		resume,
 	)?;
 
 	wallet_lock!(wallet_inst, w);
@@ -1180,14 +2873,15 @@ where
 }
 
 // Checking if node head is fine and we can perform the scanning
-// Result: (tip_height: u64, tip_hash:String, first_block_to_scan_from: ScannedBlockInfo, is_reorg: bool)
-// is_reorg true if new need go back by the chain to perform scanning
+// Result: (tip_height: u64, tip_hash:String, first_block_to_scan_from: ScannedBlockInfo, reorg_head: Option<u64>)
+// reorg_head is the height of the last scanned block that is not on the chain any more. In this case
+// scanning need go back by the chain
 // Note: In case of error return tip 0!!!
 fn get_last_detect_last_scanned_block<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
 	keychain_mask: Option<&SecretKey>,
 	status_send_channel: &Option<Sender<StatusMessage>>,
-) -> Result<(u64, String, ScannedBlockInfo, bool), Error>
+) -> Result<(u64, String, ScannedBlockInfo, Option<u64>), Error>
 where
 	L: WalletLCProvider<'a, C, K>,
 	C: NodeClient + 'a,
@@ -1209,7 +2903,7 @@ where
 					"Unable to contact mwc-node".to_owned(),
 				));
 			}
-			return Ok((0, String::new(), ScannedBlockInfo::empty(), false));
+			return Ok((0, String::new(), ScannedBlockInfo::empty(), None));
 		}
 	};
 
@@ -1227,7 +2921,7 @@ where
 				String::from("Wallet Update is skipped, please wait for sync on node to complete or fork to resolve.")
 			));
 		}
-		return Ok((0, String::new(), ScannedBlockInfo::empty(), false));
+		return Ok((0, String::new(), ScannedBlockInfo::empty(), None));
 	}
 
 	let mut last_scanned_block = ScannedBlockInfo::empty();
@@ -1237,17 +2931,32 @@ where
 		if bl.height > tip_height {
 			continue; // Possible because of the parch (switch from branches)
 		}
-		if let Ok(hdr_info) = w.w2n_client().get_header_info(bl.height) {
-			if hdr_info.hash == bl.hash {
-				last_scanned_block = bl;
-				break;
+		// Node error is not a reorg, the update will be retried
+		let hdr_info = match w.w2n_client().get_header_info(bl.height) {
+			Ok(hdr_info) => hdr_info,
+			Err(e) => {
+				warn!("Unable to check the block at height {}, {}", bl.height, e);
+				if let Some(ref s) = status_send_channel {
+					let _ = s.send(StatusMessage::Warning(
+						"Unable to contact mwc-node".to_owned(),
+					));
+				}
+				return Ok((0, String::new(), ScannedBlockInfo::empty(), None));
 			}
+		};
+		if hdr_info.hash == bl.hash {
+			last_scanned_block = bl;
+			break;
 		}
 	}
 
-	let has_reorg = last_scanned_block.height != head_height;
+	let reorg_head = if last_scanned_block.height != head_height {
+		Some(head_height)
+	} else {
+		None
+	};
 
-	Ok((tip_height, tip_hash, last_scanned_block, has_reorg))
+	Ok((tip_height, tip_hash, last_scanned_block, reorg_head))
 }
 
 /// Experimental, wrap the entire definition of how a wallet's state is updated
@@ -1261,24 +2970,37 @@ where
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
+	// No updates in maintenance mode, the wallet data stays as it is
+	let _operation = match begin_wallet_operation() {
+		Ok(op) => op,
+		Err(_) => return Ok(false),
+	};
+
 	// Checking from what point we should start scanning
-	let (tip_height, tip_hash, last_scanned_block, has_reorg) = get_last_detect_last_scanned_block(
-		wallet_inst.clone(),
-		keychain_mask,
-		status_send_channel,
-	)?;
+	let (tip_height, tip_hash, last_scanned_block, reorg_head) =
+		get_last_detect_last_scanned_block(
+			wallet_inst.clone(),
+			keychain_mask,
+			status_send_channel,
+		)?;
 
 	if tip_height == 0 {
 		return Ok(false);
 	}
 
-	if has_reorg {
+	let has_reorg = reorg_head.is_some();
+	// Transactions from the blocks that are not on the chain any more
+	let mut rolled_back_txs = vec![];
+	if let Some(reorg_head) = reorg_head {
 		wallet_lock!(wallet_inst, w);
 		w.w2n_client().reset_cache(); // let's reset cach to be safe
 
-		info!(
-			"Wallet update will do full outputs checking because since last update reorg happend"
+		warn!(
+			"Chain reorg is detected, blocks {}-{} are replaced. Wallet update will do full outputs checking",
+			last_scanned_block.height + 1,
+			reorg_head
 		);
+		rolled_back_txs = rollback_reorg_txs(&mut **w, keychain_mask, last_scanned_block.height)?;
 	}
 
 	debug!(
@@ -1311,7 +3033,15 @@ where
 
 		while blocks.last().unwrap().height.saturating_sub(step) > last_scanned_block.height {
 			let h = blocks.last().unwrap().height.saturating_sub(step);
-			let hdr = w.w2n_client().get_header_info(h)?;
+			// The blocks are the checkpoints for the reorg detection, fewer of them is not an error.
+			// The rolled back transactions still need the scan.
+			let hdr = match w.w2n_client().get_header_info(h) {
+				Ok(hdr) => hdr,
+				Err(e) => {
+					warn!("Unable to get the block header at height {}, {}", h, e);
+					break;
+				}
+			};
 			blocks.push(ScannedBlockInfo::new(h, hdr.hash));
 			step *= 2;
 		}
@@ -1327,8 +3057,21 @@ where
 		status_send_channel,
 		show_progress,
 		has_reorg,
+		true,
 	)?;
 
+	if let Some(reorg_head) = reorg_head {
+		wallet_lock!(wallet_inst, w);
+		report_reorg(
+			&mut **w,
+			last_scanned_block.height,
+			reorg_head,
+			tip_height,
+			rolled_back_txs,
+			status_send_channel,
+		);
+	}
+
 	// Checking if tip was changed. In this case we need to retry. Retry will be handles naturally optimal
 	let mut tip_was_changed = false;
 	{
@@ -1343,6 +3086,11 @@ where
 			}
 
 			if after_tip_height == tip_height && after_tip_hash == tip_hash {
+				settle_txs(&mut **w, keychain_mask, tip_height)?;
+				// Full scan finds the historical transactions, they are not reported
+				if last_scanned_block.height > 0 && notifications::is_enabled() {
+					notify_confirmed_txs(&mut **w, last_scanned_block.height, tip_height);
+				}
 				return Ok(true);
 			} else {
 				tip_was_changed = true;
@@ -1359,6 +3107,171 @@ where
 	Ok(false)
 }
 
+// Roll back the confirmations of the transactions from the blocks above the fork height.
+// The scan validates their kernels again. Returns the rolled back transactions.
+fn rollback_reorg_txs<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	fork_height: u64,
+) -> Result<Vec<(Identifier, u32)>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let txs: Vec<TxLogEntry> = w
+		.tx_log_iter()
+		.filter(|t| t.confirmed && t.output_height > fork_height)
+		.collect();
+	if txs.is_empty() {
+		return Ok(vec![]);
+	}
+
+	let mut rolled_back = vec![];
+	let mut batch = w.batch(keychain_mask)?;
+	for mut t in txs {
+		info!(
+			"Transaction {} confirmation at height {} is rolled back by reorg",
+			t.id, t.output_height
+		);
+		t.confirmed = false;
+		rolled_back.push((t.parent_key_id.clone(), t.id));
+		let parent_key_id = t.parent_key_id.clone();
+		batch.save_tx_log_entry(t, &parent_key_id)?;
+	}
+	batch.commit()?;
+	Ok(rolled_back)
+}
+
+// Log and report the reorg after the scan repaired the wallet data. The rolled back
+// transactions that are not confirmed again are not on the chain any more.
+fn report_reorg<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	fork_height: u64,
+	reorg_head: u64,
+	tip_height: u64,
+	rolled_back_txs: Vec<(Identifier, u32)>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+) where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let unconfirmed_txs: Vec<u32> = w
+		.tx_log_iter()
+		.filter(|t| !t.confirmed && rolled_back_txs.contains(&(t.parent_key_id.clone(), t.id)))
+		.map(|t| t.id)
+		.collect();
+
+	let msg = format!(
+		"Chain reorg from height {}, the wallet is repaired. {} transaction(s) were rechecked, not confirmed any more: {:?}",
+		fork_height + 1,
+		rolled_back_txs.len(),
+		unconfirmed_txs
+	);
+	warn!("{}", msg);
+	if let Some(ref s) = status_send_channel {
+		let _ = s.send(StatusMessage::Warning(msg));
+	}
+
+	notifications::notify(WalletEvent::ChainReorg {
+		fork_height,
+		prev_height: reorg_head,
+		tip_height,
+		rolled_back_txs: rolled_back_txs.into_iter().map(|(_, id)| id).collect(),
+		unconfirmed_txs,
+	});
+}
+
+// Report the transactions that reached the notification confirmations number between
+// the previously scanned height and the tip
+fn notify_confirmed_txs<'a, T: ?Sized, C, K>(w: &mut T, prev_height: u64, tip_height: u64)
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let confirmations = notifications::get_notify_confirmations();
+	for t in w
+		.tx_log_iter()
+		.filter(|t| t.confirmed && t.output_height > 0)
+	{
+		if t.confirmations_at(prev_height) < confirmations
+			&& t.confirmations_at(tip_height) >= confirmations
+		{
+			notifications::notify(WalletEvent::TxConfirmed {
+				tx_id: t.id,
+				slate_id: t.tx_slate_id.map(|id| id.to_string()),
+				tx_type: format!("{:?}", t.tx_type),
+				amount_credited: t.amount_credited,
+				amount_debited: t.amount_debited,
+				height: t.output_height,
+				confirmations: t.confirmations_at(tip_height),
+			});
+		}
+	}
+}
+
+// Move the confirmed transactions that reached their confirmation targets into Settled state
+// and report them
+fn settle_txs<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	tip_height: u64,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let mut settled = vec![];
+	for mut t in w.tx_log_iter().filter(|t| t.confirmation_target.is_some()) {
+		if let Some(event) = settle_tx(&mut t, tip_height) {
+			settled.push((t, event));
+		}
+	}
+	if settled.is_empty() {
+		return Ok(());
+	}
+
+	let mut batch = w.batch(keychain_mask)?;
+	for (t, _) in &settled {
+		batch.save_tx_log_entry(t.clone(), &t.parent_key_id)?;
+	}
+	batch.commit()?;
+
+	for (t, event) in settled {
+		info!(
+			"Transaction {} is settled with {} confirmations",
+			t.id,
+			t.confirmations_at(tip_height)
+		);
+		notifications::notify(event);
+	}
+	Ok(())
+}
+
+// Settle the confirmed transaction if it reached the confirmation target at the height.
+// Returns the event to report.
+fn settle_tx(t: &mut TxLogEntry, height: u64) -> Option<WalletEvent> {
+	let target = t.confirmation_target?;
+	let confirmations = t.confirmations_at(height);
+	if t.current_status() != TxStatus::Confirmed || confirmations < target {
+		return None;
+	}
+	t.status = TxStatus::Settled;
+	Some(WalletEvent::TxSettled {
+		tx_id: t.id,
+		slate_id: t.tx_slate_id.map(|id| id.to_string()),
+		tx_type: format!("{:?}", t.tx_type),
+		amount_credited: t.amount_credited,
+		amount_debited: t.amount_debited,
+		height: t.output_height,
+		confirmations,
+		target,
+	})
+}
+
 /// Check TTL
 pub fn check_ttl<'a, T: ?Sized, C, K>(
 	w: &mut T,
@@ -1385,6 +3298,61 @@ where
 	Ok(())
 }
 
+/// Look up the transaction kernel on the node by the kernel excess (hex). The result has the
+/// inclusion height and the number of the confirmations, and the transaction of the current
+/// account with this kernel if the wallet has it.
+pub fn lookup_kernel<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	excess: &str,
+) -> Result<KernelLookupResult, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let excess = excess.trim().to_lowercase();
+	let commit = match util::from_hex(&excess) {
+		Ok(bytes) if bytes.len() == PEDERSEN_COMMITMENT_SIZE => {
+			pedersen::Commitment::from_vec(bytes)
+		}
+		_ => {
+			return Err(ErrorKind::GenericError(format!(
+				"Invalid kernel excess '{}', expected {} bytes in hex",
+				excess, PEDERSEN_COMMITMENT_SIZE
+			))
+			.into())
+		}
+	};
+
+	let (client, tx_id) = {
+		wallet_lock!(wallet_inst, w);
+		// the keychain isn't used, but the caller must have the valid mask
+		w.keychain(keychain_mask)?;
+		let parent_key_id = w.parent_key_id();
+		let tx_id = w
+			.tx_log_iter()
+			.find(|t| t.parent_key_id == parent_key_id && t.kernel_excess == Some(commit))
+			.map(|t| t.id);
+		(w.w2n_client().clone(), tx_id)
+	};
+
+	let tip_height = client.get_chain_tip()?.0;
+	let kernel = client.get_kernel(&commit, None, None)?;
+	Ok(KernelLookupResult {
+		excess,
+		on_chain: kernel.is_some(),
+		height: kernel.as_ref().map(|k| k.1),
+		mmr_index: kernel.as_ref().map(|k| k.2),
+		confirmations: match &kernel {
+			Some((_, height, _)) if tip_height >= *height => tip_height - height + 1,
+			_ => 0,
+		},
+		tip_height,
+		tx_id,
+	})
+}
+
 /// Verify/validate arbitrary payment proof
 /// Returns (whether this wallet is the sender, whether this wallet is the recipient)
 pub fn verify_payment_proof<'a, L, C, K>(
@@ -1397,55 +3365,22 @@ where
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
-	let sender_pubkey = proof.sender_address.clone().public_key;
-	let msg = tx::payment_proof_message(proof.amount, &proof.excess, sender_pubkey)?;
-
 	let (client, keychain) = {
 		wallet_lock!(wallet_inst, w);
 		(w.w2n_client().clone(), w.keychain(keychain_mask)?)
 	};
 
-	// Check kernel exists
-	match client.get_kernel(&proof.excess, None, None) {
-		Err(e) => {
-			return Err(ErrorKind::PaymentProof(format!(
-				"Error retrieving kernel from chain: {}",
-				e
-			))
-			.into());
-		}
-		Ok(None) => {
-			return Err(ErrorKind::PaymentProof(format!(
-				"Transaction kernel with excess {:?} not found on chain",
-				proof.excess
-			))
-			.into());
-		}
-		Ok(Some(_)) => {}
-	};
-
-	// Check Sigs
-	let recipient_pubkey = proof.recipient_address.public_key()?;
-	//	std::str::from_utf8(&msg).unwrap(),
-	crypto::verify_signature(
-		&msg,
-		&crypto::signature_from_string(&proof.recipient_sig).unwrap(),
-		&recipient_pubkey,
-	)
-	.map_err(|e| ErrorKind::TxProofVerifySignature(format!("{}", e)))?;
-
-	let sender_pubkey = proof.sender_address.public_key()?;
-
-	crypto::verify_signature(
-		&msg,
-		&crypto::signature_from_string(&proof.sender_sig).unwrap(),
-		&sender_pubkey,
-	)
-	.map_err(|e| ErrorKind::TxProofVerifySignature(format!("{}", e)))?;
+	payment_proof::verify_payment_proof_kernel(&client, proof)?;
+	payment_proof::verify_payment_proof_signatures(proof)?;
 
-	let my_address_pubkey = proofaddress::payment_proof_address_pubkey(&keychain)?;
-	let sender_mine = my_address_pubkey == sender_pubkey;
-	let recipient_mine = my_address_pubkey == recipient_pubkey;
+	let my_address = proofaddress::payment_proof_address(&keychain, ProofAddressType::MQS)?;
+	let my_tor_address = proofaddress::payment_proof_address(&keychain, ProofAddressType::Onion)?;
+	let is_mine = |address: &ProvableAddress| {
+		address.public_key == my_address.public_key
+			|| address.public_key == my_tor_address.public_key
+	};
+	let sender_mine = is_mine(&proof.sender_address);
+	let recipient_mine = is_mine(&proof.recipient_address);
 
 	Ok((sender_mine, recipient_mine))
 }
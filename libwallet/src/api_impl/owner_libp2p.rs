@@ -326,7 +326,7 @@ where
 		// Found that some fee we can pay now. Will process only one transaction
 
 		// We can't keep wallet locked on post. Test environment doesn't work this way
-		let tx_to_post = {
+		let (slate_id, tx_to_post) = {
 			wallet_lock!(wallet_inst.clone(), w);
 
 			if account.is_none() {
@@ -398,7 +398,7 @@ where
 
 			results[i] = (Some(integrity_context), false);
 
-			slate.tx
+			(slate.id, slate.tx)
 		};
 
 		// Posting transaction...
@@ -407,7 +407,7 @@ where
 			w.w2n_client().clone()
 		};
 
-		owner::post_tx(&client, &tx_to_post, false)?;
+		owner::post_tx(&client, &tx_to_post, Some(slate_id), false)?;
 
 		break; // Processing only one iteration at a time
 	}
@@ -470,7 +470,7 @@ where
 	let (slate, _context1) = owner::finalize_tx(&mut **w, keychain_mask, &slate, false, false)?;
 
 	// Posting transaction...
-	owner::post_tx(w.w2n_client(), &slate.tx, false)?;
+	owner::post_tx(w.w2n_client(), &slate.tx, Some(slate.id), false)?;
 
 	Ok(args.amount)
 }
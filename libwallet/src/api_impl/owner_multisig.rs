@@ -0,0 +1,671 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generic implementation of owner API multisig output functions
+
+use crate::api_impl::owner;
+use crate::api_impl::owner_swap::create_receive_tx_record;
+use crate::grin_core::libtx::{build, proof, tx_fee};
+use crate::grin_keychain::{BlindSum, BlindingFactor, Keychain};
+use crate::grin_util::secp::key::SecretKey;
+use crate::grin_util::secp::pedersen::Commitment;
+use crate::grin_util::Mutex;
+use crate::multisig::{
+	self, MultisigRecord, MultisigScheme, MultisigSecrets, MultisigSession, MultisigSpend,
+	MultisigStatus,
+};
+use crate::swap::swap::{tx_add_input, tx_add_output};
+use crate::types::NodeClient;
+use crate::{
+	wallet_lock, Error, ErrorKind, InitTxArgs, MultisigCreateArgs, Slate, WalletBackend,
+	WalletInst, WalletLCProvider,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Start a new multisig session. This wallet is the participant 0, it funds the output.
+/// The returned session document goes to the next participant.
+pub fn multisig_create<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	args: &MultisigCreateArgs,
+) -> Result<MultisigSession, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	if args.amount == 0 {
+		return Err(ErrorKind::Multisig("Multisig output amount can't be zero".to_string()).into());
+	}
+	wallet_lock!(wallet_inst, w);
+	let keychain = w.keychain(keychain_mask)?;
+
+	let init_args = InitTxArgs {
+		src_acct_name: args.src_acct_name.clone(),
+		amount: args.amount,
+		minimum_confirmations: args.minimum_confirmations,
+		..Default::default()
+	};
+	let mut slate = owner::init_send_tx(&mut **w, keychain_mask, &init_args, false, 1)?;
+	// The funder wallet and every participant of the output sign the funding transaction
+	slate.num_participants = args.scheme.participants() + 1;
+
+	let mut session = MultisigSession::new(args.scheme, args.amount, slate);
+	owner::tx_lock_outputs(
+		&mut **w,
+		keychain_mask,
+		&session.funding_slate,
+		Some(format!("multisig {}", session.id)),
+		0,
+		false,
+	)?;
+
+	let secrets = MultisigSecrets::new(&keychain, &session.id, 0)?;
+	session.join(&keychain, 0, &secrets)?;
+
+	let mut record = MultisigRecord::new(0, session);
+	multisig::store_multisig(w.get_data_file_dir(), &mut record)?;
+	Ok(record.session)
+}
+
+/// Process the session document from another participant. The wallet joins the session if it
+/// is new, adds everything it can at the current step and returns the updated document.
+pub fn multisig_process<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	session: &MultisigSession,
+) -> Result<MultisigSession, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let keychain = w.keychain(keychain_mask)?;
+	let data_dir = w.get_data_file_dir().to_string();
+
+	let (mut record, local) = match multisig::get_multisig(&data_dir, &session.id)? {
+		Some(mut record) => {
+			session.check_same(&record.session)?;
+			if status_rank(&session.status) < status_rank(&record.session.status) {
+				return Err(ErrorKind::Multisig(format!(
+					"Session {} document is outdated, it is '{}' but the wallet has it '{}'",
+					session.id, session.status, record.session.status
+				))
+				.into());
+			}
+			let local = record.session.clone();
+			record.session = with_own_data(session, &record)?;
+			(record, Some(local))
+		}
+		None => {
+			if session.status != MultisigStatus::Setup {
+				return Err(ErrorKind::Multisig(format!(
+					"Session {} is unknown and it is '{}', please restore it",
+					session.id, session.status
+				))
+				.into());
+			}
+			(
+				MultisigRecord::new(session.parties.len(), session.clone()),
+				None,
+			)
+		}
+	};
+
+	let secrets = MultisigSecrets::new(&keychain, &record.session.id, record.participant_id)?;
+	match record.session.status {
+		MultisigStatus::Setup => setup_step(
+			&mut **w,
+			keychain_mask,
+			&keychain,
+			&data_dir,
+			&mut record,
+			&secrets,
+		)?,
+		MultisigStatus::Spending => spend_step(
+			&mut **w,
+			keychain_mask,
+			&keychain,
+			&data_dir,
+			&mut record,
+			local.as_ref(),
+			&secrets,
+		)?,
+		MultisigStatus::Active | MultisigStatus::Spent => (),
+		MultisigStatus::Cancelled => {
+			return Err(
+				ErrorKind::Multisig(format!("Session {} is cancelled", record.session.id)).into(),
+			)
+		}
+	}
+
+	multisig::store_multisig(&data_dir, &mut record)?;
+	Ok(record.session)
+}
+
+/// Start spending the active multisig output into this wallet. For 2-of-3 the `cosigner` is the
+/// participant that signs together with this wallet.
+pub fn multisig_spend<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	id: &Uuid,
+	cosigner: Option<usize>,
+) -> Result<MultisigSession, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let keychain = w.keychain(keychain_mask)?;
+	let data_dir = w.get_data_file_dir().to_string();
+	let mut record = load_record(&data_dir, id)?;
+	let me = record.participant_id;
+
+	if record.session.status != MultisigStatus::Active {
+		return Err(ErrorKind::Multisig(format!(
+			"Session {} is '{}', only active output can be spent",
+			id, record.session.status
+		))
+		.into());
+	}
+	let cosigner = match (record.session.scheme, cosigner) {
+		(MultisigScheme::TwoOfTwo, None) => 1 - me,
+		(MultisigScheme::TwoOfTwo, Some(c)) if c == 1 - me => c,
+		(MultisigScheme::TwoOfThree, Some(c)) if c < 3 && c != me => c,
+		(scheme, _) => {
+			return Err(ErrorKind::Multisig(format!(
+				"Invalid co-signer for {} output, this wallet is participant {}",
+				scheme, me
+			))
+			.into())
+		}
+	};
+
+	let secrets = MultisigSecrets::new(&keychain, id, me)?;
+	let commit = verified_commit(&keychain, &record.session, me, &secrets)?;
+	let fee = tx_fee(1, 1, 1, None);
+	if record.session.amount <= fee {
+		return Err(ErrorKind::Multisig(format!(
+			"Multisig output amount {} doesn't cover the fee {}",
+			record.session.amount, fee
+		))
+		.into());
+	}
+
+	let parent_key_id = w.parent_key_id();
+	let output_key_id = w.next_child(keychain_mask, Some(parent_key_id), None)?;
+
+	let mut slate = Slate::blank(2, false);
+	slate.fee = fee;
+	slate.amount = record.session.amount - fee;
+	slate.height = w.w2n_client().get_chain_tip()?.0;
+	tx_add_input(&mut slate, commit);
+	slate.add_transaction_elements(
+		&keychain,
+		&proof::ProofBuilder::new(&keychain),
+		vec![build::output(slate.amount, output_key_id.clone())],
+	)?;
+
+	// Payee is the slate participant 0, it generates the offset
+	let mut sec_key = keychain
+		.blind_sum(
+			&BlindSum::new()
+				.add_key_id(output_key_id.to_value_path(slate.amount))
+				.sub_blinding_factor(BlindingFactor::from_secret_key(
+					record
+						.session
+						.signing_key(&keychain, me, cosigner, &secrets)?,
+				)),
+		)?
+		.secret_key()?;
+	let nonce = secrets.spend_nonce(&slate.id)?;
+	slate.fill_round_1(&keychain, &mut sec_key, &nonce, 0, None, false)?;
+
+	record.signed_spends.push(slate.id);
+	record.session.spend = Some(MultisigSpend {
+		payee: me,
+		cosigner,
+		output_key_id,
+		slate,
+	});
+	record.session.status = MultisigStatus::Spending;
+	multisig::store_multisig(&data_dir, &mut record)?;
+	Ok(record.session)
+}
+
+/// Cancel the session setup or the spending in progress
+pub fn multisig_cancel<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	id: &Uuid,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let record = {
+		wallet_lock!(wallet_inst, w);
+		w.keychain(keychain_mask)?;
+		load_record(w.get_data_file_dir(), id)?
+	};
+
+	let mut record = record;
+	match record.session.status {
+		MultisigStatus::Setup => {
+			if record.session.commit.is_some() {
+				return Err(ErrorKind::Multisig(format!(
+					"Funding transaction of the session {} is already finalized",
+					id
+				))
+				.into());
+			}
+			if record.participant_id == 0 {
+				// Unlock the funder outputs
+				owner::cancel_tx(
+					wallet_inst.clone(),
+					keychain_mask,
+					&None,
+					None,
+					Some(record.session.funding_slate.id),
+				)?;
+			}
+			record.session.status = MultisigStatus::Cancelled;
+		}
+		MultisigStatus::Spending => {
+			record.session.spend = None;
+			record.session.status = MultisigStatus::Active;
+		}
+		_ => {
+			return Err(ErrorKind::Multisig(format!(
+				"Session {} is '{}', nothing to cancel",
+				id, record.session.status
+			))
+			.into())
+		}
+	}
+
+	wallet_lock!(wallet_inst, w);
+	multisig::store_multisig(w.get_data_file_dir(), &mut record)?;
+	Ok(())
+}
+
+/// Restore the session from the document after the wallet was restored from the seed. The
+/// participant data of the document is checked against the wallet secrets.
+pub fn multisig_restore<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	session: &MultisigSession,
+	participant_id: usize,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let keychain = w.keychain(keychain_mask)?;
+	let data_dir = w.get_data_file_dir().to_string();
+
+	if multisig::get_multisig(&data_dir, &session.id)?.is_some() {
+		return Err(ErrorKind::Multisig(format!(
+			"Session {} already exists in the wallet",
+			session.id
+		))
+		.into());
+	}
+	if participant_id >= session.parties.len() {
+		return Err(ErrorKind::Multisig(format!(
+			"Participant {} is not joined the session {}",
+			participant_id, session.id
+		))
+		.into());
+	}
+	let secrets = MultisigSecrets::new(&keychain, &session.id, participant_id)?;
+	session.builder(keychain.secp(), participant_id, &secrets)?;
+
+	// The lost wallet might sign with the same nonces, they are never used again
+	let mut record = MultisigRecord::new(participant_id, session.clone());
+	record.funding_signed = true;
+	if let Some(spend) = &session.spend {
+		record.signed_spends.push(spend.slate.id);
+	}
+	multisig::store_multisig(&data_dir, &mut record)?;
+	Ok(())
+}
+
+/// List the multisig sessions of the wallet
+pub fn multisig_list<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<Vec<MultisigRecord>, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	w.keychain(keychain_mask)?;
+	multisig::list_multisig(w.get_data_file_dir())
+}
+
+/// Get the multisig session of the wallet
+pub fn multisig_get<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	id: &Uuid,
+) -> Result<MultisigRecord, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	w.keychain(keychain_mask)?;
+	load_record(w.get_data_file_dir(), id)
+}
+
+fn load_record(data_dir: &str, id: &Uuid) -> Result<MultisigRecord, Error> {
+	multisig::get_multisig(data_dir, id)?
+		.ok_or(ErrorKind::Multisig(format!("Session {} is not found", id)).into())
+}
+
+// The session can't go back, except the spending cancel that is local
+fn status_rank(status: &MultisigStatus) -> u8 {
+	match status {
+		MultisigStatus::Setup => 0,
+		MultisigStatus::Active | MultisigStatus::Spending => 1,
+		MultisigStatus::Spent => 2,
+		MultisigStatus::Cancelled => 3,
+	}
+}
+
+// The document might come back without the data of this wallet or with the altered one. The
+// stored copy is trusted.
+fn with_own_data(
+	session: &MultisigSession,
+	record: &MultisigRecord,
+) -> Result<MultisigSession, Error> {
+	let me = record.participant_id;
+	let mut session = session.clone();
+	if let Some(own) = record.session.parties.get(me) {
+		if session.parties.len() <= me {
+			return Err(ErrorKind::Multisig(format!(
+				"Session {} document doesn't have the participant {}",
+				session.id, me
+			))
+			.into());
+		}
+		session.parties[me] = own.clone();
+	}
+	session.shares.retain(|s| s.from != me);
+	session.shares.extend(
+		record
+			.session
+			.shares
+			.iter()
+			.filter(|s| s.from == me)
+			.cloned(),
+	);
+	if record.funding_signed {
+		if let Some(own) = record.session.funding_slate.participant_with_id(me + 1) {
+			for p in session.funding_slate.participant_data.iter_mut() {
+				if p.id == own.id {
+					*p = own.clone();
+				}
+			}
+		}
+	}
+	Ok(session)
+}
+
+// Commitment of the output, it must match the participants of the session
+fn verified_commit<K: Keychain>(
+	keychain: &K,
+	session: &MultisigSession,
+	participant_id: usize,
+	secrets: &MultisigSecrets,
+) -> Result<Commitment, Error> {
+	let commit = session.commit.clone().ok_or(ErrorKind::Multisig(format!(
+		"Session {} output is not created yet",
+		session.id
+	)))?;
+	let builder = session.builder(keychain.secp(), participant_id, secrets)?;
+	if builder.commit(keychain.secp())? != commit {
+		return Err(ErrorKind::Multisig(format!(
+			"Session {} output doesn't match the participants",
+			session.id
+		))
+		.into());
+	}
+	Ok(commit)
+}
+
+fn setup_step<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	keychain: &K,
+	data_dir: &str,
+	record: &mut MultisigRecord,
+	secrets: &MultisigSecrets,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let me = record.participant_id;
+	if record.session.commit.is_none() {
+		let secp = keychain.secp();
+		let session = &mut record.session;
+		if session.parties.len() == me {
+			session.join(keychain, me, secrets)?;
+		}
+		if !session.all_joined() {
+			return Ok(());
+		}
+
+		if !session.parties[me].data.has_round_1() {
+			let mut builder = session.builder(secp, me, secrets)?;
+			builder.round_1(secp, &secrets.blind)?;
+			session.parties[me].data = builder.export()?;
+		}
+		session.add_shares(keychain, me, secrets)?;
+		if !session.funding_signed(me) {
+			if record.funding_signed {
+				return Err(ErrorKind::Multisig(format!(
+					"Funding transaction of the session {} is already signed by this wallet",
+					session.id
+				))
+				.into());
+			}
+			session.sign_funding(keychain, me, secrets)?;
+			record.funding_signed = true;
+		}
+		if session.parties.iter().all(|p| p.data.has_round_1())
+			&& !session.parties[me].data.has_round_2()
+		{
+			let mut builder = session.builder(secp, me, secrets)?;
+			builder.round_2(secp, &secrets.blind)?;
+			session.parties[me].data = builder.export()?;
+		}
+		let n = session.scheme.participants();
+		if (0..n).all(|id| session.shares_sent(id)) {
+			// The shares are checked before the output exists
+			session.combined_share(keychain, me, secrets)?;
+		}
+
+		let complete = session.parties.iter().all(|p| p.data.has_round_2())
+			&& (0..n).all(|id| session.funding_signed(id) && session.shares_sent(id));
+		if me != 0 || !complete {
+			return Ok(());
+		}
+
+		// The funder builds the output and finalizes the transaction
+		let builder = session.builder(secp, me, secrets)?;
+		let commit = builder.commit(secp)?;
+		let proof = builder.finalize(secp, &secrets.blind)?;
+		let mut slate = session.funding_slate.clone();
+		tx_add_output(&mut slate, commit.clone(), proof);
+		let (slate, _) = owner::finalize_tx(w, keychain_mask, &slate, false, false)?;
+		session.funding_slate = slate;
+		session.commit = Some(commit);
+		multisig::store_multisig(data_dir, record)?;
+	}
+
+	if me == 0 {
+		let client = w.w2n_client().clone();
+		owner::post_tx(
+			&client,
+			&record.session.funding_slate.tx,
+			Some(record.session.funding_slate.id),
+			false,
+		)?;
+		record.session.status = MultisigStatus::Active;
+	}
+	Ok(())
+}
+
+fn spend_step<'a, T: ?Sized, C, K>(
+	w: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	keychain: &K,
+	data_dir: &str,
+	record: &mut MultisigRecord,
+	local: Option<&MultisigSession>,
+	secrets: &MultisigSecrets,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let me = record.participant_id;
+	let session_id = record.session.id;
+	let spend = record
+		.session
+		.spend
+		.clone()
+		.ok_or(ErrorKind::Multisig(format!(
+			"Session {} doesn't have the spending transaction",
+			session_id
+		)))?;
+	let commit = verified_commit(keychain, &record.session, me, secrets)?;
+	let mut slate = spend.slate.clone();
+	if slate.tx.inputs_committed() != vec![commit]
+		|| slate.tx.body.outputs.len() != 1
+		|| slate.amount + slate.fee != record.session.amount
+	{
+		return Err(ErrorKind::Multisig(format!(
+			"Spending transaction of the session {} doesn't spend the output",
+			session_id
+		))
+		.into());
+	}
+	let nonce = secrets.spend_nonce(&slate.id)?;
+	let signed = |slate: &Slate, id: usize| {
+		slate
+			.participant_with_id(id)
+			.map(|p| p.part_sig.is_some())
+			.unwrap_or(false)
+	};
+
+	if me == spend.cosigner {
+		if signed(&slate, 1) {
+			return Ok(());
+		}
+		if record.signed_spends.contains(&slate.id) {
+			return Err(ErrorKind::Multisig(format!(
+				"Spending transaction {} is already signed by this wallet",
+				slate.id
+			))
+			.into());
+		}
+		let mut sec_key = keychain
+			.blind_sum(&BlindSum::new().sub_blinding_factor(
+				BlindingFactor::from_secret_key(record.session.signing_key(
+					keychain,
+					me,
+					spend.payee,
+					secrets,
+				)?),
+			))?
+			.secret_key()?;
+		slate.fill_round_1(keychain, &mut sec_key, &nonce, 1, None, false)?;
+		slate.fill_round_2(keychain.secp(), &sec_key, &nonce, 1)?;
+		record.signed_spends.push(slate.id);
+	} else if me == spend.payee {
+		if !signed(&slate, 1) {
+			return Ok(());
+		}
+		if !signed(&slate, 0) {
+			// Only the spending that this wallet started, the output must be the same
+			let own = local
+				.and_then(|s| s.spend.clone())
+				.filter(|s| s.slate.id == slate.id)
+				.ok_or(ErrorKind::Multisig(format!(
+					"Spending transaction {} is not started by this wallet",
+					slate.id
+				)))?;
+			if own.slate.tx.outputs_committed() != slate.tx.outputs_committed()
+				|| own.slate.fee != slate.fee
+			{
+				return Err(ErrorKind::Multisig(format!(
+					"Spending transaction {} is changed",
+					slate.id
+				))
+				.into());
+			}
+			let sec_key = keychain
+				.blind_sum(
+					&BlindSum::new()
+						.add_key_id(own.output_key_id.to_value_path(slate.amount))
+						.sub_blinding_factor(BlindingFactor::from_secret_key(
+							record
+								.session
+								.signing_key(keychain, me, spend.cosigner, secrets)?,
+						))
+						.sub_blinding_factor(slate.tx.offset.clone()),
+				)?
+				.secret_key()?;
+			slate.fill_round_2(keychain.secp(), &sec_key, &nonce, 0)?;
+			slate.finalize(keychain)?;
+			create_receive_tx_record(
+				w,
+				keychain_mask,
+				&slate,
+				format!("multisig {}", session_id),
+				&own.output_key_id.parent_path(),
+				&own.output_key_id,
+			)?;
+			record.session.spend = Some(MultisigSpend {
+				slate: slate.clone(),
+				..own
+			});
+			multisig::store_multisig(data_dir, record)?;
+		}
+		let client = w.w2n_client().clone();
+		owner::post_tx(&client, &slate.tx, Some(slate.id), false)?;
+		record.session.status = MultisigStatus::Spent;
+		return Ok(());
+	} else {
+		return Ok(());
+	}
+
+	record.session.spend = Some(MultisigSpend { slate, ..spend });
+	Ok(())
+}
@@ -23,18 +23,25 @@ use crate::grin_keychain::ExtKeychainPath;
 use crate::grin_keychain::{Identifier, Keychain, SwitchCommitmentType};
 use crate::grin_util::to_hex;
 use crate::internal::selection;
+use crate::notifications::{self, WalletEvent};
+use crate::swap::bitcoin::{
+	self, BtcNodeClient, ElectrumNodeClient, SecondaryAddress, SweptAddress,
+	WALLET_SECONDARY_ADDRESS,
+};
 use crate::swap::error::ErrorKind;
 use crate::swap::fsm::state::{Input, StateEtaInfo, StateId, StateProcessRespond};
+use crate::swap::history::{self, SwapHistoryRecord};
 use crate::swap::message::{Message, SecondaryUpdate, Update};
-use crate::swap::swap::{Swap, SwapJournalRecord};
+use crate::swap::swap::{self, Swap, SwapJournalRecord};
 use crate::swap::types::{Action, Currency, Network, Role, SwapTransactionsConfirmations};
 use crate::swap::{trades, BuyApi, Context, SwapApi};
 use crate::types::NodeClient;
 use crate::{get_receive_account, owner_eth, Error};
 use crate::{
 	wallet_lock, OutputData, OutputStatus, Slate, SwapStartArgs, TxLogEntry, TxLogEntryType,
-	WalletBackend, WalletInst, WalletLCProvider,
+	TxStatus, WalletBackend, WalletInst, WalletLCProvider,
 };
+use chrono::Duration;
 use serde_json::json;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
@@ -66,7 +73,7 @@ pub fn remove_published_offer(message_uuid: &Uuid) {
 		.retain(|_k, v| v != message_uuid);
 }
 
-fn get_swap_storage_key<K: Keychain>(keychain: &K) -> Result<SecretKey, Error> {
+pub(crate) fn get_swap_storage_key<K: Keychain>(keychain: &K) -> Result<SecretKey, Error> {
 	Ok(keychain.derive_key(
 		0,
 		&ExtKeychainPath::new(3, 3, 2, 1, 0).to_identifier(),
@@ -74,6 +81,56 @@ fn get_swap_storage_key<K: Keychain>(keychain: &K) -> Result<SecretKey, Error> {
 	)?)
 }
 
+/// Cancel the seller offer that is not accepted before it expires. Nothing is locked at this stage,
+/// so the trade goes to the final state without the swap API. Return true if the trade is cancelled.
+fn cancel_expired_offer(swap: &mut Swap) -> bool {
+	match swap.state {
+		StateId::SellerOfferCreated
+		| StateId::SellerSendingOffer
+		| StateId::SellerWaitingForAcceptanceMessage => (),
+		_ => return false,
+	}
+	if swap.redeem_public.is_some() || swap::get_cur_time() <= swap.get_time_offer_expiration() {
+		return false;
+	}
+	let msg = swap.offer_expired_message();
+	swap.add_journal_message(msg);
+	swap.state = StateId::SellerCancelled;
+	true
+}
+
+// Secondary address of the trade. 'wallet' value is replaced with a new address of the wallet.
+fn resolve_secondary_address<K: Keychain>(
+	keychain: &K,
+	currency: &Currency,
+	address: String,
+) -> Result<String, Error> {
+	if address == WALLET_SECONDARY_ADDRESS {
+		Ok(bitcoin::new_secondary_address(keychain, currency)?.address)
+	} else {
+		Ok(address)
+	}
+}
+
+/// MWC amount of the active trades where the wallet is the seller. The funds are reserved for
+/// the lock transaction or are already in the multisig output.
+pub fn swap_committed_amount<K: Keychain>(keychain: &K) -> Result<u64, Error> {
+	let skey = get_swap_storage_key(keychain)?;
+	let mut committed = 0;
+	for sw_id in trades::list_swap_trades()? {
+		let swap_lock = trades::get_swap_lock(&sw_id);
+		let _l = swap_lock.lock();
+		let (context, mut swap) = trades::get_swap_trade(sw_id.as_str(), &skey, &*swap_lock)?;
+		if cancel_expired_offer(&mut swap) {
+			trades::store_swap_trade(&context, &swap, &skey, &*swap_lock)?;
+		}
+		if swap.is_seller() && !swap.state.is_final_state() {
+			committed += swap.primary_amount;
+		}
+	}
+	Ok(committed)
+}
+
 /// Start swap trade process. Return SwapID that can be used to check the status or perform further action.
 pub fn swap_start<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
@@ -130,7 +187,11 @@ where
 		for sw_id in &swap_id {
 			let swap_lock = trades::get_swap_lock(sw_id);
 			let _l = swap_lock.lock();
-			let (_, swap) = trades::get_swap_trade(sw_id.as_str(), &skey, &*swap_lock)?;
+			let (context, mut swap) = trades::get_swap_trade(sw_id.as_str(), &skey, &*swap_lock)?;
+			// Expired offer doesn't reserve the outputs any more
+			if cancel_expired_offer(&mut swap) {
+				trades::store_swap_trade(&context, &swap, &skey, &*swap_lock)?;
+			}
 
 			if swap.is_seller() && !swap.state.is_final_state() {
 				// Check if funds are not locked yet
@@ -203,7 +264,7 @@ where
 	swap_api.test_client_connections()?;
 
 	let parent_key_id = w.parent_key_id(); // account is current one
-	let (outputs, total, amount, fee) = if !(params.dry_run && params.mwc_amount == 0) {
+	let (outputs, total, amount, fee, _) = if !(params.dry_run && params.mwc_amount == 0) {
 		crate::internal::selection::select_coins_and_fee(
 			&mut **w,
 			params.mwc_amount,
@@ -212,6 +273,7 @@ where
 			params.minimum_confirmations.unwrap_or(10),
 			500,
 			1,
+			&crate::internal::selection::ChangePolicy::default(),
 			false,
 			&parent_key_id,
 			&Some(outputs), // outputs to include into the transaction
@@ -221,7 +283,7 @@ where
 		)?
 	} else {
 		// dry run with no amount. It is possible for Buy offer validation
-		(vec![], 0, 0, 0)
+		(vec![], 0, 0, 0, 1)
 	};
 
 	let context = create_context(
@@ -241,13 +303,19 @@ where
 		total - amount - fee,
 	)?;
 
+	let secondary_redeem_address = resolve_secondary_address(
+		&keychain,
+		&secondary_currency,
+		params.secondary_redeem_address.clone(),
+	)?;
+
 	let mut swap = (*swap_api).create_swap_offer(
 		&keychain,
 		&context,
 		params.mwc_amount, // mwc amount to sell
 		secondary_amount,  // btc amount to buy
 		secondary_currency,
-		params.secondary_redeem_address.clone(),
+		secondary_redeem_address,
 		params.seller_lock_first,
 		params.mwc_confirmations,
 		params.secondary_confirmations,
@@ -275,6 +343,20 @@ where
 	};
 
 	swap.secondary_fee = secondary_fee;
+	if params.secondary_fee.is_some() {
+		swap.secondary_fee_estimate = false;
+	}
+	swap.lock_window_sec = params.lock_window_sec.filter(|w| *w > 0);
+	if let Some(expiration) = params.offer_expiration_sec {
+		if expiration == 0 || expiration > params.message_exchange_time_sec {
+			return Err(ErrorKind::Generic(
+				"Offer expiration must be positive and not longer than the message exchange time"
+					.to_string(),
+			)
+			.into());
+		}
+		swap.offer_expires_at = Some(swap.started + Duration::seconds(expiration as i64));
+	}
 	if secondary_fee <= 0.0 {
 		return Err(ErrorKind::Generic("Invalid secondary transaction fee".to_string()).into());
 	}
@@ -375,6 +457,7 @@ where
 		let (context, mut swap) = trades::get_swap_trade(sw_id.as_str(), &skey, &*swap_lock)?;
 		let trade_start_time = swap.started.timestamp();
 		swap.wait_for_backup1 = true; // always waiting because moving forward it is not a swap list task
+		cancel_expired_offer(&mut swap);
 
 		if do_check && !swap.state.is_final_state() {
 			let (state, action, expiration) = match update_swap_status_action_impl(
@@ -462,6 +545,104 @@ where
 	Ok(())
 }
 
+/// History of the finished trades. The finished trades that are not archived yet, for example
+/// the ones finished by the older wallet version, are archived first.
+pub fn swap_history<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<Vec<SwapHistoryRecord>, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let keychain = w.keychain(keychain_mask)?;
+	let skey = get_swap_storage_key(&keychain)?;
+
+	for swap_id in trades::list_swap_trades()? {
+		let swap_lock = trades::get_swap_lock(&swap_id);
+		let _l = swap_lock.lock();
+		let (_, swap) = trades::get_swap_trade(&swap_id, &skey, &*swap_lock)?;
+		history::archive_swap_trade(&swap, &skey)?;
+	}
+	trades::archive_deleted_trades(&skey)?;
+
+	Ok(history::list_swap_history(&skey)?)
+}
+
+/// Generate a new secondary currency address of the wallet
+pub fn swap_new_secondary_address<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	currency: &str,
+) -> Result<SecondaryAddress, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let keychain = w.keychain(keychain_mask)?;
+	let currency = Currency::try_from(currency)?;
+	Ok(bitcoin::new_secondary_address(&keychain, &currency)?)
+}
+
+/// List the generated secondary currency addresses, all or for the currency
+pub fn swap_list_secondary_addresses(
+	currency: Option<String>,
+) -> Result<Vec<SecondaryAddress>, Error> {
+	let currency = match currency {
+		Some(c) => Some(Currency::try_from(c.as_str())?),
+		None => None,
+	};
+	Ok(bitcoin::list_secondary_addresses(currency)?)
+}
+
+/// Sweep the confirmed funds of the secondary currency addresses of the wallet to the
+/// destination address. With post_tx false the transactions are built but not published.
+pub fn swap_sweep_secondary_addresses<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	currency: &str,
+	destination: &String,
+	secondary_fee: Option<f32>,
+	electrum_node_uri1: Option<String>,
+	electrum_node_uri2: Option<String>,
+	post_tx: bool,
+) -> Result<Vec<SweptAddress>, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let keychain = w.keychain(keychain_mask)?;
+	let currency = Currency::try_from(currency)?;
+	if !currency.is_btc_family() {
+		return Err(ErrorKind::InvalidCurrency(format!(
+			"{} addresses can't be generated by the wallet",
+			currency
+		))
+		.into());
+	}
+	let fee = match secondary_fee {
+		Some(fee) => fee,
+		None => currency.get_default_fee(&Network::from_chain_type(global::get_chain_type())?),
+	};
+	let (uri1, _) = trades::get_electrumx_uri(&currency, &electrum_node_uri1, &electrum_node_uri2)?;
+	let mut client =
+		ElectrumNodeClient::new(uri1, currency.get_block1_tx_hash(!global::is_mainnet()));
+	Ok(bitcoin::sweep_secondary_addresses(
+		&keychain,
+		&currency,
+		&mut client,
+		destination,
+		fee,
+		post_tx,
+	)?)
+}
+
 /// Get a Swap kernel object.
 pub fn swap_get<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
@@ -497,6 +678,7 @@ pub fn swap_adjust<'a, L, C, K>(
 	electrum_node_uri2: Option<String>,
 	eth_infura_project_id: Option<String>,
 	tag: Option<String>,
+	lock_window_sec: Option<u64>, // 0 disables the lock window
 ) -> Result<(StateId, Action), Error>
 where
 	L: WalletLCProvider<'a, C, K>,
@@ -603,7 +785,11 @@ where
 				.into());
 			}
 
-			let secondary_address = secondary_address.unwrap();
+			let secondary_address = resolve_secondary_address(
+				&keychain,
+				&swap.secondary_currency,
+				secondary_address.unwrap(),
+			)?;
 			swap.secondary_currency
 				.validate_address(&secondary_address)?;
 
@@ -649,6 +835,7 @@ where
 			}
 
 			swap.secondary_fee = secondary_fee;
+			swap.secondary_fee_estimate = false;
 			trades::store_swap_trade(&context, &swap, &skey, &*swap_lock)?;
 			return Ok((swap.state.clone(), Action::None));
 		}
@@ -661,6 +848,25 @@ where
 			trades::store_swap_trade(&context, &swap, &skey, &*swap_lock)?;
 			return Ok((swap.state.clone(), Action::None));
 		}
+		"lock_window" => {
+			let lock_window_sec = lock_window_sec.ok_or(ErrorKind::Generic(
+				"Please define '--lock_window' value".to_string(),
+			))?;
+			if swap.other_lock_first_done {
+				return Err(ErrorKind::Generic(
+					"The lock of the other party is already confirmed".to_string(),
+				)
+				.into());
+			}
+
+			swap.lock_window_sec = if lock_window_sec > 0 {
+				Some(lock_window_sec)
+			} else {
+				None
+			};
+			trades::store_swap_trade(&context, &swap, &skey, &*swap_lock)?;
+			return Ok((swap.state.clone(), Action::None));
+		}
 		_ => (), // Nothing to do. Will continue with api construction
 	}
 
@@ -721,6 +927,19 @@ where
 
 			return Ok((swap.state.clone(), resp.action.unwrap_or(Action::None)));
 		}
+		"sweep_secondary" => {
+			// Overpaid or late deposits that redeem or refund didn't spend
+			if !swap.state.is_final_state() {
+				return Err(ErrorKind::Generic(
+					"Lock account can be swept after the trade is finished".to_string(),
+				)
+				.into());
+			}
+
+			swap_api.sweep_secondary_lock(&keychain, &context, &mut swap, true)?;
+			trades::store_swap_trade(&context, &swap, &skey, &*swap_lock)?;
+			return Ok((swap.state.clone(), Action::None));
+		}
 		adjusted_state => {
 			let state = StateId::from_cmd_str(adjusted_state)?;
 			if !fsm.has_state(&state) {
@@ -764,6 +983,30 @@ where
 	Ok(dump_res)
 }
 
+/// Export swap trade into the file. The trade is validated before the export, so the archive
+/// can be restored by the wallet with the same seed.
+pub fn swap_export_trade<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	swap_id: &str,
+	export_file_name: &str,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	wallet_lock!(wallet_inst, w);
+	let keychain = w.keychain(keychain_mask)?;
+	let skey = get_swap_storage_key(&keychain)?;
+	let swap_lock = trades::get_swap_lock(&swap_id.to_string());
+	let _l = swap_lock.lock();
+	// Context, swap and multisig data must be readable, otherwise the archive is useless
+	let _ = trades::get_swap_trade(swap_id, &skey, &*swap_lock)?;
+	trades::export_trade(swap_id, export_file_name)?;
+	Ok(())
+}
+
 /// Import swap trade from the file
 /// Return: trade SwapId
 pub fn swap_import_trade<'a, L, C, K>(
@@ -1134,6 +1377,38 @@ where
 	}
 }
 
+/// Check the ElectrumX servers that can be used for the currency: the swap specific ones and the
+/// servers from the config. Returns the server URI with its height or the connection error.
+pub fn check_electrumx_servers(
+	currency: &Currency,
+	electrum_node_uri1: &Option<String>,
+	electrum_node_uri2: &Option<String>,
+) -> Vec<(String, Result<u64, ErrorKind>)> {
+	let mut servers: Vec<String> = vec![];
+	for uri in electrum_node_uri1
+		.iter()
+		.chain(electrum_node_uri2.iter())
+		.cloned()
+		.chain(trades::get_electrumx_servers(currency))
+	{
+		if !servers.contains(&uri) {
+			servers.push(uri);
+		}
+	}
+
+	servers
+		.into_iter()
+		.map(|uri| {
+			let mut client = ElectrumNodeClient::new(
+				uri.clone(),
+				currency.get_block1_tx_hash(!global::is_mainnet()),
+			);
+			let height = client.height();
+			(uri, height)
+		})
+		.collect()
+}
+
 /// Get a status of the transactions that involved into the swap.
 pub fn get_swap_tx_tstatus<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
@@ -1244,16 +1519,21 @@ where
 {
 	if let Some(secondary_fee) = secondary_fee {
 		swap.secondary_fee = secondary_fee;
+		swap.secondary_fee_estimate = false;
 	}
 
 	if swap.is_seller() {
 		if let Some(secondary_address) = secondary_address {
+			let secondary_address =
+				resolve_secondary_address(&keychain, &swap.secondary_currency, secondary_address)?;
 			swap.secondary_currency
 				.validate_address(&secondary_address)?;
 			swap.update_secondary_address(secondary_address);
 		}
 	} else {
 		if let Some(secondary_address) = buyer_refund_address {
+			let secondary_address =
+				resolve_secondary_address(&keychain, &swap.secondary_currency, secondary_address)?;
 			swap.secondary_currency
 				.validate_address(&secondary_address)?;
 			swap.update_secondary_address(secondary_address);
@@ -1402,6 +1682,7 @@ where
 					&slate_context,
 					Some(format!("Swap {} Lock", swap.id)),
 					None,
+					TxStatus::AwaitingConfirmation,
 				)?;
 			}
 
@@ -1551,7 +1832,16 @@ where
 			Ok(respond)
 		}
 		Err(e) => {
-			swap.last_process_error = Some((swap.state.clone(), format!("{}", e)));
+			let last_process_error = Some((swap.state.clone(), format!("{}", e)));
+			// Processing is retried periodically, the same error is reported once
+			if swap.last_process_error != last_process_error {
+				notifications::notify(WalletEvent::SwapFailed {
+					swap_id: swap.id.to_string(),
+					state: format!("{:?}", swap.state),
+					error: format!("{}", e),
+				});
+			}
+			swap.last_process_error = last_process_error;
 			swap.add_journal_message(format!("Processing error: {}", e));
 			trades::store_swap_trade(&context, &swap, &skey, &*swap_lock)?;
 			Err(e)
@@ -1560,7 +1850,7 @@ where
 }
 
 // Creating Transaction and output for expected recieve slate
-fn create_receive_tx_record<'a, T: ?Sized, C, K>(
+pub(crate) fn create_receive_tx_record<'a, T: ?Sized, C, K>(
 	wallet: &mut T,
 	keychain_mask: Option<&SecretKey>,
 	slate: &Slate,
@@ -1589,6 +1879,8 @@ where
 	assert!(slate.tx.body.kernels.len() == 1);
 	t.kernel_excess = Some(slate.tx.body.kernels[0].excess);
 	t.kernel_lookup_min_height = Some(slate.height);
+	// Redeem transaction is published by the swap
+	t.status = TxStatus::AwaitingConfirmation;
 	batch.save_tx_log_entry(t, parent_key_id)?;
 
 	assert!(slate.tx.body.outputs.len() == 1);
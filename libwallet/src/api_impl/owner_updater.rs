@@ -13,6 +13,7 @@
 // limitations under the License.
 
 //! A threaded persistent Updater that can be controlled by a grin wallet
+use std::cmp;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
@@ -24,6 +25,7 @@ use crate::grin_util::secp::key::SecretKey;
 use crate::grin_util::Mutex;
 
 use crate::api_impl::owner;
+use crate::backup;
 use crate::types::NodeClient;
 use crate::Error;
 use crate::{WalletInst, WalletLCProvider};
@@ -76,7 +78,15 @@ pub fn start_updater_log_thread(
 					StatusMessage::Scanning(show_progress, s, m) => {
 						info!("{}", s);
 						if show_progress {
-							warn!("Scanning - {}% complete", m);
+							let progress = owner::get_scan_progress();
+							warn!(
+								"Scanning {} {}% complete, height {} of {}, found {} outputs",
+								scan_progress_bar(m),
+								m,
+								progress.height,
+								progress.tip_height,
+								progress.outputs_found
+							);
 						} else {
 							info!("Scanning - {}% complete", m);
 						}
@@ -102,6 +112,12 @@ pub fn start_updater_log_thread(
 	Ok(handle)
 }
 
+/// Text progress bar for the scanning percent
+fn scan_progress_bar(percent: u8) -> String {
+	let filled = cmp::min(percent as usize, 100) / 5;
+	format!("[{}{}]", "#".repeat(filled), "-".repeat(20 - filled))
+}
+
 /// Helper function that starts a simple console printing thread for updater messages
 /// Used by mwc713. This loop MUST end
 pub fn start_updater_console_thread(
@@ -156,6 +172,7 @@ where
 {
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
 	is_running: Arc<AtomicBool>,
+	scheduled_backups: Arc<AtomicBool>,
 }
 
 impl<'a, L, C, K> Updater<'a, L, C, K>
@@ -164,15 +181,18 @@ where
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
-	/// create a new updater
+	/// create a new updater. The scheduled backups are done only while
+	/// 'scheduled_backups' is set.
 	pub fn new(
 		wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
 		is_running: Arc<AtomicBool>,
+		scheduled_backups: Arc<AtomicBool>,
 	) -> Self {
 		is_running.store(false, Ordering::Relaxed);
 		Updater {
 			wallet_inst,
 			is_running,
+			scheduled_backups,
 		}
 	}
 
@@ -190,14 +210,34 @@ where
 				let w_provider = w_lock.lc_provider()?;
 				w_provider.wallet_inst().is_ok()
 			};
-			if wallet_opened {
+			// Maintenance mode, no updates until the wallet is resumed
+			let operation = match wallet_opened {
+				true => owner::begin_wallet_operation().ok(),
+				false => None,
+			};
+			if operation.is_some() {
 				// Business goes here
 				owner::update_wallet_state(
 					self.wallet_inst.clone(),
 					(&keychain_mask).as_ref(),
 					status_send_channel,
 				)?;
+				if let Err(e) = owner::auto_repost_txs(
+					self.wallet_inst.clone(),
+					(&keychain_mask).as_ref(),
+					status_send_channel,
+				) {
+					warn!("Unable to repost unconfirmed transactions, {}", e);
+				}
+				if self.scheduled_backups.load(Ordering::Relaxed) {
+					if let Err(e) =
+						backup::auto_backup(self.wallet_inst.clone(), (&keychain_mask).as_ref())
+					{
+						warn!("Unable to create the scheduled wallet backup, {}", e);
+					}
+				}
 			}
+			drop(operation);
 
 			let sec = frequency.as_secs();
 
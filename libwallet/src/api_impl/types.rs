@@ -17,10 +17,12 @@
 use crate::grin_core::libtx::secp_ser;
 use crate::grin_keychain::Identifier;
 use crate::grin_util::secp::pedersen;
+use crate::multisig::MultisigScheme;
 use crate::proof::proofaddress;
 use crate::proof::proofaddress::ProvableAddress;
 use crate::slate_versions::SlateVersion;
-use crate::types::OutputData;
+use crate::types::{OutputData, OutputStatus, TxLogEntry, TxLogEntryType, WalletInfo};
+use chrono::{DateTime, Duration, Utc};
 
 /// Send TX API Args
 // TODO: This is here to ensure the legacy V1 API remains intact
@@ -143,6 +145,16 @@ pub struct InitTxArgs {
 	pub late_lock: Option<bool>,
 	/// Minimal fee. Can be used to bump fee higher then usual value.
 	pub min_fee: Option<u64>,
+	/// Change that is smaller than this amount is added to the fee instead of creating a dust
+	/// output. Overrides the wallet `min_change_output` setting.
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub min_change_output: Option<u64>,
+	/// If set, the change is split into outputs of at least this amount, `num_change_outputs`
+	/// is the minimum. Overrides the wallet `change_split_target` setting.
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub change_split_target: Option<u64>,
 }
 
 /// Send TX API Args, for convenience functionality that inits the transaction and sends
@@ -189,6 +201,8 @@ impl Default for InitTxArgs {
 			outputs: None,
 			slatepack_recipient: None,
 			min_fee: None,
+			min_change_output: None,
+			change_split_target: None,
 		}
 	}
 }
@@ -312,6 +326,10 @@ pub struct OutputCommitMapping {
 		deserialize_with = "secp_ser::commitment_from_hex"
 	)]
 	pub commit: pedersen::Commitment,
+	/// Height at which the output becomes spendable, if it is immature or time locked
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub spendable_at_height: Option<u64>,
 }
 
 /// Node height result
@@ -326,6 +344,385 @@ pub struct NodeHeightResult {
 	pub updated_from_node: bool,
 }
 
+/// Result of the transaction kernel lookup on the node
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KernelLookupResult {
+	/// Kernel excess, hex
+	pub excess: String,
+	/// Whether the kernel is included in the chain
+	pub on_chain: bool,
+	/// Height of the block with the kernel
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	pub height: Option<u64>,
+	/// Kernel MMR index
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	pub mmr_index: Option<u64>,
+	/// Number of the confirmations, 0 if the kernel is not on the chain
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub confirmations: u64,
+	/// Chain tip height at the lookup
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub tip_height: u64,
+	/// Id of the wallet transaction with this kernel, if the wallet has it
+	pub tx_id: Option<u32>,
+}
+
+/// Policy for the automatic cancellation of the transactions that were never confirmed.
+/// It is applied on every wallet update, including the updater thread.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AutoCancelPolicy {
+	/// Cancel unconfirmed transactions after their TTL cutoff height is passed
+	pub cancel_on_ttl: bool,
+	/// Cancel unconfirmed transactions that are older than this number of hours
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub max_age_hours: Option<u64>,
+	/// Received transactions that the sender didn't finalize within this number of days are
+	/// expired. Expired receives are not counted as awaiting finalization and can be cancelled
+	/// in bulk. They are not cancelled automatically because the sender still can post them.
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub receive_expire_days: Option<u64>,
+}
+
+/// Retention policy for the garbage collection of the saved transactions and swap files
+/// in the wallet data directory
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GcArgs {
+	/// Files that were modified within this number of days are never removed
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub retention_days: u64,
+	/// Remove also saved transactions of the confirmed and cancelled transactions.
+	/// By default only the files that are not referenced by the transaction log are removed.
+	pub finished_txs: bool,
+	/// Report the files without removing them
+	pub dry_run: bool,
+}
+
+impl Default for GcArgs {
+	fn default() -> Self {
+		GcArgs {
+			retention_days: 30,
+			finished_txs: false,
+			dry_run: false,
+		}
+	}
+}
+
+/// Files removed by the garbage collection, or the files to remove for the dry run
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct GcResult {
+	/// Saved transaction file names
+	pub tx_files: Vec<String>,
+	/// Swap trade file names: deleted trades and leftovers of interrupted writes
+	pub swap_files: Vec<String>,
+	/// Total size of the files in bytes
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub bytes: u64,
+}
+
+/// Re-issue of a stuck send with a higher fee
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReissueTxArgs {
+	/// Id of the unconfirmed send transaction in the transaction log
+	pub tx_id: u32,
+	/// New fee is the original fee multiplied by this value. Must be greater than 1.0
+	pub fee_multiplier: f32,
+	/// Slate version of the new slate. Put 4 for the slatepack (compact slate)
+	pub target_slate_version: Option<u16>,
+	/// Recipient of the slatepack, the new slate is a compact slate for it
+	/// ProvableAddress has to be tor (DalekPublicKey) address
+	pub slatepack_recipient: Option<ProvableAddress>,
+	/// Reissue the finalized transaction. The original transaction can still be confirmed
+	/// if it is already in the mempool
+	#[serde(default)]
+	pub force: bool,
+}
+
+/// Local label and memo of the transaction. None keeps the current value, empty string removes it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TxLabelArgs {
+	/// Id of the transaction in the transaction log
+	pub tx_id: u32,
+	/// Short label, up to MAX_TX_LABEL_LENGTH characters
+	#[serde(default)]
+	pub label: Option<String>,
+	/// Free-form memo, up to MAX_TX_MEMO_LENGTH characters
+	#[serde(default)]
+	pub memo: Option<String>,
+}
+
+/// Freeze flag and local tags of the output. None keeps the current value.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct OutputTagArgs {
+	/// Output commitment, hex
+	pub commit: String,
+	/// Frozen output is not selected for sending unless it is listed explicitly
+	#[serde(default)]
+	pub frozen: Option<bool>,
+	/// New tags of the output, replace the current ones. Empty list removes all tags.
+	#[serde(default)]
+	pub tags: Option<Vec<String>>,
+}
+
+/// Summary info of the wallet account
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountSummaryInfo {
+	/// Account label
+	pub label: String,
+	/// Account parent key path
+	pub path: Identifier,
+	/// Summary info of the account
+	pub info: WalletInfo,
+}
+
+/// Wallet output that the node confirms as a part of the UTXO set
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UtxoSnapshotOutput {
+	/// Output commitment, hex
+	pub commit: String,
+	/// Output value in nanoMWC
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub value: u64,
+	/// Label of the account that owns the output
+	pub account: String,
+	/// Whether the output is a coinbase output
+	pub is_coinbase: bool,
+	/// Height of the block that includes the output, reported by the node
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub height: u64,
+	/// Output MMR index, reported by the node
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub mmr_index: u64,
+}
+
+/// Unspent outputs of the wallet anchored to the block at the snapshot height. Every output
+/// can be checked against the chain data with the commitment, block height and MMR index.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UtxoSnapshot {
+	/// Snapshot height
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub height: u64,
+	/// Hash of the block header at the snapshot height
+	pub header_hash: String,
+	/// Chain tip height when the snapshot was taken
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub tip_height: u64,
+	/// Outputs confirmed by the node at or below the snapshot height
+	pub outputs: Vec<UtxoSnapshotOutput>,
+	/// Total value of the outputs in nanoMWC
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub total: u64,
+	/// Commitments of the wallet unspent outputs that the node doesn't have in the UTXO set.
+	/// Non empty list means that the wallet state needs to be refreshed or repaired.
+	pub missing: Vec<String>,
+}
+
+impl Default for AutoCancelPolicy {
+	fn default() -> Self {
+		AutoCancelPolicy {
+			cancel_on_ttl: true,
+			max_age_hours: None,
+			receive_expire_days: None,
+		}
+	}
+}
+
+impl AutoCancelPolicy {
+	/// Check if the transaction is a receive that is expired at `now` by `receive_expire_days`
+	pub fn is_receive_expired(&self, tx: &TxLogEntry, now: DateTime<Utc>) -> bool {
+		tx.tx_type == TxLogEntryType::TxReceived
+			&& !tx.confirmed
+			&& self
+				.receive_expire_days
+				.map(|days| tx.creation_ts < now - Duration::days(days as i64))
+				.unwrap_or(false)
+	}
+}
+
+/// Progress of the chain scan. It is updated by the wallet update and scan, including the
+/// updater thread.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ScanProgress {
+	/// True while the scan is running
+	pub running: bool,
+	/// Percent complete
+	pub percent: u8,
+	/// Height of the last processed block or output
+	pub height: u64,
+	/// Height where the scan ends
+	pub tip_height: u64,
+	/// Number of the wallet outputs found so far
+	pub outputs_found: u64,
+}
+
+/// Consensus parameters that the wallet uses for the fee and dust calculations.
+/// Transaction fee is `max(inputs * input_weight + outputs * output_weight +
+/// kernels * kernel_weight, 1) * base_fee`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConsensusParams {
+	/// Fee per weight unit, nanoMWC
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub base_fee: u64,
+	/// Weight of the transaction input. Negative because spending reduces the UTXO set
+	pub input_weight: i64,
+	/// Weight of the transaction output
+	pub output_weight: i64,
+	/// Weight of the transaction kernel
+	pub kernel_weight: i64,
+	/// Fee of the smallest transaction (1 input, 1 output, 1 kernel). Outputs with a
+	/// smaller value are dust, spending them costs more than they are worth
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub min_tx_fee: u64,
+	/// Number of blocks before the coinbase output can be spent
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub coinbase_maturity: u64,
+	/// Last height confirmed by the wallet
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub current_height: u64,
+}
+
+/// Wallet activity for a time bucket, aggregated from the confirmed transactions
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistoryBucket {
+	/// Bucket start time, UTC timestamp in seconds
+	pub start_ts: i64,
+	/// Balance at the end of the bucket, nanoMWC
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub balance: u64,
+	/// Fees paid for the sent transactions, nanoMWC
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub fees_paid: u64,
+	/// Received amount, including coinbase rewards, nanoMWC
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub amount_in: u64,
+	/// Sent amount without the fees, nanoMWC
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub amount_out: u64,
+	/// Number of the transactions in the bucket
+	pub tx_count: u32,
+}
+
+/// Filters and pagination for the transactions query. All filters are optional, the matching
+/// transactions are sorted by the creation time, then `offset` and `limit` are applied.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TxQueryArgs {
+	/// Number of the matching transactions to skip
+	#[serde(default)]
+	pub offset: Option<u32>,
+	/// Max number of the transactions to return
+	#[serde(default)]
+	pub limit: Option<u32>,
+	/// Created at or after this time, UTC timestamp in seconds
+	#[serde(default)]
+	pub min_creation_ts: Option<i64>,
+	/// Created before this time, UTC timestamp in seconds
+	#[serde(default)]
+	pub max_creation_ts: Option<i64>,
+	/// Min transaction amount (difference between credited and debited), nanoMWC
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub min_amount: Option<u64>,
+	/// Max transaction amount (difference between credited and debited), nanoMWC
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub max_amount: Option<u64>,
+	/// Include only these transaction types
+	#[serde(default)]
+	pub tx_types: Option<Vec<TxLogEntryType>>,
+	/// Include only confirmed (true) or only unconfirmed (false) transactions
+	#[serde(default)]
+	pub confirmed: Option<bool>,
+	/// Include only transactions with the label or memo that contains this text, case insensitive
+	#[serde(default)]
+	pub label: Option<String>,
+}
+
+impl TxQueryArgs {
+	/// Check if the transaction passes the filters
+	pub fn matches(&self, tx: &TxLogEntry) -> bool {
+		let ts = tx.creation_ts.timestamp();
+		let amount = if tx.amount_credited > tx.amount_debited {
+			tx.amount_credited - tx.amount_debited
+		} else {
+			tx.amount_debited - tx.amount_credited
+		};
+		self.min_creation_ts.map(|min| ts >= min).unwrap_or(true)
+			&& self.max_creation_ts.map(|max| ts < max).unwrap_or(true)
+			&& self.min_amount.map(|min| amount >= min).unwrap_or(true)
+			&& self.max_amount.map(|max| amount <= max).unwrap_or(true)
+			&& self
+				.tx_types
+				.as_ref()
+				.map(|types| types.contains(&tx.tx_type))
+				.unwrap_or(true)
+			&& self.confirmed.map(|c| tx.confirmed == c).unwrap_or(true)
+			&& self
+				.label
+				.as_ref()
+				.map(|text| tx.has_label_text(text))
+				.unwrap_or(true)
+	}
+}
+
+/// Filters and pagination for the outputs query. All filters are optional, the matching
+/// outputs are sorted by the derivation index, then `offset` and `limit` are applied.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct OutputQueryArgs {
+	/// Number of the matching outputs to skip
+	#[serde(default)]
+	pub offset: Option<u32>,
+	/// Max number of the outputs to return
+	#[serde(default)]
+	pub limit: Option<u32>,
+	/// Created at or after this block height
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub min_height: Option<u64>,
+	/// Created at or before this block height
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub max_height: Option<u64>,
+	/// Min output value, nanoMWC
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub min_value: Option<u64>,
+	/// Max output value, nanoMWC
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default)]
+	pub max_value: Option<u64>,
+	/// Include only outputs with these statuses. None - all outputs including the spent ones
+	#[serde(default)]
+	pub status: Option<Vec<OutputStatus>>,
+	/// Include only outputs with this tag
+	#[serde(default)]
+	pub tag: Option<String>,
+	/// Include only frozen (true) or not frozen (false) outputs
+	#[serde(default)]
+	pub frozen: Option<bool>,
+}
+
+impl OutputQueryArgs {
+	/// Check if the output passes the filters
+	pub fn matches(&self, out: &OutputData) -> bool {
+		self.min_height.map(|min| out.height >= min).unwrap_or(true)
+			&& self.max_height.map(|max| out.height <= max).unwrap_or(true)
+			&& self.min_value.map(|min| out.value >= min).unwrap_or(true)
+			&& self.max_value.map(|max| out.value <= max).unwrap_or(true)
+			&& self
+				.status
+				.as_ref()
+				.map(|status| status.contains(&out.status))
+				.unwrap_or(true)
+			&& self
+				.tag
+				.as_ref()
+				.map(|tag| out.tags.contains(tag))
+				.unwrap_or(true)
+			&& self.frozen.map(|f| out.frozen == f).unwrap_or(true)
+	}
+}
+
 /// Version request result
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct VersionInfo {
@@ -357,6 +754,32 @@ pub struct PaymentProof {
 	pub sender_sig: String,
 }
 
+/// Wallet addresses at the derivation index. Every index has its own MQS and Tor proof address,
+/// a service can hand out a distinct address per customer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublicProofAddress {
+	/// Address derivation index
+	pub index: u32,
+	/// MQS address
+	pub mqs_address: ProvableAddress,
+	/// Tor address, it is the proof address for the Tor and slatepack payments
+	pub tor_address: ProvableAddress,
+}
+
+/// Start a multisig output session
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MultisigCreateArgs {
+	/// 2-of-2 or 2-of-3
+	pub scheme: MultisigScheme,
+	/// Amount of the multisig output, this wallet funds it
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub amount: u64,
+	/// Account to fund the output from, the current one if None
+	pub src_acct_name: Option<String>,
+	/// Minimum confirmation number for the inputs to spend
+	pub minimum_confirmations: u64,
+}
+
 /// Init swap operation
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SwapStartArgs {
@@ -404,4 +827,218 @@ pub struct SwapStartArgs {
 	pub dry_run: bool,
 	/// Tag for this offer. Needed for swap marketplace related offers management
 	pub tag: Option<String>,
+	/// Time in seconds for the buyer lock to be accepted by the network and then confirmed.
+	/// Applicable if the buyer locks first. If it is expired, the trade is cancelled.
+	pub lock_window_sec: Option<u64>,
+	/// Time in seconds for the buyer to accept the offer. The offer that is not accepted in time
+	/// is cancelled and its outputs can be used again. Default is the message exchange time.
+	pub offer_expiration_sec: Option<u64>,
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::grin_keychain::{ExtKeychain, Keychain};
+	use chrono::TimeZone;
+
+	fn tx(tx_type: TxLogEntryType, ts: i64, credited: u64, debited: u64) -> TxLogEntry {
+		let mut t = TxLogEntry::new(ExtKeychain::derive_key_id(2, 0, 0, 0, 0), tx_type, 0);
+		t.creation_ts = Utc.timestamp(ts, 0);
+		t.amount_credited = credited;
+		t.amount_debited = debited;
+		t
+	}
+
+	fn output(height: u64, value: u64, status: OutputStatus) -> OutputData {
+		OutputData {
+			root_key_id: ExtKeychain::derive_key_id(2, 0, 0, 0, 0),
+			key_id: ExtKeychain::derive_key_id(3, 0, 0, 1, 0),
+			n_child: 1,
+			commit: None,
+			mmr_index: None,
+			value,
+			status,
+			height,
+			lock_height: 0,
+			is_coinbase: false,
+			tx_log_entry: None,
+			frozen: false,
+			tags: vec![],
+		}
+	}
+
+	#[test]
+	fn test_tx_query_matches() {
+		let received = tx(TxLogEntryType::TxReceived, 1000, 500, 0);
+		// change back is credited, the amount is the difference
+		let sent = tx(TxLogEntryType::TxSent, 2000, 300, 1000);
+		assert!(TxQueryArgs::default().matches(&received));
+		assert!(TxQueryArgs::default().matches(&sent));
+
+		// creation time, min is inclusive, max is exclusive
+		let q = TxQueryArgs {
+			min_creation_ts: Some(1000),
+			max_creation_ts: Some(2000),
+			..Default::default()
+		};
+		assert!(q.matches(&received));
+		assert!(!q.matches(&sent));
+		let q = TxQueryArgs {
+			min_creation_ts: Some(1001),
+			..Default::default()
+		};
+		assert!(!q.matches(&received));
+		assert!(q.matches(&sent));
+
+		// amount, both bounds are inclusive
+		let q = TxQueryArgs {
+			min_amount: Some(500),
+			max_amount: Some(600),
+			..Default::default()
+		};
+		assert!(q.matches(&received));
+		assert!(!q.matches(&sent));
+		let q = TxQueryArgs {
+			min_amount: Some(700),
+			..Default::default()
+		};
+		assert!(!q.matches(&received));
+		assert!(q.matches(&sent));
+		let q = TxQueryArgs {
+			max_amount: Some(499),
+			..Default::default()
+		};
+		assert!(!q.matches(&received));
+
+		// types
+		let q = TxQueryArgs {
+			tx_types: Some(vec![
+				TxLogEntryType::TxSent,
+				TxLogEntryType::TxSentCancelled,
+			]),
+			..Default::default()
+		};
+		assert!(!q.matches(&received));
+		assert!(q.matches(&sent));
+		let q = TxQueryArgs {
+			tx_types: Some(vec![]),
+			..Default::default()
+		};
+		assert!(!q.matches(&sent));
+
+		// confirmed
+		let mut confirmed = received.clone();
+		confirmed.confirmed = true;
+		let q = TxQueryArgs {
+			confirmed: Some(true),
+			..Default::default()
+		};
+		assert!(q.matches(&confirmed));
+		assert!(!q.matches(&received));
+		let q = TxQueryArgs {
+			confirmed: Some(false),
+			..Default::default()
+		};
+		assert!(!q.matches(&confirmed));
+		assert!(q.matches(&received));
+
+		// label or memo text, case insensitive
+		let mut labeled = sent.clone();
+		labeled.label = Some("Rent".to_string());
+		labeled.memo = Some("March payment".to_string());
+		let q = |text: &str| TxQueryArgs {
+			label: Some(text.to_string()),
+			..Default::default()
+		};
+		assert!(q("rent").matches(&labeled));
+		assert!(q("MARCH").matches(&labeled));
+		assert!(!q("april").matches(&labeled));
+		assert!(!q("rent").matches(&sent));
+
+		// all filters must pass
+		let q = TxQueryArgs {
+			tx_types: Some(vec![TxLogEntryType::TxSent]),
+			min_amount: Some(1),
+			label: Some("rent".to_string()),
+			confirmed: Some(true),
+			..Default::default()
+		};
+		assert!(!q.matches(&labeled));
+		labeled.confirmed = true;
+		assert!(q.matches(&labeled));
+	}
+
+	#[test]
+	fn test_output_query_matches() {
+		let unspent = output(10, 1_000, OutputStatus::Unspent);
+		let spent = output(20, 5_000, OutputStatus::Spent);
+		assert!(OutputQueryArgs::default().matches(&unspent));
+		assert!(OutputQueryArgs::default().matches(&spent));
+
+		// height, both bounds are inclusive
+		let q = OutputQueryArgs {
+			min_height: Some(10),
+			max_height: Some(19),
+			..Default::default()
+		};
+		assert!(q.matches(&unspent));
+		assert!(!q.matches(&spent));
+		let q = OutputQueryArgs {
+			min_height: Some(11),
+			max_height: Some(20),
+			..Default::default()
+		};
+		assert!(!q.matches(&unspent));
+		assert!(q.matches(&spent));
+
+		// value, both bounds are inclusive
+		let q = OutputQueryArgs {
+			min_value: Some(1_000),
+			max_value: Some(4_999),
+			..Default::default()
+		};
+		assert!(q.matches(&unspent));
+		assert!(!q.matches(&spent));
+		let q = OutputQueryArgs {
+			min_value: Some(5_000),
+			..Default::default()
+		};
+		assert!(!q.matches(&unspent));
+		assert!(q.matches(&spent));
+
+		// status
+		let q = OutputQueryArgs {
+			status: Some(vec![OutputStatus::Unspent, OutputStatus::Locked]),
+			..Default::default()
+		};
+		assert!(q.matches(&unspent));
+		assert!(!q.matches(&spent));
+
+		// tag, exact match
+		let mut tagged = unspent.clone();
+		tagged.tags = vec!["cold".to_string(), "exchange".to_string()];
+		let q = |tag: &str| OutputQueryArgs {
+			tag: Some(tag.to_string()),
+			..Default::default()
+		};
+		assert!(q("exchange").matches(&tagged));
+		assert!(!q("exch").matches(&tagged));
+		assert!(!q("cold").matches(&unspent));
+
+		// frozen
+		let mut frozen = unspent.clone();
+		frozen.frozen = true;
+		let q = OutputQueryArgs {
+			frozen: Some(true),
+			..Default::default()
+		};
+		assert!(q.matches(&frozen));
+		assert!(!q.matches(&unspent));
+		let q = OutputQueryArgs {
+			frozen: Some(false),
+			..Default::default()
+		};
+		assert!(!q.matches(&frozen));
+		assert!(q.matches(&unspent));
+	}
 }
@@ -0,0 +1,398 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Audit log of the state changing owner API operations. Every record is a JSON line appended
+//! to the log file. Records are chained by hash: every record includes the hash of the previous
+//! one, so the modified, inserted or deleted records are detected by the verification.
+//! Truncation of the latest records can't be detected by the log itself, keep a copy of the
+//! latest hash outside if that matters.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::libtx::secp_ser;
+use crate::grin_keychain::Keychain;
+use crate::grin_util::{to_hex, Mutex};
+use crate::types::{NodeClient, OutputStatus, WalletBackend};
+use blake2_rfc::blake2b::blake2b;
+use chrono::Utc;
+use std::cell::RefCell;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::RwLock;
+
+/// Previous hash of the first record in the log
+pub const AUDIT_LOG_GENESIS_HASH: &str =
+	"0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Caller that is reported when it wasn't set for the thread
+pub const LOCAL_CALLER: &str = "local";
+
+lazy_static! {
+	/// Audit log file, None if audit is disabled
+	static ref AUDIT_LOG_FILE: RwLock<Option<String>> = RwLock::new(None);
+
+	/// Latest record of the log. Records must be appended one by one, every record depends on
+	/// the previous one, so it is the write lock as well.
+	static ref AUDIT_CHAIN_HEAD: Mutex<Option<AuditChainHead>> = Mutex::new(None);
+}
+
+// The latest record of the log file, so the file is not read for every new record
+struct AuditChainHead {
+	file: String,
+	// File size after the record, other size means the file was changed outside
+	file_len: u64,
+	seq: u64,
+	hash: String,
+}
+
+thread_local! {
+	/// Caller of the current API call. Owner API listeners are serving every request from
+	/// a separate thread, so the caller is set per thread.
+	static AUDIT_CALLER: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Set from config the audit log file. None disables the audit log
+pub fn set_audit_log_file(file: Option<String>) {
+	*AUDIT_LOG_FILE.write().unwrap() = file;
+	*AUDIT_CHAIN_HEAD.lock() = None;
+}
+
+/// Read the audit log file
+pub fn get_audit_log_file() -> Option<String> {
+	AUDIT_LOG_FILE.read().unwrap().clone()
+}
+
+/// True if the audit log is enabled
+pub fn is_enabled() -> bool {
+	AUDIT_LOG_FILE.read().unwrap().is_some()
+}
+
+/// Set the caller info for the operations that are called from this thread
+pub fn set_caller(caller: Option<String>) {
+	AUDIT_CALLER.with(|c| *c.borrow_mut() = caller);
+}
+
+/// Caller info for the operations that are called from this thread
+pub fn get_caller() -> String {
+	AUDIT_CALLER.with(|c| {
+		c.borrow()
+			.clone()
+			.unwrap_or_else(|| LOCAL_CALLER.to_string())
+	})
+}
+
+/// Balance of the active account at the moment of the audit record
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditBalance {
+	/// Account parent key path, hex
+	pub account: String,
+	/// Unspent outputs, confirmed
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub unspent: u64,
+	/// Outputs that are waiting for the confirmation
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub unconfirmed: u64,
+	/// Outputs locked by the transactions in progress
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub locked: u64,
+}
+
+impl AuditBalance {
+	/// Balance of the active account. Outputs are read from the wallet DB, no refresh.
+	pub fn from_wallet<'a, T: ?Sized, C, K>(wallet: &T) -> Self
+	where
+		T: WalletBackend<'a, C, K>,
+		C: NodeClient + 'a,
+		K: Keychain + 'a,
+	{
+		let parent_key_id = wallet.parent_key_id();
+		let mut balance = AuditBalance {
+			account: parent_key_id.to_hex(),
+			unspent: 0,
+			unconfirmed: 0,
+			locked: 0,
+		};
+		for out in wallet.iter().filter(|o| o.root_key_id == parent_key_id) {
+			match out.status {
+				OutputStatus::Unspent => balance.unspent += out.value,
+				OutputStatus::Unconfirmed => balance.unconfirmed += out.value,
+				OutputStatus::Locked => balance.locked += out.value,
+				_ => (),
+			}
+		}
+		balance
+	}
+}
+
+/// Audit log record
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+	/// Record number, starting from 0
+	pub seq: u64,
+	/// Operation time, RFC 3339
+	pub timestamp: String,
+	/// Owner API method name
+	pub operation: String,
+	/// Caller info: 'local' for the command line and the embedded usage, or the API listener,
+	/// the peer address and the request headers for the API calls
+	pub caller: String,
+	/// Operation arguments
+	pub details: String,
+	/// Error if the operation failed, None on success
+	pub error: Option<String>,
+	/// Balance before the operation, None if the wallet was not open
+	pub balance_before: Option<AuditBalance>,
+	/// Balance after the operation, None if the wallet is not open
+	pub balance_after: Option<AuditBalance>,
+	/// Hash of the previous record
+	pub prev_hash: String,
+	/// Hash of this record, calculated with the empty hash field
+	pub hash: String,
+}
+
+impl AuditRecord {
+	/// Hash of the record. The 'hash' field is not included.
+	pub fn calc_hash(&self) -> Result<String, Error> {
+		let mut r = self.clone();
+		r.hash = String::new();
+		let data = serde_json::to_vec(&r).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to serialize audit record, {}", e))
+		})?;
+		Ok(to_hex(blake2b(32, &[], &data).as_bytes()))
+	}
+}
+
+/// Write the operation into the audit log. The operation is already done, so the failures are
+/// only logged.
+pub fn record_operation(
+	operation: &str,
+	details: String,
+	error: Option<String>,
+	balance_before: Option<AuditBalance>,
+	balance_after: Option<AuditBalance>,
+) {
+	let file = match get_audit_log_file() {
+		Some(f) => f,
+		None => return,
+	};
+
+	let mut head = AUDIT_CHAIN_HEAD.lock();
+	let cached = head
+		.take()
+		.filter(|h| h.file == file && Some(h.file_len) == file_len(&file));
+	let last = match cached {
+		Some(h) => Ok(Some((h.seq, h.hash))),
+		None => last_record(&file).map(|r| r.map(|r| (r.seq, r.hash))),
+	};
+	let res = last.and_then(|last| {
+		let (seq, prev_hash) = match last {
+			Some((seq, hash)) => (seq + 1, hash),
+			None => (0, AUDIT_LOG_GENESIS_HASH.to_string()),
+		};
+		let mut record = AuditRecord {
+			seq,
+			timestamp: Utc::now().to_rfc3339(),
+			operation: operation.to_string(),
+			caller: get_caller(),
+			details,
+			error,
+			balance_before,
+			balance_after,
+			prev_hash,
+			hash: String::new(),
+		};
+		record.hash = record.calc_hash()?;
+		append_record(&file, &record)?;
+		Ok(AuditChainHead {
+			file_len: file_len(&file).unwrap_or(0),
+			file,
+			seq: record.seq,
+			hash: record.hash,
+		})
+	});
+	match res {
+		Ok(h) => *head = Some(h),
+		Err(e) => error!("Unable to write audit record for {}, {}", operation, e),
+	}
+}
+
+/// Result of the audit log verification
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditVerifyResult {
+	/// Number of the records that passed the check
+	pub valid_records: u64,
+	/// Line number (starting from 1) of the first broken record and the reason
+	pub broken_at: Option<(u64, String)>,
+}
+
+/// Read the audit log and check the hash chain
+pub fn verify_audit_log(file: &str) -> Result<AuditVerifyResult, Error> {
+	let mut res = AuditVerifyResult {
+		valid_records: 0,
+		broken_at: None,
+	};
+	let mut prev_hash = AUDIT_LOG_GENESIS_HASH.to_string();
+	for (i, line) in read_lines(file)?.into_iter().enumerate() {
+		let line_num = i as u64 + 1;
+		let record: AuditRecord = match serde_json::from_str(&line) {
+			Ok(r) => r,
+			Err(e) => {
+				res.broken_at = Some((line_num, format!("unable to parse the record, {}", e)));
+				break;
+			}
+		};
+		let reason = if record.seq != res.valid_records {
+			Some(format!(
+				"expected record {}, found {}",
+				res.valid_records, record.seq
+			))
+		} else if record.prev_hash != prev_hash {
+			Some("previous record hash doesn't match".to_string())
+		} else if record.calc_hash()? != record.hash {
+			Some("record hash doesn't match, the record is modified".to_string())
+		} else {
+			None
+		};
+		if let Some(reason) = reason {
+			res.broken_at = Some((line_num, reason));
+			break;
+		}
+		prev_hash = record.hash;
+		res.valid_records += 1;
+	}
+	Ok(res)
+}
+
+/// Read all records from the audit log. Records are not verified.
+pub fn read_audit_log(file: &str) -> Result<Vec<AuditRecord>, Error> {
+	read_lines(file)?
+		.iter()
+		.map(|line| {
+			serde_json::from_str(line).map_err(|e| {
+				ErrorKind::GenericError(format!("Unable to parse audit record, {}", e)).into()
+			})
+		})
+		.collect()
+}
+
+fn read_lines(file: &str) -> Result<Vec<String>, Error> {
+	if !Path::new(file).exists() {
+		return Ok(vec![]);
+	}
+	let f =
+		File::open(file).map_err(|e| ErrorKind::IO(format!("Unable to open {}, {}", file, e)))?;
+	let mut lines = vec![];
+	for line in BufReader::new(f).lines() {
+		let line = line.map_err(|e| ErrorKind::IO(format!("Unable to read {}, {}", file, e)))?;
+		if !line.trim().is_empty() {
+			lines.push(line);
+		}
+	}
+	Ok(lines)
+}
+
+fn last_record(file: &str) -> Result<Option<AuditRecord>, Error> {
+	match read_lines(file)?.last() {
+		Some(line) => Ok(Some(serde_json::from_str(line).map_err(|e| {
+			ErrorKind::GenericError(format!(
+				"Unable to parse the last record of {}, {}",
+				file, e
+			))
+		})?)),
+		None => Ok(None),
+	}
+}
+
+fn file_len(file: &str) -> Option<u64> {
+	fs::metadata(file).map(|m| m.len()).ok()
+}
+
+fn append_record(file: &str, record: &AuditRecord) -> Result<(), Error> {
+	let line = serde_json::to_string(record)
+		.map_err(|e| ErrorKind::GenericError(format!("Unable to serialize audit record, {}", e)))?;
+	let mut f = OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(file)
+		.map_err(|e| ErrorKind::IO(format!("Unable to open {}, {}", file, e)))?;
+	f.write_all(format!("{}\n", line).as_bytes())
+		.and_then(|_| f.sync_data())
+		.map_err(|e| ErrorKind::IO(format!("Unable to write into {}, {}", file, e)))?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_audit_log_chain() {
+		let dir = "test_output/audit_log";
+		let _ = fs::remove_dir_all(dir);
+		fs::create_dir_all(dir).unwrap();
+		let file = format!("{}/audit.log", dir);
+
+		set_audit_log_file(Some(file.clone()));
+		set_caller(Some("owner_api_v3".to_string()));
+		for i in 0..3 {
+			record_operation("init_send_tx", format!("amount: {}", i), None, None, None);
+		}
+		set_audit_log_file(None);
+		record_operation("cancel_tx", "not recorded".to_string(), None, None, None);
+
+		let records = read_audit_log(&file).unwrap();
+		assert_eq!(records.len(), 3);
+		assert_eq!(records[0].prev_hash, AUDIT_LOG_GENESIS_HASH);
+		assert_eq!(records[2].prev_hash, records[1].hash);
+		assert_eq!(records[2].caller, "owner_api_v3");
+		let res = verify_audit_log(&file).unwrap();
+		assert_eq!(res.valid_records, 3);
+		assert!(res.broken_at.is_none());
+
+		// Modified record
+		let content = fs::read_to_string(&file).unwrap();
+		fs::write(&file, content.replace("amount: 1", "amount: 9")).unwrap();
+		let res = verify_audit_log(&file).unwrap();
+		assert_eq!(res.valid_records, 1);
+		assert_eq!(res.broken_at.unwrap().0, 2);
+
+		// Deleted record
+		let lines: Vec<&str> = content.lines().collect();
+		fs::write(&file, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+		let res = verify_audit_log(&file).unwrap();
+		assert_eq!(res.valid_records, 1);
+		assert_eq!(res.broken_at.unwrap().0, 2);
+
+		// The log is changed outside, the chain continues from the latest record in the file
+		set_audit_log_file(Some(file.clone()));
+		record_operation("init_send_tx", "amount: 3".to_string(), None, None, None);
+		fs::write(&file, format!("{}\n", lines[0])).unwrap();
+		record_operation("init_send_tx", "amount: 4".to_string(), None, None, None);
+		let records = read_audit_log(&file).unwrap();
+		assert_eq!(records.len(), 2);
+		assert_eq!(records[1].seq, 1);
+		assert_eq!(verify_audit_log(&file).unwrap().valid_records, 2);
+		// A new file starts a new chain
+		fs::remove_file(&file).unwrap();
+		record_operation("init_send_tx", "amount: 5".to_string(), None, None, None);
+		let records = read_audit_log(&file).unwrap();
+		assert_eq!(records.len(), 1);
+		assert_eq!(records[0].prev_hash, AUDIT_LOG_GENESIS_HASH);
+		set_audit_log_file(None);
+
+		set_caller(None);
+		assert_eq!(get_caller(), LOCAL_CALLER);
+		let _ = fs::remove_dir_all(dir);
+	}
+}
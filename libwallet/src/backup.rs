@@ -0,0 +1,438 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encrypted backups of the wallet data. The snapshot has every file of the wallet data
+//! directory: the seed file, the wallet DB, the swap trades, the saved transactions and proofs.
+//! It is encrypted with the key derived from the wallet seed, so the snapshots can be kept on
+//! untrusted storage. The restore needs the recovery phrase.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_keychain::{ExtKeychainPath, Keychain, SwitchCommitmentType};
+use crate::grin_util::secp::key::SecretKey;
+use crate::grin_util::Mutex;
+use crate::types::{NodeClient, WalletInst, WalletLCProvider};
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use rand::{thread_rng, Rng};
+use ring::aead;
+use std::cmp;
+use std::convert::TryInto;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Default interval of the scheduled backups
+pub const DEFAULT_BACKUP_INTERVAL_HOURS: u64 = 24;
+/// Default number of the scheduled backups to keep
+pub const DEFAULT_BACKUP_KEEP: usize = 7;
+/// Failed scheduled backup is retried after this time
+const BACKUP_RETRY_HOURS: u64 = 1;
+
+/// Names of the backups are 'mwc-wallet-backup-<UTC time>.bak'
+const BACKUP_PREFIX: &str = "mwc-wallet-backup-";
+const BACKUP_SUFFIX: &str = ".bak";
+const BACKUP_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+/// Snapshot header, the version of the format
+const BACKUP_MAGIC: &[u8] = b"MWCBACKUP1";
+const NONCE_LEN: usize = 12;
+/// LMDB lock file is recreated when the DB is opened
+const SKIP_FILES: [&str; 1] = ["lock.mdb"];
+
+/// Storage of the backups: a directory or a cloud bucket
+pub trait BackupStore: Send + Sync {
+	/// Location for the messages
+	fn location(&self) -> String;
+	/// Write the backup
+	fn put(&self, name: &str, data: &[u8]) -> Result<(), Error>;
+	/// Read the backup
+	fn get(&self, name: &str) -> Result<Vec<u8>, Error>;
+	/// Names of all files in the storage
+	fn list(&self) -> Result<Vec<String>, Error>;
+	/// Delete the backup
+	fn delete(&self, name: &str) -> Result<(), Error>;
+}
+
+/// Scheduled backups
+#[derive(Clone)]
+pub struct BackupSchedule {
+	/// Storage for the backups
+	pub store: Arc<dyn BackupStore>,
+	/// Backup is done if the latest one is older than this. 0 is handled as 1 hour.
+	pub interval_hours: u64,
+	/// Older backups are deleted, 0 keeps all of them
+	pub keep: usize,
+}
+
+lazy_static! {
+	/// Scheduled backups, None - disabled
+	static ref BACKUP_SCHEDULE: RwLock<Option<BackupSchedule>> = RwLock::new(None);
+	/// Time of the latest backup in the scheduled store. None - the store wasn't checked yet
+	static ref LAST_BACKUP: RwLock<Option<DateTime<Utc>>> = RwLock::new(None);
+	/// Backups of the same wallet must not run in parallel
+	static ref BACKUP_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Set from config the scheduled backups. None disables them
+pub fn set_backup_schedule(schedule: Option<BackupSchedule>) {
+	*BACKUP_SCHEDULE.write().unwrap() = schedule;
+	// the store might be different, the latest backup will be checked again
+	*LAST_BACKUP.write().unwrap() = None;
+}
+
+/// Read the scheduled backups settings
+pub fn get_backup_schedule() -> Option<BackupSchedule> {
+	BACKUP_SCHEDULE.read().unwrap().clone()
+}
+
+/// Key of the backups encryption
+pub fn backup_key<K: Keychain>(keychain: &K) -> Result<SecretKey, Error> {
+	Ok(keychain.derive_key(
+		0,
+		&ExtKeychainPath::new(3, 3, 2, 3, 0).to_identifier(),
+		SwitchCommitmentType::None,
+	)?)
+}
+
+/// Name of the backup that is created at this time
+pub fn backup_name(time: &DateTime<Utc>) -> String {
+	format!(
+		"{}{}{}",
+		BACKUP_PREFIX,
+		time.format(BACKUP_TIME_FORMAT),
+		BACKUP_SUFFIX
+	)
+}
+
+/// Creation time of the backup, None if the name is not a backup name
+pub fn backup_time(name: &str) -> Option<DateTime<Utc>> {
+	if !name.starts_with(BACKUP_PREFIX) || !name.ends_with(BACKUP_SUFFIX) {
+		return None;
+	}
+	let time = &name[BACKUP_PREFIX.len()..name.len() - BACKUP_SUFFIX.len()];
+	NaiveDateTime::parse_from_str(time, BACKUP_TIME_FORMAT)
+		.ok()
+		.map(|t| Utc.from_utc_datetime(&t))
+}
+
+/// Backups in the store, the oldest first
+pub fn list_backups(store: &dyn BackupStore) -> Result<Vec<String>, Error> {
+	let mut backups: Vec<(DateTime<Utc>, String)> = store
+		.list()?
+		.into_iter()
+		.filter_map(|name| backup_time(&name).map(|t| (t, name)))
+		.collect();
+	backups.sort();
+	Ok(backups.into_iter().map(|(_, name)| name).collect())
+}
+
+fn collect_files(dir: &Path, rel: &str, files: &mut Vec<(String, PathBuf)>) -> Result<(), Error> {
+	let entries = fs::read_dir(dir).map_err(|e| {
+		ErrorKind::Backup(format!("Unable to read directory {}, {}", dir.display(), e))
+	})?;
+	for entry in entries {
+		let entry = entry?;
+		let file_type = entry.file_type()?;
+		let name = match entry.file_name().into_string() {
+			Ok(name) => name,
+			Err(name) => {
+				warn!("Backup skips the file with non UTF-8 name {:?}", name);
+				continue;
+			}
+		};
+		let rel_name = if rel.is_empty() {
+			name.clone()
+		} else {
+			format!("{}/{}", rel, name)
+		};
+		if file_type.is_dir() {
+			collect_files(&entry.path(), &rel_name, files)?;
+		} else if file_type.is_file()
+			&& !SKIP_FILES.contains(&name.as_str())
+			&& backup_time(&name).is_none()
+		{
+			files.push((rel_name, entry.path()));
+		}
+	}
+	Ok(())
+}
+
+/// Snapshot of the wallet data directory, encrypted with the backup key
+pub fn create_snapshot(data_file_dir: &str, key: &SecretKey) -> Result<Vec<u8>, Error> {
+	let mut files = vec![];
+	collect_files(Path::new(data_file_dir), "", &mut files)?;
+	files.sort();
+
+	// Every file: path length u32, path, data length u64, data
+	let mut data: Vec<u8> = vec![];
+	for (name, path) in &files {
+		let content = fs::read(path)
+			.map_err(|e| ErrorKind::Backup(format!("Unable to read {}, {}", path.display(), e)))?;
+		data.extend_from_slice(&(name.len() as u32).to_be_bytes());
+		data.extend_from_slice(name.as_bytes());
+		data.extend_from_slice(&(content.len() as u64).to_be_bytes());
+		data.extend_from_slice(&content);
+	}
+
+	let nonce: [u8; NONCE_LEN] = thread_rng().gen();
+	let sealing_key = aead::LessSafeKey::new(
+		aead::UnboundKey::new(&aead::AES_256_GCM, &key.0)
+			.map_err(|e| ErrorKind::Backup(format!("Unable to build a key, {}", e)))?,
+	);
+	sealing_key
+		.seal_in_place_append_tag(
+			aead::Nonce::assume_unique_for_key(nonce),
+			aead::Aad::from(BACKUP_MAGIC),
+			&mut data,
+		)
+		.map_err(|e| ErrorKind::Backup(format!("Encryption failed, {}", e)))?;
+
+	let mut snapshot = BACKUP_MAGIC.to_vec();
+	snapshot.extend_from_slice(&nonce);
+	snapshot.extend_from_slice(&data);
+	Ok(snapshot)
+}
+
+/// Decrypt the snapshot, the result is the list of the relative paths and the file data
+pub fn read_snapshot(snapshot: &[u8], key: &SecretKey) -> Result<Vec<(String, Vec<u8>)>, Error> {
+	if snapshot.len() < BACKUP_MAGIC.len() + NONCE_LEN || !snapshot.starts_with(BACKUP_MAGIC) {
+		return Err(ErrorKind::Backup("It is not a wallet backup".to_string()).into());
+	}
+	let mut nonce = [0u8; NONCE_LEN];
+	nonce.copy_from_slice(&snapshot[BACKUP_MAGIC.len()..BACKUP_MAGIC.len() + NONCE_LEN]);
+	let mut data = snapshot[BACKUP_MAGIC.len() + NONCE_LEN..].to_vec();
+	let opening_key = aead::LessSafeKey::new(
+		aead::UnboundKey::new(&aead::AES_256_GCM, &key.0)
+			.map_err(|e| ErrorKind::Backup(format!("Unable to build a key, {}", e)))?,
+	);
+	let data = opening_key
+		.open_in_place(
+			aead::Nonce::assume_unique_for_key(nonce),
+			aead::Aad::from(BACKUP_MAGIC),
+			&mut data,
+		)
+		.map_err(|_| {
+			ErrorKind::Backup(
+				"Unable to decrypt the backup, it belongs to another wallet or it is damaged"
+					.to_string(),
+			)
+		})?;
+
+	let damaged = || Error::from(ErrorKind::Backup("Backup data is damaged".to_string()));
+	let mut files = vec![];
+	let mut rest: &[u8] = data;
+	while !rest.is_empty() {
+		let len =
+			u32::from_be_bytes(rest.get(..4).ok_or_else(damaged)?.try_into().unwrap()) as usize;
+		rest = &rest[4..];
+		let name = String::from_utf8(rest.get(..len).ok_or_else(damaged)?.to_vec())
+			.map_err(|_| damaged())?;
+		rest = &rest[len..];
+		let len = u64::from_be_bytes(rest.get(..8).ok_or_else(damaged)?.try_into().unwrap());
+		if len > rest.len() as u64 - 8 {
+			return Err(damaged());
+		}
+		let content = rest[8..8 + len as usize].to_vec();
+		rest = &rest[8 + len as usize..];
+
+		// The files must stay inside the data directory
+		if name.is_empty()
+			|| !Path::new(&name).components().all(|c| match c {
+				Component::Normal(_) => true,
+				_ => false,
+			}) {
+			return Err(
+				ErrorKind::Backup(format!("Backup has invalid file name '{}'", name)).into(),
+			);
+		}
+		files.push((name, content));
+	}
+	Ok(files)
+}
+
+/// Write the snapshot files into the data directory. Returns the number of the files.
+pub fn restore_snapshot(
+	snapshot: &[u8],
+	key: &SecretKey,
+	data_file_dir: &str,
+) -> Result<usize, Error> {
+	let files = read_snapshot(snapshot, key)?;
+	for (name, content) in &files {
+		let path = Path::new(data_file_dir).join(name);
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent).map_err(|e| {
+				ErrorKind::Backup(format!(
+					"Unable to create directory {}, {}",
+					parent.display(),
+					e
+				))
+			})?;
+		}
+		fs::write(&path, content)
+			.map_err(|e| ErrorKind::Backup(format!("Unable to write {}, {}", path.display(), e)))?;
+	}
+	Ok(files.len())
+}
+
+/// Create the backup in the store and delete the old ones, so only 'keep' backups are left.
+/// keep 0 - nothing is deleted. Returns the name of the backup.
+pub fn run_backup(
+	store: &dyn BackupStore,
+	data_file_dir: &str,
+	key: &SecretKey,
+	keep: usize,
+) -> Result<String, Error> {
+	let _l = BACKUP_LOCK.lock();
+	let now = Utc::now();
+	let name = backup_name(&now);
+	let snapshot = create_snapshot(data_file_dir, key)?;
+	store.put(&name, &snapshot)?;
+
+	if keep > 0 {
+		let backups = list_backups(store)?;
+		if backups.len() > keep {
+			for old in &backups[..backups.len() - keep] {
+				if let Err(e) = store.delete(old) {
+					warn!(
+						"Unable to delete old backup {} from {}, {}",
+						old,
+						store.location(),
+						e
+					);
+				}
+			}
+		}
+	}
+	Ok(name)
+}
+
+/// Backup of the opened wallet into the store. Returns the name of the backup.
+pub fn backup_wallet<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	store: &dyn BackupStore,
+	keep: usize,
+) -> Result<String, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	// The wallet is locked for the whole backup, so the DB is not changed
+	wallet_lock!(wallet_inst, w);
+	let key = backup_key(&w.keychain(keychain_mask)?)?;
+	run_backup(store, w.get_data_file_dir(), &key, keep)
+}
+
+/// Backup from the updater thread. It is done if the latest backup in the scheduled store is
+/// older than the interval.
+pub fn auto_backup<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+) -> Result<Option<String>, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let schedule = match get_backup_schedule() {
+		Some(s) => s,
+		None => return Ok(None),
+	};
+
+	let last_backup = *LAST_BACKUP.read().unwrap();
+	let last_backup = match last_backup {
+		Some(t) => t,
+		None => {
+			let t = list_backups(&*schedule.store)?
+				.last()
+				.and_then(|name| backup_time(name))
+				.unwrap_or(Utc.timestamp(0, 0));
+			*LAST_BACKUP.write().unwrap() = Some(t);
+			t
+		}
+	};
+	let now = Utc::now();
+	let interval_hours = cmp::max(schedule.interval_hours, 1);
+	if now < last_backup + Duration::hours(interval_hours as i64) {
+		return Ok(None);
+	}
+
+	match backup_wallet(wallet_inst, keychain_mask, &*schedule.store, schedule.keep) {
+		Ok(name) => {
+			*LAST_BACKUP.write().unwrap() = Some(now);
+			info!(
+				"Wallet backup {} is created at {}",
+				name,
+				schedule.store.location()
+			);
+			Ok(Some(name))
+		}
+		Err(e) => {
+			// The next try is after the retry interval, not on every update
+			let retry = cmp::min(interval_hours, BACKUP_RETRY_HOURS);
+			*LAST_BACKUP.write().unwrap() =
+				Some(now - Duration::hours((interval_hours - retry) as i64));
+			Err(e)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn backup_snapshot() {
+		let dir = std::env::temp_dir().join(format!("backup_snapshot_{}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(dir.join("db")).unwrap();
+		fs::write(dir.join("wallet.seed"), b"seed").unwrap();
+		fs::write(dir.join("db").join("data.mdb"), b"db data").unwrap();
+		fs::write(dir.join("db").join("lock.mdb"), b"lock").unwrap();
+		fs::write(dir.join(backup_name(&Utc::now())), b"old backup").unwrap();
+
+		let key = SecretKey::from_slice(&[1; 32]).unwrap();
+		let snapshot = create_snapshot(dir.to_str().unwrap(), &key).unwrap();
+		let files = read_snapshot(&snapshot, &key).unwrap();
+		assert_eq!(
+			files,
+			vec![
+				("db/data.mdb".to_string(), b"db data".to_vec()),
+				("wallet.seed".to_string(), b"seed".to_vec()),
+			]
+		);
+
+		let other_key = SecretKey::from_slice(&[2; 32]).unwrap();
+		assert!(read_snapshot(&snapshot, &other_key).is_err());
+
+		let restore_dir = dir.join("restore");
+		assert_eq!(
+			restore_snapshot(&snapshot, &key, restore_dir.to_str().unwrap()).unwrap(),
+			2
+		);
+		assert_eq!(
+			fs::read(restore_dir.join("db").join("data.mdb")).unwrap(),
+			b"db data".to_vec()
+		);
+		fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn backup_names() {
+		let time = Utc.ymd(2021, 3, 4).and_hms(5, 6, 7);
+		let name = backup_name(&time);
+		assert_eq!(name, "mwc-wallet-backup-20210304-050607.bak");
+		assert_eq!(backup_time(&name), Some(time));
+		assert_eq!(backup_time("wallet.seed"), None);
+	}
+}
@@ -20,6 +20,7 @@ use crate::grin_keychain;
 use crate::grin_store;
 use crate::grin_util::secp;
 use crate::swap::error::ErrorKind as SwapErrorKind;
+use crate::swap::multisig::ErrorKind as MultisigErrorKind;
 use crate::util;
 use failure::{Backtrace, Context, Fail};
 use std::env;
@@ -163,6 +164,13 @@ pub enum ErrorKind {
 	#[fail(display = "Cancellation Error: {}", _0)]
 	TransactionCancellationError(&'static str),
 
+	/// Transaction kernel is already on the chain, it can't be cancelled
+	#[fail(
+		display = "Transaction {} kernel is already on the chain at height {}, it can't be cancelled. Transaction is marked as confirmed",
+		_0, _1
+	)]
+	TransactionKernelOnChain(String, u64),
+
 	/// Cancellation error
 	#[fail(display = "Tx dump Error: {}", _0)]
 	TransactionDumpError(&'static str),
@@ -202,9 +210,36 @@ pub enum ErrorKind {
 	#[fail(display = "error: default account cannot be renamed!")]
 	AccountDefaultCannotBeRenamed,
 
+	/// Default account can't be deleted
+	#[fail(display = "error: default account cannot be deleted!")]
+	AccountDefaultCannotBeDeleted,
+
+	/// Active account can't be deleted
+	#[fail(
+		display = "Account '{}' is active, switch to another account first",
+		_0
+	)]
+	AccountIsActive(String),
+
+	/// Account still has the funds
+	#[fail(
+		display = "Account '{}' has {} not spent outputs, move the funds to another account first",
+		_0, _1
+	)]
+	AccountNotEmpty(String, usize),
+
 	/// Reference unknown account label
 	#[fail(display = "Unknown Account Label '{}'", _0)]
 	UnknownAccountLabel(String),
+	/// Invalid address book contact data
+	#[fail(display = "Invalid contact, {}", _0)]
+	InvalidContact(String),
+	/// Attempt to add a contact that exists
+	#[fail(display = "Contact '{}' already exists", _0)]
+	ContactAlreadyExists(String),
+	/// Reference unknown contact
+	#[fail(display = "Contact '{}' not found", _0)]
+	ContactNotFound(String),
 
 	/// Error from summing commitments via committed trait.
 	#[fail(display = "Committed Error, {}", _0)]
@@ -226,6 +261,10 @@ pub enum ErrorKind {
 	#[fail(display = "Unknown Slate Version: {}", _0)]
 	SlateVersion(u16),
 
+	/// Slate or slatepack is produced by grin wallet and can't be processed by MWC wallet
+	#[fail(display = "Grin slate is not compatible with MWC wallet, {}", _0)]
+	GrinSlateIncompatible(String),
+
 	/// Slate Validation error
 	#[fail(display = "Unable to validate slate, {}", _0)]
 	SlateValidation(String),
@@ -274,6 +313,10 @@ pub enum ErrorKind {
 	#[fail(display = "Proof Address decoding: {}", _0)]
 	AddressDecoding(String),
 
+	/// Unable to parse mwc: payment URI
+	#[fail(display = "Invalid payment URI, {}", _0)]
+	PaymentUri(String),
+
 	/// Transaction has expired it's TTL
 	#[fail(display = "Transaction Expired")]
 	TransactionExpired,
@@ -355,6 +398,53 @@ pub enum ErrorKind {
 	/// Ethereum Wallet Error
 	#[fail(display = "Ethereum wallet error, {}", _0)]
 	EthereumWalletError(String),
+
+	/// Wallet is in maintenance mode
+	#[fail(display = "Wallet is paused for maintenance, please retry later")]
+	WalletPaused,
+
+	/// Incoming slate or message exceeds the configured limits (name, value, limit)
+	#[fail(
+		display = "Incoming slate is too large or complex, {} is {}, the limit is {}",
+		_0, _1, _2
+	)]
+	SlateLimitExceeded(String, usize, usize),
+
+	/// Transaction is rejected by the hook command
+	#[fail(display = "Transaction is rejected by the {} hook, {}", _0, _1)]
+	TxHookRejected(String, String),
+
+	/// Multisig output error
+	#[fail(display = "Multisig error, {}", _0)]
+	Multisig(String),
+
+	/// Wallet backup error
+	#[fail(display = "Backup error, {}", _0)]
+	Backup(String),
+
+	/// Received transaction is rejected by the receive policy
+	#[fail(display = "Receive is rejected by the policy, {}", _0)]
+	ReceivePolicy(String),
+
+	/// Exchange rate isn't available
+	#[fail(display = "Exchange rate error, {}", _0)]
+	PriceFeed(String),
+
+	/// Payment identifier 'name@domain' can't be resolved
+	#[fail(display = "Payment id error, {}", _0)]
+	PaymentId(String),
+
+	/// Invalid mapping of the address index to the account or label
+	#[fail(display = "Address mapping error, {}", _0)]
+	AddressMapping(String),
+
+	/// Transactions history can't be imported
+	#[fail(display = "Transaction import error, {}", _0)]
+	TxImport(String),
+
+	/// Range proof of the output is not valid
+	#[fail(display = "Invalid range proof of the output {}", _0)]
+	InvalidRangeProof(String),
 }
 
 impl Display for Error {
@@ -485,3 +575,9 @@ impl From<SwapErrorKind> for Error {
 		Error::from(ErrorKind::SwapError(format!("{}", error)))
 	}
 }
+
+impl From<MultisigErrorKind> for Error {
+	fn from(error: MultisigErrorKind) -> Error {
+		Error::from(ErrorKind::Multisig(format!("{}", error)))
+	}
+}
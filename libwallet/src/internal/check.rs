@@ -0,0 +1,468 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wallet consistency check. The wallet data is compared with the chain and the swap storage,
+//! the problems are reported by category and only the selected categories are repaired.
+
+use crate::api_impl::owner_swap;
+use crate::api_impl::owner_updater::StatusMessage;
+use crate::grin_keychain::{Identifier, Keychain};
+use crate::grin_util as util;
+use crate::grin_util::secp::key::SecretKey;
+use crate::grin_util::secp::pedersen;
+use crate::grin_util::Mutex;
+use crate::internal::scan::{self, OutputResult};
+use crate::internal::updater;
+use crate::swap::trades;
+use crate::types::{
+	NodeClient, OutputData, OutputStatus, TxLogEntry, TxLogEntryType, TxStatus, WalletInst,
+	WalletLCProvider,
+};
+use crate::{wallet_lock, Error, ErrorKind};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+/// Extension of the swap trade files that can't be read by this wallet and are put aside
+pub const ORPHANED_SWAP_EXT: &str = "swap.orphan";
+
+/// Category of the problems found by the check
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckCategory {
+	/// Outputs that are unspent on the chain, but missing in the wallet or marked as spent
+	MissingOutputs,
+	/// Outputs that are unspent in the wallet, but spent on the chain
+	SpentOutputs,
+	/// Unconfirmed transactions that don't have any outputs in the wallet
+	DanglingTxs,
+	/// Swap trades that can't be read and auto swap sessions without the trade
+	OrphanedSwaps,
+}
+
+impl CheckCategory {
+	/// All categories in the report order
+	pub const ALL: [CheckCategory; 4] = [
+		CheckCategory::MissingOutputs,
+		CheckCategory::SpentOutputs,
+		CheckCategory::DanglingTxs,
+		CheckCategory::OrphanedSwaps,
+	];
+}
+
+impl fmt::Display for CheckCategory {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match *self {
+			CheckCategory::MissingOutputs => write!(f, "missing_outputs"),
+			CheckCategory::SpentOutputs => write!(f, "spent_outputs"),
+			CheckCategory::DanglingTxs => write!(f, "dangling_txs"),
+			CheckCategory::OrphanedSwaps => write!(f, "orphaned_swaps"),
+		}
+	}
+}
+
+impl FromStr for CheckCategory {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		CheckCategory::ALL
+			.iter()
+			.find(|c| c.to_string() == s)
+			.cloned()
+			.ok_or_else(|| {
+				ErrorKind::ArgumentError(format!(
+					"Unknown check category '{}', expected one of: missing_outputs, spent_outputs, dangling_txs, orphaned_swaps",
+					s
+				))
+				.into()
+			})
+	}
+}
+
+/// Output that is not in sync with the chain
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CheckOutput {
+	/// Output commitment, hex
+	pub commit: String,
+	/// Output value
+	pub value: u64,
+	/// Height of the block with the output
+	pub height: u64,
+	/// Status in the wallet, None if the output is not in the wallet
+	pub local_status: Option<OutputStatus>,
+	/// Transaction of the output in the wallet
+	pub tx_log_id: Option<u32>,
+}
+
+/// Transaction log entry without the outputs
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CheckTx {
+	/// Local transaction id
+	pub id: u32,
+	/// Slate id
+	pub tx_slate_id: Option<String>,
+	/// Transaction type
+	pub tx_type: TxLogEntryType,
+	/// Amount credited
+	pub amount_credited: u64,
+	/// Amount debited
+	pub amount_debited: u64,
+}
+
+/// Swap data that doesn't belong to a readable trade
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CheckSwap {
+	/// Swap trade Id
+	pub swap_id: String,
+	/// What is wrong with it
+	pub issue: String,
+}
+
+/// Result of the wallet check
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CheckReport {
+	/// First block of the checked chain range
+	pub start_height: u64,
+	/// Chain height at the check
+	pub tip_height: u64,
+	/// Outputs found on the chain that are missing in the wallet or marked as spent
+	pub missing_outputs: Vec<CheckOutput>,
+	/// Outputs unspent in the wallet that are spent on the chain
+	pub spent_outputs: Vec<CheckOutput>,
+	/// Unconfirmed transactions without outputs in the wallet
+	pub dangling_txs: Vec<CheckTx>,
+	/// Unreadable swap trades and auto swap sessions without the trade
+	pub orphaned_swaps: Vec<CheckSwap>,
+	/// Categories that were repaired
+	pub fixed: Vec<CheckCategory>,
+}
+
+impl CheckReport {
+	/// Number of the problems in the category
+	pub fn count(&self, category: CheckCategory) -> usize {
+		match category {
+			CheckCategory::MissingOutputs => self.missing_outputs.len(),
+			CheckCategory::SpentOutputs => self.spent_outputs.len(),
+			CheckCategory::DanglingTxs => self.dangling_txs.len(),
+			CheckCategory::OrphanedSwaps => self.orphaned_swaps.len(),
+		}
+	}
+
+	/// True if no problems are found
+	pub fn is_clean(&self) -> bool {
+		CheckCategory::ALL.iter().all(|c| self.count(*c) == 0)
+	}
+}
+
+fn parse_commit(commit: &str) -> Result<pedersen::Commitment, Error> {
+	Ok(pedersen::Commitment::from_vec(
+		util::from_hex(commit).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to parse HEX commit {}, {}", commit, e))
+		})?,
+	))
+}
+
+/// Check the wallet against the chain from 'start_height' and the swap storage. The problems
+/// of the categories from 'fix' are repaired, the rest are only reported.
+pub fn check<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	start_height: u64,
+	fix: &[CheckCategory],
+	status_send_channel: &Option<Sender<StatusMessage>>,
+) -> Result<CheckReport, Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let (client, keychain, outputs, txs) = {
+		wallet_lock!(wallet_inst, w);
+		w.w2n_client().reset_cache();
+		let outputs: Vec<OutputData> = w.iter().collect();
+		let txs: Vec<TxLogEntry> = w.tx_log_iter().collect();
+		(
+			w.w2n_client().clone(),
+			w.keychain(keychain_mask)?,
+			outputs,
+			txs,
+		)
+	};
+
+	let tip_height = client.get_chain_tip()?.0;
+	if tip_height == 0 {
+		return Err(ErrorKind::NodeNotReady)?;
+	}
+	let start_height = start_height.max(1).min(tip_height);
+
+	if let Some(ref s) = status_send_channel {
+		let _ = s.send(StatusMessage::Info(format!(
+			"Checking the wallet against the chain from height {} to {}",
+			start_height, tip_height
+		)));
+	}
+
+	let local: HashMap<String, &OutputData> = outputs
+		.iter()
+		.filter_map(|o| o.commit.clone().map(|c| (c, o)))
+		.collect();
+
+	// Outputs that the chain has but the wallet doesn't know about or thinks are spent
+	let pmmr_range = client.height_range_to_pmmr_indices(start_height, None)?;
+	let (chain_outs, _) = scan::collect_chain_outputs(
+		&keychain,
+		client.clone(),
+		pmmr_range.0,
+		Some(pmmr_range.1),
+		status_send_channel,
+		false,
+		Some(scan::get_replay_config()),
+	)?;
+	let mut missing: Vec<OutputResult> = vec![];
+	let mut missing_outputs = vec![];
+	for out in chain_outs {
+		let commit = util::to_hex(&out.commit.0);
+		let local_out = local.get(&commit);
+		if let Some(o) = local_out {
+			if o.status != OutputStatus::Spent {
+				continue;
+			}
+		}
+		missing_outputs.push(CheckOutput {
+			commit,
+			value: out.value,
+			height: out.height,
+			local_status: local_out.map(|o| o.status.clone()),
+			tx_log_id: local_out.and_then(|o| o.tx_log_entry),
+		});
+		missing.push(out);
+	}
+
+	// Outputs that the wallet can spend but the chain doesn't have any more
+	let unspent: Vec<&OutputData> = outputs
+		.iter()
+		.filter(|o| o.status == OutputStatus::Unspent && o.commit.is_some())
+		.collect();
+	let mut commits = vec![];
+	for o in &unspent {
+		commits.push(parse_commit(o.commit.as_ref().unwrap())?);
+	}
+	let on_chain = if commits.is_empty() {
+		HashMap::new()
+	} else {
+		client.get_outputs_from_node(&commits)?
+	};
+	let spent_outputs: Vec<CheckOutput> = unspent
+		.iter()
+		.zip(commits.iter())
+		.filter(|(_, c)| !on_chain.contains_key(c))
+		.map(|(o, _)| CheckOutput {
+			commit: o.commit.clone().unwrap(),
+			value: o.value,
+			height: o.height,
+			local_status: Some(o.status.clone()),
+			tx_log_id: o.tx_log_entry,
+		})
+		.collect();
+
+	// Unconfirmed transactions that lost all their outputs. The transactions that are still
+	// negotiated with the other party don't have outputs yet.
+	let linked_txs: HashSet<(Identifier, u32)> = outputs
+		.iter()
+		.filter_map(|o| o.tx_log_entry.map(|id| (o.root_key_id.clone(), id)))
+		.collect();
+	let dangling: Vec<TxLogEntry> = txs
+		.into_iter()
+		.filter(|t| {
+			!t.confirmed
+				&& !t.is_cancelled()
+				&& t.tx_type != TxLogEntryType::ConfirmedCoinbase
+				&& match t.current_status() {
+					TxStatus::AwaitingCounterparty | TxStatus::AwaitingFinalization => false,
+					_ => true,
+				} && !linked_txs.contains(&(t.parent_key_id.clone(), t.id))
+				&& !t
+					.input_commits
+					.iter()
+					.chain(t.output_commits.iter())
+					.any(|c| local.contains_key(&util::to_hex(&c.0)))
+		})
+		.collect();
+	let dangling_txs = dangling
+		.iter()
+		.map(|t| CheckTx {
+			id: t.id,
+			tx_slate_id: t.tx_slate_id.map(|id| id.to_string()),
+			tx_type: t.tx_type.clone(),
+			amount_credited: t.amount_credited,
+			amount_debited: t.amount_debited,
+		})
+		.collect();
+
+	// Swap data that doesn't belong to a trade of this wallet
+	let mut orphaned_swaps = vec![];
+	let mut unreadable_trades = vec![];
+	let mut orphaned_sessions = vec![];
+	if trades::is_trade_backend_initialized() {
+		let skey = owner_swap::get_swap_storage_key(&keychain)?;
+		let mut readable = HashSet::new();
+		for swap_id in trades::list_swap_trades()? {
+			let swap_lock = trades::get_swap_lock(&swap_id);
+			let _l = swap_lock.lock();
+			match trades::get_swap_trade(&swap_id, &skey, &*swap_lock) {
+				Ok(_) => {
+					readable.insert(swap_id);
+				}
+				Err(e) => {
+					orphaned_swaps.push(CheckSwap {
+						swap_id: swap_id.clone(),
+						issue: format!("Trade can't be read by this wallet, {}", e),
+					});
+					unreadable_trades.push(swap_id);
+				}
+			}
+		}
+		for session in trades::list_autoswap_sessions()? {
+			if !readable.contains(&session.swap_id) {
+				orphaned_swaps.push(CheckSwap {
+					swap_id: session.swap_id.clone(),
+					issue: "Auto swap session without the trade".to_string(),
+				});
+				orphaned_sessions.push(session.swap_id);
+			}
+		}
+	}
+
+	let mut report = CheckReport {
+		start_height,
+		tip_height,
+		missing_outputs,
+		spent_outputs,
+		dangling_txs,
+		orphaned_swaps,
+		fixed: vec![],
+	};
+
+	for category in CheckCategory::ALL.iter() {
+		if !fix.contains(category) || report.count(*category) == 0 {
+			continue;
+		}
+		match category {
+			CheckCategory::MissingOutputs => {
+				let mut found_parents = HashMap::new();
+				let mut recovered_txs = HashMap::new();
+				for out in missing.clone() {
+					let commit = util::to_hex(&out.commit.0);
+					match local.get(&commit) {
+						Some(o) => {
+							wallet_lock!(wallet_inst, w);
+							let mut o = (*o).clone();
+							o.status = OutputStatus::Unspent;
+							let mut batch = w.batch(keychain_mask)?;
+							batch.save(o)?;
+							batch.commit()?;
+						}
+						None => scan::restore_missing_output(
+							wallet_inst.clone(),
+							keychain_mask,
+							out,
+							&HashMap::new(),
+							&HashMap::new(),
+							&mut found_parents,
+							&mut recovered_txs,
+						)?,
+					}
+				}
+				scan::associate_recovered_kernels(
+					wallet_inst.clone(),
+					keychain_mask,
+					&recovered_txs,
+					status_send_channel,
+				)?;
+				scan::restore_labels(
+					wallet_inst.clone(),
+					keychain_mask,
+					&found_parents,
+					status_send_channel,
+				)?;
+			}
+			CheckCategory::SpentOutputs => {
+				wallet_lock!(wallet_inst, w);
+				let mut batch = w.batch(keychain_mask)?;
+				for out in &report.spent_outputs {
+					if let Some(o) = local.get(&out.commit) {
+						let mut o = (*o).clone();
+						o.status = OutputStatus::Spent;
+						batch.save(o)?;
+					}
+				}
+				batch.commit()?;
+			}
+			CheckCategory::DanglingTxs => {
+				wallet_lock!(wallet_inst, w);
+				for tx in &dangling {
+					let parent_key_id = tx.parent_key_id.clone();
+					updater::cancel_tx_and_outputs(
+						&mut **w,
+						keychain_mask,
+						tx.clone(),
+						vec![],
+						&parent_key_id,
+					)?;
+				}
+			}
+			CheckCategory::OrphanedSwaps => {
+				let path = trades::get_swap_storage_path()?;
+				for swap_id in &unreadable_trades {
+					// Put aside, the file might belong to another wallet with the same data dir
+					fs::rename(
+						path.join(format!("{}.swap", swap_id)),
+						path.join(format!("{}.{}", swap_id, ORPHANED_SWAP_EXT)),
+					)?;
+				}
+				for swap_id in &orphaned_sessions {
+					trades::remove_autoswap_session(swap_id)?;
+				}
+			}
+		}
+		info!(
+			"Wallet check fixed {} problems of category {}",
+			report.count(*category),
+			category
+		);
+		report.fixed.push(*category);
+	}
+
+	Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_check_category() {
+		for c in CheckCategory::ALL.iter() {
+			assert_eq!(CheckCategory::from_str(&c.to_string()).unwrap(), *c);
+			assert_eq!(
+				serde_json::to_string(c).unwrap(),
+				format!("\"{}\"", c.to_string())
+			);
+		}
+		assert!(CheckCategory::from_str("outputs").is_err());
+	}
+}
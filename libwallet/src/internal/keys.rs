@@ -13,10 +13,11 @@
 // limitations under the License.
 
 //! Wallet key management functions
+use crate::blake2::blake2b::blake2b;
 use crate::error::{Error, ErrorKind};
-use crate::grin_keychain::{ChildNumber, ExtKeychain, Identifier, Keychain};
+use crate::grin_keychain::{ChildNumber, ExtKeychain, Identifier, Keychain, SwitchCommitmentType};
 use crate::grin_util::secp::key::SecretKey;
-use crate::types::{AcctPathMapping, NodeClient, WalletBackend};
+use crate::types::{AcctPathMapping, NodeClient, OutputStatus, WalletBackend};
 use std::collections::HashSet;
 
 /// Get next available key in the wallet for a given parent
@@ -33,6 +34,20 @@ where
 	Ok(child)
 }
 
+/// Hash key that separates the receive-only wallet seed from the other root key hashes
+const RECEIVE_WALLET_SEED_KEY: &[u8] = b"MWC receive-only wallet seed";
+
+/// Seed of the receive-only wallet. It is a one way hash of the root key, so the main wallet can
+/// always recreate it, but the receive-only wallet can't derive any keys of the main wallet.
+pub fn receive_wallet_seed<K>(keychain: &K) -> Result<Vec<u8>, Error>
+where
+	K: Keychain,
+{
+	let root_key = keychain.derive_key(0, &K::root_key_id(), SwitchCommitmentType::Regular)?;
+	let seed = blake2b(32, RECEIVE_WALLET_SEED_KEY, &root_key.0[..]);
+	Ok(seed.as_bytes().to_vec())
+}
+
 /// Retrieve an existing key from a wallet
 pub fn retrieve_existing_key<'a, T: ?Sized, C, K>(
 	wallet: &T,
@@ -99,6 +114,46 @@ where
 	Ok(())
 }
 
+/// Deletes an account path. Account must not be active and must not hold any funds.
+/// Transactions and spent outputs of the account stay in the wallet history.
+pub fn delete_acct_path<'a, T: ?Sized, C, K>(
+	wallet: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	label: &str,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let label = label.to_string();
+	if label == "default" {
+		return Err(ErrorKind::AccountDefaultCannotBeDeleted.into());
+	}
+
+	let account = wallet
+		.acct_path_iter()
+		.find(|l| l.label == label)
+		.ok_or(ErrorKind::AccountLabelNotExists(label.clone()))?;
+
+	if wallet.parent_key_id() == account.path {
+		return Err(ErrorKind::AccountIsActive(label).into());
+	}
+
+	let not_spent = wallet
+		.iter()
+		.filter(|o| o.root_key_id == account.path && o.status != OutputStatus::Spent)
+		.count();
+	if not_spent > 0 {
+		return Err(ErrorKind::AccountNotEmpty(label, not_spent).into());
+	}
+
+	let mut batch = wallet.batch(keychain_mask)?;
+	batch.delete_acct_path(&label)?;
+	batch.commit()?;
+	Ok(())
+}
+
 /// Adds an new parent account path with a given label
 pub fn new_acct_path<'a, T: ?Sized, C, K>(
 	wallet: &mut T,
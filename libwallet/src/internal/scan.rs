@@ -16,12 +16,12 @@
 use crate::api_impl::foreign;
 use crate::api_impl::owner;
 use crate::api_impl::owner_updater::StatusMessage;
-use crate::api_impl::types::InitTxArgs;
+use crate::api_impl::types::{InitTxArgs, ScanProgress};
 use crate::grin_core::consensus::{valid_header_version, WEEK_HEIGHT};
 use crate::grin_core::core::Committed;
 use crate::grin_core::core::HeaderVersion;
 use crate::grin_core::core::Transaction;
-use crate::grin_core::global;
+use crate::grin_core::{global, ser};
 use crate::grin_core::libtx::{proof, tx_fee};
 use crate::grin_keychain::{ChildNumber, Identifier, Keychain, SwitchCommitmentType};
 use crate::grin_util as util;
@@ -32,12 +32,16 @@ use crate::grin_util::static_secp_instance;
 use crate::grin_util::Mutex;
 use crate::internal::tx;
 use crate::internal::{keys, updater};
+use crate::proof_verifier;
 use crate::types::*;
 use crate::ReplayMitigationConfig;
 use crate::{wallet_lock, Error, ErrorKind};
+use chrono::{Duration, Utc};
 use std::cmp;
 use std::collections::{HashMap, HashSet};
-use std::sync::mpsc::Sender;
+use crossbeam_utils::thread::scope;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -54,8 +58,38 @@ const OUTPUT_TO_BLOCK: usize = 100;
 // of the node load. 4 is a reasonable number
 const SYNC_BLOCKS_THREADS: usize = 4;
 
+// How many workers rewind the range proofs of the fetched outputs during the full scan. Rewind is
+// CPU bound, so it scales with the threads well. Can be changed with the 'scan_threads' config setting.
+const DEFAULT_SCAN_THREADS: usize = 4;
+
+// Upper limit for the 'scan_threads' setting. Every worker holds a chunk of the outputs in memory.
+const MAX_SCAN_THREADS: usize = 64;
+
+// Number of the PMMR indexes that are requested from the node in one call during the full scan.
+// Large requests cut the number of the round trips, that was the main cost of the restore.
+const FETCH_BATCH_SIZE: u64 = 5000;
+
+// How many output requests to the node are in flight during the full scan. Fetching is IO bound,
+// a couple of requests keep the rewind workers busy without loading the node much.
+const FETCH_THREADS: usize = 2;
+
+// Fetched batches are split into the chunks of this size for the rewind workers
+const REWIND_CHUNK_SIZE: usize = 250;
+
+// Fetched chunks that wait for the rewind, per rewind worker. Limits the memory if the node is
+// faster than the rewind.
+const REWIND_QUEUE_CHUNKS: usize = 4;
+
+// Scan checkpoint is saved every time when this number of output batches are processed
+const CHECKPOINT_BATCHES: usize = 4;
+
+// All unspent outputs are validated against the chain at least once in this number of blocks.
+// The updates in between check only the inputs of the new blocks, it finds the outputs that are
+// spent by another instance of this wallet as well.
+const OUTPUTS_VALIDATION_INTERVAL: u64 = 1440;
+
 /// Utility struct for return values from below
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputResult {
 	///
 	pub commit: pedersen::Commitment,
@@ -75,6 +109,24 @@ pub struct OutputResult {
 	pub is_coinbase: bool,
 }
 
+impl ser::Writeable for OutputResult {
+	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let data = serde_json::to_vec(self).map_err(|e| {
+			ser::Error::CorruptedData(format!("OutputResult to json conversion failed, {}", e))
+		})?;
+		writer.write_bytes(&data)
+	}
+}
+
+impl ser::Readable for OutputResult {
+	fn read<R: ser::Reader>(reader: &mut R) -> Result<OutputResult, ser::Error> {
+		let data = reader.read_bytes_len_prefix()?;
+		serde_json::from_slice(&data[..]).map_err(|e| {
+			ser::Error::CorruptedData(format!("json to OutputResult conversion failed, {}", e))
+		})
+	}
+}
+
 /// Utility struct for self spend
 #[derive(Debug, Clone)]
 pub struct OutputResultLight {
@@ -128,6 +180,12 @@ lazy_static! {
 
 	/// Global config in memory storage.
 	pub static ref REPLAY_MITIGATION_CONFIG: Mutex< ReplayMitigationConfig> = Mutex::new(ReplayMitigationConfig::default());
+
+	/// Progress of the running scan
+	static ref SCAN_PROGRESS: Mutex<ScanProgress> = Mutex::new(ScanProgress::default());
+
+	/// Number of the scan workers
+	static ref SCAN_THREADS: Mutex<usize> = Mutex::new(DEFAULT_SCAN_THREADS);
 }
 /// Set address derivative index
 pub fn set_replay_config(config: ReplayMitigationConfig) {
@@ -139,6 +197,19 @@ pub fn get_replay_config() -> ReplayMitigationConfig {
 	REPLAY_MITIGATION_CONFIG.lock().clone()
 }
 
+/// Set number of the workers that fetch and rewind the output batches during the scan.
+/// None restores the default.
+pub fn set_scan_threads(threads: Option<usize>) {
+	*SCAN_THREADS.lock() = threads
+		.unwrap_or(DEFAULT_SCAN_THREADS)
+		.max(1)
+		.min(MAX_SCAN_THREADS);
+}
+/// Get number of the scan workers
+pub fn get_scan_threads() -> usize {
+	*SCAN_THREADS.lock()
+}
+
 fn identify_utxo_outputs<'a, K>(
 	keychain: &K,
 	outputs: Vec<(pedersen::Commitment, pedersen::RangeProof, bool, u64, u64)>,
@@ -151,6 +222,8 @@ where
 {
 	let mut wallet_outputs: Vec<OutputResult> = Vec::new();
 	let mut self_spend_outputs: Vec<OutputResult> = Vec::new();
+	// Range proofs of the found outputs, they are verified together at the end
+	let mut found_proofs: Vec<(pedersen::Commitment, pedersen::RangeProof)> = Vec::new();
 
 	let legacy_builder = proof::LegacyProofBuilder::new(keychain);
 	let builder = proof::ProofBuilder::new(keychain);
@@ -194,6 +267,7 @@ where
 			"Output found: {:?}, amount: {:?}, key_id: {:?}, mmr_index: {},",
 			commit, amount, key_id, mmr_index
 		);
+		found_proofs.push((*commit, *proof));
 
 		if switch != SwitchCommitmentType::Regular {
 			warn!("Unexpected switch commitment type {:?}", switch);
@@ -254,6 +328,23 @@ where
 		}
 	}
 
+	// Rewind doesn't verify the proof, the output with invalid proof can't be spent
+	let invalid: HashSet<pedersen::Commitment> =
+		proof_verifier::invalid_proofs(keychain.secp(), &found_proofs)
+			.into_iter()
+			.map(|i| found_proofs[i].0)
+			.collect();
+	if !invalid.is_empty() {
+		warn!(
+			"Skipping {} outputs with invalid range proofs ({} verifier): {:?}",
+			invalid.len(),
+			proof_verifier::get_proof_verifier().name(),
+			invalid
+		);
+		wallet_outputs.retain(|o| !invalid.contains(&o.commit));
+		self_spend_outputs.retain(|o| !invalid.contains(&o.commit));
+	}
+
 	Ok((wallet_outputs, self_spend_outputs))
 }
 
@@ -271,11 +362,39 @@ where
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
-	let batch_size = 1000;
-	let start_index_stat = start_index;
-	let mut start_index = start_index;
-	let mut result_vec: Vec<OutputResult> = vec![];
-	let mut self_spend_candidate_list: Vec<OutputResult> = vec![];
+	collect_chain_outputs_with_checkpoints(
+		keychain,
+		client,
+		start_index,
+		end_index,
+		status_send_channel,
+		show_progress,
+		replay_config,
+		&mut |_, _, _, _| Ok(()),
+	)
+}
+
+/// Scanning chain for the outputs.
+/// PMMR index range is split into the batches. FETCH_THREADS workers request the batches from
+/// the node and pass them in chunks to get_scan_threads() workers that rewind the range proofs,
+/// so the fetching and the rewind run at the same time. Every CHECKPOINT_BATCHES batches the
+/// checkpoint callback is called with (last_pmmr_index, last_height, outputs, self_spend_candidates)
+/// that are found since the previous checkpoint. All outputs up to last_pmmr_index are processed
+/// at that moment.
+fn collect_chain_outputs_with_checkpoints<'a, C, K>(
+	keychain: &K,
+	client: C,
+	start_index: u64,
+	end_index: Option<u64>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	show_progress: bool,
+	replay_config: Option<ReplayMitigationConfig>,
+	checkpoint: &mut dyn FnMut(u64, u64, &[OutputResult], &[OutputResult]) -> Result<(), Error>,
+) -> Result<(Vec<OutputResult>, Vec<OutputResult>), Error>
+where
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
 	let mut should_self_spent = false;
 	let mut self_spent_amount = 0;
 	if let Some(conf) = replay_config {
@@ -284,49 +403,298 @@ where
 			self_spent_amount = conf.replay_mitigation_min_amount;
 		}
 	}
+
+	// Batches are processed in parallel, so we need to know where the range ends
+	let end_index = match end_index {
+		Some(i) => i,
+		None => client.get_outputs_by_pmmr_index(start_index, None, 1)?.0,
+	};
+
+	let mut batches: Vec<(u64, u64)> = vec![];
+	let mut index = start_index;
+	while index <= end_index {
+		let last = cmp::min(index + FETCH_BATCH_SIZE - 1, end_index);
+		batches.push((index, last));
+		index = last + 1;
+	}
+
+	let mut result_vec: Vec<OutputResult> = vec![];
+	let mut self_spend_candidate_list: Vec<OutputResult> = vec![];
+
+	let rewind_threads = get_scan_threads();
+	let next_batch = AtomicUsize::new(0);
+	let stop = AtomicBool::new(false);
+
+	scope(|s| -> Result<(), Error> {
+		// (batch number, number of the chunks in the batch, outputs)
+		let (chunk_tx, chunk_rx) = mpsc::sync_channel::<(
+			usize,
+			usize,
+			Vec<(pedersen::Commitment, pedersen::RangeProof, bool, u64, u64)>,
+		)>(rewind_threads * REWIND_QUEUE_CHUNKS);
+		let chunk_rx = Arc::new(Mutex::new(chunk_rx));
+		let (tx, rx) = mpsc::channel();
+
+		for _ in 0..cmp::min(FETCH_THREADS, batches.len()) {
+			let chunk_tx = chunk_tx.clone();
+			let tx = tx.clone();
+			let client = client.clone();
+			let batches = &batches;
+			let next_batch = &next_batch;
+			let stop = &stop;
+			s.spawn(move |_| {
+				while !stop.load(Ordering::Relaxed) {
+					let i = next_batch.fetch_add(1, Ordering::Relaxed);
+					if i >= batches.len() {
+						break;
+					}
+					let outputs = match fetch_batch_outputs(&client, batches[i]) {
+						Ok(outputs) => outputs,
+						Err(e) => {
+							let _ = tx.send(Err(e));
+							break;
+						}
+					};
+					// Empty batch still needs one chunk to be reported as completed
+					let chunks = cmp::max(
+						1,
+						(outputs.len() + REWIND_CHUNK_SIZE - 1) / REWIND_CHUNK_SIZE,
+					);
+					let mut outputs = outputs.into_iter();
+					for _ in 0..chunks {
+						let chunk: Vec<_> = outputs.by_ref().take(REWIND_CHUNK_SIZE).collect();
+						// Rewind workers are gone because of the error
+						if chunk_tx.send((i, chunks, chunk)).is_err() {
+							return;
+						}
+					}
+				}
+			});
+		}
+		// Fetch workers hold the only chunk senders, rewind workers stop when all of them are done
+		drop(chunk_tx);
+
+		for _ in 0..rewind_threads {
+			let chunk_rx = chunk_rx.clone();
+			let tx = tx.clone();
+			let stop = &stop;
+			s.spawn(move |_| {
+				while !stop.load(Ordering::Relaxed) {
+					let next_chunk = chunk_rx.lock().recv();
+					let (i, chunks, outputs) = match next_chunk {
+						Ok(chunk) => chunk,
+						Err(_) => break,
+					};
+					let height = outputs.iter().map(|o| o.3).max().unwrap_or(0);
+					let res = identify_utxo_outputs(
+						keychain,
+						outputs,
+						None,
+						should_self_spent,
+						self_spent_amount,
+					);
+					if tx
+						.send(
+							res.map(|(outputs, self_spend)| {
+								(i, chunks, outputs, self_spend, height)
+							}),
+						)
+						.is_err()
+					{
+						break;
+					}
+				}
+			});
+		}
+		drop(chunk_rx);
+		// Workers hold the only senders, so the loop below ends when all of them are done
+		drop(tx);
+
+		let total = end_index.saturating_sub(start_index) + 1;
+		let mut processed = 0;
+		let mut height = 0;
+		// Processed chunks and max output height of the batches in progress
+		let mut batch_chunks: HashMap<usize, (usize, u64)> = HashMap::new();
+		// Batches are completed in random order, checkpoint can cover only completed prefix
+		let mut batch_heights: HashMap<usize, u64> = HashMap::new();
+		let mut checkpoint_batch = 0;
+		let mut checkpoint_index = start_index;
+		let mut checkpoint_height = 0;
+		for res in rx.iter() {
+			let (i, chunks, mut outputs, mut self_spend_outputs, chunk_height) = match res {
+				Ok(r) => r,
+				Err(e) => {
+					stop.store(true, Ordering::Relaxed);
+					return Err(e);
+				}
+			};
+			height = cmp::max(height, chunk_height);
+			result_vec.append(&mut outputs);
+			self_spend_candidate_list.append(&mut self_spend_outputs);
+
+			let batch_height = {
+				let progress = batch_chunks.entry(i).or_insert((0, 0));
+				progress.0 += 1;
+				progress.1 = cmp::max(progress.1, chunk_height);
+				if progress.0 < chunks {
+					continue;
+				}
+				progress.1
+			};
+			batch_chunks.remove(&i);
+			let (first, last) = batches[i];
+			processed += last - first + 1;
+
+			batch_heights.insert(i, batch_height);
+			let mut completed = checkpoint_batch;
+			let mut completed_height = checkpoint_height;
+			while completed < batches.len() {
+				match batch_heights.get(&completed) {
+					Some(h) => completed_height = cmp::max(completed_height, *h),
+					None => break,
+				}
+				completed += 1;
+			}
+			if completed >= checkpoint_batch + CHECKPOINT_BATCHES && completed < batches.len() {
+				let last_index = batches[completed - 1].1;
+				let in_checkpoint =
+					|o: &&OutputResult| o.mmr_index >= checkpoint_index && o.mmr_index <= last_index;
+				let outputs: Vec<OutputResult> =
+					result_vec.iter().filter(in_checkpoint).cloned().collect();
+				let self_spend_outputs: Vec<OutputResult> = self_spend_candidate_list
+					.iter()
+					.filter(in_checkpoint)
+					.cloned()
+					.collect();
+				if let Err(e) = checkpoint(
+					last_index,
+					completed_height,
+					&outputs,
+					&self_spend_outputs,
+				) {
+					stop.store(true, Ordering::Relaxed);
+					return Err(e);
+				}
+				checkpoint_batch = completed;
+				checkpoint_index = last_index + 1;
+				checkpoint_height = completed_height;
+			}
+
+			let msg = format!(
+				"Checked outputs {} - {} of {}, found {} outputs",
+				first,
+				last,
+				end_index,
+				result_vec.len()
+			);
+			report_scan_progress(
+				status_send_channel,
+				show_progress,
+				msg,
+				cmp::min(processed * 100 / total, 99) as u8,
+				Some(height),
+				Some(result_vec.len() as u64),
+			);
+		}
+		Ok(())
+	})
+	.map_err(|_| ErrorKind::GenericError("Chain scanning worker thread is crashed".to_string()))??;
+
+	// Batches are completed in random order
+	result_vec.sort_by_key(|o| o.mmr_index);
+	result_vec.dedup_by_key(|o| o.mmr_index);
+	self_spend_candidate_list.sort_by_key(|o| o.mmr_index);
+	self_spend_candidate_list.dedup_by_key(|o| o.mmr_index);
+	Ok((result_vec, self_spend_candidate_list))
+}
+
+/// Fetch the unspent outputs for the PMMR index range (first, last). The node can return
+/// less outputs than requested, the rest is requested again.
+fn fetch_batch_outputs<'a, C>(
+	client: &C,
+	(first, last): (u64, u64),
+) -> Result<Vec<(pedersen::Commitment, pedersen::RangeProof, bool, u64, u64)>, Error>
+where
+	C: NodeClient + 'a,
+{
+	let mut result = vec![];
+	let mut start_index = first;
 	loop {
-		let (highest_index, last_retrieved_index, outputs) =
-			client.get_outputs_by_pmmr_index(start_index, end_index, batch_size)?;
-
-		let range = highest_index as f64 - start_index_stat as f64;
-		let progress = last_retrieved_index as f64 - start_index_stat as f64;
-		let perc_complete = cmp::min(((progress / range) * 100.0) as u8, 99);
-
-		let msg = format!(
-			"Checking {} outputs, up to index {}. (Highest index: {})",
-			outputs.len(),
-			highest_index,
-			last_retrieved_index,
-		);
-		if let Some(ref s) = status_send_channel {
-			let _ = s.send(StatusMessage::Scanning(show_progress, msg, perc_complete));
+		let (highest_index, last_retrieved_index, mut outputs) =
+			client.get_outputs_by_pmmr_index(start_index, Some(last), FETCH_BATCH_SIZE)?;
+		if outputs.is_empty() {
+			break;
 		}
-		let mut chain_outs_pair = identify_utxo_outputs(
-			keychain,
-			outputs,
-			None,
-			should_self_spent,
-			self_spent_amount,
-		)?;
-		result_vec.append(&mut chain_outs_pair.0);
-		self_spend_candidate_list.append(&mut chain_outs_pair.1);
+		result.append(&mut outputs);
 
-		if highest_index <= last_retrieved_index {
+		if last_retrieved_index >= last || highest_index <= last_retrieved_index {
 			break;
 		}
 		start_index = last_retrieved_index + 1;
 	}
-	Ok((result_vec, self_spend_candidate_list))
+	Ok(result)
+}
+
+/// Progress of the running scan. Also available while the updater thread is scanning.
+pub fn get_scan_progress() -> ScanProgress {
+	SCAN_PROGRESS.lock().clone()
+}
+
+/// Update the scan progress and send the Scanning status message
+fn report_scan_progress(
+	status_send_channel: &Option<Sender<StatusMessage>>,
+	show_progress: bool,
+	msg: String,
+	percent: u8,
+	height: Option<u64>,
+	outputs_found: Option<u64>,
+) {
+	{
+		let mut progress = SCAN_PROGRESS.lock();
+		progress.percent = percent;
+		if let Some(height) = height {
+			progress.height = height;
+		}
+		if let Some(outputs_found) = outputs_found {
+			progress.outputs_found = outputs_found;
+		}
+	}
+	if let Some(ref s) = status_send_channel {
+		let _ = s.send(StatusMessage::Scanning(show_progress, msg, percent));
+	}
+}
+
+/// Marks the scan progress as running until it is dropped
+struct ScanProgressGuard {}
+
+impl ScanProgressGuard {
+	fn start(tip_height: u64) -> Self {
+		*SCAN_PROGRESS.lock() = ScanProgress {
+			running: true,
+			percent: 0,
+			height: 0,
+			tip_height,
+			outputs_found: 0,
+		};
+		ScanProgressGuard {}
+	}
+}
+
+impl Drop for ScanProgressGuard {
+	fn drop(&mut self) {
+		SCAN_PROGRESS.lock().running = false;
+	}
 }
 
 /// Respore missing outputs. Shared with mwc713
-fn restore_missing_output<'a, L, C, K>(
+pub(crate) fn restore_missing_output<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
 	keychain_mask: Option<&SecretKey>,
 	output: OutputResult,
 	commit2transactionuuid: &HashMap<String, String>,
 	transaction: &HashMap<String, WalletTxInfo>,
 	found_parents: &mut HashMap<Identifier, u32>,
+	recovered_txs: &mut HashMap<(Identifier, u64), TxLogEntry>,
 ) -> Result<(), Error>
 where
 	L: WalletLCProvider<'a, C, K>,
@@ -351,6 +719,16 @@ where
 		{
 			// Transaction already exist. using it...
 			transaction.get(uuid).unwrap().tx_log.id
+		} else if let Some(t) = recovered_txs
+			.get_mut(&(parent_key_id.clone(), output.height))
+			.filter(|_| !output.is_coinbase)
+		{
+			// Outputs received in the same block are likely from the same transaction
+			t.amount_credited += output.value;
+			t.num_outputs += 1;
+			t.output_commits.push(output.commit.clone());
+			batch.save_tx_log_entry(t.clone(), &parent_key_id)?;
+			t.id
 		} else {
 			// Creating new transaction
 			let log_id = batch.next_tx_log_id(&parent_key_id)?;
@@ -360,14 +738,22 @@ where
 			};
 			let mut t = TxLogEntry::new(parent_key_id.clone(), entry_type, log_id);
 			t.confirmed = true;
+			t.recovered = true;
 			t.output_height = output.height;
 			t.amount_credited = output.value;
 			t.num_outputs = 1;
 			t.output_commits = vec![output.commit.clone()];
 			if let Ok(hdr_info) = node_client.get_header_info(t.output_height) {
 				t.update_confirmation_ts(hdr_info.confirmed_time);
+				// The history is lost, the block time is the best guess of the creation time
+				if let Some(ts) = t.confirmation_ts {
+					t.creation_ts = ts;
+				}
+			}
+			batch.save_tx_log_entry(t.clone(), &parent_key_id)?;
+			if !output.is_coinbase {
+				recovered_txs.insert((parent_key_id.clone(), output.height), t);
 			}
-			batch.save_tx_log_entry(t, &parent_key_id)?;
 			log_id
 		}
 	};
@@ -384,6 +770,8 @@ where
 		lock_height: output.lock_height,
 		is_coinbase: output.is_coinbase,
 		tx_log_entry: Some(log_id),
+		frozen: false,
+		tags: vec![],
 	});
 
 	let max_child_index = *found_parents.get(&parent_key_id).unwrap_or(&0);
@@ -395,6 +783,76 @@ where
 	Ok(())
 }
 
+/// Associate the kernels with the transactions that were inferred from the restored outputs.
+/// The block can contain many transactions, the kernel is known only if the block has a single
+/// not coinbase kernel.
+pub(crate) fn associate_recovered_kernels<'a, L, C, K>(
+	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
+	keychain_mask: Option<&SecretKey>,
+	recovered_txs: &HashMap<(Identifier, u64), TxLogEntry>,
+	status_send_channel: &Option<Sender<StatusMessage>>,
+) -> Result<(), Error>
+where
+	L: WalletLCProvider<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	if recovered_txs.is_empty() {
+		return Ok(());
+	}
+
+	let node_client = {
+		wallet_lock!(wallet_inst, w);
+		w.w2n_client().clone()
+	};
+
+	let mut updated: Vec<TxLogEntry> = Vec::new();
+	for ((_, height), tx) in recovered_txs {
+		// Best effort, the history is still usable without the kernel
+		let blocks = match node_client.get_blocks_by_height(*height, *height, 1) {
+			Ok(blocks) => blocks,
+			Err(e) => {
+				if let Some(ref s) = status_send_channel {
+					let _ = s.send(StatusMessage::Warning(format!(
+						"Unable to get the block at height {} to find the kernel of the recovered transaction, {}",
+						height, e
+					)));
+				}
+				continue;
+			}
+		};
+
+		let kernels: Vec<String> = blocks
+			.into_iter()
+			.filter(|b| b.header.height == *height)
+			.flat_map(|b| b.kernels)
+			.filter(|k| k.features != "Coinbase")
+			.map(|k| k.excess)
+			.collect();
+
+		if kernels.len() == 1 {
+			let excess = util::from_hex(&kernels[0]).map_err(|e| {
+				ErrorKind::GenericError(format!("Kernel excess parse error, {}", e))
+			})?;
+			let mut tx = tx.clone();
+			tx.kernel_excess = Some(pedersen::Commitment::from_vec(excess));
+			tx.kernel_lookup_min_height = Some(*height);
+			updated.push(tx);
+		}
+	}
+
+	if !updated.is_empty() {
+		wallet_lock!(wallet_inst, w);
+		let mut batch = w.batch(keychain_mask)?;
+		for tx in updated {
+			let parent_key_id = tx.parent_key_id.clone();
+			batch.save_tx_log_entry(tx, &parent_key_id)?;
+		}
+		batch.commit()?;
+	}
+	Ok(())
+}
+
 #[derive(Debug)]
 struct WalletOutputInfo {
 	updated: bool,  // true if data was updated, we need push it into DB
@@ -435,7 +893,7 @@ impl WalletOutputInfo {
 }
 
 #[derive(Debug)]
-struct WalletTxInfo {
+pub(crate) struct WalletTxInfo {
 	updated: bool,   // true if data was updated, we need push it into DB
 	tx_uuid: String, // transaction uuid++. Foramt:  "{}/{}/{}", uuid_str, tx.id, tx.parent_key_id.to_hex()
 	tx_log: TxLogEntry,
@@ -506,6 +964,7 @@ fn get_wallet_and_chain_data<'a, L, C, K>(
 	status_send_channel: &Option<Sender<StatusMessage>>,
 	show_progress: bool,
 	do_full_outputs_refresh: bool, // true expected at the first and in case of reorgs
+	resume: bool,                  // continue from the scan checkpoint if it is valid
 	replay_config: Option<ReplayMitigationConfig>,
 ) -> Result<
 	(
@@ -513,6 +972,7 @@ fn get_wallet_and_chain_data<'a, L, C, K>(
 		Vec<OutputResult>,                 // Chain outputs
 		HashMap<String, WalletTxInfo>,     // Slate based Transaction. Key: tx uuid
 		String,                            // Commit of the last output in the sequence
+		Option<u64>,                       // New outputs validation height, None - not changed
 	),
 	Error,
 >
@@ -539,6 +999,7 @@ where
 	// Key: transaction uuid
 	let mut transactions: HashMap<String, WalletTxInfo> = HashMap::new();
 	let chain_outs: Vec<OutputResult>;
+	let mut new_validated_height: Option<u64> = None;
 	{
 		wallet_lock!(wallet_inst.clone(), w);
 		// First, reading data from the wallet
@@ -631,10 +1092,30 @@ where
 		let height_deep_limit =
 			SYNC_BLOCKS_DEEPNESS + not_confirmed_txs / 2 + spendable_outputs / OUTPUT_TO_BLOCK;
 
+		// Blocks since the last validation have all the spent outputs. All unspent outputs are
+		// validated one by one if some blocks are not checked: there is a gap after the last
+		// validation or the node moved too far for the blocks. Or if the last validation is old.
+		let validated_height = w.last_outputs_validation_height()?;
+		let validation_gap = validated_height < start_height
+			|| end_height.saturating_sub(validated_height) > height_deep_limit as u64;
+		let full_outputs_validation = do_full_outputs_refresh
+			|| validated_height == 0
+			|| validation_gap
+			|| end_height.saturating_sub(validated_height) > OUTPUTS_VALIDATION_INTERVAL;
+		if validation_gap && !do_full_outputs_refresh && validated_height > 0 {
+			debug!(
+				"Validating all outputs, blocks after the validation at height {} are not checked",
+				validated_height
+			);
+		}
+
 		// We need to choose a strategy. If there are few blocks, it is really make sense request those blocks
-		if !do_full_outputs_refresh && (end_height - start_height <= height_deep_limit as u64) {
+		if !full_outputs_validation {
 			debug!("get_wallet_and_chain_data using block base strategy");
 
+			// No gap, the blocks continue the last validation
+			new_validated_height = Some(end_height);
+
 			// Validate kernels from transaction. Kernel are a source of truth
 			// Because of account transfer we might have 2 transactions with same kernel from the both sides.
 			let mut txkernel_to_txuuid: HashMap<String, Vec<String>> = HashMap::new();
@@ -683,23 +1164,24 @@ where
 				);
 
 				// printing the progress
-				if let Some(ref s) = status_send_channel {
-					let msg = format!(
-						"Checking {} blocks, Height: {} - {}",
-						next_h - cur_height + 1,
-						cur_height,
-						next_h,
-					);
-					// 10 - 90 %
-					let perc_complete = ((next_h + cur_height) / 2 - start_height) * 80
-						/ (end_height - start_height + 1)
-						+ 10;
-					let _ = s.send(StatusMessage::Scanning(
-						show_progress,
-						msg,
-						perc_complete as u8,
-					));
-				}
+				let msg = format!(
+					"Checking {} blocks, Height: {} - {}",
+					next_h - cur_height + 1,
+					cur_height,
+					next_h,
+				);
+				// 10 - 90 %
+				let perc_complete = ((next_h + cur_height) / 2 - start_height) * 80
+					/ (end_height - start_height + 1)
+					+ 10;
+				report_scan_progress(
+					status_send_channel,
+					show_progress,
+					msg,
+					perc_complete as u8,
+					Some(cur_height),
+					None,
+				);
 
 				blocks.extend(client.get_blocks_by_height(
 					cur_height,
@@ -844,18 +1326,85 @@ where
 			// Retrieve the actual PMMR index range we're looking for
 			let pmmr_range = client.height_range_to_pmmr_indices(start_height, Some(end_height))?;
 
+			// Continue the interrupted scan if it has the same range and the chain is not changed
+			let end_hash = client.get_header_info(end_height)?.hash;
+			let mut checkpoint = if resume { w.scan_checkpoint()? } else { None };
+			if let Some(cp) = &checkpoint {
+				if cp.start_height != start_height
+					|| cp.end_height > end_height
+					|| client.get_header_info(cp.end_height)?.hash != cp.end_hash
+				{
+					checkpoint = None;
+				}
+			}
+
+			// New scan replaces the checkpoint of the previous one
+			let mut clean_checkpoint = checkpoint.is_none();
+			let (first_index, mut prev_outputs, mut prev_self_spend) = match checkpoint {
+				Some(cp) => {
+					if let Some(ref s) = status_send_channel {
+						let _ = s.send(StatusMessage::Info(format!(
+							"Resuming the scan from height {}, PMMR index {}",
+							cp.last_height, cp.last_pmmr_index
+						)));
+					}
+					// Some of the found outputs might be spent since the checkpoint
+					let commits: Vec<pedersen::Commitment> = cp
+						.outputs
+						.iter()
+						.chain(cp.self_spend_candidates.iter())
+						.map(|o| o.commit.clone())
+						.collect();
+					let unspent = if commits.is_empty() {
+						HashMap::new()
+					} else {
+						client.get_outputs_from_node(&commits)?
+					};
+					let is_unspent = |o: &OutputResult| unspent.contains_key(&o.commit);
+					(
+						cp.last_pmmr_index + 1,
+						cp.outputs.into_iter().filter(is_unspent).collect(),
+						cp.self_spend_candidates
+							.into_iter()
+							.filter(is_unspent)
+							.collect(),
+					)
+				}
+				None => (pmmr_range.0, vec![], vec![]),
+			};
+
 			// Getting outputs that are published on the chain.
-			let chain_outs_pair = collect_chain_outputs(
+			let chain_outs_pair = collect_chain_outputs_with_checkpoints(
 				&keychain,
 				client,
-				pmmr_range.0,
+				first_index,
 				Some(pmmr_range.1),
 				status_send_channel,
 				show_progress,
 				replay_config,
+				&mut |last_pmmr_index, last_height, outputs, self_spend_candidates| {
+					let mut batch = w.batch(keychain_mask)?;
+					if clean_checkpoint {
+						batch.delete_scan_checkpoint()?;
+						clean_checkpoint = false;
+					}
+					batch.save_scan_checkpoint(&ScanCheckpoint {
+						start_height,
+						end_height,
+						end_hash: end_hash.clone(),
+						last_pmmr_index,
+						last_height,
+						outputs: outputs.to_vec(),
+						self_spend_candidates: self_spend_candidates.to_vec(),
+					})?;
+					batch.commit()?;
+					Ok(())
+				},
 			)?;
-			chain_outs = chain_outs_pair.0;
-			self_spend_candidate_list = chain_outs_pair.1;
+			prev_outputs.extend(chain_outs_pair.0);
+			prev_self_spend.extend(chain_outs_pair.1);
+			chain_outs = prev_outputs;
+			self_spend_candidate_list = prev_self_spend;
 
 			// Reporting user what outputs we found
 			if let Some(ref s) = status_send_channel {
@@ -920,6 +1469,7 @@ where
 
 			// Validate all 'active output' - Unspend and Locked if they still on the chain
 			// Spent and Unconfirmed news should come from the updates
+			new_validated_height = Some(end_height);
 			let wallet_outputs_to_check: Vec<pedersen::Commitment> = outputs
 				.values()
 				.filter(|out| out.output.is_spendable() && !out.commit.is_empty())
@@ -1073,7 +1623,13 @@ where
 		)?;
 	}
 
-	Ok((outputs, chain_outs, transactions, last_output))
+	Ok((
+		outputs,
+		chain_outs,
+		transactions,
+		last_output,
+		new_validated_height,
+	))
 }
 
 /// Check / repair wallet contents by scanning against chain
@@ -1088,12 +1644,15 @@ pub fn scan<'a, L, C, K>(
 	status_send_channel: &Option<Sender<StatusMessage>>,
 	show_progress: bool,
 	do_full_outputs_refresh: bool,
+	resume: bool,
 ) -> Result<(), Error>
 where
 	L: WalletLCProvider<'a, C, K>,
 	C: NodeClient + 'a,
 	K: Keychain + 'a,
 {
+	let _progress_guard = ScanProgressGuard::start(tip_height);
+
 	// First, get a definitive list of outputs we own from the chain
 	if let Some(ref s) = status_send_channel {
 		let _ = s.send(StatusMessage::Scanning(
@@ -1105,16 +1664,18 @@ where
 
 	// Collect the data form the chain and from the wallet
 	let replay_config = get_replay_config();
-	let (mut outputs, chain_outs, mut transactions, last_output) = get_wallet_and_chain_data(
-		wallet_inst.clone(),
-		keychain_mask.clone(),
-		start_height,
-		tip_height,
-		status_send_channel,
-		show_progress,
-		do_full_outputs_refresh,
-		Some(replay_config),
-	)?;
+	let (mut outputs, chain_outs, mut transactions, last_output, validated_height) =
+		get_wallet_and_chain_data(
+			wallet_inst.clone(),
+			keychain_mask.clone(),
+			start_height,
+			tip_height,
+			status_send_channel,
+			show_progress,
+			do_full_outputs_refresh,
+			resume,
+			Some(replay_config),
+		)?;
 
 	// Printing values for debug...
 	/*	{
@@ -1137,6 +1698,7 @@ where
 
 	// Validated outputs states against the chain
 	let mut found_parents: HashMap<Identifier, u32> = HashMap::new();
+	let mut recovered_txs: HashMap<(Identifier, u64), TxLogEntry> = HashMap::new();
 	let outputs2del = validate_outputs(
 		wallet_inst.clone(),
 		keychain_mask.clone(),
@@ -1146,6 +1708,15 @@ where
 		&transactions,
 		status_send_channel,
 		&mut found_parents,
+		&mut recovered_txs,
+	)?;
+
+	// Restored outputs got the inferred transactions, let's find their kernels
+	associate_recovered_kernels(
+		wallet_inst.clone(),
+		keychain_mask.clone(),
+		&recovered_txs,
+		status_send_channel,
 	)?;
 
 	// Processing slate based transactions. Just need to update 'confirmed flag' and height
@@ -1210,17 +1781,33 @@ where
 		wallet_lock!(wallet_inst, w);
 
 		let accounts: Vec<Identifier> = w.acct_path_iter().map(|m| m.path).collect();
+		// Checkpoint is covered by this scan, nothing to resume
+		let checkpoint_done = w
+			.scan_checkpoint()?
+			.map(|cp| cp.start_height >= start_height)
+			.unwrap_or(false);
 		let mut batch = w.batch(keychain_mask)?;
 
 		for par_id in &accounts {
 			batch.save_last_confirmed_height(par_id, tip_height)?;
 		}
+		if let Some(height) = validated_height {
+			batch.save_last_outputs_validation_height(height)?;
+		}
+		if checkpoint_done {
+			batch.delete_scan_checkpoint()?;
+		}
 		batch.commit()?;
 	}
 
-	// Cancel any cancellable transactions with an expired TTL
+	// Cancel any cancellable transactions with an expired TTL or age, according to the auto cancel policy
 	// We need to do that at the end when all scan data is updated and written. Otherwise data can be overwritten on updates
 	{
+		let policy = owner::get_auto_cancel_policy();
+		let age_cutoff = policy
+			.max_age_hours
+			.map(|h| Utc::now() - Duration::hours(h as i64));
+
 		wallet_lock!(wallet_inst, w);
 
 		let transactions =
@@ -1231,25 +1818,43 @@ where
 				continue;
 			}
 
-			if let Some(h) = tx_log.ttl_cutoff_height {
-				if tip_height >= h {
-					match tx::cancel_tx(
-						&mut **w,
-						keychain_mask,
-						&tx_log.parent_key_id,
-						Some(tx_log.id),
-						None,
-					) {
-						Err(e) => {
-							if let Some(ref s) = status_send_channel {
-								let _ = s.send(StatusMessage::Warning(format!(
-									"Unable to cancel TTL expired transaction {} because of error: {}",
-									tx_log.tx_slate_id.clone().unwrap_or(Uuid::nil()),
-									e
-								)));
-							}
+			let ttl_expired = policy.cancel_on_ttl
+				&& tx_log
+					.ttl_cutoff_height
+					.map(|h| tip_height >= h)
+					.unwrap_or(false);
+			let age_expired = (tx_log.tx_type == TxLogEntryType::TxSent
+				|| tx_log.tx_type == TxLogEntryType::TxReceived)
+				&& age_cutoff
+					.map(|c| tx_log.creation_ts < c)
+					.unwrap_or(false);
+
+			if ttl_expired || age_expired {
+				match tx::cancel_tx(
+					&mut **w,
+					keychain_mask,
+					&tx_log.parent_key_id,
+					Some(tx_log.id),
+					None,
+					true,
+				) {
+					Err(e) => {
+						if let Some(ref s) = status_send_channel {
+							let _ = s.send(StatusMessage::Warning(format!(
+								"Unable to cancel {} expired transaction {} because of error: {}",
+								if ttl_expired { "TTL" } else { "age" },
+								tx_log.tx_slate_id.clone().unwrap_or(Uuid::nil()),
+								e
+							)));
+						}
+					}
+					_ => {
+						if let Some(ref s) = status_send_channel {
+							let _ = s.send(StatusMessage::Info(format!(
+								"Transaction {} is cancelled because it is expired",
+								tx_log.tx_slate_id.clone().unwrap_or(Uuid::nil()),
+							)));
 						}
-						_ => (),
 					}
 				}
 			}
@@ -1279,6 +1884,7 @@ where
 		}
 	}*/
 
+	SCAN_PROGRESS.lock().percent = 100;
 	if let Some(ref s) = status_send_channel {
 		let _ = s.send(StatusMessage::ScanningComplete(
 			show_progress,
@@ -1302,6 +1908,7 @@ fn validate_outputs<'a, L, C, K>(
 	transaction: &HashMap<String, WalletTxInfo>,
 	status_send_channel: &Option<Sender<StatusMessage>>,
 	found_parents: &mut HashMap<Identifier, u32>,
+	recovered_txs: &mut HashMap<(Identifier, u64), TxLogEntry>,
 ) -> Result<Vec<OutputData>, Error>
 where
 	L: WalletLCProvider<'a, C, K>,
@@ -1384,6 +1991,7 @@ where
 					&commit2transactionuuid,
 					transaction,
 					found_parents,
+					recovered_txs,
 				)?;
 			}
 		}
@@ -1943,7 +2551,7 @@ where
 }
 
 // restore labels, account paths and child derivation indices
-fn restore_labels<'a, L, C, K>(
+pub(crate) fn restore_labels<'a, L, C, K>(
 	wallet_inst: Arc<Mutex<Box<dyn WalletInst<'a, L, C, K>>>>,
 	keychain_mask: Option<&SecretKey>,
 	found_parents: &HashMap<Identifier, u32>,
@@ -2161,6 +2769,6 @@ where
 		let _ = w.keychain(keychain_mask)?;
 		w.w2n_client().clone()
 	};
-	owner::post_tx(&client, &slate.tx, false)?;
+	owner::post_tx(&client, &slate.tx, Some(slate.id), false)?;
 	Ok(())
 }
@@ -14,6 +14,7 @@
 
 //! Selection of inputs for building transactions
 
+use crate::api_impl::types::InitTxArgs;
 use crate::error::{Error, ErrorKind};
 use crate::grin_core::core::amount_to_hr_string;
 use crate::grin_core::libtx::{
@@ -29,6 +30,7 @@ use crate::proof::proofaddress;
 use crate::slate::Slate;
 use crate::types::*;
 use grin_wallet_util::grin_util as util;
+use std::cmp;
 use std::collections::HashMap;
 use std::sync::RwLock;
 
@@ -37,6 +39,49 @@ lazy_static! {
 	/// MWC price will go up, the base fee better to be adjustedable. Normally miners are
 	/// dictating the fees.
 	static ref BASE_FEE: RwLock<Option<u64>> = RwLock::new(None);
+
+	/// Wallet level policy for the change outputs
+	static ref CHANGE_POLICY: RwLock<ChangePolicy> = RwLock::new(ChangePolicy::default());
+}
+
+/// Default limit for the number of the change outputs when the change is split
+pub const DEFAULT_MAX_SPLIT_CHANGE_OUTPUTS: usize = 10;
+
+/// Policy for the change outputs of the send transactions. The number of the change outputs
+/// that the caller asks for is adjusted by the change amount.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChangePolicy {
+	/// Change that is smaller than this amount is not created, it goes to the fee instead.
+	/// Such outputs cost more to spend than they are worth.
+	pub min_change: Option<u64>,
+	/// If set, the change is split into outputs of at least this amount
+	pub split_target: Option<u64>,
+	/// Max number of the change outputs that the split can produce
+	pub max_split_outputs: usize,
+}
+
+impl Default for ChangePolicy {
+	fn default() -> ChangePolicy {
+		ChangePolicy {
+			min_change: None,
+			split_target: None,
+			max_split_outputs: DEFAULT_MAX_SPLIT_CHANGE_OUTPUTS,
+		}
+	}
+}
+
+impl ChangePolicy {
+	/// Wallet policy with the send parameters overrides
+	pub fn for_send(args: &InitTxArgs) -> ChangePolicy {
+		let mut policy = get_change_policy();
+		if args.min_change_output.is_some() {
+			policy.min_change = args.min_change_output;
+		}
+		if args.change_split_target.is_some() {
+			policy.split_target = args.change_split_target;
+		}
+		policy
+	}
 }
 
 /// Set from config base fee units for all transaction.
@@ -53,6 +98,16 @@ pub fn get_base_fee() -> u64 {
 		.unwrap_or(crate::grin_core::libtx::DEFAULT_BASE_FEE)
 }
 
+/// Set from config the policy for the change outputs
+pub fn set_change_policy(policy: ChangePolicy) {
+	*CHANGE_POLICY.write().unwrap() = policy;
+}
+
+/// Read the wallet policy for the change outputs
+pub fn get_change_policy() -> ChangePolicy {
+	CHANGE_POLICY.read().unwrap().clone()
+}
+
 /// Initialize a transaction on the sender side, returns a corresponding
 /// libwallet transaction slate with the appropriate inputs selected,
 /// and saves the private wallet identifiers of our selected outputs
@@ -67,6 +122,7 @@ pub fn build_send_tx<'a, T: ?Sized, C, K>(
 	minimum_confirmations: u64,
 	max_outputs: usize,
 	change_outputs: usize,
+	change_policy: &ChangePolicy,
 	selection_strategy_is_use_all: bool,
 	parent_key_id: Identifier,
 	participant_id: usize,
@@ -92,6 +148,7 @@ where
 		minimum_confirmations,
 		max_outputs,
 		change_outputs,
+		change_policy,
 		selection_strategy_is_use_all,
 		&parent_key_id,
 		outputs,
@@ -162,6 +219,7 @@ pub fn lock_tx_context<'a, T: ?Sized, C, K>(
 	context: &Context,
 	address: Option<String>,
 	excess_override: Option<Commitment>,
+	status: TxStatus,
 ) -> Result<(), Error>
 where
 	T: WalletBackend<'a, C, K>,
@@ -227,6 +285,7 @@ where
 		}
 
 		t.address = address;
+		t.status = status;
 
 		if let Ok(e) = slate.calc_excess(Some(&keychain)) {
 			t.kernel_excess = Some(e)
@@ -300,6 +359,8 @@ where
 				lock_height: 0,
 				is_coinbase: false,
 				tx_log_entry: Some(t.id),
+				frozen: false,
+				tags: vec![],
 			})?;
 		}
 		batch.save_tx_log_entry(t.clone(), &parent_key_id)?;
@@ -463,6 +524,12 @@ where
 	t.output_commits = commit_ped;
 	t.messages = messages;
 	t.ttl_cutoff_height = slate.ttl_cutoff_height;
+	// Invoice issuer waits for the payer, receiver waits for the sender to finalize
+	t.status = if is_initiator {
+		TxStatus::AwaitingCounterparty
+	} else {
+		TxStatus::AwaitingFinalization
+	};
 	//add the offset to the database tx record.
 	let offset_skey = slate.tx.offset.secret_key()?;
 	let offset_commit = keychain.secp().commit(0, offset_skey)?;
@@ -493,6 +560,8 @@ where
 			lock_height: 0,
 			is_coinbase: false,
 			tx_log_entry: Some(log_id),
+			frozen: false,
+			tags: vec![],
 		})?;
 		i = i + 1;
 	}
@@ -515,6 +584,7 @@ pub fn select_send_tx<'a, T: ?Sized, C, K, B>(
 	minimum_confirmations: u64,
 	max_outputs: usize,
 	change_outputs: usize,
+	change_policy: &ChangePolicy,
 	selection_strategy_is_use_all: bool,
 	parent_key_id: &Identifier,
 	outputs: &Option<Vec<String>>, // outputs to include into the transaction
@@ -537,7 +607,7 @@ where
 	K: Keychain + 'a,
 	B: ProofBuild,
 {
-	let (coins, _total, amount, fee) = select_coins_and_fee(
+	let (coins, _total, amount, fee, change_outputs) = select_coins_and_fee(
 		wallet,
 		amount,
 		min_fee,
@@ -545,6 +615,7 @@ where
 		minimum_confirmations,
 		max_outputs,
 		change_outputs,
+		change_policy,
 		selection_strategy_is_use_all,
 		&parent_key_id,
 		outputs,  // outputs to include into the transaction
@@ -570,6 +641,7 @@ where
 
 /// Select outputs and calculating fee.
 /// fee - can be larger that standard fee, but never smaller.
+/// Number of the change outputs is adjusted by the change policy.
 pub fn select_coins_and_fee<'a, T: ?Sized, C, K>(
 	wallet: &mut T,
 	amount: u64,
@@ -578,6 +650,7 @@ pub fn select_coins_and_fee<'a, T: ?Sized, C, K>(
 	minimum_confirmations: u64,
 	max_outputs: usize,
 	change_outputs: usize,
+	change_policy: &ChangePolicy,
 	selection_strategy_is_use_all: bool,
 	parent_key_id: &Identifier,
 	outputs: &Option<Vec<String>>, // outputs to include into the transaction
@@ -587,9 +660,10 @@ pub fn select_coins_and_fee<'a, T: ?Sized, C, K>(
 ) -> Result<
 	(
 		Vec<OutputData>,
-		u64, // total
-		u64, // amount
-		u64, // fee
+		u64,   // total
+		u64,   // amount
+		u64,   // fee
+		usize, // change outputs
 	),
 	Error,
 >
@@ -690,7 +764,85 @@ where
 			})?;
 		}
 	}
-	Ok((coins, total, amount, fee))
+
+	let (change_outputs, fee) = apply_change_policy(
+		change_policy,
+		coins.len(),
+		routputs,
+		change_outputs,
+		max_outputs,
+		total,
+		amount,
+		fee,
+		min_fee,
+	);
+	Ok((coins, total, amount, fee, change_outputs))
+}
+
+/// Number of the change outputs and the fee after the change policy is applied to the selected
+/// coins. Change below min_change goes to the fee, large change is split into the split_target
+/// outputs. Fee is recalculated for the resulting number of outputs.
+fn apply_change_policy(
+	policy: &ChangePolicy,
+	inputs: usize,
+	routputs: usize,
+	change_outputs: usize,
+	max_outputs: usize,
+	total: u64,
+	amount: u64,
+	fee: u64,
+	min_fee: &Option<u64>,
+) -> (usize, u64) {
+	let change = total - amount - fee;
+	if change == 0 {
+		return (change_outputs, fee);
+	}
+
+	let mut outputs = change_outputs;
+	if let Some(target) = policy.split_target.filter(|t| *t > 0) {
+		let max_split = cmp::min(
+			policy.max_split_outputs,
+			max_outputs.saturating_sub(inputs + routputs),
+		);
+		outputs = cmp::max(outputs, cmp::min((change / target) as usize, max_split));
+	}
+	if let Some(min_change) = policy.min_change.filter(|m| *m > 0) {
+		outputs = cmp::min(outputs, (change / min_change) as usize);
+	}
+
+	while outputs > 0 {
+		let mut outputs_fee = tx_fee(inputs, routputs + outputs, 1, Some(get_base_fee()));
+		if let Some(min_fee) = min_fee {
+			outputs_fee = cmp::max(*min_fee, outputs_fee);
+		}
+		if total >= amount + outputs_fee {
+			let part = (total - amount - outputs_fee) / outputs as u64;
+			let too_small = part == 0
+				|| policy.min_change.map(|m| part < m).unwrap_or(false)
+				|| (outputs > change_outputs
+					&& policy.split_target.map(|t| part < t).unwrap_or(false));
+			if !too_small {
+				return (outputs, outputs_fee);
+			}
+		}
+		outputs -= 1;
+	}
+
+	// Only the dust is burned, the larger change is kept even if it is below the minimum
+	if change > get_base_fee() {
+		warn!(
+			"Change {} is below the minimum change output but above the base fee {}, keeping it",
+			change,
+			get_base_fee()
+		);
+		return (change_outputs, fee);
+	}
+
+	debug!(
+		"Change {} is below the minimum change output, adding it to the fee",
+		change
+	);
+	(0, total - amount)
 }
 
 /// Selects inputs and change for a transaction
@@ -841,7 +993,8 @@ where
 				})
 				.collect::<Vec<OutputData>>();
 		}
-		None => (),
+		// Frozen outputs can be spent only if the user lists them explicitly
+		None => eligible.retain(|out| !out.frozen),
 	}
 
 	let max_available = eligible.len();
@@ -973,3 +1126,75 @@ where
 	slate.tx.offset = slate.offset.clone();
 	Ok(())
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	const MWC: u64 = 1_000_000_000;
+
+	#[test]
+	fn change_policy() {
+		let fee_for = |outputs: usize| tx_fee(2, outputs, 1, Some(get_base_fee()));
+		let amount = 5 * MWC;
+
+		// No policy, requested number of outputs is kept
+		let fee = fee_for(2);
+		let total = amount + fee + 3 * MWC;
+		let no_policy = ChangePolicy::default();
+		assert_eq!(
+			apply_change_policy(&no_policy, 2, 1, 1, 500, total, amount, fee, &None),
+			(1, fee)
+		);
+
+		// Dust change goes to the fee
+		let dust = ChangePolicy {
+			min_change: Some(MWC / 100),
+			..ChangePolicy::default()
+		};
+		let total = amount + fee + 1000;
+		assert_eq!(
+			apply_change_policy(&dust, 2, 1, 1, 500, total, amount, fee, &None),
+			(0, total - amount)
+		);
+		// At most the base fee is burned, larger change keeps the requested outputs
+		let burn = ChangePolicy {
+			min_change: Some(MWC),
+			..ChangePolicy::default()
+		};
+		let total = amount + fee + get_base_fee();
+		assert_eq!(
+			apply_change_policy(&burn, 2, 1, 1, 500, total, amount, fee, &None),
+			(0, total - amount)
+		);
+		let total = amount + fee + MWC / 2;
+		assert_eq!(
+			apply_change_policy(&burn, 2, 1, 1, 500, total, amount, fee, &None),
+			(1, fee)
+		);
+
+		// Large change is split into the target size outputs
+		let split = ChangePolicy {
+			split_target: Some(MWC),
+			max_split_outputs: 10,
+			..ChangePolicy::default()
+		};
+		let total = amount + fee + 3 * MWC + MWC / 2;
+		assert_eq!(
+			apply_change_policy(&split, 2, 1, 1, 500, total, amount, fee, &None),
+			(3, fee_for(4))
+		);
+		// Split is limited by the max number of outputs
+		let total = amount + fee + 50 * MWC;
+		assert_eq!(
+			apply_change_policy(&split, 2, 1, 1, 500, total, amount, fee, &None),
+			(10, fee_for(11))
+		);
+		// Extra fee for the split outputs makes the last part too small
+		let total = amount + fee + 2 * MWC;
+		assert_eq!(
+			apply_change_policy(&split, 2, 1, 1, 500, total, amount, fee, &None),
+			(1, fee)
+		);
+	}
+}
@@ -24,6 +24,7 @@ use crate::grin_util::secp::key::SecretKey;
 use crate::grin_util::secp::{pedersen, Signature};
 use crate::grin_util::Mutex;
 use crate::internal::{selection, updater};
+use crate::log_privacy;
 use crate::proof::crypto;
 use crate::proof::crypto::Hex;
 use crate::proof::proofaddress;
@@ -31,7 +32,10 @@ use crate::proof::proofaddress::{get_address_index, ProvableAddress};
 use crate::proof::tx_proof::{push_proof_for_slate, TxProof};
 use crate::signature::Signature as otherSignature;
 use crate::slate::Slate;
-use crate::types::{Context, NodeClient, StoredProofInfo, TxLogEntryType, WalletBackend};
+use crate::types::{
+	Context, NodeClient, OutputStatus, StoredProofInfo, TxLogEntry, TxLogEntryType, TxStatus,
+	WalletBackend,
+};
 use crate::InitTxArgs;
 use crate::{Error, ErrorKind};
 use ed25519_dalek::Keypair as DalekKeypair;
@@ -62,6 +66,26 @@ where
 	K: Keychain + 'a,
 {
 	let current_height = wallet.w2n_client().get_chain_tip()?.0;
+	new_tx_slate_at_height(
+		amount,
+		num_participants,
+		use_test_rng,
+		ttl_blocks,
+		compact_slate,
+		current_height,
+	)
+}
+
+/// Creates a new slate for the chain height that is known to the caller. The offline wallet
+/// doesn't have a node, it gets the height from the offline signing bundle.
+pub fn new_tx_slate_at_height(
+	amount: u64,
+	num_participants: usize,
+	use_test_rng: bool,
+	ttl_blocks: Option<u64>,
+	compact_slate: bool,
+	current_height: u64,
+) -> Result<Slate, Error> {
 	let mut slate = Slate::blank(num_participants, compact_slate);
 	if let Some(b) = ttl_blocks {
 		slate.ttl_cutoff_height = Some(current_height + b);
@@ -105,6 +129,7 @@ pub fn estimate_send_tx<'a, T: ?Sized, C, K>(
 	minimum_confirmations: u64,
 	max_outputs: usize,
 	num_change_outputs: usize,
+	change_policy: &selection::ChangePolicy,
 	selection_strategy_is_use_all: bool,
 	parent_key_id: &Identifier,
 	outputs: &Option<Vec<String>>, // outputs to include into the transaction
@@ -134,7 +159,7 @@ where
 	// according to plan
 	// This function is just a big helper to do all of that, in theory
 	// this process can be split up in any way
-	let (_coins, total, _amount, fee) = selection::select_coins_and_fee(
+	let (_coins, total, _amount, fee, _change_outputs) = selection::select_coins_and_fee(
 		wallet,
 		amount,
 		min_fee,
@@ -142,6 +167,7 @@ where
 		minimum_confirmations,
 		max_outputs,
 		num_change_outputs,
+		change_policy,
 		selection_strategy_is_use_all,
 		parent_key_id,
 		outputs,
@@ -162,6 +188,7 @@ pub fn add_inputs_to_slate<'a, T: ?Sized, C, K>(
 	minimum_confirmations: u64,
 	max_outputs: usize,
 	num_change_outputs: usize,
+	change_policy: &selection::ChangePolicy,
 	selection_strategy_is_use_all: bool,
 	parent_key_id: &Identifier,
 	participant_id: usize,
@@ -196,6 +223,7 @@ where
 		minimum_confirmations,
 		max_outputs,
 		num_change_outputs,
+		change_policy,
 		selection_strategy_is_use_all,
 		parent_key_id.clone(),
 		participant_id,
@@ -328,7 +356,7 @@ where
 
 	// we're just going to run a selection to get the potential fee,
 	// but this won't be locked
-	let (_coins, _total, _amount, fee) = selection::select_coins_and_fee(
+	let (_coins, _total, _amount, fee, _change_outputs) = selection::select_coins_and_fee(
 		wallet,
 		init_tx_args.amount,
 		&init_tx_args.min_fee,
@@ -336,6 +364,7 @@ where
 		init_tx_args.minimum_confirmations,
 		init_tx_args.max_outputs as usize,
 		init_tx_args.num_change_outputs as usize,
+		&selection::ChangePolicy::for_send(init_tx_args),
 		init_tx_args.selection_strategy_is_use_all,
 		&parent_key_id,
 		&init_tx_args.outputs,
@@ -408,18 +437,20 @@ where
 	slate.fill_round_2(keychain.secp(), sec_key, sec_nonce, participant_id)?;
 
 	// Final transaction can be built by anyone at this stage
-	trace!("Slate to finalize is: {:?}", slate);
+	trace!("Slate to finalize is: {}", log_privacy::slate(slate));
 	slate.finalize(&keychain)?;
 	Ok(())
 }
 
-/// Rollback outputs associated with a transaction in the wallet
+/// Rollback outputs associated with a transaction in the wallet. Expired transactions
+/// are recorded with the Expired status.
 pub fn cancel_tx<'a, T: ?Sized, C, K>(
 	wallet: &mut T,
 	keychain_mask: Option<&SecretKey>,
 	parent_key_id: &Identifier,
 	tx_id: Option<u32>,
 	tx_slate_id: Option<Uuid>,
+	expired: bool,
 ) -> Result<(), Error>
 where
 	T: WalletBackend<'a, C, K>,
@@ -452,6 +483,39 @@ where
 	if tx.confirmed {
 		return Err(ErrorKind::TransactionNotCancellable(tx_id_string).into());
 	}
+	// Kernel can be already on the chain even if the wallet doesn't know about that yet.
+	// Cancelling such transaction will break the balance until the rescan, so repairing
+	// the confirmed status instead. The check is best effort, the offline wallet still can
+	// cancel.
+	if let Some(excess) = &tx.kernel_excess {
+		let client = wallet.w2n_client().clone();
+		let min_height = std::cmp::max(1, tx.kernel_lookup_min_height.unwrap_or(1));
+		let kernel = client.get_chain_tip().and_then(|(tip_height, _, _)| {
+			client.get_kernel(excess, Some(min_height), Some(tip_height))
+		});
+		match kernel {
+			Ok(Some((_, height, _))) => {
+				let confirmed_time = client
+					.get_header_info(height)
+					.map(|hdr| hdr.confirmed_time)
+					.unwrap_or(String::new());
+				confirm_tx_and_outputs(
+					wallet,
+					keychain_mask,
+					tx,
+					height,
+					confirmed_time,
+					parent_key_id,
+				)?;
+				return Err(ErrorKind::TransactionKernelOnChain(tx_id_string, height).into());
+			}
+			Ok(None) => (),
+			Err(e) => warn!(
+				"Unable to check if the kernel of transaction {} is on the chain, cancelling anyway. {}",
+				tx_id_string, e
+			),
+		}
+	}
 	// get outputs associated with tx
 	let res = updater::retrieve_outputs(
 		wallet,
@@ -463,10 +527,60 @@ where
 		None,
 	)?;
 	let outputs = res.iter().map(|m| m.output.clone()).collect();
+	let mut tx = tx;
+	if expired {
+		tx.status = TxStatus::Expired;
+	}
 	updater::cancel_tx_and_outputs(wallet, keychain_mask, tx, outputs, parent_key_id)?;
 	Ok(())
 }
 
+/// Mark the transaction and its outputs as confirmed at the height where its kernel was found.
+/// The inputs become spent and the new outputs unspent, the same as the next update would do.
+fn confirm_tx_and_outputs<'a, T: ?Sized, C, K>(
+	wallet: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	tx: TxLogEntry,
+	height: u64,
+	confirmed_time: String,
+	parent_key_id: &Identifier,
+) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let outputs = updater::retrieve_outputs(
+		wallet,
+		keychain_mask,
+		true,
+		Some(&tx),
+		&parent_key_id,
+		None,
+		None,
+	)?;
+	let mut batch = wallet.batch(keychain_mask)?;
+	for o in outputs {
+		let mut o = o.output;
+		match o.status {
+			OutputStatus::Locked => o.status = OutputStatus::Spent,
+			OutputStatus::Unconfirmed => {
+				o.status = OutputStatus::Unspent;
+				o.height = height;
+			}
+			_ => continue,
+		}
+		batch.save(o)?;
+	}
+	let mut tx = tx;
+	tx.confirmed = true;
+	tx.output_height = height;
+	tx.update_confirmation_ts(confirmed_time);
+	batch.save_tx_log_entry(tx, parent_key_id)?;
+	batch.commit()?;
+	Ok(())
+}
+
 /// Update the stored transaction (this update needs to happen when the TX is finalized)
 pub fn update_stored_tx<'a, T: ?Sized, C, K>(
 	wallet: &mut T,
@@ -559,6 +673,7 @@ where
 	}
 
 	wallet.store_tx(&format!("{}", slate.id), &slate.tx)?;
+	tx.status = TxStatus::AwaitingConfirmation;
 
 	let mut batch = wallet.batch(keychain_mask)?;
 	batch.save_tx_log_entry(tx, &parent_key)?;
@@ -608,7 +723,10 @@ pub fn payment_proof_message(
 ) -> Result<String, Error> {
 	let mut message = String::new();
 	debug!("the kernel excess is {:?}", kernel_commitment.0.to_vec());
-	debug!("the sender public key is {}", &sender_address_publickey);
+	debug!(
+		"the sender public key is {}",
+		log_privacy::address(&sender_address_publickey)
+	);
 	message.push_str(&util::to_hex(&kernel_commitment.0));
 	message.push_str(&sender_address_publickey);
 	message.push_str(&amount.to_string());
@@ -15,9 +15,12 @@
 //! Utilities to check the status of all the outputs we have stored in
 //! the wallet storage and update them.
 
+use chrono::Utc;
+use std::cmp;
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use crate::api_impl::owner;
 use crate::error::{Error, ErrorKind};
 use crate::grin_core::consensus::reward;
 use crate::grin_core::core::{Output, TxKernel};
@@ -32,7 +35,7 @@ use crate::internal::keys;
 use crate::types::{
 	NodeClient, OutputData, OutputStatus, TxLogEntry, TxLogEntryType, WalletBackend, WalletInfo,
 };
-use crate::{BlockFees, CbData, OutputCommitMapping};
+use crate::{BlockFees, CbData, HistoryBucket, OutputCommitMapping, OutputQueryArgs};
 
 /// Retrieve all of the outputs (doesn't attempt to update from node)
 pub fn retrieve_outputs<'a, T: ?Sized, C, K>(
@@ -82,6 +85,7 @@ where
 
 	outputs.sort_by_key(|out| out.n_child);
 	let keychain = wallet.keychain(keychain_mask)?;
+	let current_height = wallet.last_confirmed_height()?;
 
 	// Key: tx_log id;  Value: true if active, false if cancelled
 	let tx_log_is_active: HashMap<u32, bool> = wallet
@@ -102,17 +106,7 @@ where
 			continue;
 		}
 
-		let commit = match out.commit.clone() {
-			Some(c) => pedersen::Commitment::from_vec(util::from_hex(&c).map_err(|e| {
-				ErrorKind::GenericError(format!("Unable to parse HEX commit {}, {}", c, e))
-			})?),
-			None => keychain // TODO: proper support for different switch commitment schemes
-				.commit(out.value, &out.key_id, SwitchCommitmentType::Regular)?,
-		};
-		res.push(OutputCommitMapping {
-			output: out,
-			commit,
-		});
+		res.push(output_commit_mapping(&keychain, out, current_height)?);
 	}
 
 	if pagination_len.is_some() || pagination_start.is_some() {
@@ -138,6 +132,80 @@ where
 	}
 }
 
+/// Retrieve the outputs that match the query. The filters are applied to the stored data,
+/// the commits are built for the requested page only.
+pub fn query_outputs<'a, T: ?Sized, C, K>(
+	wallet: &mut T,
+	keychain_mask: Option<&SecretKey>,
+	parent_key_id: &Identifier,
+	query: &OutputQueryArgs,
+) -> Result<Vec<OutputCommitMapping>, Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	// Unconfirmed outputs of the cancelled transactions are hidden, the same as for retrieve_outputs
+	let tx_log_cancelled: HashSet<u32> = wallet
+		.tx_log_iter()
+		.filter(|tx_log| tx_log.parent_key_id == *parent_key_id && tx_log.is_cancelled())
+		.map(|tx_log| tx_log.id)
+		.collect();
+
+	let mut outputs = wallet
+		.iter()
+		.filter(|out| {
+			out.root_key_id == *parent_key_id
+				&& query.matches(out)
+				&& !(out.status == OutputStatus::Unconfirmed
+					&& out
+						.tx_log_entry
+						.map(|id| tx_log_cancelled.contains(&id))
+						.unwrap_or(false))
+		})
+		.collect::<Vec<_>>();
+	outputs.sort_by_key(|out| out.n_child);
+
+	let keychain = wallet.keychain(keychain_mask)?;
+	let current_height = wallet.last_confirmed_height()?;
+	paginate(outputs.into_iter(), query.offset, query.limit)
+		.into_iter()
+		.map(|out| output_commit_mapping(&keychain, out, current_height))
+		.collect()
+}
+
+/// Output with its commit, the commit is built if the wallet doesn't have it
+fn output_commit_mapping<K: Keychain>(
+	keychain: &K,
+	out: OutputData,
+	current_height: u64,
+) -> Result<OutputCommitMapping, Error> {
+	let commit = match out.commit.clone() {
+		Some(c) => pedersen::Commitment::from_vec(util::from_hex(&c).map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to parse HEX commit {}, {}", c, e))
+		})?),
+		None => keychain // TODO: proper support for different switch commitment schemes
+			.commit(out.value, &out.key_id, SwitchCommitmentType::Regular)?,
+	};
+	Ok(OutputCommitMapping {
+		spendable_at_height: out.spendable_at_height(current_height),
+		output: out,
+		commit,
+	})
+}
+
+/// Skip `offset` items and return at most `limit` of the rest
+pub fn paginate<T>(
+	items: impl Iterator<Item = T>,
+	offset: Option<u32>,
+	limit: Option<u32>,
+) -> Vec<T> {
+	items
+		.skip(offset.unwrap_or(0) as usize)
+		.take(limit.map(|l| l as usize).unwrap_or(std::usize::MAX))
+		.collect()
+}
+
 /// Retrieve all of the transaction entries, or a particular entry
 /// if `parent_key_id` is set, only return entries from that key
 pub fn retrieve_txs<'a, T: ?Sized, C, K>(
@@ -213,6 +281,66 @@ where
 	}
 }
 
+/// Aggregate the confirmed transactions into the time buckets of `bucket_sec` seconds, from the
+/// first transaction up to `now_ts`. Buckets without transactions keep the previous balance.
+pub fn history_series(txs: &Vec<TxLogEntry>, bucket_sec: u64, now_ts: i64) -> Vec<HistoryBucket> {
+	let bucket_sec = cmp::max(bucket_sec, 1) as i64;
+	let bucket_start = |ts: i64| ts - ts.rem_euclid(bucket_sec);
+
+	let mut txs: Vec<(i64, &TxLogEntry)> = txs
+		.iter()
+		.filter(|t| t.confirmed)
+		.map(|t| (t.confirmation_ts.unwrap_or(t.creation_ts).timestamp(), t))
+		.collect();
+	txs.sort_by_key(|(ts, _)| *ts);
+
+	let mut res: Vec<HistoryBucket> = vec![];
+	let first_ts = match txs.first() {
+		Some((ts, _)) => bucket_start(*ts),
+		None => return res,
+	};
+
+	let mut balance: i64 = 0;
+	let mut txs = txs.into_iter().peekable();
+	let mut start_ts = first_ts;
+	while start_ts <= cmp::max(bucket_start(now_ts), first_ts) || txs.peek().is_some() {
+		let mut bucket = HistoryBucket {
+			start_ts,
+			balance: 0,
+			fees_paid: 0,
+			amount_in: 0,
+			amount_out: 0,
+			tx_count: 0,
+		};
+		while txs
+			.peek()
+			.map(|(ts, _)| *ts < start_ts + bucket_sec)
+			.unwrap_or(false)
+		{
+			let (_, t) = txs.next().unwrap();
+			balance += t.amount_credited as i64 - t.amount_debited as i64;
+			bucket.tx_count += 1;
+			match t.tx_type {
+				TxLogEntryType::TxSent => {
+					let fee = t.fee.unwrap_or(0);
+					bucket.fees_paid += fee;
+					bucket.amount_out += t
+						.amount_debited
+						.saturating_sub(t.amount_credited)
+						.saturating_sub(fee);
+				}
+				_ => {
+					bucket.amount_in += t.amount_credited.saturating_sub(t.amount_debited);
+				}
+			}
+		}
+		bucket.balance = cmp::max(balance, 0) as u64;
+		res.push(bucket);
+		start_ts += bucket_sec;
+	}
+	res
+}
+
 /// Cancel transaction and associated outputs
 pub fn cancel_tx_and_outputs<'a, T: ?Sized, C, K>(
 	wallet: &mut T,
@@ -268,11 +396,18 @@ where
 		.iter()
 		.filter(|out| out.root_key_id == *parent_key_id);
 
-	// Key: tx_log id;  Value: true if active, false if cancelled
+	// Key: tx_log id;  Value: true if active, false if cancelled or expired receive
+	let policy = owner::get_auto_cancel_policy();
+	let now = Utc::now();
 	let tx_log_cancellation_status: HashMap<u32, bool> = wallet
 		.tx_log_iter()
 		.filter(|tx_log| tx_log.parent_key_id == *parent_key_id)
-		.map(|tx_log| (tx_log.id, !tx_log.is_cancelled()))
+		.map(|tx_log| {
+			(
+				tx_log.id,
+				!tx_log.is_cancelled() && !policy.is_receive_expired(&tx_log, now),
+			)
+		})
 		.collect();
 
 	let mut unspent_total = 0;
@@ -280,12 +415,17 @@ where
 	let mut awaiting_finalization_total = 0;
 	let mut unconfirmed_total = 0;
 	let mut locked_total = 0;
+	let mut immature_spendable_at_height: Option<u64> = None;
 
 	for out in outputs {
 		match out.status {
 			OutputStatus::Unspent => {
 				if out.is_coinbase && out.lock_height > current_height {
 					immature_total += out.value;
+					immature_spendable_at_height = Some(
+						immature_spendable_at_height
+							.map_or(out.lock_height, |h| std::cmp::min(h, out.lock_height)),
+					);
 				} else if out.num_confirmations(current_height) < minimum_confirmations {
 					// Treat anything less than minimum confirmations as "unconfirmed".
 					unconfirmed_total += out.value;
@@ -325,6 +465,8 @@ where
 		amount_immature: immature_total,
 		amount_locked: locked_total,
 		amount_currently_spendable: unspent_total,
+		immature_spendable_at_height,
+		amount_committed_to_swaps: None,
 	})
 }
 
@@ -392,6 +534,8 @@ where
 			lock_height: lock_height,
 			is_coinbase: true,
 			tx_log_entry: None,
+			frozen: false,
+			tags: vec![],
 		})?;
 		batch.commit()?;
 	}
@@ -418,3 +562,74 @@ where
 	)?;
 	Ok((out, kern, block_fees))
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::grin_keychain::ExtKeychain;
+	use chrono::{TimeZone, Utc};
+
+	fn tx(
+		id: u32,
+		tx_type: TxLogEntryType,
+		ts: i64,
+		credited: u64,
+		debited: u64,
+		fee: u64,
+	) -> TxLogEntry {
+		let mut t = TxLogEntry::new(ExtKeychain::derive_key_id(2, 0, 0, 0, 0), tx_type, id);
+		t.confirmed = true;
+		t.confirmation_ts = Some(Utc.timestamp(ts, 0));
+		t.amount_credited = credited;
+		t.amount_debited = debited;
+		t.fee = if fee > 0 { Some(fee) } else { None };
+		t
+	}
+
+	#[test]
+	fn test_history_series() {
+		let day = 86400;
+		let mut unconfirmed = tx(3, TxLogEntryType::TxReceived, day + 10, 500, 0, 0);
+		unconfirmed.confirmed = false;
+		let txs = vec![
+			tx(0, TxLogEntryType::TxReceived, day + 100, 1000, 0, 0),
+			tx(1, TxLogEntryType::TxSent, 3 * day + 5, 300, 1000, 10),
+			unconfirmed,
+		];
+
+		let series = history_series(&txs, day as u64, 4 * day + 1);
+		assert_eq!(series.len(), 4);
+		assert_eq!(series[0].start_ts, day);
+		assert_eq!(series[0].balance, 1000);
+		assert_eq!(series[0].amount_in, 1000);
+		assert_eq!(series[0].tx_count, 1);
+		// Empty bucket keeps the balance
+		assert_eq!(series[1].balance, 1000);
+		assert_eq!(series[1].tx_count, 0);
+		assert_eq!(series[2].balance, 300);
+		assert_eq!(series[2].fees_paid, 10);
+		assert_eq!(series[2].amount_out, 690);
+		assert_eq!(series[3].start_ts, 4 * day);
+		assert_eq!(series[3].balance, 300);
+
+		assert!(history_series(&vec![], day as u64, 4 * day).is_empty());
+	}
+
+	#[test]
+	fn test_paginate() {
+		let items = || (0..5u32).into_iter();
+		assert_eq!(paginate(items(), None, None), vec![0, 1, 2, 3, 4]);
+		assert_eq!(paginate(items(), Some(1), Some(2)), vec![1, 2]);
+		assert_eq!(paginate(items(), Some(3), None), vec![3, 4]);
+		assert_eq!(paginate(items(), None, Some(3)), vec![0, 1, 2]);
+		// Last page is shorter
+		assert_eq!(paginate(items(), Some(4), Some(3)), vec![4]);
+		// Offset past the end
+		assert!(paginate(items(), Some(5), None).is_empty());
+		assert!(paginate(items(), Some(100), Some(10)).is_empty());
+		// Zero limit
+		assert!(paginate(items(), None, Some(0)).is_empty());
+		assert!(paginate(items(), Some(2), Some(0)).is_empty());
+		assert!(paginate(std::iter::empty::<u32>(), Some(1), Some(1)).is_empty());
+	}
+}
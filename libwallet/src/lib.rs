@@ -55,21 +55,41 @@ extern crate crc;
 
 pub mod address;
 pub mod api_impl;
+pub mod audit_log;
+pub mod backup;
 /// Ring prev version internals that are needed for our internal encription functionality
 mod error;
 pub mod internal;
+pub mod listener_limits;
+pub mod listener_status;
+pub mod log_privacy;
+pub mod migration;
+pub mod multisig;
+pub mod node_cache;
+pub mod notifications;
+pub mod payment_id;
+pub mod payment_uri;
+pub mod price;
 pub mod proof;
+pub mod proof_verifier;
+pub mod proxy;
+pub mod receive_policy;
+pub mod secure_api;
 mod slate;
+pub mod slate_limits;
 pub mod slate_versions;
 pub mod slatepack;
 /// Atomic Swap library
 pub mod swap;
+pub mod tx_hooks;
+pub mod tx_import;
+pub mod tx_mirror;
 mod types;
 extern crate bitcoin as bitcoin_lib;
 extern crate bitcoin_hashes;
 extern crate zcash_primitives as zcash;
 
-pub use crate::slatepack::{SlatePurpose, Slatepack, SlatepackArmor, Slatepacker};
+pub use crate::slatepack::{SlateEnvelope, SlatePurpose, Slatepack, SlatepackArmor, Slatepacker};
 
 pub use bitcoin::Address as BitcoinAddress;
 
@@ -77,27 +97,32 @@ pub use crate::error::{Error, ErrorKind};
 pub use crate::slate::{ParticipantData, ParticipantMessageData, ParticipantMessages, Slate};
 pub use crate::slate_versions::{
 	SlateVersion, VersionedCoinbase, VersionedSlate, CURRENT_SLATE_VERSION,
-	GRIN_BLOCK_HEADER_VERSION,
+	GRIN_BLOCK_HEADER_VERSION, GRIN_V4_TARGET_SLATE_VERSION,
 };
 pub use api_impl::foreign;
 pub use api_impl::owner;
 pub use api_impl::owner_eth;
 pub use api_impl::owner_libp2p;
+pub use api_impl::owner_multisig;
 pub use api_impl::owner_swap;
 pub use api_impl::owner_updater::StatusMessage;
 pub use api_impl::types::{
-	BlockFees, InitTxArgs, InitTxSendArgs, IssueInvoiceTxArgs, NodeHeightResult,
-	OutputCommitMapping, PaymentProof, ReplayMitigationConfig, SendTXArgs, SwapStartArgs,
+	AccountSummaryInfo, AutoCancelPolicy, BlockFees, ConsensusParams, GcArgs, GcResult, HistoryBucket, InitTxArgs,
+	InitTxSendArgs, IssueInvoiceTxArgs, KernelLookupResult, MultisigCreateArgs, NodeHeightResult,
+	OutputCommitMapping, OutputQueryArgs, OutputTagArgs, PaymentProof, PublicProofAddress, ReissueTxArgs,
+	ReplayMitigationConfig, ScanProgress, SendTXArgs, SwapStartArgs, TxLabelArgs, TxQueryArgs, UtxoSnapshot, UtxoSnapshotOutput,
 	VersionInfo,
 };
-pub use internal::scan::{scan, set_replay_config};
+pub use internal::check::{CheckCategory, CheckOutput, CheckReport, CheckSwap, CheckTx};
+pub use internal::scan::{get_scan_threads, scan, set_replay_config, set_scan_threads};
 pub use proof::tx_proof::TxProof;
 pub use proof::tx_proof::{proof_ok, verify_tx_proof_wrapper};
 pub use slate_versions::ser as dalek_ser;
 pub use types::{
-	AcctPathMapping, BlockIdentifier, CbData, Context, HeaderInfo, NodeClient, NodeVersionInfo,
-	OutputData, OutputStatus, ScannedBlockInfo, StoredProofInfo, TxLogEntry, TxLogEntryType,
-	WalletBackend, WalletInfo, WalletInst, WalletLCProvider, WalletOutputBatch,
+	AcctPathMapping, AddressMapping, BlockIdentifier, CbData, Contact, Context, HeaderInfo, NodeClient, NodeVersionInfo,
+	OutputData, OutputStatus, ScanCheckpoint, ScannedBlockInfo, StoredProofInfo, TxLogEntry,
+	TxLogEntryType, TxStatus, WalletBackend, WalletInfo, WalletInst, WalletLCProvider,
+	WalletOutputBatch,
 };
 
 pub use api_impl::foreign::{get_receive_account, set_receive_account};
@@ -0,0 +1,236 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rate limiting and the bound of the concurrent requests for the HTTP listeners. The owner and
+//! the foreign listeners share the settings, but every listener has own limits and metrics.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::Instant;
+
+/// Client buckets are cleaned up when there are more of them
+const MAX_IDLE_BUCKETS: usize = 1024;
+
+/// Limits for the HTTP listener requests
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListenerLimits {
+	/// Requests per minute from the same client, 0 - no limit
+	pub rate_limit: u32,
+	/// Max number of the requests that are processed at the same time, 0 - no limit
+	pub max_concurrent: usize,
+	/// Identify the clients by the X-Forwarded-For and X-Real-IP headers. Only for the
+	/// listeners behind a reverse proxy, otherwise the client can set any address.
+	pub trust_proxy_headers: bool,
+}
+
+impl Default for ListenerLimits {
+	fn default() -> Self {
+		ListenerLimits {
+			rate_limit: 300,
+			max_concurrent: 32,
+			trust_proxy_headers: false,
+		}
+	}
+}
+
+/// Request counters of the listener
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ListenerMetrics {
+	/// Listener name, 'owner' or 'foreign'
+	pub listener: String,
+	/// Accepted requests
+	pub requests: u64,
+	/// Requests rejected because the client exceeded the rate limit
+	pub rate_limited: u64,
+	/// Requests rejected because of too many concurrent requests
+	pub concurrency_rejected: u64,
+	/// Requests that are processed now
+	pub in_flight: usize,
+	/// Max number of the requests that were processed at the same time
+	pub max_in_flight: usize,
+}
+
+/// Reason to reject the request
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimitRejection {
+	/// The client exceeded the rate limit, it can retry after the number of seconds
+	RateLimited(u64),
+	/// The listener processes too many requests
+	TooManyRequests,
+}
+
+// Token bucket of the client
+struct Bucket {
+	tokens: f64,
+	updated: Instant,
+}
+
+/// Request limiter of the listener
+pub struct RequestLimiter {
+	buckets: Mutex<HashMap<String, Bucket>>,
+	in_flight: AtomicUsize,
+	metrics: Mutex<ListenerMetrics>,
+}
+
+/// Request slot, it is released when the guard is dropped
+pub struct RequestGuard {
+	limiter: &'static RequestLimiter,
+}
+
+impl Drop for RequestGuard {
+	fn drop(&mut self) {
+		let in_flight = self.limiter.in_flight.fetch_sub(1, Ordering::SeqCst) - 1;
+		self.limiter.metrics.lock().unwrap().in_flight = in_flight;
+	}
+}
+
+impl RequestLimiter {
+	fn new(listener: &str) -> Self {
+		RequestLimiter {
+			buckets: Mutex::new(HashMap::new()),
+			in_flight: AtomicUsize::new(0),
+			metrics: Mutex::new(ListenerMetrics {
+				listener: listener.to_string(),
+				..Default::default()
+			}),
+		}
+	}
+
+	/// Check the limits for the request from the client. The request is accepted if the guard
+	/// is returned, the guard must be kept until the request is processed.
+	pub fn start_request(&'static self, client: &str) -> Result<RequestGuard, LimitRejection> {
+		let limits = get_listener_limits();
+		if limits.rate_limit > 0 {
+			if let Err(retry_after) = self.take_token(client, limits.rate_limit, Instant::now()) {
+				self.metrics.lock().unwrap().rate_limited += 1;
+				return Err(LimitRejection::RateLimited(retry_after));
+			}
+		}
+		let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+		// The guard releases the slot for the rejected request too
+		let guard = RequestGuard { limiter: self };
+		let mut metrics = self.metrics.lock().unwrap();
+		if limits.max_concurrent > 0 && in_flight > limits.max_concurrent {
+			metrics.concurrency_rejected += 1;
+			return Err(LimitRejection::TooManyRequests);
+		}
+		metrics.requests += 1;
+		metrics.in_flight = in_flight;
+		metrics.max_in_flight = cmp::max(metrics.max_in_flight, in_flight);
+		Ok(guard)
+	}
+
+	// Take a token from the client bucket. The bucket holds a minute of requests and refills
+	// continuously. Returns the seconds to wait if the bucket is empty.
+	fn take_token(&self, client: &str, rate_limit: u32, now: Instant) -> Result<(), u64> {
+		let capacity = rate_limit as f64;
+		let per_sec = capacity / 60.0;
+		let mut buckets = self.buckets.lock().unwrap();
+		if buckets.len() > MAX_IDLE_BUCKETS {
+			// Buckets that are full again don't limit anything
+			buckets.retain(|_, b| {
+				b.tokens + now.duration_since(b.updated).as_secs_f64() * per_sec < capacity
+			});
+		}
+		let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+			tokens: capacity,
+			updated: now,
+		});
+		let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+		bucket.tokens = (bucket.tokens + elapsed * per_sec).min(capacity);
+		bucket.updated = now;
+		if bucket.tokens < 1.0 {
+			return Err(((1.0 - bucket.tokens) / per_sec).ceil() as u64);
+		}
+		bucket.tokens -= 1.0;
+		Ok(())
+	}
+
+	/// Request counters of the listener
+	pub fn metrics(&self) -> ListenerMetrics {
+		self.metrics.lock().unwrap().clone()
+	}
+}
+
+lazy_static! {
+	/// Limits for the listener requests
+	static ref LISTENER_LIMITS: RwLock<ListenerLimits> = RwLock::new(ListenerLimits::default());
+	/// Owner API listener limiter
+	pub static ref OWNER_LIMITER: RequestLimiter = RequestLimiter::new("owner");
+	/// Foreign API listener limiter
+	pub static ref FOREIGN_LIMITER: RequestLimiter = RequestLimiter::new("foreign");
+}
+
+/// Set from config the limits for the listener requests
+pub fn set_listener_limits(limits: ListenerLimits) {
+	*LISTENER_LIMITS.write().unwrap() = limits;
+}
+
+/// Read the limits for the listener requests
+pub fn get_listener_limits() -> ListenerLimits {
+	LISTENER_LIMITS.read().unwrap().clone()
+}
+
+/// Request counters of the owner and foreign listeners
+pub fn get_listener_metrics() -> Vec<ListenerMetrics> {
+	vec![OWNER_LIMITER.metrics(), FOREIGN_LIMITER.metrics()]
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::time::Duration;
+
+	#[test]
+	fn test_rate_limit() {
+		let limiter = RequestLimiter::new("test");
+		let start = Instant::now();
+		for _ in 0..60 {
+			assert!(limiter.take_token("a", 60, start).is_ok());
+		}
+		assert_eq!(limiter.take_token("a", 60, start), Err(1));
+		// Other clients have own buckets
+		assert!(limiter.take_token("b", 60, start).is_ok());
+		// One token per second
+		let later = start + Duration::from_secs(1);
+		assert!(limiter.take_token("a", 60, later).is_ok());
+		assert!(limiter.take_token("a", 60, later).is_err());
+	}
+
+	#[test]
+	fn test_concurrent_limit() {
+		lazy_static! {
+			static ref LIMITER: RequestLimiter = RequestLimiter::new("test");
+		}
+		set_listener_limits(ListenerLimits {
+			rate_limit: 0,
+			max_concurrent: 2,
+			trust_proxy_headers: false,
+		});
+		let g1 = LIMITER.start_request("a").unwrap();
+		let _g2 = LIMITER.start_request("a").unwrap();
+		assert!(LIMITER.start_request("a").is_err());
+		drop(g1);
+		let _g3 = LIMITER.start_request("a").unwrap();
+
+		let metrics = LIMITER.metrics();
+		assert_eq!(metrics.requests, 3);
+		assert_eq!(metrics.concurrency_rejected, 1);
+		assert_eq!(metrics.in_flight, 2);
+		assert_eq!(metrics.max_in_flight, 2);
+		set_listener_limits(ListenerLimits::default());
+	}
+}
@@ -0,0 +1,206 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Health of the listeners that receive the slates and the swap messages. The listeners report
+//! when they are started, stopped, connected and every processed or failed message. The state
+//! is kept while the wallet is running. The process that runs the listeners can register the
+//! control to start and stop them from the Owner API.
+
+use crate::error::{Error, ErrorKind};
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, RwLock};
+
+/// HTTP foreign listener
+pub const HTTP_LISTENER: &str = "http";
+/// Tor onion service of the foreign listener. The requests are forwarded to the http listener
+/// and are counted there.
+pub const TOR_LISTENER: &str = "tor";
+/// MWCMQS listener
+pub const MWCMQS_LISTENER: &str = "mwcmqs";
+
+/// State of the listener transport
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ListenerStatus {
+	/// Transport name: 'http', 'tor', 'mwcmqs' or the messenger method
+	pub transport: String,
+	/// True if the listener is running
+	pub running: bool,
+	/// Connection to the server for the transports that need it, None for others
+	pub connected: Option<bool>,
+	/// Bound address for http, onion address for tor, wallet address for the messengers
+	pub address: Option<String>,
+	/// Time when the listener was started
+	pub started: Option<DateTime<Utc>>,
+	/// Time of the last successfully processed message
+	pub last_message: Option<DateTime<Utc>>,
+	/// Number of the processed messages
+	pub messages: u64,
+	/// Number of the failed messages and the connection errors
+	pub errors: u64,
+	/// The last error
+	pub last_error: Option<String>,
+}
+
+impl ListenerStatus {
+	fn new(transport: &str) -> Self {
+		ListenerStatus {
+			transport: transport.to_string(),
+			running: false,
+			connected: None,
+			address: None,
+			started: None,
+			last_message: None,
+			messages: 0,
+			errors: 0,
+			last_error: None,
+		}
+	}
+}
+
+/// Starts and stops the listeners of the running wallet process
+pub trait ListenerControl: Send + Sync {
+	/// Start the listener of the transport with the settings from the config
+	fn start_listener(&self, transport: &str) -> Result<(), Error>;
+	/// Stop the listener of the transport. The requests in progress are finished first.
+	fn stop_listener(&self, transport: &str) -> Result<(), Error>;
+}
+
+lazy_static! {
+	/// Listeners by transport. The main transports are always listed.
+	static ref LISTENERS: RwLock<Vec<ListenerStatus>> = RwLock::new(vec![
+		ListenerStatus::new(HTTP_LISTENER),
+		ListenerStatus::new(TOR_LISTENER),
+		ListenerStatus::new(MWCMQS_LISTENER),
+	]);
+	/// Control of the listeners, None - the listeners are not managed by this process
+	static ref LISTENER_CONTROL: RwLock<Option<Arc<dyn ListenerControl>>> = RwLock::new(None);
+}
+
+// Update the state of the transport, it is added if it is not listed yet
+fn update<F>(transport: &str, f: F)
+where
+	F: FnOnce(&mut ListenerStatus),
+{
+	let mut listeners = LISTENERS.write().unwrap();
+	match listeners.iter_mut().find(|l| l.transport == transport) {
+		Some(l) => f(l),
+		None => {
+			let mut l = ListenerStatus::new(transport);
+			f(&mut l);
+			listeners.push(l);
+		}
+	}
+}
+
+/// The listener is started and accepts the messages at the address
+pub fn listener_started(transport: &str, address: Option<String>) {
+	update(transport, |l| {
+		l.running = true;
+		l.address = address;
+		l.started = Some(Utc::now());
+	});
+}
+
+/// The listener is stopped
+pub fn listener_stopped(transport: &str) {
+	update(transport, |l| {
+		l.running = false;
+		if l.connected.is_some() {
+			l.connected = Some(false);
+		}
+	});
+}
+
+/// Connection of the listener to its server is established or lost
+pub fn listener_connected(transport: &str, connected: bool) {
+	update(transport, |l| l.connected = Some(connected));
+}
+
+/// The listener processed the message
+pub fn message_processed(transport: &str) {
+	update(transport, |l| {
+		l.messages += 1;
+		l.last_message = Some(Utc::now());
+	});
+}
+
+/// The listener failed to process the message or lost the connection
+pub fn listener_error(transport: &str, error: String) {
+	update(transport, |l| {
+		l.errors += 1;
+		l.last_error = Some(error);
+	});
+}
+
+/// State of all listeners of the wallet
+pub fn get_listeners_status() -> Vec<ListenerStatus> {
+	LISTENERS.read().unwrap().clone()
+}
+
+/// Register the control of the listeners of this process. None disables it.
+pub fn set_listener_control(control: Option<Arc<dyn ListenerControl>>) {
+	*LISTENER_CONTROL.write().unwrap() = control;
+}
+
+/// Control of the listeners of this process
+pub fn get_listener_control() -> Result<Arc<dyn ListenerControl>, Error> {
+	LISTENER_CONTROL.read().unwrap().clone().ok_or(
+		ErrorKind::GenericError(
+			"Listeners are not managed by this wallet process, please start the wallet with owner_api command".to_string(),
+		)
+		.into(),
+	)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_listener_status() {
+		let transport = "test_transport";
+		assert!(get_listeners_status()
+			.iter()
+			.find(|l| l.transport == transport)
+			.is_none());
+
+		listener_started(transport, Some("test@address".to_string()));
+		listener_connected(transport, true);
+		message_processed(transport);
+		message_processed(transport);
+		listener_error(transport, "lost connection".to_string());
+
+		let status = get_listeners_status();
+		assert_eq!(status[0].transport, HTTP_LISTENER);
+		assert_eq!(status[1].transport, TOR_LISTENER);
+		assert_eq!(status[2].transport, MWCMQS_LISTENER);
+		let l = status.iter().find(|l| l.transport == transport).unwrap();
+		assert!(l.running);
+		assert_eq!(l.connected, Some(true));
+		assert_eq!(l.address, Some("test@address".to_string()));
+		assert!(l.started.is_some());
+		assert!(l.last_message.is_some());
+		assert_eq!(l.messages, 2);
+		assert_eq!(l.errors, 1);
+		assert_eq!(l.last_error, Some("lost connection".to_string()));
+
+		listener_stopped(transport);
+		let status = get_listeners_status();
+		let l = status.iter().find(|l| l.transport == transport).unwrap();
+		assert!(!l.running);
+		assert_eq!(l.connected, Some(false));
+		// counters are kept while the wallet is running
+		assert_eq!(l.messages, 2);
+	}
+}
@@ -0,0 +1,152 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Privacy policy for the log output. Addresses, amounts and slate ids that are written into
+//! the wallet log go through the functions of this module, so they can be hidden from the log
+//! files that are shared for the troubleshooting.
+
+use crate::blake2::blake2b::blake2b;
+use crate::grin_core::core::amount_to_hr_string;
+use crate::grin_util::{to_hex, RwLock};
+use crate::{Error, ErrorKind, Slate};
+use rand::{thread_rng, Rng};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Replacement of the values for the 'full' policy
+const HIDDEN_VALUE: &str = "***";
+
+/// Bytes of the hash that are printed for the 'redacted' policy
+const HASH_LEN: usize = 4;
+
+/// How the sensitive values are written into the log
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogPrivacy {
+	/// Values are logged as they are
+	Off,
+	/// Values are replaced with a short salted hash. The same value has the same hash
+	/// until the wallet is restarted, so the log records can still be matched.
+	Redacted,
+	/// Values are not logged at all
+	Full,
+}
+
+impl FromStr for LogPrivacy {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.trim().to_lowercase().as_str() {
+			"off" => Ok(LogPrivacy::Off),
+			"redacted" => Ok(LogPrivacy::Redacted),
+			"full" => Ok(LogPrivacy::Full),
+			_ => Err(ErrorKind::GenericError(format!(
+				"Unknown log_privacy value '{}', expected full, redacted or off",
+				s
+			))
+			.into()),
+		}
+	}
+}
+
+lazy_static! {
+	static ref LOG_PRIVACY: RwLock<LogPrivacy> = RwLock::new(LogPrivacy::Off);
+	// Salt is generated per process, otherwise the hashes of the amounts can be brute forced
+	static ref SALT: [u8; 16] = thread_rng().gen();
+}
+
+/// Set the log privacy policy
+pub fn set_log_privacy(policy: LogPrivacy) {
+	*LOG_PRIVACY.write() = policy;
+}
+
+/// Get the log privacy policy
+pub fn get_log_privacy() -> LogPrivacy {
+	*LOG_PRIVACY.read()
+}
+
+fn protect(value: String) -> String {
+	match get_log_privacy() {
+		LogPrivacy::Off => value,
+		LogPrivacy::Redacted => format!(
+			"#{}",
+			to_hex(blake2b(HASH_LEN, &SALT[..], value.as_bytes()).as_bytes())
+		),
+		LogPrivacy::Full => HIDDEN_VALUE.to_string(),
+	}
+}
+
+/// Sensitive value as it should be logged
+pub fn value<T: Display>(value: T) -> String {
+	protect(value.to_string())
+}
+
+/// Wallet address as it should be logged
+pub fn address<T: Display>(address: T) -> String {
+	value(address)
+}
+
+/// Amount in MWC as it should be logged
+pub fn amount(amount: u64) -> String {
+	protect(amount_to_hr_string(amount, false))
+}
+
+/// Slate id as it should be logged
+pub fn slate_id<T: Display>(id: T) -> String {
+	value(id)
+}
+
+/// Slate as it should be logged. The debug dump of the slate has the amount and the addresses
+/// of the payment proof, so with the privacy policy only the slate id is logged.
+pub fn slate(slate: &Slate) -> String {
+	match get_log_privacy() {
+		LogPrivacy::Off => format!("{:?}", slate),
+		_ => format!("slate {}", slate_id(slate.id)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn log_privacy_policy() {
+		assert_eq!(
+			LogPrivacy::from_str(" Redacted").unwrap(),
+			LogPrivacy::Redacted
+		);
+		assert!(LogPrivacy::from_str("partial").is_err());
+
+		let addr = "xmgwbyjMEMBojnVadEkwVi1GyL1WPiVE5dziQf3TLedHdrVBPGw5";
+
+		set_log_privacy(LogPrivacy::Off);
+		assert_eq!(address(addr), addr);
+		assert_eq!(amount(1_500_000_000), "1.500000000");
+
+		set_log_privacy(LogPrivacy::Redacted);
+		let hashed = address(addr);
+		assert_eq!(hashed.len(), 1 + HASH_LEN * 2);
+		assert!(hashed.starts_with('#'));
+		assert_eq!(hashed, address(addr));
+		assert_ne!(amount(1_500_000_000), amount(1_500_000_001));
+
+		set_log_privacy(LogPrivacy::Full);
+		assert_eq!(address(addr), HIDDEN_VALUE);
+		assert_eq!(
+			slate_id("0436430c-2b02-624c-2032-570501212b00"),
+			HIDDEN_VALUE
+		);
+
+		set_log_privacy(LogPrivacy::Off);
+	}
+}
@@ -0,0 +1,99 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Migration of the data of another wallet with the same seed, like mwc713. The restore from
+//! seed finds the outputs only, the migration brings the accounts, the transactions with their
+//! outputs and the address book as they were recorded by the old wallet.
+
+use crate::types::{AcctPathMapping, Contact, OutputData, OutputStatus, TxLogEntry};
+
+/// Wallet data that is migrated
+#[derive(Debug, Clone, Default)]
+pub struct WalletMigrationData {
+	/// Accounts, label -> derivation path
+	pub accounts: Vec<AcctPathMapping>,
+	/// Outputs of all accounts
+	pub outputs: Vec<OutputData>,
+	/// Transactions of all accounts
+	pub txs: Vec<TxLogEntry>,
+	/// Address book
+	pub contacts: Vec<Contact>,
+}
+
+impl WalletMigrationData {
+	/// Balance that the old wallet had by its own records. Compare it with the balance of the
+	/// outputs after the scan to check the migration.
+	pub fn balance(&self) -> u64 {
+		outputs_balance(self.outputs.iter())
+	}
+}
+
+/// Balance of the outputs that the migration checks: the unspent and the locked outputs
+pub fn outputs_balance<'a>(outputs: impl Iterator<Item = &'a OutputData>) -> u64 {
+	outputs
+		.filter(|o| o.status == OutputStatus::Unspent || o.status == OutputStatus::Locked)
+		.map(|o| o.value)
+		.sum()
+}
+
+/// Result of the migration
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct WalletMigrationReport {
+	/// Number of the imported accounts
+	pub accounts: usize,
+	/// Number of the imported outputs
+	pub outputs: usize,
+	/// Number of the imported transactions
+	pub txs: usize,
+	/// Number of the imported contacts
+	pub contacts: usize,
+	/// Records that were not imported, with the reason
+	pub skipped: Vec<String>,
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::grin_keychain::{ExtKeychain, Keychain};
+
+	#[test]
+	fn test_migration_balance() {
+		let output = |n_child: u32, value: u64, status: OutputStatus| OutputData {
+			root_key_id: ExtKeychain::derive_key_id(2, 0, 0, 0, 0),
+			key_id: ExtKeychain::derive_key_id(3, 0, 0, n_child, 0),
+			n_child,
+			commit: None,
+			mmr_index: None,
+			value,
+			status,
+			height: 1,
+			lock_height: 0,
+			is_coinbase: false,
+			tx_log_entry: None,
+			frozen: false,
+			tags: vec![],
+		};
+		let data = WalletMigrationData {
+			outputs: vec![
+				output(0, 1_000_000_000, OutputStatus::Unspent),
+				output(1, 2_000_000_000, OutputStatus::Locked),
+				output(2, 4_000_000_000, OutputStatus::Spent),
+				output(3, 8_000_000_000, OutputStatus::Unconfirmed),
+			],
+			..Default::default()
+		};
+		assert_eq!(data.balance(), 3_000_000_000);
+		assert_eq!(WalletMigrationData::default().balance(), 0);
+	}
+}
@@ -0,0 +1,36 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Multisig outputs. The output is owned by 2 or 3 wallets together, it is spent by any 2 of them.
+//!
+//! The output is built with the same multiparty bulletproof as the swap lock output. Every
+//! participant keeps its part of the blinding factor, the parts never leave the wallets.
+//! For 2-of-3 every participant also shares its part between the others (dealerless Feldman
+//! sharing), so any two of them can sign the spending transaction.
+//!
+//! The parties exchange a single session document, one step at a time. The document doesn't
+//! have any secrets, it can be sent by any transport. The wallet secrets of the session are
+//! derived from the wallet seed and the session id, so the restored wallet can continue with
+//! the session document.
+
+mod storage;
+mod types;
+
+pub use self::storage::{
+	delete_multisig, get_multisig, list_multisig, store_multisig, MULTISIG_SAVE_DIR,
+};
+pub use self::types::{
+	lagrange_coefficient, MultisigParty, MultisigRecord, MultisigScheme, MultisigSecrets,
+	MultisigSession, MultisigShare, MultisigSpend, MultisigStatus, MULTISIG_SESSION_VERSION,
+};
@@ -0,0 +1,128 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage of the multisig sessions. One json file per session in the wallet data directory.
+//! The records don't have secrets, the secrets are derived from the wallet seed.
+
+use super::types::MultisigRecord;
+use crate::{Error, ErrorKind};
+use chrono::Utc;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Location of the multisig sessions
+pub const MULTISIG_SAVE_DIR: &'static str = "saved_multisig";
+
+fn multisig_dir(data_file_dir: &str) -> Result<PathBuf, Error> {
+	let path = Path::new(data_file_dir).join(MULTISIG_SAVE_DIR);
+	fs::create_dir_all(&path).map_err(|e| {
+		ErrorKind::IO(format!(
+			"Unable to create multisig storage directory {}, {}",
+			path.display(),
+			e
+		))
+	})?;
+	Ok(path)
+}
+
+fn read_record(path: &Path) -> Result<MultisigRecord, Error> {
+	let mut data = String::new();
+	File::open(path)
+		.and_then(|mut f| f.read_to_string(&mut data))
+		.map_err(|e| {
+			ErrorKind::IO(format!(
+				"Unable to read multisig session from {}, {}",
+				path.display(),
+				e
+			))
+		})?;
+	serde_json::from_str(&data).map_err(|e| {
+		ErrorKind::Multisig(format!(
+			"Unable to parse multisig session {}, {}",
+			path.display(),
+			e
+		))
+		.into()
+	})
+}
+
+/// All multisig sessions of the wallet, the latest first
+pub fn list_multisig(data_file_dir: &str) -> Result<Vec<MultisigRecord>, Error> {
+	let mut result = vec![];
+	for entry in fs::read_dir(multisig_dir(data_file_dir)?)? {
+		let path = entry?.path();
+		if path.extension().and_then(|e| e.to_str()) == Some("json") {
+			result.push(read_record(&path)?);
+		}
+	}
+	result.sort_by(|a, b| b.session.created.cmp(&a.session.created));
+	Ok(result)
+}
+
+/// Multisig session by id
+pub fn get_multisig(data_file_dir: &str, id: &Uuid) -> Result<Option<MultisigRecord>, Error> {
+	let path = multisig_dir(data_file_dir)?.join(format!("{}.json", id));
+	if !path.exists() {
+		return Ok(None);
+	}
+	Ok(Some(read_record(&path)?))
+}
+
+/// Save the multisig session. The file is replaced only after the new content is written.
+pub fn store_multisig(data_file_dir: &str, record: &mut MultisigRecord) -> Result<(), Error> {
+	record.updated = Utc::now();
+	let dir = multisig_dir(data_file_dir)?;
+	let id = record.session.id;
+	let tmp_path = dir.join(format!("{}.json.bak", id));
+	let data = serde_json::to_string_pretty(record).map_err(|e| {
+		ErrorKind::Multisig(format!(
+			"Unable to serialize multisig session {}, {}",
+			id, e
+		))
+	})?;
+	File::create(&tmp_path)
+		.and_then(|mut f| {
+			f.write_all(data.as_bytes())?;
+			f.sync_all()
+		})
+		.map_err(|e| {
+			ErrorKind::IO(format!(
+				"Unable to write multisig session to {}, {}",
+				tmp_path.display(),
+				e
+			))
+		})?;
+	fs::rename(&tmp_path, dir.join(format!("{}.json", id))).map_err(|e| {
+		ErrorKind::IO(format!(
+			"Unable to save multisig session {}, rename failed, {}",
+			id, e
+		))
+	})?;
+	Ok(())
+}
+
+/// Delete the multisig session record
+pub fn delete_multisig(data_file_dir: &str, id: &Uuid) -> Result<(), Error> {
+	let path = multisig_dir(data_file_dir)?.join(format!("{}.json", id));
+	fs::remove_file(&path).map_err(|e| {
+		ErrorKind::IO(format!(
+			"Unable to delete multisig session {}, {}",
+			path.display(),
+			e
+		))
+	})?;
+	Ok(())
+}
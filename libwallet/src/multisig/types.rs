@@ -0,0 +1,897 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::blake2::blake2b::blake2b;
+use crate::grin_core::libtx::secp_ser;
+use crate::grin_keychain::{
+	BlindSum, BlindingFactor, ExtKeychainPath, Identifier, Keychain, SwitchCommitmentType,
+};
+use crate::grin_util::secp::key::{PublicKey, SecretKey};
+use crate::grin_util::secp::pedersen::Commitment;
+use crate::grin_util::secp::Secp256k1;
+use crate::swap::multisig::{Builder, ParticipantData};
+use crate::swap::ser::*;
+use crate::{Error, ErrorKind, Slate};
+use chrono::{DateTime, Utc};
+use rand::thread_rng;
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Current version of the session document
+pub const MULTISIG_SESSION_VERSION: u16 = 1;
+
+/// Who can spend the multisig output
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MultisigScheme {
+	/// Two participants, both sign
+	TwoOfTwo,
+	/// Three participants, any two sign
+	TwoOfThree,
+}
+
+impl MultisigScheme {
+	/// Number of the output owners
+	pub fn participants(&self) -> usize {
+		match self {
+			MultisigScheme::TwoOfTwo => 2,
+			MultisigScheme::TwoOfThree => 3,
+		}
+	}
+}
+
+impl fmt::Display for MultisigScheme {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			MultisigScheme::TwoOfTwo => "2-of-2",
+			MultisigScheme::TwoOfThree => "2-of-3",
+		};
+		write!(f, "{}", s)
+	}
+}
+
+impl FromStr for MultisigScheme {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"2-of-2" => Ok(MultisigScheme::TwoOfTwo),
+			"2-of-3" => Ok(MultisigScheme::TwoOfThree),
+			_ => Err(ErrorKind::Multisig(format!(
+				"Unknown scheme '{}', expected 2-of-2 or 2-of-3",
+				s
+			))
+			.into()),
+		}
+	}
+}
+
+/// Status of the multisig output
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MultisigStatus {
+	/// Participants are building the output and signing the funding transaction
+	Setup,
+	/// Funding transaction is posted, the output can be spent
+	Active,
+	/// Spending transaction is signing
+	Spending,
+	/// Spending transaction is posted
+	Spent,
+	/// Session is cancelled before the funding transaction was posted
+	Cancelled,
+}
+
+impl fmt::Display for MultisigStatus {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			MultisigStatus::Setup => "setup",
+			MultisigStatus::Active => "active",
+			MultisigStatus::Spending => "spending",
+			MultisigStatus::Spent => "spent",
+			MultisigStatus::Cancelled => "cancelled",
+		};
+		write!(f, "{}", s)
+	}
+}
+
+/// Public data of the participant
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultisigParty {
+	/// Multiparty bulletproof data, the partial commitment is the public key of the participant
+	pub data: ParticipantData,
+	/// 2-of-3 only, public key of the sharing polynomial coefficient
+	#[serde(
+		serialize_with = "option_pubkey_to_hex",
+		deserialize_with = "option_pubkey_from_hex",
+		skip_serializing_if = "Option::is_none",
+		default
+	)]
+	pub share_commit: Option<PublicKey>,
+}
+
+/// 2-of-3 share that one participant sends to another. The share is masked with the key that
+/// only these two participants know.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultisigShare {
+	/// Participant that created the share
+	pub from: usize,
+	/// Participant that receives the share
+	pub to: usize,
+	/// Masked share value
+	#[serde(serialize_with = "seckey_to_hex", deserialize_with = "seckey_from_hex")]
+	pub share: SecretKey,
+}
+
+/// Transaction that spends the multisig output into the payee wallet
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultisigSpend {
+	/// Participant that receives the funds and posts the transaction
+	pub payee: usize,
+	/// Participant that co-signs the transaction
+	pub cosigner: usize,
+	/// Output of the payee wallet
+	pub output_key_id: Identifier,
+	/// Spending transaction, the payee is the slate participant 0, the co-signer is 1
+	#[serde(deserialize_with = "slate_deser")]
+	pub slate: Slate,
+}
+
+/// The session document. It is passed between the participants, every participant does its
+/// step and passes it to the next one.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultisigSession {
+	/// Version of the document format
+	pub version: u16,
+	/// Session Id
+	pub id: Uuid,
+	/// Multisig scheme
+	pub scheme: MultisigScheme,
+	/// Amount of the output
+	#[serde(with = "secp_ser::string_or_u64")]
+	pub amount: u64,
+	/// Current status
+	pub status: MultisigStatus,
+	/// Time when the funder created the session
+	pub created: DateTime<Utc>,
+	/// Bulletproof common nonce
+	#[serde(serialize_with = "seckey_to_hex", deserialize_with = "seckey_from_hex")]
+	pub common_nonce: SecretKey,
+	/// Participants in the joining order. Participant 0 funds the output.
+	pub parties: Vec<MultisigParty>,
+	/// 2-of-3 masked shares
+	#[serde(default)]
+	pub shares: Vec<MultisigShare>,
+	/// Funding transaction. Slate participant 0 is the funder wallet inputs and change,
+	/// participant N+1 is the multisig participant N.
+	#[serde(deserialize_with = "slate_deser")]
+	pub funding_slate: Slate,
+	/// Multisig output commitment, known when the funding transaction is finalized
+	#[serde(
+		serialize_with = "option_commit_to_hex",
+		deserialize_with = "option_commit_from_hex",
+		skip_serializing_if = "Option::is_none",
+		default
+	)]
+	pub commit: Option<Commitment>,
+	/// Spending transaction in progress or posted
+	#[serde(skip_serializing_if = "Option::is_none", default)]
+	pub spend: Option<MultisigSpend>,
+}
+
+impl MultisigSession {
+	/// New session for the funding slate of the funder
+	pub fn new(scheme: MultisigScheme, amount: u64, funding_slate: Slate) -> Self {
+		MultisigSession {
+			version: MULTISIG_SESSION_VERSION,
+			id: Uuid::new_v4(),
+			scheme,
+			amount,
+			status: MultisigStatus::Setup,
+			created: Utc::now(),
+			common_nonce: SecretKey::new(&mut thread_rng()),
+			parties: vec![],
+			shares: vec![],
+			funding_slate,
+			commit: None,
+			spend: None,
+		}
+	}
+
+	/// Parse the session from json
+	pub fn from_json(data: &str) -> Result<Self, Error> {
+		let session: MultisigSession = serde_json::from_str(data).map_err(|e| {
+			ErrorKind::Multisig(format!("Unable to parse the session document, {}", e))
+		})?;
+		if session.version > MULTISIG_SESSION_VERSION {
+			return Err(ErrorKind::Multisig(format!(
+				"Session version {} is not supported, please upgrade the wallet",
+				session.version
+			))
+			.into());
+		}
+		if session.parties.len() > session.scheme.participants() {
+			return Err(ErrorKind::Multisig(format!(
+				"Session {} has {} participants, {} expected",
+				session.id,
+				session.parties.len(),
+				session.scheme
+			))
+			.into());
+		}
+		Ok(session)
+	}
+
+	/// Session json
+	pub fn to_json(&self) -> Result<String, Error> {
+		serde_json::to_string_pretty(self).map_err(|e| {
+			ErrorKind::Multisig(format!("Unable to serialize the session document, {}", e)).into()
+		})
+	}
+
+	/// All participants joined the session
+	pub fn all_joined(&self) -> bool {
+		self.parties.len() == self.scheme.participants()
+	}
+
+	/// Check that the document describes the same session as the stored one
+	pub fn check_same(&self, other: &MultisigSession) -> Result<(), Error> {
+		if self.id != other.id
+			|| self.scheme != other.scheme
+			|| self.amount != other.amount
+			|| self.common_nonce != other.common_nonce
+			|| self.funding_slate.id != other.funding_slate.id
+		{
+			return Err(ErrorKind::Multisig(format!(
+				"Session {} document doesn't match the stored session",
+				self.id
+			))
+			.into());
+		}
+		let known = std::cmp::min(self.parties.len(), other.parties.len());
+		for id in 0..known {
+			if self.parties[id].data.partial_commitment != other.parties[id].data.partial_commitment
+			{
+				return Err(ErrorKind::Multisig(format!(
+					"Participant {} of the session {} is changed",
+					id, self.id
+				))
+				.into());
+			}
+		}
+		Ok(())
+	}
+
+	/// Public key of the participant, it is the participant part of the output blinding factor
+	pub fn party_pubkey(&self, id: usize) -> Result<PublicKey, Error> {
+		let commit = self
+			.parties
+			.get(id)
+			.and_then(|p| p.data.partial_commitment.clone())
+			.ok_or(ErrorKind::Multisig(format!(
+				"Participant {} is not joined",
+				id
+			)))?;
+		Ok(commit.to_pubkey()?)
+	}
+
+	/// Multisig builder of the participant with the data of the session
+	pub fn builder(
+		&self,
+		secp: &Secp256k1,
+		participant_id: usize,
+		secrets: &MultisigSecrets,
+	) -> Result<Builder, Error> {
+		let mut builder = Builder::new(
+			self.scheme.participants(),
+			self.amount,
+			false,
+			participant_id,
+			secrets.bp_nonce.clone(),
+			Some(self.common_nonce.clone()),
+		);
+		for (id, party) in self.parties.iter().enumerate() {
+			if id == participant_id {
+				builder.create_participant(secp, &secrets.blind)?;
+				if builder.export()?.partial_commitment != party.data.partial_commitment {
+					return Err(ErrorKind::Multisig(format!(
+						"Participant {} of the session {} doesn't belong to this wallet",
+						id, self.id
+					))
+					.into());
+				}
+			} else {
+				builder.import_participant(id, &party.data)?;
+			}
+		}
+		if self.parties.len() == participant_id {
+			builder.create_participant(secp, &secrets.blind)?;
+		}
+		if self.all_joined() {
+			for (id, party) in self.parties.iter().enumerate() {
+				if party.data.has_round_1() {
+					builder.round_1_participant(id, &party.data)?;
+				}
+			}
+			for (id, party) in self.parties.iter().enumerate() {
+				if party.data.has_round_2() {
+					builder.round_2_participant(id, &party.data)?;
+				}
+			}
+		}
+		Ok(builder)
+	}
+
+	/// Add the participant to the session and to the funding transaction
+	pub fn join<K: Keychain>(
+		&mut self,
+		keychain: &K,
+		participant_id: usize,
+		secrets: &MultisigSecrets,
+	) -> Result<(), Error> {
+		if self.all_joined() {
+			return Err(ErrorKind::Multisig(format!(
+				"All {} participants already joined the session {}",
+				self.scheme, self.id
+			))
+			.into());
+		}
+		if self.parties.len() != participant_id {
+			return Err(ErrorKind::Multisig(format!(
+				"Participant {} can't join the session {}, the next one is {}",
+				participant_id,
+				self.id,
+				self.parties.len()
+			))
+			.into());
+		}
+		// The funder split the offset, the participants don't touch it
+		if self.funding_slate.tx.offset == BlindingFactor::zero() {
+			return Err(ErrorKind::Multisig(format!(
+				"Funding transaction of the session {} is not started",
+				self.id
+			))
+			.into());
+		}
+
+		let builder = self.builder(keychain.secp(), participant_id, secrets)?;
+		let share_commit = match self.scheme {
+			MultisigScheme::TwoOfTwo => None,
+			MultisigScheme::TwoOfThree => Some(PublicKey::from_secret_key(
+				keychain.secp(),
+				&secrets.coefficient,
+			)?),
+		};
+		self.parties.push(MultisigParty {
+			data: builder.export()?,
+			share_commit,
+		});
+
+		let mut sec_key = secrets.blind.clone();
+		self.funding_slate.fill_round_1(
+			keychain,
+			&mut sec_key,
+			&secrets.funding_nonce,
+			participant_id + 1,
+			None,
+			false,
+		)?;
+		Ok(())
+	}
+
+	/// Participant signed the funding transaction
+	pub fn funding_signed(&self, participant_id: usize) -> bool {
+		self.funding_slate
+			.participant_with_id(participant_id + 1)
+			.map(|p| p.part_sig.is_some())
+			.unwrap_or(false)
+	}
+
+	/// Sign the funding transaction. All participants must be joined.
+	pub fn sign_funding<K: Keychain>(
+		&mut self,
+		keychain: &K,
+		participant_id: usize,
+		secrets: &MultisigSecrets,
+	) -> Result<(), Error> {
+		if self.funding_slate.participant_data.len() != self.funding_slate.num_participants {
+			return Err(ErrorKind::Multisig(format!(
+				"Funding transaction of the session {} is missing participants",
+				self.id
+			))
+			.into());
+		}
+		self.funding_slate.fill_round_2(
+			keychain.secp(),
+			&secrets.blind,
+			&secrets.funding_nonce,
+			participant_id + 1,
+		)?;
+		Ok(())
+	}
+
+	/// Share that `from` sent to `to`
+	pub fn share(&self, from: usize, to: usize) -> Option<&MultisigShare> {
+		self.shares.iter().find(|s| s.from == from && s.to == to)
+	}
+
+	/// Participant sent the shares to all others. Always true for 2-of-2.
+	pub fn shares_sent(&self, participant_id: usize) -> bool {
+		match self.scheme {
+			MultisigScheme::TwoOfTwo => true,
+			MultisigScheme::TwoOfThree => (0..self.scheme.participants())
+				.filter(|id| *id != participant_id)
+				.all(|id| self.share(participant_id, id).is_some()),
+		}
+	}
+
+	/// 2-of-3, add the shares of the participant for the others. All participants must be joined.
+	pub fn add_shares<K: Keychain>(
+		&mut self,
+		keychain: &K,
+		participant_id: usize,
+		secrets: &MultisigSecrets,
+	) -> Result<(), Error> {
+		if self.scheme == MultisigScheme::TwoOfTwo {
+			return Ok(());
+		}
+		for to in 0..self.scheme.participants() {
+			if to == participant_id || self.share(participant_id, to).is_some() {
+				continue;
+			}
+			let value = eval_share(keychain, secrets, to)?;
+			let mask = share_mask(
+				keychain.secp(),
+				&secrets.blind,
+				&self.party_pubkey(to)?,
+				&self.id,
+				participant_id,
+				to,
+			)?;
+			let share = keychain
+				.blind_sum(
+					&BlindSum::new()
+						.add_blinding_factor(BlindingFactor::from_secret_key(value))
+						.add_blinding_factor(BlindingFactor::from_secret_key(mask)),
+				)?
+				.secret_key()?;
+			self.shares.push(MultisigShare {
+				from: participant_id,
+				to,
+				share,
+			});
+		}
+		Ok(())
+	}
+
+	/// Participant secret that can be combined with the secrets of the other participants.
+	/// For 2-of-2 it is the participant part of the blinding factor. For 2-of-3 it is the sum of
+	/// the shares from all participants, every received share is verified.
+	pub fn combined_share<K: Keychain>(
+		&self,
+		keychain: &K,
+		participant_id: usize,
+		secrets: &MultisigSecrets,
+	) -> Result<SecretKey, Error> {
+		if self.scheme == MultisigScheme::TwoOfTwo {
+			return Ok(secrets.blind.clone());
+		}
+		let secp = keychain.secp();
+		let mut sum = BlindSum::new().add_blinding_factor(BlindingFactor::from_secret_key(
+			eval_share(keychain, secrets, participant_id)?,
+		));
+		for from in 0..self.scheme.participants() {
+			if from == participant_id {
+				continue;
+			}
+			let share = self
+				.share(from, participant_id)
+				.ok_or(ErrorKind::Multisig(format!(
+					"Participant {} didn't send the share yet",
+					from
+				)))?;
+			let mask = share_mask(
+				secp,
+				&secrets.blind,
+				&self.party_pubkey(from)?,
+				&self.id,
+				from,
+				participant_id,
+			)?;
+			let value = keychain
+				.blind_sum(
+					&BlindSum::new()
+						.add_blinding_factor(BlindingFactor::from_secret_key(share.share.clone()))
+						.sub_blinding_factor(BlindingFactor::from_secret_key(mask)),
+				)?
+				.secret_key()?;
+
+			// Feldman check, the share must be on the line that the participant committed to
+			let party_pubkey = self.party_pubkey(from)?;
+			let share_commit =
+				self.parties[from]
+					.share_commit
+					.clone()
+					.ok_or(ErrorKind::Multisig(format!(
+						"Participant {} doesn't have the share commitment",
+						from
+					)))?;
+			let mut keys = vec![&party_pubkey];
+			for _ in 0..share_x(participant_id) {
+				keys.push(&share_commit);
+			}
+			if PublicKey::from_secret_key(secp, &value)? != PublicKey::from_combination(keys)? {
+				return Err(ErrorKind::Multisig(format!(
+					"Share from the participant {} is invalid",
+					from
+				))
+				.into());
+			}
+			sum = sum.add_blinding_factor(BlindingFactor::from_secret_key(value));
+		}
+		Ok(keychain.blind_sum(&sum)?.secret_key()?)
+	}
+
+	/// Part of the output blinding factor that the participant contributes to the spending
+	/// transaction together with the `cosigner`
+	pub fn signing_key<K: Keychain>(
+		&self,
+		keychain: &K,
+		participant_id: usize,
+		cosigner: usize,
+		secrets: &MultisigSecrets,
+	) -> Result<SecretKey, Error> {
+		let share = self.combined_share(keychain, participant_id, secrets)?;
+		scale(
+			keychain,
+			&share,
+			lagrange_coefficient(self.scheme, participant_id, cosigner),
+		)
+	}
+
+	/// Participants that need to process the document next
+	pub fn waiting_for(&self) -> Vec<usize> {
+		match self.status {
+			MultisigStatus::Setup => {
+				if !self.all_joined() {
+					return vec![self.parties.len()];
+				}
+				let round_1_done = self.parties.iter().all(|p| p.data.has_round_1());
+				let pending: Vec<usize> = (0..self.parties.len())
+					.filter(|id| {
+						let data = &self.parties[*id].data;
+						!data.has_round_1()
+							|| !self.funding_signed(*id)
+							|| !self.shares_sent(*id)
+							|| (round_1_done && !data.has_round_2())
+					})
+					.collect();
+				if pending.is_empty() {
+					// The funder finalizes and posts the funding transaction
+					vec![0]
+				} else {
+					pending
+				}
+			}
+			MultisigStatus::Spending => match &self.spend {
+				Some(spend) => {
+					let cosigned = spend
+						.slate
+						.participant_with_id(1)
+						.map(|p| p.part_sig.is_some())
+						.unwrap_or(false);
+					if cosigned {
+						vec![spend.payee]
+					} else {
+						vec![spend.cosigner]
+					}
+				}
+				None => vec![],
+			},
+			_ => vec![],
+		}
+	}
+}
+
+/// The session record that the participant wallet stores
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultisigRecord {
+	/// This wallet participant id
+	pub participant_id: usize,
+	/// Latest session document
+	pub session: MultisigSession,
+	/// This wallet signed the funding transaction, the nonce is never used again
+	#[serde(default)]
+	pub funding_signed: bool,
+	/// Spending transactions that this wallet signed, the nonce is never used again
+	#[serde(default)]
+	pub signed_spends: Vec<Uuid>,
+	/// Last update time
+	pub updated: DateTime<Utc>,
+}
+
+impl MultisigRecord {
+	/// New record of the participant
+	pub fn new(participant_id: usize, session: MultisigSession) -> Self {
+		MultisigRecord {
+			participant_id,
+			session,
+			funding_signed: false,
+			signed_spends: vec![],
+			updated: Utc::now(),
+		}
+	}
+}
+
+/// Secrets of the participant in the session. They are derived from the wallet seed, the session
+/// id and the participant id, so they can be restored together with the wallet.
+pub struct MultisigSecrets {
+	root: SecretKey,
+	session_id: Uuid,
+	participant_id: usize,
+	/// Participant part of the output blinding factor
+	pub blind: SecretKey,
+	/// 2-of-3 sharing polynomial coefficient
+	pub coefficient: SecretKey,
+	/// Bulletproof nonce of the participant
+	pub bp_nonce: SecretKey,
+	/// Nonce of the funding transaction signature
+	pub funding_nonce: SecretKey,
+}
+
+impl MultisigSecrets {
+	/// Derive the secrets of the participant
+	pub fn new<K: Keychain>(
+		keychain: &K,
+		session_id: &Uuid,
+		participant_id: usize,
+	) -> Result<Self, Error> {
+		let root = keychain.derive_key(
+			0,
+			&ExtKeychainPath::new(3, 3, 2, 2, 0).to_identifier(),
+			SwitchCommitmentType::None,
+		)?;
+		let derive = |purpose: &[u8]| derive_secret(&root, session_id, participant_id, purpose);
+		Ok(MultisigSecrets {
+			blind: derive(b"blind")?,
+			coefficient: derive(b"coefficient")?,
+			bp_nonce: derive(b"bp_nonce")?,
+			funding_nonce: derive(b"funding_nonce")?,
+			root: root.clone(),
+			session_id: session_id.clone(),
+			participant_id,
+		})
+	}
+
+	/// Nonce of the spending transaction signature
+	pub fn spend_nonce(&self, spend_id: &Uuid) -> Result<SecretKey, Error> {
+		let mut purpose = b"spend_nonce".to_vec();
+		purpose.extend_from_slice(spend_id.as_bytes());
+		derive_secret(&self.root, &self.session_id, self.participant_id, &purpose)
+	}
+}
+
+fn derive_secret(
+	root: &SecretKey,
+	session_id: &Uuid,
+	participant_id: usize,
+	purpose: &[u8],
+) -> Result<SecretKey, Error> {
+	let mut data = session_id.as_bytes().to_vec();
+	data.extend_from_slice(&(participant_id as u32).to_be_bytes());
+	data.extend_from_slice(purpose);
+	Ok(SecretKey::from_slice(
+		blake2b(32, &root.0, &data).as_bytes(),
+	)?)
+}
+
+/// x coordinate of the participant share. With 2, 3 and 4 all 2-of-3 Lagrange coefficients
+/// are integers.
+fn share_x(participant_id: usize) -> usize {
+	participant_id + 2
+}
+
+/// Lagrange coefficient of the participant for the signing pair (`participant_id`, `cosigner`).
+/// The output blinding factor is the sum of the coefficient * combined share of the pair.
+pub fn lagrange_coefficient(scheme: MultisigScheme, participant_id: usize, cosigner: usize) -> i64 {
+	match scheme {
+		MultisigScheme::TwoOfTwo => 1,
+		MultisigScheme::TwoOfThree => {
+			let xi = share_x(participant_id) as i64;
+			let xj = share_x(cosigner) as i64;
+			debug_assert!(xj % (xj - xi) == 0);
+			xj / (xj - xi)
+		}
+	}
+}
+
+// key * factor, the factor is a small integer
+fn scale<K: Keychain>(keychain: &K, key: &SecretKey, factor: i64) -> Result<SecretKey, Error> {
+	let mut sum = BlindSum::new();
+	for _ in 0..factor.abs() {
+		let b = BlindingFactor::from_secret_key(key.clone());
+		sum = if factor > 0 {
+			sum.add_blinding_factor(b)
+		} else {
+			sum.sub_blinding_factor(b)
+		};
+	}
+	Ok(keychain.blind_sum(&sum)?.secret_key()?)
+}
+
+// Value of the participant sharing line blind + coefficient * x at the share x of `to`
+fn eval_share<K: Keychain>(
+	keychain: &K,
+	secrets: &MultisigSecrets,
+	to: usize,
+) -> Result<SecretKey, Error> {
+	let mut sum =
+		BlindSum::new().add_blinding_factor(BlindingFactor::from_secret_key(secrets.blind.clone()));
+	for _ in 0..share_x(to) {
+		sum = sum.add_blinding_factor(BlindingFactor::from_secret_key(secrets.coefficient.clone()));
+	}
+	Ok(keychain.blind_sum(&sum)?.secret_key()?)
+}
+
+// Mask for the share from `from` to `to`, Diffie-Hellman of the participants keys
+fn share_mask(
+	secp: &Secp256k1,
+	own_key: &SecretKey,
+	other_pubkey: &PublicKey,
+	session_id: &Uuid,
+	from: usize,
+	to: usize,
+) -> Result<SecretKey, Error> {
+	let mut shared = other_pubkey.clone();
+	shared.mul_assign(secp, own_key)?;
+	let mut data = shared.serialize_vec(true).to_vec();
+	data.extend_from_slice(session_id.as_bytes());
+	data.extend_from_slice(&(from as u32).to_be_bytes());
+	data.extend_from_slice(&(to as u32).to_be_bytes());
+	Ok(SecretKey::from_slice(blake2b(32, &[], &data).as_bytes())?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::grin_keychain::ExtKeychain;
+
+	fn session_with_parties<K: Keychain>(
+		keychains: &[K],
+		scheme: MultisigScheme,
+	) -> (MultisigSession, Vec<MultisigSecrets>) {
+		let mut slate = Slate::blank(scheme.participants() + 1, false);
+		slate.tx.offset = BlindingFactor::from_secret_key(SecretKey::new(&mut thread_rng()));
+		let mut session = MultisigSession::new(scheme, 1_000_000_000, slate);
+		let mut secrets = vec![];
+		for (id, k) in keychains.iter().enumerate() {
+			let s = MultisigSecrets::new(k, &session.id, id).unwrap();
+			session.join(k, id, &s).unwrap();
+			secrets.push(s);
+		}
+		(session, secrets)
+	}
+
+	#[test]
+	fn test_lagrange_coefficients() {
+		assert_eq!(lagrange_coefficient(MultisigScheme::TwoOfTwo, 0, 1), 1);
+		// any pair of x in 2, 3, 4 gives integer coefficients that sum to 1
+		for i in 0..3 {
+			for j in 0..3 {
+				if i != j {
+					let li = lagrange_coefficient(MultisigScheme::TwoOfThree, i, j);
+					let lj = lagrange_coefficient(MultisigScheme::TwoOfThree, j, i);
+					assert_eq!(li + lj, 1);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_secrets_are_deterministic() {
+		let keychain = ExtKeychain::from_random_seed(false).unwrap();
+		let id = Uuid::new_v4();
+		let s1 = MultisigSecrets::new(&keychain, &id, 0).unwrap();
+		let s2 = MultisigSecrets::new(&keychain, &id, 0).unwrap();
+		let s3 = MultisigSecrets::new(&keychain, &id, 1).unwrap();
+		assert_eq!(s1.blind, s2.blind);
+		assert_ne!(s1.blind, s3.blind);
+		let spend = Uuid::new_v4();
+		assert_eq!(
+			s1.spend_nonce(&spend).unwrap(),
+			s2.spend_nonce(&spend).unwrap()
+		);
+		assert_ne!(
+			s1.spend_nonce(&spend).unwrap(),
+			s1.spend_nonce(&Uuid::new_v4()).unwrap()
+		);
+	}
+
+	#[test]
+	fn test_two_of_three_any_pair_spends() {
+		let keychains: Vec<ExtKeychain> = (0..3)
+			.map(|_| ExtKeychain::from_random_seed(false).unwrap())
+			.collect();
+		let (mut session, secrets) = session_with_parties(&keychains, MultisigScheme::TwoOfThree);
+		assert!(session.all_joined());
+		for id in 0..3 {
+			session
+				.add_shares(&keychains[id], id, &secrets[id])
+				.unwrap();
+			assert!(session.shares_sent(id));
+		}
+
+		// the output blinding factor is the sum of the participant parts
+		let secp = keychains[0].secp();
+		let total = PublicKey::from_combination(
+			(0..3)
+				.map(|id| session.party_pubkey(id).unwrap())
+				.collect::<Vec<PublicKey>>()
+				.iter()
+				.collect(),
+		)
+		.unwrap();
+
+		for i in 0..3 {
+			for j in (i + 1)..3 {
+				let ki = session
+					.signing_key(&keychains[i], i, j, &secrets[i])
+					.unwrap();
+				let kj = session
+					.signing_key(&keychains[j], j, i, &secrets[j])
+					.unwrap();
+				let sum = keychains[0]
+					.blind_sum(
+						&BlindSum::new()
+							.add_blinding_factor(BlindingFactor::from_secret_key(ki))
+							.add_blinding_factor(BlindingFactor::from_secret_key(kj)),
+					)
+					.unwrap()
+					.secret_key()
+					.unwrap();
+				assert_eq!(PublicKey::from_secret_key(secp, &sum).unwrap(), total);
+			}
+		}
+
+		// a broken share is detected
+		session.shares[0].share = SecretKey::new(&mut thread_rng());
+		let to = session.shares[0].to;
+		assert!(session
+			.combined_share(&keychains[to], to, &secrets[to])
+			.is_err());
+	}
+
+	#[test]
+	fn test_session_json() {
+		let keychains: Vec<ExtKeychain> = (0..2)
+			.map(|_| ExtKeychain::from_random_seed(false).unwrap())
+			.collect();
+		let (session, secrets) = session_with_parties(&keychains, MultisigScheme::TwoOfTwo);
+		let restored = MultisigSession::from_json(&session.to_json().unwrap()).unwrap();
+		assert!(restored.check_same(&session).is_ok());
+		assert_eq!(restored.waiting_for(), vec![0, 1]);
+
+		// builder is restored from the document, the participant data must be its own
+		assert!(restored
+			.builder(keychains[1].secp(), 1, &secrets[1])
+			.is_ok());
+		assert!(restored
+			.builder(keychains[1].secp(), 0, &secrets[1])
+			.is_err());
+
+		// another session with the same id is rejected
+		let mut other = restored.clone();
+		other.amount += 1;
+		assert!(other.check_same(&session).is_err());
+	}
+}
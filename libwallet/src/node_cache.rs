@@ -0,0 +1,141 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Settings and counters of the node responses cache. The node client keeps the responses for
+//! the TTL, so the updater thread and the commands don't request the same data again and again.
+
+use std::sync::{Mutex, RwLock};
+
+/// Cache of the chain tip
+pub const CACHE_CHAIN_TIP: &str = "chain_tip";
+/// Cache of the block headers
+pub const CACHE_HEADER: &str = "header";
+/// Cache of the blocks
+pub const CACHE_BLOCK: &str = "block";
+/// Cache of the kernel lookups
+pub const CACHE_KERNEL: &str = "kernel";
+/// Cache of the output queries
+pub const CACHE_OUTPUTS: &str = "outputs";
+
+const CACHES: [&str; 5] = [
+	CACHE_CHAIN_TIP,
+	CACHE_HEADER,
+	CACHE_BLOCK,
+	CACHE_KERNEL,
+	CACHE_OUTPUTS,
+];
+
+/// How long the node responses are valid
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeCacheTtl {
+	/// TTL of the chain tip, ms. 0 - no caching
+	pub chain_tip_ms: u64,
+	/// TTL of the headers, blocks, kernels and outputs, ms. 0 - no caching
+	pub data_ms: u64,
+}
+
+impl Default for NodeCacheTtl {
+	fn default() -> Self {
+		NodeCacheTtl {
+			chain_tip_ms: 5000,
+			data_ms: 5000,
+		}
+	}
+}
+
+impl NodeCacheTtl {
+	/// TTL for the cache, ms
+	pub fn ttl_ms(&self, cache: &str) -> u64 {
+		if cache == CACHE_CHAIN_TIP {
+			self.chain_tip_ms
+		} else {
+			self.data_ms
+		}
+	}
+}
+
+/// Hit and miss counters of the cache
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct NodeCacheMetrics {
+	/// Cache name: 'chain_tip', 'header', 'block', 'kernel' or 'outputs'
+	pub cache: String,
+	/// Requests served from the cache
+	pub hits: u64,
+	/// Requests that went to the node
+	pub misses: u64,
+}
+
+lazy_static! {
+	static ref NODE_CACHE_TTL: RwLock<NodeCacheTtl> = RwLock::new(NodeCacheTtl::default());
+	static ref NODE_CACHE_METRICS: Mutex<Vec<NodeCacheMetrics>> = Mutex::new(
+		CACHES
+			.iter()
+			.map(|c| NodeCacheMetrics {
+				cache: c.to_string(),
+				..Default::default()
+			})
+			.collect()
+	);
+}
+
+/// Set from config the TTLs of the node responses
+pub fn set_node_cache_ttl(ttl: NodeCacheTtl) {
+	*NODE_CACHE_TTL.write().unwrap() = ttl;
+}
+
+/// Read the TTLs of the node responses
+pub fn get_node_cache_ttl() -> NodeCacheTtl {
+	NODE_CACHE_TTL.read().unwrap().clone()
+}
+
+/// Count the cache lookup
+pub fn record_lookup(cache: &str, hit: bool) {
+	let mut metrics = NODE_CACHE_METRICS.lock().unwrap();
+	if let Some(m) = metrics.iter_mut().find(|m| m.cache == cache) {
+		if hit {
+			m.hits += 1;
+		} else {
+			m.misses += 1;
+		}
+	}
+}
+
+/// Hit and miss counters of the node caches since the wallet start
+pub fn get_node_cache_metrics() -> Vec<NodeCacheMetrics> {
+	NODE_CACHE_METRICS.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_node_cache_metrics() {
+		let ttl = NodeCacheTtl {
+			chain_tip_ms: 1000,
+			data_ms: 60000,
+		};
+		assert_eq!(ttl.ttl_ms(CACHE_CHAIN_TIP), 1000);
+		assert_eq!(ttl.ttl_ms(CACHE_KERNEL), 60000);
+
+		record_lookup(CACHE_OUTPUTS, true);
+		record_lookup(CACHE_OUTPUTS, false);
+		record_lookup(CACHE_OUTPUTS, true);
+		record_lookup("unknown", true);
+		let metrics = get_node_cache_metrics();
+		assert_eq!(metrics.len(), 5);
+		let outputs = metrics.iter().find(|m| m.cache == CACHE_OUTPUTS).unwrap();
+		assert_eq!((outputs.hits, outputs.misses), (2, 1));
+	}
+}
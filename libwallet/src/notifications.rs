@@ -0,0 +1,276 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wallet and swap event notifications. Events are passed to the notifier that is set at
+//! the wallet start, normally it POSTs them to the webhook URLs from the config. API servers
+//! can also subscribe to the events and stream them to their clients.
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Events that are reported to the notifier
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WalletEvent {
+	/// Payment slate is received by a listener or from a file
+	PaymentReceived {
+		/// Slate Id
+		slate_id: String,
+		/// Received amount, nanoMWC
+		amount: u64,
+		/// Sender address if it is known
+		from: Option<String>,
+	},
+	/// Transaction reached the configured number of confirmations
+	TxConfirmed {
+		/// Wallet transaction Id
+		tx_id: u32,
+		/// Slate Id if the transaction has it
+		slate_id: Option<String>,
+		/// Transaction type
+		tx_type: String,
+		/// Amount credited, nanoMWC
+		amount_credited: u64,
+		/// Amount debited, nanoMWC
+		amount_debited: u64,
+		/// Height of the block with the transaction
+		height: u64,
+		/// Number of confirmations
+		confirmations: u64,
+	},
+	/// Transaction reached its own target number of confirmations and is settled
+	TxSettled {
+		/// Wallet transaction Id
+		tx_id: u32,
+		/// Slate Id if the transaction has it
+		slate_id: Option<String>,
+		/// Transaction type
+		tx_type: String,
+		/// Amount credited, nanoMWC
+		amount_credited: u64,
+		/// Amount debited, nanoMWC
+		amount_debited: u64,
+		/// Height of the block with the transaction
+		height: u64,
+		/// Number of confirmations
+		confirmations: u64,
+		/// Target number of confirmations of the transaction
+		target: u64,
+	},
+	/// Merchant invoice transaction reached the configured number of confirmations
+	InvoicePaid {
+		/// Invoice Id, it is the slate Id
+		invoice_id: String,
+		/// Paid amount, nanoMWC
+		amount: u64,
+		/// Merchant memo of the invoice
+		memo: Option<String>,
+		/// Wallet transaction Id
+		tx_id: u32,
+		/// Height of the block with the transaction
+		height: u64,
+		/// Number of confirmations
+		confirmations: u64,
+	},
+	/// Transaction moved into the new lifecycle state
+	TxStatusChanged {
+		/// Wallet transaction Id
+		tx_id: u32,
+		/// Slate Id if the transaction has it
+		slate_id: Option<String>,
+		/// Transaction type
+		tx_type: String,
+		/// Previous state, None for the new transaction
+		from_status: Option<String>,
+		/// New state
+		to_status: String,
+	},
+	/// Swap trade moved into the new state
+	SwapStateChanged {
+		/// Swap trade Id
+		swap_id: String,
+		/// Previous state
+		from_state: String,
+		/// New state
+		to_state: String,
+		/// Description of the new state
+		description: String,
+	},
+	/// Swap processing failed, the trade needs attention
+	SwapFailed {
+		/// Swap trade Id
+		swap_id: String,
+		/// State where the processing failed
+		state: String,
+		/// Error description
+		error: String,
+	},
+	/// Swap trade is waiting for the user or is refunding, the trade needs attention
+	SwapActionRequired {
+		/// Swap trade Id
+		swap_id: String,
+		/// Current state
+		state: String,
+		/// Required action
+		action: String,
+		/// Description of the action
+		description: String,
+	},
+	/// Blocks that the wallet scanned are replaced by the chain reorg. The wallet data is repaired,
+	/// the confirmations of the transactions from the replaced blocks are rechecked.
+	ChainReorg {
+		/// Height of the last block that is still on the chain
+		fork_height: u64,
+		/// Height of the last scanned block before the reorg
+		prev_height: u64,
+		/// Current chain height
+		tip_height: u64,
+		/// Wallet transactions Ids from the replaced blocks
+		rolled_back_txs: Vec<u32>,
+		/// Wallet transactions Ids that are not confirmed after the reorg
+		unconfirmed_txs: Vec<u32>,
+	},
+}
+
+impl WalletEvent {
+	/// True for the events that need the attention of the user, they are sent to the alert
+	/// command as well
+	pub fn is_alert(&self) -> bool {
+		match self {
+			WalletEvent::SwapFailed { .. } | WalletEvent::SwapActionRequired { .. } => true,
+			WalletEvent::ChainReorg {
+				unconfirmed_txs, ..
+			} => !unconfirmed_txs.is_empty(),
+			_ => false,
+		}
+	}
+}
+
+/// Receiver of the wallet events
+pub trait EventNotifier: Send + Sync {
+	/// Report the event. Must not block the caller for long.
+	fn notify(&self, event: WalletEvent);
+}
+
+/// Notifier that reports the events to every notifier in the list
+pub struct NotifierList(pub Vec<Arc<dyn EventNotifier>>);
+
+impl EventNotifier for NotifierList {
+	fn notify(&self, event: WalletEvent) {
+		for notifier in &self.0 {
+			notifier.notify(event.clone());
+		}
+	}
+}
+
+/// Default number of confirmations for TxConfirmed event
+pub const DEFAULT_NOTIFY_CONFIRMATIONS: u64 = 10;
+
+lazy_static! {
+	/// Notifier for the wallet events, None - notifications are disabled
+	static ref EVENT_NOTIFIER: RwLock<Option<Arc<dyn EventNotifier>>> = RwLock::new(None);
+	/// Number of confirmations for TxConfirmed event
+	static ref NOTIFY_CONFIRMATIONS: RwLock<u64> = RwLock::new(DEFAULT_NOTIFY_CONFIRMATIONS);
+	/// Event subscribers, the receiver is dropped when the subscriber is gone
+	static ref EVENT_SUBSCRIBERS: Mutex<Vec<Sender<WalletEvent>>> = Mutex::new(vec![]);
+}
+
+/// Set the notifier for the wallet events. None disables the notifications
+pub fn set_event_notifier(notifier: Option<Arc<dyn EventNotifier>>) {
+	*EVENT_NOTIFIER.write().unwrap() = notifier;
+}
+
+/// Set number of confirmations for TxConfirmed event
+pub fn set_notify_confirmations(confirmations: u64) {
+	*NOTIFY_CONFIRMATIONS.write().unwrap() = std::cmp::max(confirmations, 1);
+}
+
+/// Number of confirmations for TxConfirmed event
+pub fn get_notify_confirmations() -> u64 {
+	*NOTIFY_CONFIRMATIONS.read().unwrap()
+}
+
+/// Subscribe to the wallet events. Drop the receiver to unsubscribe.
+pub fn subscribe() -> Receiver<WalletEvent> {
+	let (tx, rx) = channel();
+	EVENT_SUBSCRIBERS.lock().unwrap().push(tx);
+	rx
+}
+
+/// Check if notifier is set or somebody is subscribed to the events
+pub fn is_enabled() -> bool {
+	EVENT_NOTIFIER.read().unwrap().is_some() || !EVENT_SUBSCRIBERS.lock().unwrap().is_empty()
+}
+
+/// Report the event to the notifier if it is set and to the subscribers
+pub fn notify(event: WalletEvent) {
+	EVENT_SUBSCRIBERS
+		.lock()
+		.unwrap()
+		.retain(|s| s.send(event.clone()).is_ok());
+	let notifier = EVENT_NOTIFIER.read().unwrap().clone();
+	if let Some(notifier) = notifier {
+		notifier.notify(event);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_event_json() {
+		let event = WalletEvent::PaymentReceived {
+			slate_id: "0436430c-2b02-624c-2032-570501212b00".to_string(),
+			amount: 2_000_000_000,
+			from: None,
+		};
+		assert_eq!(
+			serde_json::to_string(&event).unwrap(),
+			"{\"event\":\"payment_received\",\"slate_id\":\"0436430c-2b02-624c-2032-570501212b00\",\"amount\":2000000000,\"from\":null}"
+		);
+	}
+
+	#[test]
+	fn test_reorg_alert() {
+		let event = |unconfirmed_txs: Vec<u32>| WalletEvent::ChainReorg {
+			fork_height: 100,
+			prev_height: 102,
+			tip_height: 103,
+			rolled_back_txs: vec![1, 2],
+			unconfirmed_txs,
+		};
+		assert!(!event(vec![]).is_alert());
+		assert!(event(vec![2]).is_alert());
+		assert!(serde_json::to_string(&event(vec![]))
+			.unwrap()
+			.starts_with("{\"event\":\"chain_reorg\","));
+	}
+
+	#[test]
+	fn test_event_subscribers() {
+		let rx = subscribe();
+		assert!(is_enabled());
+		let event = WalletEvent::SwapFailed {
+			swap_id: "swap".to_string(),
+			state: "SellerWaitingForBuyerLock".to_string(),
+			error: "timeout".to_string(),
+		};
+		notify(event.clone());
+		assert_eq!(rx.recv().unwrap(), event);
+		drop(rx);
+		notify(event);
+		assert!(EVENT_SUBSCRIBERS.lock().unwrap().is_empty());
+	}
+}
@@ -0,0 +1,317 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Human readable payment identifiers 'name@domain'. The owner of the domain publishes a record
+//! with the wallet address in the DNS TXT record '<name>.user._mwc-payment.<domain>' or at
+//! 'https://<domain>/.well-known/mwc-payment/<name>.json'. The record is signed by the proof
+//! address of the wallet, the resolved address is used only if the signature is valid.
+
+use crate::grin_keychain::Keychain;
+use crate::grin_util as util;
+use crate::grin_util::secp::key::PublicKey;
+use crate::proof::crypto::{self, Hex};
+use crate::proof::proofaddress::{self, ProvableAddress};
+use crate::{Error, ErrorKind};
+use ed25519_dalek::Keypair as DalekKeypair;
+use ed25519_dalek::PublicKey as DalekPublicKey;
+use ed25519_dalek::Signature as DalekSignature;
+use ed25519_dalek::{Signer, Verifier};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Prefix of the TXT record
+pub const TXT_RECORD_PREFIX: &str = "mwc1";
+
+/// Payment identifier 'name@domain'
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentId {
+	/// User name
+	pub name: String,
+	/// Domain that publishes the record
+	pub domain: String,
+}
+
+impl PaymentId {
+	/// Parse the payment identifier. Returns None for anything else, including the MQS
+	/// addresses 'pubkey@domain' and the URLs.
+	pub fn parse(s: &str) -> Option<PaymentId> {
+		if s.contains("://") {
+			return None;
+		}
+		let mut parts = s.split('@');
+		let (name, domain) = match (parts.next(), parts.next(), parts.next()) {
+			(Some(n), Some(d), None) => (n.to_lowercase(), d.to_lowercase()),
+			_ => return None,
+		};
+		let valid_name = !name.is_empty()
+			&& name.len() <= 64
+			&& name
+				.chars()
+				.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.');
+		let valid_domain = domain.contains('.')
+			&& !domain.starts_with('.')
+			&& !domain.ends_with('.')
+			&& domain
+				.chars()
+				.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+		if !valid_name || !valid_domain {
+			return None;
+		}
+		// MQS address has the public key as a name
+		if PublicKey::from_base58_check(&name, proofaddress::version_bytes()).is_ok() {
+			return None;
+		}
+		Some(PaymentId { name, domain })
+	}
+
+	/// DNS name of the TXT record
+	pub fn txt_record_name(&self) -> String {
+		format!("{}.user._mwc-payment.{}", self.name, self.domain)
+	}
+
+	/// URL of the record at the domain web server
+	pub fn well_known_url(&self) -> String {
+		format!(
+			"https://{}/.well-known/mwc-payment/{}.json",
+			self.domain, self.name
+		)
+	}
+}
+
+impl fmt::Display for PaymentId {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}@{}", self.name, self.domain)
+	}
+}
+
+/// Published record of the payment identifier
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PaymentIdRecord {
+	/// Destination for send: Tor address, MQS address or http(s) URL
+	pub address: String,
+	/// Proof address of the wallet that signs the record
+	pub proof_address: String,
+	/// Signature of the proof address key, hex
+	pub signature: String,
+}
+
+/// Resolved payment identifier
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResolvedPaymentId {
+	/// Payment identifier 'name@domain'
+	pub payment_id: String,
+	/// Destination for send
+	pub address: String,
+	/// Proof address, the record is signed by its key
+	pub proof_address: String,
+	/// Where the record was found: 'dns' or 'https'
+	pub source: String,
+}
+
+fn signed_message(id: &PaymentId, address: &str, proof_address: &str) -> String {
+	format!("mwc-payment|{}|{}|{}", id, address, proof_address)
+}
+
+impl PaymentIdRecord {
+	/// Sign the record for the payment identifier with the wallet proof address key.
+	/// The Tor key is used if 'tor' is true, the MQS key otherwise.
+	pub fn sign<K: Keychain>(
+		keychain: &K,
+		id: &PaymentId,
+		address: &str,
+		tor: bool,
+	) -> Result<PaymentIdRecord, Error> {
+		let (proof_address, signature) = if tor {
+			let secret = proofaddress::payment_proof_address_dalek_secret(keychain, None)?;
+			let public: DalekPublicKey = (&secret).into();
+			let proof_address = ProvableAddress::from_tor_pub_key(&public).public_key;
+			let keypair = DalekKeypair { public, secret };
+			let msg = signed_message(id, address, &proof_address);
+			let signature = util::to_hex(&keypair.sign(msg.as_bytes()).to_bytes().to_vec());
+			(proof_address, signature)
+		} else {
+			let sec_key = proofaddress::payment_proof_address_secret(keychain, None)?;
+			let public = crypto::public_key_from_secret_key(&sec_key)?;
+			let proof_address = ProvableAddress::from_pub_key(&public).public_key;
+			let msg = signed_message(id, address, &proof_address);
+			let signature = crypto::sign_challenge(&msg, &sec_key)?.to_hex();
+			(proof_address, signature)
+		};
+		Ok(PaymentIdRecord {
+			address: address.to_string(),
+			proof_address,
+			signature,
+		})
+	}
+
+	/// Verify the signature of the record for the payment identifier
+	pub fn verify(&self, id: &PaymentId) -> Result<(), Error> {
+		let invalid = |e: String| -> Error {
+			ErrorKind::PaymentId(format!("Invalid record signature for {}, {}", id, e)).into()
+		};
+		let proof_address =
+			ProvableAddress::from_str(&self.proof_address).map_err(|e| invalid(e.to_string()))?;
+		let msg = signed_message(id, &self.address, &proof_address.public_key);
+		if proof_address.public_key.len() == 56 {
+			let public = proof_address
+				.tor_public_key()
+				.map_err(|e| invalid(e.to_string()))?;
+			let sig = util::from_hex(&self.signature).map_err(|e| invalid(e.to_string()))?;
+			let sig = DalekSignature::from_bytes(&sig).map_err(|e| invalid(e.to_string()))?;
+			public
+				.verify(msg.as_bytes(), &sig)
+				.map_err(|e| invalid(e.to_string()))
+		} else {
+			let public = proof_address
+				.public_key()
+				.map_err(|e| invalid(e.to_string()))?;
+			let sig = crypto::signature_from_string(&self.signature)
+				.map_err(|e| invalid(e.to_string()))?;
+			crypto::verify_signature(&msg, &sig, &public).map_err(|e| invalid(e.to_string()))
+		}
+	}
+
+	/// TXT record text: 'mwc1 address=<address> proof=<proof address> sig=<signature>'
+	pub fn to_txt(&self) -> String {
+		format!(
+			"{} address={} proof={} sig={}",
+			TXT_RECORD_PREFIX, self.address, self.proof_address, self.signature
+		)
+	}
+
+	/// Parse the TXT record text. Returns None if it is not a payment id record.
+	pub fn from_txt(txt: &str) -> Option<PaymentIdRecord> {
+		let mut fields = txt.split_whitespace();
+		if fields.next() != Some(TXT_RECORD_PREFIX) {
+			return None;
+		}
+		let (mut address, mut proof_address, mut signature) = (None, None, None);
+		for field in fields {
+			let mut kv = field.splitn(2, '=');
+			match (kv.next(), kv.next()) {
+				(Some("address"), Some(v)) => address = Some(v.to_string()),
+				(Some("proof"), Some(v)) => proof_address = Some(v.to_string()),
+				(Some("sig"), Some(v)) => signature = Some(v.to_string()),
+				_ => (),
+			}
+		}
+		Some(PaymentIdRecord {
+			address: address?,
+			proof_address: proof_address?,
+			signature: signature?,
+		})
+	}
+}
+
+/// Lookup of the published payment id records
+pub trait PaymentIdResolver: Send + Sync {
+	/// Texts of the DNS TXT records with the name
+	fn txt_records(&self, name: &str) -> Result<Vec<String>, Error>;
+	/// Record from the domain web server, None if the server doesn't have it
+	fn well_known_record(&self, id: &PaymentId) -> Result<Option<PaymentIdRecord>, Error>;
+}
+
+lazy_static! {
+	/// Lookup of the payment id records, None - payment ids are not supported
+	static ref PAYMENT_ID_RESOLVER: RwLock<Option<Arc<dyn PaymentIdResolver>>> = RwLock::new(None);
+}
+
+/// Set from config the lookup of the payment id records
+pub fn set_payment_id_resolver(resolver: Option<Arc<dyn PaymentIdResolver>>) {
+	*PAYMENT_ID_RESOLVER.write().unwrap() = resolver;
+}
+
+/// Resolve the payment identifier 'name@domain'. DNS TXT records are checked first, then the
+/// domain web server. Only the records with the valid signature are accepted.
+pub fn resolve_payment_id(payment_id: &str) -> Result<ResolvedPaymentId, Error> {
+	let id = PaymentId::parse(payment_id).ok_or(ErrorKind::PaymentId(format!(
+		"'{}' is not a payment id 'name@domain'",
+		payment_id
+	)))?;
+	let resolver = PAYMENT_ID_RESOLVER
+		.read()
+		.unwrap()
+		.clone()
+		.ok_or(ErrorKind::PaymentId(
+			"resolver is not configured, please set payment_id_doh_url".to_string(),
+		))?;
+
+	let resolved = |record: PaymentIdRecord, source: &str| ResolvedPaymentId {
+		payment_id: id.to_string(),
+		address: record.address,
+		proof_address: record.proof_address,
+		source: source.to_string(),
+	};
+
+	let mut last_error = None;
+	match resolver.txt_records(&id.txt_record_name()) {
+		Ok(records) => {
+			for record in records.iter().filter_map(|t| PaymentIdRecord::from_txt(t)) {
+				match record.verify(&id) {
+					Ok(_) => return Ok(resolved(record, "dns")),
+					Err(e) => last_error = Some(e),
+				}
+			}
+		}
+		Err(e) => warn!("DNS lookup of {} failed, {}", id.txt_record_name(), e),
+	}
+	match resolver.well_known_record(&id) {
+		Ok(Some(record)) => match record.verify(&id) {
+			Ok(_) => return Ok(resolved(record, "https")),
+			Err(e) => last_error = Some(e),
+		},
+		Ok(None) => (),
+		Err(e) => warn!("Request of {} failed, {}", id.well_known_url(), e),
+	}
+	Err(last_error
+		.unwrap_or(ErrorKind::PaymentId(format!("Payment id {} is not found", id)).into()))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::grin_keychain::ExtKeychain;
+
+	#[test]
+	fn test_payment_id_record() {
+		assert_eq!(
+			PaymentId::parse("Alice@Example.com"),
+			Some(PaymentId {
+				name: "alice".to_string(),
+				domain: "example.com".to_string()
+			})
+		);
+		assert!(PaymentId::parse("alice@localhost").is_none());
+		assert!(PaymentId::parse("http://alice@example.com").is_none());
+		assert!(PaymentId::parse("@alice").is_none());
+
+		let keychain = ExtKeychain::from_random_seed(true).unwrap();
+		let id = PaymentId::parse("alice@example.com").unwrap();
+		for tor in &[true, false] {
+			let record =
+				PaymentIdRecord::sign(&keychain, &id, "http://127.0.0.1:3415", *tor).unwrap();
+			record.verify(&id).unwrap();
+			let parsed = PaymentIdRecord::from_txt(&record.to_txt()).unwrap();
+			assert_eq!(parsed, record);
+
+			// The record can't be used for the other name or the other address
+			let bob = PaymentId::parse("bob@example.com").unwrap();
+			assert!(record.verify(&bob).is_err());
+			let mut changed = record.clone();
+			changed.address = "http://127.0.0.1:3416".to_string();
+			assert!(changed.verify(&id).is_err());
+		}
+		assert!(PaymentIdRecord::from_txt("v=spf1 -all").is_none());
+	}
+}
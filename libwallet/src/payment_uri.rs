@@ -0,0 +1,233 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! BIP21 style payment URI. Merchant can share a single string or QR code with everything
+//! the payer needs:
+//!   mwc:<address>?amount=<amount>&message=<message>&method=<http|mwcmqs|slatepack>
+//! Amount is in MWC with optional fraction. Unknown parameters are ignored, except the
+//! ones with 'req-' prefix, payer must refuse the URI with required parameter that it
+//! doesn't understand.
+
+use crate::grin_core::core::{amount_from_hr_string, amount_to_hr_string};
+use crate::{Error, ErrorKind};
+use std::fmt;
+
+/// URI scheme for MWC payments
+pub const PAYMENT_URI_SCHEME: &str = "mwc";
+
+/// Transport methods that can be used as a hint in the URI
+pub const PAYMENT_URI_METHODS: [&str; 3] = ["http", "mwcmqs", "slatepack"];
+
+/// Parsed mwc: payment URI
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentUri {
+	/// Destination address: tor, mwcmqs or http(s) listener
+	pub address: String,
+	/// Amount to pay in nanoMWC
+	pub amount: Option<u64>,
+	/// Message for the payer to include into the transaction
+	pub message: Option<String>,
+	/// Transport hint, see PAYMENT_URI_METHODS
+	pub method: Option<String>,
+}
+
+impl PaymentUri {
+	/// New URI for the address, no amount, message or transport hints
+	pub fn new(address: String) -> Self {
+		PaymentUri {
+			address,
+			amount: None,
+			message: None,
+			method: None,
+		}
+	}
+
+	/// Parse mwc:<address>?<params> string
+	pub fn parse(uri: &str) -> Result<Self, Error> {
+		let uri = uri.trim();
+		let scheme_len = PAYMENT_URI_SCHEME.len();
+		if uri.len() <= scheme_len + 1
+			|| !uri[..scheme_len].eq_ignore_ascii_case(PAYMENT_URI_SCHEME)
+			|| &uri[scheme_len..scheme_len + 1] != ":"
+		{
+			return Err(ErrorKind::PaymentUri(format!(
+				"expected '{}:<address>' format, get '{}'",
+				PAYMENT_URI_SCHEME, uri
+			))
+			.into());
+		}
+
+		let body = &uri[scheme_len + 1..];
+		let (address, query) = match body.find('?') {
+			Some(idx) => (&body[..idx], Some(&body[idx + 1..])),
+			None => (body, None),
+		};
+		let address = percent_decode(address)?;
+		if address.is_empty() {
+			return Err(ErrorKind::PaymentUri("address is empty".to_string()).into());
+		}
+
+		let mut res = PaymentUri::new(address);
+		for param in query.unwrap_or("").split('&').filter(|p| !p.is_empty()) {
+			let mut split = param.splitn(2, '=');
+			let key = split.next().unwrap_or("");
+			let value = percent_decode(split.next().unwrap_or(""))?;
+			match key {
+				"amount" => {
+					let amount = amount_from_hr_string(&value).map_err(|e| {
+						ErrorKind::PaymentUri(format!("unable to parse amount '{}', {}", value, e))
+					})?;
+					res.amount = Some(amount);
+				}
+				"message" => res.message = Some(value),
+				"method" => {
+					if !PAYMENT_URI_METHODS.contains(&value.as_str()) {
+						return Err(ErrorKind::PaymentUri(format!(
+							"unknown method '{}', expected one of {:?}",
+							value, PAYMENT_URI_METHODS
+						))
+						.into());
+					}
+					res.method = Some(value);
+				}
+				k if k.starts_with("req-") => {
+					return Err(ErrorKind::PaymentUri(format!(
+						"required parameter '{}' is not supported",
+						k
+					))
+					.into());
+				}
+				_ => (), // Unknown optional parameters are ignored
+			}
+		}
+		Ok(res)
+	}
+}
+
+impl fmt::Display for PaymentUri {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{}:{}",
+			PAYMENT_URI_SCHEME,
+			percent_encode(&self.address, ":/@")
+		)?;
+		let mut params: Vec<String> = vec![];
+		if let Some(amount) = self.amount {
+			params.push(format!("amount={}", amount_to_hr_string(amount, true)));
+		}
+		if let Some(message) = &self.message {
+			params.push(format!("message={}", percent_encode(message, "")));
+		}
+		if let Some(method) = &self.method {
+			params.push(format!("method={}", percent_encode(method, "")));
+		}
+		if !params.is_empty() {
+			write!(f, "?{}", params.join("&"))?;
+		}
+		Ok(())
+	}
+}
+
+// Encode everything except RFC3986 unreserved characters and the 'keep' list
+fn percent_encode(s: &str, keep: &str) -> String {
+	let mut res = String::with_capacity(s.len());
+	for b in s.bytes() {
+		let c = b as char;
+		if c.is_ascii_alphanumeric() || "-._~".contains(c) || keep.contains(c) {
+			res.push(c);
+		} else {
+			res.push_str(&format!("%{:02X}", b));
+		}
+	}
+	res
+}
+
+fn percent_decode(s: &str) -> Result<String, Error> {
+	let bytes = s.as_bytes();
+	let mut res: Vec<u8> = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		match bytes[i] {
+			b'%' => {
+				let hex = s.get(i + 1..i + 3).ok_or(ErrorKind::PaymentUri(format!(
+					"broken escape sequence at '{}'",
+					s
+				)))?;
+				let b = u8::from_str_radix(hex, 16).map_err(|_| {
+					ErrorKind::PaymentUri(format!("broken escape sequence at '{}'", s))
+				})?;
+				res.push(b);
+				i += 3;
+			}
+			b'+' => {
+				res.push(b' ');
+				i += 1;
+			}
+			b => {
+				res.push(b);
+				i += 1;
+			}
+		}
+	}
+	String::from_utf8(res)
+		.map_err(|e| ErrorKind::PaymentUri(format!("non UTF-8 value '{}', {}", s, e)).into())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn payment_uri_roundtrip() {
+		let uri = PaymentUri {
+			address: "xmgEvZ4MCCGMJnRnNXKHBbHmSGWQchNr9uZpY5J1XXnsCFS45fsU".to_string(),
+			amount: Some(1_500_000_000),
+			message: Some("Order #42, thank you!".to_string()),
+			method: Some("mwcmqs".to_string()),
+		};
+		let uri_str = uri.to_string();
+		assert_eq!(
+			uri_str,
+			"mwc:xmgEvZ4MCCGMJnRnNXKHBbHmSGWQchNr9uZpY5J1XXnsCFS45fsU?amount=1.5&message=Order%20%2342%2C%20thank%20you%21&method=mwcmqs"
+		);
+		assert_eq!(PaymentUri::parse(&uri_str).unwrap(), uri);
+
+		let uri = PaymentUri::new("http://192.168.0.10:3415".to_string());
+		assert_eq!(uri.to_string(), "mwc:http://192.168.0.10:3415");
+		assert_eq!(PaymentUri::parse(&uri.to_string()).unwrap(), uri);
+	}
+
+	#[test]
+	fn payment_uri_parse() {
+		let uri = PaymentUri::parse(
+			"MWC:2a6at2obto3uvkpkitqp4wxcg6u36qf534eucbskqciturczzc5suyid?message=coffee+time&label=shop",
+		)
+		.unwrap();
+		assert_eq!(
+			uri.address,
+			"2a6at2obto3uvkpkitqp4wxcg6u36qf534eucbskqciturczzc5suyid"
+		);
+		assert_eq!(uri.amount, None);
+		assert_eq!(uri.message, Some("coffee time".to_string()));
+		assert_eq!(uri.method, None);
+
+		assert!(PaymentUri::parse("bitcoin:1BoatSLRHtKNngkdXEeobR76b53LETtpyT").is_err());
+		assert!(PaymentUri::parse("mwc:").is_err());
+		assert!(PaymentUri::parse("mwc:addr?amount=1.x").is_err());
+		assert!(PaymentUri::parse("mwc:addr?method=pigeon").is_err());
+		assert!(PaymentUri::parse("mwc:addr?req-expires=100").is_err());
+		assert!(PaymentUri::parse("mwc:addr?message=%4").is_err());
+	}
+}
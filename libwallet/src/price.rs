@@ -0,0 +1,230 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! MWC exchange rates for the fiat values of the amounts. The rates are requested from the
+//! configured source only when they are asked and are cached: the current rate for the
+//! configured time, the historical daily rates while the wallet is running.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::core::amount_to_hr_string;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Default time to cache the current exchange rate
+pub const DEFAULT_PRICE_CACHE_MINUTES: u64 = 10;
+
+/// Source of the MWC exchange rates, for example a price API
+pub trait PriceSource: Send + Sync {
+	/// Location for the messages
+	fn location(&self) -> String;
+	/// Price of 1 MWC in the currency now
+	fn current_rate(&self, currency: &str) -> Result<f64, Error>;
+	/// Price of 1 MWC in the currency on the date (UTC)
+	fn historical_rate(&self, currency: &str, date: &NaiveDate) -> Result<f64, Error>;
+}
+
+/// Exchange rate of MWC
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExchangeRate {
+	/// Currency code, lower case, e.g. 'usd'
+	pub currency: String,
+	/// Price of 1 MWC in the currency
+	pub rate: f64,
+	/// Date of the historical rate, None - the current rate
+	pub date: Option<NaiveDate>,
+	/// Time when the rate was requested from the source
+	pub updated: DateTime<Utc>,
+}
+
+impl ExchangeRate {
+	/// Value of the amount in nanoMWC in the currency
+	pub fn value(&self, amount: u64) -> f64 {
+		amount as f64 / 1_000_000_000.0 * self.rate
+	}
+
+	/// Value of the amount in nanoMWC as a string, like '12.34 USD'
+	pub fn value_hr(&self, amount: u64) -> String {
+		format!("{:.2} {}", self.value(amount), self.currency.to_uppercase())
+	}
+}
+
+/// Exchange rates source with the cache
+#[derive(Clone)]
+pub struct PriceProvider {
+	/// Source of the rates
+	pub source: Arc<dyn PriceSource>,
+	/// Minutes to keep the current rate in the cache
+	pub cache_minutes: u64,
+}
+
+lazy_static! {
+	/// Exchange rates source, None - fiat values are disabled
+	static ref PRICE_PROVIDER: RwLock<Option<PriceProvider>> = RwLock::new(None);
+	/// Requested rates by currency and date. The current rate has no date.
+	static ref RATES_CACHE: RwLock<HashMap<(String, Option<NaiveDate>), ExchangeRate>> =
+		RwLock::new(HashMap::new());
+}
+
+/// Set from config the exchange rates source. None disables the fiat values
+pub fn set_price_provider(provider: Option<PriceProvider>) {
+	*PRICE_PROVIDER.write().unwrap() = provider;
+	// the source might be different, the rates will be requested again
+	RATES_CACHE.write().unwrap().clear();
+}
+
+/// Read the exchange rates source settings
+pub fn get_price_provider() -> Option<PriceProvider> {
+	PRICE_PROVIDER.read().unwrap().clone()
+}
+
+/// Exchange rate of MWC in the currency, the current one or the daily rate on the date.
+/// Today and future dates get the current rate.
+pub fn get_exchange_rate(currency: &str, date: Option<NaiveDate>) -> Result<ExchangeRate, Error> {
+	let provider = get_price_provider().ok_or(ErrorKind::PriceFeed(
+		"exchange rate source is not configured, please set price_source_url".to_string(),
+	))?;
+	let currency = currency.trim().to_lowercase();
+	if currency.is_empty() || !currency.chars().all(|c| c.is_ascii_alphanumeric()) {
+		return Err(ErrorKind::PriceFeed(format!("invalid currency '{}'", currency)).into());
+	}
+	let now = Utc::now();
+	let date = date.filter(|d| *d < now.date().naive_utc());
+
+	let key = (currency.clone(), date.clone());
+	if let Some(rate) = RATES_CACHE.read().unwrap().get(&key) {
+		if date.is_some() || now - rate.updated < Duration::minutes(provider.cache_minutes as i64) {
+			return Ok(rate.clone());
+		}
+	}
+
+	let rate = match &date {
+		Some(d) => provider.source.historical_rate(&currency, d),
+		None => provider.source.current_rate(&currency),
+	}
+	.map_err(|e| {
+		ErrorKind::PriceFeed(format!(
+			"unable to get {} rate from {}, {}",
+			currency,
+			provider.source.location(),
+			e
+		))
+	})?;
+	if !rate.is_finite() || rate < 0.0 {
+		return Err(ErrorKind::PriceFeed(format!(
+			"invalid {} rate {} from {}",
+			currency,
+			rate,
+			provider.source.location()
+		))
+		.into());
+	}
+
+	let rate = ExchangeRate {
+		currency,
+		rate,
+		date,
+		updated: now,
+	};
+	debug!(
+		"MWC rate {} {:?}: 1 MWC = {}",
+		rate.currency,
+		rate.date,
+		rate.value_hr(1_000_000_000)
+	);
+	RATES_CACHE.write().unwrap().insert(key, rate.clone());
+	Ok(rate)
+}
+
+/// Amount with the fiat value, like '1.500000000 (3.75 USD)'. If the rate is not available,
+/// only the amount is returned.
+pub fn amount_with_value(amount: u64, rate: Option<&ExchangeRate>) -> String {
+	match rate {
+		Some(rate) => format!(
+			"{} ({})",
+			amount_to_hr_string(amount, false),
+			rate.value_hr(amount)
+		),
+		None => amount_to_hr_string(amount, false),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	struct TestSource {
+		requests: AtomicUsize,
+	}
+
+	impl PriceSource for TestSource {
+		fn location(&self) -> String {
+			"test".to_string()
+		}
+		fn current_rate(&self, currency: &str) -> Result<f64, Error> {
+			self.requests.fetch_add(1, Ordering::Relaxed);
+			match currency {
+				"usd" => Ok(2.5),
+				_ => Err(ErrorKind::GenericError("unknown currency".to_string()).into()),
+			}
+		}
+		fn historical_rate(&self, _currency: &str, date: &NaiveDate) -> Result<f64, Error> {
+			self.requests.fetch_add(1, Ordering::Relaxed);
+			Ok(date.format("%d").to_string().parse::<f64>().unwrap())
+		}
+	}
+
+	#[test]
+	fn test_exchange_rates() {
+		set_price_provider(None);
+		assert!(get_exchange_rate("usd", None).is_err());
+
+		let source = Arc::new(TestSource {
+			requests: AtomicUsize::new(0),
+		});
+		set_price_provider(Some(PriceProvider {
+			source: source.clone(),
+			cache_minutes: DEFAULT_PRICE_CACHE_MINUTES,
+		}));
+
+		let rate = get_exchange_rate("USD", None).unwrap();
+		assert_eq!(rate.currency, "usd");
+		assert_eq!(rate.date, None);
+		assert_eq!(rate.value_hr(3_000_000_000), "7.50 USD");
+		assert_eq!(
+			amount_with_value(3_000_000_000, Some(&rate)),
+			"3.000000000 (7.50 USD)"
+		);
+		// cached
+		get_exchange_rate("usd", None).unwrap();
+		assert_eq!(source.requests.load(Ordering::Relaxed), 1);
+
+		let date = NaiveDate::from_ymd(2021, 3, 12);
+		let rate = get_exchange_rate("usd", Some(date)).unwrap();
+		assert_eq!(rate.rate, 12.0);
+		assert_eq!(rate.date, Some(date));
+		get_exchange_rate("usd", Some(date)).unwrap();
+		assert_eq!(source.requests.load(Ordering::Relaxed), 2);
+
+		// Future date is the current rate
+		let rate = get_exchange_rate("usd", Some(NaiveDate::from_ymd(2200, 1, 1))).unwrap();
+		assert_eq!(rate.date, None);
+		assert_eq!(source.requests.load(Ordering::Relaxed), 2);
+
+		assert!(get_exchange_rate("eur", None).is_err());
+		assert!(get_exchange_rate("us/d", None).is_err());
+		set_price_provider(None);
+	}
+}
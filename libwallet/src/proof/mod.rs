@@ -27,10 +27,14 @@ pub mod crypto;
 pub mod hasher;
 /// Proff messages
 pub mod message;
+/// Verification of the packaged payment proofs without the wallet
+pub mod payment_proof;
 /// Addresses
 pub mod proofaddress;
 /// Proofs that come froom mwc713. Expected that they will be used for all transports
 pub mod tx_proof;
+/// Deterministic test vectors for addresses and payment proofs
+pub mod vectors;
 
 ///
 pub mod base58;
@@ -0,0 +1,150 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of the packaged payment proofs without the wallet. Anybody can verify the proof
+//! signatures, the kernel is checked optionally with any node.
+
+use crate::grin_util as util;
+use crate::internal::tx;
+use crate::proof::crypto;
+use crate::proof::proofaddress::ProvableAddress;
+use crate::types::NodeClient;
+use crate::{Error, ErrorKind, PaymentProof};
+use ed25519_dalek::Signature as DalekSignature;
+use ed25519_dalek::Verifier;
+
+/// Verify the signature of the proof message by the MQS or Tor address
+fn verify_address_signature(
+	address: &ProvableAddress,
+	msg: &str,
+	signature: &str,
+) -> Result<(), Error> {
+	if address.public_key.len() == 56 {
+		let public = address.tor_public_key()?;
+		let sig = util::from_hex(signature).map_err(|e| {
+			ErrorKind::TxProofVerifySignature(format!("Unable to decode signature, {}", e))
+		})?;
+		let sig = DalekSignature::from_bytes(&sig).map_err(|e| {
+			ErrorKind::TxProofVerifySignature(format!("Unable to decode signature, {}", e))
+		})?;
+		public
+			.verify(msg.as_bytes(), &sig)
+			.map_err(|e| ErrorKind::TxProofVerifySignature(format!("{}", e)))?;
+	} else {
+		let public = address.public_key()?;
+		let sig = crypto::signature_from_string(signature)
+			.map_err(|e| ErrorKind::TxProofVerifySignature(format!("{}", e)))?;
+		crypto::verify_signature(msg, &sig, &public)
+			.map_err(|e| ErrorKind::TxProofVerifySignature(format!("{}", e)))?;
+	}
+	Ok(())
+}
+
+/// Verify the payment proof signatures of the sender and the recipient. No wallet or node is
+/// needed for that.
+pub fn verify_payment_proof_signatures(proof: &PaymentProof) -> Result<(), Error> {
+	let msg = tx::payment_proof_message(
+		proof.amount,
+		&proof.excess,
+		proof.sender_address.public_key.clone(),
+	)?;
+	verify_address_signature(&proof.recipient_address, &msg, &proof.recipient_sig)?;
+	verify_address_signature(&proof.sender_address, &msg, &proof.sender_sig)?;
+	Ok(())
+}
+
+/// Check that the kernel of the payment proof exists on the chain. Any node can be used.
+/// Returns the height of the kernel block.
+pub fn verify_payment_proof_kernel<C>(client: &C, proof: &PaymentProof) -> Result<u64, Error>
+where
+	C: NodeClient,
+{
+	match client.get_kernel(&proof.excess, None, None) {
+		Err(e) => Err(ErrorKind::PaymentProof(format!(
+			"Error retrieving kernel from chain: {}",
+			e
+		))
+		.into()),
+		Ok(None) => Err(ErrorKind::PaymentProof(format!(
+			"Transaction kernel with excess {:?} not found on chain",
+			proof.excess
+		))
+		.into()),
+		Ok(Some((_kernel, height, _mmr_index))) => Ok(height),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::grin_core::global;
+	use crate::grin_keychain::{ExtKeychainPath, Keychain, SwitchCommitmentType};
+	use crate::proof::proofaddress::{self, ProofAddressType};
+	use crate::proof::vectors;
+
+	fn signed_proof(tor: bool) -> PaymentProof {
+		let keychain = vectors::test_vectors_keychain().unwrap();
+		let excess = keychain
+			.commit(
+				0,
+				&ExtKeychainPath::new(1, 0, 0, 0, 0).to_identifier(),
+				SwitchCommitmentType::Regular,
+			)
+			.unwrap();
+		let address = |index: u32| {
+			let addr_type = match tor {
+				true => ProofAddressType::Onion,
+				false => ProofAddressType::MQS,
+			};
+			proofaddress::payment_proof_address_from_index(&keychain, index, addr_type).unwrap()
+		};
+		let sender = address(0);
+		let recipient = address(1);
+		let sign = |signer: &ProvableAddress, index: u32| {
+			tx::create_payment_proof_signature(
+				1_000_000_000,
+				&excess,
+				sender.clone(),
+				signer.clone(),
+				proofaddress::payment_proof_address_secret(&keychain, Some(index)).unwrap(),
+			)
+			.unwrap()
+		};
+		PaymentProof {
+			amount: 1_000_000_000,
+			excess,
+			recipient_sig: sign(&recipient, 1),
+			sender_sig: sign(&sender, 0),
+			recipient_address: recipient,
+			sender_address: sender,
+		}
+	}
+
+	#[test]
+	fn test_verify_payment_proof_signatures() {
+		global::set_local_chain_type(global::ChainTypes::Floonet);
+		for tor in &[false, true] {
+			let proof = signed_proof(*tor);
+			verify_payment_proof_signatures(&proof).unwrap();
+
+			let mut changed = proof.clone();
+			changed.amount = 20;
+			assert!(verify_payment_proof_signatures(&changed).is_err());
+
+			let mut swapped = proof.clone();
+			swapped.recipient_sig = proof.sender_sig.clone();
+			assert!(verify_payment_proof_signatures(&swapped).is_err());
+		}
+	}
+}
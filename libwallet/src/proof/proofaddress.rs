@@ -21,6 +21,7 @@ use crate::grin_util::secp::key::SecretKey;
 use crate::proof::crypto;
 use crate::proof::hasher;
 use crate::ErrorKind;
+use bech32::{FromBase32, ToBase32};
 use ed25519_dalek::PublicKey as DalekPublicKey;
 use ed25519_dalek::SecretKey as DalekSecretKey;
 use grin_wallet_util::OnionV3Address;
@@ -36,6 +37,11 @@ pub const PROOFABLE_ADDRESS_VERSION_MAINNET: [u8; 2] = [1, 69];
 /// Address prefixes for floonet
 pub const PROOFABLE_ADDRESS_VERSION_TESTNET: [u8; 2] = [1, 121];
 
+/// Grin slatepack address human readable part for mainnet
+pub const GRIN_SLATEPACK_ADDRESS_HRP_MAINNET: &str = "grin";
+/// Grin slatepack address human readable part for floonet
+pub const GRIN_SLATEPACK_ADDRESS_HRP_TESTNET: &str = "tgrin";
+
 lazy_static! {
 	/// Wallet address derive index
 	static ref ADDRESS_INDEX: AtomicU32 = AtomicU32::new(0);
@@ -80,6 +86,12 @@ impl ProvableAddress {
 
 	/// new instance
 	pub fn from_str(public_key: &str) -> Result<Self, Error> {
+		// Grin slatepack address is the same ed25519 key as tor address, just bech32 encoded
+		if is_grin_slatepack_address(public_key) {
+			let pk = grin_slatepack_address_to_pub_key(public_key)?;
+			return Ok(Self::from_tor_pub_key(&pk));
+		}
+
 		// Just check if it works
 		//this can be either PublicKey or DalekPublicKey
 		if public_key.len() != 56 {
@@ -131,6 +143,53 @@ pub fn version_bytes() -> Vec<u8> {
 	}
 }
 
+fn grin_slatepack_address_hrp() -> &'static str {
+	if global::is_mainnet() {
+		GRIN_SLATEPACK_ADDRESS_HRP_MAINNET
+	} else {
+		GRIN_SLATEPACK_ADDRESS_HRP_TESTNET
+	}
+}
+
+/// Check if the address looks like a grin slatepack address (grin1... or tgrin1...)
+pub fn is_grin_slatepack_address(addr: &str) -> bool {
+	let addr = addr.to_lowercase();
+	addr.starts_with(&format!("{}1", GRIN_SLATEPACK_ADDRESS_HRP_MAINNET))
+		|| addr.starts_with(&format!("{}1", GRIN_SLATEPACK_ADDRESS_HRP_TESTNET))
+}
+
+/// Build grin slatepack address (bech32) from the tor public key
+pub fn grin_slatepack_address(tor_pub_key: &DalekPublicKey) -> Result<String, Error> {
+	bech32::encode(
+		grin_slatepack_address_hrp(),
+		tor_pub_key.as_bytes().to_base32(),
+	)
+	.map_err(|e| {
+		ErrorKind::AddressDecoding(format!("Unable to build grin slatepack address, {}", e)).into()
+	})
+}
+
+/// Decode grin slatepack address (bech32) into the tor public key
+pub fn grin_slatepack_address_to_pub_key(addr: &str) -> Result<DalekPublicKey, Error> {
+	let (hrp, data) = bech32::decode(addr).map_err(|e| {
+		ErrorKind::AddressDecoding(format!("Unable to decode grin address {}, {}", addr, e))
+	})?;
+	if hrp != grin_slatepack_address_hrp() {
+		return Err(ErrorKind::AddressDecoding(format!(
+			"Grin address {} belongs to another network",
+			addr
+		))
+		.into());
+	}
+	let bytes = Vec::<u8>::from_base32(&data).map_err(|e| {
+		ErrorKind::AddressDecoding(format!("Unable to decode grin address {}, {}", addr, e))
+	})?;
+	DalekPublicKey::from_bytes(&bytes).map_err(|e| {
+		ErrorKind::AddressDecoding(format!("Invalid grin address {} public key, {}", addr, e))
+			.into()
+	})
+}
+
 ///convert a tor onion address to the pub key
 pub fn address_to_pubkey(addr: String) -> String {
 	//if it is an onion address, need to remove the http:// or https:// and .onion.
@@ -202,6 +261,31 @@ where
 	}
 }
 
+/// Find the index of the wallet address, MQS or Tor. The current address index is checked
+/// first, then the indexes from 0 to max_index.
+pub fn find_address_index<K>(
+	keychain: &K,
+	address: &ProvableAddress,
+	max_index: u32,
+) -> Result<Option<u32>, Error>
+where
+	K: Keychain,
+{
+	let tor = address.public_key.len() == 56;
+	let current = get_address_index();
+	for index in std::iter::once(current).chain((0..=max_index).filter(|i| *i != current)) {
+		let addr_type = match tor {
+			true => ProofAddressType::Onion,
+			false => ProofAddressType::MQS,
+		};
+		let candidate = payment_proof_address_from_index(keychain, index, addr_type)?;
+		if candidate.public_key == address.public_key {
+			return Ok(Some(index));
+		}
+	}
+	Ok(None)
+}
+
 /// Current secret that is used for public wallet address
 pub fn payment_proof_address_secret<K>(
 	keychain: &K,
@@ -0,0 +1,220 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deterministic test vectors for the wallet addresses and payment proofs. Third party
+//! implementations can validate compatibility with mwc-wallet against them.
+
+use crate::error::Error;
+use crate::grin_core::global;
+use crate::grin_keychain::{ExtKeychain, ExtKeychainPath, Keychain, SwitchCommitmentType};
+use crate::grin_util as util;
+use crate::internal::tx;
+use crate::proof::proofaddress::{self, ProofAddressType};
+
+/// Seed for the test vectors. It is the seed of the BIP32 test vector 1.
+pub const TEST_VECTORS_SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+/// Addresses derived for a single address index
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AddressTestVector {
+	/// Address derivation index
+	pub index: u32,
+	/// Address secret key, hex
+	pub secret_key: String,
+	/// MQS address (secp256k1 public key, base58check)
+	pub mqs_address: String,
+	/// Tor proof address (ed25519 public key, onion v3 format)
+	pub proof_address: String,
+	/// Tor onion service address
+	pub onion_address: String,
+}
+
+/// Payment proof signed by the receiver
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofTestVector {
+	/// Address index of the sender
+	pub sender_index: u32,
+	/// Address index of the receiver
+	pub receiver_index: u32,
+	/// Amount in nanoMWC
+	pub amount: u64,
+	/// Kernel excess, hex
+	pub kernel_excess: String,
+	/// Sender address
+	pub sender_address: String,
+	/// Receiver address
+	pub receiver_address: String,
+	/// Message that receiver is signing
+	pub message: String,
+	/// Receiver signature, hex. DER for MQS addresses, ed25519 for Tor addresses
+	pub signature: String,
+}
+
+/// Complete set of the test vectors
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TestVectors {
+	/// Chain type the vectors are generated for. Address prefixes depend on it
+	pub chain_type: String,
+	/// Wallet seed, hex
+	pub seed: String,
+	/// Addresses per derivation index
+	pub addresses: Vec<AddressTestVector>,
+	/// Payment proofs with MQS addresses
+	pub mqs_proofs: Vec<ProofTestVector>,
+	/// Payment proofs with Tor addresses
+	pub tor_proofs: Vec<ProofTestVector>,
+}
+
+/// Build the keychain from the test vectors seed
+pub fn test_vectors_keychain() -> Result<ExtKeychain, Error> {
+	let seed = util::from_hex(TEST_VECTORS_SEED).unwrap();
+	let keychain = ExtKeychain::from_seed(&seed, !global::is_mainnet())?;
+	Ok(keychain)
+}
+
+/// Generate test vectors for the address indexes [0..count). Every proof is sent from the
+/// address at index i to the address at index i+1.
+pub fn generate_test_vectors<K>(keychain: &K, count: u32) -> Result<TestVectors, Error>
+where
+	K: Keychain,
+{
+	let mut addresses = vec![];
+	for index in 0..count {
+		let secret = proofaddress::payment_proof_address_secret(keychain, Some(index))?;
+		let mqs_address =
+			proofaddress::payment_proof_address_from_index(keychain, index, ProofAddressType::MQS)?;
+		let proof_address = proofaddress::payment_proof_address_from_index(
+			keychain,
+			index,
+			ProofAddressType::Onion,
+		)?;
+		addresses.push(AddressTestVector {
+			index,
+			secret_key: util::to_hex(&secret.0),
+			mqs_address: mqs_address.public_key,
+			onion_address: format!("{}.onion", proof_address.public_key),
+			proof_address: proof_address.public_key,
+		});
+	}
+
+	let mut mqs_proofs = vec![];
+	let mut tor_proofs = vec![];
+	for sender_index in 0..count {
+		let receiver_index = sender_index + 1;
+		let amount = 1_000_000_000 * receiver_index as u64;
+		let kernel_excess = keychain.commit(
+			0,
+			&ExtKeychainPath::new(1, sender_index, 0, 0, 0).to_identifier(),
+			SwitchCommitmentType::Regular,
+		)?;
+		let receiver_secret =
+			proofaddress::payment_proof_address_secret(keychain, Some(receiver_index))?;
+
+		for addr_type in vec![ProofAddressType::MQS, ProofAddressType::Onion] {
+			let (sender_address, receiver_address, proofs) = match addr_type {
+				ProofAddressType::MQS => (
+					proofaddress::payment_proof_address_from_index(
+						keychain,
+						sender_index,
+						ProofAddressType::MQS,
+					)?,
+					proofaddress::payment_proof_address_from_index(
+						keychain,
+						receiver_index,
+						ProofAddressType::MQS,
+					)?,
+					&mut mqs_proofs,
+				),
+				ProofAddressType::Onion => (
+					proofaddress::payment_proof_address_from_index(
+						keychain,
+						sender_index,
+						ProofAddressType::Onion,
+					)?,
+					proofaddress::payment_proof_address_from_index(
+						keychain,
+						receiver_index,
+						ProofAddressType::Onion,
+					)?,
+					&mut tor_proofs,
+				),
+			};
+			let message = tx::payment_proof_message(
+				amount,
+				&kernel_excess,
+				sender_address.public_key.clone(),
+			)?;
+			let signature = tx::create_payment_proof_signature(
+				amount,
+				&kernel_excess,
+				sender_address.clone(),
+				receiver_address.clone(),
+				receiver_secret.clone(),
+			)?;
+			proofs.push(ProofTestVector {
+				sender_index,
+				receiver_index,
+				amount,
+				kernel_excess: util::to_hex(&kernel_excess.0),
+				sender_address: sender_address.public_key,
+				receiver_address: receiver_address.public_key,
+				message,
+				signature,
+			});
+		}
+	}
+
+	Ok(TestVectors {
+		chain_type: format!("{:?}", global::get_chain_type()),
+		seed: TEST_VECTORS_SEED.to_string(),
+		addresses,
+		mqs_proofs,
+		tor_proofs,
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::proof::crypto;
+
+	#[test]
+	fn test_vectors_are_deterministic() {
+		global::set_local_chain_type(global::ChainTypes::Floonet);
+		let keychain = test_vectors_keychain().unwrap();
+		let v1 = generate_test_vectors(&keychain, 3).unwrap();
+		let v2 = generate_test_vectors(&test_vectors_keychain().unwrap(), 3).unwrap();
+		assert_eq!(
+			serde_json::to_string(&v1).unwrap(),
+			serde_json::to_string(&v2).unwrap()
+		);
+		assert_eq!(v1.addresses.len(), 3);
+		assert_eq!(v1.mqs_proofs.len(), 3);
+		assert_eq!(v1.tor_proofs.len(), 3);
+
+		for (addr, proof) in v1.addresses.iter().zip(v1.mqs_proofs.iter()) {
+			assert_eq!(addr.mqs_address, proof.sender_address);
+			assert_eq!(addr.mqs_address.len(), 52);
+			assert_eq!(addr.proof_address.len(), 56);
+
+			// MQS signature must be verifiable with the receiver address
+			let receiver = proofaddress::ProvableAddress::from_str(&proof.receiver_address)
+				.unwrap()
+				.public_key()
+				.unwrap();
+			let signature = crypto::signature_from_string(&proof.signature).unwrap();
+			crypto::verify_signature(&proof.message, &signature, &receiver).unwrap();
+		}
+	}
+}
@@ -0,0 +1,163 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of the output range proofs for the scan and the receive. Batched verification
+//! shares the multi-exponentiation between the proofs, so it is much cheaper than verifying
+//! the proofs one by one. Other backends (SIMD, GPU) can be plugged in with set_proof_verifier.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_util::secp::pedersen::{Commitment, RangeProof};
+use crate::grin_util::secp::Secp256k1;
+use crate::grin_util::to_hex;
+use std::sync::{Arc, RwLock};
+
+/// Backend that verifies the range proofs
+pub trait ProofVerifier: Send + Sync {
+	/// Name for the logs
+	fn name(&self) -> &str;
+	/// True if all proofs are valid for their commits
+	fn verify(&self, secp: &Secp256k1, commits: &[Commitment], proofs: &[RangeProof]) -> bool;
+}
+
+/// Verifies every proof separately
+pub struct SingleProofVerifier;
+
+impl ProofVerifier for SingleProofVerifier {
+	fn name(&self) -> &str {
+		"single"
+	}
+
+	fn verify(&self, secp: &Secp256k1, commits: &[Commitment], proofs: &[RangeProof]) -> bool {
+		commits.len() == proofs.len()
+			&& commits
+				.iter()
+				.zip(proofs)
+				.all(|(commit, proof)| secp.verify_bullet_proof(*commit, *proof, None).is_ok())
+	}
+}
+
+/// Verifies all proofs with one call
+pub struct BatchProofVerifier;
+
+impl ProofVerifier for BatchProofVerifier {
+	fn name(&self) -> &str {
+		"batch"
+	}
+
+	fn verify(&self, secp: &Secp256k1, commits: &[Commitment], proofs: &[RangeProof]) -> bool {
+		if commits.len() != proofs.len() {
+			return false;
+		}
+		if commits.is_empty() {
+			return true;
+		}
+		secp.verify_bullet_proof_multi(commits.to_vec(), proofs.to_vec(), None)
+			.is_ok()
+	}
+}
+
+lazy_static! {
+	/// Backend for the range proof verification
+	static ref PROOF_VERIFIER: RwLock<Arc<dyn ProofVerifier>> =
+		RwLock::new(Arc::new(BatchProofVerifier));
+}
+
+/// Set the backend for the range proof verification
+pub fn set_proof_verifier(verifier: Arc<dyn ProofVerifier>) {
+	*PROOF_VERIFIER.write().unwrap() = verifier;
+}
+
+/// Backend for the range proof verification
+pub fn get_proof_verifier() -> Arc<dyn ProofVerifier> {
+	PROOF_VERIFIER.read().unwrap().clone()
+}
+
+/// Indexes of the outputs with invalid range proofs. All proofs are verified together, only
+/// if the check fails they are checked one by one to find the invalid ones.
+pub fn invalid_proofs(secp: &Secp256k1, outputs: &[(Commitment, RangeProof)]) -> Vec<usize> {
+	let verifier = get_proof_verifier();
+	let (commits, proofs): (Vec<Commitment>, Vec<RangeProof>) = outputs.iter().cloned().unzip();
+	if verifier.verify(secp, &commits, &proofs) {
+		return vec![];
+	}
+	(0..outputs.len())
+		.filter(|&i| !verifier.verify(secp, &commits[i..i + 1], &proofs[i..i + 1]))
+		.collect()
+}
+
+/// Fails if any of the outputs has invalid range proof
+pub fn verify_proofs(secp: &Secp256k1, outputs: &[(Commitment, RangeProof)]) -> Result<(), Error> {
+	match invalid_proofs(secp, outputs).first() {
+		Some(&i) => Err(ErrorKind::InvalidRangeProof(to_hex(&outputs[i].0 .0)).into()),
+		None => Ok(()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::grin_core::libtx::proof::{self, ProofBuilder};
+	use crate::grin_keychain::{ExtKeychain, Keychain, SwitchCommitmentType};
+	use crate::grin_util::secp::ContextFlag;
+
+	fn outputs(n: u64) -> Vec<(Commitment, RangeProof)> {
+		let keychain = ExtKeychain::from_random_seed(true).unwrap();
+		let builder = ProofBuilder::new(&keychain);
+		(0..n)
+			.map(|i| {
+				let key_id = ExtKeychain::derive_key_id(1, i as u32, 0, 0, 0);
+				let commit = keychain
+					.commit(i + 1, &key_id, SwitchCommitmentType::Regular)
+					.unwrap();
+				let proof = proof::create(
+					&keychain,
+					&builder,
+					i + 1,
+					&key_id,
+					SwitchCommitmentType::Regular,
+					commit,
+					None,
+				)
+				.unwrap();
+				(commit, proof)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn verify_range_proofs() {
+		let secp = Secp256k1::with_caps(ContextFlag::Commit);
+		let mut outputs = outputs(4);
+		assert!(invalid_proofs(&secp, &outputs).is_empty());
+		assert!(verify_proofs(&secp, &[]).is_ok());
+
+		let (commits, proofs): (Vec<Commitment>, Vec<RangeProof>) = outputs.iter().cloned().unzip();
+		assert!(SingleProofVerifier.verify(&secp, &commits, &proofs));
+		assert!(BatchProofVerifier.verify(&secp, &commits, &proofs));
+		assert!(!BatchProofVerifier.verify(&secp, &commits[1..], &proofs));
+
+		// Proof of another commit
+		outputs[2].1 = outputs[1].1;
+		assert_eq!(invalid_proofs(&secp, &outputs), vec![2]);
+		match verify_proofs(&secp, &outputs).unwrap_err().kind() {
+			ErrorKind::InvalidRangeProof(commit) => {
+				assert_eq!(commit, to_hex(&outputs[2].0 .0))
+			}
+			e => panic!("Unexpected error {:?}", e),
+		}
+		let (commits, proofs): (Vec<Commitment>, Vec<RangeProof>) = outputs.iter().cloned().unzip();
+		assert!(!SingleProofVerifier.verify(&secp, &commits, &proofs));
+		assert!(!BatchProofVerifier.verify(&secp, &commits, &proofs));
+	}
+}
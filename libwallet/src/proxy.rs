@@ -0,0 +1,184 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Outbound proxy settings. When the proxy is set, HTTP senders, node client and
+//! Electrum connections are routed through it. Tor connections keep using the Tor socks port.
+
+use crate::error::{Error, ErrorKind};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::RwLock;
+use std::time::Duration;
+
+const SOCKS5_SCHEME: &str = "socks5://";
+const SOCKS5H_SCHEME: &str = "socks5h://";
+
+lazy_static! {
+	/// SOCKS5 proxy for the outbound connections
+	static ref PROXY_ADDR: RwLock<Option<SocketAddr>> = RwLock::new(None);
+}
+
+/// Parse the proxy address. Accepted formats: 'socks5://host:port', 'socks5h://host:port'
+/// or 'host:port'. Destination host names are always resolved by the proxy.
+pub fn parse_proxy_addr(proxy_addr: &str) -> Result<SocketAddr, Error> {
+	let proxy_addr = proxy_addr.trim();
+	let host_port = if proxy_addr.starts_with(SOCKS5_SCHEME) {
+		&proxy_addr[SOCKS5_SCHEME.len()..]
+	} else if proxy_addr.starts_with(SOCKS5H_SCHEME) {
+		&proxy_addr[SOCKS5H_SCHEME.len()..]
+	} else if proxy_addr.contains("://") {
+		return Err(ErrorKind::GenericError(format!(
+			"Unsupported proxy {}, only SOCKS5 proxy is supported",
+			proxy_addr
+		))
+		.into());
+	} else {
+		proxy_addr
+	};
+	let host_port = host_port.trim_end_matches('/');
+
+	host_port
+		.to_socket_addrs()
+		.map_err(|e| {
+			ErrorKind::GenericError(format!("Unable to resolve proxy {}, {}", proxy_addr, e))
+		})?
+		.next()
+		.ok_or(ErrorKind::GenericError(format!("Unable to resolve proxy {}", proxy_addr)).into())
+}
+
+/// Set from config the proxy for outbound connections. None disables the proxy
+pub fn set_proxy_addr(proxy_addr: &Option<String>) -> Result<(), Error> {
+	let addr = match proxy_addr {
+		Some(a) if !a.trim().is_empty() => Some(parse_proxy_addr(a)?),
+		_ => None,
+	};
+	*PROXY_ADDR.write().unwrap() = addr;
+	Ok(())
+}
+
+/// Read the proxy for outbound connections
+pub fn get_proxy_addr() -> Option<SocketAddr> {
+	PROXY_ADDR.read().unwrap().clone()
+}
+
+/// Open TCP connection to the 'host:port' address through the SOCKS5 proxy
+pub fn connect_via_proxy(
+	proxy_addr: &SocketAddr,
+	address: &str,
+	timeout: Duration,
+) -> Result<TcpStream, Error> {
+	let (host, port) = split_host_port(address)?;
+	if host.len() > 255 {
+		return Err(ErrorKind::GenericError(format!("Host name {} is too long", host)).into());
+	}
+
+	let mut stream = TcpStream::connect_timeout(proxy_addr, timeout)?;
+	stream.set_read_timeout(Some(timeout))?;
+	stream.set_write_timeout(Some(timeout))?;
+
+	// Greeting, no authentication
+	stream.write_all(&[0x05, 0x01, 0x00])?;
+	let mut resp = [0u8; 2];
+	stream.read_exact(&mut resp)?;
+	if resp != [0x05, 0x00] {
+		return Err(ErrorKind::GenericError(format!(
+			"Proxy {} rejected the connection without authentication",
+			proxy_addr
+		))
+		.into());
+	}
+
+	// Connect with the domain name, so the proxy resolves it
+	let mut req = vec![0x05, 0x01, 0x00, 0x03, host.len() as u8];
+	req.extend_from_slice(host.as_bytes());
+	req.extend_from_slice(&port.to_be_bytes());
+	stream.write_all(&req)?;
+
+	let mut resp = [0u8; 4];
+	stream.read_exact(&mut resp)?;
+	if resp[0] != 0x05 || resp[1] != 0x00 {
+		return Err(ErrorKind::GenericError(format!(
+			"Proxy {} is unable to connect to {}, reply code {}",
+			proxy_addr, address, resp[1]
+		))
+		.into());
+	}
+	// Skip the bound address
+	let addr_len = match resp[3] {
+		0x01 => 4,
+		0x04 => 16,
+		0x03 => {
+			let mut len = [0u8; 1];
+			stream.read_exact(&mut len)?;
+			len[0] as usize
+		}
+		t => {
+			return Err(ErrorKind::GenericError(format!(
+				"Proxy {} responded with unknown address type {}",
+				proxy_addr, t
+			))
+			.into())
+		}
+	};
+	let mut bound = vec![0u8; addr_len + 2];
+	stream.read_exact(&mut bound)?;
+
+	Ok(stream)
+}
+
+fn split_host_port(address: &str) -> Result<(String, u16), Error> {
+	let idx = address.rfind(':').ok_or(ErrorKind::GenericError(format!(
+		"Port is missing at {}",
+		address
+	)))?;
+	let port = address[idx + 1..]
+		.parse::<u16>()
+		.map_err(|e| ErrorKind::GenericError(format!("Invalid port at {}, {}", address, e)))?;
+	let host = address[..idx].trim_start_matches('[').trim_end_matches(']');
+	Ok((host.to_string(), port))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_parse_proxy_addr() {
+		let expected: SocketAddr = "127.0.0.1:1080".parse().unwrap();
+		assert_eq!(parse_proxy_addr("127.0.0.1:1080").unwrap(), expected);
+		assert_eq!(
+			parse_proxy_addr("socks5://127.0.0.1:1080").unwrap(),
+			expected
+		);
+		assert_eq!(
+			parse_proxy_addr("socks5h://127.0.0.1:1080/").unwrap(),
+			expected
+		);
+		assert!(parse_proxy_addr("http://127.0.0.1:8080").is_err());
+		assert!(parse_proxy_addr("127.0.0.1").is_err());
+	}
+
+	#[test]
+	fn test_split_host_port() {
+		assert_eq!(
+			split_host_port("btc.main1.swap.mwc.mw:18337").unwrap(),
+			("btc.main1.swap.mwc.mw".to_string(), 18337)
+		);
+		assert_eq!(
+			split_host_port("[::1]:50001").unwrap(),
+			("::1".to_string(), 50001)
+		);
+		assert!(split_host_port("btc.main1.swap.mwc.mw").is_err());
+	}
+}
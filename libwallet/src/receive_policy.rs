@@ -0,0 +1,206 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Policy for the slates received by the foreign API: amount limits, the daily cap and the
+//! allowlist of the senders. The senders are identified by the payment proof addresses.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::core::amount_to_hr_string;
+use crate::grin_keychain::Keychain;
+use crate::log_privacy;
+use crate::proof::proofaddress::ProvableAddress;
+use crate::{NodeClient, Slate, TxLogEntry, TxLogEntryType, WalletBackend};
+use chrono::{Duration, Utc};
+use std::sync::RwLock;
+
+/// Limits for the received transactions. None - no limit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReceivePolicy {
+	/// Min amount of the transaction, nanoMWC
+	pub min_amount: Option<u64>,
+	/// Max amount of the transaction, nanoMWC
+	pub max_amount: Option<u64>,
+	/// Max amount received in the last 24 hours, nanoMWC
+	pub daily_cap: Option<u64>,
+	/// Payment proof addresses of the senders that can send to the wallet
+	pub allowed_senders: Option<Vec<String>>,
+}
+
+lazy_static! {
+	/// Policy for the received transactions
+	static ref RECEIVE_POLICY: RwLock<ReceivePolicy> = RwLock::new(ReceivePolicy::default());
+}
+
+/// Set from config the policy for the received transactions
+pub fn set_receive_policy(policy: ReceivePolicy) {
+	*RECEIVE_POLICY.write().unwrap() = policy;
+}
+
+/// Read the policy for the received transactions
+pub fn get_receive_policy() -> ReceivePolicy {
+	RECEIVE_POLICY.read().unwrap().clone()
+}
+
+/// Check the received slate against the policy
+pub fn check_receive_policy<'a, T: ?Sized, C, K>(w: &T, slate: &Slate) -> Result<(), Error>
+where
+	T: WalletBackend<'a, C, K>,
+	C: NodeClient + 'a,
+	K: Keychain + 'a,
+{
+	let policy = get_receive_policy();
+	check_slate(&policy, slate)?;
+	if let Some(daily_cap) = policy.daily_cap {
+		let received = received_last_day(w.tx_log_iter());
+		if received.saturating_add(slate.amount) > daily_cap {
+			return Err(ErrorKind::ReceivePolicy(format!(
+				"the daily cap {} MWC is reached, {} MWC is received in the last 24 hours",
+				amount_to_hr_string(daily_cap, true),
+				amount_to_hr_string(received, true)
+			))
+			.into());
+		}
+	}
+	Ok(())
+}
+
+// Amount and sender checks that don't need the wallet data
+fn check_slate(policy: &ReceivePolicy, slate: &Slate) -> Result<(), Error> {
+	if let Some(min_amount) = policy.min_amount {
+		if slate.amount < min_amount {
+			return Err(ErrorKind::ReceivePolicy(format!(
+				"amount {} MWC is less than the minimum {} MWC",
+				amount_to_hr_string(slate.amount, true),
+				amount_to_hr_string(min_amount, true)
+			))
+			.into());
+		}
+	}
+	if let Some(max_amount) = policy.max_amount {
+		if slate.amount > max_amount {
+			return Err(ErrorKind::ReceivePolicy(format!(
+				"amount {} MWC is more than the maximum {} MWC",
+				amount_to_hr_string(slate.amount, true),
+				amount_to_hr_string(max_amount, true)
+			))
+			.into());
+		}
+	}
+	if let Some(allowed_senders) = &policy.allowed_senders {
+		let sender = slate
+			.payment_proof
+			.as_ref()
+			.map(|p| p.sender_address.public_key.clone())
+			.ok_or_else(|| {
+				ErrorKind::ReceivePolicy(
+					"the wallet accepts only the transactions with the payment proof".to_string(),
+				)
+			})?;
+		let allowed = allowed_senders
+			.iter()
+			.any(|a| match ProvableAddress::from_str(a) {
+				Ok(addr) => addr.public_key == sender,
+				Err(e) => {
+					warn!(
+						"Invalid address {} in the receive allowlist, {}",
+						log_privacy::address(a),
+						e
+					);
+					false
+				}
+			});
+		if !allowed {
+			return Err(ErrorKind::ReceivePolicy(format!(
+				"sender {} is not in the allowlist",
+				sender
+			))
+			.into());
+		}
+	}
+	Ok(())
+}
+
+// Amount received in the last 24 hours, the cancelled transactions are not counted
+fn received_last_day<I: Iterator<Item = TxLogEntry>>(txs: I) -> u64 {
+	let since = Utc::now() - Duration::hours(24);
+	txs.filter(|t| t.tx_type == TxLogEntryType::TxReceived && t.creation_ts > since)
+		.fold(0u64, |sum, t| sum.saturating_add(t.amount_credited))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::grin_keychain::Identifier;
+	use crate::slate::PaymentInfo;
+
+	fn received(amount: u64, hours_ago: i64) -> TxLogEntry {
+		let mut t = TxLogEntry::new(Identifier::zero(), TxLogEntryType::TxReceived, 0);
+		t.amount_credited = amount;
+		t.creation_ts = Utc::now() - Duration::hours(hours_ago);
+		t
+	}
+
+	#[test]
+	fn test_receive_policy_amounts() {
+		let mut slate = Slate::blank(1, false);
+		slate.amount = 5_000_000_000;
+		let policy = ReceivePolicy {
+			min_amount: Some(1_000_000_000),
+			max_amount: Some(10_000_000_000),
+			..Default::default()
+		};
+		assert!(check_slate(&policy, &slate).is_ok());
+		slate.amount = 100;
+		assert!(check_slate(&policy, &slate).is_err());
+		slate.amount = 20_000_000_000;
+		assert!(check_slate(&policy, &slate).is_err());
+	}
+
+	#[test]
+	fn test_receive_policy_senders() {
+		let secret = ed25519_dalek::SecretKey::from_bytes(&[1; 32]).unwrap();
+		let sender = ProvableAddress::from_tor_pub_key(&ed25519_dalek::PublicKey::from(&secret));
+		let mut slate = Slate::blank(1, false);
+		let policy = ReceivePolicy {
+			allowed_senders: Some(vec![sender.public_key.clone()]),
+			..Default::default()
+		};
+		// No payment proof
+		assert!(check_slate(&policy, &slate).is_err());
+		slate.payment_proof = Some(PaymentInfo {
+			sender_address: sender.clone(),
+			receiver_address: sender.clone(),
+			receiver_signature: None,
+		});
+		assert!(check_slate(&policy, &slate).is_ok());
+		let policy = ReceivePolicy {
+			allowed_senders: Some(vec![]),
+			..Default::default()
+		};
+		assert!(check_slate(&policy, &slate).is_err());
+	}
+
+	#[test]
+	fn test_receive_policy_daily() {
+		let mut cancelled = received(7, 1);
+		cancelled.tx_type = TxLogEntryType::TxReceivedCancelled;
+		let txs = vec![
+			received(10, 1),
+			received(20, 23),
+			received(40, 25),
+			cancelled,
+		];
+		assert_eq!(received_last_day(txs.into_iter()), 30);
+	}
+}
@@ -0,0 +1,282 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encryption of the JSON-RPC calls. The key is an ECDH shared secret on the secp256k1 curve,
+//! the body is encrypted with AES-256-GCM. The Owner API V3 and the encrypted Foreign API use
+//! it, the wallet that sends the slates uses it as a client of the Foreign API.
+
+use crate::address;
+use crate::grin_util::secp::key::{PublicKey, SecretKey};
+use crate::grin_util::static_secp_instance;
+use crate::grin_util::{from_hex, to_hex};
+use crate::{Error, ErrorKind};
+use ed25519_dalek::Signature as DalekSignature;
+use ed25519_dalek::{Keypair as DalekKeypair, Signer, Verifier};
+use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey};
+use rand::{thread_rng, Rng};
+use ring::aead;
+use serde_json::{self, Value};
+
+/// Shared key of the secure API: x coordinate of the ECDH point
+pub fn ecdh_shared_key(other_pubkey: &PublicKey, secret: &SecretKey) -> Result<SecretKey, Error> {
+	let secp_inst = static_secp_instance();
+	let secp = secp_inst.lock();
+	let mut shared_pubkey = *other_pubkey;
+	shared_pubkey.mul_assign(&secp, secret)?;
+	let x_coord = shared_pubkey.serialize_vec(true);
+	Ok(SecretKey::from_slice(&x_coord[1..])?)
+}
+
+/// ECDH key of the Foreign API listener, signed with the key of the wallet Tor address. The
+/// sender checks it against the address it sends to, so nobody in the middle can replace it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ListenerKey {
+	/// ECDH public key of the listener, hex
+	pub ecdh_pubkey: String,
+	/// Tor address of the listener wallet
+	pub address: String,
+	/// Signature of the ECDH key by the address key, hex
+	pub signature: String,
+}
+
+fn listener_key_message(ecdh_pubkey: &str) -> String {
+	format!("mwc-foreign-api|{}", ecdh_pubkey)
+}
+
+impl ListenerKey {
+	/// Sign the ECDH public key with the Tor address secret of the wallet
+	pub fn sign(ecdh_pubkey: &PublicKey, secret: &DalekSecretKey) -> Result<ListenerKey, Error> {
+		let ecdh_pubkey = to_hex(ecdh_pubkey.serialize_vec(true).to_vec());
+		let public: DalekPublicKey = secret.into();
+		let secret = DalekSecretKey::from_bytes(secret.as_bytes())
+			.map_err(|e| ErrorKind::APIEncryption(format!("Invalid address secret, {}", e)))?;
+		let keypair = DalekKeypair { public, secret };
+		let signature = keypair.sign(listener_key_message(&ecdh_pubkey).as_bytes());
+		Ok(ListenerKey {
+			ecdh_pubkey,
+			address: address::onion_v3_from_pubkey(&public)?,
+			signature: to_hex(signature.to_bytes().to_vec()),
+		})
+	}
+
+	/// Check the signature and the address, None accepts any address. Returns the ECDH key.
+	pub fn verify(&self, expected_address: Option<&DalekPublicKey>) -> Result<PublicKey, Error> {
+		let invalid = |e: String| -> Error {
+			ErrorKind::APIEncryption(format!("Invalid listener key, {}", e)).into()
+		};
+		let public =
+			address::pubkey_from_onion_v3(&self.address).map_err(|e| invalid(e.to_string()))?;
+		if let Some(expected) = expected_address {
+			if public != *expected {
+				return Err(invalid(format!(
+					"it is signed by {}, expected {}",
+					self.address,
+					address::onion_v3_from_pubkey(expected)?
+				)));
+			}
+		}
+		let signature = from_hex(&self.signature).map_err(|e| invalid(e.to_string()))?;
+		let signature =
+			DalekSignature::from_bytes(&signature).map_err(|e| invalid(e.to_string()))?;
+		public
+			.verify(
+				listener_key_message(&self.ecdh_pubkey).as_bytes(),
+				&signature,
+			)
+			.map_err(|e| invalid(e.to_string()))?;
+
+		let secp_inst = static_secp_instance();
+		let secp = secp_inst.lock();
+		let ecdh_pubkey = from_hex(&self.ecdh_pubkey).map_err(|e| invalid(e.to_string()))?;
+		PublicKey::from_slice(&secp, &ecdh_pubkey).map_err(|e| invalid(e.to_string()))
+	}
+}
+
+/// Encrypted JSON body with its nonce
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EncryptedBody {
+	/// nonce used for encryption
+	pub nonce: String,
+	/// Encrypted base64 body request
+	pub body_enc: String,
+}
+
+impl EncryptedBody {
+	/// Encrypts and encodes json as base 64
+	pub fn from_json(json_in: &Value, enc_key: &SecretKey) -> Result<Self, Error> {
+		let mut to_encrypt = serde_json::to_string(&json_in)
+			.map_err(|e| {
+				ErrorKind::APIEncryption(format!("EncryptedBody Enc: Unable to encode JSON, {}", e))
+			})?
+			.as_bytes()
+			.to_vec();
+
+		let nonce: [u8; 12] = thread_rng().gen();
+
+		let unbound_key = aead::UnboundKey::new(&aead::AES_256_GCM, &enc_key.0).unwrap();
+		let sealing_key: aead::LessSafeKey = aead::LessSafeKey::new(unbound_key);
+		let aad = aead::Aad::from(&[]);
+		let res = sealing_key.seal_in_place_append_tag(
+			aead::Nonce::assume_unique_for_key(nonce),
+			aad,
+			&mut to_encrypt,
+		);
+		if let Err(e) = res {
+			return Err(ErrorKind::APIEncryption(format!(
+				"EncryptedBody: encryption failed, {}",
+				e
+			))
+			.into());
+		}
+
+		Ok(EncryptedBody {
+			nonce: to_hex(&nonce),
+			body_enc: base64::encode(&to_encrypt),
+		})
+	}
+
+	/// return serialize JSON self
+	pub fn as_json_value(&self) -> Result<Value, Error> {
+		let res = serde_json::to_value(self).map_err(|e| {
+			ErrorKind::APIEncryption(format!("EncryptedBody: JSON serialization failed, {}", e))
+		})?;
+		Ok(res)
+	}
+
+	/// return serialized JSON self as string
+	pub fn as_json_str(&self) -> Result<String, Error> {
+		let res = self.as_json_value()?;
+		let res = serde_json::to_string(&res).map_err(|e| {
+			ErrorKind::APIEncryption(format!(
+				"EncryptedBody: JSON String serialization failed, {}",
+				e
+			))
+		})?;
+		Ok(res)
+	}
+
+	/// Return original request
+	pub fn decrypt(&self, dec_key: &SecretKey) -> Result<Value, Error> {
+		let mut to_decrypt = base64::decode(&self.body_enc).map_err(|e| {
+			ErrorKind::APIEncryption(format!(
+				"EncryptedBody Dec: Encrypted request contains invalid Base64, {}",
+				e
+			))
+		})?;
+
+		let nonce = from_hex(&self.nonce).map_err(|e| {
+			ErrorKind::APIEncryption(format!(
+				"EncryptedBody Dec: Encrypted request contains invalid nonce, {}",
+				e
+			))
+		})?;
+
+		if nonce.len() < 12 {
+			return Err(ErrorKind::APIEncryption(
+				"EncryptedBody Dec: Invalid Nonce length".to_string(),
+			)
+			.into());
+		}
+		let mut n = [0u8; 12];
+		n.copy_from_slice(&nonce[0..12]);
+		let unbound_key = aead::UnboundKey::new(&aead::AES_256_GCM, &dec_key.0).unwrap();
+		let opening_key: aead::LessSafeKey = aead::LessSafeKey::new(unbound_key);
+		let aad = aead::Aad::from(&[]);
+		opening_key
+			.open_in_place(aead::Nonce::assume_unique_for_key(n), aad, &mut to_decrypt)
+			.map_err(|e| {
+				ErrorKind::APIEncryption(format!("EncryptedBody: decryption failed, {}", e))
+			})?;
+
+		for _ in 0..aead::AES_256_GCM.tag_len() {
+			to_decrypt.pop();
+		}
+
+		let decrypted = String::from_utf8(to_decrypt).map_err(|_| {
+			ErrorKind::APIEncryption("EncryptedBody Dec: Invalid UTF-8".to_string())
+		})?;
+
+		Ok(serde_json::from_str(&decrypted).map_err(|e| {
+			ErrorKind::APIEncryption(format!("EncryptedBody Dec: Invalid JSON, {}", e))
+		})?)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn test_ecdh_shared_key() {
+		let secp_inst = static_secp_instance();
+		let (client_secret, client_pub, server_secret, server_pub) = {
+			let secp = secp_inst.lock();
+			let client_secret = SecretKey::new(&mut thread_rng());
+			let server_secret = SecretKey::new(&mut thread_rng());
+			let client_pub = PublicKey::from_secret_key(&secp, &client_secret).unwrap();
+			let server_pub = PublicKey::from_secret_key(&secp, &server_secret).unwrap();
+			(client_secret, client_pub, server_secret, server_pub)
+		};
+
+		let client_key = ecdh_shared_key(&server_pub, &client_secret).unwrap();
+		let server_key = ecdh_shared_key(&client_pub, &server_secret).unwrap();
+		assert_eq!(client_key, server_key);
+
+		let req = json!({ "method": "receive_tx", "params": [ "slate" ] });
+		let enc = EncryptedBody::from_json(&req, &client_key).unwrap();
+		assert_eq!(enc.decrypt(&server_key).unwrap(), req);
+	}
+
+	#[test]
+	fn test_listener_key() {
+		let (ecdh_secret, ecdh_pub, other_pub) = {
+			let secp_inst = static_secp_instance();
+			let secp = secp_inst.lock();
+			let ecdh_secret = SecretKey::new(&mut thread_rng());
+			let ecdh_pub = PublicKey::from_secret_key(&secp, &ecdh_secret).unwrap();
+			let other_secret = SecretKey::new(&mut thread_rng());
+			let other_pub = PublicKey::from_secret_key(&secp, &other_secret).unwrap();
+			(ecdh_secret, ecdh_pub, other_pub)
+		};
+		let address_secret = DalekSecretKey::from_bytes(&[7; 32]).unwrap();
+		let address_pub: DalekPublicKey = (&address_secret).into();
+		let other_address: DalekPublicKey = (&DalekSecretKey::from_bytes(&[8; 32]).unwrap()).into();
+
+		let key = ListenerKey::sign(&ecdh_pub, &address_secret).unwrap();
+		assert_eq!(
+			key.address,
+			address::onion_v3_from_pubkey(&address_pub).unwrap()
+		);
+		assert_eq!(key.verify(Some(&address_pub)).unwrap(), ecdh_pub);
+		assert_eq!(key.verify(None).unwrap(), ecdh_pub);
+		assert!(ecdh_shared_key(&key.verify(None).unwrap(), &ecdh_secret).is_ok());
+
+		// Signed by another wallet
+		assert!(key.verify(Some(&other_address)).is_err());
+
+		// Key replaced in the middle
+		let mut replaced = key.clone();
+		replaced.ecdh_pubkey = to_hex(other_pub.serialize_vec(true).to_vec());
+		assert!(replaced.verify(Some(&address_pub)).is_err());
+		assert!(replaced.verify(None).is_err());
+
+		// Key and address replaced
+		let mut replaced =
+			ListenerKey::sign(&other_pub, &DalekSecretKey::from_bytes(&[8; 32]).unwrap()).unwrap();
+		assert!(replaced.verify(Some(&address_pub)).is_err());
+		replaced.address = key.address.clone();
+		assert!(replaced.verify(Some(&address_pub)).is_err());
+	}
+}
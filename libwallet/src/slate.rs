@@ -39,6 +39,8 @@ use std::fmt;
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::slate_versions::grin_compat;
+use crate::slate_versions::grin_v4::SlateGrinV4;
 use crate::slate_versions::v2::SlateV2;
 use crate::slate_versions::v2::SlateV2ParseTTL;
 
@@ -265,6 +267,26 @@ impl Slate {
 
 	/// Recieve a slate, upgrade it to the latest version internally
 	pub fn deserialize_upgrade_plain(slate_json: &str) -> Result<Slate, Error> {
+		if SlateGrinV4::is_grin_v4_json(slate_json) {
+			let slate: SlateGrinV4 = serde_json::from_str(slate_json).map_err(|e| {
+				ErrorKind::SlateDeser(format!(
+					"Json to Grin V4 slate conversion failed for {}, {}",
+					slate_json, e
+				))
+			})?;
+			return Ok(slate.to_slate()?.0);
+		}
+
+		// Grin V2/V3 slates look like MWC slates, they need to be caught before the parsing
+		if let Some(grin_version) = grin_compat::detect_grin_legacy_json(slate_json) {
+			let converted = grin_compat::convert_grin_legacy_json(slate_json, grin_version)?;
+			warn!(
+				"Processing grin V{} slate with MWC wallet, only the shared fields are used",
+				grin_version
+			);
+			return Slate::deserialize_upgrade_plain(&converted);
+		}
+
 		let version = Slate::parse_slate_version(slate_json)?;
 
 		//I don't think we need to do this for coin_type and network_type, the slate containing these two
@@ -0,0 +1,154 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Limits for the slates and messages that are received by the listeners. Message size and
+//! JSON nesting depth are checked before parsing, so hostile payloads can't exhaust the memory.
+
+use crate::error::{Error, ErrorKind};
+use crate::Slate;
+use std::cmp;
+use std::sync::RwLock;
+
+/// Limits for the incoming slates and messages
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlateLimits {
+	/// Max size of the message, bytes
+	pub max_size: usize,
+	/// Max number of the transaction inputs
+	pub max_inputs: usize,
+	/// Max number of the transaction outputs
+	pub max_outputs: usize,
+	/// Max number of the slate participants
+	pub max_participants: usize,
+	/// Max nesting depth of JSON arrays and objects
+	pub max_json_depth: usize,
+}
+
+impl Default for SlateLimits {
+	fn default() -> Self {
+		SlateLimits {
+			max_size: 4 * 1024 * 1024,
+			max_inputs: 5000,
+			max_outputs: 500,
+			max_participants: 10,
+			max_json_depth: 32,
+		}
+	}
+}
+
+lazy_static! {
+	/// Limits for the slates received by the listeners
+	static ref SLATE_LIMITS: RwLock<SlateLimits> = RwLock::new(SlateLimits::default());
+}
+
+/// Set from config the limits for the incoming slates
+pub fn set_slate_limits(limits: SlateLimits) {
+	*SLATE_LIMITS.write().unwrap() = limits;
+}
+
+/// Read the limits for the incoming slates
+pub fn get_slate_limits() -> SlateLimits {
+	SLATE_LIMITS.read().unwrap().clone()
+}
+
+/// Check the size of the message. Can be called before the whole message is read.
+pub fn check_message_size(size: usize) -> Result<(), Error> {
+	check_limit("message size", size, get_slate_limits().max_size)
+}
+
+/// Check the size and JSON nesting depth of the message before parsing it
+pub fn check_message_limits(message: &[u8]) -> Result<(), Error> {
+	let limits = get_slate_limits();
+	check_limit("message size", message.len(), limits.max_size)?;
+	check_limit(
+		"JSON nesting depth",
+		json_depth(message),
+		limits.max_json_depth,
+	)
+}
+
+/// Check the number of inputs, outputs and participants of the received slate
+pub fn check_slate_limits(slate: &Slate) -> Result<(), Error> {
+	let limits = get_slate_limits();
+	check_limit("inputs", slate.tx.inputs().len(), limits.max_inputs)?;
+	check_limit("outputs", slate.tx.outputs().len(), limits.max_outputs)?;
+	check_limit(
+		"participants",
+		cmp::max(slate.num_participants, slate.participant_data.len()),
+		limits.max_participants,
+	)
+}
+
+fn check_limit(name: &str, value: usize, limit: usize) -> Result<(), Error> {
+	if value > limit {
+		return Err(ErrorKind::SlateLimitExceeded(name.to_string(), value, limit).into());
+	}
+	Ok(())
+}
+
+// Max nesting depth of JSON arrays and objects. Brackets inside the strings are skipped.
+fn json_depth(json: &[u8]) -> usize {
+	let mut depth = 0usize;
+	let mut max_depth = 0usize;
+	let mut in_string = false;
+	let mut escaped = false;
+	for &b in json {
+		if in_string {
+			if escaped {
+				escaped = false;
+			} else if b == b'\\' {
+				escaped = true;
+			} else if b == b'"' {
+				in_string = false;
+			}
+			continue;
+		}
+		match b {
+			b'"' => in_string = true,
+			b'{' | b'[' => {
+				depth += 1;
+				max_depth = cmp::max(max_depth, depth);
+			}
+			b'}' | b']' => depth = depth.saturating_sub(1),
+			_ => (),
+		}
+	}
+	max_depth
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_json_depth() {
+		assert_eq!(json_depth(b"\"text\""), 0);
+		assert_eq!(json_depth(b"{\"a\":[1,2,{\"b\":3}]}"), 3);
+		assert_eq!(json_depth(b"{\"a\":\"[[[{{{\"}"), 1);
+		assert_eq!(json_depth(b"{\"a\":\"\\\"[[\"}"), 1);
+		assert_eq!(json_depth(b"[[]][[[]]]"), 3);
+	}
+
+	#[test]
+	fn test_check_limit() {
+		assert!(check_limit("inputs", 10, 10).is_ok());
+		match check_limit("inputs", 11, 10) {
+			Err(e) => assert_eq!(
+				e.kind(),
+				ErrorKind::SlateLimitExceeded("inputs".to_string(), 11, 10)
+			),
+			Ok(_) => panic!("limit is not enforced"),
+		}
+	}
+}
@@ -0,0 +1,194 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Detection of the slates and slatepacks produced by grin wallets. MWC slates share the
+//! structure with grin V2/V3 slates, but grin slates don't have the coin and network fields,
+//! so they can pass the deserialization and fail much later with a cryptic error.
+//! Grin V4 compact slates are handled by grin_v4 module, everything else is rejected on
+//! mainnet. At floonet and other test networks the legacy grin slates are converted, only
+//! the fields that are shared between grin and MWC are kept.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::global;
+use serde_json::{json, Map, Value};
+
+/// MWC slates are always built with this block header version. Grin 3.0 and later wallets
+/// write 3 or higher.
+const MWC_SLATE_BLOCK_HEADER_VERSION: u64 = 1;
+
+/// Lowest block header version that is written by grin wallets only
+const GRIN_MIN_BLOCK_HEADER_VERSION: u64 = 3;
+
+/// Check if the plain json slate was produced by grin wallet with V2/V3 format.
+/// Returns the slate version if it was.
+pub fn detect_grin_legacy_json(slate_json: &str) -> Option<u16> {
+	let v: Value = serde_json::from_str(slate_json).ok()?;
+	detect_grin_legacy(&v)
+}
+
+fn detect_grin_legacy(v: &Value) -> Option<u16> {
+	let version_info = v.get("version_info")?;
+	let version = version_info["version"].as_u64()?;
+	if version != 2 && version != 3 {
+		return None;
+	}
+	// MWC specific fields, grin never writes them
+	if !v["coin_type"].is_null() || !v["network_type"].is_null() {
+		return None;
+	}
+	match version_info["block_header_version"].as_u64() {
+		Some(bhv) if bhv >= GRIN_MIN_BLOCK_HEADER_VERSION => Some(version as u16),
+		_ => None,
+	}
+}
+
+/// Check if the decoded slatepack armor payload belongs to the grin slatepack. Grin puts json
+/// into the armor, MWC slatepack is binary and starts from the version byte.
+pub fn is_grin_slatepack_payload(payload: &[u8]) -> bool {
+	if payload.first() != Some(&b'{') {
+		return false;
+	}
+	match serde_json::from_slice::<Value>(payload) {
+		Ok(v) => !v["slatepack"].is_null(),
+		Err(_) => false,
+	}
+}
+
+/// Error for the grin slatepack
+pub fn grin_slatepack_error() -> Error {
+	ErrorKind::GrinSlateIncompatible(
+		"grin slatepacks can't be decoded by MWC wallet. Please ask the grin wallet for a plain V4 slate file"
+			.to_string(),
+	)
+	.into()
+}
+
+/// Convert grin V2/V3 slate into MWC V3 json. Only test networks are allowed to do that,
+/// on mainnet the grin slate is rejected because the coins belong to a different chain.
+pub fn convert_grin_legacy_json(slate_json: &str, version: u16) -> Result<String, Error> {
+	if global::is_mainnet() {
+		return Err(ErrorKind::GrinSlateIncompatible(format!(
+			"found grin V{} slate, it doesn't have MWC coin and network fields and belongs to the grin chain",
+			version
+		))
+		.into());
+	}
+
+	let mut v: Value = serde_json::from_str(slate_json)
+		.map_err(|e| ErrorKind::SlateDeser(format!("Unable to parse grin slate json, {}", e)))?;
+
+	v["version_info"]["block_header_version"] = json!(MWC_SLATE_BLOCK_HEADER_VERSION);
+	v["coin_type"] = json!("mwc");
+	v["network_type"] = json!(global::get_network_name());
+
+	// grin 4.0 wallets write the kernel fee and lock height inside the features
+	if let Some(kernels) = v["tx"]["body"]["kernels"].as_array_mut() {
+		for kernel in kernels.iter_mut() {
+			convert_grin_kernel(kernel, version)?;
+		}
+	}
+
+	Ok(v.to_string())
+}
+
+fn convert_grin_kernel(kernel: &mut Value, version: u16) -> Result<(), Error> {
+	let features = match kernel["features"].as_object() {
+		Some(f) => f.clone(),
+		None => return Ok(()),
+	};
+	let (name, args) = features
+		.iter()
+		.next()
+		.ok_or(ErrorKind::GrinSlateIncompatible(format!(
+			"grin V{} slate has empty kernel features",
+			version
+		)))?;
+	let args = args.as_object().cloned().unwrap_or(Map::new());
+	match name.as_str() {
+		"Plain" | "Coinbase" | "HeightLocked" => (),
+		_ => {
+			return Err(ErrorKind::GrinSlateIncompatible(format!(
+				"grin kernel features {} are not supported by MWC",
+				name
+			))
+			.into())
+		}
+	}
+	let kernel = kernel.as_object_mut().ok_or(ErrorKind::SlateDeser(
+		"Grin kernel is not an object".to_string(),
+	))?;
+	kernel.insert("features".to_string(), json!(name));
+	kernel.insert(
+		"fee".to_string(),
+		args.get("fee").cloned().unwrap_or(json!(0)),
+	);
+	kernel.insert(
+		"lock_height".to_string(),
+		args.get("lock_height").cloned().unwrap_or(json!(0)),
+	);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const GRIN_V3_SLATE: &str = r#"{"version_info":{"version":3,"orig_version":3,"block_header_version":4},"num_participants":2,"id":"0436430c-2b02-624c-2032-570501212b00","tx":{"offset":"d202964900000000d302964900000000d402964900000000d502964900000000","body":{"inputs":[],"outputs":[],"kernels":[{"features":{"HeightLocked":{"fee":"7000000","lock_height":"12"}},"excess":"000000000000000000000000000000000000000000000000000000000000000000","excess_sig":"00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"}]}},"amount":"60000000000","fee":"7000000","height":"5","lock_height":"12","ttl_cutoff_height":null,"participant_data":[],"payment_proof":null}"#;
+
+	#[test]
+	fn grin_legacy_detection() {
+		assert_eq!(detect_grin_legacy_json(GRIN_V3_SLATE), Some(3));
+
+		let mut v: Value = serde_json::from_str(GRIN_V3_SLATE).unwrap();
+		v["version_info"]["block_header_version"] = json!(1);
+		assert_eq!(detect_grin_legacy(&v), None);
+
+		let mut v: Value = serde_json::from_str(GRIN_V3_SLATE).unwrap();
+		v["coin_type"] = json!("mwc");
+		assert_eq!(detect_grin_legacy(&v), None);
+
+		assert_eq!(detect_grin_legacy_json("not a json"), None);
+	}
+
+	#[test]
+	fn grin_slatepack_payload() {
+		assert!(is_grin_slatepack_payload(
+			br#"{"slatepack":[1,0],"mode":0,"payload":"AAA="}"#
+		));
+		assert!(!is_grin_slatepack_payload(&[0u8, 1, 2, 3]));
+		assert!(!is_grin_slatepack_payload(br#"{"id":1}"#));
+	}
+
+	#[test]
+	fn grin_legacy_conversion() {
+		global::set_local_chain_type(global::ChainTypes::Floonet);
+		let converted = convert_grin_legacy_json(GRIN_V3_SLATE, 3).unwrap();
+		let v: Value = serde_json::from_str(&converted).unwrap();
+		assert_eq!(v["version_info"]["block_header_version"], json!(1));
+		assert_eq!(v["coin_type"], json!("mwc"));
+		let kernel = &v["tx"]["body"]["kernels"][0];
+		assert_eq!(kernel["features"], json!("HeightLocked"));
+		assert_eq!(kernel["fee"], json!("7000000"));
+		assert_eq!(kernel["lock_height"], json!("12"));
+
+		global::set_local_chain_type(global::ChainTypes::Mainnet);
+		match convert_grin_legacy_json(GRIN_V3_SLATE, 3) {
+			Err(e) => match e.kind() {
+				ErrorKind::GrinSlateIncompatible(_) => (),
+				k => panic!("unexpected error {}", k),
+			},
+			Ok(_) => panic!("grin slate must be rejected on mainnet"),
+		}
+	}
+}
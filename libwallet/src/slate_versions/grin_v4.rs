@@ -0,0 +1,485 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Grin V4 compact slate. It is used for interop with grin wallets only, MWC wallets exchange
+//! the compact slates as slatepacks. Grin V4 slate doesn't have the transaction height
+//! and participant messages, so those fields are lost at conversion.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::core::transaction::OutputFeatures;
+use crate::grin_core::core::{Input, Inputs, Output, OutputIdentifier};
+use crate::grin_core::libtx::secp_ser;
+use crate::grin_keychain::BlindingFactor;
+use crate::grin_util::secp::key::PublicKey;
+use crate::grin_util::secp::pedersen::{Commitment, RangeProof};
+use crate::grin_util::secp::Signature;
+use crate::grin_util::{from_hex, to_hex};
+use crate::proof::proofaddress::ProvableAddress;
+use crate::slate::{ParticipantData, PaymentInfo, Slate};
+use crate::slate_versions::ser as dalek_ser;
+use crate::slate_versions::GRIN_BLOCK_HEADER_VERSION;
+use crate::slatepack::SlatePurpose;
+use crate::Slatepack;
+use ed25519_dalek::PublicKey as DalekPublicKey;
+use ed25519_dalek::Signature as DalekSignature;
+use uuid::Uuid;
+
+/// Grin slate version that is supported
+pub const GRIN_SLATE_VERSION_V4: u16 = 4;
+
+/// Grin kernel features value for the height locked kernel
+const GRIN_KERNEL_FEAT_HEIGHT_LOCKED: u8 = 2;
+
+/// Grin V4 slate
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SlateGrinV4 {
+	/// Versioning info, "<slate version>:<block header version>"
+	pub ver: String,
+	/// Unique transaction ID, selected by sender
+	pub id: Uuid,
+	/// Slate state
+	pub sta: SlateStateGrinV4,
+	/// Offset, modified by each participant inserting inputs
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub off: Option<String>,
+	/// The number of participants intended to take part in this transaction
+	#[serde(default = "default_num_parts", skip_serializing_if = "num_parts_is_2")]
+	pub num_parts: u8,
+	/// Base amount (excluding fee)
+	#[serde(
+		with = "secp_ser::string_or_u64",
+		default,
+		skip_serializing_if = "u64_is_zero"
+	)]
+	pub amt: u64,
+	/// Fee
+	#[serde(
+		with = "secp_ser::string_or_u64",
+		default,
+		skip_serializing_if = "u64_is_zero"
+	)]
+	pub fee: u64,
+	/// Kernel features, 0 - plain, 2 - height locked
+	#[serde(default, skip_serializing_if = "u8_is_zero")]
+	pub feat: u8,
+	/// TTL, the block height at which wallets should refuse to process the transaction
+	#[serde(
+		with = "secp_ser::string_or_u64",
+		default,
+		skip_serializing_if = "u64_is_zero"
+	)]
+	pub ttl: u64,
+	/// Participant data
+	pub sigs: Vec<ParticipantDataGrinV4>,
+	/// Inputs/Output commits added to slate
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub coms: Option<Vec<CommitsGrinV4>>,
+	/// Payment Proof
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub proof: Option<PaymentInfoGrinV4>,
+	/// Kernel features arguments
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub feat_args: Option<KernelFeaturesArgsGrinV4>,
+}
+
+/// Grin V4 slate state
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SlateStateGrinV4 {
+	/// Unknown
+	#[serde(rename = "NA")]
+	Unknown,
+	/// Standard flow, freshly init
+	#[serde(rename = "S1")]
+	Standard1,
+	/// Standard flow, return journey
+	#[serde(rename = "S2")]
+	Standard2,
+	/// Standard flow, ready for transaction posting
+	#[serde(rename = "S3")]
+	Standard3,
+	/// Invoice flow, freshly init
+	#[serde(rename = "I1")]
+	Invoice1,
+	/// Invoice flow, return journey
+	#[serde(rename = "I2")]
+	Invoice2,
+	/// Invoice flow, ready for transaction posting
+	#[serde(rename = "I3")]
+	Invoice3,
+}
+
+/// Grin V4 participant data. Participant id is defined by the slate state
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ParticipantDataGrinV4 {
+	/// Public key corresponding to private blinding factor
+	#[serde(with = "secp_ser::pubkey_serde")]
+	pub xs: PublicKey,
+	/// Public key corresponding to private nonce
+	#[serde(with = "secp_ser::pubkey_serde")]
+	pub nonce: PublicKey,
+	/// Public partial signature
+	#[serde(
+		with = "secp_ser::option_sig_serde",
+		default,
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub part: Option<Signature>,
+}
+
+/// Grin V4 input or output. Outputs have a range proof, inputs don't
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommitsGrinV4 {
+	/// Output features, 0 - plain, 1 - coinbase
+	#[serde(default, skip_serializing_if = "u8_is_zero")]
+	pub f: u8,
+	/// The homomorphic commitment
+	#[serde(
+		serialize_with = "secp_ser::as_hex",
+		deserialize_with = "secp_ser::commitment_from_hex"
+	)]
+	pub c: Commitment,
+	/// Range proof as a hex string, only for outputs
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub p: Option<String>,
+}
+
+/// Grin V4 payment proof. Only slatepack (tor) addresses are supported by grin
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PaymentInfoGrinV4 {
+	/// Sender address
+	#[serde(with = "dalek_ser::dalek_pubkey_serde")]
+	pub saddr: DalekPublicKey,
+	/// Receiver address
+	#[serde(with = "dalek_ser::dalek_pubkey_serde")]
+	pub raddr: DalekPublicKey,
+	/// Receiver signature
+	#[serde(
+		with = "dalek_ser::option_dalek_sig_serde",
+		default,
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub rsig: Option<DalekSignature>,
+}
+
+/// Grin V4 kernel features arguments
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KernelFeaturesArgsGrinV4 {
+	/// Lock height, for HeightLocked
+	pub lock_hgt: u64,
+}
+
+fn default_num_parts() -> u8 {
+	2
+}
+
+fn num_parts_is_2(n: &u8) -> bool {
+	*n == 2
+}
+
+fn u64_is_zero(n: &u64) -> bool {
+	*n == 0
+}
+
+fn u8_is_zero(n: &u8) -> bool {
+	*n == 0
+}
+
+impl SlateStateGrinV4 {
+	/// Slate state that match the slatepack content
+	pub fn from_purpose(purpose: &SlatePurpose) -> Self {
+		match purpose {
+			SlatePurpose::SendInitial => SlateStateGrinV4::Standard1,
+			SlatePurpose::SendResponse => SlateStateGrinV4::Standard2,
+			SlatePurpose::InvoiceInitial => SlateStateGrinV4::Invoice1,
+			SlatePurpose::InvoiceResponse => SlateStateGrinV4::Invoice2,
+			SlatePurpose::FullSlate => SlateStateGrinV4::Standard3,
+		}
+	}
+
+	/// Slatepack content that match this state
+	pub fn to_purpose(&self) -> SlatePurpose {
+		match self {
+			SlateStateGrinV4::Standard1 => SlatePurpose::SendInitial,
+			SlateStateGrinV4::Standard2 => SlatePurpose::SendResponse,
+			SlateStateGrinV4::Invoice1 => SlatePurpose::InvoiceInitial,
+			SlateStateGrinV4::Invoice2 => SlatePurpose::InvoiceResponse,
+			SlateStateGrinV4::Standard3
+			| SlateStateGrinV4::Invoice3
+			| SlateStateGrinV4::Unknown => SlatePurpose::FullSlate,
+		}
+	}
+}
+
+impl SlateGrinV4 {
+	/// Check if json looks like a grin V4 slate
+	pub fn is_grin_v4_json(slate_json: &str) -> bool {
+		match serde_json::from_str::<serde_json::Value>(slate_json) {
+			Ok(v) => v["ver"]
+				.as_str()
+				.map(|ver| ver.starts_with(&format!("{}:", GRIN_SLATE_VERSION_V4)))
+				.unwrap_or(false),
+			Err(_) => false,
+		}
+	}
+
+	/// Build grin V4 slate from the compact slate. The slate fields that are
+	/// transferred are the same as slatepack transfers for this content
+	pub fn from_slate(slate: &Slate, purpose: &SlatePurpose) -> Result<Self, Error> {
+		if !slate.compact_slate {
+			return Err(ErrorKind::SlateSer(
+				"Grin V4 slate can be built from compact slate only".to_string(),
+			)
+			.into());
+		}
+
+		// amount, fee, offset, coms, participant ids, proof addresses, proof signature
+		let (write_amount, write_fee, write_offset, write_inputs, write_outputs) = match purpose {
+			SlatePurpose::SendInitial => (true, true, false, false, false),
+			SlatePurpose::SendResponse => (false, false, true, false, true),
+			SlatePurpose::InvoiceInitial => (true, false, false, false, false),
+			SlatePurpose::InvoiceResponse => (true, true, true, true, true),
+			SlatePurpose::FullSlate => (true, true, true, true, true),
+		};
+		let (participants, write_proof_addr, write_proof_sig) = match purpose {
+			SlatePurpose::SendInitial => (vec![0], true, false),
+			SlatePurpose::SendResponse => (vec![1], true, true),
+			SlatePurpose::InvoiceInitial => (vec![0], false, false),
+			SlatePurpose::InvoiceResponse => (vec![1], false, false),
+			SlatePurpose::FullSlate => ((0..slate.num_participants).collect(), true, true),
+		};
+
+		let mut sigs = vec![];
+		for id in participants {
+			let pd = slate
+				.participant_with_id(id)
+				.ok_or(ErrorKind::SlateSer(format!(
+					"Not found slate participant data for id {}",
+					id
+				)))?;
+			sigs.push(ParticipantDataGrinV4 {
+				xs: pd.public_blind_excess,
+				nonce: pd.public_nonce,
+				part: pd.part_sig,
+			});
+		}
+
+		let mut coms = vec![];
+		if write_inputs {
+			match &slate.tx.body.inputs {
+				Inputs::CommitOnly(commits) => {
+					for commit in commits {
+						coms.push(CommitsGrinV4 {
+							f: OutputFeatures::Plain as u8,
+							c: commit.commitment(),
+							p: None,
+						});
+					}
+				}
+				Inputs::FeaturesAndCommit(inputs) => {
+					for input in inputs {
+						coms.push(CommitsGrinV4 {
+							f: input.features as u8,
+							c: input.commit,
+							p: None,
+						});
+					}
+				}
+			}
+		}
+		if write_outputs {
+			for output in &slate.tx.body.outputs {
+				coms.push(CommitsGrinV4 {
+					f: output.identifier.features as u8,
+					c: output.identifier.commit,
+					p: Some(to_hex(output.proof.bytes())),
+				});
+			}
+		}
+
+		let proof = match &slate.payment_proof {
+			Some(pp) if write_proof_addr => {
+				let rsig = match &pp.receiver_signature {
+					Some(sig) if write_proof_sig => {
+						let sig = from_hex(sig).map_err(|e| {
+							ErrorKind::SlateSer(format!("Invalid payment proof signature, {}", e))
+						})?;
+						Some(DalekSignature::from_bytes(&sig).map_err(|e| {
+							ErrorKind::SlateSer(format!(
+								"Grin payment proof signature must be ed25519 signature, {}",
+								e
+							))
+						})?)
+					}
+					_ => None,
+				};
+				Some(PaymentInfoGrinV4 {
+					saddr: grin_proof_address(&pp.sender_address)?,
+					raddr: grin_proof_address(&pp.receiver_address)?,
+					rsig,
+				})
+			}
+			_ => None,
+		};
+
+		let (feat, feat_args) = match slate.lock_height {
+			0 => (0, None),
+			lock_hgt => (
+				GRIN_KERNEL_FEAT_HEIGHT_LOCKED,
+				Some(KernelFeaturesArgsGrinV4 { lock_hgt }),
+			),
+		};
+
+		Ok(SlateGrinV4 {
+			ver: format!("{}:{}", GRIN_SLATE_VERSION_V4, GRIN_BLOCK_HEADER_VERSION),
+			id: slate.id,
+			sta: SlateStateGrinV4::from_purpose(purpose),
+			off: if write_offset {
+				Some(to_hex(slate.offset.as_ref().to_vec()))
+			} else {
+				None
+			},
+			num_parts: slate.num_participants as u8,
+			amt: if write_amount { slate.amount } else { 0 },
+			fee: if write_fee { slate.fee } else { 0 },
+			feat,
+			ttl: slate.ttl_cutoff_height.unwrap_or(0),
+			sigs,
+			coms: if coms.is_empty() { None } else { Some(coms) },
+			proof,
+			feat_args,
+		})
+	}
+
+	/// Convert into the internal compact slate. Return the slate and its content.
+	pub fn to_slate(&self) -> Result<(Slate, SlatePurpose), Error> {
+		if !self.ver.starts_with(&format!("{}:", GRIN_SLATE_VERSION_V4)) {
+			return Err(ErrorKind::SlateDeser(format!(
+				"Unsupported grin slate version {}",
+				self.ver
+			))
+			.into());
+		}
+
+		let purpose = self.sta.to_purpose();
+		let mut slate = Slate::blank(self.num_parts as usize, true);
+		slate.id = self.id;
+		slate.amount = self.amt;
+		slate.fee = self.fee;
+		slate.ttl_cutoff_height = match self.ttl {
+			0 => None,
+			ttl => Some(ttl),
+		};
+		slate.lock_height = match self.feat {
+			0 => 0,
+			GRIN_KERNEL_FEAT_HEIGHT_LOCKED => {
+				self.feat_args
+					.as_ref()
+					.ok_or(ErrorKind::SlateDeser(
+						"Height locked kernel without lock height".to_string(),
+					))?
+					.lock_hgt
+			}
+			feat => {
+				return Err(ErrorKind::SlateDeser(format!(
+					"Unsupported grin kernel features {}",
+					feat
+				))
+				.into())
+			}
+		};
+
+		if let Some(off) = &self.off {
+			let off = from_hex(off)
+				.map_err(|e| ErrorKind::SlateDeser(format!("Invalid slate offset, {}", e)))?;
+			slate.offset = BlindingFactor::from_slice(&off);
+		}
+
+		// Responses carry the data of the second participant
+		let first_id = match purpose {
+			SlatePurpose::SendResponse | SlatePurpose::InvoiceResponse => 1,
+			_ => 0,
+		};
+		for (i, sig) in self.sigs.iter().enumerate() {
+			slate.participant_data.push(ParticipantData {
+				id: first_id + i as u64,
+				public_blind_excess: sig.xs.clone(),
+				public_nonce: sig.nonce.clone(),
+				part_sig: sig.part.clone(),
+				message: None,
+				message_sig: None,
+			});
+		}
+
+		let mut inputs: Vec<Input> = vec![];
+		for com in self.coms.iter().flatten() {
+			let features = match com.f {
+				0 => OutputFeatures::Plain,
+				1 => OutputFeatures::Coinbase,
+				f => {
+					return Err(
+						ErrorKind::SlateDeser(format!("Unsupported output features {}", f)).into(),
+					)
+				}
+			};
+			match &com.p {
+				Some(p) => {
+					let proof_data = from_hex(p).map_err(|e| {
+						ErrorKind::SlateDeser(format!("Invalid range proof, {}", e))
+					})?;
+					let mut proof = RangeProof::zero();
+					if proof_data.len() > proof.proof.len() {
+						return Err(
+							ErrorKind::SlateDeser("Range proof is too long".to_string()).into()
+						);
+					}
+					proof.plen = proof_data.len();
+					proof.proof[..proof_data.len()].copy_from_slice(&proof_data);
+					slate.tx.body.outputs.push(Output {
+						identifier: OutputIdentifier {
+							features,
+							commit: com.c,
+						},
+						proof,
+					});
+				}
+				None => inputs.push(Input::new(features, com.c)),
+			}
+		}
+		if !inputs.is_empty() {
+			slate.tx.body.inputs = Inputs::FeaturesAndCommit(inputs);
+		}
+
+		if let Some(proof) = &self.proof {
+			slate.payment_proof = Some(PaymentInfo {
+				sender_address: ProvableAddress::from_tor_pub_key(&proof.saddr),
+				receiver_address: ProvableAddress::from_tor_pub_key(&proof.raddr),
+				receiver_signature: proof.rsig.as_ref().map(|s| to_hex(s.to_bytes().to_vec())),
+			});
+		}
+
+		Slatepack::update_tx_from_slate(&mut slate)?;
+		Ok((slate, purpose))
+	}
+}
+
+// Grin payment proof addresses are ed25519 public keys, MQS addresses can't be used
+fn grin_proof_address(address: &ProvableAddress) -> Result<DalekPublicKey, Error> {
+	address.tor_public_key().map_err(|e| {
+		ErrorKind::SlateSer(format!(
+			"Grin slate support only slatepack payment proof addresses, {}",
+			e
+		))
+		.into()
+	})
+}
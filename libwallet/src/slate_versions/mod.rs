@@ -18,6 +18,7 @@
 //! remains for future needs
 
 use crate::slate::Slate;
+use crate::slate_versions::grin_v4::SlateGrinV4;
 use crate::slate_versions::v2::{CoinbaseV2, SlateV2};
 use crate::slate_versions::v3::{CoinbaseV3, SlateV3};
 use crate::slatepack::SlatePurpose;
@@ -27,6 +28,8 @@ use crate::{Error, ErrorKind};
 use ed25519_dalek::PublicKey as DalekPublicKey;
 use ed25519_dalek::SecretKey as DalekSecretKey;
 
+pub mod grin_compat;
+pub mod grin_v4;
 pub mod ser;
 
 #[allow(missing_docs)]
@@ -40,6 +43,10 @@ pub const CURRENT_SLATE_VERSION: u16 = 3;
 /// The grin block header this slate is intended to be compatible with
 pub const GRIN_BLOCK_HEADER_VERSION: u16 = 3;
 
+/// target_slate_version value that requests grin V4 compact slate. Value 4 is already taken by
+/// the MWC slatepack.
+pub const GRIN_V4_TARGET_SLATE_VERSION: u16 = 104;
+
 /// Existing versions of the slate
 #[derive(EnumIter, Serialize, Deserialize, Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
 pub enum SlateVersion {
@@ -53,6 +60,8 @@ pub enum SlateVersion {
 	V3,
 	/// V2 (2.0.0 - Onwards)
 	V2,
+	/// Grin V4 compact slate, for interop with grin wallets
+	GV4,
 }
 
 impl SlateVersion {
@@ -62,6 +71,7 @@ impl SlateVersion {
 			SlateVersion::SP => 4,
 			SlateVersion::V3 | SlateVersion::V3B => 3,
 			SlateVersion::V2 => 2,
+			SlateVersion::GV4 => GRIN_V4_TARGET_SLATE_VERSION as i32,
 		}
 	}
 }
@@ -73,6 +83,8 @@ impl SlateVersion {
 pub enum VersionedSlate {
 	/// Slatepack
 	SP(String),
+	/// Grin V4 compact slate
+	GV4(SlateGrinV4),
 	// V3B is not needed because it is a V3 slate with some optional fields, so it is compatible
 	/// Current (3.0.0 Onwards )
 	V3(SlateV3),
@@ -85,6 +97,7 @@ impl VersionedSlate {
 	pub fn version(&self) -> SlateVersion {
 		match *self {
 			VersionedSlate::SP(_) => SlateVersion::SP,
+			VersionedSlate::GV4(_) => SlateVersion::GV4,
 			VersionedSlate::V3(_) => SlateVersion::V3,
 			VersionedSlate::V2(_) => SlateVersion::V2,
 		}
@@ -121,6 +134,9 @@ impl VersionedSlate {
 				)?;
 				Ok(VersionedSlate::SP(armored_slatepack))
 			}
+			SlateVersion::GV4 => Ok(VersionedSlate::GV4(SlateGrinV4::from_slate(
+				&slate, &content,
+			)?)),
 			_ => Ok(Self::into_version_plain(slate.clone(), version)?),
 		}
 	}
@@ -134,6 +150,12 @@ impl VersionedSlate {
 			SlateVersion::SP => {
 				return Err(ErrorKind::GenericError("Slate is encrypted".to_string()).into())
 			}
+			SlateVersion::GV4 => {
+				return Err(ErrorKind::GenericError(
+					"Grin V4 slate requires the slate content".to_string(),
+				)
+				.into())
+			}
 			SlateVersion::V3B | SlateVersion::V3 => Ok(VersionedSlate::V3(slate.into())),
 			// Left here as a reminder of what needs to be inserted on
 			// the release of a new slate
@@ -152,6 +174,15 @@ impl VersionedSlate {
 				let packer = Slatepacker::decrypt_slatepack(arm_slatepack.as_bytes(), dec_key)?;
 				Ok(packer)
 			}
+			VersionedSlate::GV4(s) => {
+				let (slate, content) = s.to_slate()?;
+				Ok(Slatepacker {
+					sender: None,
+					recipient: None,
+					content,
+					slate,
+				})
+			}
 			VersionedSlate::V3(s) => Ok(Slatepacker::wrap_slate(s.clone().to_slate()?)),
 			VersionedSlate::V2(s) => {
 				let s = SlateV3::from(s.clone());
@@ -166,6 +197,7 @@ impl VersionedSlate {
 			VersionedSlate::SP(_) => {
 				return Err(ErrorKind::GenericError("Slate is encrypted".to_string()).into())
 			}
+			VersionedSlate::GV4(s) => Ok(s.to_slate()?.0),
 			VersionedSlate::V3(s) => Ok(s.clone().to_slate()?),
 			VersionedSlate::V2(s) => {
 				let s = SlateV3::from(s.clone());
@@ -178,6 +210,9 @@ impl VersionedSlate {
 	pub fn as_string(&self) -> Result<String, Error> {
 		let str = match self {
 			VersionedSlate::SP(s) => s.clone(),
+			VersionedSlate::GV4(s) => serde_json::to_string(&s).map_err(|e| {
+				ErrorKind::GenericError(format!("Failed convert Grin V4 slate to Json, {}", e))
+			})?,
 			VersionedSlate::V3(s) => serde_json::to_string(&s).map_err(|e| {
 				ErrorKind::GenericError(format!("Failed convert SlateV3 to Json, {}", e))
 			})?,
@@ -204,7 +239,7 @@ impl VersionedCoinbase {
 	/// convert this coinbase data to a specific versioned representation for the json api.
 	pub fn into_version(cb: CbData, version: SlateVersion) -> VersionedCoinbase {
 		match version {
-			SlateVersion::SP | SlateVersion::V3B | SlateVersion::V3 => {
+			SlateVersion::SP | SlateVersion::V3B | SlateVersion::V3 | SlateVersion::GV4 => {
 				VersionedCoinbase::V3(cb.into())
 			}
 			SlateVersion::V2 => VersionedCoinbase::V2(cb.into()),
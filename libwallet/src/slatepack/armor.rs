@@ -120,7 +120,7 @@ impl SlatepackArmor {
 }
 
 // Takes an error check code and a slate binary and verifies that the code was generated from slate
-fn error_check(error_code: &[u8], slate_bytes: &[u8]) -> Result<(), Error> {
+pub(crate) fn error_check(error_code: &[u8], slate_bytes: &[u8]) -> Result<(), Error> {
 	let new_check = generate_check(slate_bytes)?;
 	if error_code.iter().eq(new_check.iter()) {
 		Ok(())
@@ -159,7 +159,7 @@ fn check_footer(footer: &[u8]) -> Result<bool, Error> {
 }
 
 // MODIFIED Base58Check encoding for slate bytes
-fn base58check(slate: &[u8]) -> Result<String, Error> {
+pub(crate) fn base58check(slate: &[u8]) -> Result<String, Error> {
 	// Serialize the slate json string to a vector of bytes
 	let mut slate_bytes: Vec<u8> = slate.to_vec();
 	// Get the four byte checksum for the slate binary
@@ -174,7 +174,7 @@ fn base58check(slate: &[u8]) -> Result<String, Error> {
 }
 
 // Adds human readable formatting to the slate payload for armoring
-fn format_slatepack(slatepack: &str) -> Result<String, Error> {
+pub(crate) fn format_slatepack(slatepack: &str) -> Result<String, Error> {
 	let formatter = slatepack
 		.chars()
 		.enumerate()
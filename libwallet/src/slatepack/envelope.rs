@@ -0,0 +1,184 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encrypted envelope for the plain (V2/V3 JSON) slates. Slatepacks can carry only the
+//! compact slates, the envelope keeps the JSON slate as it is and encrypts it for the
+//! recipient address with the same key exchange as the slatepack. The armor has its own
+//! header, so the reader detects the envelope by the content.
+//!
+//! Binary layout before the armor: version (1 byte), sender (32 bytes), recipient (32 bytes),
+//! nonce (12 bytes), encrypted JSON with the tag.
+
+use super::armor::{base58check, error_check, format_slatepack};
+use super::slatepack::Slatepack;
+use crate::{Error, ErrorKind};
+use ed25519_dalek::{PublicKey as DalekPublicKey, SecretKey as DalekSecretKey, PUBLIC_KEY_LENGTH};
+
+static HEADER_ENV: &str = "BEGINSLATE_ENV.";
+static FOOTER_ENV: &str = ". ENDSLATE_ENV.";
+static FOOTER_NAME: &str = "ENDSLATE_ENV";
+const ENVELOPE_VERSION: u8 = 1;
+const NONCE_LENGTH: usize = 12;
+const ARMOR_SPACES: [char; 5] = ['>', '\n', '\r', '\t', ' '];
+
+/// Wrapper for associated functions
+pub struct SlateEnvelope;
+
+impl SlateEnvelope {
+	/// Check if the content is an envelope
+	pub fn is_envelope(content: &str) -> bool {
+		content
+			.trim_start_matches(&ARMOR_SPACES[..])
+			.starts_with(HEADER_ENV)
+	}
+
+	/// Encrypt the slate JSON for the recipient. The sender is the public key of the secret.
+	pub fn seal(
+		slate_json: &str,
+		secret: &DalekSecretKey,
+		recipient: &DalekPublicKey,
+		use_test_rng: bool,
+	) -> Result<String, Error> {
+		let sender = DalekPublicKey::from(secret);
+		let (encrypted, nonce) = Slatepack::encrypt_payload(
+			slate_json.as_bytes().to_vec(),
+			secret,
+			recipient,
+			use_test_rng,
+		)?;
+
+		let mut data =
+			Vec::with_capacity(1 + 2 * PUBLIC_KEY_LENGTH + NONCE_LENGTH + encrypted.len());
+		data.push(ENVELOPE_VERSION);
+		data.extend_from_slice(sender.as_bytes());
+		data.extend_from_slice(recipient.as_bytes());
+		data.extend_from_slice(&nonce);
+		data.extend_from_slice(&encrypted);
+
+		let armored = format_slatepack(&format!("{}{}", HEADER_ENV, base58check(&data)?))?;
+		Ok(format!("{}{}", armored, FOOTER_ENV))
+	}
+
+	/// Decrypt the envelope. Both the recipient and the sender can open it.
+	/// Return: slate JSON, sender, recipient
+	pub fn open(
+		content: &str,
+		secret: &DalekSecretKey,
+	) -> Result<(String, DalekPublicKey, DalekPublicKey), Error> {
+		let body = content.trim_start_matches(&ARMOR_SPACES[..]);
+		if !body.starts_with(HEADER_ENV) {
+			return Err(
+				ErrorKind::SlatepackDecodeError("Bad slate envelope header".to_string()).into(),
+			);
+		}
+		let body = &body[HEADER_ENV.len()..];
+		let footer_pos = body
+			.find(FOOTER_NAME)
+			.ok_or(ErrorKind::SlatepackDecodeError(
+				"Bad slate envelope footer".to_string(),
+			))?;
+		let payload = body[..footer_pos]
+			.chars()
+			.filter(|c| !ARMOR_SPACES.contains(c) && *c != '.')
+			.collect::<String>();
+
+		let decoded = bs58::decode(&payload).into_vec().map_err(|_| {
+			ErrorKind::SlatepackDecodeError("Invalid slate envelope data".to_string())
+		})?;
+		if decoded.len() < 4 + 1 + 2 * PUBLIC_KEY_LENGTH + NONCE_LENGTH {
+			return Err(
+				ErrorKind::SlatepackDecodeError("Slate envelope is too short".to_string()).into(),
+			);
+		}
+		error_check(&decoded[0..4], &decoded[4..])?;
+		let data = &decoded[4..];
+
+		if data[0] != ENVELOPE_VERSION {
+			return Err(ErrorKind::SlatepackDecodeError(format!(
+				"Unsupported slate envelope version {}",
+				data[0]
+			))
+			.into());
+		}
+		let key = |bytes: &[u8]| -> Result<DalekPublicKey, Error> {
+			DalekPublicKey::from_bytes(bytes).map_err(|e| {
+				ErrorKind::SlatepackDecodeError(format!("Invalid slate envelope key, {}", e)).into()
+			})
+		};
+		let sender = key(&data[1..1 + PUBLIC_KEY_LENGTH])?;
+		let recipient = key(&data[1 + PUBLIC_KEY_LENGTH..1 + 2 * PUBLIC_KEY_LENGTH])?;
+		let mut nonce = [0u8; NONCE_LENGTH];
+		nonce.copy_from_slice(
+			&data[1 + 2 * PUBLIC_KEY_LENGTH..1 + 2 * PUBLIC_KEY_LENGTH + NONCE_LENGTH],
+		);
+		let encrypted = data[1 + 2 * PUBLIC_KEY_LENGTH + NONCE_LENGTH..].to_vec();
+
+		// The shared secret is the same for both sides, the other party key is needed
+		let own_key = DalekPublicKey::from(secret);
+		let other_key = if own_key == recipient {
+			sender
+		} else if own_key == sender {
+			recipient
+		} else {
+			return Err(ErrorKind::SlatepackDecodeError(
+				"The slate envelope is encrypted for another wallet".to_string(),
+			)
+			.into());
+		};
+		let json = Slatepack::decrypt_payload(encrypted, nonce, secret, &other_key)?;
+		let json = String::from_utf8(json).map_err(|e| {
+			ErrorKind::SlatepackDecodeError(format!("Invalid slate envelope content, {}", e))
+		})?;
+		Ok((json, sender, recipient))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_slate_envelope() {
+		let sender_secret = DalekSecretKey::from_bytes(&[1; 32]).unwrap();
+		let recipient_secret = DalekSecretKey::from_bytes(&[2; 32]).unwrap();
+		let other_secret = DalekSecretKey::from_bytes(&[3; 32]).unwrap();
+		let sender = DalekPublicKey::from(&sender_secret);
+		let recipient = DalekPublicKey::from(&recipient_secret);
+		let json = r#"{"version_info":{"version":3},"amount":"1000000000"}"#;
+
+		let envelope = SlateEnvelope::seal(json, &sender_secret, &recipient, true).unwrap();
+		assert!(envelope.starts_with("BEGINSLATE_ENV. "));
+		assert!(envelope.ends_with(". ENDSLATE_ENV."));
+		assert!(!envelope.contains("amount"));
+		assert!(SlateEnvelope::is_envelope(&format!("\n> {}", envelope)));
+		assert!(!SlateEnvelope::is_envelope(json));
+
+		assert_eq!(
+			SlateEnvelope::open(&envelope, &recipient_secret).unwrap(),
+			(json.to_string(), sender, recipient)
+		);
+		assert_eq!(
+			SlateEnvelope::open(&envelope, &sender_secret).unwrap(),
+			(json.to_string(), sender, recipient)
+		);
+		assert!(SlateEnvelope::open(&envelope, &other_secret).is_err());
+
+		// Corrupted data doesn't pass the check code
+		let mut corrupted = envelope.clone().into_bytes();
+		let pos = HEADER_ENV.len() + 10;
+		corrupted[pos] = if corrupted[pos] == b'2' { b'3' } else { b'2' };
+		let corrupted = String::from_utf8(corrupted).unwrap();
+		assert!(SlateEnvelope::open(&corrupted, &recipient_secret).is_err());
+	}
+}
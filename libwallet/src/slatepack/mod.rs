@@ -15,9 +15,11 @@
 //! Functions and types for handling Slatepack transactions
 
 mod armor;
+mod envelope;
 mod packer;
 mod slatepack;
 
 pub use self::armor::{generate_check, SlatepackArmor};
+pub use self::envelope::SlateEnvelope;
 pub use self::packer::Slatepacker;
 pub use self::slatepack::{SlatePurpose, Slatepack};
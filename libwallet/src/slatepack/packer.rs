@@ -18,6 +18,7 @@ use crate::{Slate, SlateVersion, Slatepack, SlatepackArmor};
 use ed25519_dalek::PublicKey as DalekPublicKey;
 use ed25519_dalek::SecretKey as DalekSecretKey;
 
+use crate::slate_versions::grin_compat;
 use crate::slatepack::slatepack::SlatePurpose;
 
 #[derive(Clone, Debug)]
@@ -69,6 +70,9 @@ impl Slatepacker {
 	/// return slatepack
 	pub fn decrypt_slatepack(data: &[u8], dec_key: &DalekSecretKey) -> Result<Self, Error> {
 		let (slate_bytes, encrypted) = SlatepackArmor::decode(data)?;
+		if grin_compat::is_grin_slatepack_payload(&slate_bytes) {
+			return Err(grin_compat::grin_slatepack_error());
+		}
 
 		let slatepack = Slatepack::from_binary(&slate_bytes, encrypted, dec_key)?;
 
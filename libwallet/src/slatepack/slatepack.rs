@@ -1010,7 +1010,7 @@ impl Slatepack {
 
 	/// Encrypt the payload. For encryption we are using  Diffie-Hellman key exchange for secret exchange
 	/// Then everything will be encrypted with EAED.
-	fn encrypt_payload(
+	pub(crate) fn encrypt_payload(
 		payload: Vec<u8>,
 		secret: &DalekSecretKey,
 		recipient: &DalekPublicKey,
@@ -1049,7 +1049,7 @@ impl Slatepack {
 
 	/// As above, decrypt if needed
 	/// dec_key - is a secret that is used for all types of weallet addresses.
-	fn decrypt_payload(
+	pub(crate) fn decrypt_payload(
 		payload: Vec<u8>,
 		nonce: [u8; 12],
 		secret: &DalekSecretKey,
@@ -1082,7 +1082,7 @@ impl Slatepack {
 	}
 
 	// Update a transaction form the slate data.
-	fn update_tx_from_slate(slate: &mut Slate) -> Result<(), Error> {
+	pub(crate) fn update_tx_from_slate(slate: &mut Slate) -> Result<(), Error> {
 		let secp = static_secp_instance();
 		let secp = secp.lock();
 		debug_assert!(slate.compact_slate);
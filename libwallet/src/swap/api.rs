@@ -99,6 +99,12 @@ pub trait SwapApi<K: Keychain>: Sync + Send {
 		confirmations_needed: u64,
 	) -> Result<(u64, u64, u64), ErrorKind>;
 
+	/// Check the deposits that the buyer made manually from an external wallet or an exchange.
+	/// Besides the lock account, the buyer refund address is checked because funds might be sent
+	/// there by mistake.
+	/// Return: (<lock account deposit amounts>, <refund address deposit amounts>)
+	fn request_secondary_deposits(&self, swap: &Swap) -> Result<(Vec<u64>, Vec<u64>), ErrorKind>;
+
 	/// Build secondary update part of the offer message
 	fn build_offer_message_secondary_update(
 		&self,
@@ -141,6 +147,17 @@ pub trait SwapApi<K: Keychain>: Sync + Send {
 		post_tx: bool,
 	) -> Result<(), ErrorKind>;
 
+	/// Buyer: sweep the confirmed coins that are left at the lock account to the refund address.
+	/// That covers the overpaid or late deposits that redeem or refund transactions didn't spend.
+	/// Available after the lock time only. Return the swept amount.
+	fn sweep_secondary_lock(
+		&self,
+		keychain: &K,
+		context: &Context,
+		swap: &mut Swap,
+		post_tx: bool,
+	) -> Result<u64, ErrorKind>;
+
 	/// deposit secondary currecny to lock account.
 	fn post_secondary_lock_tx(&self, swap: &mut Swap) -> Result<(), ErrorKind>;
 
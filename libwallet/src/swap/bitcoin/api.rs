@@ -30,12 +30,19 @@ use crate::swap::types::{
 	BuyerContext, Context, Currency, RoleContext, SecondaryBuyerContext, SecondarySellerContext,
 	SellerContext, SwapTransactionsConfirmations,
 };
-use crate::swap::{ErrorKind, SellApi, Swap, SwapApi};
+use crate::swap::{swap, ErrorKind, SellApi, Swap, SwapApi};
 use crate::{NodeClient, Slate};
 use bitcoin::{Script, Txid};
 use failure::_core::marker::PhantomData;
 use std::sync::Arc;
 
+/// Target of the fee estimation, the redeem and refund transactions have the time limits
+const FEE_ESTIMATE_BLOCKS: u32 = 2;
+/// Min estimated fee, satoshi per byte
+const FEE_ESTIMATE_MIN: f32 = 1.0;
+/// Max estimated fee relative to the default fee of the coin
+const FEE_ESTIMATE_MAX_K: f32 = 10.0;
+
 /// SwapApi trait implementaiton for BTC
 #[derive(Clone)]
 pub struct BtcSwapApi<'a, C, B>
@@ -112,6 +119,21 @@ where
 		)?)
 	}
 
+	/// Unspent outputs of the address. Second node is used if the first one is failed.
+	fn unspent(&self, address: &String) -> Result<Vec<Output>, ErrorKind> {
+		match self
+			.btc_node_client1
+			.lock()
+			.unspent(self.secondary_currency, address)
+		{
+			Ok(r) => Ok(r),
+			Err(_) => self
+				.btc_node_client2
+				.lock()
+				.unspent(self.secondary_currency, address),
+		}
+	}
+
 	/// Check BTC amount at the chain.
 	/// Return output with at least 1 confirmations because it is needed for refunds or redeems. Both party want to take everything
 	pub(crate) fn btc_balance(
@@ -123,17 +145,7 @@ where
 		let btc_data = swap.secondary_data.unwrap_btc()?;
 		let address = btc_data.address(self.secondary_currency, input_script, swap.network)?;
 		debug_assert!(address.len() > 0);
-		let outputs = match self
-			.btc_node_client1
-			.lock()
-			.unspent(self.secondary_currency, &address[0])
-		{
-			Ok(r) => r,
-			Err(_) => self
-				.btc_node_client2
-				.lock()
-				.unspent(self.secondary_currency, &address[0])?,
-		};
+		let outputs = self.unspent(&address[0])?;
 		let height = match self.btc_node_client1.lock().height() {
 			Ok(r) => r,
 			Err(_) => self.btc_node_client2.lock().height()?,
@@ -230,6 +242,52 @@ where
 		Ok(btc_transaction)
 	}
 
+	/// Set the secondary fee of the trade from the network estimation if the user didn't define
+	/// it. The fee is estimated once for the transaction, the rebuilt transaction must keep its hash.
+	fn estimate_secondary_fee(&self, swap: &mut Swap) {
+		if !swap.secondary_fee_estimate {
+			return;
+		}
+		let estimated = match self
+			.btc_node_client1
+			.lock()
+			.estimate_fee(FEE_ESTIMATE_BLOCKS)
+		{
+			Ok(Some(fee)) => Some(fee),
+			_ => self
+				.btc_node_client2
+				.lock()
+				.estimate_fee(FEE_ESTIMATE_BLOCKS)
+				.unwrap_or(None),
+		};
+		let fee = match estimated {
+			Some(fee) => fee,
+			None => {
+				warn!(
+					"Unable to estimate {} fee for swap {}, using {}",
+					self.secondary_currency, swap.id, swap.secondary_fee
+				);
+				return;
+			}
+		};
+
+		let max_fee = self.secondary_currency.get_default_fee(&swap.network) * FEE_ESTIMATE_MAX_K;
+		let bounded_fee = fee.max(FEE_ESTIMATE_MIN).min(max_fee);
+		let units = self.secondary_currency.get_fee_units().0;
+		swap.secondary_fee = bounded_fee;
+		if bounded_fee == fee {
+			swap.add_journal_message(format!(
+				"{} fee {} {} is estimated from the network",
+				self.secondary_currency, bounded_fee, units
+			));
+		} else {
+			swap.add_journal_message(format!(
+				"{} fee {} {} is estimated from the network, {} {} is used",
+				self.secondary_currency, fee, units, bounded_fee, units
+			));
+		}
+	}
+
 	fn buyer_refund<K: Keychain>(
 		&self,
 		keychain: &K,
@@ -248,6 +306,40 @@ where
 			));
 		}
 
+		if swap.secondary_data.unwrap_btc()?.refund_tx.is_none() {
+			self.estimate_secondary_fee(swap);
+		}
+
+		let refund_tx = self.build_refund_tx(
+			keychain,
+			context,
+			swap,
+			refund_address,
+			input_script,
+			&conf_outputs,
+		)?;
+
+		if post_tx {
+			self.post_btc_tx(refund_tx.tx.clone())?;
+		}
+
+		let btc_data = swap.secondary_data.unwrap_btc_mut()?;
+		btc_data.refund_tx = Some(refund_tx.txid);
+		btc_data.tx_fee = Some(swap.secondary_fee);
+		Ok(())
+	}
+
+	/// Build the transaction that spends lock outputs to the refund address with refund path.
+	/// Valid only after the lock time.
+	fn build_refund_tx<K: Keychain>(
+		&self,
+		keychain: &K,
+		context: &Context,
+		swap: &Swap,
+		refund_address: &String,
+		input_script: &Script,
+		conf_outputs: &Vec<Output>,
+	) -> Result<BtcTtansaction, ErrorKind> {
 		let refund_key = keychain.derive_key(
 			0,
 			&context.unwrap_buyer()?.unwrap_btc()?.refund,
@@ -272,20 +364,16 @@ where
 			input_script,
 			swap.secondary_fee,
 			btc_lock_time,
-			&conf_outputs,
+			conf_outputs,
 			refund_script_sig,
 		)?;
+		Ok(refund_tx)
+	}
 
-		let tx = refund_tx.tx.clone();
-		if post_tx {
-			if let Err(_) = self.btc_node_client1.lock().post_tx(tx.clone()) {
-				self.btc_node_client2.lock().post_tx(tx)?;
-			}
+	fn post_btc_tx(&self, tx: Vec<u8>) -> Result<(), ErrorKind> {
+		if let Err(_) = self.btc_node_client1.lock().post_tx(tx.clone()) {
+			self.btc_node_client2.lock().post_tx(tx)?;
 		}
-
-		let btc_data = swap.secondary_data.unwrap_btc_mut()?;
-		btc_data.refund_tx = Some(refund_tx.txid);
-		btc_data.tx_fee = Some(swap.secondary_fee);
 		Ok(())
 	}
 
@@ -535,6 +623,9 @@ where
 
 		let input_script = self.script(swap)?;
 
+		if swap.secondary_data.unwrap_btc()?.redeem_tx.is_none() {
+			self.estimate_secondary_fee(swap);
+		}
 		let btc_tx = self.seller_build_redeem_tx(keychain, swap, context, &input_script)?;
 
 		if post_tx {
@@ -645,6 +736,32 @@ where
 		Ok((pending_amount, confirmed_amount, least_confirmations))
 	}
 
+	fn request_secondary_deposits(&self, swap: &Swap) -> Result<(Vec<u64>, Vec<u64>), ErrorKind> {
+		let lock_address = self.get_secondary_lock_address(swap)?;
+		debug_assert!(lock_address.len() > 0);
+		let lock_deposits = self
+			.unspent(&lock_address[0])?
+			.iter()
+			.map(|o| o.value)
+			.collect();
+
+		let refund_deposits = match swap.unwrap_buyer()? {
+			Some(refund_address)
+				if self
+					.secondary_currency
+					.validate_address(&refund_address)
+					.is_ok() =>
+			{
+				self.unspent(&refund_address)?
+					.iter()
+					.map(|o| o.value)
+					.collect()
+			}
+			_ => vec![],
+		};
+		Ok((lock_deposits, refund_deposits))
+	}
+
 	// Build state machine that match the swap data
 	fn get_fsm(&self, keychain: &K, swap: &Swap) -> StateMachine {
 		let kc = Arc::new(keychain.clone());
@@ -776,6 +893,65 @@ where
 		Ok(())
 	}
 
+	/// Sweep BTC that is left at the lock account to the refund address
+	fn sweep_secondary_lock(
+		&self,
+		keychain: &K,
+		context: &Context,
+		swap: &mut Swap,
+		post_tx: bool,
+	) -> Result<u64, ErrorKind> {
+		if swap.is_seller() {
+			return Err(ErrorKind::Generic(
+				"Only Buyer can sweep the lock account".to_string(),
+			));
+		}
+
+		let time_limit = swap.get_time_secondary_lock_publish();
+		if swap::get_cur_time() < time_limit {
+			return Err(ErrorKind::Generic(format!(
+				"Lock account can be swept after the lock time, please wait for {} seconds",
+				time_limit - swap::get_cur_time()
+			)));
+		}
+
+		let refund_address = swap.unwrap_buyer()?.ok_or(ErrorKind::Generic(
+			"Please define refund address".to_string(),
+		))?;
+		swap.secondary_currency.validate_address(&refund_address)?;
+
+		let input_script = self.script(swap)?;
+		let (_, confirmed_amount, _, conf_outputs) = self.btc_balance(swap, &input_script, 1)?;
+		if conf_outputs.is_empty() {
+			return Err(ErrorKind::Generic(
+				"Not found confirmed outputs at the lock account, nothing to sweep".to_string(),
+			));
+		}
+
+		let sweep_tx = self.build_refund_tx(
+			keychain,
+			context,
+			swap,
+			&refund_address,
+			&input_script,
+			&conf_outputs,
+		)?;
+
+		if post_tx {
+			self.post_btc_tx(sweep_tx.tx.clone())?;
+		}
+
+		swap.add_journal_message(format!(
+			"{} {} left at the lock account is swept to {}, transaction {}",
+			self.secondary_currency
+				.amount_to_hr_string(confirmed_amount, true),
+			self.secondary_currency,
+			refund_address,
+			sweep_tx.txid
+		));
+		Ok(confirmed_amount)
+	}
+
 	/// deposit secondary currecny to lock account.
 	fn post_secondary_lock_tx(&self, _swap: &mut Swap) -> Result<(), ErrorKind> {
 		Ok(())
@@ -73,6 +73,9 @@ pub trait BtcNodeClient: Sync + Send + 'static {
 		&mut self,
 		tx_hash: &Txid, // tx hash
 	) -> Result<Option<u64>, ErrorKind>;
+	/// Fee rate in satoshi per byte for the transaction to be confirmed within the number of
+	/// blocks. None if the node doesn't have enough data for the estimation.
+	fn estimate_fee(&mut self, blocks: u32) -> Result<Option<f32>, ErrorKind>;
 }
 
 /// Mock BTC node for the testing
@@ -86,6 +89,8 @@ pub struct TestBtcNodeClientState {
 	pub txs: HashMap<Txid, Transaction>,
 	/// Pending transactions
 	pub pending: HashMap<Txid, Transaction>,
+	/// Fee rate that is returned by the fee estimation
+	pub fee_rate: Option<f32>,
 }
 
 /// Mock BTC node client
@@ -104,6 +109,7 @@ impl TestBtcNodeClient {
 				tx_heights: HashMap::new(),
 				txs: HashMap::new(),
 				pending: HashMap::new(),
+				fee_rate: None,
 			})),
 		}
 	}
@@ -287,4 +293,8 @@ impl BtcNodeClient for TestBtcNodeClient {
 		};
 		Ok(res)
 	}
+
+	fn estimate_fee(&mut self, _blocks: u32) -> Result<Option<f32>, ErrorKind> {
+		Ok(self.state.lock().fee_rate)
+	}
 }
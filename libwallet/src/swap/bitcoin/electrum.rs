@@ -15,6 +15,7 @@
 use super::client::*;
 use super::rpc::*;
 use crate::grin_util::{from_hex, to_hex};
+use crate::swap::trades;
 use crate::swap::types::Currency;
 use crate::swap::ErrorKind;
 use bitcoin::{OutPoint, Script, Txid};
@@ -154,6 +155,15 @@ impl ElectrumRpcClient {
 		Ok(())
 	}
 
+	/// Fee rate in coins per kilobyte, negative if the server can't estimate it
+	pub fn estimate_fee(&mut self, blocks: u32) -> Result<f64, ErrorKind> {
+		let params = EstimateFeeParams { number: blocks };
+		let request = RpcRequest::new(self.next_id(), "blockchain.estimatefee", params)?;
+		self.write(&request)?;
+		let fee: f64 = self.wait(request.id)?;
+		Ok(fee)
+	}
+
 	pub fn transaction(
 		&mut self,
 		tx_hash: String,
@@ -224,6 +234,11 @@ impl TransactionParams {
 	}
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct EstimateFeeParams {
+	number: u32,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct BroadcastParams {
 	raw_tx: String,
@@ -301,10 +316,12 @@ impl ElectrumNodeClient {
 		}
 
 		if self.client.is_none() {
-			self.client = Some((
-				ElectrumRpcClient::new(self.address.clone())?,
-				Instant::now(),
-			));
+			let client = ElectrumRpcClient::new(self.address.clone()).map_err(|e| {
+				trades::electrumx_failed(&self.address);
+				e
+			})?;
+			trades::electrumx_ok(&self.address);
+			self.client = Some((client, Instant::now()));
 		}
 
 		let (c, t) = self.client.as_mut().unwrap();
@@ -412,6 +429,22 @@ impl BtcNodeClient for ElectrumNodeClient {
 		};
 		Ok(height)
 	}
+	/// Estimate the fee rate from the node mempool
+	fn estimate_fee(&mut self, blocks: u32) -> Result<Option<f32>, ErrorKind> {
+		let client = self.client()?;
+		let fee_per_kb = client.estimate_fee(blocks)?;
+		Ok(fee_per_kb_to_sat_per_byte(fee_per_kb))
+	}
+}
+
+/// Convert the ElectrumX fee estimation (coins per kilobyte, -1 if not available) into
+/// satoshi per byte
+fn fee_per_kb_to_sat_per_byte(fee_per_kb: f64) -> Option<f32> {
+	if fee_per_kb > 0.0 {
+		Some((fee_per_kb * 100_000_000.0 / 1000.0) as f32)
+	} else {
+		None
+	}
 }
 
 /// ElectrumX client error response.
@@ -423,6 +456,12 @@ struct ElectrumResponseError {
 
 #[cfg(test)]
 mod tests {
+	#[test]
+	fn test_fee_conversion() {
+		assert_eq!(super::fee_per_kb_to_sat_per_byte(0.00012), Some(12.0));
+		assert_eq!(super::fee_per_kb_to_sat_per_byte(-1.0), None);
+	}
+
 	/*	use super::*;
 	use crate::swap::bitcoin::BtcData;
 	use crate::swap::types::Network;
@@ -17,8 +17,13 @@ mod client;
 mod electrum;
 mod rpc;
 mod types;
+mod wallet;
 
 pub use api::BtcSwapApi;
 pub use client::*;
 pub use electrum::ElectrumNodeClient;
 pub use types::{BtcBuyerContext, BtcData, BtcSellerContext, BtcUpdate};
+pub use wallet::{
+	list_secondary_addresses, new_secondary_address, secondary_key, sweep_secondary_addresses,
+	SecondaryAddress, SweptAddress, SECONDARY_ADDRESSES_FILE, WALLET_SECONDARY_ADDRESS,
+};
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::proxy;
 use crate::swap::ErrorKind;
 use native_tls::{TlsConnector, TlsStream};
 use serde::Serialize;
@@ -39,12 +40,21 @@ impl LineStream {
 	}
 
 	fn create_tcp_stream(address: &String) -> Result<TcpStream, ErrorKind> {
+		let timeout = Duration::from_secs(10);
+		if let Some(proxy_addr) = proxy::get_proxy_addr() {
+			return proxy::connect_via_proxy(&proxy_addr, address, timeout).map_err(|e| {
+				ErrorKind::ElectrumNodeClient(format!(
+					"Unable to connect to {} via proxy {}, {}",
+					address, proxy_addr, e
+				))
+			});
+		}
+
 		let address = address
 			.to_socket_addrs()?
 			.next()
 			.ok_or(ErrorKind::Generic("Unable to parse address".into()))?;
 
-		let timeout = Duration::from_secs(10);
 		let stream = TcpStream::connect_timeout(&address, timeout)?;
 		stream.set_read_timeout(Some(timeout))?;
 		stream.set_write_timeout(Some(timeout))?;
@@ -247,6 +247,42 @@ impl BtcData {
 		}
 	}
 
+	/// Generate the P2PKH address for the public key
+	pub fn p2pkh_address(
+		currency: Currency,
+		public: &PublicKey,
+		network: Network,
+	) -> Result<String, ErrorKind> {
+		let public = public.serialize_vec(true);
+		if currency == Currency::Bch {
+			let pubkey_hash = hash160::Hash::hash(&public);
+			let address = bch::address::cashaddr_encode(
+				&pubkey_hash,
+				bch::address::AddressType::P2PKH,
+				bch_network(network),
+			)
+			.map_err(|e| {
+				ErrorKind::BchError(format!(
+					"Unable to encode BCH address from public key hash, {}",
+					e
+				))
+			})?;
+			return Ok(address);
+		}
+
+		let public = bitcoin::PublicKey::from_slice(&public)
+			.map_err(|e| ErrorKind::Generic(format!("Invalid public key, {}", e)))?;
+		let address = match currency {
+			Currency::Btc => Address::new_btc().p2pkh(&public, btc_network(network)),
+			Currency::Ltc => Address::new_ltc().p2pkh(&public, btc_network(network)),
+			Currency::Dash => Address::new_dash().p2pkh(&public, btc_network(network)),
+			Currency::ZCash => Address::new_zec().p2pkh(&public, btc_network(network)),
+			Currency::Doge => Address::new_doge().p2pkh(&public, btc_network(network)),
+			_ => return Err(ErrorKind::UnexpectedCoinType),
+		};
+		Ok(address.to_string())
+	}
+
 	// Build input/output for redeem or refund btc transaciton
 	// Inputs need to have amounts for BCH signature
 	fn build_input_outputs(
@@ -402,26 +438,85 @@ impl BtcData {
 		),
 		ErrorKind,
 	> {
-		let (input, output, total_amount) =
-			Self::build_input_outputs(currency, address, conf_outputs)?;
-		let mut tx = Transaction {
-			version: 2,
-			lock_time: if btc_lock_time == 0 {
+		let number_of_signatures = if btc_lock_time > 0 { 1 } else { 2 };
+
+		// Calculate script sig size
+		let mut script_sig_size = input_script.len();
+		script_sig_size += VarInt(script_sig_size as u64).len();
+		script_sig_size += number_of_signatures * (1 + 72 + 1); // Signature (uno for refund)
+		script_sig_size += number_of_signatures; // Opcodes (by accident they match number of signatures)
+
+		Self::spend_transaction(
+			currency,
+			address,
+			input_script,
+			script_sig_size,
+			fee,
+			if btc_lock_time == 0 {
 				0
 			} else {
 				(btc_lock_time + 1) as u32
 			}, // lock time must be larger for BCH
+			conf_outputs,
+			script_sig,
+		)
+	}
+
+	/// Build the transaction that spends the P2PKH outputs of a single key. pubkey_script is the
+	/// script of the address that is spent.
+	pub(crate) fn spend_p2pkh_transaction(
+		currency: &Currency,
+		address: &String,
+		pubkey_script: &Script,
+		fee: f32,
+		conf_outputs: &Vec<Output>,
+		script_sig: impl Fn(&Message) -> Result<Script, ErrorKind>,
+	) -> Result<BtcTtansaction, ErrorKind> {
+		// Signature and compressed public key
+		let script_sig_size = (1 + 72 + 1) + (1 + 33);
+		let (tx, _, _, _) = Self::spend_transaction(
+			currency,
+			address,
+			pubkey_script,
+			script_sig_size,
+			fee,
+			0,
+			conf_outputs,
+			script_sig,
+		)?;
+		Ok(tx)
+	}
+
+	// Build and sign the transaction that spends conf_outputs to the address.
+	// input_script is signed for every input, script_sig_size is the size of the input script sig.
+	fn spend_transaction(
+		currency: &Currency,
+		address: &String,
+		input_script: &Script,
+		script_sig_size: usize,
+		fee: f32,
+		lock_time: u32,
+		conf_outputs: &Vec<Output>,
+		script_sig: impl Fn(&Message) -> Result<Script, ErrorKind>,
+	) -> Result<
+		(
+			BtcTtansaction,
+			Option<Transaction>,
+			Option<usize>,
+			Option<usize>,
+		),
+		ErrorKind,
+	> {
+		let (input, output, total_amount) =
+			Self::build_input_outputs(currency, address, conf_outputs)?;
+		let mut tx = Transaction {
+			version: 2,
+			lock_time,
 			input: input.iter().map(|i| i.0.clone()).collect(),
 			output,
 		};
 
-		let number_of_signatures = if btc_lock_time > 0 { 1 } else { 2 };
-
 		// Calculate tx size
-		let mut script_sig_size = input_script.len();
-		script_sig_size += VarInt(script_sig_size as u64).len();
-		script_sig_size += number_of_signatures * (1 + 72 + 1); // Signature (uno for refund)
-		script_sig_size += number_of_signatures; // Opcodes (by accident they match number of signatures)
 		let tx_size = tx.get_weight() / 4 + script_sig_size * tx.input.len();
 
 		// Subtract fee from output
@@ -432,7 +527,13 @@ impl BtcData {
 			(fee * k as f32 + 0.5) as u64
 		};
 
-		tx.output[0].value = total_amount.saturating_sub(fee);
+		if total_amount <= fee {
+			return Err(ErrorKind::Generic(format!(
+				"Amount {} doesn't cover the transaction fee {}",
+				total_amount, fee
+			)));
+		}
+		tx.output[0].value = total_amount - fee;
 
 		match currency {
 			Currency::Btc | Currency::Ltc | Currency::Dash | Currency::Doge => {
@@ -628,6 +729,35 @@ impl BtcData {
 		Ok(script_sig)
 	}
 
+	/// Script sig for the P2PKH input: signature and the compressed public key
+	pub(crate) fn p2pkh_script_sig(
+		currency: &Currency,
+		signature: &mut Signature,
+		public: &PublicKey,
+	) -> Result<Script, ErrorKind> {
+		let sign_ser = match currency {
+			Currency::Bch => {
+				signature.normalize_s();
+				let mut sign_ser = signature.serialize_der();
+				sign_ser.push(0x41); // SIGHASH_ALL | SIGHASH_FORKID
+				sign_ser
+			}
+			Currency::Btc | Currency::Ltc | Currency::Dash | Currency::ZCash | Currency::Doge => {
+				let mut sign_ser = signature.serialize_der();
+				sign_ser.push(0x01); // SIGHASH_ALL
+				sign_ser
+			}
+			_ => return Err(ErrorKind::UnexpectedCoinType),
+		};
+
+		let script_sig = Builder::new()
+			.push_slice(&sign_ser)
+			.push_slice(&public.serialize_vec(true))
+			.into_script();
+
+		Ok(script_sig)
+	}
+
 	/// Seller init BTC offer for buyer
 	pub(crate) fn offer_update(&self) -> BtcUpdate {
 		BtcUpdate::Offer(BtcOfferUpdate {
@@ -917,4 +1047,23 @@ mod tests {
 		};
 		tx.unwrap().verify(verify_fn).unwrap();
 	}
+
+	#[test]
+	fn test_p2pkh_address() {
+		let secp = Secp256k1::with_caps(ContextFlag::Commit);
+		let mut key = [0u8; 32];
+		key[31] = 1;
+		let key = SecretKey::from_slice(&key).unwrap();
+		let public = PublicKey::from_secret_key(&secp, &key).unwrap();
+
+		assert_eq!(
+			BtcData::p2pkh_address(Currency::Btc, &public, Network::Mainnet).unwrap(),
+			"1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+		);
+		assert_eq!(
+			BtcData::p2pkh_address(Currency::Btc, &public, Network::Floonet).unwrap(),
+			"mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r"
+		);
+		assert!(BtcData::p2pkh_address(Currency::Ether, &public, Network::Mainnet).is_err());
+	}
 }
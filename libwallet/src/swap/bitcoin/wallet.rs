@@ -0,0 +1,237 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Secondary currency addresses of the wallet. The keys are derived from the wallet seed at
+//! 3/4/<BIP44 coin type>/<index>, so the buyer refund or the seller redeem can go to the wallet
+//! itself. The funds are swept to the external address later. The list of the generated addresses
+//! is kept in the swap trades directory, the keys can be derived again from the seed.
+
+use super::client::{BtcNodeClient, Output};
+use super::types::BtcData;
+use crate::grin_keychain::{ExtKeychainPath, Keychain, SwitchCommitmentType};
+use crate::grin_util::secp::key::{PublicKey, SecretKey};
+use crate::grin_util::secp::Message;
+use crate::grin_util::Mutex;
+use crate::swap::trades;
+use crate::swap::types::{Currency, Network};
+use crate::swap::ErrorKind;
+use chrono::{DateTime, Utc};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// File with the generated addresses, inside the swap trades directory
+pub const SECONDARY_ADDRESSES_FILE: &str = "secondary_addresses.json";
+
+/// Address value that asks the swap to use a new address of the wallet
+pub const WALLET_SECONDARY_ADDRESS: &str = "wallet";
+
+/// Keychain branch of the secondary currency keys
+const SECONDARY_KEYS_BRANCH: u32 = 4;
+
+lazy_static! {
+	static ref SECONDARY_ADDRESSES_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Secondary currency address that belongs to the wallet
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SecondaryAddress {
+	/// Currency of the address
+	pub currency: Currency,
+	/// Index of the key in the currency branch
+	pub index: u32,
+	/// P2PKH address
+	pub address: String,
+	/// Time when the address was generated
+	pub created: DateTime<Utc>,
+}
+
+/// Swept address
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SweptAddress {
+	/// Address of the wallet
+	pub address: String,
+	/// Swept amount, the fee is included
+	pub amount: u64,
+	/// Sweep transaction Id
+	pub txid: String,
+}
+
+// BIP44 coin type of the currency
+fn coin_type(currency: &Currency) -> Result<u32, ErrorKind> {
+	match currency {
+		Currency::Btc => Ok(0),
+		Currency::Ltc => Ok(2),
+		Currency::Doge => Ok(3),
+		Currency::Dash => Ok(5),
+		Currency::ZCash => Ok(133),
+		Currency::Bch => Ok(145),
+		_ => Err(ErrorKind::InvalidCurrency(format!(
+			"{} addresses can't be generated by the wallet",
+			currency
+		))),
+	}
+}
+
+/// Key of the secondary currency address
+pub fn secondary_key<K: Keychain>(
+	keychain: &K,
+	currency: &Currency,
+	index: u32,
+) -> Result<SecretKey, ErrorKind> {
+	let key_id = ExtKeychainPath::new(4, 3, SECONDARY_KEYS_BRANCH, coin_type(currency)?, index)
+		.to_identifier();
+	Ok(keychain.derive_key(0, &key_id, SwitchCommitmentType::None)?)
+}
+
+fn secondary_address_for_key<K: Keychain>(
+	keychain: &K,
+	currency: &Currency,
+	key: &SecretKey,
+) -> Result<(PublicKey, String), ErrorKind> {
+	let public = PublicKey::from_secret_key(keychain.secp(), key)?;
+	let address = BtcData::p2pkh_address(*currency, &public, Network::current_network()?)?;
+	Ok((public, address))
+}
+
+fn addresses_file() -> Result<PathBuf, ErrorKind> {
+	Ok(trades::get_swap_storage_path()?.join(SECONDARY_ADDRESSES_FILE))
+}
+
+fn read_addresses(path: &PathBuf) -> Result<Vec<SecondaryAddress>, ErrorKind> {
+	if !path.exists() {
+		return Ok(vec![]);
+	}
+	let content = fs::read_to_string(path)?;
+	serde_json::from_str(&content).map_err(|e| {
+		ErrorKind::IO(format!(
+			"Unable to read secondary addresses from {}, {}",
+			path.display(),
+			e
+		))
+	})
+}
+
+fn write_addresses(path: &PathBuf, addresses: &Vec<SecondaryAddress>) -> Result<(), ErrorKind> {
+	let content = serde_json::to_string_pretty(addresses).map_err(|e| {
+		ErrorKind::Generic(format!(
+			"Unable to convert secondary addresses to Json, {}",
+			e
+		))
+	})?;
+	let tmp_path = path.with_extension("json.tmp");
+	{
+		let mut file = File::create(&tmp_path)?;
+		file.write_all(content.as_bytes())?;
+		file.sync_all()?;
+	}
+	fs::rename(&tmp_path, path)?;
+	Ok(())
+}
+
+/// Generate the next address of the currency
+pub fn new_secondary_address<K: Keychain>(
+	keychain: &K,
+	currency: &Currency,
+) -> Result<SecondaryAddress, ErrorKind> {
+	let _l = SECONDARY_ADDRESSES_LOCK.lock();
+	let path = addresses_file()?;
+	let mut addresses = read_addresses(&path)?;
+	let index = addresses
+		.iter()
+		.filter(|a| a.currency == *currency)
+		.map(|a| a.index + 1)
+		.max()
+		.unwrap_or(0);
+
+	let key = secondary_key(keychain, currency, index)?;
+	let (_, address) = secondary_address_for_key(keychain, currency, &key)?;
+	let address = SecondaryAddress {
+		currency: currency.clone(),
+		index,
+		address,
+		created: Utc::now(),
+	};
+	addresses.push(address.clone());
+	write_addresses(&path, &addresses)?;
+	Ok(address)
+}
+
+/// Generated addresses, all or for the currency
+pub fn list_secondary_addresses(
+	currency: Option<Currency>,
+) -> Result<Vec<SecondaryAddress>, ErrorKind> {
+	let _l = SECONDARY_ADDRESSES_LOCK.lock();
+	let addresses = read_addresses(&addresses_file()?)?;
+	Ok(addresses
+		.into_iter()
+		.filter(|a| currency.map(|c| c == a.currency).unwrap_or(true))
+		.collect())
+}
+
+/// Sweep the confirmed outputs of the currency addresses to the destination. Every address is
+/// swept with its own transaction.
+pub fn sweep_secondary_addresses<K: Keychain, B: BtcNodeClient>(
+	keychain: &K,
+	currency: &Currency,
+	node_client: &mut B,
+	destination: &String,
+	fee: f32,
+	post_tx: bool,
+) -> Result<Vec<SweptAddress>, ErrorKind> {
+	currency.validate_address(destination)?;
+
+	let mut result = vec![];
+	for address in list_secondary_addresses(Some(currency.clone()))? {
+		let outputs: Vec<Output> = node_client
+			.unspent(currency.clone(), &address.address)?
+			.into_iter()
+			.filter(|o| o.height > 0)
+			.collect();
+		if outputs.is_empty() {
+			continue;
+		}
+		let amount = outputs.iter().map(|o| o.value).sum();
+
+		let key = secondary_key(keychain, currency, address.index)?;
+		let (public, addr) = secondary_address_for_key(keychain, currency, &key)?;
+		if addr != address.address {
+			return Err(ErrorKind::Generic(format!(
+				"Address {} doesn't belong to this wallet",
+				address.address
+			)));
+		}
+		let secp = keychain.secp();
+		let script_sig = |msg: &Message| {
+			BtcData::p2pkh_script_sig(currency, &mut secp.sign(msg, &key)?, &public)
+		};
+		let sweep_tx = BtcData::spend_p2pkh_transaction(
+			currency,
+			destination,
+			&currency.address_2_script_pubkey(&address.address)?,
+			fee,
+			&outputs,
+			script_sig,
+		)?;
+		if post_tx {
+			node_client.post_tx(sweep_tx.tx.clone())?;
+		}
+		result.push(SweptAddress {
+			address: address.address,
+			amount,
+			txid: sweep_tx.txid.to_string(),
+		});
+	}
+	Ok(result)
+}
@@ -77,6 +77,14 @@ impl BuyApi {
 			));
 		}
 
+		if let Some(expires_at) = &offer.expires_at {
+			if expires_at.timestamp() < now_ts {
+				return Err(ErrorKind::InvalidMessageData(
+					"The offer is expired".to_string(),
+				));
+			}
+		}
+
 		// Multisig tx needs to be unlocked and valid. Let's take a look at what we get.
 		let lock_slate: Slate = offer.lock_slate.into_slate_plain()?;
 		if lock_slate.lock_height > 0 {
@@ -301,6 +309,12 @@ impl BuyApi {
 					wait_for_backup1: false,
 					tag: None,
 					other_lock_first_done: false,
+					secondary_deposit_guidance: None,
+					secondary_fee_estimate: offer.secondary_currency.is_fee_estimated(),
+					lock_window_sec: None,
+					other_lock_window_start: None,
+					other_lock_seen: false,
+					offer_expires_at: offer.expires_at.clone(),
 				}
 			}
 			_ => {
@@ -360,6 +374,12 @@ impl BuyApi {
 					wait_for_backup1: false,
 					tag: None,
 					other_lock_first_done: false,
+					secondary_deposit_guidance: None,
+					secondary_fee_estimate: offer.secondary_currency.is_fee_estimated(),
+					lock_window_sec: None,
+					other_lock_window_start: None,
+					other_lock_seen: false,
+					offer_expires_at: offer.expires_at.clone(),
 				}
 			}
 		};
@@ -640,6 +640,11 @@ where
 		Ok((0, amount, 0))
 	}
 
+	/// ETH is locked by the wallet into the contract, there are no manual deposits
+	fn request_secondary_deposits(&self, _swap: &Swap) -> Result<(Vec<u64>, Vec<u64>), ErrorKind> {
+		Ok((vec![], vec![]))
+	}
+
 	// Build state machine that match the swap data
 	fn get_fsm(&self, keychain: &K, swap: &Swap) -> StateMachine {
 		let kc = Arc::new(keychain.clone());
@@ -766,6 +771,20 @@ where
 		Ok(())
 	}
 
+	/// ETH lock contract holds exact amount, refund returns everything
+	fn sweep_secondary_lock(
+		&self,
+		_keychain: &K,
+		_context: &Context,
+		_swap: &mut Swap,
+		_post_tx: bool,
+	) -> Result<u64, ErrorKind> {
+		Err(ErrorKind::Generic(
+			"Ethereum lock contract doesn't need sweeping, refund returns all the funds"
+				.to_string(),
+		))
+	}
+
 	/// deposit secondary currecny to lock account.
 	fn post_secondary_lock_tx(&self, swap: &mut Swap) -> Result<(), ErrorKind> {
 		assert!(!swap.is_seller());
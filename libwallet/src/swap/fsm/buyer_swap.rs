@@ -121,7 +121,8 @@ where
 	}
 	fn get_eta(&self, swap: &Swap) -> Option<StateEtaInfo> {
 		Some(
-			StateEtaInfo::new("Send Accept Offer Message").end_time(swap.get_time_message_offers()),
+			StateEtaInfo::new("Send Accept Offer Message")
+				.end_time(swap.get_time_offer_expiration()),
 		)
 	}
 	fn is_cancellable(&self) -> bool {
@@ -146,7 +147,7 @@ where
 				}
 			}
 			Input::Check => {
-				let time_limit = swap.get_time_message_offers();
+				let time_limit = swap.get_time_offer_expiration();
 				if swap.posted_msg1.unwrap_or(0)
 					< swap::get_cur_time() - super::state::SEND_MESSAGE_RETRY_PERIOD
 				{
@@ -166,7 +167,7 @@ where
 								.time_limit(time_limit),
 						)
 					} else {
-						swap.add_journal_message(JOURNAL_CANCELLED_BY_TIMEOUT.to_string());
+						swap.add_journal_message(swap.offer_expired_message());
 						if tx_conf.secondary_lock_amount == 0 {
 							Ok(StateProcessRespond::new(StateId::BuyerCancelled))
 						} else {
@@ -291,7 +292,21 @@ impl State for BuyerWaitingForSellerToLock {
 				} else {
 					let conf = tx_conf.mwc_lock_conf.unwrap_or(0);
 
+					// MWC lock is visible when it is mined, the network acceptance is the first confirmation
+					if let Some(reason) = swap.check_other_lock_window(conf >= 1, conf >= 1) {
+						swap.add_journal_message(reason);
+						return if tx_conf.secondary_lock_amount == 0 {
+							Ok(StateProcessRespond::new(StateId::BuyerCancelled))
+						} else {
+							Ok(StateProcessRespond::new(StateId::BuyerWaitingForRefundTime))
+						};
+					}
+
 					if conf < 1 {
+						let time_limit = match swap.get_time_other_lock_window() {
+							Some(t) => std::cmp::min(t, time_limit),
+							None => time_limit,
+						};
 						Ok(
 							StateProcessRespond::new(StateId::BuyerWaitingForSellerToLock)
 								.action(Action::WaitForMwcConfirmations {
@@ -347,6 +362,68 @@ where
 			phantom: PhantomData,
 		}
 	}
+
+	/// Guidance for the buyer who funds the lock account from an external wallet or an exchange.
+	/// None if there is nothing specific to report.
+	fn deposit_guidance(&self, swap: &Swap, lock_address: &str) -> Option<String> {
+		let (lock_deposits, refund_deposits) = match self.swap_api.request_secondary_deposits(swap)
+		{
+			Ok(r) => r,
+			Err(e) => {
+				warn!("Unable to check the secondary deposits, {}", e);
+				return None;
+			}
+		};
+		let currency = swap.secondary_currency;
+		let hr = |amount: u64| {
+			format!(
+				"{} {}",
+				currency.amount_to_hr_string(amount, true),
+				currency
+			)
+		};
+
+		let received: u64 = lock_deposits.iter().sum();
+		let missing = swap.secondary_amount.saturating_sub(received);
+		let mut guidance = if received == 0 {
+			None
+		} else if received < swap.secondary_amount {
+			Some(format!(
+				"Lock account {} received {} in {} deposit(s), it is short by {}. Please send {} more to the same address",
+				lock_address,
+				hr(received),
+				lock_deposits.len(),
+				hr(missing),
+				hr(missing)
+			))
+		} else {
+			// Overpaid trade is cancelled, the reason is journaled with the cancellation
+			None
+		};
+
+		// Missing amount landed at the refund address, the buyer sent it to the wrong address
+		if missing > 0 && refund_deposits.contains(&missing) {
+			let wrong_address = format!(
+				"Deposit of {} was found at the refund address {}, but it is expected at the lock account {}",
+				hr(missing),
+				swap.unwrap_buyer().ok().flatten().unwrap_or_default(),
+				lock_address
+			);
+			guidance = Some(match guidance {
+				Some(g) => format!("{}. {}", g, wrong_address),
+				None => wrong_address,
+			});
+		}
+		guidance
+	}
+
+	/// Journal the guidance if it is changed since the last check
+	fn journal_deposit_guidance(swap: &mut Swap, guidance: Option<String>) {
+		if guidance.is_some() && guidance != swap.secondary_deposit_guidance {
+			swap.add_journal_message(guidance.clone().unwrap());
+		}
+		swap.secondary_deposit_guidance = guidance;
+	}
 }
 
 impl<'a, K> State for BuyerPostingSecondaryToMultisigAccount<'a, K>
@@ -428,6 +505,9 @@ where
 					// For eth, coins are locked in contract address in automatic mode,
 					// so it will face this situation
 					if swap.secondary_currency.is_btc_family() {
+						let lock_address = self.swap_api.get_secondary_lock_address(swap)?;
+						let guidance = self.deposit_guidance(swap, &lock_address[0]);
+						Self::journal_deposit_guidance(swap, guidance);
 						return Ok(StateProcessRespond::new(
 							StateId::BuyerPostingSecondaryToMultisigAccount,
 						)
@@ -449,11 +529,12 @@ where
 				let lock_address = self.swap_api.get_secondary_lock_address(swap)?;
 				debug_assert!(lock_address.len() > 0);
 				debug_assert!(lock_address.len() <= 2);
+				swap.secondary_deposit_guidance = None;
 
 				// Posted more then expected. We are not going forward. Deal is broken, probably it is a mistake. We are cancelling the trade because of that.
 				if chain_amount > swap.secondary_amount {
 					swap.add_journal_message(format!(
-						"{} {}. Expected {} {}, but get {} {}",
+						"{} {}. Expected {} {}, but get {} {}, overpaid by {} {}",
 						JOURNAL_CANCELLED_BYER_LOCK_TOO_MUCH_FUNDS,
 						lock_address.join(" or "),
 						swap.secondary_currency
@@ -461,6 +542,9 @@ where
 						swap.secondary_currency,
 						swap.secondary_currency
 							.amount_to_hr_string(chain_amount, true),
+						swap.secondary_currency,
+						swap.secondary_currency
+							.amount_to_hr_string(chain_amount - swap.secondary_amount, true),
 						swap.secondary_currency
 					));
 					return Ok(StateProcessRespond::new(StateId::BuyerWaitingForRefundTime));
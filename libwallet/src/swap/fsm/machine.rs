@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::notifications::{self, WalletEvent};
 use crate::swap::fsm::state::{Input, State, StateEtaInfo, StateId, StateProcessRespond};
 use crate::swap::types::SwapTransactionsConfirmations;
 use crate::swap::{Context, ErrorKind, Swap};
@@ -86,6 +87,12 @@ impl<'a> StateMachine<'a> {
 
 		while respond.next_state_id != swap.state {
 			debug!("New state: {:?}", swap.state);
+			notifications::notify(WalletEvent::SwapStateChanged {
+				swap_id: swap.id.to_string(),
+				from_state: format!("{:?}", swap.state),
+				to_state: format!("{:?}", respond.next_state_id),
+				description: respond.next_state_id.to_string(),
+			});
 			swap.state = respond.next_state_id.clone();
 			let state =
 				self.state_map
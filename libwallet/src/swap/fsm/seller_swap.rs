@@ -113,7 +113,7 @@ where
 		StateId::SellerSendingOffer
 	}
 	fn get_eta(&self, swap: &Swap) -> Option<StateEtaInfo> {
-		Some(StateEtaInfo::new("Sending Offer to Buyer").end_time(swap.get_time_message_offers()))
+		Some(StateEtaInfo::new("Sending Offer to Buyer").end_time(swap.get_time_offer_expiration()))
 	}
 	fn is_cancellable(&self) -> bool {
 		true
@@ -136,7 +136,7 @@ where
 				if swap.posted_msg1.unwrap_or(0)
 					< swap::get_cur_time() - super::state::SEND_MESSAGE_RETRY_PERIOD
 				{
-					let time_limit = swap.get_time_message_offers();
+					let time_limit = swap.get_time_offer_expiration();
 					if swap::get_cur_time() < time_limit {
 						if self.message.is_none() {
 							self.message = swap.message1.clone();
@@ -153,7 +153,7 @@ where
 							))
 							.time_limit(time_limit))
 					} else {
-						swap.add_journal_message(JOURNAL_CANCELLED_BY_TIMEOUT.to_string());
+						swap.add_journal_message(swap.offer_expired_message());
 						Ok(StateProcessRespond::new(StateId::SellerCancelled))
 					}
 				} else {
@@ -208,7 +208,7 @@ impl<K: Keychain> State for SellerWaitingForAcceptanceMessage<K> {
 	fn get_eta(&self, swap: &Swap) -> Option<StateEtaInfo> {
 		Some(
 			StateEtaInfo::new("Waiting For Buyer to accept the offer")
-				.end_time(swap.get_time_message_offers()),
+				.end_time(swap.get_time_offer_expiration()),
 		)
 	}
 	fn is_cancellable(&self) -> bool {
@@ -230,7 +230,7 @@ impl<K: Keychain> State for SellerWaitingForAcceptanceMessage<K> {
 			}
 			Input::Check => {
 				if swap.redeem_public.is_none() {
-					let time_limit = swap.get_time_message_offers();
+					let time_limit = swap.get_time_offer_expiration();
 					if swap::get_cur_time() < time_limit {
 						// Check if we need to retry to send the message
 						if swap.posted_msg1.unwrap_or(0)
@@ -245,7 +245,7 @@ impl<K: Keychain> State for SellerWaitingForAcceptanceMessage<K> {
 						)
 					} else {
 						// cancelling
-						swap.add_journal_message(JOURNAL_CANCELLED_BY_TIMEOUT.to_string());
+						swap.add_journal_message(swap.offer_expired_message());
 						Ok(StateProcessRespond::new(StateId::SellerCancelled))
 					}
 				} else {
@@ -256,6 +256,11 @@ impl<K: Keychain> State for SellerWaitingForAcceptanceMessage<K> {
 			Input::IncomeMessage(message) => {
 				// Double processing should be fine
 				if swap.redeem_public.is_none() {
+					if swap::get_cur_time() > swap.get_time_offer_expiration() {
+						return Err(ErrorKind::InvalidMessageData(
+							"The offer is expired".to_string(),
+						));
+					}
 					let (_, accept_offer, secondary_update) = message.unwrap_accept_offer()?;
 					match swap.secondary_currency.is_btc_family() {
 						true => {
@@ -410,7 +415,20 @@ where
 						swap.other_lock_first_done = true;
 					}
 
+					// Partial lock doesn't count, the buyer have to deposit the full amount within the window
+					if let Some(reason) = swap.check_other_lock_window(
+						tx_conf.secondary_lock_amount == swap.secondary_amount,
+						conf >= 1,
+					) {
+						swap.add_journal_message(reason);
+						return Ok(StateProcessRespond::new(StateId::SellerCancelled));
+					}
+
 					if conf < 1 {
+						let time_limit = match swap.get_time_other_lock_window() {
+							Some(t) => std::cmp::min(t, time_limit),
+							None => time_limit,
+						};
 						Ok(StateProcessRespond::new(StateId::SellerWaitingForBuyerLock)
 							.action(Action::WaitForSecondaryConfirmations {
 								name: format!("Buyer to lock {}", swap.secondary_currency),
@@ -30,6 +30,8 @@ pub const JOURNAL_CANCELLED_BY_USER: &str = "Cancelled by user";
 /// Journal messages that are repeatable for State
 pub const JOURNAL_CANCELLED_BY_TIMEOUT: &str = "Cancelled as expired";
 /// Journal messages that are repeatable for State
+pub const JOURNAL_CANCELLED_OFFER_EXPIRED: &str = "Cancelled because the offer is expired";
+/// Journal messages that are repeatable for State
 pub const JOURNAL_CANCELLED_BYER_LOCK_TOO_MUCH_FUNDS: &str =
 	"Cancelled because the buyer posted funds greater than the agreed upon amount to the lock account";
 /// Journal messages that are repeatable for State
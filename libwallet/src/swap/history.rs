@@ -0,0 +1,279 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Archive of the finished swap trades. The record is written when the trade gets to the final
+//! state and it stays when the trade is deleted. Records are encrypted with the same key as
+//! the trades.
+
+use super::fsm::state::StateId;
+use super::trades::{self, EncryptedSwap};
+use super::types::Currency;
+use super::{ErrorKind, Swap};
+use crate::grin_core::core::amount_to_hr_string;
+use crate::grin_util::secp::key::SecretKey;
+use chrono::{DateTime, TimeZone, Utc};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+/// Location of the finished trades records, inside the swap trades directory
+pub const SWAP_HISTORY_DIR: &'static str = "history";
+
+/// Finished swap trade. The balance changes are in the minimal units of the coin, the secondary
+/// currency network fees are not included because only the fee rate is known.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SwapHistoryRecord {
+	/// Swap trade Id
+	pub swap_id: String,
+	/// Marketplace tag of the trade
+	pub tag: Option<String>,
+	/// True if the wallet was selling MWC
+	pub is_seller: bool,
+	/// Final state of the trade
+	pub state: StateId,
+	/// Time when the trade was started
+	pub started: DateTime<Utc>,
+	/// Time when the trade got to the final state
+	pub finished: DateTime<Utc>,
+	/// Traded MWC amount, nanoMWC
+	pub mwc_amount: u64,
+	/// Secondary currency
+	pub secondary_currency: Currency,
+	/// Traded secondary amount
+	pub secondary_amount: u64,
+	/// Price of 1 MWC in the secondary currency
+	pub exchange_rate: f64,
+	/// MWC network fees paid by the wallet, nanoMWC
+	pub mwc_fee: u64,
+	/// Fee rate of the secondary currency transactions
+	pub secondary_fee: f32,
+	/// Units of the secondary fee rate
+	pub secondary_fee_units: String,
+	/// Change of the MWC balance, nanoMWC
+	pub mwc_change: i64,
+	/// Change of the secondary currency balance
+	pub secondary_change: i64,
+}
+
+impl SwapHistoryRecord {
+	/// Record of the finished trade
+	pub fn from_swap(swap: &Swap, finished: DateTime<Utc>) -> Self {
+		let (mwc_fee, mwc_change, secondary_change) = match swap.state {
+			StateId::SellerSwapComplete => (
+				swap.lock_slate.fee,
+				-((swap.primary_amount + swap.lock_slate.fee) as i64),
+				swap.secondary_amount as i64,
+			),
+			StateId::SellerCancelledRefunded => {
+				let fee = swap.lock_slate.fee + swap.refund_slate.fee;
+				(fee, -(fee as i64), 0)
+			}
+			StateId::BuyerSwapComplete => (
+				swap.redeem_slate.fee,
+				swap.primary_amount.saturating_sub(swap.redeem_slate.fee) as i64,
+				-(swap.secondary_amount as i64),
+			),
+			// Nothing was locked or the locked funds are refunded
+			_ => (0, 0, 0),
+		};
+
+		let exchange_rate = if swap.primary_amount > 0 {
+			(swap.secondary_amount as f64 / 10f64.powi(swap.secondary_currency.exponent() as i32))
+				/ (swap.primary_amount as f64 / 1_000_000_000f64)
+		} else {
+			0.0
+		};
+
+		SwapHistoryRecord {
+			swap_id: swap.id.to_string(),
+			tag: swap.tag.clone(),
+			is_seller: swap.is_seller(),
+			state: swap.state.clone(),
+			started: swap.started.clone(),
+			finished,
+			mwc_amount: swap.primary_amount,
+			secondary_currency: swap.secondary_currency,
+			secondary_amount: swap.secondary_amount,
+			exchange_rate,
+			mwc_fee,
+			secondary_fee: swap.secondary_fee,
+			secondary_fee_units: swap.secondary_currency.get_fee_units().0,
+			mwc_change,
+			secondary_change,
+		}
+	}
+}
+
+/// Write the record of the trade if it is finished and not archived yet.
+/// Return true if the record is written.
+pub fn archive_swap_trade(swap: &Swap, enc_key: &SecretKey) -> Result<bool, ErrorKind> {
+	if !swap.state.is_final_state() {
+		return Ok(false);
+	}
+	let dir = trades::get_swap_storage_path()?.join(SWAP_HISTORY_DIR);
+	let path = dir.join(format!("{}.hist", swap.id));
+	if path.exists() {
+		return Ok(false);
+	}
+	fs::create_dir_all(&dir)?;
+
+	// The final state is reached with the last journal record
+	let finished = swap
+		.journal
+		.last()
+		.map(|j| Utc.timestamp(j.time, 0))
+		.unwrap_or(Utc::now());
+	let record = SwapHistoryRecord::from_swap(swap, finished);
+	let record_ser = serde_json::to_string(&record).map_err(|e| {
+		ErrorKind::Generic(format!("Unable to convert swap history to Json, {}", e))
+	})?;
+	let encrypted = EncryptedSwap::from_json(&record_ser, enc_key)?;
+	let enc_ser = serde_json::to_string(&encrypted).map_err(|e| {
+		ErrorKind::TradeEncDecError(format!("Unable to serialize swap history, {}", e))
+	})?;
+
+	let tmp_path = dir.join(format!("{}.hist.tmp", swap.id));
+	{
+		let mut file = File::create(&tmp_path)?;
+		file.write_all(enc_ser.as_bytes())?;
+		file.sync_all()?;
+	}
+	fs::rename(&tmp_path, &path)?;
+	Ok(true)
+}
+
+fn read_history_record(path: &Path, dec_key: &SecretKey) -> Result<SwapHistoryRecord, ErrorKind> {
+	let content = fs::read_to_string(path)?;
+	let encrypted: EncryptedSwap = serde_json::from_str(&content).map_err(|e| {
+		ErrorKind::TradeEncDecError(format!(
+			"Unable to read swap history {}, {}",
+			path.display(),
+			e
+		))
+	})?;
+	let record = encrypted.decrypt(dec_key)?;
+	serde_json::from_str(&record).map_err(|e| {
+		ErrorKind::TradeEncDecError(format!(
+			"Unable to read swap history {}, {}",
+			path.display(),
+			e
+		))
+	})
+}
+
+/// Records of the finished trades, ordered by the finish time
+pub fn list_swap_history(dec_key: &SecretKey) -> Result<Vec<SwapHistoryRecord>, ErrorKind> {
+	let dir = trades::get_swap_storage_path()?.join(SWAP_HISTORY_DIR);
+	if !dir.exists() {
+		return Ok(vec![]);
+	}
+	let mut result = vec![];
+	for entry in fs::read_dir(&dir)? {
+		let path = entry?.path();
+		if path.extension().map(|e| e == "hist").unwrap_or(false) {
+			result.push(read_history_record(&path, dec_key)?);
+		}
+	}
+	result.sort_by_key(|r| r.finished);
+	Ok(result)
+}
+
+fn signed_amount(amount: i64, to_hr: &dyn Fn(u64) -> String) -> String {
+	if amount < 0 {
+		format!("-{}", to_hr(amount.abs() as u64))
+	} else {
+		to_hr(amount as u64)
+	}
+}
+
+fn csv_field(value: String) -> String {
+	if value.contains(',') || value.contains('"') || value.contains('\n') {
+		format!("\"{}\"", value.replace('"', "\"\""))
+	} else {
+		value
+	}
+}
+
+/// CSV report of the trades, the amounts are in the coin units
+pub fn history_to_csv(records: &[SwapHistoryRecord]) -> String {
+	let mut csv = "swap_id,tag,role,state,started,finished,mwc_amount,secondary_currency,\
+		secondary_amount,exchange_rate,mwc_fee,secondary_fee,secondary_fee_units,mwc_change,\
+		secondary_change\n"
+		.to_string();
+	for r in records {
+		let currency = r.secondary_currency;
+		let row = vec![
+			r.swap_id.clone(),
+			r.tag.clone().unwrap_or_default(),
+			if r.is_seller { "Seller" } else { "Buyer" }.to_string(),
+			r.state.to_cmd_str(),
+			r.started.to_rfc3339(),
+			r.finished.to_rfc3339(),
+			amount_to_hr_string(r.mwc_amount, true),
+			currency.to_string(),
+			currency.amount_to_hr_string(r.secondary_amount, true),
+			format!("{}", r.exchange_rate),
+			amount_to_hr_string(r.mwc_fee, true),
+			format!("{}", r.secondary_fee),
+			r.secondary_fee_units.clone(),
+			signed_amount(r.mwc_change, &|a| amount_to_hr_string(a, true)),
+			signed_amount(r.secondary_change, &|a| {
+				currency.amount_to_hr_string(a, true)
+			}),
+		];
+		csv.push_str(
+			&row.into_iter()
+				.map(csv_field)
+				.collect::<Vec<String>>()
+				.join(","),
+		);
+		csv.push('\n');
+	}
+	csv
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_history_csv() {
+		let record = SwapHistoryRecord {
+			swap_id: "7b4c2b4f-5a7a-4a3e-9b1e-3c6a1f0a2d11".to_string(),
+			tag: Some("desk, main".to_string()),
+			is_seller: true,
+			state: StateId::SellerSwapComplete,
+			started: Utc.timestamp(1617000000, 0),
+			finished: Utc.timestamp(1617003600, 0),
+			mwc_amount: 100_000_000_000,
+			secondary_currency: Currency::Btc,
+			secondary_amount: 1_500_000,
+			exchange_rate: 0.00015,
+			mwc_fee: 8_000_000,
+			secondary_fee: 26.0,
+			secondary_fee_units: "satoshi per byte".to_string(),
+			mwc_change: -100_008_000_000,
+			secondary_change: 1_500_000,
+		};
+		let csv = history_to_csv(&[record]);
+		let lines: Vec<&str> = csv.lines().collect();
+		assert_eq!(lines.len(), 2);
+		assert_eq!(
+			lines[1],
+			"7b4c2b4f-5a7a-4a3e-9b1e-3c6a1f0a2d11,\"desk, main\",Seller,SellerSwapComplete,\
+			2021-03-29T06:40:00+00:00,2021-03-29T07:40:00+00:00,100.0,BTC,0.015,0.00015,0.008,26,\
+			satoshi per byte,-100.008,0.015"
+		);
+	}
+}
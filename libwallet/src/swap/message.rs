@@ -183,6 +183,9 @@ pub struct OfferUpdate {
 	pub message_exchange_time_sec: u64,
 	/// Time interval needed to redeem or execute a refund transaction.
 	pub redeem_time_sec: u64,
+	/// The offer can't be accepted after this time
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub expires_at: Option<DateTime<Utc>>,
 }
 
 /// Buyer, Status::Offered  Buyer responded for offer
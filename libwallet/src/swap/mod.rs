@@ -35,6 +35,8 @@ pub mod fsm;
 
 /// Swap buyer API (selling MWC for BTC)
 pub mod buyer;
+/// Archive of the finished swap trades
+pub mod history;
 /// Swap Seller API (selling BTC for MWC)
 pub mod seller;
 /// Swap state object that is used by both byer abd seller
@@ -581,6 +583,168 @@ mod tests {
 		); // Swap cannot be accepted
 	}
 
+	#[test]
+	#[serial]
+	fn test_lock_window() {
+		set_test_mode(true);
+		global::set_local_chain_type(global::ChainTypes::Floonet);
+		swap::set_testing_cur_time(1567632152);
+
+		let kc_sell = keychain(1);
+		let ctx_sell = context_sell(&kc_sell);
+		let secondary_redeem_address = btc_address(&kc_sell);
+
+		let mut api_sell = BtcSwapApi::new_test(
+			Arc::new(TestNodeClient::new(100_000)),
+			Arc::new(Mutex::new(TestBtcNodeClient::new(1))),
+		);
+		let mut swap = api_sell
+			.create_swap_offer(
+				&kc_sell,
+				&ctx_sell,
+				100 * GRIN_UNIT,
+				3_000_000,
+				Currency::Btc,
+				secondary_redeem_address,
+				false, // buyer locks first
+				30,
+				3,
+				3600,
+				3600,
+				"file".to_string(),
+				"/tmp/del.me".to_string(),
+				None,
+				None,
+				None,
+				None,
+				None,
+				Some(false),
+				false,
+				None,
+			)
+			.unwrap();
+
+		// No window - no limits
+		swap::set_testing_cur_time(1567632152 + 100_000);
+		assert_eq!(swap.check_other_lock_window(false, false), None);
+		assert_eq!(swap.get_time_other_lock_window(), None);
+
+		swap.lock_window_sec = Some(600);
+		swap::set_testing_cur_time(1567632152);
+		assert_eq!(swap.check_other_lock_window(false, false), None);
+		assert_eq!(swap.get_time_other_lock_window(), Some(1567632152 + 600));
+		swap::set_testing_cur_time(1567632152 + 500);
+		assert_eq!(swap.check_other_lock_window(false, false), None);
+
+		// Accepted lock starts a new window for the confirmation
+		assert_eq!(swap.check_other_lock_window(true, false), None);
+		assert!(swap.other_lock_seen);
+		assert_eq!(swap.get_time_other_lock_window(), Some(1567632152 + 1100));
+		swap::set_testing_cur_time(1567632152 + 1000);
+		assert_eq!(swap.check_other_lock_window(true, false), None);
+		assert_eq!(swap.check_other_lock_window(true, true), None);
+
+		swap::set_testing_cur_time(1567632152 + 1101);
+		assert!(swap
+			.check_other_lock_window(true, false)
+			.unwrap()
+			.contains("is not confirmed within 10 minutes"));
+		assert_eq!(swap.check_other_lock_window(true, true), None);
+
+		// The lock that is not accepted in time
+		swap.other_lock_seen = false;
+		swap.other_lock_window_start = None;
+		assert_eq!(swap.check_other_lock_window(false, false), None);
+		swap::set_testing_cur_time(1567632152 + 1702);
+		assert!(swap
+			.check_other_lock_window(false, false)
+			.unwrap()
+			.contains("is not accepted by the network within 10 minutes"));
+	}
+
+	#[test]
+	#[serial]
+	fn test_offer_expiration() {
+		set_test_mode(true);
+		global::set_local_chain_type(global::ChainTypes::Floonet);
+		swap::set_testing_cur_time(1567632152);
+
+		let kc_sell = keychain(1);
+		let ctx_sell = context_sell(&kc_sell);
+		let secondary_redeem_address = btc_address(&kc_sell);
+		let height = 100_000;
+
+		let mut api_sell = BtcSwapApi::new_test(
+			Arc::new(TestNodeClient::new(height)),
+			Arc::new(Mutex::new(TestBtcNodeClient::new(1))),
+		);
+		let mut swap = api_sell
+			.create_swap_offer(
+				&kc_sell,
+				&ctx_sell,
+				100 * GRIN_UNIT,
+				3_000_000,
+				Currency::Btc,
+				secondary_redeem_address,
+				true,
+				30,
+				3,
+				3600,
+				3600,
+				"file".to_string(),
+				"/tmp/del.me".to_string(),
+				None,
+				None,
+				None,
+				None,
+				None,
+				Some(false),
+				false,
+				None,
+			)
+			.unwrap();
+		assert_eq!(swap.get_time_offer_expiration(), 1567632152 + 3600);
+		swap.offer_expires_at = Some(swap.started + chrono::Duration::seconds(600));
+		assert_eq!(swap.get_time_offer_expiration(), 1567632152 + 600);
+
+		let mut fsm_sell = api_sell.get_fsm(&kc_sell, &swap);
+		let tx_state = api_sell
+			.request_tx_confirmations(&kc_sell, &mut swap)
+			.unwrap();
+		let res = fsm_sell
+			.process(Input::Check, &mut swap, &ctx_sell, &tx_state)
+			.unwrap();
+		assert_eq!(res.time_limit, Some(1567632152 + 600));
+		let message = match res.action.unwrap() {
+			Action::SellerSendOfferMessage(message) => message,
+			_ => panic!("Unexpected action"),
+		};
+
+		// Expired offer can't be accepted
+		swap::set_testing_cur_time(1567632152 + 700);
+		let kc_buy = keychain(2);
+		let ctx_buy = context_buy(&kc_buy);
+		let nc = TestNodeClient::new(height);
+		let (id, offer, secondary_update) = message.unwrap_offer().unwrap();
+		assert_eq!(offer.expires_at, swap.offer_expires_at);
+		let res =
+			BuyApi::accept_swap_offer(None, &kc_buy, &ctx_buy, id, offer, secondary_update, &nc);
+		assert_eq!(
+			res.err().unwrap(),
+			ErrorKind::InvalidMessageData("The offer is expired".to_string())
+		);
+
+		// Seller cancels the offer
+		let res = fsm_sell
+			.process(Input::Check, &mut swap, &ctx_sell, &tx_state)
+			.unwrap();
+		assert_eq!(res.next_state_id, StateId::SellerCancelled);
+		assert_eq!(
+			swap.journal.last().unwrap().message,
+			"Cancelled because the offer is expired"
+		);
+	}
+
 	// Because of gonden output new line symbol we skipping Windows.
 	#[cfg(not(target_os = "windows"))]
 	#[test]
@@ -803,6 +967,15 @@ mod tests {
 			} => assert_eq!(amount, btc_amount_2),
 			_ => panic!("Invalid action"),
 		};
+		// Buyer gets the guidance about the missing amount, it is journaled once
+		let guidance = swap_buy.secondary_deposit_guidance.clone().unwrap();
+		assert!(guidance.contains("in 1 deposit(s), it is short by 0.01 BTC"));
+		assert_eq!(swap_buy.journal.last().unwrap().message, guidance);
+		let journal_len = swap_buy.journal.len();
+		fsm_buy
+			.process(Input::Check, &mut swap_buy, &ctx_buy, &tx_conf)
+			.unwrap();
+		assert_eq!(swap_buy.journal.len(), journal_len);
 
 		// Buyer: second deposit
 		btc_nc.mine_blocks(2);
@@ -3628,6 +3801,41 @@ mod tests {
 			assert_eq!(res.action.is_none(), true);
 			assert_eq!(res.time_limit.is_none(), true);
 
+			// Late deposit to the lock account can be swept to the refund address
+			let tx_late = BtcTransaction {
+				version: 2,
+				lock_time: 0,
+				input: vec![],
+				output: vec![TxOut {
+					value: btc_amount_plus + 1,
+					script_pubkey: Currency::Btc
+						.address_2_script_pubkey(&btc_address_to_deposit[0])
+						.unwrap(),
+				}],
+			};
+			btc_nc.post_transaction(&tx_late);
+			btc_nc.mine_block();
+			assert!(seller
+				.api
+				.sweep_secondary_lock(&seller.kc, &seller.ctx, &mut seller.swap, true)
+				.is_err());
+			let pending_txs = btc_nc.get_state().pending.len();
+			let swept = buyer
+				.api
+				.sweep_secondary_lock(&buyer.kc, &buyer.ctx, &mut buyer.swap, true)
+				.unwrap();
+			assert!(swept > btc_amount_plus);
+			assert_eq!(btc_nc.get_state().pending.len(), pending_txs + 1);
+			assert!(buyer
+				.swap
+				.journal
+				.last()
+				.unwrap()
+				.message
+				.contains("is swept to"));
+			let res = buyer.process(Input::Check).unwrap();
+			assert_eq!(res.next_state_id, StateId::BuyerCancelledRefunded);
+
 			swap::set_testing_cur_time(time_to_restore);
 			buyer.pops();
 			seller.pops();
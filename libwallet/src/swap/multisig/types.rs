@@ -464,6 +464,16 @@ impl ParticipantData {
 		})
 	}
 
+	/// Round 1 data (T1, T2) is present
+	pub fn has_round_1(&self) -> bool {
+		self.t_1.is_some() && self.t_2.is_some()
+	}
+
+	/// Round 2 data (tau_x) is present
+	pub fn has_round_2(&self) -> bool {
+		self.tau_x.is_some()
+	}
+
 	/// Check if partial_commitment match the hash
 	fn reveal(&mut self, partial_commitment: &Commitment) -> Result<(), ErrorKind> {
 		let hash = self
@@ -145,6 +145,12 @@ impl SellApi {
 			wait_for_backup1: false,
 			tag,
 			other_lock_first_done: false,
+			secondary_deposit_guidance: None,
+			secondary_fee_estimate: secondary_currency.is_fee_estimated(),
+			lock_window_sec: None,
+			other_lock_window_start: None,
+			other_lock_seen: false,
+			offer_expires_at: None,
 		};
 
 		swap.add_journal_message("Swap offer created".to_string());
@@ -399,6 +405,7 @@ impl SellApi {
 				secondary_confirmations: swap.secondary_confirmations,
 				message_exchange_time_sec: swap.message_exchange_time_sec,
 				redeem_time_sec: swap.redeem_time_sec,
+				expires_at: swap.offer_expires_at.clone(),
 			}),
 			secondary_update,
 		)
@@ -28,7 +28,9 @@ use crate::grin_util::secp::key::{PublicKey, SecretKey};
 use crate::grin_util::secp::pedersen::{Commitment, RangeProof};
 use crate::grin_util::secp::{Message as SecpMessage, Secp256k1, Signature};
 use crate::grin_util::RwLock;
-use crate::swap::fsm::state::StateId;
+use crate::swap::fsm::state::{
+	StateId, JOURNAL_CANCELLED_BY_TIMEOUT, JOURNAL_CANCELLED_OFFER_EXPIRED,
+};
 use crate::{NodeClient, Slate};
 use chrono::{DateTime, Utc};
 use std::sync::Arc;
@@ -170,6 +172,30 @@ pub struct Swap {
 	/// Flag that other party locking is confirmed. Utility flag for swap marketplace
 	#[serde(default = "default_false")]
 	pub other_lock_first_done: bool,
+	/// Last guidance for the buyer about the secondary deposit. Journal gets a new record only
+	/// when the guidance is changed.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub secondary_deposit_guidance: Option<String>,
+	/// If true, the secondary fee is estimated from the network before the redeem or the refund
+	/// transaction is built. The fee that is set by the user disables the estimation.
+	#[serde(default = "default_false")]
+	pub secondary_fee_estimate: bool,
+	/// Lock window in seconds. If it is set, the lock of the other party must be accepted by the
+	/// network within the window and confirmed within the window after that. Otherwise the trade
+	/// is cancelled before this wallet locks its funds.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub lock_window_sec: Option<u64>,
+	/// Start of the current lock window: the time when the wallet started to wait for the other
+	/// party lock, or the time when that lock was accepted by the network.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub other_lock_window_start: Option<i64>,
+	/// True if the other party lock was accepted by the network with the full amount
+	#[serde(default = "default_false")]
+	pub other_lock_seen: bool,
+	/// Time when the offer expires if it is not accepted. The offer can't be accepted later than
+	/// the end of the offer message exchange anyway.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub offer_expires_at: Option<DateTime<Utc>>,
 }
 
 fn default_false() -> bool {
@@ -404,6 +430,24 @@ impl Swap {
 		self.get_time_start() + self.message_exchange_time_sec as i64
 	}
 
+	/// Time when the offer is cancelled if it is not accepted
+	pub fn get_time_offer_expiration(&self) -> i64 {
+		let offers = self.get_time_message_offers();
+		match &self.offer_expires_at {
+			Some(t) => std::cmp::min(t.timestamp(), offers),
+			None => offers,
+		}
+	}
+
+	/// Journal message for the offer that is not accepted in time
+	pub fn offer_expired_message(&self) -> String {
+		if self.offer_expires_at.is_some() {
+			JOURNAL_CANCELLED_OFFER_EXPIRED.to_string()
+		} else {
+			JOURNAL_CANCELLED_BY_TIMEOUT.to_string()
+		}
+	}
+
 	/// When locking need to be started
 	pub fn get_time_start_lock(&self) -> i64 {
 		// We can get 5% from the total lock time. We have to post fast
@@ -488,6 +532,61 @@ impl Swap {
 			/ 10
 	}
 
+	/// Check the other party lock against the lock window. Return the reason if the window is
+	/// expired and the trade must be cancelled.
+	pub fn check_other_lock_window(
+		&mut self,
+		lock_seen: bool,
+		lock_confirmed: bool,
+	) -> Option<String> {
+		let window = match self.lock_window_sec {
+			Some(w) => w as i64,
+			None => return None,
+		};
+		if lock_confirmed {
+			return None;
+		}
+		let cur_time = get_cur_time();
+		if lock_seen && !self.other_lock_seen {
+			// Confirmation gets a new window
+			self.other_lock_seen = true;
+			self.other_lock_window_start = Some(cur_time);
+			self.add_journal_message(
+				"Lock transaction of the other party is accepted by the network".to_string(),
+			);
+			return None;
+		}
+		let start = match self.other_lock_window_start {
+			Some(t) => t,
+			None => {
+				self.other_lock_window_start = Some(cur_time);
+				return None;
+			}
+		};
+		if cur_time <= start + window {
+			return None;
+		}
+		Some(if self.other_lock_seen {
+			format!(
+				"Cancelled because the lock transaction of the other party is not confirmed within {} minutes",
+				window / 60
+			)
+		} else {
+			format!(
+				"Cancelled because the lock transaction of the other party with the full amount is not accepted by the network within {} minutes",
+				window / 60
+			)
+		})
+	}
+
+	/// End of the current lock window, None if the window is not set or not started
+	pub fn get_time_other_lock_window(&self) -> Option<i64> {
+		match (self.lock_window_sec, self.other_lock_window_start) {
+			(Some(w), Some(t)) => Some(t + w as i64),
+			_ => None,
+		}
+	}
+
 	/// Latest error message. Check has higher priority because it is normally done first
 	pub fn get_last_error(&self) -> Option<String> {
 		if self.last_check_error.is_some() {
@@ -584,6 +683,7 @@ pub fn publish_transaction<C: NodeClient>(
 	.map_err(|e| ErrorKind::UnexpectedAction(format!("slate is not valid, {}", e)))?;
 
 	node_client.post_tx(tx, fluff)?;
+	crate::tx_mirror::mirror_posted_tx(tx, None);
 	Ok(())
 }
 
@@ -17,6 +17,7 @@ use crate::grin_core::global;
 use crate::grin_util::secp::key::SecretKey;
 use crate::grin_util::{from_hex, to_hex};
 use crate::grin_util::{Mutex, RwLock};
+use crate::swap::history;
 use crate::swap::types::{Context, Currency};
 use crate::swap::Swap;
 use base64;
@@ -29,6 +30,7 @@ use std::io::{Read, Write};
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
 /// Location of the swaps states
 pub const SWAP_DEAL_SAVE_DIR: &'static str = "saved_swap_deal";
@@ -36,15 +38,23 @@ pub const SWAP_DEAL_SAVE_DIR: &'static str = "saved_swap_deal";
 pub const SWAP_DEAL_DELETED_DIR: &'static str = "deleted";
 /// Location of the marketplace not started swap trades.
 pub const SWAP_DEAL_MKT_DELETED_DIR: &'static str = "deleted_mkt";
+/// Trades that are running in the auto swap mode, they are resumed at the wallet restart
+pub const AUTOSWAP_SESSIONS_FILE: &'static str = "autoswap_sessions.json";
+/// Failed ElectrumX server is moved to the end of the list for this time
+const ELECTRUM_X_RETRY_TIME: Duration = Duration::from_secs(600);
 
 lazy_static! {
 	static ref TRADE_DEALS_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
 	static ref ELECTRUM_X_URI: RwLock<Option<BTreeMap<String, String>>> = RwLock::new( Some(BTreeMap::new()));
+	// Last connection failure time for the ElectrumX servers
+	static ref ELECTRUM_X_FAILURES: RwLock<HashMap<String, Instant>> = RwLock::new(HashMap::new());
 	static ref ETH_SWAP_CONTRACT_ADDR: RwLock<Option<String>> = RwLock::new(None);
 	static ref ERC20_SWAP_CONTRACT_ADDR: RwLock<Option<String>> = RwLock::new(None);
 	static ref ETH_INFURA_PROJECTID: RwLock<Option<String>> = RwLock::new(None);
 	// Locks for the swap reads. Note, all instances are in the memory, we don't expect too many of them
 	static ref SWAP_LOCKS: RwLock<HashMap< String, Arc<Mutex<()>>>> = RwLock::new(HashMap::new());
+	// Lock for the auto swap sessions file, the auto swap threads update it concurrently
+	static ref AUTOSWAP_SESSIONS_LOCK: Mutex<()> = Mutex::new(());
 }
 
 /// Init for file storage for saving swap deals
@@ -65,6 +75,9 @@ pub fn init_swap_trade_backend(
 	let deleted_mkts = stored_swap_deal_path.join(SWAP_DEAL_MKT_DELETED_DIR);
 	fs::create_dir_all(&deleted_mkts).expect("Could not create swap deal storage directory!");
 
+	let history = stored_swap_deal_path.join(history::SWAP_HISTORY_DIR);
+	fs::create_dir_all(&history).expect("Could not create swap deal storage directory!");
+
 	TRADE_DEALS_PATH.write().replace(stored_swap_deal_path);
 	if electrumx_config_uri.is_some() {
 		ELECTRUM_X_URI
@@ -91,33 +104,82 @@ pub fn init_swap_trade_backend(
 	}
 }
 
-/// Get ElextrumX URL.
+/// Register the ElectrumX server connection failure. The server will be selected after the
+/// healthy ones until ELECTRUM_X_RETRY_TIME is passed.
+pub fn electrumx_failed(uri: &str) {
+	ELECTRUM_X_FAILURES
+		.write()
+		.insert(uri.to_string(), Instant::now());
+}
+
+/// Register the successful ElectrumX server connection
+pub fn electrumx_ok(uri: &str) {
+	ELECTRUM_X_FAILURES.write().remove(uri);
+}
+
+/// Check if the ElectrumX server failed recently
+pub fn is_electrumx_failed(uri: &str) -> bool {
+	ELECTRUM_X_FAILURES
+		.read()
+		.get(uri)
+		.map(|t| t.elapsed() < ELECTRUM_X_RETRY_TIME)
+		.unwrap_or(false)
+}
+
+/// ElectrumX servers from the config for the currency and the current network, in the
+/// config order. Keys are <coin>_[main|test]_<N>, N starts from 1 and defines the order.
+pub fn get_electrumx_servers(currency: &Currency) -> Vec<String> {
+	let network = if global::is_mainnet() { "main" } else { "test" };
+	let prefix = format!("{}_{}_", currency.to_string().to_lowercase(), network);
+
+	let map = ELECTRUM_X_URI.read();
+	let mut servers: Vec<(u32, String)> = map
+		.as_ref()
+		.unwrap()
+		.iter()
+		.filter_map(|(k, v)| {
+			k.strip_prefix(&prefix)
+				.and_then(|n| n.parse::<u32>().ok())
+				.map(|n| (n, v.clone()))
+		})
+		.collect();
+	servers.sort_by_key(|(n, _)| *n);
+
+	let mut res: Vec<String> = vec![];
+	for (_, uri) in servers {
+		if !res.contains(&uri) {
+			res.push(uri);
+		}
+	}
+	res
+}
+
+/// Get ElextrumX URL. The URIs from the swap are used as they are. Otherwise the primary and
+/// secondary are the first healthy servers from the config, the failed ones are rotated to the end.
 pub fn get_electrumx_uri(
 	currency: &Currency,
 	swap_electrum_node_uri1: &Option<String>,
 	swap_electrum_node_uri2: &Option<String>,
 ) -> Result<(String, String), ErrorKind> {
-	let network = if global::is_mainnet() { "main" } else { "test" };
-
-	let map = ELECTRUM_X_URI.read();
-	let sec_coin = currency.to_string().to_lowercase();
+	let (mut servers, failed): (Vec<String>, Vec<String>) = get_electrumx_servers(currency)
+		.into_iter()
+		.partition(|uri| !is_electrumx_failed(uri));
+	servers.extend(failed);
 
 	// unwrap_or/unwrap_or_else  doesn't work because we don't wanle evaluate else part and else part can report error.
 	let uri1 = match swap_electrum_node_uri1.clone() {
 		Some(s) => s,
-		None => map
-			.as_ref()
-			.unwrap()
-			.get(&format!("{}_{}_1", sec_coin, network))
+		None => servers
+			.first()
 			.ok_or(ErrorKind::UndefinedElectrumXURI("primary".to_string()))?
 			.clone(),
 	};
 	let uri2 = match swap_electrum_node_uri2.clone() {
 		Some(s) => s,
-		None => map
-			.as_ref()
-			.unwrap()
-			.get(&format!("{}_{}_2", sec_coin, network))
+		None => servers
+			.iter()
+			.find(|uri| **uri != uri1)
+			.or(servers.first())
 			.ok_or(ErrorKind::UndefinedElectrumXURI("secondary".to_string()))?
 			.clone(),
 	};
@@ -173,6 +235,19 @@ pub fn get_eth_infura_projectid(
 	}
 }
 
+/// Directory of the swap trades
+pub fn get_swap_storage_path() -> Result<PathBuf, ErrorKind> {
+	TRADE_DEALS_PATH.read().clone().ok_or(ErrorKind::Generic(
+		"Swap trades storage is not initialized".to_string(),
+	))
+}
+
+/// True if the swap trades storage is initialized. Wallets that are used without the command
+/// line or the swap listener don't have it.
+pub fn is_trade_backend_initialized() -> bool {
+	TRADE_DEALS_PATH.read().is_some()
+}
+
 /// List available swap trades.
 pub fn list_swap_trades() -> Result<Vec<String>, ErrorKind> {
 	let mut result: Vec<String> = Vec::new();
@@ -189,6 +264,83 @@ pub fn list_swap_trades() -> Result<Vec<String>, ErrorKind> {
 	Ok(result)
 }
 
+/// Swap files that are not needed any more: deleted trades and the leftovers of the interrupted
+/// writes (only if the trade file exists). Only files modified before 'older_than' are listed.
+/// Returns file path and size.
+pub fn list_swap_garbage(older_than: SystemTime) -> Result<Vec<(PathBuf, u64)>, ErrorKind> {
+	let trades_path = match TRADE_DEALS_PATH.read().clone() {
+		Some(p) => p,
+		None => return Ok(vec![]), // swap storage is not initialized, nothing to collect
+	};
+
+	let mut result = vec![];
+	let mut collect = |dir: &Path, is_garbage: &dyn Fn(&str) -> bool| -> Result<(), ErrorKind> {
+		for entry in fs::read_dir(dir)? {
+			let entry = entry?;
+			let metadata = entry.metadata()?;
+			if !metadata.is_file() || metadata.modified()? > older_than {
+				continue;
+			}
+			if let Some(name) = entry.file_name().to_str() {
+				if is_garbage(name) {
+					result.push((entry.path(), metadata.len()));
+				}
+			}
+		}
+		Ok(())
+	};
+
+	collect(&trades_path, &|name: &str| {
+		name.ends_with(".bak")
+			&& name
+				.split(".swap_")
+				.next()
+				.map(|id| trades_path.join(format!("{}.swap", id)).exists())
+				.unwrap_or(false)
+	})?;
+	for del_dir in &[SWAP_DEAL_DELETED_DIR, SWAP_DEAL_MKT_DELETED_DIR] {
+		let dir = trades_path.join(del_dir);
+		if dir.exists() {
+			collect(&dir, &|name: &str| name.ends_with(".swap.del"))?;
+		}
+	}
+	Ok(result)
+}
+
+/// Write the history records of the deleted finished trades that are not archived yet.
+/// Return number of the written records.
+pub fn archive_deleted_trades(dec_key: &SecretKey) -> Result<usize, ErrorKind> {
+	let trades_path = get_swap_storage_path()?;
+	let mut archived = 0;
+	for del_dir in &[SWAP_DEAL_DELETED_DIR, SWAP_DEAL_MKT_DELETED_DIR] {
+		let dir = trades_path.join(del_dir);
+		if !dir.exists() {
+			continue;
+		}
+		for entry in fs::read_dir(&dir)? {
+			let path = entry?.path();
+			let is_deleted_trade = path
+				.file_name()
+				.and_then(|n| n.to_str())
+				.map(|n| n.ends_with(".swap.del"))
+				.unwrap_or(false);
+			if !is_deleted_trade {
+				continue;
+			}
+			match read_swap_data_from_file(&path, dec_key) {
+				Ok((_context, swap)) => {
+					if history::archive_swap_trade(&swap, dec_key)? {
+						archived += 1;
+					}
+				}
+				// Deleted trades might be written by the older wallet versions
+				Err(e) => warn!("Unable to read deleted swap {}, {}", path.display(), e),
+			}
+		}
+	}
+	Ok(archived)
+}
+
 /// Caller suppose to lock the swap object first before call other swap related functions.
 pub fn get_swap_lock(swap_id: &String) -> Arc<Mutex<()>> {
 	let mut swap_lock_hash = SWAP_LOCKS.write();
@@ -223,6 +375,8 @@ pub fn delete_swap_trade(
 			swap_id
 		)));
 	}
+	// The trades that were finished before the history was introduced
+	history::archive_swap_trade(&swap, dec_key)?;
 
 	let target_path = TRADE_DEALS_PATH
 		.read()
@@ -424,6 +578,11 @@ pub fn store_swap_trade(
 		)
 	})?;
 
+	// The trade is stored, failed history record must not fail the swap processing
+	if let Err(e) = history::archive_swap_trade(swap, enc_key) {
+		warn!("Unable to write swap {} into the history, {}", swap_id, e);
+	}
+
 	Ok(())
 }
 
@@ -463,6 +622,12 @@ pub fn export_trade(swap_id: &str, export_file_name: &str) -> Result<(), ErrorKi
 	if !path.exists() {
 		return Err(ErrorKind::TradeNotFound(swap_id.to_string()));
 	}
+	if Path::new(export_file_name).exists() {
+		return Err(ErrorKind::IO(format!(
+			"File {} already exists, please specify another file name",
+			export_file_name
+		)));
+	}
 
 	fs::copy(path, export_file_name).map_err(|e| {
 		ErrorKind::IO(format!(
@@ -494,11 +659,111 @@ pub fn import_trade(
 
 	let (context, swap) = read_swap_data_from_file(src_path, dec_key)?;
 
+	// Restoring an old backup over the active trade can lose the funds
+	let trade_path = TRADE_DEALS_PATH
+		.read()
+		.clone()
+		.unwrap()
+		.join(format!("{}.swap", swap.id));
+	if trade_path.exists() {
+		return Err(ErrorKind::Generic(format!(
+			"Swap trade {} already exists in this wallet. Please delete it before the import",
+			swap.id
+		)));
+	}
+
 	store_swap_trade(&context, &swap, dec_key, lock)?;
 
 	Ok(format!("{}", swap.id))
 }
 
+/// Parameters of the trade that is running in the auto swap mode. The method and the
+/// destination are stored with the trade. The api secret of the other party is not stored,
+/// the trades that need it can't be resumed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AutoSwapSession {
+	/// Swap trade Id
+	pub swap_id: String,
+	/// Secondary currency fee. Satoshi per byte.
+	pub secondary_fee: Option<f32>,
+	/// File name for the messages if the trade is processed with files
+	pub message_file_name: Option<String>,
+	/// Refund address for the buyer
+	pub buyer_refund_address: Option<String>,
+	/// Secondary address
+	pub secondary_address: Option<String>,
+	/// Need to wait for the first backup
+	pub wait_for_backup1: bool,
+}
+
+fn read_autoswap_sessions(path: &Path) -> Result<Vec<AutoSwapSession>, ErrorKind> {
+	if !path.exists() {
+		return Ok(vec![]);
+	}
+	let content = fs::read_to_string(path)?;
+	serde_json::from_str(&content).map_err(|e| {
+		ErrorKind::Generic(format!(
+			"Unable to read auto swap sessions from {}, {}",
+			path.display(),
+			e
+		))
+	})
+}
+
+fn write_autoswap_sessions(path: &Path, sessions: &Vec<AutoSwapSession>) -> Result<(), ErrorKind> {
+	let content = serde_json::to_string_pretty(sessions).map_err(|e| {
+		ErrorKind::Generic(format!(
+			"Unable to convert auto swap sessions to Json, {}",
+			e
+		))
+	})?;
+	// Writing to the tmp file first, the sessions must survive the crash during the write
+	let tmp_path = path.with_extension("json.tmp");
+	{
+		let mut file = File::create(&tmp_path)?;
+		file.write_all(content.as_bytes())?;
+		file.sync_all()?;
+	}
+	fs::rename(&tmp_path, path)?;
+	Ok(())
+}
+
+fn update_autoswap_sessions<F>(f: F) -> Result<(), ErrorKind>
+where
+	F: FnOnce(&mut Vec<AutoSwapSession>),
+{
+	let path = get_swap_storage_path()?.join(AUTOSWAP_SESSIONS_FILE);
+	let _lock = AUTOSWAP_SESSIONS_LOCK.lock();
+	let mut sessions = read_autoswap_sessions(&path)?;
+	f(&mut sessions);
+	write_autoswap_sessions(&path, &sessions)
+}
+
+/// Register the trade that is started in the auto swap mode. The previous session of the
+/// same trade is replaced.
+pub fn save_autoswap_session(session: AutoSwapSession) -> Result<(), ErrorKind> {
+	update_autoswap_sessions(|sessions| {
+		sessions.retain(|s| s.swap_id != session.swap_id);
+		sessions.push(session);
+	})
+}
+
+/// The auto swap of the trade is finished or stopped by the user
+pub fn remove_autoswap_session(swap_id: &str) -> Result<(), ErrorKind> {
+	update_autoswap_sessions(|sessions| sessions.retain(|s| s.swap_id != swap_id))
+}
+
+/// Trades that were running in the auto swap mode when the wallet was stopped
+pub fn list_autoswap_sessions() -> Result<Vec<AutoSwapSession>, ErrorKind> {
+	match TRADE_DEALS_PATH.read().clone() {
+		Some(p) => {
+			let _lock = AUTOSWAP_SESSIONS_LOCK.lock();
+			read_autoswap_sessions(&p.join(AUTOSWAP_SESSIONS_FILE))
+		}
+		None => Ok(vec![]),
+	}
+}
+
 /// Encrypt and decrypt swap files
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EncryptedSwap {
@@ -593,3 +858,76 @@ impl EncryptedSwap {
 		})?)
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_electrumx_rotation() {
+		global::set_local_chain_type(global::ChainTypes::Floonet);
+		ELECTRUM_X_URI.write().replace(
+			[
+				("zcash_test_2", "zcash2:1"),
+				("zcash_test_10", "zcash10:1"),
+				("zcash_test_1", "zcash1:1"),
+				("zcash_test_3", "zcash1:1"),
+				("zcash_main_1", "zcash_main:1"),
+			]
+			.iter()
+			.map(|i| (i.0.to_string(), i.1.to_string()))
+			.collect(),
+		);
+
+		let currency = Currency::ZCash;
+		assert_eq!(
+			get_electrumx_servers(&currency),
+			vec!["zcash1:1", "zcash2:1", "zcash10:1"]
+		);
+		assert_eq!(
+			get_electrumx_uri(&currency, &None, &None).unwrap(),
+			("zcash1:1".to_string(), "zcash2:1".to_string())
+		);
+
+		electrumx_failed("zcash1:1");
+		assert_eq!(
+			get_electrumx_uri(&currency, &None, &None).unwrap(),
+			("zcash2:1".to_string(), "zcash10:1".to_string())
+		);
+		assert_eq!(
+			get_electrumx_uri(&currency, &Some("my:1".to_string()), &None).unwrap(),
+			("my:1".to_string(), "zcash2:1".to_string())
+		);
+
+		electrumx_ok("zcash1:1");
+		assert_eq!(
+			get_electrumx_uri(&currency, &None, &None).unwrap(),
+			("zcash1:1".to_string(), "zcash2:1".to_string())
+		);
+	}
+
+	#[test]
+	fn test_autoswap_sessions_file() {
+		let dir =
+			std::env::temp_dir().join(format!("autoswap_sessions_{}", thread_rng().gen::<u64>()));
+		fs::create_dir_all(&dir).unwrap();
+		let path = dir.join(AUTOSWAP_SESSIONS_FILE);
+		assert!(read_autoswap_sessions(&path).unwrap().is_empty());
+
+		let sessions = vec![AutoSwapSession {
+			swap_id: "7b4c2b4f-5a7a-4a3e-9b1e-3c6a1f0a2d11".to_string(),
+			secondary_fee: Some(12.5),
+			message_file_name: None,
+			buyer_refund_address: Some("mjdcskZHLKdZVFLgS5wa2WqaqXwNs2uxYn".to_string()),
+			secondary_address: None,
+			wait_for_backup1: true,
+		}];
+		write_autoswap_sessions(&path, &sessions).unwrap();
+		assert_eq!(read_autoswap_sessions(&path).unwrap(), sessions);
+		assert!(!path.with_extension("json.tmp").exists());
+
+		fs::write(&path, "not a json").unwrap();
+		assert!(read_autoswap_sessions(&path).is_err());
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
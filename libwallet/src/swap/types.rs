@@ -317,7 +317,7 @@ impl Currency {
 					bitcoin::util::address::Payload::PubkeyHash(_)
 					| bitcoin::util::address::Payload::ScriptHash(_) => (),
 					_ => {
-						// Dash doesn't have Segwit
+						// ZCash doesn't have Segwit
 						return Err(ErrorKind::Generic(
 							"Address is not supported by ZCash".to_string(),
 						));
@@ -337,9 +337,9 @@ impl Currency {
 					bitcoin::util::address::Payload::PubkeyHash(_)
 					| bitcoin::util::address::Payload::ScriptHash(_) => (),
 					_ => {
-						// Dash doesn't have Segwit
+						// Dogecoin doesn't have Segwit
 						return Err(ErrorKind::Generic(
-							"Address is not supported by DOgecoin".to_string(),
+							"Address is not supported by Dogecoin".to_string(),
 						));
 					}
 				}
@@ -629,6 +629,15 @@ impl Currency {
 		}
 	}
 
+	/// True if the fee rate for the redeem and refund transactions can be estimated by the
+	/// ElectrumX node
+	pub fn is_fee_estimated(&self) -> bool {
+		match self {
+			Currency::Btc | Currency::Bch => true,
+			_ => false,
+		}
+	}
+
 	/// check is erc20 tokens
 	pub fn is_erc20(&self) -> bool {
 		match self {
@@ -1133,6 +1142,20 @@ impl Action {
 		}
 	}
 
+	/// Return true if the user needs to know about this action even when the auto swap is
+	/// running: the trade waits for the user or it is refunding.
+	pub fn requires_attention(&self) -> bool {
+		match &self {
+			Action::DepositSecondary { .. }
+			| Action::WaitingForTradeBackup
+			| Action::SellerPublishMwcRefundTx
+			| Action::BuyerPublishSecondaryRefundTx { .. }
+			| Action::WaitForMwcRefundUnlock { .. }
+			| Action::WaitingForBtcRefund { .. } => true,
+			_ => false,
+		}
+	}
+
 	/// Convert action to a name string
 	pub fn get_id_str(&self) -> String {
 		let res = match &self {
@@ -1528,4 +1551,33 @@ mod tests {
 			assert_eq!(btc_script.as_bytes()[i], bch_q_script.as_bytes()[i]);
 		}
 	}
+
+	#[test]
+	fn test_dash_doge_address_parsers() {
+		global::set_local_chain_type(ChainTypes::Floonet);
+
+		// All addresses have the same public key hash
+		let btc_address = "mz73pyxw6hpnyb8HHnPrTe5DikC2xYrfPX".to_string();
+		let dash_address = "yeuYP8cHgwGChAAnzx46XGy2yNfPd9fUeA".to_string();
+		let doge_address = "nnnFoCZWX4kYcTQTLd4VRtcnykhvU63j6s".to_string();
+
+		assert!(Currency::Dash.validate_address(&dash_address).is_ok());
+		assert!(Currency::Doge.validate_address(&doge_address).is_ok());
+		assert!(Currency::Dash.validate_address(&doge_address).is_err());
+		assert!(Currency::Doge.validate_address(&dash_address).is_err());
+
+		let btc_script = Currency::Btc.address_2_script_pubkey(&btc_address).unwrap();
+		assert_eq!(
+			Currency::Dash
+				.address_2_script_pubkey(&dash_address)
+				.unwrap(),
+			btc_script
+		);
+		assert_eq!(
+			Currency::Doge
+				.address_2_script_pubkey(&doge_address)
+				.unwrap(),
+			btc_script
+		);
+	}
 }
@@ -0,0 +1,220 @@
+// Copyright 2020 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Transaction hooks. External commands that can inspect the transaction before the outputs are
+//! locked, before it is finalized and after it is posted. The transaction data is written
+//! into the command stdin as JSON. Non zero exit code rejects the transaction, the command
+//! output is reported as a reason.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::core::hash::Hashed;
+use crate::grin_core::core::Transaction;
+use crate::Slate;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::RwLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Hook command has this time to respond, otherwise the transaction is rejected
+const HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Commands for the transaction hooks. None - hook is not used
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TxHooks {
+	/// Called before the outputs are locked for the send transaction
+	pub pre_lock: Option<String>,
+	/// Called before the transaction is finalized
+	pub pre_finalize: Option<String>,
+	/// Called after the transaction is posted. It can't reject the transaction, errors are logged
+	pub post_post: Option<String>,
+}
+
+lazy_static! {
+	/// Transaction hooks commands
+	static ref TX_HOOKS: RwLock<TxHooks> = RwLock::new(TxHooks::default());
+}
+
+/// Set from config the transaction hooks commands
+pub fn set_tx_hooks(hooks: TxHooks) {
+	*TX_HOOKS.write().unwrap() = hooks;
+}
+
+/// Read the transaction hooks commands
+pub fn get_tx_hooks() -> TxHooks {
+	TX_HOOKS.read().unwrap().clone()
+}
+
+/// Transaction data that is passed to the hook command
+#[derive(Serialize, Debug, Clone)]
+pub struct TxHookData {
+	/// Hook name: pre_lock, pre_finalize or post_post
+	pub hook: String,
+	/// Slate Id, None for post_post
+	pub slate_id: Option<String>,
+	/// Transaction amount, nanoMWC. 0 for post_post
+	pub amount: u64,
+	/// Transaction fee, nanoMWC
+	pub fee: u64,
+	/// Destination address if it is known
+	pub destination: Option<String>,
+	/// Number of transaction inputs
+	pub num_inputs: usize,
+	/// Number of transaction outputs
+	pub num_outputs: usize,
+	/// Transaction hash, only for post_post
+	pub tx_hash: Option<String>,
+}
+
+impl TxHookData {
+	fn from_slate(hook: &str, slate: &Slate, destination: Option<String>) -> Self {
+		TxHookData {
+			hook: hook.to_string(),
+			slate_id: Some(slate.id.to_string()),
+			amount: slate.amount,
+			fee: slate.fee,
+			destination,
+			num_inputs: slate.tx.inputs().len(),
+			num_outputs: slate.tx.outputs().len(),
+			tx_hash: None,
+		}
+	}
+}
+
+/// Run pre_lock hook for the send slate
+pub fn run_pre_lock(slate: &Slate, destination: Option<String>) -> Result<(), Error> {
+	match get_tx_hooks().pre_lock {
+		Some(cmd) => run_hook(
+			&cmd,
+			&TxHookData::from_slate("pre_lock", slate, destination),
+		),
+		None => Ok(()),
+	}
+}
+
+/// Run pre_finalize hook for the slate
+pub fn run_pre_finalize(slate: &Slate, destination: Option<String>) -> Result<(), Error> {
+	match get_tx_hooks().pre_finalize {
+		Some(cmd) => run_hook(
+			&cmd,
+			&TxHookData::from_slate("pre_finalize", slate, destination),
+		),
+		None => Ok(()),
+	}
+}
+
+/// Run post_post hook for the posted transaction. The transaction is already posted, so
+/// the hook failure is only logged.
+pub fn run_post_post(tx: &Transaction) {
+	if let Some(cmd) = get_tx_hooks().post_post {
+		let data = TxHookData {
+			hook: "post_post".to_string(),
+			slate_id: None,
+			amount: 0,
+			fee: tx.fee(),
+			destination: None,
+			num_inputs: tx.inputs().len(),
+			num_outputs: tx.outputs().len(),
+			tx_hash: Some(tx.hash().to_hex()),
+		};
+		if let Err(e) = run_hook(&cmd, &data) {
+			warn!("{}", e);
+		}
+	}
+}
+
+fn run_hook(cmd: &str, data: &TxHookData) -> Result<(), Error> {
+	let reject =
+		|reason: String| -> Error { ErrorKind::TxHookRejected(data.hook.clone(), reason).into() };
+
+	let input = serde_json::to_string(data)
+		.map_err(|e| reject(format!("Unable to serialize transaction data, {}", e)))?;
+
+	let mut child = Command::new(cmd)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.map_err(|e| reject(format!("Unable to start {}, {}", cmd, e)))?;
+
+	if let Some(mut stdin) = child.stdin.take() {
+		stdin
+			.write_all(input.as_bytes())
+			.map_err(|e| reject(format!("Unable to write into {} stdin, {}", cmd, e)))?;
+	}
+
+	let start = Instant::now();
+	let status = loop {
+		match child.try_wait() {
+			Ok(Some(status)) => break status,
+			Ok(None) => {
+				if start.elapsed() > HOOK_TIMEOUT {
+					let _ = child.kill();
+					return Err(reject(format!("{} is not responding", cmd)));
+				}
+				thread::sleep(Duration::from_millis(100));
+			}
+			Err(e) => return Err(reject(format!("Unable to run {}, {}", cmd, e))),
+		}
+	};
+
+	if status.success() {
+		return Ok(());
+	}
+
+	let mut output = String::new();
+	if let Some(mut stdout) = child.stdout.take() {
+		let _ = stdout.read_to_string(&mut output);
+	}
+	if let Some(mut stderr) = child.stderr.take() {
+		let _ = stderr.read_to_string(&mut output);
+	}
+	let output = output.trim();
+	Err(reject(if output.is_empty() {
+		format!("{} exit with {}", cmd, status)
+	} else {
+		output.to_string()
+	}))
+}
+
+#[cfg(all(test, unix))]
+mod test {
+	use super::*;
+
+	fn test_data() -> TxHookData {
+		TxHookData {
+			hook: "pre_lock".to_string(),
+			slate_id: None,
+			amount: 1_000_000_000,
+			fee: 8_000_000,
+			destination: None,
+			num_inputs: 1,
+			num_outputs: 2,
+			tx_hash: None,
+		}
+	}
+
+	#[test]
+	fn test_run_hook() {
+		assert!(run_hook("true", &test_data()).is_ok());
+		match run_hook("false", &test_data()) {
+			Err(e) => match e.kind() {
+				ErrorKind::TxHookRejected(hook, _) => assert_eq!(hook, "pre_lock"),
+				_ => panic!("unexpected error {}", e),
+			},
+			Ok(_) => panic!("hook exit code is ignored"),
+		}
+		assert!(run_hook("/non/existing/hook", &test_data()).is_err());
+	}
+}
@@ -0,0 +1,425 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Import of the transaction history that was recorded outside of this wallet, for example by
+//! the recovered mwc713 wallet or the GUI wallet export. The restore from seed finds the outputs
+//! only, the imported records bring back the history. The field names follow the tx log entry,
+//! so the Json of the wallet transactions can be imported as is. Other column or key names
+//! are mapped to the fields.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::libtx::secp_ser;
+use crate::grin_util::secp::pedersen;
+use crate::types::TxLogEntryType;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Imported transaction record
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TxImportRecord {
+	/// Slate transaction id
+	#[serde(default)]
+	pub tx_slate_id: Option<Uuid>,
+	/// Transaction type
+	pub tx_type: TxLogEntryType,
+	/// Address of the other party
+	#[serde(default)]
+	pub address: Option<String>,
+	/// Creation time
+	pub creation_ts: DateTime<Utc>,
+	/// Confirmation time
+	#[serde(default)]
+	pub confirmation_ts: Option<DateTime<Utc>>,
+	/// Whether the transaction is confirmed. Default is true
+	#[serde(default = "TxImportRecord::default_confirmed")]
+	pub confirmed: bool,
+	/// Height of the confirmation block, 0 if unknown
+	#[serde(with = "secp_ser::string_or_u64", default)]
+	pub output_height: u64,
+	/// Amount credited, nanoMWC
+	#[serde(with = "secp_ser::string_or_u64", default)]
+	pub amount_credited: u64,
+	/// Amount debited, nanoMWC
+	#[serde(with = "secp_ser::string_or_u64", default)]
+	pub amount_debited: u64,
+	/// Fee, nanoMWC
+	#[serde(with = "secp_ser::opt_string_or_u64", default)]
+	pub fee: Option<u64>,
+	/// Kernel excess, the transaction id on the chain
+	#[serde(with = "secp_ser::option_commitment_serde", default)]
+	pub kernel_excess: Option<pedersen::Commitment>,
+	/// Local label
+	#[serde(default)]
+	pub label: Option<String>,
+	/// Local memo
+	#[serde(default)]
+	pub memo: Option<String>,
+}
+
+impl TxImportRecord {
+	fn default_confirmed() -> bool {
+		true
+	}
+}
+
+/// Record that was not imported
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TxImportSkipped {
+	/// Position of the record in the import, starting from 1
+	pub record: usize,
+	/// Slate transaction id of the record
+	pub tx_slate_id: Option<Uuid>,
+	/// Wallet transaction with the same slate id or kernel, None if it is a duplicate of
+	/// another imported record or the record is invalid
+	pub existing_tx_id: Option<u32>,
+	/// Why the record was skipped
+	pub reason: String,
+}
+
+/// Result of the transactions import
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TxImportReport {
+	/// Ids of the created transactions. Empty for the dry run.
+	pub imported: Vec<u32>,
+	/// Number of the records that were (or would be for the dry run) imported
+	pub imported_count: usize,
+	/// Records that were not imported
+	pub skipped: Vec<TxImportSkipped>,
+}
+
+/// Format of the imported file
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxImportFormat {
+	/// Comma separated values with the header row
+	Csv,
+	/// Json array of the records
+	Json,
+}
+
+impl TxImportFormat {
+	/// Format from the file extension, Json for anything but '.csv'
+	pub fn from_file_name(file_name: &str) -> TxImportFormat {
+		if file_name.to_lowercase().ends_with(".csv") {
+			TxImportFormat::Csv
+		} else {
+			TxImportFormat::Json
+		}
+	}
+}
+
+/// Parse the imported file. `columns` maps the record fields to the column or key names of
+/// the file, like 'creation_ts' -> 'Date'. The unmapped fields use the field names and the
+/// common aliases.
+pub fn parse_tx_import(
+	data: &str,
+	format: &TxImportFormat,
+	columns: &HashMap<String, String>,
+) -> Result<Vec<TxImportRecord>, Error> {
+	let rows = match format {
+		TxImportFormat::Csv => csv_rows(data)?,
+		TxImportFormat::Json => {
+			let value: Value = serde_json::from_str(data)
+				.map_err(|e| ErrorKind::TxImport(format!("Invalid Json, {}", e)))?;
+			match value {
+				Value::Array(rows) => rows,
+				_ => {
+					return Err(ErrorKind::TxImport(
+						"Json array of records is expected".to_string(),
+					)
+					.into())
+				}
+			}
+		}
+	};
+	rows.into_iter()
+		.enumerate()
+		.map(|(i, row)| {
+			record_from_value(row, columns)
+				.map_err(|e| ErrorKind::TxImport(format!("Record {}: {}", i + 1, e)).into())
+		})
+		.collect()
+}
+
+/// Aliases of the field names that the other wallets use
+const FIELD_ALIASES: &[(&str, &[&str])] = &[
+	("tx_slate_id", &["slate_id", "txid", "tx_id", "uuid"]),
+	("tx_type", &["type"]),
+	("creation_ts", &["created", "date", "time", "timestamp"]),
+	("confirmation_ts", &["confirmed_at", "confirmation_time"]),
+	("output_height", &["height"]),
+	("amount_credited", &["credited"]),
+	("amount_debited", &["debited"]),
+	("kernel_excess", &["kernel", "excess"]),
+];
+
+fn record_from_value(
+	value: Value,
+	columns: &HashMap<String, String>,
+) -> Result<TxImportRecord, String> {
+	let row = match value {
+		Value::Object(row) => row,
+		_ => return Err("object is expected".to_string()),
+	};
+	// Column names are case insensitive, the empty values are missing values
+	let row: HashMap<String, Value> = row
+		.into_iter()
+		.filter(|(_, v)| !v.is_null() && v.as_str().map(|s| !s.trim().is_empty()).unwrap_or(true))
+		.map(|(k, v)| (k.trim().to_lowercase(), v))
+		.collect();
+	let field = |name: &str| -> Option<Value> {
+		if let Some(column) = columns.get(name) {
+			return row.get(&column.trim().to_lowercase()).cloned();
+		}
+		let aliases = FIELD_ALIASES
+			.iter()
+			.find(|(f, _)| *f == name)
+			.map(|(_, a)| *a)
+			.unwrap_or(&[]);
+		std::iter::once(&name)
+			.chain(aliases.iter())
+			.find_map(|n| row.get(*n).cloned())
+	};
+
+	let mut record = Map::new();
+	for name in &[
+		"tx_slate_id",
+		"address",
+		"output_height",
+		"amount_credited",
+		"amount_debited",
+		"fee",
+		"kernel_excess",
+		"label",
+		"memo",
+	] {
+		if let Some(v) = field(name) {
+			record.insert(name.to_string(), value_to_string(v));
+		}
+	}
+	let tx_type = field("tx_type").ok_or("tx_type is missing".to_string())?;
+	record.insert(
+		"tx_type".to_string(),
+		Value::String(parse_tx_type(&value_to_string(tx_type))?),
+	);
+	let creation_ts = field("creation_ts").ok_or("creation_ts is missing".to_string())?;
+	record.insert(
+		"creation_ts".to_string(),
+		Value::String(parse_ts(&value_to_string(creation_ts))?.to_rfc3339()),
+	);
+	if let Some(ts) = field("confirmation_ts") {
+		record.insert(
+			"confirmation_ts".to_string(),
+			Value::String(parse_ts(&value_to_string(ts))?.to_rfc3339()),
+		);
+	}
+	if let Some(confirmed) = field("confirmed") {
+		let confirmed = match value_to_string(confirmed).as_str() {
+			Some(c) => match c.trim().to_lowercase().as_str() {
+				"true" | "yes" | "1" => true,
+				"false" | "no" | "0" => false,
+				c => return Err(format!("invalid confirmed value '{}'", c)),
+			},
+			None => return Err("invalid confirmed value".to_string()),
+		};
+		record.insert("confirmed".to_string(), Value::Bool(confirmed));
+	}
+	serde_json::from_value(Value::Object(record)).map_err(|e| e.to_string())
+}
+
+// Numbers and flags are passed as the strings, the serde helpers accept them
+fn value_to_string(value: Value) -> Value {
+	match value {
+		Value::String(s) => Value::String(s.trim().to_string()),
+		Value::Number(n) => Value::String(n.to_string()),
+		Value::Bool(b) => Value::String(b.to_string()),
+		v => v,
+	}
+}
+
+fn parse_tx_type(value: &Value) -> Result<String, String> {
+	let s = value.as_str().ok_or("invalid tx_type".to_string())?;
+	let normalized: String = s
+		.chars()
+		.filter(|c| c.is_ascii_alphanumeric())
+		.collect::<String>()
+		.to_lowercase();
+	let tx_type = match normalized.as_str() {
+		"txreceived" | "received" | "receivedtx" | "receive" => "TxReceived",
+		"txsent" | "sent" | "senttx" | "send" => "TxSent",
+		"confirmedcoinbase" | "coinbase" => "ConfirmedCoinbase",
+		"txreceivedcancelled" | "receivedcancelled" | "receivedtxcancelled" => {
+			"TxReceivedCancelled"
+		}
+		"txsentcancelled" | "sentcancelled" | "senttxcancelled" => "TxSentCancelled",
+		_ => return Err(format!("unknown tx_type '{}'", s)),
+	};
+	Ok(tx_type.to_string())
+}
+
+fn parse_ts(value: &Value) -> Result<DateTime<Utc>, String> {
+	let s = value.as_str().ok_or("invalid time".to_string())?;
+	if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
+		return Ok(ts.with_timezone(&Utc));
+	}
+	for fmt in &["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+		if let Ok(ts) = NaiveDateTime::parse_from_str(s, fmt) {
+			return Ok(Utc.from_utc_datetime(&ts));
+		}
+	}
+	if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+		return Ok(Utc.from_utc_datetime(&date.and_hms(0, 0, 0)));
+	}
+	// Unix time in seconds
+	if let Ok(secs) = s.parse::<i64>() {
+		return Ok(Utc.timestamp(secs, 0));
+	}
+	Err(format!("invalid time '{}'", s))
+}
+
+/// Rows of the CSV data with the header row, as the Json objects with the string values
+fn csv_rows(data: &str) -> Result<Vec<Value>, Error> {
+	let mut lines = csv_records(data)?.into_iter();
+	let header = lines
+		.next()
+		.ok_or(ErrorKind::TxImport("CSV header is missing".to_string()))?;
+	let mut rows = vec![];
+	for (i, line) in lines.enumerate() {
+		if line.len() == 1 && line[0].trim().is_empty() {
+			continue;
+		}
+		if line.len() != header.len() {
+			return Err(ErrorKind::TxImport(format!(
+				"CSV row {} has {} values, the header has {} columns",
+				i + 1,
+				line.len(),
+				header.len()
+			))
+			.into());
+		}
+		let row: Map<String, Value> = header
+			.iter()
+			.cloned()
+			.zip(line.into_iter().map(Value::String))
+			.collect();
+		rows.push(Value::Object(row));
+	}
+	Ok(rows)
+}
+
+// CSV records, the quoted values can have the commas, the double quotes and the line breaks
+fn csv_records(data: &str) -> Result<Vec<Vec<String>>, Error> {
+	let mut records = vec![];
+	let mut record = vec![];
+	let mut value = String::new();
+	let mut quoted = false;
+	let mut chars = data.trim_start_matches('\u{feff}').chars().peekable();
+	while let Some(c) = chars.next() {
+		match (quoted, c) {
+			(true, '"') => {
+				if chars.peek() == Some(&'"') {
+					chars.next();
+					value.push('"');
+				} else {
+					quoted = false;
+				}
+			}
+			(true, c) => value.push(c),
+			(false, '"') => quoted = true,
+			(false, ',') => record.push(std::mem::replace(&mut value, String::new())),
+			(false, '\r') => (),
+			(false, '\n') => {
+				record.push(std::mem::replace(&mut value, String::new()));
+				records.push(std::mem::replace(&mut record, vec![]));
+			}
+			(false, c) => value.push(c),
+		}
+	}
+	if quoted {
+		return Err(ErrorKind::TxImport("CSV quoted value is not closed".to_string()).into());
+	}
+	if !value.is_empty() || !record.is_empty() {
+		record.push(value);
+		records.push(record);
+	}
+	Ok(records)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_parse_tx_import_csv() {
+		let csv = "Id,Type,Date,amount_credited,amount_debited,Fee,confirmed,label\r\n\
+			0fd2e1b7-5b24-4e6c-a5d4-0e7b5d0f4c3a,Received Tx,2021-03-12 10:20:30,2000000000,0,,yes,\"shop, order 1\"\r\n\
+			,sent,2021-03-13,0,1008000000,8000000,true,\"say \"\"hi\"\"\"\r\n";
+		let mut columns = HashMap::new();
+		columns.insert("tx_slate_id".to_string(), "Id".to_string());
+		let records = parse_tx_import(csv, &TxImportFormat::Csv, &columns).unwrap();
+		assert_eq!(records.len(), 2);
+		assert_eq!(
+			records[0].tx_slate_id,
+			Some(Uuid::parse_str("0fd2e1b7-5b24-4e6c-a5d4-0e7b5d0f4c3a").unwrap())
+		);
+		assert_eq!(records[0].tx_type, TxLogEntryType::TxReceived);
+		assert_eq!(
+			records[0].creation_ts.to_rfc3339(),
+			"2021-03-12T10:20:30+00:00"
+		);
+		assert_eq!(records[0].amount_credited, 2_000_000_000);
+		assert_eq!(records[0].fee, None);
+		assert_eq!(records[0].label, Some("shop, order 1".to_string()));
+		assert_eq!(records[1].tx_slate_id, None);
+		assert_eq!(records[1].tx_type, TxLogEntryType::TxSent);
+		assert_eq!(records[1].fee, Some(8_000_000));
+		assert_eq!(records[1].label, Some("say \"hi\"".to_string()));
+
+		// Unknown type and the broken row are reported with the record number
+		let csv = "type,creation_ts\nswap,2021-03-13\n";
+		let err = parse_tx_import(csv, &TxImportFormat::Csv, &HashMap::new()).unwrap_err();
+		assert!(format!("{}", err).contains("Record 1"));
+		let csv = "type,creation_ts\nsent\n";
+		assert!(parse_tx_import(csv, &TxImportFormat::Csv, &HashMap::new()).is_err());
+	}
+
+	#[test]
+	fn test_parse_tx_import_json() {
+		// Json of the wallet transactions, the unknown fields are ignored
+		let json = r#"[{
+			"parent_key_id": "0200000000000000000000000000000000",
+			"id": 3,
+			"tx_slate_id": null,
+			"tx_type": "TxSent",
+			"creation_ts": "2021-03-12T10:20:30.123Z",
+			"confirmation_ts": "2021-03-12T10:25:00Z",
+			"confirmed": true,
+			"output_height": 700000,
+			"amount_credited": "0",
+			"amount_debited": "1008000000",
+			"fee": "8000000",
+			"kernel_excess": "08b7e57c448db5ef25aa119dde2312c64d7ff1b890c416c6dda5ec73cbfed2edea"
+		}]"#;
+		let records = parse_tx_import(json, &TxImportFormat::Json, &HashMap::new()).unwrap();
+		assert_eq!(records.len(), 1);
+		assert_eq!(records[0].tx_type, TxLogEntryType::TxSent);
+		assert_eq!(records[0].output_height, 700_000);
+		assert_eq!(records[0].amount_debited, 1_008_000_000);
+		assert!(records[0].kernel_excess.is_some());
+		assert!(records[0].confirmation_ts.is_some());
+
+		assert!(parse_tx_import("{}", &TxImportFormat::Json, &HashMap::new()).is_err());
+	}
+}
@@ -0,0 +1,179 @@
+// Copyright 2021 The MWC Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mirror of the posted transactions. Every transaction that is posted to the node is appended
+//! as a JSON line into the external file (normally on the different host or file share) and/or
+//! to the syslog. If the wallet host is lost, the record of the in-flight transactions is still
+//! available for the reconciliation. Other sinks can be fed by the post_post transaction hook.
+
+use crate::error::{Error, ErrorKind};
+use crate::grin_core::core::hash::Hashed;
+use crate::grin_core::core::Transaction;
+use crate::grin_util::Mutex;
+use crate::grin_util::ToHex;
+use crate::slate_versions::v3::TransactionV3;
+use chrono::Utc;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::RwLock;
+use uuid::Uuid;
+
+/// Sinks for the posted transactions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TxMirrorConfig {
+	/// Append the transactions into this file
+	pub file: Option<String>,
+	/// Send the transactions to the local syslog
+	pub syslog: bool,
+}
+
+lazy_static! {
+	/// Posted transactions sinks
+	static ref TX_MIRROR: RwLock<TxMirrorConfig> = RwLock::new(TxMirrorConfig::default());
+
+	/// Posts can run from different threads, records must not be mixed
+	static ref MIRROR_WRITE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Set from config the sinks for the posted transactions
+pub fn set_tx_mirror(config: TxMirrorConfig) {
+	*TX_MIRROR.write().unwrap() = config;
+}
+
+/// Read the sinks for the posted transactions
+pub fn get_tx_mirror() -> TxMirrorConfig {
+	TX_MIRROR.read().unwrap().clone()
+}
+
+/// True if any sink is configured
+pub fn is_enabled() -> bool {
+	let config = TX_MIRROR.read().unwrap();
+	config.file.is_some() || config.syslog
+}
+
+/// Posted transaction record
+#[derive(Serialize, Debug, Clone)]
+pub struct PostedTxRecord {
+	/// Post time, RFC 3339
+	pub timestamp: String,
+	/// Slate Id if it is known
+	pub slate_id: Option<String>,
+	/// Transaction hash
+	pub tx_hash: String,
+	/// Excess of the first kernel, it is the transaction id on the chain
+	pub kernel_excess: Option<String>,
+	/// Transaction fee, nanoMWC
+	pub fee: u64,
+	/// The posted transaction
+	pub tx: TransactionV3,
+}
+
+impl PostedTxRecord {
+	fn new(tx: &Transaction, slate_id: Option<Uuid>) -> Self {
+		PostedTxRecord {
+			timestamp: Utc::now().to_rfc3339(),
+			slate_id: slate_id.map(|id| id.to_string()),
+			tx_hash: tx.hash().to_hex(),
+			kernel_excess: tx.kernels().first().map(|k| k.excess.to_hex()),
+			fee: tx.fee(),
+			tx: TransactionV3::from(tx),
+		}
+	}
+}
+
+/// Write the posted transaction into the configured sinks. The transaction is already posted,
+/// so the failures are only logged.
+pub fn mirror_posted_tx(tx: &Transaction, slate_id: Option<Uuid>) {
+	let config = get_tx_mirror();
+	if config.file.is_none() && !config.syslog {
+		return;
+	}
+
+	let record = match serde_json::to_string(&PostedTxRecord::new(tx, slate_id)) {
+		Ok(r) => r,
+		Err(e) => {
+			error!(
+				"Unable to serialize posted transaction {}, {}",
+				tx.hash(),
+				e
+			);
+			return;
+		}
+	};
+
+	let _lock = MIRROR_WRITE_LOCK.lock();
+	if let Some(file) = &config.file {
+		if let Err(e) = append_to_file(file, &record) {
+			error!("Unable to mirror posted transaction {}, {}", tx.hash(), e);
+		}
+	}
+	if config.syslog {
+		if let Err(e) = send_to_syslog(&record) {
+			error!("Unable to mirror posted transaction {}, {}", tx.hash(), e);
+		}
+	}
+}
+
+fn append_to_file(file: &str, record: &str) -> Result<(), Error> {
+	let mut f = OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(file)
+		.map_err(|e| ErrorKind::IO(format!("Unable to open {}, {}", file, e)))?;
+	f.write_all(format!("{}\n", record).as_bytes())
+		.and_then(|_| f.sync_data())
+		.map_err(|e| ErrorKind::IO(format!("Unable to write into {}, {}", file, e)))?;
+	Ok(())
+}
+
+#[cfg(unix)]
+fn send_to_syslog(record: &str) -> Result<(), Error> {
+	use std::os::unix::net::UnixDatagram;
+
+	// facility user (1), severity info (6)
+	let message = format!("<14>mwc-wallet: {}", record);
+	let socket = UnixDatagram::unbound()
+		.map_err(|e| ErrorKind::IO(format!("Unable to create syslog socket, {}", e)))?;
+	socket
+		.send_to(message.as_bytes(), "/dev/log")
+		.map_err(|e| ErrorKind::IO(format!("Unable to write into syslog, {}", e)))?;
+	Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_to_syslog(_record: &str) -> Result<(), Error> {
+	Err(ErrorKind::IO("syslog is supported on unix only".to_string()).into())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::fs;
+
+	#[test]
+	fn test_append_to_file() {
+		let dir = "test_output/tx_mirror";
+		let _ = fs::remove_dir_all(dir);
+		fs::create_dir_all(dir).unwrap();
+		let file = format!("{}/posted_txs.json", dir);
+
+		append_to_file(&file, "{\"a\":1}").unwrap();
+		append_to_file(&file, "{\"a\":2}").unwrap();
+		let content = fs::read_to_string(&file).unwrap();
+		assert_eq!(content, "{\"a\":1}\n{\"a\":2}\n");
+
+		assert!(append_to_file(&format!("{}/no/such/dir", dir), "{}").is_err());
+		let _ = fs::remove_dir_all(dir);
+	}
+}
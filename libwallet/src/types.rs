@@ -31,6 +31,7 @@ use crate::grin_util::secp::pedersen::Commitment;
 use crate::grin_util::secp::{self, pedersen, Secp256k1};
 use crate::grin_util::ToHex;
 use crate::grin_util::ZeroingString;
+use crate::internal::scan::OutputResult;
 use crate::proof::proofaddress::ProvableAddress;
 use crate::slate::ParticipantMessages;
 use crate::Slate;
@@ -42,6 +43,7 @@ use serde;
 use serde_json;
 use std::collections::HashMap;
 use std::fmt;
+use std::time::SystemTime;
 use uuid::Uuid;
 
 /// Combined trait to allow dynamic wallet dispatch
@@ -140,6 +142,16 @@ where
 		wallet_data_dir: Option<&str>,
 	) -> Result<(), Error>;
 
+	/// Sets the decoy password, it opens a separate wallet derived from the same seed.
+	/// `None` removes the decoy. The wallet password is required.
+	fn set_decoy_password(
+		&self,
+		name: Option<&str>,
+		password: ZeroingString,
+		decoy_password: Option<ZeroingString>,
+		wallet_data_dir: Option<&str>,
+	) -> Result<(), Error>;
+
 	/// deletes wallet
 	fn delete_wallet(&self, name: Option<&str>) -> Result<(), Error>;
 
@@ -221,6 +233,18 @@ where
 	/// Gets an account path for a given label
 	fn get_acct_path(&self, label: String) -> Result<Option<AcctPathMapping>, Error>;
 
+	/// Iterate over all stored contacts
+	fn contact_iter<'a>(&'a self) -> Box<dyn Iterator<Item = Contact> + 'a>;
+
+	/// Gets a contact by name
+	fn get_contact(&self, name: &str) -> Result<Option<Contact>, Error>;
+
+	/// Iterate over the address index mappings, sorted by index
+	fn address_mapping_iter<'a>(&'a self) -> Box<dyn Iterator<Item = AddressMapping> + 'a>;
+
+	/// Gets the mapping of the address index
+	fn get_address_mapping(&self, index: u32) -> Result<Option<AddressMapping>, Error>;
+
 	/// Stores a transaction
 	fn store_tx(&self, uuid: &str, tx: &Transaction) -> Result<(), Error>;
 
@@ -233,6 +257,18 @@ where
 	/// Load a txn from specified file
 	fn load_stored_tx(&self, path: &str) -> Result<Transaction, Error>;
 
+	/// List saved transaction files: name, modification time and size
+	fn stored_tx_files(&self) -> Result<Vec<(String, SystemTime, u64)>, Error>;
+
+	/// Remove the saved transaction file
+	fn delete_stored_tx_file(&self, filename: &str) -> Result<(), Error>;
+
+	/// Stores the response slate of the received transaction, so it can be sent again
+	fn store_response_slate(&self, slate: &Slate) -> Result<(), Error>;
+
+	/// Load the stored response slate by the transaction UUID
+	fn get_stored_response_slate(&self, uuid: &str) -> Result<Option<Slate>, Error>;
+
 	/// Create a new write batch to update or remove output data
 	fn batch<'a>(
 		&'a mut self,
@@ -259,6 +295,16 @@ where
 	/// last block scanned during scan or restore
 	fn last_scanned_blocks(&mut self) -> Result<Vec<ScannedBlockInfo>, Error>;
 
+	/// Checkpoint of the interrupted full scan, None if there is no such scan
+	fn scan_checkpoint(&mut self) -> Result<Option<ScanCheckpoint>, Error>;
+
+	/// Height at what all unspent outputs were validated against the chain, 0 if never
+	fn last_outputs_validation_height(&mut self) -> Result<u64, Error>;
+
+	/// Highest address index that was handed out, None if the addresses were never requested
+	/// by index
+	fn max_issued_address_index(&mut self) -> Result<Option<u32>, Error>;
+
 	/// set ethereum wallet instance
 	fn set_ethereum_wallet(&mut self, ethereum_wallet: Option<EthereumWallet>)
 		-> Result<(), Error>;
@@ -308,6 +354,18 @@ where
 		block: &Vec<ScannedBlockInfo>,
 	) -> Result<(), Error>;
 
+	/// Save the full scan checkpoint. Outputs are added to the ones that are already stored.
+	fn save_scan_checkpoint(&mut self, checkpoint: &ScanCheckpoint) -> Result<(), Error>;
+
+	/// Delete the full scan checkpoint with all its outputs
+	fn delete_scan_checkpoint(&mut self) -> Result<(), Error>;
+
+	/// Save the height at what all unspent outputs were validated against the chain
+	fn save_last_outputs_validation_height(&mut self, height: u64) -> Result<(), Error>;
+
+	/// Save the highest address index that was handed out
+	fn save_max_issued_address_index(&mut self, index: u32) -> Result<(), Error>;
+
 	/// Save the last used good node index
 	fn save_last_working_node_index(&mut self, node_index: u8) -> Result<(), Error>;
 
@@ -331,12 +389,27 @@ where
 		new_name: &str,
 	) -> Result<(), Error>;
 
+	/// delete an account label -> path mapping
+	fn delete_acct_path(&mut self, label: &str) -> Result<(), Error>;
+
 	/// save an account label -> path mapping
 	fn save_acct_path(&mut self, mapping: AcctPathMapping) -> Result<(), Error>;
 
 	/// Iterate over account names stored in backend
 	fn acct_path_iter(&self) -> Box<dyn Iterator<Item = AcctPathMapping>>;
 
+	/// save an address book contact
+	fn save_contact(&mut self, contact: Contact) -> Result<(), Error>;
+
+	/// delete an address book contact
+	fn delete_contact(&mut self, name: &str) -> Result<(), Error>;
+
+	/// save the mapping of the address index
+	fn save_address_mapping(&mut self, mapping: AddressMapping) -> Result<(), Error>;
+
+	/// delete the mapping of the address index
+	fn delete_address_mapping(&mut self, index: u32) -> Result<(), Error>;
+
 	/// Save an output as locked in the backend
 	fn lock_output(&mut self, out: &mut OutputData) -> Result<(), Error>;
 
@@ -522,6 +595,12 @@ pub struct OutputData {
 	pub is_coinbase: bool,
 	/// Optional corresponding internal entry in tx entry log
 	pub tx_log_entry: Option<u32>,
+	/// Frozen output is never selected for sending unless it is listed explicitly
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub frozen: bool,
+	/// Local tags of the output
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub tags: Vec<String>,
 }
 
 impl ser::Writeable for OutputData {
@@ -586,6 +665,16 @@ impl OutputData {
 		}
 	}
 
+	/// Height at which this output becomes spendable if it is still locked by the
+	/// coinbase maturity or the lock height. None if it is already spendable or not unspent
+	pub fn spendable_at_height(&self, current_height: u64) -> Option<u64> {
+		if self.status == OutputStatus::Unspent && self.lock_height > current_height {
+			Some(self.lock_height)
+		} else {
+			None
+		}
+	}
+
 	/// Marks this output as unspent if it was previously unconfirmed
 	pub fn mark_unspent(&mut self) {
 		if let OutputStatus::Unconfirmed = self.status {
@@ -934,6 +1023,16 @@ pub struct WalletInfo {
 	/// amount locked via previous transactions
 	#[serde(with = "secp_ser::string_or_u64")]
 	pub amount_locked: u64,
+	/// height at which the next immature coinbase becomes spendable
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub immature_spendable_at_height: Option<u64>,
+	/// MWC amount of the active swap trades where this wallet is the seller. None if there are
+	/// no such trades. The amount is a part of the spendable funds until the lock transaction is
+	/// posted, after that it is in the swap multisig output.
+	#[serde(with = "secp_ser::opt_string_or_u64")]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub amount_committed_to_swaps: Option<u64>,
 }
 
 /// Types of transactions that can be contained within a TXLog entry
@@ -963,6 +1062,49 @@ impl fmt::Display for TxLogEntryType {
 	}
 }
 
+/// Lifecycle state of the transaction. Confirmed and cancelled states follow the
+/// `confirmed` flag and the entry type, the intermediate states are set by the flows.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum TxStatus {
+	/// Entry was created by the older wallet, the state is not recorded
+	Unknown,
+	/// Slate or invoice is sent, waiting for the other party to respond
+	AwaitingCounterparty,
+	/// Slate is signed by this wallet, waiting for the other party to finalize it
+	AwaitingFinalization,
+	/// Transaction is finalized, waiting for the confirmation on the chain
+	AwaitingConfirmation,
+	/// Transaction is confirmed on the chain
+	Confirmed,
+	/// Confirmed transaction reached its target number of confirmations
+	Settled,
+	/// Transaction was cancelled
+	Cancelled,
+	/// Transaction was cancelled because it expired by TTL or age
+	Expired,
+}
+
+impl Default for TxStatus {
+	fn default() -> Self {
+		TxStatus::Unknown
+	}
+}
+
+impl fmt::Display for TxStatus {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match *self {
+			TxStatus::Unknown => write!(f, "Unknown"),
+			TxStatus::AwaitingCounterparty => write!(f, "Awaiting\nCounterparty"),
+			TxStatus::AwaitingFinalization => write!(f, "Awaiting\nFinalization"),
+			TxStatus::AwaitingConfirmation => write!(f, "Awaiting\nConfirmation"),
+			TxStatus::Confirmed => write!(f, "Confirmed"),
+			TxStatus::Settled => write!(f, "Settled"),
+			TxStatus::Cancelled => write!(f, "Cancelled"),
+			TxStatus::Expired => write!(f, "Expired"),
+		}
+	}
+}
+
 /// Optional transaction information, recorded when an event happens
 /// to add or remove funds from a wallet. One Transaction log entry
 /// maps to one or many outputs
@@ -1035,6 +1177,24 @@ pub struct TxLogEntry {
 	/// Output commits as Strings, defined for send & recieve
 	#[serde(default = "TxLogEntry::default_commits")]
 	pub output_commits: Vec<pedersen::Commitment>,
+	/// Transaction lifecycle state
+	#[serde(default)]
+	pub status: TxStatus,
+	/// Local label, it is never sent to the other party
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub label: Option<String>,
+	/// Local free-form memo, it is never sent to the other party
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub memo: Option<String>,
+	/// Number of confirmations when the transaction is settled, None - no target
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub confirmation_target: Option<u64>,
+	/// Index of the wallet address that received the payment and signed its proof
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub address_index: Option<u32>,
+	/// Entry is inferred from the outputs found at the chain during the restore
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub recovered: bool,
 }
 
 impl ser::Writeable for TxLogEntry {
@@ -1088,6 +1248,12 @@ impl TxLogEntry {
 			payment_proof: None,
 			input_commits: vec![],
 			output_commits: vec![],
+			status: TxStatus::Unknown,
+			label: None,
+			memo: None,
+			confirmation_target: None,
+			address_index: None,
+			recovered: false,
 		}
 	}
 
@@ -1141,6 +1307,12 @@ impl TxLogEntry {
 			payment_proof,
 			input_commits,
 			output_commits,
+			status: TxStatus::Unknown,
+			label: None,
+			memo: None,
+			confirmation_target: None,
+			address_index: None,
+			recovered: false,
 		}
 	}
 
@@ -1202,6 +1374,52 @@ impl TxLogEntry {
 		};
 		self.ttl_cutoff_height = None;
 	}
+
+	/// Check if the local label or memo contains the text, case insensitive
+	pub fn has_label_text(&self, text: &str) -> bool {
+		let text = text.to_lowercase();
+		self.label
+			.iter()
+			.chain(self.memo.iter())
+			.any(|s| s.to_lowercase().contains(&text))
+	}
+
+	/// Current lifecycle state. Terminal states are derived from the confirmation and
+	/// cancellation flags. Pending entries from the older wallets stay `Unknown`.
+	pub fn current_status(&self) -> TxStatus {
+		if self.confirmed {
+			return match self.status {
+				TxStatus::Settled => TxStatus::Settled,
+				_ => TxStatus::Confirmed,
+			};
+		}
+		if self.is_cancelled() {
+			return match self.status {
+				TxStatus::Expired => TxStatus::Expired,
+				_ => TxStatus::Cancelled,
+			};
+		}
+		match self.status {
+			// Confirmation was reverted by reorg or the transaction was uncancelled
+			TxStatus::Confirmed | TxStatus::Settled | TxStatus::Cancelled | TxStatus::Expired => {
+				TxStatus::AwaitingConfirmation
+			}
+			ref s => s.clone(),
+		}
+	}
+
+	/// Number of confirmations at the chain height, 0 for the unconfirmed transaction
+	pub fn confirmations_at(&self, height: u64) -> u64 {
+		if !self.confirmed || self.output_height == 0 {
+			return 0;
+		}
+		(height + 1).saturating_sub(self.output_height)
+	}
+
+	/// Update stored status to the current one. Called before the entry is saved.
+	pub fn sync_status(&mut self) {
+		self.status = self.current_status();
+	}
 }
 
 /// Payment proof information. Differs from what is sent via
@@ -1277,6 +1495,111 @@ impl ser::Readable for AcctPathMapping {
 	}
 }
 
+/// Address book record, maps the user defined name to the MQS/Tor/HTTP address
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Contact {
+	/// Contact name. Used as '@name' instead of the address
+	pub name: String,
+	/// Address of the contact wallet: MQS, Tor or HTTP(S)
+	pub address: String,
+}
+
+impl Contact {
+	/// Prefix that is used to reference contact by name instead of the address
+	pub const NAME_PREFIX: char = '@';
+
+	/// Create a new contact
+	pub fn new(name: &str, address: &str) -> Self {
+		Contact {
+			name: name.to_string(),
+			address: address.to_string(),
+		}
+	}
+
+	/// Return contact name if the destination is a contact reference '@name'
+	pub fn name_from_reference(dest: &str) -> Option<&str> {
+		if dest.starts_with(Self::NAME_PREFIX) {
+			Some(&dest[1..])
+		} else {
+			None
+		}
+	}
+
+	/// Contact name can contain letters, digits, '_', '-' and '.'
+	pub fn validate_name(name: &str) -> Result<(), Error> {
+		if name.is_empty() {
+			return Err(ErrorKind::InvalidContact("Contact name is empty".to_string()).into());
+		}
+		if !name
+			.chars()
+			.all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.')
+		{
+			return Err(ErrorKind::InvalidContact(format!(
+				"Contact name '{}' contains invalid characters. Expected letters, digits, '_', '-' or '.'",
+				name
+			))
+			.into());
+		}
+		Ok(())
+	}
+}
+
+impl ser::Writeable for Contact {
+	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let data = serde_json::to_vec(self).map_err(|e| {
+			ser::Error::CorruptedData(format!("Contact to json conversion failed, {}", e))
+		})?;
+		if data.len() > ser::READ_CHUNK_LIMIT {
+			return Err(ser::Error::TooLargeWriteErr(format!(
+				"Contact data length is {}",
+				data.len()
+			)));
+		}
+		writer.write_bytes(&data)
+	}
+}
+
+impl ser::Readable for Contact {
+	fn read<R: ser::Reader>(reader: &mut R) -> Result<Contact, ser::Error> {
+		let data = reader.read_bytes_len_prefix()?;
+		serde_json::from_slice(&data[..]).map_err(|e| {
+			ser::Error::CorruptedData(format!("json to Contact conversion failed, {}", e))
+		})
+	}
+}
+
+/// Booking of the payments received on the address index. The received transaction is put
+/// into the account and gets the label.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AddressMapping {
+	/// Address derivation index
+	pub index: u32,
+	/// Account that receives the payments, None - the receive account
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub account: Option<String>,
+	/// Label of the received transactions
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub label: Option<String>,
+}
+
+impl ser::Writeable for AddressMapping {
+	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let data = serde_json::to_vec(self).map_err(|e| {
+			ser::Error::CorruptedData(format!("AddressMapping to json conversion failed, {}", e))
+		})?;
+		writer.write_bytes(&data)
+	}
+}
+
+impl ser::Readable for AddressMapping {
+	fn read<R: ser::Reader>(reader: &mut R) -> Result<AddressMapping, ser::Error> {
+		let data = reader.read_bytes_len_prefix()?;
+		serde_json::from_slice(&data[..]).map_err(|e| {
+			ser::Error::CorruptedData(format!("json to AddressMapping conversion failed, {}", e))
+		})
+	}
+}
+
 /// Store details of the last scanned block
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ScannedBlockInfo {
@@ -1324,6 +1647,46 @@ impl ser::Readable for ScannedBlockInfo {
 	}
 }
 
+/// Checkpoint of the full scan. Chain outputs up to last_pmmr_index are already identified,
+/// so the interrupted scan can continue from the next PMMR index instead of starting over.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+	/// First height of the scan
+	pub start_height: u64,
+	/// Last height of the scan, it is the chain tip when the scan was started
+	pub end_height: u64,
+	/// Hash of the end_height block. If it is not on the chain any more, checkpoint is invalid
+	pub end_hash: String,
+	/// Last PMMR index that is processed
+	pub last_pmmr_index: u64,
+	/// Height of the last processed output
+	pub last_height: u64,
+	/// Wallet outputs that are found before last_pmmr_index. Stored as separate records.
+	#[serde(skip)]
+	pub outputs: Vec<OutputResult>,
+	/// Self spend candidates that are found before last_pmmr_index. Stored as separate records.
+	#[serde(skip)]
+	pub self_spend_candidates: Vec<OutputResult>,
+}
+
+impl ser::Writeable for ScanCheckpoint {
+	fn write<W: ser::Writer>(&self, writer: &mut W) -> Result<(), ser::Error> {
+		let data = serde_json::to_vec(self).map_err(|e| {
+			ser::Error::CorruptedData(format!("ScanCheckpoint to json conversion failed, {}", e))
+		})?;
+		writer.write_bytes(&data)
+	}
+}
+
+impl ser::Readable for ScanCheckpoint {
+	fn read<R: ser::Reader>(reader: &mut R) -> Result<ScanCheckpoint, ser::Error> {
+		let data = reader.read_bytes_len_prefix()?;
+		serde_json::from_slice(&data[..]).map_err(|e| {
+			ser::Error::CorruptedData(format!("json to ScanCheckpoint conversion failed, {}", e))
+		})
+	}
+}
+
 /// Wrapper for reward output and kernel used when building a coinbase for a mining node.
 /// Note: Not serializable, must be converted to necesssary "versioned" representation
 /// before serializing to json to ensure compatibility with mining node.
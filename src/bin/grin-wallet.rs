@@ -22,6 +22,7 @@ extern crate grin_wallet_util;
 extern crate log;
 use crate::config::ConfigError;
 use crate::core::global;
+use crate::util::file::get_first_line;
 use crate::util::init_logger;
 use clap::App;
 use grin_wallet_config as config;
@@ -31,9 +32,11 @@ use grin_wallet_util::grin_util as util;
 use std::env;
 use std::path::PathBuf;
 
-use grin_wallet_libwallet::internal::selection;
+use grin_wallet_api::apply_runtime_config;
 use grin_wallet_config::parse_node_address_string;
+use grin_wallet_controller::embedded_node;
 use grin_wallet_libwallet::proof::proofaddress;
+use grin_wallet_libwallet::proxy;
 use mwc_wallet::cmd;
 
 // include build information
@@ -110,6 +113,8 @@ fn real_main() -> i32 {
 			}
 			create_path = true;
 		}
+		// the wallet is created from the mwc713 data, like with init
+		("migrate", Some(_)) => create_path = true,
 		_ => {}
 	}
 
@@ -147,6 +152,38 @@ fn real_main() -> i32 {
 
 	log_build_info();
 
+	let config_chain_type = config
+		.members
+		.as_ref()
+		.unwrap()
+		.wallet
+		.chain_type
+		.clone()
+		.unwrap_or(global::ChainTypes::Mainnet);
+	if (args.is_present("floonet") || args.is_present("usernet")) && config_chain_type != chain_type
+	{
+		println!(
+			"Wallet configuration at {} is created for {}, but {} is requested",
+			config.config_file_path.as_ref().unwrap().to_str().unwrap(),
+			config_chain_type.shortname(),
+			chain_type.shortname()
+		);
+		return 0;
+	}
+
+	match config.check_chain_collisions() {
+		Ok(warnings) => {
+			for w in warnings {
+				warn!("{}", w);
+				println!("WARNING: {}", w);
+			}
+		}
+		Err(e) => {
+			println!("Unable to use wallet configuration: {}", e);
+			return 0;
+		}
+	}
+
 	global::init_global_chain_type(
 		config
 			.members
@@ -159,19 +196,66 @@ fn real_main() -> i32 {
 			.clone(),
 	);
 
-	let wallet_config = config.clone().members.unwrap().wallet;
-
-	if let Some(base_fee) = &wallet_config.base_fee {
-		selection::set_base_fee(base_fee.clone());
-	}
+	let mut wallet_config = config.clone().members.unwrap().wallet;
 
 	// Default derive index is 1 to match what mwc713 has by default...
 	proofaddress::set_address_index(wallet_config.grinbox_address_index.unwrap_or(0));
 
+	if let Err(e) = proxy::set_proxy_addr(&wallet_config.proxy_addr) {
+		println!("Invalid proxy_addr at wallet configuration: {}", e);
+		return 0;
+	}
+
+	// Settings that can be changed later with config reload
+	if let Err(e) = apply_runtime_config(&config) {
+		println!("Unable to apply wallet configuration: {}", e);
+		return 0;
+	}
+
+	// Embedded node replaces the external one. The node runs with the long running modes only,
+	// the other commands connect to the node of the running wallet process.
+	let mut config = config;
+	let mut node = None;
+	if wallet_config.is_embedded_node() {
+		let res = match args.subcommand_name() {
+			Some("listen") | Some("owner_api") => {
+				embedded_node::start_embedded_node(&wallet_config).map(|n| {
+					let res = (n.api_url().to_string(), n.api_secret_path().to_string());
+					node = Some(n);
+					res
+				})
+			}
+			_ => embedded_node::embedded_node_api_secret(&wallet_config)
+				.map(|secret| (embedded_node::embedded_node_api_url(&wallet_config), secret)),
+		};
+		match res {
+			Ok((api_url, api_secret_path)) => {
+				wallet_config.check_node_api_http_addr = api_url.clone();
+				wallet_config.node_api_secret_path = Some(api_secret_path.clone());
+				let members_wallet = &mut config.members.as_mut().unwrap().wallet;
+				members_wallet.check_node_api_http_addr = api_url;
+				members_wallet.node_api_secret_path = Some(api_secret_path);
+			}
+			Err(e) => {
+				println!("Unable to start the embedded node: {}", e);
+				return 0;
+			}
+		}
+	}
+	if let Some(node) = &node {
+		println!("Waiting for the embedded node to sync the chain...");
+		node.wait_for_sync();
+	}
+
 	//parse the nodes address and put them in a vec
 	let node_list = parse_node_address_string(wallet_config.check_node_api_http_addr.clone());
-	let node_client = HTTPNodeClient::new(node_list, None)
+	let node_api_secret = get_first_line(wallet_config.node_api_secret_path.clone());
+	let node_client = HTTPNodeClient::new(node_list, node_api_secret)
 		.expect("Unable create HTTP client for mwc-node connection");
 
-	cmd::wallet_command(&args, config, node_client)
+	let res = cmd::wallet_command(&args, config, node_client);
+	if let Some(node) = node {
+		node.stop();
+	}
+	res
 }
@@ -20,8 +20,9 @@ use clap::App;
 use grin_wallet_api::Owner;
 use grin_wallet_config::{MQSConfig, TorConfig, WalletConfig};
 use grin_wallet_controller::command::GlobalArgs;
-use grin_wallet_controller::Error;
+use grin_wallet_controller::{Error, ErrorKind};
 use grin_wallet_impls::DefaultWalletImpl;
+use grin_wallet_libwallet::swap::trades;
 use grin_wallet_libwallet::{NodeClient, WalletInst, WalletLCProvider};
 use grin_wallet_util::grin_keychain as keychain;
 use rustyline::completion::{Completer, FilenameCompleter, Pair};
@@ -31,12 +32,17 @@ use rustyline::hint::Hinter;
 use rustyline::validate::Validator;
 use rustyline::{CompletionType, Config, Context, EditMode, Editor, Helper, OutputStreamType};
 use std::borrow::Cow::{self, Borrowed, Owned};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
+use yaml_rust::Yaml;
 
 const COLORED_PROMPT: &'static str = "\x1b[36mmwc-wallet>\x1b[0m ";
 const PROMPT: &'static str = "mwc-wallet> ";
-//const HISTORY_PATH: &str = ".history";
+const HISTORY_PATH: &str = ".history";
+const MAX_HISTORY_SIZE: usize = 1000;
 
 // static for keeping track of current stdin buffer contents
 lazy_static! {
@@ -90,6 +96,7 @@ pub fn command_loop<L, C, K>(
 	mqs_config: &MQSConfig,
 	global_wallet_args: &GlobalArgs,
 	test_mode: bool,
+	script: Option<String>,
 ) -> Result<(), Error>
 where
 	DefaultWalletImpl<'static, C>: WalletInst<'static, L, C, K>,
@@ -97,42 +104,132 @@ where
 	C: NodeClient + 'static,
 	K: keychain::Keychain + 'static,
 {
+	let yml = load_yaml!("../bin/mwc-wallet.yml");
+	let mut app = App::from_yaml(yml).version(crate_version!());
+	let mut keychain_mask = keychain_mask;
+
+	// catch updater messages
+	// mwc updater thread is better, it will be created for None
+	let mut owner_api = Owner::new(wallet_inst, None, None);
+
+	// start the automatic updater
+	owner_api.start_updater((&keychain_mask).as_ref(), Duration::from_secs(60))?;
+
+	let mut execute_command = |owner_api: &mut Owner<L, C, K>,
+	                           keychain_mask: &mut Option<SecretKey>,
+	                           command: &str|
+	 -> Result<(), Error> {
+		// Just add 'mwc-wallet' to each command behind the scenes
+		// so we don't need to maintain a separate definition file
+		let augmented_command = format!("mwc-wallet {}", command);
+		let args = app
+			.get_matches_from_safe_borrow(augmented_command.trim().split_whitespace())
+			.map_err(|e| ErrorKind::ArgumentError(format!("{}", e)))?;
+
+		// handle opening /closing separately
+		match args.subcommand() {
+			("open", Some(_)) => {
+				let mut wallet_lock = owner_api.wallet_inst.lock();
+				let lc = wallet_lock.lc_provider().unwrap();
+
+				// mask of the previously opened wallet is not valid anymore
+				*keychain_mask = None;
+				*keychain_mask = lc.open_wallet(
+					None,
+					wallet_args::prompt_password(&global_wallet_args.password),
+					false,
+					false,
+					None,
+				)?;
+
+				let wallet_inst = lc.wallet_inst()?;
+
+				trades::init_swap_trade_backend(
+					wallet_inst.get_data_file_dir(),
+					&wallet_config.swap_electrumx_addr,
+					&wallet_config.eth_swap_contract_address,
+					&wallet_config.erc20_swap_contract_address,
+					&wallet_config.eth_infura_project_id,
+				);
+
+				if let Some(account) = args.value_of("account") {
+					wallet_inst.set_parent_key_id_by_name(account)?;
+				}
+			}
+			("close", Some(_)) => {
+				let mut wallet_lock = owner_api.wallet_inst.lock();
+				let lc = wallet_lock.lc_provider().unwrap();
+				lc.close_wallet(None)?;
+				*keychain_mask = None;
+			}
+			_ => {}
+		};
+		wallet_args::parse_and_execute(
+			owner_api,
+			keychain_mask.clone(),
+			&wallet_config,
+			&tor_config,
+			&mqs_config,
+			&global_wallet_args,
+			&args,
+			test_mode,
+			true,
+			HashMap::new(),
+		)?;
+		cli_message!("Command '{}' completed", args.subcommand().0);
+		Ok(())
+	};
+
+	// Batch mode, the first failed command stops the script
+	if let Some(script) = script {
+		let content = fs::read_to_string(&script).map_err(|e| {
+			ErrorKind::ArgumentError(format!("Unable to read the script {}, {}", script, e))
+		})?;
+		for (line_num, line) in content.lines().enumerate() {
+			let command = line.trim();
+			if command.is_empty() || command.starts_with('#') {
+				continue;
+			}
+			if command.to_lowercase() == "exit" {
+				break;
+			}
+			cli_message!("{}{}", PROMPT, command);
+			execute_command(&mut owner_api, &mut keychain_mask, command).map_err(|e| {
+				ErrorKind::GenericError(format!(
+					"Script {} failed at line {}, {}",
+					script,
+					line_num + 1,
+					e
+				))
+			})?;
+		}
+		return Ok(());
+	}
+
 	let editor = Config::builder()
 		.history_ignore_space(true)
+		.history_ignore_dups(true)
+		.max_history_size(MAX_HISTORY_SIZE)
 		.completion_type(CompletionType::List)
 		.edit_mode(EditMode::Emacs)
 		.output_stream(OutputStreamType::Stdout)
 		.build();
 
 	let mut reader = Editor::with_config(editor);
-	reader.set_helper(Some(EditorHelper(
-		FilenameCompleter::new(),
-		MatchingBracketHighlighter::new(),
-	)));
-
-	/*let history_file = self
-		.api
-		.config()
-		.get_data_path()
-		.unwrap()
-		.parent()
-		.unwrap()
-		.join(HISTORY_PATH);
+	reader.set_helper(Some(EditorHelper {
+		file_completer: FilenameCompleter::new(),
+		highlighter: MatchingBracketHighlighter::new(),
+		commands: CommandSpec::from_yaml(yml),
+		global_args: ArgSpec::from_yaml(&yml["args"]),
+		values: CompletionValues::load(&owner_api, (&keychain_mask).as_ref()),
+	}));
+
+	// History is shared by the sessions of the wallet
+	let history_file = Path::new(&wallet_config.data_file_dir).join(HISTORY_PATH);
 	if history_file.exists() {
 		let _ = reader.load_history(&history_file);
-	}*/
-
-	let yml = load_yaml!("../bin/mwc-wallet.yml");
-	let mut app = App::from_yaml(yml).version(crate_version!());
-	let mut keychain_mask = keychain_mask;
-
-	// catch updater messages
-	// mwc updater thread is better, it will be created for None
-	let mut owner_api = Owner::new(wallet_inst, None, None);
+	}
 
-	// start the automatic updater
-	owner_api.start_updater((&keychain_mask).as_ref(), Duration::from_secs(60))?;
-	let mut wallet_opened = false;
 	loop {
 		match reader.readline(PROMPT) {
 			Ok(command) => {
@@ -151,92 +248,15 @@ where
 					*contents = String::from("");
 				}
 
-				// Just add 'mwc-wallet' to each command behind the scenes
-				// so we don't need to maintain a separate definition file
-				let augmented_command = format!("mwc-wallet {}", command);
-				let args =
-					app.get_matches_from_safe_borrow(augmented_command.trim().split_whitespace());
-				let done = match args {
-					Ok(args) => {
-						// handle opening /closing separately
-						keychain_mask = match args.subcommand() {
-							("open", Some(_)) => {
-								let mut wallet_lock = owner_api.wallet_inst.lock();
-								let lc = wallet_lock.lc_provider().unwrap();
-
-								let mask = match lc.open_wallet(
-									None,
-									wallet_args::prompt_password(&global_wallet_args.password),
-									false,
-									false,
-									None,
-								) {
-									Ok(m) => {
-										wallet_opened = true;
-										m
-									}
-									Err(e) => {
-										cli_message!("{}", e);
-										None
-									}
-								};
-
-								let wallet_inst = lc.wallet_inst()?;
-
-								grin_wallet_libwallet::swap::trades::init_swap_trade_backend(
-									wallet_inst.get_data_file_dir(),
-									&wallet_config.swap_electrumx_addr,
-									&wallet_config.eth_swap_contract_address,
-									&wallet_config.erc20_swap_contract_address,
-									&wallet_config.eth_infura_project_id,
-								);
-
-								if let Some(account) = args.value_of("account") {
-									if wallet_opened {
-										let wallet_inst = lc.wallet_inst()?;
-										wallet_inst.set_parent_key_id_by_name(account)?;
-									}
-								}
-								mask
-							}
-							("close", Some(_)) => {
-								let mut wallet_lock = owner_api.wallet_inst.lock();
-								let lc = wallet_lock.lc_provider().unwrap();
-								lc.close_wallet(None)?;
-								None
-							}
-							_ => keychain_mask,
-						};
-						match wallet_args::parse_and_execute(
-							&mut owner_api,
-							keychain_mask.clone(),
-							&wallet_config,
-							&tor_config,
-							&mqs_config,
-							&global_wallet_args,
-							&args,
-							test_mode,
-							true,
-						) {
-							Ok(_) => {
-								cli_message!("Command '{}' completed", args.subcommand().0);
-								false
-							}
-							Err(err) => {
-								cli_message!("{}", err);
-								false
-							}
-						}
-					}
-					Err(err) => {
-						cli_message!("{}", err);
-						false
-					}
-				};
+				if let Err(err) = execute_command(&mut owner_api, &mut keychain_mask, &command) {
+					cli_message!("{}", err);
+				}
 				reader.add_history_entry(command);
-				if done {
-					println!();
-					break;
+				let _ = reader.save_history(&history_file);
+
+				// Accounts, trades and transactions might be changed by the command
+				if let Some(helper) = reader.helper_mut() {
+					helper.values = CompletionValues::load(&owner_api, (&keychain_mask).as_ref());
 				}
 			}
 			Err(err) => {
@@ -245,12 +265,114 @@ where
 			}
 		}
 	}
+
+	let _ = reader.save_history(&history_file);
 	Ok(())
+}
+
+/// Argument of the command, from the commands definition file
+struct ArgSpec {
+	name: String,
+	long: Option<String>,
+	short: Option<String>,
+	takes_value: bool,
+}
+
+impl ArgSpec {
+	fn from_yaml(args: &Yaml) -> Vec<ArgSpec> {
+		let mut res = vec![];
+		for arg in args.as_vec().into_iter().flatten() {
+			for (name, spec) in arg.as_hash().into_iter().flatten() {
+				res.push(ArgSpec {
+					name: name.as_str().unwrap_or_default().to_string(),
+					long: spec["long"].as_str().map(|s| format!("--{}", s)),
+					short: spec["short"].as_str().map(|s| format!("-{}", s)),
+					takes_value: spec["takes_value"].as_bool().unwrap_or(false),
+				});
+			}
+		}
+		res
+	}
+
+	fn matches(&self, word: &str) -> bool {
+		self.long.as_deref() == Some(word) || self.short.as_deref() == Some(word)
+	}
+}
+
+/// Command with its arguments, from the commands definition file
+struct CommandSpec {
+	name: String,
+	args: Vec<ArgSpec>,
+}
+
+impl CommandSpec {
+	fn from_yaml(yml: &Yaml) -> Vec<CommandSpec> {
+		let mut res = vec![];
+		for cmd in yml["subcommands"].as_vec().into_iter().flatten() {
+			for (name, spec) in cmd.as_hash().into_iter().flatten() {
+				res.push(CommandSpec {
+					name: name.as_str().unwrap_or_default().to_string(),
+					args: ArgSpec::from_yaml(&spec["args"]),
+				});
+			}
+		}
+		res
+	}
+}
 
-	//let _ = reader.save_history(&history_file);
+/// Wallet data for the completion of the argument values. Empty if the wallet is closed.
+#[derive(Default)]
+struct CompletionValues {
+	accounts: Vec<String>,
+	swap_ids: Vec<String>,
+	tx_ids: Vec<String>,
+	tx_slate_ids: Vec<String>,
 }
 
-struct EditorHelper(FilenameCompleter, MatchingBracketHighlighter);
+impl CompletionValues {
+	fn load<L, C, K>(owner_api: &Owner<L, C, K>, keychain_mask: Option<&SecretKey>) -> Self
+	where
+		L: WalletLCProvider<'static, C, K> + 'static,
+		C: NodeClient + 'static,
+		K: keychain::Keychain + 'static,
+	{
+		let mut values = CompletionValues::default();
+		if let Ok(accounts) = owner_api.accounts(keychain_mask) {
+			values.accounts = accounts.into_iter().map(|a| a.label).collect();
+		} else {
+			// wallet is not opened
+			return values;
+		}
+		if let Ok((_, txs)) = owner_api.retrieve_txs(keychain_mask, false, None, None) {
+			values.tx_ids = txs.iter().map(|t| t.id.to_string()).collect();
+			values.tx_slate_ids = txs
+				.iter()
+				.filter_map(|t| t.tx_slate_id.map(|id| id.to_string()))
+				.collect();
+		}
+		values.swap_ids = trades::list_swap_trades().unwrap_or_default();
+		values
+	}
+
+	/// Values for the argument, None if the argument value is not known to the wallet
+	fn for_arg(&self, arg: &ArgSpec) -> Option<&Vec<String>> {
+		match arg.name.as_str() {
+			"swap_id" => Some(&self.swap_ids),
+			"id" => Some(&self.tx_ids),
+			"txid" => Some(&self.tx_slate_ids),
+			name if name.contains("account") => Some(&self.accounts),
+			_ => None,
+		}
+	}
+}
+
+struct EditorHelper {
+	file_completer: FilenameCompleter,
+	highlighter: MatchingBracketHighlighter,
+	commands: Vec<CommandSpec>,
+	global_args: Vec<ArgSpec>,
+	values: CompletionValues,
+}
 
 impl Completer for EditorHelper {
 	type Candidate = Pair;
@@ -261,7 +383,55 @@ impl Completer for EditorHelper {
 		pos: usize,
 		ctx: &Context<'_>,
 	) -> std::result::Result<(usize, Vec<Pair>), ReadlineError> {
-		self.0.complete(line, pos, ctx)
+		let start = line[..pos]
+			.rfind(|c: char| c == ' ' || c == '\t')
+			.map(|i| i + 1)
+			.unwrap_or(0);
+		let word = &line[start..pos];
+		let prev_words: Vec<&str> = line[..start].split_whitespace().collect();
+
+		let command = prev_words
+			.first()
+			.and_then(|name| self.commands.iter().find(|c| c.name == *name));
+		let args = || {
+			command
+				.into_iter()
+				.flat_map(|c| c.args.iter())
+				.chain(self.global_args.iter())
+		};
+
+		let candidates: Vec<String> = match prev_words.last() {
+			// Command name
+			None => self
+				.commands
+				.iter()
+				.map(|c| c.name.clone())
+				.chain(std::iter::once("exit".to_string()))
+				.collect(),
+			Some(_) if word.starts_with("--") => args().filter_map(|a| a.long.clone()).collect(),
+			Some(prev) => {
+				match args()
+					.find(|a| a.matches(prev))
+					.filter(|a| a.takes_value)
+					.and_then(|a| self.values.for_arg(a))
+				{
+					Some(values) => values.clone(),
+					None => return self.file_completer.complete(line, pos, ctx),
+				}
+			}
+		};
+
+		let mut pairs: Vec<Pair> = candidates
+			.into_iter()
+			.filter(|c| c.starts_with(word))
+			.map(|c| Pair {
+				display: c.clone(),
+				replacement: c,
+			})
+			.collect();
+		pairs.sort_by(|a, b| a.display.cmp(&b.display));
+		pairs.dedup_by(|a, b| a.display == b.display);
+		Ok((start, pairs))
 	}
 }
 
@@ -275,7 +445,7 @@ impl Hinter for EditorHelper {
 
 impl Highlighter for EditorHelper {
 	fn highlight<'l>(&self, line: &'l str, pos: usize) -> Cow<'l, str> {
-		self.1.highlight(line, pos)
+		self.highlighter.highlight(line, pos)
 	}
 
 	fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
@@ -295,7 +465,7 @@ impl Highlighter for EditorHelper {
 	}
 
 	fn highlight_char(&self, line: &str, pos: usize) -> bool {
-		self.1.highlight_char(line, pos)
+		self.highlighter.highlight_char(line, pos)
 	}
 }
 impl Validator for EditorHelper {}
@@ -15,6 +15,8 @@
 use crate::cmd::wallet_args;
 use crate::config::GlobalWalletConfig;
 use clap::ArgMatches;
+use grin_wallet_controller::locale;
+use grin_wallet_libwallet::log_privacy::{self, LogPrivacy};
 use grin_wallet_libwallet::NodeClient;
 use semver::Version;
 use std::thread;
@@ -36,6 +38,19 @@ where
 	let tor_config = config.members.clone().unwrap().tor;
 	let mqs_config = config.members.unwrap().mqs;
 
+	if let Some(l) = &wallet_config.locale {
+		if let Err(e) = locale::set_locale(l) {
+			println!("{}, using en-US", e);
+		}
+	}
+
+	if let Some(p) = &wallet_config.log_privacy {
+		match p.parse::<LogPrivacy>() {
+			Ok(p) => log_privacy::set_log_privacy(p),
+			Err(e) => println!("{}, using off", e),
+		}
+	}
+
 	// Check the node version info, and exit with report if we're not compatible
 	let global_wallet_args = wallet_args::parse_global_args(&wallet_config, &wallet_args)
 		.expect("Can't read configuration file");
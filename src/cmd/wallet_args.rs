@@ -20,6 +20,7 @@ use crate::util::file::get_first_line;
 use crate::util::secp::key::SecretKey;
 use crate::util::{Mutex, ZeroingString};
 
+use chrono::NaiveDate;
 /// Argument parsing and error handling for wallet commands
 use clap::ArgMatches;
 use ed25519_dalek::SecretKey as DalekSecretKey;
@@ -27,17 +28,25 @@ use failure::Fail;
 use grin_wallet_api::Owner;
 use grin_wallet_config::parse_node_address_string;
 use grin_wallet_config::{MQSConfig, TorConfig, WalletConfig};
+use grin_wallet_controller::api_tokens;
 use grin_wallet_controller::command;
 use grin_wallet_controller::{Error, ErrorKind};
+use grin_wallet_impls::lifecycle::slip39;
 use grin_wallet_impls::tor::config::is_tor_address;
 use grin_wallet_impls::{DefaultLCProvider, DefaultWalletImpl};
 use grin_wallet_impls::{PathToSlateGetter, SlateGetter};
+use grin_wallet_libwallet::multisig::MultisigScheme;
+use grin_wallet_libwallet::payment_id::PaymentId;
+use grin_wallet_libwallet::payment_uri::PaymentUri;
 use grin_wallet_libwallet::proof::proofaddress;
 use grin_wallet_libwallet::proof::proofaddress::ProvableAddress;
+use grin_wallet_libwallet::tx_import::TxImportFormat;
 use grin_wallet_libwallet::Slate;
 use grin_wallet_libwallet::{
-	swap::types::Currency, IssueInvoiceTxArgs, NodeClient, SwapStartArgs, WalletInst,
-	WalletLCProvider,
+	swap::types::Currency, AddressMapping, CheckCategory, Contact, GcArgs, IssueInvoiceTxArgs,
+	NodeClient, OutputQueryArgs, OutputStatus, OutputTagArgs, SlateVersion, SwapStartArgs,
+	TxLabelArgs, TxLogEntryType, TxQueryArgs, WalletInst, WalletLCProvider,
+	GRIN_V4_TARGET_SLATE_VERSION,
 };
 use grin_wallet_util::grin_core as core;
 use grin_wallet_util::grin_core::core::amount_to_hr_string;
@@ -48,8 +57,10 @@ use linefeed::{Interface, ReadResult};
 use rpassword;
 use std::sync::Arc;
 use std::{
+	collections::HashMap,
 	convert::TryFrom,
 	path::{Path, PathBuf},
+	str::FromStr,
 };
 use uuid::Uuid;
 
@@ -117,10 +128,15 @@ where
 {
 	let interface = Arc::new(Interface::new("recover")?);
 	let mut phrase = ZeroingString::from("");
+	let mut shares: Vec<ZeroingString> = vec![];
 	interface.set_report_signal(Signal::Interrupt, true);
 	interface.set_prompt("phrase> ")?;
 	loop {
-		println!("Please enter your recovery phrase:");
+		if shares.is_empty() {
+			println!("Please enter your recovery phrase or a SLIP-39 share:");
+		} else {
+			println!("Please enter the next SLIP-39 share:");
+		}
 		let res = interface.read_line()?;
 		match res {
 			ReadResult::Eof => break,
@@ -131,16 +147,49 @@ where
 				}
 			}
 			ReadResult::Input(line) => {
-				let mut w_lock = wallet.lock();
-				let p = w_lock.lc_provider().unwrap();
-				if p.validate_mnemonic(ZeroingString::from(line.clone()))
-					.is_ok()
-				{
-					phrase = ZeroingString::from(line);
-					break;
+				if shares.is_empty() {
+					let mut w_lock = wallet.lock();
+					let p = w_lock.lc_provider().unwrap();
+					if p.validate_mnemonic(ZeroingString::from(line.clone()))
+						.is_ok()
+					{
+						phrase = ZeroingString::from(line);
+						break;
+					}
+				}
+				if slip39::is_share(&line) {
+					shares.push(ZeroingString::from(line.trim()));
+					match slip39::is_complete(&shares) {
+						Ok(true) => match slip39::recover_mnemonic(&shares) {
+							Ok(p) => {
+								phrase = p;
+								break;
+							}
+							Err(e) => {
+								println!();
+								println!("Unable to recover the seed from the shares, {}", e);
+								println!();
+								shares.clear();
+								interface.set_prompt("phrase> ")?;
+							}
+						},
+						Ok(false) => {
+							interface.set_prompt(&format!("share {}> ", shares.len() + 1))?
+						}
+						Err(e) => {
+							println!();
+							println!("The share doesn't match the previous ones, {}", e);
+							println!();
+							shares.pop();
+						}
+					}
 				} else {
 					println!();
-					println!("Recovery word phrase is invalid.");
+					if shares.is_empty() {
+						println!("Recovery word phrase is invalid.");
+					} else {
+						println!("SLIP-39 share is invalid.");
+					}
 					println!();
 					interface.set_buffer(&line)?;
 				}
@@ -386,6 +435,88 @@ where
 	})
 }
 
+pub fn parse_backup_args(args: &ArgMatches) -> Result<command::BackupArgs, ParseError> {
+	Ok(command::BackupArgs {
+		dest: args.value_of("dest").map(|d| d.to_string()),
+	})
+}
+
+pub fn parse_restore_args<L, C, K>(
+	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>,
+	args: &ArgMatches,
+) -> Result<command::RestoreArgs, ParseError>
+where
+	DefaultWalletImpl<'static, C>: WalletInst<'static, L, C, K>,
+	L: WalletLCProvider<'static, C, K>,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let src = parse_required(args, "src")?.to_string();
+	let name = args.value_of("name").map(|n| n.to_string());
+	let phrase = prompt_recovery_phrase(wallet)?;
+	if phrase.is_empty() {
+		return Err(ParseError::ArgumentError(
+			"Recovery phrase is required to decrypt the backup".to_string(),
+		));
+	}
+	Ok(command::RestoreArgs { src, name, phrase })
+}
+
+pub fn parse_migrate_args(
+	g_args: &command::GlobalArgs,
+	args: &ArgMatches,
+) -> Result<command::MigrateArgs, ParseError> {
+	let from_mwc713 = parse_required(args, "from_mwc713")?.to_string();
+	println!("Please enter the password of the mwc713 wallet");
+	let password = prompt_password(&g_args.password);
+	Ok(command::MigrateArgs {
+		from_mwc713,
+		password,
+	})
+}
+
+pub fn parse_seed_export_shamir_args(
+	g_args: &command::GlobalArgs,
+	args: &ArgMatches,
+) -> Result<command::SeedExportShamirArgs, ParseError> {
+	let shares = parse_u64(parse_required(args, "shares")?, "shares")?;
+	let threshold = parse_u64(parse_required(args, "threshold")?, "threshold")?;
+	if shares == 0 || shares > 16 {
+		let msg = format!("Number of shares must be between 1 and 16, got {}", shares);
+		return Err(ParseError::ArgumentError(msg));
+	}
+	if threshold == 0 || threshold > shares {
+		let msg = format!(
+			"Threshold must be between 1 and the number of shares, got {}",
+			threshold
+		);
+		return Err(ParseError::ArgumentError(msg));
+	}
+	let passphrase = prompt_password(&g_args.password);
+	Ok(command::SeedExportShamirArgs {
+		passphrase,
+		shares: shares as u8,
+		threshold: threshold as u8,
+	})
+}
+
+pub fn parse_decoy_args(
+	g_args: &command::GlobalArgs,
+	args: &ArgMatches,
+) -> Result<command::DecoyArgs, ParseError> {
+	let password = prompt_password(&g_args.password);
+	let decoy_password = if args.is_present("remove") {
+		None
+	} else {
+		println!("Please enter the decoy password");
+		Some(prompt_password_confirm())
+	};
+	Ok(command::DecoyArgs {
+		password,
+		decoy_password,
+	})
+}
+
 pub fn parse_listen_args(
 	config: &mut WalletConfig,
 	tor_config: &mut TorConfig,
@@ -404,6 +535,10 @@ pub fn parse_listen_args(
 	}
 	Ok(command::ListenArgs {
 		method: method.to_owned(),
+		show_queue: args.is_present("show_queue"),
+		flush_queue: args.is_present("flush_queue"),
+		status: args.is_present("status"),
+		stop: args.is_present("stop"),
 	})
 }
 
@@ -420,33 +555,192 @@ pub fn parse_owner_api_args(
 	Ok(())
 }
 
+pub fn parse_owner_api_token_args(
+	args: &ArgMatches,
+) -> Result<Option<command::ApiTokenArgs>, ParseError> {
+	if args.is_present("issue_token") {
+		let scopes = api_tokens::parse_scopes(parse_required(args, "scopes")?)
+			.map_err(|e| ParseError::ArgumentError(format!("{}", e)))?;
+		let ttl = api_tokens::parse_ttl(parse_required(args, "ttl")?)
+			.map_err(|e| ParseError::ArgumentError(format!("{}", e)))?;
+		return Ok(Some(command::ApiTokenArgs::Issue { scopes, ttl }));
+	}
+	if args.is_present("list_tokens") {
+		return Ok(Some(command::ApiTokenArgs::List));
+	}
+	if let Some(id) = args.value_of("revoke_token") {
+		return Ok(Some(command::ApiTokenArgs::Revoke(id.to_string())));
+	}
+	Ok(None)
+}
+
+pub fn parse_contact_args(args: &ArgMatches) -> Result<command::ContactArgs, ParseError> {
+	let subcommand = if args.is_present("add") {
+		command::ContactSubcommand::Add
+	} else if args.is_present("remove") {
+		command::ContactSubcommand::Remove
+	} else {
+		command::ContactSubcommand::List
+	};
+
+	let name = match subcommand {
+		command::ContactSubcommand::Add => Some(parse_required(args, "add")?.to_string()),
+		command::ContactSubcommand::Remove => Some(parse_required(args, "remove")?.to_string()),
+		command::ContactSubcommand::List => None,
+	};
+
+	let address = match subcommand {
+		command::ContactSubcommand::Add => Some(parse_required(args, "address")?.to_string()),
+		_ => None,
+	};
+
+	Ok(command::ContactArgs {
+		subcommand,
+		name,
+		address,
+	})
+}
+
+pub fn parse_tor_args(args: &ArgMatches) -> Result<command::TorArgs, ParseError> {
+	let subcommand = if args.is_present("rotate") {
+		command::TorSubcommand::Rotate
+	} else {
+		command::TorSubcommand::ListAddresses
+	};
+	Ok(command::TorArgs { subcommand })
+}
+
+// parses an address derivation index
+fn parse_address_index(arg: &str, name: &str) -> Result<u32, ParseError> {
+	let index = parse_u64(arg, name)?;
+	if index > u32::MAX as u64 {
+		let msg = format!("Address index {} is out of range", index);
+		return Err(ParseError::ArgumentError(msg));
+	}
+	Ok(index as u32)
+}
+
+pub fn parse_address_args(args: &ArgMatches) -> Result<command::AddressArgs, ParseError> {
+	let index = match args.value_of("index") {
+		Some(i) => Some(parse_address_index(i, "index")?),
+		None => None,
+	};
+	Ok(command::AddressArgs {
+		index,
+		list_used: args.is_present("list_used"),
+	})
+}
+
+pub fn parse_address_mapping_args(
+	args: &ArgMatches,
+) -> Result<command::AddressMappingArgs, ParseError> {
+	let subcommand = if let Some(i) = args.value_of("map") {
+		let account = args.value_of("account").map(|s| s.to_string());
+		let label = args.value_of("label").map(|s| s.to_string());
+		if account.is_none() && label.is_none() {
+			let msg = "--account or --label is required to map the address index".to_string();
+			return Err(ParseError::ArgumentError(msg));
+		}
+		command::AddressMappingSubcommand::Map(AddressMapping {
+			index: parse_address_index(i, "map")?,
+			account,
+			label,
+		})
+	} else if let Some(i) = args.value_of("unmap") {
+		command::AddressMappingSubcommand::Unmap(parse_address_index(i, "unmap")?)
+	} else {
+		command::AddressMappingSubcommand::List
+	};
+	Ok(command::AddressMappingArgs { subcommand })
+}
+
 pub fn parse_account_args(account_args: &ArgMatches) -> Result<command::AccountArgs, ParseError> {
 	let create = match account_args.value_of("create") {
 		None => None,
 		Some(s) => Some(s.to_owned()),
 	};
-	Ok(command::AccountArgs { create: create })
+	let rename = match account_args.value_of("rename") {
+		None => None,
+		Some(s) => {
+			let mut split = s.splitn(2, ':');
+			match (split.next(), split.next()) {
+				(Some(old), Some(new)) if !old.is_empty() && !new.is_empty() => {
+					Some((old.to_owned(), new.to_owned()))
+				}
+				_ => {
+					let msg = format!("Expected 'old_name:new_name' for rename, get '{}'", s);
+					return Err(ParseError::ArgumentError(msg));
+				}
+			}
+		}
+	};
+	let delete = match account_args.value_of("delete") {
+		None => None,
+		Some(s) => Some(s.to_owned()),
+	};
+	Ok(command::AccountArgs {
+		create: create,
+		rename,
+		delete,
+	})
+}
+
+// dest argument value with '@name' contacts already resolved into the address
+fn parse_required_dest<'a>(dest: Option<&'a str>) -> Result<&'a str, ParseError> {
+	dest.ok_or(ParseError::ArgumentError(
+		"Value for argument 'dest' is required in this context".to_string(),
+	))
+}
+
+pub fn parse_payment_id_args(args: &ArgMatches) -> Result<command::PaymentIdArgs, ParseError> {
+	let payment_id = parse_required(args, "payment_id")?;
+	if PaymentId::parse(payment_id).is_none() {
+		let msg = format!("'{}' is not a payment id name@domain", payment_id);
+		return Err(ParseError::ArgumentError(msg));
+	}
+	Ok(command::PaymentIdArgs {
+		payment_id: payment_id.to_string(),
+		address: parse_required(args, "address")?.to_string(),
+		tor: args.is_present("tor"),
+	})
 }
 
-pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseError> {
+pub fn parse_send_args(
+	args: &ArgMatches,
+	dest: Option<&str>,
+	proof_address: Option<&str>,
+) -> Result<command::SendArgs, ParseError> {
+	// payment URI, it has defaults for amount, message, method and dest
+	let uri = match args.value_of("uri") {
+		Some(u) => {
+			Some(PaymentUri::parse(u).map_err(|e| ParseError::ArgumentError(format!("{}", e)))?)
+		}
+		None => None,
+	};
+
 	// amount
-	let amount = parse_required(args, "amount")?;
-	let amount = core::core::amount_from_hr_string(amount);
-	let amount = match amount {
-		Ok(a) => a,
-		Err(e) => {
-			let msg = format!(
-				"Could not parse amount as a number with optional decimal point. e={}",
-				e
-			);
-			return Err(ParseError::ArgumentError(msg));
+	let amount = match uri.as_ref().and_then(|u| u.amount) {
+		Some(uri_amount) if !args.is_present("amount") => uri_amount,
+		_ => {
+			let amount = parse_required(args, "amount")?;
+			let amount = core::core::amount_from_hr_string(amount);
+			match amount {
+				Ok(a) => a,
+				Err(e) => {
+					let msg = format!(
+						"Could not parse amount as a number with optional decimal point. e={}",
+						e
+					);
+					return Err(ParseError::ArgumentError(msg));
+				}
+			}
 		}
 	};
 
 	// message
 	let message = match args.is_present("message") {
 		true => Some(args.value_of("message").unwrap().to_owned()),
-		false => None,
+		false => uri.as_ref().and_then(|u| u.message.clone()),
 	};
 
 	// minimum_confirmations
@@ -462,7 +756,17 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 	let late_lock = args.is_present("late_lock");
 
 	// method
-	let method = parse_required(args, "method")?;
+	let method = match &uri {
+		Some(u) if args.occurrences_of("method") == 0 => match &u.method {
+			Some(m) => m.clone(),
+			None if u.address.starts_with("http") || is_tor_address(&u.address).is_ok() => {
+				"http".to_string()
+			}
+			None => "mwcmqs".to_string(),
+		},
+		_ => parse_required(args, "method")?.to_string(),
+	};
+	let method = method.as_str();
 	let address = {
 		if method == "file" && args.is_present("proof") {
 			Some("file_proof".to_owned())
@@ -474,15 +778,19 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 	};
 
 	// dest
+	let dest = match &uri {
+		Some(u) if dest.is_none() && method != "slatepack" => Some(u.address.as_str()),
+		_ => dest,
+	};
 	let dest = {
 		if method == "self" {
-			match args.value_of("dest") {
+			match dest {
 				Some(d) => d,
 				None => "default",
 			}
 		} else {
 			if !estimate_selection_strategies && method != "slatepack" {
-				parse_required(args, "dest")?
+				parse_required_dest(dest)?
 			} else {
 				""
 			}
@@ -528,6 +836,32 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 		}
 	};
 
+	// Grin wallets can read only plain json slates from the file
+	if target_slate_version == Some(GRIN_V4_TARGET_SLATE_VERSION)
+		&& !(method == "file" || method == "slatepack")
+	{
+		let msg = format!(
+			"Grin V4 slate version {} can be used only with 'file' or 'slatepack' method",
+			GRIN_V4_TARGET_SLATE_VERSION
+		);
+		return Err(ParseError::ArgumentError(msg));
+	}
+
+	// Late locking works with compact slates only. The file based methods are switching to
+	// the slatepack, for other methods the version is negotiated with the receiver wallet.
+	let slatepack_version = SlateVersion::SP.to_numeric_version() as u16;
+	let target_slate_version = match target_slate_version {
+		None if late_lock && (method == "file" || method == "slatepack") => Some(slatepack_version),
+		Some(v) if late_lock && v < slatepack_version => {
+			let msg = format!(
+				"--late-lock requires the slatepack, slate version {} or higher. Found version {}",
+				slatepack_version, v
+			);
+			return Err(ParseError::ArgumentError(msg));
+		}
+		v => v,
+	};
+
 	let payment_proof_address = {
 		match args.is_present("request_payment_proof")
 			|| (args.is_present("proof") && method != "file")
@@ -540,7 +874,10 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 				match ProvableAddress::from_str(&proof_dest) {
 					Ok(a) => Some(a),
 					Err(_) => {
-						let addr = parse_required(args, "proof_address")?;
+						let addr = proof_address.ok_or(ParseError::ArgumentError(
+							"Value for argument 'proof_address' is required in this context"
+								.to_string(),
+						))?;
 						match ProvableAddress::from_str(&proofaddress::address_to_pubkey(
 							addr.to_string(),
 						)) {
@@ -578,7 +915,13 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 		false => None,
 	};
 
-	let slatepack_recipient: Option<ProvableAddress> = match args.value_of("slatepack_recipient") {
+	let slatepack_recipient = match &uri {
+		Some(u) if method == "slatepack" => args
+			.value_of("slatepack_recipient")
+			.or(Some(u.address.as_str())),
+		_ => args.value_of("slatepack_recipient"),
+	};
+	let slatepack_recipient: Option<ProvableAddress> = match slatepack_recipient {
 		Some(s) => {
 			let addr = ProvableAddress::from_str(s).map_err(|e| {
 				ParseError::ArgumentError(format!("Unable to parse slatepack_recipient, {}", e))
@@ -595,6 +938,26 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 		None => None,
 	};
 
+	let envelope_recipient: Option<ProvableAddress> = match args.value_of("envelope_recipient") {
+		Some(s) => {
+			let addr = ProvableAddress::from_str(s).map_err(|e| {
+				ParseError::ArgumentError(format!("Unable to parse envelope_recipient, {}", e))
+			})?;
+			if addr.tor_public_key().is_err() {
+				return Err(ParseError::ArgumentError(
+					"Expecting tor PK address as an envelope recipient value".to_string(),
+				));
+			}
+			if slatepack_recipient.is_some() || method == "slatepack" || method == "self" {
+				return Err(ParseError::ArgumentError(
+					"Slatepacks are encrypted already, envelope_recipient can't be used with slatepack_recipient, 'slatepack' or 'self' method".to_string(),
+				));
+			}
+			Some(addr)
+		}
+		None => None,
+	};
+
 	let min_fee = match args.value_of("min_fee") {
 		Some(min_fee) => match core::core::amount_from_hr_string(min_fee) {
 			Ok(min_fee) => Some(min_fee),
@@ -608,6 +971,20 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 		None => None,
 	};
 
+	// The offline wallet builds the slate, the online wallet sends it and waits for the response
+	let unsigned_bundle = args.value_of("unsigned_bundle").map(|s| s.to_string());
+	if unsigned_bundle.is_some()
+		&& (late_lock
+			|| estimate_selection_strategies
+			|| method == "file"
+			|| method == "slatepack"
+			|| method == "self")
+	{
+		return Err(ParseError::ArgumentError(
+			"--unsigned-bundle needs a method that gets the response back directly, 'file', 'slatepack', 'self', --late-lock and --estimate-selection can't be used with it".to_string(),
+		));
+	}
+
 	if minimum_confirmations_change_outputs_is_present && !exclude_change_outputs {
 		Err(ArgumentError("minimum_confirmations_change_outputs may only be specified if exclude_change_outputs is set".to_string()))
 	} else {
@@ -631,12 +1008,141 @@ pub fn parse_send_args(args: &ArgMatches) -> Result<command::SendArgs, ParseErro
 			address: address,
 			outputs,
 			slatepack_recipient,
+			envelope_recipient,
 			late_lock,
 			min_fee,
+			unsigned_bundle,
 		})
 	}
 }
 
+pub fn parse_request_args(args: &ArgMatches) -> Result<command::RequestArgs, ParseError> {
+	let amount = match args.value_of("amount") {
+		Some(amount) => Some(core::core::amount_from_hr_string(amount).map_err(|e| {
+			ParseError::ArgumentError(format!(
+				"Could not parse amount as a number with optional decimal point. e={}",
+				e
+			))
+		})?),
+		None => None,
+	};
+
+	Ok(command::RequestArgs {
+		amount,
+		message: args.value_of("message").map(|s| s.to_string()),
+		method: parse_required(args, "method")?.to_string(),
+	})
+}
+
+pub fn parse_split_pay_args(args: &ArgMatches) -> Result<command::SplitPayArgs, ParseError> {
+	// amount
+	let amount = parse_required(args, "amount")?;
+	let amount = core::core::amount_from_hr_string(amount).map_err(|e| {
+		ParseError::ArgumentError(format!(
+			"Could not parse amount as a number with optional decimal point. e={}",
+			e
+		))
+	})?;
+
+	// method
+	let method = parse_required(args, "method")?;
+
+	// destinations, every one is expected in format <address>:<weight>
+	let mut destinations: Vec<(String, u64)> = vec![];
+	for dest_str in parse_required(args, "destinations")?.split(",") {
+		let dest_str = dest_str.trim();
+		if dest_str.is_empty() {
+			continue;
+		}
+		let mut split = dest_str.rsplitn(2, ":");
+		let weight = split.next().unwrap_or("");
+		let dest = match split.next() {
+			Some(d) => d,
+			None => {
+				let msg = format!(
+					"Destination '{}' doesn't have a weight. Expected format is <address>:<weight>",
+					dest_str
+				);
+				return Err(ParseError::ArgumentError(msg));
+			}
+		};
+		let weight = parse_u64(weight, "destination weight")?;
+		if weight == 0 {
+			let msg = format!("Destination '{}' has zero weight", dest);
+			return Err(ParseError::ArgumentError(msg));
+		}
+		if method == "http"
+			&& !dest.starts_with("http://")
+			&& !dest.starts_with("https://")
+			&& is_tor_address(&dest).is_err()
+		{
+			let msg = format!(
+				"HTTP Destination should start with http://: or https://: {}",
+				dest,
+			);
+			return Err(ParseError::ArgumentError(msg));
+		}
+		if destinations.iter().any(|(d, _)| d == dest) {
+			let msg = format!("Destination '{}' is listed more than once", dest);
+			return Err(ParseError::ArgumentError(msg));
+		}
+		destinations.push((dest.to_string(), weight));
+	}
+
+	if destinations.is_empty() {
+		return Err(ParseError::ArgumentError(
+			"Please specify at least one destination".to_string(),
+		));
+	}
+
+	// minimum_confirmations
+	let min_c = parse_required(args, "minimum_confirmations")?;
+	let min_c = parse_u64(min_c, "minimum_confirmations")?;
+
+	// selection_strategy
+	let selection_strategy = parse_required(args, "selection_strategy")?;
+
+	// change_outputs
+	let change_outputs = parse_required(args, "change_outputs")?;
+	let change_outputs = parse_u64(change_outputs, "change_outputs")? as usize;
+
+	// max_outputs
+	let max_outputs = 500;
+
+	let minimum_confirmations_change_outputs_is_present =
+		args.occurrences_of("minimum_confirmations_change_outputs") != 0;
+	let minimum_confirmations_change_outputs =
+		parse_required(args, "minimum_confirmations_change_outputs")?;
+	let minimum_confirmations_change_outputs = parse_u64(
+		minimum_confirmations_change_outputs,
+		"minimum_confirmations_change_outputs",
+	)?;
+	let exclude_change_outputs = args.is_present("exclude_change_outputs");
+	if minimum_confirmations_change_outputs_is_present && !exclude_change_outputs {
+		return Err(ParseError::ArgumentError(
+			"minimum_confirmations_change_outputs may only be specified if exclude_change_outputs is set"
+				.to_string(),
+		));
+	}
+
+	Ok(command::SplitPayArgs {
+		amount,
+		destinations,
+		message: args.value_of("message").map(|s| s.to_string()),
+		minimum_confirmations: min_c,
+		selection_strategy: selection_strategy.to_owned(),
+		method: method.to_owned(),
+		apisecret: args.value_of("apisecret").map(|s| s.to_string()),
+		fluff: args.is_present("fluff"),
+		change_outputs,
+		max_outputs,
+		exclude_change_outputs,
+		minimum_confirmations_change_outputs,
+		ttl_blocks: parse_u64_or_none(args.value_of("ttl_blocks")),
+		stop_on_error: args.is_present("stop_on_error"),
+	})
+}
+
 pub fn parse_receive_unpack_args(args: &ArgMatches) -> Result<command::ReceiveArgs, ParseError> {
 	// input file
 	let input_file = match args.is_present("file") {
@@ -660,6 +1166,20 @@ pub fn parse_receive_unpack_args(args: &ArgMatches) -> Result<command::ReceiveAr
 	})
 }
 
+pub fn parse_retrieve_response_args(
+	args: &ArgMatches,
+) -> Result<command::RetrieveResponseArgs, ParseError> {
+	let slate_id = parse_required(args, "slate_id")?;
+	let tx_slate_id = Uuid::parse_str(slate_id).map_err(|e| {
+		ParseError::ArgumentError(format!("Could not parse slate-id parameter. e={}", e))
+	})?;
+
+	Ok(command::RetrieveResponseArgs {
+		tx_slate_id,
+		outfile: args.value_of("outfile").map(|s| s.to_string()),
+	})
+}
+
 pub fn parse_finalize_args(args: &ArgMatches) -> Result<command::FinalizeArgs, ParseError> {
 	// input file
 	let input_file = match args.is_present("file") {
@@ -684,8 +1204,34 @@ pub fn parse_finalize_args(args: &ArgMatches) -> Result<command::FinalizeArgs, P
 	})
 }
 
+pub fn parse_bundle_args(args: &ArgMatches) -> Result<command::BundleArgs, ParseError> {
+	let bundle = parse_required(args, "bundle")?.to_owned();
+	if !Path::new(&bundle).is_file() {
+		let msg = format!("File {} not found.", &bundle);
+		return Err(ParseError::ArgumentError(msg));
+	}
+	Ok(command::BundleArgs { bundle })
+}
+
+pub fn parse_roundtrip_args(args: &ArgMatches) -> Result<command::RoundtripArgs, ParseError> {
+	let input_file = parse_required(args, "input")?.to_owned();
+	if !Path::new(&input_file).is_file() {
+		let msg = format!("File {} not found.", &input_file);
+		return Err(ParseError::ArgumentError(msg));
+	}
+
+	Ok(command::RoundtripArgs {
+		input_file,
+		message: args.value_of("message").map(|s| s.to_string()),
+		outfile: args.value_of("outfile").map(|s| s.to_string()),
+		post: args.is_present("post"),
+		fluff: args.is_present("fluff"),
+	})
+}
+
 pub fn parse_issue_invoice_args(
 	args: &ArgMatches,
+	dest: Option<&str>,
 ) -> Result<command::IssueInvoiceArgs, ParseError> {
 	let amount = parse_required(args, "amount")?;
 	let amount = core::core::amount_from_hr_string(amount);
@@ -732,10 +1278,28 @@ pub fn parse_issue_invoice_args(
 		None => None,
 	};
 
-	// dest (output file)
-	let dest = parse_required(args, "dest")?;
+	// method
+	let method = parse_required(args, "method")?;
+
+	// dest (output file or payer address)
+	let dest = parse_required_dest(dest)?;
+	if method == "http"
+		&& !dest.starts_with("http://")
+		&& !dest.starts_with("https://")
+		&& is_tor_address(&dest).is_err()
+	{
+		let msg = format!(
+			"HTTP Destination should start with http://: or https://: {}",
+			dest,
+		);
+		return Err(ParseError::ArgumentError(msg));
+	}
+
 	Ok(command::IssueInvoiceArgs {
 		dest: dest.into(),
+		method: method.to_string(),
+		apisecret: args.value_of("apisecret").map(|s| String::from(s)),
+		fluff: args.is_present("fluff"),
 		issue_args: IssueInvoiceTxArgs {
 			dest_acct_name: None,
 			address: Some(String::from(dest)),
@@ -747,6 +1311,22 @@ pub fn parse_issue_invoice_args(
 	})
 }
 
+pub fn parse_gc_args(args: &ArgMatches) -> Result<GcArgs, ParseError> {
+	let retention_days = parse_required(args, "retention_days")?;
+	Ok(GcArgs {
+		retention_days: parse_u64(retention_days, "retention_days")?,
+		finished_txs: args.is_present("finished_txs"),
+		dry_run: args.is_present("dry_run"),
+	})
+}
+
+pub fn parse_audit_args(args: &ArgMatches) -> Result<command::AuditArgs, ParseError> {
+	Ok(command::AuditArgs {
+		verify: args.is_present("verify"),
+		file: args.value_of("file").map(|s| s.to_string()),
+	})
+}
+
 pub fn parse_process_invoice_args(
 	args: &ArgMatches,
 	prompt: bool,
@@ -821,70 +1401,430 @@ pub fn parse_process_invoice_args(
 			.map_err(|e| ParseError::ArgumentError(format!("Unable to read the slate, {}", e)))?
 			.0;
 
-		prompt_pay_invoice(&slate, method, dest)?;
+		prompt_pay_invoice(&slate, method, dest)?;
+	}
+
+	Ok(command::ProcessInvoiceArgs {
+		message: message,
+		minimum_confirmations: min_c,
+		selection_strategy: selection_strategy.to_owned(),
+		estimate_selection_strategies,
+		method: method.to_owned(),
+		dest: dest.to_owned(),
+		max_outputs: max_outputs,
+		input: tx_file.to_owned(),
+		ttl_blocks,
+	})
+}
+
+pub fn parse_info_args(args: &ArgMatches) -> Result<command::InfoArgs, ParseError> {
+	// minimum_confirmations
+	let mc = parse_required(args, "minimum_confirmations")?;
+	let mc = parse_u64(mc, "minimum_confirmations")?;
+	Ok(command::InfoArgs {
+		minimum_confirmations: mc,
+		all_accounts: args.is_present("all_accounts"),
+		fiat: args.value_of("fiat").map(|c| c.to_string()),
+	})
+}
+
+pub fn parse_check_args(args: &ArgMatches) -> Result<command::CheckArgs, ParseError> {
+	let delete_unconfirmed = args.is_present("delete_unconfirmed");
+	let start_height = parse_u64_or_none(args.value_of("start_height"));
+
+	let backwards_from_tip = parse_u64_or_none(args.value_of("backwards_from_tip"));
+	if backwards_from_tip.is_some() && start_height.is_some() {
+		let msg = format!("backwards_from tip and start_height cannot both be present");
+		return Err(ParseError::ArgumentError(msg));
+	}
+	let resume = args.is_present("resume");
+	Ok(command::CheckArgs {
+		start_height,
+		backwards_from_tip,
+		delete_unconfirmed,
+		resume,
+	})
+}
+
+pub fn parse_check_repair_args(args: &ArgMatches) -> Result<command::CheckRepairArgs, ParseError> {
+	let start_height = parse_u64_or_none(args.value_of("start_height"));
+	let backwards_from_tip = parse_u64_or_none(args.value_of("backwards_from_tip"));
+	if backwards_from_tip.is_some() && start_height.is_some() {
+		let msg = format!("backwards_from tip and start_height cannot both be present");
+		return Err(ParseError::ArgumentError(msg));
+	}
+	let fix = if args.is_present("repair") {
+		CheckCategory::ALL.to_vec()
+	} else {
+		let mut fix = vec![];
+		if let Some(categories) = args.value_of("fix") {
+			for c in categories.split(',') {
+				fix.push(
+					CheckCategory::from_str(c.trim())
+						.map_err(|e| ParseError::ArgumentError(format!("{}", e)))?,
+				);
+			}
+		}
+		fix
+	};
+	Ok(command::CheckRepairArgs {
+		start_height,
+		backwards_from_tip,
+		fix,
+		json: args.is_present("json"),
+	})
+}
+
+pub fn parse_sweep_seed_args<L, C, K>(
+	wallet: Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>,
+	args: &ArgMatches,
+) -> Result<command::SweepSeedArgs, ParseError>
+where
+	DefaultWalletImpl<'static, C>: WalletInst<'static, L, C, K>,
+	L: WalletLCProvider<'static, C, K>,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let start_height = parse_u64_or_none(args.value_of("start_height"));
+	let mc = parse_required(args, "minimum_confirmations")?;
+	let minimum_confirmations = parse_u64(mc, "minimum_confirmations")?;
+	let recovery_phrase = if args.is_present("receive_wallet") {
+		None
+	} else {
+		let phrase = prompt_recovery_phrase(wallet)?;
+		if phrase.is_empty() {
+			return Err(ParseError::ArgumentError(
+				"Recovery phrase of the swept wallet is required".to_string(),
+			));
+		}
+		Some(phrase)
+	};
+	Ok(command::SweepSeedArgs {
+		recovery_phrase,
+		start_height,
+		minimum_confirmations,
+		fluff: args.is_present("fluff"),
+	})
+}
+
+pub fn parse_txs_args(args: &ArgMatches) -> Result<command::TxsArgs, ParseError> {
+	let tx_id = match args.value_of("id") {
+		None => None,
+		Some(tx) => Some(parse_u64(tx, "id")? as u32),
+	};
+	let tx_slate_id = match args.value_of("txid") {
+		None => None,
+		Some(tx) => match tx.parse() {
+			Ok(t) => Some(t),
+			Err(e) => {
+				let msg = format!("Could not parse txid parameter. e={}", e);
+				return Err(ParseError::ArgumentError(msg));
+			}
+		},
+	};
+	if tx_id.is_some() && tx_slate_id.is_some() {
+		let msg = format!("At most one of 'id' (-i) or 'txid' (-t) may be provided.");
+		return Err(ParseError::ArgumentError(msg));
+	}
+
+	let query_params = [
+		"offset",
+		"limit",
+		"from",
+		"to",
+		"min_amount",
+		"max_amount",
+		"status",
+		"search",
+	];
+	let query = if query_params.iter().any(|p| args.is_present(p)) {
+		if tx_id.is_some() || tx_slate_id.is_some() {
+			let msg = format!("Filters can't be used together with 'id' (-i) or 'txid' (-t).");
+			return Err(ParseError::ArgumentError(msg));
+		}
+		let (tx_types, confirmed) = match args.value_of("status") {
+			Some("confirmed") => (None, Some(true)),
+			Some("unconfirmed") => (
+				Some(vec![
+					TxLogEntryType::TxReceived,
+					TxLogEntryType::TxSent,
+					TxLogEntryType::ConfirmedCoinbase,
+				]),
+				Some(false),
+			),
+			Some("cancelled") => (
+				Some(vec![
+					TxLogEntryType::TxReceivedCancelled,
+					TxLogEntryType::TxSentCancelled,
+				]),
+				None,
+			),
+			_ => (None, None),
+		};
+		Some(TxQueryArgs {
+			offset: parse_opt_u32(args, "offset")?,
+			limit: parse_opt_u32(args, "limit")?,
+			min_creation_ts: parse_opt_date(args, "from")?,
+			max_creation_ts: parse_opt_date(args, "to")?,
+			min_amount: parse_opt_amount(args, "min_amount")?,
+			max_amount: parse_opt_amount(args, "max_amount")?,
+			tx_types,
+			confirmed,
+			label: args.value_of("search").map(|s| s.to_string()),
+		})
+	} else {
+		None
+	};
+
+	// --label <id> <text> and --memo <id> <text>
+	let mut set_label: Option<TxLabelArgs> = None;
+	for (arg, is_label) in [("label", true), ("memo", false)].iter() {
+		if let Some(mut values) = args.values_of(arg) {
+			let id = parse_u64(values.next().unwrap_or(""), arg)? as u32;
+			let text = values.next().unwrap_or("").to_string();
+			let label_args = set_label.get_or_insert(TxLabelArgs {
+				tx_id: id,
+				..Default::default()
+			});
+			if label_args.tx_id != id {
+				let msg = format!("'label' and 'memo' must be set for the same transaction.");
+				return Err(ParseError::ArgumentError(msg));
+			}
+			if *is_label {
+				label_args.label = Some(text);
+			} else {
+				label_args.memo = Some(text);
+			}
+		}
+	}
+	let kernel = args.value_of("kernel").map(|k| k.to_string());
+	if set_label.is_some()
+		&& (query.is_some() || tx_id.is_some() || tx_slate_id.is_some() || kernel.is_some())
+	{
+		let msg = format!("'label' and 'memo' can't be used together with other arguments.");
+		return Err(ParseError::ArgumentError(msg));
+	}
+	if kernel.is_some() && (query.is_some() || tx_id.is_some() || tx_slate_id.is_some()) {
+		let msg = format!("'kernel' can't be used together with 'id', 'txid' and the filters.");
+		return Err(ParseError::ArgumentError(msg));
+	}
+	// --target <id> <confirmations>
+	let set_target = match args.values_of("target") {
+		Some(mut values) => {
+			let id = parse_u64(values.next().unwrap_or(""), "target")? as u32;
+			let confirmations = parse_u64(values.next().unwrap_or(""), "target")?;
+			if set_label.is_some()
+				|| kernel.is_some()
+				|| query.is_some()
+				|| tx_id.is_some()
+				|| tx_slate_id.is_some()
+			{
+				let msg = format!("'target' can't be used together with other arguments.");
+				return Err(ParseError::ArgumentError(msg));
+			}
+			Some((id, confirmations))
+		}
+		None => None,
+	};
+
+	// --import <file>
+	let import = match args.value_of("import") {
+		Some(file) => {
+			if set_label.is_some()
+				|| set_target.is_some()
+				|| kernel.is_some()
+				|| query.is_some()
+				|| tx_id.is_some()
+				|| tx_slate_id.is_some()
+			{
+				let msg = format!("'import' can't be used together with other arguments.");
+				return Err(ParseError::ArgumentError(msg));
+			}
+			let format = match args.value_of("import_format") {
+				Some("csv") => TxImportFormat::Csv,
+				Some(_) => TxImportFormat::Json,
+				None => TxImportFormat::from_file_name(file),
+			};
+			let mut columns = HashMap::new();
+			for pair in args
+				.value_of("import_columns")
+				.unwrap_or("")
+				.split(',')
+				.filter(|p| !p.trim().is_empty())
+			{
+				let mut kv = pair.splitn(2, '=');
+				match (kv.next(), kv.next()) {
+					(Some(field), Some(column)) if !column.trim().is_empty() => {
+						columns.insert(field.trim().to_string(), column.trim().to_string());
+					}
+					_ => {
+						let msg =
+							format!("Invalid import column '{}', expected 'field=column'", pair);
+						return Err(ParseError::ArgumentError(msg));
+					}
+				}
+			}
+			Some(command::TxImportArgs {
+				file: file.to_string(),
+				format,
+				columns,
+				dry_run: args.is_present("dry_run"),
+			})
+		}
+		None => None,
+	};
+
+	Ok(command::TxsArgs {
+		id: tx_id,
+		tx_slate_id: tx_slate_id,
+		query,
+		set_label,
+		fiat: args.value_of("fiat").map(|c| c.to_string()),
+		kernel,
+		set_target,
+		import,
+	})
+}
+
+pub fn parse_outputs_args(args: &ArgMatches) -> Result<command::OutputsArgs, ParseError> {
+	// --freeze <commit>, --unfreeze <commit> and --tag <commit> <tags>
+	let mut tag_output: Option<OutputTagArgs> = None;
+	for (arg, frozen) in [("freeze", true), ("unfreeze", false)].iter() {
+		if let Some(commit) = args.value_of(arg) {
+			if tag_output.is_some() {
+				let msg = format!("'freeze' and 'unfreeze' can't be used together.");
+				return Err(ParseError::ArgumentError(msg));
+			}
+			tag_output = Some(OutputTagArgs {
+				commit: commit.to_string(),
+				frozen: Some(*frozen),
+				..Default::default()
+			});
+		}
+	}
+	if let Some(mut values) = args.values_of("tag") {
+		let commit = values.next().unwrap_or("").to_string();
+		let tags = values
+			.next()
+			.unwrap_or("")
+			.split(",")
+			.map(|t| t.trim().to_string())
+			.filter(|t| !t.is_empty())
+			.collect();
+		let tag_args = tag_output.get_or_insert(OutputTagArgs {
+			commit: commit.clone(),
+			..Default::default()
+		});
+		if tag_args.commit != commit {
+			let msg = format!("'tag' and 'freeze' must be set for the same output.");
+			return Err(ParseError::ArgumentError(msg));
+		}
+		tag_args.tags = Some(tags);
+	}
+
+	let query_params = [
+		"offset",
+		"limit",
+		"min_height",
+		"max_height",
+		"min_amount",
+		"max_amount",
+		"status",
+		"tagged",
+		"frozen",
+	];
+	let has_query = query_params.iter().any(|p| args.is_present(p));
+	if tag_output.is_some() && has_query {
+		let msg =
+			format!("'freeze', 'unfreeze' and 'tag' can't be used together with the filters.");
+		return Err(ParseError::ArgumentError(msg));
+	}
+	if !has_query {
+		return Ok(command::OutputsArgs {
+			query: None,
+			tag_output,
+		});
 	}
 
-	Ok(command::ProcessInvoiceArgs {
-		message: message,
-		minimum_confirmations: min_c,
-		selection_strategy: selection_strategy.to_owned(),
-		estimate_selection_strategies,
-		method: method.to_owned(),
-		dest: dest.to_owned(),
-		max_outputs: max_outputs,
-		input: tx_file.to_owned(),
-		ttl_blocks,
-	})
-}
+	let status = match args.value_of("status") {
+		Some(status) => {
+			let mut res = vec![];
+			for st in status.split(",") {
+				res.push(match st.trim().to_lowercase().as_str() {
+					"unconfirmed" => OutputStatus::Unconfirmed,
+					"unspent" => OutputStatus::Unspent,
+					"locked" => OutputStatus::Locked,
+					"spent" => OutputStatus::Spent,
+					_ => {
+						let msg = format!("Unknown output status '{}'. Expected unconfirmed, unspent, locked or spent", st);
+						return Err(ParseError::ArgumentError(msg));
+					}
+				});
+			}
+			Some(res)
+		}
+		None => None,
+	};
 
-pub fn parse_info_args(args: &ArgMatches) -> Result<command::InfoArgs, ParseError> {
-	// minimum_confirmations
-	let mc = parse_required(args, "minimum_confirmations")?;
-	let mc = parse_u64(mc, "minimum_confirmations")?;
-	Ok(command::InfoArgs {
-		minimum_confirmations: mc,
+	Ok(command::OutputsArgs {
+		query: Some(OutputQueryArgs {
+			offset: parse_opt_u32(args, "offset")?,
+			limit: parse_opt_u32(args, "limit")?,
+			min_height: match args.value_of("min_height") {
+				Some(h) => Some(parse_u64(h, "min_height")?),
+				None => None,
+			},
+			max_height: match args.value_of("max_height") {
+				Some(h) => Some(parse_u64(h, "max_height")?),
+				None => None,
+			},
+			min_value: parse_opt_amount(args, "min_amount")?,
+			max_value: parse_opt_amount(args, "max_amount")?,
+			status,
+			tag: args.value_of("tagged").map(|t| t.trim().to_string()),
+			frozen: if args.is_present("frozen") {
+				Some(true)
+			} else {
+				None
+			},
+		}),
+		tag_output: None,
 	})
 }
 
-pub fn parse_check_args(args: &ArgMatches) -> Result<command::CheckArgs, ParseError> {
-	let delete_unconfirmed = args.is_present("delete_unconfirmed");
-	let start_height = parse_u64_or_none(args.value_of("start_height"));
+// parses optional u32 argument
+fn parse_opt_u32(args: &ArgMatches, name: &str) -> Result<Option<u32>, ParseError> {
+	match args.value_of(name) {
+		Some(v) => Ok(Some(parse_u64(v, name)? as u32)),
+		None => Ok(None),
+	}
+}
 
-	let backwards_from_tip = parse_u64_or_none(args.value_of("backwards_from_tip"));
-	if backwards_from_tip.is_some() && start_height.is_some() {
-		let msg = format!("backwards_from tip and start_height cannot both be present");
-		return Err(ParseError::ArgumentError(msg));
+// parses optional MWC amount argument into nanoMWC
+fn parse_opt_amount(args: &ArgMatches, name: &str) -> Result<Option<u64>, ParseError> {
+	match args.value_of(name) {
+		Some(amount) => core::core::amount_from_hr_string(amount)
+			.map(|a| Some(a))
+			.map_err(|e| {
+				ParseError::ArgumentError(format!("Could not parse {} as a number, {}", name, e))
+			}),
+		None => Ok(None),
 	}
-	Ok(command::CheckArgs {
-		start_height,
-		backwards_from_tip,
-		delete_unconfirmed,
-	})
 }
 
-pub fn parse_txs_args(args: &ArgMatches) -> Result<command::TxsArgs, ParseError> {
-	let tx_id = match args.value_of("id") {
-		None => None,
-		Some(tx) => Some(parse_u64(tx, "id")? as u32),
-	};
-	let tx_slate_id = match args.value_of("txid") {
-		None => None,
-		Some(tx) => match tx.parse() {
-			Ok(t) => Some(t),
-			Err(e) => {
-				let msg = format!("Could not parse txid parameter. e={}", e);
-				return Err(ParseError::ArgumentError(msg));
-			}
-		},
-	};
-	if tx_id.is_some() && tx_slate_id.is_some() {
-		let msg = format!("At most one of 'id' (-i) or 'txid' (-t) may be provided.");
-		return Err(ParseError::ArgumentError(msg));
+// parses optional YYYY-MM-DD date argument into UTC timestamp
+fn parse_opt_date(args: &ArgMatches, name: &str) -> Result<Option<i64>, ParseError> {
+	match args.value_of(name) {
+		Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+			.map(|d| Some(d.and_hms(0, 0, 0).timestamp()))
+			.map_err(|e| {
+				ParseError::ArgumentError(format!(
+					"Could not parse {} as YYYY-MM-DD date, {}",
+					name, e
+				))
+			}),
+		None => Ok(None),
 	}
-	Ok(command::TxsArgs {
-		id: tx_id,
-		tx_slate_id: tx_slate_id,
-	})
 }
 
 pub fn parse_post_args(args: &ArgMatches) -> Result<command::PostArgs, ParseError> {
@@ -924,14 +1864,25 @@ pub fn parse_repost_args(args: &ArgMatches) -> Result<command::RepostArgs, Parse
 		Some(tx) => Some(parse_u64(tx, "id")? as u32),
 	};
 
+	let all_unconfirmed = args.is_present("all_unconfirmed");
+	if tx_id.is_some() == all_unconfirmed {
+		let msg = format!("'id' (-i) or 'all-unconfirmed' (-a) argument is required.");
+		return Err(ParseError::ArgumentError(msg));
+	}
+
 	let fluff = args.is_present("fluff");
 	let dump_file = match args.value_of("dumpfile") {
 		None => None,
 		Some(d) => Some(d.to_owned()),
 	};
+	if all_unconfirmed && dump_file.is_some() {
+		let msg = format!("'dumpfile' can be used with a single transaction 'id' only.");
+		return Err(ParseError::ArgumentError(msg));
+	}
 
 	Ok(command::RepostArgs {
-		id: tx_id.unwrap(),
+		id: tx_id,
+		all_unconfirmed,
 		dump_file: dump_file,
 		fluff: fluff,
 	})
@@ -967,6 +1918,45 @@ pub fn parse_cancel_args(args: &ArgMatches) -> Result<command::CancelArgs, Parse
 	})
 }
 
+pub fn parse_reissue_args(args: &ArgMatches) -> Result<command::ReissueArgs, ParseError> {
+	let tx_id = parse_u64(parse_required(args, "id")?, "id")? as u32;
+	let fee_multiplier = parse_f32(parse_required(args, "fee_multiplier")?, "fee_multiplier")?;
+	if !(fee_multiplier > 1.0) {
+		let msg = "fee_multiplier must be greater than 1.0".to_string();
+		return Err(ParseError::ArgumentError(msg));
+	}
+
+	let target_slate_version = match args.value_of("slate_version") {
+		Some(v) => Some(parse_u64(v, "slate_version")? as u16),
+		None => None,
+	};
+
+	let slatepack_recipient: Option<ProvableAddress> = match args.value_of("slatepack_recipient") {
+		Some(s) => {
+			let addr = ProvableAddress::from_str(s).map_err(|e| {
+				ParseError::ArgumentError(format!("Unable to parse slatepack_recipient, {}", e))
+			})?;
+
+			if addr.tor_public_key().is_err() {
+				return Err(ParseError::ArgumentError(
+					"Expecting tor PK address as a slatepack recipient value".to_string(),
+				));
+			}
+			Some(addr)
+		}
+		None => None,
+	};
+
+	Ok(command::ReissueArgs {
+		tx_id,
+		fee_multiplier,
+		outfile: args.value_of("outfile").map(|s| s.to_string()),
+		target_slate_version,
+		slatepack_recipient,
+		force: args.is_present("force"),
+	})
+}
+
 pub fn parse_export_proof_args(args: &ArgMatches) -> Result<command::ProofExportArgs, ParseError> {
 	let output_file = parse_required(args, "output")?;
 	let tx_id = match args.value_of("id") {
@@ -983,11 +1973,20 @@ pub fn parse_export_proof_args(args: &ArgMatches) -> Result<command::ProofExport
 			}
 		},
 	};
+	let all = args.is_present("all");
+	if all && (tx_id.is_some() || tx_slate_id.is_some()) {
+		let msg = format!("'all' (-a) can't be used with 'id' (-i) or 'txid' (-t).");
+		return Err(ParseError::ArgumentError(msg));
+	}
+	if !all && args.is_present("since") {
+		let msg = format!("'since' (-s) can be used only with 'all' (-a).");
+		return Err(ParseError::ArgumentError(msg));
+	}
 	if tx_id.is_some() && tx_slate_id.is_some() {
 		let msg = format!("At most one of 'id' (-i) or 'txid' (-t) may be provided.");
 		return Err(ParseError::ArgumentError(msg));
 	}
-	if tx_id.is_none() && tx_slate_id.is_none() {
+	if !all && tx_id.is_none() && tx_slate_id.is_none() {
 		let msg = format!("Either 'id' (-i) or 'txid' (-t) must be provided.");
 		return Err(ParseError::ArgumentError(msg));
 	}
@@ -995,6 +1994,8 @@ pub fn parse_export_proof_args(args: &ArgMatches) -> Result<command::ProofExport
 		output_file: output_file.to_owned(),
 		id: tx_id,
 		tx_slate_id: tx_slate_id,
+		all,
+		since: parse_opt_date(args, "since")?,
 	})
 }
 
@@ -1002,10 +2003,15 @@ pub fn parse_verify_proof_args(args: &ArgMatches) -> Result<command::ProofVerify
 	let input_file = parse_required(args, "input")?;
 	Ok(command::ProofVerifyArgs {
 		input_file: input_file.to_owned(),
+		batch: args.is_present("batch"),
+		offline: args.is_present("offline"),
 	})
 }
 
-pub fn parse_swap_start_args(args: &ArgMatches) -> Result<SwapStartArgs, ParseError> {
+pub fn parse_swap_start_args(
+	args: &ArgMatches,
+	dest: Option<&str>,
+) -> Result<SwapStartArgs, ParseError> {
 	let mwc_amount = parse_required(args, "mwc_amount")?;
 	let mwc_amount = core::core::amount_from_hr_string(mwc_amount);
 	let mwc_amount = match mwc_amount {
@@ -1060,7 +2066,7 @@ pub fn parse_swap_start_args(args: &ArgMatches) -> Result<SwapStartArgs, ParseEr
 	let redeem_time = parse_u64(redeem_time, "redeem_time")?;
 
 	let method = parse_required(args, "method")?;
-	let destination = parse_required(args, "dest")?;
+	let destination = parse_required_dest(dest)?;
 
 	let electrum_node_uri1 = args
 		.value_of("electrum_uri1")
@@ -1092,6 +2098,16 @@ pub fn parse_swap_start_args(args: &ArgMatches) -> Result<SwapStartArgs, ParseEr
 		None => None,
 	};
 
+	let lock_window = match args.value_of("lock_window") {
+		Some(w) => Some(parse_u64(w, "lock_window")? * 60),
+		None => None,
+	};
+
+	let offer_expiration = match args.value_of("offer_expiration") {
+		Some(e) => Some(parse_u64(e, "offer_expiration")? * 60),
+		None => None,
+	};
+
 	let dry_run = args.is_present("dry_run");
 
 	Ok(SwapStartArgs {
@@ -1119,17 +2135,22 @@ pub fn parse_swap_start_args(args: &ArgMatches) -> Result<SwapStartArgs, ParseEr
 		eth_redirect_to_private_wallet,
 		dry_run,
 		tag: args.value_of("tag").map(|s| s.to_string()),
+		lock_window_sec: lock_window,
+		offer_expiration_sec: offer_expiration,
 	})
 }
 
-pub fn parse_swap_args(args: &ArgMatches) -> Result<command::SwapArgs, ParseError> {
-	let swap_id = args.value_of("swap_id").map(|s| String::from(s));
+pub fn parse_swap_args(
+	args: &ArgMatches,
+	dest: Option<&str>,
+) -> Result<command::SwapArgs, ParseError> {
+	let mut swap_id = args.value_of("swap_id").map(|s| String::from(s));
 	let adjust = args
 		.value_of("adjust")
 		.map(|s| s.split(",").map(|s| String::from(s)).collect())
 		.unwrap_or(vec![]);
 	let method = args.value_of("method").map(|s| String::from(s));
-	let mut destination = args.value_of("dest").map(|s| String::from(s));
+	let mut destination = dest.map(|s| String::from(s));
 	let apisecret = args.value_of("apisecret").map(|s| String::from(s));
 	let secondary_fee = match args.value_of("secondary_fee") {
 		Some(s) => Some(parse_f32(s, "secondary_fee")?),
@@ -1141,6 +2162,11 @@ pub fn parse_swap_args(args: &ArgMatches) -> Result<command::SwapArgs, ParseErro
 		.map(|s| String::from(s));
 	let secondary_address = args.value_of("secondary_address").map(|s| String::from(s));
 	let start_listener = args.is_present("start_listener");
+	let lock_window_sec = match args.value_of("lock_window") {
+		Some(w) => Some(parse_u64(w, "lock_window")? * 60),
+		None => None,
+	};
+	let mut currency = None;
 
 	let subcommand = if args.is_present("list") {
 		if args.is_present("check") {
@@ -1162,6 +2188,25 @@ pub fn parse_swap_args(args: &ArgMatches) -> Result<command::SwapArgs, ParseErro
 	} else if args.is_present("trade_import") {
 		destination = args.value_of("trade_import").map(|s| String::from(s));
 		command::SwapSubcommand::TradeImport
+	} else if args.is_present("export") {
+		let values: Vec<&str> = args.values_of("export").unwrap().collect();
+		swap_id = values.get(0).map(|s| String::from(*s));
+		destination = values.get(1).map(|s| String::from(*s));
+		command::SwapSubcommand::TradeExport
+	} else if args.is_present("import") {
+		destination = args.value_of("import").map(|s| String::from(s));
+		command::SwapSubcommand::TradeImport
+	} else if args.is_present("history") || args.is_present("history_export") {
+		destination = args.value_of("history_export").map(|s| String::from(s));
+		command::SwapSubcommand::History
+	} else if args.is_present("wallet_address") {
+		currency = args.value_of("wallet_address").map(|s| String::from(s));
+		command::SwapSubcommand::NewSecondaryAddress
+	} else if args.is_present("wallet_addresses") {
+		command::SwapSubcommand::ListSecondaryAddresses
+	} else if args.is_present("wallet_sweep") {
+		currency = args.value_of("wallet_sweep").map(|s| String::from(s));
+		command::SwapSubcommand::SweepSecondaryAddresses
 	} else if !adjust.is_empty() {
 		command::SwapSubcommand::Adjust
 	} else if args.is_present("autoswap") {
@@ -1208,6 +2253,8 @@ pub fn parse_swap_args(args: &ArgMatches) -> Result<command::SwapArgs, ParseErro
 		eth_redirect_to_private_wallet,
 		wait_for_backup1: false, // waiting is a primary usage for qt wallet. We are not documented that properly to make available for all users.
 		tag: args.value_of("tag").map(|s| String::from(s)),
+		lock_window_sec,
+		currency,
 	})
 }
 
@@ -1249,6 +2296,108 @@ pub fn parse_integrity_args(args: &ArgMatches) -> Result<command::IntegrityArgs,
 	})
 }
 
+fn parse_multisig_id(args: &ArgMatches) -> Result<Uuid, ParseError> {
+	let id = parse_required(args, "id")?;
+	Uuid::parse_str(id).map_err(|e| {
+		ParseError::ArgumentError(format!("Could not parse session id parameter. e={}", e))
+	})
+}
+
+fn parse_multisig_file(args: &ArgMatches, must_exist: bool) -> Result<String, ParseError> {
+	let file = parse_required(args, "file")?.to_owned();
+	if must_exist && !Path::new(&file).is_file() {
+		let msg = format!("File {} not found.", &file);
+		return Err(ParseError::ArgumentError(msg));
+	}
+	Ok(file)
+}
+
+pub fn parse_multisig_args(args: &ArgMatches) -> Result<command::MultisigArgs, ParseError> {
+	let mut result = command::MultisigArgs {
+		subcommand: command::MultisigSubcommand::List,
+		id: None,
+		scheme: None,
+		amount: 0,
+		minimum_confirmations: 10,
+		cosigner: None,
+		participant_id: None,
+		file: None,
+	};
+	match args.subcommand() {
+		("create", Some(args)) => {
+			let amount = parse_required(args, "amount")?;
+			result.amount = core::core::amount_from_hr_string(amount).map_err(|e| {
+				ParseError::ArgumentError(format!(
+					"Could not parse amount as a number with optional decimal point. e={}",
+					e
+				))
+			})?;
+			let scheme = parse_required(args, "scheme")?;
+			result.scheme = Some(
+				scheme
+					.parse::<MultisigScheme>()
+					.map_err(|e| ParseError::ArgumentError(format!("{}", e)))?,
+			);
+			let min_c = parse_required(args, "minimum_confirmations")?;
+			result.minimum_confirmations = parse_u64(min_c, "minimum_confirmations")?;
+			result.file = Some(parse_multisig_file(args, false)?);
+			result.subcommand = command::MultisigSubcommand::Create;
+		}
+		("process", Some(args)) => {
+			result.file = Some(parse_multisig_file(args, true)?);
+			result.subcommand = command::MultisigSubcommand::Process;
+		}
+		("spend", Some(args)) => {
+			result.id = Some(parse_multisig_id(args)?);
+			result.cosigner = match args.value_of("cosigner") {
+				Some(c) => Some(parse_u64(c, "cosigner")? as usize),
+				None => None,
+			};
+			result.file = Some(parse_multisig_file(args, false)?);
+			result.subcommand = command::MultisigSubcommand::Spend;
+		}
+		("list", _) => (),
+		("cancel", Some(args)) => {
+			result.id = Some(parse_multisig_id(args)?);
+			result.subcommand = command::MultisigSubcommand::Cancel;
+		}
+		("restore", Some(args)) => {
+			result.file = Some(parse_multisig_file(args, true)?);
+			let participant = parse_required(args, "participant")?;
+			result.participant_id = Some(parse_u64(participant, "participant")? as usize);
+			result.subcommand = command::MultisigSubcommand::Restore;
+		}
+		("export", Some(args)) => {
+			result.id = Some(parse_multisig_id(args)?);
+			result.file = Some(parse_multisig_file(args, false)?);
+			result.subcommand = command::MultisigSubcommand::Export;
+		}
+		(cmd, _) => {
+			return Err(ParseError::ArgumentError(format!(
+				"Unknown multisig command '{}', use 'mwc-wallet multisig help' for details",
+				cmd
+			)))
+		}
+	}
+	Ok(result)
+}
+
+pub fn parse_devtools_vectors_args(
+	args: &ArgMatches,
+) -> Result<command::DevtoolsVectorsArgs, ParseError> {
+	let count = parse_required(args, "count")?;
+	let count = parse_u64(count, "count")?;
+	if count == 0 || count > 1000 {
+		return Err(ParseError::ArgumentError(format!(
+			"Expected count value is in range 1..1000, get {}",
+			count
+		)));
+	}
+	Ok(command::DevtoolsVectorsArgs {
+		count: count as u32,
+	})
+}
+
 pub fn parse_messaging_args(args: &ArgMatches) -> Result<command::MessagingArgs, ParseError> {
 	let fee = match args.value_of("fee") {
 		Some(s) => Some(core::core::amount_from_hr_string(s).map_err(|e| {
@@ -1431,12 +2580,36 @@ where
 	// local wallet proxy, etc)
 	wallet_inst_cb(wallet.clone());
 
+	// Additional wallets for the owner API, they are opened with the owner API 'open_wallet'
+	let mut owner_api_wallets = HashMap::new();
+	if let ("owner_api", _) | ("web", _) = wallet_args.subcommand() {
+		for (wallet_id, dir) in wallet_config.owner_api_wallets.clone().unwrap_or_default() {
+			let mut config = wallet_config.clone();
+			config.data_file_dir = dir;
+			let w = arg_parse!(inst_wallet::<
+				DefaultLCProvider<C, keychain::ExtKeychain>,
+				C,
+				keychain::ExtKeychain,
+			>(config, node_client.clone()));
+			owner_api_wallets.insert(wallet_id, w);
+		}
+	}
+
 	// don't open wallet for certain lifecycle commands
 	let mut open_wallet = true;
 	match wallet_args.subcommand() {
 		("init", Some(_)) => open_wallet = false,
 		("recover", _) => open_wallet = false,
+		("decoy", _) => open_wallet = false,
+		("seed", _) => open_wallet = false,
+		("restore", _) => open_wallet = false,
+		("migrate", _) => open_wallet = false,
 		("cli", _) => open_wallet = false,
+		("devtools", _) => open_wallet = false,
+		// audit log is verified without the wallet password
+		("audit", _) => open_wallet = false,
+		// proofs are verified without the wallet, so anybody can check them
+		("verify_proof", _) => open_wallet = false,
 		("owner_api", _) => {
 			// If wallet exists, open it. Otherwise, that's fine too.
 			let mut wallet_lock = wallet.lock();
@@ -1489,7 +2662,7 @@ where
 	};
 
 	let res = match wallet_args.subcommand() {
-		("cli", Some(_)) => command_loop(
+		("cli", Some(args)) => command_loop(
 			wallet,
 			keychain_mask,
 			&wallet_config,
@@ -1497,6 +2670,7 @@ where
 			&mqs_config,
 			&global_wallet_args,
 			test_mode,
+			args.value_of("script").map(|s| String::from(s)),
 		),
 		_ => {
 			let mut owner_api = Owner::new(wallet, None, Some(tor_config.clone()));
@@ -1510,6 +2684,7 @@ where
 				&wallet_args,
 				test_mode,
 				false,
+				owner_api_wallets,
 			)
 		}
 	};
@@ -1521,6 +2696,67 @@ where
 	}
 }
 
+// Resolve '@name' address book reference into the contact address. Other destinations
+// are returned as they are.
+fn resolve_contact_dest<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	dest: Option<&str>,
+) -> Result<Option<String>, Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	match dest {
+		Some(d) if Contact::name_from_reference(d).is_some() => {
+			let address = owner_api
+				.resolve_contact_address(keychain_mask, d)
+				.map_err(|e| ErrorKind::LibWallet(format!("Unable to resolve {}, {}", d, e)))?;
+			Ok(Some(address))
+		}
+		Some(d) if PaymentId::parse(d).is_some() => {
+			let resolved = owner_api.resolve_payment_id(d)?;
+			Ok(Some(resolved.address))
+		}
+		_ => Ok(dest.map(|s| s.to_string())),
+	}
+}
+
+// Resolve the send destination and the proof address. For a payment id 'name@domain' both
+// come from the signed record, '--proof_address' can be a payment id as well.
+fn resolve_send_dest<L, C, K>(
+	owner_api: &mut Owner<L, C, K>,
+	keychain_mask: Option<&SecretKey>,
+	dest: Option<&str>,
+	proof_address: Option<&str>,
+) -> Result<(Option<String>, Option<String>), Error>
+where
+	L: WalletLCProvider<'static, C, K> + 'static,
+	C: NodeClient + 'static,
+	K: keychain::Keychain + 'static,
+{
+	let proof_address = match proof_address {
+		Some(p) if PaymentId::parse(p).is_some() => {
+			Some(owner_api.resolve_payment_id(p)?.proof_address)
+		}
+		p => p.map(|s| s.to_string()),
+	};
+	match dest {
+		Some(d) if PaymentId::parse(d).is_some() => {
+			let resolved = owner_api.resolve_payment_id(d)?;
+			Ok((
+				Some(resolved.address),
+				proof_address.or(Some(resolved.proof_address)),
+			))
+		}
+		_ => Ok((
+			resolve_contact_dest(owner_api, keychain_mask, dest)?,
+			proof_address,
+		)),
+	}
+}
+
 pub fn parse_and_execute<L, C, K>(
 	owner_api: &mut Owner<L, C, K>,
 	keychain_mask: Option<SecretKey>,
@@ -1531,6 +2767,7 @@ pub fn parse_and_execute<L, C, K>(
 	wallet_args: &ArgMatches,
 	test_mode: bool,
 	cli_mode: bool,
+	owner_api_wallets: HashMap<String, Arc<Mutex<Box<dyn WalletInst<'static, L, C, K>>>>>,
 ) -> Result<(), Error>
 where
 	DefaultWalletImpl<'static, C>: WalletInst<'static, L, C, K>,
@@ -1559,6 +2796,40 @@ where
 			let a = arg_parse!(parse_recover_args(&global_wallet_args,));
 			command::recover(owner_api, a, wallet_config.wallet_data_dir.as_deref())
 		}
+		("backup", Some(args)) => {
+			let a = arg_parse!(parse_backup_args(&args));
+			command::backup(owner_api, km, a)
+		}
+		("restore", Some(args)) => {
+			let a = arg_parse!(parse_restore_args(owner_api.wallet_inst.clone(), &args));
+			command::restore(owner_api, a, wallet_config.wallet_data_dir.as_deref())
+		}
+		("migrate", Some(args)) => {
+			let a = arg_parse!(parse_migrate_args(&global_wallet_args, &args));
+			command::migrate(
+				owner_api,
+				&global_wallet_args,
+				a,
+				wallet_config.wallet_data_dir.as_deref(),
+			)
+		}
+		("seed", Some(args)) => match args.subcommand() {
+			("export-shamir", Some(args)) => {
+				let a = arg_parse!(parse_seed_export_shamir_args(&global_wallet_args, &args));
+				command::seed_export_shamir(owner_api, a, wallet_config.wallet_data_dir.as_deref())
+			}
+			(cmd, _) => {
+				return Err(ErrorKind::ArgumentError(format!(
+					"Unknown seed command '{}', use 'mwc-wallet seed help' for details",
+					cmd
+				))
+				.into());
+			}
+		},
+		("decoy", Some(args)) => {
+			let a = arg_parse!(parse_decoy_args(&global_wallet_args, &args));
+			command::decoy(owner_api, a, wallet_config.wallet_data_dir.as_deref())
+		}
 		("listen", Some(args)) => {
 			let mut c = wallet_config.clone();
 			let mut t = tor_config.clone();
@@ -1576,11 +2847,22 @@ where
 			)
 		}
 		("owner_api", Some(args)) => {
+			if let Some(a) = arg_parse!(parse_owner_api_token_args(&args)) {
+				return command::owner_api_token(owner_api, a);
+			}
 			let mut c = wallet_config.clone();
 			let mut g = global_wallet_args.clone();
 			g.tls_conf = None;
 			arg_parse!(parse_owner_api_args(&mut c, &args));
-			command::owner_api(owner_api, keychain_mask, &c, &tor_config, &mqs_config, &g)
+			command::owner_api(
+				owner_api,
+				keychain_mask,
+				&c,
+				&tor_config,
+				&mqs_config,
+				&g,
+				owner_api_wallets,
+			)
 		}
 		("web", Some(_)) => command::owner_api(
 			owner_api,
@@ -1589,13 +2871,32 @@ where
 			tor_config,
 			mqs_config,
 			global_wallet_args,
+			owner_api_wallets,
 		),
 		("account", Some(args)) => {
 			let a = arg_parse!(parse_account_args(&args));
 			command::account(owner_api, km, a)
 		}
+		("contact", Some(args)) => {
+			let a = arg_parse!(parse_contact_args(&args));
+			command::contact(owner_api, km, a)
+		}
+		("tor", Some(args)) => {
+			let a = arg_parse!(parse_tor_args(&args));
+			command::tor(owner_api, km, wallet_config, tor_config, a)
+		}
 		("send", Some(args)) => {
-			let a = arg_parse!(parse_send_args(&args));
+			let (dest, proof_address) = resolve_send_dest(
+				owner_api,
+				km,
+				args.value_of("dest"),
+				args.value_of("proof_address"),
+			)?;
+			let a = arg_parse!(parse_send_args(
+				&args,
+				dest.as_deref(),
+				proof_address.as_deref()
+			));
 			command::send(
 				owner_api,
 				&wallet_config,
@@ -1608,6 +2909,22 @@ where
 				wallet_config.dark_background_color_scheme.unwrap_or(true),
 			)
 		}
+		("split-pay", Some(args)) => {
+			let a = arg_parse!(parse_split_pay_args(&args));
+			command::split_pay(
+				owner_api,
+				&wallet_config,
+				km,
+				Some(tor_config.clone()),
+				Some(mqs_config.clone()),
+				a,
+				wallet_config.dark_background_color_scheme.unwrap_or(true),
+			)
+		}
+		("request", Some(args)) => {
+			let a = arg_parse!(parse_request_args(&args));
+			command::request(owner_api, km, a)
+		}
 		("unpack", Some(args)) => {
 			let a = arg_parse!(parse_receive_unpack_args(&args));
 			command::unpack(owner_api, km, a)
@@ -1616,17 +2933,43 @@ where
 			let a = arg_parse!(parse_receive_unpack_args(&args));
 			command::receive(owner_api, km, &global_wallet_args, a)
 		}
+		("retrieve_response", Some(args)) => {
+			let a = arg_parse!(parse_retrieve_response_args(&args));
+			command::retrieve_response(owner_api, km, a)
+		}
+		("finalize", Some(args)) if args.is_present("bundle") => {
+			let a = arg_parse!(parse_bundle_args(&args));
+			command::finalize_bundle(owner_api, km, Some(mqs_config.clone()), a)
+		}
 		("finalize", Some(args)) => {
 			let a = arg_parse!(parse_finalize_args(&args));
 			command::finalize(owner_api, km, a, false)
 		}
+		("sign", Some(args)) => {
+			let a = arg_parse!(parse_bundle_args(&args));
+			command::sign(owner_api, km, a)
+		}
 		("finalize_invoice", Some(args)) => {
 			let a = arg_parse!(parse_finalize_args(&args));
 			command::finalize(owner_api, km, a, true)
 		}
+		("roundtrip", Some(args)) => {
+			let a = arg_parse!(parse_roundtrip_args(&args));
+			command::roundtrip(owner_api, km, &global_wallet_args, a)
+		}
 		("invoice", Some(args)) => {
-			let a = arg_parse!(parse_issue_invoice_args(&args));
-			command::issue_invoice_tx(owner_api, km, a)
+			let dest = match args.value_of("method") {
+				Some("file") | None => args.value_of("dest").map(|d| d.to_string()),
+				_ => resolve_contact_dest(owner_api, km, args.value_of("dest"))?,
+			};
+			let a = arg_parse!(parse_issue_invoice_args(&args, dest.as_deref()));
+			command::issue_invoice_tx(
+				owner_api,
+				km,
+				Some(tor_config.clone()),
+				Some(mqs_config.clone()),
+				a,
+			)
 		}
 		("pay", Some(args)) => {
 			let slatepack_secret = {
@@ -1661,12 +3004,16 @@ where
 				wallet_config.dark_background_color_scheme.unwrap_or(true),
 			)
 		}
-		("outputs", Some(_)) => command::outputs(
-			owner_api,
-			km,
-			&global_wallet_args,
-			wallet_config.dark_background_color_scheme.unwrap_or(true),
-		),
+		("outputs", Some(args)) => {
+			let a = arg_parse!(parse_outputs_args(&args));
+			command::outputs(
+				owner_api,
+				km,
+				&global_wallet_args,
+				a,
+				wallet_config.dark_background_color_scheme.unwrap_or(true),
+			)
+		}
 		("txs", Some(args)) => {
 			let a = arg_parse!(parse_txs_args(&args));
 			command::txs(
@@ -1694,19 +3041,58 @@ where
 			let a = arg_parse!(parse_cancel_args(&args));
 			command::cancel(owner_api, km, a)
 		}
+		("cancel-expired", Some(_)) => command::cancel_expired(owner_api, km),
+		("reissue", Some(args)) => {
+			let a = arg_parse!(parse_reissue_args(&args));
+			command::reissue(owner_api, km, a)
+		}
 		("export_proof", Some(args)) => {
 			let a = arg_parse!(parse_export_proof_args(&args));
 			command::proof_export(owner_api, km, a)
 		}
 		("verify_proof", Some(args)) => {
 			let a = arg_parse!(parse_verify_proof_args(&args));
-			command::proof_verify(owner_api, km, a)
+			command::proof_verify(
+				owner_api,
+				km,
+				wallet_config,
+				global_wallet_args.node_api_secret.clone(),
+				a,
+			)
+		}
+		("address", Some(args)) => {
+			let a = arg_parse!(parse_address_args(&args));
+			command::address(owner_api, &global_wallet_args, km, a)
+		}
+		("address-mapping", Some(args)) => {
+			let a = arg_parse!(parse_address_mapping_args(&args));
+			command::address_mapping(owner_api, km, a)
+		}
+		("payment-id", Some(args)) => {
+			let a = arg_parse!(parse_payment_id_args(&args));
+			command::payment_id(owner_api, km, a)
 		}
-		("address", Some(_)) => command::address(owner_api, &global_wallet_args, km),
 		("scan", Some(args)) => {
 			let a = arg_parse!(parse_check_args(&args));
 			command::scan(owner_api, km, a)
 		}
+		("check", Some(args)) => {
+			let a = arg_parse!(parse_check_repair_args(&args));
+			command::check(owner_api, km, a)
+		}
+		("sweep-seed", Some(args)) => {
+			let a = arg_parse!(parse_sweep_seed_args(owner_api.wallet_inst.clone(), &args));
+			command::sweep_seed(owner_api, km, wallet_config, a)
+		}
+		("receive-wallet", Some(_)) => command::receive_wallet(owner_api, km),
+		("gc", Some(args)) => {
+			let a = arg_parse!(parse_gc_args(&args));
+			command::gc(owner_api, km, a)
+		}
+		("audit", Some(args)) => {
+			let a = arg_parse!(parse_audit_args(&args));
+			command::audit(a)
+		}
 		("dump-wallet-data", Some(args)) => command::dump_wallet_data(
 			owner_api,
 			km,
@@ -1721,7 +3107,8 @@ where
 			Ok(())
 		}
 		("swap_start", Some(args)) => {
-			let a = arg_parse!(parse_swap_start_args(&args));
+			let dest = resolve_contact_dest(owner_api, km, args.value_of("dest"))?;
+			let a = arg_parse!(parse_swap_start_args(&args, dest.as_deref()));
 			command::swap_start(owner_api, km, &a)
 		}
 		("swap_create_from_offer", Some(args)) => {
@@ -1729,7 +3116,8 @@ where
 			command::swap_create_from_offer(owner_api, km, mwc_amount.to_string())
 		}
 		("swap", Some(args)) => {
-			let a = arg_parse!(parse_swap_args(&args));
+			let dest = resolve_contact_dest(owner_api, km, args.value_of("dest"))?;
+			let a = arg_parse!(parse_swap_args(&args, dest.as_deref()));
 			command::swap(
 				owner_api.wallet_inst.clone(),
 				km,
@@ -1756,6 +3144,23 @@ where
 		("check_tor_connection", _) => {
 			command::check_tor_connection(owner_api.wallet_inst.clone(), km, tor_config)
 		}
+		("multisig", Some(args)) => {
+			let a = arg_parse!(parse_multisig_args(&args));
+			command::multisig(owner_api, km, a)
+		}
+		("devtools", Some(args)) => match args.subcommand() {
+			("vectors", Some(args)) => {
+				let a = arg_parse!(parse_devtools_vectors_args(&args));
+				command::devtools_vectors(a)
+			}
+			(cmd, _) => {
+				return Err(ErrorKind::ArgumentError(format!(
+					"Unknown devtools command '{}', use 'mwc-wallet devtools help' for details",
+					cmd
+				))
+				.into());
+			}
+		},
 		("eth", Some(args)) => {
 			let a = arg_parse!(parse_eth_args(&args));
 			command::eth(owner_api.wallet_inst.clone(), a)
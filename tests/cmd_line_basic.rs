@@ -161,6 +161,19 @@ fn command_line_test_impl(test_dir: &str) -> Result<(), grin_wallet_controller::
 	let arg_vec = vec!["mwc-wallet", "-p", "password", "-a", "mining", "info"];
 	execute_command(&app, test_dir, "wallet1", &client1, arg_vec)?;
 
+	// payment request URI for the payer
+	let arg_vec = vec![
+		"mwc-wallet",
+		"-p",
+		"password",
+		"request",
+		"--amount",
+		"0.3",
+		"-g",
+		"Order 42",
+	];
+	execute_command(&app, test_dir, "wallet2", &client2, arg_vec)?;
+
 	// try a file exchange
 	let file_name = format!("{}/tx1.part_tx", test_dir);
 	let response_file_name = format!("{}/tx1.part_tx.response", test_dir);
@@ -485,6 +498,44 @@ fn command_line_test_impl(test_dir: &str) -> Result<(), grin_wallet_controller::
 	let _ = test_framework::award_blocks_to_wallet(&chain, wallet1.clone(), mask1, 5, false);
 	//bh += 5;
 
+	// file exchange with one step processing at both sides
+	let file_name = format!("{}/tx_roundtrip.part_tx", test_dir);
+	let response_file_name = format!("{}/tx_roundtrip.part_tx.response", test_dir);
+	let arg_vec = vec![
+		"mwc-wallet",
+		"-p",
+		"password",
+		"-a",
+		"mining",
+		"send",
+		"-m",
+		"file",
+		"-d",
+		&file_name,
+		"0.1",
+	];
+	execute_command(&app, test_dir, "wallet1", &client1, arg_vec)?;
+
+	// wallet 2 doesn't know this transaction, so it is receiving
+	let arg_vec = vec!["mwc-wallet", "-p", "password", "roundtrip", "-i", &file_name];
+	execute_command(&app, test_dir, "wallet2", &client2, arg_vec.clone())?;
+	// already received, can't be processed again
+	assert!(execute_command(&app, test_dir, "wallet2", &client2, arg_vec).is_err());
+
+	// wallet 1 is the sender, so it finalizes and posts
+	let arg_vec = vec![
+		"mwc-wallet",
+		"-p",
+		"password",
+		"-a",
+		"mining",
+		"roundtrip",
+		"-i",
+		&response_file_name,
+		"--post",
+	];
+	execute_command(&app, test_dir, "wallet1", &client1, arg_vec)?;
+
 	// txs and outputs (mostly spit out for a visual in test logs)
 	let arg_vec = vec!["mwc-wallet", "-p", "password", "-a", "mining", "txs"];
 	execute_command(&app, test_dir, "wallet1", &client1, arg_vec)?;